@@ -1,5 +1,7 @@
 //! BLE integration configuration.
 
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
 /// Configuration for the passive BLE integration.
@@ -20,8 +22,39 @@ pub struct BleConfig {
     ///
     /// When empty, all detected Mi Flora sensors are accepted.
     pub miflora_filter: Vec<String>,
-    /// Per-device GATT connection timeout, in seconds.
+    /// Per-device GATT connection timeout, in seconds, also applied to
+    /// stock LYWSD03MMC readouts.
     pub miflora_connect_timeout_secs: u16,
+    /// How many Mi Flora sensors are read at the same time after a scan.
+    pub max_concurrent_connections: usize,
+    /// Enable active GATT readout for LYWSD03MMC sensors on stock Xiaomi
+    /// firmware, which do not advertise their readings in clear.
+    ///
+    /// Sensors matched by [`Self::device_filter`] only.
+    pub lywsd_stock_enabled: bool,
+    /// Offsets added to the readings of temperature/humidity sensors, keyed
+    /// by MAC address (e.g. `"A4:C1:38:AA:BB:CC"`, case-insensitive).
+    pub calibration: BTreeMap<String, Calibration>,
+    /// Track iBeacon and Eddystone-UID beacons as `device_tracker` entities.
+    pub beacons_enabled: bool,
+    /// Optional allowlist of beacon identifiers: `"<uuid>:<major>:<minor>"`
+    /// for iBeacons, `"<namespace>:<instance>"` in hex for Eddystone.
+    ///
+    /// When empty, every beacon in range is tracked.
+    pub beacon_filter: Vec<String>,
+    /// How long a beacon may go unheard before it is reported `not_home`,
+    /// in seconds. Should span a few scan intervals.
+    pub beacon_away_timeout_secs: u16,
+}
+
+/// Offsets correcting the readings of one temperature/humidity sensor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Calibration {
+    /// Added to the temperature, in degrees Celsius.
+    pub temperature: f64,
+    /// Added to the relative humidity, in percent.
+    pub humidity: f64,
 }
 
 impl Default for BleConfig {
@@ -33,6 +66,12 @@ impl Default for BleConfig {
             miflora_enabled: false,
             miflora_filter: Vec::new(),
             miflora_connect_timeout_secs: 10,
+            max_concurrent_connections: 3,
+            lywsd_stock_enabled: false,
+            calibration: BTreeMap::new(),
+            beacons_enabled: false,
+            beacon_filter: Vec::new(),
+            beacon_away_timeout_secs: 180,
         }
     }
 }
@@ -50,6 +89,12 @@ mod tests {
         assert!(!config.miflora_enabled);
         assert!(config.miflora_filter.is_empty());
         assert_eq!(config.miflora_connect_timeout_secs, 10);
+        assert_eq!(config.max_concurrent_connections, 3);
+        assert!(!config.lywsd_stock_enabled);
+        assert!(config.calibration.is_empty());
+        assert!(!config.beacons_enabled);
+        assert!(config.beacon_filter.is_empty());
+        assert_eq!(config.beacon_away_timeout_secs, 180);
     }
 
     #[test]
@@ -58,8 +103,10 @@ mod tests {
             scan_duration_secs = 20
             update_interval_secs = 120
             device_filter = ["A4:C1:38:AA:BB:CC", "A4:C1:38:DD:EE:FF"]
+            lywsd_stock_enabled = true
         "#;
         let config: BleConfig = toml::from_str(toml).unwrap();
+        assert!(config.lywsd_stock_enabled);
         assert_eq!(config.scan_duration_secs, 20);
         assert_eq!(config.update_interval_secs, 120);
         assert_eq!(config.device_filter.len(), 2);
@@ -72,13 +119,53 @@ mod tests {
             miflora_enabled = true
             miflora_filter = ["C4:7C:8D:6A:XX:YY"]
             miflora_connect_timeout_secs = 15
+            max_concurrent_connections = 5
         "#;
         let config: BleConfig = toml::from_str(toml).unwrap();
         assert!(config.miflora_enabled);
+        assert_eq!(config.max_concurrent_connections, 5);
         assert_eq!(config.miflora_filter, vec!["C4:7C:8D:6A:XX:YY"]);
         assert_eq!(config.miflora_connect_timeout_secs, 15);
     }
 
+    #[test]
+    fn should_deserialize_beacon_fields_from_toml() {
+        let toml = r#"
+            beacons_enabled = true
+            beacon_filter = ["f7826da6-4fa2-4e98-8024-bc5b71e0893e:1:42"]
+            beacon_away_timeout_secs = 300
+        "#;
+        let config: BleConfig = toml::from_str(toml).unwrap();
+        assert!(config.beacons_enabled);
+        assert_eq!(
+            config.beacon_filter,
+            vec!["f7826da6-4fa2-4e98-8024-bc5b71e0893e:1:42"]
+        );
+        assert_eq!(config.beacon_away_timeout_secs, 300);
+    }
+
+    #[test]
+    fn should_deserialize_calibration_from_toml() {
+        let toml = r#"
+            calibration = { "A4:C1:38:AA:BB:CC" = { temperature = -1.2, humidity = 3.0 }, "A4:C1:38:DD:EE:FF" = { humidity = -2.5 } }
+        "#;
+        let config: BleConfig = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.calibration["A4:C1:38:AA:BB:CC"],
+            Calibration {
+                temperature: -1.2,
+                humidity: 3.0,
+            }
+        );
+        assert_eq!(
+            config.calibration["A4:C1:38:DD:EE:FF"],
+            Calibration {
+                temperature: 0.0,
+                humidity: -2.5,
+            }
+        );
+    }
+
     #[test]
     fn should_use_defaults_for_missing_fields() {
         let toml = r"scan_duration_secs = 5";
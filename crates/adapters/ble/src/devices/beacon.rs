@@ -0,0 +1,520 @@
+//! iBeacon and Eddystone presence beacon handler.
+//!
+//! Recognises two advertisement formats:
+//!
+//! - **iBeacon** — Apple manufacturer data (`0x004C`), identified by its
+//!   proximity UUID, major and minor
+//! - **Eddystone-UID** — service data on UUID `0xFEAA`, identified by its
+//!   namespace and instance
+//!
+//! Eddystone URL, TLM and EID frames carry no stable identity and are
+//! ignored.
+//!
+//! Each beacon becomes a `device_tracker.ble_<id>` entity whose state is
+//! `home` while the beacon is heard and `not_home` once it has not been
+//! heard for the configured away timeout. The entity is only persisted when
+//! the state flips, not on every advertisement.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use minihub_app::ports::integration::DiscoveredDevice;
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+use minihub_domain::error::MiniHubError;
+
+use crate::error::{BleError, PayloadParseError};
+use crate::parser::{APPLE_COMPANY_ID, ServiceUuid};
+
+use super::BleDeviceHandler;
+
+/// iBeacon frame: type `0x02`, length `0x15`, then 21 bytes of payload.
+const IBEACON_LEN: usize = 23;
+const IBEACON_PREFIX: [u8; 2] = [0x02, 0x15];
+
+/// Eddystone-UID frame, without and with its two reserved trailing bytes.
+const EDDYSTONE_UID_LEN: usize = 18;
+const EDDYSTONE_UID_PADDED_LEN: usize = 20;
+const EDDYSTONE_UID_FRAME: u8 = 0x00;
+
+/// State reported while the beacon is heard.
+const HOME: &str = "home";
+/// State reported once the beacon has been silent for the away timeout.
+const NOT_HOME: &str = "not_home";
+
+/// Identity of a beacon, stable across advertisements and MAC rotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum BeaconId {
+    IBeacon {
+        uuid: uuid::Uuid,
+        major: u16,
+        minor: u16,
+    },
+    Eddystone {
+        namespace: [u8; 10],
+        instance: [u8; 6],
+    },
+}
+
+impl BeaconId {
+    /// Identifier used in `beacon_filter` and as the device `unique_id`,
+    /// e.g. `"f7826da6-4fa2-4e98-8024-bc5b71e0893e:1:42"` for an iBeacon or
+    /// `"edd1ebeac04e5defa017:0123456789ab"` for an Eddystone beacon.
+    pub(crate) fn key(&self) -> String {
+        match self {
+            Self::IBeacon { uuid, major, minor } => format!("{uuid}:{major}:{minor}"),
+            Self::Eddystone {
+                namespace,
+                instance,
+            } => format!("{}:{}", hex(namespace), hex(instance)),
+        }
+    }
+
+    /// Lowercase slug suitable for entity IDs.
+    fn slug(&self) -> String {
+        match self {
+            Self::IBeacon { uuid, major, minor } => {
+                format!("ibeacon_{}_{major}_{minor}", uuid.simple())
+            }
+            Self::Eddystone {
+                namespace,
+                instance,
+            } => format!("eddystone_{}_{}", hex(namespace), hex(instance)),
+        }
+    }
+}
+
+/// A parsed beacon advertisement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BeaconFrame {
+    pub id: BeaconId,
+    /// Calibrated transmit power, in dBm (at 1 m for iBeacon, 0 m for
+    /// Eddystone).
+    pub tx_power: i8,
+}
+
+/// Last sighting of a beacon.
+#[derive(Debug)]
+struct Sighting {
+    frame: BeaconFrame,
+    last_seen: Instant,
+    home: bool,
+}
+
+/// Handler turning iBeacon and Eddystone advertisements into presence
+/// entities.
+pub(crate) struct BeaconHandler {
+    /// Allowlist of [`BeaconId::key`] values, compared case-insensitively.
+    filter: Vec<String>,
+    away_timeout: Duration,
+    sightings: Mutex<HashMap<BeaconId, Sighting>>,
+}
+
+impl BeaconHandler {
+    pub(crate) fn new(filter: Vec<String>, away_timeout: Duration) -> Self {
+        Self {
+            filter,
+            away_timeout,
+            sightings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn passes_filter(&self, id: &BeaconId) -> bool {
+        if self.filter.is_empty() {
+            return true;
+        }
+        let key = id.key();
+        self.filter.iter().any(|f| f.eq_ignore_ascii_case(&key))
+    }
+
+    /// Try to parse a manufacturer-data advertisement as an iBeacon.
+    ///
+    /// Same contract as [`BleDeviceHandler::try_parse_advertisement`]:
+    /// `Ok(Some(dd))` only when the beacon just came home.
+    pub(crate) fn try_parse_manufacturer_data(
+        &self,
+        company_id: u16,
+        data: &[u8],
+    ) -> Result<Option<DiscoveredDevice>, BleError> {
+        if company_id != APPLE_COMPANY_ID || !data.starts_with(&IBEACON_PREFIX) {
+            return Ok(None);
+        }
+        match parse_ibeacon(data) {
+            Ok(frame) => self.record(frame, Instant::now()),
+            Err(err) => {
+                tracing::debug!(%err, "iBeacon payload parse failed");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record a sighting of an allowed beacon, returning its entity when it
+    /// was away or never seen before.
+    fn record(
+        &self,
+        frame: BeaconFrame,
+        now: Instant,
+    ) -> Result<Option<DiscoveredDevice>, BleError> {
+        if !self.passes_filter(&frame.id) {
+            tracing::trace!(beacon = %frame.id.key(), "filtered out by beacon_filter");
+            return Ok(None);
+        }
+        if !self.observe(frame, now) {
+            return Ok(None);
+        }
+        tracing::debug!(beacon = %frame.id.key(), "beacon is home");
+        build_discovered(&frame, true)
+            .map(Some)
+            .map_err(BleError::Domain)
+    }
+
+    /// Update the last sighting of `frame`, returning `true` when the beacon
+    /// was away or never seen before.
+    fn observe(&self, frame: BeaconFrame, now: Instant) -> bool {
+        let mut sightings = self
+            .sightings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let sighting = sightings.entry(frame.id).or_insert(Sighting {
+            frame,
+            last_seen: now,
+            home: false,
+        });
+        sighting.frame = frame;
+        sighting.last_seen = now;
+        !std::mem::replace(&mut sighting.home, true)
+    }
+
+    /// Mark the beacons not heard for the away timeout as away, returning
+    /// their frames.
+    fn expire(&self, now: Instant) -> Vec<BeaconFrame> {
+        let mut sightings = self
+            .sightings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        sightings
+            .values_mut()
+            .filter(|sighting| {
+                sighting.home && now.duration_since(sighting.last_seen) >= self.away_timeout
+            })
+            .map(|sighting| {
+                sighting.home = false;
+                sighting.frame
+            })
+            .collect()
+    }
+}
+
+impl BleDeviceHandler for BeaconHandler {
+    fn name(&self) -> &'static str {
+        "Beacon"
+    }
+
+    fn try_parse_advertisement(
+        &self,
+        uuid: uuid::Uuid,
+        data: &[u8],
+    ) -> Result<Option<DiscoveredDevice>, BleError> {
+        if uuid != ServiceUuid::EDDYSTONE || data.first() != Some(&EDDYSTONE_UID_FRAME) {
+            return Ok(None);
+        }
+        match parse_eddystone_uid(data) {
+            Ok(frame) => self.record(frame, Instant::now()),
+            Err(err) => {
+                tracing::debug!(%err, "Eddystone payload parse failed");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Flip the beacons that went silent to `not_home`.
+    async fn process_after_scan(
+        &self,
+        _adapter: &btleplug::platform::Adapter,
+    ) -> Vec<DiscoveredDevice> {
+        let mut discovered = Vec::new();
+        for frame in self.expire(Instant::now()) {
+            tracing::debug!(beacon = %frame.id.key(), "beacon is away");
+            match build_discovered(&frame, false) {
+                Ok(dd) => discovered.push(dd),
+                Err(err) => {
+                    tracing::warn!(%err, beacon = %frame.id.key(), "failed to build beacon discovered device");
+                }
+            }
+        }
+        discovered
+    }
+}
+
+/// Parse iBeacon manufacturer data (company ID already stripped).
+///
+/// | Offset | Field | Type |
+/// |--------|-------|------|
+/// | 0 | Type | u8, `0x02` |
+/// | 1 | Length | u8, `0x15` |
+/// | 2–17 | Proximity UUID | 16 bytes BE |
+/// | 18–19 | Major | u16 BE |
+/// | 20–21 | Minor | u16 BE |
+/// | 22 | Measured power | i8, dBm at 1 m |
+fn parse_ibeacon(data: &[u8]) -> Result<BeaconFrame, BleError> {
+    if data.len() != IBEACON_LEN {
+        return Err(BleError::PayloadParse(PayloadParseError::WrongLength {
+            format: "iBeacon",
+            expected: IBEACON_LEN,
+            actual: data.len(),
+        }));
+    }
+
+    let mut uuid = [0u8; 16];
+    uuid.copy_from_slice(&data[2..18]);
+
+    Ok(BeaconFrame {
+        id: BeaconId::IBeacon {
+            uuid: uuid::Uuid::from_bytes(uuid),
+            major: u16::from_be_bytes([data[18], data[19]]),
+            minor: u16::from_be_bytes([data[20], data[21]]),
+        },
+        tx_power: i8::from_be_bytes([data[22]]),
+    })
+}
+
+/// Parse an Eddystone-UID service-data frame.
+///
+/// | Offset | Field | Type |
+/// |--------|-------|------|
+/// | 0 | Frame type | u8, `0x00` |
+/// | 1 | Ranging data | i8, dBm at 0 m |
+/// | 2–11 | Namespace | 10 bytes |
+/// | 12–17 | Instance | 6 bytes |
+///
+/// Bytes 18-19 are reserved and may be omitted.
+fn parse_eddystone_uid(data: &[u8]) -> Result<BeaconFrame, BleError> {
+    if data.len() != EDDYSTONE_UID_LEN && data.len() != EDDYSTONE_UID_PADDED_LEN {
+        return Err(BleError::PayloadParse(PayloadParseError::WrongLength {
+            format: "Eddystone-UID",
+            expected: EDDYSTONE_UID_LEN,
+            actual: data.len(),
+        }));
+    }
+
+    let mut namespace = [0u8; 10];
+    namespace.copy_from_slice(&data[2..12]);
+    let mut instance = [0u8; 6];
+    instance.copy_from_slice(&data[12..18]);
+
+    Ok(BeaconFrame {
+        id: BeaconId::Eddystone {
+            namespace,
+            instance,
+        },
+        tx_power: i8::from_be_bytes([data[1]]),
+    })
+}
+
+/// Lowercase hex encoding of `bytes`.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
+
+/// Build a [`DiscoveredDevice`] reporting the beacon `home` or `not_home`.
+fn build_discovered(frame: &BeaconFrame, home: bool) -> Result<DiscoveredDevice, MiniHubError> {
+    let key = frame.id.key();
+    let (model, details) = match frame.id {
+        BeaconId::IBeacon { uuid, major, minor } => (
+            "iBeacon",
+            vec![
+                ("uuid", AttributeValue::String(uuid.to_string())),
+                ("major", AttributeValue::Int(i64::from(major))),
+                ("minor", AttributeValue::Int(i64::from(minor))),
+            ],
+        ),
+        BeaconId::Eddystone {
+            namespace,
+            instance,
+        } => (
+            "Eddystone",
+            vec![
+                ("namespace", AttributeValue::String(hex(&namespace))),
+                ("instance", AttributeValue::String(hex(&instance))),
+            ],
+        ),
+    };
+
+    let device = Device::builder()
+        .name(format!("{model} {key}"))
+        .model(model)
+        .integration("ble")
+        .unique_id(&key)
+        .build()?;
+
+    let mut builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(format!("device_tracker.ble_{}", frame.id.slug()))
+        .friendly_name(format!("BLE {model} {key}"))
+        .state(EntityState::Text(
+            if home { HOME } else { NOT_HOME }.to_string(),
+        ))
+        .attribute("tx_power", AttributeValue::Int(i64::from(frame.tx_power)));
+    for (name, value) in details {
+        builder = builder.attribute(name, value);
+    }
+    let entity = builder.build()?;
+
+    Ok(DiscoveredDevice {
+        device,
+        entities: vec![entity],
+        via_device: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXIMITY_UUID: [u8; 16] = [
+        0xF7, 0x82, 0x6D, 0xA6, 0x4F, 0xA2, 0x4E, 0x98, 0x80, 0x24, 0xBC, 0x5B, 0x71, 0xE0, 0x89,
+        0x3E,
+    ];
+
+    fn ibeacon_payload() -> Vec<u8> {
+        let mut data = IBEACON_PREFIX.to_vec();
+        data.extend_from_slice(&PROXIMITY_UUID);
+        data.extend_from_slice(&[0x00, 0x01, 0x00, 0x2A, 0xC5]);
+        data
+    }
+
+    fn eddystone_payload() -> Vec<u8> {
+        let mut data = vec![EDDYSTONE_UID_FRAME, 0xEE];
+        data.extend_from_slice(&[0xED, 0xD1, 0xEB, 0xEA, 0xC0, 0x4E, 0x5D, 0xEF, 0xA0, 0x17]);
+        data.extend_from_slice(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xAB]);
+        data
+    }
+
+    fn ibeacon_frame() -> BeaconFrame {
+        parse_ibeacon(&ibeacon_payload()).unwrap()
+    }
+
+    #[test]
+    fn should_parse_ibeacon_manufacturer_data() {
+        let frame = ibeacon_frame();
+        assert_eq!(
+            frame.id,
+            BeaconId::IBeacon {
+                uuid: uuid::Uuid::from_bytes(PROXIMITY_UUID),
+                major: 1,
+                minor: 42,
+            }
+        );
+        assert_eq!(frame.tx_power, -59);
+        assert_eq!(frame.id.key(), "f7826da6-4fa2-4e98-8024-bc5b71e0893e:1:42");
+    }
+
+    #[test]
+    fn should_reject_ibeacon_with_wrong_length() {
+        let mut data = ibeacon_payload();
+        data.pop();
+        let result = parse_ibeacon(&data);
+        assert!(matches!(
+            result,
+            Err(BleError::PayloadParse(PayloadParseError::WrongLength {
+                expected: IBEACON_LEN,
+                actual: 22,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn should_ignore_manufacturer_data_from_other_companies() {
+        let handler = BeaconHandler::new(Vec::new(), Duration::from_mins(1));
+        let result = handler
+            .try_parse_manufacturer_data(0x0059, &ibeacon_payload())
+            .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_parse_eddystone_uid_with_and_without_reserved_bytes() {
+        let mut data = eddystone_payload();
+        let frame = parse_eddystone_uid(&data).unwrap();
+        assert_eq!(frame.tx_power, -18);
+        assert_eq!(frame.id.key(), "edd1ebeac04e5defa017:0123456789ab");
+
+        data.extend_from_slice(&[0x00, 0x00]);
+        assert_eq!(parse_eddystone_uid(&data).unwrap(), frame);
+    }
+
+    #[test]
+    fn should_ignore_eddystone_url_frames() {
+        let handler = BeaconHandler::new(Vec::new(), Duration::from_mins(1));
+        let url_frame = [0x10, 0xEE, 0x03, b'm', b'i', b'n', b'i', 0x07];
+        let result = handler
+            .try_parse_advertisement(ServiceUuid::EDDYSTONE, &url_frame)
+            .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn should_report_home_only_when_first_seen_or_back() {
+        let handler = BeaconHandler::new(Vec::new(), Duration::from_mins(1));
+        let start = Instant::now();
+        let dd = handler.record(ibeacon_frame(), start).unwrap().unwrap();
+        assert_eq!(dd.entities[0].state, EntityState::Text("home".to_string()));
+        assert!(
+            handler
+                .record(ibeacon_frame(), start + Duration::from_secs(10))
+                .unwrap()
+                .is_none()
+        );
+
+        assert!(handler.expire(start + Duration::from_secs(69)).is_empty());
+        assert_eq!(
+            handler.expire(start + Duration::from_secs(70)),
+            vec![ibeacon_frame()]
+        );
+        assert!(handler.expire(start + Duration::from_secs(200)).is_empty());
+
+        assert!(
+            handler
+                .record(ibeacon_frame(), start + Duration::from_mins(5))
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn should_skip_beacons_not_in_filter() {
+        let handler = BeaconHandler::new(
+            vec!["F7826DA6-4FA2-4E98-8024-BC5B71E0893E:1:42".to_string()],
+            Duration::from_mins(1),
+        );
+        let start = Instant::now();
+        assert!(handler.record(ibeacon_frame(), start).unwrap().is_some());
+
+        let other = parse_eddystone_uid(&eddystone_payload()).unwrap();
+        assert!(handler.record(other, start).unwrap().is_none());
+        assert_eq!(handler.expire(start + Duration::from_mins(10)).len(), 1);
+    }
+
+    #[test]
+    fn should_build_device_tracker_entity() {
+        let frame = parse_eddystone_uid(&eddystone_payload()).unwrap();
+        let dd = build_discovered(&frame, false).unwrap();
+        let entity = &dd.entities[0];
+        assert_eq!(
+            entity.entity_id,
+            "device_tracker.ble_eddystone_edd1ebeac04e5defa017_0123456789ab"
+        );
+        assert_eq!(entity.state, EntityState::Text("not_home".to_string()));
+        assert_eq!(
+            entity.attributes.get("instance"),
+            Some(&AttributeValue::String("0123456789ab".to_string()))
+        );
+        assert_eq!(dd.device.unique_id, "edd1ebeac04e5defa017:0123456789ab");
+    }
+}
@@ -4,19 +4,50 @@
 //!
 //! - **PVVX custom** (19 bytes, little-endian)
 //! - **ATC1441 original** (13 bytes, big-endian)
+//!
+//! Sensors still on stock Xiaomi firmware only advertise encrypted
+//! `MiBeacon` frames. With [`Lywsd03mmcHandler::with_stock_readout`], they
+//! are read after the scan instead: the handler connects, waits for one
+//! notification of the `EBE0CCC1` characteristic and disconnects.
+//!
+//! Readings are corrected with the configured per-MAC [`Calibration`]
+//! before the entity is built, keeping the uncorrected values as `raw_*`
+//! attributes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use btleplug::api::{Central, Peripheral as _, PeripheralProperties};
+use btleplug::platform::{Adapter, Peripheral};
+use tokio_stream::StreamExt as _;
 
 use minihub_app::ports::integration::DiscoveredDevice;
 use minihub_domain::device::Device;
-use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
 use minihub_domain::error::MiniHubError;
 
+use crate::config::Calibration;
 use crate::error::{BleError, PayloadParseError};
 use crate::parser::{self, ServiceUuid};
 
-use super::BleDeviceHandler;
+use super::miflora::find_characteristic;
+use super::{BleDeviceHandler, parse_mibeacon_mac};
 
 const PVVX_LEN: usize = 19;
 const ATC1441_LEN: usize = 13;
+const STOCK_DATA_LEN: usize = 5;
+
+/// The local name advertised by LYWSD03MMC peripherals on stock firmware.
+const STOCK_LOCAL_NAME: &str = "LYWSD03MMC";
+
+/// GATT characteristic UUID notifying temperature, humidity and battery
+/// voltage on stock firmware.
+const STOCK_DATA_CHAR: uuid::Uuid =
+    uuid::Uuid::from_u128(0xebe0_ccc1_7a0a_4b0c_8a1a_6ff2_997d_a3a6);
+
+/// Battery voltages mapped to 0 % and 100 %, in millivolts.
+const BATTERY_EMPTY_MV: u16 = 2100;
+const BATTERY_FULL_MV: u16 = 3100;
 
 /// Parsed sensor reading from a BLE advertisement.
 #[derive(Debug, Clone, PartialEq)]
@@ -33,14 +64,41 @@ pub(crate) struct SensorReading {
     pub battery_voltage: f64,
 }
 
-/// Handler for Xiaomi LYWSD03MMC sensors running ATC/PVVX firmware.
+/// Handler for Xiaomi LYWSD03MMC sensors running ATC/PVVX firmware, and
+/// optionally stock firmware through GATT.
 pub(crate) struct Lywsd03mmcHandler {
     filter: Vec<String>,
+    /// Offsets keyed by upper-case MAC address.
+    calibration: HashMap<String, Calibration>,
+    /// Per-device GATT timeout, set when stock sensors are read.
+    stock_timeout: Option<Duration>,
 }
 
 impl Lywsd03mmcHandler {
     pub(crate) fn new(filter: Vec<String>) -> Self {
-        Self { filter }
+        Self {
+            filter,
+            calibration: HashMap::new(),
+            stock_timeout: None,
+        }
+    }
+
+    /// Read sensors on stock firmware after each scan, giving up on a
+    /// sensor after `connect_timeout`.
+    #[must_use]
+    pub(crate) fn with_stock_readout(mut self, connect_timeout: Duration) -> Self {
+        self.stock_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Correct the readings of the sensors listed in `calibration`.
+    #[must_use]
+    pub(crate) fn with_calibration(mut self, calibration: &BTreeMap<String, Calibration>) -> Self {
+        self.calibration = calibration
+            .iter()
+            .map(|(mac, offsets)| (mac.to_ascii_uppercase(), *offsets))
+            .collect();
+        self
     }
 
     fn passes_filter(&self, mac: &str) -> bool {
@@ -79,10 +137,84 @@ impl BleDeviceHandler for Lywsd03mmcHandler {
             return Ok(None);
         }
 
-        build_discovered(&reading)
+        build_discovered(&reading, self.calibration.get(&mac_str))
             .map(Some)
             .map_err(BleError::Domain)
     }
+
+    async fn process_after_scan(&self, adapter: &Adapter) -> Vec<DiscoveredDevice> {
+        let Some(timeout) = self.stock_timeout else {
+            return Vec::new();
+        };
+        let peripherals = match adapter.peripherals().await {
+            Ok(list) => list,
+            Err(err) => {
+                tracing::warn!(%err, "failed to list peripherals for LYWSD03MMC readout");
+                return Vec::new();
+            }
+        };
+
+        let mut discovered = Vec::new();
+
+        for peripheral in &peripherals {
+            let Ok(Some(props)) = peripheral.properties().await else {
+                continue;
+            };
+            if !is_stock_peripheral(&props) {
+                continue;
+            }
+            let Some(mac) = stock_mac(&props) else {
+                tracing::debug!("LYWSD03MMC peripheral has no usable MAC address, skipping");
+                continue;
+            };
+
+            let mac_str = parser::format_mac(mac);
+            if !self.passes_filter(&mac_str) {
+                tracing::debug!(mac = %mac_str, "filtered out by device_filter");
+                continue;
+            }
+
+            tracing::debug!(mac = %mac_str, "reading stock LYWSD03MMC sensor via GATT");
+
+            let reading = match poll_stock(peripheral, mac, timeout).await {
+                Ok(reading) => reading,
+                Err(err) => {
+                    tracing::warn!(%err, mac = %mac_str, "failed to read LYWSD03MMC device");
+                    continue;
+                }
+            };
+            match build_discovered(&reading, self.calibration.get(&mac_str)) {
+                Ok(dd) => discovered.push(dd),
+                Err(err) => {
+                    tracing::warn!(%err, mac = %mac_str, "failed to build LYWSD03MMC discovered device");
+                }
+            }
+        }
+
+        discovered
+    }
+}
+
+/// Whether `props` describe a LYWSD03MMC on stock firmware.
+///
+/// Sensors on custom firmware also advertise `0x181A` and are already
+/// handled by the passive phase.
+fn is_stock_peripheral(props: &PeripheralProperties) -> bool {
+    props.local_name.as_deref() == Some(STOCK_LOCAL_NAME)
+        && !props.service_data.contains_key(&ServiceUuid::ATC1441)
+}
+
+/// MAC address of a stock sensor, from its `MiBeacon` frame when it carries
+/// one, otherwise from the peripheral address (zeroed on macOS).
+fn stock_mac(props: &PeripheralProperties) -> Option<[u8; 6]> {
+    props
+        .service_data
+        .get(&ServiceUuid::MIFLORA)
+        .and_then(|data| parse_mibeacon_mac(data).ok())
+        .or_else(|| {
+            let mac = props.address.into_inner();
+            (mac != [0; 6]).then_some(mac)
+        })
 }
 
 /// Dispatch to the correct parser based on payload length.
@@ -171,10 +303,57 @@ fn parse_atc1441(data: &[u8]) -> Result<SensorReading, BleError> {
     })
 }
 
-/// Build a [`DiscoveredDevice`] from a [`SensorReading`].
-pub(crate) fn build_discovered(reading: &SensorReading) -> Result<DiscoveredDevice, MiniHubError> {
+/// Parse the 5-byte stock firmware notification of `STOCK_DATA_CHAR`.
+///
+/// | Offset | Field | Type |
+/// |--------|-------|------|
+/// | 0–1 | Temperature | i16 LE, x0.01 C |
+/// | 2 | Humidity | u8, % |
+/// | 3–4 | Battery voltage | u16 LE, mV |
+///
+/// Stock firmware does not report a battery level, so it is estimated from
+/// the voltage.
+fn parse_stock_data(mac: [u8; 6], data: &[u8]) -> Result<SensorReading, BleError> {
+    if data.len() != STOCK_DATA_LEN {
+        return Err(BleError::PayloadParse(PayloadParseError::WrongLength {
+            format: "LYWSD03MMC stock",
+            expected: STOCK_DATA_LEN,
+            actual: data.len(),
+        }));
+    }
+
+    let temp_raw = i16::from_le_bytes([data[0], data[1]]);
+    let humidity = data[2];
+    let batt_mv = u16::from_le_bytes([data[3], data[4]]);
+
+    Ok(SensorReading {
+        mac,
+        temperature: f64::from(temp_raw) * 0.01,
+        humidity: f64::from(humidity),
+        battery_level: battery_level(batt_mv),
+        battery_voltage: f64::from(batt_mv) * 0.001,
+    })
+}
+
+/// Battery level estimated linearly between `BATTERY_EMPTY_MV` and
+/// `BATTERY_FULL_MV`.
+fn battery_level(millivolts: u16) -> u8 {
+    let span = u32::from(BATTERY_FULL_MV - BATTERY_EMPTY_MV);
+    let level = u32::from(millivolts.saturating_sub(BATTERY_EMPTY_MV)).min(span) * 100 / span;
+    u8::try_from(level).unwrap_or(100)
+}
+
+/// Build a [`DiscoveredDevice`] from a [`SensorReading`], corrected with
+/// `calibration` when given.
+pub(crate) fn build_discovered(
+    reading: &SensorReading,
+    calibration: Option<&Calibration>,
+) -> Result<DiscoveredDevice, MiniHubError> {
     let mac_str = parser::format_mac(reading.mac);
     let slug = parser::mac_slug(reading.mac);
+    let offsets = calibration.copied().unwrap_or_default();
+    let temperature = reading.temperature + offsets.temperature;
+    let humidity = (reading.humidity + offsets.humidity).clamp(0.0, 100.0);
 
     let device = Device::builder()
         .name(format!("LYWSD03MMC {mac_str}"))
@@ -184,14 +363,16 @@ pub(crate) fn build_discovered(reading: &SensorReading) -> Result<DiscoveredDevi
         .unique_id(&mac_str)
         .build()?;
 
-    let entity = Entity::builder()
+    let mut builder = Entity::builder()
         .device_id(device.id)
         .entity_id(format!("sensor.ble_{slug}"))
         .friendly_name(format!("BLE Temp/Humidity {mac_str}"))
-        .state(EntityState::On)
+        .state(EntityState::Numeric(temperature))
         .mac_address(&mac_str)
-        .attribute("temperature", AttributeValue::Float(reading.temperature))
-        .attribute("humidity", AttributeValue::Float(reading.humidity))
+        .device_class(DeviceClass::Temperature)
+        .unit_of_measurement("\u{b0}C")
+        .attribute("temperature", AttributeValue::Float(temperature))
+        .attribute("humidity", AttributeValue::Float(humidity))
         .attribute(
             "battery_level",
             AttributeValue::Int(i64::from(reading.battery_level)),
@@ -200,14 +381,113 @@ pub(crate) fn build_discovered(reading: &SensorReading) -> Result<DiscoveredDevi
             "battery_voltage",
             AttributeValue::Float(reading.battery_voltage),
         )
-        .build()?;
+        .attribute_meta(
+            "temperature",
+            AttributeMeta::default()
+                .with_precision(1)
+                .with_display_unit("\u{b0}C"),
+        )
+        .attribute_meta(
+            "humidity",
+            AttributeMeta::default()
+                .with_precision(1)
+                .with_range(0.0, 100.0)
+                .with_display_unit("%"),
+        )
+        .attribute_meta(
+            "battery_level",
+            AttributeMeta::default()
+                .with_range(0.0, 100.0)
+                .with_display_unit("%"),
+        )
+        .attribute_meta(
+            "battery_voltage",
+            AttributeMeta::default()
+                .with_precision(2)
+                .with_display_unit("V"),
+        );
+    if calibration.is_some() {
+        builder = builder
+            .attribute(
+                "raw_temperature",
+                AttributeValue::Float(reading.temperature),
+            )
+            .attribute("raw_humidity", AttributeValue::Float(reading.humidity))
+            .attribute_meta(
+                "raw_temperature",
+                AttributeMeta::default()
+                    .with_precision(1)
+                    .with_display_unit("\u{b0}C"),
+            )
+            .attribute_meta(
+                "raw_humidity",
+                AttributeMeta::default()
+                    .with_precision(1)
+                    .with_display_unit("%"),
+            );
+    }
+    let entity = builder.build()?;
 
     Ok(DiscoveredDevice {
         device,
         entities: vec![entity],
+        via_device: None,
     })
 }
 
+// GATT operations
+
+/// Read a stock LYWSD03MMC peripheral, giving up after `timeout`.
+///
+/// # Errors
+///
+/// Returns [`BleError::GattTimeout`] when the readout takes longer than
+/// `timeout`, or the error of the readout itself.
+async fn poll_stock(
+    peripheral: &Peripheral,
+    mac: [u8; 6],
+    timeout: Duration,
+) -> Result<SensorReading, BleError> {
+    tokio::time::timeout(timeout, read_stock(peripheral, mac))
+        .await
+        .map_err(|_| BleError::GattTimeout)?
+}
+
+/// Connect to a stock LYWSD03MMC peripheral, wait for one reading and
+/// disconnect.
+///
+/// The connection is always closed on return, even if the readout fails.
+async fn read_stock(peripheral: &Peripheral, mac: [u8; 6]) -> Result<SensorReading, BleError> {
+    peripheral.connect().await.map_err(BleError::GattConnect)?;
+
+    let result = read_stock_inner(peripheral, mac).await;
+
+    if let Err(err) = peripheral.disconnect().await {
+        tracing::warn!(%err, "failed to disconnect LYWSD03MMC peripheral");
+    }
+
+    result
+}
+
+async fn read_stock_inner(
+    peripheral: &Peripheral,
+    mac: [u8; 6],
+) -> Result<SensorReading, BleError> {
+    peripheral.discover_services().await?;
+
+    let data_char = find_characteristic(peripheral, STOCK_DATA_CHAR)?;
+
+    let mut notifications = peripheral.notifications().await?;
+    peripheral.subscribe(&data_char).await?;
+
+    while let Some(notification) = notifications.next().await {
+        if notification.uuid == STOCK_DATA_CHAR {
+            return parse_stock_data(mac, &notification.value);
+        }
+    }
+    Err(BleError::NotificationEnded)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,6 +592,57 @@ mod tests {
         assert!(source.to_string().contains("13 bytes"));
     }
 
+    // Stock firmware tests
+
+    #[test]
+    fn should_parse_stock_notification() {
+        let mac = [0xA4, 0xC1, 0x38, 0x5B, 0x0E, 0xDF];
+        let data: [u8; 5] = [
+            0x06, 0x09, // temp: 2310 (0x0906) LE → 23.10 C
+            0x28, // hum: 40%
+            0xD6, 0x0B, // voltage: 3030 mV (0x0BD6) LE
+        ];
+
+        let reading = parse_stock_data(mac, &data).unwrap();
+        assert_eq!(reading.mac, mac);
+        assert!((reading.temperature - 23.10).abs() < 0.001);
+        assert!((reading.humidity - 40.0).abs() < 0.001);
+        assert_eq!(reading.battery_level, 93);
+        assert!((reading.battery_voltage - 3.030).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_reject_stock_notification_with_wrong_length() {
+        let err = parse_stock_data([0; 6], &[0u8; 3]).unwrap_err();
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.to_string().contains("5 bytes"));
+    }
+
+    #[test]
+    fn should_clamp_estimated_battery_level() {
+        assert_eq!(battery_level(1900), 0);
+        assert_eq!(battery_level(2600), 50);
+        assert_eq!(battery_level(3300), 100);
+    }
+
+    #[test]
+    fn should_only_read_stock_peripherals() {
+        let mut props = PeripheralProperties {
+            local_name: Some("LYWSD03MMC".to_string()),
+            ..PeripheralProperties::default()
+        };
+        assert!(is_stock_peripheral(&props));
+
+        props
+            .service_data
+            .insert(ServiceUuid::ATC1441, vec![0u8; 19]);
+        assert!(!is_stock_peripheral(&props));
+
+        props.local_name = Some("Flower care".to_string());
+        props.service_data.clear();
+        assert!(!is_stock_peripheral(&props));
+    }
+
     // Dispatch tests
 
     #[test]
@@ -452,7 +783,7 @@ mod tests {
             battery_voltage: 3.05,
         };
 
-        let dd = build_discovered(&reading).unwrap();
+        let dd = build_discovered(&reading, None).unwrap();
         assert_eq!(dd.device.name, "LYWSD03MMC A4:C1:38:5B:0E:DF");
         assert_eq!(dd.device.manufacturer.as_deref(), Some("Xiaomi"));
         assert_eq!(dd.device.model.as_deref(), Some("LYWSD03MMC"));
@@ -461,7 +792,7 @@ mod tests {
         let entity = &dd.entities[0];
         assert_eq!(entity.entity_id, "sensor.ble_a4c1385b0edf");
         assert_eq!(entity.friendly_name, "BLE Temp/Humidity A4:C1:38:5B:0E:DF");
-        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(entity.state, EntityState::Numeric(23.1));
         assert_eq!(
             entity.get_attribute("temperature"),
             Some(&AttributeValue::Float(23.1))
@@ -478,5 +809,74 @@ mod tests {
             entity.get_attribute("battery_voltage"),
             Some(&AttributeValue::Float(3.05))
         );
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        let humidity = entity.get_attribute_meta("humidity").unwrap();
+        assert_eq!(humidity.precision, Some(1));
+        assert_eq!(humidity.max, Some(100.0));
+        assert_eq!(humidity.display_unit.as_deref(), Some("%"));
+        assert_eq!(entity.get_attribute("raw_temperature"), None);
+    }
+
+    #[test]
+    fn should_apply_calibration_offsets_and_keep_raw_values() {
+        let reading = SensorReading {
+            mac: [0xA4, 0xC1, 0x38, 0x5B, 0x0E, 0xDF],
+            temperature: 23.5,
+            humidity: 98.5,
+            battery_level: 87,
+            battery_voltage: 3.05,
+        };
+        let calibration = Calibration {
+            temperature: -1.5,
+            humidity: 3.0,
+        };
+
+        let dd = build_discovered(&reading, Some(&calibration)).unwrap();
+
+        let entity = &dd.entities[0];
+        assert_eq!(
+            entity.get_attribute("temperature"),
+            Some(&AttributeValue::Float(22.0))
+        );
+        assert_eq!(
+            entity.get_attribute("humidity"),
+            Some(&AttributeValue::Float(100.0))
+        );
+        assert_eq!(
+            entity.get_attribute("raw_temperature"),
+            Some(&AttributeValue::Float(23.5))
+        );
+        assert_eq!(
+            entity.get_attribute("raw_humidity"),
+            Some(&AttributeValue::Float(98.5))
+        );
+    }
+
+    #[test]
+    fn should_look_up_calibration_by_mac_case_insensitively() {
+        let handler = Lywsd03mmcHandler::new(Vec::new()).with_calibration(&BTreeMap::from([(
+            "a4:c1:38:5b:0e:df".to_owned(),
+            Calibration {
+                temperature: 1.0,
+                humidity: 0.0,
+            },
+        )]));
+        // MAC A4:C1:38:5B:0E:DF, temp 23.10 C, hum 40.00%, batt 100%, 3130 mV
+        let data: [u8; 19] = [
+            0xA4, 0xC1, 0x38, 0x5B, 0x0E, 0xDF, 0x06, 0x09, 0xA0, 0x0F, 0x3A, 0x0C, 0x64, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let dd = handler
+            .try_parse_advertisement(ServiceUuid::ATC1441, &data)
+            .unwrap()
+            .unwrap();
+
+        let entity = &dd.entities[0];
+        let Some(AttributeValue::Float(temperature)) = entity.get_attribute("temperature") else {
+            panic!("temperature attribute missing");
+        };
+        assert!((temperature - 24.1).abs() < 1e-9);
+        assert!(entity.get_attribute("raw_temperature").is_some());
     }
 }
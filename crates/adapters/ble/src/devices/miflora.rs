@@ -6,12 +6,13 @@
 
 use std::time::Duration;
 
-use btleplug::api::{Central, Characteristic, Peripheral as _, WriteType};
+use btleplug::api::{Central, Characteristic, Peripheral as _, PeripheralProperties, WriteType};
 use btleplug::platform::{Adapter, Peripheral};
+use futures::stream::{self, StreamExt as _};
 
 use minihub_app::ports::integration::DiscoveredDevice;
 use minihub_domain::device::Device;
-use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
 use minihub_domain::error::MiniHubError;
 
 use crate::error::{BleError, PayloadParseError};
@@ -79,6 +80,8 @@ pub(crate) struct MifloraReading {
 pub(crate) struct MifloraHandler {
     filter: Vec<String>,
     connect_timeout: Duration,
+    /// How many sensors are read at the same time after a scan.
+    max_concurrent_connections: usize,
 }
 
 impl MifloraHandler {
@@ -86,9 +89,17 @@ impl MifloraHandler {
         Self {
             filter,
             connect_timeout,
+            max_concurrent_connections: 1,
         }
     }
 
+    /// Read up to `max` sensors at the same time; `0` is treated as `1`.
+    #[must_use]
+    pub(crate) fn with_max_concurrent_connections(mut self, max: usize) -> Self {
+        self.max_concurrent_connections = max.max(1);
+        self
+    }
+
     fn passes_filter(&self, mac: &str) -> bool {
         if self.filter.is_empty() {
             return true;
@@ -120,67 +131,78 @@ impl BleDeviceHandler for MifloraHandler {
             }
         };
 
-        let mut discovered = Vec::new();
-
-        for peripheral in &peripherals {
+        let mut targets = Vec::new();
+        for peripheral in peripherals {
             let Ok(Some(props)) = peripheral.properties().await else {
                 continue;
             };
-
-            let name_matches = props
-                .local_name
-                .as_deref()
-                .is_some_and(|name| name == MIFLORA_LOCAL_NAME);
-            if !name_matches {
-                continue;
+            if let Some(mac) = self.target_mac(&props) {
+                targets.push((peripheral, mac));
             }
+        }
 
-            let Some(mibeacon_data) = props.service_data.get(&ServiceUuid::MIFLORA) else {
-                tracing::debug!("Mi Flora peripheral has no 0xFE95 service data, skipping");
-                continue;
-            };
+        // Each readout carries its own timeout and logs its own failure, so
+        // a slow or broken sensor only delays its own result.
+        stream::iter(targets)
+            .map(|(peripheral, mac)| async move { self.read_device(&peripheral, mac).await })
+            .buffer_unordered(self.max_concurrent_connections)
+            .filter_map(|dd| async move { dd })
+            .collect()
+            .await
+    }
+}
 
-            let mac_bytes = match parse_mibeacon_mac(mibeacon_data) {
-                Ok(mac) => mac,
-                Err(err) => {
-                    tracing::warn!(%err, "failed to parse Mi Flora MAC from MiBeacon payload");
-                    continue;
-                }
-            };
+impl MifloraHandler {
+    /// MAC address of the Mi Flora sensor described by `props`, if it is
+    /// one and passes the filter.
+    fn target_mac(&self, props: &PeripheralProperties) -> Option<[u8; 6]> {
+        let name_matches = props
+            .local_name
+            .as_deref()
+            .is_some_and(|name| name == MIFLORA_LOCAL_NAME);
+        if !name_matches {
+            return None;
+        }
 
-            let mac_str = parser::format_mac(mac_bytes);
-            if !self.passes_filter(&mac_str) {
-                tracing::debug!(mac = %mac_str, "Mi Flora filtered out by miflora_filter");
-                continue;
+        let Some(mibeacon_data) = props.service_data.get(&ServiceUuid::MIFLORA) else {
+            tracing::debug!("Mi Flora peripheral has no 0xFE95 service data, skipping");
+            return None;
+        };
+
+        let mac_bytes = match parse_mibeacon_mac(mibeacon_data) {
+            Ok(mac) => mac,
+            Err(err) => {
+                tracing::warn!(%err, "failed to parse Mi Flora MAC from MiBeacon payload");
+                return None;
             }
+        };
 
-            tracing::debug!(mac = %mac_str, "reading Mi Flora sensor via GATT");
-
-            let result =
-                tokio::time::timeout(self.connect_timeout, read_miflora(peripheral, mac_bytes))
-                    .await;
-
-            let reading = match result {
-                Ok(Ok(reading)) => reading,
-                Ok(Err(err)) => {
-                    tracing::warn!(%err, mac = %mac_str, "failed to read Mi Flora device");
-                    continue;
-                }
-                Err(_) => {
-                    tracing::warn!(mac = %mac_str, "Mi Flora GATT readout timed out");
-                    continue;
-                }
-            };
+        let mac_str = parser::format_mac(mac_bytes);
+        if !self.passes_filter(&mac_str) {
+            tracing::debug!(mac = %mac_str, "Mi Flora filtered out by miflora_filter");
+            return None;
+        }
+        Some(mac_bytes)
+    }
 
-            match build_discovered(&reading) {
-                Ok(dd) => discovered.push(dd),
-                Err(err) => {
-                    tracing::warn!(%err, mac = %mac_str, "failed to build Mi Flora discovered device");
-                }
-            }
+    /// Read the sensor `mac` and build its discovered device, including the
+    /// polling status when the readout failed.
+    async fn read_device(&self, peripheral: &Peripheral, mac: [u8; 6]) -> Option<DiscoveredDevice> {
+        let mac_str = parser::format_mac(mac);
+        tracing::debug!(mac = %mac_str, "reading Mi Flora sensor via GATT");
+
+        let result = poll_miflora(peripheral, mac, self.connect_timeout).await;
+        if let Err(err) = &result {
+            tracing::warn!(%err, mac = %mac_str, "failed to read Mi Flora device");
         }
 
-        discovered
+        match build_poll_result(mac, &result) {
+            Ok(dd) => Some(dd),
+            Err(err) => {
+                tracing::warn!(%err, mac = %mac_str, "failed to build Mi Flora discovered device");
+                None
+            }
+        }
     }
 }
 
@@ -285,10 +307,19 @@ fn parse_firmware(data: &[u8]) -> Result<MifloraFirmware, BleError> {
 
 // Domain mapping
 
-/// Build a [`DiscoveredDevice`] from a [`MifloraReading`].
-fn build_discovered(reading: &MifloraReading) -> Result<DiscoveredDevice, MiniHubError> {
-    let mac_str = parser::format_mac(reading.mac);
-    let slug = parser::mac_slug(reading.mac);
+/// Build the [`DiscoveredDevice`] reporting the outcome of a readout of
+/// the sensor `mac`.
+///
+/// Always contains the polling status entity
+/// (`sensor.miflora_<mac>_poll`), which is `on` when the readout succeeded
+/// and `off` with a `last_error` attribute when it failed. The sensor entity
+/// is only included on success, so a failed readout keeps the last known
+/// values.
+pub(crate) fn build_poll_result(
+    mac: [u8; 6],
+    result: &Result<MifloraReading, BleError>,
+) -> Result<DiscoveredDevice, MiniHubError> {
+    let mac_str = parser::format_mac(mac);
 
     let device = Device::builder()
         .name(format!("Mi Flora {mac_str}"))
@@ -298,12 +329,55 @@ fn build_discovered(reading: &MifloraReading) -> Result<DiscoveredDevice, MiniHu
         .unique_id(&mac_str)
         .build()?;
 
-    let entity = Entity::builder()
+    let mut entities = Vec::with_capacity(2);
+    if let Ok(reading) = result {
+        entities.push(build_sensor_entity(&device, reading)?);
+    }
+    entities.push(build_poll_entity(&device, mac, result.as_ref().err())?);
+
+    Ok(DiscoveredDevice {
+        device,
+        entities,
+        via_device: None,
+    })
+}
+
+/// Build the polling status entity of the sensor `mac`.
+fn build_poll_entity(
+    device: &Device,
+    mac: [u8; 6],
+    error: Option<&BleError>,
+) -> Result<Entity, MiniHubError> {
+    let mac_str = parser::format_mac(mac);
+    let slug = parser::mac_slug(mac);
+
+    let builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(format!("sensor.miflora_{slug}_poll"))
+        .friendly_name(format!("Mi Flora {mac_str} Polling"))
+        .mac_address(&mac_str);
+    let builder = match error {
+        None => builder.state(EntityState::On),
+        Some(err) => builder
+            .state(EntityState::Off)
+            .attribute("last_error", AttributeValue::String(err.to_string())),
+    };
+    builder.build()
+}
+
+/// Build the sensor entity carrying the values of `reading`.
+fn build_sensor_entity(device: &Device, reading: &MifloraReading) -> Result<Entity, MiniHubError> {
+    let mac_str = parser::format_mac(reading.mac);
+    let slug = parser::mac_slug(reading.mac);
+
+    Entity::builder()
         .device_id(device.id)
         .entity_id(format!("sensor.miflora_{slug}"))
         .friendly_name(format!("Mi Flora {mac_str}"))
-        .state(EntityState::On)
+        .state(EntityState::Numeric(f64::from(reading.sensor.moisture)))
         .mac_address(&mac_str)
+        .device_class(DeviceClass::Moisture)
+        .unit_of_measurement("%")
         .attribute(
             "temperature",
             AttributeValue::Float(reading.sensor.temperature),
@@ -328,19 +402,37 @@ fn build_discovered(reading: &MifloraReading) -> Result<DiscoveredDevice, MiniHu
             "firmware",
             AttributeValue::String(reading.firmware.firmware_version.clone()),
         )
-        .build()?;
-
-    Ok(DiscoveredDevice {
-        device,
-        entities: vec![entity],
-    })
+        .attribute_meta(
+            "temperature",
+            AttributeMeta::default()
+                .with_precision(1)
+                .with_display_unit("\u{b0}C"),
+        )
+        .attribute_meta("light", AttributeMeta::default().with_display_unit("lx"))
+        .attribute_meta(
+            "moisture",
+            AttributeMeta::default()
+                .with_range(0.0, 100.0)
+                .with_display_unit("%"),
+        )
+        .attribute_meta(
+            "conductivity",
+            AttributeMeta::default().with_display_unit("\u{b5}S/cm"),
+        )
+        .attribute_meta(
+            "battery_level",
+            AttributeMeta::default()
+                .with_range(0.0, 100.0)
+                .with_display_unit("%"),
+        )
+        .build()
 }
 
 // GATT operations
 
 /// Find a GATT characteristic by UUID on a peripheral that has already
 /// discovered its services.
-fn find_characteristic(
+pub(crate) fn find_characteristic(
     peripheral: &Peripheral,
     uuid: uuid::Uuid,
 ) -> Result<Characteristic, BleError> {
@@ -351,6 +443,22 @@ fn find_characteristic(
         .ok_or(BleError::CharacteristicNotFound { uuid })
 }
 
+/// Read a Mi Flora peripheral, giving up after `timeout`.
+///
+/// # Errors
+///
+/// Returns [`BleError::GattTimeout`] when the readout takes longer than
+/// `timeout`, or the error of the readout itself.
+pub(crate) async fn poll_miflora(
+    peripheral: &Peripheral,
+    mac: [u8; 6],
+    timeout: Duration,
+) -> Result<MifloraReading, BleError> {
+    tokio::time::timeout(timeout, read_miflora(peripheral, mac))
+        .await
+        .map_err(|_| BleError::GattTimeout)?
+}
+
 /// Connect to a Mi Flora peripheral, read sensor data and firmware info,
 /// and return a [`MifloraReading`].
 ///
@@ -668,12 +776,12 @@ mod tests {
         assert!(source.to_string().contains("MAC address"));
     }
 
-    // build_discovered
+    // build_poll_result
 
     #[test]
     fn should_build_discovered_device_from_reading() {
         let reading = sample_reading();
-        let dd = build_discovered(&reading).unwrap();
+        let dd = build_poll_result(reading.mac, &Ok(reading)).unwrap();
 
         assert_eq!(dd.device.name, "Mi Flora C4:7C:8D:6A:12:34");
         assert_eq!(dd.device.manufacturer.as_deref(), Some("Xiaomi"));
@@ -681,11 +789,11 @@ mod tests {
         assert_eq!(dd.device.integration, "ble");
         assert_eq!(dd.device.unique_id, "C4:7C:8D:6A:12:34");
 
-        assert_eq!(dd.entities.len(), 1);
+        assert_eq!(dd.entities.len(), 2);
         let entity = &dd.entities[0];
         assert_eq!(entity.entity_id, "sensor.miflora_c47c8d6a1234");
         assert_eq!(entity.friendly_name, "Mi Flora C4:7C:8D:6A:12:34");
-        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(entity.state, EntityState::Numeric(56.0));
 
         assert_eq!(
             entity.get_attribute("temperature"),
@@ -703,6 +811,10 @@ mod tests {
             entity.get_attribute("conductivity"),
             Some(&AttributeValue::Int(1561))
         );
+        assert_eq!(entity.device_class, Some(DeviceClass::Moisture));
+        let moisture = entity.get_attribute_meta("moisture").unwrap();
+        assert_eq!(moisture.min, Some(0.0));
+        assert_eq!(moisture.max, Some(100.0));
         assert_eq!(
             entity.get_attribute("battery_level"),
             Some(&AttributeValue::Int(99))
@@ -713,6 +825,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_report_poll_success_when_reading_succeeds() {
+        let reading = sample_reading();
+        let dd = build_poll_result(reading.mac, &Ok(reading)).unwrap();
+
+        let poll = &dd.entities[1];
+        assert_eq!(poll.entity_id, "sensor.miflora_c47c8d6a1234_poll");
+        assert_eq!(poll.friendly_name, "Mi Flora C4:7C:8D:6A:12:34 Polling");
+        assert_eq!(poll.state, EntityState::On);
+        assert_eq!(poll.mac_address.as_deref(), Some("C4:7C:8D:6A:12:34"));
+        assert_eq!(poll.get_attribute("last_error"), None);
+    }
+
+    #[test]
+    fn should_only_report_poll_failure_when_reading_fails() {
+        let mac = [0xC4, 0x7C, 0x8D, 0x6A, 0x12, 0x34];
+        let dd = build_poll_result(mac, &Err(BleError::GattTimeout)).unwrap();
+
+        assert_eq!(dd.device.unique_id, "C4:7C:8D:6A:12:34");
+        assert_eq!(dd.entities.len(), 1);
+        let poll = &dd.entities[0];
+        assert_eq!(poll.entity_id, "sensor.miflora_c47c8d6a1234_poll");
+        assert_eq!(poll.state, EntityState::Off);
+        assert_eq!(
+            poll.get_attribute("last_error"),
+            Some(&AttributeValue::String("GATT read timed out".to_owned()))
+        );
+    }
+
     // Handler trait tests
 
     #[test]
@@ -751,4 +892,38 @@ mod tests {
         assert!(handler.passes_filter("C4:7C:8D:6A:12:34"));
         assert!(handler.passes_filter("c4:7c:8d:6a:12:34"));
     }
+
+    #[test]
+    fn should_select_filtered_miflora_peripherals_for_readout() {
+        let handler = MifloraHandler::new(
+            vec!["C4:7C:8D:6A:12:34".to_owned()],
+            Duration::from_secs(10),
+        );
+        let mut props = PeripheralProperties {
+            local_name: Some("Flower care".to_owned()),
+            ..PeripheralProperties::default()
+        };
+        assert_eq!(handler.target_mac(&props), None);
+
+        props.service_data.insert(
+            ServiceUuid::MIFLORA,
+            vec![
+                0x71, 0x20, 0x98, 0x00, 0x03, 0x34, 0x12, 0x6A, 0x8D, 0x7C, 0xC4,
+            ],
+        );
+        assert_eq!(
+            handler.target_mac(&props),
+            Some([0xC4, 0x7C, 0x8D, 0x6A, 0x12, 0x34])
+        );
+
+        props.local_name = Some("LYWSD03MMC".to_owned());
+        assert_eq!(handler.target_mac(&props), None);
+    }
+
+    #[test]
+    fn should_read_at_least_one_device_at_a_time() {
+        let handler = MifloraHandler::new(Vec::new(), Duration::from_secs(10))
+            .with_max_concurrent_connections(0);
+        assert_eq!(handler.max_concurrent_connections, 1);
+    }
 }
@@ -4,14 +4,16 @@
 //! how to parse passive advertisements and optionally perform post-scan
 //! active GATT reads.
 
+mod beacon;
 pub(crate) mod lywsd03mmc;
 mod miflora;
 
+pub(crate) use beacon::BeaconHandler;
 pub(crate) use lywsd03mmc::Lywsd03mmcHandler;
 pub(crate) use miflora::MifloraHandler;
 
 // Re-exports used by lib.rs for service-call handling.
-pub(crate) use miflora::{blink_miflora, parse_mibeacon_mac};
+pub(crate) use miflora::{blink_miflora, build_poll_result, parse_mibeacon_mac, poll_miflora};
 
 use std::future::Future;
 
@@ -19,6 +21,15 @@ use minihub_app::ports::integration::DiscoveredDevice;
 
 use crate::error::BleError;
 
+/// The device handlers a [`BleScanner`](crate::scanner::BleScanner)
+/// dispatches advertisements to; the optional ones are disabled by
+/// configuration.
+pub(crate) struct DeviceHandlers {
+    pub lywsd: Lywsd03mmcHandler,
+    pub miflora: Option<MifloraHandler>,
+    pub beacons: Option<BeaconHandler>,
+}
+
 /// A supported BLE device type that the scanner knows how to handle.
 ///
 /// Implementors define how to identify their devices from advertisements
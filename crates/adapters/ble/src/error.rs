@@ -25,6 +25,10 @@ pub enum BleError {
     #[error("GATT read timed out")]
     GattTimeout,
 
+    /// The peripheral stopped sending notifications before a reading came in.
+    #[error("GATT notification stream ended before a reading")]
+    NotificationEnded,
+
     /// A required GATT characteristic was not found on the peripheral.
     #[error("GATT characteristic {uuid} not found")]
     CharacteristicNotFound {
@@ -163,6 +167,15 @@ mod tests {
         assert_eq!(err.to_string(), "GATT read timed out");
     }
 
+    #[test]
+    fn should_display_notification_ended_error() {
+        let err = BleError::NotificationEnded;
+        assert_eq!(
+            err.to_string(),
+            "GATT notification stream ended before a reading"
+        );
+    }
+
     #[test]
     fn should_display_characteristic_not_found_error() {
         let uuid = uuid::Uuid::from_u128(0x0000_1a00_0000_1000_8000_0080_5f9b_34fb);
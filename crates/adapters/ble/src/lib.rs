@@ -10,8 +10,9 @@
 //! 1. **Passive phase** — collects service-data advertisements (no
 //!    connection needed) and parses them into sensor entities.
 //! 2. **Active GATT phase** (optional) — after the passive scan stops,
-//!    connects to discovered Mi Flora plant sensors, reads sensor data
-//!    and firmware info via GATT, then disconnects.
+//!    connects to discovered Mi Flora plant sensors and LYWSD03MMC sensors
+//!    on stock firmware, reads their data via GATT, then disconnects. Up to
+//!    `max_concurrent_connections` Mi Flora sensors are read at once.
 //!
 //! ## Supported formats
 //!
@@ -19,7 +20,33 @@
 //! |--------|------|------|---------|------------|
 //! | PVVX custom | Passive | `0x181A` | 19 bytes | Little-endian |
 //! | ATC1441 original | Passive | `0x181A` | 13 bytes | Big-endian |
+//! | LYWSD03MMC stock | Active GATT | `EBE0CCC1` notification | 5 bytes | Little-endian |
 //! | Mi Flora (HHCCJCY01) | Active GATT | `0xFE95` | 16 + 7 bytes | Little-endian |
+//! | iBeacon | Passive | Manufacturer `0x004C` | 23 bytes | Big-endian |
+//! | Eddystone-UID | Passive | `0xFEAA` | 18 or 20 bytes | Big-endian |
+//!
+//! LYWSD03MMC readings can be corrected with per-MAC `calibration` offsets;
+//! the uncorrected values are kept in the `raw_temperature` and
+//! `raw_humidity` attributes.
+//!
+//! Every Mi Flora readout also updates a polling status entity
+//! (`sensor.miflora_<mac>_poll`): `on` after a successful readout, `off`
+//! with a `last_error` attribute after a failed one.
+//!
+//! iBeacon and Eddystone beacons (tags, phones) become presence entities,
+//! `device_tracker.ble_<id>`, reporting `home` while heard and `not_home`
+//! once silent for `beacon_away_timeout_secs`; automations can trigger on
+//! these states.
+//!
+//! ## Service calls
+//!
+//! | Service | Devices | Effect |
+//! |---------|---------|--------|
+//! | `blink` | Mi Flora | Blinks the LED |
+//! | `refresh` | Mi Flora | Reads the sensor now instead of waiting for the next scan |
+//!
+//! Both complete asynchronously with a `service_call_completed` or
+//! `service_call_failed` event.
 //!
 //! ## Dependency rule
 //!
@@ -31,24 +58,24 @@ mod error;
 pub mod parser;
 mod scanner;
 
-pub use config::BleConfig;
+pub use config::{BleConfig, Calibration};
 pub use error::BleError;
 
 use std::collections::HashMap;
 use std::time::Duration;
 
 use btleplug::api::{BDAddr, Central, Manager as _, Peripheral as _, ScanFilter};
-use btleplug::platform::Manager;
+use btleplug::platform::{Manager, Peripheral};
 use tokio::task::JoinHandle;
 use tokio_stream::StreamExt as _;
 
 use minihub_app::ports::integration::{Integration, IntegrationContext};
 use minihub_domain::entity::Entity;
 use minihub_domain::error::{MiniHubError, NotFoundError};
-use minihub_domain::event::{Event, EventType};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload, ServiceCallResultPayload};
 use minihub_domain::id::EntityId;
 
-use crate::devices::{Lywsd03mmcHandler, MifloraHandler};
+use crate::devices::{BeaconHandler, DeviceHandlers, Lywsd03mmcHandler, MifloraHandler};
 use crate::parser::ServiceUuid;
 use crate::scanner::BleScanner;
 
@@ -96,11 +123,21 @@ impl Integration for BleIntegration {
         let scan_duration = Duration::from_secs(u64::from(self.config.scan_duration_secs));
         let interval = Duration::from_secs(u64::from(self.config.update_interval_secs));
 
-        let lywsd = Lywsd03mmcHandler::new(self.config.device_filter.clone());
+        let connect_timeout =
+            Duration::from_secs(u64::from(self.config.miflora_connect_timeout_secs));
+        let mut lywsd = Lywsd03mmcHandler::new(self.config.device_filter.clone())
+            .with_calibration(&self.config.calibration);
+        if self.config.lywsd_stock_enabled {
+            lywsd = lywsd.with_stock_readout(connect_timeout);
+        }
         let miflora = self.config.miflora_enabled.then(|| {
-            MifloraHandler::new(
-                self.config.miflora_filter.clone(),
-                Duration::from_secs(u64::from(self.config.miflora_connect_timeout_secs)),
+            MifloraHandler::new(self.config.miflora_filter.clone(), connect_timeout)
+                .with_max_concurrent_connections(self.config.max_concurrent_connections)
+        });
+        let beacons = self.config.beacons_enabled.then(|| {
+            BeaconHandler::new(
+                self.config.beacon_filter.clone(),
+                Duration::from_secs(u64::from(self.config.beacon_away_timeout_secs)),
             )
         });
 
@@ -113,12 +150,20 @@ impl Integration for BleIntegration {
             adapter,
             scan_duration,
             interval,
-            lywsd,
-            miflora,
+            DeviceHandlers {
+                lywsd,
+                miflora,
+                beacons,
+            },
         ));
 
         let subscriber_ctx = ctx;
-        self.subscriber_handle = Some(tokio::spawn(run_event_subscriber(subscriber_ctx)));
+        let connect_timeout =
+            Duration::from_secs(u64::from(self.config.miflora_connect_timeout_secs));
+        self.subscriber_handle = Some(tokio::spawn(run_event_subscriber(
+            subscriber_ctx,
+            connect_timeout,
+        )));
 
         tracing::info!(
             interval_secs = self.config.update_interval_secs,
@@ -166,7 +211,10 @@ const SERVICE_CALL_SCAN_SECS: u64 = 3;
 /// Subscribe to the event bus, filter for [`EventType::ServiceCallRequested`]
 /// events that target BLE entities (those with a stored `mac_address`), and
 /// handle them.
-async fn run_event_subscriber(ctx: impl IntegrationContext + 'static) {
+///
+/// Supported services are `blink` and `refresh`; `connect_timeout` bounds
+/// the GATT readout of the latter.
+async fn run_event_subscriber(ctx: impl IntegrationContext + 'static, connect_timeout: Duration) {
     let mut rx = ctx.subscribe();
 
     loop {
@@ -210,11 +258,10 @@ async fn run_event_subscriber(ctx: impl IntegrationContext + 'static) {
             continue;
         };
 
-        let service = event
-            .data
-            .get("service")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+        let Ok(ServiceCallPayload { service, .. }) = event.payload() else {
+            continue;
+        };
+        let service = service.as_str();
         let mac_str = crate::parser::format_mac(mac);
 
         tracing::info!(
@@ -224,45 +271,71 @@ async fn run_event_subscriber(ctx: impl IntegrationContext + 'static) {
             "BLE handling service call"
         );
 
-        match service {
-            "blink" => {
-                let result = handle_blink(mac).await;
-                let result_event = match result {
-                    Ok(()) => {
-                        tracing::info!(mac = %mac_str, "BLE blink completed");
-                        Event::new(
-                            EventType::ServiceCallCompleted,
-                            Some(entity_id),
-                            serde_json::json!({ "service": "blink" }),
-                        )
-                    }
-                    Err(err) => {
-                        tracing::warn!(%err, mac = %mac_str, "BLE blink failed");
-                        Event::new(
-                            EventType::ServiceCallFailed,
-                            Some(entity_id),
-                            serde_json::json!({
-                                "service": "blink",
-                                "error": err.to_string(),
-                            }),
-                        )
-                    }
-                };
-
-                if let Err(err) = ctx.publish(result_event).await {
-                    tracing::warn!(%err, "failed to publish service call result event");
-                }
-            }
+        let result = match service {
+            "blink" => handle_blink(mac).await,
+            "refresh" => handle_refresh(&ctx, mac, connect_timeout).await,
             other => {
                 tracing::debug!(service = other, "BLE ignoring unknown service");
+                continue;
+            }
+        };
+
+        let result_event = match result {
+            Ok(()) => {
+                tracing::info!(mac = %mac_str, service, "BLE service call completed");
+                Event::with_payload(
+                    EventType::ServiceCallCompleted,
+                    Some(entity_id),
+                    &ServiceCallResultPayload::completed(service),
+                )
+            }
+            Err(err) => {
+                tracing::warn!(%err, mac = %mac_str, service, "BLE service call failed");
+                Event::with_payload(
+                    EventType::ServiceCallFailed,
+                    Some(entity_id),
+                    &ServiceCallResultPayload::failed(service, err.to_string()),
+                )
             }
+        };
+
+        if let Err(err) = ctx.publish(result_event.with_cause(&event)).await {
+            tracing::warn!(%err, "failed to publish service call result event");
         }
     }
 }
 
-/// Perform a short BLE scan to find the peripheral with the given MAC,
-/// then call [`devices::miflora::blink_miflora`] on it.
+/// Find the peripheral with the given MAC, then call
+/// [`devices::miflora::blink_miflora`] on it.
 async fn handle_blink(mac: [u8; 6]) -> Result<(), BleError> {
+    let peripheral = find_miflora_peripheral(mac).await?;
+    devices::blink_miflora(&peripheral).await
+}
+
+/// Read the Mi Flora sensor with the given MAC right away and persist the
+/// outcome, like a scheduled readout would.
+///
+/// The polling status entity is updated even when the peripheral cannot be
+/// found or read.
+async fn handle_refresh(
+    ctx: &impl IntegrationContext,
+    mac: [u8; 6],
+    connect_timeout: Duration,
+) -> Result<(), BleError> {
+    let result = match find_miflora_peripheral(mac).await {
+        Ok(peripheral) => devices::poll_miflora(&peripheral, mac, connect_timeout).await,
+        Err(err) => Err(err),
+    };
+
+    let dd = devices::build_poll_result(mac, &result).map_err(BleError::Domain)?;
+    ctx.persist_discovered(dd).await.map_err(BleError::Domain)?;
+
+    result.map(|_| ())
+}
+
+/// Perform a short BLE scan to find the Mi Flora peripheral with the given
+/// MAC.
+async fn find_miflora_peripheral(mac: [u8; 6]) -> Result<Peripheral, BleError> {
     let manager = Manager::new().await?;
     let adapters = manager.adapters().await?;
     let central = adapters.into_iter().next().ok_or(BleError::NotAvailable)?;
@@ -288,7 +361,7 @@ async fn handle_blink(mac: [u8; 6]) -> Result<(), BleError> {
     central.stop_scan().await?;
 
     let peripherals = central.peripherals().await?;
-    for peripheral in &peripherals {
+    for peripheral in peripherals {
         let Ok(Some(props)) = peripheral.properties().await else {
             continue;
         };
@@ -302,7 +375,7 @@ async fn handle_blink(mac: [u8; 6]) -> Result<(), BleError> {
         };
 
         if parsed_mac == mac {
-            return devices::blink_miflora(peripheral).await;
+            return Ok(peripheral);
         }
     }
 
@@ -325,6 +398,8 @@ mod tests {
 
     use crate::devices::lywsd03mmc::{SensorReading, build_discovered};
 
+    const TEST_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
     struct NoOpContext;
 
     impl IntegrationContext for NoOpContext {
@@ -369,6 +444,7 @@ mod tests {
     struct BroadcastContext {
         tx: broadcast::Sender<Event>,
         published: Arc<Mutex<Vec<Event>>>,
+        upserted: Arc<Mutex<Vec<Entity>>>,
         entities: Arc<Mutex<HashMap<EntityId, Entity>>>,
     }
 
@@ -378,6 +454,7 @@ mod tests {
             Self {
                 tx,
                 published: Arc::new(Mutex::new(Vec::new())),
+                upserted: Arc::new(Mutex::new(Vec::new())),
                 entities: Arc::new(Mutex::new(HashMap::new())),
             }
         }
@@ -394,10 +471,14 @@ mod tests {
                 device_id: DeviceId::new(),
                 friendly_name: "Test entity".into(),
                 state: EntityState::On,
+                device_class: None,
+                unit_of_measurement: None,
                 attributes: HashMap::new(),
+                attribute_meta: HashMap::new(),
                 mac_address: Some(mac.to_owned()),
                 last_changed: minihub_domain::time::now(),
                 last_updated: minihub_domain::time::now(),
+                version: 1,
             };
             self.entities.lock().unwrap().insert(entity_id, entity);
         }
@@ -412,6 +493,10 @@ mod tests {
         }
 
         async fn upsert_entity(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            self.upserted
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(entity.clone());
             Ok(entity)
         }
 
@@ -467,7 +552,7 @@ mod tests {
             battery_voltage: 3.05,
         };
 
-        let dd = build_discovered(&reading).unwrap();
+        let dd = build_discovered(&reading, None).unwrap();
         assert_eq!(dd.device.name, "LYWSD03MMC A4:C1:38:5B:0E:DF");
         assert_eq!(dd.device.manufacturer.as_deref(), Some("Xiaomi"));
         assert_eq!(dd.device.model.as_deref(), Some("LYWSD03MMC"));
@@ -476,7 +561,7 @@ mod tests {
         let entity = &dd.entities[0];
         assert_eq!(entity.entity_id, "sensor.ble_a4c1385b0edf");
         assert_eq!(entity.friendly_name, "BLE Temp/Humidity A4:C1:38:5B:0E:DF");
-        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(entity.state, EntityState::Numeric(23.1));
         assert_eq!(
             entity.get_attribute("temperature"),
             Some(&AttributeValue::Float(23.1))
@@ -516,10 +601,10 @@ mod tests {
     async fn should_teardown_abort_background_tasks() {
         let mut integration = BleIntegration::new(BleConfig::default());
         integration.scan_handle = Some(tokio::spawn(async {
-            tokio::time::sleep(Duration::from_secs(3600)).await;
+            tokio::time::sleep(Duration::from_hours(1)).await;
         }));
         integration.subscriber_handle = Some(tokio::spawn(async {
-            tokio::time::sleep(Duration::from_secs(3600)).await;
+            tokio::time::sleep(Duration::from_hours(1)).await;
         }));
         assert!(integration.scan_handle.is_some());
         assert!(integration.subscriber_handle.is_some());
@@ -535,7 +620,7 @@ mod tests {
         let entity_id = EntityId::new();
         ctx.insert_entity(entity_id, "C4:7C:8D:6A:12:34");
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         ctx.send(Event::new(
             EventType::StateChanged,
@@ -558,7 +643,7 @@ mod tests {
     async fn should_ignore_service_call_for_unknown_entity() {
         let ctx = BroadcastContext::new();
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         ctx.send(Event::new(
             EventType::ServiceCallRequested,
@@ -581,7 +666,7 @@ mod tests {
     async fn should_ignore_service_call_without_entity_id() {
         let ctx = BroadcastContext::new();
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         ctx.send(Event::new(
             EventType::ServiceCallRequested,
@@ -606,7 +691,7 @@ mod tests {
         let entity_id = EntityId::new();
         ctx.insert_entity(entity_id, "C4:7C:8D:6A:12:34");
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         ctx.send(Event::new(
             EventType::ServiceCallRequested,
@@ -631,7 +716,7 @@ mod tests {
         let entity_id = EntityId::new();
         ctx.insert_entity(entity_id, "C4:7C:8D:6A:12:34");
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         // Yield to let the subscriber task start and call subscribe()/recv()
         tokio::task::yield_now().await;
@@ -659,6 +744,46 @@ mod tests {
         handle.abort();
     }
 
+    #[tokio::test]
+    async fn should_publish_failure_and_poll_status_when_refresh_fails() {
+        let ctx = BroadcastContext::new();
+        let entity_id = EntityId::new();
+        ctx.insert_entity(entity_id, "C4:7C:8D:6A:12:34");
+
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
+
+        tokio::task::yield_now().await;
+
+        ctx.send(Event::new(
+            EventType::ServiceCallRequested,
+            Some(entity_id),
+            serde_json::json!({ "service": "refresh" }),
+        ));
+
+        // Same as blink: fails immediately without an adapter, or after the
+        // 3-second lookup scan when the peripheral is not around.
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let published = ctx
+            .published
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallFailed);
+        assert_eq!(published[0].data["service"], "refresh");
+
+        let upserted = ctx
+            .upserted
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        assert_eq!(upserted.len(), 1);
+        assert_eq!(upserted[0].entity_id, "sensor.miflora_c47c8d6a1234_poll");
+        assert_eq!(upserted[0].state, EntityState::Off);
+        assert!(upserted[0].get_attribute("last_error").is_some());
+
+        handle.abort();
+    }
+
     /// Context that subscribes from an external sender (not owned by self).
     ///
     /// This allows the test to close the channel externally, causing the
@@ -716,7 +841,7 @@ mod tests {
         let (tx, rx) = broadcast::channel::<Event>(16);
         let ctx = ExternalSenderContext::new(rx);
 
-        let handle = tokio::spawn(run_event_subscriber(ctx));
+        let handle = tokio::spawn(run_event_subscriber(ctx, TEST_CONNECT_TIMEOUT));
 
         // Drop the only sender so the receiver gets Closed
         drop(tx);
@@ -737,14 +862,18 @@ mod tests {
             device_id: DeviceId::new(),
             friendly_name: "No-MAC entity".into(),
             state: EntityState::On,
+            device_class: None,
+            unit_of_measurement: None,
             attributes: HashMap::new(),
+            attribute_meta: HashMap::new(),
             mac_address: None,
             last_changed: minihub_domain::time::now(),
             last_updated: minihub_domain::time::now(),
+            version: 1,
         };
         ctx.entities.lock().unwrap().insert(entity_id, entity);
 
-        let handle = tokio::spawn(run_event_subscriber(ctx.clone()));
+        let handle = tokio::spawn(run_event_subscriber(ctx.clone(), TEST_CONNECT_TIMEOUT));
 
         tokio::task::yield_now().await;
 
@@ -1,4 +1,5 @@
-//! Shared BLE utilities — service UUIDs and MAC address formatting.
+//! Shared BLE utilities — service UUIDs, company identifiers and MAC
+//! address formatting.
 
 /// Supported BLE service UUIDs used by this adapter.
 pub struct ServiceUuid;
@@ -11,8 +12,16 @@ impl ServiceUuid {
     /// Xiaomi Mi Flora (HHCCJCY01) service UUID (`0xFE95`).
     pub const MIFLORA: uuid::Uuid =
         uuid::Uuid::from_u128(0x0000_FE95_0000_1000_8000_0080_5F9B_34FB);
+
+    /// Google Eddystone beacon service UUID (`0xFEAA`).
+    pub const EDDYSTONE: uuid::Uuid =
+        uuid::Uuid::from_u128(0x0000_FEAA_0000_1000_8000_0080_5F9B_34FB);
 }
 
+/// Bluetooth SIG company identifier of Apple, under which iBeacon frames are
+/// advertised as manufacturer data.
+pub const APPLE_COMPANY_ID: u16 = 0x004C;
+
 /// Format a 6-byte MAC as a colon-separated hex string (e.g. `"A4:C1:38:5B:0E:DF"`).
 #[must_use]
 pub fn format_mac(mac: [u8; 6]) -> String {
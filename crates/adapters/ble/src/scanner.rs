@@ -15,17 +15,23 @@ use tokio_stream::StreamExt as _;
 use minihub_app::ports::integration::IntegrationContext;
 use minihub_domain::event::{Event, EventType};
 
-use crate::devices::{BleDeviceHandler, Lywsd03mmcHandler, MifloraHandler};
+use minihub_app::ports::integration::DiscoveredDevice;
+
+use crate::devices::{
+    BeaconHandler, BleDeviceHandler, DeviceHandlers, Lywsd03mmcHandler, MifloraHandler,
+};
 use crate::error::BleError;
 use crate::parser::ServiceUuid;
 
 /// BLE scanner that discovers sensors via passive advertisements and,
-/// optionally, reads Mi Flora plant sensors via active GATT connections.
+/// optionally, reads Mi Flora plant sensors and stock LYWSD03MMC sensors
+/// via active GATT connections and tracks presence beacons.
 ///
 /// Each received advertisement is immediately persisted via the
 /// [`IntegrationContext`] — there is no batching or post-scan persistence step.
-/// After each passive scan, if Mi Flora is enabled, the scanner connects to
-/// discovered Mi Flora peripherals to read sensor data.
+/// After each passive scan, the scanner connects to the discovered Mi Flora
+/// and stock LYWSD03MMC peripherals, when enabled, to read sensor data, then
+/// marks the beacons that went silent as away.
 pub struct BleScanner<C> {
     context: C,
     manager: Manager,
@@ -34,6 +40,7 @@ pub struct BleScanner<C> {
     interval: Duration,
     lywsd: Lywsd03mmcHandler,
     miflora: Option<MifloraHandler>,
+    beacons: Option<BeaconHandler>,
 }
 
 impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
@@ -44,9 +51,13 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
         central: Adapter,
         scan_duration: Duration,
         interval: Duration,
-        lywsd: Lywsd03mmcHandler,
-        miflora: Option<MifloraHandler>,
+        handlers: DeviceHandlers,
     ) -> JoinHandle<()> {
+        let DeviceHandlers {
+            lywsd,
+            miflora,
+            beacons,
+        } = handlers;
         let scanner = Self {
             context,
             manager,
@@ -55,6 +66,7 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
             interval,
             lywsd,
             miflora,
+            beacons,
         };
 
         tokio::spawn(scanner.run())
@@ -98,6 +110,14 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
             match tokio::time::timeout(remaining, events.next()).await {
                 Ok(Some(CentralEvent::ServiceDataAdvertisement { id, service_data })) => {
                     for (uuid, data) in &service_data {
+                        if *uuid == ServiceUuid::EDDYSTONE {
+                            if let Some(ref beacons) = self.beacons {
+                                self.persist_beacon(beacons.try_parse_advertisement(*uuid, data))
+                                    .await;
+                            }
+                            continue;
+                        }
+
                         let dd = match self.lywsd.try_parse_advertisement(*uuid, data) {
                             Ok(Some(dd)) => dd,
                             Ok(None) => continue,
@@ -133,6 +153,17 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
                         }
                     }
                 }
+                Ok(Some(CentralEvent::ManufacturerDataAdvertisement {
+                    manufacturer_data, ..
+                })) => {
+                    let Some(ref beacons) = self.beacons else {
+                        continue;
+                    };
+                    for (company_id, data) in &manufacturer_data {
+                        self.persist_beacon(beacons.try_parse_manufacturer_data(*company_id, data))
+                            .await;
+                    }
+                }
                 Ok(Some(CentralEvent::DeviceDiscovered(id))) => {
                     if let Ok(peripheral) = central.peripheral(&id).await
                         && let Ok(Some(props)) = peripheral.properties().await
@@ -161,7 +192,19 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
 
         central.stop_scan().await?;
 
-        // Post-scan active phase: GATT-based device handlers.
+        self.process_after_scan(central).await;
+
+        Ok(())
+    }
+
+    /// Post-scan active phase: GATT-based device handlers, then beacon
+    /// timeouts.
+    async fn process_after_scan(&self, central: &Adapter) {
+        for dd in self.lywsd.process_after_scan(central).await {
+            if let Err(err) = self.context.persist_discovered(dd).await {
+                tracing::warn!(%err, handler = self.lywsd.name(), "failed to persist discovery");
+            }
+        }
         if let Some(ref miflora) = self.miflora {
             for dd in miflora.process_after_scan(central).await {
                 if let Err(err) = self.context.persist_discovered(dd).await {
@@ -169,8 +212,27 @@ impl<C: IntegrationContext + Clone + 'static> BleScanner<C> {
                 }
             }
         }
+        if let Some(ref beacons) = self.beacons {
+            for dd in beacons.process_after_scan(central).await {
+                if let Err(err) = self.context.persist_discovered(dd).await {
+                    tracing::warn!(%err, handler = beacons.name(), "failed to persist discovery");
+                }
+            }
+        }
+    }
 
-        Ok(())
+    /// Persist the outcome of a beacon advertisement, if the beacon just
+    /// came home.
+    async fn persist_beacon(&self, result: Result<Option<DiscoveredDevice>, BleError>) {
+        match result {
+            Ok(Some(dd)) => {
+                if let Err(err) = self.context.persist_discovered(dd).await {
+                    tracing::warn!(%err, "failed to persist BLE beacon");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => tracing::debug!(%err, handler = "Beacon", "advertisement parse error"),
+        }
     }
 }
 
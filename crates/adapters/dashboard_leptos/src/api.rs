@@ -1,12 +1,26 @@
 //! HTTP API client wrapping `gloo-net` for calls to `/api/*`.
+//!
+//! Every call carries the token of [`crate::auth`], if any, and reports
+//! whether the server could be reached to [`crate::connection`].
 
-use gloo_net::http::{Request, Response};
+use gloo_net::http::{Request, RequestBuilder, Response};
 use minihub_domain::{
-    area::Area, automation::Automation, device::Device, entity::Entity,
-    entity_history::EntityHistory, event::Event,
+    area::Area,
+    automation::Automation,
+    dashboard::DashboardConfig,
+    device::{Device, DeviceWithStatus},
+    energy::{EnergyPeriod, EnergyReport},
+    entity::Entity,
+    entity_history::{EntityHistory, HistorySeries},
+    event::Event,
+    id::{AreaId, DeviceId, PendingDeviceId},
+    input_helper::InputHelper,
+    pending_device::{Adoption, PendingDevice},
 };
 use serde::Deserialize;
 
+use crate::{auth, connection};
+
 /// Error returned by API client methods.
 #[derive(Debug, Clone)]
 pub struct ApiError {
@@ -21,73 +35,131 @@ impl std::fmt::Display for ApiError {
 
 impl From<gloo_net::Error> for ApiError {
     fn from(err: gloo_net::Error) -> Self {
+        // `fetch` rejects only when the request could not be sent at all.
+        if matches!(err, gloo_net::Error::JsError(_)) {
+            connection::report_offline();
+        }
         Self {
             message: err.to_string(),
         }
     }
 }
 
-/// JSON error body returned by the server on non-2xx responses.
+/// JSON error envelope returned by the server on non-2xx responses.
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: ErrorBody,
+}
+
+/// Error details inside [`ErrorEnvelope`].
 #[derive(Deserialize)]
 struct ErrorBody {
-    error: String,
+    message: String,
+}
+
+/// `request` with the stored token, if any.
+fn authorized(request: RequestBuilder) -> RequestBuilder {
+    match auth::token() {
+        Some(token) => request.header("Authorization", &format!("Bearer {token}")),
+        None => request,
+    }
+}
+
+fn get(url: &str) -> RequestBuilder {
+    authorized(Request::get(url))
+}
+
+fn post(url: &str) -> RequestBuilder {
+    authorized(Request::post(url))
+}
+
+fn put(url: &str) -> RequestBuilder {
+    authorized(Request::put(url))
+}
+
+fn delete(url: &str) -> RequestBuilder {
+    authorized(Request::delete(url))
 }
 
 /// Check the HTTP response status and extract an error if non-2xx.
+///
+/// Gateway errors mean a reverse proxy answered instead of the server;
+/// `401` means the server wants a token.
 async fn check_response(resp: Response) -> Result<Response, ApiError> {
+    if matches!(resp.status(), 502 | 504) {
+        connection::report_offline();
+    } else {
+        connection::report_online();
+    }
+    if resp.status() == 401 {
+        auth::report_unauthorized();
+    }
     if resp.ok() {
         return Ok(resp);
     }
-    let message = match resp.json::<ErrorBody>().await {
-        Ok(body) => body.error,
+    let message = match resp.json::<ErrorEnvelope>().await {
+        Ok(envelope) => envelope.error.message,
         Err(_) => format!("HTTP {}", resp.status()),
     };
     Err(ApiError { message })
 }
 
 /// Percent-encode a query parameter value (handles `+`, `&`, `=`, spaces, etc.).
-fn encode_query_value(value: &str) -> String {
+pub(crate) fn encode_query_value(value: &str) -> String {
     value
         .replace('%', "%25")
         .replace('+', "%2B")
         .replace('&', "%26")
         .replace('=', "%3D")
         .replace(' ', "%20")
+        .replace('#', "%23")
 }
 
 /// Fetch all entities from the API.
 pub async fn fetch_entities() -> Result<Vec<Entity>, ApiError> {
-    let resp = check_response(Request::get("/api/entities").send().await?).await?;
+    let resp = check_response(get("/api/entities").send().await?).await?;
     let entities: Vec<Entity> = resp.json().await?;
     Ok(entities)
 }
 
 /// Fetch all devices from the API.
-pub async fn fetch_devices() -> Result<Vec<Device>, ApiError> {
-    let resp = check_response(Request::get("/api/devices").send().await?).await?;
-    let devices: Vec<Device> = resp.json().await?;
+pub async fn fetch_devices() -> Result<Vec<DeviceWithStatus>, ApiError> {
+    let resp = check_response(get("/api/devices").send().await?).await?;
+    let devices: Vec<DeviceWithStatus> = resp.json().await?;
     Ok(devices)
 }
 
 /// Fetch all areas from the API.
 pub async fn fetch_areas() -> Result<Vec<Area>, ApiError> {
-    let resp = check_response(Request::get("/api/areas").send().await?).await?;
+    let resp = check_response(get("/api/areas").send().await?).await?;
     let areas: Vec<Area> = resp.json().await?;
     Ok(areas)
 }
 
-/// Fetch a single device by ID from the API.
-pub async fn fetch_device(id: &str) -> Result<Device, ApiError> {
-    let url = format!("/api/devices/{id}");
-    let resp = check_response(Request::get(&url).send().await?).await?;
-    let device: Device = resp.json().await?;
-    Ok(device)
+/// A device with its entities and recent events, as served by
+/// `/api/devices/{id}?include=entities,events`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceDetail {
+    #[serde(flatten)]
+    pub device: DeviceWithStatus,
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+/// Fetch a single device by ID along with its entities and recent events.
+pub async fn fetch_device(id: &str) -> Result<DeviceDetail, ApiError> {
+    let url = format!("/api/devices/{id}?include=entities,events");
+    let resp = check_response(get(&url).send().await?).await?;
+    let detail: DeviceDetail = resp.json().await?;
+    Ok(detail)
 }
 
 /// Fetch a single entity by ID from the API.
 pub async fn fetch_entity(id: &str) -> Result<Entity, ApiError> {
     let url = format!("/api/entities/{id}");
-    let resp = check_response(Request::get(&url).send().await?).await?;
+    let resp = check_response(get(&url).send().await?).await?;
     let entity: Entity = resp.json().await?;
     Ok(entity)
 }
@@ -106,7 +178,7 @@ pub async fn update_entity_state(
 
     let url = format!("/api/entities/{id}/state");
     let resp = check_response(
-        Request::put(&url)
+        put(&url)
             .json(&UpdateStateRequest { state })?
             .send()
             .await?,
@@ -118,14 +190,91 @@ pub async fn update_entity_state(
 
 /// Fetch all events from the API.
 pub async fn fetch_events() -> Result<Vec<Event>, ApiError> {
-    let resp = check_response(Request::get("/api/events").send().await?).await?;
+    let resp = check_response(get("/api/events").send().await?).await?;
     let events: Vec<Event> = resp.json().await?;
     Ok(events)
 }
 
+/// Fetch a single event by ID.
+pub async fn fetch_event(id: &str) -> Result<Event, ApiError> {
+    let url = format!("/api/events/{id}");
+    let resp = check_response(get(&url).send().await?).await?;
+    let event: Event = resp.json().await?;
+    Ok(event)
+}
+
+/// Fetch the causal chain of an event, oldest first.
+pub async fn fetch_event_chain(id: &str) -> Result<Vec<Event>, ApiError> {
+    let url = format!("/api/events/{id}/chain");
+    let resp = check_response(get(&url).send().await?).await?;
+    let events: Vec<Event> = resp.json().await?;
+    Ok(events)
+}
+
+/// Filters of an event listing; empty fields match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    /// `event_type` value, e.g. `state_changed`.
+    pub event_type: String,
+    /// Id of the entity the events are about.
+    pub entity_id: String,
+}
+
+/// A page of events, newest first, with the cursor of the next one.
+#[derive(Debug, Clone)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    pub next_cursor: Option<String>,
+}
+
+/// Fetch a page of events matching `filter`, starting after `cursor`.
+pub async fn fetch_event_page(
+    filter: &EventFilter,
+    cursor: Option<&str>,
+) -> Result<EventPage, ApiError> {
+    let mut params = Vec::new();
+    if !filter.event_type.is_empty() {
+        params.push(("event_type", filter.event_type.as_str()));
+    }
+    if !filter.entity_id.is_empty() {
+        params.push(("entity_id", filter.entity_id.as_str()));
+    }
+    if let Some(cursor) = cursor {
+        params.push(("cursor", cursor));
+    }
+    let resp = check_response(get("/api/events").query(params).send().await?).await?;
+    let next_cursor = resp.headers().get("x-next-cursor");
+    let events: Vec<Event> = resp.json().await?;
+    Ok(EventPage {
+        events,
+        next_cursor,
+    })
+}
+
+/// An integration, whether it is enabled and how its startup went, as
+/// reported by the daemon.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IntegrationSummary {
+    pub name: String,
+    pub enabled: bool,
+    /// `disabled`, `starting`, `running`, `timed_out`, `restarting` or
+    /// `failed`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Fetch the configured integrations from the API.
+pub async fn fetch_integrations() -> Result<Vec<IntegrationSummary>, ApiError> {
+    let resp = check_response(get("/api/integrations").send().await?).await?;
+    let integrations: Vec<IntegrationSummary> = resp.json().await?;
+    Ok(integrations)
+}
+
 /// Fetch all automations from the API.
 pub async fn fetch_automations() -> Result<Vec<Automation>, ApiError> {
-    let resp = check_response(Request::get("/api/automations").send().await?).await?;
+    let resp = check_response(get("/api/automations").send().await?).await?;
     let automations: Vec<Automation> = resp.json().await?;
     Ok(automations)
 }
@@ -133,7 +282,7 @@ pub async fn fetch_automations() -> Result<Vec<Automation>, ApiError> {
 /// Fetch a single automation by ID from the API.
 pub async fn fetch_automation(id: &str) -> Result<Automation, ApiError> {
     let url = format!("/api/automations/{id}");
-    let resp = check_response(Request::get(&url).send().await?).await?;
+    let resp = check_response(get(&url).send().await?).await?;
     let automation: Automation = resp.json().await?;
     Ok(automation)
 }
@@ -159,11 +308,38 @@ pub async fn fetch_entity_history(
         url.push('?');
         url.push_str(&params.join("&"));
     }
-    let resp = check_response(Request::get(&url).send().await?).await?;
+    let resp = check_response(get(&url).send().await?).await?;
     let history: Vec<EntityHistory> = resp.json().await?;
     Ok(history)
 }
 
+/// Fetch the numeric state and attributes of an entity between `from` and
+/// `to` (RFC 3339), downsampled by the server into `buckets` buckets.
+pub async fn fetch_history_series(
+    id: &str,
+    from: &str,
+    to: &str,
+    buckets: u32,
+) -> Result<Vec<HistorySeries>, ApiError> {
+    let url = format!(
+        "/api/entities/{id}/history/series?from={}&to={}&buckets={buckets}",
+        encode_query_value(from),
+        encode_query_value(to),
+    );
+    let resp = check_response(get(&url).send().await?).await?;
+    let series: Vec<HistorySeries> = resp.json().await?;
+    Ok(series)
+}
+
+/// Fetch the consumption of a power or energy sensor, summed per `period`
+/// over the server's default range for it.
+pub async fn fetch_energy(id: &str, period: EnergyPeriod) -> Result<EnergyReport, ApiError> {
+    let url = format!("/api/energy/{id}?period={period}");
+    let resp = check_response(get(&url).send().await?).await?;
+    let report: EnergyReport = resp.json().await?;
+    Ok(report)
+}
+
 /// Call a service on an entity (e.g. "blink").
 ///
 /// `POST /api/entities/{id}/service` — returns 202 with no body on success.
@@ -177,7 +353,7 @@ pub async fn call_entity_service(id: &str, service: &str) -> Result<(), ApiError
 
     let url = format!("/api/entities/{id}/service");
     check_response(
-        Request::post(&url)
+        post(&url)
             .json(&ServiceCallRequest {
                 service: service.to_owned(),
             })?
@@ -188,6 +364,15 @@ pub async fn call_entity_service(id: &str, service: &str) -> Result<(), ApiError
     Ok(())
 }
 
+/// Ask the owning integration for an immediate readout of an entity.
+///
+/// `POST /api/entities/{id}/refresh` — returns 202 with no body on success.
+pub async fn refresh_entity(id: &str) -> Result<(), ApiError> {
+    let url = format!("/api/entities/{id}/refresh");
+    check_response(post(&url).send().await?).await?;
+    Ok(())
+}
+
 /// Update automation (toggle enabled state).
 pub async fn update_automation(automation: Automation) -> Result<Automation, ApiError> {
     use serde::Serialize;
@@ -199,17 +384,21 @@ pub async fn update_automation(automation: Automation) -> Result<Automation, Api
         trigger: minihub_domain::automation::Trigger,
         conditions: Vec<minihub_domain::automation::Condition>,
         actions: Vec<minihub_domain::automation::Action>,
+        execution_mode: minihub_domain::automation::ExecutionMode,
+        version: u32,
     }
 
     let url = format!("/api/automations/{}", automation.id);
     let resp = check_response(
-        Request::put(&url)
+        put(&url)
             .json(&UpdateAutomationRequest {
                 name: automation.name,
                 enabled: automation.enabled,
                 trigger: automation.trigger,
                 conditions: automation.conditions,
                 actions: automation.actions,
+                execution_mode: automation.execution_mode,
+                version: automation.version,
             })?
             .send()
             .await?,
@@ -218,3 +407,307 @@ pub async fn update_automation(automation: Automation) -> Result<Automation, Api
     let updated: Automation = resp.json().await?;
     Ok(updated)
 }
+
+/// Create a new, enabled automation without conditions.
+pub async fn create_automation(
+    name: String,
+    trigger: minihub_domain::automation::Trigger,
+    actions: Vec<minihub_domain::automation::Action>,
+) -> Result<Automation, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct CreateAutomationRequest {
+        name: String,
+        trigger: minihub_domain::automation::Trigger,
+        actions: Vec<minihub_domain::automation::Action>,
+    }
+
+    let resp = check_response(
+        post("/api/automations")
+            .json(&CreateAutomationRequest {
+                name,
+                trigger,
+                actions,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let created: Automation = resp.json().await?;
+    Ok(created)
+}
+
+/// Create a device, e.g. to hold manually-fed entities.
+pub async fn create_device(
+    name: String,
+    integration: String,
+    unique_id: String,
+    area_id: Option<AreaId>,
+) -> Result<Device, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct CreateDeviceRequest {
+        name: String,
+        integration: String,
+        unique_id: String,
+        area_id: Option<AreaId>,
+    }
+
+    let resp = check_response(
+        post("/api/devices")
+            .json(&CreateDeviceRequest {
+                name,
+                integration,
+                unique_id,
+                area_id,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let created: Device = resp.json().await?;
+    Ok(created)
+}
+
+/// Create an entity attached to `device_id`.
+pub async fn create_entity(
+    device_id: DeviceId,
+    entity_id: String,
+    friendly_name: String,
+) -> Result<Entity, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct CreateEntityRequest {
+        device_id: DeviceId,
+        entity_id: String,
+        friendly_name: String,
+    }
+
+    let resp = check_response(
+        post("/api/entities")
+            .json(&CreateEntityRequest {
+                device_id,
+                entity_id,
+                friendly_name,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let created: Entity = resp.json().await?;
+    Ok(created)
+}
+
+/// Outcome of a dry-run validation, listing every invalid field.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<FieldIssue>,
+}
+
+/// A problem with one field, e.g. `actions[1]`, of a validated payload.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldIssue {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Validate `automation` with `POST /api/automations/validate`, without
+/// saving it.
+pub async fn validate_automation(automation: Automation) -> Result<ValidationReport, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ValidateAutomationRequest {
+        name: String,
+        enabled: bool,
+        trigger: minihub_domain::automation::Trigger,
+        conditions: Vec<minihub_domain::automation::Condition>,
+        actions: Vec<minihub_domain::automation::Action>,
+        execution_mode: minihub_domain::automation::ExecutionMode,
+    }
+
+    let resp = check_response(
+        post("/api/automations/validate")
+            .json(&ValidateAutomationRequest {
+                name: automation.name,
+                enabled: automation.enabled,
+                trigger: automation.trigger,
+                conditions: automation.conditions,
+                actions: automation.actions,
+                execution_mode: automation.execution_mode,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let report: ValidationReport = resp.json().await?;
+    Ok(report)
+}
+
+/// Validate an entity with `POST /api/entities/validate`, without creating
+/// it.
+pub async fn validate_entity(
+    device_id: DeviceId,
+    entity_id: String,
+    friendly_name: String,
+) -> Result<ValidationReport, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct ValidateEntityRequest {
+        device_id: DeviceId,
+        entity_id: String,
+        friendly_name: String,
+    }
+
+    let resp = check_response(
+        post("/api/entities/validate")
+            .json(&ValidateEntityRequest {
+                device_id,
+                entity_id,
+                friendly_name,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let report: ValidationReport = resp.json().await?;
+    Ok(report)
+}
+
+/// Create an input helper called `name`, starting from `initial` when set.
+pub async fn create_input_helper(
+    name: String,
+    helper: InputHelper,
+    initial: Option<serde_json::Value>,
+) -> Result<Entity, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct CreateInputHelperRequest {
+        name: String,
+        #[serde(flatten)]
+        helper: InputHelper,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        initial: Option<serde_json::Value>,
+    }
+
+    let resp = check_response(
+        post("/api/input_helpers")
+            .json(&CreateInputHelperRequest {
+                name,
+                helper,
+                initial,
+            })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let created: Entity = resp.json().await?;
+    Ok(created)
+}
+
+/// Create a top-level area called `name`.
+pub async fn create_area(name: String) -> Result<Area, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct CreateAreaRequest {
+        name: String,
+    }
+
+    let resp = check_response(
+        post("/api/areas")
+            .json(&CreateAreaRequest { name })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let created: Area = resp.json().await?;
+    Ok(created)
+}
+
+/// Rename the area `id` via PUT /api/areas/{id}.
+pub async fn rename_area(id: AreaId, name: String) -> Result<Area, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct UpdateAreaRequest {
+        name: String,
+    }
+
+    let url = format!("/api/areas/{id}");
+    let resp = check_response(put(&url).json(&UpdateAreaRequest { name })?.send().await?).await?;
+    let updated: Area = resp.json().await?;
+    Ok(updated)
+}
+
+/// Delete the area `id`.
+pub async fn delete_area(id: AreaId) -> Result<(), ApiError> {
+    let url = format!("/api/areas/{id}");
+    check_response(delete(&url).send().await?).await?;
+    Ok(())
+}
+
+/// Move the device `id` into `area_id`, or out of any area when `None`.
+pub async fn assign_device_area(
+    id: DeviceId,
+    area_id: Option<AreaId>,
+) -> Result<DeviceWithStatus, ApiError> {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct AssignAreaRequest {
+        area_id: Option<AreaId>,
+    }
+
+    let url = format!("/api/devices/{id}/area");
+    let resp = check_response(
+        put(&url)
+            .json(&AssignAreaRequest { area_id })?
+            .send()
+            .await?,
+    )
+    .await?;
+    let device: DeviceWithStatus = resp.json().await?;
+    Ok(device)
+}
+
+/// Fetch the detected devices waiting to be adopted.
+pub async fn fetch_pending_devices() -> Result<Vec<PendingDevice>, ApiError> {
+    let resp = check_response(get("/api/discovery/pending").send().await?).await?;
+    let pending: Vec<PendingDevice> = resp.json().await?;
+    Ok(pending)
+}
+
+/// Register the pending device `id` as a regular device.
+pub async fn adopt_pending_device(
+    id: PendingDeviceId,
+    adoption: Adoption,
+) -> Result<Device, ApiError> {
+    let url = format!("/api/discovery/pending/{id}/adopt");
+    let resp = check_response(post(&url).json(&adoption)?.send().await?).await?;
+    let device: Device = resp.json().await?;
+    Ok(device)
+}
+
+/// Fetch the cards pinned to the home page.
+pub async fn fetch_dashboard_config() -> Result<DashboardConfig, ApiError> {
+    let resp = check_response(get("/api/dashboard_config").send().await?).await?;
+    let config: DashboardConfig = resp.json().await?;
+    Ok(config)
+}
+
+/// Replace the cards pinned to the home page, in display order.
+pub async fn update_dashboard_config(
+    config: &DashboardConfig,
+) -> Result<DashboardConfig, ApiError> {
+    let resp = check_response(put("/api/dashboard_config").json(config)?.send().await?).await?;
+    let updated: DashboardConfig = resp.json().await?;
+    Ok(updated)
+}
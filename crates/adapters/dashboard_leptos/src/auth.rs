@@ -0,0 +1,55 @@
+//! API token of the dashboard, for servers configured with tokens.
+//!
+//! The token is kept in `localStorage` and sent with every API call as
+//! `Authorization: Bearer <token>`; the SSE stream, which cannot send
+//! headers, passes it as the `access_token` query parameter instead. Once
+//! the server answers `401`, the app asks for a token.
+
+use std::sync::OnceLock;
+
+use leptos::prelude::*;
+
+/// Key used to persist the token in `localStorage`.
+const STORAGE_KEY: &str = "minihub-token";
+
+/// Whether the server asked for a token, reachable from the API client
+/// which has no reactive owner.
+fn required_signal() -> &'static ArcRwSignal<bool> {
+    static REQUIRED: OnceLock<ArcRwSignal<bool>> = OnceLock::new();
+    REQUIRED.get_or_init(|| ArcRwSignal::new(false))
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+/// The stored token, if any.
+pub fn token() -> Option<String> {
+    storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .filter(|token| !token.is_empty())
+}
+
+/// Store `token` and reload the app, so every page and the SSE stream use
+/// it.
+pub fn save_token(token: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(STORAGE_KEY, token.trim());
+    }
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().reload();
+    }
+}
+
+/// Whether the server rejected the stored token, or its absence, tracked.
+pub fn is_required() -> bool {
+    required_signal().get()
+}
+
+/// Record that the server answered `401`.
+pub fn report_unauthorized() {
+    let required = required_signal();
+    if !required.get_untracked() {
+        required.set(true);
+    }
+}
@@ -0,0 +1,354 @@
+//! Editable board of areas: one column per area plus an "Unassigned" one,
+//! with devices dragged between them.
+//!
+//! Entities belong to the area of their device, so dragging an entity moves
+//! its whole device.
+
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::components::A;
+use minihub_domain::area::Area;
+use minihub_domain::device::DeviceWithStatus;
+use minihub_domain::entity::Entity;
+use minihub_domain::id::{AreaId, DeviceId};
+
+use crate::api;
+use crate::components::use_toasts;
+
+/// Devices placed in `area_id`, or in no area when `None`, sorted by name.
+fn devices_in(devices: &[DeviceWithStatus], area_id: Option<AreaId>) -> Vec<DeviceWithStatus> {
+    let mut found: Vec<_> = devices
+        .iter()
+        .filter(|item| item.device.area_id == area_id)
+        .cloned()
+        .collect();
+    found.sort_by(|a, b| a.device.name.cmp(&b.device.name));
+    found
+}
+
+/// Entities attached to `device_id`.
+fn entities_of(entities: &[Entity], device_id: DeviceId) -> Vec<Entity> {
+    entities
+        .iter()
+        .filter(|entity| entity.device_id == device_id)
+        .cloned()
+        .collect()
+}
+
+/// Drag-and-drop board assigning devices, and so their entities, to areas.
+#[component]
+pub fn AreaBoard(
+    /// Every area, one column each.
+    areas: Vec<Area>,
+    /// Every device, placed in the column of its area.
+    devices: Vec<DeviceWithStatus>,
+    /// Every entity, listed under its device.
+    entities: Vec<Entity>,
+    /// Called after an area or an assignment changed, to reload the data.
+    #[prop(into)]
+    on_change: Callback<()>,
+) -> impl IntoView {
+    let toasts = use_toasts();
+    let dragged = RwSignal::new(None::<DeviceId>);
+
+    let on_assign = Callback::new(move |(device_id, area_id): (DeviceId, Option<AreaId>)| {
+        let toasts = toasts.clone();
+        spawn_local(async move {
+            match api::assign_device_area(device_id, area_id).await {
+                Ok(_) => on_change.run(()),
+                Err(err) => toasts.push(format!("Failed to move device: {err}")),
+            }
+        });
+    });
+
+    let columns = std::iter::once(None)
+        .chain(areas.iter().cloned().map(Some))
+        .map(|area| {
+            let area_id = area.as_ref().map(|area| area.id);
+            view! {
+                <AreaColumn
+                    area=area
+                    areas=areas.clone()
+                    devices=devices_in(&devices, area_id)
+                    entities=entities.clone()
+                    dragged=dragged
+                    on_assign=on_assign
+                    on_change=on_change
+                />
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! { <div class="area-board">{columns}</div> }
+}
+
+/// A drop zone listing the devices of one area, or the unassigned ones.
+#[component]
+fn AreaColumn(
+    /// The area of this column, `None` for unassigned devices.
+    area: Option<Area>,
+    /// Every area, offered as move targets.
+    areas: Vec<Area>,
+    /// Devices placed in this column.
+    devices: Vec<DeviceWithStatus>,
+    /// Every entity, filtered per device.
+    entities: Vec<Entity>,
+    /// Device being dragged, if any.
+    dragged: RwSignal<Option<DeviceId>>,
+    /// Move a device to an area.
+    on_assign: Callback<(DeviceId, Option<AreaId>)>,
+    /// Called after the area was renamed or deleted.
+    on_change: Callback<()>,
+) -> impl IntoView {
+    let area_id = area.as_ref().map(|area| area.id);
+    let (is_over, set_is_over) = signal(false);
+
+    let on_drop = move |ev: leptos::ev::DragEvent| {
+        ev.prevent_default();
+        set_is_over.set(false);
+        if let Some(device_id) = dragged.get_untracked() {
+            dragged.set(None);
+            on_assign.run((device_id, area_id));
+        }
+    };
+
+    let header = match area {
+        Some(area) => view! { <AreaHeader area=area on_change=on_change/> }.into_any(),
+        None => view! { <h2>"Unassigned"</h2> }.into_any(),
+    };
+
+    let cards = if devices.is_empty() {
+        view! { <p class="hint">"Drop devices here"</p> }.into_any()
+    } else {
+        devices
+            .into_iter()
+            .map(|item| {
+                let device_entities = entities_of(&entities, item.device.id);
+                view! {
+                    <DeviceCard
+                        item=item
+                        entities=device_entities
+                        areas=areas.clone()
+                        dragged=dragged
+                        on_assign=on_assign
+                    />
+                }
+            })
+            .collect::<Vec<_>>()
+            .into_any()
+    };
+
+    view! {
+        <section
+            class="card area-column"
+            class:drop-target=move || is_over.get()
+            on:dragover=move |ev| {
+                ev.prevent_default();
+                set_is_over.set(true);
+            }
+            on:dragleave=move |_| set_is_over.set(false)
+            on:drop=on_drop
+        >
+            {header}
+            {cards}
+        </section>
+    }
+}
+
+/// Name of an area, with rename and delete controls.
+#[component]
+fn AreaHeader(
+    /// The area to edit.
+    area: Area,
+    /// Called after the area was renamed or deleted.
+    on_change: Callback<()>,
+) -> impl IntoView {
+    let toasts = use_toasts();
+    let (editing, set_editing) = signal(false);
+    let (name, set_name) = signal(area.name.clone());
+    let area_id = area.id;
+
+    let rename_toasts = toasts.clone();
+    let rename = move |_| {
+        let toasts = rename_toasts.clone();
+        let new_name = name.get_untracked().trim().to_string();
+        spawn_local(async move {
+            match api::rename_area(area_id, new_name).await {
+                Ok(_) => on_change.run(()),
+                Err(err) => toasts.push(format!("Failed to rename area: {err}")),
+            }
+        });
+        set_editing.set(false);
+    };
+
+    let delete = move |_| {
+        let toasts = toasts.clone();
+        spawn_local(async move {
+            match api::delete_area(area_id).await {
+                Ok(()) => on_change.run(()),
+                Err(err) => toasts.push(format!("Failed to delete area: {err}")),
+            }
+        });
+    };
+
+    let original = area.name;
+    view! {
+        <div class="area-header">
+            {move || if editing.get() {
+                view! {
+                    <input
+                        type="text"
+                        prop:value=move || name.get()
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                    />
+                    <button
+                        class="btn btn-primary btn-sm"
+                        disabled=move || name.get().trim().is_empty()
+                        on:click=rename.clone()
+                    >
+                        "Save"
+                    </button>
+                    <button class="btn btn-secondary btn-sm" on:click=move |_| set_editing.set(false)>
+                        "Cancel"
+                    </button>
+                }.into_any()
+            } else {
+                view! {
+                    <h2>{original.clone()}</h2>
+                    <button class="btn btn-secondary btn-sm" on:click=move |_| set_editing.set(true)>
+                        "Rename"
+                    </button>
+                    <button class="btn btn-secondary btn-sm" on:click=delete.clone()>
+                        "Delete"
+                    </button>
+                }.into_any()
+            }}
+        </div>
+    }
+}
+
+/// A draggable device with its entities and a keyboard-friendly area picker.
+#[component]
+fn DeviceCard(
+    /// The device to display.
+    item: DeviceWithStatus,
+    /// Entities of the device.
+    entities: Vec<Entity>,
+    /// Every area, offered as move targets.
+    areas: Vec<Area>,
+    /// Device being dragged, if any.
+    dragged: RwSignal<Option<DeviceId>>,
+    /// Move a device to an area.
+    on_assign: Callback<(DeviceId, Option<AreaId>)>,
+) -> impl IntoView {
+    let device = item.device;
+    let device_id = device.id;
+    let current = device.area_id.map(|id| id.to_string()).unwrap_or_default();
+
+    let options = areas
+        .into_iter()
+        .map(|area| {
+            let id = area.id.to_string();
+            let is_selected = id == current;
+            view! { <option value=id selected=is_selected>{area.name}</option> }
+        })
+        .collect::<Vec<_>>();
+
+    let entity_items = entities
+        .into_iter()
+        .map(|entity| {
+            view! {
+                <li draggable="true" on:dragstart=move |_| dragged.set(Some(device_id))>
+                    <A href=format!("/entities/{}", entity.id)>{entity.friendly_name}</A>
+                </li>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <div
+            class="area-device"
+            draggable="true"
+            on:dragstart=move |_| dragged.set(Some(device_id))
+            on:dragend=move |_| dragged.set(None)
+        >
+            <A href=format!("/devices/{device_id}")>{device.name}</A>
+            <select on:change=move |ev| {
+                let area_id = AreaId::from_str(&event_target_value(&ev)).ok();
+                on_assign.run((device_id, area_id));
+            }>
+                <option value="" selected=current.is_empty()>"No area"</option>
+                {options}
+            </select>
+            <ul>{entity_items}</ul>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::device::{Device, DeviceStatus};
+
+    use super::*;
+
+    fn device(name: &str, area_id: Option<AreaId>) -> DeviceWithStatus {
+        let mut builder = Device::builder()
+            .name(name)
+            .integration("test")
+            .unique_id(name);
+        if let Some(area_id) = area_id {
+            builder = builder.area_id(area_id);
+        }
+        DeviceWithStatus {
+            device: builder.build().unwrap(),
+            status: DeviceStatus::Unknown,
+        }
+    }
+
+    #[test]
+    fn should_group_devices_by_area_sorted_by_name() {
+        let kitchen = AreaId::new();
+        let devices = vec![
+            device("Oven", Some(kitchen)),
+            device("Hallway sensor", None),
+            device("Fridge", Some(kitchen)),
+        ];
+
+        let names = |found: Vec<DeviceWithStatus>| {
+            found
+                .into_iter()
+                .map(|item| item.device.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(devices_in(&devices, Some(kitchen))),
+            ["Fridge", "Oven"]
+        );
+        assert_eq!(names(devices_in(&devices, None)), ["Hallway sensor"]);
+        assert!(devices_in(&devices, Some(AreaId::new())).is_empty());
+    }
+
+    #[test]
+    fn should_list_entities_of_device() {
+        let oven = device("Oven", None).device;
+        let fridge = device("Fridge", None).device;
+        let entity = |device: &Device, entity_id: &str| {
+            Entity::builder()
+                .device_id(device.id)
+                .entity_id(entity_id)
+                .friendly_name(entity_id)
+                .build()
+                .unwrap()
+        };
+        let entities = vec![
+            entity(&oven, "sensor.oven_temperature"),
+            entity(&fridge, "sensor.fridge_temperature"),
+        ];
+
+        let found = entities_of(&entities, oven.id);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].entity_id, "sensor.oven_temperature");
+    }
+}
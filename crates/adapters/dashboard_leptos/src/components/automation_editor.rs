@@ -0,0 +1,1067 @@
+//! Form editing the trigger, conditions and actions of an automation.
+
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use minihub_domain::automation::{Action, Automation, Condition, Trigger};
+use minihub_domain::device::DeviceWithStatus;
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::home_mode::HomeMode;
+use minihub_domain::id::{DeviceId, EntityId};
+use minihub_domain::notification::Severity;
+use minihub_domain::sun::SunEvent;
+
+use crate::api;
+use crate::components::Loading;
+
+/// Trigger types offered by the type picker, with their labels.
+const TRIGGER_KINDS: &[(&str, &str)] = &[
+    ("state_changed", "An entity changes state"),
+    ("value_changed", "A helper value changes"),
+    ("device_unavailable", "A device goes offline"),
+    ("device_back_online", "A device comes back online"),
+    ("time_pattern", "A time pattern matches"),
+    ("webhook", "A webhook is called"),
+    ("sun", "The sun rises or sets"),
+    ("manual", "Only when run manually"),
+];
+
+/// Condition types offered by the type picker, with their labels.
+const CONDITION_KINDS: &[(&str, &str)] = &[
+    ("state_is", "An entity is in a state"),
+    ("time_range", "The time is between"),
+    ("home_mode_is", "The home mode is"),
+    ("value_is", "A helper value is"),
+    ("expression", "An expression holds"),
+    ("sun_above_horizon", "The sun is up"),
+    ("sun_below_horizon", "The sun is down"),
+];
+
+/// Action types offered by the type picker, with their labels.
+const ACTION_KINDS: &[(&str, &str)] = &[
+    ("call_service", "Call a service"),
+    ("delay", "Wait"),
+    ("wait_for_state", "Wait for a state"),
+    ("notify", "Send a notification"),
+];
+
+/// Entity states offered by the state pickers.
+const STATES: &[&str] = &["on", "off", "unknown", "unavailable"];
+
+/// Notification severities offered by the severity picker.
+const SEVERITIES: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Critical];
+
+/// Targets used when switching an item to a type that needs an entity or a
+/// device: the first known one, so the pickers start on a real choice.
+#[derive(Debug, Clone, Copy)]
+struct Defaults {
+    entity_id: EntityId,
+    device_id: DeviceId,
+}
+
+impl Defaults {
+    fn new(entities: &[Entity], devices: &[DeviceWithStatus]) -> Self {
+        Self {
+            entity_id: entities
+                .first()
+                .map_or_else(EntityId::new, |entity| entity.id),
+            device_id: devices
+                .first()
+                .map_or_else(DeviceId::new, |device| device.device.id),
+        }
+    }
+}
+
+fn trigger_kind(trigger: &Trigger) -> &'static str {
+    match trigger {
+        Trigger::StateChanged { .. } => "state_changed",
+        Trigger::ValueChanged { .. } => "value_changed",
+        Trigger::DeviceUnavailable { .. } => "device_unavailable",
+        Trigger::DeviceBackOnline { .. } => "device_back_online",
+        Trigger::TimePattern { .. } => "time_pattern",
+        Trigger::Webhook { .. } => "webhook",
+        Trigger::Sun { .. } => "sun",
+        Trigger::Manual => "manual",
+    }
+}
+
+fn condition_kind(condition: &Condition) -> &'static str {
+    match condition {
+        Condition::StateIs { .. } => "state_is",
+        Condition::TimeRange { .. } => "time_range",
+        Condition::HomeModeIs { .. } => "home_mode_is",
+        Condition::ValueIs { .. } => "value_is",
+        Condition::Expression { .. } => "expression",
+        Condition::SunAboveHorizon => "sun_above_horizon",
+        Condition::SunBelowHorizon => "sun_below_horizon",
+    }
+}
+
+fn action_kind(action: &Action) -> &'static str {
+    match action {
+        Action::CallService { .. } => "call_service",
+        Action::Delay { .. } => "delay",
+        Action::Notify { .. } => "notify",
+        Action::WaitForState { .. } => "wait_for_state",
+        Action::If { .. } => "if",
+        Action::Repeat { .. } => "repeat",
+    }
+}
+
+/// A fresh trigger of type `kind`.
+fn default_trigger(kind: &str, defaults: Defaults) -> Trigger {
+    match kind {
+        "device_unavailable" => Trigger::DeviceUnavailable {
+            device_id: defaults.device_id,
+        },
+        "device_back_online" => Trigger::DeviceBackOnline {
+            device_id: defaults.device_id,
+        },
+        "time_pattern" => Trigger::TimePattern {
+            cron: "0 8 * * *".to_string(),
+        },
+        "webhook" => Trigger::Webhook {
+            webhook_id: minihub_domain::webhook::generate_id(),
+        },
+        "sun" => Trigger::Sun {
+            event: SunEvent::Sunset,
+            offset_minutes: 0,
+        },
+        "manual" => Trigger::Manual,
+        "value_changed" => Trigger::ValueChanged {
+            entity_id: defaults.entity_id,
+            to: None,
+        },
+        _ => Trigger::StateChanged {
+            entity_id: defaults.entity_id,
+            from: None,
+            to: Some(EntityState::On),
+        },
+    }
+}
+
+/// A fresh condition of type `kind`.
+fn default_condition(kind: &str, defaults: Defaults) -> Condition {
+    match kind {
+        "time_range" => Condition::TimeRange {
+            after: "08:00".to_string(),
+            before: "22:00".to_string(),
+        },
+        "home_mode_is" => Condition::HomeModeIs {
+            mode: HomeMode::Home,
+        },
+        "value_is" => Condition::ValueIs {
+            entity_id: defaults.entity_id,
+            value: serde_json::Value::from(""),
+        },
+        "expression" => Condition::Expression {
+            expr: "time.hour >= 22".to_string(),
+        },
+        "sun_above_horizon" => Condition::SunAboveHorizon,
+        "sun_below_horizon" => Condition::SunBelowHorizon,
+        _ => Condition::StateIs {
+            entity_id: defaults.entity_id,
+            state: "on".to_string(),
+        },
+    }
+}
+
+/// A fresh action of type `kind`.
+fn default_action(kind: &str, defaults: Defaults) -> Action {
+    match kind {
+        "delay" => Action::Delay { seconds: 5 },
+        "wait_for_state" => Action::WaitForState {
+            entity_id: defaults.entity_id,
+            state: "off".to_string(),
+            timeout_secs: 60,
+        },
+        "notify" => Action::Notify {
+            title: None,
+            message: String::new(),
+            severity: Severity::Info,
+        },
+        _ => Action::CallService {
+            entity_id: defaults.entity_id,
+            service: "turn_on".to_string(),
+            data: serde_json::Value::Null,
+        },
+    }
+}
+
+/// Parse a state picker value; `"any"` means no filter.
+fn parse_state(value: &str) -> Option<EntityState> {
+    match value {
+        "on" => Some(EntityState::On),
+        "off" => Some(EntityState::Off),
+        "unknown" => Some(EntityState::Unknown),
+        "unavailable" => Some(EntityState::Unavailable),
+        _ => None,
+    }
+}
+
+/// Parse a helper value typed in a text field: numbers and booleans as
+/// such, anything else as an option name.
+fn parse_value(text: &str) -> serde_json::Value {
+    let text = text.trim();
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_))) => value,
+        _ => serde_json::Value::from(text),
+    }
+}
+
+/// Text shown in a value field: option names without their quotes.
+fn format_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_severity(value: &str) -> Severity {
+    SEVERITIES
+        .into_iter()
+        .find(|severity| severity.to_string() == value)
+        .unwrap_or_default()
+}
+
+/// Swap the item at `index` with its neighbour above (`up`) or below.
+///
+/// Moving the first item up or the last item down does nothing.
+fn move_item<T>(items: &mut [T], index: usize, up: bool) {
+    let target = if up {
+        index.checked_sub(1)
+    } else {
+        Some(index + 1)
+    };
+    if let Some(target) = target.filter(|target| *target < items.len()) {
+        items.swap(index, target);
+    }
+}
+
+/// Apply `edit` to the item at `index` of `list`.
+fn edit_item<T>(list: RwSignal<Vec<T>>, index: usize, edit: impl FnOnce(&mut T))
+where
+    T: Send + Sync + 'static,
+{
+    list.update(|items| {
+        if let Some(item) = items.get_mut(index) {
+            edit(item);
+        }
+    });
+}
+
+/// Editor for an existing automation, saved with `PUT /api/automations/{id}`.
+///
+/// The automation version is sent along, so saving over a concurrent change
+/// fails with a conflict instead of overwriting it.
+#[component]
+pub fn AutomationEditor(
+    /// The automation being edited.
+    automation: Automation,
+    /// Called with the saved automation.
+    #[prop(into)]
+    on_saved: Callback<Automation>,
+    /// Called when editing is abandoned.
+    #[prop(into)]
+    on_cancel: Callback<()>,
+) -> impl IntoView {
+    let entities = LocalResource::new(api::fetch_entities);
+    let devices = LocalResource::new(api::fetch_devices);
+
+    let name = RwSignal::new(automation.name.clone());
+    let enabled = RwSignal::new(automation.enabled);
+    let trigger = RwSignal::new(automation.trigger.clone());
+    let conditions = RwSignal::new(automation.conditions.clone());
+    let actions = RwSignal::new(automation.actions.clone());
+    let original = StoredValue::new(automation);
+    let (is_saving, set_is_saving) = signal(false);
+    let (error_message, set_error_message) = signal::<Option<String>>(None);
+
+    let edited = move || {
+        let mut updated = original.get_value();
+        updated.name = name.get().trim().to_string();
+        updated.enabled = enabled.get();
+        updated.trigger = trigger.get();
+        updated.conditions = conditions.get();
+        updated.actions = actions.get();
+        updated
+    };
+    // Re-validated on every edit, so problems show up before saving.
+    let report = LocalResource::new(move || api::validate_automation(edited()));
+    let is_invalid = move || {
+        report
+            .read()
+            .as_ref()
+            .and_then(|report| report.as_ref().ok())
+            .is_some_and(|report| !report.valid)
+    };
+
+    let save = move |_| {
+        let updated = untrack(edited);
+
+        set_is_saving.set(true);
+        set_error_message.set(None);
+        spawn_local(async move {
+            match api::update_automation(updated).await {
+                Ok(saved) => on_saved.run(saved),
+                Err(err) => set_error_message.set(Some(err.to_string())),
+            }
+            set_is_saving.set(false);
+        });
+    };
+
+    let form = move || {
+        let (entities, devices) = match (
+            entities.read().as_ref().cloned(),
+            devices.read().as_ref().cloned(),
+        ) {
+            (Some(Ok(entities)), Some(Ok(devices))) => (entities, devices),
+            (Some(Err(err)), _) | (_, Some(Err(err))) => {
+                return view! {
+                    <p class="error">{"Failed to load entities and devices: "} {err.to_string()}</p>
+                }
+                .into_any();
+            }
+            _ => return ().into_any(),
+        };
+        let defaults = Defaults::new(&entities, &devices);
+        let condition_entities = entities.clone();
+        let action_entities = entities.clone();
+
+        view! {
+            <label>
+                "Name"
+                <input
+                    type="text"
+                    prop:value=move || name.get()
+                    on:input=move |ev| name.set(event_target_value(&ev))
+                />
+            </label>
+            <label class="editor-checkbox">
+                <input
+                    type="checkbox"
+                    prop:checked=move || enabled.get()
+                    on:change=move |ev| enabled.set(event_target_checked(&ev))
+                />
+                "Enabled"
+            </label>
+
+            <h3>"Trigger"</h3>
+            {move || trigger_editor(trigger, &entities, &devices, defaults)}
+
+            <h3>"Conditions"</h3>
+            <ol class="editor-list">
+                {move || {
+                    let items = conditions.get();
+                    let count = items.len();
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, condition)| view! {
+                            <li class="editor-row">
+                                {condition_editor(conditions, index, &condition, &condition_entities, defaults)}
+                                {row_buttons(conditions, index, count)}
+                            </li>
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </ol>
+            <button
+                class="btn btn-sm btn-secondary"
+                on:click=move |_| conditions.update(|items| items.push(default_condition("state_is", defaults)))
+            >
+                "+ Add condition"
+            </button>
+
+            <h3>"Actions"</h3>
+            <ol class="editor-list">
+                {move || {
+                    let items = actions.get();
+                    let count = items.len();
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, action)| view! {
+                            <li class="editor-row">
+                                {action_editor(actions, index, &action, &action_entities, defaults)}
+                                {row_buttons(actions, index, count)}
+                            </li>
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </ol>
+            <button
+                class="btn btn-sm btn-secondary"
+                on:click=move |_| actions.update(|items| items.push(default_action("call_service", defaults)))
+            >
+                "+ Add action"
+            </button>
+        }
+        .into_any()
+    };
+
+    view! {
+        <div class="card automation-editor">
+            <Suspense fallback=move || view! { <Loading message="Loading entities\u{2026}"/> }>
+                {form}
+            </Suspense>
+            {move || error_message.get().map(|msg| view! {
+                <p class="error">{msg}</p>
+            })}
+            {move || {
+                let issues = report.read().as_ref()?.as_ref().ok()?.errors.clone();
+                (!issues.is_empty()).then(|| view! {
+                    <ul class="error validation-issues">
+                        {issues
+                            .into_iter()
+                            .map(|issue| view! {
+                                <li><code>{issue.field}</code> {" "} {issue.message}</li>
+                            })
+                            .collect::<Vec<_>>()}
+                    </ul>
+                })
+            }}
+            <div class="wizard-buttons">
+                <button class="btn btn-secondary" on:click=move |_| on_cancel.run(())>
+                    "Cancel"
+                </button>
+                <button
+                    class="btn btn-primary"
+                    disabled=move || {
+                        is_saving.get()
+                            || name.get().trim().is_empty()
+                            || actions.get().is_empty()
+                            || is_invalid()
+                    }
+                    on:click=save
+                >
+                    {move || if is_saving.get() { "Saving\u{2026}" } else { "Save" }}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// Type picker listing `kinds`, with `current` selected.
+fn kind_select(
+    kinds: &'static [(&'static str, &'static str)],
+    current: &'static str,
+    on_pick: impl Fn(String) + 'static,
+) -> impl IntoView {
+    let options = kinds
+        .iter()
+        .map(|(value, label)| {
+            let is_selected = *value == current;
+            view! { <option value=*value selected=is_selected>{*label}</option> }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        <select class="editor-kind" on:change=move |ev| on_pick(event_target_value(&ev))>
+            {options}
+        </select>
+    }
+}
+
+/// Entity picker with `selected` preselected.
+fn entity_picker(
+    entities: &[Entity],
+    selected: EntityId,
+    on_pick: impl Fn(EntityId) + 'static,
+) -> impl IntoView {
+    let known = entities.iter().any(|entity| entity.id == selected);
+    let options = entities
+        .iter()
+        .map(|entity| {
+            let is_selected = entity.id == selected;
+            view! {
+                <option value=entity.id.to_string() selected=is_selected>
+                    {format!("{} ({})", entity.friendly_name, entity.entity_id)}
+                </option>
+            }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        <select on:change=move |ev| {
+            if let Ok(id) = EntityId::from_str(&event_target_value(&ev)) {
+                on_pick(id);
+            }
+        }>
+            <option value="" disabled=true selected=!known>"Choose an entity\u{2026}"</option>
+            {options}
+        </select>
+    }
+}
+
+/// Device picker with `selected` preselected.
+fn device_picker(
+    devices: &[DeviceWithStatus],
+    selected: DeviceId,
+    on_pick: impl Fn(DeviceId) + 'static,
+) -> impl IntoView {
+    let known = devices.iter().any(|device| device.device.id == selected);
+    let options = devices
+        .iter()
+        .map(|device| {
+            let is_selected = device.device.id == selected;
+            view! {
+                <option value=device.device.id.to_string() selected=is_selected>
+                    {device.device.name.clone()}
+                </option>
+            }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        <select on:change=move |ev| {
+            if let Ok(id) = DeviceId::from_str(&event_target_value(&ev)) {
+                on_pick(id);
+            }
+        }>
+            <option value="" disabled=true selected=!known>"Choose a device\u{2026}"</option>
+            {options}
+        </select>
+    }
+}
+
+/// State picker; `any_label` adds a first "no filter" choice.
+fn state_picker(
+    selected: Option<&str>,
+    any_label: Option<&'static str>,
+    on_pick: impl Fn(String) + 'static,
+) -> impl IntoView {
+    let any = any_label.map(|label| {
+        let is_selected = selected.is_none();
+        view! { <option value="any" selected=is_selected>{label}</option> }
+    });
+    let options = STATES
+        .iter()
+        .map(|state| {
+            let is_selected = selected == Some(*state);
+            view! { <option value=*state selected=is_selected>{*state}</option> }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        <select on:change=move |ev| on_pick(event_target_value(&ev))>
+            {any}
+            {options}
+        </select>
+    }
+}
+
+/// Type picker and fields of the automation trigger.
+fn trigger_editor(
+    trigger: RwSignal<Trigger>,
+    entities: &[Entity],
+    devices: &[DeviceWithStatus],
+    defaults: Defaults,
+) -> impl IntoView {
+    let current = trigger.get();
+    let kind = kind_select(TRIGGER_KINDS, trigger_kind(&current), move |kind| {
+        trigger.set(default_trigger(&kind, defaults));
+    });
+    let fields = match current {
+        Trigger::StateChanged {
+            entity_id,
+            from,
+            to,
+        } => {
+            let from = from.map(|state| state.to_string());
+            let to = to.map(|state| state.to_string());
+            view! {
+                {entity_picker(entities, entity_id, move |id| trigger.update(|trigger| {
+                    if let Trigger::StateChanged { entity_id, .. } = trigger {
+                        *entity_id = id;
+                    }
+                }))}
+                <label>
+                    "from"
+                    {state_picker(from.as_deref(), Some("any state"), move |value| trigger.update(|trigger| {
+                        if let Trigger::StateChanged { from, .. } = trigger {
+                            *from = parse_state(&value);
+                        }
+                    }))}
+                </label>
+                <label>
+                    "to"
+                    {state_picker(to.as_deref(), Some("any state"), move |value| trigger.update(|trigger| {
+                        if let Trigger::StateChanged { to, .. } = trigger {
+                            *to = parse_state(&value);
+                        }
+                    }))}
+                </label>
+            }
+            .into_any()
+        }
+        Trigger::DeviceUnavailable { device_id } | Trigger::DeviceBackOnline { device_id } => {
+            device_picker(devices, device_id, move |id| {
+                trigger.update(|trigger| match trigger {
+                    Trigger::DeviceUnavailable { device_id }
+                    | Trigger::DeviceBackOnline { device_id } => *device_id = id,
+                    _ => {}
+                });
+            })
+            .into_any()
+        }
+        Trigger::TimePattern { cron } => view! {
+            <input
+                type="text"
+                placeholder="0 8 * * *"
+                prop:value=cron
+                on:change=move |ev| trigger.set(Trigger::TimePattern { cron: event_target_value(&ev) })
+            />
+        }
+        .into_any(),
+        Trigger::Webhook { webhook_id } => view! {
+            <input
+                type="text"
+                placeholder="at least 16 characters, hard to guess"
+                prop:value=webhook_id
+                on:change=move |ev| trigger.set(Trigger::Webhook { webhook_id: event_target_value(&ev) })
+            />
+            <span class="hint">"POST /api/webhook/<id>"</span>
+        }
+        .into_any(),
+        Trigger::ValueChanged { entity_id, to } => view! {
+            {entity_picker(entities, entity_id, move |id| trigger.update(|trigger| {
+                if let Trigger::ValueChanged { entity_id, .. } = trigger {
+                    *entity_id = id;
+                }
+            }))}
+            <label>
+                "to"
+                <input
+                    type="text"
+                    placeholder="any value"
+                    prop:value=to.as_ref().map(format_value).unwrap_or_default()
+                    on:change=move |ev| {
+                        let text = event_target_value(&ev);
+                        trigger.update(|trigger| {
+                            if let Trigger::ValueChanged { to, .. } = trigger {
+                                *to = Some(text).filter(|text| !text.trim().is_empty()).map(|text| parse_value(&text));
+                            }
+                        });
+                    }
+                />
+            </label>
+        }
+        .into_any(),
+        Trigger::Sun {
+            event,
+            offset_minutes,
+        } => {
+            let options = [SunEvent::Sunrise, SunEvent::Sunset]
+                .into_iter()
+                .map(|option| {
+                    let is_selected = option == event;
+                    view! { <option value=option.as_str() selected=is_selected>{option.as_str()}</option> }
+                })
+                .collect::<Vec<_>>();
+            view! {
+                <select on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    trigger.update(|trigger| {
+                        if let Trigger::Sun { event, .. } = trigger {
+                            *event = if value == "sunrise" { SunEvent::Sunrise } else { SunEvent::Sunset };
+                        }
+                    });
+                }>
+                    {options}
+                </select>
+                <input
+                    type="number"
+                    min="-720"
+                    max="720"
+                    prop:value=offset_minutes.to_string()
+                    on:change=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse::<i32>() {
+                            trigger.update(|trigger| {
+                                if let Trigger::Sun { offset_minutes, .. } = trigger {
+                                    *offset_minutes = value;
+                                }
+                            });
+                        }
+                    }
+                />
+                "minutes after (negative for before)"
+            }
+            .into_any()
+        }
+        Trigger::Manual => ().into_any(),
+    };
+    view! {
+        <div class="editor-row">
+            {kind}
+            {fields}
+        </div>
+    }
+}
+
+/// Type picker and fields of the condition at `index`.
+fn condition_editor(
+    conditions: RwSignal<Vec<Condition>>,
+    index: usize,
+    condition: &Condition,
+    entities: &[Entity],
+    defaults: Defaults,
+) -> impl IntoView {
+    let kind = kind_select(CONDITION_KINDS, condition_kind(condition), move |kind| {
+        edit_item(conditions, index, |condition| {
+            *condition = default_condition(&kind, defaults);
+        });
+    });
+    let fields = match condition.clone() {
+        Condition::StateIs { entity_id, state } => view! {
+            {entity_picker(entities, entity_id, move |id| edit_item(conditions, index, |condition| {
+                if let Condition::StateIs { entity_id, .. } = condition {
+                    *entity_id = id;
+                }
+            }))}
+            {state_picker(Some(&state), None, move |value| edit_item(conditions, index, |condition| {
+                if let Condition::StateIs { state, .. } = condition {
+                    *state = value;
+                }
+            }))}
+        }
+        .into_any(),
+        Condition::TimeRange { after, before } => view! {
+            <input
+                type="time"
+                prop:value=after
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    edit_item(conditions, index, |condition| {
+                        if let Condition::TimeRange { after, .. } = condition {
+                            *after = value;
+                        }
+                    });
+                }
+            />
+            "and"
+            <input
+                type="time"
+                prop:value=before
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    edit_item(conditions, index, |condition| {
+                        if let Condition::TimeRange { before, .. } = condition {
+                            *before = value;
+                        }
+                    });
+                }
+            />
+        }
+        .into_any(),
+        Condition::HomeModeIs { mode } => {
+            let options = HomeMode::ALL
+                .into_iter()
+                .map(|option| {
+                    let is_selected = option == mode;
+                    view! { <option value=option.as_str() selected=is_selected>{option.as_str()}</option> }
+                })
+                .collect::<Vec<_>>();
+            view! {
+                <select on:change=move |ev| {
+                    if let Ok(value) = HomeMode::from_str(&event_target_value(&ev)) {
+                        edit_item(conditions, index, |condition| {
+                            *condition = Condition::HomeModeIs { mode: value };
+                        });
+                    }
+                }>
+                    {options}
+                </select>
+            }
+            .into_any()
+        }
+        Condition::ValueIs { entity_id, value } => view! {
+            {entity_picker(entities, entity_id, move |id| edit_item(conditions, index, |condition| {
+                if let Condition::ValueIs { entity_id, .. } = condition {
+                    *entity_id = id;
+                }
+            }))}
+            <input
+                type="text"
+                placeholder="21.5, movie\u{2026}"
+                prop:value=format_value(&value)
+                on:change=move |ev| {
+                    let text = event_target_value(&ev);
+                    edit_item(conditions, index, |condition| {
+                        if let Condition::ValueIs { value, .. } = condition {
+                            *value = parse_value(&text);
+                        }
+                    });
+                }
+            />
+        }
+        .into_any(),
+        Condition::Expression { expr } => view! {
+            <input
+                type="text"
+                placeholder="states('sensor.kitchen').temperature > 25"
+                prop:value=expr
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    edit_item(conditions, index, |condition| {
+                        *condition = Condition::Expression { expr: value };
+                    });
+                }
+            />
+        }
+        .into_any(),
+        Condition::SunAboveHorizon | Condition::SunBelowHorizon => ().into_any(),
+    };
+    view! {
+        {kind}
+        {fields}
+    }
+}
+
+/// Type picker and fields of the action at `index`.
+fn action_editor(
+    actions: RwSignal<Vec<Action>>,
+    index: usize,
+    action: &Action,
+    entities: &[Entity],
+    defaults: Defaults,
+) -> impl IntoView {
+    let kind = kind_select(ACTION_KINDS, action_kind(action), move |kind| {
+        edit_item(actions, index, |action| {
+            *action = default_action(&kind, defaults);
+        });
+    });
+    let fields = match action.clone() {
+        Action::CallService {
+            entity_id, service, ..
+        } => view! {
+            <input
+                type="text"
+                placeholder="turn_on"
+                prop:value=service
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    edit_item(actions, index, |action| {
+                        if let Action::CallService { service, .. } = action {
+                            *service = value;
+                        }
+                    });
+                }
+            />
+            {entity_picker(entities, entity_id, move |id| edit_item(actions, index, |action| {
+                if let Action::CallService { entity_id, .. } = action {
+                    *entity_id = id;
+                }
+            }))}
+        }
+        .into_any(),
+        Action::Delay { seconds } => view! {
+            <input
+                type="number"
+                min="0"
+                prop:value=seconds.to_string()
+                on:change=move |ev| {
+                    if let Ok(value) = event_target_value(&ev).parse::<u64>() {
+                        edit_item(actions, index, |action| {
+                            *action = Action::Delay { seconds: value };
+                        });
+                    }
+                }
+            />
+            "seconds"
+        }
+        .into_any(),
+        Action::Notify {
+            title,
+            message,
+            severity,
+        } => {
+            let severities = SEVERITIES
+                .into_iter()
+                .map(|option| {
+                    let is_selected = option == severity;
+                    let value = option.to_string();
+                    view! { <option value=value.clone() selected=is_selected>{value}</option> }
+                })
+                .collect::<Vec<_>>();
+            view! {
+                <input
+                    type="text"
+                    placeholder="Title (optional)"
+                    prop:value=title.unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        edit_item(actions, index, |action| {
+                            if let Action::Notify { title, .. } = action {
+                                *title = Some(value).filter(|value| !value.trim().is_empty());
+                            }
+                        });
+                    }
+                />
+                <input
+                    type="text"
+                    placeholder="Message"
+                    prop:value=message
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        edit_item(actions, index, |action| {
+                            if let Action::Notify { message, .. } = action {
+                                *message = value;
+                            }
+                        });
+                    }
+                />
+                <select on:change=move |ev| {
+                    let value = parse_severity(&event_target_value(&ev));
+                    edit_item(actions, index, |action| {
+                        if let Action::Notify { severity, .. } = action {
+                            *severity = value;
+                        }
+                    });
+                }>
+                    {severities}
+                </select>
+            }
+            .into_any()
+        }
+        Action::WaitForState {
+            entity_id,
+            state,
+            timeout_secs,
+        } => view! {
+            {entity_picker(entities, entity_id, move |id| edit_item(actions, index, |action| {
+                if let Action::WaitForState { entity_id, .. } = action {
+                    *entity_id = id;
+                }
+            }))}
+            <input
+                type="text"
+                placeholder="off"
+                prop:value=state
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    edit_item(actions, index, |action| {
+                        if let Action::WaitForState { state, .. } = action {
+                            *state = value;
+                        }
+                    });
+                }
+            />
+            "within"
+            <input
+                type="number"
+                min="0"
+                prop:value=timeout_secs.to_string()
+                on:change=move |ev| {
+                    if let Ok(value) = event_target_value(&ev).parse::<u64>() {
+                        edit_item(actions, index, |action| {
+                            if let Action::WaitForState { timeout_secs, .. } = action {
+                                *timeout_secs = value;
+                            }
+                        });
+                    }
+                }
+            />
+            "seconds"
+        }
+        .into_any(),
+        // Nested actions are not editable in the form yet.
+        Action::If { .. } | Action::Repeat { .. } => {
+            return view! { <em>{format!("{action} \u{2014} edit through the API")}</em> }
+                .into_any();
+        }
+    };
+    view! {
+        {kind}
+        {fields}
+    }
+    .into_any()
+}
+
+/// Move up, move down and remove buttons of the item at `index`.
+pub(crate) fn row_buttons<T>(list: RwSignal<Vec<T>>, index: usize, count: usize) -> impl IntoView
+where
+    T: Send + Sync + 'static,
+{
+    let is_first = index == 0;
+    let is_last = index + 1 == count;
+    view! {
+        <div class="editor-row-buttons">
+            <button
+                class="btn btn-sm btn-secondary"
+                title="Move up"
+                disabled=is_first
+                on:click=move |_| list.update(|items| move_item(items, index, true))
+            >
+                "\u{2191}"
+            </button>
+            <button
+                class="btn btn-sm btn-secondary"
+                title="Move down"
+                disabled=is_last
+                on:click=move |_| list.update(|items| move_item(items, index, false))
+            >
+                "\u{2193}"
+            </button>
+            <button
+                class="btn btn-sm btn-secondary"
+                title="Remove"
+                on:click=move |_| list.update(|items| {
+                    if index < items.len() {
+                        items.remove(index);
+                    }
+                })
+            >
+                "\u{2715}"
+            </button>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> Defaults {
+        Defaults {
+            entity_id: EntityId::new(),
+            device_id: DeviceId::new(),
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_kinds_through_defaults() {
+        let defaults = defaults();
+        for (kind, _) in TRIGGER_KINDS {
+            assert_eq!(trigger_kind(&default_trigger(kind, defaults)), *kind);
+        }
+        for (kind, _) in CONDITION_KINDS {
+            assert_eq!(condition_kind(&default_condition(kind, defaults)), *kind);
+        }
+        for (kind, _) in ACTION_KINDS {
+            assert_eq!(action_kind(&default_action(kind, defaults)), *kind);
+        }
+    }
+
+    #[test]
+    fn should_move_items_within_bounds() {
+        let mut items = vec!['a', 'b', 'c'];
+
+        move_item(&mut items, 2, true);
+        assert_eq!(items, ['a', 'c', 'b']);
+
+        move_item(&mut items, 0, true);
+        move_item(&mut items, 2, false);
+        assert_eq!(items, ['a', 'c', 'b']);
+    }
+
+    #[test]
+    fn should_parse_numbers_and_options_from_value_field() {
+        assert_eq!(parse_value(" 21.5 "), serde_json::json!(21.5));
+        assert_eq!(parse_value("movie"), serde_json::json!("movie"));
+        assert_eq!(format_value(&parse_value("movie")), "movie");
+    }
+
+    #[test]
+    fn should_treat_any_as_no_state_filter() {
+        assert_eq!(parse_state("any"), None);
+        assert_eq!(parse_state("off"), Some(EntityState::Off));
+    }
+}
@@ -59,6 +59,7 @@ fn AutomationRow(
     let enabled = automation.enabled;
     let trigger = format!("{:?}", automation.trigger);
     let actions_count = automation.actions.len();
+    let read_only = automation.is_read_only();
 
     let (is_updating, set_is_updating) = signal(false);
     let (error_message, set_error_message) = signal::<Option<String>>(None);
@@ -95,7 +96,8 @@ fn AutomationRow(
                 <button
                     class=move || if enabled { "btn-enabled" } else { "btn-disabled" }
                     on:click=toggle_enabled
-                    disabled=move || is_updating.get()
+                    disabled=move || read_only || is_updating.get()
+                    title=read_only.then_some("Defined in a file")
                 >
                     {move || if is_updating.get() {
                         "…".to_string()
@@ -0,0 +1,216 @@
+//! Guided form creating a first "when X changes, do Y" automation.
+
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use minihub_domain::automation::{Action, Trigger};
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::id::EntityId;
+
+use crate::api;
+use crate::components::Loading;
+
+/// Wizard steps, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Trigger,
+    Action,
+    Review,
+}
+
+/// Services offered for the action step.
+const SERVICES: &[(&str, &str)] = &[
+    ("turn_on", "Turn on"),
+    ("turn_off", "Turn off"),
+    ("toggle", "Toggle"),
+];
+
+/// Map the trigger state choice to the `to` filter of the trigger.
+fn target_state(value: &str) -> Option<EntityState> {
+    match value {
+        "on" => Some(EntityState::On),
+        "off" => Some(EntityState::Off),
+        _ => None,
+    }
+}
+
+/// A three-step wizard — trigger, action, review — posting the result to
+/// `POST /api/automations`.
+#[component]
+pub fn AutomationWizard(
+    /// Called once the automation has been created.
+    #[prop(into)]
+    on_created: Callback<()>,
+) -> impl IntoView {
+    let entities = LocalResource::new(api::fetch_entities);
+
+    let (step, set_step) = signal(Step::Trigger);
+    let (trigger_entity, set_trigger_entity) = signal(String::new());
+    let (trigger_state, set_trigger_state) = signal("on".to_string());
+    let (action_entity, set_action_entity) = signal(String::new());
+    let (service, set_service) = signal("turn_on".to_string());
+    let (name, set_name) = signal(String::new());
+    let (is_saving, set_is_saving) = signal(false);
+    let (error_message, set_error_message) = signal::<Option<String>>(None);
+
+    let entity_name = move |id: &str| {
+        entities
+            .read()
+            .as_ref()
+            .and_then(|result| result.as_ref().ok())
+            .and_then(|list| list.iter().find(|entity| entity.id.to_string() == id))
+            .map(|entity| entity.friendly_name.clone())
+            .unwrap_or_default()
+    };
+
+    let go_to_review = move |_| {
+        if name.get_untracked().is_empty() {
+            set_name.set(format!(
+                "When {} turns {}, {} {}",
+                entity_name(&trigger_entity.get_untracked()),
+                trigger_state.get_untracked(),
+                service.get_untracked().replace('_', " "),
+                entity_name(&action_entity.get_untracked()),
+            ));
+        }
+        set_step.set(Step::Review);
+    };
+
+    let create = move |_| {
+        let (Ok(trigger_id), Ok(action_id)) = (
+            EntityId::from_str(&trigger_entity.get_untracked()),
+            EntityId::from_str(&action_entity.get_untracked()),
+        ) else {
+            set_error_message.set(Some("Pick both entities first".to_string()));
+            return;
+        };
+        let trigger = Trigger::StateChanged {
+            entity_id: trigger_id,
+            from: None,
+            to: target_state(&trigger_state.get_untracked()),
+        };
+        let actions = vec![Action::CallService {
+            entity_id: action_id,
+            service: service.get_untracked(),
+            data: serde_json::Value::Null,
+        }];
+
+        set_is_saving.set(true);
+        set_error_message.set(None);
+        spawn_local(async move {
+            match api::create_automation(name.get_untracked(), trigger, actions).await {
+                Ok(_) => on_created.run(()),
+                Err(err) => set_error_message.set(Some(err.to_string())),
+            }
+            set_is_saving.set(false);
+        });
+    };
+
+    let entity_options = move |selected: ReadSignal<String>| {
+        entities.read().as_ref().map(|result| match result {
+            Ok(list) => entity_select_options(list, &selected.get()).into_any(),
+            Err(err) => view! {
+                <option value="" disabled=true>{format!("Failed to load entities: {err}")}</option>
+            }
+            .into_any(),
+        })
+    };
+
+    view! {
+        <div class="card empty-state automation-wizard">
+            <h2>"Create your first automation"</h2>
+            <Suspense fallback=move || view! { <Loading message="Loading entities\u{2026}"/> }>
+                {move || match step.get() {
+                    Step::Trigger => view! {
+                        <p>"1. When this entity\u{2026}"</p>
+                        <select on:change=move |ev| set_trigger_entity.set(event_target_value(&ev))>
+                            {move || entity_options(trigger_entity)}
+                        </select>
+                        <select on:change=move |ev| set_trigger_state.set(event_target_value(&ev))>
+                            <option value="on" selected=move || trigger_state.get() == "on">"turns on"</option>
+                            <option value="off" selected=move || trigger_state.get() == "off">"turns off"</option>
+                            <option value="any" selected=move || trigger_state.get() == "any">"changes state"</option>
+                        </select>
+                        <div class="wizard-buttons">
+                            <button
+                                class="btn btn-primary"
+                                disabled=move || trigger_entity.get().is_empty()
+                                on:click=move |_| set_step.set(Step::Action)
+                            >
+                                "Next"
+                            </button>
+                        </div>
+                    }.into_any(),
+                    Step::Action => view! {
+                        <p>"2. \u{2026}then do this"</p>
+                        <select on:change=move |ev| set_service.set(event_target_value(&ev))>
+                            {SERVICES.iter().map(|(value, label)| view! {
+                                <option value=*value selected=move || service.get() == *value>{*label}</option>
+                            }).collect::<Vec<_>>()}
+                        </select>
+                        <select on:change=move |ev| set_action_entity.set(event_target_value(&ev))>
+                            {move || entity_options(action_entity)}
+                        </select>
+                        <div class="wizard-buttons">
+                            <button class="btn btn-secondary" on:click=move |_| set_step.set(Step::Trigger)>
+                                "Back"
+                            </button>
+                            <button
+                                class="btn btn-primary"
+                                disabled=move || action_entity.get().is_empty()
+                                on:click=go_to_review
+                            >
+                                "Next"
+                            </button>
+                        </div>
+                    }.into_any(),
+                    Step::Review => view! {
+                        <p>"3. Name it"</p>
+                        <input
+                            type="text"
+                            prop:value=move || name.get()
+                            on:input=move |ev| set_name.set(event_target_value(&ev))
+                        />
+                        <div class="wizard-buttons">
+                            <button class="btn btn-secondary" on:click=move |_| set_step.set(Step::Action)>
+                                "Back"
+                            </button>
+                            <button
+                                class="btn btn-primary"
+                                disabled=move || is_saving.get() || name.get().trim().is_empty()
+                                on:click=create
+                            >
+                                {move || if is_saving.get() { "Creating\u{2026}" } else { "Create automation" }}
+                            </button>
+                        </div>
+                    }.into_any(),
+                }}
+            </Suspense>
+            {move || error_message.get().map(|msg| view! {
+                <p class="error">{msg}</p>
+            })}
+        </div>
+    }
+}
+
+/// Render `<option>`s for every entity, with a placeholder first.
+fn entity_select_options(entities: &[Entity], selected: &str) -> impl IntoView {
+    let placeholder_selected = selected.is_empty();
+    let options = entities
+        .iter()
+        .map(|entity| {
+            let id = entity.id.to_string();
+            let is_selected = id == selected;
+            view! {
+                <option value=id selected=is_selected>
+                    {format!("{} ({})", entity.friendly_name, entity.entity_id)}
+                </option>
+            }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        <option value="" disabled=true selected=placeholder_selected>"Choose an entity\u{2026}"</option>
+        {options}
+    }
+}
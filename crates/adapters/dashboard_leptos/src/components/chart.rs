@@ -1,13 +1,18 @@
 //! Sensor history chart component using `leptos-chartistry` with SVG rendering.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
 use chrono::{DateTime, Duration, Utc};
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos_chartistry::*;
-use minihub_domain::entity::AttributeValue;
-use minihub_domain::entity_history::EntityHistory;
+use minihub_domain::entity_history::{EntityHistory, HistorySeries};
+use minihub_domain::id::EntityId;
+
+use crate::api::{fetch_entity_history, fetch_history_series};
 
-use crate::api::fetch_entity_history;
+/// Buckets the history chart asks the server to downsample a range into.
+const CHART_BUCKETS: u32 = 200;
 
 /// Available time ranges for the history chart.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,6 +21,7 @@ pub enum TimeRange {
     SixHours,
     TwentyFourHours,
     SevenDays,
+    ThirtyDays,
 }
 
 impl TimeRange {
@@ -26,6 +32,7 @@ impl TimeRange {
             Self::SixHours => Duration::hours(6),
             Self::TwentyFourHours => Duration::hours(24),
             Self::SevenDays => Duration::days(7),
+            Self::ThirtyDays => Duration::days(30),
         }
     }
 
@@ -36,15 +43,17 @@ impl TimeRange {
             Self::SixHours => "6h",
             Self::TwentyFourHours => "24h",
             Self::SevenDays => "7d",
+            Self::ThirtyDays => "30d",
         }
     }
 }
 
-const TIME_RANGES: [TimeRange; 4] = [
+const TIME_RANGES: [TimeRange; 5] = [
     TimeRange::OneHour,
     TimeRange::SixHours,
     TimeRange::TwentyFourHours,
     TimeRange::SevenDays,
+    TimeRange::ThirtyDays,
 ];
 
 /// A single data point for a chart series.
@@ -54,39 +63,93 @@ struct ChartPoint {
     value: f64,
 }
 
-/// Extract numeric attribute values from history records for the given key.
-fn extract_series(history: &[EntityHistory], attr_key: &str) -> Vec<ChartPoint> {
+/// Extract the readings of numeric states from history records.
+fn extract_state_series(history: &[EntityHistory]) -> Vec<ChartPoint> {
     history
         .iter()
         .filter_map(|record| {
-            let value = record.get_attribute(attr_key)?;
-            let num = match value {
-                AttributeValue::Float(v) => *v,
-                AttributeValue::Int(v) => *v as f64,
-                _ => return None,
-            };
             Some(ChartPoint {
                 timestamp: record.recorded_at,
-                value: num,
+                value: record.state.as_f64()?,
             })
         })
         .collect()
 }
 
-/// Discover numeric attribute keys present in the history data.
-fn discover_numeric_attributes(history: &[EntityHistory]) -> Vec<String> {
-    let mut keys = Vec::new();
-    for record in history {
-        for (key, value) in &record.attributes {
-            if matches!(value, AttributeValue::Float(_) | AttributeValue::Int(_))
-                && !keys.contains(key)
-            {
-                keys.push(key.clone());
+/// One instant of a multi-series chart, with the values of every line
+/// plotted at that instant.
+#[derive(Clone, Debug, PartialEq)]
+struct ChartRow {
+    timestamp: DateTime<Utc>,
+    values: HashMap<String, f64>,
+}
+
+impl ChartRow {
+    /// Value of `line`, `NaN` leaving a gap when that line has none here.
+    fn value(&self, line: &str) -> f64 {
+        self.values.get(line).copied().unwrap_or(f64::NAN)
+    }
+}
+
+/// Name of the line of the minimum, or maximum, of `series`.
+fn band_line(series: &str, bound: &str) -> String {
+    format!("{series} {bound}")
+}
+
+/// Whether some bucket of `series` aggregates several samples, so min and
+/// max bands tell something the mean does not.
+fn has_bands(series: &HistorySeries) -> bool {
+    series.buckets.iter().any(|bucket| bucket.count > 1)
+}
+
+/// Lines plotted for the `selected` series: each mean, plus min and max
+/// bands when aggregated.
+fn chart_lines(series: &[HistorySeries], selected: &BTreeSet<String>) -> Vec<String> {
+    series
+        .iter()
+        .filter(|series| selected.contains(&series.name))
+        .flat_map(|series| {
+            let mut lines = vec![series.name.clone()];
+            if has_bands(series) {
+                lines.push(band_line(&series.name, "min"));
+                lines.push(band_line(&series.name, "max"));
             }
+            lines
+        })
+        .collect()
+}
+
+/// Merge the buckets of the `selected` series into rows, oldest first.
+fn chart_rows(series: &[HistorySeries], selected: &BTreeSet<String>) -> Vec<ChartRow> {
+    let mut rows: BTreeMap<DateTime<Utc>, HashMap<String, f64>> = BTreeMap::new();
+    for series in series
+        .iter()
+        .filter(|series| selected.contains(&series.name))
+    {
+        let bands = has_bands(series);
+        for bucket in &series.buckets {
+            let values = rows.entry(bucket.start).or_default();
+            values.insert(series.name.clone(), bucket.mean);
+            if bands {
+                values.insert(band_line(&series.name, "min"), bucket.min);
+                values.insert(band_line(&series.name, "max"), bucket.max);
+            }
+        }
+    }
+    rows.into_iter()
+        .map(|(timestamp, values)| ChartRow { timestamp, values })
+        .collect()
+}
+
+/// Keep the selected series that are still available, falling back to the
+/// first one when none is.
+fn retain_selection(selected: &mut BTreeSet<String>, series: &[HistorySeries]) {
+    selected.retain(|name| series.iter().any(|series| &series.name == name));
+    if selected.is_empty() {
+        if let Some(first) = series.first() {
+            selected.insert(first.name.clone());
         }
     }
-    keys.sort();
-    keys
 }
 
 /// Build timestamp tick labels (extracted to avoid turbofish inside `view!` macro).
@@ -124,17 +187,53 @@ fn AttributeChart(name: String, data: Signal<Vec<ChartPoint>>) -> impl IntoView
     }
 }
 
+/// Render the `lines` of `rows` together on one chart.
+#[component]
+fn MultiSeriesChart(lines: Vec<String>, rows: Vec<ChartRow>) -> impl IntoView {
+    let series = lines.into_iter().fold(
+        Series::new(|row: &ChartRow| row.timestamp),
+        |series, line| {
+            let name = line.clone();
+            series.line(Line::new(move |row: &ChartRow| row.value(&line)).with_name(name))
+        },
+    );
+    let inner = vec![
+        AxisMarker::left_edge().into_inner(),
+        AxisMarker::bottom_edge().into_inner(),
+        XGridLine::default().into_inner(),
+        YGridLine::default().into_inner(),
+        XGuideLine::over_data().into_inner(),
+        YGuideLine::over_mouse().into_inner(),
+    ];
+    view! {
+        <div class="attribute-chart">
+            <Chart
+                aspect_ratio=AspectRatio::from_env_width_apply_ratio(3.0)
+                left=TickLabels::aligned_floats()
+                bottom=timestamp_ticks()
+                inner=inner
+                tooltip=Tooltip::left_cursor()
+                series=series
+                data=Signal::derive(move || rows.clone())
+            />
+        </div>
+    }
+}
+
 /// Sensor history chart component.
 ///
-/// Fetches entity history from the API and renders interactive SVG line charts
-/// using `leptos-chartistry`. One chart per numeric attribute, each full-width
-/// and responsive. Provides a time range selector (1h, 6h, 24h, 7d).
+/// Fetches the numeric state and attributes of an entity downsampled by the
+/// server, and plots the series picked by the user together on one
+/// interactive SVG chart using `leptos-chartistry`. When buckets aggregate
+/// several samples, the min and max of each series are drawn around its
+/// mean. Provides a time range selector (1h to 30d).
 #[component]
 pub fn HistoryChart(entity_id: ReadSignal<String>) -> impl IntoView {
     let (range, set_range) = signal(TimeRange::TwentyFourHours);
     let (chart_error, set_chart_error) = signal(None::<String>);
     let (loading, set_loading) = signal(false);
-    let (series_list, set_series_list) = signal(Vec::<(String, Vec<ChartPoint>)>::new());
+    let (series_list, set_series_list) = signal(Vec::<HistorySeries>::new());
+    let selected = RwSignal::new(BTreeSet::<String>::new());
 
     Effect::new(move |_| {
         let eid = entity_id.get();
@@ -149,19 +248,16 @@ pub fn HistoryChart(entity_id: ReadSignal<String>) -> impl IntoView {
 
         let now = Utc::now();
         let from = (now - selected_range.duration()).to_rfc3339();
+        let to = now.to_rfc3339();
 
         spawn_local(async move {
-            match fetch_entity_history(&eid, Some(&from), None).await {
-                Ok(history) => {
-                    let attr_keys = discover_numeric_attributes(&history);
-                    let series: Vec<(String, Vec<ChartPoint>)> = attr_keys
+            match fetch_history_series(&eid, &from, &to, CHART_BUCKETS).await {
+                Ok(series) => {
+                    let series: Vec<_> = series
                         .into_iter()
-                        .map(|key| {
-                            let points = extract_series(&history, &key);
-                            (key, points)
-                        })
-                        .filter(|(_, points)| !points.is_empty())
+                        .filter(|series| !series.buckets.is_empty())
                         .collect();
+                    selected.update(|selected| retain_selection(selected, &series));
                     set_series_list.set(series);
                     set_loading.set(false);
                 }
@@ -173,6 +269,17 @@ pub fn HistoryChart(entity_id: ReadSignal<String>) -> impl IntoView {
         });
     });
 
+    let chart = move || {
+        series_list.with(|series| {
+            selected.with(|selected| {
+                let rows = chart_rows(series, selected);
+                (!rows.is_empty()).then(|| {
+                    view! { <MultiSeriesChart lines=chart_lines(series, selected) rows=rows/> }
+                })
+            })
+        })
+    };
+
     view! {
         <div class="history-chart">
             <h3>"State History"</h3>
@@ -196,6 +303,38 @@ pub fn HistoryChart(entity_id: ReadSignal<String>) -> impl IntoView {
                     .collect_view()}
             </div>
 
+            <div class="series-selector">
+                <For
+                    each=move || series_list.get()
+                    key=|series| series.name.clone()
+                    let(series)
+                >
+                    {
+                        let name = series.name.clone();
+                        let toggled = series.name.clone();
+                        view! {
+                            <label>
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || selected.with(|selected| selected.contains(&name))
+                                    on:change=move |ev| {
+                                        let checked = event_target_checked(&ev);
+                                        selected.update(|selected| {
+                                            if checked {
+                                                selected.insert(toggled.clone());
+                                            } else {
+                                                selected.remove(&toggled);
+                                            }
+                                        });
+                                    }
+                                />
+                                {series.name}
+                            </label>
+                        }
+                    }
+                </For>
+            </div>
+
             <Show when=move || loading.get()>
                 <p>"Loading history..."</p>
             </Show>
@@ -205,13 +344,142 @@ pub fn HistoryChart(entity_id: ReadSignal<String>) -> impl IntoView {
             <Show when=move || !loading.get() && chart_error.get().is_none() && series_list.get().is_empty()>
                 <p><em>"No numeric sensor data available for this entity."</em></p>
             </Show>
-            <For
-                each=move || series_list.get()
-                key=|(name, _)| name.clone()
-                let((name, points))
-            >
-                <AttributeChart name=name data=Signal::derive(move || points.clone()) />
-            </For>
+            {chart}
         </div>
     }
 }
+
+/// Chart of the numeric state of an entity over the last `hours`, without
+/// range selector, as pinned to the home page.
+#[component]
+pub fn StateChart(
+    /// Entity whose state is charted.
+    entity_id: EntityId,
+    /// Title of the chart.
+    #[prop(into)]
+    name: String,
+    /// Length of the time range, ending now.
+    hours: u32,
+) -> impl IntoView {
+    let (points, set_points) = signal(None::<Result<Vec<ChartPoint>, String>>);
+
+    Effect::new(move |_| {
+        let from = (Utc::now() - Duration::hours(i64::from(hours))).to_rfc3339();
+        spawn_local(async move {
+            let result = fetch_entity_history(&entity_id.to_string(), Some(&from), None)
+                .await
+                .map(|history| extract_state_series(&history))
+                .map_err(|err| err.message);
+            set_points.set(Some(result));
+        });
+    });
+
+    move || match points.get() {
+        None => view! { <p>"Loading history..."</p> }.into_any(),
+        Some(Err(err)) => view! { <p class="error">"Chart error: " {err}</p> }.into_any(),
+        Some(Ok(points)) if points.is_empty() => view! {
+            <p><em>{format!("No numeric state recorded for {name} in the last {hours}h.")}</em></p>
+        }
+        .into_any(),
+        Some(Ok(points)) => view! {
+            <AttributeChart
+                name=format!("{name} \u{b7} {hours}h")
+                data=Signal::derive(move || points.clone())
+            />
+        }
+        .into_any(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::entity_history::HistoryBucket;
+
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn bucket(start: &str, mean: f64, count: u32) -> HistoryBucket {
+        HistoryBucket {
+            start: at(start),
+            mean,
+            min: mean - 1.0,
+            max: mean + 1.0,
+            count,
+        }
+    }
+
+    fn series(name: &str, buckets: Vec<HistoryBucket>) -> HistorySeries {
+        HistorySeries {
+            name: name.to_string(),
+            buckets,
+        }
+    }
+
+    fn selection(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    #[test]
+    fn should_merge_selected_series_into_rows_by_timestamp() {
+        let series = [
+            series("temperature", vec![bucket("2026-01-01T00:00:00Z", 20.0, 1)]),
+            series(
+                "humidity",
+                vec![
+                    bucket("2026-01-01T00:00:00Z", 55.0, 1),
+                    bucket("2026-01-01T01:00:00Z", 60.0, 1),
+                ],
+            ),
+            series("battery", vec![bucket("2026-01-01T00:00:00Z", 80.0, 1)]),
+        ];
+
+        let rows = chart_rows(&series, &selection(&["temperature", "humidity"]));
+
+        assert_eq!(rows.len(), 2);
+        assert!((rows[0].value("temperature") - 20.0).abs() < f64::EPSILON);
+        assert!((rows[0].value("humidity") - 55.0).abs() < f64::EPSILON);
+        assert!(rows[1].value("temperature").is_nan());
+        assert!(rows[0].value("battery").is_nan());
+    }
+
+    #[test]
+    fn should_add_min_max_bands_only_when_buckets_are_aggregated() {
+        let series = [
+            series("temperature", vec![bucket("2026-01-01T00:00:00Z", 20.0, 4)]),
+            series("humidity", vec![bucket("2026-01-01T00:00:00Z", 55.0, 1)]),
+        ];
+
+        let lines = chart_lines(&series, &selection(&["temperature", "humidity"]));
+        let rows = chart_rows(&series, &selection(&["temperature"]));
+
+        assert_eq!(
+            lines,
+            [
+                "temperature",
+                "temperature min",
+                "temperature max",
+                "humidity"
+            ]
+        );
+        assert!((rows[0].value("temperature min") - 19.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn should_select_first_series_when_selection_is_no_longer_available() {
+        let series = [
+            series("state", vec![bucket("2026-01-01T00:00:00Z", 20.0, 1)]),
+            series("humidity", vec![bucket("2026-01-01T00:00:00Z", 55.0, 1)]),
+        ];
+
+        let mut selected = selection(&["humidity", "battery"]);
+        retain_selection(&mut selected, &series);
+        assert_eq!(selected, selection(&["humidity"]));
+
+        let mut selected = selection(&["battery"]);
+        retain_selection(&mut selected, &series);
+        assert_eq!(selected, selection(&["state"]));
+    }
+}
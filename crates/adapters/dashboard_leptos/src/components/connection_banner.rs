@@ -0,0 +1,18 @@
+//! Banner shown while the server cannot be reached.
+
+use leptos::prelude::*;
+
+use crate::connection::{self, ConnectionStatus};
+
+/// A banner telling the data shown may be stale, while the connection to
+/// the server is being re-established.
+#[component]
+pub fn ConnectionBanner() -> impl IntoView {
+    view! {
+        <Show when=move || connection::status() == ConnectionStatus::Reconnecting>
+            <div class="connection-banner" role="status">
+                "Connection to minihub lost \u{2014} reconnecting\u{2026}"
+            </div>
+        </Show>
+    }
+}
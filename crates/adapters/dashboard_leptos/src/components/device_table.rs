@@ -2,13 +2,13 @@
 
 use leptos::prelude::*;
 use leptos_router::components::A;
-use minihub_domain::device::Device;
+use minihub_domain::device::{DeviceStatus, DeviceWithStatus};
 
 /// A table displaying a list of devices.
 #[component]
 pub fn DeviceTable(
     /// The list of devices to display.
-    devices: Vec<Device>,
+    devices: Vec<DeviceWithStatus>,
 ) -> impl IntoView {
     if devices.is_empty() {
         view! {
@@ -24,6 +24,7 @@ pub fn DeviceTable(
                         <th>"Manufacturer"</th>
                         <th>"Model"</th>
                         <th>"Integration"</th>
+                        <th>"Status"</th>
                     </tr>
                 </thead>
                 <tbody>
@@ -43,8 +44,9 @@ pub fn DeviceTable(
 #[component]
 fn DeviceRow(
     /// The device to display.
-    device: Device,
+    device: DeviceWithStatus,
 ) -> impl IntoView {
+    let DeviceWithStatus { device, status } = device;
     let device_id = device.id.to_string();
     let name = device.name;
     let manufacturer = device.manufacturer.unwrap_or_else(|| "—".to_string());
@@ -61,6 +63,28 @@ fn DeviceRow(
             <td>{manufacturer}</td>
             <td>{model}</td>
             <td>{integration}</td>
+            <td>
+                <DeviceStatusBadge status/>
+            </td>
         </tr>
     }
 }
+
+/// A badge displaying a device availability status.
+#[component]
+pub fn DeviceStatusBadge(
+    /// The device status to display.
+    status: DeviceStatus,
+) -> impl IntoView {
+    let status_class = match status {
+        DeviceStatus::Online => "badge-on",
+        DeviceStatus::Unavailable => "badge-unavailable",
+        DeviceStatus::Unknown => "badge-unknown",
+    };
+
+    view! {
+        <span class=format!("badge {}", status_class)>
+            {status.to_string()}
+        </span>
+    }
+}
@@ -0,0 +1,235 @@
+//! Empty-state panels shown on list pages of a fresh hub.
+
+use leptos::prelude::*;
+use leptos_router::components::A;
+use minihub_domain::event::{Event, EventType};
+
+use crate::api;
+use crate::components::Loading;
+
+/// An integration that creates entities, and how to turn it on.
+struct EntitySource {
+    name: &'static str,
+    label: &'static str,
+    description: &'static str,
+    enable: &'static str,
+}
+
+/// Integrations suggested when no entity exists yet.
+const ENTITY_SOURCES: &[EntitySource] = &[
+    EntitySource {
+        name: "virtual",
+        label: "Virtual devices",
+        description: "a simulated light, sensor and switch to try minihub out.",
+        enable: "virtual_enabled = true under [integrations]",
+    },
+    EntitySource {
+        name: "mqtt",
+        label: "MQTT",
+        description: "devices announcing themselves on minihub/+/config.",
+        enable: "MINIHUB_MQTT_ENABLED=1",
+    },
+    EntitySource {
+        name: "zigbee2mqtt",
+        label: "zigbee2mqtt",
+        description: "Zigbee devices paired with a zigbee2mqtt bridge.",
+        enable: "MINIHUB_ZIGBEE2MQTT_ENABLED=1",
+    },
+    EntitySource {
+        name: "remote",
+        label: "Remote minihub",
+        description: "entities of another minihub federated over MQTT.",
+        enable: "MINIHUB_REMOTE_ENABLED=1",
+    },
+    EntitySource {
+        name: "esphome",
+        label: "ESPHome",
+        description: "ESPHome nodes listed under [[integrations.esphome.devices]].",
+        enable: "MINIHUB_ESPHOME_ENABLED=1",
+    },
+    EntitySource {
+        name: "rest",
+        label: "HTTP/REST",
+        description: "JSON HTTP devices listed under [[integrations.rest.devices]].",
+        enable: "MINIHUB_REST_ENABLED=1",
+    },
+];
+
+/// Shown on the Entities page when no entity exists.
+///
+/// Reads `/api/integrations` to suggest the entity sources that are turned
+/// off, and to explain that enabled ones are still waiting for devices.
+#[component]
+pub fn EntitiesEmptyState() -> impl IntoView {
+    let integrations = LocalResource::new(api::fetch_integrations);
+
+    view! {
+        <div class="card empty-state">
+            <h2>"No entities yet"</h2>
+            <p>"Entities appear as soon as an integration discovers devices."</p>
+            <Suspense fallback=move || view! { <Loading message="Checking integrations\u{2026}"/> }>
+                {move || {
+                    integrations.read().as_ref().map(|result| match result {
+                        Ok(list) => view! {
+                            <ul class="empty-state-list">
+                                {ENTITY_SOURCES.iter().map(|source| {
+                                    let integration = list
+                                        .iter()
+                                        .find(|integration| integration.name == source.name && integration.enabled);
+                                    let status = match integration {
+                                        Some(integration) if integration.status == "failed" => view! {
+                                            <p class="error">
+                                                "Failed to start: "
+                                                {integration.error.clone().unwrap_or_default()}
+                                            </p>
+                                        }.into_any(),
+                                        Some(integration) if integration.status == "restarting" => view! {
+                                            <p class="hint">
+                                                "Failed to start, retrying: "
+                                                {integration.error.clone().unwrap_or_default()}
+                                            </p>
+                                        }.into_any(),
+                                        Some(integration) if integration.status == "timed_out" => view! {
+                                            <p class="hint">"Enabled \u{2014} still starting, setup is taking longer than expected."</p>
+                                        }.into_any(),
+                                        Some(_) => view! {
+                                            <p class="hint">"Enabled \u{2014} waiting for devices to report in."</p>
+                                        }.into_any(),
+                                        None => view! {
+                                            <p>"Enable it with " <code>{source.enable}</code> " and restart minihubd."</p>
+                                        }.into_any(),
+                                    };
+                                    view! {
+                                        <li>
+                                            <strong>{source.label}</strong>
+                                            " \u{2014} "
+                                            {source.description}
+                                            {status}
+                                        </li>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </ul>
+                        }.into_any(),
+                        Err(err) => view! {
+                            <p class="error">{"Failed to load integrations: "} {err.to_string()}</p>
+                        }.into_any(),
+                    })
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+/// A device reported by a `device_detected` event.
+#[derive(Debug, Clone, PartialEq)]
+struct DetectedDevice {
+    integration: String,
+    address: String,
+    name: String,
+}
+
+/// Collect the distinct devices announced by recent `device_detected` events.
+fn detected_devices(events: &[Event]) -> Vec<DetectedDevice> {
+    let mut detected: Vec<DetectedDevice> = Vec::new();
+    for event in events {
+        if event.event_type != EventType::DeviceDetected {
+            continue;
+        }
+        let field = |key: &str| event.data.get(key).and_then(|v| v.as_str());
+        let Some(address) = field("mac").or_else(|| field("address")) else {
+            continue;
+        };
+        let integration = field("integration").unwrap_or("unknown");
+        if detected
+            .iter()
+            .any(|device| device.integration == integration && device.address == address)
+        {
+            continue;
+        }
+        detected.push(DetectedDevice {
+            integration: integration.to_string(),
+            address: address.to_string(),
+            name: field("name").unwrap_or("Unnamed device").to_string(),
+        });
+    }
+    detected
+}
+
+/// Shown on the Devices page when no device is registered.
+///
+/// Lists the devices integrations have detected but not registered, taken
+/// from the recent `device_detected` events, and links to the event log.
+#[component]
+pub fn DevicesEmptyState() -> impl IntoView {
+    let events = LocalResource::new(api::fetch_events);
+
+    view! {
+        <div class="card empty-state">
+            <h2>"No devices yet"</h2>
+            <p>"Devices are registered by integrations once they are discovered."</p>
+            <h3>"Discovery inbox"</h3>
+            <Suspense fallback=move || view! { <Loading message="Loading detected devices\u{2026}"/> }>
+                {move || {
+                    events.read().as_ref().map(|result| match result {
+                        Ok(list) => {
+                            let detected = detected_devices(list);
+                            if detected.is_empty() {
+                                view! {
+                                    <p class="hint">"Nothing detected yet \u{2014} enable an integration to start scanning."</p>
+                                }.into_any()
+                            } else {
+                                view! {
+                                    <ul class="empty-state-list">
+                                        {detected.into_iter().map(|device| view! {
+                                            <li>
+                                                <strong>{device.name}</strong>
+                                                " " <code>{device.address}</code>
+                                                " via " {device.integration}
+                                            </li>
+                                        }).collect::<Vec<_>>()}
+                                    </ul>
+                                }.into_any()
+                            }
+                        }
+                        Err(err) => view! {
+                            <p class="error">{"Failed to load events: "} {err.to_string()}</p>
+                        }.into_any(),
+                    })
+                }}
+            </Suspense>
+            <A href="/discovery">"Adopt detected devices"</A>
+            " \u{b7} "
+            <A href="/events">"Open the event log"</A>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detected(mac: &str, name: Option<&str>) -> Event {
+        Event::new(
+            EventType::DeviceDetected,
+            None,
+            serde_json::json!({ "integration": "ble", "mac": mac, "name": name }),
+        )
+    }
+
+    #[test]
+    fn should_deduplicate_detected_devices_by_address() {
+        let events = vec![
+            detected("A4:C1:38:00:00:01", Some("LYWSD03MMC")),
+            Event::new(EventType::StateChanged, None, serde_json::json!({})),
+            detected("A4:C1:38:00:00:01", Some("LYWSD03MMC")),
+            detected("C4:7C:8D:00:00:02", None),
+        ];
+
+        let devices = detected_devices(&events);
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "LYWSD03MMC");
+        assert_eq!(devices[1].name, "Unnamed device");
+        assert_eq!(devices[1].integration, "ble");
+    }
+}
@@ -0,0 +1,122 @@
+//! Energy consumption panel for power and energy sensors.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use minihub_domain::energy::{EnergyPeriod, EnergyReport};
+
+use crate::api::fetch_energy;
+
+/// Periods offered by the selector.
+const PERIODS: [EnergyPeriod; 4] = [
+    EnergyPeriod::Hour,
+    EnergyPeriod::Day,
+    EnergyPeriod::Week,
+    EnergyPeriod::Month,
+];
+
+/// Label shown on the selector button.
+fn period_label(period: EnergyPeriod) -> &'static str {
+    match period {
+        EnergyPeriod::Hour => "Hourly",
+        EnergyPeriod::Day => "Daily",
+        EnergyPeriod::Week => "Weekly",
+        EnergyPeriod::Month => "Monthly",
+    }
+}
+
+/// Label of the bucket starting at `start`.
+fn bucket_label(period: EnergyPeriod, start: chrono::DateTime<chrono::Utc>) -> String {
+    let format = match period {
+        EnergyPeriod::Hour => "%b %d %H:00",
+        EnergyPeriod::Day => "%a %b %d",
+        EnergyPeriod::Week => "Week of %b %d",
+        EnergyPeriod::Month => "%B %Y",
+    };
+    start.format(format).to_string()
+}
+
+/// Consumption of an entity in kWh, one bar per hour, day, week or month,
+/// with a period selector. Bars are scaled to the largest bucket.
+#[component]
+pub fn EnergyPanel(entity_id: ReadSignal<String>) -> impl IntoView {
+    let (period, set_period) = signal(EnergyPeriod::Day);
+    let (report, set_report) = signal(None::<Result<EnergyReport, String>>);
+
+    Effect::new(move |_| {
+        let eid = entity_id.get();
+        let selected = period.get();
+        if eid.is_empty() {
+            return;
+        }
+        set_report.set(None);
+        spawn_local(async move {
+            let result = fetch_energy(&eid, selected)
+                .await
+                .map_err(|err| err.message);
+            set_report.set(Some(result));
+        });
+    });
+
+    view! {
+        <div class="energy-panel">
+            <h3>"Energy"</h3>
+
+            <div class="time-range-selector">
+                {PERIODS
+                    .into_iter()
+                    .map(|p| {
+                        view! {
+                            <button
+                                class=move || {
+                                    if period.get() == p { "btn btn-primary btn-sm" } else { "btn btn-secondary btn-sm" }
+                                }
+                                on:click=move |_| set_period.set(p)
+                            >
+                                {period_label(p)}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+
+            {move || match report.get() {
+                None => view! { <p>"Loading consumption..."</p> }.into_any(),
+                Some(Err(err)) => view! { <p class="error">"Energy error: " {err}</p> }.into_any(),
+                Some(Ok(report)) if report.buckets.is_empty() => view! {
+                    <p><em>"No consumption recorded yet."</em></p>
+                }
+                .into_any(),
+                Some(Ok(report)) => {
+                    let max = report
+                        .buckets
+                        .iter()
+                        .map(|bucket| bucket.energy_kwh)
+                        .fold(0.0_f64, f64::max);
+                    view! {
+                        <p class="energy-total">
+                            <strong>{format!("{:.2} kWh", report.total_kwh)}</strong>
+                        </p>
+                        <ul class="energy-bars">
+                            {report
+                                .buckets
+                                .into_iter()
+                                .rev()
+                                .map(|bucket| {
+                                    let width = if max > 0.0 { bucket.energy_kwh / max * 100.0 } else { 0.0 };
+                                    view! {
+                                        <li>
+                                            <span>{bucket_label(report.period, bucket.start)}</span>
+                                            <div class="energy-bar" style=format!("width: {width:.1}%")></div>
+                                            <span class="energy-value">{format!("{:.2} kWh", bucket.energy_kwh)}</span>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+                    }
+                    .into_any()
+                }
+            }}
+        </div>
+    }
+}
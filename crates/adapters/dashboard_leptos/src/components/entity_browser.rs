@@ -0,0 +1,488 @@
+//! Searchable, filterable and groupable view of the entity list.
+//!
+//! Everything happens client-side against the full list returned by the
+//! entities API, so filtering stays instant while typing.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use minihub_domain::area::Area;
+use minihub_domain::device::DeviceWithStatus;
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::id::DeviceId;
+
+use super::entity_table::{EntityTable, SortColumn};
+
+/// Device, integration and area an entity comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityOrigin {
+    /// Name of the device.
+    pub device: String,
+    /// Integration that registered the device, e.g. `"ble"`.
+    pub integration: String,
+    /// Name of the area of the device, if assigned.
+    pub area: Option<String>,
+}
+
+/// Origin of the entities of every device, keyed by device id.
+#[must_use]
+pub fn origins(devices: &[DeviceWithStatus], areas: &[Area]) -> HashMap<DeviceId, EntityOrigin> {
+    let area_names: HashMap<_, _> = areas.iter().map(|area| (area.id, &area.name)).collect();
+    devices
+        .iter()
+        .map(|item| {
+            let device = &item.device;
+            let origin = EntityOrigin {
+                device: device.name.clone(),
+                integration: device.integration.clone(),
+                area: device
+                    .area_id
+                    .and_then(|id| area_names.get(&id))
+                    .map(|name| (*name).clone()),
+            };
+            (device.id, origin)
+        })
+        .collect()
+}
+
+/// How entities are grouped into separate tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grouping {
+    /// A single table.
+    #[default]
+    None,
+    /// One table per device.
+    Device,
+    /// One table per area, plus one for unassigned entities.
+    Area,
+}
+
+impl Grouping {
+    /// Value of the option in the grouping select.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Device => "device",
+            Self::Area => "area",
+        }
+    }
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(Self::None),
+            "device" => Ok(Self::Device),
+            "area" => Ok(Self::Area),
+            other => Err(format!("unknown grouping: {other}")),
+        }
+    }
+}
+
+/// Attribute filtered by chips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facet {
+    Domain,
+    Integration,
+}
+
+/// A titled set of entities, `title` being `None` when ungrouped.
+#[derive(Debug, Clone)]
+pub struct EntityGroup {
+    pub title: Option<String>,
+    pub entities: Vec<Entity>,
+}
+
+/// Search, filters, grouping and sort order of the entity list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityQuery {
+    /// Case-insensitive text the entity id or friendly name must contain;
+    /// every entity when empty.
+    pub search: String,
+    /// Domains shown, e.g. `"sensor"`; every domain when empty.
+    pub domains: BTreeSet<String>,
+    /// Integrations shown; every integration when empty.
+    pub integrations: BTreeSet<String>,
+    pub grouping: Grouping,
+    pub sort: SortColumn,
+    pub descending: bool,
+}
+
+impl EntityQuery {
+    /// Whether `entity`, coming from `origin` when known, is shown.
+    #[must_use]
+    pub fn matches(&self, entity: &Entity, origin: Option<&EntityOrigin>) -> bool {
+        if !self.domains.is_empty() && !self.domains.contains(entity.domain()) {
+            return false;
+        }
+        if !self.integrations.is_empty()
+            && !origin.is_some_and(|origin| self.integrations.contains(&origin.integration))
+        {
+            return false;
+        }
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || entity.entity_id.to_lowercase().contains(&search)
+            || entity.friendly_name.to_lowercase().contains(&search)
+    }
+
+    /// Sort by `column`, flipping the direction when already sorted by it.
+    pub fn sort_by(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = false;
+        }
+    }
+
+    /// Values of `facet` shown.
+    #[must_use]
+    pub fn selected(&self, facet: Facet) -> &BTreeSet<String> {
+        match facet {
+            Facet::Domain => &self.domains,
+            Facet::Integration => &self.integrations,
+        }
+    }
+
+    /// Show or stop showing `value` of `facet`, as done by a filter chip.
+    pub fn toggle(&mut self, facet: Facet, value: &str) {
+        let selected = match facet {
+            Facet::Domain => &mut self.domains,
+            Facet::Integration => &mut self.integrations,
+        };
+        if !selected.remove(value) {
+            selected.insert(value.to_string());
+        }
+    }
+
+    /// The matching entities, sorted and split into groups.
+    ///
+    /// Groups are ordered by title, the one of entities without a device or
+    /// area coming last.
+    #[must_use]
+    pub fn apply(
+        &self,
+        entities: &[Entity],
+        origins: &HashMap<DeviceId, EntityOrigin>,
+    ) -> Vec<EntityGroup> {
+        let mut matching: Vec<_> = entities
+            .iter()
+            .filter(|entity| self.matches(entity, origins.get(&entity.device_id)))
+            .cloned()
+            .collect();
+        matching.sort_by(|a, b| {
+            let ordering = self.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let title = |entity: &Entity| {
+            let origin = origins.get(&entity.device_id);
+            match self.grouping {
+                Grouping::None => None,
+                Grouping::Device => origin.map(|origin| origin.device.clone()),
+                Grouping::Area => origin.and_then(|origin| origin.area.clone()),
+            }
+        };
+        let mut groups: Vec<EntityGroup> = Vec::new();
+        for entity in matching {
+            let title = title(&entity);
+            match groups.iter_mut().find(|group| group.title == title) {
+                Some(group) => group.entities.push(entity),
+                None => groups.push(EntityGroup {
+                    title,
+                    entities: vec![entity],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| match (&a.title, &b.title) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        groups
+    }
+
+    /// Ascending order of `a` and `b` along the sort column.
+    fn compare(&self, a: &Entity, b: &Entity) -> Ordering {
+        match self.sort {
+            SortColumn::EntityId => a.entity_id.cmp(&b.entity_id),
+            SortColumn::Name => a
+                .friendly_name
+                .to_lowercase()
+                .cmp(&b.friendly_name.to_lowercase()),
+            SortColumn::State => match (&a.state, &b.state) {
+                (EntityState::Numeric(a), EntityState::Numeric(b)) => a.total_cmp(b),
+                (EntityState::Numeric(_), _) => Ordering::Less,
+                (_, EntityState::Numeric(_)) => Ordering::Greater,
+                (a, b) => a.to_string().cmp(&b.to_string()),
+            },
+        }
+    }
+}
+
+/// Entity list with a search box, domain and integration filter chips,
+/// grouping by device or area, and sortable columns.
+#[component]
+pub fn EntityBrowser(
+    /// Every entity.
+    entities: Vec<Entity>,
+    /// Every device, to filter by integration and group by device.
+    devices: Vec<DeviceWithStatus>,
+    /// Every area, to group by area.
+    areas: Vec<Area>,
+    /// Search, filters and sort order, kept by the page across reloads.
+    query: RwSignal<EntityQuery>,
+) -> impl IntoView {
+    let origins = origins(&devices, &areas);
+    let domains: BTreeSet<String> = entities
+        .iter()
+        .map(|entity| entity.domain().to_string())
+        .collect();
+    let integrations: BTreeSet<String> = origins
+        .values()
+        .map(|origin| origin.integration.clone())
+        .collect();
+
+    let chip = move |facet: Facet, label: String| {
+        let value = label.clone();
+        let toggled = label.clone();
+        view! {
+            <button
+                class=move || {
+                    if query.with(|query| query.selected(facet).contains(&value)) {
+                        "btn btn-primary btn-sm"
+                    } else {
+                        "btn btn-secondary btn-sm"
+                    }
+                }
+                on:click=move |_| query.update(|query| query.toggle(facet, &toggled))
+            >
+                {label}
+            </button>
+        }
+    };
+    let domain_chips = domains
+        .into_iter()
+        .map(|domain| chip(Facet::Domain, domain))
+        .collect_view();
+    let integration_chips = integrations
+        .into_iter()
+        .map(|integration| chip(Facet::Integration, integration))
+        .collect_view();
+
+    let on_sort = Callback::new(move |column| query.update(|query| query.sort_by(column)));
+    let groups = move || {
+        query.with(|query| {
+            let sorted = (query.sort, query.descending);
+            query
+                .apply(&entities, &origins)
+                .into_iter()
+                .map(|group| {
+                    let title = group.title.map(|title| view! { <h2>{title}</h2> });
+                    view! {
+                        {title}
+                        <EntityTable entities=group.entities sorted=sorted on_sort=on_sort/>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <div class="event-filters">
+            <input
+                type="search"
+                placeholder="Search by entity ID or name"
+                prop:value=move || query.with(|query| query.search.clone())
+                on:input=move |ev| {
+                    let search = event_target_value(&ev);
+                    query.update(|query| query.search = search);
+                }
+            />
+            <select on:change=move |ev| {
+                if let Ok(grouping) = event_target_value(&ev).parse() {
+                    query.update(|query| query.grouping = grouping);
+                }
+            }>
+                {[Grouping::None, Grouping::Device, Grouping::Area]
+                    .into_iter()
+                    .map(|grouping| view! {
+                        <option
+                            value=grouping.as_str()
+                            selected=move || query.with(|query| query.grouping == grouping)
+                        >
+                            {match grouping {
+                                Grouping::None => "No grouping",
+                                Grouping::Device => "Group by device",
+                                Grouping::Area => "Group by area",
+                            }}
+                        </option>
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+        <div class="filter-chips">
+            <span class="hint">"Domain"</span>
+            {domain_chips}
+        </div>
+        <div class="filter-chips">
+            <span class="hint">"Integration"</span>
+            {integration_chips}
+        </div>
+        {groups}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::device::{Device, DeviceStatus};
+
+    use super::*;
+
+    fn device(name: &str, integration: &str) -> DeviceWithStatus {
+        DeviceWithStatus {
+            device: Device::builder()
+                .name(name)
+                .integration(integration)
+                .unique_id(name)
+                .build()
+                .unwrap(),
+            status: DeviceStatus::Unknown,
+        }
+    }
+
+    fn entity(
+        device: &DeviceWithStatus,
+        entity_id: &str,
+        name: &str,
+        state: EntityState,
+    ) -> Entity {
+        Entity::builder()
+            .device_id(device.device.id)
+            .entity_id(entity_id)
+            .friendly_name(name)
+            .state(state)
+            .build()
+            .unwrap()
+    }
+
+    fn ids(groups: &[EntityGroup]) -> Vec<Vec<&str>> {
+        groups
+            .iter()
+            .map(|group| {
+                group
+                    .entities
+                    .iter()
+                    .map(|entity| entity.entity_id.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_match_search_on_entity_id_or_name_ignoring_case() {
+        let sensor = device("Sensor", "ble");
+        let entity = entity(
+            &sensor,
+            "sensor.kitchen_temp",
+            "Fridge",
+            EntityState::Unknown,
+        );
+        let query = |search: &str| EntityQuery {
+            search: search.to_string(),
+            ..EntityQuery::default()
+        };
+
+        assert!(query("KITCHEN").matches(&entity, None));
+        assert!(query("fridge").matches(&entity, None));
+        assert!(query("  ").matches(&entity, None));
+        assert!(!query("oven").matches(&entity, None));
+    }
+
+    #[test]
+    fn should_filter_by_domain_and_integration() {
+        let ble = device("Sensor", "ble");
+        let virt = device("Helper", "virtual");
+        let devices = [ble.clone(), virt.clone()];
+        let origins = origins(&devices, &[]);
+        let entities = [
+            entity(&ble, "sensor.temp", "Temp", EntityState::Unknown),
+            entity(&virt, "sensor.virtual", "Virtual", EntityState::Unknown),
+            entity(&virt, "light.desk", "Desk", EntityState::On),
+        ];
+
+        let mut query = EntityQuery::default();
+        query.toggle(Facet::Domain, "sensor");
+        assert_eq!(
+            ids(&query.apply(&entities, &origins)),
+            [["sensor.temp", "sensor.virtual"]]
+        );
+
+        query.toggle(Facet::Integration, "ble");
+        assert_eq!(ids(&query.apply(&entities, &origins)), [["sensor.temp"]]);
+
+        query.toggle(Facet::Domain, "sensor");
+        query.toggle(Facet::Integration, "ble");
+        assert_eq!(query, EntityQuery::default());
+    }
+
+    #[test]
+    fn should_sort_numeric_states_by_value_and_flip_direction() {
+        let sensor = device("Sensor", "ble");
+        let entities = [
+            entity(&sensor, "sensor.a", "A", EntityState::Numeric(9.5)),
+            entity(&sensor, "sensor.b", "B", EntityState::Unavailable),
+            entity(&sensor, "sensor.c", "C", EntityState::Numeric(10.0)),
+        ];
+        let origins = HashMap::new();
+
+        let mut query = EntityQuery::default();
+        query.sort_by(SortColumn::State);
+        assert_eq!(
+            ids(&query.apply(&entities, &origins)),
+            [["sensor.a", "sensor.c", "sensor.b"]]
+        );
+
+        query.sort_by(SortColumn::State);
+        assert!(query.descending);
+        assert_eq!(
+            ids(&query.apply(&entities, &origins)),
+            [["sensor.b", "sensor.c", "sensor.a"]]
+        );
+    }
+
+    #[test]
+    fn should_group_by_area_with_unassigned_last() {
+        let kitchen = Area::builder().name("Kitchen").build().unwrap();
+        let mut oven = device("Oven", "ble");
+        oven.device.area_id = Some(kitchen.id);
+        let lamp = device("Lamp", "ble");
+        let devices = [oven.clone(), lamp.clone()];
+        let origins = origins(&devices, &[kitchen]);
+        let entities = [
+            entity(&lamp, "light.lamp", "Lamp", EntityState::Off),
+            entity(&oven, "sensor.oven", "Oven", EntityState::Unknown),
+        ];
+
+        let query = EntityQuery {
+            grouping: Grouping::Area,
+            ..EntityQuery::default()
+        };
+        let groups = query.apply(&entities, &origins);
+
+        assert_eq!(groups[0].title.as_deref(), Some("Kitchen"));
+        assert_eq!(groups[1].title, None);
+        assert_eq!(ids(&groups), [["sensor.oven"], ["light.lamp"]]);
+    }
+}
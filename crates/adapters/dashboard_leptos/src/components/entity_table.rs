@@ -4,12 +4,59 @@ use leptos::prelude::*;
 use leptos_router::components::A;
 use minihub_domain::entity::{Entity, EntityState};
 
+/// Column an entity table can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortColumn {
+    #[default]
+    EntityId,
+    Name,
+    State,
+}
+
+impl SortColumn {
+    /// Header label of the column.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::EntityId => "Entity ID",
+            Self::Name => "Name",
+            Self::State => "State",
+        }
+    }
+}
+
 /// A table displaying a list of entities.
+///
+/// When `on_sort` is set, column headers are clickable and report the
+/// column to sort by; `sorted` marks the current one with an arrow.
 #[component]
 pub fn EntityTable(
     /// The list of entities to display.
     entities: Vec<Entity>,
+    /// Current sort column and whether it is descending.
+    #[prop(optional)]
+    sorted: Option<(SortColumn, bool)>,
+    /// Called with the header clicked.
+    #[prop(optional, into)]
+    on_sort: Option<Callback<SortColumn>>,
 ) -> impl IntoView {
+    let header = move |column: SortColumn| {
+        let arrow = match sorted {
+            Some((current, false)) if current == column => " \u{25B2}",
+            Some((current, true)) if current == column => " \u{25BC}",
+            _ => "",
+        };
+        match on_sort {
+            Some(on_sort) => view! {
+                <th class="sortable" on:click=move |_| on_sort.run(column)>
+                    {column.label()}{arrow}
+                </th>
+            }
+            .into_any(),
+            None => view! { <th>{column.label()}</th> }.into_any(),
+        }
+    };
+
     if entities.is_empty() {
         view! {
             <p>"No entities found."</p>
@@ -20,9 +67,9 @@ pub fn EntityTable(
             <table>
                 <thead>
                     <tr>
-                        <th>"Entity ID"</th>
-                        <th>"Name"</th>
-                        <th>"State"</th>
+                        {header(SortColumn::EntityId)}
+                        {header(SortColumn::Name)}
+                        {header(SortColumn::State)}
                     </tr>
                 </thead>
                 <tbody>
@@ -75,6 +122,7 @@ fn StateBadge(
         EntityState::Off => "badge-off",
         EntityState::Unknown => "badge-unknown",
         EntityState::Unavailable => "badge-unavailable",
+        EntityState::Numeric(_) | EntityState::Text(_) => "badge-value",
     };
     let state_str = state.to_string();
 
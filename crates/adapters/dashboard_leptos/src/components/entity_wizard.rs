@@ -0,0 +1,453 @@
+//! Guided form creating a manual sensor or an input helper entity.
+
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
+use minihub_domain::entity::Entity;
+use minihub_domain::id::AreaId;
+use minihub_domain::input_helper::InputHelper;
+
+use crate::api;
+
+/// Wizard steps, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Kind,
+    Configure,
+    Details,
+}
+
+/// Kinds of entity the wizard can create.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Sensor,
+    Boolean,
+    Number,
+    Select,
+}
+
+/// Kinds offered on the first step, with their labels.
+const KINDS: &[(Kind, &str, &str)] = &[
+    (Kind::Sensor, "sensor", "Manual sensor"),
+    (Kind::Boolean, "input_boolean", "Toggle (input_boolean)"),
+    (Kind::Number, "input_number", "Number (input_number)"),
+    (Kind::Select, "input_select", "Dropdown (input_select)"),
+];
+
+/// Integration owning the devices of manual sensors.
+const MANUAL_INTEGRATION: &str = "manual";
+
+fn kind_from_value(value: &str) -> Kind {
+    KINDS
+        .iter()
+        .find(|(_, key, _)| *key == value)
+        .map_or(Kind::Sensor, |(kind, _, _)| *kind)
+}
+
+/// Lowercase `name` and join its words with `_`, e.g. `"Pool temp"` →
+/// `"pool_temp"`.
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Split the select options typed one per line or comma-separated.
+fn parse_options(text: &str) -> Vec<String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_number(label: &str, text: &str) -> Result<f64, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("{label} must be a number"))
+}
+
+/// Build the helper constraints of `kind` from the configure step fields.
+///
+/// Returns `None` for manual sensors, which have no constraints.
+fn build_helper(
+    kind: Kind,
+    min: &str,
+    max: &str,
+    step: &str,
+    options: &str,
+) -> Result<Option<InputHelper>, String> {
+    let helper = match kind {
+        Kind::Sensor => return Ok(None),
+        Kind::Boolean => InputHelper::Boolean,
+        Kind::Number => InputHelper::Number {
+            min: parse_number("Minimum", min)?,
+            max: parse_number("Maximum", max)?,
+            step: parse_number("Step", step)?,
+        },
+        Kind::Select => InputHelper::Select {
+            options: parse_options(options),
+        },
+    };
+    helper.validate().map_err(ToString::to_string)?;
+    Ok(Some(helper))
+}
+
+/// The initial value sent along with a helper, if any was chosen.
+fn initial_value(
+    helper: &InputHelper,
+    number: &str,
+    switched_on: bool,
+    option: &str,
+) -> Result<Option<serde_json::Value>, String> {
+    let value = match helper {
+        InputHelper::Boolean => serde_json::Value::Bool(switched_on),
+        InputHelper::Number { .. } if number.trim().is_empty() => return Ok(None),
+        InputHelper::Number { .. } => {
+            serde_json::Value::from(parse_number("Initial value", number)?)
+        }
+        InputHelper::Select { .. } if option.is_empty() => return Ok(None),
+        InputHelper::Select { .. } => serde_json::Value::String(option.to_string()),
+    };
+    helper.parse_value(&value).map_err(ToString::to_string)?;
+    Ok(Some(value))
+}
+
+/// Create a device for the manual sensor `name`, then its entity.
+async fn create_sensor(name: String, area_id: Option<AreaId>) -> Result<Entity, api::ApiError> {
+    let slug = slugify(&name);
+    let device = api::create_device(
+        name.clone(),
+        MANUAL_INTEGRATION.to_string(),
+        slug.clone(),
+        area_id,
+    )
+    .await?;
+    api::create_entity(device.id, format!("sensor.{slug}"), name).await
+}
+
+/// A three-step wizard — kind, constraints, name and area — creating the
+/// entity and opening its detail page.
+#[component]
+pub fn EntityWizard(
+    /// Called when the wizard is dismissed without creating anything.
+    #[prop(into)]
+    on_cancel: Callback<()>,
+) -> impl IntoView {
+    let areas = LocalResource::new(api::fetch_areas);
+    let navigate = use_navigate();
+
+    let (step, set_step) = signal(Step::Kind);
+    let (kind, set_kind) = signal(Kind::Sensor);
+    let (min, set_min) = signal("0".to_string());
+    let (max, set_max) = signal("100".to_string());
+    let (step_size, set_step_size) = signal("1".to_string());
+    let (initial_number, set_initial_number) = signal(String::new());
+    let (switched_on, set_switched_on) = signal(false);
+    let (options_text, set_options_text) = signal(String::new());
+    let (initial_option, set_initial_option) = signal(String::new());
+    let (name, set_name) = signal(String::new());
+    let (area_id, set_area_id) = signal(String::new());
+    let (is_saving, set_is_saving) = signal(false);
+    let (error_message, set_error_message) = signal::<Option<String>>(None);
+
+    let helper = move || {
+        build_helper(
+            kind.get(),
+            &min.get(),
+            &max.get(),
+            &step_size.get(),
+            &options_text.get(),
+        )
+    };
+
+    let go_to_details = move |_| match helper() {
+        Ok(_) => {
+            set_error_message.set(None);
+            set_step.set(Step::Details);
+        }
+        Err(msg) => set_error_message.set(Some(msg)),
+    };
+
+    let create = move |_| {
+        let name = name.get_untracked().trim().to_string();
+        let request = match helper() {
+            Ok(Some(helper)) => initial_value(
+                &helper,
+                &initial_number.get_untracked(),
+                switched_on.get_untracked(),
+                &initial_option.get_untracked(),
+            )
+            .map(|initial| Some((helper, initial))),
+            Ok(None) => Ok(None),
+            Err(msg) => Err(msg),
+        };
+        let request = match request {
+            Ok(request) => request,
+            Err(msg) => {
+                set_error_message.set(Some(msg));
+                return;
+            }
+        };
+        let area_id = AreaId::from_str(&area_id.get_untracked()).ok();
+        let navigate = navigate.clone();
+
+        set_is_saving.set(true);
+        set_error_message.set(None);
+        spawn_local(async move {
+            let created = match request {
+                Some((helper, initial)) => api::create_input_helper(name, helper, initial).await,
+                None => create_sensor(name, area_id).await,
+            };
+            match created {
+                Ok(entity) => navigate(&format!("/entities/{}", entity.id), Default::default()),
+                Err(err) => set_error_message.set(Some(err.to_string())),
+            }
+            set_is_saving.set(false);
+        });
+    };
+
+    view! {
+        <div class="card automation-wizard entity-wizard">
+            <h2>"New entity"</h2>
+            {move || match step.get() {
+                Step::Kind => view! {
+                    <p>"1. What kind of entity?"</p>
+                    <select on:change=move |ev| set_kind.set(kind_from_value(&event_target_value(&ev)))>
+                        {KINDS.iter().map(|(value, key, label)| view! {
+                            <option value=*key selected=move || kind.get() == *value>{*label}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <div class="wizard-buttons">
+                        <button class="btn btn-secondary" on:click=move |_| on_cancel.run(())>
+                            "Cancel"
+                        </button>
+                        <button class="btn btn-primary" on:click=move |_| set_step.set(Step::Configure)>
+                            "Next"
+                        </button>
+                    </div>
+                }.into_any(),
+                Step::Configure => view! {
+                    <p>"2. Configure it"</p>
+                    {move || configure_fields(
+                        kind.get(),
+                        (min, set_min),
+                        (max, set_max),
+                        (step_size, set_step_size),
+                        (initial_number, set_initial_number),
+                        (switched_on, set_switched_on),
+                        (options_text, set_options_text),
+                        (initial_option, set_initial_option),
+                    )}
+                    <div class="wizard-buttons">
+                        <button class="btn btn-secondary" on:click=move |_| set_step.set(Step::Kind)>
+                            "Back"
+                        </button>
+                        <button class="btn btn-primary" on:click=go_to_details>
+                            "Next"
+                        </button>
+                    </div>
+                }.into_any(),
+                Step::Details => view! {
+                    <p>"3. Name it"</p>
+                    <input
+                        type="text"
+                        placeholder="e.g. Pool temperature"
+                        prop:value=move || name.get()
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                    />
+                    {move || if kind.get() == Kind::Sensor {
+                        view! {
+                            <select on:change=move |ev| set_area_id.set(event_target_value(&ev))>
+                                <option value="" selected=move || area_id.get().is_empty()>"No area"</option>
+                                {move || areas.read().as_ref().and_then(|result| result.as_ref().ok()).map(|list| {
+                                    list.iter().map(|area| {
+                                        let id = area.id.to_string();
+                                        let is_selected = id == area_id.get_untracked();
+                                        view! { <option value=id selected=is_selected>{area.name.clone()}</option> }
+                                    }).collect::<Vec<_>>()
+                                })}
+                            </select>
+                        }.into_any()
+                    } else {
+                        view! {
+                            <p class="hint">"Helpers live on the hub device and have no area."</p>
+                        }.into_any()
+                    }}
+                    <div class="wizard-buttons">
+                        <button class="btn btn-secondary" on:click=move |_| set_step.set(Step::Configure)>
+                            "Back"
+                        </button>
+                        <button
+                            class="btn btn-primary"
+                            disabled=move || is_saving.get() || slugify(&name.get()).is_empty()
+                            on:click=create.clone()
+                        >
+                            {move || if is_saving.get() { "Creating\u{2026}" } else { "Create entity" }}
+                        </button>
+                    </div>
+                }.into_any(),
+            }}
+            {move || error_message.get().map(|msg| view! {
+                <p class="error">{msg}</p>
+            })}
+        </div>
+    }
+}
+
+type Field<T> = (ReadSignal<T>, WriteSignal<T>);
+
+/// Render the constraint fields of `kind`.
+#[allow(clippy::too_many_arguments)]
+fn configure_fields(
+    kind: Kind,
+    min: Field<String>,
+    max: Field<String>,
+    step: Field<String>,
+    initial_number: Field<String>,
+    switched_on: Field<bool>,
+    options: Field<String>,
+    initial_option: Field<String>,
+) -> AnyView {
+    match kind {
+        Kind::Sensor => view! {
+            <p class="hint">
+                "Manual sensors get a device of their own and are fed through the API."
+            </p>
+        }
+        .into_any(),
+        Kind::Boolean => view! {
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || switched_on.0.get()
+                    on:change=move |ev| switched_on.1.set(event_target_checked(&ev))
+                />
+                " Initially on"
+            </label>
+        }
+        .into_any(),
+        Kind::Number => view! {
+            {number_field("Minimum", min)}
+            {number_field("Maximum", max)}
+            {number_field("Step", step)}
+            {number_field("Initial value (defaults to the minimum)", initial_number)}
+        }
+        .into_any(),
+        Kind::Select => view! {
+            <label>
+                "Options, one per line"
+                <textarea
+                    prop:value=move || options.0.get()
+                    on:input=move |ev| options.1.set(event_target_value(&ev))
+                ></textarea>
+            </label>
+            <label>
+                "Initial option"
+                <select on:change=move |ev| initial_option.1.set(event_target_value(&ev))>
+                    <option value="" selected=move || initial_option.0.get().is_empty()>"First option"</option>
+                    {move || parse_options(&options.0.get()).into_iter().map(|option| {
+                        let is_selected = option == initial_option.0.get_untracked();
+                        view! { <option value=option.clone() selected=is_selected>{option}</option> }
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+        }
+        .into_any(),
+    }
+}
+
+fn number_field(label: &'static str, (value, set_value): Field<String>) -> impl IntoView {
+    view! {
+        <label>
+            {label}
+            <input
+                type="number"
+                prop:value=move || value.get()
+                on:input=move |ev| set_value.set(event_target_value(&ev))
+            />
+        </label>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_slugify_name_when_it_has_spaces_and_punctuation() {
+        assert_eq!(slugify("Pool temp (outside)"), "pool_temp_outside");
+        assert_eq!(slugify("  !! "), "");
+    }
+
+    #[test]
+    fn should_parse_options_when_separated_by_lines_or_commas() {
+        assert_eq!(
+            parse_options("eco, comfort\n\n boost \n"),
+            vec!["eco", "comfort", "boost"]
+        );
+    }
+
+    #[test]
+    fn should_return_no_helper_when_kind_is_sensor() {
+        assert_eq!(build_helper(Kind::Sensor, "", "", "", ""), Ok(None));
+    }
+
+    #[test]
+    fn should_build_number_helper_when_fields_are_valid() {
+        assert_eq!(
+            build_helper(Kind::Number, "10", "30", "0.5", ""),
+            Ok(Some(InputHelper::Number {
+                min: 10.0,
+                max: 30.0,
+                step: 0.5,
+            }))
+        );
+    }
+
+    #[test]
+    fn should_reject_number_helper_when_range_is_empty_or_unparsable() {
+        assert!(build_helper(Kind::Number, "30", "10", "1", "").is_err());
+        assert_eq!(
+            build_helper(Kind::Number, "abc", "10", "1", ""),
+            Err("Minimum must be a number".to_string())
+        );
+    }
+
+    #[test]
+    fn should_reject_select_helper_when_options_are_missing() {
+        assert!(build_helper(Kind::Select, "", "", "", " , \n").is_err());
+    }
+
+    #[test]
+    fn should_skip_initial_value_when_left_empty() {
+        let helper = InputHelper::Number {
+            min: 0.0,
+            max: 10.0,
+            step: 1.0,
+        };
+        assert_eq!(initial_value(&helper, " ", false, ""), Ok(None));
+    }
+
+    #[test]
+    fn should_reject_initial_value_when_outside_constraints() {
+        let helper = InputHelper::Number {
+            min: 0.0,
+            max: 10.0,
+            step: 1.0,
+        };
+        assert!(initial_value(&helper, "11", false, "").is_err());
+    }
+
+    #[test]
+    fn should_send_switch_position_when_helper_is_boolean() {
+        assert_eq!(
+            initial_value(&InputHelper::Boolean, "", true, ""),
+            Ok(Some(serde_json::Value::Bool(true)))
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Live tail of the event stream, filtered client-side.
+//!
+//! Incoming events are kept in a bounded ring buffer so the page stays
+//! responsive after hours of uptime: once full, the oldest events are
+//! dropped as new ones arrive.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use leptos::prelude::*;
+use minihub_domain::entity::Entity;
+use minihub_domain::event::Event;
+use minihub_domain::id::EntityId;
+
+use super::event_table::EventRow;
+
+/// Events kept by the live tail.
+const TAIL_CAPACITY: usize = 500;
+
+/// The most recent events, newest first, holding at most `capacity`.
+#[derive(Debug, Clone)]
+pub struct TailBuffer {
+    events: VecDeque<Event>,
+    capacity: usize,
+}
+
+impl TailBuffer {
+    /// Create an empty buffer holding at most `capacity` events.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add `event` as the newest one, dropping the oldest when full.
+    pub fn push(&mut self, event: Event) {
+        if self.events.len() == self.capacity {
+            self.events.pop_back();
+        }
+        self.events.push_front(event);
+    }
+
+    /// Move every event of `other` into this buffer, oldest first.
+    pub fn append(&mut self, other: &mut Self) {
+        while let Some(event) = other.events.pop_back() {
+            self.push(event);
+        }
+    }
+
+    /// Events, newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    /// Number of events held.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no event is held.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drop every event.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+/// Client-side filter of the live tail.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TailFilter {
+    /// Event types shown; every type when empty.
+    pub event_types: BTreeSet<String>,
+    /// Case-insensitive text the entity id, e.g. `light.kitchen`, or the
+    /// entity UUID must contain; every event when empty.
+    pub entity_search: String,
+}
+
+impl TailFilter {
+    /// Whether `event` is shown, `entity_name` being the entity id of the
+    /// entity it is about, when known.
+    #[must_use]
+    pub fn matches(&self, event: &Event, entity_name: Option<&str>) -> bool {
+        if !self.event_types.is_empty() && !self.event_types.contains(event.event_type.as_str()) {
+            return false;
+        }
+        let search = self.entity_search.trim().to_lowercase();
+        if search.is_empty() {
+            return true;
+        }
+        let Some(entity_id) = event.entity_id else {
+            return false;
+        };
+        entity_id.to_string().contains(&search)
+            || entity_name.is_some_and(|name| name.to_lowercase().contains(&search))
+    }
+}
+
+/// Live tail of the events received over SSE, with type and entity filters
+/// and a pause button.
+///
+/// While paused, incoming events are held back, bounded like the tail, and
+/// shown on resume.
+#[component]
+pub fn EventLiveTail(
+    /// Last event received over SSE.
+    live_event: ReadSignal<Option<Event>>,
+    /// Known entities, to search events by entity id.
+    entities: ReadSignal<Vec<Entity>>,
+    /// Event types offered by the type filter.
+    event_types: &'static [&'static str],
+) -> impl IntoView {
+    let buffer = RwSignal::new(TailBuffer::new(TAIL_CAPACITY));
+    let held = RwSignal::new(TailBuffer::new(TAIL_CAPACITY));
+    let paused = RwSignal::new(false);
+    let filter = RwSignal::new(TailFilter::default());
+
+    Effect::new(move |_| {
+        let Some(event) = live_event.get() else {
+            return;
+        };
+        if paused.get_untracked() {
+            held.update(|held| held.push(event));
+        } else {
+            buffer.update(|buffer| buffer.push(event));
+        }
+    });
+
+    let names = Memo::new(move |_| {
+        entities
+            .get()
+            .into_iter()
+            .map(|entity| (entity.id, entity.entity_id))
+            .collect::<HashMap<EntityId, String>>()
+    });
+    let visible = move || {
+        let filter = filter.get();
+        names.with(|names| {
+            buffer.with(|buffer| {
+                buffer
+                    .iter()
+                    .filter(|event| {
+                        let name = event
+                            .entity_id
+                            .and_then(|id| names.get(&id))
+                            .map(String::as_str);
+                        filter.matches(event, name)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+        })
+    };
+
+    let toggle_pause = move |_| {
+        if paused.get_untracked() {
+            held.update(|held| buffer.update(|buffer| buffer.append(held)));
+        }
+        paused.update(|paused| *paused = !*paused);
+    };
+
+    view! {
+        <div class="event-filters">
+            <input
+                type="search"
+                placeholder="Search entity"
+                prop:value=move || filter.with(|filter| filter.entity_search.clone())
+                on:input=move |ev| {
+                    let search = event_target_value(&ev);
+                    filter.update(|filter| filter.entity_search = search);
+                }
+            />
+            <button class="btn btn-secondary" on:click=toggle_pause>
+                {move || {
+                    if paused.get() {
+                        format!("Resume ({} new)", held.with(TailBuffer::len))
+                    } else {
+                        "Pause".to_string()
+                    }
+                }}
+            </button>
+            <button class="btn btn-secondary" on:click=move |_| buffer.update(TailBuffer::clear)>
+                "Clear"
+            </button>
+        </div>
+        <div class="event-type-filters">
+            {event_types
+                .iter()
+                .map(|event_type| view! {
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || filter.with(|filter| filter.event_types.contains(*event_type))
+                            on:change=move |ev| {
+                                let checked = event_target_checked(&ev);
+                                filter.update(|filter| {
+                                    if checked {
+                                        filter.event_types.insert((*event_type).to_string());
+                                    } else {
+                                        filter.event_types.remove(*event_type);
+                                    }
+                                });
+                            }
+                        />
+                        {*event_type}
+                    </label>
+                })
+                .collect_view()}
+        </div>
+        <p class="hint">
+            {move || format!(
+                "Newest events first \u{2014} the last {TAIL_CAPACITY} received are kept ({} so far)",
+                buffer.with(TailBuffer::len),
+            )}
+        </p>
+        <Show
+            when=move || !visible().is_empty()
+            fallback=|| view! { <p>"Waiting for matching events\u{2026}"</p> }
+        >
+            <table>
+                <thead>
+                    <tr>
+                        <th>"Timestamp"</th>
+                        <th>"Event Type"</th>
+                        <th>"Entity"</th>
+                        <th>"Data"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=visible
+                        key=|event| event.id
+                        let(event)
+                    >
+                        <EventRow event/>
+                    </For>
+                </tbody>
+            </table>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::event::EventType;
+
+    use super::*;
+
+    fn event(event_type: EventType, entity_id: Option<EntityId>) -> Event {
+        Event::new(event_type, entity_id, serde_json::json!({}))
+    }
+
+    #[test]
+    fn should_drop_oldest_events_when_buffer_is_full() {
+        let mut buffer = TailBuffer::new(2);
+        let events: Vec<Event> = (0..3)
+            .map(|_| event(EventType::StateChanged, None))
+            .collect();
+
+        for event in &events {
+            buffer.push(event.clone());
+        }
+
+        let ids: Vec<_> = buffer.iter().map(|event| event.id).collect();
+        assert_eq!(ids, vec![events[2].id, events[1].id]);
+    }
+
+    #[test]
+    fn should_append_held_events_in_arrival_order() {
+        let mut buffer = TailBuffer::new(10);
+        let mut held = TailBuffer::new(10);
+        let first = event(EventType::StateChanged, None);
+        let second = event(EventType::StateChanged, None);
+        buffer.push(first.clone());
+        held.push(second.clone());
+
+        buffer.append(&mut held);
+
+        let ids: Vec<_> = buffer.iter().map(|event| event.id).collect();
+        assert_eq!(ids, vec![second.id, first.id]);
+        assert!(held.is_empty());
+    }
+
+    #[test]
+    fn should_match_every_event_when_filter_is_empty() {
+        let filter = TailFilter::default();
+
+        assert!(filter.matches(&event(EventType::DeviceDetected, None), None));
+    }
+
+    #[test]
+    fn should_match_only_selected_event_types() {
+        let filter = TailFilter {
+            event_types: BTreeSet::from(["state_changed".to_string()]),
+            ..TailFilter::default()
+        };
+
+        assert!(filter.matches(&event(EventType::StateChanged, None), None));
+        assert!(!filter.matches(&event(EventType::DeviceDetected, None), None));
+    }
+
+    #[test]
+    fn should_match_entity_search_against_entity_id_case_insensitively() {
+        let filter = TailFilter {
+            entity_search: "Kitchen".to_string(),
+            ..TailFilter::default()
+        };
+        let about_entity = event(EventType::StateChanged, Some(EntityId::new()));
+
+        assert!(filter.matches(&about_entity, Some("light.kitchen")));
+        assert!(!filter.matches(&about_entity, Some("light.bedroom")));
+        assert!(!filter.matches(&event(EventType::StateChanged, None), None));
+    }
+}
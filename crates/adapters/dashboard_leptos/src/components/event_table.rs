@@ -1,6 +1,7 @@
 //! Event table component for displaying a list of events.
 
 use leptos::prelude::*;
+use leptos_router::components::A;
 use minihub_domain::event::Event;
 
 /// A table displaying a list of events.
@@ -40,10 +41,11 @@ pub fn EventTable(
 
 /// A single row in the event table.
 #[component]
-fn EventRow(
+pub fn EventRow(
     /// The event to display.
     event: Event,
 ) -> impl IntoView {
+    let href = format!("/events/{}", event.id);
     let timestamp = event.timestamp.to_string();
     let event_type = format!("{:?}", event.event_type);
     let entity = event.entity_id.map_or("—".to_string(), |e| e.to_string());
@@ -51,7 +53,7 @@ fn EventRow(
 
     view! {
         <tr>
-            <td>{timestamp}</td>
+            <td><A href=href>{timestamp}</A></td>
             <td>{event_type}</td>
             <td>{entity}</td>
             <td>
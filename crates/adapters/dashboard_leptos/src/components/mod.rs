@@ -1,27 +1,47 @@
-mod area_table;
+mod area_board;
+mod automation_editor;
 mod automation_table;
+mod automation_wizard;
 mod chart;
+mod connection_banner;
 mod device_table;
+mod empty_state;
+mod energy_panel;
+mod entity_browser;
 mod entity_table;
+mod entity_wizard;
+mod event_live_tail;
 mod event_table;
 mod loading;
 mod nav;
+mod pinned_cards;
 mod plant_card;
 pub(crate) mod sensor_card;
 mod stat_card;
 mod theme_toggle;
 mod toast;
+mod token_prompt;
 
-pub use area_table::AreaTable;
+pub use area_board::AreaBoard;
+pub use automation_editor::AutomationEditor;
 pub use automation_table::AutomationTable;
-pub use chart::HistoryChart;
-pub use device_table::DeviceTable;
-pub use entity_table::EntityTable;
+pub use automation_wizard::AutomationWizard;
+pub use chart::{HistoryChart, StateChart};
+pub use connection_banner::ConnectionBanner;
+pub use device_table::{DeviceStatusBadge, DeviceTable};
+pub use empty_state::{DevicesEmptyState, EntitiesEmptyState};
+pub use energy_panel::EnergyPanel;
+pub use entity_browser::{EntityBrowser, EntityQuery};
+pub use entity_table::{EntityTable, SortColumn};
+pub use entity_wizard::EntityWizard;
+pub use event_live_tail::EventLiveTail;
 pub use event_table::EventTable;
 pub use loading::Loading;
 pub use nav::Nav;
+pub use pinned_cards::PinnedCards;
 pub use plant_card::PlantCardGrid;
 pub use sensor_card::SensorCardGrid;
 pub use stat_card::StatCard;
 pub use theme_toggle::ThemeToggle;
 pub use toast::{ToastContainer, use_toasts};
+pub use token_prompt::TokenPrompt;
@@ -11,6 +11,7 @@ pub fn Nav() -> impl IntoView {
             <ul>
                 <li><A href="/">"Home"</A></li>
                 <li><A href="/devices">"Devices"</A></li>
+                <li><A href="/discovery">"Discovery"</A></li>
                 <li><A href="/entities">"Entities"</A></li>
                 <li><A href="/areas">"Areas"</A></li>
                 <li><A href="/events">"Events"</A></li>
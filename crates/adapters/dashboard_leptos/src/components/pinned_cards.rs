@@ -0,0 +1,398 @@
+//! Cards pinned to the home page: entity states, history charts and area
+//! summaries, in the order chosen by the user.
+//!
+//! The layout is stored server-side at `/api/dashboard_config`, so it
+//! survives restarts and is shared between browsers.
+
+use std::str::FromStr;
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::components::A;
+use minihub_domain::area::Area;
+use minihub_domain::dashboard::{Card, DashboardConfig, MAX_CHART_HOURS};
+use minihub_domain::device::DeviceWithStatus;
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::id::{AreaId, EntityId};
+
+use super::automation_editor::row_buttons;
+use crate::api;
+use crate::components::{StateChart, use_toasts};
+
+/// Name of the entity `id`, or a placeholder once it was removed.
+fn entity_name(entities: &[Entity], id: EntityId) -> String {
+    entities.iter().find(|entity| entity.id == id).map_or_else(
+        || "Removed entity".to_owned(),
+        |entity| entity.friendly_name.clone(),
+    )
+}
+
+/// Name of the area `id`, or a placeholder once it was removed.
+fn area_name(areas: &[Area], id: AreaId) -> String {
+    areas
+        .iter()
+        .find(|area| area.id == id)
+        .map_or_else(|| "Removed area".to_owned(), |area| area.name.clone())
+}
+
+/// One-line description of `card`, shown while editing the layout.
+fn describe(card: &Card, entities: &[Entity], areas: &[Area]) -> String {
+    match card {
+        Card::Entity { entity_id } => {
+            format!("Entity \u{b7} {}", entity_name(entities, *entity_id))
+        }
+        Card::Chart { entity_id, hours } => {
+            format!(
+                "Chart \u{b7} {} \u{b7} {hours}h",
+                entity_name(entities, *entity_id)
+            )
+        }
+        Card::Area { area_id } => format!("Area \u{b7} {}", area_name(areas, *area_id)),
+    }
+}
+
+/// Entities in `area_id`, through the area of their device.
+fn entities_in_area(
+    entities: &[Entity],
+    devices: &[DeviceWithStatus],
+    area_id: AreaId,
+) -> Vec<Entity> {
+    entities
+        .iter()
+        .filter(|entity| {
+            devices.iter().any(|item| {
+                item.device.id == entity.device_id && item.device.area_id == Some(area_id)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Current state of an entity, with a toggle button for on/off entities.
+fn entity_card(entity: Entity) -> impl IntoView {
+    let toasts = use_toasts();
+    let id = entity.id.to_string();
+    let toggle = matches!(entity.state, EntityState::On | EntityState::Off).then(|| {
+        view! {
+            <button
+                class="btn btn-sm btn-secondary"
+                on:click=move |_| {
+                    let id = id.clone();
+                    let toasts = toasts.clone();
+                    spawn_local(async move {
+                        if let Err(err) = api::call_entity_service(&id, "toggle").await {
+                            toasts.push(format!("Failed to toggle: {err}"));
+                        }
+                    });
+                }
+            >
+                "Toggle"
+            </button>
+        }
+    });
+    view! {
+        <div class="pinned-card">
+            <A href=format!("/entities/{}", entity.id) attr:class="pinned-card-name">
+                {entity.friendly_name}
+            </A>
+            <span class="pinned-card-state">{entity.state.to_string()}</span>
+            {toggle}
+        </div>
+    }
+}
+
+/// Name of an area and how many of its entities are on.
+fn area_card(area: Area, entities: &[Entity]) -> impl IntoView {
+    let on = entities
+        .iter()
+        .filter(|entity| entity.state == EntityState::On)
+        .count();
+    view! {
+        <div class="pinned-card">
+            <span class="pinned-card-name">{area.name}</span>
+            <span class="pinned-card-state">{format!("{on} / {} on", entities.len())}</span>
+            <ul class="pinned-card-list">
+                {entities
+                    .iter()
+                    .map(|entity| view! {
+                        <li>
+                            <A href=format!("/entities/{}", entity.id)>{entity.friendly_name.clone()}</A>
+                            " \u{b7} "
+                            {entity.state.to_string()}
+                        </li>
+                    })
+                    .collect::<Vec<_>>()}
+            </ul>
+        </div>
+    }
+}
+
+/// Placeholder for a card whose entity or area was removed.
+fn missing_card(text: &'static str) -> AnyView {
+    view! {
+        <div class="pinned-card">
+            <span class="pinned-card-name">{text}</span>
+            <em>"Edit the dashboard to unpin it."</em>
+        </div>
+    }
+    .into_any()
+}
+
+/// Render `card`, following the entities, devices and areas it shows.
+///
+/// Chart cards only read the entity name once, so state changes do not
+/// reload their history.
+fn card_view(
+    card: Card,
+    entities: ReadSignal<Vec<Entity>>,
+    devices: ReadSignal<Vec<DeviceWithStatus>>,
+    areas: ReadSignal<Vec<Area>>,
+) -> AnyView {
+    match card {
+        Card::Entity { entity_id } => view! {
+            {move || {
+                entities
+                    .with(|entities| entities.iter().find(|entity| entity.id == entity_id).cloned())
+                    .map_or_else(
+                        || missing_card("Removed entity"),
+                        |entity| entity_card(entity).into_any(),
+                    )
+            }}
+        }
+        .into_any(),
+        Card::Chart { entity_id, hours } => {
+            let name = entities.with_untracked(|entities| entity_name(entities, entity_id));
+            view! {
+                <div class="pinned-card pinned-card-wide">
+                    <StateChart entity_id=entity_id name=name hours=hours/>
+                </div>
+            }
+            .into_any()
+        }
+        Card::Area { area_id } => view! {
+            {move || {
+                let area = areas.with(|areas| areas.iter().find(|area| area.id == area_id).cloned());
+                match area {
+                    Some(area) => {
+                        let members = entities.with(|entities| {
+                            devices.with(|devices| entities_in_area(entities, devices, area_id))
+                        });
+                        area_card(area, &members).into_any()
+                    }
+                    None => missing_card("Removed area"),
+                }
+            }}
+        }
+        .into_any(),
+    }
+}
+
+/// Form appending a card to the layout being edited.
+#[component]
+fn AddCardForm(
+    cards: RwSignal<Vec<Card>>,
+    entities: ReadSignal<Vec<Entity>>,
+    areas: ReadSignal<Vec<Area>>,
+) -> impl IntoView {
+    let (kind, set_kind) = signal("entity".to_owned());
+    let (target, set_target) = signal(String::new());
+    let (hours, set_hours) = signal(24_u32);
+
+    let options = move || {
+        if kind.get() == "area" {
+            areas
+                .get()
+                .into_iter()
+                .map(|area| view! { <option value=area.id.to_string()>{area.name}</option> })
+                .collect::<Vec<_>>()
+        } else {
+            entities
+                .get()
+                .into_iter()
+                .map(|entity| {
+                    view! { <option value=entity.id.to_string()>{entity.friendly_name}</option> }
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let add = move |_| {
+        let target = target.get_untracked();
+        let card = match kind.get_untracked().as_str() {
+            "area" => AreaId::from_str(&target)
+                .ok()
+                .map(|area_id| Card::Area { area_id }),
+            "chart" => EntityId::from_str(&target)
+                .ok()
+                .map(|entity_id| Card::Chart {
+                    entity_id,
+                    hours: hours.get_untracked(),
+                }),
+            _ => EntityId::from_str(&target)
+                .ok()
+                .map(|entity_id| Card::Entity { entity_id }),
+        };
+        if let Some(card) = card {
+            cards.update(|cards| cards.push(card));
+        }
+    };
+
+    view! {
+        <div class="editor-row">
+            <select on:change=move |ev| {
+                set_kind.set(event_target_value(&ev));
+                set_target.set(String::new());
+            }>
+                <option value="entity">"Entity"</option>
+                <option value="chart">"Chart"</option>
+                <option value="area">"Area"</option>
+            </select>
+            <select
+                prop:value=move || target.get()
+                on:change=move |ev| set_target.set(event_target_value(&ev))
+            >
+                <option value="">"Select\u{2026}"</option>
+                {options}
+            </select>
+            <Show when=move || kind.get() == "chart">
+                <label>
+                    "Hours"
+                    <input
+                        type="number"
+                        min="1"
+                        max=MAX_CHART_HOURS
+                        prop:value=move || hours.get().to_string()
+                        on:change=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse() {
+                                set_hours.set(value);
+                            }
+                        }
+                    />
+                </label>
+            </Show>
+            <button
+                class="btn btn-sm btn-secondary"
+                disabled=move || target.get().is_empty()
+                on:click=add
+            >
+                "+ Pin card"
+            </button>
+        </div>
+    }
+}
+
+/// Cards pinned to the home page, with an edit mode to add, remove and
+/// reorder them.
+#[component]
+pub fn PinnedCards(
+    /// Every entity, to render entity, chart and area cards.
+    entities: ReadSignal<Vec<Entity>>,
+    /// Every device, placing entities in their area.
+    devices: ReadSignal<Vec<DeviceWithStatus>>,
+    /// Every area, to render area cards.
+    areas: ReadSignal<Vec<Area>>,
+) -> impl IntoView {
+    let toasts = use_toasts();
+    let cards = RwSignal::new(Vec::<Card>::new());
+    let saved = RwSignal::new(Vec::<Card>::new());
+    let editing = RwSignal::new(false);
+
+    {
+        let toasts = toasts.clone();
+        Effect::new(move |_| {
+            let toasts = toasts.clone();
+            spawn_local(async move {
+                match api::fetch_dashboard_config().await {
+                    Ok(config) => {
+                        saved.set(config.cards.clone());
+                        cards.set(config.cards);
+                    }
+                    Err(err) => toasts.push(format!("Failed to load dashboard layout: {err}")),
+                }
+            });
+        });
+    }
+
+    let save = move |_| {
+        let toasts = toasts.clone();
+        let config = DashboardConfig {
+            cards: cards.get_untracked(),
+        };
+        spawn_local(async move {
+            match api::update_dashboard_config(&config).await {
+                Ok(config) => {
+                    saved.set(config.cards.clone());
+                    cards.set(config.cards);
+                    editing.set(false);
+                    toasts.push_success("Dashboard saved".to_owned());
+                }
+                Err(err) => toasts.push(format!("Failed to save dashboard: {err}")),
+            }
+        });
+    };
+
+    let cancel = move |_| {
+        cards.set(saved.get_untracked());
+        editing.set(false);
+    };
+
+    view! {
+        <div class="pinned-header">
+            <h2>"Pinned"</h2>
+            <Show
+                when=move || editing.get()
+                fallback=move || view! {
+                    <button class="btn btn-sm btn-secondary" on:click=move |_| editing.set(true)>
+                        "Edit"
+                    </button>
+                }
+            >
+                <button class="btn btn-sm btn-primary" on:click=save.clone()>"Save"</button>
+                <button class="btn btn-sm btn-secondary" on:click=cancel>"Cancel"</button>
+            </Show>
+        </div>
+        <Show
+            when=move || editing.get()
+            fallback=move || view! {
+                <Show
+                    when=move || !cards.with(Vec::is_empty)
+                    fallback=|| view! {
+                        <p><em>"Nothing pinned yet. Use Edit to pin entities, charts and areas."</em></p>
+                    }
+                >
+                    <div class="pinned-grid">
+                        {move || {
+                            cards
+                                .get()
+                                .into_iter()
+                                .map(|card| card_view(card, entities, devices, areas))
+                                .collect::<Vec<_>>()
+                        }}
+                    </div>
+                </Show>
+            }
+        >
+            <ol class="editor-list">
+                {move || {
+                    let items = cards.get();
+                    let count = items.len();
+                    entities.with(|entities| {
+                        areas.with(|areas| {
+                            items
+                                .iter()
+                                .enumerate()
+                                .map(|(index, card)| view! {
+                                    <li class="editor-row">
+                                        <span>{describe(card, entities, areas)}</span>
+                                        {row_buttons(cards, index, count)}
+                                    </li>
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                }}
+            </ol>
+            <AddCardForm cards=cards entities=entities areas=areas/>
+        </Show>
+    }
+}
@@ -55,6 +55,15 @@ pub(crate) fn int_attr(entity: &Entity, key: &str) -> Option<i64> {
     }
 }
 
+/// Display unit of an attribute, taken from its metadata when the
+/// integration declared one.
+fn unit(entity: &Entity, key: &str, fallback: &'static str) -> String {
+    entity
+        .get_attribute_meta(key)
+        .and_then(|meta| meta.display_unit.clone())
+        .unwrap_or_else(|| fallback.to_owned())
+}
+
 /// Format a float to one decimal place.
 fn fmt_f1(v: f64) -> String {
     format!("{v:.1}")
@@ -116,6 +125,8 @@ pub fn SensorCard(
 fn temp_humidity_body(entity: &Entity) -> impl IntoView {
     let temperature = float_attr(entity, "temperature");
     let humidity = float_attr(entity, "humidity");
+    let temperature_unit = unit(entity, "temperature", "°C");
+    let humidity_unit = unit(entity, "humidity", "%");
 
     view! {
         <div class="sensor-metrics">
@@ -123,14 +134,14 @@ fn temp_humidity_body(entity: &Entity) -> impl IntoView {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Temp"</span>
                     <span class="sensor-metric-value">{fmt_f1(t)}</span>
-                    <span class="sensor-metric-unit">"°C"</span>
+                    <span class="sensor-metric-unit">{temperature_unit}</span>
                 </div>
             })}
             {humidity.map(|h| view! {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Humidity"</span>
                     <span class="sensor-metric-value">{fmt_f1(h)}</span>
-                    <span class="sensor-metric-unit">"%"</span>
+                    <span class="sensor-metric-unit">{humidity_unit}</span>
                 </div>
             })}
         </div>
@@ -143,6 +154,10 @@ fn miflora_body(entity: &Entity) -> impl IntoView {
     let moisture = int_attr(entity, "moisture");
     let light = int_attr(entity, "light");
     let conductivity = int_attr(entity, "conductivity");
+    let temperature_unit = unit(entity, "temperature", "°C");
+    let moisture_unit = unit(entity, "moisture", "%");
+    let light_unit = unit(entity, "light", "lux");
+    let conductivity_unit = unit(entity, "conductivity", "µS/cm");
 
     view! {
         <div class="sensor-metrics sensor-metrics-grid">
@@ -150,28 +165,28 @@ fn miflora_body(entity: &Entity) -> impl IntoView {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Temp"</span>
                     <span class="sensor-metric-value">{fmt_f1(t)}</span>
-                    <span class="sensor-metric-unit">"°C"</span>
+                    <span class="sensor-metric-unit">{temperature_unit}</span>
                 </div>
             })}
             {moisture.map(|m| view! {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Moisture"</span>
                     <span class="sensor-metric-value">{m.to_string()}</span>
-                    <span class="sensor-metric-unit">"%"</span>
+                    <span class="sensor-metric-unit">{moisture_unit}</span>
                 </div>
             })}
             {light.map(|l| view! {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Light"</span>
                     <span class="sensor-metric-value">{l.to_string()}</span>
-                    <span class="sensor-metric-unit">"lux"</span>
+                    <span class="sensor-metric-unit">{light_unit}</span>
                 </div>
             })}
             {conductivity.map(|c| view! {
                 <div class="sensor-metric">
                     <span class="sensor-metric-label">"Conductivity"</span>
                     <span class="sensor-metric-value">{c.to_string()}</span>
-                    <span class="sensor-metric-unit">"µS/cm"</span>
+                    <span class="sensor-metric-unit">{conductivity_unit}</span>
                 </div>
             })}
         </div>
@@ -0,0 +1,43 @@
+//! Prompt for an API token, shown once the server asks for one.
+
+use leptos::prelude::*;
+
+use crate::auth;
+
+/// A form asking for the API token after the server answered `401`.
+///
+/// Saving the token reloads the app with it.
+#[component]
+pub fn TokenPrompt() -> impl IntoView {
+    let (token, set_token) = signal(String::new());
+    let save = move |_| auth::save_token(&token.get_untracked());
+    let message = move || {
+        if auth::token().is_some() {
+            "minihub rejected the API token \u{2014} enter another one"
+        } else {
+            "minihub requires an API token"
+        }
+    };
+
+    view! {
+        <Show when=auth::is_required>
+            <div class="token-prompt" role="alert">
+                <span>{message}</span>
+                <input
+                    type="password"
+                    placeholder="API token"
+                    autocomplete="current-password"
+                    prop:value=move || token.get()
+                    on:input=move |ev| set_token.set(event_target_value(&ev))
+                />
+                <button
+                    class="btn btn-primary"
+                    disabled=move || token.get().trim().is_empty()
+                    on:click=save
+                >
+                    "Save token"
+                </button>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,84 @@
+//! Connection status of the dashboard to minihubd.
+//!
+//! API calls and the SSE stream report whether the server answered. Once it
+//! stops answering, the liveness probe is polled until it does again; the
+//! app shows a banner meanwhile and re-renders the current page, and so
+//! re-fetches its data, when the connection is back.
+
+use std::sync::OnceLock;
+
+use gloo_net::http::Request;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+/// Delay between two liveness probes while the server is unreachable.
+const RETRY_INTERVAL_MS: u32 = 3_000;
+
+/// Whether the server answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Online,
+    /// The server stopped answering; it is polled until it answers again.
+    Reconnecting,
+}
+
+/// Shared state, reachable from the API client which has no reactive owner.
+struct Connection {
+    status: ArcRwSignal<ConnectionStatus>,
+    /// Number of times the connection came back.
+    reconnects: ArcRwSignal<u32>,
+}
+
+fn connection() -> &'static Connection {
+    static CONNECTION: OnceLock<Connection> = OnceLock::new();
+    CONNECTION.get_or_init(|| Connection {
+        status: ArcRwSignal::new(ConnectionStatus::Online),
+        reconnects: ArcRwSignal::new(0),
+    })
+}
+
+/// The current connection status, tracked.
+pub fn status() -> ConnectionStatus {
+    connection().status.get()
+}
+
+/// How many times the connection came back, tracked; changes whenever
+/// page data should be fetched again.
+pub fn reconnects() -> u32 {
+    connection().reconnects.get()
+}
+
+/// Record that the server answered.
+pub fn report_online() {
+    let connection = connection();
+    if connection.status.get_untracked() == ConnectionStatus::Reconnecting {
+        connection.status.set(ConnectionStatus::Online);
+        connection.reconnects.update(|count| *count += 1);
+    }
+}
+
+/// Record that the server could not be reached, and start polling it.
+pub fn report_offline() {
+    let connection = connection();
+    if connection.status.get_untracked() == ConnectionStatus::Online {
+        connection.status.set(ConnectionStatus::Reconnecting);
+        spawn_local(poll_until_online());
+    }
+}
+
+/// Probe the server until it answers, unless something else reports it
+/// online first.
+async fn poll_until_online() {
+    loop {
+        gloo_timers::future::TimeoutFuture::new(RETRY_INTERVAL_MS).await;
+        if connection().status.get_untracked() == ConnectionStatus::Online {
+            return;
+        }
+        if let Ok(resp) = Request::get("/health/live").send().await
+            && resp.ok()
+        {
+            report_online();
+            return;
+        }
+    }
+}
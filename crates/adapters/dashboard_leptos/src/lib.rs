@@ -5,35 +5,49 @@ use leptos_router::{
 };
 
 pub mod api;
+pub mod auth;
 mod components;
+pub mod connection;
 mod pages;
 pub mod sse;
 
-use components::{Nav, ToastContainer};
+use components::{ConnectionBanner, Nav, ToastContainer, TokenPrompt};
 use pages::{
-    Areas, AutomationDetail, Automations, DeviceDetail, Devices, Entities, EntityDetail, Events,
-    Home, NotFound,
+    Areas, AutomationDetail, Automations, DeviceDetail, Devices, Discovery, Entities, EntityDetail,
+    EventDetail, Events, Home, NotFound,
 };
 
 /// Root application component.
+///
+/// The current page is rendered again, and so re-fetches its data, every
+/// time the connection to the server comes back.
 #[component]
 pub fn App() -> impl IntoView {
     view! {
         <ToastContainer>
             <Router>
                 <Nav/>
+                <ConnectionBanner/>
+                <TokenPrompt/>
                 <main>
-                    <Routes fallback=|| view! { <NotFound/> }>
-                        <Route path=path!("/") view=Home/>
-                        <Route path=path!("devices") view=Devices/>
-                        <Route path=path!("devices/:id") view=DeviceDetail/>
-                        <Route path=path!("entities") view=Entities/>
-                        <Route path=path!("entities/:id") view=EntityDetail/>
-                        <Route path=path!("areas") view=Areas/>
-                        <Route path=path!("events") view=Events/>
-                        <Route path=path!("automations") view=Automations/>
-                        <Route path=path!("automations/:id") view=AutomationDetail/>
-                    </Routes>
+                    {move || {
+                        connection::reconnects();
+                        view! {
+                            <Routes fallback=|| view! { <NotFound/> }>
+                                <Route path=path!("/") view=Home/>
+                                <Route path=path!("devices") view=Devices/>
+                                <Route path=path!("devices/:id") view=DeviceDetail/>
+                                <Route path=path!("discovery") view=Discovery/>
+                                <Route path=path!("entities") view=Entities/>
+                                <Route path=path!("entities/:id") view=EntityDetail/>
+                                <Route path=path!("areas") view=Areas/>
+                                <Route path=path!("events") view=Events/>
+                                <Route path=path!("events/:id") view=EventDetail/>
+                                <Route path=path!("automations") view=Automations/>
+                                <Route path=path!("automations/:id") view=AutomationDetail/>
+                            </Routes>
+                        }
+                    }}
                 </main>
             </Router>
         </ToastContainer>
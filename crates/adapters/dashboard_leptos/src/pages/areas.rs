@@ -1,21 +1,73 @@
 use leptos::prelude::*;
+use leptos::task::spawn_local;
 
 use crate::api;
-use crate::components::{AreaTable, Loading};
+use crate::components::{AreaBoard, Loading, use_toasts};
 
-/// Areas page displaying all areas in a table.
+/// Areas page: create, rename and delete areas, and drag devices between
+/// them.
 #[component]
 pub fn Areas() -> impl IntoView {
-    let areas = LocalResource::new(api::fetch_areas);
+    let toasts = use_toasts();
+    let (reload_trigger, set_reload_trigger) = signal(0);
+    let (new_name, set_new_name) = signal(String::new());
+
+    let data = LocalResource::new(move || {
+        reload_trigger.track();
+        async move {
+            let areas = api::fetch_areas().await?;
+            let devices = api::fetch_devices().await?;
+            let entities = api::fetch_entities().await?;
+            Ok::<_, api::ApiError>((areas, devices, entities))
+        }
+    });
+
+    let handle_change = move || {
+        set_reload_trigger.update(|v| *v += 1);
+    };
+
+    let create = move |_| {
+        let toasts = toasts.clone();
+        let name = new_name.get_untracked().trim().to_string();
+        spawn_local(async move {
+            match api::create_area(name).await {
+                Ok(_) => {
+                    set_new_name.set(String::new());
+                    handle_change();
+                }
+                Err(err) => toasts.push(format!("Failed to create area: {err}")),
+            }
+        });
+    };
 
     view! {
         <div>
             <h1>"Areas"</h1>
+            <div class="detail-section area-create">
+                <input
+                    type="text"
+                    placeholder="New area name"
+                    prop:value=move || new_name.get()
+                    on:input=move |ev| set_new_name.set(event_target_value(&ev))
+                />
+                <button
+                    class="btn btn-primary"
+                    disabled=move || new_name.get().trim().is_empty()
+                    on:click=create
+                >
+                    "Add area"
+                </button>
+            </div>
             <Suspense fallback=move || view! { <Loading message="Loading areas\u{2026}"/> }>
                 {move || {
-                    areas.read().as_ref().map(|result| match result {
-                        Ok(areas_list) => view! {
-                            <AreaTable areas=areas_list.clone()/>
+                    data.read().as_ref().map(|result| match result {
+                        Ok((areas, devices, entities)) => view! {
+                            <AreaBoard
+                                areas=areas.clone()
+                                devices=devices.clone()
+                                entities=entities.clone()
+                                on_change=handle_change
+                            />
                         }.into_any(),
                         Err(err) => view! {
                             <p class="error">{"Failed to load areas: "} {err.to_string()}</p>
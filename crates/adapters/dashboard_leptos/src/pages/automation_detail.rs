@@ -1,27 +1,45 @@
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_params_map;
+use minihub_domain::automation::Automation;
 
 use crate::api;
-use crate::components::Loading;
+use crate::components::{AutomationEditor, Loading};
 
-/// Automation detail page showing trigger, conditions, and actions.
+/// Automation detail page showing trigger, conditions, and actions, which
+/// can be switched to an editor.
 #[component]
 pub fn AutomationDetail() -> impl IntoView {
     let params = use_params_map();
     let id = move || params.read().get("id").unwrap_or_default();
 
+    let (reload_trigger, set_reload_trigger) = signal(0);
+    let (editing, set_editing) = signal(false);
+
     let automation = LocalResource::new(move || {
+        reload_trigger.track();
         let automation_id = id();
         async move { api::fetch_automation(&automation_id).await }
     });
 
+    let on_saved = move |_: Automation| {
+        set_editing.set(false);
+        set_reload_trigger.update(|v| *v += 1);
+    };
+
     view! {
         <div>
             <h1>"Automation Detail"</h1>
             <Suspense fallback=move || view! { <Loading message="Loading automation\u{2026}"/> }>
                 {move || {
                     automation.read().as_ref().map(|result| match result {
+                        Ok(auto) if editing.get() => view! {
+                            <AutomationEditor
+                                automation=auto.clone()
+                                on_saved=on_saved
+                                on_cancel=move || set_editing.set(false)
+                            />
+                        }.into_any(),
                         Ok(auto) => view! {
                             <div class="automation-detail">
                                 <div class="detail-section">
@@ -35,6 +53,12 @@ pub fn AutomationDetail() -> impl IntoView {
                                     {auto.last_triggered.as_ref().map(|ts| view! {
                                         <p><strong>"Last Triggered: "</strong> {ts.to_string()}</p>
                                     })}
+                                    {auto.source_key.clone().map(|source_key| view! {
+                                        <p class="hint">
+                                            "Defined in " <code>{format!("{source_key}.yaml")}</code>
+                                            " — edit the file to change it."
+                                        </p>
+                                    })}
                                 </div>
 
                                 <div class="detail-section">
@@ -80,6 +104,12 @@ pub fn AutomationDetail() -> impl IntoView {
                                 </div>
 
                                 <div class="detail-section">
+                                    {(!auto.is_read_only()).then(|| view! {
+                                        <button class="btn btn-primary" on:click=move |_| set_editing.set(true)>
+                                            "Edit"
+                                        </button>
+                                    })}
+                                    " "
                                     <A href="/automations">"← Back to Automations"</A>
                                 </div>
                             </div>
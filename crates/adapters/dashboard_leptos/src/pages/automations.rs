@@ -1,7 +1,7 @@
 use leptos::prelude::*;
 
 use crate::api;
-use crate::components::{AutomationTable, Loading};
+use crate::components::{AutomationTable, AutomationWizard, Loading};
 
 /// Automations page displaying all automations with enable/disable toggle.
 #[component]
@@ -23,6 +23,9 @@ pub fn Automations() -> impl IntoView {
             <Suspense fallback=move || view! { <Loading message="Loading automations\u{2026}"/> }>
                 {move || {
                     automations.read().as_ref().map(|result| match result {
+                        Ok(automations_list) if automations_list.is_empty() => view! {
+                            <AutomationWizard on_created=handle_update/>
+                        }.into_any(),
                         Ok(automations_list) => view! {
                             <AutomationTable
                                 automations=automations_list.clone()
@@ -1,12 +1,12 @@
-//! Device detail page showing device information and its entities.
+//! Device detail page showing device information, its entities and its
+//! recent events.
 
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_router::hooks::use_params_map;
-use minihub_domain::id::DeviceId;
 
-use crate::api::{self, ApiError};
-use crate::components::{EntityTable, Loading};
+use crate::api;
+use crate::components::{DeviceStatusBadge, EntityTable, EventTable, Loading};
 
 /// Device detail page.
 #[component]
@@ -19,39 +19,38 @@ pub fn DeviceDetail() -> impl IntoView {
         async move { api::fetch_device(&device_id).await }
     });
 
-    let entities: LocalResource<Result<Vec<minihub_domain::entity::Entity>, ApiError>> =
-        LocalResource::new(move || {
-            let device_id = id();
-            async move {
-                let all = api::fetch_entities().await?;
-                let did: DeviceId = device_id.parse().map_err(|err: uuid::Error| ApiError {
-                    message: err.to_string(),
-                })?;
-                Ok(all
-                    .into_iter()
-                    .filter(|e| e.device_id == did)
-                    .collect::<Vec<_>>())
-            }
-        });
-
     view! {
         <div>
             <h1>"Device Detail"</h1>
             <Suspense fallback=move || view! { <Loading/> }>
                 {move || {
                     device.read().as_ref().map(|result| match result {
-                        Ok(dev) => {
+                        Ok(detail) => {
+                            let status = detail.device.status;
+                            let dev = &detail.device.device;
                             let manufacturer = dev.manufacturer.clone().unwrap_or_else(|| "\u{2014}".to_string());
                             let model = dev.model.clone().unwrap_or_else(|| "\u{2014}".to_string());
                             let area = dev.area_id.as_ref().map_or("\u{2014}".to_string(), |a| a.to_string());
+                            let sw_version = dev.sw_version.clone().unwrap_or_else(|| "\u{2014}".to_string());
+                            let hw_version = dev.hw_version.clone().unwrap_or_else(|| "\u{2014}".to_string());
+                            let suggested_area = dev.suggested_area.clone().unwrap_or_else(|| "\u{2014}".to_string());
+                            let via_device = dev.via_device_id.map_or_else(
+                                || view! { "\u{2014}" }.into_any(),
+                                |via| view! { <A href=format!("/devices/{via}")>{via.to_string()}</A> }.into_any(),
+                            );
 
                             view! {
                                 <div class="card">
                                     <h2>{dev.name.clone()}</h2>
+                                    <p><strong>"Status: "</strong> <DeviceStatusBadge status/></p>
                                     <p><strong>"Manufacturer: "</strong> {manufacturer}</p>
                                     <p><strong>"Model: "</strong> {model}</p>
+                                    <p><strong>"Software version: "</strong> {sw_version}</p>
+                                    <p><strong>"Hardware version: "</strong> {hw_version}</p>
                                     <p><strong>"Integration: "</strong> {dev.integration.clone()}</p>
                                     <p><strong>"Area: "</strong> {area}</p>
+                                    <p><strong>"Suggested area: "</strong> {suggested_area}</p>
+                                    <p><strong>"Via device: "</strong> {via_device}</p>
                                     <p><strong>"Unique ID: "</strong> {dev.unique_id.clone()}</p>
                                 </div>
                             }.into_any()
@@ -66,9 +65,9 @@ pub fn DeviceDetail() -> impl IntoView {
             <h2>"Entities"</h2>
             <Suspense fallback=move || view! { <Loading message="Loading entities\u{2026}"/> }>
                 {move || {
-                    entities.read().as_ref().map(|result: &Result<Vec<minihub_domain::entity::Entity>, _>| match result {
-                        Ok(entity_list) => view! {
-                            <EntityTable entities=entity_list.clone()/>
+                    device.read().as_ref().map(|result| match result {
+                        Ok(detail) => view! {
+                            <EntityTable entities=detail.entities.clone()/>
                         }.into_any(),
                         Err(err) => view! {
                             <p class="error">{"Failed to load entities: "} {err.to_string()}</p>
@@ -77,6 +76,20 @@ pub fn DeviceDetail() -> impl IntoView {
                 }}
             </Suspense>
 
+            <h2>"Recent Events"</h2>
+            <Suspense fallback=move || view! { <Loading message="Loading events\u{2026}"/> }>
+                {move || {
+                    device.read().as_ref().map(|result| match result {
+                        Ok(detail) => view! {
+                            <EventTable events=detail.events.clone()/>
+                        }.into_any(),
+                        Err(err) => view! {
+                            <p class="error">{"Failed to load events: "} {err.to_string()}</p>
+                        }.into_any(),
+                    })
+                }}
+            </Suspense>
+
             <p><A href="/devices">"\u{2190} Back to Devices"</A></p>
         </div>
     }
@@ -1,7 +1,7 @@
 use leptos::prelude::*;
 
 use crate::api;
-use crate::components::{DeviceTable, Loading};
+use crate::components::{DeviceTable, DevicesEmptyState, Loading};
 
 /// Devices page displaying all devices in a table.
 #[component]
@@ -14,6 +14,9 @@ pub fn Devices() -> impl IntoView {
             <Suspense fallback=move || view! { <Loading message="Loading devices\u{2026}"/> }>
                 {move || {
                     devices.read().as_ref().map(|result| match result {
+                        Ok(devices_list) if devices_list.is_empty() => view! {
+                            <DevicesEmptyState/>
+                        }.into_any(),
                         Ok(devices_list) => view! {
                             <DeviceTable devices=devices_list.clone()/>
                         }.into_any(),
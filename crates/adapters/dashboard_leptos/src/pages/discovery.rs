@@ -0,0 +1,139 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use minihub_domain::pending_device::{Adoption, PendingDevice};
+
+use crate::api;
+use crate::components::sensor_card::format_relative_time;
+use crate::components::{Loading, use_toasts};
+
+/// Discovery page: devices detected by integrations but not registered
+/// yet, each with a form adopting it.
+#[component]
+pub fn Discovery() -> impl IntoView {
+    let (reload_trigger, set_reload_trigger) = signal(0);
+
+    let pending = LocalResource::new(move || {
+        reload_trigger.track();
+        api::fetch_pending_devices()
+    });
+
+    let on_adopted = Callback::new(move |()| set_reload_trigger.update(|v| *v += 1));
+
+    view! {
+        <div>
+            <h1>"Discovery"</h1>
+            <Suspense fallback=move || view! { <Loading message="Loading detected devices\u{2026}"/> }>
+                {move || {
+                    pending.read().as_ref().map(|result| match result {
+                        Ok(devices) if devices.is_empty() => view! {
+                            <p>"No detected device is waiting to be adopted."</p>
+                        }.into_any(),
+                        Ok(devices) => view! {
+                            <table>
+                                <thead>
+                                    <tr>
+                                        <th>"Name"</th>
+                                        <th>"Address"</th>
+                                        <th>"Detected by"</th>
+                                        <th>"Last seen"</th>
+                                        <th>"Integration"</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {devices.iter().cloned().map(|device| view! {
+                                        <PendingRow device on_adopted/>
+                                    }).collect::<Vec<_>>()}
+                                </tbody>
+                            </table>
+                        }.into_any(),
+                        Err(err) => view! {
+                            <p class="error">{"Failed to load detected devices: "} {err.to_string()}</p>
+                        }.into_any(),
+                    })
+                }}
+            </Suspense>
+        </div>
+    }
+}
+
+/// A pending device, with inputs for the name and integration it is
+/// adopted with.
+#[component]
+fn PendingRow(
+    /// The detected device.
+    device: PendingDevice,
+    /// Called once the device is adopted, to reload the list.
+    on_adopted: Callback<()>,
+) -> impl IntoView {
+    let toasts = use_toasts();
+    let (name, set_name) = signal(
+        device
+            .name
+            .clone()
+            .unwrap_or_else(|| device.address.clone()),
+    );
+    let (integration, set_integration) = signal(
+        device
+            .suggested_integration
+            .clone()
+            .unwrap_or_else(|| device.integration.clone()),
+    );
+    let (adopting, set_adopting) = signal(false);
+    let id = device.id;
+
+    let adopt = move |_| {
+        let toasts = toasts.clone();
+        let adoption = Adoption {
+            name: Some(name.get_untracked().trim().to_string()),
+            integration: Some(integration.get_untracked().trim().to_string()),
+            ..Adoption::default()
+        };
+        set_adopting.set(true);
+        spawn_local(async move {
+            match api::adopt_pending_device(id, adoption).await {
+                Ok(device) => {
+                    toasts.push_success(format!("Adopted {}", device.name));
+                    on_adopted.run(());
+                }
+                Err(err) => toasts.push(format!("Failed to adopt device: {err}")),
+            }
+            set_adopting.set(false);
+        });
+    };
+
+    view! {
+        <tr>
+            <td>
+                <input
+                    type="text"
+                    prop:value=move || name.get()
+                    on:input=move |ev| set_name.set(event_target_value(&ev))
+                />
+            </td>
+            <td><code>{device.address}</code></td>
+            <td>{device.integration}</td>
+            <td>{format_relative_time(device.last_seen)}</td>
+            <td>
+                <input
+                    type="text"
+                    prop:value=move || integration.get()
+                    on:input=move |ev| set_integration.set(event_target_value(&ev))
+                />
+            </td>
+            <td>
+                <button
+                    class="btn btn-primary"
+                    disabled=move || {
+                        adopting.get()
+                            || name.get().trim().is_empty()
+                            || integration.get().trim().is_empty()
+                    }
+                    on:click=adopt
+                >
+                    "Adopt"
+                </button>
+            </td>
+        </tr>
+    }
+}
@@ -1,28 +1,97 @@
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use minihub_domain::area::Area;
+use minihub_domain::device::DeviceWithStatus;
+use minihub_domain::entity::Entity;
+use minihub_domain::event::EventType;
 
 use crate::api;
-use crate::components::{EntityTable, Loading};
+use crate::components::{EntitiesEmptyState, EntityBrowser, EntityQuery, EntityWizard, Loading};
+use crate::sse::{patch_entities, use_sse_events};
 
-/// Entities page displaying all entities in a table with state badges.
+/// Entities page displaying all entities in a table with state badges, and
+/// a wizard creating new ones.
+///
+/// The list can be searched, filtered by domain and integration, grouped by
+/// device or area, and sorted by column. The query survives reloads.
+///
+/// State and attribute changes streamed over SSE are patched into the
+/// loaded list; created and removed entities trigger a re-fetch.
 #[component]
 pub fn Entities() -> impl IntoView {
-    let entities = LocalResource::new(api::fetch_entities);
+    let (entities, set_entities) = signal(None::<Result<Vec<Entity>, String>>);
+    let (devices, set_devices) = signal(Vec::<DeviceWithStatus>::new());
+    let (areas, set_areas) = signal(Vec::<Area>::new());
+    let (creating, set_creating) = signal(false);
+    let query = RwSignal::new(EntityQuery::default());
+
+    let reload = move || {
+        spawn_local(async move {
+            let result = api::fetch_entities().await.map_err(|err| err.message);
+            set_entities.set(Some(result));
+            // Devices and areas only enrich the filters and grouping, so
+            // failing to load them still shows the entities.
+            set_devices.set(api::fetch_devices().await.unwrap_or_default());
+            set_areas.set(api::fetch_areas().await.unwrap_or_default());
+        });
+    };
+
+    Effect::new(move |_| reload());
+
+    let sse_event = use_sse_events();
+
+    Effect::new(move |_| {
+        let Some(event) = sse_event.get() else {
+            return;
+        };
+
+        match event.event_type {
+            EventType::StateChanged | EventType::AttributeChanged => {
+                set_entities.maybe_update(|loaded| match loaded {
+                    Some(Ok(list)) => patch_entities(list, &event),
+                    _ => false,
+                });
+            }
+            EventType::EntityCreated | EventType::EntityRemoved | EventType::EntityRenamed => {
+                reload();
+            }
+            _ => {}
+        }
+    });
 
     view! {
         <div>
             <h1>"Entities"</h1>
-            <Suspense fallback=move || view! { <Loading message="Loading entities\u{2026}"/> }>
-                {move || {
-                    entities.read().as_ref().map(|result| match result {
-                        Ok(entities_list) => view! {
-                            <EntityTable entities=entities_list.clone()/>
-                        }.into_any(),
-                        Err(err) => view! {
-                            <p class="error">{"Failed to load entities: "} {err.to_string()}</p>
-                        }.into_any(),
-                    })
-                }}
-            </Suspense>
+            {move || if creating.get() {
+                view! {
+                    <EntityWizard on_cancel=move || set_creating.set(false)/>
+                }.into_any()
+            } else {
+                view! {
+                    <div class="detail-section">
+                        <button class="btn btn-primary" on:click=move |_| set_creating.set(true)>
+                            "New entity"
+                        </button>
+                    </div>
+                }.into_any()
+            }}
+            {move || match entities.get() {
+                None => view! { <Loading message="Loading entities\u{2026}"/> }.into_any(),
+                Some(Ok(entities_list)) if entities_list.is_empty() => view! {
+                    <EntitiesEmptyState/>
+                }.into_any(),
+                Some(Ok(entities_list)) => view! {
+                    <EntityBrowser
+                        entities=entities_list
+                        devices=devices.get()
+                        areas=areas.get()
+                        query=query
+                    />
+                }.into_any(),
+                Some(Err(err)) => view! {
+                    <p class="error">{"Failed to load entities: "} {err}</p>
+                }.into_any(),
+            }}
         </div>
     }
 }
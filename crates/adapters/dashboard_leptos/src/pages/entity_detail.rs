@@ -1,12 +1,20 @@
-use crate::api::{call_entity_service, fetch_entity, update_entity_state};
-use crate::components::{HistoryChart, Loading, use_toasts};
-use crate::sse::use_sse_events;
+use crate::api::{call_entity_service, fetch_entity, refresh_entity, update_entity_state};
+use crate::components::{EnergyPanel, HistoryChart, Loading, use_toasts};
+use crate::sse::{patch_entity, use_sse_events};
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos_router::hooks::use_params_map;
-use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::entity::{DeviceClass, Entity, EntityState};
 use minihub_domain::event::EventType;
 
+/// Returns `true` for power and energy sensors, whose consumption is tracked.
+fn is_metered(entity: &Entity) -> bool {
+    matches!(
+        entity.device_class,
+        Some(DeviceClass::Power | DeviceClass::Energy)
+    )
+}
+
 /// Returns `true` when the entity's domain-level id starts with `"sensor.miflora_"`.
 fn is_miflora(entity: &Entity) -> bool {
     entity.entity_id.starts_with("sensor.miflora_")
@@ -24,6 +32,7 @@ pub fn EntityDetail() -> impl IntoView {
     let (loading, set_loading) = signal(true);
     let (updating, set_updating) = signal(false);
     let (blinking, set_blinking) = signal(false);
+    let (refreshing, set_refreshing) = signal(false);
 
     Effect::new(move |_| {
         let entity_id = id();
@@ -56,43 +65,48 @@ pub fn EntityDetail() -> impl IntoView {
             return;
         };
 
-        let current_entity = entity.get();
-        let Some(event_entity_id) = &event.entity_id else {
+        let Some(event_entity_id) = event.entity_id else {
             return;
         };
 
-        let Some(ref current) = current_entity else {
-            return;
-        };
-
-        if current.id != *event_entity_id {
+        let is_current = entity.with_untracked(|current| {
+            current
+                .as_ref()
+                .is_some_and(|current| current.id == event_entity_id)
+        });
+        if !is_current {
             return;
         }
 
         match event.event_type {
             EventType::StateChanged | EventType::AttributeChanged => {
-                let entity_id = id();
-                spawn_local(async move {
-                    match fetch_entity(&entity_id).await {
-                        Ok(updated) => set_entity.set(Some(updated)),
-                        Err(err) => {
-                            leptos::logging::warn!("SSE re-fetch failed: {}", err.message);
-                        }
-                    }
+                set_entity.maybe_update(|current| {
+                    current
+                        .as_mut()
+                        .is_some_and(|current| patch_entity(current, &event))
                 });
             }
-            EventType::ServiceCallCompleted => {
-                set_blinking.set(false);
-                sse_toasts.push_success("Blink succeeded".to_owned());
-            }
-            EventType::ServiceCallFailed => {
-                set_blinking.set(false);
-                let reason = event
-                    .data
-                    .get("error")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown error");
-                sse_toasts.push(format!("Blink failed: {reason}"));
+            EventType::ServiceCallCompleted | EventType::ServiceCallFailed => {
+                let action = match event.data.get("service").and_then(|v| v.as_str()) {
+                    Some("refresh") => {
+                        set_refreshing.set(false);
+                        "Refresh"
+                    }
+                    _ => {
+                        set_blinking.set(false);
+                        "Blink"
+                    }
+                };
+                if event.event_type == EventType::ServiceCallCompleted {
+                    sse_toasts.push_success(format!("{action} succeeded"));
+                } else {
+                    let reason = event
+                        .data
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("unknown error");
+                    sse_toasts.push(format!("{action} failed: {reason}"));
+                }
             }
             _ => {}
         }
@@ -136,6 +150,19 @@ pub fn EntityDetail() -> impl IntoView {
         });
     });
 
+    let refresh_toasts = toasts.clone();
+    let handle_refresh = Callback::new(move |()| {
+        let entity_id = id();
+        let t = refresh_toasts.clone();
+        set_refreshing.set(true);
+        spawn_local(async move {
+            if let Err(err) = refresh_entity(&entity_id).await {
+                set_refreshing.set(false);
+                t.push(err.message);
+            }
+        });
+    });
+
     let (chart_entity_id, set_chart_entity_id) = signal(String::new());
 
     Effect::new(move |_| {
@@ -163,9 +190,11 @@ pub fn EntityDetail() -> impl IntoView {
                         EntityState::Off => "state-off",
                         EntityState::Unknown => "state-unknown",
                         EntityState::Unavailable => "state-unavailable",
+                        EntityState::Numeric(_) | EntityState::Text(_) => "state-value",
                     };
 
-                    let show_blink = is_miflora(&e);
+                    let show_miflora_controls = is_miflora(&e);
+                    let show_energy = is_metered(&e);
 
                     view! {
                         <div class="entity-detail">
@@ -232,7 +261,7 @@ pub fn EntityDetail() -> impl IntoView {
                                     >
                                         {move || if updating.get() { "Updating..." } else { "Turn Off" }}
                                     </button>
-                                    {if show_blink {
+                                    {if show_miflora_controls {
                                         Some(view! {
                                             <button
                                                 on:click=move |_| handle_blink.run(())
@@ -243,6 +272,15 @@ pub fn EntityDetail() -> impl IntoView {
                                                     if blinking.get() { "Blinking..." } else { "Blink" }
                                                 }}
                                             </button>
+                                            <button
+                                                on:click=move |_| handle_refresh.run(())
+                                                disabled=move || refreshing.get()
+                                                class="btn btn-secondary"
+                                            >
+                                                {move || {
+                                                    if refreshing.get() { "Refreshing..." } else { "Refresh" }
+                                                }}
+                                            </button>
                                         })
                                     } else {
                                         None
@@ -250,6 +288,7 @@ pub fn EntityDetail() -> impl IntoView {
                                 </div>
                             </div>
 
+                            {show_energy.then(|| view! { <EnergyPanel entity_id=chart_entity_id/> })}
                             <HistoryChart entity_id=chart_entity_id/>
                         </div>
                     }
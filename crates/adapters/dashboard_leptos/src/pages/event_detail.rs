@@ -0,0 +1,60 @@
+use leptos::prelude::*;
+use leptos_router::hooks::use_params_map;
+
+use crate::api;
+use crate::components::{EventTable, Loading};
+
+/// Event detail page showing an event and the causal chain it belongs to:
+/// the event that started it and everything produced in reaction.
+#[component]
+pub fn EventDetail() -> impl IntoView {
+    let params = use_params_map();
+    let id = move || params.read().get("id").unwrap_or_default();
+
+    let details = LocalResource::new(move || {
+        let event_id = id();
+        async move {
+            let event = api::fetch_event(&event_id).await?;
+            let chain = api::fetch_event_chain(&event_id).await?;
+            Ok::<_, api::ApiError>((event, chain))
+        }
+    });
+
+    view! {
+        <div>
+            <h1>"Event Detail"</h1>
+            <Suspense fallback=move || view! { <Loading message="Loading event\u{2026}"/> }>
+                {move || {
+                    details.read().as_ref().map(|result| match result {
+                        Ok((event, chain)) => view! {
+                            <div class="detail-section">
+                                <h2>{event.event_type.as_str()}</h2>
+                                <p><strong>"Timestamp: "</strong> {event.timestamp.to_string()}</p>
+                                <p>
+                                    <strong>"Entity: "</strong>
+                                    {event.entity_id.map_or("—".to_string(), |id| id.to_string())}
+                                </p>
+                                <p>
+                                    <strong>"Caused by: "</strong>
+                                    {event.caused_by.map_or("—".to_string(), |id| id.to_string())}
+                                </p>
+                                <code class="json-data">{event.data.to_string()}</code>
+                            </div>
+                            <div class="detail-section">
+                                <h3>"Causal chain"</h3>
+                                <p class="hint">
+                                    "Events sharing correlation id " {event.correlation_id.to_string()}
+                                    ", oldest first"
+                                </p>
+                                <EventTable events=chain.clone()/>
+                            </div>
+                        }.into_any(),
+                        Err(err) => view! {
+                            <p class="error">{"Failed to load event: "} {err.message.clone()}</p>
+                        }.into_any(),
+                    })
+                }}
+            </Suspense>
+        </div>
+    }
+}
@@ -1,57 +1,185 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use minihub_domain::entity::Entity;
 use minihub_domain::event::Event;
 
-use crate::api;
-use crate::components::{EventTable, Loading};
+use crate::api::{self, EventFilter, EventPage};
+use crate::components::{EventLiveTail, EventTable, Loading};
 use crate::sse::use_sse_events;
 
 const MAX_EVENTS: usize = 100;
 
-/// Events page displaying recent events with live SSE updates.
+/// Event types offered by the type filter.
+const EVENT_TYPES: &[&str] = &[
+    "state_changed",
+    "attribute_changed",
+    "entity_created",
+    "entity_removed",
+    "entity_renamed",
+    "automation_triggered",
+    "automation_suppressed",
+    "device_detected",
+    "device_updated",
+    "device_unavailable",
+    "device_back_online",
+    "service_call_requested",
+    "service_call_completed",
+    "service_call_failed",
+    "notification_requested",
+    "webhook_received",
+    "sun_event",
+    "time_pattern_fired",
+];
+
+/// Whether a live `event` belongs in a listing filtered by `filter`.
+fn matches(filter: &EventFilter, event: &Event) -> bool {
+    (filter.event_type.is_empty() || event.event_type.as_str() == filter.event_type)
+        && (filter.entity_id.is_empty()
+            || event
+                .entity_id
+                .is_some_and(|id| id.to_string() == filter.entity_id))
+}
+
+/// Events page listing events newest first, filtered by type and entity,
+/// with live SSE updates and paging through older events, or tailing the
+/// live stream only.
 #[component]
 pub fn Events() -> impl IntoView {
-    let (events, set_events) = signal(None::<Result<Vec<Event>, String>>);
+    let (live_tail, set_live_tail) = signal(false);
+    let (events, set_events) = signal(None::<Result<EventPage, String>>);
+    let (filter, set_filter) = signal(EventFilter::default());
+    let (entities, set_entities) = signal(Vec::<Entity>::new());
 
-    // Initial fetch
     spawn_local(async move {
-        match api::fetch_events().await {
-            Ok(list) => set_events.set(Some(Ok(list))),
-            Err(err) => set_events.set(Some(Err(err.to_string()))),
+        if let Ok(list) = api::fetch_entities().await {
+            set_entities.set(list);
         }
     });
 
-    // Subscribe to SSE and prepend new events
+    // Fetch the first page whenever the filter changes
+    Effect::new(move |_| {
+        let filter = filter.get();
+        set_events.set(None);
+        spawn_local(async move {
+            let result = api::fetch_event_page(&filter, None)
+                .await
+                .map_err(|err| err.message);
+            set_events.set(Some(result));
+        });
+    });
+
+    let load_more = move |_| {
+        let Some(Ok(page)) = events.get_untracked() else {
+            return;
+        };
+        let Some(cursor) = page.next_cursor else {
+            return;
+        };
+        let filter = filter.get_untracked();
+        spawn_local(async move {
+            match api::fetch_event_page(&filter, Some(&cursor)).await {
+                Ok(next) => set_events.update(|current| {
+                    if let Some(Ok(page)) = current {
+                        page.events.extend(next.events);
+                        page.next_cursor = next.next_cursor;
+                    }
+                }),
+                Err(err) => set_events.set(Some(Err(err.message))),
+            }
+        });
+    };
+
+    // Subscribe to SSE and prepend new events matching the filter
     let sse_event = use_sse_events();
 
     Effect::new(move |_| {
         let Some(event) = sse_event.get() else {
             return;
         };
+        if !matches(&filter.get_untracked(), &event) {
+            return;
+        }
 
         set_events.update(|current| {
-            if let Some(Ok(list)) = current {
-                list.insert(0, event);
-                list.truncate(MAX_EVENTS);
+            if let Some(Ok(page)) = current {
+                page.events.insert(0, event);
+                if page.next_cursor.is_none() {
+                    page.events.truncate(MAX_EVENTS);
+                }
             }
         });
     });
 
-    view! {
-        <div>
-            <h1>"Events"</h1>
-            <p class="hint">"Showing most recent events (up to 100) — live updates enabled"</p>
+    let history = move || {
+        view! {
+            <div class="event-filters">
+                <select on:change=move |ev| {
+                    let event_type = event_target_value(&ev);
+                    set_filter.update(|filter| filter.event_type = event_type);
+                }>
+                    <option value="">"All types"</option>
+                    {EVENT_TYPES
+                        .iter()
+                        .map(|event_type| view! { <option value=*event_type>{*event_type}</option> })
+                        .collect_view()}
+                </select>
+                <select on:change=move |ev| {
+                    let entity_id = event_target_value(&ev);
+                    set_filter.update(|filter| filter.entity_id = entity_id);
+                }>
+                    <option value="">"All entities"</option>
+                    {move || entities
+                        .get()
+                        .into_iter()
+                        .map(|entity| view! {
+                            <option value=entity.id.to_string()>{entity.entity_id}</option>
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <p class="hint">"Newest events first \u{2014} live updates enabled"</p>
             {move || {
                 match events.get() {
                     None => view! { <Loading message="Loading events\u{2026}"/> }.into_any(),
                     Some(Err(err)) => view! {
                         <p class="error">{"Failed to load events: "} {err}</p>
                     }.into_any(),
-                    Some(Ok(events_list)) => view! {
-                        <EventTable events=events_list/>
-                    }.into_any(),
+                    Some(Ok(page)) => {
+                        let has_more = page.next_cursor.is_some();
+                        view! {
+                            <EventTable events=page.events/>
+                            {has_more.then(|| view! {
+                                <button class="btn btn-secondary" on:click=load_more>
+                                    "Load older events"
+                                </button>
+                            })}
+                        }.into_any()
+                    }
                 }
             }}
+        }
+    };
+
+    view! {
+        <div>
+            <h1>"Events"</h1>
+            <div class="event-filters">
+                <button
+                    class=move || if live_tail.get() { "btn btn-secondary" } else { "btn btn-primary" }
+                    on:click=move |_| set_live_tail.set(false)
+                >
+                    "History"
+                </button>
+                <button
+                    class=move || if live_tail.get() { "btn btn-primary" } else { "btn btn-secondary" }
+                    on:click=move |_| set_live_tail.set(true)
+                >
+                    "Live tail"
+                </button>
+            </div>
+            <Show when=move || live_tail.get() fallback=history>
+                <EventLiveTail live_event=sse_event entities event_types=EVENT_TYPES/>
+            </Show>
         </div>
     }
 }
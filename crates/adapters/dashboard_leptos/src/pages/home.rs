@@ -1,39 +1,49 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use minihub_domain::area::Area;
+use minihub_domain::device::DeviceWithStatus;
 use minihub_domain::entity::Entity;
 use minihub_domain::event::EventType;
 
 use crate::api;
-use crate::components::{Loading, PlantCardGrid, SensorCardGrid, StatCard};
+use crate::components::{Loading, PinnedCards, PlantCardGrid, SensorCardGrid, StatCard};
 use crate::sse::use_sse_events;
 
 /// Dashboard data loaded on the home page.
 #[derive(Debug, Clone)]
 struct DashboardData {
-    entity_count: usize,
-    device_count: usize,
-    area_count: usize,
     entities: Vec<Entity>,
+    devices: Vec<DeviceWithStatus>,
+    areas: Vec<Area>,
 }
 
-/// Fetch counts and full entity list in one pass.
+impl DashboardData {
+    /// Number of entities, devices and areas.
+    fn counts(&self) -> (usize, usize, usize) {
+        (self.entities.len(), self.devices.len(), self.areas.len())
+    }
+}
+
+/// Fetch the full entity, device and area lists in one pass.
 async fn fetch_dashboard_data() -> Result<DashboardData, crate::api::ApiError> {
     let entities = api::fetch_entities().await?;
     let devices = api::fetch_devices().await?;
     let areas = api::fetch_areas().await?;
 
     Ok(DashboardData {
-        entity_count: entities.len(),
-        device_count: devices.len(),
-        area_count: areas.len(),
         entities,
+        devices,
+        areas,
     })
 }
 
-/// Home page displaying counts and BLE sensor cards with live SSE updates.
+/// Home page displaying counts, the pinned cards and BLE sensor cards with
+/// live SSE updates.
 #[component]
 pub fn Home() -> impl IntoView {
     let (entities, set_entities) = signal(Vec::<Entity>::new());
+    let (devices, set_devices) = signal(Vec::<DeviceWithStatus>::new());
+    let (areas, set_areas) = signal(Vec::<Area>::new());
     let (counts, set_counts) = signal(None::<(usize, usize, usize)>);
     let (error, set_error) = signal(None::<String>);
     let (loading, set_loading) = signal(true);
@@ -44,8 +54,10 @@ pub fn Home() -> impl IntoView {
             match fetch_dashboard_data().await {
                 Ok(dd) => {
                     set_error.set(None);
-                    set_counts.set(Some((dd.entity_count, dd.device_count, dd.area_count)));
+                    set_counts.set(Some(dd.counts()));
                     set_entities.set(dd.entities);
+                    set_devices.set(dd.devices);
+                    set_areas.set(dd.areas);
                     set_loading.set(false);
                 }
                 Err(err) => {
@@ -76,8 +88,10 @@ pub fn Home() -> impl IntoView {
                 spawn_local(async move {
                     if let Ok(dd) = fetch_dashboard_data().await {
                         set_error.set(None);
-                        set_counts.set(Some((dd.entity_count, dd.device_count, dd.area_count)));
+                        set_counts.set(Some(dd.counts()));
                         set_entities.set(dd.entities);
+                        set_devices.set(dd.devices);
+                        set_areas.set(dd.areas);
                     }
                 });
             }
@@ -103,12 +117,17 @@ pub fn Home() -> impl IntoView {
                             <StatCard label="Devices" value=dc/>
                             <StatCard label="Areas" value=ac/>
                         </div>
-                        <PlantCardGrid entities/>
-                        <h2>"Sensors"</h2>
-                        <SensorCardGrid entities/>
                     }.into_any()
                 }
             }}
+            // Outside the block above so that count changes keep the layout
+            // being edited.
+            <Show when=move || !loading.get() && error.get().is_none()>
+                <PinnedCards entities devices areas/>
+                <PlantCardGrid entities/>
+                <h2>"Sensors"</h2>
+                <SensorCardGrid entities/>
+            </Show>
         </div>
     }
 }
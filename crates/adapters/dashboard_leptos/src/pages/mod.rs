@@ -3,8 +3,10 @@ mod automation_detail;
 mod automations;
 mod device_detail;
 mod devices;
+mod discovery;
 mod entities;
 mod entity_detail;
+mod event_detail;
 mod events;
 mod home;
 mod not_found;
@@ -14,8 +16,10 @@ pub use automation_detail::AutomationDetail;
 pub use automations::Automations;
 pub use device_detail::DeviceDetail;
 pub use devices::Devices;
+pub use discovery::Discovery;
 pub use entities::Entities;
 pub use entity_detail::EntityDetail;
+pub use event_detail::EventDetail;
 pub use events::Events;
 pub use home::Home;
 pub use not_found::NotFound;
@@ -1,17 +1,22 @@
 //! SSE client module for subscribing to `/api/events/stream`.
 //!
 //! Provides a reactive hook that connects to the server-sent events endpoint
-//! and delivers parsed domain events to Leptos signals.
+//! and delivers parsed domain events to Leptos signals, plus helpers patching
+//! already-loaded entities from those events.
 
 use leptos::prelude::*;
 use leptos::reactive::owner::{LocalStorage, StoredValue};
-use minihub_domain::event::Event;
+use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+use minihub_domain::event::{Event, EventType};
 use wasm_bindgen::prelude::*;
 use web_sys::{EventSource, MessageEvent};
 
+use crate::{api, auth, connection};
+
 /// Guard that closes the `EventSource` connection on drop (if connected).
 pub struct SseConnection {
     source: EventSource,
+    _on_open: Closure<dyn FnMut(web_sys::Event)>,
     _on_message: Closure<dyn FnMut(MessageEvent)>,
     _on_error: Closure<dyn FnMut(web_sys::Event)>,
 }
@@ -26,12 +31,21 @@ impl Drop for SseConnection {
 ///
 /// Returns a read signal that yields each incoming [`Event`] as it arrives.
 /// The connection is established lazily after the component mounts and is
-/// automatically closed when the owning reactive scope is disposed.
+/// automatically closed when the owning reactive scope is disposed. Its
+/// openings and errors are reported to [`crate::connection`]. The token of
+/// [`crate::auth`], if any, is sent as the `access_token` query parameter.
 pub fn use_sse_events() -> ReadSignal<Option<Event>> {
     let (event_sig, set_event) = signal(None::<Event>);
 
     Effect::new(move |_| {
-        let source = match EventSource::new("/api/events/stream") {
+        let url = match auth::token() {
+            Some(token) => format!(
+                "/api/events/stream?access_token={}",
+                api::encode_query_value(&token)
+            ),
+            None => "/api/events/stream".to_string(),
+        };
+        let source = match EventSource::new(&url) {
             Ok(s) => s,
             Err(err) => {
                 leptos::logging::warn!("failed to create EventSource: {err:?}");
@@ -39,6 +53,10 @@ pub fn use_sse_events() -> ReadSignal<Option<Event>> {
             }
         };
 
+        let on_open = Closure::<dyn FnMut(web_sys::Event)>::new(move |_: web_sys::Event| {
+            connection::report_online();
+        });
+
         let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |msg: MessageEvent| {
             if let Some(data) = msg.data().as_string() {
                 match serde_json::from_str::<Event>(&data) {
@@ -52,8 +70,12 @@ pub fn use_sse_events() -> ReadSignal<Option<Event>> {
 
         let on_error = Closure::<dyn FnMut(web_sys::Event)>::new(move |_: web_sys::Event| {
             leptos::logging::warn!("SSE connection error — browser will auto-reconnect");
+            connection::report_offline();
         });
 
+        source
+            .add_event_listener_with_callback("open", on_open.as_ref().unchecked_ref())
+            .expect("failed to add open listener to EventSource");
         source
             .add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref())
             .expect("failed to add message listener to EventSource");
@@ -63,6 +85,7 @@ pub fn use_sse_events() -> ReadSignal<Option<Event>> {
 
         let conn = SseConnection {
             source,
+            _on_open: on_open,
             _on_message: on_message,
             _on_error: on_error,
         };
@@ -73,3 +96,142 @@ pub fn use_sse_events() -> ReadSignal<Option<Event>> {
 
     event_sig
 }
+
+/// Apply a `StateChanged` or `AttributeChanged` event to `entity` in place.
+///
+/// Returns `true` when the entity was modified, so callers can skip
+/// notifying subscribers for events about other entities or replays.
+pub fn patch_entity(entity: &mut Entity, event: &Event) -> bool {
+    if event.entity_id != Some(entity.id) {
+        return false;
+    }
+    match event.event_type {
+        EventType::StateChanged => {
+            let Some(state) = event
+                .data
+                .get("new_state")
+                .and_then(|value| serde_json::from_value::<EntityState>(value.clone()).ok())
+            else {
+                return false;
+            };
+            if entity.state == state {
+                return false;
+            }
+            entity.update_state(state, event.timestamp);
+            true
+        }
+        EventType::AttributeChanged => {
+            let Some(changed) = event
+                .data
+                .get("changed")
+                .and_then(|value| value.as_object())
+            else {
+                return false;
+            };
+            let mut modified = false;
+            for (key, value) in changed {
+                let Ok(value) = serde_json::from_value::<AttributeValue>(value.clone()) else {
+                    continue;
+                };
+                if entity.get_attribute(key) != Some(&value) {
+                    entity.set_attribute(key.clone(), value);
+                    modified = true;
+                }
+            }
+            if modified {
+                entity.last_updated = event.timestamp;
+            }
+            modified
+        }
+        _ => false,
+    }
+}
+
+/// Apply `event` to whichever entity of `entities` it is about.
+///
+/// Returns `true` when one of them was modified.
+pub fn patch_entities(entities: &mut [Entity], event: &Event) -> bool {
+    entities
+        .iter_mut()
+        .any(|entity| patch_entity(entity, event))
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::id::DeviceId;
+
+    use super::*;
+
+    fn light() -> Entity {
+        Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::Off)
+            .build()
+            .unwrap()
+    }
+
+    fn state_changed(entity: &Entity, state: &str) -> Event {
+        Event::new(
+            EventType::StateChanged,
+            Some(entity.id),
+            serde_json::json!({ "old_state": "off", "new_state": state }),
+        )
+    }
+
+    #[test]
+    fn should_patch_state_when_event_is_about_entity() {
+        let mut entity = light();
+        let event = state_changed(&entity, "on");
+
+        assert!(patch_entity(&mut entity, &event));
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(entity.last_changed, event.timestamp);
+    }
+
+    #[test]
+    fn should_not_patch_when_state_is_unchanged() {
+        let mut entity = light();
+        let event = state_changed(&entity, "off");
+
+        assert!(!patch_entity(&mut entity, &event));
+    }
+
+    #[test]
+    fn should_not_patch_when_event_is_about_another_entity() {
+        let mut entity = light();
+        let other = light();
+        let event = state_changed(&other, "on");
+
+        assert!(!patch_entity(&mut entity, &event));
+        assert_eq!(entity.state, EntityState::Off);
+    }
+
+    #[test]
+    fn should_patch_attributes_when_attribute_changed() {
+        let mut entity = light();
+        let event = Event::new(
+            EventType::AttributeChanged,
+            Some(entity.id),
+            serde_json::json!({ "changed": { "brightness": 128 } }),
+        );
+
+        assert!(patch_entity(&mut entity, &event));
+        assert_eq!(
+            entity.attributes.get("brightness"),
+            Some(&AttributeValue::Int(128))
+        );
+        assert!(!patch_entity(&mut entity, &event));
+    }
+
+    #[test]
+    fn should_patch_matching_entity_when_patching_a_list() {
+        let mut entities = vec![light(), light()];
+        let event = state_changed(&entities[1], "on");
+
+        assert!(patch_entities(&mut entities, &event));
+        assert_eq!(entities[0].state, EntityState::Off);
+        assert_eq!(entities[1].state, EntityState::On);
+    }
+}
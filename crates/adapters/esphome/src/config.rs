@@ -0,0 +1,110 @@
+//! ESPHome integration configuration.
+
+use serde::Deserialize;
+
+/// Default port of the ESPHome native API.
+pub const DEFAULT_PORT: u16 = 6053;
+
+/// Configuration for the ESPHome integration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EsphomeConfig {
+    /// Devices to connect to.
+    pub devices: Vec<EsphomeDeviceConfig>,
+    /// Name reported to devices in the hello handshake.
+    pub client_info: String,
+    /// Delay in seconds before reconnecting to a device that dropped.
+    pub reconnect_interval_secs: u64,
+}
+
+impl Default for EsphomeConfig {
+    fn default() -> Self {
+        Self {
+            devices: Vec::new(),
+            client_info: "minihub".to_string(),
+            reconnect_interval_secs: 30,
+        }
+    }
+}
+
+/// Connection settings of a single ESPHome device.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EsphomeDeviceConfig {
+    /// Hostname or IP address of the device, e.g. `"living-room.local"`.
+    pub host: String,
+    /// Native API port.
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// API password, when the device sets `api: password:`.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl EsphomeDeviceConfig {
+    /// Describe a device listening on the default port without password.
+    #[must_use]
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port: DEFAULT_PORT,
+            password: None,
+        }
+    }
+
+    /// `host:port` address used to open the connection.
+    #[must_use]
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_sensible_defaults() {
+        let config = EsphomeConfig::default();
+        assert!(config.devices.is_empty());
+        assert_eq!(config.client_info, "minihub");
+        assert_eq!(config.reconnect_interval_secs, 30);
+    }
+
+    #[test]
+    fn should_deserialize_devices_from_toml() {
+        let toml = r#"
+            reconnect_interval_secs = 10
+
+            [[devices]]
+            host = "living-room.local"
+
+            [[devices]]
+            host = "192.168.1.42"
+            port = 6054
+            password = "secret"
+        "#;
+        let config: EsphomeConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.reconnect_interval_secs, 10);
+        assert_eq!(
+            config.devices,
+            vec![
+                EsphomeDeviceConfig::new("living-room.local"),
+                EsphomeDeviceConfig {
+                    host: "192.168.1.42".to_string(),
+                    port: 6054,
+                    password: Some("secret".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_format_address_with_port() {
+        let device = EsphomeDeviceConfig::new("10.0.0.5");
+        assert_eq!(device.address(), "10.0.0.5:6053");
+    }
+}
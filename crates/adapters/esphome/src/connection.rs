@@ -0,0 +1,258 @@
+//! Handshake and discovery over a native API connection.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::entities::Listed;
+use crate::error::EsphomeError;
+use crate::frame::{self, Frame};
+use crate::proto::{
+    ApiMessage, ConnectRequest, ConnectResponse, DeviceInfoRequest, DeviceInfoResponse,
+    DisconnectRequest, DisconnectResponse, GetTimeRequest, GetTimeResponse, HelloRequest,
+    HelloResponse, ListEntitiesDoneResponse, ListEntitiesRequest, PingRequest, PingResponse,
+    SubscribeStatesRequest,
+};
+
+/// API version announced in the hello request.
+const API_VERSION: (u32, u32) = (1, 10);
+
+/// A native API connection before it switches to streaming states.
+pub(crate) struct Connection<S> {
+    stream: S,
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(stream: S) -> Self {
+        Self { stream }
+    }
+
+    /// Exchange hello and connect messages.
+    ///
+    /// The connect request is sent even without password, as devices
+    /// refuse every other request until it has been received.
+    pub async fn handshake(
+        &mut self,
+        client_info: &str,
+        password: Option<&str>,
+    ) -> Result<HelloResponse, EsphomeError> {
+        let (api_version_major, api_version_minor) = API_VERSION;
+        frame::write_message(
+            &mut self.stream,
+            &HelloRequest {
+                client_info: client_info.to_string(),
+                api_version_major,
+                api_version_minor,
+            },
+        )
+        .await?;
+        let hello: HelloResponse = self.expect().await?;
+
+        frame::write_message(
+            &mut self.stream,
+            &ConnectRequest {
+                password: password.unwrap_or_default().to_string(),
+            },
+        )
+        .await?;
+        let connect: ConnectResponse = self.expect().await?;
+        if connect.invalid_password {
+            return Err(EsphomeError::InvalidPassword);
+        }
+        Ok(hello)
+    }
+
+    /// Ask the device to describe itself.
+    pub async fn device_info(&mut self) -> Result<DeviceInfoResponse, EsphomeError> {
+        frame::write_message(&mut self.stream, &DeviceInfoRequest {}).await?;
+        self.expect().await
+    }
+
+    /// Enumerate the device's components.
+    ///
+    /// Component kinds minihub does not map are skipped.
+    pub async fn list_entities(&mut self) -> Result<Vec<Listed>, EsphomeError> {
+        frame::write_message(&mut self.stream, &ListEntitiesRequest {}).await?;
+        let mut listed = Vec::new();
+        loop {
+            let frame = frame::read_frame(&mut self.stream).await?;
+            if frame.is::<ListEntitiesDoneResponse>() {
+                return Ok(listed);
+            }
+            if let Some(entity) = Listed::from_frame(&frame)? {
+                listed.push(entity);
+            } else if !respond(&mut self.stream, &frame).await? {
+                tracing::debug!(
+                    message_type = frame.message_type,
+                    "ignoring unsupported ESPHome component"
+                );
+            }
+        }
+    }
+
+    /// Ask the device to push the current and future states.
+    pub async fn subscribe_states(&mut self) -> Result<(), EsphomeError> {
+        frame::write_message(&mut self.stream, &SubscribeStatesRequest {}).await
+    }
+
+    /// Give back the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Read frames until one of type `M` arrives, answering keep-alives on
+    /// the way.
+    async fn expect<M: ApiMessage>(&mut self) -> Result<M, EsphomeError> {
+        loop {
+            let frame = frame::read_frame(&mut self.stream).await?;
+            if frame.is::<M>() {
+                return frame.decode();
+            }
+            if !respond(&mut self.stream, &frame).await? {
+                return Err(EsphomeError::Protocol(format!(
+                    "expected message type {}, got {}",
+                    M::TYPE,
+                    frame.message_type
+                )));
+            }
+        }
+    }
+}
+
+/// Answer the requests a device may send at any time: pings, time
+/// requests and disconnects.
+///
+/// Returns `false` when the frame is not one of those.
+///
+/// # Errors
+///
+/// Returns [`EsphomeError::NotConnected`] once the device asked to
+/// disconnect, or an I/O error if the answer could not be written.
+pub(crate) async fn respond<W>(writer: &mut W, frame: &Frame) -> Result<bool, EsphomeError>
+where
+    W: AsyncWrite + Unpin,
+{
+    if frame.is::<PingRequest>() {
+        frame::write_message(writer, &PingResponse {}).await?;
+    } else if frame.is::<GetTimeRequest>() {
+        let epoch_seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u32::try_from(elapsed.as_secs()).unwrap_or(u32::MAX)
+            });
+        frame::write_message(writer, &GetTimeResponse { epoch_seconds }).await?;
+    } else if frame.is::<DisconnectRequest>() {
+        frame::write_message(writer, &DisconnectResponse {}).await?;
+        return Err(EsphomeError::NotConnected);
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::DuplexStream;
+
+    use super::*;
+    use crate::proto::{ListEntitiesLightResponse, ListEntitiesSwitchResponse};
+
+    /// Scripted device side: reads a request of type `Req` and answers
+    /// with each of `responses`.
+    async fn reply<Req: ApiMessage>(device: &mut DuplexStream, responses: &[Vec<u8>]) -> Req {
+        let frame = frame::read_frame(device).await.unwrap();
+        let request = frame.decode::<Req>().unwrap();
+        for response in responses {
+            tokio::io::AsyncWriteExt::write_all(device, response)
+                .await
+                .unwrap();
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn should_handshake_and_list_entities() {
+        let (client, mut device) = tokio::io::duplex(4096);
+        let fake = tokio::spawn(async move {
+            let hello: HelloRequest = reply(
+                &mut device,
+                &[frame::encode(&HelloResponse {
+                    name: "living-room".to_string(),
+                    ..HelloResponse::default()
+                })],
+            )
+            .await;
+            let connect: ConnectRequest = reply(
+                &mut device,
+                &[
+                    frame::encode(&PingRequest {}),
+                    frame::encode(&ConnectResponse::default()),
+                ],
+            )
+            .await;
+            // Answer to the ping sent in between.
+            let _: PingResponse = reply(&mut device, &[]).await;
+            let _: ListEntitiesRequest = reply(
+                &mut device,
+                &[
+                    frame::encode(&ListEntitiesSwitchResponse {
+                        object_id: "relay".to_string(),
+                        key: 1,
+                        name: "Relay".to_string(),
+                    }),
+                    // Text sensors are not mapped.
+                    vec![0x00, 0x00, 18],
+                    frame::encode(&ListEntitiesLightResponse {
+                        object_id: "ceiling".to_string(),
+                        key: 2,
+                        name: "Ceiling".to_string(),
+                    }),
+                    frame::encode(&ListEntitiesDoneResponse {}),
+                ],
+            )
+            .await;
+            (hello, connect)
+        });
+
+        let mut connection = Connection::new(client);
+        let hello = connection
+            .handshake("minihub", Some("secret"))
+            .await
+            .unwrap();
+        let listed = connection.list_entities().await.unwrap();
+        let (sent_hello, sent_connect) = fake.await.unwrap();
+
+        assert_eq!(hello.name, "living-room");
+        assert_eq!(sent_hello.client_info, "minihub");
+        assert_eq!(sent_connect.password, "secret");
+        assert_eq!(listed.len(), 2);
+        assert!(matches!(&listed[0], Listed::Switch(switch) if switch.key == 1));
+        assert!(matches!(&listed[1], Listed::Light(light) if light.key == 2));
+    }
+
+    #[tokio::test]
+    async fn should_fail_handshake_on_invalid_password() {
+        let (client, mut device) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            let _: HelloRequest =
+                reply(&mut device, &[frame::encode(&HelloResponse::default())]).await;
+            let _: ConnectRequest = reply(
+                &mut device,
+                &[frame::encode(&ConnectResponse {
+                    invalid_password: true,
+                })],
+            )
+            .await;
+        });
+
+        let err = Connection::new(client)
+            .handshake("minihub", None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, EsphomeError::InvalidPassword));
+    }
+}
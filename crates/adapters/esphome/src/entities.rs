@@ -0,0 +1,576 @@
+//! Mapping of ESPHome device descriptions onto minihub entities.
+//!
+//! Each listed ESPHome component becomes its own entity, named
+//! `{domain}.{device}_{object_id}`. Sensors keep their reading in an
+//! attribute named after their device class (`"value"` when the class is
+//! unknown), lights keep their brightness as a `0..=255` attribute.
+
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
+use minihub_domain::error::MiniHubError;
+use serde_json::Value;
+
+use crate::error::EsphomeError;
+use crate::frame::Frame;
+use crate::proto::{
+    BinarySensorStateResponse, DeviceInfoResponse, LightCommandRequest, LightStateResponse,
+    ListEntitiesBinarySensorResponse, ListEntitiesLightResponse, ListEntitiesSensorResponse,
+    ListEntitiesSwitchResponse, SensorStateResponse, SwitchCommandRequest, SwitchStateResponse,
+};
+
+/// Attribute holding the brightness of a light.
+const BRIGHTNESS: &str = "brightness";
+/// Attribute holding the reading of a sensor without known device class.
+const VALUE: &str = "value";
+
+/// The kind of ESPHome component behind an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component {
+    BinarySensor,
+    Light,
+    Sensor,
+    Switch,
+}
+
+/// How an entity maps onto its device connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Binding {
+    /// `host:port` of the owning device, as configured.
+    pub device: String,
+    /// Key of the component, used by state messages and commands.
+    pub key: u32,
+    pub component: Component,
+}
+
+/// A component announced during `ListEntities`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Listed {
+    BinarySensor(ListEntitiesBinarySensorResponse),
+    Light(ListEntitiesLightResponse),
+    Sensor(ListEntitiesSensorResponse),
+    Switch(ListEntitiesSwitchResponse),
+}
+
+impl Listed {
+    /// Decode a list response, or `None` for component kinds minihub does
+    /// not map.
+    pub fn from_frame(frame: &Frame) -> Result<Option<Self>, EsphomeError> {
+        let listed = if frame.is::<ListEntitiesBinarySensorResponse>() {
+            Self::BinarySensor(frame.decode()?)
+        } else if frame.is::<ListEntitiesLightResponse>() {
+            Self::Light(frame.decode()?)
+        } else if frame.is::<ListEntitiesSensorResponse>() {
+            Self::Sensor(frame.decode()?)
+        } else if frame.is::<ListEntitiesSwitchResponse>() {
+            Self::Switch(frame.decode()?)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(listed))
+    }
+}
+
+/// A state update pushed after `SubscribeStates`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum StateUpdate {
+    BinarySensor(BinarySensorStateResponse),
+    Light(LightStateResponse),
+    Sensor(SensorStateResponse),
+    Switch(SwitchStateResponse),
+}
+
+impl StateUpdate {
+    /// Decode a state response, or `None` for other message types.
+    pub fn from_frame(frame: &Frame) -> Result<Option<Self>, EsphomeError> {
+        let update = if frame.is::<BinarySensorStateResponse>() {
+            Self::BinarySensor(frame.decode()?)
+        } else if frame.is::<LightStateResponse>() {
+            Self::Light(frame.decode()?)
+        } else if frame.is::<SensorStateResponse>() {
+            Self::Sensor(frame.decode()?)
+        } else if frame.is::<SwitchStateResponse>() {
+            Self::Switch(frame.decode()?)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(update))
+    }
+
+    /// Key of the component the update is about.
+    pub fn key(&self) -> u32 {
+        match self {
+            Self::BinarySensor(state) => state.key,
+            Self::Light(state) => state.key,
+            Self::Sensor(state) => state.key,
+            Self::Switch(state) => state.key,
+        }
+    }
+}
+
+/// A command to send to a device.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Command {
+    Light(LightCommandRequest),
+    Switch(SwitchCommandRequest),
+}
+
+/// Build the minihub device for an ESPHome node.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the device has no usable name.
+pub(crate) fn map_device(info: &DeviceInfoResponse) -> Result<Device, MiniHubError> {
+    let name = if info.friendly_name.is_empty() {
+        &info.name
+    } else {
+        &info.friendly_name
+    };
+    let unique_id = if info.mac_address.is_empty() {
+        &info.name
+    } else {
+        &info.mac_address
+    };
+    let mut builder = Device::builder()
+        .name(name)
+        .integration("esphome")
+        .unique_id(unique_id);
+    if !info.manufacturer.is_empty() {
+        builder = builder.manufacturer(&info.manufacturer);
+    }
+    if !info.model.is_empty() {
+        builder = builder.model(&info.model);
+    }
+    if !info.esphome_version.is_empty() {
+        builder = builder.sw_version(&info.esphome_version);
+    }
+    if !info.suggested_area.is_empty() {
+        builder = builder.suggested_area(&info.suggested_area);
+    }
+    builder.build()
+}
+
+/// Map a listed component to an entity of `device`.
+///
+/// `slug` is the `entity_id`-safe node name and `address` the configured
+/// `host:port` recorded in the binding.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the entity fails domain
+/// validation.
+pub(crate) fn map_entity(
+    device: &Device,
+    slug: &str,
+    address: &str,
+    listed: &Listed,
+) -> Result<(Entity, Binding), MiniHubError> {
+    let (domain, component, object_id, name, key) = match listed {
+        Listed::BinarySensor(info) => (
+            "binary_sensor",
+            Component::BinarySensor,
+            &info.object_id,
+            &info.name,
+            info.key,
+        ),
+        Listed::Light(info) => (
+            "light",
+            Component::Light,
+            &info.object_id,
+            &info.name,
+            info.key,
+        ),
+        Listed::Sensor(info) => (
+            "sensor",
+            Component::Sensor,
+            &info.object_id,
+            &info.name,
+            info.key,
+        ),
+        Listed::Switch(info) => (
+            "switch",
+            Component::Switch,
+            &info.object_id,
+            &info.name,
+            info.key,
+        ),
+    };
+    // Recent ESPHome versions leave the name empty for the node's main
+    // component, which then takes the device name.
+    let friendly_name = if name.is_empty() { &device.name } else { name };
+
+    let mut builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(format!("{domain}.{slug}_{}", slugify(object_id)))
+        .friendly_name(friendly_name);
+    match listed {
+        Listed::BinarySensor(info) => {
+            if let Some(class) = device_class(&info.device_class) {
+                builder = builder.device_class(class);
+            }
+        }
+        Listed::Light(_) => {
+            builder = builder.attribute_meta(
+                BRIGHTNESS,
+                AttributeMeta {
+                    min: Some(0.0),
+                    max: Some(255.0),
+                    ..AttributeMeta::default()
+                },
+            );
+        }
+        Listed::Sensor(info) => {
+            let class = device_class(&info.device_class);
+            if let Some(class) = class {
+                builder = builder.device_class(class);
+            }
+            let unit =
+                (!info.unit_of_measurement.is_empty()).then(|| info.unit_of_measurement.clone());
+            if let Some(unit) = &unit {
+                builder = builder.unit_of_measurement(unit);
+            }
+            builder = builder.attribute_meta(
+                sensor_attribute(class),
+                AttributeMeta {
+                    precision: u32::try_from(info.accuracy_decimals).ok(),
+                    display_unit: unit,
+                    ..AttributeMeta::default()
+                },
+            );
+        }
+        Listed::Switch(_) => {}
+    }
+
+    let entity = builder.build()?;
+    let binding = Binding {
+        device: address.to_string(),
+        key,
+        component,
+    };
+    Ok((entity, binding))
+}
+
+/// ESPHome device classes with a minihub equivalent.
+const DEVICE_CLASSES: &[(&str, DeviceClass)] = &[
+    ("temperature", DeviceClass::Temperature),
+    ("humidity", DeviceClass::Humidity),
+    ("illuminance", DeviceClass::Illuminance),
+    ("moisture", DeviceClass::Moisture),
+    ("battery", DeviceClass::Battery),
+    ("voltage", DeviceClass::Voltage),
+    ("power", DeviceClass::Power),
+    ("energy", DeviceClass::Energy),
+    ("pressure", DeviceClass::Pressure),
+    ("atmospheric_pressure", DeviceClass::Pressure),
+    ("motion", DeviceClass::Motion),
+    ("occupancy", DeviceClass::Occupancy),
+    ("presence", DeviceClass::Occupancy),
+    ("door", DeviceClass::Door),
+    ("garage_door", DeviceClass::Door),
+    ("window", DeviceClass::Window),
+];
+
+fn device_class(name: &str) -> Option<DeviceClass> {
+    DEVICE_CLASSES
+        .iter()
+        .find(|(esphome, _)| *esphome == name)
+        .map(|(_, class)| *class)
+}
+
+/// Attribute carrying a sensor's reading.
+fn sensor_attribute(class: Option<DeviceClass>) -> &'static str {
+    class.map_or(VALUE, DeviceClass::as_str)
+}
+
+/// Apply a state update to the entity it targets.
+///
+/// Returns `true` when the entity's state or one of its attributes changed.
+pub(crate) fn apply_state(entity: &mut Entity, update: &StateUpdate) -> bool {
+    let before = (entity.state.clone(), entity.attributes.clone());
+    let now = minihub_domain::time::now();
+    match update {
+        StateUpdate::BinarySensor(state) => {
+            entity.update_state(
+                match (state.missing_state, state.state) {
+                    (true, _) => EntityState::Unknown,
+                    (false, true) => EntityState::On,
+                    (false, false) => EntityState::Off,
+                },
+                now,
+            );
+        }
+        StateUpdate::Switch(state) => entity.update_state(on_off(state.state), now),
+        StateUpdate::Light(state) => {
+            entity.update_state(on_off(state.state), now);
+            entity.set_attribute(
+                BRIGHTNESS.to_string(),
+                AttributeValue::Int(brightness_to_int(state.brightness)),
+            );
+        }
+        StateUpdate::Sensor(state) => {
+            if state.missing_state || state.state.is_nan() {
+                entity.update_state(EntityState::Unknown, now);
+            } else {
+                entity.update_state(EntityState::On, now);
+                entity.set_attribute(
+                    sensor_attribute(entity.device_class).to_string(),
+                    AttributeValue::Float(f64::from(state.state)),
+                );
+            }
+        }
+    }
+    before != (entity.state.clone(), entity.attributes.clone())
+}
+
+fn on_off(on: bool) -> EntityState {
+    if on {
+        EntityState::On
+    } else {
+        EntityState::Off
+    }
+}
+
+/// Convert ESPHome's `0.0..=1.0` brightness to `0..=255`.
+#[allow(clippy::cast_possible_truncation)]
+fn brightness_to_int(brightness: f32) -> i64 {
+    (brightness.clamp(0.0, 1.0) * 255.0).round() as i64
+}
+
+/// Build the command for a service call.
+///
+/// `turn_on`, `turn_off` and `toggle` are supported for switches and
+/// lights; `turn_on` on a light also accepts a `brightness` between `0`
+/// and `255` in `data`. Returns `None` when the entity cannot be
+/// controlled or the service is not supported.
+pub(crate) fn command(
+    binding: &Binding,
+    current: &EntityState,
+    service: &str,
+    data: &Value,
+) -> Option<Command> {
+    let state = match service {
+        "turn_on" => true,
+        "turn_off" => false,
+        "toggle" => *current != EntityState::On,
+        _ => return None,
+    };
+    match binding.component {
+        Component::Switch => Some(Command::Switch(SwitchCommandRequest {
+            key: binding.key,
+            state,
+        })),
+        Component::Light => {
+            let brightness = data
+                .get(BRIGHTNESS)
+                .and_then(Value::as_f64)
+                .filter(|_| state);
+            #[allow(clippy::cast_possible_truncation)]
+            Some(Command::Light(LightCommandRequest {
+                key: binding.key,
+                has_state: true,
+                state,
+                has_brightness: brightness.is_some(),
+                brightness: brightness
+                    .map_or(0.0, |value| (value.clamp(0.0, 255.0) / 255.0) as f32),
+            }))
+        }
+        Component::BinarySensor | Component::Sensor => None,
+    }
+}
+
+/// Turn a node or object name into an `entity_id`-safe slug.
+pub(crate) fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "living-room.local:6053";
+
+    fn device() -> Device {
+        map_device(&DeviceInfoResponse {
+            name: "living-room".to_string(),
+            mac_address: "AC:67:B2:00:00:01".to_string(),
+            esphome_version: "2024.6.0".to_string(),
+            model: "esp32dev".to_string(),
+            manufacturer: "Espressif".to_string(),
+            friendly_name: "Living Room".to_string(),
+            suggested_area: "Living Room".to_string(),
+        })
+        .unwrap()
+    }
+
+    fn map(listed: &Listed) -> (Entity, Binding) {
+        map_entity(&device(), "living_room", ADDRESS, listed).unwrap()
+    }
+
+    fn temperature_sensor() -> Listed {
+        Listed::Sensor(ListEntitiesSensorResponse {
+            object_id: "temperature".to_string(),
+            key: 7,
+            name: "Temperature".to_string(),
+            unit_of_measurement: "°C".to_string(),
+            accuracy_decimals: 1,
+            device_class: "temperature".to_string(),
+        })
+    }
+
+    fn light() -> Listed {
+        Listed::Light(ListEntitiesLightResponse {
+            object_id: "ceiling".to_string(),
+            key: 3,
+            name: "Ceiling".to_string(),
+        })
+    }
+
+    #[test]
+    fn should_map_device_info_to_device() {
+        let device = device();
+        assert_eq!(device.name, "Living Room");
+        assert_eq!(device.manufacturer.as_deref(), Some("Espressif"));
+        assert_eq!(device.model.as_deref(), Some("esp32dev"));
+        assert_eq!(device.integration, "esphome");
+        assert_eq!(device.unique_id, "AC:67:B2:00:00:01");
+        assert_eq!(device.sw_version.as_deref(), Some("2024.6.0"));
+        assert_eq!(device.suggested_area.as_deref(), Some("Living Room"));
+    }
+
+    #[test]
+    fn should_map_sensor_with_class_unit_and_precision() {
+        let (entity, binding) = map(&temperature_sensor());
+
+        assert_eq!(entity.entity_id, "sensor.living_room_temperature");
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.unit_of_measurement.as_deref(), Some("°C"));
+        assert_eq!(entity.attribute_meta["temperature"].precision, Some(1));
+        assert_eq!(
+            binding,
+            Binding {
+                device: ADDRESS.to_string(),
+                key: 7,
+                component: Component::Sensor,
+            }
+        );
+    }
+
+    #[test]
+    fn should_use_device_name_for_unnamed_component() {
+        let (entity, _) = map(&Listed::Switch(ListEntitiesSwitchResponse {
+            object_id: "relay".to_string(),
+            key: 1,
+            name: String::new(),
+        }));
+        assert_eq!(entity.entity_id, "switch.living_room_relay");
+        assert_eq!(entity.friendly_name, "Living Room");
+    }
+
+    #[test]
+    fn should_store_sensor_reading_under_class_attribute() {
+        let (mut entity, _) = map(&temperature_sensor());
+        let update = StateUpdate::Sensor(SensorStateResponse {
+            key: 7,
+            state: 21.5,
+            missing_state: false,
+        });
+
+        assert!(apply_state(&mut entity, &update));
+        assert!(!apply_state(&mut entity, &update));
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("temperature"),
+            Some(&AttributeValue::Float(21.5))
+        );
+    }
+
+    #[test]
+    fn should_mark_sensor_unknown_when_state_is_missing() {
+        let (mut entity, _) = map(&Listed::Sensor(ListEntitiesSensorResponse {
+            object_id: "uptime".to_string(),
+            key: 9,
+            ..ListEntitiesSensorResponse::default()
+        }));
+        let update = StateUpdate::Sensor(SensorStateResponse {
+            key: 9,
+            state: f32::NAN,
+            missing_state: true,
+        });
+
+        apply_state(&mut entity, &update);
+
+        assert_eq!(entity.state, EntityState::Unknown);
+        assert!(entity.get_attribute(VALUE).is_none());
+    }
+
+    #[test]
+    fn should_apply_light_state_and_brightness() {
+        let (mut entity, _) = map(&light());
+        let update = StateUpdate::Light(LightStateResponse {
+            key: 3,
+            state: true,
+            brightness: 0.5,
+        });
+
+        assert!(apply_state(&mut entity, &update));
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute(BRIGHTNESS),
+            Some(&AttributeValue::Int(128))
+        );
+    }
+
+    #[test]
+    fn should_build_light_command_with_brightness() {
+        let (_, binding) = map(&light());
+        let command = command(
+            &binding,
+            &EntityState::Off,
+            "turn_on",
+            &serde_json::json!({ "brightness": 255 }),
+        );
+        assert_eq!(
+            command,
+            Some(Command::Light(LightCommandRequest {
+                key: 3,
+                has_state: true,
+                state: true,
+                has_brightness: true,
+                brightness: 1.0,
+            }))
+        );
+    }
+
+    #[test]
+    fn should_toggle_switch_from_current_state() {
+        let (_, binding) = map(&Listed::Switch(ListEntitiesSwitchResponse {
+            object_id: "relay".to_string(),
+            key: 1,
+            name: "Relay".to_string(),
+        }));
+        let command = command(&binding, &EntityState::On, "toggle", &Value::Null);
+        assert_eq!(
+            command,
+            Some(Command::Switch(SwitchCommandRequest {
+                key: 1,
+                state: false
+            }))
+        );
+    }
+
+    #[test]
+    fn should_refuse_commands_for_sensors_and_unknown_services() {
+        let (_, sensor) = map(&temperature_sensor());
+        let (_, light) = map(&light());
+        assert_eq!(
+            command(&sensor, &EntityState::On, "turn_on", &Value::Null),
+            None
+        );
+        assert_eq!(
+            command(&light, &EntityState::On, "set_color", &Value::Null),
+            None
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! ESPHome adapter error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the ESPHome adapter.
+#[derive(Debug, thiserror::Error)]
+pub enum EsphomeError {
+    /// Reading from or writing to the device socket failed.
+    #[error("ESPHome connection error")]
+    Io(#[source] std::io::Error),
+
+    /// A frame payload could not be decoded as the expected message.
+    #[error("failed to decode ESPHome message")]
+    Decode(#[source] prost::DecodeError),
+
+    /// The device sent something the native API does not allow here.
+    #[error("ESPHome protocol error: {0}")]
+    Protocol(String),
+
+    /// The device rejected the configured API password.
+    #[error("ESPHome device rejected the API password")]
+    InvalidPassword,
+
+    /// The device is not connected at the moment.
+    #[error("ESPHome device not connected")]
+    NotConnected,
+
+    /// The target entity cannot handle the requested service.
+    #[error("unsupported service: {0}")]
+    UnsupportedService(String),
+
+    /// A domain-level error (validation, not-found, etc.).
+    #[error("{0}")]
+    Domain(#[source] MiniHubError),
+}
+
+impl EsphomeError {
+    /// Convert into a [`MiniHubError::Storage`] for propagation across port
+    /// boundaries.
+    #[must_use]
+    pub fn into_domain(self) -> MiniHubError {
+        match self {
+            Self::Domain(err) => err,
+            other => MiniHubError::Storage(other.into()),
+        }
+    }
+}
+
+impl From<EsphomeError> for MiniHubError {
+    fn from(err: EsphomeError) -> Self {
+        err.into_domain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_protocol_error() {
+        let err = EsphomeError::Protocol("unexpected message type 42".to_string());
+        assert_eq!(
+            err.to_string(),
+            "ESPHome protocol error: unexpected message type 42"
+        );
+    }
+
+    #[test]
+    fn should_convert_not_connected_to_storage_error() {
+        let err: MiniHubError = EsphomeError::NotConnected.into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+
+    #[test]
+    fn should_convert_domain_error_back_to_domain() {
+        let domain_err =
+            MiniHubError::Validation(minihub_domain::error::ValidationError::EmptyName);
+        let back: MiniHubError = EsphomeError::Domain(domain_err).into();
+        assert!(matches!(back, MiniHubError::Validation(_)));
+    }
+}
@@ -0,0 +1,176 @@
+//! Plaintext framing of the native API.
+//!
+//! Every message travels as a `0x00` preamble, the payload size and the
+//! message type (both protobuf varints), then the protobuf payload.
+//! Devices configured with `api: encryption:` answer with a `0x01`
+//! preamble instead; the Noise handshake is not implemented.
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::EsphomeError;
+use crate::proto::ApiMessage;
+
+/// Preamble of a plaintext frame.
+const PLAINTEXT: u8 = 0x00;
+/// Preamble of a Noise-encrypted frame.
+const ENCRYPTED: u8 = 0x01;
+/// Largest payload accepted from a device.
+const MAX_PAYLOAD: u32 = 1024 * 1024;
+
+/// A raw frame read from the socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Frame {
+    pub message_type: u32,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Whether the frame carries a message of type `M`.
+    pub fn is<M: ApiMessage>(&self) -> bool {
+        self.message_type == M::TYPE
+    }
+
+    /// Decode the payload as `M`.
+    pub fn decode<M: ApiMessage>(&self) -> Result<M, EsphomeError> {
+        M::decode(self.payload.as_slice()).map_err(EsphomeError::Decode)
+    }
+}
+
+/// Serialize `message` into a complete plaintext frame.
+pub(crate) fn encode<M: ApiMessage>(message: &M) -> Vec<u8> {
+    let payload = message.encode_to_vec();
+    let mut buf = Vec::with_capacity(payload.len() + 11);
+    buf.push(PLAINTEXT);
+    prost::encoding::encode_varint(payload.len() as u64, &mut buf);
+    prost::encoding::encode_varint(u64::from(M::TYPE), &mut buf);
+    buf.extend_from_slice(&payload);
+    buf
+}
+
+/// Write `message` as a frame and flush it.
+pub(crate) async fn write_message<W, M>(writer: &mut W, message: &M) -> Result<(), EsphomeError>
+where
+    W: AsyncWrite + Unpin,
+    M: ApiMessage,
+{
+    writer
+        .write_all(&encode(message))
+        .await
+        .map_err(EsphomeError::Io)?;
+    writer.flush().await.map_err(EsphomeError::Io)
+}
+
+/// Read the next frame.
+///
+/// Not cancel-safe: a frame interrupted halfway leaves the stream out of
+/// sync, so callers must not race it against other futures.
+pub(crate) async fn read_frame<R>(reader: &mut R) -> Result<Frame, EsphomeError>
+where
+    R: AsyncRead + Unpin,
+{
+    match reader.read_u8().await.map_err(EsphomeError::Io)? {
+        PLAINTEXT => {}
+        ENCRYPTED => {
+            return Err(EsphomeError::Protocol(
+                "device requires API encryption, which is not supported".to_string(),
+            ));
+        }
+        other => {
+            return Err(EsphomeError::Protocol(format!(
+                "invalid frame preamble {other:#04x}"
+            )));
+        }
+    }
+    let size = read_varint(reader).await?;
+    if size > MAX_PAYLOAD {
+        return Err(EsphomeError::Protocol(format!(
+            "frame of {size} bytes exceeds the limit"
+        )));
+    }
+    let message_type = read_varint(reader).await?;
+    let mut payload = vec![0; size as usize];
+    reader
+        .read_exact(&mut payload)
+        .await
+        .map_err(EsphomeError::Io)?;
+    Ok(Frame {
+        message_type,
+        payload,
+    })
+}
+
+/// Read a protobuf varint of at most 32 bits.
+async fn read_varint<R>(reader: &mut R) -> Result<u32, EsphomeError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut value = 0u32;
+    for shift in (0..32).step_by(7) {
+        let byte = reader.read_u8().await.map_err(EsphomeError::Io)?;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(EsphomeError::Protocol("varint too long".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::{HelloRequest, PingRequest, SensorStateResponse};
+
+    #[test]
+    fn should_encode_empty_message_as_header_only() {
+        assert_eq!(encode(&PingRequest {}), vec![0x00, 0x00, 0x07]);
+    }
+
+    #[tokio::test]
+    async fn should_read_back_encoded_frame() {
+        let hello = HelloRequest {
+            client_info: "minihub".to_string(),
+            api_version_major: 1,
+            api_version_minor: 10,
+        };
+        let bytes = encode(&hello);
+
+        let frame = read_frame(&mut bytes.as_slice()).await.unwrap();
+
+        assert!(frame.is::<HelloRequest>());
+        assert_eq!(frame.decode::<HelloRequest>().unwrap(), hello);
+    }
+
+    #[tokio::test]
+    async fn should_read_multi_byte_varints() {
+        let state = SensorStateResponse {
+            key: 42,
+            state: 21.5,
+            missing_state: false,
+        };
+        let mut bytes = vec![0x00];
+        let payload = prost::Message::encode_to_vec(&state);
+        prost::encoding::encode_varint(payload.len() as u64, &mut bytes);
+        // Message type 300 needs two varint bytes.
+        prost::encoding::encode_varint(300, &mut bytes);
+        bytes.extend_from_slice(&payload);
+
+        let frame = read_frame(&mut bytes.as_slice()).await.unwrap();
+
+        assert_eq!(frame.message_type, 300);
+        assert_eq!(frame.payload, payload);
+    }
+
+    #[tokio::test]
+    async fn should_reject_encrypted_frames() {
+        let bytes = [0x01, 0x00, 0x00];
+        let err = read_frame(&mut bytes.as_slice()).await.unwrap_err();
+        assert!(matches!(err, EsphomeError::Protocol(msg) if msg.contains("encryption")));
+    }
+
+    #[tokio::test]
+    async fn should_return_io_error_on_truncated_frame() {
+        let bytes = [0x00, 0x05, 0x07, 0x01];
+        let err = read_frame(&mut bytes.as_slice()).await.unwrap_err();
+        assert!(matches!(err, EsphomeError::Io(_)));
+    }
+}
@@ -0,0 +1,574 @@
+//! # minihub-adapter-esphome
+//!
+//! ESPHome adapter — bridges ESPHome devices into minihub over their
+//! native API (TCP port 6053).
+//!
+//! ## How it works
+//!
+//! Each configured device gets its own background task which:
+//!
+//! 1. opens the connection and exchanges `Hello` / `Connect` (with the API
+//!    password when configured),
+//! 2. reads `DeviceInfo` and enumerates components with `ListEntities`,
+//!    persisting the device and its entities,
+//! 3. sends `SubscribeStates` and applies every pushed state to the
+//!    matching entity, answering pings and time requests meanwhile.
+//!
+//! When the connection drops, the device's entities are marked
+//! `unavailable` and the task reconnects after
+//! [`EsphomeConfig::reconnect_interval_secs`].
+//!
+//! ## Supported components
+//!
+//! | ESPHome component | minihub entity | Services |
+//! |-------------------|----------------|----------|
+//! | `binary_sensor` | `binary_sensor.{node}_{object_id}` | — |
+//! | `sensor` | `sensor.{node}_{object_id}` | — |
+//! | `switch` | `switch.{node}_{object_id}` | `turn_on`, `turn_off`, `toggle` |
+//! | `light` | `light.{node}_{object_id}` | `turn_on` (optional `brightness` 0–255), `turn_off`, `toggle` |
+//!
+//! Only plaintext connections are supported: devices configured with
+//! `api: encryption:` are reported as protocol errors.
+//!
+//! ## Dependency rule
+//!
+//! Same as other adapters: depends on `minihub-app` and `minihub-domain`.
+
+mod config;
+mod connection;
+mod entities;
+mod error;
+mod frame;
+mod proto;
+
+pub use config::{EsphomeConfig, EsphomeDeviceConfig};
+pub use error::EsphomeError;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::io::{AsyncWrite, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use minihub_app::integration_manager::forward_service_calls;
+use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::ServiceCallPayload;
+use minihub_domain::id::EntityId;
+
+use crate::connection::Connection;
+use crate::entities::{Binding, Command, StateUpdate};
+
+/// Entities known to the integration, keyed by `entity_id` string.
+type Tracked = Arc<Mutex<HashMap<String, (Entity, Binding)>>>;
+
+/// Command channels of the connected devices, keyed by `host:port`.
+type Senders = Arc<Mutex<HashMap<String, mpsc::Sender<Command>>>>;
+
+/// ESPHome integration.
+///
+/// Keeps one native API connection per configured device, mirrors
+/// component states into entities and sends switch and light commands.
+pub struct EsphomeIntegration {
+    config: EsphomeConfig,
+    device_handles: Vec<JoinHandle<()>>,
+    subscriber_handle: Option<JoinHandle<()>>,
+    entities: Tracked,
+    senders: Senders,
+}
+
+impl EsphomeIntegration {
+    /// Create a new ESPHome integration with the given configuration.
+    #[must_use]
+    pub fn new(config: EsphomeConfig) -> Self {
+        Self {
+            config,
+            device_handles: Vec::new(),
+            subscriber_handle: None,
+            entities: Arc::new(Mutex::new(HashMap::new())),
+            senders: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Connect to a device forever, reconnecting after each failure.
+    async fn device_loop(
+        device: EsphomeDeviceConfig,
+        client_info: String,
+        reconnect_interval: Duration,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+        senders: Senders,
+    ) {
+        let address = device.address();
+        loop {
+            match Self::session(&device, &client_info, &ctx, &entities, &senders).await {
+                Ok(()) => tracing::info!(%address, "ESPHome device closed the connection"),
+                Err(err) => tracing::warn!(%err, %address, "ESPHome connection failed"),
+            }
+            senders
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&address);
+            for entity in Self::mark_unavailable(&address, &entities) {
+                if let Err(err) = ctx.upsert_entity(entity).await {
+                    tracing::warn!(%err, "failed to persist ESPHome availability");
+                }
+            }
+            tokio::time::sleep(reconnect_interval).await;
+        }
+    }
+
+    /// Run one connection: handshake, discovery, then state streaming until
+    /// the connection ends.
+    async fn session(
+        device: &EsphomeDeviceConfig,
+        client_info: &str,
+        ctx: &impl IntegrationContext,
+        entities: &Tracked,
+        senders: &Senders,
+    ) -> Result<(), EsphomeError> {
+        let address = device.address();
+        let stream = TcpStream::connect(&address)
+            .await
+            .map_err(EsphomeError::Io)?;
+        let mut connection = Connection::new(stream);
+        let hello = connection
+            .handshake(client_info, device.password.as_deref())
+            .await?;
+        tracing::info!(%address, server = %hello.server_info, "connected to ESPHome device");
+
+        let info = connection.device_info().await?;
+        let listed = connection.list_entities().await?;
+        let discovered = Self::apply_listing(&address, &info, &listed, entities)?;
+        tracing::info!(
+            device = %discovered.device.name,
+            entity_count = discovered.entities.len(),
+            "discovered ESPHome device"
+        );
+        if let Err(err) = ctx.persist_discovered(discovered).await {
+            tracing::warn!(%err, "failed to persist ESPHome discovery");
+        }
+
+        connection.subscribe_states().await?;
+        let (mut reader, mut writer) = tokio::io::split(connection.into_inner());
+
+        // Frames are read in their own task since `read_frame` must not be
+        // interrupted by an incoming command.
+        let (frame_tx, mut frame_rx) = mpsc::channel(64);
+        let reader_handle = tokio::spawn(async move {
+            loop {
+                let frame = frame::read_frame(&mut reader).await;
+                let failed = frame.is_err();
+                if frame_tx.send(frame).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let (command_tx, mut command_rx) = mpsc::channel(16);
+        senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(address.clone(), command_tx);
+
+        let result = loop {
+            tokio::select! {
+                frame = frame_rx.recv() => {
+                    let Some(frame) = frame else {
+                        break Ok(());
+                    };
+                    let handled = match frame {
+                        Ok(frame) => {
+                            Self::handle_frame(&address, &frame, &mut writer, ctx, entities).await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    if let Err(err) = handled {
+                        break Err(err);
+                    }
+                }
+                Some(command) = command_rx.recv() => {
+                    if let Err(err) = Self::write_command(&mut writer, &command).await {
+                        break Err(err);
+                    }
+                }
+            }
+        };
+        reader_handle.abort();
+        match result {
+            Err(EsphomeError::NotConnected) => Ok(()),
+            other => other,
+        }
+    }
+
+    /// Map the device description and track its entities.
+    fn apply_listing(
+        address: &str,
+        info: &proto::DeviceInfoResponse,
+        listed: &[entities::Listed],
+        entities: &Tracked,
+    ) -> Result<DiscoveredDevice, EsphomeError> {
+        let device = entities::map_device(info).map_err(EsphomeError::Domain)?;
+        let slug = entities::slugify(&info.name);
+
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut device_entities = Vec::new();
+        for item in listed {
+            let (entity, binding) = entities::map_entity(&device, &slug, address, item)
+                .map_err(EsphomeError::Domain)?;
+            tracked.insert(entity.entity_id.clone(), (entity.clone(), binding));
+            device_entities.push(entity);
+        }
+        Ok(DiscoveredDevice {
+            device,
+            entities: device_entities,
+            via_device: None,
+        })
+    }
+
+    /// Apply a state update, or answer a keep-alive request.
+    async fn handle_frame<W>(
+        address: &str,
+        frame: &frame::Frame,
+        writer: &mut W,
+        ctx: &impl IntegrationContext,
+        entities: &Tracked,
+    ) -> Result<(), EsphomeError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        if let Some(update) = StateUpdate::from_frame(frame)? {
+            if let Some(entity) = Self::apply_update(address, &update, entities)
+                && let Err(err) = ctx.upsert_entity(entity).await
+            {
+                tracing::warn!(%err, "failed to persist ESPHome state update");
+            }
+        } else if !connection::respond(writer, frame).await? {
+            tracing::debug!(
+                message_type = frame.message_type,
+                "ignoring ESPHome message"
+            );
+        }
+        Ok(())
+    }
+
+    /// Apply a state update to the entity with the matching key, returning
+    /// the updated snapshot when something changed.
+    fn apply_update(address: &str, update: &StateUpdate, entities: &Tracked) -> Option<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let (entity, _) = tracked
+            .values_mut()
+            .find(|(_, binding)| binding.device == address && binding.key == update.key())?;
+        entities::apply_state(entity, update).then(|| entity.clone())
+    }
+
+    /// Mark every entity of a device unavailable, returning those that
+    /// changed.
+    fn mark_unavailable(address: &str, entities: &Tracked) -> Vec<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        tracked
+            .values_mut()
+            .filter(|(entity, binding)| binding.device == address && entity.state.is_available())
+            .map(|(entity, _)| {
+                entity.update_state(EntityState::Unavailable, minihub_domain::time::now());
+                entity.clone()
+            })
+            .collect()
+    }
+
+    async fn write_command(
+        writer: &mut WriteHalf<TcpStream>,
+        command: &Command,
+    ) -> Result<(), EsphomeError> {
+        match command {
+            Command::Light(request) => frame::write_message(writer, request).await,
+            Command::Switch(request) => frame::write_message(writer, request).await,
+        }
+    }
+
+    /// Build the command for `service` and hand it to the device's
+    /// connection.
+    async fn send_command(
+        entities: &Tracked,
+        senders: &Senders,
+        entity_id: &str,
+        service: &str,
+        data: &serde_json::Value,
+    ) -> Result<Entity, EsphomeError> {
+        let (entity, binding) = entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| {
+                EsphomeError::Domain(
+                    NotFoundError {
+                        entity: "Entity",
+                        id: entity_id.to_string(),
+                    }
+                    .into(),
+                )
+            })?;
+        let command = entities::command(&binding, &entity.state, service, data)
+            .ok_or_else(|| EsphomeError::UnsupportedService(service.to_string()))?;
+        let sender = senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&binding.device)
+            .cloned()
+            .ok_or(EsphomeError::NotConnected)?;
+        sender
+            .send(command)
+            .await
+            .map_err(|_| EsphomeError::NotConnected)?;
+
+        tracing::info!(service, entity_id, device = %binding.device, "sent ESPHome command");
+        Ok(entity)
+    }
+
+    /// Send the service call requested on `entity_id` to its device,
+    /// unless the entity is not an ESPHome one. The new state is persisted
+    /// once the device reports it.
+    async fn forward_service_call(
+        entities: Tracked,
+        senders: Senders,
+        entity_id: String,
+        request: ServiceCallPayload,
+    ) -> Option<Result<(), EsphomeError>> {
+        if !entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&entity_id)
+        {
+            return None;
+        }
+        let sent = Self::send_command(
+            &entities,
+            &senders,
+            &entity_id,
+            &request.service,
+            &request.data,
+        )
+        .await;
+        Some(sent.map(|_| ()))
+    }
+}
+
+impl Integration for EsphomeIntegration {
+    fn name(&self) -> &'static str {
+        "esphome"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        // Devices may be offline at startup, so connections are only opened
+        // by the background tasks, which keep retrying.
+        tracing::info!(
+            device_count = self.config.devices.len(),
+            "ESPHome integration configured"
+        );
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let bus_rx = ctx.subscribe();
+        let (entities, senders) = (Arc::clone(&self.entities), Arc::clone(&self.senders));
+        self.subscriber_handle = Some(tokio::spawn(forward_service_calls(
+            "esphome",
+            bus_rx,
+            ctx.clone(),
+            move |entity_id, request| {
+                Self::forward_service_call(
+                    Arc::clone(&entities),
+                    Arc::clone(&senders),
+                    entity_id,
+                    request,
+                )
+            },
+        )));
+
+        let reconnect_interval = Duration::from_secs(self.config.reconnect_interval_secs);
+        for device in &self.config.devices {
+            self.device_handles.push(tokio::spawn(Self::device_loop(
+                device.clone(),
+                self.config.client_info.clone(),
+                reconnect_interval,
+                ctx.clone(),
+                Arc::clone(&self.entities),
+                Arc::clone(&self.senders),
+            )));
+        }
+
+        tracing::info!("ESPHome background tasks started");
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+    ) -> Result<Entity, MiniHubError> {
+        let key = self
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .find(|(entity, _)| entity.id == entity_id)
+            .map(|(entity, _)| entity.entity_id.clone())
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+
+        Ok(Self::send_command(&self.entities, &self.senders, &key, service, &data).await?)
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        for handle in self
+            .device_handles
+            .drain(..)
+            .chain(self.subscriber_handle.take())
+        {
+            handle.abort();
+        }
+        self.senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        tracing::info!("ESPHome integration stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Listed;
+    use crate::proto::{
+        DeviceInfoResponse, ListEntitiesSwitchResponse, SwitchCommandRequest, SwitchStateResponse,
+    };
+
+    const ADDRESS: &str = "10.0.0.5:6053";
+
+    fn discovered() -> (Tracked, DiscoveredDevice) {
+        let entities: Tracked = Arc::new(Mutex::new(HashMap::new()));
+        let info = DeviceInfoResponse {
+            name: "garage".to_string(),
+            mac_address: "AC:67:B2:00:00:02".to_string(),
+            ..DeviceInfoResponse::default()
+        };
+        let listed = [Listed::Switch(ListEntitiesSwitchResponse {
+            object_id: "door_relay".to_string(),
+            key: 11,
+            name: "Door relay".to_string(),
+        })];
+        let discovered =
+            EsphomeIntegration::apply_listing(ADDRESS, &info, &listed, &entities).unwrap();
+        (entities, discovered)
+    }
+
+    #[test]
+    fn should_track_listed_entities() {
+        let (entities, discovered) = discovered();
+
+        assert_eq!(discovered.device.name, "garage");
+        assert_eq!(discovered.entities.len(), 1);
+        assert!(
+            entities
+                .lock()
+                .unwrap()
+                .contains_key("switch.garage_door_relay")
+        );
+    }
+
+    #[test]
+    fn should_apply_update_only_to_matching_key() {
+        let (entities, _) = discovered();
+        let update = |key| StateUpdate::Switch(SwitchStateResponse { key, state: true });
+
+        assert!(EsphomeIntegration::apply_update(ADDRESS, &update(12), &entities).is_none());
+        let entity = EsphomeIntegration::apply_update(ADDRESS, &update(11), &entities).unwrap();
+        assert_eq!(entity.state, EntityState::On);
+        assert!(EsphomeIntegration::apply_update(ADDRESS, &update(11), &entities).is_none());
+    }
+
+    #[test]
+    fn should_mark_entities_unavailable_once() {
+        let (entities, _) = discovered();
+
+        assert_eq!(
+            EsphomeIntegration::mark_unavailable(ADDRESS, &entities).len(),
+            1
+        );
+        assert!(EsphomeIntegration::mark_unavailable(ADDRESS, &entities).is_empty());
+        assert!(EsphomeIntegration::mark_unavailable("other:6053", &entities).is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_forward_command_to_connected_device() {
+        let (entities, _) = discovered();
+        let senders: Senders = Arc::new(Mutex::new(HashMap::new()));
+        let (tx, mut rx) = mpsc::channel(1);
+        senders.lock().unwrap().insert(ADDRESS.to_string(), tx);
+
+        EsphomeIntegration::send_command(
+            &entities,
+            &senders,
+            "switch.garage_door_relay",
+            "turn_on",
+            &serde_json::Value::Null,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            rx.recv().await,
+            Some(Command::Switch(SwitchCommandRequest {
+                key: 11,
+                state: true
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_not_connected_when_device_is_offline() {
+        let (entities, _) = discovered();
+        let senders: Senders = Arc::new(Mutex::new(HashMap::new()));
+
+        let err = EsphomeIntegration::send_command(
+            &entities,
+            &senders,
+            "switch.garage_door_relay",
+            "turn_off",
+            &serde_json::Value::Null,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, EsphomeError::NotConnected));
+    }
+
+    #[tokio::test]
+    async fn should_return_error_for_unknown_entity() {
+        let integration = EsphomeIntegration::new(EsphomeConfig::default());
+        let result = integration
+            .handle_service_call(EntityId::new(), "turn_on", serde_json::Value::Null)
+            .await;
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_teardown_without_error_when_not_started() {
+        let mut integration = EsphomeIntegration::new(EsphomeConfig::default());
+        assert!(integration.teardown().await.is_ok());
+    }
+}
@@ -0,0 +1,241 @@
+//! Subset of the ESPHome native API messages (`api.proto`).
+//!
+//! Only the messages minihub needs are declared. Field tags and message
+//! type ids follow the upstream definitions; fields minihub does not read
+//! are left out, and prost skips them while decoding.
+
+/// A protobuf message with its native API message type id.
+pub(crate) trait ApiMessage: prost::Message + Default {
+    /// Message type id written in the frame header.
+    const TYPE: u32;
+}
+
+macro_rules! api_message {
+    ($($name:ident = $id:literal),* $(,)?) => {
+        $(impl ApiMessage for $name {
+            const TYPE: u32 = $id;
+        })*
+    };
+}
+
+api_message! {
+    HelloRequest = 1,
+    HelloResponse = 2,
+    ConnectRequest = 3,
+    ConnectResponse = 4,
+    DisconnectRequest = 5,
+    DisconnectResponse = 6,
+    PingRequest = 7,
+    PingResponse = 8,
+    DeviceInfoRequest = 9,
+    DeviceInfoResponse = 10,
+    ListEntitiesRequest = 11,
+    ListEntitiesBinarySensorResponse = 12,
+    ListEntitiesLightResponse = 15,
+    ListEntitiesSensorResponse = 16,
+    ListEntitiesSwitchResponse = 17,
+    ListEntitiesDoneResponse = 19,
+    SubscribeStatesRequest = 20,
+    BinarySensorStateResponse = 21,
+    LightStateResponse = 24,
+    SensorStateResponse = 25,
+    SwitchStateResponse = 26,
+    LightCommandRequest = 32,
+    SwitchCommandRequest = 33,
+    GetTimeRequest = 36,
+    GetTimeResponse = 37,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HelloRequest {
+    #[prost(string, tag = "1")]
+    pub client_info: String,
+    #[prost(uint32, tag = "2")]
+    pub api_version_major: u32,
+    #[prost(uint32, tag = "3")]
+    pub api_version_minor: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HelloResponse {
+    #[prost(uint32, tag = "1")]
+    pub api_version_major: u32,
+    #[prost(uint32, tag = "2")]
+    pub api_version_minor: u32,
+    #[prost(string, tag = "3")]
+    pub server_info: String,
+    #[prost(string, tag = "4")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ConnectRequest {
+    #[prost(string, tag = "1")]
+    pub password: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ConnectResponse {
+    #[prost(bool, tag = "1")]
+    pub invalid_password: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct DisconnectRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct DisconnectResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct PingRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct PingResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct DeviceInfoRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct DeviceInfoResponse {
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub mac_address: String,
+    #[prost(string, tag = "4")]
+    pub esphome_version: String,
+    #[prost(string, tag = "6")]
+    pub model: String,
+    #[prost(string, tag = "12")]
+    pub manufacturer: String,
+    #[prost(string, tag = "13")]
+    pub friendly_name: String,
+    #[prost(string, tag = "16")]
+    pub suggested_area: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesBinarySensorResponse {
+    #[prost(string, tag = "1")]
+    pub object_id: String,
+    #[prost(fixed32, tag = "2")]
+    pub key: u32,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "5")]
+    pub device_class: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesLightResponse {
+    #[prost(string, tag = "1")]
+    pub object_id: String,
+    #[prost(fixed32, tag = "2")]
+    pub key: u32,
+    #[prost(string, tag = "3")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesSensorResponse {
+    #[prost(string, tag = "1")]
+    pub object_id: String,
+    #[prost(fixed32, tag = "2")]
+    pub key: u32,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "6")]
+    pub unit_of_measurement: String,
+    #[prost(int32, tag = "7")]
+    pub accuracy_decimals: i32,
+    #[prost(string, tag = "9")]
+    pub device_class: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesSwitchResponse {
+    #[prost(string, tag = "1")]
+    pub object_id: String,
+    #[prost(fixed32, tag = "2")]
+    pub key: u32,
+    #[prost(string, tag = "3")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ListEntitiesDoneResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct SubscribeStatesRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct BinarySensorStateResponse {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(bool, tag = "2")]
+    pub state: bool,
+    #[prost(bool, tag = "3")]
+    pub missing_state: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct LightStateResponse {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(bool, tag = "2")]
+    pub state: bool,
+    /// Brightness between `0.0` and `1.0`.
+    #[prost(float, tag = "3")]
+    pub brightness: f32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct SensorStateResponse {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(float, tag = "2")]
+    pub state: f32,
+    #[prost(bool, tag = "3")]
+    pub missing_state: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct SwitchStateResponse {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(bool, tag = "2")]
+    pub state: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct LightCommandRequest {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(bool, tag = "2")]
+    pub has_state: bool,
+    #[prost(bool, tag = "3")]
+    pub state: bool,
+    #[prost(bool, tag = "4")]
+    pub has_brightness: bool,
+    #[prost(float, tag = "5")]
+    pub brightness: f32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct SwitchCommandRequest {
+    #[prost(fixed32, tag = "1")]
+    pub key: u32,
+    #[prost(bool, tag = "2")]
+    pub state: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct GetTimeRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct GetTimeResponse {
+    #[prost(fixed32, tag = "1")]
+    pub epoch_seconds: u32,
+}
@@ -0,0 +1,58 @@
+//! Attribution of API requests to an audit [`Actor`].
+
+use axum::extract::Request;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use minihub_app::audit::act_as;
+use minihub_domain::audit::Actor;
+
+/// Header set by browsers on every request, telling whether it comes from
+/// a page of the same origin.
+const SEC_FETCH_SITE: &str = "sec-fetch-site";
+
+/// Middleware running the request on behalf of its [`Actor`], so the writes
+/// it makes are attributed to the dashboard or to an API client in the
+/// audit log.
+pub async fn attribute(request: Request, next: Next) -> Response {
+    let actor = actor_of(request.headers());
+    act_as(actor, next.run(request)).await
+}
+
+/// The actor behind a request: the dashboard when a browser sends it from a
+/// page served by the hub, an API client otherwise.
+fn actor_of(headers: &HeaderMap) -> Actor {
+    let same_origin = headers
+        .get(SEC_FETCH_SITE)
+        .is_some_and(|value| value.as_bytes() == b"same-origin");
+    if same_origin {
+        Actor::Dashboard
+    } else {
+        Actor::Api
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn should_attribute_same_origin_browser_requests_to_dashboard() {
+        let mut headers = HeaderMap::new();
+        headers.insert(SEC_FETCH_SITE, HeaderValue::from_static("same-origin"));
+
+        assert_eq!(actor_of(&headers), Actor::Dashboard);
+    }
+
+    #[test]
+    fn should_attribute_other_requests_to_api() {
+        let mut cross_site = HeaderMap::new();
+        cross_site.insert(SEC_FETCH_SITE, HeaderValue::from_static("cross-site"));
+
+        assert_eq!(actor_of(&HeaderMap::new()), Actor::Api);
+        assert_eq!(actor_of(&cross_site), Actor::Api);
+    }
+}
@@ -8,16 +8,12 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde::Deserialize;
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
 use minihub_domain::area::Area;
-use minihub_domain::error::MiniHubError;
 use minihub_domain::id::AreaId;
 
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
 
 /// Request body for creating an area.
 #[derive(Deserialize)]
@@ -26,6 +22,12 @@ pub struct CreateAreaRequest {
     pub parent_id: Option<String>,
 }
 
+/// Request body for renaming an area.
+#[derive(Deserialize)]
+pub struct UpdateAreaRequest {
+    pub name: String,
+}
+
 /// Possible responses from the list endpoint.
 pub enum ListResponse {
     Ok(Json<Vec<Area>>),
@@ -79,68 +81,30 @@ impl IntoResponse for DeleteResponse {
 }
 
 /// `GET /api/areas`
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
     let areas = state.area_service.list_areas().await?;
     Ok(ListResponse::Ok(Json(areas)))
 }
 
 /// `GET /api/areas/:id`
-pub async fn get<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let area_id = AreaId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
+) -> Result<GetResponse, ApiError> {
+    let area_id = AreaId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
     let area = state.area_service.get_area(area_id).await?;
     Ok(GetResponse::Ok(Json(area)))
 }
 
 /// `POST /api/areas`
-pub async fn create<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-    Json(req): Json<CreateAreaRequest>,
-) -> Result<CreateResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<CreateAreaRequest>,
+) -> Result<CreateResponse, ApiError> {
     let parent_id = req
         .parent_id
-        .map(|s| AreaId::from_str(&s))
-        .transpose()
-        .map_err(|_| {
-            ApiError::from(MiniHubError::Validation(
-                minihub_domain::error::ValidationError::EmptyName,
-            ))
-        })?;
+        .map(|s| AreaId::from_str(&s).map_err(|_| ApiError::invalid_id("parent_id", &s)))
+        .transpose()?;
 
     let mut builder = Area::builder().name(req.name);
     if let Some(parent_id) = parent_id {
@@ -152,25 +116,25 @@ where
     Ok(CreateResponse::Created(Json(created)))
 }
 
+/// `PUT /api/areas/:id`
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<UpdateAreaRequest>,
+) -> Result<GetResponse, ApiError> {
+    let area_id = AreaId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let mut area = state.area_service.get_area(area_id).await?;
+    area.name = req.name;
+    let updated = state.area_service.update_area(area).await?;
+    Ok(GetResponse::Ok(Json(updated)))
+}
+
 /// `DELETE /api/areas/:id`
-pub async fn delete<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<DeleteResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let area_id = AreaId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
+) -> Result<DeleteResponse, ApiError> {
+    let area_id = AreaId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
     state.area_service.delete_area(area_id).await?;
     Ok(DeleteResponse::NoContent)
 }
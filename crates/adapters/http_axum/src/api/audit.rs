@@ -0,0 +1,125 @@
+//! JSON REST handler for the audit log of write operations.
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_app::ports::{AuditQuery, AuditRepository};
+use minihub_domain::audit::{Actor, AuditEntry};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::AuditEntryId;
+
+use crate::api::events::{NEXT_CURSOR_HEADER, decode_cursor, encode_cursor};
+use crate::auth::CallerRole;
+use crate::error::ApiError;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
+
+/// Number of entries listed per page unless `limit` says otherwise.
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// Largest page the list endpoint returns.
+const MAX_LIST_LIMIT: usize = 1000;
+
+/// Query parameters for the list endpoint.
+#[derive(Deserialize)]
+pub struct ListQuery {
+    /// Only entries of this actor, e.g. `integration:mqtt`, or of this kind
+    /// of actor, e.g. `integration`.
+    pub actor: Option<String>,
+    /// Only entries about this kind of resource, e.g. `automation`.
+    pub target_type: Option<String>,
+    /// Only entries about the resource with this identifier.
+    pub target_id: Option<String>,
+    /// Maximum number of entries. Defaults to 100, capped at 1000.
+    pub limit: Option<usize>,
+    /// Cursor of the page to fetch, from the previous page's
+    /// [`NEXT_CURSOR_HEADER`].
+    pub cursor: Option<String>,
+}
+
+impl ListQuery {
+    /// Build the repository query, rejecting malformed parameters.
+    fn to_audit_query(&self) -> Result<AuditQuery, ApiError> {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let mut query = AuditQuery::new(limit);
+        if let Some(actor) = &self.actor {
+            if !Actor::KINDS.contains(&actor.as_str()) {
+                actor.parse::<Actor>().map_err(MiniHubError::from)?;
+            }
+            query = query.with_actor(actor.clone());
+        }
+        if let Some(target_type) = &self.target_type {
+            query = query.with_target_type(target_type.clone());
+        }
+        if let Some(target_id) = &self.target_id {
+            query = query.with_target_id(target_id.clone());
+        }
+        if let Some(cursor) = &self.cursor {
+            let (timestamp, id): (_, AuditEntryId) = decode_cursor(cursor)?;
+            query = query.with_before(timestamp, id);
+        }
+        Ok(query)
+    }
+}
+
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    /// 200 OK with the entries, newest first, and the cursor of the next
+    /// page when there may be one.
+    Ok {
+        entries: Json<Vec<AuditEntry>>,
+        next_cursor: Option<String>,
+    },
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok {
+                entries,
+                next_cursor,
+            } => {
+                let mut response = entries.into_response();
+                if let Some(value) = next_cursor.and_then(|c| HeaderValue::from_str(&c).ok()) {
+                    response.headers_mut().insert(NEXT_CURSOR_HEADER, value);
+                }
+                response
+            }
+        }
+    }
+}
+
+/// `GET /api/audit?actor=&target_type=&target_id=&limit=&cursor=` — list
+/// the creates, updates and deletes, newest first.
+///
+/// When the page is full, the cursor of the next one is returned in the
+/// [`NEXT_CURSOR_HEADER`] header.
+pub async fn list<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+    QueryParams(params): QueryParams<ListQuery>,
+) -> Result<ListResponse, ApiError> {
+    let query = params.to_audit_query()?;
+    let limit = query.limit;
+    let mut entries = state.audit_repo.query(query).await?;
+    for entry in &mut entries {
+        crate::redact::audit_entry(caller, entry);
+    }
+    let next_cursor = (entries.len() == limit)
+        .then(|| {
+            entries
+                .last()
+                .map(|entry| encode_cursor(entry.timestamp, entry.id))
+        })
+        .flatten();
+    Ok(ListResponse::Ok {
+        entries: Json(entries),
+        next_cursor,
+    })
+}
@@ -3,21 +3,28 @@
 use std::str::FromStr;
 
 use axum::Json;
+use axum::body::Bytes;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::{IntoResponse, Response};
+use json_patch::jsonptr::PointerBuf;
+use json_patch::{Patch, PatchErrorKind, PatchOperation};
 use serde::Deserialize;
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
+use minihub_app::ports::AutomationRunRepository;
+use minihub_domain::automation::{
+    Action, Automation, Condition, ExecutionMode, ScheduledFiring, Trigger, upcoming_firings,
 };
-use minihub_domain::automation::{Action, Automation, Condition, Trigger};
-use minihub_domain::error::MiniHubError;
-use minihub_domain::id::AutomationId;
+use minihub_domain::automation_run::{AutomationRun, AutomationTrace};
+use minihub_domain::error::{ConflictError, MiniHubError, ValidationError};
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::{AutomationId, EntityId};
+use minihub_domain::time::{Timestamp, now};
 
+use crate::auth::CallerRole;
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::{JsonBody, QueryParams};
+use crate::state::{AppState, Ports};
 
 /// Request body for creating an automation.
 #[derive(Deserialize)]
@@ -27,6 +34,8 @@ pub struct CreateAutomationRequest {
     pub trigger: Trigger,
     pub conditions: Option<Vec<Condition>>,
     pub actions: Vec<Action>,
+    /// Defaults to `single`.
+    pub execution_mode: Option<ExecutionMode>,
 }
 
 /// Request body for updating an automation.
@@ -37,6 +46,68 @@ pub struct UpdateAutomationRequest {
     pub trigger: Trigger,
     pub conditions: Vec<Condition>,
     pub actions: Vec<Action>,
+    /// Defaults to the current execution mode.
+    pub execution_mode: Option<ExecutionMode>,
+    /// Version the update is based on. The update is rejected with a
+    /// conflict when the automation changed since. Defaults to the current
+    /// version.
+    pub version: Option<u32>,
+}
+
+/// Request body for testing an automation against a sample event.
+#[derive(Deserialize)]
+pub struct TestAutomationRequest {
+    pub event_type: EventType,
+    pub entity_id: Option<EntityId>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Media type of RFC 6902 JSON Patch documents.
+const JSON_PATCH_MEDIA_TYPE: &str = "application/json-patch+json";
+
+/// Fields of the automation document a patch may not change.
+const READ_ONLY_FIELDS: [&str; 4] = ["id", "last_triggered", "version", "source_key"];
+
+/// Default number of runs returned by the runs endpoint.
+const DEFAULT_RUNS_LIMIT: usize = 50;
+
+/// Query parameters for the runs endpoint.
+#[derive(Deserialize)]
+pub struct RunsQuery {
+    /// Maximum number of runs. Defaults to 50.
+    pub limit: Option<usize>,
+}
+
+/// Number of days covered by the schedule endpoint unless `days` says
+/// otherwise.
+const DEFAULT_SCHEDULE_DAYS: u32 = 7;
+
+/// Longest period covered by the schedule endpoint.
+const MAX_SCHEDULE_DAYS: u32 = 31;
+
+/// Largest number of firings returned by the schedule endpoint.
+const MAX_SCHEDULE_FIRINGS: usize = 1000;
+
+/// Serialization format of the automation schedule.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleFormat {
+    /// JSON array of firings.
+    #[default]
+    Json,
+    /// iCalendar feed, one event per firing.
+    Ics,
+}
+
+/// Query parameters for the schedule endpoint.
+#[derive(Deserialize)]
+pub struct ScheduleQuery {
+    /// Number of days ahead to cover. Defaults to 7, capped at 31.
+    pub days: Option<u32>,
+    /// Output format. Defaults to `json`.
+    #[serde(default)]
+    pub format: ScheduleFormat,
 }
 
 /// Possible responses from the list endpoint.
@@ -78,6 +149,25 @@ impl IntoResponse for CreateResponse {
     }
 }
 
+/// Possible responses from the patch endpoint.
+pub enum PatchResponse {
+    Ok(Json<Automation>),
+    UnsupportedMediaType,
+}
+
+impl IntoResponse for PatchResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+            Self::UnsupportedMediaType => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                [("accept-patch", JSON_PATCH_MEDIA_TYPE)],
+            )
+                .into_response(),
+        }
+    }
+}
+
 /// Possible responses from the delete endpoint.
 pub enum DeleteResponse {
     NoContent,
@@ -91,69 +181,94 @@ impl IntoResponse for DeleteResponse {
     }
 }
 
+/// Possible responses from the test endpoint.
+pub enum TestResponse {
+    Ok(Json<AutomationTrace>),
+}
+
+impl IntoResponse for TestResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the runs endpoint.
+pub enum RunsResponse {
+    Ok(Json<Vec<AutomationRun>>),
+}
+
+impl IntoResponse for RunsResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the schedule endpoint.
+pub enum ScheduleResponse {
+    Json(Json<Vec<ScheduledFiring>>),
+    /// 200 OK with an iCalendar feed.
+    Ics(String),
+}
+
+impl IntoResponse for ScheduleResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Json(json) => json.into_response(),
+            Self::Ics(body) => (
+                [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+                body,
+            )
+                .into_response(),
+        }
+    }
+}
+
 /// `GET /api/automations` — list all automations.
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let automations = state.automation_service.list_automations().await?;
+pub async fn list<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+) -> Result<ListResponse, ApiError> {
+    let mut automations = state.automation_service.list_automations().await?;
+    for automation in &mut automations {
+        crate::redact::automation(caller, automation);
+    }
     Ok(ListResponse::Ok(Json(automations)))
 }
 
 /// `GET /api/automations/:id` — get automation by ID.
-pub async fn get<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
     Path(id): Path<String>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let automation_id = AutomationId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
-    let automation = state
+) -> Result<GetResponse, ApiError> {
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let mut automation = state
         .automation_service
         .get_automation(automation_id)
         .await?;
+    crate::redact::automation(caller, &mut automation);
     Ok(GetResponse::Ok(Json(automation)))
 }
 
 /// `POST /api/automations` — create a new automation.
-pub async fn create<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-    Json(req): Json<CreateAutomationRequest>,
-) -> Result<CreateResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<CreateAutomationRequest>,
+) -> Result<CreateResponse, ApiError> {
     let mut builder = Automation::builder().name(req.name).trigger(req.trigger);
 
     if let Some(enabled) = req.enabled {
         builder = builder.enabled(enabled);
     }
 
+    if let Some(execution_mode) = req.execution_mode {
+        builder = builder.execution_mode(execution_mode);
+    }
+
     if let Some(conditions) = req.conditions {
         for c in conditions {
             builder = builder.condition(c);
@@ -173,28 +288,15 @@ where
 }
 
 /// `PUT /api/automations/:id` — update an existing automation.
-pub async fn update<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-    Json(req): Json<UpdateAutomationRequest>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let automation_id = AutomationId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
+    JsonBody(req): JsonBody<UpdateAutomationRequest>,
+) -> Result<GetResponse, ApiError> {
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
 
     // Verify it exists
-    state
+    let existing = state
         .automation_service
         .get_automation(automation_id)
         .await?;
@@ -203,7 +305,13 @@ where
         .id(automation_id)
         .name(req.name)
         .enabled(req.enabled)
-        .trigger(req.trigger);
+        .trigger(req.trigger)
+        .execution_mode(req.execution_mode.unwrap_or(existing.execution_mode))
+        .version(req.version.unwrap_or(existing.version));
+
+    if let Some(last_triggered) = existing.last_triggered {
+        builder = builder.last_triggered(last_triggered);
+    }
 
     for c in req.conditions {
         builder = builder.condition(c);
@@ -220,28 +328,398 @@ where
     Ok(GetResponse::Ok(Json(updated)))
 }
 
+/// `PATCH /api/automations/:id` — apply an RFC 6902 JSON Patch.
+///
+/// The body must be sent as `application/json-patch+json`. The patch is
+/// applied to the current automation document, which is validated before
+/// being stored with a bumped version. Clients guard against concurrent
+/// edits with a `test` operation on `/version`.
+pub async fn patch<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<PatchResponse, ApiError> {
+    if !is_json_patch(&headers) {
+        return Ok(PatchResponse::UnsupportedMediaType);
+    }
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let operations: Patch = serde_json::from_slice(&body)
+        .map_err(|err| MiniHubError::from(ValidationError::InvalidPatch(err.to_string())))?;
+
+    let existing = state
+        .automation_service
+        .get_automation(automation_id)
+        .await?;
+    let patched = apply_patch(&existing, &operations)?;
+    let updated = state.automation_service.update_automation(patched).await?;
+    Ok(PatchResponse::Ok(Json(updated)))
+}
+
+/// Whether the request body is declared as a JSON Patch document.
+fn is_json_patch(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(JSON_PATCH_MEDIA_TYPE)
+        })
+}
+
+/// Apply `operations` to the document of `automation`.
+///
+/// The patch is atomic: either every operation applies or the automation
+/// is left unchanged.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Conflict`] when a `test` operation fails, and
+/// [`ValidationError::InvalidPatch`] when an operation cannot be applied,
+/// touches a read-only field, or leaves an invalid automation document.
+fn apply_patch(automation: &Automation, operations: &Patch) -> Result<Automation, MiniHubError> {
+    let invalid = |message: String| MiniHubError::from(ValidationError::InvalidPatch(message));
+
+    for pointer in operations.iter().flat_map(modified_paths) {
+        let field = pointer.first().map(|token| token.decoded().into_owned());
+        if field.is_none_or(|field| READ_ONLY_FIELDS.contains(&field.as_str())) {
+            return Err(invalid(format!("path \"{pointer}\" is read-only")));
+        }
+    }
+
+    let mut document = serde_json::to_value(automation).map_err(|err| invalid(err.to_string()))?;
+    if let Err(err) = json_patch::patch(&mut document, operations) {
+        if matches!(err.kind, PatchErrorKind::TestFailed)
+            && let Some(PatchOperation::Test(test)) = operations.get(err.operation)
+        {
+            let actual = document
+                .pointer(test.path.as_str())
+                .map_or_else(|| "missing".to_string(), ToString::to_string);
+            return Err(ConflictError {
+                entity: "Automation",
+                id: automation.id.to_string(),
+                expected: format!("{} = {}", test.path, test.value),
+                actual: format!("{} = {actual}", test.path),
+            }
+            .into());
+        }
+        return Err(invalid(err.to_string()));
+    }
+
+    serde_json::from_value(document).map_err(|err| invalid(err.to_string()))
+}
+
+/// The locations `operation` modifies.
+fn modified_paths(operation: &PatchOperation) -> Vec<&PointerBuf> {
+    match operation {
+        PatchOperation::Add(op) => vec![&op.path],
+        PatchOperation::Remove(op) => vec![&op.path],
+        PatchOperation::Replace(op) => vec![&op.path],
+        PatchOperation::Move(op) => vec![&op.path, &op.from],
+        PatchOperation::Copy(op) => vec![&op.path],
+        PatchOperation::Test(_) => Vec::new(),
+    }
+}
+
 /// `DELETE /api/automations/:id` — delete an automation.
-pub async fn delete<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<DeleteResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let automation_id = AutomationId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
+) -> Result<DeleteResponse, ApiError> {
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
     state
         .automation_service
         .delete_automation(automation_id)
         .await?;
     Ok(DeleteResponse::NoContent)
 }
+
+/// `POST /api/automations/:id/test` — dry-run an automation against a
+/// sample event.
+///
+/// Matches the trigger and evaluates every condition against the current
+/// entity states without executing any action, and returns the trace.
+pub async fn test<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<TestAutomationRequest>,
+) -> Result<TestResponse, ApiError> {
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let automation = state
+        .automation_service
+        .get_automation(automation_id)
+        .await?;
+
+    let event = Event::new(req.event_type, req.entity_id, req.data);
+    let trace = state
+        .entity_service
+        .condition_evaluator()
+        .trace(&automation, &event)
+        .await?;
+    Ok(TestResponse::Ok(Json(trace)))
+}
+
+/// `GET /api/automations/:id/runs?limit=` — execution log of an automation, newest first.
+pub async fn runs<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<RunsQuery>,
+) -> Result<RunsResponse, ApiError> {
+    let automation_id = AutomationId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+
+    // Verify it exists
+    state
+        .automation_service
+        .get_automation(automation_id)
+        .await?;
+
+    let runs = state
+        .automation_run_repo
+        .find_by_automation(automation_id, params.limit.unwrap_or(DEFAULT_RUNS_LIMIT))
+        .await?;
+    Ok(RunsResponse::Ok(Json(runs)))
+}
+
+/// `GET /api/automations/schedule?days=&format=` — upcoming firings of the
+/// enabled `time_pattern` automations, as JSON or an iCalendar feed.
+pub async fn schedule<P: Ports>(
+    State(state): State<AppState<P>>,
+    QueryParams(params): QueryParams<ScheduleQuery>,
+) -> Result<ScheduleResponse, ApiError> {
+    let days = params
+        .days
+        .unwrap_or(DEFAULT_SCHEDULE_DAYS)
+        .clamp(1, MAX_SCHEDULE_DAYS);
+    let automations = state.automation_service.list_automations().await?;
+    let from = now();
+    let until = from + chrono::Duration::days(i64::from(days));
+    let firings = upcoming_firings(&automations, from, until, MAX_SCHEDULE_FIRINGS);
+    Ok(match params.format {
+        ScheduleFormat::Json => ScheduleResponse::Json(Json(firings)),
+        ScheduleFormat::Ics => ScheduleResponse::Ics(render_ics(&firings, from)),
+    })
+}
+
+/// Format `timestamp` as an iCalendar UTC date-time, e.g. `20260301T080000Z`.
+fn ics_timestamp(timestamp: Timestamp) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape `text` for an iCalendar TEXT value.
+fn ics_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append the content line `line` to `out`, folded every 75 octets as
+/// required by RFC 5545.
+fn push_ics_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Render `firings` as an iCalendar feed, one zero-length event each.
+fn render_ics(firings: &[ScheduledFiring], stamp: Timestamp) -> String {
+    let mut out = String::new();
+    push_ics_line(&mut out, "BEGIN:VCALENDAR");
+    push_ics_line(&mut out, "VERSION:2.0");
+    push_ics_line(&mut out, "PRODID:-//minihub//automation schedule//EN");
+    push_ics_line(&mut out, "X-WR-CALNAME:minihub automations");
+    for firing in firings {
+        let at = ics_timestamp(firing.at);
+        push_ics_line(&mut out, "BEGIN:VEVENT");
+        push_ics_line(
+            &mut out,
+            &format!("UID:{}-{at}@minihub", firing.automation_id),
+        );
+        push_ics_line(&mut out, &format!("DTSTAMP:{}", ics_timestamp(stamp)));
+        push_ics_line(&mut out, &format!("DTSTART:{at}"));
+        push_ics_line(&mut out, &format!("DTEND:{at}"));
+        push_ics_line(&mut out, &format!("SUMMARY:{}", ics_escape(&firing.name)));
+        push_ics_line(&mut out, "END:VEVENT");
+    }
+    push_ics_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::id::EntityId;
+
+    use super::*;
+
+    fn automation() -> Automation {
+        Automation::builder()
+            .name("Porch light")
+            .trigger(Trigger::Manual)
+            .action(Action::Delay { seconds: 5 })
+            .version(3)
+            .build()
+            .unwrap()
+    }
+
+    fn patch(operations: serde_json::Value) -> Patch {
+        serde_json::from_value(operations).unwrap()
+    }
+
+    #[test]
+    fn should_apply_targeted_changes() {
+        let existing = automation();
+        let entity_id = EntityId::new();
+
+        let patched = apply_patch(
+            &existing,
+            &patch(serde_json::json!([
+                { "op": "test", "path": "/version", "value": 3 },
+                { "op": "replace", "path": "/actions/0/seconds", "value": 30 },
+                {
+                    "op": "add",
+                    "path": "/conditions/-",
+                    "value": { "type": "state_is", "entity_id": entity_id, "state": "on" }
+                }
+            ])),
+        )
+        .unwrap();
+
+        assert_eq!(patched.id, existing.id);
+        assert_eq!(patched.version, 3);
+        assert!(matches!(patched.actions[0], Action::Delay { seconds: 30 }));
+        assert!(matches!(
+            &patched.conditions[..],
+            [Condition::StateIs { entity_id: id, state }] if *id == entity_id && state == "on"
+        ));
+    }
+
+    #[test]
+    fn should_return_conflict_when_version_test_fails() {
+        let err = apply_patch(
+            &automation(),
+            &patch(serde_json::json!([
+                { "op": "test", "path": "/version", "value": 2 },
+                { "op": "replace", "path": "/name", "value": "Renamed" }
+            ])),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Conflict(ConflictError { ref actual, .. }) if actual == "/version = 3"
+        ));
+    }
+
+    #[test]
+    fn should_reject_changes_to_read_only_fields() {
+        for operations in [
+            serde_json::json!([{ "op": "replace", "path": "/version", "value": 9 }]),
+            serde_json::json!([{ "op": "remove", "path": "/last_triggered" }]),
+            serde_json::json!([{ "op": "add", "path": "/source_key", "value": "evening" }]),
+            serde_json::json!([{ "op": "move", "from": "/id", "path": "/name" }]),
+            serde_json::json!([{ "op": "replace", "path": "", "value": {} }]),
+        ] {
+            let err = apply_patch(&automation(), &patch(operations.clone())).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    MiniHubError::Validation(ValidationError::InvalidPatch(ref message))
+                        if message.contains("read-only")
+                ),
+                "{operations} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_patch_producing_invalid_document() {
+        let err = apply_patch(
+            &automation(),
+            &patch(serde_json::json!([
+                { "op": "replace", "path": "/trigger", "value": { "type": "sunrise" } }
+            ])),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_patch_with_missing_path() {
+        let err = apply_patch(
+            &automation(),
+            &patch(serde_json::json!([
+                { "op": "replace", "path": "/actions/4/seconds", "value": 1 }
+            ])),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn should_only_accept_json_patch_content_type() {
+        let headers = |value: &'static str| {
+            HeaderMap::from_iter([(header::CONTENT_TYPE, value.parse().unwrap())])
+        };
+
+        assert!(is_json_patch(&headers("application/json-patch+json")));
+        assert!(is_json_patch(&headers(
+            "application/json-patch+json; charset=utf-8"
+        )));
+        assert!(!is_json_patch(&headers("application/json")));
+        assert!(!is_json_patch(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn should_render_one_calendar_event_per_firing() {
+        let firing = ScheduledFiring {
+            automation_id: AutomationId::new(),
+            name: "Lights, on; now".to_string(),
+            at: "2026-03-01T08:00:00Z".parse().unwrap(),
+        };
+
+        let ics = render_ics(&[firing], "2026-02-28T12:00:00Z".parse().unwrap());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20260228T120000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20260301T080000Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Lights\\, on\\; now\r\n"));
+    }
+
+    #[test]
+    fn should_fold_long_calendar_lines() {
+        let mut out = String::new();
+
+        push_ics_line(&mut out, &format!("SUMMARY:{}", "a".repeat(100)));
+
+        let lines: Vec<_> = out.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines[2], "");
+    }
+}
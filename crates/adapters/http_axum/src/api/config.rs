@@ -0,0 +1,55 @@
+//! JSON REST handlers for the daemon configuration.
+
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use minihub_app::config_reload::{ReloadError, ReloadReport};
+
+use crate::error::ApiError;
+use crate::state::{AppState, Ports};
+
+/// Settings changed by a configuration reload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReloadSummary {
+    /// Settings applied to the running daemon, e.g. `"logging.filter"`.
+    pub applied: Vec<String>,
+    /// Changed settings that only take effect after a restart.
+    pub requires_restart: Vec<String>,
+}
+
+impl From<ReloadReport> for ReloadSummary {
+    fn from(report: ReloadReport) -> Self {
+        Self {
+            applied: report.applied,
+            requires_restart: report.requires_restart,
+        }
+    }
+}
+
+/// Possible responses from the reload endpoint.
+pub enum ReloadResponse {
+    Ok(Json<ReloadSummary>),
+}
+
+impl IntoResponse for ReloadResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `POST /api/config/reload` — re-read the configuration file and apply
+/// the settings that can change without a restart.
+pub async fn reload<P: Ports>(
+    State(state): State<AppState<P>>,
+) -> Result<ReloadResponse, ApiError> {
+    let handle = state
+        .config_reload
+        .as_ref()
+        .ok_or(ReloadError::Unavailable)?;
+    let report = handle.reload().await?;
+    Ok(ReloadResponse::Ok(Json(report.into())))
+}
@@ -0,0 +1,41 @@
+//! JSON REST handlers for the layout of the dashboard home page.
+
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+
+use minihub_domain::dashboard::DashboardConfig;
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Possible responses from the get and update endpoints.
+pub enum GetResponse {
+    Ok(Json<DashboardConfig>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/dashboard_config`
+pub async fn get<P: Ports>(State(state): State<AppState<P>>) -> Result<GetResponse, ApiError> {
+    let config = state.dashboard_service().get_config().await?;
+    Ok(GetResponse::Ok(Json(config)))
+}
+
+/// `PUT /api/dashboard_config`
+///
+/// Replaces the whole layout; the order of the cards is the display order.
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(config): JsonBody<DashboardConfig>,
+) -> Result<GetResponse, ApiError> {
+    let config = state.dashboard_service().update_config(config).await?;
+    Ok(GetResponse::Ok(Json(config)))
+}
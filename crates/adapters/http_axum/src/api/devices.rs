@@ -1,23 +1,27 @@
 //! JSON REST handlers for devices.
 
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use axum::Json;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use serde::Deserialize;
-
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
-use minihub_domain::device::Device;
-use minihub_domain::error::MiniHubError;
+use serde::{Deserialize, Serialize};
+
+use minihub_app::ports::EventStore;
+use minihub_domain::device::{Device, DeviceStatus, DeviceWithStatus};
+use minihub_domain::entity::Entity;
+use minihub_domain::event::Event;
 use minihub_domain::id::{AreaId, DeviceId};
 
+use crate::api::sse::split;
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::{JsonBody, QueryParams};
+use crate::state::{AppState, Ports};
+
+/// Number of recent events embedded by `?include=events`.
+const RECENT_EVENTS_LIMIT: usize = 50;
 
 /// Request body for creating a device.
 #[derive(Deserialize)]
@@ -28,11 +32,69 @@ pub struct CreateDeviceRequest {
     pub area_id: Option<String>,
     pub integration: String,
     pub unique_id: String,
+    pub via_device_id: Option<String>,
+    pub sw_version: Option<String>,
+    pub hw_version: Option<String>,
+    pub suggested_area: Option<String>,
+}
+
+/// Query parameters for the get endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct GetQuery {
+    /// Comma-separated related resources to embed: `entities`, `events`.
+    pub include: Option<String>,
+}
+
+/// Related resources embedded in a device detail.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Include {
+    entities: bool,
+    events: bool,
+}
+
+impl GetQuery {
+    fn include(&self) -> Result<Include, ApiError> {
+        let mut include = Include::default();
+        for item in self.include.as_deref().map(split).into_iter().flatten() {
+            match item {
+                "entities" => include.entities = true,
+                "events" => include.events = true,
+                other => {
+                    return Err(ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        "invalid_include",
+                        format!("{other:?} cannot be included, expected entities or events"),
+                    ));
+                }
+            }
+        }
+        Ok(include)
+    }
+}
+
+/// A device with the related resources requested through `include`.
+#[derive(Debug, Serialize)]
+pub struct DeviceDetail {
+    #[serde(flatten)]
+    pub device: DeviceWithStatus,
+    /// Entities of the device, with `include=entities`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<Entity>>,
+    /// Most recent events about the device entities, newest first, with
+    /// `include=events`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<Event>>,
+}
+
+/// Request body for moving a device into an area; `null` clears it.
+#[derive(Deserialize)]
+pub struct AssignAreaRequest {
+    pub area_id: Option<String>,
 }
 
 /// Possible responses from the list endpoint.
 pub enum ListResponse {
-    Ok(Json<Vec<Device>>),
+    Ok(Json<Vec<DeviceWithStatus>>),
 }
 
 impl IntoResponse for ListResponse {
@@ -45,7 +107,7 @@ impl IntoResponse for ListResponse {
 
 /// Possible responses from the get endpoint.
 pub enum GetResponse {
-    Ok(Json<Device>),
+    Ok(Json<DeviceDetail>),
 }
 
 impl IntoResponse for GetResponse {
@@ -56,9 +118,22 @@ impl IntoResponse for GetResponse {
     }
 }
 
+/// Possible responses from the area assignment endpoint.
+pub enum AssignAreaResponse {
+    Ok(Json<DeviceWithStatus>),
+}
+
+impl IntoResponse for AssignAreaResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
 /// Possible responses from the create endpoint.
 pub enum CreateResponse {
-    Created(Json<Device>),
+    Created(Json<DeviceWithStatus>),
 }
 
 impl IntoResponse for CreateResponse {
@@ -83,68 +158,106 @@ impl IntoResponse for DeleteResponse {
 }
 
 /// `GET /api/devices`
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
     let devices = state.device_service.list_devices().await?;
-    Ok(ListResponse::Ok(Json(devices)))
+    let entities = state.entity_service.list_entities().await?;
+    Ok(ListResponse::Ok(Json(with_status(devices, entities))))
 }
 
-/// `GET /api/devices/:id`
-pub async fn get<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+/// `GET /api/areas/:id/devices` — the devices placed in an area.
+pub async fn list_by_area<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let device_id = DeviceId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
+) -> Result<ListResponse, ApiError> {
+    let area_id = AreaId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let area = state.area_service.get_area(area_id).await?;
+    let devices = state.device_service.list_devices_in_area(area.id).await?;
+    let mut entities = Vec::new();
+    for device in &devices {
+        entities.extend(
+            state
+                .entity_service
+                .list_entities_by_device(device.id)
+                .await?,
+        );
+    }
+    Ok(ListResponse::Ok(Json(with_status(devices, entities))))
+}
+
+/// Pair each device with the status derived from its entities among
+/// `entities`.
+fn with_status(devices: Vec<Device>, entities: Vec<Entity>) -> Vec<DeviceWithStatus> {
+    let mut entities_by_device: HashMap<DeviceId, Vec<Entity>> = HashMap::new();
+    for entity in entities {
+        entities_by_device
+            .entry(entity.device_id)
+            .or_default()
+            .push(entity);
+    }
+    devices
+        .into_iter()
+        .map(|device| {
+            let status = entities_by_device
+                .get(&device.id)
+                .map_or(DeviceStatus::Unknown, DeviceStatus::from_entities);
+            DeviceWithStatus { device, status }
+        })
+        .collect()
+}
+
+/// `GET /api/devices/:id?include=entities,events`
+///
+/// With `include`, the response also embeds the device entities and the
+/// [`RECENT_EVENTS_LIMIT`] most recent events about them.
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<GetQuery>,
+) -> Result<GetResponse, ApiError> {
+    let device_id = DeviceId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let include = params.include()?;
     let device = state.device_service.get_device(device_id).await?;
-    Ok(GetResponse::Ok(Json(device)))
+    let entities = state
+        .entity_service
+        .list_entities_by_device(device_id)
+        .await?;
+    let status = DeviceStatus::from_entities(&entities);
+    let events = if include.events {
+        let mut events = Vec::new();
+        for entity in &entities {
+            events.extend(
+                state
+                    .event_store
+                    .find_by_entity(entity.id, RECENT_EVENTS_LIMIT)
+                    .await?,
+            );
+        }
+        events.sort_by_key(|event| std::cmp::Reverse((event.timestamp, event.id.as_uuid())));
+        events.truncate(RECENT_EVENTS_LIMIT);
+        Some(events)
+    } else {
+        None
+    };
+    Ok(GetResponse::Ok(Json(DeviceDetail {
+        device: DeviceWithStatus { device, status },
+        entities: include.entities.then_some(entities),
+        events,
+    })))
 }
 
 /// `POST /api/devices`
-pub async fn create<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-    Json(req): Json<CreateDeviceRequest>,
-) -> Result<CreateResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<CreateDeviceRequest>,
+) -> Result<CreateResponse, ApiError> {
     let area_id = req
         .area_id
-        .map(|s| AreaId::from_str(&s))
-        .transpose()
-        .map_err(|_| {
-            ApiError::from(MiniHubError::Validation(
-                minihub_domain::error::ValidationError::EmptyName,
-            ))
-        })?;
+        .map(|s| AreaId::from_str(&s).map_err(|_| ApiError::invalid_id("area_id", &s)))
+        .transpose()?;
+    let via_device_id = req
+        .via_device_id
+        .map(|s| DeviceId::from_str(&s).map_err(|_| ApiError::invalid_id("via_device_id", &s)))
+        .transpose()?;
 
     let mut builder = Device::builder().name(req.name);
     if let Some(manufacturer) = req.manufacturer {
@@ -156,34 +269,67 @@ where
     if let Some(area_id) = area_id {
         builder = builder.area_id(area_id);
     }
+    if let Some(via_device_id) = via_device_id {
+        builder = builder.via_device_id(via_device_id);
+    }
+    if let Some(sw_version) = req.sw_version {
+        builder = builder.sw_version(sw_version);
+    }
+    if let Some(hw_version) = req.hw_version {
+        builder = builder.hw_version(hw_version);
+    }
+    if let Some(suggested_area) = req.suggested_area {
+        builder = builder.suggested_area(suggested_area);
+    }
     builder = builder
         .integration(req.integration)
         .unique_id(req.unique_id);
 
     let device = builder.build()?;
     let created = state.device_service.create_device(device).await?;
-    Ok(CreateResponse::Created(Json(created)))
+    Ok(CreateResponse::Created(Json(DeviceWithStatus {
+        device: created,
+        status: DeviceStatus::Unknown,
+    })))
+}
+
+/// `PUT /api/devices/:id/area`
+pub async fn assign_area<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<AssignAreaRequest>,
+) -> Result<AssignAreaResponse, ApiError> {
+    let device_id = DeviceId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let area_id = req
+        .area_id
+        .map(|s| AreaId::from_str(&s).map_err(|_| ApiError::invalid_id("area_id", &s)))
+        .transpose()?;
+    if let Some(area_id) = area_id {
+        state.area_service.get_area(area_id).await?;
+    }
+
+    let device = state.device_service.assign_area(device_id, area_id).await?;
+    let entities = state
+        .entity_service
+        .list_entities_by_device(device_id)
+        .await?;
+    let status = DeviceStatus::from_entities(&entities);
+    Ok(AssignAreaResponse::Ok(Json(DeviceWithStatus {
+        device,
+        status,
+    })))
 }
 
-/// `DELETE /api/devices/:id`
-pub async fn delete<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+/// `DELETE /api/devices/:id` — also removes the entities of the device,
+/// publishing an `entity_removed` event for each.
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<DeleteResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let device_id = DeviceId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
-    state.device_service.delete_device(device_id).await?;
+) -> Result<DeleteResponse, ApiError> {
+    let device_id = DeviceId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    state
+        .device_service
+        .delete_device_cascade(device_id, &state.entity_service)
+        .await?;
     Ok(DeleteResponse::NoContent)
 }
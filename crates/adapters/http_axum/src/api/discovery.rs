@@ -0,0 +1,63 @@
+//! JSON REST handlers for the inbox of detected devices.
+
+use std::str::FromStr;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use minihub_domain::device::Device;
+use minihub_domain::id::PendingDeviceId;
+use minihub_domain::pending_device::{Adoption, PendingDevice};
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Possible responses from the pending list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<PendingDevice>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the adopt endpoint.
+pub enum AdoptResponse {
+    Created(Json<Device>),
+}
+
+impl IntoResponse for AdoptResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Created(json) => (StatusCode::CREATED, json).into_response(),
+        }
+    }
+}
+
+/// `GET /api/discovery/pending` — list the detected devices not registered
+/// yet, most recently seen first.
+pub async fn list_pending<P: Ports>(
+    State(state): State<AppState<P>>,
+) -> Result<ListResponse, ApiError> {
+    let pending = state.discovery_service.list_pending().await?;
+    Ok(ListResponse::Ok(Json(pending)))
+}
+
+/// `POST /api/discovery/pending/:id/adopt` — register a detected device,
+/// assigned to an integration.
+pub async fn adopt<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(adoption): JsonBody<Adoption>,
+) -> Result<AdoptResponse, ApiError> {
+    let pending_id = PendingDeviceId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let device = state.discovery_service.adopt(pending_id, adoption).await?;
+    Ok(AdoptResponse::Created(Json(device)))
+}
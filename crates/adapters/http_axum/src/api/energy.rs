@@ -0,0 +1,82 @@
+//! JSON REST handler for the energy consumption of metered entities.
+
+use std::str::FromStr;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_app::ports::EnergyUsageRepository;
+use minihub_domain::energy::{EnergyPeriod, EnergyReport};
+use minihub_domain::id::EntityId;
+use minihub_domain::time::now;
+
+use crate::api::entity_history::parse_timestamp;
+use crate::error::ApiError;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
+
+/// Query parameters for the energy endpoint.
+#[derive(Deserialize)]
+pub struct EnergyQuery {
+    /// Length of the buckets. Defaults to `day`.
+    #[serde(default)]
+    pub period: EnergyPeriod,
+    /// Start of time range (RFC 3339), moved back to the start of its
+    /// period. Defaults to 24 hours, 30 days, 13 weeks or 12 months ago.
+    pub from: Option<String>,
+    /// End of time range (RFC 3339). Defaults to now.
+    pub to: Option<String>,
+}
+
+/// Possible responses from the energy endpoint.
+pub enum GetResponse {
+    /// 200 OK with the consumption summed per period.
+    Ok(Json<EnergyReport>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/energy/:id?period=&from=&to=`
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<EnergyQuery>,
+) -> Result<GetResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    state.entity_service.get_entity(entity_id).await?;
+
+    let period = params.period;
+    let to = params
+        .to
+        .as_deref()
+        .map(parse_timestamp)
+        .transpose()?
+        .unwrap_or_else(now);
+    // Widened to whole periods, so the first bucket is complete
+    let from = params
+        .from
+        .as_deref()
+        .map(parse_timestamp)
+        .transpose()?
+        .map_or_else(
+            || period.periods_before(to, period.default_count() - 1),
+            |from| period.start_of(from),
+        );
+
+    let usage = state
+        .energy_usage_repo
+        .find_by_entity_in_range(entity_id, from, to)
+        .await?;
+
+    Ok(GetResponse::Ok(Json(EnergyReport::aggregate(
+        entity_id, period, from, to, &usage,
+    ))))
+}
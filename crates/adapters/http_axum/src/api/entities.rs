@@ -1,24 +1,23 @@
 //! JSON REST handlers for entities.
 
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 
 use axum::Json;
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::{IntoResponse, Response};
-use serde::Deserialize;
-
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
-use minihub_domain::entity::{Entity, EntityState};
-use minihub_domain::error::MiniHubError;
-use minihub_domain::event::{Event, EventType};
+use serde::{Deserialize, Serialize};
+
+use minihub_app::event_bus::EventFilter;
+use minihub_domain::entity::{AttributeValue, Entity, EntityState};
 use minihub_domain::id::{DeviceId, EntityId};
+use minihub_domain::time::Timestamp;
 
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::{JsonBody, QueryParams};
+use crate::state::{AppState, Ports};
 
 /// Request body for creating an entity.
 #[derive(Deserialize)]
@@ -29,9 +28,24 @@ pub struct CreateEntityRequest {
 }
 
 /// Request body for updating entity state.
+///
+/// At least one of `state` and `value` is required; `value` sets the value
+/// of a number or select input helper.
 #[derive(Deserialize)]
 pub struct UpdateStateRequest {
-    pub state: EntityState,
+    #[serde(default)]
+    pub state: Option<EntityState>,
+    #[serde(default)]
+    pub value: Option<serde_json::Value>,
+    /// Only apply the update if the entity is currently in this state.
+    #[serde(default)]
+    pub expected_state: Option<EntityState>,
+}
+
+/// Request body for renaming an entity.
+#[derive(Deserialize)]
+pub struct RenameEntityRequest {
+    pub entity_id: String,
 }
 
 /// Request body for calling a service on an entity.
@@ -42,6 +56,33 @@ pub struct ServiceCallRequest {
     pub data: serde_json::Value,
 }
 
+/// Request body for reading the states of several entities.
+#[derive(Deserialize)]
+pub struct StatesRequest {
+    pub ids: Vec<String>,
+}
+
+/// The state of an entity, without its descriptive fields.
+#[derive(Debug, Serialize)]
+pub struct EntityStateSnapshot {
+    pub id: EntityId,
+    pub state: EntityState,
+    pub attributes: HashMap<String, AttributeValue>,
+    pub last_changed: Timestamp,
+}
+
+impl From<Entity> for EntityStateSnapshot {
+    fn from(entity: Entity) -> Self {
+        let entity = entity.rounded();
+        Self {
+            id: entity.id,
+            state: entity.state,
+            attributes: entity.attributes,
+            last_changed: entity.last_changed,
+        }
+    }
+}
+
 /// Possible responses from the list endpoint.
 pub enum ListResponse {
     Ok(Json<Vec<Entity>>),
@@ -61,6 +102,88 @@ pub enum GetResponse {
 }
 
 impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => ([(header::ETAG, etag(&json))], json).into_response(),
+        }
+    }
+}
+
+/// Strong entity tag of `entity`, its quoted version.
+fn etag(entity: &Entity) -> String {
+    format!("\"{}\"", entity.version)
+}
+
+/// Version required by the `If-Match` header, if any.
+///
+/// Accepts the entity tag sent in the `ETag` header, or the bare version;
+/// `*` matches any version.
+fn if_match(headers: &HeaderMap) -> Result<Option<u32>, ApiError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let invalid = || {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_header",
+            "If-Match must be an entity version or *",
+        )
+    };
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let tag = value.strip_prefix("W/").unwrap_or(value);
+    let version = tag
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .unwrap_or(tag);
+    version.parse().map(Some).map_err(|_| invalid())
+}
+
+/// Header carrying the [`Entity::version`] returned by the wait endpoint,
+/// to pass back as `since_version`.
+pub const ENTITY_VERSION_HEADER: &str = "x-entity-version";
+
+/// Default wait of the long-poll endpoint, in seconds.
+const DEFAULT_WAIT_SECS: u64 = 30;
+
+/// Longest wait accepted by the long-poll endpoint, in seconds.
+const MAX_WAIT_SECS: u64 = 300;
+
+/// Query parameters of the long-poll endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct WaitQuery {
+    /// Longest wait for a change, in seconds; defaults to 30.
+    pub timeout: Option<u64>,
+    /// Version the client already has, from the `X-Entity-Version` header
+    /// of a previous answer; a newer entity is returned right away.
+    pub since_version: Option<u32>,
+}
+
+/// Possible responses from the wait endpoint.
+pub enum WaitResponse {
+    Changed { version: u32, entity: Box<Entity> },
+    NotModified,
+}
+
+impl IntoResponse for WaitResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Changed { version, entity } => {
+                ([(ENTITY_VERSION_HEADER, version.to_string())], Json(entity)).into_response()
+            }
+            Self::NotModified => StatusCode::NOT_MODIFIED.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the states endpoint.
+pub enum StatesResponse {
+    Ok(Json<Vec<EntityStateSnapshot>>),
+}
+
+impl IntoResponse for StatesResponse {
     fn into_response(self) -> Response {
         match self {
             Self::Ok(json) => json.into_response(),
@@ -108,64 +231,131 @@ impl IntoResponse for ServiceCallResponse {
 }
 
 /// `GET /api/entities`
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
     let entities = state.entity_service.list_entities().await?;
+    let entities = entities.into_iter().map(Entity::rounded).collect();
+    Ok(ListResponse::Ok(Json(entities)))
+}
+
+/// `GET /api/devices/:id/entities` — the entities exposed by a device.
+pub async fn list_by_device<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<ListResponse, ApiError> {
+    let device_id = DeviceId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let device = state.device_service.get_device(device_id).await?;
+    let entities = state
+        .entity_service
+        .list_entities_by_device(device.id)
+        .await?;
+    let entities = entities.into_iter().map(Entity::rounded).collect();
     Ok(ListResponse::Ok(Json(entities)))
 }
 
 /// `GET /api/entities/:id`
-pub async fn get<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let entity_id = EntityId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
-    })?;
+) -> Result<GetResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let entity = state.entity_service.get_entity(entity_id).await?;
+    Ok(GetResponse::Ok(Json(entity.rounded())))
+}
+
+/// `GET /api/entities/{id}/wait?timeout=&since_version=` — long-poll for
+/// clients that cannot hold an SSE stream.
+///
+/// Answers `200` with the entity and its version as soon as it is newer
+/// than `since_version` — right away if it already is — or than the
+/// current one when omitted, and `304` once `timeout` expires without a
+/// change. The wait is cut short of the request timeout so the client gets
+/// a `304` rather than a `408`.
+pub async fn wait<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<WaitQuery>,
+) -> Result<WaitResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let timeout_secs = params.timeout.unwrap_or(DEFAULT_WAIT_SECS);
+    if !(1..=MAX_WAIT_SECS).contains(&timeout_secs) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_query",
+            format!("timeout must be between 1 and {MAX_WAIT_SECS} seconds"),
+        ));
+    }
+    let wait = Duration::from_secs(timeout_secs).min(
+        state
+            .request_limits
+            .timeout
+            .saturating_sub(Duration::from_secs(1)),
+    );
+    let deadline = tokio::time::Instant::now() + wait;
+
+    // Subscribe before reading the entity so no change slips in between.
+    let mut changes = state
+        .event_bus
+        .subscribe_filtered(EventFilter::all().with_entity_ids(vec![entity_id]));
     let entity = state.entity_service.get_entity(entity_id).await?;
-    Ok(GetResponse::Ok(Json(entity)))
+    let current = entity.version;
+    if let Some(since) = params.since_version
+        && current > since
+    {
+        return Ok(WaitResponse::Changed {
+            version: current,
+            entity: Box::new(entity.rounded()),
+        });
+    }
+    let baseline = params
+        .since_version
+        .map_or(current, |since| since.max(current));
+
+    loop {
+        match tokio::time::timeout_at(deadline, changes.recv()).await {
+            // A matching event, or missed ones: look whether it changed.
+            Ok(Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_))) => {
+                let entity = state.entity_service.get_entity(entity_id).await?;
+                let latest = entity.version;
+                if latest > baseline {
+                    return Ok(WaitResponse::Changed {
+                        version: latest,
+                        entity: Box::new(entity.rounded()),
+                    });
+                }
+            }
+            Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) | Err(_) => {
+                return Ok(WaitResponse::NotModified);
+            }
+        }
+    }
+}
+
+/// `POST /api/entities/states` — the states of the requested entities, in
+/// one round trip. Unknown ids are left out of the response.
+pub async fn states<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<StatesRequest>,
+) -> Result<StatesResponse, ApiError> {
+    let ids = req
+        .ids
+        .iter()
+        .map(|id| EntityId::from_str(id).map_err(|_| ApiError::invalid_id("ids", id)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let entities = state.entity_service.get_entities(&ids).await?;
+    let states = entities
+        .into_iter()
+        .map(EntityStateSnapshot::from)
+        .collect();
+    Ok(StatesResponse::Ok(Json(states)))
 }
 
 /// `POST /api/entities`
-pub async fn create<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-    Json(req): Json<CreateEntityRequest>,
-) -> Result<CreateResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let device_id = DeviceId::from_str(&req.device_id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
-    })?;
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<CreateEntityRequest>,
+) -> Result<CreateResponse, ApiError> {
+    let device_id = DeviceId::from_str(&req.device_id)
+        .map_err(|_| ApiError::invalid_id("device_id", &req.device_id))?;
 
     let entity = Entity::builder()
         .device_id(device_id)
@@ -178,90 +368,122 @@ where
 }
 
 /// `PUT /api/entities/:id/state`
-pub async fn update_state<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+///
+/// Responds `409 Conflict` with the actual state when `expected_state` is
+/// set and does not match, or with the actual version when the `If-Match`
+/// header names another one than the entity's. The `value` is only applied
+/// once the state was updated.
+pub async fn update_state<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-    Json(req): Json<UpdateStateRequest>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let entity_id = EntityId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
+    headers: HeaderMap,
+    JsonBody(req): JsonBody<UpdateStateRequest>,
+) -> Result<GetResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let mut expected_version = if_match(&headers)?;
+    let mut updated = None;
+    if let Some(new_state) = req.state {
+        let service = &state.entity_service;
+        updated = Some(match expected_version.take() {
+            Some(version) => {
+                service
+                    .update_entity_state_at_version(
+                        entity_id,
+                        new_state,
+                        req.expected_state,
+                        version,
+                    )
+                    .await?
+            }
+            None => {
+                service
+                    .update_entity_state(entity_id, new_state, req.expected_state)
+                    .await?
+            }
+        });
+    }
+    if let Some(value) = req.value {
+        updated = Some(
+            state
+                .input_helper_service()
+                .set_value(entity_id, &value, expected_version)
+                .await?,
+        );
+    }
+    let updated = updated.ok_or_else(|| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_body",
+            "expected a state or a value",
+        )
     })?;
-    let updated = state
+    Ok(GetResponse::Ok(Json(updated)))
+}
+
+/// `PUT /api/entities/:id/rename`
+///
+/// The previous `entity_id` keeps resolving to the entity as an alias.
+pub async fn rename<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<RenameEntityRequest>,
+) -> Result<GetResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let renamed = state
         .entity_service
-        .update_entity_state(entity_id, req.state)
+        .rename_entity(entity_id, req.entity_id.trim())
         .await?;
-    Ok(GetResponse::Ok(Json(updated)))
+    Ok(GetResponse::Ok(Json(renamed)))
 }
 
 /// `DELETE /api/entities/:id`
-pub async fn delete<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-) -> Result<DeleteResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let entity_id = EntityId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
-    })?;
+) -> Result<DeleteResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
     state.entity_service.delete_entity(entity_id).await?;
     Ok(DeleteResponse::NoContent)
 }
 
 /// `POST /api/entities/:id/service`
-pub async fn service_call<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn service_call<P: Ports>(
+    State(state): State<AppState<P>>,
     Path(id): Path<String>,
-    Json(req): Json<ServiceCallRequest>,
-) -> Result<ServiceCallResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let entity_id = EntityId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
-    })?;
+    JsonBody(req): JsonBody<ServiceCallRequest>,
+) -> Result<ServiceCallResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
 
-    state.entity_service.get_entity(entity_id).await?;
+    state
+        .entity_service
+        .call_service(entity_id, &req.service, req.data)
+        .await?;
 
-    let event = Event::new(
-        EventType::ServiceCallRequested,
-        Some(entity_id),
-        serde_json::json!({ "service": req.service, "data": req.data }),
-    );
-    state.event_bus.publish(event).await?;
+    Ok(ServiceCallResponse::Accepted)
+}
+
+/// `POST /api/entities/:id/refresh`
+///
+/// Asks the owning integration for an immediate readout, routed like a
+/// `refresh` service call. Completion is reported through
+/// `service_call_completed` / `service_call_failed` events.
+pub async fn refresh<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<ServiceCallResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+
+    state
+        .entity_service
+        .call_service(entity_id, "refresh", serde_json::json!({}))
+        .await?;
 
     Ok(ServiceCallResponse::Accepted)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::marker::PhantomData;
     use std::sync::Arc;
 
     use axum::body::Body;
@@ -274,25 +496,40 @@ mod tests {
     use minihub_app::services::automation_service::AutomationService;
     use minihub_app::services::device_service::DeviceService;
     use minihub_app::services::entity_service::EntityService;
+    use minihub_app::services::scene_service::SceneService;
     use minihub_domain::area::Area;
     use minihub_domain::automation::Automation;
+    use minihub_domain::automation_run::AutomationRun;
     use minihub_domain::device::Device;
-    use minihub_domain::entity::Entity;
+    use minihub_domain::entity::{AttributeMeta, AttributeValue, Entity};
     use minihub_domain::entity_history::EntityHistory;
     use minihub_domain::error::MiniHubError;
     use minihub_domain::event::Event;
-    use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId, EventId};
+    use minihub_domain::group::Group;
+    use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId, EventId, GroupId, SceneId};
+    use minihub_domain::input_helper::InputHelper;
+    use minihub_domain::report::{Overview, StorageStats};
+    use minihub_domain::scene::Scene;
     use minihub_domain::time::Timestamp;
 
-    use crate::state::AppState;
+    use crate::state::{AppState, Ports};
 
     struct StubEntityRepo;
     struct StubDeviceRepo;
     struct StubAreaRepo;
+    #[derive(Clone)]
     struct StubPublisher;
     struct StubEventStore;
     struct StubAutomationRepo;
     struct StubEntityHistoryRepo;
+    struct StubAutomationRunRepo;
+    struct StubReportRepo;
+    struct StubSceneRepo;
+    struct StubGroupRepo;
+    struct StubAuditRepo;
+    struct StubSettingsRepo;
+    struct StubEnergyUsageRepo;
+    struct StubSecretsRepo;
 
     impl minihub_app::ports::EntityRepository for StubEntityRepo {
         async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
@@ -305,10 +542,24 @@ mod tests {
                     .entity_id("light.test")
                     .friendly_name("Test Light")
                     .id(id)
+                    .attribute("brightness", AttributeValue::Float(127.6))
+                    .attribute_meta(
+                        "brightness",
+                        AttributeMeta::default()
+                            .with_precision(0)
+                            .with_range(0.0, 255.0),
+                    )
                     .build()
                     .unwrap(),
             ))
         }
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            let mut entities = Vec::with_capacity(ids.len());
+            for id in ids {
+                entities.extend(self.get_by_id(*id).await?);
+            }
+            Ok(entities)
+        }
         async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
             Ok(vec![])
         }
@@ -330,6 +581,12 @@ mod tests {
         async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
             Ok(())
         }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
     }
 
     struct NotFoundEntityRepo;
@@ -341,6 +598,9 @@ mod tests {
         async fn get_by_id(&self, _id: EntityId) -> Result<Option<Entity>, MiniHubError> {
             Ok(None)
         }
+        async fn get_many(&self, _ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
         async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
             Ok(vec![])
         }
@@ -362,6 +622,12 @@ mod tests {
         async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
             Ok(())
         }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
     }
 
     impl minihub_app::ports::DeviceRepository for StubDeviceRepo {
@@ -374,6 +640,15 @@ mod tests {
         async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
             Ok(vec![])
         }
+        async fn find_by_area(&self, _area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_integration(
+            &self,
+            _integration: &str,
+        ) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
         async fn find_by_integration_unique_id(
             &self,
             _integration: &str,
@@ -413,6 +688,12 @@ mod tests {
         }
     }
 
+    impl minihub_app::ports::Storage for StubEventStore {
+        async fn ping(&self) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
     impl minihub_app::ports::EventStore for StubEventStore {
         async fn store(&self, event: Event) -> Result<Event, MiniHubError> {
             Ok(event)
@@ -430,6 +711,22 @@ mod tests {
         ) -> Result<Vec<Event>, MiniHubError> {
             Ok(vec![])
         }
+        async fn find_in_range(
+            &self,
+            _from: Timestamp,
+            _to: Timestamp,
+            _after: Option<(Timestamp, EventId)>,
+            _limit: usize,
+        ) -> Result<Vec<Event>, MiniHubError> {
+            Ok(vec![])
+        }
+
+        async fn query(
+            &self,
+            _query: minihub_app::ports::EventQuery,
+        ) -> Result<Vec<Event>, MiniHubError> {
+            Ok(vec![])
+        }
     }
 
     impl minihub_app::ports::AutomationRepository for StubAutomationRepo {
@@ -448,6 +745,13 @@ mod tests {
         async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
             Ok(automation)
         }
+        async fn record_triggered(
+            &self,
+            _id: AutomationId,
+            _triggered_at: Timestamp,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
         async fn delete(&self, _id: AutomationId) -> Result<(), MiniHubError> {
             Ok(())
         }
@@ -471,19 +775,198 @@ mod tests {
         }
     }
 
+    impl minihub_app::ports::AutomationRunRepository for StubAutomationRunRepo {
+        async fn record(&self, run: AutomationRun) -> Result<AutomationRun, MiniHubError> {
+            Ok(run)
+        }
+        async fn find_by_automation(
+            &self,
+            _automation_id: AutomationId,
+            _limit: usize,
+        ) -> Result<Vec<AutomationRun>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::ReportRepository for StubReportRepo {
+        async fn overview(&self, since: Timestamp) -> Result<Overview, MiniHubError> {
+            Ok(Overview {
+                since,
+                ..Overview::default()
+            })
+        }
+
+        async fn storage_stats(&self) -> Result<StorageStats, MiniHubError> {
+            Ok(StorageStats::default())
+        }
+    }
+
+    impl minihub_app::ports::SceneRepository for StubSceneRepo {
+        async fn create(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+            Ok(scene)
+        }
+        async fn get_by_id(&self, _id: SceneId) -> Result<Option<Scene>, MiniHubError> {
+            Ok(None)
+        }
+        async fn get_all(&self) -> Result<Vec<Scene>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn update(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+            Ok(scene)
+        }
+        async fn delete(&self, _id: SceneId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    impl minihub_app::ports::SecretsRepository for StubSecretsRepo {
+        async fn get(
+            &self,
+            _name: &str,
+        ) -> Result<Option<minihub_domain::secret::SecretValue>, MiniHubError> {
+            Ok(None)
+        }
+        async fn list(&self) -> Result<Vec<minihub_domain::secret::SecretInfo>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn put(
+            &self,
+            name: &str,
+            _value: &minihub_domain::secret::SecretValue,
+        ) -> Result<minihub_domain::secret::SecretInfo, MiniHubError> {
+            Ok(minihub_domain::secret::SecretInfo {
+                name: name.to_string(),
+                updated_at: minihub_domain::time::now(),
+            })
+        }
+        async fn delete(&self, _name: &str) -> Result<bool, MiniHubError> {
+            Ok(false)
+        }
+    }
+
+    impl minihub_app::ports::EnergyUsageRepository for StubEnergyUsageRepo {
+        async fn add(
+            &self,
+            _usage: Vec<minihub_domain::energy::EnergyUsage>,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_entity_in_range(
+            &self,
+            _entity_id: EntityId,
+            _from: minihub_domain::time::Timestamp,
+            _to: minihub_domain::time::Timestamp,
+        ) -> Result<Vec<minihub_domain::energy::EnergyUsage>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::SettingsRepository for StubSettingsRepo {
+        async fn get(
+            &self,
+            _namespace: &str,
+            _key: &str,
+        ) -> Result<Option<serde_json::Value>, MiniHubError> {
+            Ok(None)
+        }
+        async fn list(
+            &self,
+            _namespace: &str,
+        ) -> Result<minihub_domain::settings::Settings, MiniHubError> {
+            Ok(minihub_domain::settings::Settings::new())
+        }
+        async fn put(
+            &self,
+            _namespace: &str,
+            _key: &str,
+            _value: serde_json::Value,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn delete(&self, _namespace: &str, _key: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    impl minihub_app::ports::AuditRepository for StubAuditRepo {
+        async fn record(
+            &self,
+            entry: minihub_domain::audit::AuditEntry,
+        ) -> Result<minihub_domain::audit::AuditEntry, MiniHubError> {
+            Ok(entry)
+        }
+        async fn query(
+            &self,
+            _query: minihub_app::ports::AuditQuery,
+        ) -> Result<Vec<minihub_domain::audit::AuditEntry>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::GroupRepository for StubGroupRepo {
+        async fn create(&self, group: Group) -> Result<Group, MiniHubError> {
+            Ok(group)
+        }
+        async fn get_by_id(&self, _id: GroupId) -> Result<Option<Group>, MiniHubError> {
+            Ok(None)
+        }
+        async fn get_all(&self) -> Result<Vec<Group>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn update(&self, group: Group) -> Result<Group, MiniHubError> {
+            Ok(group)
+        }
+        async fn delete(&self, _id: GroupId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    /// Stub ports around the entity repository and publisher under test.
+    struct TestPorts<ER, EP>(PhantomData<fn() -> (ER, EP)>);
+
+    impl<ER, EP> Ports for TestPorts<ER, EP>
+    where
+        ER: minihub_app::ports::EntityRepository + Send + Sync + 'static,
+        EP: EventPublisher + Clone + Send + Sync + 'static,
+    {
+        type Entities = ER;
+        type Devices = StubDeviceRepo;
+        type Areas = StubAreaRepo;
+        type Publisher = EP;
+        type EventStore = StubEventStore;
+        type Automations = StubAutomationRepo;
+        type EntityHistory = StubEntityHistoryRepo;
+        type AutomationRuns = StubAutomationRunRepo;
+        type Reports = StubReportRepo;
+        type Scenes = StubSceneRepo;
+        type Groups = StubGroupRepo;
+        type Audit = StubAuditRepo;
+        type Settings = StubSettingsRepo;
+        type EnergyUsage = StubEnergyUsageRepo;
+        type Secrets = StubSecretsRepo;
+    }
+
     fn build_app_with_entity_repo<
         ER: minihub_app::ports::EntityRepository + Send + Sync + 'static,
     >(
         entity_repo: ER,
     ) -> axum::Router {
         let event_bus = Arc::new(InProcessEventBus::new(16));
-        let state = AppState::new(
+        let state: AppState<TestPorts<_, _>> = AppState::new(
             EntityService::new(entity_repo, StubPublisher),
             DeviceService::new(StubDeviceRepo),
             AreaService::new(StubAreaRepo),
             StubEventStore,
             AutomationService::new(StubAutomationRepo),
             StubEntityHistoryRepo,
+            StubAutomationRunRepo,
+            StubReportRepo,
+            SceneService::new(StubSceneRepo, StubPublisher),
+            StubGroupRepo,
+            StubAuditRepo,
+            StubSettingsRepo,
+            StubEnergyUsageRepo,
+            StubSecretsRepo,
             event_bus,
         );
         crate::router::build(state, None)
@@ -535,20 +1018,8 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn should_publish_service_call_requested_event() {
-        let event_bus = Arc::new(InProcessEventBus::new(16));
-        let mut rx = event_bus.subscribe();
-
-        let state = AppState::new(
-            EntityService::new(StubEntityRepo, StubPublisher),
-            DeviceService::new(StubDeviceRepo),
-            AreaService::new(StubAreaRepo),
-            StubEventStore,
-            AutomationService::new(StubAutomationRepo),
-            StubEntityHistoryRepo,
-            Arc::clone(&event_bus),
-        );
-        let app = crate::router::build(state, None);
+    async fn should_return_bad_request_when_service_data_out_of_range() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
         let entity_id = EntityId::new();
 
         let response = app
@@ -558,7 +1029,7 @@ mod tests {
                     .uri(format!("/api/entities/{entity_id}/service"))
                     .header("content-type", "application/json")
                     .body(Body::from(
-                        serde_json::json!({ "service": "blink", "data": { "times": 3 } })
+                        serde_json::json!({ "service": "turn_on", "data": { "brightness": 300 } })
                             .to_string(),
                     ))
                     .unwrap(),
@@ -566,21 +1037,12 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::ACCEPTED);
-
-        let event = rx.try_recv().unwrap();
-        assert_eq!(
-            event.event_type,
-            minihub_domain::event::EventType::ServiceCallRequested
-        );
-        assert_eq!(event.entity_id, Some(entity_id));
-        assert_eq!(event.data["service"], "blink");
-        assert_eq!(event.data["data"]["times"], 3);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn should_return_not_found_when_entity_does_not_exist() {
-        let app = build_app_with_entity_repo(NotFoundEntityRepo);
+    async fn should_list_invalid_parameters_when_service_data_does_not_match_definition() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
         let entity_id = EntityId::new();
 
         let response = app
@@ -590,66 +1052,631 @@ mod tests {
                     .uri(format!("/api/entities/{entity_id}/service"))
                     .header("content-type", "application/json")
                     .body(Body::from(
-                        serde_json::json!({ "service": "blink" }).to_string(),
+                        serde_json::json!({ "service": "turn_on", "data": { "brightness": "max" } })
+                            .to_string(),
                     ))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "invalid_service_data");
+        assert_eq!(body["error"]["details"]["service"], "turn_on");
+        assert_eq!(
+            body["error"]["details"]["errors"][0]["parameter"],
+            "brightness"
+        );
     }
 
     #[tokio::test]
-    async fn should_return_bad_request_when_entity_id_is_invalid() {
+    async fn should_update_state_when_expected_state_matches() {
         let app = build_app_with_entity_repo(StubEntityRepo);
+        let entity_id = EntityId::new();
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .method("POST")
-                    .uri("/api/entities/not-a-uuid/service")
+                    .method("PUT")
+                    .uri(format!("/api/entities/{entity_id}/state"))
                     .header("content-type", "application/json")
                     .body(Body::from(
-                        serde_json::json!({ "service": "blink" }).to_string(),
+                        serde_json::json!({ "state": "on", "expected_state": "unknown" })
+                            .to_string(),
                     ))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn should_reject_when_body_is_invalid_json() {
+    async fn should_return_conflict_with_actual_state_when_expected_state_differs() {
         let app = build_app_with_entity_repo(StubEntityRepo);
         let entity_id = EntityId::new();
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .method("POST")
-                    .uri(format!("/api/entities/{entity_id}/service"))
+                    .method("PUT")
+                    .uri(format!("/api/entities/{entity_id}/state"))
                     .header("content-type", "application/json")
-                    .body(Body::from("not json"))
+                    .body(Body::from(
+                        serde_json::json!({ "state": "on", "expected_state": "off" }).to_string(),
+                    ))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert!(response.status().is_client_error());
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "entity_conflict");
+        assert_eq!(body["error"]["details"]["actual"], "unknown");
     }
 
-    #[tokio::test]
-    async fn should_reject_when_service_field_is_missing() {
+    async fn put_state_if_match(if_match: &str) -> axum::response::Response {
         let app = build_app_with_entity_repo(StubEntityRepo);
-        let entity_id = EntityId::new();
+        app.oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/api/entities/{}/state", EntityId::new()))
+                .header("content-type", "application/json")
+                .header("if-match", if_match)
+                .body(Body::from(serde_json::json!({ "state": "on" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method("POST")
+    #[tokio::test]
+    async fn should_update_state_when_if_match_names_current_version() {
+        let response = put_state_if_match("\"1\"").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["etag"], "\"1\"");
+    }
+
+    #[tokio::test]
+    async fn should_return_conflict_when_if_match_names_another_version() {
+        let response = put_state_if_match("\"2\"").await;
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "entity_conflict");
+        assert_eq!(body["error"]["details"]["actual"], "version 1");
+    }
+
+    #[tokio::test]
+    async fn should_reject_malformed_if_match() {
+        let response = put_state_if_match("latest").await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    struct HelperEntityRepo;
+
+    impl minihub_app::ports::EntityRepository for HelperEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            let helper = InputHelper::Number {
+                min: 15.0,
+                max: 25.0,
+                step: 0.5,
+            };
+            let mut entity = helper.to_entity(DeviceId::new(), "Target", None)?;
+            entity.id = id;
+            Ok(Some(entity))
+        }
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            let mut entities = Vec::with_capacity(ids.len());
+            for id in ids {
+                entities.extend(self.get_by_id(*id).await?);
+            }
+            Ok(entities)
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(
+            &self,
+            _entity_id: &str,
+        ) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    async fn put_state(
+        app: axum::Router,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/api/entities/{}/state", EntityId::new()))
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_set_input_helper_value_through_state_endpoint() {
+        let app = build_app_with_entity_repo(HelperEntityRepo);
+
+        let (status, body) = put_state(app, serde_json::json!({ "value": 21.5 })).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["attributes"]["value"], 21.5);
+    }
+
+    #[tokio::test]
+    async fn should_reject_input_helper_value_out_of_range() {
+        let app = build_app_with_entity_repo(HelperEntityRepo);
+
+        let (status, body) = put_state(app, serde_json::json!({ "value": 40 })).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_input_value");
+    }
+
+    #[tokio::test]
+    async fn should_reject_value_for_entity_that_is_not_a_helper() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let (status, body) = put_state(app, serde_json::json!({ "value": 1 })).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_input_value");
+    }
+
+    #[tokio::test]
+    async fn should_reject_state_update_without_state_or_value() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let (status, body) = put_state(app, serde_json::json!({})).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "invalid_body");
+    }
+
+    #[tokio::test]
+    async fn should_round_attributes_when_getting_entity() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/entities/{entity_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["attributes"]["brightness"], 128.0);
+        assert_eq!(body["attribute_meta"]["brightness"]["max"], 255.0);
+    }
+
+    async fn wait(app: axum::Router, query: &str) -> axum::response::Response {
+        app.oneshot(
+            Request::builder()
+                .uri(format!("/api/entities/{}/wait?{query}", EntityId::new()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_return_entity_right_away_when_newer_than_since_version() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = wait(app, "since_version=0").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let version: u32 = response.headers()[super::ENTITY_VERSION_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(version > 0);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_modified_when_nothing_changes_before_timeout() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = wait(app, &format!("timeout=1&since_version={}", u32::MAX)).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    /// Entity updated between every read: each read is at the next version.
+    #[derive(Default)]
+    struct ChangingEntityRepo {
+        reads: std::sync::atomic::AtomicU32,
+    }
+
+    impl minihub_app::ports::EntityRepository for ChangingEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            StubEntityRepo.create(entity).await
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            let version = self.reads.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            let entity = StubEntityRepo.get_by_id(id).await?;
+            Ok(entity.map(|entity| Entity { version, ..entity }))
+        }
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            StubEntityRepo.get_many(ids).await
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            StubEntityRepo.get_all().await
+        }
+        async fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            StubEntityRepo.find_by_device_id(device_id).await
+        }
+        async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
+            StubEntityRepo.find_by_entity_id(entity_id).await
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            StubEntityRepo.update(entity).await
+        }
+        async fn delete(&self, id: EntityId) -> Result<(), MiniHubError> {
+            StubEntityRepo.delete(id).await
+        }
+        async fn find_by_alias(&self, alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            StubEntityRepo.find_by_alias(alias).await
+        }
+        async fn add_alias(&self, id: EntityId, alias: &str) -> Result<(), MiniHubError> {
+            StubEntityRepo.add_alias(id, alias).await
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_entity_once_it_changes_while_waiting() {
+        let event_bus = Arc::new(InProcessEventBus::new(16));
+        let state: AppState<TestPorts<_, _>> = AppState::new(
+            EntityService::new(ChangingEntityRepo::default(), StubPublisher),
+            DeviceService::new(StubDeviceRepo),
+            AreaService::new(StubAreaRepo),
+            StubEventStore,
+            AutomationService::new(StubAutomationRepo),
+            StubEntityHistoryRepo,
+            StubAutomationRunRepo,
+            StubReportRepo,
+            SceneService::new(StubSceneRepo, StubPublisher),
+            StubGroupRepo,
+            StubAuditRepo,
+            StubSettingsRepo,
+            StubEnergyUsageRepo,
+            StubSecretsRepo,
+            Arc::clone(&event_bus),
+        );
+        let app = crate::router::build(state, None);
+        let entity_id = EntityId::new();
+        let waiting = tokio::spawn(
+            app.oneshot(
+                Request::builder()
+                    .uri(format!("/api/entities/{entity_id}/wait?timeout=5"))
+                    .body(Body::empty())
+                    .unwrap(),
+            ),
+        );
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        event_bus
+            .publish(Event::new(
+                minihub_domain::event::EventType::StateChanged,
+                Some(entity_id),
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+
+        let response = waiting.await.unwrap().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_reject_wait_timeout_out_of_range() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = wait(app, "timeout=0").await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn should_return_only_state_fields_of_requested_entities() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+        let ids = [EntityId::new(), EntityId::new()];
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/entities/states")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "ids": ids.map(|id| id.to_string()) }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[0]["id"], ids[0].to_string());
+        assert_eq!(body[0]["attributes"]["brightness"], 128.0);
+        assert!(body[0].get("last_changed").is_some());
+        assert!(body[0].get("friendly_name").is_none());
+    }
+
+    #[tokio::test]
+    async fn should_reject_malformed_id_when_reading_states() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/entities/states")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"ids": ["not-a-uuid"]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn should_publish_service_call_requested_event() {
+        let event_bus = Arc::new(InProcessEventBus::new(16));
+        let mut rx = event_bus.subscribe();
+
+        let state: AppState<TestPorts<_, _>> = AppState::new(
+            EntityService::new(StubEntityRepo, Arc::clone(&event_bus)),
+            DeviceService::new(StubDeviceRepo),
+            AreaService::new(StubAreaRepo),
+            StubEventStore,
+            AutomationService::new(StubAutomationRepo),
+            StubEntityHistoryRepo,
+            StubAutomationRunRepo,
+            StubReportRepo,
+            SceneService::new(StubSceneRepo, Arc::clone(&event_bus)),
+            StubGroupRepo,
+            StubAuditRepo,
+            StubSettingsRepo,
+            StubEnergyUsageRepo,
+            StubSecretsRepo,
+            Arc::clone(&event_bus),
+        );
+        let app = crate::router::build(state, None);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entities/{entity_id}/service"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "service": "blink", "data": { "times": 3 } })
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(
+            event.event_type,
+            minihub_domain::event::EventType::ServiceCallRequested
+        );
+        assert_eq!(event.entity_id, Some(entity_id));
+        assert_eq!(event.data["service"], "blink");
+        assert_eq!(event.data["data"]["times"], 3);
+    }
+
+    #[tokio::test]
+    async fn should_publish_refresh_service_call_when_refresh_requested() {
+        let event_bus = Arc::new(InProcessEventBus::new(16));
+        let mut rx = event_bus.subscribe();
+
+        let state: AppState<TestPorts<_, _>> = AppState::new(
+            EntityService::new(StubEntityRepo, Arc::clone(&event_bus)),
+            DeviceService::new(StubDeviceRepo),
+            AreaService::new(StubAreaRepo),
+            StubEventStore,
+            AutomationService::new(StubAutomationRepo),
+            StubEntityHistoryRepo,
+            StubAutomationRunRepo,
+            StubReportRepo,
+            SceneService::new(StubSceneRepo, Arc::clone(&event_bus)),
+            StubGroupRepo,
+            StubAuditRepo,
+            StubSettingsRepo,
+            StubEnergyUsageRepo,
+            StubSecretsRepo,
+            Arc::clone(&event_bus),
+        );
+        let app = crate::router::build(state, None);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entities/{entity_id}/refresh"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(
+            event.event_type,
+            minihub_domain::event::EventType::ServiceCallRequested
+        );
+        assert_eq!(event.entity_id, Some(entity_id));
+        assert_eq!(event.data["service"], "refresh");
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_refreshing_unknown_entity() {
+        let app = build_app_with_entity_repo(NotFoundEntityRepo);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entities/{entity_id}/refresh"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_entity_does_not_exist() {
+        let app = build_app_with_entity_repo(NotFoundEntityRepo);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entities/{entity_id}/service"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "service": "blink" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn should_return_bad_request_when_entity_id_is_invalid() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/entities/not-a-uuid/service")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "service": "blink" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn should_reject_when_body_is_invalid_json() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/entities/{entity_id}/service"))
+                    .header("content-type", "application/json")
+                    .body(Body::from("not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(response.status().is_client_error());
+    }
+
+    #[tokio::test]
+    async fn should_reject_when_service_field_is_missing() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+        let entity_id = EntityId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
                     .uri(format!("/api/entities/{entity_id}/service"))
                     .header("content-type", "application/json")
                     .body(Body::from(serde_json::json!({ "data": {} }).to_string()))
@@ -660,4 +1687,49 @@ mod tests {
 
         assert!(response.status().is_client_error());
     }
+
+    fn rename_request(entity_id: EntityId, new_entity_id: &str) -> Request<Body> {
+        Request::builder()
+            .method("PUT")
+            .uri(format!("/api/entities/{entity_id}/rename"))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "entity_id": new_entity_id }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_rename_entity() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = app
+            .oneshot(rename_request(EntityId::new(), "light.lamp"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["entity_id"], "light.lamp");
+    }
+
+    #[tokio::test]
+    async fn should_reject_rename_to_another_domain() {
+        let app = build_app_with_entity_repo(StubEntityRepo);
+
+        let response = app
+            .oneshot(rename_request(EntityId::new(), "switch.lamp"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "invalid_entity_id");
+    }
 }
@@ -3,22 +3,21 @@
 use std::str::FromStr;
 
 use axum::Json;
-use axum::extract::{Path, Query, State};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use chrono::Duration;
 use serde::Deserialize;
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
-use minihub_domain::entity_history::EntityHistory;
+use minihub_app::ports::EntityHistoryRepository;
+use minihub_domain::entity_history::{EntityHistory, HistorySeries};
 use minihub_domain::error::MiniHubError;
 use minihub_domain::id::EntityId;
 use minihub_domain::time::{Timestamp, now};
 
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
 
 /// Default limit for history records.
 const DEFAULT_LIMIT: usize = 1000;
@@ -37,6 +36,26 @@ pub struct HistoryQuery {
     pub limit: Option<usize>,
 }
 
+/// Default number of buckets of a downsampled series.
+const DEFAULT_BUCKETS: u32 = 200;
+
+/// Largest number of buckets of a downsampled series.
+const MAX_BUCKETS: u32 = 1000;
+
+/// Query parameters for the downsampled history endpoint.
+#[derive(Deserialize)]
+pub struct SeriesQuery {
+    /// Start of time range (RFC 3339). Defaults to 24 hours ago.
+    pub from: Option<String>,
+    /// End of time range (RFC 3339). Defaults to now.
+    pub to: Option<String>,
+    /// Number of buckets the range is split into. Defaults to 200.
+    pub buckets: Option<u32>,
+    /// Comma-separated series to return, `state` or attribute keys.
+    /// Defaults to every numeric one.
+    pub series: Option<String>,
+}
+
 /// Possible responses from the history list endpoint.
 pub enum ListResponse {
     /// 200 OK with a JSON array of history records.
@@ -52,7 +71,7 @@ impl IntoResponse for ListResponse {
 }
 
 /// Parse an optional RFC 3339 timestamp string, returning a validation error on failure.
-fn parse_timestamp(value: &str) -> Result<Timestamp, ApiError> {
+pub(crate) fn parse_timestamp(value: &str) -> Result<Timestamp, ApiError> {
     chrono::DateTime::parse_from_rfc3339(value)
         .map(|dt| dt.to_utc())
         .map_err(|_| {
@@ -62,40 +81,25 @@ fn parse_timestamp(value: &str) -> Result<Timestamp, ApiError> {
         })
 }
 
-/// `GET /api/entities/:id/history?from=&to=&limit=`
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-    Path(id): Path<String>,
-    Query(params): Query<HistoryQuery>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let entity_id = EntityId::from_str(&id).map_err(|_| {
-        ApiError::from(MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyEntityId,
-        ))
-    })?;
-
+/// The `from`/`to` range of a history query, the last 24 hours by default.
+fn time_range(from: Option<&str>, to: Option<&str>) -> Result<(Timestamp, Timestamp), ApiError> {
     let current = now();
-    let from = params
-        .from
-        .as_deref()
+    let from = from
         .map(parse_timestamp)
         .transpose()?
         .unwrap_or_else(|| current - Duration::hours(DEFAULT_HOURS));
-    let to = params
-        .to
-        .as_deref()
-        .map(parse_timestamp)
-        .transpose()?
-        .unwrap_or(current);
+    let to = to.map(parse_timestamp).transpose()?.unwrap_or(current);
+    Ok((from, to))
+}
+
+/// `GET /api/entities/:id/history?from=&to=&limit=`
+pub async fn list<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<HistoryQuery>,
+) -> Result<ListResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let (from, to) = time_range(params.from.as_deref(), params.to.as_deref())?;
     let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
 
     let records = state
@@ -105,3 +109,67 @@ where
 
     Ok(ListResponse::Ok(Json(records)))
 }
+
+/// Possible responses from the downsampled history endpoint.
+pub enum SeriesResponse {
+    /// 200 OK with one downsampled series per numeric state or attribute.
+    Ok(Json<Vec<HistorySeries>>),
+}
+
+impl IntoResponse for SeriesResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/entities/:id/history/series?from=&to=&buckets=&series=`
+///
+/// Splits the range into `buckets` periods of equal length and returns the
+/// mean, min and max of each numeric series per period, so long ranges stay
+/// small to transfer and plot.
+pub async fn series<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    QueryParams(params): QueryParams<SeriesQuery>,
+) -> Result<SeriesResponse, ApiError> {
+    let entity_id = EntityId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let (from, to) = time_range(params.from.as_deref(), params.to.as_deref())?;
+    let buckets = params.buckets.unwrap_or(DEFAULT_BUCKETS);
+    if !(1..=MAX_BUCKETS).contains(&buckets) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_query",
+            format!("buckets must be between 1 and {MAX_BUCKETS}"),
+        ));
+    }
+    if to <= from {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_query",
+            "from must be before to",
+        ));
+    }
+
+    let records = state
+        .entity_history_repo
+        .find_by_entity_in_range(entity_id, from, to, None)
+        .await?;
+    let names = match params.series.as_deref() {
+        Some(series) => series
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => HistorySeries::names(&records),
+    };
+    let bucket = (to - from) / i32::try_from(buckets).unwrap_or(i32::MAX);
+    let series = names
+        .iter()
+        .map(|name| HistorySeries::downsample(&records, name, from, bucket))
+        .collect();
+
+    Ok(SeriesResponse::Ok(Json(series)))
+}
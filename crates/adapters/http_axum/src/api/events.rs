@@ -1,30 +1,110 @@
 //! JSON REST handlers for events.
 
+use std::fmt::Display;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use axum::Json;
+use axum::body::Body;
 use axum::extract::{Path, State};
+use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
-use minihub_domain::event::Event;
-use minihub_domain::id::EventId;
+use minihub_app::ports::{EventQuery, EventStore};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::{EntityId, EventId};
+use minihub_domain::time::{Timestamp, now};
 
+use crate::api::entity_history::parse_timestamp;
+use crate::auth::CallerRole;
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
+
+/// Number of events listed per page unless `limit` says otherwise.
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// Largest page the list endpoint returns.
+const MAX_LIST_LIMIT: usize = 1000;
+
+/// Response header carrying the cursor of the next page of events.
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// Largest causal chain returned by the chain endpoint.
+const MAX_CHAIN_LENGTH: usize = 500;
+
+/// Number of events fetched from the store per export chunk.
+const EXPORT_CHUNK_SIZE: usize = 500;
+
+/// Number of serialized chunks buffered ahead of a slow client.
+const EXPORT_BUFFERED_CHUNKS: usize = 2;
+
+/// Serialization format of an event export.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Newline-delimited JSON, one event per line.
+    #[default]
+    Ndjson,
+}
+
+/// Query parameters for the export endpoint.
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// Start of time range (RFC 3339, inclusive). Defaults to the Unix epoch.
+    pub from: Option<String>,
+    /// End of time range (RFC 3339, exclusive). Defaults to now.
+    pub to: Option<String>,
+    /// Output format. Defaults to `ndjson`.
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Query parameters for the list endpoint.
+#[derive(Deserialize)]
+pub struct ListQuery {
+    /// Only events of this type.
+    pub event_type: Option<EventType>,
+    /// Only events about this entity.
+    pub entity_id: Option<String>,
+    /// Start of time range (RFC 3339, inclusive).
+    pub from: Option<String>,
+    /// End of time range (RFC 3339, exclusive).
+    pub to: Option<String>,
+    /// Maximum number of events. Defaults to 100, capped at 1000.
+    pub limit: Option<usize>,
+    /// Cursor of the page to fetch, from the previous page's
+    /// [`NEXT_CURSOR_HEADER`].
+    pub cursor: Option<String>,
+}
 
 /// Possible responses from the list endpoint.
 pub enum ListResponse {
-    Ok(Json<Vec<Event>>),
+    /// 200 OK with the events, newest first, and the cursor of the next
+    /// page when there may be one.
+    Ok {
+        events: Json<Vec<Event>>,
+        next_cursor: Option<String>,
+    },
 }
 
 impl IntoResponse for ListResponse {
     fn into_response(self) -> Response {
         match self {
-            Self::Ok(json) => json.into_response(),
+            Self::Ok {
+                events,
+                next_cursor,
+            } => {
+                let mut response = events.into_response();
+                if let Some(value) = next_cursor.and_then(|c| HeaderValue::from_str(&c).ok()) {
+                    response.headers_mut().insert(NEXT_CURSOR_HEADER, value);
+                }
+                response
+            }
         }
     }
 }
@@ -42,43 +122,130 @@ impl IntoResponse for GetResponse {
     }
 }
 
-/// `GET /api/events` — list recent events.
-pub async fn list<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Result<ListResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let events = state.event_store.get_recent(100).await?;
-    Ok(ListResponse::Ok(Json(events)))
+/// Possible responses from the chain endpoint.
+pub enum ChainResponse {
+    Ok(Json<Vec<Event>>),
+}
+
+impl IntoResponse for ChainResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the export endpoint.
+pub enum ExportResponse {
+    /// 200 OK with a streamed newline-delimited JSON body.
+    Ndjson(Body),
+}
+
+impl IntoResponse for ExportResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ndjson(body) => {
+                ([(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response()
+            }
+        }
+    }
+}
+
+/// Encode the position of an item of a list sorted by `(timestamp, id)` as
+/// a page cursor: `<seconds>.<nanoseconds>_<id>`.
+pub(crate) fn encode_cursor(timestamp: Timestamp, id: impl Display) -> String {
+    format!(
+        "{}.{:09}_{id}",
+        timestamp.timestamp(),
+        timestamp.timestamp_subsec_nanos(),
+    )
+}
+
+/// Decode a cursor produced by [`encode_cursor`].
+pub(crate) fn decode_cursor<I: FromStr>(cursor: &str) -> Result<(Timestamp, I), ApiError> {
+    let invalid = || {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_cursor",
+            format!("invalid cursor: {cursor}"),
+        )
+    };
+    let (timestamp, id) = cursor.split_once('_').ok_or_else(invalid)?;
+    let (secs, nanos) = timestamp.split_once('.').ok_or_else(invalid)?;
+    let secs = secs.parse().map_err(|_| invalid())?;
+    let nanos = nanos.parse().map_err(|_| invalid())?;
+    let timestamp = Timestamp::from_timestamp(secs, nanos).ok_or_else(invalid)?;
+    let id = I::from_str(id).map_err(|_| invalid())?;
+    Ok((timestamp, id))
+}
+
+impl ListQuery {
+    /// Build the store query, rejecting malformed parameters.
+    fn to_event_query(&self) -> Result<EventQuery, ApiError> {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let mut query = EventQuery::new(limit);
+        if let Some(event_type) = &self.event_type {
+            query = query.with_event_type(event_type.clone());
+        }
+        if let Some(entity_id) = &self.entity_id {
+            let entity_id = EntityId::from_str(entity_id)
+                .map_err(|_| ApiError::invalid_id("entity_id", entity_id))?;
+            query = query.with_entity_id(entity_id);
+        }
+        if let Some(from) = &self.from {
+            query = query.with_from(parse_timestamp(from)?);
+        }
+        if let Some(to) = &self.to {
+            query = query.with_to(parse_timestamp(to)?);
+        }
+        if let Some(cursor) = &self.cursor {
+            let (timestamp, id): (_, EventId) = decode_cursor(cursor)?;
+            query = query.with_before(timestamp, id);
+        }
+        Ok(query)
+    }
+}
+
+/// `GET /api/events?event_type=&entity_id=&from=&to=&limit=&cursor=` —
+/// list events, newest first.
+///
+/// When the page is full, the cursor of the next one is returned in the
+/// [`NEXT_CURSOR_HEADER`] header.
+pub async fn list<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+    QueryParams(params): QueryParams<ListQuery>,
+) -> Result<ListResponse, ApiError> {
+    let query = params.to_event_query()?;
+    let limit = query.limit;
+    let mut events = state.event_store.query(query).await?;
+    for event in &mut events {
+        crate::redact::event(caller, event);
+    }
+    let next_cursor = (events.len() == limit)
+        .then(|| {
+            events
+                .last()
+                .map(|event| encode_cursor(event.timestamp, event.id))
+        })
+        .flatten();
+    Ok(ListResponse::Ok {
+        events: Json(events),
+        next_cursor,
+    })
 }
 
 /// `GET /api/events/:id` — get event by ID.
-pub async fn get<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
     Path(id): Path<String>,
-) -> Result<GetResponse, ApiError>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    let event_id = EventId::from_str(&id).map_err(|_| {
-        ApiError::from(minihub_domain::error::MiniHubError::Validation(
-            minihub_domain::error::ValidationError::EmptyName,
-        ))
-    })?;
-    let event = state
+) -> Result<GetResponse, ApiError> {
+    let event_id = EventId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let mut event = state
         .event_store
         .get_by_id(event_id)
         .await?
@@ -90,5 +257,127 @@ where
                 },
             ))
         })?;
+    crate::redact::event(caller, &mut event);
     Ok(GetResponse::Ok(Json(event)))
 }
+
+/// `GET /api/events/:id/chain` — the causal chain of an event: every event
+/// sharing its correlation id, oldest first.
+pub async fn chain<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+    Path(id): Path<String>,
+) -> Result<ChainResponse, ApiError> {
+    let event_id = EventId::from_str(&id).map_err(|_| ApiError::invalid_id("id", &id))?;
+    let event = state
+        .event_store
+        .get_by_id(event_id)
+        .await?
+        .ok_or_else(|| {
+            ApiError::from(minihub_domain::error::MiniHubError::NotFound(
+                minihub_domain::error::NotFoundError {
+                    entity: "Event",
+                    id,
+                },
+            ))
+        })?;
+    let mut events = state
+        .event_store
+        .query(EventQuery::new(MAX_CHAIN_LENGTH).with_correlation_id(event.correlation_id))
+        .await?;
+    events.reverse();
+    for event in &mut events {
+        crate::redact::event(caller, event);
+    }
+    Ok(ChainResponse::Ok(Json(events)))
+}
+
+/// `GET /api/events/export?from=&to=&format=ndjson` — stream stored events.
+///
+/// Events are read from the store in chunks of [`EXPORT_CHUNK_SIZE`],
+/// oldest-first. A chunk is only fetched once the client has consumed the
+/// previous ones, so large exports never hold the whole range in memory.
+pub async fn export<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+    QueryParams(params): QueryParams<ExportQuery>,
+) -> Result<ExportResponse, ApiError> {
+    let from = params
+        .from
+        .as_deref()
+        .map(parse_timestamp)
+        .transpose()?
+        .unwrap_or(Timestamp::UNIX_EPOCH);
+    let to = params
+        .to
+        .as_deref()
+        .map(parse_timestamp)
+        .transpose()?
+        .unwrap_or_else(now);
+
+    let (tx, rx) = mpsc::channel(EXPORT_BUFFERED_CHUNKS);
+    tokio::spawn(export_chunks(
+        Arc::clone(&state.event_store),
+        caller,
+        from,
+        to,
+        tx,
+    ));
+
+    match params.format {
+        ExportFormat::Ndjson => Ok(ExportResponse::Ndjson(Body::from_stream(
+            ReceiverStream::new(rx),
+        ))),
+    }
+}
+
+/// Page through the store and send each page as one NDJSON chunk.
+///
+/// Stops when the range is exhausted or the client disconnects. A store
+/// error is forwarded so the response is aborted instead of silently
+/// truncated.
+async fn export_chunks<ES: EventStore>(
+    store: Arc<ES>,
+    caller: CallerRole,
+    from: Timestamp,
+    to: Timestamp,
+    tx: mpsc::Sender<Result<String, MiniHubError>>,
+) {
+    let mut after = None;
+    loop {
+        let mut events = match store
+            .find_in_range(from, to, after, EXPORT_CHUNK_SIZE)
+            .await
+        {
+            Ok(events) => events,
+            Err(err) => {
+                tracing::warn!(%err, "event export failed");
+                let _ = tx.send(Err(err)).await;
+                return;
+            }
+        };
+        let Some(last) = events.last() else {
+            return;
+        };
+        after = Some((last.timestamp, last.id));
+
+        let mut chunk = String::new();
+        for event in &mut events {
+            crate::redact::event(caller, event);
+            match serde_json::to_string(event) {
+                Ok(line) => {
+                    chunk.push_str(&line);
+                    chunk.push('\n');
+                }
+                Err(err) => tracing::warn!(%err, "failed to serialize event for export"),
+            }
+        }
+        if tx.send(Ok(chunk)).await.is_err() {
+            tracing::debug!("event export client disconnected");
+            return;
+        }
+        if events.len() < EXPORT_CHUNK_SIZE {
+            return;
+        }
+    }
+}
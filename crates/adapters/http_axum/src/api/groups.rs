@@ -0,0 +1,144 @@
+//! JSON REST handlers for light and switch groups.
+
+use std::str::FromStr;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::group::{Group, GroupKind};
+use minihub_domain::id::{EntityId, GroupId};
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Request body for creating or updating a group.
+#[derive(Deserialize)]
+pub struct GroupRequest {
+    pub name: String,
+    /// Ignored on update: the kind of a group cannot change.
+    #[serde(default)]
+    pub kind: Option<GroupKind>,
+    pub members: Vec<EntityId>,
+}
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<Group>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the get endpoint.
+pub enum GetResponse {
+    Ok(Json<Group>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the create endpoint.
+pub enum CreateResponse {
+    Created(Json<Group>),
+}
+
+impl IntoResponse for CreateResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Created(json) => (StatusCode::CREATED, json).into_response(),
+        }
+    }
+}
+
+/// Possible responses from the delete endpoint.
+pub enum DeleteResponse {
+    NoContent,
+}
+
+impl IntoResponse for DeleteResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::NoContent => StatusCode::NO_CONTENT.into_response(),
+        }
+    }
+}
+
+fn parse_group_id(id: &str) -> Result<GroupId, ApiError> {
+    GroupId::from_str(id).map_err(|_| ApiError::invalid_id("id", id))
+}
+
+fn build_group(id: Option<GroupId>, req: GroupRequest) -> Result<Group, MiniHubError> {
+    let mut builder = Group::builder().name(req.name);
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    if let Some(kind) = req.kind {
+        builder = builder.kind(kind);
+    }
+    for member in req.members {
+        builder = builder.member(member);
+    }
+    builder.build()
+}
+
+/// `GET /api/groups` — list all groups.
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
+    let groups = state.group_service.list_groups().await?;
+    Ok(ListResponse::Ok(Json(groups)))
+}
+
+/// `GET /api/groups/:id` — get a single group.
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<GetResponse, ApiError> {
+    let group_id = parse_group_id(&id)?;
+    let group = state.group_service.get_group(group_id).await?;
+    Ok(GetResponse::Ok(Json(group)))
+}
+
+/// `POST /api/groups` — create a group and its entity.
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<GroupRequest>,
+) -> Result<CreateResponse, ApiError> {
+    let group = build_group(None, req)?;
+    let created = state.group_service.create_group(group).await?;
+    Ok(CreateResponse::Created(Json(created)))
+}
+
+/// `PUT /api/groups/:id` — replace the name and members of a group.
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<GroupRequest>,
+) -> Result<GetResponse, ApiError> {
+    let group_id = parse_group_id(&id)?;
+    let group = build_group(Some(group_id), req)?;
+    let updated = state.group_service.update_group(group).await?;
+    Ok(GetResponse::Ok(Json(updated)))
+}
+
+/// `DELETE /api/groups/:id` — delete a group and its entity.
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<DeleteResponse, ApiError> {
+    let group_id = parse_group_id(&id)?;
+    state.group_service.delete_group(group_id).await?;
+    Ok(DeleteResponse::NoContent)
+}
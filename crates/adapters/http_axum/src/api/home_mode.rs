@@ -0,0 +1,65 @@
+//! JSON REST handlers for the hub-wide home mode.
+
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+use minihub_domain::home_mode::HomeMode;
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Request body for changing the home mode.
+#[derive(Deserialize)]
+pub struct UpdateHomeModeRequest {
+    pub mode: HomeMode,
+}
+
+/// The current home mode and the modes it can be switched to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HomeModeSummary {
+    pub mode: HomeMode,
+    pub options: [HomeMode; 4],
+}
+
+impl From<HomeMode> for HomeModeSummary {
+    fn from(mode: HomeMode) -> Self {
+        Self {
+            mode,
+            options: HomeMode::ALL,
+        }
+    }
+}
+
+/// Possible responses from the get and update endpoints.
+pub enum GetResponse {
+    Ok(Json<HomeModeSummary>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/home_mode`
+pub async fn get<P: Ports>(State(state): State<AppState<P>>) -> Result<GetResponse, ApiError> {
+    let mode = state.home_mode_service().get_mode().await?;
+    Ok(GetResponse::Ok(Json(mode.into())))
+}
+
+/// `PUT /api/home_mode`
+///
+/// Publishes an `attribute_changed` event on the `input_select.home_mode`
+/// entity when the mode actually changes.
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<UpdateHomeModeRequest>,
+) -> Result<GetResponse, ApiError> {
+    let mode = state.home_mode_service().set_mode(req.mode).await?;
+    Ok(GetResponse::Ok(Json(mode.into())))
+}
@@ -0,0 +1,75 @@
+//! JSON REST handlers for input helpers.
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_domain::entity::Entity;
+use minihub_domain::input_helper::InputHelper;
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Request body for creating an input helper.
+///
+/// The constraints are flattened next to the name, e.g.
+/// `{"name": "Target", "kind": "number", "min": 15, "max": 25, "step": 0.5}`.
+#[derive(Deserialize)]
+pub struct CreateInputHelperRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub helper: InputHelper,
+    /// Starting value: `true`/`false` for booleans, a number or an option.
+    #[serde(default)]
+    pub initial: Option<serde_json::Value>,
+}
+
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<Entity>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the create endpoint.
+pub enum CreateResponse {
+    Created(Json<Entity>),
+}
+
+impl IntoResponse for CreateResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Created(json) => (StatusCode::CREATED, json).into_response(),
+        }
+    }
+}
+
+/// `GET /api/input_helpers`
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
+    let helpers = state.input_helper_service().list().await?;
+    Ok(ListResponse::Ok(Json(helpers)))
+}
+
+/// `POST /api/input_helpers`
+///
+/// The helper is attached to the hub device; its `entity_id` is derived
+/// from the name, e.g. `input_boolean.guest_mode`.
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<CreateInputHelperRequest>,
+) -> Result<CreateResponse, ApiError> {
+    let created = state
+        .input_helper_service()
+        .create(&req.name, &req.helper, req.initial.as_ref())
+        .await?;
+    Ok(CreateResponse::Created(Json(created)))
+}
@@ -0,0 +1,131 @@
+//! JSON REST handlers for the configured integrations.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use minihub_app::integration_manager::IntegrationReport;
+use minihub_domain::device::Device;
+
+use crate::error::ApiError;
+use crate::state::{AppState, Ports};
+
+/// An integration known to the daemon and how its startup went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrationSummary {
+    /// Integration name, e.g. `"mqtt"`.
+    pub name: String,
+    /// Whether the integration is enabled, in the configuration or at
+    /// runtime.
+    pub enabled: bool,
+    /// Startup status: `disabled`, `starting`, `running`, `timed_out`,
+    /// `restarting` or `failed`.
+    pub status: &'static str,
+    /// Last startup error, when the status is `restarting` or `failed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of restarts after a failed startup.
+    pub restarts: u32,
+}
+
+impl From<IntegrationReport> for IntegrationSummary {
+    fn from(report: IntegrationReport) -> Self {
+        Self {
+            status: report.status.as_str(),
+            error: report.status.error().map(str::to_string),
+            name: report.name,
+            enabled: report.enabled,
+            restarts: report.restarts,
+        }
+    }
+}
+
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<IntegrationSummary>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the devices endpoint.
+pub enum DevicesResponse {
+    Ok(Json<Vec<Device>>),
+}
+
+impl IntoResponse for DevicesResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the control endpoints.
+pub enum ControlResponse {
+    /// The request was queued; progress shows up in the integration status.
+    Accepted,
+}
+
+impl IntoResponse for ControlResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Accepted => StatusCode::ACCEPTED.into_response(),
+        }
+    }
+}
+
+/// `GET /api/integrations` — list the integrations and their startup status.
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> ListResponse {
+    ListResponse::Ok(Json(
+        state
+            .integrations
+            .reports()
+            .into_iter()
+            .map(IntegrationSummary::from)
+            .collect(),
+    ))
+}
+
+/// `GET /api/integrations/:name/devices` — list the devices the integration reported.
+pub async fn devices<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+) -> Result<DevicesResponse, ApiError> {
+    let devices = state.device_service.find_by_integration(&name).await?;
+    Ok(DevicesResponse::Ok(Json(devices)))
+}
+
+/// `POST /api/integrations/:name/restart` — tear the integration down and start it again.
+pub async fn restart<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+) -> Result<ControlResponse, ApiError> {
+    state.integrations.restart(&name)?;
+    Ok(ControlResponse::Accepted)
+}
+
+/// `POST /api/integrations/:name/disable` — tear the integration down until it is enabled.
+pub async fn disable<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+) -> Result<ControlResponse, ApiError> {
+    state.integrations.disable(&name)?;
+    Ok(ControlResponse::Accepted)
+}
+
+/// `POST /api/integrations/:name/enable` — start again an integration disabled at runtime.
+pub async fn enable<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+) -> Result<ControlResponse, ApiError> {
+    state.integrations.enable(&name)?;
+    Ok(ControlResponse::Accepted)
+}
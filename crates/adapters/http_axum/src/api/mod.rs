@@ -3,104 +3,204 @@
 #[allow(clippy::missing_errors_doc)]
 pub mod areas;
 #[allow(clippy::missing_errors_doc)]
+pub mod audit;
+#[allow(clippy::missing_errors_doc)]
 pub mod automations;
 #[allow(clippy::missing_errors_doc)]
+pub mod config;
+#[allow(clippy::missing_errors_doc)]
+pub mod dashboard_config;
+#[allow(clippy::missing_errors_doc)]
 pub mod devices;
 #[allow(clippy::missing_errors_doc)]
+pub mod discovery;
+#[allow(clippy::missing_errors_doc)]
+pub mod energy;
+#[allow(clippy::missing_errors_doc)]
 pub mod entities;
 #[allow(clippy::missing_errors_doc)]
 pub mod entity_history;
 #[allow(clippy::missing_errors_doc)]
 pub mod events;
+#[allow(clippy::missing_errors_doc)]
+pub mod groups;
+#[allow(clippy::missing_errors_doc)]
+pub mod home_mode;
+#[allow(clippy::missing_errors_doc)]
+pub mod input_helpers;
+#[allow(clippy::missing_errors_doc)]
+pub mod integrations;
+#[allow(clippy::missing_errors_doc)]
+pub mod reports;
+#[allow(clippy::missing_errors_doc)]
+pub mod scenes;
+#[allow(clippy::missing_errors_doc)]
+pub mod secrets;
+#[allow(clippy::missing_errors_doc)]
+pub mod settings;
 pub mod sse;
+#[allow(clippy::missing_errors_doc)]
+pub mod system;
+#[allow(clippy::missing_errors_doc)]
+pub mod validation;
+#[allow(clippy::missing_errors_doc)]
+pub mod webhooks;
 
 use axum::Router;
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{get, post, put};
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
+use crate::state::{AppState, Ports};
 
-use crate::state::AppState;
+/// Build the `/api` routes anyone may call, served apart from [`routes`]
+/// so their limits are not shared with authenticated clients.
+pub fn public_routes<P: Ports>() -> Router<AppState<P>> {
+    Router::new().route(
+        "/webhook/{webhook_id}",
+        post(webhooks::receive::<P>).layer(DefaultBodyLimit::max(webhooks::MAX_BODY_BYTES)),
+    )
+}
 
 /// Build the `/api` sub-router.
-pub fn routes<ER, DR, AR, EP, ES, AUR, EHR>() -> Router<AppState<ER, DR, AR, EP, ES, AUR, EHR>>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
+#[allow(clippy::too_many_lines)]
+pub fn routes<P: Ports>() -> Router<AppState<P>> {
     Router::new()
+        .route("/openapi.json", get(crate::openapi::document))
         // Entities
         .route(
             "/entities",
-            get(entities::list::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .post(entities::create::<ER, DR, AR, EP, ES, AUR, EHR>),
+            get(entities::list::<P>).post(entities::create::<P>),
         )
+        .route("/entities/validate", post(validation::entity))
+        .route("/entities/states", post(entities::states::<P>))
         .route(
             "/entities/{id}",
-            get(entities::get::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .delete(entities::delete::<ER, DR, AR, EP, ES, AUR, EHR>),
-        )
-        .route(
-            "/entities/{id}/state",
-            put(entities::update_state::<ER, DR, AR, EP, ES, AUR, EHR>),
-        )
-        .route(
-            "/entities/{id}/service",
-            post(entities::service_call::<ER, DR, AR, EP, ES, AUR, EHR>),
+            get(entities::get::<P>).delete(entities::delete::<P>),
         )
+        .route("/entities/{id}/state", put(entities::update_state::<P>))
+        .route("/entities/{id}/wait", get(entities::wait::<P>))
+        .route("/entities/{id}/rename", put(entities::rename::<P>))
+        .route("/entities/{id}/service", post(entities::service_call::<P>))
+        .route("/entities/{id}/refresh", post(entities::refresh::<P>))
+        .route("/entities/{id}/history", get(entity_history::list::<P>))
         .route(
-            "/entities/{id}/history",
-            get(entity_history::list::<ER, DR, AR, EP, ES, AUR, EHR>),
+            "/entities/{id}/history/series",
+            get(entity_history::series::<P>),
         )
         // Devices
         .route(
             "/devices",
-            get(devices::list::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .post(devices::create::<ER, DR, AR, EP, ES, AUR, EHR>),
+            get(devices::list::<P>).post(devices::create::<P>),
         )
         .route(
             "/devices/{id}",
-            get(devices::get::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .delete(devices::delete::<ER, DR, AR, EP, ES, AUR, EHR>),
+            get(devices::get::<P>).delete(devices::delete::<P>),
         )
+        .route("/devices/{id}/entities", get(entities::list_by_device::<P>))
+        .route("/devices/{id}/area", put(devices::assign_area::<P>))
+        // Discovery
+        .route("/discovery/pending", get(discovery::list_pending::<P>))
+        .route("/discovery/pending/{id}/adopt", post(discovery::adopt::<P>))
         // Areas
-        .route(
-            "/areas",
-            get(areas::list::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .post(areas::create::<ER, DR, AR, EP, ES, AUR, EHR>),
-        )
+        .route("/areas", get(areas::list::<P>).post(areas::create::<P>))
         .route(
             "/areas/{id}",
-            get(areas::get::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .delete(areas::delete::<ER, DR, AR, EP, ES, AUR, EHR>),
+            get(areas::get::<P>)
+                .put(areas::update::<P>)
+                .delete(areas::delete::<P>),
         )
+        .route("/areas/{id}/devices", get(devices::list_by_area::<P>))
         // Events
-        .route("/events", get(events::list::<ER, DR, AR, EP, ES, AUR, EHR>))
+        .route("/events", get(events::list::<P>))
+        .route("/events/export", get(events::export::<P>))
+        .route("/events/stream", get(sse::stream::<P>))
+        .route("/events/{id}", get(events::get::<P>))
+        .route("/events/{id}/chain", get(events::chain::<P>))
+        // Automations
         .route(
-            "/events/stream",
-            get(sse::stream::<ER, DR, AR, EP, ES, AUR, EHR>),
+            "/automations",
+            get(automations::list::<P>).post(automations::create::<P>),
         )
+        .route("/automations/validate", post(validation::automation))
+        .route("/automations/schedule", get(automations::schedule::<P>))
         .route(
-            "/events/{id}",
-            get(events::get::<ER, DR, AR, EP, ES, AUR, EHR>),
+            "/automations/{id}",
+            get(automations::get::<P>)
+                .put(automations::update::<P>)
+                .patch(automations::patch::<P>)
+                .delete(automations::delete::<P>),
         )
-        // Automations
+        .route("/automations/{id}/runs", get(automations::runs::<P>))
+        .route("/automations/{id}/test", post(automations::test::<P>))
+        // Integrations
+        .route("/integrations", get(integrations::list::<P>))
         .route(
-            "/automations",
-            get(automations::list::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .post(automations::create::<ER, DR, AR, EP, ES, AUR, EHR>),
+            "/integrations/{name}/devices",
+            get(integrations::devices::<P>),
         )
         .route(
-            "/automations/{id}",
-            get(automations::get::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .put(automations::update::<ER, DR, AR, EP, ES, AUR, EHR>)
-                .delete(automations::delete::<ER, DR, AR, EP, ES, AUR, EHR>),
+            "/integrations/{name}/restart",
+            post(integrations::restart::<P>),
+        )
+        .route(
+            "/integrations/{name}/disable",
+            post(integrations::disable::<P>),
+        )
+        .route(
+            "/integrations/{name}/enable",
+            post(integrations::enable::<P>),
+        )
+        // Configuration
+        .route("/config/reload", post(config::reload::<P>))
+        // Energy
+        .route("/energy/{id}", get(energy::get::<P>))
+        // Reports
+        .route("/reports/overview", get(reports::overview::<P>))
+        // System
+        .route("/system/info", get(system::info::<P>))
+        .route("/system/backup", post(system::backup::<P>))
+        // Audit log
+        .route("/audit", get(audit::list::<P>))
+        // Home mode
+        .route(
+            "/home_mode",
+            get(home_mode::get::<P>).put(home_mode::update::<P>),
+        )
+        // Dashboard layout
+        .route(
+            "/dashboard_config",
+            get(dashboard_config::get::<P>).put(dashboard_config::update::<P>),
+        )
+        // Settings
+        .route(
+            "/settings/{namespace}",
+            get(settings::get::<P>).put(settings::update::<P>),
+        )
+        // Secrets
+        .route("/secrets", get(secrets::list::<P>))
+        .route(
+            "/secrets/{name}",
+            post(secrets::store::<P>).delete(secrets::delete::<P>),
+        )
+        // Input helpers
+        .route(
+            "/input_helpers",
+            get(input_helpers::list::<P>).post(input_helpers::create::<P>),
+        )
+        // Scenes
+        .route("/groups", get(groups::list::<P>).post(groups::create::<P>))
+        .route(
+            "/groups/{id}",
+            get(groups::get::<P>)
+                .put(groups::update::<P>)
+                .delete(groups::delete::<P>),
+        )
+        .route("/scenes", get(scenes::list::<P>).post(scenes::create::<P>))
+        .route(
+            "/scenes/{id}",
+            get(scenes::get::<P>)
+                .put(scenes::update::<P>)
+                .delete(scenes::delete::<P>),
         )
+        .route("/scenes/{id}/activate", post(scenes::activate::<P>))
 }
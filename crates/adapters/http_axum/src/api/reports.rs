@@ -0,0 +1,51 @@
+//! JSON REST handlers for aggregated reports.
+
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use chrono::Duration;
+use serde::Deserialize;
+
+use minihub_app::ports::ReportRepository;
+use minihub_domain::report::Overview;
+use minihub_domain::time::now;
+
+use crate::error::ApiError;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
+
+/// Default activity window: last 24 hours.
+const DEFAULT_HOURS: i64 = 24;
+
+/// Query parameters for the overview endpoint.
+#[derive(Deserialize)]
+pub struct OverviewQuery {
+    /// Size of the activity window in hours. Defaults to 24.
+    pub hours: Option<u32>,
+}
+
+/// Possible responses from the overview endpoint.
+pub enum OverviewResponse {
+    Ok(Json<Overview>),
+}
+
+impl IntoResponse for OverviewResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/reports/overview?hours=` — inventory counts and recent activity.
+pub async fn overview<P: Ports>(
+    State(state): State<AppState<P>>,
+    QueryParams(params): QueryParams<OverviewQuery>,
+) -> Result<OverviewResponse, ApiError> {
+    let hours = params.hours.map_or(DEFAULT_HOURS, i64::from);
+    let overview = state
+        .report_repo
+        .overview(now() - Duration::hours(hours))
+        .await?;
+    Ok(OverviewResponse::Ok(Json(overview)))
+}
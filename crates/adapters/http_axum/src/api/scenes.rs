@@ -0,0 +1,167 @@
+//! JSON REST handlers for scenes.
+
+use std::str::FromStr;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::SceneId;
+use minihub_domain::scene::{Scene, SceneMember};
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Request body for creating or updating a scene.
+#[derive(Deserialize)]
+pub struct SceneRequest {
+    pub name: String,
+    pub members: Vec<SceneMember>,
+}
+
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<Scene>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the get endpoint.
+pub enum GetResponse {
+    Ok(Json<Scene>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the create endpoint.
+pub enum CreateResponse {
+    Created(Json<Scene>),
+}
+
+impl IntoResponse for CreateResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Created(json) => (StatusCode::CREATED, json).into_response(),
+        }
+    }
+}
+
+/// Possible responses from the delete endpoint.
+pub enum DeleteResponse {
+    NoContent,
+}
+
+impl IntoResponse for DeleteResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::NoContent => StatusCode::NO_CONTENT.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the activate endpoint.
+pub enum ActivateResponse {
+    /// Service calls were requested; devices are actuated asynchronously.
+    Accepted(Json<Scene>),
+}
+
+impl IntoResponse for ActivateResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Accepted(json) => (StatusCode::ACCEPTED, json).into_response(),
+        }
+    }
+}
+
+fn parse_scene_id(id: &str) -> Result<SceneId, ApiError> {
+    SceneId::from_str(id).map_err(|_| ApiError::invalid_id("id", id))
+}
+
+fn build_scene(id: Option<SceneId>, req: SceneRequest) -> Result<Scene, MiniHubError> {
+    let mut builder = Scene::builder().name(req.name);
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    for member in req.members {
+        builder = builder.member(member);
+    }
+    builder.build()
+}
+
+/// `GET /api/scenes` — list all scenes.
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
+    let scenes = state.scene_service.list_scenes().await?;
+    Ok(ListResponse::Ok(Json(scenes)))
+}
+
+/// `GET /api/scenes/:id` — get a single scene.
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<GetResponse, ApiError> {
+    let scene_id = parse_scene_id(&id)?;
+    let scene = state.scene_service.get_scene(scene_id).await?;
+    Ok(GetResponse::Ok(Json(scene)))
+}
+
+/// `POST /api/scenes` — create a new scene.
+pub async fn create<P: Ports>(
+    State(state): State<AppState<P>>,
+    JsonBody(req): JsonBody<SceneRequest>,
+) -> Result<CreateResponse, ApiError> {
+    let scene = build_scene(None, req)?;
+    let created = state.scene_service.create_scene(scene).await?;
+    Ok(CreateResponse::Created(Json(created)))
+}
+
+/// `PUT /api/scenes/:id` — replace the name and members of a scene.
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+    JsonBody(req): JsonBody<SceneRequest>,
+) -> Result<GetResponse, ApiError> {
+    let scene_id = parse_scene_id(&id)?;
+
+    // Verify it exists
+    state.scene_service.get_scene(scene_id).await?;
+
+    let scene = build_scene(Some(scene_id), req)?;
+    let updated = state.scene_service.update_scene(scene).await?;
+    Ok(GetResponse::Ok(Json(updated)))
+}
+
+/// `DELETE /api/scenes/:id` — delete a scene.
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<DeleteResponse, ApiError> {
+    let scene_id = parse_scene_id(&id)?;
+    state.scene_service.delete_scene(scene_id).await?;
+    Ok(DeleteResponse::NoContent)
+}
+
+/// `POST /api/scenes/:id/activate` — request a service call for every member.
+pub async fn activate<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(id): Path<String>,
+) -> Result<ActivateResponse, ApiError> {
+    let scene_id = parse_scene_id(&id)?;
+    let scene = state.scene_service.activate_scene(scene_id).await?;
+    Ok(ActivateResponse::Accepted(Json(scene)))
+}
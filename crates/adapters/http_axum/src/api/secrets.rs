@@ -0,0 +1,92 @@
+//! JSON REST handlers for the secrets of integrations.
+//!
+//! Values can be written and removed but never read back: every response
+//! only carries the name of the secret and when it was last written.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+
+use minihub_domain::secret::{SecretInfo, SecretValue};
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Request body for storing a secret.
+#[derive(Deserialize)]
+pub struct StoreSecretRequest {
+    pub value: String,
+}
+
+/// Possible responses from the list endpoint.
+pub enum ListResponse {
+    Ok(Json<Vec<SecretInfo>>),
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the store endpoint.
+pub enum StoreResponse {
+    Ok(Json<SecretInfo>),
+}
+
+impl IntoResponse for StoreResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// Possible responses from the delete endpoint.
+pub enum DeleteResponse {
+    NoContent,
+}
+
+impl IntoResponse for DeleteResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::NoContent => StatusCode::NO_CONTENT.into_response(),
+        }
+    }
+}
+
+/// `GET /api/secrets`
+pub async fn list<P: Ports>(State(state): State<AppState<P>>) -> Result<ListResponse, ApiError> {
+    let secrets = state.secrets_service.list().await?;
+    Ok(ListResponse::Ok(Json(secrets)))
+}
+
+/// `POST /api/secrets/{name}`
+///
+/// Stores the value, replacing any previous one, and answers with the name
+/// and write time only.
+pub async fn store<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+    JsonBody(req): JsonBody<StoreSecretRequest>,
+) -> Result<StoreResponse, ApiError> {
+    let info = state
+        .secrets_service
+        .store(&name, SecretValue::new(req.value))
+        .await?;
+    Ok(StoreResponse::Ok(Json(info)))
+}
+
+/// `DELETE /api/secrets/{name}`
+pub async fn delete<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(name): Path<String>,
+) -> Result<DeleteResponse, ApiError> {
+    state.secrets_service.delete(&name).await?;
+    Ok(DeleteResponse::NoContent)
+}
@@ -0,0 +1,50 @@
+//! JSON REST handlers for the hub-wide settings, one namespace at a time.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+
+use minihub_domain::settings::Settings;
+
+use crate::error::ApiError;
+use crate::extract::JsonBody;
+use crate::state::{AppState, Ports};
+
+/// Possible responses from the get and update endpoints.
+pub enum GetResponse {
+    Ok(Json<Settings>),
+}
+
+impl IntoResponse for GetResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/settings/{namespace}`
+///
+/// An unknown namespace has no settings yet, so it returns an empty object.
+pub async fn get<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(namespace): Path<String>,
+) -> Result<GetResponse, ApiError> {
+    let settings = state.settings_service().get_namespace(&namespace).await?;
+    Ok(GetResponse::Ok(Json(settings)))
+}
+
+/// `PUT /api/settings/{namespace}`
+///
+/// Replaces the whole namespace: keys missing from the body are removed.
+pub async fn update<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(namespace): Path<String>,
+    JsonBody(settings): JsonBody<Settings>,
+) -> Result<GetResponse, ApiError> {
+    let settings = state
+        .settings_service()
+        .replace_namespace(&namespace, settings)
+        .await?;
+    Ok(GetResponse::Ok(Json(settings)))
+}
@@ -1,39 +1,103 @@
 //! Server-Sent Events (SSE) stream for real-time updates.
 
+use std::str::FromStr;
+
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::response::sse::{Event, KeepAlive, Sse};
+use serde::Deserialize;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::BroadcastStream;
 
-use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
-};
+use minihub_app::event_bus::EventFilter;
+
+use minihub_domain::event::EventType;
+use minihub_domain::id::EntityId;
+
+use crate::auth::CallerRole;
+use crate::error::ApiError;
+use crate::extract::QueryParams;
+use crate::state::{AppState, Ports};
+
+/// Query parameters for the stream endpoint, each a comma-separated list.
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamQuery {
+    /// Only stream events of these types, e.g. `state_changed,entity_created`.
+    pub event_type: Option<String>,
+    /// Only stream events about these entities.
+    pub entity_id: Option<String>,
+    /// Only stream events whose payload names one of these integrations.
+    pub integration: Option<String>,
+}
 
-use crate::state::AppState;
+impl StreamQuery {
+    /// The filter described by the parameters.
+    fn filter(&self) -> Result<EventFilter, ApiError> {
+        let mut filter = EventFilter::all();
+        if let Some(types) = &self.event_type {
+            let types = split(types)
+                .map(|value| {
+                    serde_json::from_value::<EventType>(serde_json::Value::from(value)).map_err(
+                        |_| {
+                            ApiError::new(
+                                StatusCode::BAD_REQUEST,
+                                "invalid_event_type",
+                                format!("{value:?} is not an event type"),
+                            )
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            filter = filter.with_event_types(types);
+        }
+        if let Some(ids) = &self.entity_id {
+            let ids = split(ids)
+                .map(|value| {
+                    EntityId::from_str(value).map_err(|_| ApiError::invalid_id("entity_id", value))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            filter = filter.with_entity_ids(ids);
+        }
+        if let Some(integrations) = &self.integration {
+            filter = filter.with_integrations(split(integrations));
+        }
+        Ok(filter)
+    }
+}
+
+/// Non-empty, trimmed items of a comma-separated list.
+pub(crate) fn split(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
 
-/// `GET /api/events/stream` — SSE stream of real-time domain events.
+/// `GET /api/events/stream?event_type=&entity_id=&integration=` — SSE
+/// stream of real-time domain events.
 ///
 /// Subscribes to the event bus broadcast channel and sends JSON-encoded
 /// events as SSE `data:` frames. The stream continues until the client
-/// disconnects or the event bus is closed.
+/// disconnects or the event bus is closed. Events not passing the query
+/// filter are skipped before being serialized.
 ///
 /// Each event is sent as a JSON object with the event structure from the domain.
-pub async fn stream<ER, DR, AR, EP, ES, AUR, EHR>(
-    State(state): State<AppState<ER, DR, AR, EP, ES, AUR, EHR>>,
-) -> Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
+///
+/// # Errors
+///
+/// Returns a `400` error when a filter names an unknown event type or an
+/// invalid entity id.
+pub async fn stream<P: Ports>(
+    State(state): State<AppState<P>>,
+    caller: CallerRole,
+    QueryParams(params): QueryParams<StreamQuery>,
+) -> Result<Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>>, ApiError>
 {
+    let filter = params.filter()?;
     let event_rx = state.event_bus.subscribe();
-    let event_stream = BroadcastStream::new(event_rx).filter_map(|result| match result {
-        Ok(event) => {
+    let event_stream = BroadcastStream::new(event_rx).filter_map(move |result| match result {
+        Ok(event) if !filter.matches(&event) => None,
+        Ok(mut event) => {
+            crate::redact::event(caller, &mut event);
             // Serialize event to JSON
             match serde_json::to_string(&event) {
                 Ok(json) => Some(Ok(Event::default().data(json))),
@@ -52,7 +116,7 @@ where
         }
     });
 
-    Sse::new(event_stream).keep_alive(KeepAlive::default())
+    Ok(Sse::new(event_stream).keep_alive(KeepAlive::default()))
 }
 
 #[cfg(test)]
@@ -60,18 +124,25 @@ mod tests {
     use super::*;
     use crate::state::AppState;
     use minihub_app::event_bus::InProcessEventBus;
+    use minihub_app::ports::{EventPublisher, EventStore, Storage};
     use minihub_app::services::area_service::AreaService;
     use minihub_app::services::automation_service::AutomationService;
     use minihub_app::services::device_service::DeviceService;
     use minihub_app::services::entity_service::EntityService;
+    use minihub_app::services::scene_service::SceneService;
     use minihub_domain::area::Area;
     use minihub_domain::automation::Automation;
+    use minihub_domain::automation_run::AutomationRun;
     use minihub_domain::device::Device;
     use minihub_domain::entity::Entity;
     use minihub_domain::entity_history::EntityHistory;
     use minihub_domain::error::MiniHubError;
     use minihub_domain::event::{Event as DomainEvent, EventType};
-    use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId, EventId};
+    use minihub_domain::group::Group;
+    use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId, EventId, GroupId, SceneId};
+    use minihub_domain::report::{Overview, StorageStats};
+    use minihub_domain::role::Role;
+    use minihub_domain::scene::Scene;
     use minihub_domain::time::Timestamp;
     use std::sync::Arc;
 
@@ -81,6 +152,14 @@ mod tests {
     struct StubEventStore;
     struct StubAutomationRepo;
     struct StubEntityHistoryRepo;
+    struct StubAutomationRunRepo;
+    struct StubReportRepo;
+    struct StubSceneRepo;
+    struct StubGroupRepo;
+    struct StubAuditRepo;
+    struct StubSettingsRepo;
+    struct StubEnergyUsageRepo;
+    struct StubSecretsRepo;
 
     impl minihub_app::ports::EntityRepository for StubEntityRepo {
         async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
@@ -89,6 +168,10 @@ mod tests {
         async fn get_by_id(&self, _id: EntityId) -> Result<Option<Entity>, MiniHubError> {
             Ok(None)
         }
+
+        async fn get_many(&self, _ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
         async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
             Ok(vec![])
         }
@@ -110,6 +193,12 @@ mod tests {
         async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
             Ok(())
         }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
     }
 
     impl minihub_app::ports::DeviceRepository for StubDeviceRepo {
@@ -122,6 +211,15 @@ mod tests {
         async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
             Ok(vec![])
         }
+        async fn find_by_area(&self, _area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_integration(
+            &self,
+            _integration: &str,
+        ) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
         async fn find_by_integration_unique_id(
             &self,
             _integration: &str,
@@ -155,6 +253,12 @@ mod tests {
         }
     }
 
+    impl Storage for StubEventStore {
+        async fn ping(&self) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
     impl EventStore for StubEventStore {
         async fn store(&self, event: DomainEvent) -> Result<DomainEvent, MiniHubError> {
             Ok(event)
@@ -172,6 +276,22 @@ mod tests {
         ) -> Result<Vec<DomainEvent>, MiniHubError> {
             Ok(vec![])
         }
+        async fn find_in_range(
+            &self,
+            _from: Timestamp,
+            _to: Timestamp,
+            _after: Option<(Timestamp, EventId)>,
+            _limit: usize,
+        ) -> Result<Vec<DomainEvent>, MiniHubError> {
+            Ok(vec![])
+        }
+
+        async fn query(
+            &self,
+            _query: minihub_app::ports::EventQuery,
+        ) -> Result<Vec<DomainEvent>, MiniHubError> {
+            Ok(vec![])
+        }
     }
 
     impl minihub_app::ports::AutomationRepository for StubAutomationRepo {
@@ -190,6 +310,13 @@ mod tests {
         async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
             Ok(automation)
         }
+        async fn record_triggered(
+            &self,
+            _id: AutomationId,
+            _triggered_at: Timestamp,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
         async fn delete(&self, _id: AutomationId) -> Result<(), MiniHubError> {
             Ok(())
         }
@@ -213,19 +340,173 @@ mod tests {
         }
     }
 
-    #[allow(clippy::type_complexity)]
-    fn test_state() -> (
-        AppState<
-            StubEntityRepo,
-            StubDeviceRepo,
-            StubAreaRepo,
-            Arc<InProcessEventBus>,
-            StubEventStore,
-            StubAutomationRepo,
-            StubEntityHistoryRepo,
-        >,
-        Arc<InProcessEventBus>,
-    ) {
+    impl minihub_app::ports::AutomationRunRepository for StubAutomationRunRepo {
+        async fn record(&self, run: AutomationRun) -> Result<AutomationRun, MiniHubError> {
+            Ok(run)
+        }
+        async fn find_by_automation(
+            &self,
+            _automation_id: AutomationId,
+            _limit: usize,
+        ) -> Result<Vec<AutomationRun>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::ReportRepository for StubReportRepo {
+        async fn overview(&self, since: Timestamp) -> Result<Overview, MiniHubError> {
+            Ok(Overview {
+                since,
+                ..Overview::default()
+            })
+        }
+
+        async fn storage_stats(&self) -> Result<StorageStats, MiniHubError> {
+            Ok(StorageStats::default())
+        }
+    }
+
+    impl minihub_app::ports::SceneRepository for StubSceneRepo {
+        async fn create(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+            Ok(scene)
+        }
+        async fn get_by_id(&self, _id: SceneId) -> Result<Option<Scene>, MiniHubError> {
+            Ok(None)
+        }
+        async fn get_all(&self) -> Result<Vec<Scene>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn update(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+            Ok(scene)
+        }
+        async fn delete(&self, _id: SceneId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    impl minihub_app::ports::SecretsRepository for StubSecretsRepo {
+        async fn get(
+            &self,
+            _name: &str,
+        ) -> Result<Option<minihub_domain::secret::SecretValue>, MiniHubError> {
+            Ok(None)
+        }
+        async fn list(&self) -> Result<Vec<minihub_domain::secret::SecretInfo>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn put(
+            &self,
+            name: &str,
+            _value: &minihub_domain::secret::SecretValue,
+        ) -> Result<minihub_domain::secret::SecretInfo, MiniHubError> {
+            Ok(minihub_domain::secret::SecretInfo {
+                name: name.to_string(),
+                updated_at: minihub_domain::time::now(),
+            })
+        }
+        async fn delete(&self, _name: &str) -> Result<bool, MiniHubError> {
+            Ok(false)
+        }
+    }
+
+    impl minihub_app::ports::EnergyUsageRepository for StubEnergyUsageRepo {
+        async fn add(
+            &self,
+            _usage: Vec<minihub_domain::energy::EnergyUsage>,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_entity_in_range(
+            &self,
+            _entity_id: EntityId,
+            _from: minihub_domain::time::Timestamp,
+            _to: minihub_domain::time::Timestamp,
+        ) -> Result<Vec<minihub_domain::energy::EnergyUsage>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::SettingsRepository for StubSettingsRepo {
+        async fn get(
+            &self,
+            _namespace: &str,
+            _key: &str,
+        ) -> Result<Option<serde_json::Value>, MiniHubError> {
+            Ok(None)
+        }
+        async fn list(
+            &self,
+            _namespace: &str,
+        ) -> Result<minihub_domain::settings::Settings, MiniHubError> {
+            Ok(minihub_domain::settings::Settings::new())
+        }
+        async fn put(
+            &self,
+            _namespace: &str,
+            _key: &str,
+            _value: serde_json::Value,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn delete(&self, _namespace: &str, _key: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    impl minihub_app::ports::AuditRepository for StubAuditRepo {
+        async fn record(
+            &self,
+            entry: minihub_domain::audit::AuditEntry,
+        ) -> Result<minihub_domain::audit::AuditEntry, MiniHubError> {
+            Ok(entry)
+        }
+        async fn query(
+            &self,
+            _query: minihub_app::ports::AuditQuery,
+        ) -> Result<Vec<minihub_domain::audit::AuditEntry>, MiniHubError> {
+            Ok(vec![])
+        }
+    }
+
+    impl minihub_app::ports::GroupRepository for StubGroupRepo {
+        async fn create(&self, group: Group) -> Result<Group, MiniHubError> {
+            Ok(group)
+        }
+        async fn get_by_id(&self, _id: GroupId) -> Result<Option<Group>, MiniHubError> {
+            Ok(None)
+        }
+        async fn get_all(&self) -> Result<Vec<Group>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn update(&self, group: Group) -> Result<Group, MiniHubError> {
+            Ok(group)
+        }
+        async fn delete(&self, _id: GroupId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    struct StubPorts;
+
+    impl Ports for StubPorts {
+        type Entities = StubEntityRepo;
+        type Devices = StubDeviceRepo;
+        type Areas = StubAreaRepo;
+        type Publisher = Arc<InProcessEventBus>;
+        type EventStore = StubEventStore;
+        type Automations = StubAutomationRepo;
+        type EntityHistory = StubEntityHistoryRepo;
+        type AutomationRuns = StubAutomationRunRepo;
+        type Reports = StubReportRepo;
+        type Scenes = StubSceneRepo;
+        type Groups = StubGroupRepo;
+        type Audit = StubAuditRepo;
+        type Settings = StubSettingsRepo;
+        type EnergyUsage = StubEnergyUsageRepo;
+        type Secrets = StubSecretsRepo;
+    }
+
+    fn test_state() -> (AppState<StubPorts>, Arc<InProcessEventBus>) {
         let event_bus = Arc::new(InProcessEventBus::new(16));
 
         let state = AppState::new(
@@ -235,6 +516,14 @@ mod tests {
             StubEventStore,
             AutomationService::new(StubAutomationRepo),
             StubEntityHistoryRepo,
+            StubAutomationRunRepo,
+            StubReportRepo,
+            SceneService::new(StubSceneRepo, Arc::clone(&event_bus)),
+            StubGroupRepo,
+            StubAuditRepo,
+            StubSettingsRepo,
+            StubEnergyUsageRepo,
+            StubSecretsRepo,
             Arc::clone(&event_bus),
         );
 
@@ -249,13 +538,18 @@ mod tests {
         let mut rx = event_bus.subscribe();
 
         // Create SSE stream (this also subscribes internally)
-        let _sse_response = stream(State(state)).await;
+        let _sse_response = stream(
+            State(state),
+            CallerRole(Role::Admin),
+            QueryParams(StreamQuery::default()),
+        )
+        .await;
 
         // Publish an event to the bus
         let test_event = DomainEvent::new(
             EventType::StateChanged,
             Some(EntityId::new()),
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         );
         let event_id = test_event.id;
 
@@ -266,4 +560,42 @@ mod tests {
         assert_eq!(received.id, event_id);
         assert_eq!(received.event_type, EventType::StateChanged);
     }
+
+    #[test]
+    fn should_build_filter_from_comma_separated_params() {
+        let entity_id = EntityId::new();
+        let params = StreamQuery {
+            event_type: Some("state_changed, attribute_changed".to_string()),
+            entity_id: Some(entity_id.to_string()),
+            integration: None,
+        };
+
+        let filter = params.filter().unwrap();
+
+        let matching = DomainEvent::new(
+            EventType::AttributeChanged,
+            Some(entity_id),
+            serde_json::json!({}),
+        );
+        let other = DomainEvent::new(
+            EventType::EntityCreated,
+            Some(entity_id),
+            serde_json::json!({}),
+        );
+        assert!(filter.matches(&matching));
+        assert!(!filter.matches(&other));
+    }
+
+    #[test]
+    fn should_reject_unknown_event_type() {
+        let params = StreamQuery {
+            event_type: Some("state_changed,exploded".to_string()),
+            ..StreamQuery::default()
+        };
+
+        let err = params.filter().unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.code(), "invalid_event_type");
+    }
 }
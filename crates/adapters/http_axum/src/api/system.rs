@@ -0,0 +1,127 @@
+//! JSON REST handlers describing and maintaining the running daemon.
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use minihub_app::backup::{BackupError, BackupReport};
+use minihub_app::ports::ReportRepository;
+use minihub_domain::report::StorageStats;
+use minihub_domain::time::{Timestamp, now};
+
+use super::integrations::IntegrationSummary;
+use crate::error::ApiError;
+use crate::state::{AppState, Ports};
+
+/// Load of the in-process event bus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventBusStats {
+    /// Number of events kept for slow subscribers before they lag.
+    pub capacity: usize,
+    /// Number of active subscribers (SSE clients, automation engine, …).
+    pub subscribers: usize,
+    /// Number of events not yet received by the slowest subscriber.
+    pub queued: usize,
+}
+
+/// Version, uptime and load of the running daemon.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    /// Release version, e.g. `"0.1.1"`.
+    pub version: &'static str,
+    /// Commit the daemon was built from, when known.
+    pub git_hash: Option<&'static str>,
+    pub started_at: Timestamp,
+    pub uptime_secs: u64,
+    pub storage: StorageStats,
+    pub integrations: Vec<IntegrationSummary>,
+    pub event_bus: EventBusStats,
+}
+
+/// Possible responses from the system info endpoint.
+pub enum InfoResponse {
+    Ok(Json<SystemInfo>),
+}
+
+impl IntoResponse for InfoResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(json) => json.into_response(),
+        }
+    }
+}
+
+/// `GET /api/system/info` — version, uptime, storage size and event bus load.
+pub async fn info<P: Ports>(State(state): State<AppState<P>>) -> Result<InfoResponse, ApiError> {
+    let storage = state.report_repo.storage_stats().await?;
+    let uptime = (now() - state.started_at).num_seconds().max(0);
+    Ok(InfoResponse::Ok(Json(SystemInfo {
+        version: state.build_info.version,
+        git_hash: state.build_info.git_hash,
+        started_at: state.started_at,
+        uptime_secs: uptime.unsigned_abs(),
+        storage,
+        integrations: state
+            .integrations
+            .reports()
+            .into_iter()
+            .map(IntegrationSummary::from)
+            .collect(),
+        event_bus: EventBusStats {
+            capacity: state.event_bus.capacity(),
+            subscribers: state.event_bus.subscriber_count(),
+            queued: state.event_bus.queued(),
+        },
+    })))
+}
+
+/// Database snapshot written on demand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupSummary {
+    /// Location of the snapshot file on the hub.
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: Timestamp,
+    /// Older snapshots removed to stay within the retention count.
+    pub pruned: Vec<String>,
+}
+
+impl From<BackupReport> for BackupSummary {
+    fn from(report: BackupReport) -> Self {
+        Self {
+            path: report.path.display().to_string(),
+            size_bytes: report.size_bytes,
+            created_at: report.created_at,
+            pruned: report
+                .pruned
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Possible responses from the backup endpoint.
+pub enum BackupResponse {
+    Created(Json<BackupSummary>),
+}
+
+impl IntoResponse for BackupResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Created(json) => (StatusCode::CREATED, json).into_response(),
+        }
+    }
+}
+
+/// `POST /api/system/backup` — write a database snapshot now, rotating the
+/// older ones.
+pub async fn backup<P: Ports>(
+    State(state): State<AppState<P>>,
+) -> Result<BackupResponse, ApiError> {
+    let handle = state.backup.as_ref().ok_or(BackupError::Unavailable)?;
+    let report = handle.backup().await?;
+    Ok(BackupResponse::Created(Json(report.into())))
+}
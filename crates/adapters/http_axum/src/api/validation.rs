@@ -0,0 +1,358 @@
+//! Dry-run validation of automation and entity payloads.
+//!
+//! The payload is decoded field by field and run through the domain
+//! builders, so every problem is reported at once with the field it
+//! concerns. Nothing is persisted: the editors call these while the user
+//! types and only submit once the report is valid.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use axum::Json;
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use minihub_domain::automation::{Action, Automation, Condition, ExecutionMode, Trigger};
+use minihub_domain::entity::Entity;
+use minihub_domain::error::FieldError;
+use minihub_domain::id::DeviceId;
+
+use crate::error::{ApiError, validation_code};
+use crate::extract::JsonBody;
+
+/// Outcome of a dry-run validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    /// `true` when `errors` is empty and the payload would be accepted.
+    pub valid: bool,
+    pub errors: Vec<FieldIssue>,
+}
+
+/// A problem with one field of the payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldIssue {
+    /// Field path, e.g. `name` or `actions[1]`.
+    pub field: String,
+    /// `missing_field`, `invalid_value` or a validation error code.
+    pub code: String,
+    pub message: String,
+}
+
+/// `POST /api/automations/validate`
+///
+/// Accepts the same body as `POST /api/automations`.
+pub async fn automation(
+    JsonBody(body): JsonBody<Value>,
+) -> Result<Json<ValidationReport>, ApiError> {
+    let mut fields = Fields::new(&body)?;
+    let mut builder = Automation::builder();
+    if let Some(name) = fields.required::<String>("name") {
+        builder = builder.name(name);
+    }
+    if let Some(enabled) = fields.optional::<bool>("enabled") {
+        builder = builder.enabled(enabled);
+    }
+    if let Some(trigger) = fields.required::<Trigger>("trigger") {
+        builder = builder.trigger(trigger);
+    }
+    if let Some(execution_mode) = fields.optional::<ExecutionMode>("execution_mode") {
+        builder = builder.execution_mode(execution_mode);
+    }
+    let conditions = fields.list::<Condition>("conditions", false);
+    for condition in conditions.items {
+        builder = builder.condition(condition);
+    }
+    let actions = fields.list::<Action>("actions", true);
+    for action in actions.items {
+        builder = builder.action(action);
+    }
+    let errors = builder
+        .field_errors()
+        .into_iter()
+        .map(|err| conditions.index.reindex(actions.index.reindex(err)));
+    Ok(Json(fields.report(errors)))
+}
+
+/// `POST /api/entities/validate`
+///
+/// Accepts the same body as `POST /api/entities`.
+pub async fn entity(JsonBody(body): JsonBody<Value>) -> Result<Json<ValidationReport>, ApiError> {
+    let mut fields = Fields::new(&body)?;
+    let mut builder = Entity::builder();
+    if let Some(device_id) = fields.required::<String>("device_id") {
+        match DeviceId::from_str(&device_id) {
+            Ok(device_id) => builder = builder.device_id(device_id),
+            Err(_) => fields.issue(
+                "device_id",
+                "invalid_id",
+                format!("device_id {device_id:?} is not a valid id"),
+            ),
+        }
+    }
+    if let Some(entity_id) = fields.required::<String>("entity_id") {
+        builder = builder.entity_id(entity_id);
+    }
+    if let Some(friendly_name) = fields.required::<String>("friendly_name") {
+        builder = builder.friendly_name(friendly_name);
+    }
+    Ok(Json(fields.report(builder.field_errors())))
+}
+
+/// Decodes the fields of a JSON object one at a time, collecting issues.
+struct Fields<'a> {
+    object: &'a Map<String, Value>,
+    issues: Vec<FieldIssue>,
+    /// Fields whose domain errors would only repeat a decoding issue.
+    undecoded: HashSet<String>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(body: &'a Value) -> Result<Self, ApiError> {
+        let object = body.as_object().ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_body",
+                "expected a JSON object",
+            )
+        })?;
+        Ok(Self {
+            object,
+            issues: Vec::new(),
+            undecoded: HashSet::new(),
+        })
+    }
+
+    fn issue(&mut self, field: &str, code: &str, message: String) {
+        self.issues.push(FieldIssue {
+            field: field.to_string(),
+            code: code.to_string(),
+            message,
+        });
+        self.undecoded.insert(field.to_string());
+    }
+
+    fn required<T: DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        if !self.object.contains_key(field) {
+            self.issue(field, "missing_field", format!("missing field `{field}`"));
+            return None;
+        }
+        self.optional(field)
+    }
+
+    fn optional<T: DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        let value = self.object.get(field).filter(|value| !value.is_null())?;
+        self.decode(field, value.clone())
+    }
+
+    fn decode<T: DeserializeOwned>(&mut self, field: &str, value: Value) -> Option<T> {
+        match serde_json::from_value(value) {
+            Ok(decoded) => Some(decoded),
+            Err(err) => {
+                self.issue(field, "invalid_value", err.to_string());
+                None
+            }
+        }
+    }
+
+    /// Decode an array item by item, so one bad item does not hide the
+    /// others.
+    fn list<T: DeserializeOwned>(&mut self, field: &'static str, required: bool) -> List<T> {
+        let mut list = List {
+            items: Vec::new(),
+            index: ListIndex {
+                field,
+                original: Vec::new(),
+            },
+        };
+        let values = match self.object.get(field) {
+            Some(Value::Array(values)) => values,
+            None | Some(Value::Null) if !required => return list,
+            None => {
+                self.issue(field, "missing_field", format!("missing field `{field}`"));
+                return list;
+            }
+            Some(_) => {
+                self.issue(field, "invalid_value", "expected an array".to_string());
+                return list;
+            }
+        };
+        for (index, value) in values.iter().enumerate() {
+            if let Some(item) = self.decode(&format!("{field}[{index}]"), value.clone()) {
+                list.items.push(item);
+                list.index.original.push(index);
+            }
+        }
+        if list.items.len() < values.len() {
+            self.undecoded.insert(field.to_string());
+        }
+        list
+    }
+
+    fn report(self, errors: impl IntoIterator<Item = FieldError>) -> ValidationReport {
+        let mut issues = self.issues;
+        issues.extend(
+            errors
+                .into_iter()
+                .filter(|err| !self.undecoded.contains(&err.field))
+                .map(|err| FieldIssue {
+                    code: validation_code(&err.error).to_string(),
+                    message: err.error.to_string(),
+                    field: err.field,
+                }),
+        );
+        ValidationReport {
+            valid: issues.is_empty(),
+            errors: issues,
+        }
+    }
+}
+
+/// Items decoded from an array, with their position in the payload.
+struct List<T> {
+    items: Vec<T>,
+    index: ListIndex,
+}
+
+struct ListIndex {
+    field: &'static str,
+    original: Vec<usize>,
+}
+
+impl ListIndex {
+    /// Point a domain error about the n-th decoded item back at its
+    /// position in the payload, since undecodable items were skipped.
+    fn reindex(&self, mut err: FieldError) -> FieldError {
+        let position = err
+            .field
+            .strip_prefix(self.field)
+            .and_then(|rest| rest.strip_prefix('['))
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|index| index.parse::<usize>().ok());
+        if let Some(original) = position.and_then(|index| self.original.get(index)) {
+            err.field = format!("{}[{original}]", self.field);
+        }
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::post;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn validate(uri: &str, body: &Value) -> (StatusCode, Value) {
+        let app = Router::new()
+            .route("/automations/validate", post(automation))
+            .route("/entities/validate", post(entity));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn fields(report: &Value) -> Vec<(&str, &str)> {
+        report["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|issue| {
+                (
+                    issue["field"].as_str().unwrap(),
+                    issue["code"].as_str().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_report_valid_when_automation_is_accepted() {
+        let (status, report) = validate(
+            "/automations/validate",
+            &json!({
+                "name": "Evening",
+                "trigger": { "type": "manual" },
+                "actions": [{ "type": "delay", "seconds": 5 }],
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report, json!({ "valid": true, "errors": [] }));
+    }
+
+    #[tokio::test]
+    async fn should_report_every_invalid_automation_field() {
+        let (status, report) = validate(
+            "/automations/validate",
+            &json!({
+                "name": "",
+                "trigger": { "type": "time_pattern", "cron": "not a cron" },
+                "conditions": [
+                    { "type": "nope" },
+                    { "type": "expression", "expr": "time.hour >=" },
+                ],
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["valid"], false);
+        assert_eq!(
+            fields(&report),
+            [
+                ("conditions[0]", "invalid_value"),
+                ("actions", "missing_field"),
+                ("name", "empty_name"),
+                ("trigger", "invalid_cron"),
+                ("conditions[1]", "invalid_expression"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_every_invalid_entity_field() {
+        let (status, report) = validate(
+            "/entities/validate",
+            &json!({ "device_id": "nope", "friendly_name": "" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            fields(&report),
+            [
+                ("device_id", "invalid_id"),
+                ("entity_id", "missing_field"),
+                ("friendly_name", "empty_friendly_name"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_body_that_is_not_an_object() {
+        let (status, report) = validate("/entities/validate", &json!([])).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(report["error"]["code"], "invalid_body");
+    }
+}
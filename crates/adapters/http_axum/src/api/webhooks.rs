@@ -0,0 +1,86 @@
+//! JSON REST handler for inbound webhooks.
+
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use minihub_domain::id::EventId;
+
+use crate::error::ApiError;
+use crate::state::{AppState, Ports};
+
+/// Largest accepted webhook body, in bytes; larger ones fail with `413`.
+///
+/// Webhook calls need no token, so their bodies are bounded well below the
+/// limit of the other API requests.
+pub const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Body of an accepted webhook call.
+#[derive(Serialize)]
+pub struct WebhookAccepted {
+    /// Id of the published `webhook_received` event, to follow the
+    /// automations it triggered through its causal chain.
+    pub event_id: EventId,
+}
+
+/// Possible responses from the webhook endpoint.
+pub enum ReceiveResponse {
+    /// 202 Accepted; matching automations run asynchronously.
+    Accepted(Json<WebhookAccepted>),
+}
+
+impl IntoResponse for ReceiveResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Accepted(json) => (StatusCode::ACCEPTED, json).into_response(),
+        }
+    }
+}
+
+/// The request body as carried by the event: `null` when empty, the parsed
+/// document when JSON, the text otherwise.
+fn body_value(body: &[u8]) -> serde_json::Value {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return serde_json::Value::Null;
+    }
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(body).into_owned()))
+}
+
+/// `POST /api/webhook/:webhook_id` — publish a `webhook_received` event
+/// carrying the request body, running the automations with a matching
+/// webhook trigger. Answers `404` when no enabled automation listens on
+/// `webhook_id`.
+pub async fn receive<P: Ports>(
+    State(state): State<AppState<P>>,
+    Path(webhook_id): Path<String>,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<ReceiveResponse, ApiError> {
+    let event = state
+        .webhook_service()
+        .receive(&webhook_id, body_value(&body?))
+        .await?;
+    Ok(ReceiveResponse::Accepted(Json(WebhookAccepted {
+        event_id: event.id,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_json_body_and_keep_other_bodies_as_text() {
+        assert_eq!(body_value(b""), serde_json::Value::Null);
+        assert_eq!(body_value(b" \n"), serde_json::Value::Null);
+        assert_eq!(
+            body_value(br#"{"button": 1}"#),
+            serde_json::json!({"button": 1})
+        );
+        assert_eq!(body_value(b"ring=1"), serde_json::json!("ring=1"));
+    }
+}
@@ -0,0 +1,369 @@
+//! Role-based authorization of API requests with bearer tokens.
+//!
+//! Every API route requires a [`Role`]: reading needs `viewer`, operating
+//! devices — calling services, updating states, activating scenes or
+//! switching the home mode — needs `controller`, and every other write
+//! needs `admin`. Requests present a token in an
+//! `Authorization: Bearer <token>` header — or, on the event stream that
+//! browsers open without custom headers, an `access_token` query
+//! parameter — and run with the role of that token; their writes are attributed in the audit log to the token along
+//! with the role that allowed them.
+//!
+//! Without any configured token, authorization is disabled and every
+//! request is served, as before tokens existed.
+
+use std::sync::Arc;
+
+use axum::extract::{FromRequestParts, MatchedPath, Query, Request, State};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::json;
+
+use minihub_app::audit::act_as;
+use minihub_domain::audit::Actor;
+use minihub_domain::role::Role;
+use minihub_domain::secret::constant_time_eq;
+
+use crate::error::ApiError;
+
+/// Routes anyone may call: webhooks are authenticated by their id, which
+/// [`webhook::validate_id`](minihub_domain::webhook::validate_id) requires
+/// to be hard to guess.
+const PUBLIC_ROUTES: &[(&str, &str)] = &[("POST", "/webhook/{webhook_id}")];
+
+/// Routes also taking their token as an `access_token` query parameter,
+/// as a browser `EventSource` cannot send an `Authorization` header.
+const QUERY_TOKEN_ROUTES: &[(&str, &str)] = &[("GET", "/events/stream")];
+
+/// Writing routes a `viewer` may call, as they only check a payload.
+const VIEWER_ROUTES: &[(&str, &str)] = &[
+    ("POST", "/entities/validate"),
+    ("POST", "/automations/validate"),
+];
+
+/// Writing routes a `controller` may call, as they operate devices rather
+/// than change the configuration of the hub.
+const CONTROLLER_ROUTES: &[(&str, &str)] = &[
+    ("POST", "/entities/states"),
+    ("PUT", "/entities/{id}/state"),
+    ("POST", "/entities/{id}/service"),
+    ("POST", "/entities/{id}/refresh"),
+    ("POST", "/scenes/{id}/activate"),
+    ("PUT", "/home_mode"),
+];
+
+/// A token granting its [`Role`] to the requests presenting it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiToken {
+    /// Name the token is known by in the audit log, e.g. `"grafana"`.
+    pub name: String,
+    /// Secret value sent as `Authorization: Bearer <token>`.
+    pub token: String,
+    pub role: Role,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiToken")
+            .field("name", &self.name)
+            .field("token", &"***")
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+/// Role a request runs with, set by [`authorize`]: the role of its token,
+/// or the anonymous role, and `admin` while authorization is disabled.
+///
+/// Extracted as `viewer` from requests [`authorize`] did not see, so what
+/// it guards stays hidden by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallerRole(pub Role);
+
+impl CallerRole {
+    /// Whether the caller may read the secrets of the configuration, such
+    /// as webhook ids: only `admin` may.
+    #[must_use]
+    pub fn sees_secrets(self) -> bool {
+        self.0.allows(Role::Admin)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for CallerRole {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<Self>()
+            .copied()
+            .unwrap_or(Self(Role::Viewer)))
+    }
+}
+
+/// Tokens accepted by the API, and the role of requests without one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessControl {
+    tokens: Vec<ApiToken>,
+    anonymous_role: Option<Role>,
+}
+
+impl AccessControl {
+    /// Accept `tokens`. Requests without a token are rejected, unless
+    /// [`with_anonymous_role`](Self::with_anonymous_role) grants them a role.
+    #[must_use]
+    pub fn new(tokens: Vec<ApiToken>) -> Self {
+        Self {
+            tokens,
+            anonymous_role: None,
+        }
+    }
+
+    /// Grant `role` to requests without a token, e.g. `viewer` so a
+    /// read-only dashboard keeps working.
+    #[must_use]
+    pub fn with_anonymous_role(mut self, role: Option<Role>) -> Self {
+        self.anonymous_role = role;
+        self
+    }
+
+    /// Whether requests are authorized at all: only once a token exists.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// The token presented by a request in its `headers`, or else as its
+    /// `query_token`; `None` when it presents none.
+    ///
+    /// Every token is compared in constant time, so the time taken does
+    /// not tell how close a guess is.
+    fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        query_token: Option<&str>,
+    ) -> Result<Option<&ApiToken>, ApiError> {
+        let presented = match (headers.get(header::AUTHORIZATION), query_token) {
+            (Some(value), _) => value
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::trim)
+                .ok_or_else(invalid_token)?,
+            (None, Some(token)) => token,
+            (None, None) => return Ok(None),
+        };
+        self.tokens
+            .iter()
+            .fold(None, |found, token| {
+                let matches = constant_time_eq(token.token.as_bytes(), presented.as_bytes());
+                if matches { Some(token) } else { found }
+            })
+            .map(Some)
+            .ok_or_else(invalid_token)
+    }
+}
+
+/// Role required to call `path`, relative to `/api`, with `method`; `None`
+/// for routes anyone may call.
+fn required_role(method: &Method, path: &str) -> Option<Role> {
+    let route = (method.as_str(), path);
+    if PUBLIC_ROUTES.contains(&route) {
+        None
+    } else if matches!(*method, Method::GET | Method::HEAD) || VIEWER_ROUTES.contains(&route) {
+        Some(Role::Viewer)
+    } else if CONTROLLER_ROUTES.contains(&route) {
+        Some(Role::Controller)
+    } else {
+        Some(Role::Admin)
+    }
+}
+
+/// Middleware serving a request only when its token, or the anonymous
+/// role, allows the route it was routed to.
+///
+/// Requests with a token run on behalf of [`Actor::Token`], so their
+/// writes are attributed to the token and the role that allowed them.
+/// Served requests carry their [`CallerRole`].
+pub(crate) async fn authorize(
+    State(access): State<Arc<AccessControl>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !access.is_enabled() {
+        request.extensions_mut().insert(CallerRole(Role::Admin));
+        return next.run(request).await;
+    }
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    let Some(required) = required_role(request.method(), path) else {
+        return next.run(request).await;
+    };
+
+    let query_token = QUERY_TOKEN_ROUTES
+        .contains(&(request.method().as_str(), path))
+        .then(|| Query::<TokenQuery>::try_from_uri(request.uri()).ok())
+        .flatten()
+        .and_then(|Query(query)| query.access_token);
+    let token = match access.authenticate(request.headers(), query_token.as_deref()) {
+        Ok(token) => token,
+        Err(err) => return unauthorized(err),
+    };
+    let Some(role) = token.map(|token| token.role).or(access.anonymous_role) else {
+        return unauthorized(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "missing_token",
+            "this request needs a bearer token",
+        ));
+    };
+    let token_name = token.map(|token| token.name.clone());
+    if !role.allows(required) {
+        tracing::warn!(
+            token = token_name.as_deref().unwrap_or("anonymous"),
+            %role,
+            %required,
+            method = %request.method(),
+            path,
+            "request denied"
+        );
+        return ApiError::new(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            format!("this request needs the {required} role"),
+        )
+        .with_details(json!({ "role": role, "required": required }))
+        .into_response();
+    }
+
+    request.extensions_mut().insert(CallerRole(role));
+    match token_name {
+        Some(name) => act_as(Actor::Token { name, role }, next.run(request)).await,
+        None => next.run(request).await,
+    }
+}
+
+/// Query parameters of the [`QUERY_TOKEN_ROUTES`].
+#[derive(Deserialize)]
+struct TokenQuery {
+    access_token: Option<String>,
+}
+
+fn invalid_token() -> ApiError {
+    ApiError::new(
+        StatusCode::UNAUTHORIZED,
+        "invalid_token",
+        "the bearer token is malformed or unknown",
+    )
+}
+
+/// `401` telling the client to authenticate with a bearer token.
+fn unauthorized(err: ApiError) -> Response {
+    let mut response = err.into_response();
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        header::HeaderValue::from_static("Bearer"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn access() -> AccessControl {
+        AccessControl::new(vec![ApiToken {
+            name: "grafana".to_string(),
+            token: "0123456789abcdef".to_string(),
+            role: Role::Viewer,
+        }])
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn should_require_role_by_route() {
+        assert_eq!(
+            required_role(&Method::GET, "/automations"),
+            Some(Role::Viewer)
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/automations/validate"),
+            Some(Role::Viewer)
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/entities/{id}/service"),
+            Some(Role::Controller)
+        );
+        assert_eq!(
+            required_role(&Method::DELETE, "/devices/{id}"),
+            Some(Role::Admin)
+        );
+        assert_eq!(required_role(&Method::POST, "/webhook/{webhook_id}"), None);
+    }
+
+    #[test]
+    fn should_find_presented_token() {
+        let access = access();
+
+        let token = access
+            .authenticate(&bearer("0123456789abcdef"), None)
+            .unwrap();
+        let queried = access
+            .authenticate(&HeaderMap::new(), Some("0123456789abcdef"))
+            .unwrap();
+
+        assert_eq!(token.map(|token| token.name.as_str()), Some("grafana"));
+        assert_eq!(queried.map(|token| token.name.as_str()), Some("grafana"));
+        assert!(
+            access
+                .authenticate(&HeaderMap::new(), None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_or_malformed_token() {
+        let access = access();
+        let mut basic = HeaderMap::new();
+        basic.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic dXNlcjpwYXNz"),
+        );
+
+        assert!(
+            access
+                .authenticate(&bearer("0123456789abcdeX"), None)
+                .is_err()
+        );
+        assert!(access.authenticate(&basic, None).is_err());
+        assert!(
+            access
+                .authenticate(&HeaderMap::new(), Some("0123456789abcdeX"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn should_redact_token_when_debug_printed() {
+        let printed = format!("{:?}", access());
+
+        assert!(printed.contains("grafana"));
+        assert!(!printed.contains("0123456789abcdef"));
+    }
+}
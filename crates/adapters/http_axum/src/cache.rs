@@ -0,0 +1,151 @@
+//! HTTP caching — `ETag` validators for JSON responses and `Cache-Control`
+//! for the dashboard assets.
+
+use std::hash::{DefaultHasher, Hasher};
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+/// `Cache-Control` of assets whose file name carries a content hash.
+const IMMUTABLE: HeaderValue = HeaderValue::from_static("public, max-age=31536000, immutable");
+
+/// `Cache-Control` of responses that may change at any time: stored, but
+/// revalidated before every use.
+const REVALIDATE: HeaderValue = HeaderValue::from_static("no-cache");
+
+/// Middleware tagging successful JSON `GET` responses with a weak `ETag`
+/// computed from their body, and answering `304 Not Modified` when the
+/// request's `If-None-Match` names it.
+///
+/// Streams (SSE, NDJSON exports) are left untouched. The tag is weak since
+/// the body may be compressed on the way out.
+pub async fn etag(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::OK || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!(%err, "failed to buffer response body");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let tag = weak_etag(&bytes);
+    let Ok(value) = HeaderValue::from_str(&tag) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    parts.headers.insert(header::ETAG, value);
+    parts.headers.insert(header::CACHE_CONTROL, REVALIDATE);
+
+    if if_none_match
+        .as_ref()
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| matches_if_none_match(value, &tag))
+    {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_TYPE);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::empty());
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Middleware setting the `Cache-Control` of dashboard assets: cached for a
+/// year when their name carries a content hash, so a new build is fetched
+/// under a new name, revalidated otherwise.
+pub async fn asset_cache_control(request: Request, next: Next) -> Response {
+    let immutable = is_hashed_asset(request.uri().path());
+    let mut response = next.run(request).await;
+    if response.status().is_success() {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            if immutable { IMMUTABLE } else { REVALIDATE },
+        );
+    }
+    response
+}
+
+/// Weak `ETag` of a body.
+fn weak_etag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(body);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Whether an `If-None-Match` header value names `etag`, comparing weakly.
+fn matches_if_none_match(header: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    header
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+/// Whether the file at `path` is named after its content, as trunk does:
+/// `<name>-<16 hex digits>.<ext>`, the WebAssembly module adding `_bg`.
+fn is_hashed_asset(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    let Some((stem, _extension)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    let stem = stem.strip_suffix("_bg").unwrap_or(stem);
+    stem.rsplit_once('-').is_some_and(|(name, hash)| {
+        !name.is_empty() && hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_recognize_trunk_hashed_assets() {
+        assert!(is_hashed_asset("/minihub-dashboard-4c1d2b7e9a0f3c5d.js"));
+        assert!(is_hashed_asset(
+            "/minihub-dashboard-4c1d2b7e9a0f3c5d_bg.wasm"
+        ));
+        assert!(is_hashed_asset("/style-0123456789abcdef.css"));
+    }
+
+    #[test]
+    fn should_not_recognize_unhashed_assets() {
+        assert!(!is_hashed_asset("/"));
+        assert!(!is_hashed_asset("/index.html"));
+        assert!(!is_hashed_asset("/style.css"));
+        assert!(!is_hashed_asset("/minihub-dashboard.js"));
+        assert!(!is_hashed_asset("/devices/0123456789abcdef"));
+    }
+
+    #[test]
+    fn should_derive_same_etag_from_same_body() {
+        assert_eq!(weak_etag(b"[1,2]"), weak_etag(b"[1,2]"));
+        assert_ne!(weak_etag(b"[1,2]"), weak_etag(b"[1,3]"));
+        assert!(weak_etag(b"[]").starts_with("W/\""));
+    }
+
+    #[test]
+    fn should_match_if_none_match_weakly_and_in_lists() {
+        let etag = weak_etag(b"[]");
+        let opaque = etag.trim_start_matches("W/");
+
+        assert!(matches_if_none_match(&etag, &etag));
+        assert!(matches_if_none_match(opaque, &etag));
+        assert!(matches_if_none_match(&format!("\"other\", {etag}"), &etag));
+        assert!(matches_if_none_match("*", &etag));
+        assert!(!matches_if_none_match("\"other\"", &etag));
+    }
+}
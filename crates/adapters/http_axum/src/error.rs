@@ -1,41 +1,336 @@
 //! HTTP error response mapping.
+//!
+//! Every failing endpoint answers with the same JSON envelope:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "code": "entity_not_found",
+//!     "message": "Entity 7b0c… not found",
+//!     "details": { "id": "7b0c…" }
+//!   }
+//! }
+//! ```
+//!
+//! `code` is stable and meant for programs, `message` is meant for humans and
+//! may change. `details` is only present when the error carries structured
+//! context.
 
 use axum::Json;
+use axum::extract::rejection::{BytesRejection, JsonRejection, QueryRejection};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
+use serde_json::{Value, json};
 
-use minihub_domain::error::MiniHubError;
+use minihub_app::backup::BackupError;
+use minihub_app::config_reload::ReloadError;
+use minihub_domain::error::{MiniHubError, ValidationError};
+
+/// JSON envelope wrapping [`ErrorBody`].
+#[derive(Serialize)]
+struct ErrorEnvelope {
+    error: ErrorBody,
+}
 
 /// JSON error body returned by API endpoints.
 #[derive(Serialize)]
 struct ErrorBody {
-    error: String,
+    code: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
 }
 
-/// Maps [`MiniHubError`] to an HTTP response with appropriate status code.
-pub struct ApiError(MiniHubError);
+/// An HTTP error response with a machine-readable code.
+///
+/// Built from [`MiniHubError`] for domain failures, or with the dedicated
+/// constructors for failures detected by the HTTP layer itself.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: String,
+    message: String,
+    details: Option<Value>,
+}
 
-impl From<MiniHubError> for ApiError {
-    fn from(err: MiniHubError) -> Self {
-        Self(err)
+impl ApiError {
+    /// Create an error answered with `status`, `code` and `message`.
+    pub fn new(status: StatusCode, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.into(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Attach structured context to the error.
+    #[must_use]
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// A path or body identifier that is not a valid UUID.
+    pub fn invalid_id(field: &str, value: &str) -> Self {
+        Self::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_id",
+            format!("{field} {value:?} is not a valid id"),
+        )
+        .with_details(json!({ "field": field, "value": value }))
+    }
+
+    /// HTTP status of the response.
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Machine-readable error code, e.g. `"entity_not_found"`.
+    #[must_use]
+    pub fn code(&self) -> &str {
+        &self.code
     }
 }
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self.0 {
-            MiniHubError::Validation(err) => (StatusCode::BAD_REQUEST, err.to_string()),
-            MiniHubError::NotFound(err) => (StatusCode::NOT_FOUND, err.to_string()),
+/// Machine-readable code of a validation failure.
+pub(crate) fn validation_code(err: &ValidationError) -> &'static str {
+    match err {
+        ValidationError::EmptyEntityId => "empty_entity_id",
+        ValidationError::EmptyFriendlyName => "empty_friendly_name",
+        ValidationError::EmptyName => "empty_name",
+        ValidationError::EmptyIntegration => "empty_integration",
+        ValidationError::EmptyUniqueId => "empty_unique_id",
+        ValidationError::SelfViaDevice => "self_via_device",
+        ValidationError::NoActions => "no_actions",
+        ValidationError::EmptyMessage => "empty_message",
+        ValidationError::InvalidEventPayload { .. } => "invalid_event_payload",
+        ValidationError::NoSceneMembers => "no_scene_members",
+        ValidationError::UnsupportedSceneState(_) => "unsupported_scene_state",
+        ValidationError::NoGroupMembers => "no_group_members",
+        ValidationError::InvalidGroupMember(_) => "invalid_group_member",
+        ValidationError::AttributeOutOfRange { .. } => "attribute_out_of_range",
+        ValidationError::InvalidTimestamp(_) => "invalid_timestamp",
+        ValidationError::InvalidPatch(_) => "invalid_patch",
+        ValidationError::UnknownHomeMode(_) => "unknown_home_mode",
+        ValidationError::UnknownExecutionMode(_) => "unknown_execution_mode",
+        ValidationError::InvalidInputHelper(_) => "invalid_input_helper",
+        ValidationError::InvalidInputValue(_) => "invalid_input_value",
+        ValidationError::InvalidEntityId(_) => "invalid_entity_id",
+        ValidationError::DuplicateEntityId(_) => "duplicate_entity_id",
+        ValidationError::UnknownActor(_) => "unknown_actor",
+        ValidationError::UnknownRole(_) => "unknown_role",
+        ValidationError::InvalidStateForDomain { .. } => "invalid_state_for_domain",
+        ValidationError::InvalidWebhookId(_) => "invalid_webhook_id",
+        ValidationError::InvalidLocation(_) => "invalid_location",
+        ValidationError::InvalidSunOffset(_) => "invalid_sun_offset",
+        ValidationError::InvalidServiceData { .. } => "invalid_service_data",
+        ValidationError::InvalidCron(_) => "invalid_cron",
+        ValidationError::InvalidExpression(_) => "invalid_expression",
+        ValidationError::InvalidRepeatCount(_) => "invalid_repeat_count",
+        ValidationError::ActionsTooDeep(_) => "actions_too_deep",
+        ValidationError::InvalidDashboard(_) => "invalid_dashboard",
+        ValidationError::InvalidSettingsName(_) => "invalid_settings_name",
+        ValidationError::ReservedSettingsNamespace(_) => "reserved_settings_namespace",
+        ValidationError::ReadOnlyAutomation(_) => "read_only_automation",
+        ValidationError::InvalidSecretName(_) => "invalid_secret_name",
+        ValidationError::EmptySecretValue => "empty_secret_value",
+    }
+}
+
+/// `snake_case` form of a resource name, e.g. `"BLE peripheral"` →
+/// `"ble_peripheral"`.
+fn resource_code(resource: &str) -> String {
+    resource.to_lowercase().replace(' ', "_")
+}
+
+impl From<MiniHubError> for ApiError {
+    fn from(err: MiniHubError) -> Self {
+        match err {
+            MiniHubError::Validation(err) => {
+                let api_err = Self::new(
+                    StatusCode::BAD_REQUEST,
+                    validation_code(&err),
+                    err.to_string(),
+                );
+                match err {
+                    ValidationError::AttributeOutOfRange { key, value } => {
+                        api_err.with_details(json!({ "key": key, "value": value }))
+                    }
+                    ValidationError::InvalidServiceData { service, errors } => {
+                        api_err.with_details(json!({ "service": service, "errors": errors }))
+                    }
+                    _ => api_err,
+                }
+            }
+            MiniHubError::NotFound(err) => Self::new(
+                StatusCode::NOT_FOUND,
+                format!("{}_not_found", resource_code(err.entity)),
+                err.to_string(),
+            )
+            .with_details(json!({ "id": err.id })),
+            MiniHubError::Conflict(err) => Self::new(
+                StatusCode::CONFLICT,
+                format!("{}_conflict", resource_code(err.entity)),
+                err.to_string(),
+            )
+            .with_details(json!({
+                "id": err.id,
+                "expected": err.expected,
+                "actual": err.actual,
+            })),
             MiniHubError::Storage(err) => {
                 tracing::error!(error = ?err, "storage error");
-                (
+                Self::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "internal server error".to_string(),
+                    "internal_error",
+                    "internal server error",
                 )
             }
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        let code = if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            "payload_too_large"
+        } else {
+            "invalid_body"
+        };
+        Self::new(rejection.status(), code, rejection.body_text())
+    }
+}
+
+impl From<BytesRejection> for ApiError {
+    fn from(rejection: BytesRejection) -> Self {
+        let code = if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            "payload_too_large"
+        } else {
+            "invalid_body"
+        };
+        Self::new(rejection.status(), code, rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), "invalid_query", rejection.body_text())
+    }
+}
+
+impl From<ReloadError> for ApiError {
+    fn from(err: ReloadError) -> Self {
+        let (status, code) = match err {
+            ReloadError::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, "reload_unavailable"),
+            ReloadError::InvalidConfig(_) => (StatusCode::BAD_REQUEST, "invalid_config"),
+        };
+        Self::new(status, code, err.to_string())
+    }
+}
+
+impl From<BackupError> for ApiError {
+    fn from(err: BackupError) -> Self {
+        let (status, code) = match err {
+            BackupError::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, "backup_unavailable"),
+            BackupError::Failed(_) => (StatusCode::INTERNAL_SERVER_ERROR, "backup_failed"),
+        };
+        Self::new(status, code, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code,
+                message: self.message,
+                details: self.details,
+            },
         };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::error::{ConflictError, NotFoundError};
+
+    use super::*;
+
+    async fn body_of(err: ApiError) -> (StatusCode, Value) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_wrap_not_found_in_error_envelope() {
+        let err = ApiError::from(MiniHubError::NotFound(NotFoundError {
+            entity: "Entity",
+            id: "abc".to_string(),
+        }));
+
+        let (status, body) = body_of(err).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            json!({
+                "error": {
+                    "code": "entity_not_found",
+                    "message": "Entity abc not found",
+                    "details": { "id": "abc" },
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_use_validation_variant_as_code() {
+        let err = ApiError::from(MiniHubError::Validation(ValidationError::NoActions));
+
+        let (status, body) = body_of(err).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "no_actions");
+        assert!(body["error"].get("details").is_none());
+    }
+
+    #[tokio::test]
+    async fn should_report_expected_and_actual_on_conflict() {
+        let err = ApiError::from(MiniHubError::Conflict(ConflictError {
+            entity: "Automation",
+            id: "abc".to_string(),
+            expected: "version 1".to_string(),
+            actual: "version 2".to_string(),
+        }));
+
+        let (status, body) = body_of(err).await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "automation_conflict");
+        assert_eq!(body["error"]["details"]["actual"], "version 2");
+    }
+
+    #[tokio::test]
+    async fn should_hide_storage_error_details() {
+        let err = ApiError::from(MiniHubError::Storage(anyhow::anyhow!("disk on fire")));
+
+        let (status, body) = body_of(err).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["code"], "internal_error");
+        assert_eq!(body["error"]["message"], "internal server error");
+    }
 
-        (status, Json(ErrorBody { error: message })).into_response()
+    #[test]
+    fn should_snake_case_multi_word_resources() {
+        assert_eq!(resource_code("BLE peripheral"), "ble_peripheral");
     }
 }
@@ -0,0 +1,102 @@
+//! Health probes — `GET /health/live` and `GET /health/ready`.
+//!
+//! Both answer with the JSON [`HealthBody`]; readiness answers `503` when a
+//! required component is down, so load balancers and orchestrators can act
+//! on the status code alone.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use minihub_app::services::health_service::{ComponentHealth, HealthReport, HealthService};
+
+use crate::state::{AppState, Ports};
+
+/// Health of one component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentBody {
+    /// Component name, e.g. `"database"` or `"integration.mqtt"`.
+    pub name: String,
+    /// `up`, `degraded` or `down`.
+    pub status: &'static str,
+    /// Why the component is not up, or what was measured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl From<ComponentHealth> for ComponentBody {
+    fn from(component: ComponentHealth) -> Self {
+        Self {
+            name: component.name,
+            status: component.status.as_str(),
+            detail: component.detail,
+        }
+    }
+}
+
+/// Overall health and the components it was derived from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthBody {
+    /// Worst status of the components: `up`, `degraded` or `down`.
+    pub status: &'static str,
+    /// Every component checked.
+    pub components: Vec<ComponentBody>,
+}
+
+impl From<HealthReport> for HealthBody {
+    fn from(report: HealthReport) -> Self {
+        Self {
+            status: report.status.as_str(),
+            components: report.components.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Possible responses from the health probes.
+pub enum HealthResponse {
+    /// No required component is down.
+    Healthy(Json<HealthBody>),
+    /// A required component is down.
+    Unhealthy(Json<HealthBody>),
+}
+
+impl From<HealthReport> for HealthResponse {
+    fn from(report: HealthReport) -> Self {
+        if report.is_healthy() {
+            Self::Healthy(Json(report.into()))
+        } else {
+            Self::Unhealthy(Json(report.into()))
+        }
+    }
+}
+
+impl IntoResponse for HealthResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Healthy(json) => json.into_response(),
+            Self::Unhealthy(json) => (StatusCode::SERVICE_UNAVAILABLE, json).into_response(),
+        }
+    }
+}
+
+/// Health service over the components shared in `state`; the event store
+/// doubles as the storage handle.
+fn service<P: Ports>(state: &AppState<P>) -> HealthService<Arc<P::EventStore>> {
+    HealthService::new(Arc::clone(&state.event_store), Arc::clone(&state.event_bus))
+        .with_integrations(state.integrations.clone())
+}
+
+/// `GET /health/live` — the process is running and answering.
+pub async fn live<P: Ports>(State(state): State<AppState<P>>) -> HealthResponse {
+    service(&state).live().into()
+}
+
+/// `GET /health/ready` — the storage, the event bus and the integrations,
+/// checked now.
+pub async fn ready<P: Ports>(State(state): State<AppState<P>>) -> HealthResponse {
+    service(&state).ready().await.into()
+}
@@ -5,16 +5,40 @@
 //! ## Responsibilities
 //! - Serve a **REST-ish JSON API** for programmatic access
 //!   (`/api/entities`, `/api/devices`, `/api/areas`, …)
+//! - Describe the API as an **OpenAPI 3.1** document (`/api/openapi.json`)
 //! - Serve **static assets** (the Leptos WASM dashboard) at `/`
 //! - Map HTTP requests into application service calls (driving adapter)
-//! - Map application results into HTTP responses (JSON)
+//! - Map application results into HTTP responses (JSON), and errors into a
+//!   `{ "error": { "code", "message", "details" } }` envelope
+//! - Tag every request with an `x-request-id` header for log correlation
+//! - Bound API requests in time, size and concurrency, answering `408`,
+//!   `413` or `429` instead of tying up the hub
+//! - Attribute API writes to the dashboard or to an API client in the
+//!   audit log
+//! - Authorize API requests by the **role** of their bearer token, when
+//!   tokens are configured
+//! - Compress responses, and tag JSON responses with an `ETag` for
+//!   conditional requests
+//! - Expose **Prometheus metrics** (`/metrics`) and record request counts
+//!   and latencies
+//! - Answer **liveness and readiness probes** (`/health/live`,
+//!   `/health/ready`) with per-component status
 //!
 //! ## Dependency rule
 //! Depends on `minihub-app` (for port traits and services) and `minihub-domain`
 //! (for domain types used in request/response mapping). Never leaks axum types
 //! into the domain.
 
+mod actor;
 pub mod api;
+pub mod auth;
+mod cache;
 mod error;
+mod extract;
+pub mod health;
+pub mod limits;
+pub mod metrics;
+pub mod openapi;
+mod redact;
 pub mod router;
 pub mod state;
@@ -0,0 +1,414 @@
+//! Request limits protecting the hub from slow clients and request floods.
+//!
+//! Every API request is bounded by [`RequestLimits`]: requests beyond the
+//! concurrency limit are rejected right away with `429`, bodies declaring
+//! more than the size limit with `413`, and handlers still running after
+//! the timeout are dropped with `408`. All three answer with the standard
+//! error envelope.
+//!
+//! The public webhook route is bounded apart from the other API requests,
+//! so anonymous callers cannot take their permits, and is throttled by a
+//! [`RateLimit`] answering `429` once its budget is spent. Each webhook id
+//! spends its own budget, so flooding one webhook does not block the others.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::error::ApiError;
+
+/// Bounds applied to every API request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// How long a handler may run before the request fails with `408`.
+    pub timeout: Duration,
+    /// How many requests are served at once; the others fail with `429`.
+    pub max_concurrent_requests: usize,
+    /// Largest accepted request body, in bytes; larger ones fail with `413`.
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_concurrent_requests: 64,
+            max_body_bytes: 2 * 1024 * 1024,
+        }
+    }
+}
+
+/// [`RequestLimits`] with the permits of the requests in flight.
+#[derive(Debug, Clone)]
+pub(crate) struct Limiter {
+    limits: RequestLimits,
+    permits: Arc<Semaphore>,
+}
+
+impl Limiter {
+    pub(crate) fn new(limits: RequestLimits) -> Self {
+        Self {
+            limits,
+            permits: Arc::new(Semaphore::new(limits.max_concurrent_requests)),
+        }
+    }
+}
+
+/// Middleware enforcing the [`RequestLimits`] of `limiter`.
+///
+/// The concurrency permit is held until the handler returns its response,
+/// so streaming responses such as SSE do not count against the limit once
+/// their headers are sent. Bodies sent without a `Content-Length` are
+/// checked by the extractors, which stop reading at the same size.
+pub(crate) async fn enforce(
+    State(limiter): State<Limiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = limiter.permits.try_acquire() else {
+        return too_many_requests(limiter.limits.max_concurrent_requests);
+    };
+
+    let declared_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if let Some(length) = declared_length
+        && length > limiter.limits.max_body_bytes
+    {
+        return payload_too_large(limiter.limits.max_body_bytes);
+    }
+
+    match tokio::time::timeout(limiter.limits.timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => request_timeout(limiter.limits.timeout),
+    }
+}
+
+/// How many requests a route serves over a period; the others fail with
+/// `429` until the budget refills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests served over `period`, all at once when idle long enough.
+    pub max_requests: u32,
+    /// Time over which the whole budget refills, evenly.
+    pub period: Duration,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_requests: 60,
+            period: Duration::from_mins(1),
+        }
+    }
+}
+
+/// Token buckets spending a [`RateLimit`] budget for each request path of
+/// the routes they guard.
+///
+/// A bucket that refilled up to the whole budget is the same as a new one,
+/// so such buckets are dropped whenever another path gets one, keeping the
+/// map bounded by the paths called within a refill.
+#[derive(Debug, Clone)]
+pub(crate) struct Throttle {
+    limit: RateLimit,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    /// Add the tokens refilled since the last refill, up to `capacity`.
+    fn refill(&mut self, now: Instant, capacity: f64, per_second: f64) {
+        let refilled = now.duration_since(self.refilled_at).as_secs_f64() * per_second;
+        self.tokens = (self.tokens + refilled).min(capacity);
+        self.refilled_at = now;
+    }
+}
+
+impl Throttle {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Spend one request of the budget of `path`, or tell how long until
+    /// one is available again.
+    fn try_acquire(&self, path: &str) -> Result<(), Duration> {
+        let capacity = f64::from(self.limit.max_requests);
+        let per_second = capacity / self.limit.period.as_secs_f64();
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if !buckets.contains_key(path) {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now, capacity, per_second);
+                bucket.tokens < capacity
+            });
+        }
+        let bucket = buckets.entry(path.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        bucket.refill(now, capacity, per_second);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+/// Middleware serving requests while the [`RateLimit`] of `throttle` has
+/// budget left for their path.
+pub(crate) async fn throttle(
+    State(throttle): State<Throttle>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match throttle.try_acquire(request.uri().path()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => rate_limited(throttle.limit, wait),
+    }
+}
+
+/// `429` answered when the [`RateLimit`] budget is spent, retried once
+/// `wait` elapsed.
+fn rate_limited(limit: RateLimit, wait: Duration) -> Response {
+    let mut response = ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        "too many requests, retry later",
+    )
+    .with_details(json!({
+        "max_requests": limit.max_requests,
+        "period_secs": limit.period.as_secs(),
+    }))
+    .into_response();
+    let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+    response
+}
+
+/// `429` answered when every concurrency permit is taken.
+fn too_many_requests(limit: usize) -> Response {
+    let mut response = ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "too_many_requests",
+        "too many requests in flight, retry shortly",
+    )
+    .with_details(json!({ "max_concurrent_requests": limit }))
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, header::HeaderValue::from_static("1"));
+    response
+}
+
+/// `413` answered for a body larger than `limit` bytes.
+fn payload_too_large(limit: usize) -> Response {
+    ApiError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload_too_large",
+        format!("request body exceeds {limit} bytes"),
+    )
+    .with_details(json!({ "max_body_bytes": limit }))
+    .into_response()
+}
+
+/// `408` answered when the handler did not finish within `timeout`.
+fn request_timeout(timeout: Duration) -> Response {
+    ApiError::new(
+        StatusCode::REQUEST_TIMEOUT,
+        "request_timeout",
+        format!("request did not complete within {}s", timeout.as_secs()),
+    )
+    .with_details(json!({ "timeout_secs": timeout.as_secs() }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::middleware;
+    use axum::routing::{get, post};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(limits: RequestLimits) -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "late"
+                }),
+            )
+            .route("/echo", post(|body: String| async move { body }))
+            .layer(middleware::from_fn_with_state(
+                Limiter::new(limits),
+                enforce,
+            ))
+    }
+
+    async fn error_code(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["error"]["code"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn should_pass_requests_within_limits() {
+        let response = app(RequestLimits::default())
+            .oneshot(Request::get("/ok").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_answer_408_when_handler_exceeds_timeout() {
+        let limits = RequestLimits {
+            timeout: Duration::from_millis(20),
+            ..RequestLimits::default()
+        };
+        let response = app(limits)
+            .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(error_code(response).await, "request_timeout");
+    }
+
+    #[tokio::test]
+    async fn should_answer_413_when_declared_body_exceeds_limit() {
+        let limits = RequestLimits {
+            max_body_bytes: 4,
+            ..RequestLimits::default()
+        };
+        let response = app(limits)
+            .oneshot(
+                Request::post("/echo")
+                    .header(header::CONTENT_LENGTH, "5")
+                    .body(Body::from("hello"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_code(response).await, "payload_too_large");
+    }
+
+    #[tokio::test]
+    async fn should_answer_429_when_no_permit_is_left() {
+        let limits = RequestLimits {
+            max_concurrent_requests: 1,
+            ..RequestLimits::default()
+        };
+        let limiter = Limiter::new(limits);
+        let _busy = limiter.permits.clone().try_acquire_owned().unwrap();
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(limiter, enforce));
+
+        let response = app
+            .oneshot(Request::get("/ok").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        assert_eq!(error_code(response).await, "too_many_requests");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_answer_429_once_rate_limit_is_spent() {
+        let limit = RateLimit {
+            max_requests: 2,
+            period: Duration::from_secs(10),
+        };
+        let app = Router::new().route("/ok", get(|| async { "ok" })).layer(
+            middleware::from_fn_with_state(Throttle::new(limit), throttle),
+        );
+        let call = || {
+            app.clone()
+                .oneshot(Request::get("/ok").body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(call().await.unwrap().status(), StatusCode::OK);
+        assert_eq!(call().await.unwrap().status(), StatusCode::OK);
+        let limited = call().await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "5");
+        assert_eq!(error_code(limited).await, "rate_limited");
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(call().await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            call().await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_spend_rate_limit_of_each_path_apart() {
+        let limit = RateLimit {
+            max_requests: 1,
+            period: Duration::from_secs(10),
+        };
+        let app = Router::new()
+            .route("/hook/{id}", post(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                Throttle::new(limit),
+                throttle,
+            ));
+        let call = |path: &'static str| {
+            app.clone()
+                .oneshot(Request::post(path).body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(call("/hook/a").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            call("/hook/a").await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(call("/hook/b").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_drop_buckets_once_refilled() {
+        let throttle = Throttle::new(RateLimit {
+            max_requests: 1,
+            period: Duration::from_secs(10),
+        });
+
+        throttle.try_acquire("/hook/a").unwrap();
+        tokio::time::advance(Duration::from_secs(10)).await;
+        throttle.try_acquire("/hook/b").unwrap();
+
+        let buckets = throttle.buckets.lock().unwrap();
+        assert_eq!(buckets.keys().collect::<Vec<_>>(), ["/hook/b"]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Prometheus metrics — `GET /metrics` and the request-recording
+//! middleware.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use minihub_app::metrics::{
+    ENTITIES, EVENT_BUS_QUEUED, EVENT_BUS_SUBSCRIBERS, HTTP_REQUEST_DURATION, HTTP_REQUESTS,
+    Metrics,
+};
+
+use crate::error::ApiError;
+use crate::state::{AppState, Ports};
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Possible responses from the metrics endpoint.
+pub enum MetricsResponse {
+    Ok(String),
+}
+
+impl IntoResponse for MetricsResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Ok(body) => ([(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response(),
+        }
+    }
+}
+
+/// `GET /metrics` — every metric in the Prometheus text format.
+///
+/// Gauges derived from stored data are sampled on every scrape.
+///
+/// # Errors
+///
+/// Returns an [`ApiError`] when listing devices or entities fails.
+#[allow(clippy::cast_precision_loss)]
+pub async fn render<P: Ports>(
+    State(state): State<AppState<P>>,
+) -> Result<MetricsResponse, ApiError> {
+    let metrics = &state.metrics;
+    let integrations: HashMap<_, _> = state
+        .device_service
+        .list_devices()
+        .await?
+        .into_iter()
+        .map(|device| (device.id, device.integration))
+        .collect();
+    let mut entities: HashMap<&str, usize> = HashMap::new();
+    for entity in state.entity_service.list_entities().await? {
+        let integration = integrations
+            .get(&entity.device_id)
+            .map_or("unknown", String::as_str);
+        *entities.entry(integration).or_default() += 1;
+    }
+    metrics.reset(&ENTITIES);
+    for (integration, count) in entities {
+        metrics.set(&ENTITIES, &[("integration", integration)], count as f64);
+    }
+    metrics.set(&EVENT_BUS_QUEUED, &[], state.event_bus.queued() as f64);
+    metrics.set(
+        &EVENT_BUS_SUBSCRIBERS,
+        &[],
+        state.event_bus.subscriber_count() as f64,
+    );
+
+    Ok(MetricsResponse::Ok(metrics.render()))
+}
+
+/// Middleware counting requests and their latency by method, matched
+/// route and status.
+///
+/// Routes are labelled with their pattern (`/api/entities/{id}`) so ids do
+/// not explode the number of series; unmatched paths share one label.
+pub async fn record(State(metrics): State<Metrics>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    metrics.increment(
+        &HTTP_REQUESTS,
+        &[
+            ("method", &method),
+            ("route", &route),
+            ("status", status.as_str()),
+        ],
+    );
+    metrics.observe(
+        &HTTP_REQUEST_DURATION,
+        &[("method", &method), ("route", &route)],
+        started.elapsed().as_secs_f64(),
+    );
+    response
+}
@@ -0,0 +1,2409 @@
+//! OpenAPI 3.1 description of the `/api` routes.
+//!
+//! The document is written by hand next to the handlers rather than derived,
+//! so domain types stay free of HTTP concerns. It is served at
+//! `GET /api/openapi.json`; when enabled, a Swagger UI page rendering it is
+//! served at `GET /api/docs`.
+//!
+//! Keep it in sync when adding a route or changing a payload — the tests
+//! below check that every field the API serializes is described.
+
+use axum::Json;
+use axum::response::Html;
+use serde_json::{Value, json};
+
+/// Build the OpenAPI document.
+#[must_use]
+pub fn spec() -> Value {
+    let mut paths = serde_json::Map::new();
+    for group in [
+        entity_paths(),
+        entity_wait_paths(),
+        entity_history_paths(),
+        device_and_area_paths(),
+        event_paths(),
+        event_item_paths(),
+        automation_paths(),
+        automation_schedule_paths(),
+        scene_and_misc_paths(),
+        dashboard_and_settings_paths(),
+        secret_paths(),
+        group_paths(),
+        discovery_paths(),
+        audit_paths(),
+        integration_paths(),
+        config_paths(),
+        system_paths(),
+        energy_paths(),
+        webhook_paths(),
+        validation_paths(),
+    ] {
+        if let Value::Object(group) = group {
+            paths.extend(group);
+        }
+    }
+
+    let mut schemas = serde_json::Map::new();
+    for group in [
+        entity_schemas(),
+        device_and_area_schemas(),
+        automation_schemas(),
+        condition_and_action_schemas(),
+        automation_run_schemas(),
+        automation_schedule_schemas(),
+        misc_schemas(),
+        system_schemas(),
+        energy_schemas(),
+        webhook_schemas(),
+        validation_schemas(),
+        home_mode_and_helper_schemas(),
+        dashboard_and_settings_schemas(),
+        secret_schemas(),
+        discovery_schemas(),
+        audit_schemas(),
+        entity_request_schemas(),
+        request_schemas(),
+    ] {
+        if let Value::Object(group) = group {
+            schemas.extend(group);
+        }
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "minihub",
+            "description": "REST API of the minihub home automation hub.",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [{ "url": "/api" }],
+        "security": [{}, { "bearer": [] }],
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "parameters": parameters(),
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "API token, required once tokens are configured. \
+                        Reading needs the viewer role, operating devices the controller \
+                        role, any other write the admin role.",
+                },
+            },
+            "responses": {
+                "BadRequest": error_response("Malformed id or invalid payload"),
+                "NotFound": error_response("No resource with this id"),
+                "Conflict": error_response("The resource changed since it was read"),
+                "PayloadTooLarge": error_response("The request body is too large"),
+            },
+        },
+    })
+}
+
+/// Path parameters shared by several paths.
+fn parameters() -> Value {
+    json!({
+        "Id": {
+            "name": "id",
+            "in": "path",
+            "required": true,
+            "schema": { "type": "string", "format": "uuid" },
+        },
+        "IntegrationName": {
+            "name": "name",
+            "in": "path",
+            "required": true,
+            "schema": { "type": "string", "examples": ["mqtt"] },
+        },
+        "WebhookId": {
+            "name": "webhook_id",
+            "in": "path",
+            "required": true,
+            "schema": schema_ref("WebhookId"),
+        },
+        "SecretName": {
+            "name": "name",
+            "in": "path",
+            "required": true,
+            "schema": schema_ref("SecretName"),
+        },
+        "SettingsNamespace": {
+            "name": "namespace",
+            "in": "path",
+            "required": true,
+            "schema": schema_ref("SettingsName"),
+        },
+    })
+}
+
+/// `GET /api/openapi.json`
+pub async fn document() -> Json<Value> {
+    Json(spec())
+}
+
+/// `GET /api/docs` — Swagger UI rendering [`document`].
+///
+/// The Swagger UI assets are loaded from a public CDN.
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8">
+    <title>minihub API</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script>
+      window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+    </script>
+  </body>
+</html>
+"##;
+
+// Building blocks
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn array_of(name: &str) -> Value {
+    json!({ "type": "array", "items": schema_ref(name) })
+}
+
+fn nullable(schema: &str) -> Value {
+    json!({ "type": [schema, "null"] })
+}
+
+fn nullable_ref(name: &str) -> Value {
+    json!({ "oneOf": [schema_ref(name), { "type": "null" }] })
+}
+
+fn uuid() -> Value {
+    json!({ "type": "string", "format": "uuid" })
+}
+
+fn count() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn map_of(values: &Value) -> Value {
+    json!({ "type": "object", "additionalProperties": values })
+}
+
+fn timestamp() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+fn json_content(schema: &Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn json_body(name: &str) -> Value {
+    json!({ "required": true, "content": json_content(&schema_ref(name)) })
+}
+
+fn ok(description: &str, schema: &Value) -> Value {
+    json!({ "description": description, "content": json_content(schema) })
+}
+
+fn error_response(description: &str) -> Value {
+    ok(description, &schema_ref("Error"))
+}
+
+fn common(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/responses/{name}") })
+}
+
+fn id_param() -> Value {
+    json!([{ "$ref": "#/components/parameters/Id" }])
+}
+
+fn query_param(name: &str, description: &str, schema: &Value) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": false,
+        "description": description,
+        "schema": schema,
+    })
+}
+
+/// The `If-Match` header of updates guarded by the entity version.
+fn if_match_param() -> Value {
+    json!({
+        "name": "If-Match",
+        "in": "header",
+        "required": false,
+        "description": "Only apply the update if the entity is still at this version, as sent \
+                        in the ETag header; answers 409 otherwise.",
+        "schema": { "type": "string", "examples": ["\"3\""] },
+    })
+}
+
+/// List and create operations of a collection.
+fn collection(tag: &str, item: &str, list_item: &str, create_body: &str) -> Value {
+    json!({
+        "get": {
+            "tags": [tag],
+            "summary": format!("List {tag}"),
+            "responses": { "200": ok("All items", &array_of(list_item)) },
+        },
+        "post": {
+            "tags": [tag],
+            "summary": format!("Create an item of {tag}"),
+            "requestBody": json_body(create_body),
+            "responses": {
+                "201": ok("Created item", &schema_ref(item)),
+                "400": common("BadRequest"),
+            },
+        },
+    })
+}
+
+/// Get and delete operations of a single item.
+fn item(tag: &str, schema: &str) -> Value {
+    json!({
+        "parameters": id_param(),
+        "get": {
+            "tags": [tag],
+            "summary": "Get by id",
+            "responses": {
+                "200": ok("The item", &schema_ref(schema)),
+                "400": common("BadRequest"),
+                "404": common("NotFound"),
+            },
+        },
+        "delete": {
+            "tags": [tag],
+            "summary": "Delete by id",
+            "responses": {
+                "204": { "description": "Deleted" },
+                "400": common("BadRequest"),
+                "404": common("NotFound"),
+            },
+        },
+    })
+}
+
+fn accepted() -> Value {
+    json!({
+        "description": "Accepted; completion is reported by a `service_call_completed` or \
+                        `service_call_failed` event",
+    })
+}
+
+// Paths
+
+fn entity_paths() -> Value {
+    json!({
+        "/entities": collection("entities", "Entity", "Entity", "CreateEntityRequest"),
+        "/entities/states": {
+            "post": {
+                "tags": ["entities"],
+                "summary": "Read the states of several entities at once",
+                "description": "Unknown ids are left out of the response.",
+                "requestBody": json_body("StatesRequest"),
+                "responses": {
+                    "200": ok("States of the requested entities", &array_of("EntityStateSnapshot")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+        "/entities/{id}": item("entities", "Entity"),
+        "/entities/{id}/state": {
+            "parameters": id_param(),
+            "put": {
+                "tags": ["entities"],
+                "summary": "Set the state of an entity",
+                "parameters": [if_match_param()],
+                "requestBody": json_body("UpdateStateRequest"),
+                "responses": {
+                    "200": ok("Updated entity", &schema_ref("Entity")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                    "409": common("Conflict"),
+                },
+            },
+        },
+        "/entities/{id}/rename": {
+            "parameters": id_param(),
+            "put": {
+                "tags": ["entities"],
+                "summary": "Change the entity_id of an entity",
+                "description": "The previous entity_id keeps resolving to the entity as an alias.",
+                "requestBody": json_body("RenameEntityRequest"),
+                "responses": {
+                    "200": ok("Renamed entity", &schema_ref("Entity")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/entities/{id}/service": {
+            "parameters": id_param(),
+            "post": {
+                "tags": ["entities"],
+                "summary": "Call a service on an entity",
+                "description": "`data` is checked against the built-in definition of the service \
+                                for the entity's domain, e.g. `brightness` between 0 and 255 for \
+                                `light.turn_on`. Mismatches answer `invalid_service_data`, with \
+                                every offending `parameter` and `reason` in `details.errors`.",
+                "requestBody": json_body("ServiceCallRequest"),
+                "responses": {
+                    "202": accepted(),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/entities/{id}/refresh": {
+            "parameters": id_param(),
+            "post": {
+                "tags": ["entities"],
+                "summary": "Ask the owning integration for an immediate readout",
+                "responses": {
+                    "202": accepted(),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/entities/{id}/history": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["entities"],
+                "summary": "State history of an entity",
+                "parameters": [
+                    query_param(
+                        "from",
+                        "Start of the range (RFC 3339). Defaults to 24 hours ago.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "to",
+                        "End of the range (RFC 3339). Defaults to now.",
+                        &timestamp(),
+                    ),
+                    query_param("limit", "Maximum number of records. Defaults to 1000.", &count()),
+                ],
+                "responses": {
+                    "200": ok("History records, oldest first", &array_of("EntityHistory")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+fn entity_wait_paths() -> Value {
+    json!({
+        "/entities/{id}/wait": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["entities"],
+                "summary": "Wait for an entity to change (long-poll)",
+                "description": "For clients that cannot hold an SSE stream. Answers as soon as the \
+                                entity is newer than `since_version`, or than its current version \
+                                when omitted. The version of the returned entity is sent in the \
+                                `X-Entity-Version` header.",
+                "parameters": [
+                    query_param(
+                        "timeout",
+                        "Longest wait for a change, in seconds.",
+                        &json!({ "type": "integer", "minimum": 1, "maximum": 300, "default": 30 }),
+                    ),
+                    query_param(
+                        "since_version",
+                        "Version the client already has, from a previous `X-Entity-Version`.",
+                        &json!({ "type": "integer" }),
+                    ),
+                ],
+                "responses": {
+                    "200": ok("Changed entity", &schema_ref("Entity")),
+                    "304": { "description": "No change before the timeout" },
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+fn entity_history_paths() -> Value {
+    json!({
+        "/entities/{id}/history/series": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["entities"],
+                "summary": "Downsampled numeric history of an entity",
+                "description": "Splits the range into buckets of equal length and returns the \
+                                mean, min and max of the numeric state and of each numeric \
+                                attribute per bucket. Buckets without samples are omitted.",
+                "parameters": [
+                    query_param(
+                        "from",
+                        "Start of the range (RFC 3339). Defaults to 24 hours ago.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "to",
+                        "End of the range (RFC 3339). Defaults to now.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "buckets",
+                        "Number of buckets the range is split into.",
+                        &json!({ "type": "integer", "minimum": 1, "maximum": 1000, "default": 200 }),
+                    ),
+                    query_param(
+                        "series",
+                        "Comma-separated series to return, `state` or attribute keys. \
+                         Defaults to every numeric one.",
+                        &json!({ "type": "string" }),
+                    ),
+                ],
+                "responses": {
+                    "200": ok("One series per numeric state or attribute", &array_of("HistorySeries")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+fn device_and_area_paths() -> Value {
+    let mut area = item("areas", "Area");
+    area["put"] = json!({
+        "tags": ["areas"],
+        "summary": "Rename an area",
+        "requestBody": json_body("UpdateAreaRequest"),
+        "responses": {
+            "200": ok("Updated area", &schema_ref("Area")),
+            "400": common("BadRequest"),
+            "404": common("NotFound"),
+        },
+    });
+    let mut device = item("devices", "DeviceDetail");
+    device["delete"]["summary"] = json!("Delete by id, along with its entities");
+    device["get"]["parameters"] = json!([query_param(
+        "include",
+        "Comma-separated related resources to embed: `entities`, `events`.",
+        &json!({ "type": "string" }),
+    )]);
+    json!({
+        "/devices": collection("devices", "Device", "DeviceWithStatus", "CreateDeviceRequest"),
+        "/devices/{id}": device,
+        "/devices/{id}/entities": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["devices"],
+                "summary": "Entities exposed by a device",
+                "responses": {
+                    "200": ok("Entities of the device", &array_of("Entity")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/devices/{id}/area": {
+            "parameters": id_param(),
+            "put": {
+                "tags": ["devices"],
+                "summary": "Move a device, and so its entities, into an area",
+                "requestBody": json_body("AssignAreaRequest"),
+                "responses": {
+                    "200": ok("Updated device", &schema_ref("DeviceWithStatus")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/areas": collection("areas", "Area", "Area", "CreateAreaRequest"),
+        "/areas/{id}": area,
+        "/areas/{id}/devices": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["areas"],
+                "summary": "Devices placed in an area, sorted by name",
+                "responses": {
+                    "200": ok("Devices of the area", &array_of("DeviceWithStatus")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+/// Filter and paging parameters of the event list.
+fn event_list_params() -> Value {
+    json!([
+        query_param(
+            "event_type",
+            "Only events of this type.",
+            &json!({ "type": "string" })
+        ),
+        query_param("entity_id", "Only events about this entity.", &uuid()),
+        query_param(
+            "from",
+            "Start of the range (RFC 3339, inclusive).",
+            &timestamp()
+        ),
+        query_param(
+            "to",
+            "End of the range (RFC 3339, exclusive).",
+            &timestamp()
+        ),
+        query_param(
+            "limit",
+            "Maximum number of events. Defaults to 100, capped at 1000.",
+            &count(),
+        ),
+        query_param(
+            "cursor",
+            "Position to resume from, taken from the x-next-cursor header of the previous page.",
+            &json!({ "type": "string" }),
+        ),
+    ])
+}
+
+fn event_paths() -> Value {
+    json!({
+        "/events": {
+            "get": {
+                "tags": ["events"],
+                "summary": "List events, newest first",
+                "parameters": event_list_params(),
+                "responses": {
+                    "200": {
+                        "description": "Events, newest first",
+                        "headers": {
+                            "x-next-cursor": {
+                                "description": "Cursor of the next page, present when this page is full",
+                                "schema": { "type": "string" },
+                            },
+                        },
+                        "content": json_content(&array_of("Event")),
+                    },
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+        "/events/export": {
+            "get": {
+                "tags": ["events"],
+                "summary": "Export stored events",
+                "parameters": [
+                    query_param(
+                        "from",
+                        "Start of the range (RFC 3339, inclusive). Defaults to the Unix epoch.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "to",
+                        "End of the range (RFC 3339, exclusive). Defaults to now.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "format",
+                        "Output format.",
+                        &json!({ "type": "string", "enum": ["ndjson"], "default": "ndjson" }),
+                    ),
+                ],
+                "responses": {
+                    "200": {
+                        "description": "One JSON event per line",
+                        "content": { "application/x-ndjson": { "schema": schema_ref("Event") } },
+                    },
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+        "/events/stream": {
+            "get": {
+                "tags": ["events"],
+                "summary": "Server-sent events stream of live events",
+                "parameters": [
+                    query_param(
+                        "event_type",
+                        "Comma-separated event types to stream, e.g. `state_changed,entity_created`.",
+                        &json!({ "type": "string" }),
+                    ),
+                    query_param(
+                        "entity_id",
+                        "Comma-separated ids of the entities whose events to stream.",
+                        &json!({ "type": "string" }),
+                    ),
+                    query_param(
+                        "integration",
+                        "Comma-separated integrations named in the payload of the events to stream.",
+                        &json!({ "type": "string" }),
+                    ),
+                ],
+                "responses": {
+                    "200": {
+                        "description": "Each message carries one JSON event",
+                        "content": { "text/event-stream": { "schema": schema_ref("Event") } },
+                    },
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+fn automation_paths() -> Value {
+    let mut single = item("automations", "Automation");
+    single["put"] = json!({
+        "tags": ["automations"],
+        "summary": "Replace an automation",
+        "requestBody": json_body("UpdateAutomationRequest"),
+        "responses": {
+            "200": ok("Updated automation", &schema_ref("Automation")),
+            "400": common("BadRequest"),
+            "404": common("NotFound"),
+            "409": common("Conflict"),
+        },
+    });
+    single["patch"] = json!({
+        "tags": ["automations"],
+        "summary": "Apply an RFC 6902 JSON Patch to an automation",
+        "requestBody": {
+            "required": true,
+            "content": { "application/json-patch+json": { "schema": schema_ref("JsonPatch") } },
+        },
+        "responses": {
+            "200": ok("Updated automation", &schema_ref("Automation")),
+            "400": common("BadRequest"),
+            "404": common("NotFound"),
+            "409": common("Conflict"),
+            "415": { "description": "The body is not `application/json-patch+json`" },
+        },
+    });
+
+    json!({
+        "/automations": collection(
+            "automations",
+            "Automation",
+            "Automation",
+            "CreateAutomationRequest",
+        ),
+        "/automations/{id}": single,
+        "/automations/{id}/runs": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["automations"],
+                "summary": "Execution log of an automation, newest first",
+                "parameters": [
+                    query_param("limit", "Maximum number of runs. Defaults to 50.", &count()),
+                ],
+                "responses": {
+                    "200": ok("Runs", &array_of("AutomationRun")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/automations/{id}/test": {
+            "parameters": id_param(),
+            "post": {
+                "tags": ["automations"],
+                "summary": "Dry-run an automation against a sample event",
+                "description": "Matches the trigger and evaluates every condition against the \
+                                current entity states. No action is executed.",
+                "requestBody": json_body("TestAutomationRequest"),
+                "responses": {
+                    "200": ok("Trace of the dry run", &schema_ref("AutomationTrace")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+fn automation_schedule_paths() -> Value {
+    json!({
+        "/automations/schedule": {
+            "get": {
+                "tags": ["automations"],
+                "summary": "Upcoming firings of the enabled time pattern automations",
+                "description": "Cron patterns are evaluated in UTC. At most 1000 firings are \
+                                returned, soonest first.",
+                "parameters": [
+                    query_param(
+                        "days",
+                        "Number of days ahead to cover. Defaults to 7, capped at 31.",
+                        &json!({ "type": "integer", "minimum": 1, "maximum": 31 }),
+                    ),
+                    query_param(
+                        "format",
+                        "Output format.",
+                        &json!({ "type": "string", "enum": ["json", "ics"], "default": "json" }),
+                    ),
+                ],
+                "responses": {
+                    "200": {
+                        "description": "Upcoming firings",
+                        "content": {
+                            "application/json": { "schema": array_of("ScheduledFiring") },
+                            "text/calendar": { "schema": { "type": "string" } },
+                        },
+                    },
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+/// Operations on a single stored event.
+fn event_item_paths() -> Value {
+    json!({
+        "/events/{id}": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["events"],
+                "summary": "Get by id",
+                "responses": {
+                    "200": ok("The event", &schema_ref("Event")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/events/{id}/chain": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["events"],
+                "summary": "Events sharing the correlation id of this one, oldest first",
+                "responses": {
+                    "200": ok("The causal chain", &array_of("Event")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+fn group_paths() -> Value {
+    let mut single = item("groups", "Group");
+    single["put"] = json!({
+        "tags": ["groups"],
+        "summary": "Replace the name and members of a group",
+        "requestBody": json_body("GroupRequest"),
+        "responses": {
+            "200": ok("Updated group", &schema_ref("Group")),
+            "400": common("BadRequest"),
+            "404": common("NotFound"),
+        },
+    });
+
+    json!({
+        "/groups": collection("groups", "Group", "Group", "GroupRequest"),
+        "/groups/{id}": single,
+    })
+}
+
+fn discovery_paths() -> Value {
+    json!({
+        "/discovery/pending": {
+            "get": {
+                "tags": ["discovery"],
+                "summary": "Detected devices not registered yet, most recently seen first",
+                "responses": { "200": ok("Pending devices", &array_of("PendingDevice")) },
+            },
+        },
+        "/discovery/pending/{id}/adopt": {
+            "parameters": id_param(),
+            "post": {
+                "tags": ["discovery"],
+                "summary": "Register a pending device as a regular device",
+                "requestBody": json_body("Adoption"),
+                "responses": {
+                    "201": ok("Registered device", &schema_ref("Device")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                    "409": error_response("A device with the same integration and unique id is registered"),
+                },
+            },
+        },
+    })
+}
+
+fn audit_paths() -> Value {
+    json!({
+        "/audit": {
+            "get": {
+                "tags": ["audit"],
+                "summary": "List creates, updates and deletes, newest first",
+                "parameters": [
+                    query_param(
+                        "actor",
+                        "Only entries of this actor, e.g. `integration:mqtt`, or of this kind of actor, e.g. `integration`.",
+                        &json!({ "type": "string" }),
+                    ),
+                    query_param(
+                        "target_type",
+                        "Only entries about this kind of resource.",
+                        &json!({ "type": "string", "examples": ["automation"] }),
+                    ),
+                    query_param(
+                        "target_id",
+                        "Only entries about the resource with this id.",
+                        &json!({ "type": "string" }),
+                    ),
+                    query_param(
+                        "limit",
+                        "Maximum number of entries. Defaults to 100, capped at 1000.",
+                        &count(),
+                    ),
+                    query_param(
+                        "cursor",
+                        "Position to resume from, taken from the x-next-cursor header of the previous page.",
+                        &json!({ "type": "string" }),
+                    ),
+                ],
+                "responses": {
+                    "200": {
+                        "description": "Audit entries, newest first",
+                        "headers": {
+                            "x-next-cursor": {
+                                "description": "Cursor of the next page, present when this page is full",
+                                "schema": { "type": "string" },
+                            },
+                        },
+                        "content": json_content(&array_of("AuditEntry")),
+                    },
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+fn scene_and_misc_paths() -> Value {
+    let mut single = item("scenes", "Scene");
+    single["put"] = json!({
+        "tags": ["scenes"],
+        "summary": "Replace the name and members of a scene",
+        "requestBody": json_body("SceneRequest"),
+        "responses": {
+            "200": ok("Updated scene", &schema_ref("Scene")),
+            "400": common("BadRequest"),
+            "404": common("NotFound"),
+        },
+    });
+
+    json!({
+        "/scenes": collection("scenes", "Scene", "Scene", "SceneRequest"),
+        "/scenes/{id}": single,
+        "/scenes/{id}/activate": {
+            "parameters": id_param(),
+            "post": {
+                "tags": ["scenes"],
+                "summary": "Request a service call for every member of a scene",
+                "responses": {
+                    "202": ok("The activated scene", &schema_ref("Scene")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+        "/input_helpers": collection(
+            "input_helpers",
+            "Entity",
+            "Entity",
+            "CreateInputHelperRequest",
+        ),
+        "/home_mode": {
+            "get": {
+                "tags": ["home_mode"],
+                "summary": "Current hub-wide home mode",
+                "responses": { "200": ok("Home mode", &schema_ref("HomeModeSummary")) },
+            },
+            "put": {
+                "tags": ["home_mode"],
+                "summary": "Switch the hub-wide home mode",
+                "requestBody": json_body("UpdateHomeModeRequest"),
+                "responses": {
+                    "200": ok("Updated home mode", &schema_ref("HomeModeSummary")),
+                },
+            },
+        },
+        "/reports/overview": {
+            "get": {
+                "tags": ["reports"],
+                "summary": "Counts of the hub content and recent activity",
+                "parameters": [
+                    query_param(
+                        "hours",
+                        "Size of the activity window in hours. Defaults to 24.",
+                        &count(),
+                    ),
+                ],
+                "responses": { "200": ok("Overview", &schema_ref("Overview")) },
+            },
+        },
+    })
+}
+
+fn dashboard_and_settings_paths() -> Value {
+    json!({
+        "/dashboard_config": {
+            "get": {
+                "tags": ["dashboard"],
+                "summary": "Cards pinned to the dashboard home page",
+                "responses": { "200": ok("Dashboard layout", &schema_ref("DashboardConfig")) },
+            },
+            "put": {
+                "tags": ["dashboard"],
+                "summary": "Replace the dashboard layout",
+                "description": "Cards are shown in the order they are listed.",
+                "requestBody": json_body("DashboardConfig"),
+                "responses": {
+                    "200": ok("Updated dashboard layout", &schema_ref("DashboardConfig")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+        "/settings/{namespace}": {
+            "parameters": [{ "$ref": "#/components/parameters/SettingsNamespace" }],
+            "get": {
+                "tags": ["settings"],
+                "summary": "Settings of a namespace",
+                "description": "Empty for a namespace without settings.",
+                "responses": {
+                    "200": ok("Settings", &schema_ref("Settings")),
+                    "400": common("BadRequest"),
+                },
+            },
+            "put": {
+                "tags": ["settings"],
+                "summary": "Replace the settings of a namespace",
+                "description": "Keys missing from the body are removed. \
+                    The `dashboard` namespace is written through `/dashboard_config`.",
+                "requestBody": json_body("Settings"),
+                "responses": {
+                    "200": ok("Updated settings", &schema_ref("Settings")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+fn secret_paths() -> Value {
+    json!({
+        "/secrets": {
+            "get": {
+                "tags": ["secrets"],
+                "summary": "Stored secrets, without their value",
+                "responses": { "200": ok("Secrets", &array_of("SecretInfo")) },
+            },
+        },
+        "/secrets/{name}": {
+            "parameters": [{ "$ref": "#/components/parameters/SecretName" }],
+            "post": {
+                "tags": ["secrets"],
+                "summary": "Store a secret",
+                "description": "Replaces any previous value. The value is encrypted at rest \
+                    and never returned by the API.",
+                "requestBody": json_body("StoreSecretRequest"),
+                "responses": {
+                    "200": ok("Stored secret, without its value", &schema_ref("SecretInfo")),
+                    "400": common("BadRequest"),
+                },
+            },
+            "delete": {
+                "tags": ["secrets"],
+                "summary": "Delete a secret",
+                "responses": {
+                    "204": { "description": "Deleted" },
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+fn integration_paths() -> Value {
+    let control = |summary: &str| {
+        json!({
+            "parameters": [{ "$ref": "#/components/parameters/IntegrationName" }],
+            "post": {
+                "tags": ["integrations"],
+                "summary": summary,
+                "responses": {
+                    "202": { "description": "Accepted; progress is reported by the integration status" },
+                    "404": common("NotFound"),
+                },
+            },
+        })
+    };
+    json!({
+        "/integrations": {
+            "get": {
+                "tags": ["integrations"],
+                "summary": "Configured integrations and their startup status",
+                "responses": { "200": ok("Integrations", &array_of("IntegrationSummary")) },
+            },
+        },
+        "/integrations/{name}/devices": {
+            "parameters": [{ "$ref": "#/components/parameters/IntegrationName" }],
+            "get": {
+                "tags": ["integrations"],
+                "summary": "Devices reported by an integration, sorted by name",
+                "responses": { "200": ok("Devices", &array_of("Device")) },
+            },
+        },
+        "/integrations/{name}/restart": control("Tear an integration down and start it again"),
+        "/integrations/{name}/disable": control("Tear an integration down until it is enabled again"),
+        "/integrations/{name}/enable": control("Start again an integration disabled at runtime"),
+    })
+}
+
+fn config_paths() -> Value {
+    json!({
+        "/config/reload": {
+            "post": {
+                "tags": ["config"],
+                "summary": "Re-read the configuration file and apply what can change without a restart",
+                "responses": {
+                    "200": ok("Changed settings", &schema_ref("ReloadSummary")),
+                    "400": error_response("Invalid configuration; the running one is kept"),
+                    "503": error_response("Configuration reload is not available"),
+                },
+            },
+        },
+    })
+}
+
+fn system_paths() -> Value {
+    json!({
+        "/system/info": {
+            "get": {
+                "tags": ["system"],
+                "summary": "Version, uptime, storage size and event bus load of the daemon",
+                "responses": { "200": ok("System information", &schema_ref("SystemInfo")) },
+            },
+        },
+        "/system/backup": {
+            "post": {
+                "tags": ["system"],
+                "summary": "Write a database snapshot now, removing the oldest beyond the retention count",
+                "responses": {
+                    "201": ok("Snapshot written", &schema_ref("BackupSummary")),
+                    "500": error_response("The snapshot could not be written"),
+                    "503": error_response("Database backups are not available"),
+                },
+            },
+        },
+    })
+}
+
+fn energy_paths() -> Value {
+    json!({
+        "/energy/{id}": {
+            "parameters": id_param(),
+            "get": {
+                "tags": ["energy"],
+                "summary": "Consumption of a power or energy sensor, summed per period",
+                "description": "Periods follow the UTC calendar, weeks starting on Monday. \
+                                Consumption is recorded from the readings received since the \
+                                daemon started.",
+                "parameters": [
+                    query_param(
+                        "period",
+                        "Length of the buckets.",
+                        &json!({
+                            "type": "string",
+                            "enum": ["hour", "day", "week", "month"],
+                            "default": "day",
+                        }),
+                    ),
+                    query_param(
+                        "from",
+                        "Start of the range (RFC 3339), moved back to the start of its period. \
+                         Defaults to 24 hours, 30 days, 13 weeks or 12 months ago.",
+                        &timestamp(),
+                    ),
+                    query_param(
+                        "to",
+                        "End of the range (RFC 3339). Defaults to now.",
+                        &timestamp(),
+                    ),
+                ],
+                "responses": {
+                    "200": ok("Consumption per period", &schema_ref("EnergyReport")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                },
+            },
+        },
+    })
+}
+
+fn webhook_paths() -> Value {
+    json!({
+        "/webhook/{webhook_id}": {
+            "parameters": [{ "$ref": "#/components/parameters/WebhookId" }],
+            "post": {
+                "tags": ["automations"],
+                "summary": "Receive an inbound webhook",
+                "description": "Publishes a `webhook_received` event carrying the request body, \
+                                running the automations with a `webhook` trigger of the same id. \
+                                A JSON body is carried as is, any other body as text. \
+                                Ids no enabled automation listens on answer `404`, and bodies \
+                                are limited to 64 KiB.",
+                "requestBody": {
+                    "required": false,
+                    "content": { "*/*": { "schema": {} } },
+                },
+                "responses": {
+                    "202": ok("Published event", &schema_ref("WebhookAccepted")),
+                    "400": common("BadRequest"),
+                    "404": common("NotFound"),
+                    "413": common("PayloadTooLarge"),
+                },
+            },
+        },
+    })
+}
+
+fn validation_paths() -> Value {
+    json!({
+        "/automations/validate": {
+            "post": {
+                "tags": ["automations"],
+                "summary": "Validate an automation without creating it",
+                "description": "Reports every invalid field of the payload at once. \
+                                Nothing is persisted.",
+                "requestBody": json_body("CreateAutomationRequest"),
+                "responses": {
+                    "200": ok("Validation report", &schema_ref("ValidationReport")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+        "/entities/validate": {
+            "post": {
+                "tags": ["entities"],
+                "summary": "Validate an entity without creating it",
+                "description": "Reports every invalid field of the payload at once. \
+                                Nothing is persisted.",
+                "requestBody": json_body("CreateEntityRequest"),
+                "responses": {
+                    "200": ok("Validation report", &schema_ref("ValidationReport")),
+                    "400": common("BadRequest"),
+                },
+            },
+        },
+    })
+}
+
+// Schemas
+
+fn entity_schemas() -> Value {
+    json!({
+        "Error": {
+            "type": "object",
+            "required": ["error"],
+            "properties": {
+                "error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "description": "Stable machine-readable code",
+                            "examples": ["entity_not_found", "invalid_id", "entity_conflict"],
+                        },
+                        "message": { "type": "string", "description": "Human-readable message" },
+                        "details": {
+                            "type": "object",
+                            "description": "Structured context, e.g. `expected` and `actual` on \
+                                            conflicts",
+                        },
+                    },
+                },
+            },
+        },
+        "EntityState": {
+            "description": "`on`, `off`, `unknown` or `unavailable`, a numeric reading, or \
+                            any other string as a text value",
+            "anyOf": [
+                { "type": "string", "enum": ["on", "off", "unknown", "unavailable"] },
+                { "type": "number" },
+                { "type": "string" },
+            ],
+        },
+        "DeviceClass": {
+            "type": "string",
+            "enum": [
+                "temperature", "humidity", "illuminance", "moisture", "conductivity",
+                "battery", "voltage", "power", "energy", "pressure", "motion",
+                "occupancy", "door", "window",
+            ],
+        },
+        "AttributeValue": {
+            "description": "Boolean, integer, number, string or any JSON value",
+        },
+        "AttributeMeta": {
+            "type": "object",
+            "properties": {
+                "precision": { "type": "integer", "minimum": 0 },
+                "min": { "type": "number" },
+                "max": { "type": "number" },
+                "display_unit": { "type": "string" },
+            },
+        },
+        "Entity": {
+            "type": "object",
+            "required": [
+                "id", "device_id", "entity_id", "friendly_name", "state", "attributes",
+                "mac_address", "last_changed", "last_updated", "version",
+            ],
+            "properties": {
+                "id": uuid(),
+                "device_id": uuid(),
+                "entity_id": { "type": "string", "examples": ["sensor.living_room_temperature"] },
+                "friendly_name": { "type": "string" },
+                "state": schema_ref("EntityState"),
+                "device_class": nullable_ref("DeviceClass"),
+                "unit_of_measurement": nullable("string"),
+                "attributes": map_of(&schema_ref("AttributeValue")),
+                "attribute_meta": map_of(&schema_ref("AttributeMeta")),
+                "mac_address": nullable("string"),
+                "last_changed": timestamp(),
+                "last_updated": timestamp(),
+                "version": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Bumped by every update; sent as the ETag",
+                },
+            },
+        },
+        "EntityHistory": {
+            "type": "object",
+            "required": ["id", "entity_id", "state", "attributes", "recorded_at"],
+            "properties": {
+                "id": uuid(),
+                "entity_id": uuid(),
+                "state": schema_ref("EntityState"),
+                "attributes": map_of(&schema_ref("AttributeValue")),
+                "recorded_at": timestamp(),
+            },
+        },
+    })
+}
+
+fn device_and_area_schemas() -> Value {
+    let device_properties = json!({
+        "id": uuid(),
+        "name": { "type": "string" },
+        "manufacturer": nullable("string"),
+        "model": nullable("string"),
+        "area_id": { "type": ["string", "null"], "format": "uuid" },
+        "integration": { "type": "string" },
+        "unique_id": { "type": "string" },
+        "via_device_id": {
+            "type": ["string", "null"],
+            "format": "uuid",
+            "description": "Device this one is reached through, e.g. a bridge",
+        },
+        "sw_version": nullable("string"),
+        "hw_version": nullable("string"),
+        "suggested_area": nullable("string"),
+    });
+    let device_required = json!(["id", "name", "integration", "unique_id"]);
+    let mut with_status_properties = device_properties.clone();
+    with_status_properties["status"] = json!({
+        "type": "string",
+        "enum": ["online", "unavailable", "unknown"],
+        "description": "Availability derived from the states of the device entities",
+    });
+
+    json!({
+        "Device": {
+            "type": "object",
+            "required": device_required,
+            "properties": device_properties,
+        },
+        "DeviceWithStatus": {
+            "type": "object",
+            "required": device_required,
+            "properties": with_status_properties,
+        },
+        "DeviceDetail": {
+            "allOf": [
+                schema_ref("DeviceWithStatus"),
+                {
+                    "type": "object",
+                    "properties": {
+                        "entities": {
+                            "description": "Entities of the device, with `include=entities`",
+                            "type": "array",
+                            "items": schema_ref("Entity"),
+                        },
+                        "events": {
+                            "description": "Most recent events about the device entities, \
+                                            newest first, with `include=events`",
+                            "type": "array",
+                            "items": schema_ref("Event"),
+                        },
+                    },
+                },
+            ],
+        },
+        "Area": {
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": uuid(),
+                "name": { "type": "string" },
+                "parent_id": { "type": ["string", "null"], "format": "uuid" },
+            },
+        },
+        "UpdateAreaRequest": {
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" } },
+        },
+        "AssignAreaRequest": {
+            "type": "object",
+            "properties": {
+                "area_id": {
+                    "type": ["string", "null"],
+                    "format": "uuid",
+                    "description": "Target area, or `null` to leave every area",
+                },
+            },
+        },
+    })
+}
+
+fn automation_schemas() -> Value {
+    json!({
+        "Trigger": {
+            "oneOf": [
+                { "type": "object", "required": ["type", "entity_id"], "properties": {
+                    "type": { "const": "state_changed" },
+                    "entity_id": uuid(),
+                    "from": nullable_ref("EntityState"),
+                    "to": nullable_ref("EntityState"),
+                } },
+                { "type": "object", "required": ["type", "entity_id"], "properties": {
+                    "type": { "const": "value_changed" },
+                    "entity_id": uuid(),
+                    "to": { "description": "Only match when the new `value` attribute is this" },
+                } },
+                { "type": "object", "required": ["type", "device_id"], "properties": {
+                    "type": { "const": "device_unavailable" },
+                    "device_id": uuid(),
+                } },
+                { "type": "object", "required": ["type", "device_id"], "properties": {
+                    "type": { "const": "device_back_online" },
+                    "device_id": uuid(),
+                } },
+                { "type": "object", "required": ["type", "cron"], "properties": {
+                    "type": { "const": "time_pattern" },
+                    "cron": {
+                        "type": "string",
+                        "description": "Five cron fields, evaluated in UTC",
+                        "examples": ["0 8 * * *"],
+                    },
+                } },
+                { "type": "object", "required": ["type", "webhook_id"], "properties": {
+                    "type": { "const": "webhook" },
+                    "webhook_id": schema_ref("WebhookId"),
+                } },
+                { "type": "object", "required": ["type", "event"], "properties": {
+                    "type": { "const": "sun" },
+                    "event": { "type": "string", "enum": ["sunrise", "sunset"] },
+                    "offset_minutes": {
+                        "type": "integer",
+                        "minimum": -720,
+                        "maximum": 720,
+                        "default": 0,
+                        "description": "Minutes after the event, negative for before it; \
+                                        requires `[location]` in the configuration",
+                    },
+                } },
+                { "type": "object", "required": ["type"], "properties": {
+                    "type": { "const": "manual" },
+                } },
+            ],
+        },
+        "ExecutionMode": {
+            "type": "string",
+            "enum": ["single", "restart", "queued", "parallel"],
+            "default": "single",
+            "description": "What happens when the automation triggers while a run is in progress: \
+                            ignore the trigger, cancel the run, run after it, or run alongside it",
+        },
+        "Automation": {
+            "type": "object",
+            "required": [
+                "id", "name", "enabled", "trigger", "conditions", "actions", "execution_mode",
+                "last_triggered", "version",
+            ],
+            "properties": {
+                "id": uuid(),
+                "name": { "type": "string" },
+                "enabled": { "type": "boolean" },
+                "trigger": schema_ref("Trigger"),
+                "conditions": array_of("Condition"),
+                "actions": array_of("Action"),
+                "execution_mode": schema_ref("ExecutionMode"),
+                "last_triggered": { "type": ["string", "null"], "format": "date-time" },
+                "version": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Bumped by every update",
+                },
+                "source_key": {
+                    "type": "string",
+                    "examples": ["evening_lights"],
+                    "description": "Set on automations imported from a YAML file, which are read-only",
+                },
+            },
+        },
+    })
+}
+
+fn condition_and_action_schemas() -> Value {
+    json!({
+        "Condition": {
+            "oneOf": [
+                { "type": "object", "required": ["type", "entity_id", "state"], "properties": {
+                    "type": { "const": "state_is" },
+                    "entity_id": uuid(),
+                    "state": { "type": "string" },
+                } },
+                { "type": "object", "required": ["type", "after", "before"], "properties": {
+                    "type": { "const": "time_range" },
+                    "after": { "type": "string", "description": "`HH:MM`, 24-hour" },
+                    "before": { "type": "string", "description": "`HH:MM`, 24-hour" },
+                } },
+                { "type": "object", "required": ["type", "mode"], "properties": {
+                    "type": { "const": "home_mode_is" },
+                    "mode": schema_ref("HomeMode"),
+                } },
+                { "type": "object", "required": ["type", "entity_id", "value"], "properties": {
+                    "type": { "const": "value_is" },
+                    "entity_id": uuid(),
+                    "value": { "description": "Expected `value` attribute" },
+                } },
+                { "type": "object", "required": ["type", "expr"], "properties": {
+                    "type": { "const": "expression" },
+                    "expr": {
+                        "type": "string",
+                        "description": "Expression that must be truthy",
+                        "examples": ["states('sensor.ble_kitchen').temperature > 25 && time.hour >= 22"],
+                    },
+                } },
+                { "type": "object", "required": ["type"], "properties": {
+                    "type": { "enum": ["sun_above_horizon", "sun_below_horizon"] },
+                } },
+            ],
+        },
+        "Action": {
+            "oneOf": [
+                { "type": "object", "required": ["type", "entity_id", "service"], "properties": {
+                    "type": { "const": "call_service" },
+                    "entity_id": uuid(),
+                    "service": { "type": "string", "examples": ["turn_on"] },
+                    "data": { "description": "Strings may hold `{{ expression }}` templates" },
+                } },
+                { "type": "object", "required": ["type", "seconds"], "properties": {
+                    "type": { "const": "delay" },
+                    "seconds": { "type": "integer", "minimum": 0 },
+                } },
+                { "type": "object", "required": ["type", "message"], "properties": {
+                    "type": { "const": "notify" },
+                    "title": nullable("string"),
+                    "message": {
+                        "type": "string",
+                        "description": "May hold `{{ expression }}` templates",
+                    },
+                    "severity": {
+                        "type": "string",
+                        "enum": ["info", "warning", "critical"],
+                        "default": "info",
+                    },
+                } },
+                { "type": "object", "required": ["type", "entity_id", "state", "timeout_secs"], "properties": {
+                    "type": { "const": "wait_for_state" },
+                    "entity_id": uuid(),
+                    "state": { "type": "string", "examples": ["off"] },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "The action fails when the state is not reached in time",
+                    },
+                } },
+                { "type": "object", "required": ["type", "condition"], "properties": {
+                    "type": { "const": "if" },
+                    "condition": schema_ref("Condition"),
+                    "then": array_of("Action"),
+                    "else": array_of("Action"),
+                } },
+                { "type": "object", "required": ["type", "count", "actions"], "properties": {
+                    "type": { "const": "repeat" },
+                    "count": { "type": "integer", "minimum": 1, "maximum": 100 },
+                    "actions": array_of("Action"),
+                } },
+            ],
+        },
+    })
+}
+
+fn automation_run_schemas() -> Value {
+    json!({
+        "AutomationRun": {
+            "type": "object",
+            "required": ["id", "automation_id", "event_id", "started_at", "conditions", "actions"],
+            "properties": {
+                "id": uuid(),
+                "automation_id": uuid(),
+                "event_id": { "type": ["string", "null"], "format": "uuid" },
+                "started_at": timestamp(),
+                "conditions": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["condition", "passed"],
+                    "properties": {
+                        "condition": schema_ref("Condition"),
+                        "passed": { "type": "boolean" },
+                    },
+                } },
+                "actions": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["action", "outcome"],
+                    "properties": {
+                        "action": schema_ref("Action"),
+                        "outcome": {
+                            "type": "object",
+                            "required": ["status"],
+                            "properties": {
+                                "status": {
+                                    "type": "string",
+                                    "enum": ["succeeded", "failed", "skipped", "cancelled"],
+                                },
+                                "error": {
+                                    "type": "string",
+                                    "description": "Set when the status is `failed`",
+                                },
+                            },
+                        },
+                    },
+                } },
+            },
+        },
+        "TestAutomationRequest": {
+            "type": "object",
+            "required": ["event_type"],
+            "properties": {
+                "event_type": {
+                    "type": "string",
+                    "description": "Type of the sample event, as in `Event`",
+                },
+                "entity_id": uuid(),
+                "data": { "type": "object", "description": "Payload of the sample event" },
+            },
+        },
+        "AutomationTrace": {
+            "type": "object",
+            "required": ["automation_id", "matched", "would_run", "conditions"],
+            "properties": {
+                "automation_id": uuid(),
+                "matched": {
+                    "type": "boolean",
+                    "description": "Whether the trigger matched the event",
+                },
+                "would_run": {
+                    "type": "boolean",
+                    "description": "Whether the trigger matched and every condition passed",
+                },
+                "conditions": { "type": "array", "items": {
+                    "type": "object",
+                    "required": ["condition", "passed", "entities"],
+                    "properties": {
+                        "condition": schema_ref("Condition"),
+                        "passed": { "type": "boolean" },
+                        "entities": {
+                            "type": "array",
+                            "items": schema_ref("Entity"),
+                            "description": "Entities read to evaluate the condition",
+                        },
+                        "error": {
+                            "type": "string",
+                            "description": "Why the condition could not be evaluated",
+                        },
+                    },
+                } },
+            },
+        },
+    })
+}
+
+fn misc_schemas() -> Value {
+    json!({
+        "Event": {
+            "type": "object",
+            "required": [
+                "id", "event_type", "entity_id", "timestamp", "data", "correlation_id",
+                "schema_version",
+            ],
+            "properties": {
+                "id": uuid(),
+                "event_type": {
+                    "type": "string",
+                    "enum": [
+                        "state_changed", "attribute_changed", "entity_created", "entity_removed",
+                        "entity_renamed", "automation_triggered", "automation_suppressed",
+                        "device_detected", "device_updated", "device_unavailable",
+                        "device_back_online", "service_call_requested", "service_call_completed",
+                        "service_call_failed", "notification_requested", "webhook_received",
+                        "sun_event", "time_pattern_fired",
+                    ],
+                },
+                "entity_id": { "type": ["string", "null"], "format": "uuid" },
+                "timestamp": timestamp(),
+                "data": {
+                    "description": "Payload of the event type, e.g. `{ \"old_state\", \"new_state\" }` \
+                        for `state_changed`",
+                },
+                "correlation_id": {
+                    "type": "string",
+                    "format": "uuid",
+                    "description": "Id of the event that started the causal chain",
+                },
+                "caused_by": {
+                    "type": ["string", "null"],
+                    "format": "uuid",
+                    "description": "Event this one was produced in reaction to",
+                },
+                "schema_version": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Version of the shape of `data`",
+                },
+            },
+        },
+        "SceneMember": {
+            "type": "object",
+            "required": ["entity_id", "state"],
+            "properties": {
+                "entity_id": uuid(),
+                "state": schema_ref("EntityState"),
+                "attributes": map_of(&schema_ref("AttributeValue")),
+            },
+        },
+        "Scene": {
+            "type": "object",
+            "required": ["id", "name", "members"],
+            "properties": {
+                "id": uuid(),
+                "name": { "type": "string" },
+                "members": array_of("SceneMember"),
+            },
+        },
+        "Overview": {
+            "type": "object",
+            "required": [
+                "entities", "devices", "areas", "automations", "enabled_automations",
+                "entities_by_state", "since", "events_since",
+            ],
+            "properties": {
+                "entities": { "type": "integer" },
+                "devices": { "type": "integer" },
+                "areas": { "type": "integer" },
+                "automations": { "type": "integer" },
+                "enabled_automations": { "type": "integer" },
+                "entities_by_state": map_of(&json!({ "type": "integer" })),
+                "since": timestamp(),
+                "events_since": { "type": "integer" },
+            },
+        },
+        "IntegrationSummary": {
+            "type": "object",
+            "required": ["name", "enabled", "status", "restarts"],
+            "properties": {
+                "name": { "type": "string", "examples": ["mqtt"] },
+                "enabled": { "type": "boolean" },
+                "status": {
+                    "type": "string",
+                    "enum": ["disabled", "starting", "running", "timed_out", "restarting", "failed"],
+                },
+                "error": {
+                    "type": "string",
+                    "description": "Last startup error, set when the status is `restarting` or `failed`",
+                },
+                "restarts": { "type": "integer", "minimum": 0 },
+            },
+        },
+    })
+}
+
+fn automation_schedule_schemas() -> Value {
+    json!({
+        "ScheduledFiring": {
+            "type": "object",
+            "required": ["automation_id", "name", "at"],
+            "properties": {
+                "automation_id": uuid(),
+                "name": { "type": "string" },
+                "at": timestamp(),
+            },
+        },
+    })
+}
+
+fn energy_schemas() -> Value {
+    json!({
+        "EnergyReport": {
+            "type": "object",
+            "required": ["entity_id", "period", "from", "to", "total_kwh", "buckets"],
+            "properties": {
+                "entity_id": uuid(),
+                "period": { "type": "string", "enum": ["hour", "day", "week", "month"] },
+                "from": timestamp(),
+                "to": timestamp(),
+                "total_kwh": { "type": "number", "minimum": 0 },
+                "buckets": {
+                    "type": "array",
+                    "description": "Periods with some consumption, oldest first",
+                    "items": schema_ref("EnergyBucket"),
+                },
+            },
+        },
+        "HistorySeries": {
+            "type": "object",
+            "required": ["name", "buckets"],
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "`state` for the numeric state, the attribute key otherwise",
+                },
+                "buckets": {
+                    "type": "array",
+                    "description": "Buckets with some samples, oldest first",
+                    "items": schema_ref("HistoryBucket"),
+                },
+            },
+        },
+        "HistoryBucket": {
+            "type": "object",
+            "required": ["start", "mean", "min", "max", "count"],
+            "properties": {
+                "start": timestamp(),
+                "mean": { "type": "number" },
+                "min": { "type": "number" },
+                "max": { "type": "number" },
+                "count": { "type": "integer", "minimum": 1 },
+            },
+        },
+        "EnergyBucket": {
+            "type": "object",
+            "required": ["start", "energy_kwh"],
+            "properties": {
+                "start": timestamp(),
+                "energy_kwh": { "type": "number", "minimum": 0 },
+            },
+        },
+    })
+}
+
+fn webhook_schemas() -> Value {
+    json!({
+        "WebhookId": {
+            "type": "string",
+            "pattern": "^[A-Za-z0-9_-]{16,64}$",
+            "description": "At least 8 distinct characters, so the id cannot be guessed: webhook calls need no token.",
+            "examples": ["9f86d081884c4d63a2e4c6b9d1f7e2a0"],
+        },
+        "WebhookAccepted": {
+            "type": "object",
+            "required": ["event_id"],
+            "properties": {
+                "event_id": uuid(),
+            },
+        },
+    })
+}
+
+fn validation_schemas() -> Value {
+    json!({
+        "ValidationReport": {
+            "type": "object",
+            "required": ["valid", "errors"],
+            "properties": {
+                "valid": { "type": "boolean" },
+                "errors": array_of("FieldIssue"),
+            },
+        },
+        "FieldIssue": {
+            "type": "object",
+            "required": ["field", "code", "message"],
+            "properties": {
+                "field": { "type": "string", "examples": ["actions[1]"] },
+                "code": {
+                    "type": "string",
+                    "description": "`missing_field`, `invalid_value` or a validation error code",
+                    "examples": ["empty_name"],
+                },
+                "message": { "type": "string" },
+            },
+        },
+    })
+}
+
+fn system_schemas() -> Value {
+    json!({
+        "ReloadSummary": {
+            "type": "object",
+            "required": ["applied", "requires_restart"],
+            "properties": {
+                "applied": {
+                    "type": "array",
+                    "items": { "type": "string", "examples": ["logging.filter"] },
+                    "description": "Settings applied to the running daemon",
+                },
+                "requires_restart": {
+                    "type": "array",
+                    "items": { "type": "string", "examples": ["server"] },
+                    "description": "Changed settings that only take effect after a restart",
+                },
+            },
+        },
+        "SystemInfo": {
+            "type": "object",
+            "required": [
+                "version", "git_hash", "started_at", "uptime_secs", "storage", "integrations",
+                "event_bus",
+            ],
+            "properties": {
+                "version": { "type": "string", "examples": ["0.1.1"] },
+                "git_hash": {
+                    "type": ["string", "null"],
+                    "description": "Commit the daemon was built from, when known",
+                },
+                "started_at": timestamp(),
+                "uptime_secs": { "type": "integer", "minimum": 0 },
+                "storage": schema_ref("StorageStats"),
+                "integrations": array_of("IntegrationSummary"),
+                "event_bus": schema_ref("EventBusStats"),
+            },
+        },
+        "BackupSummary": {
+            "type": "object",
+            "required": ["path", "size_bytes", "created_at", "pruned"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Location of the snapshot file on the hub",
+                    "examples": ["/data/backups/minihub-20240101T030000000Z.db"],
+                },
+                "size_bytes": { "type": "integer", "minimum": 0 },
+                "created_at": timestamp(),
+                "pruned": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Older snapshots removed to stay within the retention count",
+                },
+            },
+        },
+        "StorageStats": {
+            "type": "object",
+            "required": ["database_bytes", "entities", "events", "entity_history"],
+            "properties": {
+                "database_bytes": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Size of the database, excluding the write-ahead log",
+                },
+                "entities": count(),
+                "events": count(),
+                "entity_history": count(),
+            },
+        },
+        "EventBusStats": {
+            "type": "object",
+            "required": ["capacity", "subscribers", "queued"],
+            "properties": {
+                "capacity": {
+                    "type": "integer",
+                    "description": "Number of events kept for slow subscribers before they lag",
+                },
+                "subscribers": count(),
+                "queued": {
+                    "type": "integer",
+                    "description": "Number of events not yet received by the slowest subscriber",
+                },
+            },
+        },
+    })
+}
+
+fn home_mode_and_helper_schemas() -> Value {
+    json!({
+        "HomeMode": {
+            "type": "string",
+            "enum": ["home", "away", "night", "vacation"],
+        },
+        "HomeModeSummary": {
+            "type": "object",
+            "required": ["mode", "options"],
+            "properties": {
+                "mode": schema_ref("HomeMode"),
+                "options": array_of("HomeMode"),
+            },
+        },
+        "CreateInputHelperRequest": {
+            "type": "object",
+            "required": ["name", "kind"],
+            "properties": {
+                "name": { "type": "string", "examples": ["Guest mode"] },
+                "kind": { "type": "string", "enum": ["boolean", "number", "select"] },
+                "min": { "type": "number", "description": "Required for `number`" },
+                "max": { "type": "number", "description": "Required for `number`" },
+                "step": { "type": "number", "default": 1 },
+                "options": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Required for `select`",
+                },
+                "initial": { "description": "Starting state or value" },
+            },
+        },
+        "UpdateHomeModeRequest": {
+            "type": "object",
+            "required": ["mode"],
+            "properties": { "mode": schema_ref("HomeMode") },
+        },
+    })
+}
+
+fn dashboard_and_settings_schemas() -> Value {
+    json!({
+        "Card": {
+            "oneOf": [
+                { "type": "object", "required": ["type", "entity_id"], "properties": {
+                    "type": { "const": "entity" },
+                    "entity_id": uuid(),
+                } },
+                { "type": "object", "required": ["type", "entity_id"], "properties": {
+                    "type": { "const": "chart" },
+                    "entity_id": uuid(),
+                    "hours": { "type": "integer", "minimum": 1, "maximum": 168, "default": 24 },
+                } },
+                { "type": "object", "required": ["type", "area_id"], "properties": {
+                    "type": { "const": "area" },
+                    "area_id": uuid(),
+                } },
+            ],
+        },
+        "SettingsName": {
+            "type": "string",
+            "pattern": "^[a-z0-9_]{1,64}$",
+            "examples": ["dashboard"],
+        },
+        "Settings": {
+            "type": "object",
+            "description": "JSON value of each key",
+            "propertyNames": schema_ref("SettingsName"),
+            "examples": [{ "layout": { "cards": [] } }],
+        },
+        "DashboardConfig": {
+            "type": "object",
+            "properties": {
+                "cards": {
+                    "type": "array",
+                    "items": schema_ref("Card"),
+                    "maxItems": 64,
+                    "description": "Cards in display order",
+                },
+            },
+        },
+    })
+}
+
+fn secret_schemas() -> Value {
+    json!({
+        "SecretName": {
+            "type": "string",
+            "pattern": "^[a-z0-9_.-]{1,64}$",
+            "examples": ["telegram.bot_token"],
+        },
+        "SecretInfo": {
+            "type": "object",
+            "required": ["name", "updated_at"],
+            "properties": {
+                "name": schema_ref("SecretName"),
+                "updated_at": timestamp(),
+            },
+        },
+        "StoreSecretRequest": {
+            "type": "object",
+            "required": ["value"],
+            "properties": {
+                "value": { "type": "string", "minLength": 1, "writeOnly": true },
+            },
+        },
+    })
+}
+
+fn discovery_schemas() -> Value {
+    json!({
+        "PendingDevice": {
+            "type": "object",
+            "required": ["id", "integration", "address", "details", "first_seen", "last_seen"],
+            "properties": {
+                "id": uuid(),
+                "integration": {
+                    "type": "string",
+                    "description": "Integration that detected the device",
+                    "examples": ["ble", "mdns"],
+                },
+                "address": {
+                    "type": "string",
+                    "description": "MAC address, or `host:port`",
+                },
+                "name": nullable("string"),
+                "suggested_integration": {
+                    "type": ["string", "null"],
+                    "description": "Integration able to drive the device, when known",
+                },
+                "details": { "description": "Payload of the last detection event" },
+                "first_seen": timestamp(),
+                "last_seen": timestamp(),
+            },
+        },
+        "Adoption": {
+            "type": "object",
+            "description": "Every field falls back to what the detection reported",
+            "properties": {
+                "name": nullable("string"),
+                "integration": nullable("string"),
+                "unique_id": nullable("string"),
+                "area_id": { "type": ["string", "null"], "format": "uuid" },
+            },
+        },
+    })
+}
+
+fn audit_schemas() -> Value {
+    let summary = json!({
+        "type": ["object", "null"],
+        "description": "Fields that changed, null when the resource did not exist",
+    });
+    json!({
+        "AuditEntry": {
+            "type": "object",
+            "required": ["id", "timestamp", "actor", "action", "target_type", "target_id"],
+            "properties": {
+                "id": uuid(),
+                "timestamp": timestamp(),
+                "actor": {
+                    "type": "string",
+                    "description": "`api`, `dashboard`, `system`, `automation:<id>`, `integration:<name>` \
+                        or `token:<name>:<role>`",
+                    "examples": ["dashboard", "integration:mqtt", "token:ci:admin"],
+                },
+                "action": { "type": "string", "enum": ["create", "update", "delete"] },
+                "target_type": {
+                    "type": "string",
+                    "examples": ["entity", "device", "area", "automation", "scene", "group", "setting"],
+                },
+                "target_id": { "type": "string" },
+                "before": summary,
+                "after": summary,
+            },
+        },
+    })
+}
+
+fn entity_request_schemas() -> Value {
+    json!({
+        "CreateEntityRequest": {
+            "type": "object",
+            "required": ["device_id", "entity_id", "friendly_name"],
+            "properties": {
+                "device_id": uuid(),
+                "entity_id": { "type": "string" },
+                "friendly_name": { "type": "string" },
+            },
+        },
+        "UpdateStateRequest": {
+            "type": "object",
+            "description": "At least one of `state` and `value` is required",
+            "properties": {
+                "state": schema_ref("EntityState"),
+                "value": { "description": "New value of a number or select input helper" },
+                "expected_state": {
+                    "$ref": "#/components/schemas/EntityState",
+                    "description": "Only apply the update if the entity is currently in this state",
+                },
+            },
+        },
+        "RenameEntityRequest": {
+            "type": "object",
+            "required": ["entity_id"],
+            "properties": {
+                "entity_id": {
+                    "type": "string",
+                    "description": "New id, in the same domain as the current one",
+                    "examples": ["light.lounge"],
+                },
+            },
+        },
+        "StatesRequest": {
+            "type": "object",
+            "required": ["ids"],
+            "properties": { "ids": { "type": "array", "items": uuid() } },
+        },
+        "EntityStateSnapshot": {
+            "type": "object",
+            "required": ["id", "state", "attributes", "last_changed"],
+            "properties": {
+                "id": uuid(),
+                "state": schema_ref("EntityState"),
+                "attributes": map_of(&schema_ref("AttributeValue")),
+                "last_changed": timestamp(),
+            },
+        },
+        "ServiceCallRequest": {
+            "type": "object",
+            "required": ["service"],
+            "properties": {
+                "service": { "type": "string", "examples": ["turn_on"] },
+                "data": { "description": "Service parameters" },
+            },
+        },
+    })
+}
+
+fn request_schemas() -> Value {
+    json!({
+        "CreateDeviceRequest": {
+            "type": "object",
+            "required": ["name", "integration", "unique_id"],
+            "properties": {
+                "name": { "type": "string" },
+                "manufacturer": { "type": "string" },
+                "model": { "type": "string" },
+                "area_id": uuid(),
+                "integration": { "type": "string" },
+                "unique_id": { "type": "string" },
+                "via_device_id": uuid(),
+                "sw_version": { "type": "string" },
+                "hw_version": { "type": "string" },
+                "suggested_area": { "type": "string" },
+            },
+        },
+        "CreateAreaRequest": {
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" }, "parent_id": uuid() },
+        },
+        "CreateAutomationRequest": {
+            "type": "object",
+            "required": ["name", "trigger", "actions"],
+            "properties": {
+                "name": { "type": "string" },
+                "enabled": { "type": "boolean", "default": true },
+                "trigger": schema_ref("Trigger"),
+                "conditions": array_of("Condition"),
+                "actions": array_of("Action"),
+                "execution_mode": schema_ref("ExecutionMode"),
+            },
+        },
+        "UpdateAutomationRequest": {
+            "type": "object",
+            "required": ["name", "enabled", "trigger", "conditions", "actions"],
+            "properties": {
+                "name": { "type": "string" },
+                "enabled": { "type": "boolean" },
+                "trigger": schema_ref("Trigger"),
+                "conditions": array_of("Condition"),
+                "actions": array_of("Action"),
+                "execution_mode": {
+                    "$ref": "#/components/schemas/ExecutionMode",
+                    "description": "Defaults to the current execution mode",
+                },
+                "version": {
+                    "type": "integer",
+                    "description": "Version the update is based on; rejected with 409 when \
+                                    the automation changed since",
+                },
+            },
+        },
+        "JsonPatch": {
+            "type": "array",
+            "description": "RFC 6902 operations; `id`, `version` and `last_triggered` are \
+                            read-only",
+            "items": {
+                "type": "object",
+                "required": ["op", "path"],
+                "properties": {
+                    "op": {
+                        "type": "string",
+                        "enum": ["add", "remove", "replace", "move", "copy", "test"],
+                    },
+                    "path": { "type": "string", "examples": ["/enabled"] },
+                    "from": { "type": "string" },
+                    "value": {},
+                },
+            },
+        },
+        "SceneRequest": {
+            "type": "object",
+            "required": ["name", "members"],
+            "properties": { "name": { "type": "string" }, "members": array_of("SceneMember") },
+        },
+        "GroupKind": { "type": "string", "enum": ["light", "switch"] },
+        "Group": {
+            "type": "object",
+            "required": ["id", "name", "kind", "entity_id", "members"],
+            "properties": {
+                "id": uuid(),
+                "name": { "type": "string" },
+                "kind": schema_ref("GroupKind"),
+                "entity_id": uuid(),
+                "members": { "type": "array", "items": uuid() },
+            },
+        },
+        "GroupRequest": {
+            "type": "object",
+            "required": ["name", "members"],
+            "properties": {
+                "name": { "type": "string" },
+                "kind": schema_ref("GroupKind"),
+                "members": { "type": "array", "items": uuid() },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::audit::{Actor, AuditEntry};
+    use minihub_domain::automation::{Action, Automation, Condition, Trigger};
+    use minihub_domain::device::{Device, DeviceStatus, DeviceWithStatus};
+    use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::id::{DeviceId, EntityId};
+    use minihub_domain::pending_device::{Adoption, PendingDevice};
+
+    use super::*;
+
+    /// Assert that every field of `value` is a property of the schema `name`.
+    fn assert_described(name: &str, value: &impl serde::Serialize) {
+        let spec = spec();
+        let properties = &spec["components"]["schemas"][name]["properties"];
+        let value = serde_json::to_value(value).unwrap();
+        for field in value.as_object().unwrap().keys() {
+            assert!(
+                properties.get(field).is_some(),
+                "{name}.{field} is missing from the OpenAPI document"
+            );
+        }
+    }
+
+    #[test]
+    fn should_describe_every_entity_field() {
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::On)
+            .attribute("brightness", AttributeValue::Int(128))
+            .build()
+            .unwrap();
+
+        assert_described("Entity", &entity);
+    }
+
+    #[test]
+    fn should_describe_every_device_field() {
+        let device = Device::builder()
+            .name("Plug")
+            .integration("rest")
+            .unique_id("plug-1")
+            .build()
+            .unwrap();
+
+        assert_described("Device", &device);
+        assert_described(
+            "DeviceWithStatus",
+            &DeviceWithStatus {
+                device,
+                status: DeviceStatus::Online,
+            },
+        );
+    }
+
+    #[test]
+    fn should_describe_every_automation_and_event_field() {
+        let automation = Automation::builder()
+            .name("Lights on")
+            .trigger(Trigger::Manual)
+            .condition(Condition::TimeRange {
+                after: "18:00".to_string(),
+                before: "23:00".to_string(),
+            })
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap();
+        let event = Event::new(
+            EventType::StateChanged,
+            Some(EntityId::new()),
+            serde_json::json!({}),
+        );
+
+        assert_described("Automation", &automation);
+        assert_described("Event", &event);
+    }
+
+    #[test]
+    fn should_describe_every_pending_device_field() {
+        let event = Event::new(
+            EventType::DeviceDetected,
+            None,
+            serde_json::json!({ "integration": "ble", "mac": "A4:C1:38:00:00:01" }),
+        );
+
+        assert_described("PendingDevice", &PendingDevice::from_event(&event).unwrap());
+        assert_described("Adoption", &Adoption::default());
+    }
+
+    #[test]
+    fn should_describe_every_audit_entry_field() {
+        let entry = AuditEntry::deleted(Actor::Api, "area", "kitchen", None);
+
+        assert_described("AuditEntry", &entry);
+    }
+
+    #[test]
+    fn should_only_reference_defined_components() {
+        let spec = spec();
+        let text = spec.to_string();
+
+        for reference in text.split("\"$ref\":\"#/components/").skip(1) {
+            let path = reference.split('"').next().unwrap();
+            let (kind, name) = path.split_once('/').unwrap();
+            assert!(
+                spec["components"][kind].get(name).is_some(),
+                "dangling reference to {path}"
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Redaction of webhook ids from the responses of callers below `admin`.
+//!
+//! Webhook calls need no token, so a webhook id is the credential of the
+//! automations it triggers: a `viewer` reading it could run them with the
+//! rights of whoever wrote them. Ids are replaced by [`REDACTED`] in the
+//! automations, events and audit entries served to such callers.
+
+use serde_json::Value;
+
+use minihub_domain::audit::AuditEntry;
+use minihub_domain::automation::{Automation, Trigger};
+use minihub_domain::event::{Event, EventType};
+
+use crate::auth::CallerRole;
+
+/// Placeholder standing for a redacted webhook id.
+pub const REDACTED: &str = "***";
+
+/// Hide the webhook id of `automation` from `caller`.
+pub(crate) fn automation(caller: CallerRole, automation: &mut Automation) {
+    if caller.sees_secrets() {
+        return;
+    }
+    if let Trigger::Webhook { webhook_id } = &mut automation.trigger {
+        *webhook_id = REDACTED.to_string();
+    }
+}
+
+/// Hide the webhook id of a `webhook_received` `event` from `caller`.
+pub(crate) fn event(caller: CallerRole, event: &mut Event) {
+    if !caller.sees_secrets() && event.event_type == EventType::WebhookReceived {
+        redact_id(&mut event.data);
+    }
+}
+
+/// Hide the webhook ids of the automation triggers recorded in `entry`
+/// from `caller`.
+pub(crate) fn audit_entry(caller: CallerRole, entry: &mut AuditEntry) {
+    if caller.sees_secrets() || entry.target_type != "automation" {
+        return;
+    }
+    for fields in [&mut entry.before, &mut entry.after].into_iter().flatten() {
+        if let Some(trigger) = fields.get_mut("trigger") {
+            redact_id(trigger);
+        }
+    }
+}
+
+/// Replace the `webhook_id` of the object `value`, if any.
+fn redact_id(value: &mut Value) {
+    if let Some(id) = value.get_mut("webhook_id") {
+        *id = Value::from(REDACTED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::audit::Actor;
+    use minihub_domain::automation::Action;
+    use minihub_domain::role::Role;
+    use minihub_domain::webhook;
+
+    use super::*;
+
+    const WEBHOOK_ID: &str = "doorbell-7f3c9a1e52";
+
+    fn webhook_automation() -> Automation {
+        Automation::builder()
+            .name("Doorbell")
+            .trigger(Trigger::Webhook {
+                webhook_id: WEBHOOK_ID.to_string(),
+            })
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_hide_webhook_id_of_automation_below_admin() {
+        let mut seen_by_viewer = webhook_automation();
+        let mut seen_by_admin = webhook_automation();
+
+        automation(CallerRole(Role::Controller), &mut seen_by_viewer);
+        automation(CallerRole(Role::Admin), &mut seen_by_admin);
+
+        assert_eq!(
+            seen_by_viewer.trigger,
+            Trigger::Webhook {
+                webhook_id: REDACTED.to_string()
+            }
+        );
+        assert_eq!(seen_by_admin.trigger, webhook_automation().trigger);
+    }
+
+    #[test]
+    fn should_hide_webhook_id_of_received_event_below_admin() {
+        let mut received = webhook::received_event(WEBHOOK_ID, serde_json::json!({"ring": 1}));
+
+        event(CallerRole(Role::Viewer), &mut received);
+
+        assert_eq!(received.data["webhook_id"], REDACTED);
+        assert_eq!(received.data["body"], serde_json::json!({"ring": 1}));
+    }
+
+    #[test]
+    fn should_hide_webhook_id_of_audited_automation_below_admin() {
+        let mut entry = AuditEntry::updated(
+            Actor::Api,
+            "automation",
+            "1",
+            &serde_json::json!({"trigger": {"type": "webhook", "webhook_id": "old-7f3c9a1e52ab"}}),
+            &serde_json::json!({"trigger": {"type": "webhook", "webhook_id": WEBHOOK_ID}}),
+        )
+        .unwrap();
+
+        audit_entry(CallerRole(Role::Viewer), &mut entry);
+
+        assert_eq!(entry.before.unwrap()["trigger"]["webhook_id"], REDACTED);
+        assert_eq!(entry.after.unwrap()["trigger"]["webhook_id"], REDACTED);
+    }
+}
@@ -2,40 +2,172 @@
 
 use std::sync::Arc;
 
+use minihub_app::backup::BackupHandle;
+use minihub_app::config_reload::ReloadHandle;
 use minihub_app::event_bus::InProcessEventBus;
+use minihub_app::integration_manager::IntegrationManager;
+use minihub_app::metrics::Metrics;
 use minihub_app::ports::{
-    AreaRepository, AutomationRepository, DeviceRepository, EntityHistoryRepository,
-    EntityRepository, EventPublisher, EventStore,
+    AreaRepository, AuditRepository, AutomationRepository, AutomationRunRepository,
+    DeviceRepository, EnergyUsageRepository, EntityHistoryRepository, EntityRepository,
+    EventPublisher, EventStore, GroupRepository, ReportRepository, SceneRepository,
+    SecretsRepository, SettingsRepository, Storage,
 };
 use minihub_app::services::area_service::AreaService;
 use minihub_app::services::automation_service::AutomationService;
+use minihub_app::services::dashboard_service::DashboardService;
 use minihub_app::services::device_service::DeviceService;
+use minihub_app::services::discovery_service::DiscoveryService;
 use minihub_app::services::entity_service::EntityService;
+use minihub_app::services::group_service::GroupService;
+use minihub_app::services::home_mode_service::HomeModeService;
+use minihub_app::services::input_helper_service::InputHelperService;
+use minihub_app::services::scene_service::SceneService;
+use minihub_app::services::secrets_service::SecretsService;
+use minihub_app::services::settings_service::SettingsService;
+use minihub_app::services::webhook_service::WebhookService;
+use minihub_domain::time::{Timestamp, now};
+
+use crate::auth::AccessControl;
+use crate::limits::{RateLimit, RequestLimits};
+
+/// Version of the running build, reported by `GET /api/system/info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// Release version, e.g. `"0.1.1"`.
+    pub version: &'static str,
+    /// Commit the binary was built from, when `MINIHUB_GIT_HASH` was set at
+    /// compile time.
+    pub git_hash: Option<&'static str>,
+}
+
+impl Default for BuildInfo {
+    /// The version of this crate and the commit it was compiled from.
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: option_env!("MINIHUB_GIT_HASH"),
+        }
+    }
+}
+
+/// Repositories and event ports the HTTP handlers are built on.
+///
+/// Implemented once per backend by a marker type naming the concrete
+/// adapters, so [`AppState`] and the handlers take a single type parameter
+/// and keep static dispatch.
+pub trait Ports: Send + Sync + 'static {
+    /// Entity repository.
+    type Entities: EntityRepository + Send + Sync + 'static;
+    /// Device repository.
+    type Devices: DeviceRepository + Send + Sync + 'static;
+    /// Area repository.
+    type Areas: AreaRepository + Send + Sync + 'static;
+    /// Publisher of the domain events.
+    type Publisher: EventPublisher + Clone + Send + Sync + 'static;
+    /// Store of the persisted events, doubling as the storage health handle.
+    type EventStore: EventStore + Storage + Send + Sync + 'static;
+    /// Automation repository.
+    type Automations: AutomationRepository + Send + Sync + 'static;
+    /// Entity history repository.
+    type EntityHistory: EntityHistoryRepository + Send + Sync + 'static;
+    /// Automation run repository.
+    type AutomationRuns: AutomationRunRepository + Send + Sync + 'static;
+    /// Report repository.
+    type Reports: ReportRepository + Send + Sync + 'static;
+    /// Scene repository.
+    type Scenes: SceneRepository + Send + Sync + 'static;
+    /// Group repository.
+    type Groups: GroupRepository + Send + Sync + 'static;
+    /// Audit repository.
+    type Audit: AuditRepository + Send + Sync + 'static;
+    /// Settings repository.
+    type Settings: SettingsRepository + Send + Sync + 'static;
+    /// Energy usage repository.
+    type EnergyUsage: EnergyUsageRepository + Send + Sync + 'static;
+    /// Secrets repository.
+    type Secrets: SecretsRepository + Send + Sync + 'static;
+}
+
+/// [`GroupService`] over the repositories of the [`Ports`] `P`.
+pub type GroupServiceOf<P> = GroupService<
+    <P as Ports>::Groups,
+    <P as Ports>::Devices,
+    <P as Ports>::Entities,
+    <P as Ports>::Publisher,
+>;
 
 /// Application state shared across all axum handlers.
 ///
-/// Generic over the repository types, event publisher, event store,
-/// automation repository, and entity history repository to avoid dynamic dispatch.
+/// Generic over the [`Ports`] of the backend to avoid dynamic dispatch.
 /// `Clone` is implemented manually so the underlying types themselves do not
 /// need to be `Clone` — only the `Arc` wrappers are cloned.
-pub struct AppState<ER, DR, AR, EP, ES, AUR, EHR> {
+pub struct AppState<P: Ports> {
     /// Entity CRUD service.
-    pub entity_service: Arc<EntityService<ER, EP>>,
+    pub entity_service: Arc<EntityService<P::Entities, P::Publisher>>,
     /// Device CRUD service.
-    pub device_service: Arc<DeviceService<DR>>,
+    pub device_service: Arc<DeviceService<P::Devices>>,
     /// Area CRUD service.
-    pub area_service: Arc<AreaService<AR>>,
+    pub area_service: Arc<AreaService<P::Areas>>,
     /// Event store for querying persisted events.
-    pub event_store: Arc<ES>,
+    pub event_store: Arc<P::EventStore>,
     /// Automation CRUD service.
-    pub automation_service: Arc<AutomationService<AUR>>,
+    pub automation_service: Arc<AutomationService<P::Automations>>,
     /// Entity history repository for time-series queries.
-    pub entity_history_repo: Arc<EHR>,
+    pub entity_history_repo: Arc<P::EntityHistory>,
+    /// Automation run repository for execution log queries.
+    pub automation_run_repo: Arc<P::AutomationRuns>,
+    /// Report repository for aggregated read models.
+    pub report_repo: Arc<P::Reports>,
+    /// Scene CRUD and activation service.
+    pub scene_service: Arc<SceneService<P::Scenes, P::Publisher>>,
+    /// Group CRUD service.
+    pub group_service: Arc<GroupServiceOf<P>>,
+    /// Inbox of detected devices waiting to be adopted.
+    pub discovery_service: Arc<DiscoveryService<P::Devices>>,
+    /// Audit log of the write operations, queried at `GET /api/audit`.
+    pub audit_repo: Arc<P::Audit>,
+    /// Hub-wide settings, including the layout of the dashboard home page.
+    pub settings_repo: Arc<P::Settings>,
+    /// Hourly consumption of power and energy sensors.
+    pub energy_usage_repo: Arc<P::EnergyUsage>,
+    /// Encrypted credentials of integrations, managed without ever echoing
+    /// a value back.
+    pub secrets_service: Arc<SecretsService<P::Secrets>>,
     /// Event bus for real-time event subscriptions (SSE).
     pub event_bus: Arc<InProcessEventBus>,
+    /// Integrations known to the daemon and their startup status, reported
+    /// by `/api/integrations`.
+    pub integrations: IntegrationManager,
+    /// Whether `GET /api/docs` serves a Swagger UI page.
+    pub swagger_ui: bool,
+    /// Origins allowed to call the API from a browser, e.g. a dashboard
+    /// served by a dev server; `"*"` allows any. Empty keeps the browser's
+    /// same-origin policy.
+    pub cors_allowed_origins: Vec<String>,
+    /// Timeout, body size and concurrency bounds of the API requests.
+    pub request_limits: RequestLimits,
+    /// Budget of each webhook id on the public webhook route, spent apart
+    /// from the other API requests.
+    pub webhook_rate_limit: RateLimit,
+    /// Tokens and roles authorizing the API requests; every request is
+    /// served when no token is configured.
+    pub access_control: Arc<AccessControl>,
+    /// Reloads the daemon configuration on `POST /api/config/reload`;
+    /// the endpoint answers `503` when unset.
+    pub config_reload: Option<ReloadHandle>,
+    /// Writes a database snapshot on `POST /api/system/backup`; the
+    /// endpoint answers `503` when unset.
+    pub backup: Option<BackupHandle>,
+    /// Registry recording HTTP requests and exposed at `GET /metrics`.
+    pub metrics: Metrics,
+    /// Version reported by `GET /api/system/info`.
+    pub build_info: BuildInfo,
+    /// When the state was built, from which the uptime is reported.
+    pub started_at: Timestamp,
 }
 
-impl<ER, DR, AR, EP, ES, AUR, EHR> Clone for AppState<ER, DR, AR, EP, ES, AUR, EHR> {
+impl<P: Ports> Clone for AppState<P> {
     fn clone(&self) -> Self {
         Self {
             entity_service: Arc::clone(&self.entity_service),
@@ -44,39 +176,88 @@ impl<ER, DR, AR, EP, ES, AUR, EHR> Clone for AppState<ER, DR, AR, EP, ES, AUR, E
             event_store: Arc::clone(&self.event_store),
             automation_service: Arc::clone(&self.automation_service),
             entity_history_repo: Arc::clone(&self.entity_history_repo),
+            automation_run_repo: Arc::clone(&self.automation_run_repo),
+            report_repo: Arc::clone(&self.report_repo),
+            scene_service: Arc::clone(&self.scene_service),
+            group_service: Arc::clone(&self.group_service),
+            discovery_service: Arc::clone(&self.discovery_service),
+            audit_repo: Arc::clone(&self.audit_repo),
+            settings_repo: Arc::clone(&self.settings_repo),
+            energy_usage_repo: Arc::clone(&self.energy_usage_repo),
+            secrets_service: Arc::clone(&self.secrets_service),
             event_bus: Arc::clone(&self.event_bus),
+            integrations: self.integrations.clone(),
+            swagger_ui: self.swagger_ui,
+            cors_allowed_origins: self.cors_allowed_origins.clone(),
+            request_limits: self.request_limits,
+            webhook_rate_limit: self.webhook_rate_limit,
+            access_control: Arc::clone(&self.access_control),
+            config_reload: self.config_reload.clone(),
+            backup: self.backup.clone(),
+            metrics: self.metrics.clone(),
+            build_info: self.build_info.clone(),
+            started_at: self.started_at,
         }
     }
 }
 
-impl<ER, DR, AR, EP, ES, AUR, EHR> AppState<ER, DR, AR, EP, ES, AUR, EHR>
-where
-    ER: EntityRepository + Send + Sync + 'static,
-    DR: DeviceRepository + Send + Sync + 'static,
-    AR: AreaRepository + Send + Sync + 'static,
-    EP: EventPublisher + Send + Sync + 'static,
-    ES: EventStore + Send + Sync + 'static,
-    AUR: AutomationRepository + Send + Sync + 'static,
-    EHR: EntityHistoryRepository + Send + Sync + 'static,
-{
-    /// Create a new application state from service instances.
+impl<P: Ports> AppState<P> {
+    /// Create a new application state from service instances. The group
+    /// service is built on `group_repo` and the entity and device services.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        entity_service: EntityService<ER, EP>,
-        device_service: DeviceService<DR>,
-        area_service: AreaService<AR>,
-        event_store: ES,
-        automation_service: AutomationService<AUR>,
-        entity_history_repo: EHR,
+        entity_service: EntityService<P::Entities, P::Publisher>,
+        device_service: DeviceService<P::Devices>,
+        area_service: AreaService<P::Areas>,
+        event_store: P::EventStore,
+        automation_service: AutomationService<P::Automations>,
+        entity_history_repo: P::EntityHistory,
+        automation_run_repo: P::AutomationRuns,
+        report_repo: P::Reports,
+        scene_service: SceneService<P::Scenes, P::Publisher>,
+        group_repo: P::Groups,
+        audit_repo: P::Audit,
+        settings_repo: P::Settings,
+        energy_usage_repo: P::EnergyUsage,
+        secrets_repo: P::Secrets,
         event_bus: Arc<InProcessEventBus>,
     ) -> Self {
+        let entity_service = Arc::new(entity_service);
+        let device_service = Arc::new(device_service);
+        let group_service = GroupService::new(
+            group_repo,
+            Arc::clone(&device_service),
+            Arc::clone(&entity_service),
+        );
+        let discovery_service = DiscoveryService::new(Arc::clone(&device_service));
         Self {
-            entity_service: Arc::new(entity_service),
-            device_service: Arc::new(device_service),
+            entity_service,
+            device_service,
             area_service: Arc::new(area_service),
             event_store: Arc::new(event_store),
             automation_service: Arc::new(automation_service),
             entity_history_repo: Arc::new(entity_history_repo),
+            automation_run_repo: Arc::new(automation_run_repo),
+            report_repo: Arc::new(report_repo),
+            scene_service: Arc::new(scene_service),
+            group_service: Arc::new(group_service),
+            discovery_service: Arc::new(discovery_service),
+            audit_repo: Arc::new(audit_repo),
+            settings_repo: Arc::new(settings_repo),
+            energy_usage_repo: Arc::new(energy_usage_repo),
+            secrets_service: Arc::new(SecretsService::new(secrets_repo)),
             event_bus,
+            integrations: IntegrationManager::default(),
+            swagger_ui: false,
+            cors_allowed_origins: Vec::new(),
+            request_limits: RequestLimits::default(),
+            webhook_rate_limit: RateLimit::default(),
+            access_control: Arc::default(),
+            config_reload: None,
+            backup: None,
+            metrics: Metrics::default(),
+            build_info: BuildInfo::default(),
+            started_at: now(),
         }
     }
 
@@ -84,15 +265,25 @@ where
     ///
     /// Use this when services need to be shared with background tasks
     /// before constructing the HTTP state.
+    #[allow(clippy::too_many_arguments)]
     pub fn from_arcs(
-        entity_service: Arc<EntityService<ER, EP>>,
-        device_service: Arc<DeviceService<DR>>,
-        area_service: Arc<AreaService<AR>>,
-        event_store: Arc<ES>,
-        automation_service: Arc<AutomationService<AUR>>,
-        entity_history_repo: Arc<EHR>,
+        entity_service: Arc<EntityService<P::Entities, P::Publisher>>,
+        device_service: Arc<DeviceService<P::Devices>>,
+        area_service: Arc<AreaService<P::Areas>>,
+        event_store: Arc<P::EventStore>,
+        automation_service: Arc<AutomationService<P::Automations>>,
+        entity_history_repo: Arc<P::EntityHistory>,
+        automation_run_repo: Arc<P::AutomationRuns>,
+        report_repo: Arc<P::Reports>,
+        scene_service: Arc<SceneService<P::Scenes, P::Publisher>>,
+        group_service: Arc<GroupServiceOf<P>>,
+        audit_repo: Arc<P::Audit>,
+        settings_repo: Arc<P::Settings>,
+        energy_usage_repo: Arc<P::EnergyUsage>,
+        secrets_service: Arc<SecretsService<P::Secrets>>,
         event_bus: Arc<InProcessEventBus>,
     ) -> Self {
+        let discovery_service = Arc::new(DiscoveryService::new(Arc::clone(&device_service)));
         Self {
             entity_service,
             device_service,
@@ -100,7 +291,154 @@ where
             event_store,
             automation_service,
             entity_history_repo,
+            automation_run_repo,
+            report_repo,
+            scene_service,
+            group_service,
+            discovery_service,
+            audit_repo,
+            settings_repo,
+            energy_usage_repo,
+            secrets_service,
             event_bus,
+            integrations: IntegrationManager::default(),
+            swagger_ui: false,
+            cors_allowed_origins: Vec::new(),
+            request_limits: RequestLimits::default(),
+            webhook_rate_limit: RateLimit::default(),
+            access_control: Arc::default(),
+            config_reload: None,
+            backup: None,
+            metrics: Metrics::default(),
+            build_info: BuildInfo::default(),
+            started_at: now(),
         }
     }
+
+    /// Report the integrations tracked by `integrations` from
+    /// `GET /api/integrations`.
+    #[must_use]
+    pub fn with_integrations(mut self, integrations: IntegrationManager) -> Self {
+        self.integrations = integrations;
+        self
+    }
+
+    /// List and adopt the devices detected by `discovery_service`, shared
+    /// with the task feeding it detection events.
+    #[must_use]
+    pub fn with_discovery_service(
+        mut self,
+        discovery_service: Arc<DiscoveryService<P::Devices>>,
+    ) -> Self {
+        self.discovery_service = discovery_service;
+        self
+    }
+
+    /// Serve a Swagger UI page for the OpenAPI document at `GET /api/docs`.
+    #[must_use]
+    pub fn with_swagger_ui(mut self, enabled: bool) -> Self {
+        self.swagger_ui = enabled;
+        self
+    }
+
+    /// Allow browsers to call the API from `origins`, `"*"` allowing any.
+    #[must_use]
+    pub fn with_cors_allowed_origins(mut self, origins: Vec<String>) -> Self {
+        self.cors_allowed_origins = origins;
+        self
+    }
+
+    /// Bound every API request by `limits` instead of the defaults.
+    #[must_use]
+    pub fn with_request_limits(mut self, limits: RequestLimits) -> Self {
+        self.request_limits = limits;
+        self
+    }
+
+    /// Throttle each webhook id of the public webhook route by `limit`
+    /// instead of the default.
+    #[must_use]
+    pub fn with_webhook_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.webhook_rate_limit = limit;
+        self
+    }
+
+    /// Authorize every API request against the tokens and roles of `access`.
+    #[must_use]
+    pub fn with_access_control(mut self, access: AccessControl) -> Self {
+        self.access_control = Arc::new(access);
+        self
+    }
+
+    /// Record requests in, and expose at `GET /metrics`, the registry
+    /// shared with the rest of the daemon.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Report `build_info` from `GET /api/system/info`, e.g. the version of
+    /// the daemon rather than the one of this crate.
+    #[must_use]
+    pub fn with_build_info(mut self, build_info: BuildInfo) -> Self {
+        self.build_info = build_info;
+        self
+    }
+
+    /// Serve `POST /api/config/reload` by sending requests to `handle`.
+    #[must_use]
+    pub fn with_config_reload(mut self, handle: ReloadHandle) -> Self {
+        self.config_reload = Some(handle);
+        self
+    }
+
+    /// Serve `POST /api/system/backup` by sending requests to `handle`.
+    #[must_use]
+    pub fn with_backup(mut self, handle: BackupHandle) -> Self {
+        self.backup = Some(handle);
+        self
+    }
+
+    /// Home mode service sharing this state's device and entity services.
+    #[must_use]
+    pub fn home_mode_service(&self) -> HomeModeService<P::Devices, P::Entities, P::Publisher> {
+        HomeModeService::new(
+            Arc::clone(&self.device_service),
+            Arc::clone(&self.entity_service),
+        )
+    }
+
+    /// Input helper service sharing this state's device and entity services.
+    #[must_use]
+    pub fn input_helper_service(
+        &self,
+    ) -> InputHelperService<P::Devices, P::Entities, P::Publisher> {
+        InputHelperService::new(
+            Arc::clone(&self.device_service),
+            Arc::clone(&self.entity_service),
+        )
+    }
+
+    /// Webhook service sharing this state's automation service and the
+    /// publisher of entity events.
+    #[must_use]
+    pub fn webhook_service(&self) -> WebhookService<P::Automations, P::Publisher> {
+        WebhookService::new(
+            Arc::clone(&self.automation_service),
+            self.entity_service.publisher().clone(),
+        )
+    }
+
+    /// Dashboard service sharing this state's settings repository.
+    #[must_use]
+    pub fn dashboard_service(&self) -> DashboardService<Arc<P::Settings>> {
+        DashboardService::new(Arc::clone(&self.settings_repo))
+    }
+
+    /// Settings service sharing this state's settings repository.
+    #[must_use]
+    pub fn settings_service(&self) -> SettingsService<Arc<P::Settings>> {
+        SettingsService::new(Arc::clone(&self.settings_repo))
+    }
 }
@@ -0,0 +1,399 @@
+//! Browsing for services advertised on the local network.
+//!
+//! Responses to browse queries come in pieces: a PTR record names an
+//! instance of a service type, its SRV record gives the port and host, and
+//! A/AAAA records give the host's addresses. The [`Browser`] puts these
+//! together and reports every instance once it is resolved, and again when
+//! its address changes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+
+use minihub_domain::event::{Event, EventType};
+
+use crate::dns::{self, Message, Question, RecordData};
+
+/// A service instance resolved to an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedService {
+    /// Service type without the domain, e.g. `"_esphomelib._tcp"`.
+    pub service_type: String,
+    /// Instance name, e.g. `"Living Room"`.
+    pub name: String,
+    /// Host name from the SRV record, e.g. `"living-room.local"`.
+    pub host: String,
+    pub ip: IpAddr,
+    pub port: u16,
+    /// TXT record entries.
+    pub txt: BTreeMap<String, String>,
+}
+
+impl DetectedService {
+    /// Integration able to drive the device, if minihub has one.
+    #[must_use]
+    pub fn suggested_integration(&self) -> Option<&'static str> {
+        match self.service_type.as_str() {
+            "_esphomelib._tcp" => Some("esphome"),
+            "_shelly._tcp" => Some("shelly"),
+            _ => None,
+        }
+    }
+
+    /// Build the [`EventType::DeviceDetected`] event announcing the service.
+    #[must_use]
+    pub fn to_event(&self) -> Event {
+        Event::new(
+            EventType::DeviceDetected,
+            None,
+            serde_json::json!({
+                "integration": "mdns",
+                "suggested_integration": self.suggested_integration(),
+                "service_type": self.service_type,
+                "name": self.name,
+                "host": self.host,
+                "address": SocketAddr::new(self.ip, self.port).to_string(),
+                "port": self.port,
+                "txt": self.txt,
+            }),
+        )
+    }
+}
+
+/// What is known so far about one instance.
+#[derive(Debug)]
+struct Instance {
+    service_type: String,
+    name: String,
+    srv: Option<(u16, String)>,
+    txt: BTreeMap<String, String>,
+    /// Address the last response about the instance came from, used when
+    /// no A/AAAA record names its host.
+    source: IpAddr,
+}
+
+/// Resolves service instances of the browsed types out of mDNS responses.
+#[derive(Debug)]
+pub struct Browser {
+    service_types: Vec<String>,
+    /// Keyed by lowercased full instance name.
+    instances: HashMap<String, Instance>,
+    /// Keyed by lowercased host name.
+    hosts: HashMap<String, IpAddr>,
+    /// Last reported state, keyed like `instances`.
+    reported: HashMap<String, DetectedService>,
+}
+
+impl Browser {
+    /// Browse for the given fully qualified service types, e.g.
+    /// `"_hap._tcp.local"`.
+    #[must_use]
+    pub fn new(service_types: Vec<String>) -> Self {
+        Self {
+            service_types,
+            instances: HashMap::new(),
+            hosts: HashMap::new(),
+            reported: HashMap::new(),
+        }
+    }
+
+    /// Whether there is nothing to browse for.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.service_types.is_empty()
+    }
+
+    /// The query asking for every instance of the browsed types.
+    #[must_use]
+    pub fn query(&self) -> Message {
+        Message {
+            questions: self
+                .service_types
+                .iter()
+                .map(|service_type| Question {
+                    name: service_type.clone(),
+                    record_type: dns::TYPE_PTR,
+                })
+                .collect(),
+            ..Message::default()
+        }
+    }
+
+    /// Apply a response received from `source`, returning the instances
+    /// that got resolved or changed address.
+    pub fn process(&mut self, message: &Message, source: IpAddr) -> Vec<DetectedService> {
+        // PTR records first, since SRV and TXT records of an instance may
+        // come before the record naming it.
+        for record in &message.records {
+            let RecordData::Ptr(target) = &record.data else {
+                continue;
+            };
+            let Some(service_type) = self
+                .service_types
+                .iter()
+                .find(|service_type| dns::same_name(&record.name, service_type))
+            else {
+                continue;
+            };
+            let key = target.to_ascii_lowercase();
+            if record.ttl == 0 {
+                // Goodbye: forget the instance so it is reported again when
+                // it comes back.
+                self.instances.remove(&key);
+                self.reported.remove(&key);
+                continue;
+            }
+            self.instances
+                .entry(key)
+                .or_insert_with(|| Instance {
+                    service_type: service_type.clone(),
+                    name: target.clone(),
+                    srv: None,
+                    txt: BTreeMap::new(),
+                    source,
+                })
+                .source = source;
+        }
+
+        for record in &message.records {
+            match &record.data {
+                RecordData::A(ip) => {
+                    self.hosts
+                        .insert(record.name.to_ascii_lowercase(), IpAddr::V4(*ip));
+                }
+                RecordData::Aaaa(ip) => {
+                    // Prefer IPv4 addresses, which every integration can use.
+                    self.hosts
+                        .entry(record.name.to_ascii_lowercase())
+                        .or_insert(IpAddr::V6(*ip));
+                }
+                RecordData::Srv { port, target } => {
+                    if let Some(instance) =
+                        self.instances.get_mut(&record.name.to_ascii_lowercase())
+                    {
+                        instance.srv = Some((*port, target.clone()));
+                    }
+                }
+                RecordData::Txt(entries) => {
+                    if let Some(instance) =
+                        self.instances.get_mut(&record.name.to_ascii_lowercase())
+                    {
+                        instance.txt = entries
+                            .iter()
+                            .map(|entry| match entry.split_once('=') {
+                                Some((key, value)) => (key.to_string(), value.to_string()),
+                                None => (entry.clone(), String::new()),
+                            })
+                            .collect();
+                    }
+                }
+                RecordData::Ptr(_) | RecordData::Other(_) => {}
+            }
+        }
+
+        self.resolve()
+    }
+
+    /// Report the resolved instances whose state differs from the last
+    /// report.
+    fn resolve(&mut self) -> Vec<DetectedService> {
+        let mut detected = Vec::new();
+        for (key, instance) in &self.instances {
+            let Some((port, host)) = &instance.srv else {
+                continue;
+            };
+            let ip = self
+                .hosts
+                .get(&host.to_ascii_lowercase())
+                .copied()
+                .unwrap_or(instance.source);
+            let service_type = instance
+                .service_type
+                .trim_end_matches('.')
+                .trim_end_matches(".local")
+                .to_string();
+            let name = instance
+                .name
+                .strip_suffix(instance.service_type.as_str())
+                .and_then(|name| name.strip_suffix('.'))
+                .unwrap_or(&instance.name)
+                .to_string();
+            let service = DetectedService {
+                service_type,
+                name,
+                host: host.clone(),
+                ip,
+                port: *port,
+                txt: instance.txt.clone(),
+            };
+            if self.reported.get(key) != Some(&service) {
+                self.reported.insert(key.clone(), service.clone());
+                detected.push(service);
+            }
+        }
+        detected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::dns::Record;
+
+    use super::*;
+
+    const SOURCE: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 50));
+
+    fn record(name: &str, ttl: u32, data: RecordData) -> Record {
+        Record {
+            name: name.to_string(),
+            ttl,
+            data,
+        }
+    }
+
+    fn response(records: Vec<Record>) -> Message {
+        Message {
+            response: true,
+            records,
+            ..Message::default()
+        }
+    }
+
+    fn esphome_response(ip: Ipv4Addr) -> Message {
+        response(vec![
+            record(
+                "_esphomelib._tcp.local",
+                4500,
+                RecordData::Ptr("Living Room._esphomelib._tcp.local".to_string()),
+            ),
+            record(
+                "Living Room._esphomelib._tcp.local",
+                120,
+                RecordData::Srv {
+                    port: 6053,
+                    target: "living-room.local".to_string(),
+                },
+            ),
+            record(
+                "Living Room._esphomelib._tcp.local",
+                4500,
+                RecordData::Txt(vec!["mac=aabbccddeeff".to_string()]),
+            ),
+            record("living-room.local", 120, RecordData::A(ip)),
+        ])
+    }
+
+    fn browser() -> Browser {
+        Browser::new(vec![
+            "_esphomelib._tcp.local".to_string(),
+            "_hap._tcp.local".to_string(),
+        ])
+    }
+
+    #[test]
+    fn should_ask_for_every_browsed_type() {
+        let query = browser().query();
+
+        assert!(!query.response);
+        assert_eq!(query.questions.len(), 2);
+        assert_eq!(query.questions[1].name, "_hap._tcp.local");
+        assert_eq!(query.questions[1].record_type, dns::TYPE_PTR);
+    }
+
+    #[test]
+    fn should_detect_service_when_response_resolves_it() {
+        let mut browser = browser();
+
+        let detected = browser.process(&esphome_response(Ipv4Addr::new(192, 168, 1, 20)), SOURCE);
+
+        assert_eq!(
+            detected,
+            vec![DetectedService {
+                service_type: "_esphomelib._tcp".to_string(),
+                name: "Living Room".to_string(),
+                host: "living-room.local".to_string(),
+                ip: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)),
+                port: 6053,
+                txt: BTreeMap::from([("mac".to_string(), "aabbccddeeff".to_string())]),
+            }]
+        );
+        assert_eq!(detected[0].suggested_integration(), Some("esphome"));
+    }
+
+    #[test]
+    fn should_report_service_once_until_its_address_changes() {
+        let mut browser = browser();
+        browser.process(&esphome_response(Ipv4Addr::new(192, 168, 1, 20)), SOURCE);
+
+        let repeated = browser.process(&esphome_response(Ipv4Addr::new(192, 168, 1, 20)), SOURCE);
+        let moved = browser.process(&esphome_response(Ipv4Addr::new(192, 168, 1, 21)), SOURCE);
+
+        assert!(repeated.is_empty());
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].ip, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 21)));
+    }
+
+    #[test]
+    fn should_fall_back_to_source_address_when_host_is_unresolved() {
+        let mut browser = browser();
+        let mut message = esphome_response(Ipv4Addr::new(192, 168, 1, 20));
+        message.records.pop();
+
+        let detected = browser.process(&message, SOURCE);
+
+        assert_eq!(detected[0].ip, SOURCE);
+    }
+
+    #[test]
+    fn should_ignore_services_of_other_types() {
+        let mut browser = browser();
+        let message = response(vec![
+            record(
+                "_http._tcp.local",
+                4500,
+                RecordData::Ptr("Printer._http._tcp.local".to_string()),
+            ),
+            record(
+                "Printer._http._tcp.local",
+                120,
+                RecordData::Srv {
+                    port: 80,
+                    target: "printer.local".to_string(),
+                },
+            ),
+        ]);
+
+        assert!(browser.process(&message, SOURCE).is_empty());
+    }
+
+    #[test]
+    fn should_report_service_again_after_goodbye() {
+        let mut browser = browser();
+        let ip = Ipv4Addr::new(192, 168, 1, 20);
+        browser.process(&esphome_response(ip), SOURCE);
+        let goodbye = response(vec![record(
+            "_esphomelib._tcp.local",
+            0,
+            RecordData::Ptr("Living Room._esphomelib._tcp.local".to_string()),
+        )]);
+
+        assert!(browser.process(&goodbye, SOURCE).is_empty());
+        assert_eq!(browser.process(&esphome_response(ip), SOURCE).len(), 1);
+    }
+
+    #[test]
+    fn should_describe_service_in_device_detected_event() {
+        let mut browser = browser();
+        let detected = browser.process(&esphome_response(Ipv4Addr::new(192, 168, 1, 20)), SOURCE);
+
+        let event = detected[0].to_event();
+
+        assert_eq!(event.event_type, EventType::DeviceDetected);
+        assert_eq!(event.data["integration"], "mdns");
+        assert_eq!(event.data["suggested_integration"], "esphome");
+        assert_eq!(event.data["address"], "192.168.1.20:6053");
+        assert_eq!(event.data["name"], "Living Room");
+        assert_eq!(event.data["txt"]["mac"], "aabbccddeeff");
+    }
+}
@@ -0,0 +1,73 @@
+//! mDNS integration configuration.
+
+use std::net::Ipv4Addr;
+
+use serde::Deserialize;
+
+/// Configuration for the mDNS integration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MdnsConfig {
+    /// Announce the HTTP API as a `_minihub._tcp` service.
+    pub announce: bool,
+    /// Instance name of the announced service, also used as its host name
+    /// (`{instance_name}.local`). Must not contain dots.
+    pub instance_name: String,
+    /// Port of the HTTP API to announce.
+    pub port: u16,
+    /// Address to announce; the address of the interface routing multicast
+    /// traffic when unset.
+    pub address: Option<Ipv4Addr>,
+    /// Service types to browse for, e.g. `"_esphomelib._tcp.local"`.
+    pub service_types: Vec<String>,
+    /// Delay between two browse queries, in seconds.
+    pub browse_interval_secs: u64,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            announce: true,
+            instance_name: "minihub".to_string(),
+            port: 8080,
+            address: None,
+            service_types: vec![
+                "_esphomelib._tcp.local".to_string(),
+                "_hap._tcp.local".to_string(),
+                "_shelly._tcp.local".to_string(),
+            ],
+            browse_interval_secs: 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_sensible_defaults() {
+        let config = MdnsConfig::default();
+        assert!(config.announce);
+        assert_eq!(config.instance_name, "minihub");
+        assert!(config.address.is_none());
+        assert_eq!(config.service_types.len(), 3);
+        assert_eq!(config.browse_interval_secs, 60);
+    }
+
+    #[test]
+    fn should_deserialize_from_toml() {
+        let toml = r#"
+            announce = false
+            address = "192.168.1.10"
+            service_types = ["_http._tcp.local"]
+        "#;
+
+        let config: MdnsConfig = toml::from_str(toml).unwrap();
+
+        assert!(!config.announce);
+        assert_eq!(config.address, Some(Ipv4Addr::new(192, 168, 1, 10)));
+        assert_eq!(config.service_types, vec!["_http._tcp.local"]);
+        assert_eq!(config.instance_name, "minihub");
+    }
+}
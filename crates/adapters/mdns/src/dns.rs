@@ -0,0 +1,471 @@
+//! Minimal DNS message codec covering what mDNS service discovery needs
+//! (RFC 1035, RFC 6762, RFC 6763): questions and PTR, SRV, TXT, A and AAAA
+//! records.
+//!
+//! Names are decoded with compression support but always encoded in full,
+//! which every resolver accepts.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::error::MdnsError;
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_TXT: u16 = 16;
+pub const TYPE_AAAA: u16 = 28;
+pub const TYPE_SRV: u16 = 33;
+pub const TYPE_ANY: u16 = 255;
+
+const CLASS_IN: u16 = 1;
+/// Top bit of the class field: "unicast response" in questions, "cache
+/// flush" in records.
+const CLASS_FLAG: u16 = 0x8000;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+/// Compression pointers followed while reading one name before it is
+/// considered a loop.
+const MAX_POINTERS: usize = 32;
+const MAX_LABEL_LEN: usize = 63;
+
+/// Whether two domain names are equal, ignoring ASCII case and a trailing
+/// dot.
+#[must_use]
+pub fn same_name(left: &str, right: &str) -> bool {
+    left.trim_end_matches('.')
+        .eq_ignore_ascii_case(right.trim_end_matches('.'))
+}
+
+/// A DNS query or response.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Message {
+    pub id: u16,
+    pub response: bool,
+    pub questions: Vec<Question>,
+    /// Records of the answer, authority and additional sections. They are
+    /// all encoded as answers.
+    pub records: Vec<Record>,
+}
+
+/// A question asking for the records of `record_type` named `name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Question {
+    pub name: String,
+    pub record_type: u16,
+}
+
+/// A resource record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+/// Payload of a [`Record`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordData {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Ptr(String),
+    Srv {
+        port: u16,
+        target: String,
+    },
+    /// `key=value` strings.
+    Txt(Vec<String>),
+    /// A record type this codec does not interpret.
+    Other(u16),
+}
+
+impl RecordData {
+    fn record_type(&self) -> u16 {
+        match self {
+            Self::A(_) => TYPE_A,
+            Self::Aaaa(_) => TYPE_AAAA,
+            Self::Ptr(_) => TYPE_PTR,
+            Self::Srv { .. } => TYPE_SRV,
+            Self::Txt(_) => TYPE_TXT,
+            Self::Other(record_type) => *record_type,
+        }
+    }
+
+    /// Unique records replace cached ones; PTR records are shared between
+    /// every instance of a service.
+    fn is_unique(&self) -> bool {
+        !matches!(self, Self::Ptr(_))
+    }
+}
+
+impl Message {
+    /// Decode a packet received from the network.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MdnsError::Malformed`] when the packet is truncated or a
+    /// name cannot be read.
+    pub fn decode(packet: &[u8]) -> Result<Self, MdnsError> {
+        let mut reader = Reader { packet, pos: 0 };
+        let id = reader.u16()?;
+        let flags = reader.u16()?;
+        let question_count = reader.u16()?;
+        let mut record_count = 0;
+        for _ in 0..3 {
+            record_count += usize::from(reader.u16()?);
+        }
+
+        let mut questions = Vec::with_capacity(usize::from(question_count));
+        for _ in 0..question_count {
+            let name = reader.name()?;
+            let record_type = reader.u16()?;
+            reader.u16()?;
+            questions.push(Question { name, record_type });
+        }
+
+        let mut records = Vec::with_capacity(record_count.min(64));
+        for _ in 0..record_count {
+            records.push(reader.record()?);
+        }
+
+        Ok(Self {
+            id,
+            response: flags & FLAG_RESPONSE != 0,
+            questions,
+            records,
+        })
+    }
+
+    /// Encode the message for sending.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(512);
+        put_u16(&mut buf, self.id);
+        put_u16(
+            &mut buf,
+            if self.response {
+                FLAG_RESPONSE | FLAG_AUTHORITATIVE
+            } else {
+                0
+            },
+        );
+        put_u16(&mut buf, count(self.questions.len()));
+        put_u16(&mut buf, count(self.records.len()));
+        put_u16(&mut buf, 0);
+        put_u16(&mut buf, 0);
+
+        for question in &self.questions {
+            put_name(&mut buf, &question.name);
+            put_u16(&mut buf, question.record_type);
+            put_u16(&mut buf, CLASS_IN);
+        }
+        for record in &self.records {
+            put_record(&mut buf, record);
+        }
+        buf
+    }
+}
+
+fn count(len: usize) -> u16 {
+    u16::try_from(len).unwrap_or(u16::MAX)
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_name(buf: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let bytes = &label.as_bytes()[..label.len().min(MAX_LABEL_LEN)];
+        buf.push(u8::try_from(bytes.len()).unwrap_or(u8::MAX));
+        buf.extend_from_slice(bytes);
+    }
+    buf.push(0);
+}
+
+fn put_record(buf: &mut Vec<u8>, record: &Record) {
+    put_name(buf, &record.name);
+    put_u16(buf, record.data.record_type());
+    put_u16(
+        buf,
+        if record.data.is_unique() {
+            CLASS_IN | CLASS_FLAG
+        } else {
+            CLASS_IN
+        },
+    );
+    buf.extend_from_slice(&record.ttl.to_be_bytes());
+
+    let length_at = buf.len();
+    put_u16(buf, 0);
+    match &record.data {
+        RecordData::A(ip) => buf.extend_from_slice(&ip.octets()),
+        RecordData::Aaaa(ip) => buf.extend_from_slice(&ip.octets()),
+        RecordData::Ptr(target) => put_name(buf, target),
+        RecordData::Srv { port, target } => {
+            put_u16(buf, 0);
+            put_u16(buf, 0);
+            put_u16(buf, *port);
+            put_name(buf, target);
+        }
+        RecordData::Txt(entries) => {
+            for entry in entries {
+                let bytes = &entry.as_bytes()[..entry.len().min(usize::from(u8::MAX))];
+                buf.push(u8::try_from(bytes.len()).unwrap_or(u8::MAX));
+                buf.extend_from_slice(bytes);
+            }
+            if entries.is_empty() {
+                buf.push(0);
+            }
+        }
+        RecordData::Other(_) => {}
+    }
+    let length = count(buf.len() - length_at - 2).to_be_bytes();
+    buf[length_at..length_at + 2].copy_from_slice(&length);
+}
+
+/// Cursor over a received packet. Names may point anywhere in `packet`.
+struct Reader<'a> {
+    packet: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], MdnsError> {
+        let bytes = self
+            .packet
+            .get(self.pos..self.pos + len)
+            .ok_or(MdnsError::Malformed("truncated packet"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, MdnsError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, MdnsError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn name(&mut self) -> Result<String, MdnsError> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut pos = self.pos;
+        let mut resume_at = None;
+        let mut pointers = 0;
+        loop {
+            let len = *self
+                .packet
+                .get(pos)
+                .ok_or(MdnsError::Malformed("truncated name"))?;
+            match len {
+                0 => {
+                    pos += 1;
+                    break;
+                }
+                len if len & 0xC0 == 0xC0 => {
+                    let low = *self
+                        .packet
+                        .get(pos + 1)
+                        .ok_or(MdnsError::Malformed("truncated name"))?;
+                    resume_at.get_or_insert(pos + 2);
+                    pointers += 1;
+                    if pointers > MAX_POINTERS {
+                        return Err(MdnsError::Malformed("name compression loop"));
+                    }
+                    pos = usize::from(u16::from_be_bytes([len & 0x3F, low]));
+                }
+                len if len & 0xC0 != 0 => {
+                    return Err(MdnsError::Malformed("unsupported label type"));
+                }
+                len => {
+                    let start = pos + 1;
+                    let end = start + usize::from(len);
+                    let label = self
+                        .packet
+                        .get(start..end)
+                        .ok_or(MdnsError::Malformed("truncated name"))?;
+                    labels.push(String::from_utf8_lossy(label).into_owned());
+                    pos = end;
+                }
+            }
+        }
+        self.pos = resume_at.unwrap_or(pos);
+        Ok(labels.join("."))
+    }
+
+    fn record(&mut self) -> Result<Record, MdnsError> {
+        let name = self.name()?;
+        let record_type = self.u16()?;
+        self.u16()?;
+        let ttl = self.u32()?;
+        let length = usize::from(self.u16()?);
+        let start = self.pos;
+        let end = start + length;
+        if end > self.packet.len() {
+            return Err(MdnsError::Malformed("truncated record"));
+        }
+
+        let data = match record_type {
+            TYPE_A if length == 4 => {
+                let bytes = self.take(4)?;
+                RecordData::A(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]))
+            }
+            TYPE_AAAA if length == 16 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(self.take(16)?);
+                RecordData::Aaaa(Ipv6Addr::from(octets))
+            }
+            TYPE_PTR => RecordData::Ptr(self.name()?),
+            TYPE_SRV => {
+                self.u16()?;
+                self.u16()?;
+                let port = self.u16()?;
+                let target = self.name()?;
+                RecordData::Srv { port, target }
+            }
+            TYPE_TXT => {
+                let mut entries = Vec::new();
+                while self.pos < end {
+                    let len = usize::from(self.take(1)?[0]);
+                    let entry = self.take(len)?;
+                    if !entry.is_empty() {
+                        entries.push(String::from_utf8_lossy(entry).into_owned());
+                    }
+                }
+                RecordData::Txt(entries)
+            }
+            other => RecordData::Other(other),
+        };
+        if self.pos > end {
+            return Err(MdnsError::Malformed("record overflows its length"));
+        }
+        self.pos = end;
+        Ok(Record { name, ttl, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, data: RecordData) -> Record {
+        Record {
+            name: name.to_string(),
+            ttl: 120,
+            data,
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_query() {
+        let message = Message {
+            id: 0,
+            response: false,
+            questions: vec![Question {
+                name: "_esphomelib._tcp.local".to_string(),
+                record_type: TYPE_PTR,
+            }],
+            records: vec![],
+        };
+
+        let decoded = Message::decode(&message.encode()).unwrap();
+
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn should_roundtrip_response_with_every_record_type() {
+        let message = Message {
+            id: 7,
+            response: true,
+            questions: vec![],
+            records: vec![
+                record(
+                    "_hap._tcp.local",
+                    RecordData::Ptr("Bridge._hap._tcp.local".to_string()),
+                ),
+                record(
+                    "Bridge._hap._tcp.local",
+                    RecordData::Srv {
+                        port: 51826,
+                        target: "bridge.local".to_string(),
+                    },
+                ),
+                record(
+                    "Bridge._hap._tcp.local",
+                    RecordData::Txt(vec!["md=Bridge".to_string(), "sf=1".to_string()]),
+                ),
+                record("bridge.local", RecordData::A(Ipv4Addr::new(192, 168, 1, 5))),
+                record("bridge.local", RecordData::Aaaa(Ipv6Addr::LOCALHOST)),
+            ],
+        };
+
+        let decoded = Message::decode(&message.encode()).unwrap();
+
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn should_decode_compressed_names() {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 2, 0, 0, 0, 0];
+        // Offset 12: "_hap._tcp.local", the name of a PTR record whose
+        // target points back to it.
+        put_name(&mut packet, "_hap._tcp.local");
+        packet.extend_from_slice(&[0, 12, 0, 1, 0, 0, 0, 120, 0, 9, 6]);
+        packet.extend_from_slice(b"Bridge");
+        packet.extend_from_slice(&[0xC0, 12]);
+        // Offset 39: an SRV record named by a pointer to the PTR target,
+        // with a target pointing to the "local" label at offset 22.
+        packet.extend_from_slice(&[0xC0, 39, 0, 33, 0x80, 1, 0, 0, 0, 120, 0, 15]);
+        packet.extend_from_slice(&[0, 0, 0, 0, 0x1F, 0x90, 6]);
+        packet.extend_from_slice(b"bridge");
+        packet.extend_from_slice(&[0xC0, 22]);
+
+        let message = Message::decode(&packet).unwrap();
+
+        assert_eq!(
+            message.records[0].data,
+            RecordData::Ptr("Bridge._hap._tcp.local".to_string())
+        );
+        assert_eq!(message.records[1].name, "Bridge._hap._tcp.local");
+        assert_eq!(
+            message.records[1].data,
+            RecordData::Srv {
+                port: 8080,
+                target: "bridge.local".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_name_compression_loop() {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        packet.extend_from_slice(&[0xC0, 12, 0, 12, 0, 1]);
+
+        let result = Message::decode(&packet);
+
+        assert!(matches!(result, Err(MdnsError::Malformed(_))));
+    }
+
+    #[test]
+    fn should_reject_truncated_packet() {
+        let message = Message {
+            id: 0,
+            response: true,
+            questions: vec![],
+            records: vec![record("minihub.local", RecordData::A(Ipv4Addr::LOCALHOST))],
+        };
+        let packet = message.encode();
+
+        let result = Message::decode(&packet[..packet.len() - 2]);
+
+        assert!(matches!(result, Err(MdnsError::Malformed(_))));
+    }
+
+    #[test]
+    fn should_compare_names_ignoring_case_and_trailing_dot() {
+        assert!(same_name("_HAP._tcp.local.", "_hap._tcp.local"));
+        assert!(!same_name("_hap._tcp.local", "_hap._udp.local"));
+    }
+}
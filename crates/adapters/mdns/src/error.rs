@@ -0,0 +1,38 @@
+//! mDNS adapter error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the mDNS adapter.
+#[derive(Debug, thiserror::Error)]
+pub enum MdnsError {
+    /// Opening, reading from or writing to the multicast socket failed.
+    #[error("mDNS socket error")]
+    Io(#[source] std::io::Error),
+
+    /// A packet is not a well-formed DNS message.
+    #[error("malformed DNS message: {0}")]
+    Malformed(&'static str),
+}
+
+impl From<MdnsError> for MiniHubError {
+    fn from(err: MdnsError) -> Self {
+        MiniHubError::Storage(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_malformed_error() {
+        let err = MdnsError::Malformed("truncated header");
+        assert_eq!(err.to_string(), "malformed DNS message: truncated header");
+    }
+
+    #[test]
+    fn should_convert_io_error_to_storage_error() {
+        let err: MiniHubError = MdnsError::Io(std::io::ErrorKind::AddrInUse.into()).into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+}
@@ -0,0 +1,282 @@
+//! # minihub-adapter-mdns
+//!
+//! mDNS adapter — makes minihub and the devices around it discoverable on
+//! the local network (multicast DNS on `224.0.0.251:5353`).
+//!
+//! ## How it works
+//!
+//! A single background task owns the multicast socket and:
+//!
+//! 1. announces the HTTP API as a `_minihub._tcp` service, then answers
+//!    queries about it, so clients can find the hub without being told its
+//!    address ([`MdnsConfig::announce`]),
+//! 2. sends a browse query for every configured service type
+//!    (`_esphomelib._tcp`, `_hap._tcp`, Shelly's `_shelly._tcp` by default)
+//!    every [`MdnsConfig::browse_interval_secs`],
+//! 3. resolves the answers to an address and publishes one
+//!    [`DeviceDetected`](minihub_domain::event::EventType::DeviceDetected)
+//!    event per instance found, and again when its address changes.
+//!
+//! Detected devices are not registered: the events let users see what can
+//! be configured — with the integration able to drive it, when there is
+//! one — before enabling that integration.
+//!
+//! The socket is bound with address and port reuse, so the adapter can run
+//! next to the system's mDNS daemon. Only IPv4 is supported.
+//!
+//! ## Dependency rule
+//!
+//! Same as other adapters: depends on `minihub-app` and `minihub-domain`.
+
+mod browser;
+mod config;
+mod dns;
+mod error;
+mod responder;
+
+pub use browser::DetectedService;
+pub use config::MdnsConfig;
+pub use error::MdnsError;
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+use minihub_app::ports::integration::{Integration, IntegrationContext};
+use minihub_domain::entity::Entity;
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::id::EntityId;
+
+use crate::browser::Browser;
+use crate::dns::Message;
+use crate::responder::Responder;
+
+/// The mDNS multicast group.
+const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+
+/// The mDNS port.
+const MDNS_PORT: u16 = 5353;
+
+/// Largest packet read from the socket.
+const MAX_PACKET_LEN: usize = 9000;
+
+/// mDNS integration.
+///
+/// Announces minihub and reports the devices advertising the configured
+/// service types.
+pub struct MdnsIntegration {
+    config: MdnsConfig,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MdnsIntegration {
+    /// Create a new mDNS integration with the given configuration.
+    #[must_use]
+    pub fn new(config: MdnsConfig) -> Self {
+        Self {
+            config,
+            handle: None,
+        }
+    }
+
+    /// The responder announcing minihub, unless announcing is disabled or
+    /// no address can be announced.
+    fn responder(&self) -> Option<Responder> {
+        if !self.config.announce {
+            return None;
+        }
+        let Some(ip) = self.config.address.or_else(local_ipv4) else {
+            tracing::warn!("no local IPv4 address found, minihub will not be announced");
+            return None;
+        };
+        tracing::info!(%ip, port = self.config.port, "announcing minihub over mDNS");
+        Some(Responder::new(
+            &self.config.instance_name,
+            ip,
+            self.config.port,
+        ))
+    }
+}
+
+impl Integration for MdnsIntegration {
+    fn name(&self) -> &'static str {
+        "mdns"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        if !self.config.announce && self.config.service_types.is_empty() {
+            tracing::warn!("mDNS announcing is disabled and no service type is browsed");
+        }
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        let socket = open_socket().map_err(MdnsError::Io)?;
+        let responder = self.responder();
+        let browser = Browser::new(self.config.service_types.clone());
+        let interval = Duration::from_secs(self.config.browse_interval_secs.max(1));
+        self.handle = Some(tokio::spawn(run(socket, responder, browser, interval, ctx)));
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        _service: &str,
+        _data: serde_json::Value,
+    ) -> Result<Entity, MiniHubError> {
+        Err(NotFoundError {
+            entity: "MdnsService",
+            id: entity_id.to_string(),
+        }
+        .into())
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+            tracing::debug!("mDNS task aborted");
+        }
+        Ok(())
+    }
+}
+
+/// Bind the mDNS port, sharing it with other responders, and join the
+/// multicast group.
+fn open_socket() -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+    socket.join_multicast_v4(&MDNS_GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// Address of the interface routing multicast traffic. Connecting a UDP
+/// socket sends nothing, it only picks the route.
+fn local_ipv4() -> Option<Ipv4Addr> {
+    let socket = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((MDNS_GROUP, MDNS_PORT)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+        _ => None,
+    }
+}
+
+async fn send(socket: &UdpSocket, message: &Message, target: SocketAddr) {
+    if let Err(err) = socket.send_to(&message.encode(), target).await {
+        tracing::warn!(%err, %target, "failed to send mDNS message");
+    }
+}
+
+/// Background task: announce, browse periodically, answer queries and
+/// report detected services.
+async fn run(
+    socket: UdpSocket,
+    responder: Option<Responder>,
+    mut browser: Browser,
+    interval: Duration,
+    ctx: impl IntegrationContext,
+) {
+    let group = SocketAddr::from((MDNS_GROUP, MDNS_PORT));
+    if let Some(responder) = &responder {
+        send(&socket, &responder.announcement(), group).await;
+    }
+
+    let mut ticker = tokio::time::interval(interval);
+    let mut buf = vec![0; MAX_PACKET_LEN];
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if !browser.is_empty() {
+                    send(&socket, &browser.query(), group).await;
+                }
+            }
+            received = socket.recv_from(&mut buf) => {
+                let (len, source) = match received {
+                    Ok(received) => received,
+                    Err(err) => {
+                        tracing::warn!(%err, "failed to read mDNS packet");
+                        continue;
+                    }
+                };
+                let message = match Message::decode(&buf[..len]) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        tracing::debug!(%err, %source, "ignoring mDNS packet");
+                        continue;
+                    }
+                };
+                if message.response {
+                    for service in browser.process(&message, source.ip()) {
+                        tracing::info!(
+                            service_type = %service.service_type,
+                            name = %service.name,
+                            ip = %service.ip,
+                            port = service.port,
+                            "mDNS service detected"
+                        );
+                        if let Err(err) = ctx.publish(service.to_event()).await {
+                            tracing::warn!(%err, "failed to publish device_detected event");
+                        }
+                    }
+                } else if let Some(mut reply) =
+                    responder.as_ref().and_then(|responder| responder.answer(&message))
+                {
+                    // Queries not sent from the mDNS port come from simple
+                    // resolvers expecting a unicast DNS reply (RFC 6762 §6.7).
+                    let target = if source.port() == MDNS_PORT {
+                        group
+                    } else {
+                        reply.id = message.id;
+                        reply.questions = message.questions;
+                        source
+                    };
+                    send(&socket, &reply, target).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_not_announce_when_disabled() {
+        let integration = MdnsIntegration::new(MdnsConfig {
+            announce: false,
+            ..MdnsConfig::default()
+        });
+
+        assert!(integration.responder().is_none());
+    }
+
+    #[test]
+    fn should_announce_configured_address() {
+        let integration = MdnsIntegration::new(MdnsConfig {
+            address: Some(Ipv4Addr::new(10, 0, 0, 2)),
+            port: 9000,
+            ..MdnsConfig::default()
+        });
+
+        let announcement = integration.responder().unwrap().announcement();
+
+        assert!(
+            announcement
+                .records
+                .iter()
+                .any(|record| record.data == dns::RecordData::A(Ipv4Addr::new(10, 0, 0, 2)))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Announcing the minihub HTTP API as a `_minihub._tcp` service, so clients
+//! can find the hub without being told its address.
+
+use std::net::Ipv4Addr;
+
+use crate::dns::{self, Message, Record, RecordData};
+
+/// Service type minihub is announced as.
+pub const SERVICE_TYPE: &str = "_minihub._tcp.local";
+
+/// Meta-query listing every service type on the network (RFC 6763 §9).
+const SERVICE_TYPES_QUERY: &str = "_services._dns-sd._udp.local";
+
+/// TTL of the host-related records, SRV and A (RFC 6762 §10).
+const HOST_TTL: u32 = 120;
+
+/// TTL of the other records.
+const SERVICE_TTL: u32 = 4500;
+
+/// Answers queries about the announced service.
+#[derive(Debug, Clone)]
+pub struct Responder {
+    /// Full instance name, e.g. `"minihub._minihub._tcp.local"`.
+    instance: String,
+    /// Host name, e.g. `"minihub.local"`.
+    host: String,
+    port: u16,
+    ip: Ipv4Addr,
+}
+
+impl Responder {
+    /// Announce `instance_name` on `ip:port`.
+    #[must_use]
+    pub fn new(instance_name: &str, ip: Ipv4Addr, port: u16) -> Self {
+        let label = instance_name.replace('.', "-");
+        Self {
+            instance: format!("{label}.{SERVICE_TYPE}"),
+            host: format!("{}.local", label.replace(' ', "-").to_ascii_lowercase()),
+            port,
+            ip,
+        }
+    }
+
+    /// The unsolicited response announcing the service at startup.
+    #[must_use]
+    pub fn announcement(&self) -> Message {
+        Message {
+            response: true,
+            records: self.service_records(),
+            ..Message::default()
+        }
+    }
+
+    /// The response to `query`, when it asks about the announced service.
+    #[must_use]
+    pub fn answer(&self, query: &Message) -> Option<Message> {
+        if query.response {
+            return None;
+        }
+        let mut records = Vec::new();
+        for question in &query.questions {
+            let asks = |record_type| {
+                question.record_type == record_type || question.record_type == dns::TYPE_ANY
+            };
+            let answers = if dns::same_name(&question.name, SERVICE_TYPE) && asks(dns::TYPE_PTR) {
+                self.service_records()
+            } else if dns::same_name(&question.name, SERVICE_TYPES_QUERY) && asks(dns::TYPE_PTR) {
+                vec![Record {
+                    name: SERVICE_TYPES_QUERY.to_string(),
+                    ttl: SERVICE_TTL,
+                    data: RecordData::Ptr(SERVICE_TYPE.to_string()),
+                }]
+            } else if dns::same_name(&question.name, &self.instance)
+                && (asks(dns::TYPE_SRV) || asks(dns::TYPE_TXT))
+            {
+                self.service_records().split_off(1)
+            } else if dns::same_name(&question.name, &self.host) && asks(dns::TYPE_A) {
+                vec![self.address_record()]
+            } else {
+                continue;
+            };
+            for record in answers {
+                if !records.contains(&record) {
+                    records.push(record);
+                }
+            }
+        }
+        (!records.is_empty()).then(|| Message {
+            response: true,
+            records,
+            ..Message::default()
+        })
+    }
+
+    /// PTR, SRV, TXT and A records describing the service.
+    fn service_records(&self) -> Vec<Record> {
+        vec![
+            Record {
+                name: SERVICE_TYPE.to_string(),
+                ttl: SERVICE_TTL,
+                data: RecordData::Ptr(self.instance.clone()),
+            },
+            Record {
+                name: self.instance.clone(),
+                ttl: HOST_TTL,
+                data: RecordData::Srv {
+                    port: self.port,
+                    target: self.host.clone(),
+                },
+            },
+            Record {
+                name: self.instance.clone(),
+                ttl: SERVICE_TTL,
+                data: RecordData::Txt(vec!["path=/api".to_string()]),
+            },
+            self.address_record(),
+        ]
+    }
+
+    fn address_record(&self) -> Record {
+        Record {
+            name: self.host.clone(),
+            ttl: HOST_TTL,
+            data: RecordData::A(self.ip),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns::Question;
+
+    use super::*;
+
+    fn responder() -> Responder {
+        Responder::new("minihub", Ipv4Addr::new(192, 168, 1, 2), 8080)
+    }
+
+    fn query(name: &str, record_type: u16) -> Message {
+        Message {
+            questions: vec![Question {
+                name: name.to_string(),
+                record_type,
+            }],
+            ..Message::default()
+        }
+    }
+
+    #[test]
+    fn should_announce_every_service_record() {
+        let announcement = responder().announcement();
+
+        assert!(announcement.response);
+        assert_eq!(
+            announcement.records[0].data,
+            RecordData::Ptr("minihub._minihub._tcp.local".to_string())
+        );
+        assert_eq!(
+            announcement.records[1].data,
+            RecordData::Srv {
+                port: 8080,
+                target: "minihub.local".to_string()
+            }
+        );
+        assert_eq!(
+            announcement.records[3].data,
+            RecordData::A(Ipv4Addr::new(192, 168, 1, 2))
+        );
+    }
+
+    #[test]
+    fn should_answer_browse_query_for_minihub() {
+        let answer = responder()
+            .answer(&query("_MINIHUB._tcp.local", dns::TYPE_PTR))
+            .unwrap();
+
+        assert_eq!(answer.records.len(), 4);
+    }
+
+    #[test]
+    fn should_answer_service_type_enumeration() {
+        let answer = responder()
+            .answer(&query(SERVICE_TYPES_QUERY, dns::TYPE_PTR))
+            .unwrap();
+
+        assert_eq!(
+            answer.records[0].data,
+            RecordData::Ptr(SERVICE_TYPE.to_string())
+        );
+    }
+
+    #[test]
+    fn should_answer_host_address_query() {
+        let answer = responder()
+            .answer(&query("minihub.local", dns::TYPE_A))
+            .unwrap();
+
+        assert_eq!(answer.records, vec![responder().address_record()]);
+    }
+
+    #[test]
+    fn should_ignore_queries_for_other_services() {
+        assert!(
+            responder()
+                .answer(&query("_hap._tcp.local", dns::TYPE_PTR))
+                .is_none()
+        );
+        assert!(
+            responder()
+                .answer(&query("minihub.local", dns::TYPE_AAAA))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn should_not_answer_responses() {
+        let mut message = query(SERVICE_TYPE, dns::TYPE_PTR);
+        message.response = true;
+
+        assert!(responder().answer(&message).is_none());
+    }
+}
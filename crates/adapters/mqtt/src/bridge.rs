@@ -0,0 +1,303 @@
+//! Outbound bridge — mirrors minihub entity states onto the broker.
+//!
+//! When [`MqttConfig::bridge_enabled`](crate::MqttConfig::bridge_enabled) is
+//! set, every state or attribute change on the event bus is published on
+//! `{base}/states/{entity_id}`, retained by default (see
+//! [`TopicClasses::states`](crate::TopicClasses::states)), so external consumers (Node-RED,
+//! Grafana, …) can observe the hub:
+//!
+//! ```json
+//! {
+//!   "state": "on",
+//!   "attributes": { "brightness": 128 },
+//!   "friendly_name": "Kitchen Light",
+//!   "device_class": null,
+//!   "unit_of_measurement": null,
+//!   "last_changed": "2026-03-01T18:00:00Z",
+//!   "last_updated": "2026-03-01T18:05:00Z"
+//! }
+//! ```
+//!
+//! With [`MqttConfig::federation_enabled`](crate::MqttConfig::federation_enabled)
+//! also set, the hub federates into a primary minihub running the
+//! [`RemoteIntegration`](crate::RemoteIntegration): every entity is
+//! published once at startup, so the primary learns about the ones that
+//! never change, `{base}/status` reports whether the hub is `online`, and
+//! `{"service": "turn_on", "data": {}}` messages on
+//! `{base}/states/{entity_id}/set` are run as local service calls.
+
+use rumqttc::AsyncClient;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use minihub_app::ports::integration::IntegrationContext;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event as DomainEvent, EventType};
+use minihub_domain::service::ServiceCall;
+
+use crate::MqttError;
+use crate::config::TopicOptions;
+
+/// Topic carrying the state of `entity`.
+pub(crate) fn state_topic(base: &str, entity: &Entity) -> String {
+    format!("{base}/states/{}", entity.entity_id)
+}
+
+/// JSON payload describing the state of `entity`.
+pub(crate) fn state_payload(entity: &Entity) -> serde_json::Value {
+    serde_json::json!({
+        "state": entity.state,
+        "attributes": entity.attributes,
+        "friendly_name": entity.friendly_name,
+        "device_class": entity.device_class,
+        "unit_of_measurement": entity.unit_of_measurement,
+        "last_changed": entity.last_changed,
+        "last_updated": entity.last_updated,
+    })
+}
+
+/// `entity_id` targeted by a federation command topic,
+/// `{base}/states/{entity_id}/set`.
+pub(crate) fn command_entity_id<'a>(base: &str, topic: &'a str) -> Option<&'a str> {
+    let entity_id = topic
+        .strip_prefix(base)?
+        .strip_prefix("/states/")?
+        .strip_suffix("/set")?;
+    (!entity_id.is_empty() && !entity_id.contains('/')).then_some(entity_id)
+}
+
+/// Service call sent by the primary hub on a federation command topic.
+#[derive(Debug, PartialEq, Deserialize)]
+pub(crate) struct RemoteCommand {
+    pub service: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Run a federation command as a local service call on `entity_id`.
+///
+/// The call is validated like one made through the API, then requested on
+/// the bus for the integration owning the entity.
+///
+/// # Errors
+///
+/// Returns [`MqttError::PayloadParse`] for a malformed command, or a
+/// [`MqttError::Domain`] error when the entity is unknown or the call is
+/// invalid.
+pub(crate) async fn run_command(
+    ctx: &impl IntegrationContext,
+    entity_id: &str,
+    payload: &[u8],
+) -> Result<(), MqttError> {
+    let command: RemoteCommand =
+        serde_json::from_slice(payload).map_err(MqttError::PayloadParse)?;
+    let entity = ctx
+        .find_entity_by_entity_id(entity_id)
+        .await
+        .map_err(MqttError::Domain)?
+        .ok_or_else(|| {
+            MqttError::Domain(MiniHubError::NotFound(NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            }))
+        })?;
+    let call =
+        ServiceCall::new(&entity, command.service, command.data).map_err(MqttError::Domain)?;
+    ctx.publish(call.requested_event())
+        .await
+        .map_err(MqttError::Domain)?;
+    tracing::info!(entity_id, service = %call.service, "running federated service call");
+    Ok(())
+}
+
+/// Publish the state message of every entity, so a primary hub learns
+/// about the ones that do not change.
+async fn publish_snapshot(
+    client: &AsyncClient,
+    options: TopicOptions,
+    base: &str,
+    ctx: &impl IntegrationContext,
+) {
+    let entities = match ctx.list_entities().await {
+        Ok(entities) => entities,
+        Err(err) => {
+            tracing::warn!(%err, "failed to list entities for the MQTT federation snapshot");
+            return;
+        }
+    };
+    for entity in &entities {
+        if let Err(err) = publish_state(client, options, base, entity).await {
+            tracing::warn!(%err, entity_id = %entity.entity_id, "failed to publish entity state to MQTT");
+        }
+    }
+    tracing::info!(count = entities.len(), "published MQTT federation snapshot");
+}
+
+/// Whether events of `event_type` change what the bridge publishes.
+fn is_bridged(event_type: &EventType) -> bool {
+    matches!(
+        event_type,
+        EventType::StateChanged | EventType::AttributeChanged
+    )
+}
+
+/// Publish the state message of `entity`.
+async fn publish_state(
+    client: &AsyncClient,
+    options: TopicOptions,
+    base: &str,
+    entity: &Entity,
+) -> Result<(), MqttError> {
+    client
+        .publish(
+            state_topic(base, entity),
+            options.qos.into(),
+            options.retain,
+            state_payload(entity).to_string().into_bytes(),
+        )
+        .await
+        .map_err(MqttError::Client)
+}
+
+/// Forward state and attribute changes from the bus to the broker.
+///
+/// Events only carry the entity id, so the current entity is looked up
+/// through `ctx` before publishing. With `snapshot` set, every entity is
+/// published first.
+pub(crate) async fn state_bridge_loop(
+    client: AsyncClient,
+    base_topic: String,
+    options: TopicOptions,
+    mut rx: broadcast::Receiver<DomainEvent>,
+    ctx: impl IntegrationContext,
+    snapshot: bool,
+) {
+    if snapshot {
+        publish_snapshot(&client, options, &base_topic, &ctx).await;
+    }
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    skipped,
+                    "MQTT state bridge lagged, some state changes were not published"
+                );
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                tracing::info!("MQTT state bridge channel closed, stopping");
+                break;
+            }
+        };
+
+        if !is_bridged(&event.event_type) {
+            continue;
+        }
+        let Some(entity_id) = event.entity_id else {
+            continue;
+        };
+
+        let entity = match ctx.find_entity_by_id(entity_id).await {
+            Ok(Some(entity)) => entity,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!(%err, %entity_id, "failed to look up entity for MQTT state bridge");
+                continue;
+            }
+        };
+
+        if let Err(err) = publish_state(&client, options, &base_topic, &entity).await {
+            tracing::warn!(%err, entity_id = %entity.entity_id, "failed to publish entity state to MQTT");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::entity::{AttributeValue, EntityState};
+    use minihub_domain::id::DeviceId;
+
+    use super::*;
+
+    fn kitchen_light() -> Entity {
+        Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen Light")
+            .state(EntityState::On)
+            .attribute("brightness", AttributeValue::Int(128))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_publish_under_states_topic() {
+        assert_eq!(
+            state_topic("minihub", &kitchen_light()),
+            "minihub/states/light.kitchen"
+        );
+    }
+
+    #[test]
+    fn should_include_state_and_attributes_in_payload() {
+        let entity = kitchen_light();
+
+        let payload = state_payload(&entity);
+
+        assert_eq!(payload["state"], "on");
+        assert_eq!(payload["attributes"]["brightness"], 128);
+        assert_eq!(
+            payload["last_changed"],
+            serde_json::to_value(entity.last_changed).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_include_metadata_in_payload() {
+        let payload = state_payload(&kitchen_light());
+
+        assert_eq!(payload["friendly_name"], "Kitchen Light");
+        assert!(payload["device_class"].is_null());
+    }
+
+    #[test]
+    fn should_extract_entity_id_from_command_topic() {
+        assert_eq!(
+            command_entity_id("garage", "garage/states/light.kitchen/set"),
+            Some("light.kitchen")
+        );
+        assert_eq!(
+            command_entity_id("garage", "garage/states/light.kitchen"),
+            None
+        );
+        assert_eq!(command_entity_id("garage", "garage/states//set"), None);
+        assert_eq!(command_entity_id("garage", "garage/a/b/set"), None);
+        assert_eq!(
+            command_entity_id("garage", "garagex/states/light.kitchen/set"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_parse_command_with_optional_data() {
+        let command: RemoteCommand = serde_json::from_str(r#"{"service":"toggle"}"#).unwrap();
+
+        assert_eq!(
+            command,
+            RemoteCommand {
+                service: "toggle".to_string(),
+                data: serde_json::Value::Null,
+            }
+        );
+    }
+
+    #[test]
+    fn should_only_bridge_state_and_attribute_changes() {
+        assert!(is_bridged(&EventType::StateChanged));
+        assert!(is_bridged(&EventType::AttributeChanged));
+        assert!(!is_bridged(&EventType::ServiceCallRequested));
+        assert!(!is_bridged(&EventType::EntityCreated));
+    }
+}
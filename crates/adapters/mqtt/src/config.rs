@@ -1,6 +1,15 @@
 //! MQTT integration configuration.
 
-use serde::Deserialize;
+use std::time::Duration;
+
+use rumqttc::{LastWill, MqttOptions, QoS};
+use serde::{Deserialize, Serialize};
+
+/// Payload of the status topic while a federated hub is connected.
+pub(crate) const STATUS_ONLINE: &str = "online";
+/// Payload of the status topic once a federated hub is gone, set as its
+/// last will.
+pub(crate) const STATUS_OFFLINE: &str = "offline";
 
 /// Configuration for the MQTT integration.
 #[derive(Debug, Clone, Deserialize)]
@@ -12,10 +21,29 @@ pub struct MqttConfig {
     pub broker_port: u16,
     /// MQTT client identifier.
     pub client_id: String,
+    /// User name to authenticate with, when the broker requires one.
+    pub username: Option<String>,
+    /// Password sent along with `username`.
+    pub password: Option<String>,
     /// Base topic prefix for all minihub MQTT communication.
     pub base_topic: String,
     /// Keep-alive interval in seconds.
     pub keep_alive_secs: u16,
+    /// Publish every entity state change to `{base}/states/{entity_id}`.
+    ///
+    /// Only used by [`MqttIntegration`](crate::MqttIntegration).
+    pub bridge_enabled: bool,
+    /// Federate this hub into a primary minihub: on top of the bridged
+    /// states, publish a snapshot of every entity at startup, announce the
+    /// hub on `{base}/status` and run the service calls the primary sends
+    /// on `{base}/states/{entity_id}/set`. Requires `bridge_enabled`.
+    ///
+    /// Only used by [`MqttIntegration`](crate::MqttIntegration).
+    pub federation_enabled: bool,
+    /// Delivery options of each class of topics.
+    pub topics: TopicClasses,
+    /// Buffering of service call publishes while the broker is unreachable.
+    pub buffer: BufferConfig,
 }
 
 impl Default for MqttConfig {
@@ -24,12 +52,168 @@ impl Default for MqttConfig {
             broker_host: "localhost".to_string(),
             broker_port: 1883,
             client_id: "minihub".to_string(),
+            username: None,
+            password: None,
             base_topic: "minihub".to_string(),
             keep_alive_secs: 30,
+            bridge_enabled: false,
+            federation_enabled: false,
+            topics: TopicClasses::default(),
+            buffer: BufferConfig::default(),
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Build rumqttc connection options.
+    pub(crate) fn mqtt_options(&self) -> MqttOptions {
+        let mut opts = MqttOptions::new(&self.client_id, &self.broker_host, self.broker_port);
+        opts.set_keep_alive(Duration::from_secs(u64::from(self.keep_alive_secs)));
+        if let Some(username) = &self.username {
+            opts.set_credentials(username, self.password.as_deref().unwrap_or_default());
+        }
+        if self.federation_enabled {
+            opts.set_last_will(LastWill::new(
+                self.status_topic(),
+                STATUS_OFFLINE,
+                QoS::AtLeastOnce,
+                true,
+            ));
+        }
+        opts
+    }
+
+    /// Topic announcing whether a federated hub is `online` or `offline`.
+    pub(crate) fn status_topic(&self) -> String {
+        format!("{}/status", self.base_topic)
+    }
+}
+
+/// MQTT quality of service level, written `0`, `1` or `2` in the
+/// configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Qos {
+    /// Delivered at most once, without acknowledgement.
+    AtMostOnce,
+    /// Delivered at least once, possibly duplicated.
+    AtLeastOnce,
+    /// Delivered exactly once.
+    ExactlyOnce,
+}
+
+impl TryFrom<u8> for Qos {
+    type Error = String;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
+        match level {
+            0 => Ok(Self::AtMostOnce),
+            1 => Ok(Self::AtLeastOnce),
+            2 => Ok(Self::ExactlyOnce),
+            other => Err(format!("invalid QoS {other}, expected 0, 1 or 2")),
+        }
+    }
+}
+
+impl From<Qos> for u8 {
+    fn from(qos: Qos) -> Self {
+        match qos {
+            Qos::AtMostOnce => 0,
+            Qos::AtLeastOnce => 1,
+            Qos::ExactlyOnce => 2,
+        }
+    }
+}
+
+impl From<Qos> for QoS {
+    fn from(qos: Qos) -> Self {
+        match qos {
+            Qos::AtMostOnce => Self::AtMostOnce,
+            Qos::AtLeastOnce => Self::AtLeastOnce,
+            Qos::ExactlyOnce => Self::ExactlyOnce,
+        }
+    }
+}
+
+/// How the messages of a class of topics are published.
+///
+/// A class written in the configuration replaces its defaults as a whole:
+/// omitted fields fall back to at-least-once delivery, not retained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TopicOptions {
+    /// Quality of service of the messages.
+    #[serde(default = "default_qos")]
+    pub qos: Qos,
+    /// Whether the broker keeps the last message for new subscribers.
+    #[serde(default)]
+    pub retain: bool,
+}
+
+fn default_qos() -> Qos {
+    Qos::AtLeastOnce
+}
+
+/// Delivery options per class of topics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TopicClasses {
+    /// Service call commands, published on the `/set` topics. Not retained
+    /// by default, so devices do not replay a stale command on reconnect.
+    pub commands: TopicOptions,
+    /// Entity states published in bridge mode on `{base}/states/…`.
+    /// Retained by default, so new subscribers get the current states.
+    pub states: TopicOptions,
+    /// Quality of service requested when subscribing to discovery and state
+    /// topics.
+    pub subscriptions: Qos,
+}
+
+impl Default for TopicClasses {
+    fn default() -> Self {
+        Self {
+            commands: TopicOptions {
+                qos: Qos::AtLeastOnce,
+                retain: false,
+            },
+            states: TopicOptions {
+                qos: Qos::AtLeastOnce,
+                retain: true,
+            },
+            subscriptions: Qos::AtLeastOnce,
         }
     }
 }
 
+/// Buffer of the service call publishes made while the broker is
+/// unreachable, flushed on reconnect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BufferConfig {
+    /// Maximum number of buffered messages; the oldest are dropped first.
+    /// `0` disables buffering.
+    pub capacity: usize,
+    /// Buffered messages older than this on reconnect are dropped, so a
+    /// light does not turn on hours after it was asked to.
+    pub ttl_secs: u64,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 100,
+            ttl_secs: 300,
+        }
+    }
+}
+
+impl BufferConfig {
+    /// Age after which a buffered message is dropped.
+    #[must_use]
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,8 +224,14 @@ mod tests {
         assert_eq!(config.broker_host, "localhost");
         assert_eq!(config.broker_port, 1883);
         assert_eq!(config.client_id, "minihub");
+        assert!(config.username.is_none());
         assert_eq!(config.base_topic, "minihub");
         assert_eq!(config.keep_alive_secs, 30);
+        assert!(!config.bridge_enabled);
+        assert!(!config.federation_enabled);
+        assert!(!config.topics.commands.retain);
+        assert!(config.topics.states.retain);
+        assert_eq!(config.buffer.capacity, 100);
     }
 
     #[test]
@@ -52,6 +242,7 @@ mod tests {
             client_id = "my-hub"
             base_topic = "home"
             keep_alive_secs = 60
+            bridge_enabled = true
         "#;
         let config: MqttConfig = toml::from_str(toml).unwrap();
         assert_eq!(config.broker_host, "mqtt.example.com");
@@ -59,6 +250,7 @@ mod tests {
         assert_eq!(config.client_id, "my-hub");
         assert_eq!(config.base_topic, "home");
         assert_eq!(config.keep_alive_secs, 60);
+        assert!(config.bridge_enabled);
     }
 
     #[test]
@@ -69,4 +261,65 @@ mod tests {
         assert_eq!(config.broker_port, 1883);
         assert_eq!(config.client_id, "minihub");
     }
+
+    #[test]
+    fn should_deserialize_topic_classes_and_buffer() {
+        let toml = r"
+            [topics.commands]
+            qos = 2
+            [topics.states]
+            qos = 0
+            [buffer]
+            ttl_secs = 60
+        ";
+        let config: MqttConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.topics.commands.qos, Qos::ExactlyOnce);
+        assert!(!config.topics.commands.retain);
+        assert_eq!(config.topics.states.qos, Qos::AtMostOnce);
+        assert!(!config.topics.states.retain);
+        assert_eq!(config.topics.subscriptions, Qos::AtLeastOnce);
+        assert_eq!(config.buffer.capacity, 100);
+        assert_eq!(config.buffer.ttl(), Duration::from_mins(1));
+    }
+
+    #[test]
+    fn should_reject_unknown_qos_level() {
+        let toml = r"
+            [topics.commands]
+            qos = 3
+        ";
+        assert!(toml::from_str::<MqttConfig>(toml).is_err());
+    }
+
+    #[test]
+    fn should_set_offline_last_will_when_federation_enabled() {
+        let config = MqttConfig {
+            base_topic: "garage".to_string(),
+            bridge_enabled: true,
+            federation_enabled: true,
+            ..MqttConfig::default()
+        };
+
+        let will = config.mqtt_options().last_will().unwrap();
+
+        assert_eq!(will.topic, "garage/status");
+        assert_eq!(will.message.as_ref(), STATUS_OFFLINE.as_bytes());
+        assert!(will.retain);
+        assert!(MqttConfig::default().mqtt_options().last_will().is_none());
+    }
+
+    #[test]
+    fn should_authenticate_when_username_set() {
+        let config = MqttConfig {
+            username: Some("minihub".to_string()),
+            password: Some("hunter2".to_string()),
+            ..MqttConfig::default()
+        };
+
+        let login = config.mqtt_options().credentials().unwrap();
+
+        assert_eq!(login.username, "minihub");
+        assert_eq!(login.password, "hunter2");
+        assert!(MqttConfig::default().mqtt_options().credentials().is_none());
+    }
 }
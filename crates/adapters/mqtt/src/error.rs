@@ -9,6 +9,11 @@ pub enum MqttError {
     #[error("MQTT client not connected")]
     NotConnected,
 
+    /// The broker is unreachable: the message was queued and will be
+    /// published on reconnect, unless it expires first.
+    #[error("MQTT broker unreachable, message buffered until reconnect")]
+    Buffered,
+
     /// The rumqttc client returned an error.
     #[error("MQTT client error")]
     Client(#[source] rumqttc::ClientError),
@@ -17,6 +22,10 @@ pub enum MqttError {
     #[error("failed to parse MQTT payload")]
     PayloadParse(#[source] serde_json::Error),
 
+    /// The target entity cannot handle the requested service.
+    #[error("unsupported service: {0}")]
+    UnsupportedService(String),
+
     /// A domain-level error (validation, not-found, etc.).
     #[error("{0}")]
     Domain(#[source] MiniHubError),
@@ -55,6 +64,15 @@ mod tests {
         assert!(matches!(err, MiniHubError::Storage(_)));
     }
 
+    #[test]
+    fn should_display_buffered_error() {
+        let err = MqttError::Buffered;
+        assert_eq!(
+            err.to_string(),
+            "MQTT broker unreachable, message buffered until reconnect"
+        );
+    }
+
     #[test]
     fn should_convert_domain_error_back_to_domain() {
         let domain_err =
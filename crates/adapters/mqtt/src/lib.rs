@@ -11,6 +11,9 @@
 //! | `{base}/{device_id}/{entity_slug}/state` | Broker → minihub | State updates from devices |
 //! | `{base}/{device_id}/{entity_slug}/set` | minihub → Broker | Service call commands |
 //! | `{base}/{device_id}/config` | Broker → minihub | Device/entity discovery |
+//! | `{base}/states/{entity_id}` | minihub → Broker | Retained entity states (bridge mode) |
+//! | `{base}/states/{entity_id}/set` | Broker → minihub | Service calls from a primary hub (federation) |
+//! | `{base}/status` | minihub → Broker | Retained `online` / `offline` (federation) |
 //!
 //! ## Discovery payload
 //!
@@ -18,49 +21,99 @@
 //!
 //! ```json
 //! {
-//!   "device": { "name": "...", "manufacturer": "...", "model": "..." },
+//!   "device": { "name": "...", "manufacturer": "...", "model": "...", "sw_version": "..." },
 //!   "entities": [
 //!     { "entity_id": "light.kitchen", "friendly_name": "Kitchen Light", "state": "off" }
 //!   ]
 //! }
 //! ```
 //!
+//! The device may also carry `hw_version`, `suggested_area` and
+//! `via_device`, the `{device_id}` of the bridge it is reached through.
+//!
+//! Entities may also carry a `device_class` (e.g. `"temperature"`), a
+//! `unit_of_measurement`, and an `attribute_meta` object mapping attribute
+//! names to display and validation hints, e.g.
+//! `{ "brightness": { "min": 0, "max": 255 } }`.
+//!
+//! ## Delivery
+//!
+//! The `QoS` level and retain flag are configured per topic class in
+//! [`MqttConfig::topics`]: service call commands, bridged states and
+//! subscriptions. Commands issued
+//! while the broker is unreachable are buffered and published once it
+//! acknowledges the reconnection, unless they outlived the TTL of
+//! [`MqttConfig::buffer`]; callers get [`MqttError::Buffered`] and the bus a
+//! `ServiceCallFailed` event with `"deferred": true`.
+//!
+//! ## Bridge mode
+//!
+//! With [`MqttConfig::bridge_enabled`] set, the integration also publishes
+//! the state and attributes of every minihub entity, whatever its
+//! integration, so external tools can observe the hub.
+//!
+//! ## Federation
+//!
+//! A hub with [`MqttConfig::federation_enabled`] also set joins a primary
+//! minihub, where [`RemoteIntegration`] materializes its entities as those
+//! of one remote device and routes service calls back over MQTT.
+//!
+//! ## zigbee2mqtt
+//!
+//! [`Zigbee2MqttIntegration`] speaks the zigbee2mqtt topic layout instead
+//! and maps each device's `exposes` metadata onto entities.
+//!
 //! ## Dependency rule
 //!
 //! Same as other adapters: depends on `minihub-app` and `minihub-domain`.
 
+mod bridge;
 mod config;
 mod error;
+mod outbox;
+mod remote;
+mod zigbee2mqtt;
 
-pub use config::MqttConfig;
+pub use config::{BufferConfig, MqttConfig, Qos, TopicClasses, TopicOptions};
 pub use error::MqttError;
+pub use remote::RemoteIntegration;
+pub use zigbee2mqtt::Zigbee2MqttIntegration;
 
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
-use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
 use minihub_domain::device::Device;
-use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::entity::{AttributeMeta, DeviceClass, Entity, EntityState};
 use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{
+    Event as DomainEvent, EventType, ServiceCallPayload, ServiceCallResultPayload,
+};
 use minihub_domain::id::EntityId;
 
+use crate::outbox::Outbox;
+
 /// MQTT integration that bridges MQTT-based devices into minihub.
 ///
 /// Connects to an MQTT broker, subscribes to discovery and state topics,
 /// and translates messages into entity state updates.
 pub struct MqttIntegration {
     config: MqttConfig,
-    client: Option<AsyncClient>,
+    outbox: Option<Arc<Outbox>>,
     eventloop_handle: Option<JoinHandle<()>>,
     /// Incoming publish packets from the event loop, consumed by
     /// [`start_background`](Integration::start_background).
     publish_rx: Option<mpsc::Receiver<rumqttc::Publish>>,
     background_handle: Option<JoinHandle<()>>,
+    /// Forwards `ServiceCallRequested` events from the bus to the broker.
+    subscriber_handle: Option<JoinHandle<()>>,
+    /// Publishes entity state changes to the broker in bridge mode.
+    bridge_handle: Option<JoinHandle<()>>,
     /// Maps `entity_id` string (e.g. `"light.kitchen"`) to the entity snapshot.
     entities: Arc<Mutex<HashMap<String, Entity>>>,
     /// Maps entity UUID to the MQTT command topic.
@@ -73,10 +126,12 @@ impl MqttIntegration {
     pub fn new(config: MqttConfig) -> Self {
         Self {
             config,
-            client: None,
+            outbox: None,
             eventloop_handle: None,
             publish_rx: None,
             background_handle: None,
+            subscriber_handle: None,
+            bridge_handle: None,
             entities: Arc::new(Mutex::new(HashMap::new())),
             command_topics: Arc::new(Mutex::new(HashMap::new())),
         }
@@ -84,21 +139,17 @@ impl MqttIntegration {
 
     /// Build rumqttc options from our config.
     fn mqtt_options(&self) -> MqttOptions {
-        let mut opts = MqttOptions::new(
-            &self.config.client_id,
-            &self.config.broker_host,
-            self.config.broker_port,
-        );
-        opts.set_keep_alive(Duration::from_secs(u64::from(self.config.keep_alive_secs)));
-        opts
+        self.config.mqtt_options()
     }
 
     /// Spawn the eventloop driver task.
     ///
     /// Returns a receiver that yields incoming [`Publish`] packets and the
-    /// join handle for the background task.
+    /// join handle for the background task. Connection changes are reported
+    /// to `outbox`, which is flushed on every connection acknowledgement.
     fn spawn_eventloop(
         mut eventloop: EventLoop,
+        outbox: Arc<Outbox>,
     ) -> (mpsc::Receiver<rumqttc::Publish>, JoinHandle<()>) {
         let (tx, rx) = mpsc::channel::<rumqttc::Publish>(256);
 
@@ -111,8 +162,18 @@ impl MqttIntegration {
                             break;
                         }
                     }
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        let pending = outbox.connected();
+                        if !pending.is_empty() {
+                            // Flushed from another task: publishing needs
+                            // this loop to keep polling.
+                            let outbox = Arc::clone(&outbox);
+                            tokio::spawn(async move { outbox.flush(pending).await });
+                        }
+                    }
                     Ok(_) => {}
                     Err(err) => {
+                        outbox.disconnected();
                         tracing::warn!(%err, "MQTT connection error, reconnecting");
                         tokio::time::sleep(Duration::from_secs(5)).await;
                     }
@@ -125,23 +186,34 @@ impl MqttIntegration {
 
     /// Subscribe to the discovery and state wildcard topics.
     async fn subscribe_topics(&self) -> Result<(), MqttError> {
-        let client = self.client.as_ref().ok_or(MqttError::NotConnected)?;
+        let outbox = self.outbox.as_ref().ok_or(MqttError::NotConnected)?;
+        let client = outbox.client();
         let base = &self.config.base_topic;
+        let qos = self.config.topics.subscriptions.into();
 
         let config_topic = format!("{base}/+/config");
         client
-            .subscribe(&config_topic, QoS::AtLeastOnce)
+            .subscribe(&config_topic, qos)
             .await
             .map_err(MqttError::Client)?;
         tracing::info!(topic = %config_topic, "subscribed to discovery topic");
 
         let state_topic = format!("{base}/+/+/state");
         client
-            .subscribe(&state_topic, QoS::AtLeastOnce)
+            .subscribe(&state_topic, qos)
             .await
             .map_err(MqttError::Client)?;
         tracing::info!(topic = %state_topic, "subscribed to state topic");
 
+        if self.config.federation_enabled {
+            let command_topic = format!("{base}/states/+/set");
+            client
+                .subscribe(&command_topic, qos)
+                .await
+                .map_err(MqttError::Client)?;
+            tracing::info!(topic = %command_topic, "subscribed to federation command topic");
+        }
+
         Ok(())
     }
 
@@ -168,26 +240,42 @@ impl MqttIntegration {
             .and_then(|rest| rest.strip_suffix("/config"))
             .unwrap_or("unknown");
 
-        let device = Device::builder()
+        let mut builder = Device::builder()
             .name(&payload.device.name)
             .manufacturer(&payload.device.manufacturer)
             .model(&payload.device.model)
             .integration("mqtt")
-            .unique_id(device_slug)
-            .build()
-            .map_err(MqttError::Domain)?;
+            .unique_id(device_slug);
+        if let Some(sw_version) = &payload.device.sw_version {
+            builder = builder.sw_version(sw_version);
+        }
+        if let Some(hw_version) = &payload.device.hw_version {
+            builder = builder.hw_version(hw_version);
+        }
+        if let Some(suggested_area) = &payload.device.suggested_area {
+            builder = builder.suggested_area(suggested_area);
+        }
+        let device = builder.build().map_err(MqttError::Domain)?;
 
         let mut entities = Vec::new();
         let mut cmd_topics = Vec::new();
         for ep in &payload.entities {
             let state = parse_state(&ep.state);
-            let entity = Entity::builder()
+            let mut builder = Entity::builder()
                 .device_id(device.id)
                 .entity_id(&ep.entity_id)
                 .friendly_name(&ep.friendly_name)
-                .state(state)
-                .build()
-                .map_err(MqttError::Domain)?;
+                .state(state);
+            if let Some(device_class) = ep.device_class {
+                builder = builder.device_class(device_class);
+            }
+            if let Some(unit) = &ep.unit_of_measurement {
+                builder = builder.unit_of_measurement(unit);
+            }
+            for (key, meta) in &ep.attribute_meta {
+                builder = builder.attribute_meta(key, meta.clone());
+            }
+            let entity = builder.build().map_err(MqttError::Domain)?;
 
             let entity_slug = ep.entity_id.split('.').next_back().unwrap_or(&ep.entity_id);
             let cmd_topic = format!("{base}/{device_slug}/{entity_slug}/set");
@@ -201,11 +289,18 @@ impl MqttIntegration {
             "discovered MQTT device"
         );
 
-        Ok(Some((DiscoveredDevice { device, entities }, cmd_topics)))
+        Ok(Some((
+            DiscoveredDevice {
+                device,
+                entities,
+                via_device: payload.device.via_device,
+            },
+            cmd_topics,
+        )))
     }
 
     /// Background message loop that processes config (discovery) and state
-    /// messages from the MQTT broker.
+    /// messages from the MQTT broker, and federation commands when enabled.
     async fn background_message_loop(
         config: MqttConfig,
         mut publish_rx: mpsc::Receiver<rumqttc::Publish>,
@@ -214,7 +309,15 @@ impl MqttIntegration {
         command_topics: Arc<Mutex<HashMap<EntityId, String>>>,
     ) {
         while let Some(publish) = publish_rx.recv().await {
-            if publish.topic.ends_with("/config") {
+            let command = config
+                .federation_enabled
+                .then(|| bridge::command_entity_id(&config.base_topic, &publish.topic))
+                .flatten();
+            if let Some(entity_id) = command {
+                if let Err(err) = bridge::run_command(&ctx, entity_id, &publish.payload).await {
+                    tracing::warn!(%err, entity_id, "rejected federated service call");
+                }
+            } else if publish.topic.ends_with("/config") {
                 match Self::parse_config_message(&config, &publish) {
                     Ok(Some((dd, cmd_topics))) => {
                         {
@@ -244,10 +347,154 @@ impl MqttIntegration {
                     payload_len = publish.payload.len(),
                     "received state update"
                 );
+                let Some(entity) = Self::apply_state_message(&publish, &entities, &command_topics)
+                else {
+                    continue;
+                };
+                if let Err(err) = ctx.upsert_entity(entity).await {
+                    tracing::warn!(%err, "failed to persist MQTT state update");
+                }
             }
         }
         tracing::debug!("MQTT background message loop stopped");
     }
+
+    /// Apply a `{base}/{device}/{entity}/state` message to the matching
+    /// known entity, returning the updated snapshot.
+    ///
+    /// The payload is the plain state value (e.g. `on`). Messages for
+    /// entities that were not discovered are ignored.
+    fn apply_state_message(
+        publish: &rumqttc::Publish,
+        entities: &Mutex<HashMap<String, Entity>>,
+        command_topics: &Mutex<HashMap<EntityId, String>>,
+    ) -> Option<Entity> {
+        let cmd_topic = format!("{}/set", publish.topic.strip_suffix("/state")?);
+        let id = command_topics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(_, topic)| **topic == cmd_topic)
+            .map(|(id, _)| *id)?;
+
+        let payload = std::str::from_utf8(&publish.payload).ok()?;
+        let mut ents = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let entity = ents.values_mut().find(|ent| ent.id == id)?;
+        entity.update_state(parse_state(payload.trim()), minihub_domain::time::now());
+        Some(entity.clone())
+    }
+
+    /// Find the command topic of a discovered entity by its `entity_id` string.
+    fn command_topic_for(
+        entities: &Mutex<HashMap<String, Entity>>,
+        command_topics: &Mutex<HashMap<EntityId, String>>,
+        entity_id: &str,
+    ) -> Option<String> {
+        let id = entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(entity_id)?
+            .id;
+        command_topics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    /// Publish a service call command on the entity's command topic.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MqttError::Buffered`] when the broker is unreachable and
+    /// the command was queued for the next connection.
+    async fn publish_command(
+        outbox: &Outbox,
+        options: TopicOptions,
+        cmd_topic: &str,
+        service: &str,
+        data: &serde_json::Value,
+    ) -> Result<(), MqttError> {
+        let payload = serde_json::json!({
+            "service": service,
+            "data": data,
+        });
+        outbox
+            .publish(cmd_topic, options, payload.to_string().into_bytes())
+            .await?;
+
+        tracing::info!(service, topic = %cmd_topic, "published MQTT service call");
+        Ok(())
+    }
+
+    /// Subscribe-side loop that forwards [`EventType::ServiceCallRequested`]
+    /// events targeting MQTT entities to the broker.
+    ///
+    /// Requests are resolved through the persisted entity's `entity_id`
+    /// string, since persisted ids differ from the locally discovered ones.
+    /// The new state is persisted once the device reports it on its state
+    /// topic.
+    async fn service_call_loop(
+        outbox: Arc<Outbox>,
+        options: TopicOptions,
+        mut rx: tokio::sync::broadcast::Receiver<DomainEvent>,
+        ctx: impl IntegrationContext,
+        entities: Arc<Mutex<HashMap<String, Entity>>>,
+        command_topics: Arc<Mutex<HashMap<EntityId, String>>>,
+    ) {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "MQTT event subscriber lagged, some events were missed"
+                    );
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    tracing::info!("MQTT event subscriber channel closed, stopping");
+                    break;
+                }
+            };
+
+            if event.event_type != EventType::ServiceCallRequested {
+                continue;
+            }
+
+            let Some(entity_id) = event.entity_id else {
+                continue;
+            };
+
+            let cmd_topic = match ctx.find_entity_by_id(entity_id).await {
+                Ok(Some(entity)) => {
+                    Self::command_topic_for(&entities, &command_topics, &entity.entity_id)
+                }
+                Ok(None) => None,
+                Err(err) => {
+                    tracing::warn!(%err, %entity_id, "failed to look up entity for service call");
+                    continue;
+                }
+            };
+
+            let Some(cmd_topic) = cmd_topic else {
+                continue;
+            };
+
+            let Ok(ServiceCallPayload { service, data }) = event.payload() else {
+                continue;
+            };
+            let service = service.as_str();
+
+            let result = Self::publish_command(&outbox, options, &cmd_topic, service, &data).await;
+            let result_event =
+                service_call_result_event(entity_id, service, result).with_cause(&event);
+
+            if let Err(err) = ctx.publish(result_event).await {
+                tracing::warn!(%err, "failed to publish service call result event");
+            }
+        }
+    }
 }
 
 impl Integration for MqttIntegration {
@@ -258,9 +505,14 @@ impl Integration for MqttIntegration {
     async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
         let opts = self.mqtt_options();
         let (client, eventloop) = AsyncClient::new(opts, 64);
-        self.client = Some(client);
+        let mut outbox = Outbox::new(client, self.config.buffer);
+        if self.config.federation_enabled {
+            outbox = outbox.with_birth(self.config.status_topic(), config::STATUS_ONLINE);
+        }
+        let outbox = Arc::new(outbox);
+        self.outbox = Some(Arc::clone(&outbox));
 
-        let (rx, handle) = Self::spawn_eventloop(eventloop);
+        let (rx, handle) = Self::spawn_eventloop(eventloop, outbox);
         self.eventloop_handle = Some(handle);
         self.publish_rx = Some(rx);
 
@@ -281,6 +533,39 @@ impl Integration for MqttIntegration {
             .ok_or(MqttError::NotConnected)
             .map_err(MqttError::into_domain)?;
 
+        let outbox = self
+            .outbox
+            .clone()
+            .ok_or(MqttError::NotConnected)
+            .map_err(MqttError::into_domain)?;
+
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        if self.config.bridge_enabled {
+            self.bridge_handle = Some(tokio::spawn(bridge::state_bridge_loop(
+                outbox.client().clone(),
+                self.config.base_topic.clone(),
+                self.config.topics.states,
+                ctx.subscribe(),
+                ctx.clone(),
+                self.config.federation_enabled,
+            )));
+            tracing::info!(
+                topic = %format!("{}/states/#", self.config.base_topic),
+                federation = self.config.federation_enabled,
+                "MQTT state bridge started"
+            );
+        }
+        let bus_rx = ctx.subscribe();
+        self.subscriber_handle = Some(tokio::spawn(Self::service_call_loop(
+            outbox,
+            self.config.topics.commands,
+            bus_rx,
+            ctx.clone(),
+            Arc::clone(&self.entities),
+            Arc::clone(&self.command_topics),
+        )));
+
         let handle = tokio::spawn(Self::background_message_loop(
             self.config.clone(),
             rx,
@@ -300,7 +585,7 @@ impl Integration for MqttIntegration {
         service: &str,
         data: serde_json::Value,
     ) -> Result<Entity, MiniHubError> {
-        let client = self.client.as_ref().ok_or(MqttError::NotConnected)?;
+        let outbox = self.outbox.as_ref().ok_or(MqttError::NotConnected)?;
         let cmd_topic = {
             let cmds = self
                 .command_topics
@@ -312,26 +597,14 @@ impl Integration for MqttIntegration {
             })?
         };
 
-        let payload = serde_json::json!({
-            "service": service,
-            "data": data,
-        });
-        client
-            .publish(
-                &cmd_topic,
-                QoS::AtLeastOnce,
-                false,
-                payload.to_string().into_bytes(),
-            )
-            .await
-            .map_err(MqttError::Client)?;
-
-        tracing::info!(
-            entity_id = %entity_id,
+        Self::publish_command(
+            outbox,
+            self.config.topics.commands,
+            &cmd_topic,
             service,
-            topic = %cmd_topic,
-            "published MQTT service call"
-        );
+            &data,
+        )
+        .await?;
 
         let ents = self.entities.lock().unwrap_or_else(PoisonError::into_inner);
         let entity = ents
@@ -346,6 +619,14 @@ impl Integration for MqttIntegration {
     }
 
     async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        if let Some(handle) = self.subscriber_handle.take() {
+            handle.abort();
+            tracing::debug!("MQTT event subscriber task aborted");
+        }
+        if let Some(handle) = self.bridge_handle.take() {
+            handle.abort();
+            tracing::debug!("MQTT state bridge task aborted");
+        }
         if let Some(handle) = self.background_handle.take() {
             handle.abort();
             tracing::debug!("MQTT background task aborted");
@@ -354,12 +635,44 @@ impl Integration for MqttIntegration {
             handle.abort();
             tracing::debug!("MQTT eventloop task aborted");
         }
-        self.client = None;
+        self.outbox = None;
         tracing::info!("MQTT integration stopped");
         Ok(())
     }
 }
 
+/// Build the `ServiceCallCompleted` / `ServiceCallFailed` event reporting
+/// the outcome of a forwarded service call.
+///
+/// A buffered command is reported as failed with `"deferred": true`, since
+/// it may still be delivered once the broker is reachable again.
+fn service_call_result_event(
+    entity_id: EntityId,
+    service: &str,
+    result: Result<(), MqttError>,
+) -> DomainEvent {
+    match result {
+        Ok(()) => DomainEvent::with_payload(
+            EventType::ServiceCallCompleted,
+            Some(entity_id),
+            &ServiceCallResultPayload::completed(service),
+        ),
+        Err(err @ MqttError::Buffered) => DomainEvent::with_payload(
+            EventType::ServiceCallFailed,
+            Some(entity_id),
+            &ServiceCallResultPayload::failed(service, err.to_string()).deferred(),
+        ),
+        Err(err) => {
+            tracing::warn!(%err, %entity_id, service, "MQTT service call failed");
+            DomainEvent::with_payload(
+                EventType::ServiceCallFailed,
+                Some(entity_id),
+                &ServiceCallResultPayload::failed(service, err.to_string()),
+            )
+        }
+    }
+}
+
 /// JSON payload published on `{base}/{device_id}/config` for device discovery.
 #[derive(Debug, serde::Deserialize)]
 struct DiscoveryPayload {
@@ -375,6 +688,15 @@ struct DevicePayload {
     manufacturer: String,
     #[serde(default)]
     model: String,
+    #[serde(default)]
+    sw_version: Option<String>,
+    #[serde(default)]
+    hw_version: Option<String>,
+    #[serde(default)]
+    suggested_area: Option<String>,
+    /// `{device_id}` of the device this one is reached through.
+    #[serde(default)]
+    via_device: Option<String>,
 }
 
 /// Entity descriptor within a discovery payload.
@@ -384,6 +706,12 @@ struct EntityPayload {
     friendly_name: String,
     #[serde(default = "default_state")]
     state: String,
+    #[serde(default)]
+    device_class: Option<DeviceClass>,
+    #[serde(default)]
+    unit_of_measurement: Option<String>,
+    #[serde(default)]
+    attribute_meta: HashMap<String, AttributeMeta>,
 }
 
 fn default_state() -> String {
@@ -391,19 +719,87 @@ fn default_state() -> String {
 }
 
 /// Map a string state value to [`EntityState`].
+///
+/// The discrete states are matched in any case, numbers become readings and
+/// any other non-empty payload is kept as text.
 fn parse_state(s: &str) -> EntityState {
     match s.to_lowercase().as_str() {
         "on" => EntityState::On,
         "off" => EntityState::Off,
         "unavailable" => EntityState::Unavailable,
-        _ => EntityState::Unknown,
+        "unknown" | "" => EntityState::Unknown,
+        _ => {
+            let Ok(state) = s.parse();
+            state
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use minihub_app::testing::FakeContext;
+    use rumqttc::QoS;
+
     use super::*;
 
+    /// Discover `light.kitchen` on `minihub/kitchen_hub` into the given
+    /// integration maps, returning the locally discovered entity.
+    fn discover_kitchen_light(
+        entities: &Mutex<HashMap<String, Entity>>,
+        command_topics: &Mutex<HashMap<EntityId, String>>,
+    ) -> Entity {
+        let payload = serde_json::json!({
+            "device": { "name": "Kitchen Hub" },
+            "entities": [
+                { "entity_id": "light.kitchen", "friendly_name": "Kitchen Light", "state": "off" }
+            ]
+        });
+        let publish = rumqttc::Publish::new(
+            "minihub/kitchen_hub/config",
+            QoS::AtLeastOnce,
+            payload.to_string(),
+        );
+        let (dd, cmd_topics) =
+            MqttIntegration::parse_config_message(&MqttConfig::default(), &publish)
+                .unwrap()
+                .unwrap();
+        let entity = dd.entities[0].clone();
+        entities
+            .lock()
+            .unwrap()
+            .insert(entity.entity_id.clone(), entity.clone());
+        command_topics.lock().unwrap().extend(cmd_topics);
+        entity
+    }
+
+    fn service_call(entity_id: EntityId) -> DomainEvent {
+        DomainEvent::new(
+            EventType::ServiceCallRequested,
+            Some(entity_id),
+            serde_json::json!({ "service": "turn_on", "data": {} }),
+        )
+    }
+
+    /// Spawn the service call loop for a persisted copy of `light.kitchen`
+    /// with a different id than the locally discovered one.
+    fn spawn_service_call_loop(outbox: Outbox) -> (FakeContext, EntityId, JoinHandle<()>) {
+        let entities = Arc::new(Mutex::new(HashMap::new()));
+        let command_topics = Arc::new(Mutex::new(HashMap::new()));
+        let mut persisted = discover_kitchen_light(&entities, &command_topics);
+        persisted.id = EntityId::new();
+        let ctx = FakeContext::with_entities(vec![persisted.clone()]);
+
+        let handle = tokio::spawn(MqttIntegration::service_call_loop(
+            Arc::new(outbox),
+            TopicClasses::default().commands,
+            ctx.subscribe(),
+            ctx.clone(),
+            entities,
+            command_topics,
+        ));
+        (ctx, persisted.id, handle)
+    }
+
     #[test]
     fn should_parse_on_state() {
         assert_eq!(parse_state("on"), EntityState::On);
@@ -422,17 +818,23 @@ mod tests {
     }
 
     #[test]
-    fn should_parse_unknown_state_for_unrecognised_value() {
-        assert_eq!(parse_state("foo"), EntityState::Unknown);
+    fn should_parse_unknown_state_when_payload_is_empty() {
+        assert_eq!(parse_state("unknown"), EntityState::Unknown);
         assert_eq!(parse_state(""), EntityState::Unknown);
     }
 
+    #[test]
+    fn should_parse_reading_or_text_for_other_values() {
+        assert_eq!(parse_state("21.5"), EntityState::Numeric(21.5));
+        assert_eq!(parse_state("Heat"), EntityState::Text("Heat".to_string()));
+    }
+
     #[test]
     fn should_create_integration_with_config() {
         let config = MqttConfig::default();
         let integration = MqttIntegration::new(config);
         assert_eq!(integration.name(), "mqtt");
-        assert!(integration.client.is_none());
+        assert!(integration.outbox.is_none());
         assert!(integration.entities.lock().unwrap().is_empty());
     }
 
@@ -484,6 +886,61 @@ mod tests {
         assert_eq!(payload.entities[0].state, "unknown");
     }
 
+    #[test]
+    fn should_apply_attribute_meta_from_config_message() {
+        let payload = serde_json::json!({
+            "device": { "name": "Dimmer" },
+            "entities": [
+                {
+                    "entity_id": "light.dimmer",
+                    "friendly_name": "Dimmer",
+                    "attribute_meta": { "brightness": { "min": 0, "max": 255 } }
+                }
+            ]
+        });
+        let publish = rumqttc::Publish::new(
+            "minihub/dimmer/config",
+            QoS::AtLeastOnce,
+            payload.to_string(),
+        );
+
+        let (dd, _) = MqttIntegration::parse_config_message(&MqttConfig::default(), &publish)
+            .unwrap()
+            .unwrap();
+
+        let meta = dd.entities[0].get_attribute_meta("brightness").unwrap();
+        assert_eq!(meta.min, Some(0.0));
+        assert_eq!(meta.max, Some(255.0));
+    }
+
+    #[test]
+    fn should_apply_device_class_and_unit_from_config_message() {
+        let payload = serde_json::json!({
+            "device": { "name": "Thermo" },
+            "entities": [
+                {
+                    "entity_id": "sensor.thermo",
+                    "friendly_name": "Thermo",
+                    "device_class": "temperature",
+                    "unit_of_measurement": "°C"
+                }
+            ]
+        });
+        let publish = rumqttc::Publish::new(
+            "minihub/thermo/config",
+            QoS::AtLeastOnce,
+            payload.to_string(),
+        );
+
+        let (dd, _) = MqttIntegration::parse_config_message(&MqttConfig::default(), &publish)
+            .unwrap()
+            .unwrap();
+
+        let entity = &dd.entities[0];
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.unit_of_measurement.as_deref(), Some("°C"));
+    }
+
     #[test]
     fn should_parse_config_message_and_return_discovered_device() {
         let config = MqttConfig {
@@ -571,6 +1028,35 @@ mod tests {
         assert_eq!(topic, "home/my_lamp/lamp/set");
     }
 
+    #[test]
+    fn should_read_versions_and_via_device_from_discovery_payload() {
+        let config = MqttConfig::default();
+
+        let payload = serde_json::json!({
+            "device": {
+                "name": "Plug",
+                "sw_version": "1.2.0",
+                "hw_version": "rev B",
+                "suggested_area": "Kitchen",
+                "via_device": "gateway"
+            },
+            "entities": [
+                { "entity_id": "switch.plug", "friendly_name": "Plug", "state": "off" }
+            ]
+        });
+
+        let publish =
+            rumqttc::Publish::new("minihub/plug/config", QoS::AtLeastOnce, payload.to_string());
+
+        let (dd, _) = MqttIntegration::parse_config_message(&config, &publish)
+            .unwrap()
+            .unwrap();
+        assert_eq!(dd.device.sw_version.as_deref(), Some("1.2.0"));
+        assert_eq!(dd.device.hw_version.as_deref(), Some("rev B"));
+        assert_eq!(dd.device.suggested_area.as_deref(), Some("Kitchen"));
+        assert_eq!(dd.via_device.as_deref(), Some("gateway"));
+    }
+
     #[test]
     fn should_discover_multiple_entities_per_device() {
         let config = MqttConfig::default();
@@ -683,7 +1169,7 @@ mod tests {
         };
         let integration = MqttIntegration::new(config);
         let opts = integration.mqtt_options();
-        assert_eq!(opts.keep_alive(), Duration::from_secs(60));
+        assert_eq!(opts.keep_alive(), Duration::from_mins(1));
     }
 
     #[tokio::test]
@@ -724,4 +1210,100 @@ mod tests {
             .await;
         assert!(result.is_err());
     }
+    #[test]
+    fn should_apply_state_message_to_discovered_entity() {
+        let entities = Mutex::new(HashMap::new());
+        let command_topics = Mutex::new(HashMap::new());
+        let light = discover_kitchen_light(&entities, &command_topics);
+
+        let publish =
+            rumqttc::Publish::new("minihub/kitchen_hub/kitchen/state", QoS::AtLeastOnce, "ON");
+        let updated =
+            MqttIntegration::apply_state_message(&publish, &entities, &command_topics).unwrap();
+
+        assert_eq!(updated.id, light.id);
+        assert_eq!(updated.state, EntityState::On);
+        assert_eq!(
+            entities.lock().unwrap()["light.kitchen"].state,
+            EntityState::On
+        );
+    }
+
+    #[test]
+    fn should_ignore_state_message_for_unknown_entity() {
+        let entities = Mutex::new(HashMap::new());
+        let command_topics = Mutex::new(HashMap::new());
+        discover_kitchen_light(&entities, &command_topics);
+
+        let publish =
+            rumqttc::Publish::new("minihub/kitchen_hub/pantry/state", QoS::AtLeastOnce, "on");
+        let updated = MqttIntegration::apply_state_message(&publish, &entities, &command_topics);
+        assert!(updated.is_none());
+    }
+
+    /// An outbox whose broker already acknowledged the connection.
+    fn connected_outbox(client: AsyncClient) -> Outbox {
+        let outbox = Outbox::new(client, BufferConfig::default());
+        outbox.connected();
+        outbox
+    }
+
+    #[tokio::test]
+    async fn should_complete_service_call_when_command_is_published() {
+        let (client, _eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        let (ctx, entity_id, handle) = spawn_service_call_loop(connected_outbox(client));
+
+        ctx.send(service_call(entity_id));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let published = ctx.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallCompleted);
+        assert_eq!(published[0].entity_id, Some(entity_id));
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn should_fail_service_call_when_client_is_disconnected() {
+        let (client, eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        drop(eventloop);
+        let (ctx, entity_id, handle) = spawn_service_call_loop(connected_outbox(client));
+
+        ctx.send(service_call(entity_id));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let published = ctx.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallFailed);
+        assert!(published[0].data.get("deferred").is_none());
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn should_report_deferred_service_call_when_broker_is_unreachable() {
+        let (client, _eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        let outbox = Outbox::new(client, BufferConfig::default());
+        let (ctx, entity_id, handle) = spawn_service_call_loop(outbox);
+
+        ctx.send(service_call(entity_id));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let published = ctx.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallFailed);
+        assert_eq!(published[0].data["deferred"], true);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn should_ignore_service_call_for_unknown_entity() {
+        let (client, _eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        let (ctx, _, handle) = spawn_service_call_loop(connected_outbox(client));
+
+        ctx.send(service_call(EntityId::new()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(ctx.published().is_empty());
+        handle.abort();
+    }
 }
@@ -0,0 +1,278 @@
+//! Outgoing service call publishes, buffered while the broker is unreachable.
+//!
+//! `rumqttc` accepts publishes whatever the state of the connection, so a
+//! command sent while the broker is down used to be lost without notice.
+//! The [`Outbox`] tracks the connection from the event loop instead: while
+//! disconnected, commands are queued and [`MqttError::Buffered`] is returned,
+//! and the queue is flushed once the broker acknowledges the reconnection.
+//! Messages older than [`BufferConfig::ttl`] by then are dropped.
+//!
+//! An outbox may also carry a birth message, published first on every
+//! connection — the counterpart of the client's last will.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+use rumqttc::AsyncClient;
+
+use crate::MqttError;
+use crate::config::{BufferConfig, Qos, TopicOptions};
+
+/// A publish waiting for the broker.
+#[derive(Debug)]
+pub(crate) struct Pending {
+    topic: String,
+    options: TopicOptions,
+    payload: Vec<u8>,
+    queued_at: Instant,
+}
+
+/// Client publishing commands, buffering them while disconnected.
+pub(crate) struct Outbox {
+    client: AsyncClient,
+    config: BufferConfig,
+    connected: AtomicBool,
+    pending: Mutex<VecDeque<Pending>>,
+    /// Retained `(topic, payload)` published on every connection.
+    birth: Option<(String, Vec<u8>)>,
+}
+
+impl Outbox {
+    /// Wrap `client`, disconnected until the event loop reports the first
+    /// connection acknowledgement.
+    pub(crate) fn new(client: AsyncClient, config: BufferConfig) -> Self {
+        Self {
+            client,
+            config,
+            connected: AtomicBool::new(false),
+            pending: Mutex::new(VecDeque::new()),
+            birth: None,
+        }
+    }
+
+    /// Publish `payload` on `topic`, retained, every time the broker
+    /// acknowledges a connection.
+    #[must_use]
+    pub(crate) fn with_birth(mut self, topic: impl Into<String>, payload: &str) -> Self {
+        self.birth = Some((topic.into(), payload.as_bytes().to_vec()));
+        self
+    }
+
+    /// The wrapped client, for subscriptions and unbuffered publishes.
+    pub(crate) fn client(&self) -> &AsyncClient {
+        &self.client
+    }
+
+    /// Publish `payload` on `topic`, or queue it while disconnected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MqttError::Buffered`] when the message was queued,
+    /// [`MqttError::NotConnected`] when disconnected with buffering
+    /// disabled, or [`MqttError::Client`] if the client rejects it.
+    pub(crate) async fn publish(
+        &self,
+        topic: &str,
+        options: TopicOptions,
+        payload: Vec<u8>,
+    ) -> Result<(), MqttError> {
+        if !self.connected.load(Ordering::Acquire) {
+            return self.enqueue(topic, options, payload);
+        }
+        self.client
+            .publish(topic, options.qos.into(), options.retain, payload)
+            .await
+            .map_err(MqttError::Client)
+    }
+
+    fn enqueue(
+        &self,
+        topic: &str,
+        options: TopicOptions,
+        payload: Vec<u8>,
+    ) -> Result<(), MqttError> {
+        if self.config.capacity == 0 {
+            return Err(MqttError::NotConnected);
+        }
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if pending.len() >= self.config.capacity {
+            pending.pop_front();
+            tracing::warn!(
+                capacity = self.config.capacity,
+                "MQTT outgoing buffer full, dropped the oldest message"
+            );
+        }
+        pending.push_back(Pending {
+            topic: topic.to_string(),
+            options,
+            payload,
+            queued_at: Instant::now(),
+        });
+        tracing::info!(%topic, buffered = pending.len(), "MQTT broker unreachable, message buffered");
+        Err(MqttError::Buffered)
+    }
+
+    /// Record that the connection was lost; publishes are buffered until
+    /// [`Self::connected`].
+    pub(crate) fn disconnected(&self) {
+        self.connected.store(false, Ordering::Release);
+    }
+
+    /// Record that the broker acknowledged the connection, returning the
+    /// buffered messages still within their TTL, oldest first, after the
+    /// birth message if any.
+    pub(crate) fn connected(&self) -> Vec<Pending> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        self.connected.store(true, Ordering::Release);
+        let ttl = self.config.ttl();
+        let total = pending.len();
+        let fresh: Vec<Pending> = pending
+            .drain(..)
+            .filter(|message| message.queued_at.elapsed() <= ttl)
+            .collect();
+        if fresh.len() < total {
+            tracing::warn!(
+                expired = total - fresh.len(),
+                "dropped buffered MQTT messages older than their TTL"
+            );
+        }
+        let birth = self.birth.iter().map(|(topic, payload)| Pending {
+            topic: topic.clone(),
+            options: TopicOptions {
+                qos: Qos::AtLeastOnce,
+                retain: true,
+            },
+            payload: payload.clone(),
+            queued_at: Instant::now(),
+        });
+        birth.chain(fresh).collect()
+    }
+
+    /// Publish messages taken by [`Self::connected`].
+    pub(crate) async fn flush(&self, messages: Vec<Pending>) {
+        let count = messages.len();
+        for message in messages {
+            let result = self
+                .client
+                .publish(
+                    &message.topic,
+                    message.options.qos.into(),
+                    message.options.retain,
+                    message.payload,
+                )
+                .await;
+            if let Err(err) = result {
+                tracing::warn!(%err, topic = %message.topic, "failed to flush buffered MQTT message");
+            }
+        }
+        tracing::info!(count, "flushed buffered MQTT messages");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rumqttc::MqttOptions;
+
+    use super::*;
+    use crate::config::TopicClasses;
+
+    fn outbox_with(config: BufferConfig) -> (Outbox, rumqttc::EventLoop) {
+        let (client, eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        (Outbox::new(client, config), eventloop)
+    }
+
+    fn outbox(capacity: usize) -> (Outbox, rumqttc::EventLoop) {
+        outbox_with(BufferConfig {
+            capacity,
+            ..BufferConfig::default()
+        })
+    }
+
+    fn command() -> TopicOptions {
+        TopicClasses::default().commands
+    }
+
+    #[tokio::test]
+    async fn should_buffer_publishes_while_disconnected() {
+        let (outbox, _eventloop) = outbox(10);
+
+        let result = outbox
+            .publish("minihub/a/set", command(), b"on".to_vec())
+            .await;
+
+        assert!(matches!(result, Err(MqttError::Buffered)));
+        let pending = outbox.connected();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].topic, "minihub/a/set");
+    }
+
+    #[tokio::test]
+    async fn should_publish_directly_once_connected() {
+        let (outbox, _eventloop) = outbox(10);
+        outbox.connected();
+
+        outbox
+            .publish("minihub/a/set", command(), b"on".to_vec())
+            .await
+            .unwrap();
+
+        assert!(outbox.connected().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_drop_oldest_message_when_buffer_is_full() {
+        let (outbox, _eventloop) = outbox(2);
+
+        for topic in ["first", "second", "third"] {
+            let _ = outbox.publish(topic, command(), Vec::new()).await;
+        }
+
+        let topics: Vec<_> = outbox
+            .connected()
+            .into_iter()
+            .map(|message| message.topic)
+            .collect();
+        assert_eq!(topics, ["second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_publishes_while_disconnected_when_buffer_is_disabled() {
+        let (outbox, _eventloop) = outbox(0);
+
+        let result = outbox.publish("minihub/a/set", command(), Vec::new()).await;
+
+        assert!(matches!(result, Err(MqttError::NotConnected)));
+    }
+
+    #[tokio::test]
+    async fn should_drop_expired_messages_on_reconnect() {
+        let (outbox, _eventloop) = outbox_with(BufferConfig {
+            capacity: 10,
+            ttl_secs: 0,
+        });
+        let _ = outbox.publish("stale", command(), Vec::new()).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        assert!(outbox.connected().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_publish_birth_message_first_on_every_connection() {
+        let (outbox, _eventloop) = outbox(10);
+        let outbox = outbox.with_birth("garage/status", "online");
+        let _ = outbox.publish("minihub/a/set", command(), Vec::new()).await;
+
+        let first: Vec<_> = outbox.connected().into_iter().map(|m| m.topic).collect();
+        outbox.disconnected();
+        let second = outbox.connected();
+
+        assert_eq!(first, ["garage/status", "minihub/a/set"]);
+        assert_eq!(second.len(), 1);
+        assert!(second[0].options.retain);
+        assert_eq!(second[0].payload, b"online");
+    }
+}
@@ -0,0 +1,655 @@
+//! Remote integration — entities of a federated minihub, seen through MQTT.
+//!
+//! A secondary hub running the MQTT integration with
+//! [`MqttConfig::federation_enabled`] publishes its entities under its base
+//! topic. This integration, run by the primary hub, materializes them as the
+//! entities of one device named after the remote hub, and sends the service
+//! calls targeting them back to the secondary.
+//!
+//! | Topic pattern | Direction | Purpose |
+//! |---------------|-----------|---------|
+//! | `{base}/states/{entity_id}` | Broker → minihub | Retained state of a remote entity |
+//! | `{base}/status` | Broker → minihub | `online` / `offline` availability of the remote hub |
+//! | `{base}/states/{entity_id}/set` | minihub → Broker | `{"service": …, "data": …}` service call |
+//!
+//! Remote entity ids are prefixed with the slug of the hub name so they
+//! cannot clash with local ones: `light.kitchen` on a hub named `Garage`
+//! becomes `light.garage_kitchen`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use rumqttc::AsyncClient;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeValue, DeviceClass, Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError, ValidationError};
+use minihub_domain::event::{Event as DomainEvent, EventType, ServiceCallPayload};
+use minihub_domain::id::EntityId;
+
+use crate::config::{STATUS_ONLINE, TopicOptions};
+use crate::outbox::Outbox;
+use crate::{MqttConfig, MqttError, MqttIntegration, service_call_result_event};
+
+/// Entities of the remote hub, keyed by their remote `entity_id`.
+type Tracked = Arc<Mutex<HashMap<String, RemoteEntity>>>;
+
+/// A remote entity and the last state its hub reported.
+#[derive(Debug, Clone)]
+struct RemoteEntity {
+    entity: Entity,
+    /// Restored when the hub comes back online.
+    reported: EntityState,
+}
+
+/// A topic under the remote hub's base topic.
+#[derive(Debug, PartialEq, Eq)]
+enum Topic<'a> {
+    Status,
+    State(&'a str),
+    Ignored,
+}
+
+impl<'a> Topic<'a> {
+    fn parse(base: &str, topic: &'a str) -> Self {
+        let Some(rest) = topic
+            .strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('/'))
+        else {
+            return Self::Ignored;
+        };
+        if rest == "status" {
+            return Self::Status;
+        }
+        match rest.strip_prefix("states/") {
+            Some(entity_id) if !entity_id.is_empty() && !entity_id.contains('/') => {
+                Self::State(entity_id)
+            }
+            _ => Self::Ignored,
+        }
+    }
+}
+
+/// State message published by the bridge of the remote hub.
+#[derive(Debug, Deserialize)]
+struct RemoteState {
+    state: EntityState,
+    #[serde(default)]
+    attributes: HashMap<String, AttributeValue>,
+    #[serde(default)]
+    friendly_name: Option<String>,
+    #[serde(default)]
+    device_class: Option<DeviceClass>,
+    #[serde(default)]
+    unit_of_measurement: Option<String>,
+}
+
+/// Remote hub integration.
+///
+/// Tracks the entities a federated minihub publishes on its bridge topics,
+/// marks them unavailable while the hub is offline, and forwards service
+/// calls to the hub's command topics.
+pub struct RemoteIntegration {
+    config: MqttConfig,
+    name: String,
+    device: Option<Device>,
+    outbox: Option<Arc<Outbox>>,
+    eventloop_handle: Option<JoinHandle<()>>,
+    publish_rx: Option<mpsc::Receiver<rumqttc::Publish>>,
+    background_handle: Option<JoinHandle<()>>,
+    subscriber_handle: Option<JoinHandle<()>>,
+    entities: Tracked,
+}
+
+impl RemoteIntegration {
+    /// Create an integration following the hub federated on
+    /// `config.base_topic`, shown as a device called `name`.
+    #[must_use]
+    pub fn new(config: MqttConfig, name: impl Into<String>) -> Self {
+        Self {
+            config,
+            name: name.into(),
+            device: None,
+            outbox: None,
+            eventloop_handle: None,
+            publish_rx: None,
+            background_handle: None,
+            subscriber_handle: None,
+            entities: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The device standing for the remote hub.
+    fn hub_device(&self) -> Result<Device, MiniHubError> {
+        Device::builder()
+            .name(&self.name)
+            .manufacturer("minihub")
+            .model("remote hub")
+            .integration("remote")
+            .unique_id(&self.config.base_topic)
+            .build()
+    }
+
+    /// Background loop handling the state and status messages of the hub.
+    async fn background_message_loop(
+        base_topic: String,
+        device: Device,
+        mut publish_rx: mpsc::Receiver<rumqttc::Publish>,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+    ) {
+        let hub = slugify(&device.name);
+        let mut online = true;
+        while let Some(publish) = publish_rx.recv().await {
+            match Topic::parse(&base_topic, &publish.topic) {
+                Topic::Status => {
+                    online = String::from_utf8_lossy(&publish.payload).trim() == STATUS_ONLINE;
+                    tracing::info!(hub = %device.name, online, "remote hub availability changed");
+                    for entity in Self::apply_status(online, &entities) {
+                        if let Err(err) = ctx.upsert_entity(entity).await {
+                            tracing::warn!(%err, "failed to persist remote availability");
+                        }
+                    }
+                }
+                Topic::State(remote_id) => {
+                    // An empty retained message clears a removed entity.
+                    if publish.payload.is_empty() {
+                        continue;
+                    }
+                    let state: RemoteState = match serde_json::from_slice(&publish.payload) {
+                        Ok(state) => state,
+                        Err(err) => {
+                            tracing::warn!(%err, remote_id, "failed to parse remote entity state");
+                            continue;
+                        }
+                    };
+                    let applied =
+                        Self::apply_state(&device, &hub, remote_id, state, online, &entities);
+                    let result = match applied {
+                        Ok((entity, true)) => {
+                            ctx.persist_discovered(DiscoveredDevice {
+                                device: device.clone(),
+                                entities: vec![entity],
+                                via_device: None,
+                            })
+                            .await
+                        }
+                        Ok((entity, false)) => ctx.upsert_entity(entity).await.map(|_| ()),
+                        Err(err) => {
+                            tracing::warn!(%err, remote_id, "skipping remote entity");
+                            continue;
+                        }
+                    };
+                    if let Err(err) = result {
+                        tracing::warn!(%err, remote_id, "failed to persist remote entity");
+                    }
+                }
+                Topic::Ignored => {}
+            }
+        }
+        tracing::debug!("remote background message loop stopped");
+    }
+
+    /// Apply the state message of `remote_id`, returning the entity and
+    /// whether it was seen for the first time.
+    ///
+    /// While the hub is offline the reported state is only kept aside and
+    /// the entity stays unavailable.
+    fn apply_state(
+        device: &Device,
+        hub: &str,
+        remote_id: &str,
+        state: RemoteState,
+        online: bool,
+        entities: &Mutex<HashMap<String, RemoteEntity>>,
+    ) -> Result<(Entity, bool), MqttError> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let shown = if online {
+            state.state.clone()
+        } else {
+            EntityState::Unavailable
+        };
+        if let Some(known) = tracked.get_mut(remote_id) {
+            known.reported = state.state;
+            known.entity.attributes = state.attributes;
+            if let Some(friendly_name) = state.friendly_name {
+                known.entity.friendly_name = friendly_name;
+            }
+            known
+                .entity
+                .update_state(shown, minihub_domain::time::now());
+            return Ok((known.entity.clone(), false));
+        }
+
+        let (domain, object) = remote_id.split_once('.').ok_or_else(|| {
+            MqttError::Domain(ValidationError::InvalidEntityId(remote_id.to_string()).into())
+        })?;
+        let mut builder = Entity::builder()
+            .device_id(device.id)
+            .entity_id(format!("{domain}.{hub}_{object}"))
+            .friendly_name(state.friendly_name.as_deref().unwrap_or(remote_id))
+            .state(shown);
+        if let Some(device_class) = state.device_class {
+            builder = builder.device_class(device_class);
+        }
+        if let Some(unit) = &state.unit_of_measurement {
+            builder = builder.unit_of_measurement(unit);
+        }
+        for (key, value) in state.attributes {
+            builder = builder.attribute(key, value);
+        }
+        let entity = builder.build().map_err(MqttError::Domain)?;
+        tracked.insert(
+            remote_id.to_string(),
+            RemoteEntity {
+                entity: entity.clone(),
+                reported: state.state,
+            },
+        );
+        Ok((entity, true))
+    }
+
+    /// Mark every entity unavailable when the hub goes offline, and restore
+    /// the last reported states once it is back, returning the changed ones.
+    fn apply_status(online: bool, entities: &Mutex<HashMap<String, RemoteEntity>>) -> Vec<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut changed = Vec::new();
+        for remote in tracked.values_mut() {
+            let state = if online {
+                remote.reported.clone()
+            } else {
+                EntityState::Unavailable
+            };
+            if remote.entity.state == state {
+                continue;
+            }
+            remote
+                .entity
+                .update_state(state, minihub_domain::time::now());
+            changed.push(remote.entity.clone());
+        }
+        changed
+    }
+
+    /// Remote `entity_id` of the tracked entity stored as `entity_id`.
+    fn remote_id_for(
+        entities: &Mutex<HashMap<String, RemoteEntity>>,
+        entity_id: &str,
+    ) -> Option<String> {
+        entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(_, remote)| remote.entity.entity_id == entity_id)
+            .map(|(remote_id, _)| remote_id.clone())
+    }
+
+    /// Publish a service call on the command topic of `remote_id`.
+    async fn publish_command(
+        outbox: &Outbox,
+        options: TopicOptions,
+        base_topic: &str,
+        remote_id: &str,
+        service: &str,
+        data: &serde_json::Value,
+    ) -> Result<(), MqttError> {
+        let topic = format!("{base_topic}/states/{remote_id}/set");
+        let payload = serde_json::json!({
+            "service": service,
+            "data": data,
+        });
+        outbox
+            .publish(&topic, options, payload.to_string().into_bytes())
+            .await?;
+
+        tracing::info!(service, topic = %topic, "published remote service call");
+        Ok(())
+    }
+
+    /// Forward [`EventType::ServiceCallRequested`] events targeting remote
+    /// entities to the hub they belong to.
+    async fn service_call_loop(
+        outbox: Arc<Outbox>,
+        options: TopicOptions,
+        base_topic: String,
+        mut rx: tokio::sync::broadcast::Receiver<DomainEvent>,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+    ) {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "remote event subscriber lagged, some events were missed"
+                    );
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    tracing::info!("remote event subscriber channel closed, stopping");
+                    break;
+                }
+            };
+
+            if event.event_type != EventType::ServiceCallRequested {
+                continue;
+            }
+            let Some(entity_id) = event.entity_id else {
+                continue;
+            };
+
+            let remote_id = match ctx.find_entity_by_id(entity_id).await {
+                Ok(Some(entity)) => Self::remote_id_for(&entities, &entity.entity_id),
+                Ok(None) => None,
+                Err(err) => {
+                    tracing::warn!(%err, %entity_id, "failed to look up entity for service call");
+                    continue;
+                }
+            };
+            let Some(remote_id) = remote_id else {
+                continue;
+            };
+
+            let Ok(ServiceCallPayload { service, data }) = event.payload() else {
+                continue;
+            };
+            let service = service.as_str();
+
+            let result =
+                Self::publish_command(&outbox, options, &base_topic, &remote_id, service, &data)
+                    .await;
+            let result_event =
+                service_call_result_event(entity_id, service, result).with_cause(&event);
+            if let Err(err) = ctx.publish(result_event).await {
+                tracing::warn!(%err, "failed to publish service call result event");
+            }
+        }
+    }
+}
+
+/// Turn a hub name into an `entity_id`-safe slug.
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl Integration for RemoteIntegration {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        self.device = Some(self.hub_device()?);
+        let (client, eventloop) = AsyncClient::new(self.config.mqtt_options(), 64);
+        let outbox = Arc::new(Outbox::new(client, self.config.buffer));
+
+        let (rx, handle) = MqttIntegration::spawn_eventloop(eventloop, Arc::clone(&outbox));
+        self.eventloop_handle = Some(handle);
+        self.publish_rx = Some(rx);
+
+        let qos = self.config.topics.subscriptions.into();
+        for topic in [
+            self.config.status_topic(),
+            format!("{}/states/+", self.config.base_topic),
+        ] {
+            outbox
+                .client()
+                .subscribe(&topic, qos)
+                .await
+                .map_err(MqttError::Client)?;
+            tracing::info!(%topic, "subscribed to remote hub topic");
+        }
+        self.outbox = Some(outbox);
+
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        let rx = self.publish_rx.take().ok_or(MqttError::NotConnected)?;
+        let outbox = self.outbox.clone().ok_or(MqttError::NotConnected)?;
+        let device = self.device.clone().ok_or(MqttError::NotConnected)?;
+
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let bus_rx = ctx.subscribe();
+        self.subscriber_handle = Some(tokio::spawn(Self::service_call_loop(
+            outbox,
+            self.config.topics.commands,
+            self.config.base_topic.clone(),
+            bus_rx,
+            ctx.clone(),
+            Arc::clone(&self.entities),
+        )));
+
+        self.background_handle = Some(tokio::spawn(Self::background_message_loop(
+            self.config.base_topic.clone(),
+            device,
+            rx,
+            ctx,
+            Arc::clone(&self.entities),
+        )));
+
+        tracing::info!(hub = %self.name, "remote background message loop started");
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+    ) -> Result<Entity, MiniHubError> {
+        let outbox = self.outbox.as_ref().ok_or(MqttError::NotConnected)?;
+        let (remote_id, entity) = self
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(_, remote)| remote.entity.id == entity_id)
+            .map(|(remote_id, remote)| (remote_id.clone(), remote.entity.clone()))
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+
+        Self::publish_command(
+            outbox,
+            self.config.topics.commands,
+            &self.config.base_topic,
+            &remote_id,
+            service,
+            &data,
+        )
+        .await?;
+        Ok(entity)
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        for handle in [
+            self.subscriber_handle.take(),
+            self.background_handle.take(),
+            self.eventloop_handle.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            handle.abort();
+        }
+        self.outbox = None;
+        tracing::info!(hub = %self.name, "remote integration stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn garage() -> Device {
+        RemoteIntegration::new(MqttConfig::default(), "Garage")
+            .hub_device()
+            .unwrap()
+    }
+
+    fn state(json: serde_json::Value) -> RemoteState {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn tracked() -> Tracked {
+        Arc::new(Mutex::new(HashMap::new()))
+    }
+
+    #[test]
+    fn should_classify_remote_topics() {
+        assert_eq!(Topic::parse("garage", "garage/status"), Topic::Status);
+        assert_eq!(
+            Topic::parse("garage", "garage/states/light.kitchen"),
+            Topic::State("light.kitchen")
+        );
+        assert_eq!(
+            Topic::parse("garage", "garage/states/light.kitchen/set"),
+            Topic::Ignored
+        );
+        assert_eq!(Topic::parse("garage", "garage/dev/config"), Topic::Ignored);
+        assert_eq!(Topic::parse("garage", "garagex/status"), Topic::Ignored);
+    }
+
+    #[test]
+    fn should_describe_remote_hub_as_device() {
+        let device = garage();
+
+        assert_eq!(device.name, "Garage");
+        assert_eq!(device.integration, "remote");
+        assert_eq!(device.unique_id, "minihub");
+    }
+
+    #[test]
+    fn should_prefix_new_entities_with_hub_slug() {
+        let entities = tracked();
+
+        let (entity, new) = RemoteIntegration::apply_state(
+            &garage(),
+            "garage",
+            "sensor.outdoor",
+            state(serde_json::json!({
+                "state": 12.5,
+                "attributes": { "battery": 80 },
+                "friendly_name": "Outdoor",
+                "device_class": "temperature",
+                "unit_of_measurement": "°C",
+            })),
+            true,
+            &entities,
+        )
+        .unwrap();
+
+        assert!(new);
+        assert_eq!(entity.entity_id, "sensor.garage_outdoor");
+        assert_eq!(entity.friendly_name, "Outdoor");
+        assert_eq!(entity.state, EntityState::Numeric(12.5));
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.attributes["battery"], AttributeValue::Int(80));
+    }
+
+    #[test]
+    fn should_update_known_entities() {
+        let entities = tracked();
+        let on = || state(serde_json::json!({ "state": "on" }));
+        RemoteIntegration::apply_state(&garage(), "garage", "light.door", on(), true, &entities)
+            .unwrap();
+
+        let (entity, new) = RemoteIntegration::apply_state(
+            &garage(),
+            "garage",
+            "light.door",
+            state(serde_json::json!({ "state": "off" })),
+            true,
+            &entities,
+        )
+        .unwrap();
+
+        assert!(!new);
+        assert_eq!(entity.entity_id, "light.garage_door");
+        assert_eq!(entity.state, EntityState::Off);
+    }
+
+    #[test]
+    fn should_reject_entity_id_without_domain() {
+        let result = RemoteIntegration::apply_state(
+            &garage(),
+            "garage",
+            "nodomain",
+            state(serde_json::json!({ "state": "on" })),
+            true,
+            &tracked(),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_mark_entities_unavailable_while_hub_is_offline() {
+        let entities = tracked();
+        let on = || state(serde_json::json!({ "state": "on" }));
+        RemoteIntegration::apply_state(&garage(), "garage", "light.door", on(), true, &entities)
+            .unwrap();
+
+        let offline = RemoteIntegration::apply_status(false, &entities);
+        let (while_offline, _) = RemoteIntegration::apply_state(
+            &garage(),
+            "garage",
+            "light.door",
+            state(serde_json::json!({ "state": "off" })),
+            false,
+            &entities,
+        )
+        .unwrap();
+        let online = RemoteIntegration::apply_status(true, &entities);
+        let again = RemoteIntegration::apply_status(true, &entities);
+
+        assert_eq!(offline[0].state, EntityState::Unavailable);
+        assert_eq!(while_offline.state, EntityState::Unavailable);
+        assert_eq!(online[0].state, EntityState::Off);
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn should_find_remote_id_of_stored_entity() {
+        let entities = tracked();
+        let on = || state(serde_json::json!({ "state": "on" }));
+        RemoteIntegration::apply_state(&garage(), "garage", "light.door", on(), true, &entities)
+            .unwrap();
+
+        assert_eq!(
+            RemoteIntegration::remote_id_for(&entities, "light.garage_door").as_deref(),
+            Some("light.door")
+        );
+        assert!(RemoteIntegration::remote_id_for(&entities, "light.door").is_none());
+    }
+
+    #[tokio::test]
+    async fn should_return_error_when_service_call_without_client() {
+        let integration = RemoteIntegration::new(MqttConfig::default(), "Garage");
+        let result = integration
+            .handle_service_call(EntityId::new(), "turn_on", serde_json::json!({}))
+            .await;
+        assert!(matches!(result, Err(MiniHubError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn should_teardown_without_error_when_not_connected() {
+        let mut integration = RemoteIntegration::new(MqttConfig::default(), "Garage");
+        assert!(integration.teardown().await.is_ok());
+        assert_eq!(integration.name(), "remote");
+    }
+}
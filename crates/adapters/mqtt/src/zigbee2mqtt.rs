@@ -0,0 +1,581 @@
+//! zigbee2mqtt integration — bridges a zigbee2mqtt instance into minihub.
+//!
+//! Unlike [`MqttIntegration`](crate::MqttIntegration), which expects devices
+//! to speak minihub's own topic layout, this integration follows the
+//! zigbee2mqtt conventions. The configured base topic defaults to
+//! `zigbee2mqtt` in the daemon configuration.
+//!
+//! | Topic pattern | Direction | Purpose |
+//! |---------------|-----------|---------|
+//! | `{base}/bridge/devices` | Broker → minihub | Device list with `exposes` metadata |
+//! | `{base}/{friendly_name}` | Broker → minihub | JSON state of a device |
+//! | `{base}/{friendly_name}/availability` | Broker → minihub | `online` / `offline` |
+//! | `{base}/{friendly_name}/set` | minihub → Broker | JSON set payload, e.g. `{"state":"ON"}` |
+
+mod exposes;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use rumqttc::AsyncClient;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event as DomainEvent, EventType, ServiceCallPayload};
+use minihub_domain::id::EntityId;
+
+use self::exposes::{Binding, BridgeDevice};
+use crate::config::TopicOptions;
+use crate::outbox::Outbox;
+use crate::{MqttConfig, MqttError, MqttIntegration, service_call_result_event};
+
+/// Entities known to the integration, keyed by `entity_id` string.
+type Tracked = Arc<Mutex<HashMap<String, (Entity, Binding)>>>;
+
+/// A topic under the zigbee2mqtt base topic.
+#[derive(Debug, PartialEq, Eq)]
+enum Topic<'a> {
+    Devices,
+    State(&'a str),
+    Availability(&'a str),
+    Ignored,
+}
+
+impl<'a> Topic<'a> {
+    fn parse(base: &str, topic: &'a str) -> Self {
+        let Some(rest) = topic
+            .strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('/'))
+        else {
+            return Self::Ignored;
+        };
+        if rest == "bridge/devices" {
+            return Self::Devices;
+        }
+        if rest.starts_with("bridge/") {
+            return Self::Ignored;
+        }
+        if let Some(device) = rest.strip_suffix("/availability") {
+            return Self::Availability(device);
+        }
+        if rest.ends_with("/set") || rest.ends_with("/get") {
+            return Self::Ignored;
+        }
+        Self::State(rest)
+    }
+}
+
+/// zigbee2mqtt integration.
+///
+/// Discovers devices from the retained `bridge/devices` message, keeps
+/// entities in sync with per-device state topics, and forwards service calls
+/// as zigbee2mqtt `/set` payloads.
+pub struct Zigbee2MqttIntegration {
+    config: MqttConfig,
+    outbox: Option<Arc<Outbox>>,
+    eventloop_handle: Option<JoinHandle<()>>,
+    publish_rx: Option<mpsc::Receiver<rumqttc::Publish>>,
+    background_handle: Option<JoinHandle<()>>,
+    subscriber_handle: Option<JoinHandle<()>>,
+    entities: Tracked,
+}
+
+impl Zigbee2MqttIntegration {
+    /// Create a new zigbee2mqtt integration.
+    ///
+    /// `config.base_topic` must match zigbee2mqtt's `mqtt.base_topic`.
+    #[must_use]
+    pub fn new(config: MqttConfig) -> Self {
+        Self {
+            config,
+            outbox: None,
+            eventloop_handle: None,
+            publish_rx: None,
+            background_handle: None,
+            subscriber_handle: None,
+            entities: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Background loop handling discovery, state and availability messages.
+    async fn background_message_loop(
+        base_topic: String,
+        mut publish_rx: mpsc::Receiver<rumqttc::Publish>,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+    ) {
+        while let Some(publish) = publish_rx.recv().await {
+            match Topic::parse(&base_topic, &publish.topic) {
+                Topic::Devices => match Self::apply_devices_message(&publish.payload, &entities) {
+                    Ok(discovered) => {
+                        for dd in discovered {
+                            if let Err(err) = ctx.persist_discovered(dd).await {
+                                tracing::warn!(%err, "failed to persist zigbee2mqtt discovery");
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        tracing::warn!(%err, "failed to parse zigbee2mqtt device list");
+                    }
+                },
+                Topic::State(device) => {
+                    let Ok(payload) = serde_json::from_slice(&publish.payload) else {
+                        tracing::debug!(device, "ignoring non-JSON zigbee2mqtt state");
+                        continue;
+                    };
+                    for entity in Self::apply_state_message(device, &payload, &entities) {
+                        if let Err(err) = ctx.upsert_entity(entity).await {
+                            tracing::warn!(%err, "failed to persist zigbee2mqtt state update");
+                        }
+                    }
+                }
+                Topic::Availability(device) => {
+                    let online = parse_availability(&publish.payload);
+                    for entity in Self::apply_availability(device, online, &entities) {
+                        if let Err(err) = ctx.upsert_entity(entity).await {
+                            tracing::warn!(%err, "failed to persist zigbee2mqtt availability");
+                        }
+                    }
+                }
+                Topic::Ignored => {}
+            }
+        }
+        tracing::debug!("zigbee2mqtt background message loop stopped");
+    }
+
+    /// Map a `bridge/devices` payload and track the resulting entities.
+    ///
+    /// Entities that were already tracked keep their last known state and
+    /// attributes, since zigbee2mqtt republishes the list whenever a device
+    /// joins or is renamed.
+    fn apply_devices_message(
+        payload: &[u8],
+        entities: &Mutex<HashMap<String, (Entity, Binding)>>,
+    ) -> Result<Vec<DiscoveredDevice>, MqttError> {
+        let devices: Vec<BridgeDevice> =
+            serde_json::from_slice(payload).map_err(MqttError::PayloadParse)?;
+
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut discovered = Vec::new();
+        for bridge in &devices {
+            let mapped = match exposes::map_device(bridge) {
+                Ok(Some(mapped)) => mapped,
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::warn!(%err, device = %bridge.friendly_name, "skipping zigbee2mqtt device");
+                    continue;
+                }
+            };
+            let mut device_entities = Vec::new();
+            for (mut entity, binding) in mapped.entities {
+                if let Some((known, _)) = tracked.get(&entity.entity_id) {
+                    entity.state = known.state.clone();
+                    entity.attributes.clone_from(&known.attributes);
+                }
+                tracked.insert(entity.entity_id.clone(), (entity.clone(), binding));
+                device_entities.push(entity);
+            }
+            tracing::info!(
+                device = %mapped.device.name,
+                entity_count = device_entities.len(),
+                "discovered zigbee2mqtt device"
+            );
+            discovered.push(DiscoveredDevice {
+                device: mapped.device,
+                entities: device_entities,
+                via_device: None,
+            });
+        }
+        Ok(discovered)
+    }
+
+    /// Apply a device state payload, returning the entities that changed.
+    fn apply_state_message(
+        device: &str,
+        payload: &serde_json::Value,
+        entities: &Mutex<HashMap<String, (Entity, Binding)>>,
+    ) -> Vec<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        tracked
+            .values_mut()
+            .filter(|(_, binding)| binding.device == device)
+            .filter_map(|(entity, binding)| {
+                exposes::apply_state(entity, binding, payload).then(|| entity.clone())
+            })
+            .collect()
+    }
+
+    /// Mark a device's entities unavailable when it goes offline, and
+    /// available again once it is back.
+    fn apply_availability(
+        device: &str,
+        online: bool,
+        entities: &Mutex<HashMap<String, (Entity, Binding)>>,
+    ) -> Vec<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut changed = Vec::new();
+        for (entity, binding) in tracked.values_mut() {
+            if binding.device != device {
+                continue;
+            }
+            let state = match (online, entity.state.is_available()) {
+                (false, true) => EntityState::Unavailable,
+                // Stateful entities learn their state from the next message.
+                (true, false) if binding.state.is_some() || binding.reading.is_some() => {
+                    EntityState::Unknown
+                }
+                (true, false) => EntityState::On,
+                _ => continue,
+            };
+            entity.update_state(state, minihub_domain::time::now());
+            changed.push(entity.clone());
+        }
+        changed
+    }
+
+    /// Publish the `/set` payload for `service` on behalf of a tracked entity.
+    async fn publish_command(
+        outbox: &Outbox,
+        options: TopicOptions,
+        base_topic: &str,
+        binding: &Binding,
+        service: &str,
+        data: &serde_json::Value,
+    ) -> Result<(), MqttError> {
+        let payload = exposes::command_payload(binding, service, data)
+            .ok_or_else(|| MqttError::UnsupportedService(service.to_string()))?;
+        let topic = format!("{base_topic}/{}/set", binding.device);
+        outbox
+            .publish(&topic, options, payload.to_string().into_bytes())
+            .await?;
+
+        tracing::info!(service, topic = %topic, "published zigbee2mqtt set command");
+        Ok(())
+    }
+
+    /// Forward [`EventType::ServiceCallRequested`] events targeting
+    /// zigbee2mqtt entities to the broker.
+    ///
+    /// As with [`MqttIntegration`], requests are resolved through the
+    /// persisted entity's `entity_id` string.
+    async fn service_call_loop(
+        outbox: Arc<Outbox>,
+        options: TopicOptions,
+        base_topic: String,
+        mut rx: tokio::sync::broadcast::Receiver<DomainEvent>,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+    ) {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "zigbee2mqtt event subscriber lagged, some events were missed"
+                    );
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    tracing::info!("zigbee2mqtt event subscriber channel closed, stopping");
+                    break;
+                }
+            };
+
+            if event.event_type != EventType::ServiceCallRequested {
+                continue;
+            }
+            let Some(entity_id) = event.entity_id else {
+                continue;
+            };
+
+            let binding = match ctx.find_entity_by_id(entity_id).await {
+                Ok(Some(entity)) => entities
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get(&entity.entity_id)
+                    .map(|(_, binding)| binding.clone()),
+                Ok(None) => None,
+                Err(err) => {
+                    tracing::warn!(%err, %entity_id, "failed to look up entity for service call");
+                    continue;
+                }
+            };
+            let Some(binding) = binding else {
+                continue;
+            };
+
+            let Ok(ServiceCallPayload { service, data }) = event.payload() else {
+                continue;
+            };
+            let service = service.as_str();
+
+            let result =
+                Self::publish_command(&outbox, options, &base_topic, &binding, service, &data)
+                    .await;
+            let result_event = service_call_result_event(entity_id, service, result);
+            if let Err(err) = ctx.publish(result_event).await {
+                tracing::warn!(%err, "failed to publish service call result event");
+            }
+        }
+    }
+}
+
+/// Interpret an availability payload, either the legacy plain `online` /
+/// `offline` string or the JSON `{"state":"online"}` form.
+fn parse_availability(payload: &[u8]) -> bool {
+    let state = serde_json::from_slice::<serde_json::Value>(payload)
+        .ok()
+        .and_then(|value| value.get("state")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| String::from_utf8_lossy(payload).trim().to_string());
+    state == "online"
+}
+
+impl Integration for Zigbee2MqttIntegration {
+    fn name(&self) -> &'static str {
+        "zigbee2mqtt"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        let (client, eventloop) = AsyncClient::new(self.config.mqtt_options(), 64);
+        let outbox = Arc::new(Outbox::new(client, self.config.buffer));
+
+        let (rx, handle) = MqttIntegration::spawn_eventloop(eventloop, Arc::clone(&outbox));
+        self.eventloop_handle = Some(handle);
+        self.publish_rx = Some(rx);
+
+        let topic = format!("{}/#", self.config.base_topic);
+        outbox
+            .client()
+            .subscribe(&topic, self.config.topics.subscriptions.into())
+            .await
+            .map_err(MqttError::Client)?;
+        tracing::info!(%topic, "subscribed to zigbee2mqtt topics");
+        self.outbox = Some(outbox);
+
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        let rx = self.publish_rx.take().ok_or(MqttError::NotConnected)?;
+        let outbox = self.outbox.clone().ok_or(MqttError::NotConnected)?;
+
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let bus_rx = ctx.subscribe();
+        self.subscriber_handle = Some(tokio::spawn(Self::service_call_loop(
+            outbox,
+            self.config.topics.commands,
+            self.config.base_topic.clone(),
+            bus_rx,
+            ctx.clone(),
+            Arc::clone(&self.entities),
+        )));
+
+        self.background_handle = Some(tokio::spawn(Self::background_message_loop(
+            self.config.base_topic.clone(),
+            rx,
+            ctx,
+            Arc::clone(&self.entities),
+        )));
+
+        tracing::info!("zigbee2mqtt background message loop started");
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+    ) -> Result<Entity, MiniHubError> {
+        let outbox = self.outbox.as_ref().ok_or(MqttError::NotConnected)?;
+        let (entity, binding) = self
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .find(|(entity, _)| entity.id == entity_id)
+            .cloned()
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+
+        Self::publish_command(
+            outbox,
+            self.config.topics.commands,
+            &self.config.base_topic,
+            &binding,
+            service,
+            &data,
+        )
+        .await?;
+        Ok(entity)
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        for handle in [
+            self.subscriber_handle.take(),
+            self.background_handle.take(),
+            self.eventloop_handle.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            handle.abort();
+        }
+        self.outbox = None;
+        tracing::info!("zigbee2mqtt integration stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICES: &str = r#"[
+        { "ieee_address": "0x00", "friendly_name": "Coordinator", "type": "Coordinator" },
+        {
+            "ieee_address": "0x0017880104e45517",
+            "friendly_name": "kitchen/bulb",
+            "type": "Router",
+            "definition": { "vendor": "Philips", "model": "9290012573A", "exposes": [
+                { "type": "light", "features": [
+                    { "type": "binary", "property": "state", "access": 7,
+                      "value_on": "ON", "value_off": "OFF", "value_toggle": "TOGGLE" },
+                    { "type": "numeric", "property": "brightness", "access": 7,
+                      "value_min": 0, "value_max": 254 }
+                ] }
+            ] }
+        }
+    ]"#;
+
+    fn discovered() -> (Tracked, Vec<DiscoveredDevice>) {
+        let entities: Tracked = Arc::new(Mutex::new(HashMap::new()));
+        let discovered =
+            Zigbee2MqttIntegration::apply_devices_message(DEVICES.as_bytes(), &entities).unwrap();
+        (entities, discovered)
+    }
+
+    #[test]
+    fn should_classify_zigbee2mqtt_topics() {
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqtt/bridge/devices"),
+            Topic::Devices
+        );
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqtt/kitchen/bulb"),
+            Topic::State("kitchen/bulb")
+        );
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqtt/kitchen/bulb/availability"),
+            Topic::Availability("kitchen/bulb")
+        );
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqtt/kitchen/bulb/set"),
+            Topic::Ignored
+        );
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqtt/bridge/state"),
+            Topic::Ignored
+        );
+        assert_eq!(
+            Topic::parse("zigbee2mqtt", "zigbee2mqttx/bulb"),
+            Topic::Ignored
+        );
+    }
+
+    #[test]
+    fn should_discover_devices_from_bridge_message() {
+        let (entities, discovered) = discovered();
+
+        assert_eq!(discovered.len(), 1);
+        assert_eq!(discovered[0].device.integration, "zigbee2mqtt");
+        assert_eq!(discovered[0].entities[0].entity_id, "light.kitchen_bulb");
+        assert!(entities.lock().unwrap().contains_key("light.kitchen_bulb"));
+    }
+
+    #[test]
+    fn should_return_error_for_invalid_device_list() {
+        let entities: Tracked = Arc::new(Mutex::new(HashMap::new()));
+        let result = Zigbee2MqttIntegration::apply_devices_message(b"{bad", &entities);
+        assert!(matches!(result, Err(MqttError::PayloadParse(_))));
+    }
+
+    #[test]
+    fn should_keep_known_state_when_device_list_is_republished() {
+        let (entities, _) = discovered();
+        Zigbee2MqttIntegration::apply_state_message(
+            "kitchen/bulb",
+            &serde_json::json!({ "state": "ON" }),
+            &entities,
+        );
+
+        let rediscovered =
+            Zigbee2MqttIntegration::apply_devices_message(DEVICES.as_bytes(), &entities).unwrap();
+
+        assert_eq!(rediscovered[0].entities[0].state, EntityState::On);
+    }
+
+    #[test]
+    fn should_return_changed_entities_for_state_message() {
+        let (entities, _) = discovered();
+        let payload = serde_json::json!({ "state": "ON", "brightness": 42 });
+
+        let changed =
+            Zigbee2MqttIntegration::apply_state_message("kitchen/bulb", &payload, &entities);
+        let unchanged =
+            Zigbee2MqttIntegration::apply_state_message("kitchen/bulb", &payload, &entities);
+        let other = Zigbee2MqttIntegration::apply_state_message("hallway", &payload, &entities);
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].state, EntityState::On);
+        assert!(unchanged.is_empty());
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn should_toggle_availability() {
+        let (entities, _) = discovered();
+
+        let offline = Zigbee2MqttIntegration::apply_availability("kitchen/bulb", false, &entities);
+        let online = Zigbee2MqttIntegration::apply_availability("kitchen/bulb", true, &entities);
+        let again = Zigbee2MqttIntegration::apply_availability("kitchen/bulb", true, &entities);
+
+        assert_eq!(offline[0].state, EntityState::Unavailable);
+        assert_eq!(online[0].state, EntityState::Unknown);
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn should_parse_both_availability_formats() {
+        assert!(parse_availability(b"online"));
+        assert!(!parse_availability(b"offline"));
+        assert!(parse_availability(br#"{"state":"online"}"#));
+        assert!(!parse_availability(br#"{"state":"offline"}"#));
+    }
+
+    #[tokio::test]
+    async fn should_return_error_when_service_call_without_client() {
+        let integration = Zigbee2MqttIntegration::new(MqttConfig::default());
+        let result = integration
+            .handle_service_call(EntityId::new(), "turn_on", serde_json::json!({}))
+            .await;
+        assert!(matches!(result, Err(MiniHubError::Storage(_))));
+    }
+
+    #[tokio::test]
+    async fn should_teardown_without_error_when_not_connected() {
+        let mut integration = Zigbee2MqttIntegration::new(MqttConfig::default());
+        assert!(integration.teardown().await.is_ok());
+        assert_eq!(integration.name(), "zigbee2mqtt");
+    }
+}
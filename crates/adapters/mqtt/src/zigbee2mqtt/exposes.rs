@@ -0,0 +1,688 @@
+//! Mapping of zigbee2mqtt device definitions onto minihub entities.
+//!
+//! zigbee2mqtt describes what a device can do through its `exposes` list.
+//! Specific exposes (`light`, `switch`) become controllable entities whose
+//! features are carried as attributes. The remaining generic exposes
+//! (`binary`, `numeric`, `enum`) are gathered into a single sensor entity
+//! per device, the same way the BLE adapter models multi-value sensors.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
+use minihub_domain::error::MiniHubError;
+
+/// Access bit set when the device publishes the property in its state.
+const ACCESS_STATE: u8 = 0b001;
+/// Access bit set when the property can be written through `/set`.
+const ACCESS_SET: u8 = 0b010;
+
+/// One entry of the `{base}/bridge/devices` payload.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct BridgeDevice {
+    pub ieee_address: String,
+    pub friendly_name: String,
+    #[serde(rename = "type")]
+    pub device_type: String,
+    /// Firmware build reported by the device, if interviewed.
+    #[serde(default)]
+    pub software_build_id: Option<String>,
+    /// `None` while the device is being interviewed or is unsupported.
+    #[serde(default)]
+    pub definition: Option<Definition>,
+}
+
+/// Model information and capabilities of a supported device.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Definition {
+    #[serde(default)]
+    pub vendor: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub exposes: Vec<Expose>,
+}
+
+/// A capability advertised by a device.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Expose {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub property: Option<String>,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub access: u8,
+    #[serde(default)]
+    pub value_on: Option<Value>,
+    #[serde(default)]
+    pub value_off: Option<Value>,
+    #[serde(default)]
+    pub value_toggle: Option<Value>,
+    #[serde(default)]
+    pub value_min: Option<f64>,
+    #[serde(default)]
+    pub value_max: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub features: Vec<Expose>,
+}
+
+/// The on/off property driving an entity's state.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StateProperty {
+    pub property: String,
+    pub value_on: Value,
+    pub value_off: Value,
+    pub value_toggle: Value,
+}
+
+/// How an entity maps onto the device's state payload and `/set` topic.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Binding {
+    /// zigbee2mqtt friendly name of the owning device.
+    pub device: String,
+    /// Property reflected in [`Entity::state`], if any.
+    pub state: Option<StateProperty>,
+    /// Numeric property reported as the [`EntityState::Numeric`] state, if any.
+    pub reading: Option<String>,
+    /// Whether `state` can be written through `/set`.
+    pub writable: bool,
+    /// Payload properties copied verbatim into entity attributes.
+    pub attributes: Vec<String>,
+}
+
+/// A device and its entities, each paired with its binding.
+#[derive(Debug)]
+pub(crate) struct MappedDevice {
+    pub device: Device,
+    pub entities: Vec<(Entity, Binding)>,
+}
+
+/// Map a bridge device to minihub entities.
+///
+/// Returns `Ok(None)` for the coordinator and for devices without a
+/// definition.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the device or an entity fails
+/// domain validation.
+pub(crate) fn map_device(bridge: &BridgeDevice) -> Result<Option<MappedDevice>, MiniHubError> {
+    if bridge.device_type == "Coordinator" {
+        return Ok(None);
+    }
+    let Some(definition) = &bridge.definition else {
+        return Ok(None);
+    };
+
+    let mut builder = Device::builder()
+        .name(&bridge.friendly_name)
+        .manufacturer(&definition.vendor)
+        .model(&definition.model)
+        .integration("zigbee2mqtt")
+        .unique_id(&bridge.ieee_address);
+    if let Some(software_build_id) = &bridge.software_build_id {
+        builder = builder.sw_version(software_build_id);
+    }
+    let device = builder.build()?;
+
+    let slug = slugify(&bridge.friendly_name);
+    let mut entities = Vec::new();
+    let mut sensor_exposes = Vec::new();
+    for expose in &definition.exposes {
+        match expose.kind.as_str() {
+            "light" | "switch" => {
+                entities.push(map_control(&device, bridge, &slug, expose)?);
+            }
+            "binary" | "numeric" | "enum" if expose.property.is_some() => {
+                sensor_exposes.push(expose);
+            }
+            _ => {}
+        }
+    }
+    if !sensor_exposes.is_empty() {
+        entities.push(map_sensor(&device, bridge, &slug, &sensor_exposes)?);
+    }
+
+    Ok(Some(MappedDevice { device, entities }))
+}
+
+/// Map a `light` or `switch` expose to a controllable entity.
+fn map_control(
+    device: &Device,
+    bridge: &BridgeDevice,
+    slug: &str,
+    expose: &Expose,
+) -> Result<(Entity, Binding), MiniHubError> {
+    let (entity_id, friendly_name) = match &expose.endpoint {
+        Some(endpoint) => (
+            format!("{}.{slug}_{}", expose.kind, slugify(endpoint)),
+            format!("{} {endpoint}", bridge.friendly_name),
+        ),
+        None => (
+            format!("{}.{slug}", expose.kind),
+            bridge.friendly_name.clone(),
+        ),
+    };
+
+    let mut builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(entity_id)
+        .friendly_name(friendly_name);
+    let mut state = None;
+    let mut writable = false;
+    let mut attributes = Vec::new();
+    for feature in &expose.features {
+        let Some(property) = &feature.property else {
+            continue;
+        };
+        match feature.kind.as_str() {
+            "binary" if state.is_none() => {
+                state = Some(state_property(feature, property));
+                writable = feature.access & ACCESS_SET != 0;
+            }
+            "numeric" | "enum" => {
+                if let Some(meta) = attribute_meta(feature) {
+                    builder = builder.attribute_meta(property, meta);
+                }
+                attributes.push(property.clone());
+            }
+            _ => {}
+        }
+    }
+
+    let entity = builder.build()?;
+    let binding = Binding {
+        device: bridge.friendly_name.clone(),
+        state,
+        reading: None,
+        writable,
+        attributes,
+    };
+    Ok((entity, binding))
+}
+
+/// Gather the generic exposes of a device into one sensor entity.
+///
+/// A recognised binary expose (motion, contact, …) drives the state and
+/// makes it a `binary_sensor`; otherwise the most significant recognised
+/// numeric expose (temperature before battery, …) picks the device class
+/// and unit.
+fn map_sensor(
+    device: &Device,
+    bridge: &BridgeDevice,
+    slug: &str,
+    exposes: &[&Expose],
+) -> Result<(Entity, Binding), MiniHubError> {
+    let binary = primary_expose(exposes, "binary", BINARY_CLASSES);
+    let numeric = primary_expose(exposes, "numeric", NUMERIC_CLASSES);
+
+    let domain = if binary.is_some() {
+        "binary_sensor"
+    } else {
+        "sensor"
+    };
+    let mut builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(format!("{domain}.{slug}"))
+        .friendly_name(&bridge.friendly_name);
+    let mut state = None;
+    let mut reading = None;
+    if let Some((expose, property, class)) = binary {
+        let mut prop = state_property(expose, property);
+        // `contact` is true while the door is closed.
+        if property == "contact" {
+            std::mem::swap(&mut prop.value_on, &mut prop.value_off);
+        }
+        state = Some(prop);
+        builder = builder.device_class(class);
+    } else if let Some((expose, property, class)) = numeric {
+        // The state stays unknown until the first payload carries a reading.
+        reading = Some(property.to_string());
+        builder = builder.device_class(class);
+        if let Some(unit) = &expose.unit {
+            builder = builder.unit_of_measurement(unit);
+        }
+    }
+
+    let mut attributes = Vec::new();
+    for expose in exposes {
+        let Some(property) = &expose.property else {
+            continue;
+        };
+        if expose.access & ACCESS_STATE == 0 {
+            continue;
+        }
+        if let Some(meta) = attribute_meta(expose) {
+            builder = builder.attribute_meta(property, meta);
+        }
+        attributes.push(property.clone());
+    }
+
+    let entity = builder.build()?;
+    let binding = Binding {
+        device: bridge.friendly_name.clone(),
+        state,
+        reading,
+        writable: false,
+        attributes,
+    };
+    Ok((entity, binding))
+}
+
+fn state_property(expose: &Expose, property: &str) -> StateProperty {
+    StateProperty {
+        property: property.to_string(),
+        value_on: expose.value_on.clone().unwrap_or(Value::Bool(true)),
+        value_off: expose.value_off.clone().unwrap_or(Value::Bool(false)),
+        value_toggle: expose
+            .value_toggle
+            .clone()
+            .unwrap_or_else(|| Value::from("TOGGLE")),
+    }
+}
+
+/// Build display and validation hints from a numeric expose.
+fn attribute_meta(expose: &Expose) -> Option<AttributeMeta> {
+    if expose.kind != "numeric" {
+        return None;
+    }
+    let meta = AttributeMeta {
+        min: expose.value_min,
+        max: expose.value_max,
+        display_unit: expose.unit.clone(),
+        ..AttributeMeta::default()
+    };
+    (meta != AttributeMeta::default()).then_some(meta)
+}
+
+/// Binary properties with a known device class, most significant first.
+const BINARY_CLASSES: &[(&str, DeviceClass)] = &[
+    ("occupancy", DeviceClass::Motion),
+    ("presence", DeviceClass::Occupancy),
+    ("contact", DeviceClass::Door),
+];
+
+/// Numeric properties with a known device class, most significant first.
+const NUMERIC_CLASSES: &[(&str, DeviceClass)] = &[
+    ("temperature", DeviceClass::Temperature),
+    ("soil_moisture", DeviceClass::Moisture),
+    ("humidity", DeviceClass::Humidity),
+    ("illuminance_lux", DeviceClass::Illuminance),
+    ("illuminance", DeviceClass::Illuminance),
+    ("pressure", DeviceClass::Pressure),
+    ("power", DeviceClass::Power),
+    ("energy", DeviceClass::Energy),
+    ("voltage", DeviceClass::Voltage),
+    ("battery", DeviceClass::Battery),
+];
+
+/// Find the most significant expose of `kind` listed in `classes`.
+fn primary_expose<'a>(
+    exposes: &[&'a Expose],
+    kind: &str,
+    classes: &[(&'static str, DeviceClass)],
+) -> Option<(&'a Expose, &'static str, DeviceClass)> {
+    classes.iter().find_map(|&(property, class)| {
+        exposes
+            .iter()
+            .find(|expose| expose.kind == kind && expose.property.as_deref() == Some(property))
+            .map(|expose| (*expose, property, class))
+    })
+}
+
+/// Apply a device state payload to an entity.
+///
+/// Returns `true` when the entity's state or one of its attributes changed.
+pub(crate) fn apply_state(entity: &mut Entity, binding: &Binding, payload: &Value) -> bool {
+    let mut changed = false;
+    if let Some(prop) = &binding.state
+        && let Some(value) = payload.get(&prop.property)
+    {
+        let state = if *value == prop.value_on {
+            EntityState::On
+        } else if *value == prop.value_off {
+            EntityState::Off
+        } else {
+            EntityState::Unknown
+        };
+        if entity.state != state {
+            entity.update_state(state, minihub_domain::time::now());
+            changed = true;
+        }
+    }
+    if let Some(property) = &binding.reading
+        && let Some(value) = payload.get(property).and_then(Value::as_f64)
+    {
+        let state = EntityState::Numeric(value);
+        if entity.state != state {
+            entity.update_state(state, minihub_domain::time::now());
+            changed = true;
+        }
+    }
+    for property in &binding.attributes {
+        let Some(value) = payload.get(property).and_then(attribute_value) else {
+            continue;
+        };
+        if entity.get_attribute(property) != Some(&value) {
+            entity.set_attribute(property.clone(), value);
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn attribute_value(value: &Value) -> Option<AttributeValue> {
+    match value {
+        Value::Bool(b) => Some(AttributeValue::Bool(*b)),
+        Value::Number(n) => n
+            .as_i64()
+            .map(AttributeValue::Int)
+            .or_else(|| n.as_f64().map(AttributeValue::Float)),
+        Value::String(s) => Some(AttributeValue::String(s.clone())),
+        Value::Null => None,
+        other => Some(AttributeValue::Json(other.clone())),
+    }
+}
+
+/// Build the `/set` payload for a service call.
+///
+/// `turn_on`, `turn_off` and `toggle` write the state property; any object
+/// fields in `data` (e.g. `brightness`) are merged into the payload.
+/// Returns `None` when the entity cannot be controlled or the service is
+/// not supported.
+pub(crate) fn command_payload(binding: &Binding, service: &str, data: &Value) -> Option<Value> {
+    let prop = binding.state.as_ref().filter(|_| binding.writable)?;
+    let value = match service {
+        "turn_on" => prop.value_on.clone(),
+        "turn_off" => prop.value_off.clone(),
+        "toggle" => prop.value_toggle.clone(),
+        _ => return None,
+    };
+    let mut payload = serde_json::Map::new();
+    if let Some(fields) = data.as_object() {
+        payload.extend(fields.clone());
+    }
+    payload.insert(prop.property.clone(), value);
+    Some(Value::Object(payload))
+}
+
+/// Turn a friendly name into an `entity_id`-safe slug.
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulb() -> BridgeDevice {
+        serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x0017880104e45517",
+            "friendly_name": "Kitchen Bulb",
+            "type": "Router",
+            "software_build_id": "1.93.11",
+            "definition": {
+                "vendor": "Philips",
+                "model": "9290012573A",
+                "exposes": [
+                    {
+                        "type": "light",
+                        "features": [
+                            { "type": "binary", "property": "state", "access": 7,
+                              "value_on": "ON", "value_off": "OFF", "value_toggle": "TOGGLE" },
+                            { "type": "numeric", "property": "brightness", "access": 7,
+                              "value_min": 0, "value_max": 254 },
+                            { "type": "composite", "property": "color", "access": 7 }
+                        ]
+                    },
+                    { "type": "numeric", "property": "linkquality", "access": 1, "unit": "lqi" }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn motion_sensor() -> BridgeDevice {
+        serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x00158d0001e4b3a1",
+            "friendly_name": "hallway/motion",
+            "type": "EndDevice",
+            "definition": {
+                "vendor": "Xiaomi",
+                "model": "RTCGQ11LM",
+                "exposes": [
+                    { "type": "numeric", "property": "battery", "access": 1, "unit": "%",
+                      "value_min": 0, "value_max": 100 },
+                    { "type": "binary", "property": "occupancy", "access": 1,
+                      "value_on": true, "value_off": false },
+                    { "type": "numeric", "property": "illuminance_lux", "access": 1, "unit": "lx" }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn find<'a>(mapped: &'a MappedDevice, entity_id: &str) -> &'a (Entity, Binding) {
+        mapped
+            .entities
+            .iter()
+            .find(|(entity, _)| entity.entity_id == entity_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn should_map_light_expose_to_light_entity() {
+        let mapped = map_device(&bulb()).unwrap().unwrap();
+
+        assert_eq!(mapped.device.manufacturer.as_deref(), Some("Philips"));
+        assert_eq!(mapped.device.unique_id, "0x0017880104e45517");
+        assert_eq!(mapped.device.sw_version.as_deref(), Some("1.93.11"));
+        let (entity, binding) = find(&mapped, "light.kitchen_bulb");
+        assert_eq!(entity.friendly_name, "Kitchen Bulb");
+        assert_eq!(entity.device_id, mapped.device.id);
+        assert!(binding.writable);
+        assert_eq!(binding.attributes, vec!["brightness".to_string()]);
+        let meta = entity.get_attribute_meta("brightness").unwrap();
+        assert_eq!(meta.max, Some(254.0));
+    }
+
+    #[test]
+    fn should_map_generic_exposes_to_single_sensor() {
+        let mapped = map_device(&motion_sensor()).unwrap().unwrap();
+
+        assert_eq!(mapped.entities.len(), 1);
+        let (entity, binding) = find(&mapped, "binary_sensor.hallway_motion");
+        assert_eq!(entity.device_class, Some(DeviceClass::Motion));
+        assert!(!binding.writable);
+        assert_eq!(binding.attributes.len(), 3);
+        assert_eq!(
+            entity.get_attribute_meta("battery").unwrap().display_unit,
+            Some("%".to_string())
+        );
+    }
+
+    #[test]
+    fn should_pick_numeric_device_class_when_no_binary_expose() {
+        let device: BridgeDevice = serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x01",
+            "friendly_name": "office",
+            "type": "EndDevice",
+            "definition": { "vendor": "Aqara", "model": "WSDCGQ11LM", "exposes": [
+                { "type": "numeric", "property": "battery", "access": 1, "unit": "%" },
+                { "type": "numeric", "property": "temperature", "access": 1, "unit": "°C" },
+                { "type": "numeric", "property": "humidity", "access": 1, "unit": "%" }
+            ] }
+        }))
+        .unwrap();
+
+        let mapped = map_device(&device).unwrap().unwrap();
+        let (entity, _) = find(&mapped, "sensor.office");
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.unit_of_measurement.as_deref(), Some("°C"));
+        assert_eq!(entity.state, EntityState::Unknown);
+    }
+
+    #[test]
+    fn should_report_numeric_reading_as_state() {
+        let device: BridgeDevice = serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x01",
+            "friendly_name": "office",
+            "type": "EndDevice",
+            "definition": { "vendor": "Aqara", "model": "WSDCGQ11LM", "exposes": [
+                { "type": "numeric", "property": "temperature", "access": 1, "unit": "°C" },
+                { "type": "numeric", "property": "humidity", "access": 1, "unit": "%" }
+            ] }
+        }))
+        .unwrap();
+        let mapped = map_device(&device).unwrap().unwrap();
+        let (entity, binding) = find(&mapped, "sensor.office");
+        let mut entity = entity.clone();
+
+        let payload = serde_json::json!({ "temperature": 21.5, "humidity": 40 });
+        assert!(apply_state(&mut entity, binding, &payload));
+
+        assert_eq!(entity.state, EntityState::Numeric(21.5));
+        assert_eq!(
+            entity.get_attribute("temperature"),
+            Some(&AttributeValue::Float(21.5))
+        );
+    }
+
+    #[test]
+    fn should_suffix_entity_id_with_endpoint() {
+        let device: BridgeDevice = serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x02",
+            "friendly_name": "relay",
+            "type": "Router",
+            "definition": { "vendor": "Tuya", "model": "TS0002", "exposes": [
+                { "type": "switch", "endpoint": "l1", "features": [
+                    { "type": "binary", "property": "state_l1", "access": 7,
+                      "value_on": "ON", "value_off": "OFF" } ] },
+                { "type": "switch", "endpoint": "l2", "features": [
+                    { "type": "binary", "property": "state_l2", "access": 7,
+                      "value_on": "ON", "value_off": "OFF" } ] }
+            ] }
+        }))
+        .unwrap();
+
+        let mapped = map_device(&device).unwrap().unwrap();
+        let (_, binding) = find(&mapped, "switch.relay_l2");
+        assert_eq!(binding.state.as_ref().unwrap().property, "state_l2");
+    }
+
+    #[test]
+    fn should_skip_coordinator_and_undefined_devices() {
+        let coordinator: BridgeDevice = serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x00", "friendly_name": "Coordinator", "type": "Coordinator"
+        }))
+        .unwrap();
+        let mut interviewing = bulb();
+        interviewing.definition = None;
+
+        assert!(map_device(&coordinator).unwrap().is_none());
+        assert!(map_device(&interviewing).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_invert_contact_sensor_state() {
+        let device: BridgeDevice = serde_json::from_value(serde_json::json!({
+            "ieee_address": "0x03",
+            "friendly_name": "front door",
+            "type": "EndDevice",
+            "definition": { "vendor": "Aqara", "model": "MCCGQ11LM", "exposes": [
+                { "type": "binary", "property": "contact", "access": 1,
+                  "value_on": true, "value_off": false }
+            ] }
+        }))
+        .unwrap();
+        let mapped = map_device(&device).unwrap().unwrap();
+        let (entity, binding) = find(&mapped, "binary_sensor.front_door");
+        let mut entity = entity.clone();
+
+        assert_eq!(entity.device_class, Some(DeviceClass::Door));
+        assert!(apply_state(
+            &mut entity,
+            binding,
+            &serde_json::json!({ "contact": false })
+        ));
+        assert_eq!(entity.state, EntityState::On);
+    }
+
+    #[test]
+    fn should_apply_state_payload_to_light() {
+        let mapped = map_device(&bulb()).unwrap().unwrap();
+        let (entity, binding) = find(&mapped, "light.kitchen_bulb");
+        let mut entity = entity.clone();
+
+        let changed = apply_state(
+            &mut entity,
+            binding,
+            &serde_json::json!({ "state": "ON", "brightness": 128, "linkquality": 90 }),
+        );
+
+        assert!(changed);
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("brightness"),
+            Some(&AttributeValue::Int(128))
+        );
+        assert!(entity.get_attribute("linkquality").is_none());
+    }
+
+    #[test]
+    fn should_report_unchanged_when_payload_matches() {
+        let mapped = map_device(&bulb()).unwrap().unwrap();
+        let (entity, binding) = find(&mapped, "light.kitchen_bulb");
+        let mut entity = entity.clone();
+        let payload = serde_json::json!({ "state": "OFF", "brightness": 10 });
+
+        assert!(apply_state(&mut entity, binding, &payload));
+        assert!(!apply_state(&mut entity, binding, &payload));
+    }
+
+    #[test]
+    fn should_build_set_payload_for_turn_on_with_data() {
+        let mapped = map_device(&bulb()).unwrap().unwrap();
+        let (_, binding) = find(&mapped, "light.kitchen_bulb");
+
+        let payload = command_payload(
+            binding,
+            "turn_on",
+            &serde_json::json!({ "brightness": 200 }),
+        );
+
+        assert_eq!(
+            payload,
+            Some(serde_json::json!({ "state": "ON", "brightness": 200 }))
+        );
+    }
+
+    #[test]
+    fn should_build_set_payload_for_toggle() {
+        let mapped = map_device(&bulb()).unwrap().unwrap();
+        let (_, binding) = find(&mapped, "light.kitchen_bulb");
+
+        let payload = command_payload(binding, "toggle", &serde_json::Value::Null);
+
+        assert_eq!(payload, Some(serde_json::json!({ "state": "TOGGLE" })));
+    }
+
+    #[test]
+    fn should_refuse_commands_for_sensors_and_unknown_services() {
+        let sensor = map_device(&motion_sensor()).unwrap().unwrap();
+        let bulb = map_device(&bulb()).unwrap().unwrap();
+        let (_, sensor_binding) = find(&sensor, "binary_sensor.hallway_motion");
+        let (_, bulb_binding) = find(&bulb, "light.kitchen_bulb");
+
+        assert!(command_payload(sensor_binding, "turn_on", &Value::Null).is_none());
+        assert!(command_payload(bulb_binding, "dim", &Value::Null).is_none());
+    }
+}
@@ -0,0 +1,40 @@
+//! Webhook notifier error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the webhook notifier.
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// The HTTP client could not be built or the request failed to complete.
+    #[error("webhook request failed")]
+    Http(#[source] reqwest::Error),
+
+    /// The webhook answered with a non-success status code.
+    #[error("webhook responded with status {0}")]
+    Status(u16),
+}
+
+/// Delivery failures surface as [`MiniHubError::Storage`] across port
+/// boundaries.
+impl From<WebhookError> for MiniHubError {
+    fn from(err: WebhookError) -> Self {
+        MiniHubError::Storage(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_status_error() {
+        let err = WebhookError::Status(503);
+        assert_eq!(err.to_string(), "webhook responded with status 503");
+    }
+
+    #[test]
+    fn should_convert_status_error_to_storage_error() {
+        let err: MiniHubError = WebhookError::Status(500).into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+}
@@ -0,0 +1,179 @@
+//! # minihub-adapter-notify-webhook
+//!
+//! Webhook notifier — implements the [`Notifier`] port by sending a `POST` for each
+//! [`Notification`] as JSON to a configured URL.
+//!
+//! The request body is the serialized notification:
+//!
+//! ```json
+//! {
+//!   "id": "…",
+//!   "title": "Leak",
+//!   "message": "Water detected in the basement",
+//!   "severity": "critical",
+//!   "entity_id": null,
+//!   "created_at": "2026-03-01T12:00:00Z"
+//! }
+//! ```
+//!
+//! ## Dependency rule
+//!
+//! Depends on `minihub-app` (port traits) and `minihub-domain` only.
+
+mod error;
+
+use std::future::Future;
+use std::time::Duration;
+
+use minihub_app::ports::Notifier;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::notification::Notification;
+
+pub use error::WebhookError;
+
+/// Configuration for the webhook notifier.
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// URL receiving the `POST` requests.
+    pub url: String,
+    /// Request timeout, in seconds.
+    pub timeout_secs: u64,
+}
+
+impl WebhookConfig {
+    /// Create a configuration targeting `url` with the default timeout.
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Notifier delivering notifications as JSON `POST` requests.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    /// Build a notifier from its configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebhookError::Http`] if the HTTP client cannot be built.
+    pub fn new(config: WebhookConfig) -> Result<Self, WebhookError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(WebhookError::Http)?;
+        Ok(Self {
+            client,
+            url: config.url,
+        })
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<(), WebhookError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(notification)
+            .send()
+            .await
+            .map_err(WebhookError::Http)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(WebhookError::Status(status.as_u16()));
+        }
+        tracing::debug!(notification_id = %notification.id, "notification delivered to webhook");
+        Ok(())
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(
+        &self,
+        notification: &Notification,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        let notification = notification.clone();
+        async move { self.send(&notification).await.map_err(MiniHubError::from) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use minihub_domain::notification::Severity;
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    async fn spawn_server(status: StatusCode) -> (String, Received) {
+        let received: Received = Arc::default();
+        let app =
+            axum::Router::new()
+                .route(
+                    "/hook",
+                    post(
+                        move |State(received): State<Received>,
+                              Json(body): Json<serde_json::Value>| async move {
+                            received.lock().unwrap().push(body);
+                            status
+                        },
+                    ),
+                )
+                .with_state(Arc::clone(&received));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/hook"), received)
+    }
+
+    fn notification() -> Notification {
+        Notification::builder()
+            .title("Leak")
+            .message("Water detected in the basement")
+            .severity(Severity::Critical)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_post_notification_as_json() {
+        let (url, received) = spawn_server(StatusCode::NO_CONTENT).await;
+        let notifier = WebhookNotifier::new(WebhookConfig::new(url)).unwrap();
+
+        notifier.notify(&notification()).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["title"], "Leak");
+        assert_eq!(received[0]["message"], "Water detected in the basement");
+        assert_eq!(received[0]["severity"], "critical");
+    }
+
+    #[tokio::test]
+    async fn should_return_error_when_webhook_rejects_request() {
+        let (url, _received) = spawn_server(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let notifier = WebhookNotifier::new(WebhookConfig::new(url)).unwrap();
+
+        let result = notifier.send(&notification()).await;
+
+        assert!(matches!(result, Err(WebhookError::Status(500))));
+    }
+
+    #[tokio::test]
+    async fn should_return_error_when_webhook_unreachable() {
+        let notifier = WebhookNotifier::new(WebhookConfig::new("http://127.0.0.1:1/hook")).unwrap();
+
+        let result = notifier.notify(&notification()).await;
+
+        assert!(matches!(result, Err(MiniHubError::Storage(_))));
+    }
+}
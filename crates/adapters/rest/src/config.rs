@@ -0,0 +1,134 @@
+//! REST integration configuration.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+/// Configuration for the REST integration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RestConfig {
+    /// Devices to poll.
+    pub devices: Vec<RestDeviceConfig>,
+    /// Delay between two polls of the same device, in seconds.
+    pub poll_interval_secs: u64,
+    /// Timeout of every HTTP request, in seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for RestConfig {
+    fn default() -> Self {
+        Self {
+            devices: Vec::new(),
+            poll_interval_secs: 30,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// A device answering its status as JSON over HTTP.
+///
+/// Each device is exposed as a single entity.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RestDeviceConfig {
+    /// Human-readable name of the device and its entity.
+    pub name: String,
+    /// Entity id exposed to minihub, e.g. `"switch.garage_plug"`.
+    pub entity_id: String,
+    /// URL answering the device status as a JSON document.
+    pub poll_url: String,
+    /// JSONPath to the on/off state in the status document.
+    ///
+    /// Without it the entity is a plain sensor, `on` while the device
+    /// answers.
+    #[serde(default)]
+    pub state_path: Option<String>,
+    /// Attribute name to the JSONPath of its value in the status document.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Service name (e.g. `"turn_on"`) to the request performing it.
+    #[serde(default)]
+    pub commands: BTreeMap<String, RestCommandConfig>,
+}
+
+/// HTTP request sent when a service is called on a device.
+///
+/// The URL and body may contain `{field}` placeholders, replaced by the
+/// matching field of the service call data.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RestCommandConfig {
+    /// HTTP method, e.g. `"GET"` or `"POST"`.
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// URL template of the request.
+    pub url: String,
+    /// Body template of the request, sent as JSON when it parses as such.
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl RestCommandConfig {
+    /// Describe a `GET` request to `url`.
+    #[must_use]
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: default_method(),
+            url: url.into(),
+            body: None,
+        }
+    }
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_sensible_defaults() {
+        let config = RestConfig::default();
+        assert!(config.devices.is_empty());
+        assert_eq!(config.poll_interval_secs, 30);
+        assert_eq!(config.timeout_secs, 10);
+    }
+
+    #[test]
+    fn should_deserialize_devices_from_toml() {
+        let toml = r#"
+            poll_interval_secs = 10
+
+            [[devices]]
+            name = "Garage Plug"
+            entity_id = "switch.garage_plug"
+            poll_url = "http://192.168.1.20/status"
+            state_path = "$.relays[0].ison"
+
+            [devices.attributes]
+            power = "$.meters[0].power"
+
+            [devices.commands.turn_on]
+            url = "http://192.168.1.20/relay/0?turn=on"
+
+            [devices.commands.set_level]
+            method = "POST"
+            url = "http://192.168.1.20/light/0"
+            body = '{"brightness": {brightness}}'
+        "#;
+
+        let config: RestConfig = toml::from_str(toml).unwrap();
+
+        assert_eq!(config.poll_interval_secs, 10);
+        assert_eq!(config.timeout_secs, 10);
+        let device = &config.devices[0];
+        assert_eq!(device.state_path.as_deref(), Some("$.relays[0].ison"));
+        assert_eq!(device.attributes["power"], "$.meters[0].power");
+        assert_eq!(
+            device.commands["turn_on"],
+            RestCommandConfig::get("http://192.168.1.20/relay/0?turn=on")
+        );
+        assert_eq!(device.commands["set_level"].method, "POST");
+    }
+}
@@ -0,0 +1,307 @@
+//! Mapping between configured REST devices and minihub entities.
+
+use reqwest::Method;
+use serde_json::Value;
+
+use minihub_app::ports::integration::DiscoveredDevice;
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+use minihub_domain::time::now;
+
+use crate::config::{RestCommandConfig, RestDeviceConfig};
+use crate::error::RestError;
+use crate::path::JsonPath;
+
+/// Integration name stored on devices.
+pub(crate) const INTEGRATION: &str = "rest";
+
+/// A configured device with its paths and methods parsed.
+#[derive(Debug, Clone)]
+pub(crate) struct RestDevice {
+    pub config: RestDeviceConfig,
+    state: Option<JsonPath>,
+    attributes: Vec<(String, JsonPath)>,
+}
+
+impl RestDevice {
+    /// Parse the JSONPath expressions and command methods of `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RestError::InvalidPath`] or [`RestError::InvalidMethod`]
+    /// when the configuration cannot be used.
+    pub fn compile(config: RestDeviceConfig) -> Result<Self, RestError> {
+        let state = config
+            .state_path
+            .as_deref()
+            .map(JsonPath::parse)
+            .transpose()?;
+        let attributes = config
+            .attributes
+            .iter()
+            .map(|(name, path)| Ok((name.clone(), JsonPath::parse(path)?)))
+            .collect::<Result<_, RestError>>()?;
+        for command in config.commands.values() {
+            method(command)?;
+        }
+        Ok(Self {
+            config,
+            state,
+            attributes,
+        })
+    }
+
+    /// The device and its single entity, before the first poll.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RestError::Domain`] when the name or entity id is invalid.
+    pub fn discovered(&self) -> Result<DiscoveredDevice, RestError> {
+        let device = Device::builder()
+            .name(&self.config.name)
+            .integration(INTEGRATION)
+            .unique_id(&self.config.entity_id)
+            .build()
+            .map_err(RestError::Domain)?;
+        let entity = Entity::builder()
+            .device_id(device.id)
+            .entity_id(&self.config.entity_id)
+            .friendly_name(&self.config.name)
+            .state(EntityState::Unknown)
+            .build()
+            .map_err(RestError::Domain)?;
+        Ok(DiscoveredDevice {
+            device,
+            entities: vec![entity],
+            via_device: None,
+        })
+    }
+
+    /// Apply a status document to `entity`.
+    ///
+    /// Attributes whose path is missing from the document keep their last
+    /// value. Returns whether the state or an attribute changed.
+    pub fn apply(&self, status: &Value, entity: &mut Entity) -> bool {
+        let state = match &self.state {
+            Some(path) => path
+                .select(status)
+                .map_or(EntityState::Unknown, parse_state),
+            None => EntityState::On,
+        };
+        let mut changed = entity.state != state;
+        entity.update_state(state, now());
+        for (name, path) in &self.attributes {
+            let Some(value) = path.select(status).and_then(attribute_value) else {
+                tracing::debug!(
+                    entity_id = %entity.entity_id,
+                    attribute = %name,
+                    "attribute missing from REST status"
+                );
+                continue;
+            };
+            if entity.get_attribute(name) != Some(&value) {
+                entity.set_attribute(name.clone(), value);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// The command performing `service`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RestError::UnsupportedService`] when no command is
+    /// configured for `service`.
+    pub fn command(&self, service: &str) -> Result<&RestCommandConfig, RestError> {
+        self.config
+            .commands
+            .get(service)
+            .ok_or_else(|| RestError::UnsupportedService(service.to_string()))
+    }
+}
+
+/// The HTTP method of `command`.
+///
+/// # Errors
+///
+/// Returns [`RestError::InvalidMethod`] for anything but the usual verbs.
+pub(crate) fn method(command: &RestCommandConfig) -> Result<Method, RestError> {
+    match command.method.to_ascii_uppercase().as_str() {
+        "GET" => Ok(Method::GET),
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        _ => Err(RestError::InvalidMethod(command.method.clone())),
+    }
+}
+
+/// Interpret a JSON value as an on/off state.
+///
+/// Booleans map directly, numbers are on unless zero, and the strings
+/// `on`/`off`, `true`/`false` and `1`/`0` are accepted in any case.
+fn parse_state(value: &Value) -> EntityState {
+    match value {
+        Value::Bool(true) => EntityState::On,
+        Value::Bool(false) => EntityState::Off,
+        Value::Number(number) => {
+            if number.as_f64() == Some(0.0) {
+                EntityState::Off
+            } else {
+                EntityState::On
+            }
+        }
+        Value::String(text) => match text.to_ascii_lowercase().as_str() {
+            "on" | "true" | "1" => EntityState::On,
+            "off" | "false" | "0" => EntityState::Off,
+            _ => EntityState::Unknown,
+        },
+        _ => EntityState::Unknown,
+    }
+}
+
+fn attribute_value(value: &Value) -> Option<AttributeValue> {
+    match value {
+        Value::Bool(b) => Some(AttributeValue::Bool(*b)),
+        Value::Number(n) => n
+            .as_i64()
+            .map(AttributeValue::Int)
+            .or_else(|| n.as_f64().map(AttributeValue::Float)),
+        Value::String(s) => Some(AttributeValue::String(s.clone())),
+        Value::Null => None,
+        other => Some(AttributeValue::Json(other.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn shelly() -> RestDevice {
+        RestDevice::compile(RestDeviceConfig {
+            name: "Garage Plug".to_string(),
+            entity_id: "switch.garage_plug".to_string(),
+            poll_url: "http://192.168.1.20/status".to_string(),
+            state_path: Some("$.relays[0].ison".to_string()),
+            attributes: BTreeMap::from([("power".to_string(), "$.meters[0].power".to_string())]),
+            commands: BTreeMap::from([(
+                "turn_on".to_string(),
+                RestCommandConfig::get("http://192.168.1.20/relay/0?turn=on"),
+            )]),
+        })
+        .unwrap()
+    }
+
+    fn entity(device: &RestDevice) -> Entity {
+        device.discovered().unwrap().entities.remove(0)
+    }
+
+    #[test]
+    fn should_discover_one_entity_per_device() {
+        let discovered = shelly().discovered().unwrap();
+
+        assert_eq!(discovered.device.integration, "rest");
+        assert_eq!(discovered.device.unique_id, "switch.garage_plug");
+        assert_eq!(discovered.entities.len(), 1);
+        assert_eq!(discovered.entities[0].entity_id, "switch.garage_plug");
+        assert_eq!(discovered.entities[0].device_id, discovered.device.id);
+        assert_eq!(discovered.entities[0].state, EntityState::Unknown);
+    }
+
+    #[test]
+    fn should_apply_state_and_attributes_from_status() {
+        let device = shelly();
+        let mut entity = entity(&device);
+        let status = serde_json::json!({
+            "relays": [{ "ison": true }],
+            "meters": [{ "power": 12.5 }],
+        });
+
+        let changed = device.apply(&status, &mut entity);
+
+        assert!(changed);
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("power"),
+            Some(&AttributeValue::Float(12.5))
+        );
+        assert!(!device.apply(&status, &mut entity));
+    }
+
+    #[test]
+    fn should_set_unknown_state_when_state_path_is_missing() {
+        let device = shelly();
+        let mut entity = entity(&device);
+        entity.update_state(EntityState::On, now());
+
+        device.apply(&serde_json::json!({}), &mut entity);
+
+        assert_eq!(entity.state, EntityState::Unknown);
+    }
+
+    #[test]
+    fn should_report_sensor_on_when_no_state_path() {
+        let mut config = shelly().config;
+        config.state_path = None;
+        let device = RestDevice::compile(config).unwrap();
+        let mut entity = entity(&device);
+
+        device.apply(&serde_json::json!({ "meters": [] }), &mut entity);
+
+        assert_eq!(entity.state, EntityState::On);
+    }
+
+    #[test]
+    fn should_parse_common_state_representations() {
+        let cases = [
+            (serde_json::json!("ON"), EntityState::On),
+            (serde_json::json!("off"), EntityState::Off),
+            (serde_json::json!(1), EntityState::On),
+            (serde_json::json!(0), EntityState::Off),
+            (serde_json::json!(false), EntityState::Off),
+            (serde_json::json!("dimmed"), EntityState::Unknown),
+            (serde_json::json!(null), EntityState::Unknown),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_state(&value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_path_or_method() {
+        let mut config = shelly().config;
+        config
+            .attributes
+            .insert("bad".to_string(), "$..power".to_string());
+        assert!(matches!(
+            RestDevice::compile(config),
+            Err(RestError::InvalidPath(_))
+        ));
+
+        let mut config = shelly().config;
+        config.commands.insert(
+            "turn_off".to_string(),
+            RestCommandConfig {
+                method: "FETCH".to_string(),
+                ..RestCommandConfig::get("http://192.168.1.20/relay/0?turn=off")
+            },
+        );
+        assert!(matches!(
+            RestDevice::compile(config),
+            Err(RestError::InvalidMethod(_))
+        ));
+    }
+
+    #[test]
+    fn should_return_unsupported_service_when_no_command() {
+        let device = shelly();
+        assert!(device.command("turn_on").is_ok());
+        assert!(matches!(
+            device.command("toggle"),
+            Err(RestError::UnsupportedService(_))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+//! REST adapter error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the REST adapter.
+#[derive(Debug, thiserror::Error)]
+pub enum RestError {
+    /// The HTTP client could not be built or a request failed.
+    #[error("REST request failed")]
+    Http(#[source] reqwest::Error),
+
+    /// A configured JSONPath expression cannot be parsed.
+    #[error("invalid JSONPath {0:?}")]
+    InvalidPath(String),
+
+    /// A configured command uses an unknown HTTP method.
+    #[error("invalid HTTP method {0:?}")]
+    InvalidMethod(String),
+
+    /// A command template references a field missing from the service data.
+    #[error("missing service data field {0:?}")]
+    MissingParameter(String),
+
+    /// The device has no command configured for the requested service.
+    #[error("unsupported service: {0}")]
+    UnsupportedService(String),
+
+    /// A domain-level error (validation, not-found, etc.).
+    #[error("{0}")]
+    Domain(#[source] MiniHubError),
+}
+
+impl RestError {
+    /// Convert into a [`MiniHubError::Storage`] for propagation across port
+    /// boundaries.
+    #[must_use]
+    pub fn into_domain(self) -> MiniHubError {
+        match self {
+            Self::Domain(err) => err,
+            other => MiniHubError::Storage(other.into()),
+        }
+    }
+}
+
+impl From<RestError> for MiniHubError {
+    fn from(err: RestError) -> Self {
+        err.into_domain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_missing_parameter() {
+        let err = RestError::MissingParameter("brightness".to_string());
+        assert_eq!(err.to_string(), "missing service data field \"brightness\"");
+    }
+
+    #[test]
+    fn should_convert_unsupported_service_to_storage_error() {
+        let err: MiniHubError = RestError::UnsupportedService("toggle".to_string()).into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+
+    #[test]
+    fn should_convert_domain_error_back_to_domain() {
+        let domain_err =
+            MiniHubError::Validation(minihub_domain::error::ValidationError::EmptyName);
+        let back: MiniHubError = RestError::Domain(domain_err).into();
+        assert!(matches!(back, MiniHubError::Validation(_)));
+    }
+}
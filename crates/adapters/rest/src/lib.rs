@@ -0,0 +1,542 @@
+//! # minihub-adapter-rest
+//!
+//! Generic HTTP/REST adapter — exposes devices with a simple JSON HTTP API
+//! (Tasmota in HTTP mode, Shelly gen1, custom firmware) as minihub
+//! entities.
+//!
+//! ## How it works
+//!
+//! Every configured device becomes one device with a single entity. A
+//! background task per device `GET`s its `poll_url` every
+//! [`RestConfig::poll_interval_secs`] and maps the JSON answer:
+//!
+//! - `state_path` selects the on/off state (`true`, `"ON"`, `1`, …); a
+//!   device without it is a sensor, `on` while it answers,
+//! - each `attributes` entry selects an attribute value.
+//!
+//! Paths use a JSONPath subset such as `$.meters[0].power`. When a poll
+//! fails, the entity becomes `unavailable` until the device answers again.
+//!
+//! Service calls run the matching entry of `commands`, whose URL and body
+//! may reference service data fields as `{field}`, then poll the device
+//! right away to pick up the new state.
+//!
+//! ## Dependency rule
+//!
+//! Same as other adapters: depends on `minihub-app` and `minihub-domain`.
+
+mod config;
+mod device;
+mod error;
+mod path;
+mod template;
+
+pub use config::{RestCommandConfig, RestConfig, RestDeviceConfig};
+pub use error::RestError;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_TYPE, HeaderValue};
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use minihub_app::integration_manager::forward_service_calls;
+use minihub_app::ports::integration::{Integration, IntegrationContext};
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::ServiceCallPayload;
+use minihub_domain::id::EntityId;
+use minihub_domain::time::now;
+
+use crate::device::RestDevice;
+
+/// Entities known to the integration, keyed by `entity_id` string.
+type Tracked = Arc<Mutex<HashMap<String, Entity>>>;
+
+/// REST polling integration.
+pub struct RestIntegration {
+    config: RestConfig,
+    client: reqwest::Client,
+    devices: Arc<Vec<RestDevice>>,
+    entities: Tracked,
+    poll_handles: Vec<JoinHandle<()>>,
+    subscriber_handle: Option<JoinHandle<()>>,
+}
+
+impl RestIntegration {
+    /// Create a new REST integration with the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RestError::InvalidPath`] or [`RestError::InvalidMethod`]
+    /// when a device is misconfigured, or [`RestError::Http`] if the HTTP
+    /// client cannot be built.
+    pub fn new(config: RestConfig) -> Result<Self, RestError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(RestError::Http)?;
+        let devices = config
+            .devices
+            .iter()
+            .cloned()
+            .map(RestDevice::compile)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            config,
+            client,
+            devices: Arc::new(devices),
+            entities: Arc::new(Mutex::new(HashMap::new())),
+            poll_handles: Vec::new(),
+            subscriber_handle: None,
+        })
+    }
+
+    /// Poll a device forever.
+    async fn poll_loop(
+        device: RestDevice,
+        client: reqwest::Client,
+        interval: Duration,
+        ctx: impl IntegrationContext,
+        entities: Tracked,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            Self::refresh(&client, &device, &ctx, &entities).await;
+        }
+    }
+
+    /// Poll `device` once and persist its entity when it changed.
+    async fn refresh(
+        client: &reqwest::Client,
+        device: &RestDevice,
+        ctx: &impl IntegrationContext,
+        entities: &Tracked,
+    ) {
+        let status = fetch(client, &device.config.poll_url).await;
+        let updated = {
+            let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+            let Some(entity) = tracked.get_mut(&device.config.entity_id) else {
+                return;
+            };
+            let changed = match &status {
+                Ok(document) => device.apply(document, entity),
+                Err(err) => {
+                    tracing::warn!(%err, entity_id = %entity.entity_id, "failed to poll REST device");
+                    let changed = entity.state != EntityState::Unavailable;
+                    entity.update_state(EntityState::Unavailable, now());
+                    changed
+                }
+            };
+            changed.then(|| entity.clone())
+        };
+        if let Some(entity) = updated
+            && let Err(err) = ctx.upsert_entity(entity).await
+        {
+            tracing::warn!(%err, entity_id = %device.config.entity_id, "failed to persist REST state");
+        }
+    }
+
+    /// Send the request performing `service` to `device`.
+    async fn send_command(
+        client: &reqwest::Client,
+        device: &RestDevice,
+        service: &str,
+        data: &Value,
+    ) -> Result<(), RestError> {
+        let command = device.command(service)?;
+        let url = template::render(&command.url, data)?;
+        let mut request = client.request(device::method(command)?, url);
+        if let Some(body) = &command.body {
+            let body = template::render(body, data)?;
+            if serde_json::from_str::<Value>(&body).is_ok() {
+                request =
+                    request.header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            }
+            request = request.body(body);
+        }
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(RestError::Http)?;
+        Ok(())
+    }
+
+    /// Send the service call requested on `entity_id` to its device,
+    /// unless the entity is not a REST one, then poll the device for its
+    /// new state.
+    async fn forward_service_call(
+        client: reqwest::Client,
+        devices: Arc<Vec<RestDevice>>,
+        entities: Tracked,
+        ctx: impl IntegrationContext,
+        entity_id: String,
+        request: ServiceCallPayload,
+    ) -> Option<Result<(), RestError>> {
+        let device = devices
+            .iter()
+            .find(|device| device.config.entity_id == entity_id)?;
+        let result = Self::send_command(&client, device, &request.service, &request.data).await;
+        if result.is_ok() {
+            Self::refresh(&client, device, &ctx, &entities).await;
+        }
+        Some(result)
+    }
+}
+
+/// `GET` `url` and parse the answer as JSON.
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Value, RestError> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(RestError::Http)?
+        .json()
+        .await
+        .map_err(RestError::Http)
+}
+
+impl Integration for RestIntegration {
+    fn name(&self) -> &'static str {
+        "rest"
+    }
+
+    async fn setup(&mut self, ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        // Devices are registered right away, in the `unknown` state, so they
+        // show up even when offline; the first poll fills in their state.
+        for device in self.devices.iter() {
+            let discovered = device.discovered()?;
+            let saved = ctx.upsert_device(discovered.device).await?;
+            for mut entity in discovered.entities {
+                entity.device_id = saved.id;
+                let entity = ctx.upsert_entity(entity).await?;
+                self.entities
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(entity.entity_id.clone(), entity);
+            }
+        }
+        tracing::info!(
+            device_count = self.devices.len(),
+            "REST integration configured"
+        );
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let bus_rx = ctx.subscribe();
+        let (client, devices, entities) = (
+            self.client.clone(),
+            Arc::clone(&self.devices),
+            Arc::clone(&self.entities),
+        );
+        let device_ctx = ctx.clone();
+        self.subscriber_handle = Some(tokio::spawn(forward_service_calls(
+            "rest",
+            bus_rx,
+            ctx.clone(),
+            move |entity_id, request| {
+                Self::forward_service_call(
+                    client.clone(),
+                    Arc::clone(&devices),
+                    Arc::clone(&entities),
+                    device_ctx.clone(),
+                    entity_id,
+                    request,
+                )
+            },
+        )));
+
+        let interval = Duration::from_secs(self.config.poll_interval_secs.max(1));
+        for device in self.devices.iter() {
+            self.poll_handles.push(tokio::spawn(Self::poll_loop(
+                device.clone(),
+                self.client.clone(),
+                interval,
+                ctx.clone(),
+                Arc::clone(&self.entities),
+            )));
+        }
+
+        tracing::info!("REST background tasks started");
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        service: &str,
+        data: Value,
+    ) -> Result<Entity, MiniHubError> {
+        let current = self
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .find(|entity| entity.id == entity_id)
+            .cloned()
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+        let device = self
+            .devices
+            .iter()
+            .find(|device| device.config.entity_id == current.entity_id)
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+
+        Self::send_command(&self.client, device, service, &data).await?;
+
+        // The tracked entity is left untouched so the next background poll
+        // still sees the change and persists it.
+        let mut entity = current;
+        match fetch(&self.client, &device.config.poll_url).await {
+            Ok(document) => {
+                device.apply(&document, &mut entity);
+            }
+            Err(err) => {
+                tracing::warn!(%err, entity_id = %entity.entity_id, "failed to poll REST device");
+            }
+        }
+        Ok(entity)
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        for handle in self
+            .poll_handles
+            .drain(..)
+            .chain(self.subscriber_handle.take())
+        {
+            handle.abort();
+        }
+        tracing::info!("REST integration stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Query, State};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use minihub_app::testing::FakeContext;
+    use minihub_domain::event::{Event, EventType};
+    use std::collections::BTreeMap;
+
+    /// Relay state of the fake device, `None` making it answer 500.
+    type Relay = Arc<Mutex<Option<bool>>>;
+
+    /// Fake Shelly-like device serving `/status` and `/relay?turn=on|off`.
+    async fn spawn_device(relay: Relay) -> String {
+        let app =
+            Router::new()
+                .route(
+                    "/status",
+                    get(|State(relay): State<Relay>| async move {
+                        match *relay.lock().unwrap() {
+                            Some(ison) => Ok(Json(serde_json::json!({
+                                "relays": [{ "ison": ison }],
+                                "meters": [{ "power": if ison { 40.5 } else { 0.0 } }],
+                            }))),
+                            None => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+                        }
+                    }),
+                )
+                .route(
+                    "/relay",
+                    get(
+                        |State(relay): State<Relay>,
+                         Query(query): Query<HashMap<String, String>>| async move {
+                            *relay.lock().unwrap() = Some(query["turn"] == "on");
+                        },
+                    ),
+                )
+                .with_state(relay);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn plug_config(base_url: &str) -> RestConfig {
+        RestConfig {
+            devices: vec![RestDeviceConfig {
+                name: "Garage Plug".to_string(),
+                entity_id: "switch.garage_plug".to_string(),
+                poll_url: format!("{base_url}/status"),
+                state_path: Some("$.relays[0].ison".to_string()),
+                attributes: BTreeMap::from([(
+                    "power".to_string(),
+                    "$.meters[0].power".to_string(),
+                )]),
+                commands: BTreeMap::from([(
+                    "turn_on".to_string(),
+                    RestCommandConfig::get(format!("{base_url}/relay?turn={{state}}")),
+                )]),
+            }],
+            ..RestConfig::default()
+        }
+    }
+
+    fn last_state(ctx: &FakeContext) -> Option<EntityState> {
+        ctx.entities().last().map(|entity| entity.state.clone())
+    }
+
+    async fn set_up(relay: Relay) -> (RestIntegration, FakeContext) {
+        let base_url = spawn_device(relay).await;
+        let mut integration = RestIntegration::new(plug_config(&base_url)).unwrap();
+        let ctx = FakeContext::new();
+        integration.setup(&ctx).await.unwrap();
+        (integration, ctx)
+    }
+
+    #[test]
+    fn should_reject_invalid_device_config() {
+        let mut config = plug_config("http://127.0.0.1");
+        config.devices[0].state_path = Some("$..ison".to_string());
+
+        let result = RestIntegration::new(config);
+
+        assert!(matches!(result, Err(RestError::InvalidPath(_))));
+    }
+
+    #[tokio::test]
+    async fn should_register_entities_as_unknown_on_setup() {
+        let (_, ctx) = set_up(Arc::new(Mutex::new(Some(true)))).await;
+
+        let upserted = ctx.entities();
+        assert_eq!(upserted.len(), 1);
+        assert_eq!(upserted[0].entity_id, "switch.garage_plug");
+        assert_eq!(upserted[0].state, EntityState::Unknown);
+    }
+
+    #[tokio::test]
+    async fn should_persist_polled_state_and_attributes() {
+        let (integration, ctx) = set_up(Arc::new(Mutex::new(Some(true)))).await;
+
+        RestIntegration::refresh(
+            &integration.client,
+            &integration.devices[0],
+            &ctx,
+            &integration.entities,
+        )
+        .await;
+
+        let upserted = ctx.entities();
+        let entity = upserted.last().unwrap();
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("power"),
+            Some(&minihub_domain::entity::AttributeValue::Float(40.5))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_mark_entity_unavailable_when_poll_fails() {
+        let relay = Arc::new(Mutex::new(None));
+        let (integration, ctx) = set_up(Arc::clone(&relay)).await;
+        let refresh = || {
+            RestIntegration::refresh(
+                &integration.client,
+                &integration.devices[0],
+                &ctx,
+                &integration.entities,
+            )
+        };
+
+        refresh().await;
+        assert_eq!(last_state(&ctx), Some(EntityState::Unavailable));
+
+        *relay.lock().unwrap() = Some(false);
+        refresh().await;
+        assert_eq!(last_state(&ctx), Some(EntityState::Off));
+    }
+
+    #[tokio::test]
+    async fn should_send_templated_command_and_return_refreshed_entity() {
+        let relay = Arc::new(Mutex::new(Some(false)));
+        let (integration, ctx) = set_up(Arc::clone(&relay)).await;
+        let entity_id = ctx.entities()[0].id;
+
+        let entity = integration
+            .handle_service_call(entity_id, "turn_on", serde_json::json!({ "state": "on" }))
+            .await
+            .unwrap();
+
+        assert_eq!(*relay.lock().unwrap(), Some(true));
+        assert_eq!(entity.state, EntityState::On);
+    }
+
+    #[tokio::test]
+    async fn should_fail_service_call_when_template_field_is_missing() {
+        let relay = Arc::new(Mutex::new(Some(false)));
+        let (integration, ctx) = set_up(Arc::clone(&relay)).await;
+        let entity_id = ctx.entities()[0].id;
+
+        let result = integration
+            .handle_service_call(entity_id, "turn_on", serde_json::json!({}))
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(*relay.lock().unwrap(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn should_return_error_for_unknown_entity() {
+        let (integration, _) = set_up(Arc::new(Mutex::new(Some(true)))).await;
+
+        let result = integration
+            .handle_service_call(EntityId::new(), "turn_on", Value::Null)
+            .await;
+
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_publish_result_when_service_call_requested_on_bus() {
+        let relay = Arc::new(Mutex::new(Some(false)));
+        let (mut integration, ctx) = set_up(Arc::clone(&relay)).await;
+        let entity_id = ctx.entities()[0].id;
+        let mut rx = ctx.subscribe();
+        integration.start_background(ctx.clone()).await.unwrap();
+
+        ctx.publish(Event::new(
+            EventType::ServiceCallRequested,
+            Some(entity_id),
+            serde_json::json!({ "service": "turn_on", "data": { "state": "on" } }),
+        ))
+        .await
+        .unwrap();
+
+        let result = loop {
+            let event = rx.recv().await.unwrap();
+            if event.event_type != EventType::ServiceCallRequested {
+                break event;
+            }
+        };
+        assert_eq!(result.event_type, EventType::ServiceCallCompleted);
+        assert_eq!(*relay.lock().unwrap(), Some(true));
+        integration.teardown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_teardown_without_error_when_not_started() {
+        let mut integration = RestIntegration::new(RestConfig::default()).unwrap();
+        assert!(integration.teardown().await.is_ok());
+    }
+}
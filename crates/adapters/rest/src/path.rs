@@ -0,0 +1,135 @@
+//! Minimal JSONPath used to pick values out of status documents.
+//!
+//! Only child steps are supported: an optional leading `$`, then `.field`
+//! and `[index]` steps, e.g. `$.meters[0].power`. Without `$` the first
+//! field may be written bare (`meters[0].power`). Wildcards, filters and
+//! slices are not supported.
+
+use serde_json::Value;
+
+use crate::error::RestError;
+
+/// One step of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Field(String),
+    Index(usize),
+}
+
+/// A parsed JSONPath expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsonPath {
+    steps: Vec<Step>,
+}
+
+impl JsonPath {
+    /// Parse `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RestError::InvalidPath`] when `path` is empty or uses
+    /// unsupported syntax.
+    pub fn parse(path: &str) -> Result<Self, RestError> {
+        let invalid = || RestError::InvalidPath(path.to_string());
+        if path.is_empty() {
+            return Err(invalid());
+        }
+        let (mut rest, mut bare_allowed) = match path.strip_prefix('$') {
+            Some(rest) => (rest, false),
+            None => (path, true),
+        };
+
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let (index, after) = after.split_once(']').ok_or_else(invalid)?;
+                let index = index.trim().parse().map_err(|_| invalid())?;
+                steps.push(Step::Index(index));
+                rest = after;
+            } else {
+                let field_start = match rest.strip_prefix('.') {
+                    Some(after) => after,
+                    None if bare_allowed => rest,
+                    None => return Err(invalid()),
+                };
+                let end = field_start.find(['.', '[']).unwrap_or(field_start.len());
+                let (field, after) = field_start.split_at(end);
+                if field.is_empty() {
+                    return Err(invalid());
+                }
+                steps.push(Step::Field(field.to_string()));
+                rest = after;
+            }
+            bare_allowed = false;
+        }
+        Ok(Self { steps })
+    }
+
+    /// The value `self` points to in `document`, if any.
+    pub fn select<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+        self.steps
+            .iter()
+            .try_fold(document, |value, step| match step {
+                Step::Field(name) => value.get(name),
+                Step::Index(index) => value.get(index),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Value {
+        serde_json::json!({
+            "relays": [{ "ison": true }],
+            "meters": [{ "power": 12.5 }, { "power": 3.0 }],
+            "POWER": "ON",
+        })
+    }
+
+    fn select(path: &str) -> Option<Value> {
+        JsonPath::parse(path).unwrap().select(&status()).cloned()
+    }
+
+    #[test]
+    fn should_select_nested_fields_and_indices() {
+        assert_eq!(select("$.relays[0].ison"), Some(Value::Bool(true)));
+        assert_eq!(select("$.meters[1].power"), Some(serde_json::json!(3.0)));
+    }
+
+    #[test]
+    fn should_accept_bare_first_field_when_no_root() {
+        assert_eq!(select("POWER"), Some(Value::from("ON")));
+        assert_eq!(select("meters[0].power"), Some(serde_json::json!(12.5)));
+    }
+
+    #[test]
+    fn should_select_whole_document_when_root_only() {
+        assert_eq!(select("$"), Some(status()));
+    }
+
+    #[test]
+    fn should_return_none_when_path_does_not_exist() {
+        assert_eq!(select("$.relays[3].ison"), None);
+        assert_eq!(select("$.missing"), None);
+        assert_eq!(select("$.POWER.value"), None);
+    }
+
+    #[test]
+    fn should_reject_unsupported_syntax() {
+        for path in [
+            "",
+            "$..power",
+            "$.meters[*]",
+            "$.meters[0",
+            "$relays",
+            "a..b",
+        ] {
+            assert!(
+                matches!(JsonPath::parse(path), Err(RestError::InvalidPath(_))),
+                "{path:?} should be rejected"
+            );
+        }
+    }
+}
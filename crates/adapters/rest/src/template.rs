@@ -0,0 +1,85 @@
+//! `{field}` placeholder substitution in command URLs and bodies.
+//!
+//! A placeholder is a field name made of ASCII letters, digits and
+//! underscores between braces. Any other brace is kept as is, so JSON
+//! bodies such as `{"brightness": {brightness}}` need no escaping.
+
+use serde_json::Value;
+
+use crate::error::RestError;
+
+/// Replace every placeholder in `template` with the matching field of
+/// `data`.
+///
+/// Strings are inserted without quotes, other values as JSON. Values are
+/// inserted verbatim, without URL encoding.
+///
+/// # Errors
+///
+/// Returns [`RestError::MissingParameter`] when `data` lacks a field
+/// referenced by the template.
+pub(crate) fn render(template: &str, data: &Value) -> Result<String, RestError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.split_once('}') {
+            Some((name, tail)) if is_placeholder(name) => {
+                let value = data
+                    .get(name)
+                    .ok_or_else(|| RestError::MissingParameter(name.to_string()))?;
+                match value {
+                    Value::String(text) => rendered.push_str(text),
+                    other => rendered.push_str(&other.to_string()),
+                }
+                rest = tail;
+            }
+            _ => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+fn is_placeholder(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_substitute_placeholders_from_data() {
+        let data = serde_json::json!({ "brightness": 128, "color": "warm" });
+
+        let url = render("http://plug/cm?cmnd=Dimmer%20{brightness}&c={color}", &data).unwrap();
+
+        assert_eq!(url, "http://plug/cm?cmnd=Dimmer%20128&c=warm");
+    }
+
+    #[test]
+    fn should_keep_json_braces_untouched() {
+        let data = serde_json::json!({ "brightness": 64 });
+
+        let body = render(r#"{"on": true, "brightness": {brightness}}"#, &data).unwrap();
+
+        assert_eq!(body, r#"{"on": true, "brightness": 64}"#);
+    }
+
+    #[test]
+    fn should_return_template_unchanged_when_no_placeholder() {
+        let url = render("http://plug/relay/0?turn=on", &Value::Null).unwrap();
+        assert_eq!(url, "http://plug/relay/0?turn=on");
+    }
+
+    #[test]
+    fn should_fail_when_field_is_missing() {
+        let err = render("http://plug/light?level={level}", &serde_json::json!({})).unwrap_err();
+        assert!(matches!(err, RestError::MissingParameter(name) if name == "level"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Shelly integration configuration.
+
+use serde::Deserialize;
+
+/// Default port of the Shelly HTTP and WebSocket API.
+pub const DEFAULT_PORT: u16 = 80;
+
+/// Configuration for the Shelly integration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShellyConfig {
+    /// Devices to connect to.
+    pub devices: Vec<ShellyDeviceConfig>,
+    /// Whether to also connect to the Shelly devices reported by the mDNS
+    /// integration.
+    pub discover_mdns: bool,
+    /// Source name sent with every request, which the device uses as the
+    /// destination of its notifications.
+    pub client_id: String,
+    /// Delay in seconds before reconnecting to a device that dropped.
+    pub reconnect_interval_secs: u64,
+}
+
+impl Default for ShellyConfig {
+    fn default() -> Self {
+        Self {
+            devices: Vec::new(),
+            discover_mdns: true,
+            client_id: "minihub".to_string(),
+            reconnect_interval_secs: 30,
+        }
+    }
+}
+
+/// Connection settings of a single Shelly device.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ShellyDeviceConfig {
+    /// Hostname or IP address of the device, e.g. `"192.168.1.30"`.
+    pub host: String,
+    /// HTTP port.
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+impl ShellyDeviceConfig {
+    /// Describe a device listening on the default port.
+    #[must_use]
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port: DEFAULT_PORT,
+        }
+    }
+
+    /// `host:port` address identifying the device.
+    #[must_use]
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// URL of the device's JSON-RPC WebSocket endpoint.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("ws://{}/rpc", self.address())
+    }
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_sensible_defaults() {
+        let config = ShellyConfig::default();
+        assert!(config.devices.is_empty());
+        assert!(config.discover_mdns);
+        assert_eq!(config.client_id, "minihub");
+        assert_eq!(config.reconnect_interval_secs, 30);
+    }
+
+    #[test]
+    fn should_deserialize_devices_from_toml() {
+        let toml = r#"
+            discover_mdns = false
+
+            [[devices]]
+            host = "192.168.1.30"
+
+            [[devices]]
+            host = "plug.local"
+            port = 8080
+        "#;
+        let config: ShellyConfig = toml::from_str(toml).unwrap();
+        assert!(!config.discover_mdns);
+        assert_eq!(
+            config.devices,
+            vec![
+                ShellyDeviceConfig::new("192.168.1.30"),
+                ShellyDeviceConfig {
+                    host: "plug.local".to_string(),
+                    port: 8080,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_build_websocket_url() {
+        let device = ShellyDeviceConfig::new("192.168.1.30");
+        assert_eq!(device.url(), "ws://192.168.1.30:80/rpc");
+    }
+}
@@ -0,0 +1,160 @@
+//! JSON-RPC connection to a Shelly device over its WebSocket.
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crate::error::ShellyError;
+use crate::rpc::{Frame, Request};
+
+/// A WebSocket speaking Shelly's JSON-RPC, numbering the requests it sends.
+pub(crate) struct Connection<S> {
+    socket: S,
+    client_id: String,
+    next_id: u64,
+}
+
+impl<S> Connection<S>
+where
+    S: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+{
+    /// Wrap `socket`, sending requests from `client_id`.
+    pub fn new(socket: S, client_id: impl Into<String>) -> Self {
+        Self {
+            socket,
+            client_id: client_id.into(),
+            next_id: 1,
+        }
+    }
+
+    /// Send a request without waiting for its response, returning its id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellyError::WebSocket`] if the frame cannot be written.
+    pub async fn send(&mut self, method: &str, params: &Value) -> Result<u64, ShellyError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = Request {
+            id,
+            src: &self.client_id,
+            method,
+            params,
+        };
+        self.socket.send(Message::text(request.to_text())).await?;
+        Ok(id)
+    }
+
+    /// Send a request and wait for its result, skipping the notifications
+    /// received meanwhile.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellyError::Rpc`] when the device rejects the request,
+    /// [`ShellyError::NotConnected`] when it closes the connection first, or
+    /// any error of [`Self::send`] and [`Self::next_frame`].
+    pub async fn call(&mut self, method: &str, params: &Value) -> Result<Value, ShellyError> {
+        let id = self.send(method, params).await?;
+        loop {
+            match self.next_frame().await? {
+                Some(Frame::Response {
+                    id: answered,
+                    result,
+                }) if answered == id => return result,
+                Some(_) => {}
+                None => return Err(ShellyError::NotConnected),
+            }
+        }
+    }
+
+    /// Read the next JSON-RPC message, or `None` once the device closed the
+    /// connection. Cancel-safe.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellyError::WebSocket`] if reading fails, or any error of
+    /// [`Frame::parse`].
+    pub async fn next_frame(&mut self) -> Result<Option<Frame>, ShellyError> {
+        loop {
+            match self.socket.next().await {
+                None | Some(Ok(Message::Close(_))) => return Ok(None),
+                Some(Ok(Message::Text(text))) => return Frame::parse(text.as_str()).map(Some),
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{accept_async, connect_async};
+
+    use super::*;
+
+    /// Start a fake device answering `Shelly.GetDeviceInfo` after pushing
+    /// a notification, returning its URL.
+    async fn fake_device() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let request: Value = serde_json::from_str(text.as_str()).unwrap();
+                let notification = serde_json::json!({
+                    "src": "shellyplus1",
+                    "dst": request["src"],
+                    "method": "NotifyStatus",
+                    "params": { "switch:0": { "output": true } },
+                });
+                let response = match request["method"].as_str() {
+                    Some("Shelly.GetDeviceInfo") => serde_json::json!({
+                        "id": request["id"],
+                        "src": "shellyplus1",
+                        "result": { "id": "shellyplus1", "mac": "A8032AB12345", "model": "SNSW-001X16EU" },
+                    }),
+                    _ => serde_json::json!({
+                        "id": request["id"],
+                        "src": "shellyplus1",
+                        "error": { "code": -114, "message": "Method not found" },
+                    }),
+                };
+                for message in [notification, response] {
+                    socket
+                        .send(Message::text(message.to_string()))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+        format!("ws://{address}/rpc")
+    }
+
+    #[tokio::test]
+    async fn should_return_result_of_matching_response() {
+        let (socket, _) = connect_async(fake_device().await).await.unwrap();
+        let mut connection = Connection::new(socket, "minihub");
+
+        let info = connection
+            .call("Shelly.GetDeviceInfo", &Value::Null)
+            .await
+            .unwrap();
+
+        assert_eq!(info["mac"], "A8032AB12345");
+    }
+
+    #[tokio::test]
+    async fn should_return_rpc_error_of_rejected_request() {
+        let (socket, _) = connect_async(fake_device().await).await.unwrap();
+        let mut connection = Connection::new(socket, "minihub");
+
+        let err = connection
+            .call("Light.Set", &serde_json::json!({ "id": 0 }))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, ShellyError::Rpc { code: -114, .. }));
+    }
+}
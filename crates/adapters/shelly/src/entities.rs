@@ -0,0 +1,547 @@
+//! Mapping of Shelly components onto minihub entities.
+//!
+//! Each supported component of the status document, keyed `{type}:{id}`,
+//! becomes its own entity named `{domain}.{device}_{type}_{id}`. Metering
+//! readings are kept as attributes: `power` (W), `energy` (total Wh since
+//! the device was set up), `voltage` (V) and `current` (A). Covers also
+//! keep their `position` (0–100 %) and `movement`.
+
+use minihub_domain::device::Device;
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
+use minihub_domain::error::MiniHubError;
+use serde_json::{Value, json};
+
+use crate::rpc::{Call, DeviceInfo};
+
+/// Attribute holding the position of a cover, in percent.
+const POSITION: &str = "position";
+/// Attribute holding what a cover is doing, e.g. `"opening"`.
+const MOVEMENT: &str = "movement";
+
+/// Metering fields of a component status, with the attribute they are
+/// stored in, its unit and precision.
+const METERING: [(&str, &str, &str, u32); 3] = [
+    ("apower", "power", "W", 1),
+    ("voltage", "voltage", "V", 1),
+    ("current", "current", "A", 3),
+];
+
+/// Attribute holding the total energy, read from `aenergy.total`.
+const ENERGY: &str = "energy";
+
+/// The kind of Shelly component behind an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component {
+    /// A relay output, `switch:N`.
+    Switch,
+    /// A roller shutter, `cover:N`.
+    Cover,
+    /// A standalone power meter, `pm1:N`.
+    PowerMeter,
+}
+
+impl Component {
+    /// Parse a status key such as `"switch:0"`, or `None` for component
+    /// kinds minihub does not map.
+    pub fn from_key(key: &str) -> Option<(Self, u32)> {
+        let (kind, id) = key.split_once(':')?;
+        let component = match kind {
+            "switch" => Self::Switch,
+            "cover" => Self::Cover,
+            "pm1" => Self::PowerMeter,
+            _ => return None,
+        };
+        Some((component, id.parse().ok()?))
+    }
+
+    fn domain(self) -> &'static str {
+        match self {
+            Self::Switch => "switch",
+            Self::Cover => "cover",
+            Self::PowerMeter => "sensor",
+        }
+    }
+
+    /// Component type as written in status keys.
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Switch => "switch",
+            Self::Cover => "cover",
+            Self::PowerMeter => "pm1",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Switch => "Switch",
+            Self::Cover => "Cover",
+            Self::PowerMeter => "Power meter",
+        }
+    }
+}
+
+/// How an entity maps onto its device connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Binding {
+    /// `host:port` of the owning device.
+    pub device: String,
+    pub component: Component,
+    /// Index of the component among those of its type.
+    pub id: u32,
+}
+
+impl Binding {
+    /// Status key of the component, e.g. `"switch:0"`.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.component.kind(), self.id)
+    }
+}
+
+/// Build the minihub device for a Shelly device.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the device has no usable name.
+pub(crate) fn map_device(info: &DeviceInfo) -> Result<Device, MiniHubError> {
+    let name = info
+        .name
+        .as_deref()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&info.id);
+    let unique_id = if info.mac.is_empty() {
+        &info.id
+    } else {
+        &info.mac
+    };
+    let mut builder = Device::builder()
+        .name(name)
+        .integration("shelly")
+        .unique_id(unique_id)
+        .manufacturer("Shelly");
+    if !info.model.is_empty() {
+        builder = builder.model(info.app.as_deref().unwrap_or(&info.model));
+    }
+    if !info.ver.is_empty() {
+        builder = builder.sw_version(&info.ver);
+    }
+    builder.build()
+}
+
+/// Map component `id` of type `component` to an entity of `device`.
+///
+/// `slug` is the `entity_id`-safe device id and `address` the `host:port`
+/// recorded in the binding. The entity takes the device name when it is
+/// the `only` component of its type.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the entity fails domain
+/// validation.
+pub(crate) fn map_entity(
+    device: &Device,
+    slug: &str,
+    address: &str,
+    (component, id): (Component, u32),
+    only: bool,
+) -> Result<(Entity, Binding), MiniHubError> {
+    let friendly_name = if only {
+        device.name.clone()
+    } else {
+        format!("{} {} {id}", device.name, component.label())
+    };
+    let mut builder = Entity::builder()
+        .device_id(device.id)
+        .entity_id(format!(
+            "{}.{slug}_{}_{id}",
+            component.domain(),
+            component.kind()
+        ))
+        .friendly_name(friendly_name);
+    for (_, attribute, unit, precision) in METERING {
+        builder = builder.attribute_meta(
+            attribute,
+            AttributeMeta::default()
+                .with_precision(precision)
+                .with_display_unit(unit),
+        );
+    }
+    builder = builder.attribute_meta(
+        ENERGY,
+        AttributeMeta::default()
+            .with_precision(2)
+            .with_display_unit("Wh"),
+    );
+    match component {
+        Component::Cover => {
+            builder = builder.attribute_meta(
+                POSITION,
+                AttributeMeta::default()
+                    .with_range(0.0, 100.0)
+                    .with_display_unit("%"),
+            );
+        }
+        Component::PowerMeter => {
+            builder = builder.device_class(DeviceClass::Power);
+        }
+        Component::Switch => {}
+    }
+
+    let entity = builder.build()?;
+    let binding = Binding {
+        device: address.to_string(),
+        component,
+        id,
+    };
+    Ok((entity, binding))
+}
+
+/// Apply a component status, full or partial, to the entity it targets.
+///
+/// Returns `true` when the entity's state or one of its attributes changed.
+pub(crate) fn apply_status(entity: &mut Entity, component: Component, status: &Value) -> bool {
+    let before = (entity.state.clone(), entity.attributes.clone());
+    let now = minihub_domain::time::now();
+    match component {
+        Component::Switch => {
+            if let Some(output) = status.get("output").and_then(Value::as_bool) {
+                entity.update_state(on_off(output), now);
+            }
+        }
+        Component::Cover => {
+            let position = status.get("current_pos").and_then(Value::as_i64);
+            let movement = status.get("state").and_then(Value::as_str);
+            if let Some(position) = position {
+                entity.set_attribute(POSITION.to_string(), AttributeValue::Int(position));
+            }
+            if let Some(movement) = movement {
+                entity.set_attribute(
+                    MOVEMENT.to_string(),
+                    AttributeValue::String(movement.to_string()),
+                );
+            }
+            // A cover is on while partly open; uncalibrated covers only
+            // report `open` and `closed`.
+            let open = position.map(|position| position > 0).or(match movement {
+                Some("open") => Some(true),
+                Some("closed") => Some(false),
+                _ => None,
+            });
+            if let Some(open) = open {
+                entity.update_state(on_off(open), now);
+            }
+        }
+        Component::PowerMeter => {
+            if status.get("apower").is_some() {
+                entity.update_state(EntityState::On, now);
+            }
+        }
+    }
+    for (field, attribute, _, _) in METERING {
+        if let Some(value) = status.get(field).and_then(Value::as_f64) {
+            entity.set_attribute(attribute.to_string(), AttributeValue::Float(value));
+        }
+    }
+    if let Some(total) = status.pointer("/aenergy/total").and_then(Value::as_f64) {
+        entity.set_attribute(ENERGY.to_string(), AttributeValue::Float(total));
+    }
+    before != (entity.state.clone(), entity.attributes.clone())
+}
+
+fn on_off(on: bool) -> EntityState {
+    if on {
+        EntityState::On
+    } else {
+        EntityState::Off
+    }
+}
+
+/// Build the call for a service.
+///
+/// Switches support `turn_on`, `turn_off` and `toggle`, all mapped to
+/// `Switch.Set`. Covers support `open` (or `turn_on`), `close` (or
+/// `turn_off`), `toggle`, `stop` and `set_position` with a `position`
+/// between `0` and `100` in `data`. Returns `None` when the entity cannot be
+/// controlled or the service is not supported.
+pub(crate) fn command(
+    binding: &Binding,
+    current: &EntityState,
+    service: &str,
+    data: &Value,
+) -> Option<Call> {
+    let id = binding.id;
+    match binding.component {
+        Component::Switch => {
+            let on = match service {
+                "turn_on" => true,
+                "turn_off" => false,
+                "toggle" => *current != EntityState::On,
+                _ => return None,
+            };
+            Some(Call {
+                method: "Switch.Set",
+                params: json!({ "id": id, "on": on }),
+            })
+        }
+        Component::Cover => {
+            let (method, params) = match service {
+                "open" | "turn_on" => ("Cover.Open", json!({ "id": id })),
+                "close" | "turn_off" => ("Cover.Close", json!({ "id": id })),
+                "toggle" => {
+                    let method = if *current == EntityState::On {
+                        "Cover.Close"
+                    } else {
+                        "Cover.Open"
+                    };
+                    (method, json!({ "id": id }))
+                }
+                "stop" => ("Cover.Stop", json!({ "id": id })),
+                "set_position" => {
+                    let position = data.get(POSITION).and_then(Value::as_f64)?;
+                    #[allow(clippy::cast_possible_truncation)]
+                    let position = position.clamp(0.0, 100.0).round() as i64;
+                    ("Cover.GoToPosition", json!({ "id": id, "pos": position }))
+                }
+                _ => return None,
+            };
+            Some(Call { method, params })
+        }
+        Component::PowerMeter => None,
+    }
+}
+
+/// Turn a device id into an `entity_id`-safe slug.
+pub(crate) fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "192.168.1.30:80";
+
+    fn info() -> DeviceInfo {
+        DeviceInfo {
+            id: "shellyplus2pm-a8032ab12345".to_string(),
+            name: Some("Kitchen".to_string()),
+            mac: "A8032AB12345".to_string(),
+            model: "SNSW-102P16EU".to_string(),
+            ver: "1.4.4".to_string(),
+            app: Some("Plus2PM".to_string()),
+        }
+    }
+
+    fn map(component: Component, only: bool) -> (Entity, Binding) {
+        let device = map_device(&info()).unwrap();
+        map_entity(
+            &device,
+            "shellyplus2pm_a8032ab12345",
+            ADDRESS,
+            (component, 1),
+            only,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_map_device_info_to_device() {
+        let device = map_device(&info()).unwrap();
+        assert_eq!(device.name, "Kitchen");
+        assert_eq!(device.integration, "shelly");
+        assert_eq!(device.unique_id, "A8032AB12345");
+        assert_eq!(device.manufacturer.as_deref(), Some("Shelly"));
+        assert_eq!(device.model.as_deref(), Some("Plus2PM"));
+        assert_eq!(device.sw_version.as_deref(), Some("1.4.4"));
+    }
+
+    #[test]
+    fn should_name_device_after_its_id_when_unnamed() {
+        let device = map_device(&DeviceInfo {
+            name: None,
+            ..info()
+        })
+        .unwrap();
+        assert_eq!(device.name, "shellyplus2pm-a8032ab12345");
+    }
+
+    #[test]
+    fn should_parse_supported_component_keys() {
+        assert_eq!(
+            Component::from_key("switch:0"),
+            Some((Component::Switch, 0))
+        );
+        assert_eq!(Component::from_key("cover:1"), Some((Component::Cover, 1)));
+        assert_eq!(
+            Component::from_key("pm1:0"),
+            Some((Component::PowerMeter, 0))
+        );
+        assert_eq!(Component::from_key("sys"), None);
+        assert_eq!(Component::from_key("wifi:0"), None);
+    }
+
+    #[test]
+    fn should_map_component_to_entity() {
+        let (entity, binding) = map(Component::Switch, false);
+
+        assert_eq!(
+            entity.entity_id,
+            "switch.shellyplus2pm_a8032ab12345_switch_1"
+        );
+        assert_eq!(entity.friendly_name, "Kitchen Switch 1");
+        assert_eq!(
+            entity.attribute_meta["power"].display_unit.as_deref(),
+            Some("W")
+        );
+        assert_eq!(binding.key(), "switch:1");
+        assert_eq!(binding.device, ADDRESS);
+    }
+
+    #[test]
+    fn should_name_only_component_after_device() {
+        let (entity, _) = map(Component::PowerMeter, true);
+
+        assert_eq!(entity.entity_id, "sensor.shellyplus2pm_a8032ab12345_pm1_1");
+        assert_eq!(entity.friendly_name, "Kitchen");
+        assert_eq!(entity.device_class, Some(DeviceClass::Power));
+    }
+
+    #[test]
+    fn should_apply_switch_output_and_metering() {
+        let (mut entity, _) = map(Component::Switch, true);
+        let status = json!({
+            "id": 1,
+            "output": true,
+            "apower": 42.5,
+            "voltage": 231.2,
+            "current": 0.184,
+            "aenergy": { "total": 1234.567, "by_minute": [0.0, 0.0, 0.0] },
+        });
+
+        assert!(apply_status(&mut entity, Component::Switch, &status));
+        assert!(!apply_status(&mut entity, Component::Switch, &status));
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("power"),
+            Some(&AttributeValue::Float(42.5))
+        );
+        assert_eq!(
+            entity.get_attribute("energy"),
+            Some(&AttributeValue::Float(1234.567))
+        );
+    }
+
+    #[test]
+    fn should_keep_fields_missing_from_partial_status() {
+        let (mut entity, _) = map(Component::Switch, true);
+        apply_status(
+            &mut entity,
+            Component::Switch,
+            &json!({ "output": true, "apower": 10.0 }),
+        );
+
+        assert!(apply_status(
+            &mut entity,
+            Component::Switch,
+            &json!({ "apower": 12.0 })
+        ));
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute("power"),
+            Some(&AttributeValue::Float(12.0))
+        );
+    }
+
+    #[test]
+    fn should_apply_cover_position_and_movement() {
+        let (mut entity, _) = map(Component::Cover, true);
+
+        apply_status(
+            &mut entity,
+            Component::Cover,
+            &json!({ "state": "open", "current_pos": 100 }),
+        );
+        assert_eq!(entity.state, EntityState::On);
+
+        apply_status(
+            &mut entity,
+            Component::Cover,
+            &json!({ "state": "closing", "current_pos": 60 }),
+        );
+        assert_eq!(entity.state, EntityState::On);
+
+        assert_eq!(
+            entity.get_attribute(POSITION),
+            Some(&AttributeValue::Int(60))
+        );
+        assert_eq!(
+            entity.get_attribute(MOVEMENT),
+            Some(&AttributeValue::String("closing".to_string()))
+        );
+
+        apply_status(
+            &mut entity,
+            Component::Cover,
+            &json!({ "state": "closed", "current_pos": 0 }),
+        );
+        assert_eq!(entity.state, EntityState::Off);
+    }
+
+    #[test]
+    fn should_map_switch_services_to_switch_set() {
+        let (_, binding) = map(Component::Switch, true);
+
+        assert_eq!(
+            command(&binding, &EntityState::Off, "turn_on", &Value::Null),
+            Some(Call {
+                method: "Switch.Set",
+                params: json!({ "id": 1, "on": true }),
+            })
+        );
+        assert_eq!(
+            command(&binding, &EntityState::On, "toggle", &Value::Null),
+            Some(Call {
+                method: "Switch.Set",
+                params: json!({ "id": 1, "on": false }),
+            })
+        );
+    }
+
+    #[test]
+    fn should_map_cover_services() {
+        let (_, binding) = map(Component::Cover, true);
+
+        assert_eq!(
+            command(&binding, &EntityState::Off, "stop", &Value::Null).map(|call| call.method),
+            Some("Cover.Stop")
+        );
+        assert_eq!(
+            command(
+                &binding,
+                &EntityState::Off,
+                "set_position",
+                &json!({ "position": 140 }),
+            ),
+            Some(Call {
+                method: "Cover.GoToPosition",
+                params: json!({ "id": 1, "pos": 100 }),
+            })
+        );
+    }
+
+    #[test]
+    fn should_refuse_commands_for_power_meters_and_unknown_services() {
+        let (_, meter) = map(Component::PowerMeter, true);
+        let (_, switch) = map(Component::Switch, true);
+        assert_eq!(
+            command(&meter, &EntityState::On, "turn_on", &Value::Null),
+            None
+        );
+        assert_eq!(
+            command(&switch, &EntityState::On, "set_position", &Value::Null),
+            None
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Shelly adapter error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the Shelly adapter.
+#[derive(Debug, thiserror::Error)]
+pub enum ShellyError {
+    /// Opening, reading from or writing to the WebSocket failed.
+    #[error("Shelly connection error")]
+    WebSocket(#[source] Box<tokio_tungstenite::tungstenite::Error>),
+
+    /// A message could not be decoded as the expected JSON.
+    #[error("failed to decode Shelly message")]
+    Decode(#[source] serde_json::Error),
+
+    /// The device answered a request with an RPC error.
+    #[error("Shelly RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    /// The device sent something the protocol does not allow here.
+    #[error("Shelly protocol error: {0}")]
+    Protocol(String),
+
+    /// The device is not connected at the moment.
+    #[error("Shelly device not connected")]
+    NotConnected,
+
+    /// The target entity cannot handle the requested service.
+    #[error("unsupported service: {0}")]
+    UnsupportedService(String),
+
+    /// A domain-level error (validation, not-found, etc.).
+    #[error("{0}")]
+    Domain(#[source] MiniHubError),
+}
+
+impl ShellyError {
+    /// Convert into a [`MiniHubError::Storage`] for propagation across port
+    /// boundaries.
+    #[must_use]
+    pub fn into_domain(self) -> MiniHubError {
+        match self {
+            Self::Domain(err) => err,
+            other => MiniHubError::Storage(other.into()),
+        }
+    }
+}
+
+impl From<ShellyError> for MiniHubError {
+    fn from(err: ShellyError) -> Self {
+        err.into_domain()
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ShellyError {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_rpc_error() {
+        let err = ShellyError::Rpc {
+            code: -103,
+            message: "Invalid argument 'id'".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Shelly RPC error -103: Invalid argument 'id'"
+        );
+    }
+
+    #[test]
+    fn should_convert_not_connected_to_storage_error() {
+        let err: MiniHubError = ShellyError::NotConnected.into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+
+    #[test]
+    fn should_convert_domain_error_back_to_domain() {
+        let domain_err =
+            MiniHubError::Validation(minihub_domain::error::ValidationError::EmptyName);
+        let back: MiniHubError = ShellyError::Domain(domain_err).into();
+        assert!(matches!(back, MiniHubError::Validation(_)));
+    }
+}
@@ -0,0 +1,660 @@
+//! # minihub-adapter-shelly
+//!
+//! Shelly adapter — bridges Shelly Gen2 devices (Plus, Pro and later) into
+//! minihub over their JSON-RPC API, on the `/rpc` WebSocket.
+//!
+//! ## How it works
+//!
+//! Each device gets its own background task which:
+//!
+//! 1. opens the WebSocket and calls `Shelly.GetDeviceInfo`,
+//! 2. calls `Shelly.GetStatus` and maps its supported components,
+//!    persisting the device and its entities with their current state,
+//! 3. applies every `NotifyStatus` and `NotifyFullStatus` notification to
+//!    the matching entities, and sends service calls as RPCs meanwhile.
+//!
+//! Devices are the configured ones, plus — with
+//! [`ShellyConfig::discover_mdns`] — those reported by the mDNS integration
+//! through [`DeviceDetected`](minihub_domain::event::EventType::DeviceDetected)
+//! events suggesting this integration.
+//!
+//! When the connection drops, the device's entities are marked
+//! `unavailable` and the task reconnects after
+//! [`ShellyConfig::reconnect_interval_secs`].
+//!
+//! ## Supported components
+//!
+//! | Shelly component | minihub entity | Services |
+//! |------------------|----------------|----------|
+//! | `switch:N` | `switch.{device}_switch_{N}` | `turn_on`, `turn_off`, `toggle` (`Switch.Set`) |
+//! | `cover:N` | `cover.{device}_cover_{N}` | `open`, `close`, `toggle`, `stop`, `set_position` (`position` 0–100) |
+//! | `pm1:N` | `sensor.{device}_pm1_{N}` | — |
+//!
+//! Every entity keeps the `power`, `voltage`, `current` and `energy`
+//! readings of its component, when it meters them. Devices protected by a
+//! password are not supported.
+//!
+//! ## Dependency rule
+//!
+//! Same as other adapters: depends on `minihub-app` and `minihub-domain`.
+
+mod config;
+mod connection;
+mod entities;
+mod error;
+mod rpc;
+
+pub use config::{ShellyConfig, ShellyDeviceConfig};
+pub use error::ShellyError;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use minihub_app::integration_manager::forward_service_calls;
+use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload};
+use minihub_domain::id::EntityId;
+
+use crate::connection::Connection;
+use crate::entities::{Binding, Component};
+use crate::rpc::{Call, DeviceInfo, Frame};
+
+/// Entities known to the integration, keyed by `entity_id` string.
+type Tracked = Arc<Mutex<HashMap<String, (Entity, Binding)>>>;
+
+/// Command channels of the connected devices, keyed by `host:port`.
+type Senders = Arc<Mutex<HashMap<String, mpsc::Sender<Call>>>>;
+
+/// Background tasks of the devices, keyed by `host:port`.
+type Devices = Arc<Mutex<HashMap<String, JoinHandle<()>>>>;
+
+/// State shared between the integration and its background tasks.
+#[derive(Clone)]
+struct Shared {
+    client_id: String,
+    reconnect_interval: Duration,
+    entities: Tracked,
+    senders: Senders,
+    devices: Devices,
+}
+
+impl Shared {
+    /// Start the task of the device at `address`, unless it has one.
+    fn spawn_device(&self, address: String, ctx: impl IntegrationContext + 'static) {
+        let mut devices = self.devices.lock().unwrap_or_else(PoisonError::into_inner);
+        if devices.contains_key(&address) {
+            return;
+        }
+        let handle = tokio::spawn(ShellyIntegration::device_loop(
+            address.clone(),
+            self.clone(),
+            ctx,
+        ));
+        devices.insert(address, handle);
+    }
+}
+
+/// Shelly integration.
+///
+/// Keeps one WebSocket per device, mirrors component statuses into
+/// entities and sends switch and cover commands.
+pub struct ShellyIntegration {
+    config: ShellyConfig,
+    shared: Shared,
+    subscriber_handle: Option<JoinHandle<()>>,
+    discovery_handle: Option<JoinHandle<()>>,
+}
+
+impl ShellyIntegration {
+    /// Create a new Shelly integration with the given configuration.
+    #[must_use]
+    pub fn new(config: ShellyConfig) -> Self {
+        let shared = Shared {
+            client_id: config.client_id.clone(),
+            reconnect_interval: Duration::from_secs(config.reconnect_interval_secs),
+            entities: Arc::new(Mutex::new(HashMap::new())),
+            senders: Arc::new(Mutex::new(HashMap::new())),
+            devices: Arc::new(Mutex::new(HashMap::new())),
+        };
+        Self {
+            config,
+            shared,
+            subscriber_handle: None,
+            discovery_handle: None,
+        }
+    }
+
+    /// Connect to a device forever, reconnecting after each failure.
+    async fn device_loop(address: String, shared: Shared, ctx: impl IntegrationContext) {
+        let url = format!("ws://{address}/rpc");
+        loop {
+            match Self::session(&address, &url, &shared, &ctx).await {
+                Ok(()) => tracing::info!(%address, "Shelly device closed the connection"),
+                Err(err) => tracing::warn!(%err, %address, "Shelly connection failed"),
+            }
+            shared
+                .senders
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&address);
+            for entity in Self::mark_unavailable(&address, &shared.entities) {
+                if let Err(err) = ctx.upsert_entity(entity).await {
+                    tracing::warn!(%err, "failed to persist Shelly availability");
+                }
+            }
+            tokio::time::sleep(shared.reconnect_interval).await;
+        }
+    }
+
+    /// Run one connection: discovery, then notifications and commands until
+    /// the connection ends.
+    async fn session(
+        address: &str,
+        url: &str,
+        shared: &Shared,
+        ctx: &impl IntegrationContext,
+    ) -> Result<(), ShellyError> {
+        let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+        let mut connection = Connection::new(socket, shared.client_id.as_str());
+
+        let info: DeviceInfo =
+            serde_json::from_value(connection.call(rpc::GET_DEVICE_INFO, &Value::Null).await?)
+                .map_err(ShellyError::Decode)?;
+        tracing::info!(%address, id = %info.id, "connected to Shelly device");
+
+        let status = connection.call(rpc::GET_STATUS, &Value::Null).await?;
+        let discovered = Self::apply_listing(address, &info, &status, &shared.entities)?;
+        tracing::info!(
+            device = %discovered.device.name,
+            entity_count = discovered.entities.len(),
+            "discovered Shelly device"
+        );
+        if let Err(err) = ctx.persist_discovered(discovered).await {
+            tracing::warn!(%err, "failed to persist Shelly discovery");
+        }
+
+        let (command_tx, mut command_rx) = mpsc::channel(16);
+        shared
+            .senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(address.to_string(), command_tx);
+
+        loop {
+            tokio::select! {
+                frame = connection.next_frame() => match frame? {
+                    None => return Ok(()),
+                    Some(Frame::Notification { method, params }) => {
+                        Self::handle_notification(address, &method, &params, ctx, &shared.entities)
+                            .await;
+                    }
+                    Some(Frame::Response { id, result: Err(err) }) => {
+                        tracing::warn!(%err, %address, id, "Shelly device rejected a command");
+                    }
+                    Some(Frame::Response { .. }) => {}
+                },
+                Some(call) = command_rx.recv() => {
+                    connection.send(call.method, &call.params).await?;
+                }
+            }
+        }
+    }
+
+    /// Map the device and the supported components of its status, tracking
+    /// the entities with their current state.
+    fn apply_listing(
+        address: &str,
+        info: &DeviceInfo,
+        status: &Value,
+        entities: &Tracked,
+    ) -> Result<DiscoveredDevice, ShellyError> {
+        let device = entities::map_device(info).map_err(ShellyError::Domain)?;
+        let slug = entities::slugify(&info.id);
+        let components: Vec<(Component, u32, &Value)> = status
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, status)| {
+                Component::from_key(key).map(|(component, id)| (component, id, status))
+            })
+            .collect();
+
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut device_entities = Vec::new();
+        for &(component, id, component_status) in &components {
+            let only = components
+                .iter()
+                .filter(|(other, _, _)| *other == component)
+                .count()
+                == 1;
+            let (mut entity, binding) =
+                entities::map_entity(&device, &slug, address, (component, id), only)
+                    .map_err(ShellyError::Domain)?;
+            entities::apply_status(&mut entity, component, component_status);
+            tracked.insert(entity.entity_id.clone(), (entity.clone(), binding));
+            device_entities.push(entity);
+        }
+        Ok(DiscoveredDevice {
+            device,
+            entities: device_entities,
+            via_device: None,
+        })
+    }
+
+    /// Apply the component statuses of a notification, persisting the
+    /// entities that changed.
+    async fn handle_notification(
+        address: &str,
+        method: &str,
+        params: &Value,
+        ctx: &impl IntegrationContext,
+        entities: &Tracked,
+    ) {
+        if method != rpc::NOTIFY_STATUS && method != rpc::NOTIFY_FULL_STATUS {
+            tracing::debug!(method, "ignoring Shelly notification");
+            return;
+        }
+        for entity in Self::apply_statuses(address, params, entities) {
+            if let Err(err) = ctx.upsert_entity(entity).await {
+                tracing::warn!(%err, "failed to persist Shelly state update");
+            }
+        }
+    }
+
+    /// Apply every component status of `params` to the entities of the
+    /// device at `address`, returning the updated snapshots of those that
+    /// changed.
+    fn apply_statuses(address: &str, params: &Value, entities: &Tracked) -> Vec<Entity> {
+        let Some(statuses) = params.as_object() else {
+            return Vec::new();
+        };
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        tracked
+            .values_mut()
+            .filter(|(_, binding)| binding.device == address)
+            .filter_map(|(entity, binding)| {
+                let status = statuses.get(&binding.key())?;
+                entities::apply_status(entity, binding.component, status).then(|| entity.clone())
+            })
+            .collect()
+    }
+
+    /// Mark every entity of a device unavailable, returning those that
+    /// changed.
+    fn mark_unavailable(address: &str, entities: &Tracked) -> Vec<Entity> {
+        let mut tracked = entities.lock().unwrap_or_else(PoisonError::into_inner);
+        tracked
+            .values_mut()
+            .filter(|(entity, binding)| binding.device == address && entity.state.is_available())
+            .map(|(entity, _)| {
+                entity.update_state(EntityState::Unavailable, minihub_domain::time::now());
+                entity.clone()
+            })
+            .collect()
+    }
+
+    /// Build the call for `service` and hand it to the device's connection.
+    async fn send_command(
+        entities: &Tracked,
+        senders: &Senders,
+        entity_id: &str,
+        service: &str,
+        data: &Value,
+    ) -> Result<Entity, ShellyError> {
+        let (entity, binding) = entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| {
+                ShellyError::Domain(
+                    NotFoundError {
+                        entity: "Entity",
+                        id: entity_id.to_string(),
+                    }
+                    .into(),
+                )
+            })?;
+        let call = entities::command(&binding, &entity.state, service, data)
+            .ok_or_else(|| ShellyError::UnsupportedService(service.to_string()))?;
+        let sender = senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&binding.device)
+            .cloned()
+            .ok_or(ShellyError::NotConnected)?;
+        let method = call.method;
+        sender
+            .send(call)
+            .await
+            .map_err(|_| ShellyError::NotConnected)?;
+
+        tracing::info!(service, entity_id, method, device = %binding.device, "sent Shelly command");
+        Ok(entity)
+    }
+
+    /// Address of the Shelly device announced by a
+    /// [`EventType::DeviceDetected`] event of the mDNS integration.
+    fn detected_address(event: &Event) -> Option<&str> {
+        if event.event_type != EventType::DeviceDetected
+            || event
+                .data
+                .get("suggested_integration")
+                .and_then(Value::as_str)
+                != Some("shelly")
+        {
+            return None;
+        }
+        event.data.get("address").and_then(Value::as_str)
+    }
+
+    /// Connect to the devices detected over mDNS.
+    async fn discovery_loop(
+        mut rx: tokio::sync::broadcast::Receiver<Event>,
+        ctx: impl IntegrationContext + Clone + 'static,
+        shared: Shared,
+    ) {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Some(address) = Self::detected_address(&event) {
+                        shared.spawn_device(address.to_string(), ctx.clone());
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Shelly discovery lagged, some events were missed");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    tracing::info!("Shelly discovery channel closed, stopping");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send the service call requested on `entity_id` to its device,
+    /// unless the entity is not a Shelly one. The new state is persisted
+    /// once the device notifies it.
+    async fn forward_service_call(
+        shared: Shared,
+        entity_id: String,
+        request: ServiceCallPayload,
+    ) -> Option<Result<(), ShellyError>> {
+        if !shared
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&entity_id)
+        {
+            return None;
+        }
+        let sent = Self::send_command(
+            &shared.entities,
+            &shared.senders,
+            &entity_id,
+            &request.service,
+            &request.data,
+        )
+        .await;
+        Some(sent.map(|_| ()))
+    }
+}
+
+impl Integration for ShellyIntegration {
+    fn name(&self) -> &'static str {
+        "shelly"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        // Devices may be offline at startup, so connections are only opened
+        // by the background tasks, which keep retrying.
+        tracing::info!(
+            device_count = self.config.devices.len(),
+            discover_mdns = self.config.discover_mdns,
+            "Shelly integration configured"
+        );
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let bus_rx = ctx.subscribe();
+        let shared = self.shared.clone();
+        self.subscriber_handle = Some(tokio::spawn(forward_service_calls(
+            "shelly",
+            bus_rx,
+            ctx.clone(),
+            move |entity_id, request| {
+                Self::forward_service_call(shared.clone(), entity_id, request)
+            },
+        )));
+        if self.config.discover_mdns {
+            self.discovery_handle = Some(tokio::spawn(Self::discovery_loop(
+                ctx.subscribe(),
+                ctx.clone(),
+                self.shared.clone(),
+            )));
+        }
+
+        for device in &self.config.devices {
+            self.shared.spawn_device(device.address(), ctx.clone());
+        }
+
+        tracing::info!("Shelly background tasks started");
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        service: &str,
+        data: Value,
+    ) -> Result<Entity, MiniHubError> {
+        let key = self
+            .shared
+            .entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .find(|(entity, _)| entity.id == entity_id)
+            .map(|(entity, _)| entity.entity_id.clone())
+            .ok_or_else(|| NotFoundError {
+                entity: "Entity",
+                id: entity_id.to_string(),
+            })?;
+
+        Ok(Self::send_command(
+            &self.shared.entities,
+            &self.shared.senders,
+            &key,
+            service,
+            &data,
+        )
+        .await?)
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        let devices: Vec<_> = self
+            .shared
+            .devices
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain()
+            .map(|(_, handle)| handle)
+            .collect();
+        let subscribers = self
+            .subscriber_handle
+            .take()
+            .into_iter()
+            .chain(self.discovery_handle.take());
+        for handle in devices.into_iter().chain(subscribers) {
+            handle.abort();
+        }
+        self.shared
+            .senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        tracing::info!("Shelly integration stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const ADDRESS: &str = "192.168.1.30:80";
+
+    fn discovered() -> (Tracked, DiscoveredDevice) {
+        let entities: Tracked = Arc::new(Mutex::new(HashMap::new()));
+        let info = DeviceInfo {
+            id: "shellyplus1pm-441793d69718".to_string(),
+            mac: "441793D69718".to_string(),
+            model: "SNSW-001P16EU".to_string(),
+            ..DeviceInfo::default()
+        };
+        let status = json!({
+            "sys": { "uptime": 1200 },
+            "wifi": { "sta_ip": "192.168.1.30" },
+            "switch:0": { "id": 0, "output": false, "apower": 0.0, "aenergy": { "total": 12.5 } },
+        });
+        let discovered =
+            ShellyIntegration::apply_listing(ADDRESS, &info, &status, &entities).unwrap();
+        (entities, discovered)
+    }
+
+    #[test]
+    fn should_track_supported_components_with_their_state() {
+        let (entities, discovered) = discovered();
+
+        assert_eq!(discovered.device.name, "shellyplus1pm-441793d69718");
+        assert_eq!(discovered.entities.len(), 1);
+        assert_eq!(discovered.entities[0].state, EntityState::Off);
+        assert!(
+            entities
+                .lock()
+                .unwrap()
+                .contains_key("switch.shellyplus1pm_441793d69718_switch_0")
+        );
+    }
+
+    #[test]
+    fn should_apply_notified_statuses_of_the_device_only() {
+        let (entities, _) = discovered();
+        let params = json!({ "ts": 1_700_000_000.0, "switch:0": { "output": true } });
+
+        assert!(ShellyIntegration::apply_statuses("10.0.0.9:80", &params, &entities).is_empty());
+        let updated = ShellyIntegration::apply_statuses(ADDRESS, &params, &entities);
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].state, EntityState::On);
+        assert!(ShellyIntegration::apply_statuses(ADDRESS, &params, &entities).is_empty());
+    }
+
+    #[test]
+    fn should_mark_entities_unavailable_once() {
+        let (entities, _) = discovered();
+
+        assert_eq!(
+            ShellyIntegration::mark_unavailable(ADDRESS, &entities).len(),
+            1
+        );
+        assert!(ShellyIntegration::mark_unavailable(ADDRESS, &entities).is_empty());
+    }
+
+    #[test]
+    fn should_only_follow_devices_detected_for_shelly() {
+        let detected = |suggested: &str| {
+            Event::new(
+                EventType::DeviceDetected,
+                None,
+                json!({
+                    "integration": "mdns",
+                    "suggested_integration": suggested,
+                    "address": "192.168.1.31:80",
+                }),
+            )
+        };
+
+        assert_eq!(
+            ShellyIntegration::detected_address(&detected("shelly")),
+            Some("192.168.1.31:80")
+        );
+        assert_eq!(
+            ShellyIntegration::detected_address(&detected("esphome")),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn should_forward_switch_set_to_connected_device() {
+        let (entities, _) = discovered();
+        let senders: Senders = Arc::new(Mutex::new(HashMap::new()));
+        let (tx, mut rx) = mpsc::channel(1);
+        senders.lock().unwrap().insert(ADDRESS.to_string(), tx);
+
+        ShellyIntegration::send_command(
+            &entities,
+            &senders,
+            "switch.shellyplus1pm_441793d69718_switch_0",
+            "turn_on",
+            &Value::Null,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            rx.recv().await,
+            Some(Call {
+                method: "Switch.Set",
+                params: json!({ "id": 0, "on": true }),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_not_connected_when_device_is_offline() {
+        let (entities, _) = discovered();
+        let senders: Senders = Arc::new(Mutex::new(HashMap::new()));
+
+        let err = ShellyIntegration::send_command(
+            &entities,
+            &senders,
+            "switch.shellyplus1pm_441793d69718_switch_0",
+            "turn_off",
+            &Value::Null,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, ShellyError::NotConnected));
+    }
+
+    #[tokio::test]
+    async fn should_return_error_for_unknown_entity() {
+        let integration = ShellyIntegration::new(ShellyConfig::default());
+        let result = integration
+            .handle_service_call(EntityId::new(), "turn_on", Value::Null)
+            .await;
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_teardown_without_error_when_not_started() {
+        let mut integration = ShellyIntegration::new(ShellyConfig::default());
+        assert!(integration.teardown().await.is_ok());
+    }
+}
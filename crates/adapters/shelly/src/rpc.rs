@@ -0,0 +1,209 @@
+//! Shelly Gen2 JSON-RPC messages, exchanged as WebSocket text frames.
+//!
+//! Every request carries an `id`, echoed by its response, and a `src`
+//! name: once a request was received, the device sends its notifications
+//! (`NotifyStatus`, `NotifyFullStatus`, `NotifyEvent`) to that name over
+//! the same connection.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::ShellyError;
+
+/// Method returning the identity of the device.
+pub(crate) const GET_DEVICE_INFO: &str = "Shelly.GetDeviceInfo";
+
+/// Method returning the status of every component, keyed `{type}:{id}`.
+pub(crate) const GET_STATUS: &str = "Shelly.GetStatus";
+
+/// Notification carrying the changed fields of some components.
+pub(crate) const NOTIFY_STATUS: &str = "NotifyStatus";
+
+/// Notification carrying the full status of some components.
+pub(crate) const NOTIFY_FULL_STATUS: &str = "NotifyFullStatus";
+
+/// A request sent to the device.
+#[derive(Debug, Serialize)]
+pub(crate) struct Request<'a> {
+    pub id: u64,
+    pub src: &'a str,
+    pub method: &'a str,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub params: &'a Value,
+}
+
+impl Request<'_> {
+    /// Serialize the request as the text of a WebSocket frame.
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A method call to issue on the device, e.g. `Switch.Set`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Call {
+    pub method: &'static str,
+    pub params: Value,
+}
+
+/// Error object of a failed request.
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Any message sent by the device, before telling responses and
+/// notifications apart.
+#[derive(Debug, Deserialize)]
+struct RawFrame {
+    id: Option<u64>,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+/// A message sent by the device.
+#[derive(Debug)]
+pub(crate) enum Frame {
+    /// The answer to the request with the same `id`.
+    Response {
+        id: u64,
+        result: Result<Value, ShellyError>,
+    },
+    /// A notification, sent without being asked.
+    Notification { method: String, params: Value },
+}
+
+impl Frame {
+    /// Decode the text of a WebSocket frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShellyError::Decode`] for invalid JSON, and
+    /// [`ShellyError::Protocol`] for a message that is neither a response
+    /// nor a notification.
+    pub fn parse(text: &str) -> Result<Self, ShellyError> {
+        let raw: RawFrame = serde_json::from_str(text).map_err(ShellyError::Decode)?;
+        match (raw.id, raw.method) {
+            (Some(id), None) => Ok(Self::Response {
+                id,
+                result: match raw.error {
+                    Some(error) => Err(ShellyError::Rpc {
+                        code: error.code,
+                        message: error.message,
+                    }),
+                    None => Ok(raw.result.unwrap_or(Value::Null)),
+                },
+            }),
+            (_, Some(method)) => Ok(Self::Notification {
+                method,
+                params: raw.params,
+            }),
+            (None, None) => Err(ShellyError::Protocol(
+                "message without id nor method".to_string(),
+            )),
+        }
+    }
+}
+
+/// Result of `Shelly.GetDeviceInfo`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub(crate) struct DeviceInfo {
+    /// Device identifier, e.g. `"shellyplus1pm-441793d69718"`.
+    pub id: String,
+    /// Name set by the user, if any.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub mac: String,
+    /// Hardware model, e.g. `"SNSW-001P16EU"`.
+    pub model: String,
+    /// Firmware version, e.g. `"1.4.4"`.
+    #[serde(default)]
+    pub ver: String,
+    /// Product family, e.g. `"Plus1PM"`.
+    #[serde(default)]
+    pub app: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serialize_request_without_null_params() {
+        let request = Request {
+            id: 1,
+            src: "minihub",
+            method: GET_STATUS,
+            params: &Value::Null,
+        };
+        assert_eq!(
+            request.to_text(),
+            r#"{"id":1,"src":"minihub","method":"Shelly.GetStatus"}"#
+        );
+    }
+
+    #[test]
+    fn should_parse_successful_response() {
+        let frame = Frame::parse(
+            r#"{"id":2,"src":"shellyplus1-a8032ab12345","dst":"minihub","result":{"was_on":false}}"#,
+        )
+        .unwrap();
+        let Frame::Response { id, result } = frame else {
+            panic!("expected a response");
+        };
+        assert_eq!(id, 2);
+        assert_eq!(result.unwrap()["was_on"], false);
+    }
+
+    #[test]
+    fn should_parse_error_response() {
+        let frame = Frame::parse(
+            r#"{"id":3,"src":"shellyplus1","error":{"code":-105,"message":"Argument 'id', value 4 not found!"}}"#,
+        )
+        .unwrap();
+        let Frame::Response { result, .. } = frame else {
+            panic!("expected a response");
+        };
+        assert!(matches!(result, Err(ShellyError::Rpc { code: -105, .. })));
+    }
+
+    #[test]
+    fn should_parse_notification() {
+        let frame = Frame::parse(
+            r#"{"src":"shellyplus1","dst":"minihub","method":"NotifyStatus","params":{"ts":1700000000.5,"switch:0":{"id":0,"output":true}}}"#,
+        )
+        .unwrap();
+        let Frame::Notification { method, params } = frame else {
+            panic!("expected a notification");
+        };
+        assert_eq!(method, NOTIFY_STATUS);
+        assert_eq!(params["switch:0"]["output"], true);
+    }
+
+    #[test]
+    fn should_reject_message_without_id_nor_method() {
+        assert!(matches!(
+            Frame::parse(r#"{"src":"shellyplus1"}"#),
+            Err(ShellyError::Protocol(_))
+        ));
+        assert!(matches!(
+            Frame::parse("not json"),
+            Err(ShellyError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_device_info() {
+        let info: DeviceInfo = serde_json::from_str(
+            r#"{"name":null,"id":"shellyplus1pm-441793d69718","mac":"441793D69718","slot":1,"model":"SNSW-001P16EU","gen":2,"fw_id":"20240625-122917/1.4.4-g6d2a586","ver":"1.4.4","app":"Plus1PM","auth_en":false,"auth_domain":null}"#,
+        )
+        .unwrap();
+        assert_eq!(info.id, "shellyplus1pm-441793d69718");
+        assert_eq!(info.name, None);
+        assert_eq!(info.app.as_deref(), Some("Plus1PM"));
+    }
+}
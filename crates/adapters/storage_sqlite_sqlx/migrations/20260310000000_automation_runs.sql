@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS automation_runs (
+    id              BLOB PRIMARY KEY NOT NULL,
+    automation_id   BLOB NOT NULL,
+    event_id        BLOB,
+    started_at      TEXT NOT NULL,
+    conditions      JSON NOT NULL DEFAULT '[]',
+    actions         JSON NOT NULL DEFAULT '[]',
+    FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_automation_runs_automation_started ON automation_runs(automation_id, started_at DESC);
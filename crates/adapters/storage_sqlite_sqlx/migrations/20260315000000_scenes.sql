@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS scenes (
+    id      BLOB PRIMARY KEY NOT NULL,
+    name    TEXT NOT NULL,
+    members JSON NOT NULL DEFAULT '[]'
+);
@@ -0,0 +1,2 @@
+-- Per-attribute display and validation hints (precision, min/max, display unit).
+ALTER TABLE entities ADD COLUMN attribute_meta JSON NOT NULL DEFAULT '{}';
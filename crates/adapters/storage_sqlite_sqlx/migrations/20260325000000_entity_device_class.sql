@@ -0,0 +1,3 @@
+-- Classification and unit of an entity's primary value (nullable, no default).
+ALTER TABLE entities ADD COLUMN device_class TEXT;
+ALTER TABLE entities ADD COLUMN unit_of_measurement TEXT;
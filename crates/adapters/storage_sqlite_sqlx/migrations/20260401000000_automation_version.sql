@@ -0,0 +1,2 @@
+-- Revision of each automation, bumped on every update for optimistic locking.
+ALTER TABLE automations ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,7 @@
+-- Former entity_id strings of renamed entities, so lookups by the old id
+-- still resolve.
+CREATE TABLE IF NOT EXISTS entity_aliases (
+    alias     TEXT PRIMARY KEY NOT NULL,
+    entity_id BLOB NOT NULL,
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE CASCADE
+);
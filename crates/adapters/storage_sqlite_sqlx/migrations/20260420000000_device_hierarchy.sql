@@ -0,0 +1,6 @@
+-- Parent device (bridge, coordinator) a device is reached through, and the
+-- version and placement hints integrations report.
+ALTER TABLE devices ADD COLUMN via_device_id BLOB REFERENCES devices(id) ON DELETE SET NULL;
+ALTER TABLE devices ADD COLUMN sw_version TEXT;
+ALTER TABLE devices ADD COLUMN hw_version TEXT;
+ALTER TABLE devices ADD COLUMN suggested_area TEXT;
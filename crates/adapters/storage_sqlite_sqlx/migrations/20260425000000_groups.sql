@@ -0,0 +1,9 @@
+-- Lights or switches controlled as one entity; `entity_id` is the entity
+-- exposing the group.
+CREATE TABLE IF NOT EXISTS groups (
+    id        BLOB PRIMARY KEY NOT NULL,
+    name      TEXT NOT NULL,
+    kind      TEXT NOT NULL,
+    entity_id BLOB NOT NULL,
+    members   JSON NOT NULL DEFAULT '[]'
+);
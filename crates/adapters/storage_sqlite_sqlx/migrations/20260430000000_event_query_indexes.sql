@@ -0,0 +1,7 @@
+-- Indexes backing event queries filtered by type or entity and paged by
+-- (timestamp, id), newest first.
+DROP INDEX IF EXISTS idx_events_timestamp;
+CREATE INDEX idx_events_timestamp ON events(timestamp DESC, id DESC);
+DROP INDEX IF EXISTS idx_events_entity_id;
+CREATE INDEX idx_events_entity_id ON events(entity_id, timestamp DESC, id DESC);
+CREATE INDEX idx_events_event_type ON events(event_type, timestamp DESC, id DESC);
@@ -0,0 +1,6 @@
+-- Causal chain of an event: the event that started the chain, and the one
+-- it directly reacted to. Older rows leave both empty and are treated as
+-- chains of their own.
+ALTER TABLE events ADD COLUMN correlation_id BLOB;
+ALTER TABLE events ADD COLUMN caused_by BLOB;
+CREATE INDEX idx_events_correlation_id ON events(correlation_id, timestamp DESC, id DESC);
@@ -0,0 +1,16 @@
+-- Creates, updates and deletes of the hub's resources, with the actor
+-- behind them (`api`, `dashboard`, `automation:<id>`, …) and the fields
+-- that changed.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id          BLOB PRIMARY KEY NOT NULL,
+    timestamp   TEXT NOT NULL,
+    actor       TEXT NOT NULL,
+    action      TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id   TEXT NOT NULL,
+    before      JSON,
+    after       JSON
+);
+
+CREATE INDEX idx_audit_log_timestamp ON audit_log(timestamp DESC, id DESC);
+CREATE INDEX idx_audit_log_target ON audit_log(target_type, target_id, timestamp DESC);
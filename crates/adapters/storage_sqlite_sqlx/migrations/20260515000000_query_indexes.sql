@@ -0,0 +1,14 @@
+-- Indexes backing every repository query path, so none of them scans a
+-- whole table once months of events and history have accumulated.
+-- Earlier migrations created most of them; ensure they exist on databases
+-- where they were dropped.
+CREATE INDEX IF NOT EXISTS idx_events_entity_id ON events(entity_id, timestamp DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_events_event_type ON events(event_type, timestamp DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_entities_device_id ON entities(device_id);
+CREATE INDEX IF NOT EXISTS idx_entity_history_entity_recorded ON entity_history(entity_id, recorded_at DESC);
+
+-- Events filtered by both entity and type, e.g. the state changes of one entity.
+CREATE INDEX IF NOT EXISTS idx_events_entity_type ON events(entity_id, event_type, timestamp DESC, id DESC);
+
+-- Purging history older than the retention period.
+CREATE INDEX IF NOT EXISTS idx_entity_history_recorded_at ON entity_history(recorded_at);
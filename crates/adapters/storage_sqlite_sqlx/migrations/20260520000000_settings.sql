@@ -0,0 +1,7 @@
+-- Hub-wide settings stored as JSON documents keyed by name, e.g. the
+-- dashboard layout.
+CREATE TABLE IF NOT EXISTS settings (
+    key        TEXT PRIMARY KEY NOT NULL,
+    value      JSON NOT NULL,
+    updated_at TEXT NOT NULL
+);
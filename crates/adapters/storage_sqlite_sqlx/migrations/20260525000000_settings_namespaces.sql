@@ -0,0 +1,16 @@
+-- Group the settings keys into namespaces. The dashboard layout moves from
+-- the `dashboard` key to the `layout` key of the `dashboard` namespace.
+CREATE TABLE settings_namespaced (
+    namespace  TEXT NOT NULL,
+    key        TEXT NOT NULL,
+    value      JSON NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (namespace, key)
+);
+
+INSERT INTO settings_namespaced (namespace, key, value, updated_at)
+SELECT 'dashboard', 'layout', value, updated_at FROM settings WHERE key = 'dashboard';
+
+DROP TABLE settings;
+
+ALTER TABLE settings_namespaced RENAME TO settings;
@@ -0,0 +1,3 @@
+-- How a trigger is handled while a previous run of the automation is still
+-- in progress: single, restart, queued or parallel.
+ALTER TABLE automations ADD COLUMN execution_mode TEXT NOT NULL DEFAULT 'single';
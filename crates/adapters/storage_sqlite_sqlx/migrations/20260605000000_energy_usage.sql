@@ -0,0 +1,8 @@
+-- Watt-hours consumed by metered entities, accumulated per hour.
+CREATE TABLE IF NOT EXISTS energy_usage (
+    entity_id   TEXT NOT NULL,
+    hour        TEXT NOT NULL,
+    energy_wh   REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (entity_id, hour),
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE CASCADE
+);
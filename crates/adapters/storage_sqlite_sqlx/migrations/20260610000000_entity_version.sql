@@ -0,0 +1,2 @@
+-- Revision of each entity, bumped on every update for optimistic locking.
+ALTER TABLE entities ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,4 @@
+-- Key of the YAML file an automation was imported from, NULL for
+-- automations managed through the API.
+ALTER TABLE automations ADD COLUMN source_key TEXT;
+CREATE UNIQUE INDEX idx_automations_source_key ON automations (source_key) WHERE source_key IS NOT NULL;
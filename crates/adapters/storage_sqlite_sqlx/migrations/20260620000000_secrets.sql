@@ -0,0 +1,9 @@
+-- Credentials of integrations, encrypted with ChaCha20-Poly1305. The secret
+-- name is authenticated along with the value, so a ciphertext copied under
+-- another name fails to decrypt.
+CREATE TABLE IF NOT EXISTS secrets (
+    name        TEXT PRIMARY KEY NOT NULL,
+    nonce       BLOB NOT NULL,
+    ciphertext  BLOB NOT NULL,
+    updated_at  TEXT NOT NULL
+);
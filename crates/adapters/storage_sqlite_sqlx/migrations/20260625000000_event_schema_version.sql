@@ -0,0 +1,3 @@
+-- Version of the shape of each event's data; events recorded before
+-- payloads were versioned have the shapes of version 1.
+ALTER TABLE events ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
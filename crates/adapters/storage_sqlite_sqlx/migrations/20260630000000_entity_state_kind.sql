@@ -0,0 +1,5 @@
+-- Kind of each stored state (`on`, `numeric`, `text`, ...), so a text state
+-- reading like a number is not reloaded as a number. Rows recorded before
+-- have none and are read back from their display form.
+ALTER TABLE entities ADD COLUMN state_kind TEXT;
+ALTER TABLE entity_history ADD COLUMN state_kind TEXT;
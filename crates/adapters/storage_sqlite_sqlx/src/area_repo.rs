@@ -1,7 +1,7 @@
 //! `SQLite` implementation of [`AreaRepository`].
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, Row};
 
 use minihub_app::ports::AreaRepository;
 use minihub_domain::area::Area;
@@ -9,6 +9,7 @@ use minihub_domain::error::MiniHubError;
 use minihub_domain::id::AreaId;
 
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 /// Wrapper for converting database rows into domain [`Area`].
 struct Wrapper(Area);
@@ -44,14 +45,17 @@ const DELETE_BY_ID: &str = "DELETE FROM areas WHERE id = ?";
 
 /// `SQLite`-backed area repository.
 pub struct SqliteAreaRepository {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteAreaRepository {
-    /// Create a new repository using the given connection pool.
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
     }
 }
 
@@ -61,7 +65,7 @@ impl AreaRepository for SqliteAreaRepository {
             .bind(area.id.as_uuid())
             .bind(&area.name)
             .bind(area.parent_id.map(AreaId::as_uuid))
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -71,7 +75,7 @@ impl AreaRepository for SqliteAreaRepository {
     async fn get_by_id(&self, id: AreaId) -> Result<Option<Area>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
             .bind(id.as_uuid())
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -80,7 +84,7 @@ impl AreaRepository for SqliteAreaRepository {
 
     async fn get_all(&self) -> Result<Vec<Area>, MiniHubError> {
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -92,7 +96,7 @@ impl AreaRepository for SqliteAreaRepository {
             .bind(&area.name)
             .bind(area.parent_id.map(AreaId::as_uuid))
             .bind(area.id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -102,7 +106,7 @@ impl AreaRepository for SqliteAreaRepository {
     async fn delete(&self, id: AreaId) -> Result<(), MiniHubError> {
         sqlx::query(DELETE_BY_ID)
             .bind(id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -116,12 +120,7 @@ mod tests {
     use crate::pool::Config;
 
     async fn setup() -> SqliteAreaRepository {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         SqliteAreaRepository::new(db.pool().clone())
     }
 
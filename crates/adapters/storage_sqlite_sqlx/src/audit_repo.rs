@@ -0,0 +1,252 @@
+//! `SQLite` implementation of [`AuditRepository`].
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
+
+use minihub_app::ports::{AuditQuery, AuditRepository};
+use minihub_domain::audit::{AuditAction, AuditEntry};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::AuditEntryId;
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+struct Wrapper(AuditEntry);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: uuid::Uuid = row.try_get("id")?;
+        let timestamp_str: String = row.try_get("timestamp")?;
+        let actor_str: String = row.try_get("actor")?;
+        let action_str: String = row.try_get("action")?;
+        let before_json: Option<String> = row.try_get("before")?;
+        let after_json: Option<String> = row.try_get("after")?;
+
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?
+            .to_utc();
+        let actor = actor_str
+            .parse()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let action = AuditAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == action_str)
+            .ok_or_else(|| {
+                sqlx::Error::Decode(format!("unknown audit action: {action_str}").into())
+            })?;
+        let parse = |json: Option<String>| {
+            json.map(|json| serde_json::from_str(&json))
+                .transpose()
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))
+        };
+
+        Ok(Self(AuditEntry {
+            id: AuditEntryId::from_uuid(id),
+            timestamp,
+            actor,
+            action,
+            target_type: row.try_get("target_type")?,
+            target_id: row.try_get("target_id")?,
+            before: parse(before_json)?,
+            after: parse(after_json)?,
+        }))
+    }
+}
+
+const INSERT: &str = r"
+    INSERT INTO audit_log (id, timestamp, actor, action, target_type, target_id, before, after)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+";
+
+/// `SQLite`-backed audit log.
+pub struct SqliteAuditRepository {
+    pools: Pools,
+}
+
+impl SqliteAuditRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl AuditRepository for SqliteAuditRepository {
+    async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, MiniHubError> {
+        let to_json = |value: &Option<serde_json::Value>| {
+            value
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(StorageError::from)
+        };
+        let before_json = to_json(&entry.before)?;
+        let after_json = to_json(&entry.after)?;
+
+        sqlx::query(INSERT)
+            .bind(entry.id.as_uuid())
+            .bind(entry.timestamp.to_rfc3339())
+            .bind(entry.actor.to_string())
+            .bind(entry.action.as_str())
+            .bind(&entry.target_type)
+            .bind(&entry.target_id)
+            .bind(before_json)
+            .bind(after_json)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(entry)
+    }
+
+    async fn query(&self, query: AuditQuery) -> Result<Vec<AuditEntry>, MiniHubError> {
+        let mut builder: QueryBuilder<'_, Sqlite> =
+            QueryBuilder::new("SELECT * FROM audit_log WHERE 1 = 1");
+        if let Some(actor) = query.actor {
+            // Either a full actor, or its kind: the part before the colon.
+            builder
+                .push(" AND (actor = ")
+                .push_bind(actor.clone())
+                .push(" OR substr(actor, 1, instr(actor, ':') - 1) = ")
+                .push_bind(actor)
+                .push(")");
+        }
+        if let Some(target_type) = query.target_type {
+            builder.push(" AND target_type = ").push_bind(target_type);
+        }
+        if let Some(target_id) = query.target_id {
+            builder.push(" AND target_id = ").push_bind(target_id);
+        }
+        if let Some((timestamp, id)) = query.before {
+            let timestamp = timestamp.to_rfc3339();
+            builder
+                .push(" AND (timestamp < ")
+                .push_bind(timestamp.clone())
+                .push(" OR (timestamp = ")
+                .push_bind(timestamp)
+                .push(" AND id < ")
+                .push_bind(id.as_uuid())
+                .push("))");
+        }
+        builder
+            .push(" ORDER BY timestamp DESC, id DESC LIMIT ")
+            .push_bind(i32::try_from(query.limit).unwrap_or(i32::MAX));
+
+        let rows: Vec<Wrapper> = builder
+            .build_query_as()
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use minihub_domain::audit::Actor;
+    use minihub_domain::id::AutomationId;
+    use serde_json::json;
+
+    use super::*;
+    use crate::pool::Config;
+
+    async fn setup() -> SqliteAuditRepository {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        SqliteAuditRepository::new(db.pool().clone())
+    }
+
+    #[tokio::test]
+    async fn should_record_and_query_entry_when_valid() {
+        let repo = setup().await;
+        let entry = AuditEntry::updated(
+            Actor::Integration("mqtt".to_string()),
+            "device",
+            "42",
+            &json!({ "name": "Plug" }),
+            &json!({ "name": "Desk plug" }),
+        )
+        .unwrap();
+
+        repo.record(entry.clone()).await.unwrap();
+
+        let entries = repo.query(AuditQuery::new(10)).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].actor, entry.actor);
+        assert_eq!(entries[0].action, AuditAction::Update);
+        assert_eq!(entries[0].before, Some(json!({ "name": "Plug" })));
+        assert_eq!(entries[0].after, Some(json!({ "name": "Desk plug" })));
+    }
+
+    #[tokio::test]
+    async fn should_filter_entries_by_actor_kind_and_target() {
+        let repo = setup().await;
+        let automation = Actor::Automation(AutomationId::new());
+        repo.record(AuditEntry::created(
+            automation.clone(),
+            "scene",
+            "1",
+            json!({}),
+        ))
+        .await
+        .unwrap();
+        repo.record(AuditEntry::created(Actor::Api, "scene", "2", json!({})))
+            .await
+            .unwrap();
+        repo.record(AuditEntry::deleted(Actor::Api, "area", "3", None))
+            .await
+            .unwrap();
+
+        let by_kind = repo
+            .query(AuditQuery::new(10).with_actor("automation"))
+            .await
+            .unwrap();
+        let by_actor = repo
+            .query(AuditQuery::new(10).with_actor(automation.to_string()))
+            .await
+            .unwrap();
+        let by_target = repo
+            .query(
+                AuditQuery::new(10)
+                    .with_actor("api")
+                    .with_target_type("scene"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(by_kind.len(), 1);
+        assert_eq!(by_actor.len(), 1);
+        assert_eq!(by_target.len(), 1);
+        assert_eq!(by_target[0].target_id, "2");
+    }
+
+    #[tokio::test]
+    async fn should_page_entries_newest_first() {
+        let repo = setup().await;
+        let base = minihub_domain::time::now();
+        for offset in 0..3 {
+            let mut entry = AuditEntry::deleted(Actor::Api, "area", offset.to_string(), None);
+            entry.timestamp = base + Duration::seconds(offset);
+            repo.record(entry).await.unwrap();
+        }
+
+        let first = repo.query(AuditQuery::new(2)).await.unwrap();
+        let last = &first[1];
+        let next = repo
+            .query(AuditQuery::new(2).with_before(last.timestamp, last.id))
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = first
+            .iter()
+            .chain(&next)
+            .map(|e| e.target_id.as_str())
+            .collect();
+        assert_eq!(ids, ["2", "1", "0"]);
+    }
+}
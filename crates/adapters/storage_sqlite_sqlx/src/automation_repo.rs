@@ -1,14 +1,16 @@
 //! `SQLite` implementation of [`AutomationRepository`].
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, Row};
 
 use minihub_app::ports::AutomationRepository;
-use minihub_domain::automation::{Action, Automation, Condition, Trigger};
-use minihub_domain::error::MiniHubError;
+use minihub_domain::automation::{Action, Automation, Condition, ExecutionMode, Trigger};
+use minihub_domain::error::{ConflictError, MiniHubError, NotFoundError};
 use minihub_domain::id::AutomationId;
+use minihub_domain::time::Timestamp;
 
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 struct Wrapper(Automation);
 
@@ -26,7 +28,10 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
         let trigger_json: String = row.try_get("trigger_data")?;
         let conditions_json: String = row.try_get("conditions")?;
         let actions_json: String = row.try_get("actions")?;
+        let execution_mode: String = row.try_get("execution_mode")?;
         let last_triggered_str: Option<String> = row.try_get("last_triggered")?;
+        let version: u32 = row.try_get("version")?;
+        let source_key: Option<String> = row.try_get("source_key")?;
 
         let id = AutomationId::from_uuid(id);
         let trigger: Trigger = serde_json::from_str(&trigger_json)
@@ -35,6 +40,9 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
         let actions: Vec<Action> = serde_json::from_str(&actions_json)
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let execution_mode: ExecutionMode = execution_mode
+            .parse()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
         let last_triggered = last_triggered_str
             .map(|s| {
                 chrono::DateTime::parse_from_rfc3339(&s)
@@ -50,21 +58,27 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
             trigger,
             conditions,
             actions,
+            execution_mode,
             last_triggered,
+            version,
+            source_key,
         }))
     }
 }
 
 /// `SQLite`-backed automation repository.
 pub struct SqliteAutomationRepository {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteAutomationRepository {
-    /// Create a new repository backed by the given connection pool.
+    /// Create a new repository backed by the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
     }
 }
 
@@ -80,7 +94,7 @@ impl AutomationRepository for SqliteAutomationRepository {
         let last_triggered = automation.last_triggered.map(|ts| ts.to_rfc3339());
 
         sqlx::query(
-                "INSERT INTO automations (id, name, enabled, trigger_data, conditions, actions, last_triggered) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                "INSERT INTO automations (id, name, enabled, trigger_data, conditions, actions, execution_mode, last_triggered, version, source_key) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             )
             .bind(id)
             .bind(&automation.name)
@@ -88,8 +102,11 @@ impl AutomationRepository for SqliteAutomationRepository {
             .bind(&trigger_json)
             .bind(&conditions_json)
             .bind(&actions_json)
+            .bind(automation.execution_mode.as_str())
             .bind(&last_triggered)
-            .execute(&self.pool)
+            .bind(automation.version)
+            .bind(&automation.source_key)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -99,7 +116,7 @@ impl AutomationRepository for SqliteAutomationRepository {
     async fn get_by_id(&self, id: AutomationId) -> Result<Option<Automation>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as("SELECT * FROM automations WHERE id = ?")
             .bind(id.as_uuid())
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
         Ok(Wrapper::maybe(row))
@@ -107,7 +124,7 @@ impl AutomationRepository for SqliteAutomationRepository {
 
     async fn get_all(&self) -> Result<Vec<Automation>, MiniHubError> {
         let rows: Vec<Wrapper> = sqlx::query_as("SELECT * FROM automations ORDER BY name")
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
         Ok(rows.into_iter().map(|w| w.0).collect())
@@ -116,7 +133,7 @@ impl AutomationRepository for SqliteAutomationRepository {
     async fn get_enabled(&self) -> Result<Vec<Automation>, MiniHubError> {
         let rows: Vec<Wrapper> =
             sqlx::query_as("SELECT * FROM automations WHERE enabled = 1 ORDER BY name")
-                .fetch_all(&self.pool)
+                .fetch_all(self.pools.reader())
                 .await
                 .map_err(StorageError::from)?;
         Ok(rows.into_iter().map(|w| w.0).collect())
@@ -130,29 +147,65 @@ impl AutomationRepository for SqliteAutomationRepository {
             serde_json::to_string(&automation.conditions).map_err(StorageError::from)?;
         let actions_json =
             serde_json::to_string(&automation.actions).map_err(StorageError::from)?;
-        let last_triggered = automation.last_triggered.map(|ts| ts.to_rfc3339());
 
-        sqlx::query(
-                "UPDATE automations SET name = ?, enabled = ?, trigger_data = ?, conditions = ?, actions = ?, last_triggered = ? WHERE id = ?",
+        let row: Option<Wrapper> = sqlx::query_as(
+                "UPDATE automations SET name = ?, enabled = ?, trigger_data = ?, conditions = ?, actions = ?, execution_mode = ?, version = version + 1 WHERE id = ? AND version = ? RETURNING *",
             )
             .bind(&automation.name)
             .bind(automation.enabled)
             .bind(&trigger_json)
             .bind(&conditions_json)
             .bind(&actions_json)
-            .bind(&last_triggered)
+            .bind(automation.execution_mode.as_str())
             .bind(id)
-            .execute(&self.pool)
+            .bind(automation.version)
+            .fetch_optional(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
+        if let Some(Wrapper(updated)) = row {
+            return Ok(updated);
+        }
 
-        Ok(automation)
+        let actual: Option<u32> =
+            sqlx::query_scalar("SELECT version FROM automations WHERE id = ?")
+                .bind(id)
+                .fetch_optional(self.pools.reader())
+                .await
+                .map_err(StorageError::from)?;
+        Err(match actual {
+            Some(actual) => ConflictError {
+                entity: "Automation",
+                id: automation.id.to_string(),
+                expected: format!("version {}", automation.version),
+                actual: format!("version {actual}"),
+            }
+            .into(),
+            None => NotFoundError {
+                entity: "Automation",
+                id: automation.id.to_string(),
+            }
+            .into(),
+        })
+    }
+
+    async fn record_triggered(
+        &self,
+        id: AutomationId,
+        triggered_at: Timestamp,
+    ) -> Result<(), MiniHubError> {
+        sqlx::query("UPDATE automations SET last_triggered = ? WHERE id = ?")
+            .bind(triggered_at.to_rfc3339())
+            .bind(id.as_uuid())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(())
     }
 
     async fn delete(&self, id: AutomationId) -> Result<(), MiniHubError> {
         sqlx::query("DELETE FROM automations WHERE id = ?")
             .bind(id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
         Ok(())
@@ -167,12 +220,7 @@ mod tests {
     use minihub_domain::id::EntityId;
 
     async fn setup() -> SqliteAutomationRepository {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         SqliteAutomationRepository::new(db.pool().clone())
     }
 
@@ -200,6 +248,7 @@ mod tests {
         assert_eq!(fetched.id, id);
         assert_eq!(fetched.name, "Test rule");
         assert!(fetched.enabled);
+        assert_eq!(fetched.execution_mode, ExecutionMode::Single);
     }
 
     #[tokio::test]
@@ -246,11 +295,78 @@ mod tests {
         let mut fetched = repo.get_by_id(id).await.unwrap().unwrap();
         fetched.name = "Updated name".to_string();
         fetched.enabled = false;
+        fetched.execution_mode = ExecutionMode::Queued;
         repo.update(fetched).await.unwrap();
 
         let updated = repo.get_by_id(id).await.unwrap().unwrap();
         assert_eq!(updated.name, "Updated name");
         assert!(!updated.enabled);
+        assert_eq!(updated.execution_mode, ExecutionMode::Queued);
+        assert_eq!(updated.version, 2);
+    }
+
+    #[tokio::test]
+    async fn should_keep_source_key_through_update() {
+        let repo = setup().await;
+        let mut auto = valid_automation();
+        auto.source_key = Some("evening_lights".to_string());
+        let id = auto.id;
+        repo.create(auto).await.unwrap();
+
+        let mut fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(fetched.source_key.as_deref(), Some("evening_lights"));
+        fetched.name = "Evening lights".to_string();
+        let updated = repo.update(fetched).await.unwrap();
+        assert_eq!(updated.source_key.as_deref(), Some("evening_lights"));
+    }
+
+    #[tokio::test]
+    async fn should_reject_update_when_version_is_stale() {
+        let repo = setup().await;
+        let auto = valid_automation();
+        let id = auto.id;
+        repo.create(auto).await.unwrap();
+
+        let stale = repo.get_by_id(id).await.unwrap().unwrap();
+        let mut first = stale.clone();
+        first.name = "First edit".to_string();
+        repo.update(first).await.unwrap();
+
+        let mut second = stale;
+        second.name = "Second edit".to_string();
+        let err = repo.update(second).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Conflict(ConflictError { ref actual, .. }) if actual == "version 2"
+        ));
+        let stored = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(stored.name, "First edit");
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_updating_missing_automation() {
+        let repo = setup().await;
+        let err = repo.update(valid_automation()).await.unwrap_err();
+        assert!(matches!(err, MiniHubError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn should_record_triggered_without_bumping_version() {
+        let repo = setup().await;
+        let auto = valid_automation();
+        let id = auto.id;
+        repo.create(auto).await.unwrap();
+        let ts = minihub_domain::time::now();
+
+        repo.record_triggered(id, ts).await.unwrap();
+
+        let stored = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(
+            stored.last_triggered.map(|t| t.timestamp()),
+            Some(ts.timestamp())
+        );
+        assert_eq!(stored.version, 1);
     }
 
     #[tokio::test]
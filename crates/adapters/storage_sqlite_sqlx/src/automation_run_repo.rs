@@ -0,0 +1,192 @@
+//! `SQLite` implementation of [`AutomationRunRepository`].
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::AutomationRunRepository;
+use minihub_domain::automation_run::{ActionResult, AutomationRun, ConditionResult};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::{AutomationId, AutomationRunId, EventId};
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+struct Wrapper(AutomationRun);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: uuid::Uuid = row.try_get("id")?;
+        let automation_id: uuid::Uuid = row.try_get("automation_id")?;
+        let event_id: Option<uuid::Uuid> = row.try_get("event_id")?;
+        let started_at_str: String = row.try_get("started_at")?;
+        let conditions_json: String = row.try_get("conditions")?;
+        let actions_json: String = row.try_get("actions")?;
+
+        let started_at = chrono::DateTime::parse_from_rfc3339(&started_at_str)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?
+            .to_utc();
+        let conditions: Vec<ConditionResult> = serde_json::from_str(&conditions_json)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let actions: Vec<ActionResult> = serde_json::from_str(&actions_json)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+
+        Ok(Self(AutomationRun {
+            id: AutomationRunId::from_uuid(id),
+            automation_id: AutomationId::from_uuid(automation_id),
+            event_id: event_id.map(EventId::from_uuid),
+            started_at,
+            conditions,
+            actions,
+        }))
+    }
+}
+
+const INSERT: &str = r"
+    INSERT INTO automation_runs (id, automation_id, event_id, started_at, conditions, actions)
+    VALUES (?, ?, ?, ?, ?, ?)
+";
+
+const SELECT_BY_AUTOMATION: &str = r"
+    SELECT * FROM automation_runs
+    WHERE automation_id = ?
+    ORDER BY started_at DESC
+    LIMIT ?
+";
+
+/// `SQLite`-backed automation run repository.
+pub struct SqliteAutomationRunRepository {
+    pools: Pools,
+}
+
+impl SqliteAutomationRunRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl AutomationRunRepository for SqliteAutomationRunRepository {
+    async fn record(&self, run: AutomationRun) -> Result<AutomationRun, MiniHubError> {
+        let conditions_json = serde_json::to_string(&run.conditions).map_err(StorageError::from)?;
+        let actions_json = serde_json::to_string(&run.actions).map_err(StorageError::from)?;
+
+        sqlx::query(INSERT)
+            .bind(run.id.as_uuid())
+            .bind(run.automation_id.as_uuid())
+            .bind(run.event_id.map(EventId::as_uuid))
+            .bind(run.started_at.to_rfc3339())
+            .bind(&conditions_json)
+            .bind(&actions_json)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(run)
+    }
+
+    async fn find_by_automation(
+        &self,
+        automation_id: AutomationId,
+        limit: usize,
+    ) -> Result<Vec<AutomationRun>, MiniHubError> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_AUTOMATION)
+            .bind(automation_id.as_uuid())
+            .bind(limit)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation_repo::SqliteAutomationRepository;
+    use crate::pool::Config;
+    use chrono::Duration;
+    use minihub_app::ports::AutomationRepository;
+    use minihub_domain::automation::{Action, Automation, Condition};
+    use minihub_domain::automation_run::ActionOutcome;
+    use minihub_domain::id::EntityId;
+    use minihub_domain::time::now;
+
+    async fn setup() -> (SqliteAutomationRunRepository, AutomationId) {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        let pool = db.pool().clone();
+
+        let automation = Automation::builder()
+            .name("Test automation")
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap();
+        let automation_id = automation.id;
+        SqliteAutomationRepository::new(pool.clone())
+            .create(automation)
+            .await
+            .unwrap();
+
+        (SqliteAutomationRunRepository::new(pool), automation_id)
+    }
+
+    #[tokio::test]
+    async fn should_record_and_retrieve_run_when_valid() {
+        let (repo, automation_id) = setup().await;
+        let event_id = EventId::new();
+        let run = AutomationRun::builder()
+            .automation_id(automation_id)
+            .event_id(event_id)
+            .condition(
+                Condition::StateIs {
+                    entity_id: EntityId::new(),
+                    state: "on".to_string(),
+                },
+                true,
+            )
+            .action(Action::Delay { seconds: 1 }, ActionOutcome::Succeeded)
+            .build();
+        let run_id = run.id;
+
+        repo.record(run).await.unwrap();
+
+        let runs = repo.find_by_automation(automation_id, 10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, run_id);
+        assert_eq!(runs[0].event_id, Some(event_id));
+        assert!(runs[0].conditions[0].passed);
+        assert_eq!(runs[0].actions[0].outcome, ActionOutcome::Succeeded);
+    }
+
+    #[tokio::test]
+    async fn should_return_runs_newest_first_when_limited() {
+        let (repo, automation_id) = setup().await;
+        let base = now();
+        for offset in 0..3 {
+            let run = AutomationRun::builder()
+                .automation_id(automation_id)
+                .started_at(base + Duration::seconds(offset))
+                .build();
+            repo.record(run).await.unwrap();
+        }
+
+        let runs = repo.find_by_automation(automation_id, 2).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].started_at > runs[1].started_at);
+    }
+
+    #[tokio::test]
+    async fn should_return_empty_when_automation_has_no_runs() {
+        let (repo, _) = setup().await;
+        let runs = repo
+            .find_by_automation(AutomationId::new(), 10)
+            .await
+            .unwrap();
+        assert!(runs.is_empty());
+    }
+}
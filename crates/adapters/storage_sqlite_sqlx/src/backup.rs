@@ -0,0 +1,170 @@
+//! Timestamped database snapshots with rotation.
+//!
+//! Each snapshot is a full copy written with [`Database::backup_to`] into
+//! the backup directory, named `minihub-<UTC timestamp>.db` so that names
+//! sort chronologically. Once written, the oldest snapshots beyond the
+//! retention count are removed. Other files in the directory are left
+//! untouched.
+
+use std::path::{Path, PathBuf};
+
+use minihub_domain::time::{Timestamp, now};
+
+use crate::error::StorageError;
+use crate::pool::Database;
+
+/// Prefix of the snapshot file names.
+const FILE_PREFIX: &str = "minihub-";
+
+/// Extension of the snapshot file names.
+const FILE_SUFFIX: &str = ".db";
+
+/// A snapshot written by [`BackupRotation::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupFile {
+    /// Location of the snapshot.
+    pub path: PathBuf,
+    /// Size of the snapshot, in bytes.
+    pub size_bytes: u64,
+    /// When the snapshot was taken.
+    pub created_at: Timestamp,
+    /// Older snapshots removed to stay within the retention count.
+    pub pruned: Vec<PathBuf>,
+}
+
+/// Writes snapshots into a directory, keeping only the most recent ones.
+#[derive(Debug, Clone)]
+pub struct BackupRotation {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl BackupRotation {
+    /// Snapshots written to `dir`, keeping the `keep` most recent ones.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep,
+        }
+    }
+
+    /// Directory the snapshots are written to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a snapshot of `db`, creating the directory if needed, then
+    /// remove the snapshots beyond the retention count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] if the snapshot cannot be written or an
+    /// old one cannot be removed.
+    pub async fn run(&self, db: &Database) -> Result<BackupFile, StorageError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let created_at = now();
+        let path = self.dir.join(format!(
+            "{FILE_PREFIX}{}{FILE_SUFFIX}",
+            created_at.format("%Y%m%dT%H%M%S%3fZ")
+        ));
+        db.backup_to(&path).await?;
+        let size_bytes = std::fs::metadata(&path)?.len();
+        let pruned = self.prune()?;
+        Ok(BackupFile {
+            path,
+            size_bytes,
+            created_at,
+            pruned,
+        })
+    }
+
+    /// Remove the oldest snapshots beyond the retention count, returning
+    /// their paths.
+    fn prune(&self) -> Result<Vec<PathBuf>, StorageError> {
+        let mut snapshots = self.snapshots()?;
+        let excess = snapshots.len().saturating_sub(self.keep);
+        snapshots.truncate(excess);
+        for path in &snapshots {
+            std::fs::remove_file(path)?;
+        }
+        Ok(snapshots)
+    }
+
+    /// Snapshots in the directory, oldest first.
+    fn snapshots(&self) -> Result<Vec<PathBuf>, StorageError> {
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let is_snapshot = name
+                .to_str()
+                .is_some_and(|name| name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX));
+            if is_snapshot && entry.file_type()?.is_file() {
+                snapshots.push(entry.path());
+            }
+        }
+        snapshots.sort();
+        Ok(snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("minihub_backup_{}_{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// File-backed database next to `dir`, as `VACUUM INTO` from an
+    /// in-memory database writes no file.
+    async fn database(dir: &Path) -> Database {
+        let path = dir.with_extension("db");
+        let _ = std::fs::remove_file(&path);
+        Config::new(format!("sqlite:{}?mode=rwc", path.display()))
+            .build()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_write_snapshot_into_created_directory() {
+        let dir = temp_dir("write");
+        let db = database(&dir).await;
+
+        let file = BackupRotation::new(&dir, 3).run(&db).await.unwrap();
+
+        assert!(file.path.starts_with(&dir));
+        assert!(file.size_bytes > 0);
+        assert!(file.pruned.is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn should_remove_oldest_snapshots_beyond_retention() {
+        let dir = temp_dir("prune");
+        std::fs::create_dir_all(&dir).unwrap();
+        let oldest = dir.join("minihub-20240101T000000000Z.db");
+        let older = dir.join("minihub-20240102T000000000Z.db");
+        let unrelated = dir.join("notes.txt");
+        for path in [&oldest, &older, &unrelated] {
+            std::fs::write(path, b"").unwrap();
+        }
+        let db = database(&dir).await;
+
+        let file = BackupRotation::new(&dir, 2).run(&db).await.unwrap();
+
+        assert_eq!(file.pruned, vec![oldest.clone()]);
+        assert!(!oldest.exists());
+        assert!(older.exists());
+        assert!(unrelated.exists());
+        assert!(file.path.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
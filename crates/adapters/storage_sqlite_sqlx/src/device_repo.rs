@@ -1,7 +1,7 @@
 //! `SQLite` implementation of [`DeviceRepository`].
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, Row};
 
 use minihub_app::ports::DeviceRepository;
 use minihub_domain::device::Device;
@@ -9,6 +9,7 @@ use minihub_domain::error::MiniHubError;
 use minihub_domain::id::{AreaId, DeviceId};
 
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 /// Wrapper for converting database rows into domain [`Device`].
 struct Wrapper(Device);
@@ -33,6 +34,12 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
         let integration: String = row.try_get("integration")?;
         let unique_id: String = row.try_get("unique_id")?;
 
+        let via_device_id: Option<uuid::Uuid> = row.try_get("via_device_id")?;
+        let via_device_id = via_device_id.map(DeviceId::from_uuid);
+        let sw_version: Option<String> = row.try_get("sw_version")?;
+        let hw_version: Option<String> = row.try_get("hw_version")?;
+        let suggested_area: Option<String> = row.try_get("suggested_area")?;
+
         Ok(Self(Device {
             id,
             name,
@@ -41,28 +48,45 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
             area_id,
             integration,
             unique_id,
+            via_device_id,
+            sw_version,
+            hw_version,
+            suggested_area,
         }))
     }
 }
 
-const INSERT: &str = "INSERT INTO devices (id, name, manufacturer, model, area_id, integration, unique_id) VALUES (?, ?, ?, ?, ?, ?, ?)";
+const INSERT: &str = r"
+    INSERT INTO devices (id, name, manufacturer, model, area_id, integration, unique_id, via_device_id, sw_version, hw_version, suggested_area)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+";
 const SELECT_BY_ID: &str = "SELECT * FROM devices WHERE id = ?";
 const SELECT_ALL: &str = "SELECT * FROM devices";
+const SELECT_BY_AREA: &str = "SELECT * FROM devices WHERE area_id = ? ORDER BY name";
+const SELECT_BY_INTEGRATION: &str = "SELECT * FROM devices WHERE integration = ? ORDER BY name";
 const SELECT_BY_INTEGRATION_UNIQUE_ID: &str =
     "SELECT * FROM devices WHERE integration = ? AND unique_id = ?";
-const UPDATE: &str = "UPDATE devices SET name = ?, manufacturer = ?, model = ?, area_id = ?, integration = ?, unique_id = ? WHERE id = ?";
+const UPDATE: &str = r"
+    UPDATE devices
+    SET name = ?, manufacturer = ?, model = ?, area_id = ?, integration = ?, unique_id = ?,
+        via_device_id = ?, sw_version = ?, hw_version = ?, suggested_area = ?
+    WHERE id = ?
+";
 const DELETE_BY_ID: &str = "DELETE FROM devices WHERE id = ?";
 
 /// `SQLite`-backed device repository.
 pub struct SqliteDeviceRepository {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteDeviceRepository {
-    /// Create a new repository using the given connection pool.
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
     }
 }
 
@@ -76,7 +100,11 @@ impl DeviceRepository for SqliteDeviceRepository {
             .bind(device.area_id.map(AreaId::as_uuid))
             .bind(&device.integration)
             .bind(&device.unique_id)
-            .execute(&self.pool)
+            .bind(device.via_device_id.map(DeviceId::as_uuid))
+            .bind(&device.sw_version)
+            .bind(&device.hw_version)
+            .bind(&device.suggested_area)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -86,7 +114,7 @@ impl DeviceRepository for SqliteDeviceRepository {
     async fn get_by_id(&self, id: DeviceId) -> Result<Option<Device>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
             .bind(id.as_uuid())
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -95,7 +123,27 @@ impl DeviceRepository for SqliteDeviceRepository {
 
     async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn find_by_area(&self, area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_AREA)
+            .bind(area_id.as_uuid())
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn find_by_integration(&self, integration: &str) -> Result<Vec<Device>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_INTEGRATION)
+            .bind(integration)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -110,7 +158,7 @@ impl DeviceRepository for SqliteDeviceRepository {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_INTEGRATION_UNIQUE_ID)
             .bind(integration)
             .bind(unique_id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -125,8 +173,12 @@ impl DeviceRepository for SqliteDeviceRepository {
             .bind(device.area_id.map(AreaId::as_uuid))
             .bind(&device.integration)
             .bind(&device.unique_id)
+            .bind(device.via_device_id.map(DeviceId::as_uuid))
+            .bind(&device.sw_version)
+            .bind(&device.hw_version)
+            .bind(&device.suggested_area)
             .bind(device.id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -136,7 +188,7 @@ impl DeviceRepository for SqliteDeviceRepository {
     async fn delete(&self, id: DeviceId) -> Result<(), MiniHubError> {
         sqlx::query(DELETE_BY_ID)
             .bind(id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -150,12 +202,7 @@ mod tests {
     use crate::pool::Config;
 
     async fn setup() -> SqliteDeviceRepository {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         SqliteDeviceRepository::new(db.pool().clone())
     }
 
@@ -223,6 +270,55 @@ mod tests {
         assert_eq!(fetched.manufacturer.as_deref(), Some("Philips"));
     }
 
+    #[tokio::test]
+    async fn should_preserve_hierarchy_and_versions_through_roundtrip() {
+        let repo = setup().await;
+        let bridge = test_device();
+        let bridge_id = bridge.id;
+        repo.create(bridge).await.unwrap();
+        let child = Device::builder()
+            .name("Motion Sensor")
+            .integration("test")
+            .unique_id("motion_sensor_1")
+            .via_device_id(bridge_id)
+            .sw_version("1.2.3")
+            .hw_version("rev2")
+            .suggested_area("Hallway")
+            .build()
+            .unwrap();
+        let id = child.id;
+
+        repo.create(child).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(fetched.via_device_id, Some(bridge_id));
+        assert_eq!(fetched.sw_version.as_deref(), Some("1.2.3"));
+        assert_eq!(fetched.hw_version.as_deref(), Some("rev2"));
+        assert_eq!(fetched.suggested_area.as_deref(), Some("Hallway"));
+    }
+
+    #[tokio::test]
+    async fn should_clear_via_device_when_parent_deleted() {
+        let repo = setup().await;
+        let bridge = test_device();
+        let bridge_id = bridge.id;
+        repo.create(bridge).await.unwrap();
+        let child = Device::builder()
+            .name("Motion Sensor")
+            .integration("test")
+            .unique_id("motion_sensor_1")
+            .via_device_id(bridge_id)
+            .build()
+            .unwrap();
+        let id = child.id;
+        repo.create(child).await.unwrap();
+
+        repo.delete(bridge_id).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert!(fetched.via_device_id.is_none());
+    }
+
     #[tokio::test]
     async fn should_delete_device_when_exists() {
         let repo = setup().await;
@@ -276,6 +372,57 @@ mod tests {
         assert_eq!(found.unwrap().id, id);
     }
 
+    #[tokio::test]
+    async fn should_find_devices_by_integration() {
+        let repo = setup().await;
+        for (name, integration) in [("Sensor", "ble"), ("Bulb", "mqtt"), ("Plug", "ble")] {
+            let device = Device::builder()
+                .name(name)
+                .integration(integration)
+                .unique_id(name.to_lowercase())
+                .build()
+                .unwrap();
+            repo.create(device).await.unwrap();
+        }
+
+        let found = repo.find_by_integration("ble").await.unwrap();
+
+        let names: Vec<_> = found.iter().map(|device| device.name.as_str()).collect();
+        assert_eq!(names, ["Plug", "Sensor"]);
+    }
+
+    #[tokio::test]
+    async fn should_find_devices_by_area() {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        let area_id = AreaId::new();
+        sqlx::query("INSERT INTO areas (id, name) VALUES (?, ?)")
+            .bind(area_id.as_uuid())
+            .bind("Kitchen")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let repo = SqliteDeviceRepository::new(db.pool().clone());
+        for (name, area) in [
+            ("Toaster", Some(area_id)),
+            ("Lamp", None),
+            ("Kettle", Some(area_id)),
+        ] {
+            let mut device = Device::builder()
+                .name(name)
+                .integration("test")
+                .unique_id(name.to_lowercase())
+                .build()
+                .unwrap();
+            device.area_id = area;
+            repo.create(device).await.unwrap();
+        }
+
+        let found = repo.find_by_area(area_id).await.unwrap();
+
+        let names: Vec<_> = found.iter().map(|device| device.name.as_str()).collect();
+        assert_eq!(names, ["Kettle", "Toaster"]);
+    }
+
     #[tokio::test]
     async fn should_return_none_when_integration_unique_id_not_found() {
         let repo = setup().await;
@@ -0,0 +1,207 @@
+//! `SQLite` implementation of [`DiscoveryRepository`].
+
+use minihub_app::ports::{DiscoveredDevice, DiscoveryRepository};
+use minihub_domain::entity::DeviceClass;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::{AreaId, DeviceId};
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+const UPSERT_DEVICE: &str = r"
+    INSERT INTO devices (id, name, manufacturer, model, area_id, integration, unique_id, via_device_id, sw_version, hw_version, suggested_area)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT(id) DO UPDATE SET
+        name = excluded.name, manufacturer = excluded.manufacturer, model = excluded.model,
+        area_id = excluded.area_id, integration = excluded.integration, unique_id = excluded.unique_id,
+        via_device_id = excluded.via_device_id, sw_version = excluded.sw_version,
+        hw_version = excluded.hw_version, suggested_area = excluded.suggested_area
+";
+
+const UPSERT_ENTITY: &str = r"
+    INSERT INTO entities (id, device_id, entity_id, friendly_name, state, state_kind, device_class, unit_of_measurement, attributes, attribute_meta, mac_address, last_changed, last_updated)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT(id) DO UPDATE SET
+        device_id = excluded.device_id, entity_id = excluded.entity_id,
+        friendly_name = excluded.friendly_name, state = excluded.state, state_kind = excluded.state_kind,
+        device_class = excluded.device_class, unit_of_measurement = excluded.unit_of_measurement,
+        attributes = excluded.attributes, attribute_meta = excluded.attribute_meta,
+        mac_address = excluded.mac_address, last_changed = excluded.last_changed,
+        last_updated = excluded.last_updated, version = entities.version + 1
+";
+
+/// `SQLite`-backed repository writing a discovered device and its entities
+/// in one transaction.
+pub struct SqliteDiscoveryRepository {
+    pools: Pools,
+}
+
+impl SqliteDiscoveryRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl DiscoveryRepository for SqliteDiscoveryRepository {
+    async fn persist(&self, discovered: &DiscoveredDevice) -> Result<(), MiniHubError> {
+        let device = &discovered.device;
+        let mut tx = self
+            .pools
+            .writer()
+            .begin()
+            .await
+            .map_err(StorageError::from)?;
+
+        sqlx::query(UPSERT_DEVICE)
+            .bind(device.id.as_uuid())
+            .bind(&device.name)
+            .bind(&device.manufacturer)
+            .bind(&device.model)
+            .bind(device.area_id.map(AreaId::as_uuid))
+            .bind(&device.integration)
+            .bind(&device.unique_id)
+            .bind(device.via_device_id.map(DeviceId::as_uuid))
+            .bind(&device.sw_version)
+            .bind(&device.hw_version)
+            .bind(&device.suggested_area)
+            .execute(&mut *tx)
+            .await
+            .map_err(StorageError::from)?;
+
+        for entity in &discovered.entities {
+            let attributes_json =
+                serde_json::to_string(&entity.attributes).map_err(StorageError::from)?;
+            let attribute_meta_json =
+                serde_json::to_string(&entity.attribute_meta).map_err(StorageError::from)?;
+
+            sqlx::query(UPSERT_ENTITY)
+                .bind(entity.id.as_uuid())
+                .bind(entity.device_id.as_uuid())
+                .bind(&entity.entity_id)
+                .bind(&entity.friendly_name)
+                .bind(entity.state.to_string())
+                .bind(entity.state.kind())
+                .bind(entity.device_class.map(DeviceClass::as_str))
+                .bind(entity.unit_of_measurement.as_deref())
+                .bind(&attributes_json)
+                .bind(&attribute_meta_json)
+                .bind(entity.mac_address.as_deref())
+                .bind(entity.last_changed.to_rfc3339())
+                .bind(entity.last_updated.to_rfc3339())
+                .execute(&mut *tx)
+                .await
+                .map_err(StorageError::from)?;
+        }
+
+        // Dropping the transaction on an early return rolls it back.
+        tx.commit().await.map_err(StorageError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+    use crate::{SqliteDeviceRepository, SqliteEntityRepository};
+    use minihub_app::ports::{DeviceRepository, EntityRepository};
+    use minihub_domain::device::Device;
+    use minihub_domain::entity::{Entity, EntityState};
+    use sqlx::SqlitePool;
+
+    async fn setup() -> SqlitePool {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        db.pool().clone()
+    }
+
+    fn discovered(entity_ids: &[&str]) -> DiscoveredDevice {
+        let device = Device::builder()
+            .name("Thermometer")
+            .integration("ble")
+            .unique_id("a4:c1:38:00:00:01")
+            .build()
+            .unwrap();
+        let entities = entity_ids
+            .iter()
+            .map(|entity_id| {
+                Entity::builder()
+                    .device_id(device.id)
+                    .entity_id(*entity_id)
+                    .friendly_name("Temperature")
+                    .state(EntityState::On)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        DiscoveredDevice {
+            device,
+            entities,
+            via_device: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_store_device_and_entities_when_persisted() {
+        let pool = setup().await;
+        let repo = SqliteDiscoveryRepository::new(pool.clone());
+        let discovered = discovered(&["sensor.temperature", "sensor.humidity"]);
+
+        repo.persist(&discovered).await.unwrap();
+
+        let device = SqliteDeviceRepository::new(pool.clone())
+            .get_by_id(discovered.device.id)
+            .await
+            .unwrap();
+        assert!(device.is_some());
+        let entities = SqliteEntityRepository::new(pool)
+            .find_by_device_id(discovered.device.id)
+            .await
+            .unwrap();
+        assert_eq!(entities.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_update_rows_in_place_when_persisted_again() {
+        let pool = setup().await;
+        let repo = SqliteDiscoveryRepository::new(pool.clone());
+        let mut discovered = discovered(&["sensor.temperature"]);
+        repo.persist(&discovered).await.unwrap();
+
+        discovered.device.name = "Bedroom thermometer".to_string();
+        discovered.entities[0].state = EntityState::Off;
+        repo.persist(&discovered).await.unwrap();
+
+        let device = SqliteDeviceRepository::new(pool.clone())
+            .get_by_id(discovered.device.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(device.name, "Bedroom thermometer");
+        let entities = SqliteEntityRepository::new(pool).get_all().await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].state, EntityState::Off);
+    }
+
+    #[tokio::test]
+    async fn should_roll_back_device_when_an_entity_fails() {
+        let pool = setup().await;
+        let repo = SqliteDiscoveryRepository::new(pool.clone());
+        let discovered = discovered(&["sensor.temperature", "sensor.temperature"]);
+
+        let result = repo.persist(&discovered).await;
+
+        assert!(result.is_err());
+        let device = SqliteDeviceRepository::new(pool.clone())
+            .get_by_id(discovered.device.id)
+            .await
+            .unwrap();
+        assert!(device.is_none());
+        let entities = SqliteEntityRepository::new(pool).get_all().await.unwrap();
+        assert!(entities.is_empty());
+    }
+}
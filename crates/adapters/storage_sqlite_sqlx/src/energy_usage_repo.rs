@@ -0,0 +1,206 @@
+//! `SQLite` implementation of [`EnergyUsageRepository`].
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::EnergyUsageRepository;
+use minihub_domain::energy::EnergyUsage;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::EntityId;
+use minihub_domain::time::Timestamp;
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+/// Wrapper for converting database rows into domain types without polluting
+/// domain structs with database concerns.
+struct Wrapper(EnergyUsage);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let entity_id: uuid::Uuid = row.try_get("entity_id")?;
+        let hour_str: String = row.try_get("hour")?;
+        let energy_wh: f64 = row.try_get("energy_wh")?;
+
+        let hour = chrono::DateTime::parse_from_rfc3339(&hour_str)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?
+            .to_utc();
+
+        Ok(Self(EnergyUsage {
+            entity_id: EntityId::from_uuid(entity_id),
+            hour,
+            energy_wh,
+        }))
+    }
+}
+
+const ADD: &str = r"
+    INSERT INTO energy_usage (entity_id, hour, energy_wh)
+    VALUES (?, ?, ?)
+    ON CONFLICT (entity_id, hour) DO UPDATE SET energy_wh = energy_wh + excluded.energy_wh
+";
+
+const SELECT_BY_ENTITY_IN_RANGE: &str = r"
+    SELECT entity_id, hour, energy_wh FROM energy_usage
+    WHERE entity_id = ? AND hour >= ? AND hour < ?
+    ORDER BY hour ASC
+";
+
+/// `SQLite`-backed energy usage repository.
+pub struct SqliteEnergyUsageRepository {
+    pools: Pools,
+}
+
+impl SqliteEnergyUsageRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl EnergyUsageRepository for SqliteEnergyUsageRepository {
+    async fn add(&self, usage: Vec<EnergyUsage>) -> Result<(), MiniHubError> {
+        let mut tx = self
+            .pools
+            .writer()
+            .begin()
+            .await
+            .map_err(StorageError::from)?;
+        for hourly in usage {
+            sqlx::query(ADD)
+                .bind(hourly.entity_id.as_uuid())
+                .bind(hourly.hour.to_rfc3339())
+                .bind(hourly.energy_wh)
+                .execute(&mut *tx)
+                .await
+                .map_err(StorageError::from)?;
+        }
+        tx.commit().await.map_err(StorageError::from)?;
+        Ok(())
+    }
+
+    async fn find_by_entity_in_range(
+        &self,
+        entity_id: EntityId,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Result<Vec<EnergyUsage>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_ENTITY_IN_RANGE)
+            .bind(entity_id.as_uuid())
+            .bind(from.to_rfc3339())
+            .bind(to.to_rfc3339())
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+    use minihub_domain::time::now;
+
+    async fn setup() -> (SqliteEnergyUsageRepository, EntityId) {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        let pool = db.pool().clone();
+        let entity_id = EntityId::new();
+
+        let device_id = uuid::Uuid::new_v4();
+        sqlx::query("INSERT INTO devices (id, name) VALUES (?, ?)")
+            .bind(device_id)
+            .bind("Plug")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("INSERT INTO entities (id, device_id, entity_id, friendly_name, state, attributes, last_changed, last_updated) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(entity_id.as_uuid())
+            .bind(device_id)
+            .bind("sensor.plug_power")
+            .bind("Plug power")
+            .bind("0")
+            .bind("{}")
+            .bind(now().to_rfc3339())
+            .bind(now().to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        (SqliteEnergyUsageRepository::new(pool), entity_id)
+    }
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    fn hourly(entity_id: EntityId, hour: &str, energy_wh: f64) -> EnergyUsage {
+        EnergyUsage {
+            entity_id,
+            hour: at(hour),
+            energy_wh,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_accumulate_usage_of_the_same_hour() {
+        let (repo, id) = setup().await;
+        repo.add(vec![hourly(id, "2026-03-02T10:00:00Z", 40.0)])
+            .await
+            .unwrap();
+        repo.add(vec![
+            hourly(id, "2026-03-02T10:00:00Z", 60.0),
+            hourly(id, "2026-03-02T11:00:00Z", 25.0),
+        ])
+        .await
+        .unwrap();
+
+        let usage = repo
+            .find_by_entity_in_range(id, at("2026-03-02T00:00:00Z"), at("2026-03-03T00:00:00Z"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            usage,
+            vec![
+                hourly(id, "2026-03-02T10:00:00Z", 100.0),
+                hourly(id, "2026-03-02T11:00:00Z", 25.0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_exclude_hours_outside_range() {
+        let (repo, id) = setup().await;
+        repo.add(vec![
+            hourly(id, "2026-03-01T23:00:00Z", 10.0),
+            hourly(id, "2026-03-02T00:00:00Z", 20.0),
+            hourly(id, "2026-03-03T00:00:00Z", 30.0),
+        ])
+        .await
+        .unwrap();
+
+        let usage = repo
+            .find_by_entity_in_range(id, at("2026-03-02T00:00:00Z"), at("2026-03-03T00:00:00Z"))
+            .await
+            .unwrap();
+
+        assert_eq!(usage, vec![hourly(id, "2026-03-02T00:00:00Z", 20.0)]);
+    }
+
+    #[tokio::test]
+    async fn should_return_empty_when_entity_has_no_usage() {
+        let (repo, _) = setup().await;
+        let usage = repo
+            .find_by_entity_in_range(EntityId::new(), at("2026-03-02T00:00:00Z"), now())
+            .await
+            .unwrap();
+        assert!(usage.is_empty());
+    }
+}
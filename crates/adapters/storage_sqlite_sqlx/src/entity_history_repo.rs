@@ -3,16 +3,18 @@
 use std::collections::HashMap;
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, Row};
 
 use minihub_app::ports::storage::EntityHistoryRepository;
-use minihub_domain::entity::{AttributeValue, EntityState};
+use minihub_domain::entity::AttributeValue;
 use minihub_domain::entity_history::{EntityHistory, EntityHistoryId};
 use minihub_domain::error::MiniHubError;
 use minihub_domain::id::EntityId;
 use minihub_domain::time::Timestamp;
 
+use crate::entity_repo::decode_state;
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 /// Wrapper for converting database rows into domain types without polluting
 /// domain structs with database concerns.
@@ -23,13 +25,13 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
         let id: uuid::Uuid = row.try_get("id")?;
         let entity_id: uuid::Uuid = row.try_get("entity_id")?;
         let state_str: String = row.try_get("state")?;
+        let state_kind: Option<String> = row.try_get("state_kind")?;
         let attributes_json: String = row.try_get("attributes")?;
         let recorded_at_str: String = row.try_get("recorded_at")?;
 
         let id = EntityHistoryId::from_uuid(id);
         let entity_id = EntityId::from_uuid(entity_id);
-        let state: EntityState = serde_json::from_str(&format!("\"{state_str}\""))
-            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let state = decode_state(&state_str, state_kind.as_deref());
         let attributes: HashMap<String, AttributeValue> = serde_json::from_str(&attributes_json)
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
         let recorded_at = chrono::DateTime::parse_from_rfc3339(&recorded_at_str)
@@ -47,8 +49,8 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
 }
 
 const INSERT: &str = r"
-    INSERT INTO entity_history (id, entity_id, state, attributes, recorded_at)
-    VALUES (?, ?, ?, ?, ?)
+    INSERT INTO entity_history (id, entity_id, state, state_kind, attributes, recorded_at)
+    VALUES (?, ?, ?, ?, ?, ?)
 ";
 
 const SELECT_BY_ENTITY_IN_RANGE: &str = r"
@@ -68,14 +70,17 @@ const DELETE_BEFORE: &str = "DELETE FROM entity_history WHERE recorded_at < ?";
 
 /// `SQLite`-backed entity history repository.
 pub struct SqliteEntityHistoryRepository {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteEntityHistoryRepository {
-    /// Create a new repository using the given connection pool.
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
     }
 }
 
@@ -88,9 +93,10 @@ impl EntityHistoryRepository for SqliteEntityHistoryRepository {
             .bind(history.id.as_uuid())
             .bind(history.entity_id.as_uuid())
             .bind(history.state.to_string())
+            .bind(history.state.kind())
             .bind(&attributes_json)
             .bind(history.recorded_at.to_rfc3339())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -111,7 +117,7 @@ impl EntityHistoryRepository for SqliteEntityHistoryRepository {
                 .bind(from.to_rfc3339())
                 .bind(to.to_rfc3339())
                 .bind(limit_i64)
-                .fetch_all(&self.pool)
+                .fetch_all(self.pools.reader())
                 .await
                 .map_err(StorageError::from)?
         } else {
@@ -119,7 +125,7 @@ impl EntityHistoryRepository for SqliteEntityHistoryRepository {
                 .bind(entity_id.as_uuid())
                 .bind(from.to_rfc3339())
                 .bind(to.to_rfc3339())
-                .fetch_all(&self.pool)
+                .fetch_all(self.pools.reader())
                 .await
                 .map_err(StorageError::from)?
         };
@@ -130,7 +136,7 @@ impl EntityHistoryRepository for SqliteEntityHistoryRepository {
     async fn purge_before(&self, before: Timestamp) -> Result<usize, MiniHubError> {
         let result = sqlx::query(DELETE_BEFORE)
             .bind(before.to_rfc3339())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -143,18 +149,13 @@ impl EntityHistoryRepository for SqliteEntityHistoryRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pool::Config;
+    use crate::pool::{Config, query_plan};
     use chrono::Duration;
     use minihub_domain::entity::EntityState;
     use minihub_domain::time::now;
 
     async fn setup() -> (SqliteEntityHistoryRepository, EntityId) {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         let pool = db.pool().clone();
         let entity_id = EntityId::new();
 
@@ -219,6 +220,22 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn should_keep_text_state_reading_like_a_number() {
+        let (repo, entity_id) = setup().await;
+        let timestamp = now();
+        let mut history = test_history(entity_id, timestamp);
+        history.state = EntityState::Text("10".to_string());
+        repo.record(history).await.unwrap();
+
+        let found = repo
+            .find_by_entity_in_range(entity_id, timestamp, timestamp, None)
+            .await
+            .unwrap();
+
+        assert_eq!(found[0].state, EntityState::Text("10".to_string()));
+    }
+
     #[tokio::test]
     async fn should_return_empty_when_no_history_in_range() {
         let (repo, entity_id) = setup().await;
@@ -388,7 +405,7 @@ mod tests {
         // Create a second entity
         let entity_id2 = EntityId::new();
         let device_id = uuid::Uuid::new_v4();
-        let pool = &repo.pool;
+        let pool = repo.pools.writer();
         sqlx::query("INSERT INTO devices (id, name, integration, unique_id) VALUES (?, ?, ?, ?)")
             .bind(device_id)
             .bind("Device 2")
@@ -435,4 +452,21 @@ mod tests {
         assert_eq!(found2.len(), 1);
         assert_eq!(found2[0].entity_id, entity_id2);
     }
+
+    #[tokio::test]
+    async fn should_seek_indexes_to_read_and_purge_history() {
+        let (repo, _) = setup().await;
+
+        let plan = query_plan(repo.pools.reader(), SELECT_BY_ENTITY_IN_RANGE).await;
+        assert!(
+            plan.contains("USING INDEX idx_entity_history_entity_recorded"),
+            "{plan}"
+        );
+
+        let plan = query_plan(repo.pools.writer(), DELETE_BEFORE).await;
+        assert!(
+            plan.contains("INDEX idx_entity_history_recorded_at (recorded_at<?)"),
+            "{plan}"
+        );
+    }
 }
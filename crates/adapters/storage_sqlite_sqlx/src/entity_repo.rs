@@ -3,14 +3,15 @@
 use std::collections::HashMap;
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
 
 use minihub_app::ports::EntityRepository;
-use minihub_domain::entity::{AttributeValue, Entity, EntityState};
-use minihub_domain::error::MiniHubError;
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass, Entity, EntityState};
+use minihub_domain::error::{ConflictError, MiniHubError, NotFoundError};
 use minihub_domain::id::{DeviceId, EntityId};
 
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 /// Wrapper for converting database rows into domain types without polluting
 /// domain structs with database concerns.
@@ -29,16 +30,27 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
         let entity_id: String = row.try_get("entity_id")?;
         let friendly_name: String = row.try_get("friendly_name")?;
         let state_str: String = row.try_get("state")?;
+        let state_kind: Option<String> = row.try_get("state_kind")?;
+        let device_class_str: Option<String> = row.try_get("device_class")?;
+        let unit_of_measurement: Option<String> = row.try_get("unit_of_measurement")?;
         let attributes_json: String = row.try_get("attributes")?;
+        let attribute_meta_json: String = row.try_get("attribute_meta")?;
         let last_changed_str: String = row.try_get("last_changed")?;
         let last_updated_str: String = row.try_get("last_updated")?;
+        let version: u32 = row.try_get("version")?;
 
         let id = EntityId::from_uuid(id);
         let device_id = DeviceId::from_uuid(device_id);
-        let state: EntityState = serde_json::from_str(&format!("\"{state_str}\""))
+        let state = decode_state(&state_str, state_kind.as_deref());
+        let device_class: Option<DeviceClass> = device_class_str
+            .map(|value| serde_json::from_str(&format!("\"{value}\"")))
+            .transpose()
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
         let attributes: HashMap<String, AttributeValue> = serde_json::from_str(&attributes_json)
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let attribute_meta: HashMap<String, AttributeMeta> =
+            serde_json::from_str(&attribute_meta_json)
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
         let last_changed = chrono::DateTime::parse_from_rfc3339(&last_changed_str)
             .map_err(|err| sqlx::Error::Decode(Box::new(err)))?
             .to_utc();
@@ -54,43 +66,81 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
             entity_id,
             friendly_name,
             state,
+            device_class,
+            unit_of_measurement,
             attributes,
+            attribute_meta,
             mac_address,
             last_changed,
             last_updated,
+            version,
         }))
     }
 }
 
+/// Read back a state stored as its display form in `state` and its kind
+/// in `state_kind`, so text reading like a number stays text.
+///
+/// Rows written before the kind was stored have none: their display form
+/// is parsed into the discrete variants, a number or text.
+pub(crate) fn decode_state(state: &str, kind: Option<&str>) -> EntityState {
+    kind.and_then(|kind| EntityState::from_kind(kind, state))
+        .unwrap_or_else(|| {
+            let Ok(state) = state.parse::<EntityState>();
+            state
+        })
+}
+
 const INSERT: &str = r"
-    INSERT INTO entities (id, device_id, entity_id, friendly_name, state, attributes, mac_address, last_changed, last_updated)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    INSERT INTO entities (id, device_id, entity_id, friendly_name, state, state_kind, device_class, unit_of_measurement, attributes, attribute_meta, mac_address, last_changed, last_updated, version)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 ";
 
 const SELECT_BY_ID: &str = "SELECT * FROM entities WHERE id = ?";
 const SELECT_ALL: &str = "SELECT * FROM entities";
+/// Ids looked up per `IN` query by `get_many`, well below the bound
+/// parameters limit of `SQLite`.
+const MAX_IDS_PER_QUERY: usize = 500;
 const SELECT_BY_DEVICE: &str = "SELECT * FROM entities WHERE device_id = ?";
 const SELECT_BY_ENTITY_ID: &str = "SELECT * FROM entities WHERE entity_id = ?";
 
 const UPDATE: &str = r"
     UPDATE entities
-    SET device_id = ?, entity_id = ?, friendly_name = ?, state = ?, attributes = ?,
-        mac_address = ?, last_changed = ?, last_updated = ?
-    WHERE id = ?
+    SET device_id = ?, entity_id = ?, friendly_name = ?, state = ?, state_kind = ?, device_class = ?,
+        unit_of_measurement = ?, attributes = ?, attribute_meta = ?, mac_address = ?, last_changed = ?, last_updated = ?,
+        version = version + 1
+    WHERE id = ? AND version = ?
+    RETURNING *
 ";
 
+const SELECT_VERSION: &str = "SELECT version FROM entities WHERE id = ?";
+
 const DELETE_BY_ID: &str = "DELETE FROM entities WHERE id = ?";
 
+const SELECT_BY_ALIAS: &str = r"
+    SELECT entities.* FROM entities
+    JOIN entity_aliases ON entity_aliases.entity_id = entities.id
+    WHERE entity_aliases.alias = ?
+";
+
+const UPSERT_ALIAS: &str = r"
+    INSERT INTO entity_aliases (alias, entity_id) VALUES (?, ?)
+    ON CONFLICT(alias) DO UPDATE SET entity_id = excluded.entity_id
+";
+
 /// `SQLite`-backed entity repository.
 pub struct SqliteEntityRepository {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteEntityRepository {
-    /// Create a new repository using the given connection pool.
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
     }
 }
 
@@ -98,6 +148,8 @@ impl EntityRepository for SqliteEntityRepository {
     async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
         let attributes_json =
             serde_json::to_string(&entity.attributes).map_err(StorageError::from)?;
+        let attribute_meta_json =
+            serde_json::to_string(&entity.attribute_meta).map_err(StorageError::from)?;
 
         sqlx::query(INSERT)
             .bind(entity.id.as_uuid())
@@ -105,11 +157,16 @@ impl EntityRepository for SqliteEntityRepository {
             .bind(&entity.entity_id)
             .bind(&entity.friendly_name)
             .bind(entity.state.to_string())
+            .bind(entity.state.kind())
+            .bind(entity.device_class.map(DeviceClass::as_str))
+            .bind(entity.unit_of_measurement.as_deref())
             .bind(&attributes_json)
+            .bind(&attribute_meta_json)
             .bind(entity.mac_address.as_deref())
             .bind(entity.last_changed.to_rfc3339())
             .bind(entity.last_updated.to_rfc3339())
-            .execute(&self.pool)
+            .bind(entity.version)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -119,16 +176,37 @@ impl EntityRepository for SqliteEntityRepository {
     async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
             .bind(id.as_uuid())
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
         Ok(Wrapper::maybe(row))
     }
 
+    async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+        let mut entities = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(MAX_IDS_PER_QUERY) {
+            let mut builder: QueryBuilder<'_, Sqlite> =
+                QueryBuilder::new("SELECT * FROM entities WHERE id IN (");
+            let mut separated = builder.separated(", ");
+            for id in chunk {
+                separated.push_bind(id.as_uuid());
+            }
+            separated.push_unseparated(")");
+
+            let rows: Vec<Wrapper> = builder
+                .build_query_as()
+                .fetch_all(self.pools.reader())
+                .await
+                .map_err(StorageError::from)?;
+            entities.extend(rows.into_iter().map(|w| w.0));
+        }
+        Ok(entities)
+    }
+
     async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -138,7 +216,7 @@ impl EntityRepository for SqliteEntityRepository {
     async fn find_by_device_id(&self, device_id: DeviceId) -> Result<Vec<Entity>, MiniHubError> {
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_DEVICE)
             .bind(device_id.as_uuid())
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -148,7 +226,7 @@ impl EntityRepository for SqliteEntityRepository {
     async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ENTITY_ID)
             .bind(entity_id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -158,28 +236,77 @@ impl EntityRepository for SqliteEntityRepository {
     async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
         let attributes_json =
             serde_json::to_string(&entity.attributes).map_err(StorageError::from)?;
+        let attribute_meta_json =
+            serde_json::to_string(&entity.attribute_meta).map_err(StorageError::from)?;
 
-        sqlx::query(UPDATE)
+        let row: Option<Wrapper> = sqlx::query_as(UPDATE)
             .bind(entity.device_id.as_uuid())
             .bind(&entity.entity_id)
             .bind(&entity.friendly_name)
             .bind(entity.state.to_string())
+            .bind(entity.state.kind())
+            .bind(entity.device_class.map(DeviceClass::as_str))
+            .bind(entity.unit_of_measurement.as_deref())
             .bind(&attributes_json)
+            .bind(&attribute_meta_json)
             .bind(entity.mac_address.as_deref())
             .bind(entity.last_changed.to_rfc3339())
             .bind(entity.last_updated.to_rfc3339())
             .bind(entity.id.as_uuid())
-            .execute(&self.pool)
+            .bind(entity.version)
+            .fetch_optional(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
+        if let Some(Wrapper(updated)) = row {
+            return Ok(updated);
+        }
 
-        Ok(entity)
+        let actual: Option<u32> = sqlx::query_scalar(SELECT_VERSION)
+            .bind(entity.id.as_uuid())
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+        Err(match actual {
+            Some(actual) => ConflictError {
+                entity: "Entity",
+                id: entity.id.to_string(),
+                expected: format!("version {}", entity.version),
+                actual: format!("version {actual}"),
+            }
+            .into(),
+            None => NotFoundError {
+                entity: "Entity",
+                id: entity.id.to_string(),
+            }
+            .into(),
+        })
     }
 
     async fn delete(&self, id: EntityId) -> Result<(), MiniHubError> {
         sqlx::query(DELETE_BY_ID)
             .bind(id.as_uuid())
-            .execute(&self.pool)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(())
+    }
+
+    async fn find_by_alias(&self, alias: &str) -> Result<Option<Entity>, MiniHubError> {
+        let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ALIAS)
+            .bind(alias)
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(Wrapper::maybe(row))
+    }
+
+    async fn add_alias(&self, id: EntityId, alias: &str) -> Result<(), MiniHubError> {
+        sqlx::query(UPSERT_ALIAS)
+            .bind(alias)
+            .bind(id.as_uuid())
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -190,17 +317,12 @@ impl EntityRepository for SqliteEntityRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pool::Config;
+    use crate::pool::{Config, query_plan};
     use minihub_domain::entity::EntityState;
     use minihub_domain::id::DeviceId;
 
     async fn setup() -> (SqliteEntityRepository, DeviceId) {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         let pool = db.pool().clone();
         let device_id = DeviceId::new();
 
@@ -241,6 +363,48 @@ mod tests {
         assert_eq!(fetched.state, EntityState::Off);
     }
 
+    #[tokio::test]
+    async fn should_roundtrip_numeric_and_text_states() {
+        let (repo, device_id) = setup().await;
+        for (entity_id, state) in [
+            ("sensor.outdoor", EntityState::Numeric(-3.5)),
+            ("sensor.mode", EntityState::Text("Heat cool".to_string())),
+            ("sensor.code", EntityState::Text("10".to_string())),
+        ] {
+            let entity = Entity::builder()
+                .device_id(device_id)
+                .entity_id(entity_id)
+                .friendly_name(entity_id)
+                .state(state.clone())
+                .build()
+                .unwrap();
+            let id = entity.id;
+
+            repo.create(entity).await.unwrap();
+
+            let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+            assert_eq!(fetched.state, state);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_parse_state_of_rows_stored_without_kind() {
+        let (repo, device_id) = setup().await;
+        let id = EntityId::new();
+        sqlx::query("INSERT INTO entities (id, device_id, entity_id, friendly_name, state, attributes, last_changed, last_updated) VALUES (?, ?, 'sensor.legacy', 'Legacy', '10', '{}', ?, ?)")
+            .bind(id.as_uuid())
+            .bind(device_id.as_uuid())
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(chrono::Utc::now().to_rfc3339())
+            .execute(repo.pools.writer())
+            .await
+            .unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+
+        assert_eq!(fetched.state, EntityState::Numeric(10.0));
+    }
+
     #[tokio::test]
     async fn should_return_none_when_entity_not_found() {
         let (repo, _device_id) = setup().await;
@@ -288,6 +452,35 @@ mod tests {
         let fetched = repo.get_by_id(id).await.unwrap().unwrap();
         assert_eq!(fetched.state, EntityState::On);
         assert_eq!(fetched.friendly_name, "Updated Name");
+        assert_eq!(fetched.version, 2);
+    }
+
+    #[tokio::test]
+    async fn should_reject_update_when_version_is_stale() {
+        let (repo, device_id) = setup().await;
+        let entity = test_entity(device_id);
+        repo.create(entity.clone()).await.unwrap();
+        let mut first = entity.clone();
+        first.state = EntityState::On;
+        repo.update(first).await.unwrap();
+
+        let mut stale = entity;
+        stale.friendly_name = "Stale".to_string();
+        let err = repo.update(stale).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Conflict(ConflictError { ref actual, .. }) if actual == "version 2"
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_updating_missing_entity() {
+        let (repo, device_id) = setup().await;
+
+        let err = repo.update(test_entity(device_id)).await.unwrap_err();
+
+        assert!(matches!(err, MiniHubError::NotFound(_)));
     }
 
     #[tokio::test]
@@ -345,4 +538,139 @@ mod tests {
             Some(&AttributeValue::Int(2))
         );
     }
+
+    #[tokio::test]
+    async fn should_preserve_attribute_meta_through_roundtrip() {
+        let (repo, device_id) = setup().await;
+        let meta = AttributeMeta::default()
+            .with_precision(1)
+            .with_range(0.0, 100.0)
+            .with_display_unit("%");
+        let mut entity = Entity::builder()
+            .device_id(device_id)
+            .entity_id("sensor.humidity")
+            .friendly_name("Humidity")
+            .attribute_meta("humidity", meta.clone())
+            .build()
+            .unwrap();
+        let id = entity.id;
+        repo.create(entity.clone()).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(fetched.get_attribute_meta("humidity"), Some(&meta));
+
+        entity.attribute_meta.clear();
+        repo.update(entity).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert!(fetched.attribute_meta.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_preserve_device_class_and_unit_through_roundtrip() {
+        let (repo, device_id) = setup().await;
+        let entity = Entity::builder()
+            .device_id(device_id)
+            .entity_id("sensor.temp")
+            .friendly_name("Temperature")
+            .device_class(DeviceClass::Temperature)
+            .unit_of_measurement("°C")
+            .build()
+            .unwrap();
+        let id = entity.id;
+        repo.create(entity).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(fetched.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(fetched.unit_of_measurement.as_deref(), Some("°C"));
+    }
+
+    #[tokio::test]
+    async fn should_store_null_device_class_when_unset() {
+        let (repo, device_id) = setup().await;
+        let entity = test_entity(device_id);
+        let id = entity.id;
+        repo.create(entity).await.unwrap();
+
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+        assert!(fetched.device_class.is_none());
+        assert!(fetched.unit_of_measurement.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_find_entity_by_alias_after_rename() {
+        let (repo, device_id) = setup().await;
+        let mut entity = test_entity(device_id);
+        let id = entity.id;
+        repo.create(entity.clone()).await.unwrap();
+
+        entity.entity_id = "light.lounge".to_string();
+        repo.update(entity).await.unwrap();
+        repo.add_alias(id, "light.living_room").await.unwrap();
+
+        let found = repo.find_by_alias("light.living_room").await.unwrap();
+        assert_eq!(found.unwrap().entity_id, "light.lounge");
+        assert!(repo.find_by_alias("light.lounge").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn should_move_alias_when_added_again() {
+        let (repo, device_id) = setup().await;
+        let first = test_entity(device_id);
+        let mut second = test_entity(device_id);
+        second.entity_id = "light.kitchen".to_string();
+        let second_id = second.id;
+        repo.create(first.clone()).await.unwrap();
+        repo.create(second).await.unwrap();
+
+        repo.add_alias(first.id, "light.old").await.unwrap();
+        repo.add_alias(second_id, "light.old").await.unwrap();
+
+        let found = repo.find_by_alias("light.old").await.unwrap().unwrap();
+        assert_eq!(found.id, second_id);
+    }
+
+    #[tokio::test]
+    async fn should_drop_aliases_when_entity_deleted() {
+        let (repo, device_id) = setup().await;
+        let entity = test_entity(device_id);
+        let id = entity.id;
+        repo.create(entity).await.unwrap();
+        repo.add_alias(id, "light.old").await.unwrap();
+
+        repo.delete(id).await.unwrap();
+
+        assert!(repo.find_by_alias("light.old").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn should_get_many_entities_skipping_missing_ids() {
+        let (repo, device_id) = setup().await;
+        let first = repo.create(test_entity(device_id)).await.unwrap();
+        let mut second = test_entity(device_id);
+        second.entity_id = "light.kitchen".to_string();
+        let second = repo.create(second).await.unwrap();
+
+        let mut fetched = repo
+            .get_many(&[first.id, EntityId::new(), second.id])
+            .await
+            .unwrap();
+        fetched.sort_by_key(|entity| entity.entity_id.clone());
+
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(fetched[0].id, second.id);
+        assert_eq!(fetched[1].id, first.id);
+        assert!(repo.get_many(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_seek_the_device_index_to_list_device_entities() {
+        let (repo, _) = setup().await;
+
+        let plan = query_plan(repo.pools.reader(), SELECT_BY_DEVICE).await;
+        assert!(
+            plan.contains("USING INDEX idx_entities_device_id"),
+            "{plan}"
+        );
+    }
 }
@@ -16,6 +16,18 @@ pub enum StorageError {
     /// Failed to run migrations.
     #[error("migration error")]
     Migration(#[from] sqlx::migrate::MigrateError),
+
+    /// A database file could not be accessed.
+    #[error("database file error")]
+    Io(#[from] std::io::Error),
+
+    /// A secret key is not 64 hexadecimal characters.
+    #[error("invalid secret key, expected 64 hexadecimal characters")]
+    InvalidSecretKey,
+
+    /// A secret could not be encrypted, or decrypted with the current key.
+    #[error("secret {0} could not be encrypted or decrypted with the configured key")]
+    Crypto(String),
 }
 
 impl From<StorageError> for MiniHubError {
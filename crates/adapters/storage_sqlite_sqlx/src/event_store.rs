@@ -1,14 +1,17 @@
-//! `SQLite` implementation of [`EventStore`].
+//! `SQLite` implementation of [`EventStore`], which also answers the
+//! [`Storage`] health check.
 
 use sqlx::sqlite::SqliteRow;
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
 
-use minihub_app::ports::EventStore;
+use minihub_app::ports::{EventQuery, EventStore, Storage};
 use minihub_domain::error::MiniHubError;
 use minihub_domain::event::{Event, EventType};
 use minihub_domain::id::{EntityId, EventId};
+use minihub_domain::time::Timestamp;
 
 use crate::error::StorageError;
+use crate::pool::Pools;
 
 struct Wrapper(Event);
 
@@ -25,6 +28,9 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
         let entity_id: Option<uuid::Uuid> = row.try_get("entity_id")?;
         let timestamp_str: String = row.try_get("timestamp")?;
         let data_json: String = row.try_get("data")?;
+        let correlation_id: Option<uuid::Uuid> = row.try_get("correlation_id")?;
+        let caused_by: Option<uuid::Uuid> = row.try_get("caused_by")?;
+        let schema_version: u32 = row.try_get("schema_version")?;
 
         let id = EventId::from_uuid(id);
         let event_type: EventType = serde_json::from_str(&format!("\"{event_type}\""))
@@ -42,30 +48,109 @@ impl<'r> FromRow<'r, SqliteRow> for Wrapper {
             entity_id,
             timestamp,
             data,
+            correlation_id: correlation_id.map_or(id, EventId::from_uuid),
+            caused_by: caused_by.map(EventId::from_uuid),
+            schema_version,
         }))
     }
 }
 
 const INSERT: &str = r"
-    INSERT INTO events (id, event_type, entity_id, timestamp, data)
-    VALUES (?, ?, ?, ?, ?)
+    INSERT INTO events (
+        id, event_type, entity_id, timestamp, data, correlation_id, caused_by, schema_version
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
 ";
 
 const SELECT_BY_ID: &str = "SELECT * FROM events WHERE id = ?";
 const SELECT_RECENT: &str = "SELECT * FROM events ORDER BY timestamp DESC LIMIT ?";
 const SELECT_BY_ENTITY: &str =
     "SELECT * FROM events WHERE entity_id = ? ORDER BY timestamp DESC LIMIT ?";
+const SELECT_IN_RANGE: &str = r"
+    SELECT * FROM events
+    WHERE timestamp >= ? AND timestamp < ?
+    ORDER BY timestamp, id
+    LIMIT ?
+";
+const SELECT_IN_RANGE_AFTER: &str = r"
+    SELECT * FROM events
+    WHERE timestamp >= ? AND timestamp < ?
+        AND (timestamp, id) > (?, ?)
+    ORDER BY timestamp, id
+    LIMIT ?
+";
+
+/// Build the query selecting the events matching `query`, newest first.
+///
+/// Every filter is a predicate on a leading column of an `events` index and
+/// the page bound compares `(timestamp, id)` as a row value, so `SQLite`
+/// seeks the index instead of scanning it.
+fn select_events(query: &EventQuery) -> QueryBuilder<'static, Sqlite> {
+    let mut builder = QueryBuilder::new("SELECT * FROM events WHERE 1 = 1");
+    if let Some(event_type) = &query.event_type {
+        builder
+            .push(" AND event_type = ")
+            .push_bind(event_type.as_str());
+    }
+    if let Some(entity_id) = query.entity_id {
+        builder
+            .push(" AND entity_id = ")
+            .push_bind(entity_id.as_uuid());
+    }
+    if let Some(correlation_id) = query.correlation_id {
+        // Rows stored before causality was recorded are chains of their own.
+        builder
+            .push(" AND (correlation_id = ")
+            .push_bind(correlation_id.as_uuid())
+            .push(" OR (correlation_id IS NULL AND id = ")
+            .push_bind(correlation_id.as_uuid())
+            .push("))");
+    }
+    if let Some(from) = query.from {
+        builder
+            .push(" AND timestamp >= ")
+            .push_bind(from.to_rfc3339());
+    }
+    if let Some(to) = query.to {
+        builder.push(" AND timestamp < ").push_bind(to.to_rfc3339());
+    }
+    if let Some((timestamp, id)) = query.before {
+        builder
+            .push(" AND (timestamp, id) < (")
+            .push_bind(timestamp.to_rfc3339())
+            .push(", ")
+            .push_bind(id.as_uuid())
+            .push(")");
+    }
+    builder
+        .push(" ORDER BY timestamp DESC, id DESC LIMIT ")
+        .push_bind(i32::try_from(query.limit).unwrap_or(i32::MAX));
+    builder
+}
 
 /// `SQLite`-backed event store.
 pub struct SqliteEventStore {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl SqliteEventStore {
-    /// Create a new event store using the given connection pool.
+    /// Create a new event store using the given connection pools, or a
+    /// single pool serving both reads and writes.
     #[must_use]
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl Storage for SqliteEventStore {
+    async fn ping(&self) -> Result<(), MiniHubError> {
+        sqlx::query("SELECT 1")
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(())
     }
 }
 
@@ -79,7 +164,10 @@ impl EventStore for SqliteEventStore {
             .bind(event.entity_id.map(EntityId::as_uuid))
             .bind(event.timestamp.to_rfc3339())
             .bind(&data_json)
-            .execute(&self.pool)
+            .bind(event.correlation_id.as_uuid())
+            .bind(event.caused_by.map(EventId::as_uuid))
+            .bind(event.schema_version)
+            .execute(self.pools.writer())
             .await
             .map_err(StorageError::from)?;
 
@@ -89,7 +177,7 @@ impl EventStore for SqliteEventStore {
     async fn get_by_id(&self, id: EventId) -> Result<Option<Event>, MiniHubError> {
         let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
             .bind(id.as_uuid())
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -100,7 +188,7 @@ impl EventStore for SqliteEventStore {
         let limit = i32::try_from(limit).unwrap_or(i32::MAX);
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_RECENT)
             .bind(limit)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -116,7 +204,45 @@ impl EventStore for SqliteEventStore {
         let rows: Vec<Wrapper> = sqlx::query_as(SELECT_BY_ENTITY)
             .bind(entity_id.as_uuid())
             .bind(limit)
-            .fetch_all(&self.pool)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn find_in_range(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+        after: Option<(Timestamp, EventId)>,
+        limit: usize,
+    ) -> Result<Vec<Event>, MiniHubError> {
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        let query = match after {
+            Some((timestamp, id)) => sqlx::query_as(SELECT_IN_RANGE_AFTER)
+                .bind(from.to_rfc3339())
+                .bind(to.to_rfc3339())
+                .bind(timestamp.to_rfc3339())
+                .bind(id.as_uuid()),
+            None => sqlx::query_as(SELECT_IN_RANGE)
+                .bind(from.to_rfc3339())
+                .bind(to.to_rfc3339()),
+        };
+        let rows: Vec<Wrapper> = query
+            .bind(limit)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn query(&self, query: EventQuery) -> Result<Vec<Event>, MiniHubError> {
+        let mut builder = select_events(&query);
+        let rows: Vec<Wrapper> = builder
+            .build_query_as()
+            .fetch_all(self.pools.reader())
             .await
             .map_err(StorageError::from)?;
 
@@ -127,17 +253,12 @@ impl EventStore for SqliteEventStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pool::Config;
-    use minihub_domain::event::EventType;
+    use crate::pool::{Config, query_plan};
+    use minihub_domain::event::{EventType, SCHEMA_VERSION};
     use minihub_domain::id::{DeviceId, EntityId};
 
     async fn setup() -> (SqliteEventStore, EntityId) {
-        let db = Config {
-            database_url: "sqlite::memory:".to_string(),
-        }
-        .build()
-        .await
-        .unwrap();
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
         let pool = db.pool().clone();
 
         let device_id = DeviceId::new();
@@ -169,7 +290,7 @@ mod tests {
         Event::new(
             EventType::StateChanged,
             entity_id,
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         )
     }
 
@@ -187,7 +308,23 @@ mod tests {
         assert_eq!(fetched.id, id);
         assert_eq!(fetched.event_type, EventType::StateChanged);
         assert_eq!(fetched.entity_id, Some(entity_id));
-        assert_eq!(fetched.data["from"], "off");
+        assert_eq!(fetched.data["old_state"], "off");
+        assert_eq!(fetched.schema_version, SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn should_ping_open_database() {
+        let (store, _) = setup().await;
+
+        assert!(store.ping().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_fail_ping_when_pool_is_closed() {
+        let (store, _) = setup().await;
+        store.pools.writer().close().await;
+
+        assert!(store.ping().await.is_err());
     }
 
     #[tokio::test]
@@ -275,4 +412,195 @@ mod tests {
         assert_eq!(fetched.data["old"], 100);
         assert_eq!(fetched.data["new"], 200);
     }
+    #[tokio::test]
+    async fn should_page_events_in_range_oldest_first() {
+        let (store, _) = setup().await;
+        let start = chrono::Utc::now();
+        let mut stored = Vec::new();
+        for offset in 0..5 {
+            let mut event = test_event(None);
+            event.timestamp = start + chrono::Duration::seconds(offset);
+            stored.push(event.id);
+            store.store(event).await.unwrap();
+        }
+        let end = start + chrono::Duration::seconds(5);
+
+        let first = store.find_in_range(start, end, None, 3).await.unwrap();
+        let ids: Vec<_> = first.iter().map(|e| e.id).collect();
+        assert_eq!(ids, stored[..3]);
+
+        let last = first.last().unwrap();
+        let second = store
+            .find_in_range(start, end, Some((last.timestamp, last.id)), 3)
+            .await
+            .unwrap();
+        let ids: Vec<_> = second.iter().map(|e| e.id).collect();
+        assert_eq!(ids, stored[3..]);
+    }
+
+    #[tokio::test]
+    async fn should_exclude_events_outside_range() {
+        let (store, _) = setup().await;
+        let start = chrono::Utc::now();
+        for offset in [-10, 0, 10] {
+            let mut event = test_event(None);
+            event.timestamp = start + chrono::Duration::seconds(offset);
+            store.store(event).await.unwrap();
+        }
+
+        let events = store
+            .find_in_range(start, start + chrono::Duration::seconds(10), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].timestamp, start);
+    }
+
+    #[tokio::test]
+    async fn should_query_events_by_type_and_entity() {
+        let (store, entity_id) = setup().await;
+        let wanted = test_event(Some(entity_id));
+        let wanted_id = wanted.id;
+        store.store(wanted).await.unwrap();
+        store.store(test_event(None)).await.unwrap();
+        store
+            .store(Event::new(
+                EventType::EntityCreated,
+                Some(entity_id),
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+
+        let events = store
+            .query(
+                EventQuery::new(10)
+                    .with_event_type(EventType::StateChanged)
+                    .with_entity_id(entity_id),
+            )
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = events.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [wanted_id]);
+    }
+
+    #[tokio::test]
+    async fn should_query_events_of_a_causal_chain() {
+        let (store, _) = setup().await;
+        let root = test_event(None);
+        let child = test_event(None).with_cause(&root);
+        let grandchild = test_event(None).with_cause(&child);
+        store.store(root.clone()).await.unwrap();
+        store.store(child.clone()).await.unwrap();
+        store.store(grandchild.clone()).await.unwrap();
+        store.store(test_event(None)).await.unwrap();
+
+        let events = store
+            .query(EventQuery::new(10).with_correlation_id(root.id))
+            .await
+            .unwrap();
+
+        assert_eq!(events.len(), 3);
+        let stored = events.iter().find(|e| e.id == grandchild.id).unwrap();
+        assert_eq!(stored.caused_by, Some(child.id));
+        assert_eq!(stored.correlation_id, root.id);
+    }
+
+    #[tokio::test]
+    async fn should_page_queried_events_newest_first() {
+        let (store, _) = setup().await;
+        let start = chrono::Utc::now();
+        let mut stored = Vec::new();
+        for offset in 0..5 {
+            let mut event = test_event(None);
+            event.timestamp = start + chrono::Duration::seconds(offset);
+            stored.push(event.id);
+            store.store(event).await.unwrap();
+        }
+        stored.reverse();
+
+        let first = store.query(EventQuery::new(3)).await.unwrap();
+        let ids: Vec<_> = first.iter().map(|e| e.id).collect();
+        assert_eq!(ids, stored[..3]);
+
+        let last = first.last().unwrap();
+        let second = store
+            .query(EventQuery::new(3).with_before(last.timestamp, last.id))
+            .await
+            .unwrap();
+        let ids: Vec<_> = second.iter().map(|e| e.id).collect();
+        assert_eq!(ids, stored[3..]);
+    }
+
+    #[tokio::test]
+    async fn should_query_events_in_time_range() {
+        let (store, _) = setup().await;
+        let start = chrono::Utc::now();
+        for offset in [-10, 0, 10] {
+            let mut event = test_event(None);
+            event.timestamp = start + chrono::Duration::seconds(offset);
+            store.store(event).await.unwrap();
+        }
+
+        let events = store
+            .query(
+                EventQuery::new(10)
+                    .with_from(start)
+                    .with_to(start + chrono::Duration::seconds(10)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].timestamp, start);
+    }
+
+    #[tokio::test]
+    async fn should_seek_an_index_for_every_event_query() {
+        let (store, entity_id) = setup().await;
+        let queries = [
+            (EventQuery::new(10), "idx_events_timestamp"),
+            (
+                EventQuery::new(10).with_event_type(EventType::StateChanged),
+                "idx_events_event_type",
+            ),
+            (
+                EventQuery::new(10).with_entity_id(entity_id),
+                "idx_events_entity_id",
+            ),
+            (
+                EventQuery::new(10)
+                    .with_entity_id(entity_id)
+                    .with_event_type(EventType::StateChanged),
+                "idx_events_entity_type",
+            ),
+            (
+                EventQuery::new(10).with_correlation_id(EventId::new()),
+                "idx_events_correlation_id",
+            ),
+        ];
+
+        for (query, index) in queries {
+            let plan = query_plan(store.pools.reader(), select_events(&query).sql()).await;
+            assert!(plan.contains(&format!("USING INDEX {index}")), "{plan}");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_seek_the_page_bound_in_the_timestamp_index() {
+        let (store, _) = setup().await;
+        let query = EventQuery::new(10).with_before(chrono::Utc::now(), EventId::new());
+
+        let plan = query_plan(store.pools.reader(), select_events(&query).sql()).await;
+        assert!(
+            plan.contains("idx_events_timestamp ((timestamp,id)<(?,?))"),
+            "{plan}"
+        );
+        assert!(!plan.contains("TEMP B-TREE"), "{plan}");
+
+        let plan = query_plan(store.pools.reader(), SELECT_IN_RANGE_AFTER).await;
+        assert!(plan.contains("USING INDEX idx_events_timestamp"), "{plan}");
+        assert!(!plan.contains("TEMP B-TREE"), "{plan}");
+    }
 }
@@ -0,0 +1,216 @@
+//! `SQLite` implementation of [`GroupRepository`].
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::GroupRepository;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::group::{Group, GroupKind};
+use minihub_domain::id::{EntityId, GroupId};
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+struct Wrapper(Group);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: uuid::Uuid = row.try_get("id")?;
+        let name: String = row.try_get("name")?;
+        let kind_str: String = row.try_get("kind")?;
+        let entity_id: uuid::Uuid = row.try_get("entity_id")?;
+        let members_json: String = row.try_get("members")?;
+
+        let kind: GroupKind = serde_json::from_str(&format!("\"{kind_str}\""))
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+        let members: Vec<EntityId> = serde_json::from_str(&members_json)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+
+        Ok(Self(Group {
+            id: GroupId::from_uuid(id),
+            name,
+            kind,
+            entity_id: EntityId::from_uuid(entity_id),
+            members,
+        }))
+    }
+}
+
+const INSERT: &str = r"
+    INSERT INTO groups (id, name, kind, entity_id, members)
+    VALUES (?, ?, ?, ?, ?)
+";
+
+const SELECT_BY_ID: &str = r"
+    SELECT * FROM groups
+    WHERE id = ?
+";
+
+const SELECT_ALL: &str = r"
+    SELECT * FROM groups
+    ORDER BY name
+";
+
+const UPDATE: &str = r"
+    UPDATE groups
+    SET name = ?, kind = ?, entity_id = ?, members = ?
+    WHERE id = ?
+";
+
+const DELETE: &str = r"
+    DELETE FROM groups
+    WHERE id = ?
+";
+
+/// `SQLite`-backed group repository.
+pub struct SqliteGroupRepository {
+    pools: Pools,
+}
+
+impl SqliteGroupRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl GroupRepository for SqliteGroupRepository {
+    async fn create(&self, group: Group) -> Result<Group, MiniHubError> {
+        let members_json = serde_json::to_string(&group.members).map_err(StorageError::from)?;
+
+        sqlx::query(INSERT)
+            .bind(group.id.as_uuid())
+            .bind(&group.name)
+            .bind(group.kind.domain())
+            .bind(group.entity_id.as_uuid())
+            .bind(&members_json)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(group)
+    }
+
+    async fn get_by_id(&self, id: GroupId) -> Result<Option<Group>, MiniHubError> {
+        let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
+            .bind(id.as_uuid())
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(row.map(|w| w.0))
+    }
+
+    async fn get_all(&self) -> Result<Vec<Group>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn update(&self, group: Group) -> Result<Group, MiniHubError> {
+        let members_json = serde_json::to_string(&group.members).map_err(StorageError::from)?;
+
+        sqlx::query(UPDATE)
+            .bind(&group.name)
+            .bind(group.kind.domain())
+            .bind(group.entity_id.as_uuid())
+            .bind(&members_json)
+            .bind(group.id.as_uuid())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(group)
+    }
+
+    async fn delete(&self, id: GroupId) -> Result<(), MiniHubError> {
+        sqlx::query(DELETE)
+            .bind(id.as_uuid())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+
+    async fn setup() -> SqliteGroupRepository {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        SqliteGroupRepository::new(db.pool().clone())
+    }
+
+    fn valid_group(name: &str) -> Group {
+        Group::builder()
+            .name(name)
+            .kind(GroupKind::Switch)
+            .member(EntityId::new())
+            .member(EntityId::new())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_create_and_retrieve_group() {
+        let repo = setup().await;
+        let group = valid_group("Plugs");
+
+        repo.create(group.clone()).await.unwrap();
+        let fetched = repo.get_by_id(group.id).await.unwrap().unwrap();
+
+        assert_eq!(fetched, group);
+    }
+
+    #[tokio::test]
+    async fn should_return_none_when_group_not_found() {
+        let repo = setup().await;
+        let result = repo.get_by_id(GroupId::new()).await.unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_list_groups_ordered_by_name() {
+        let repo = setup().await;
+        repo.create(valid_group("Upstairs")).await.unwrap();
+        repo.create(valid_group("Kitchen")).await.unwrap();
+
+        let all = repo.get_all().await.unwrap();
+        let names: Vec<&str> = all.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["Kitchen", "Upstairs"]);
+    }
+
+    #[tokio::test]
+    async fn should_update_group() {
+        let repo = setup().await;
+        let mut group = valid_group("Plugs");
+        repo.create(group.clone()).await.unwrap();
+
+        group.name = "Outlets".to_string();
+        group.members.truncate(1);
+        repo.update(group.clone()).await.unwrap();
+
+        let fetched = repo.get_by_id(group.id).await.unwrap().unwrap();
+        assert_eq!(fetched.name, "Outlets");
+        assert_eq!(fetched.members.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_delete_group() {
+        let repo = setup().await;
+        let group = valid_group("Plugs");
+        let id = group.id;
+        repo.create(group).await.unwrap();
+
+        repo.delete(id).await.unwrap();
+
+        assert!(repo.get_by_id(id).await.unwrap().is_none());
+    }
+}
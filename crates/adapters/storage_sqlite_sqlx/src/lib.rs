@@ -6,6 +6,8 @@
 //! - Implement the repository port traits defined in `minihub-app::ports::storage`
 //! - Manage `SQLite` connection pool lifecycle
 //! - Run database migrations
+//! - Write rotated database snapshots
+//! - Encrypt secrets at rest
 //! - Map between domain types and database rows
 //!
 //! ## Dependency rule
@@ -13,19 +15,39 @@
 //! The `app` and `domain` crates must never reference this adapter.
 
 mod area_repo;
+mod audit_repo;
 mod automation_repo;
+mod automation_run_repo;
+mod backup;
 mod device_repo;
+mod discovery_repo;
+mod energy_usage_repo;
 mod entity_history_repo;
 mod entity_repo;
 mod error;
 mod event_store;
+mod group_repo;
 mod pool;
+mod report_repo;
+mod scene_repo;
+mod secrets_repo;
+mod settings_repo;
 
 pub use area_repo::SqliteAreaRepository;
+pub use audit_repo::SqliteAuditRepository;
 pub use automation_repo::SqliteAutomationRepository;
+pub use automation_run_repo::SqliteAutomationRunRepository;
+pub use backup::{BackupFile, BackupRotation};
 pub use device_repo::SqliteDeviceRepository;
+pub use discovery_repo::SqliteDiscoveryRepository;
+pub use energy_usage_repo::SqliteEnergyUsageRepository;
 pub use entity_history_repo::SqliteEntityHistoryRepository;
 pub use entity_repo::SqliteEntityRepository;
 pub use error::StorageError;
 pub use event_store::SqliteEventStore;
-pub use pool::{Config, Database};
+pub use group_repo::SqliteGroupRepository;
+pub use pool::{Config, Database, JournalMode, Pools, Synchronous};
+pub use report_repo::SqliteReportRepository;
+pub use scene_repo::SqliteSceneRepository;
+pub use secrets_repo::{SecretKey, SqliteSecretsRepository};
+pub use settings_repo::SqliteSettingsRepository;
@@ -1,46 +1,239 @@
 //! `SQLite` connection pool setup and migration runner.
 
+use std::path::Path;
 use std::str::FromStr;
+use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
 
 use crate::error::StorageError;
 
+/// `SQLite` journal mode, applied with `PRAGMA journal_mode` on connect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalMode {
+    /// Rollback journal deleted after each transaction.
+    Delete,
+    /// Rollback journal truncated after each transaction.
+    Truncate,
+    /// Rollback journal kept, its header zeroed after each transaction.
+    Persist,
+    /// Rollback journal kept in memory.
+    Memory,
+    /// Write-ahead log: readers and the writer do not block each other.
+    #[default]
+    Wal,
+    /// No journal; a crash mid-transaction may corrupt the database.
+    Off,
+}
+
+impl From<JournalMode> for SqliteJournalMode {
+    fn from(mode: JournalMode) -> Self {
+        match mode {
+            JournalMode::Delete => Self::Delete,
+            JournalMode::Truncate => Self::Truncate,
+            JournalMode::Persist => Self::Persist,
+            JournalMode::Memory => Self::Memory,
+            JournalMode::Wal => Self::Wal,
+            JournalMode::Off => Self::Off,
+        }
+    }
+}
+
+/// How often `SQLite` waits for writes to reach the disk, applied with
+/// `PRAGMA synchronous` on connect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Synchronous {
+    /// Never wait; a power loss may corrupt the database.
+    Off,
+    /// Wait at checkpoints only; safe with [`JournalMode::Wal`], where a
+    /// power loss may only roll back the last transactions.
+    #[default]
+    Normal,
+    /// Wait at every commit.
+    Full,
+    /// Like [`Synchronous::Full`], also syncing the journal directory.
+    Extra,
+}
+
+impl From<Synchronous> for SqliteSynchronous {
+    fn from(synchronous: Synchronous) -> Self {
+        match synchronous {
+            Synchronous::Off => Self::Off,
+            Synchronous::Normal => Self::Normal,
+            Synchronous::Full => Self::Full,
+            Synchronous::Extra => Self::Extra,
+        }
+    }
+}
+
 /// Configuration for the `SQLite` storage adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Config {
     /// `SQLite` connection URL (e.g. `sqlite:minihub.db` or `sqlite::memory:`).
     pub database_url: String,
+    /// Maximum number of connections serving reads. Writes always go
+    /// through a single connection.
+    pub max_connections: u32,
+    /// How long a connection waits for a lock held by another one before
+    /// failing with `database is locked`, in milliseconds.
+    pub busy_timeout_ms: u64,
+    /// Journal mode of the database.
+    pub journal_mode: JournalMode,
+    /// Synchronous setting of every connection.
+    pub synchronous: Synchronous,
 }
 
 impl Config {
+    /// Default number of connections serving reads.
+    pub const DEFAULT_MAX_CONNECTIONS: u32 = 8;
+    /// Default busy timeout, in milliseconds.
+    pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5_000;
+
+    /// Configuration for `database_url` with the default pool settings: a
+    /// write-ahead log and [`Synchronous::Normal`].
+    #[must_use]
+    pub fn new(database_url: impl Into<String>) -> Self {
+        Self {
+            database_url: database_url.into(),
+            max_connections: Self::DEFAULT_MAX_CONNECTIONS,
+            busy_timeout_ms: Self::DEFAULT_BUSY_TIMEOUT_MS,
+            journal_mode: JournalMode::default(),
+            synchronous: Synchronous::default(),
+        }
+    }
+
     /// Read configuration from environment variables.
     ///
     /// # Errors
     ///
     /// Returns an error if `MINIHUB_DATABASE_URL` is not set.
     pub fn from_env() -> Result<Self, std::env::VarError> {
-        Ok(Self {
-            database_url: std::env::var("MINIHUB_DATABASE_URL")?,
-        })
+        Ok(Self::new(std::env::var("MINIHUB_DATABASE_URL")?))
     }
 
     /// Build a [`Database`] from this configuration.
     ///
-    /// Creates the connection pool, creates the database file if missing,
+    /// Creates the connection pools, creates the database file if missing,
     /// and runs all pending migrations.
     ///
     /// # Errors
     ///
     /// Returns [`StorageError`] if the connection or migrations fail.
     pub async fn build(self) -> Result<Database, StorageError> {
-        Database::initialize(&self.database_url).await
+        Database::initialize(&self).await
+    }
+
+    /// Connection options applying the configured pragmas.
+    fn connect_options(&self) -> Result<SqliteConnectOptions, StorageError> {
+        Ok(SqliteConnectOptions::from_str(&self.database_url)?
+            .create_if_missing(true)
+            .busy_timeout(Duration::from_millis(self.busy_timeout_ms))
+            .journal_mode(self.journal_mode.into())
+            .synchronous(self.synchronous.into()))
+    }
+
+    /// Whether the database only lives in memory.
+    fn is_in_memory(&self) -> bool {
+        self.database_url.contains(":memory:") || self.database_url.contains("mode=memory")
+    }
+
+    /// Create the database from a copy made by [`Database::backup_to`],
+    /// then run the migrations the copy predates.
+    ///
+    /// The copy is only read, never modified. The configured
+    /// database must not exist yet: restoring never overwrites data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] if the copy cannot be read, the database
+    /// already exists, or the migrations fail.
+    pub async fn restore_from(self, backup: impl AsRef<Path>) -> Result<Database, StorageError> {
+        let target = SqliteConnectOptions::from_str(&self.database_url)?
+            .get_filename()
+            .to_path_buf();
+        // `VACUUM INTO` opens its output with the flags of the source
+        // connection, so the copy is opened read-write with create allowed;
+        // check it exists first so a wrong path is not restored as empty
+        std::fs::metadata(backup.as_ref())?;
+        let options = SqliteConnectOptions::new()
+            .filename(backup.as_ref())
+            .create_if_missing(true);
+        let source = SqlitePool::connect_with(options).await?;
+        let copied = sqlx::query("VACUUM INTO ?")
+            .bind(target.to_string_lossy())
+            .execute(&source)
+            .await;
+        source.close().await;
+        copied?;
+        self.build().await
     }
 }
 
-/// Holds the `SQLite` connection pool and provides access to it.
+/// Connection pools of a database: a single writer, so writes queue in the
+/// pool instead of failing on the database lock, and readers that never
+/// wait for it.
+#[derive(Debug, Clone)]
+pub struct Pools {
+    writer: SqlitePool,
+    /// Separate pool serving reads, `None` when the writer serves them.
+    reader: Option<SqlitePool>,
+}
+
+impl Pools {
+    /// Pool serving queries that only read.
+    #[must_use]
+    pub fn reader(&self) -> &SqlitePool {
+        self.reader.as_ref().unwrap_or(&self.writer)
+    }
+
+    /// Pool serving queries that write.
+    #[must_use]
+    pub fn writer(&self) -> &SqlitePool {
+        &self.writer
+    }
+
+    /// Every distinct pool.
+    fn all(&self) -> impl Iterator<Item = &SqlitePool> {
+        std::iter::once(&self.writer).chain(&self.reader)
+    }
+}
+
+/// The steps of the `EXPLAIN QUERY PLAN` of `sql`, one per line, with
+/// every parameter bound to an empty string.
+#[cfg(test)]
+pub(crate) async fn query_plan(pool: &SqlitePool, sql: &str) -> String {
+    use sqlx::Row;
+
+    let explain = format!("EXPLAIN QUERY PLAN {sql}");
+    let mut query = sqlx::query(&explain);
+    for _ in 0..sql.matches('?').count() {
+        query = query.bind("");
+    }
+    let rows = query.fetch_all(pool).await.unwrap();
+    rows.iter()
+        .map(|row| row.get::<String, _>("detail"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A single pool serving both reads and writes.
+impl From<SqlitePool> for Pools {
+    fn from(writer: SqlitePool) -> Self {
+        Self {
+            writer,
+            reader: None,
+        }
+    }
+}
+
+/// Holds the `SQLite` connection pools and provides access to them.
+#[derive(Clone)]
 pub struct Database {
-    pool: SqlitePool,
+    pools: Pools,
 }
 
 impl Database {
@@ -49,20 +242,69 @@ impl Database {
     /// # Errors
     ///
     /// Returns [`StorageError`] if the connection or migrations fail.
-    async fn initialize(database_url: &str) -> Result<Self, StorageError> {
-        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+    async fn initialize(config: &Config) -> Result<Self, StorageError> {
+        let options = config.connect_options()?;
 
-        let pool = SqlitePool::connect_with(options).await?;
+        let writer = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.clone())
+            .await?;
 
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        sqlx::migrate!("./migrations").run(&writer).await?;
 
-        Ok(Self { pool })
+        // An in-memory database is dropped with its last connection and
+        // cannot use a write-ahead log: keep a single pool
+        let reader = if config.is_in_memory() {
+            None
+        } else {
+            let reader = SqlitePoolOptions::new()
+                .max_connections(config.max_connections)
+                .connect_with(options.pragma("query_only", "ON"))
+                .await?;
+            Some(reader)
+        };
+        Ok(Self {
+            pools: Pools { writer, reader },
+        })
     }
 
-    /// Borrow the underlying connection pool.
+    /// Borrow the pool serving writes, which may also serve reads, e.g. for
+    /// maintenance tasks.
     #[must_use]
     pub fn pool(&self) -> &SqlitePool {
-        &self.pool
+        &self.pools.writer
+    }
+
+    /// Borrow the reader and writer pools, to hand to the repositories.
+    #[must_use]
+    pub fn pools(&self) -> &Pools {
+        &self.pools
+    }
+
+    /// Number of open connections, idle or in use.
+    #[must_use]
+    pub fn connections(&self) -> u32 {
+        self.pools.all().map(SqlitePool::size).sum()
+    }
+
+    /// Number of open connections not currently in use.
+    #[must_use]
+    pub fn idle_connections(&self) -> usize {
+        self.pools.all().map(SqlitePool::num_idle).sum()
+    }
+
+    /// Write a consistent copy of the database to `path`, which must not
+    /// exist yet. Safe to call while the database is in use.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] if the copy cannot be written.
+    pub async fn backup_to(&self, path: impl AsRef<Path>) -> Result<(), StorageError> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.as_ref().to_string_lossy())
+            .execute(&self.pools.writer)
+            .await?;
+        Ok(())
     }
 }
 
@@ -72,9 +314,7 @@ mod tests {
 
     #[tokio::test]
     async fn should_create_pool_and_run_migrations_when_using_memory_db() {
-        let config = Config {
-            database_url: "sqlite::memory:".to_string(),
-        };
+        let config = Config::new("sqlite::memory:".to_string());
         let db = config.build().await.unwrap();
 
         // Verify tables exist by querying sqlite_master
@@ -91,5 +331,140 @@ mod tests {
         assert!(names.contains(&"entities"), "missing entities table");
         assert!(names.contains(&"events"), "missing events table");
         assert!(names.contains(&"automations"), "missing automations table");
+        assert!(
+            names.contains(&"automation_runs"),
+            "missing automation_runs table"
+        );
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("minihub_pool_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn should_restore_backup_into_new_database() {
+        let original = temp_path("original.db");
+        let backup = temp_path("backup.db");
+        let restored = temp_path("restored.db");
+        let db = Config::new(format!("sqlite:{}?mode=rwc", original.display()))
+            .build()
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO areas (id, name) VALUES (?, ?)")
+            .bind(uuid::Uuid::new_v4())
+            .bind("Kitchen")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        db.backup_to(&backup).await.unwrap();
+        let db = Config::new(format!("sqlite:{}", restored.display()))
+            .restore_from(&backup)
+            .await
+            .unwrap();
+
+        let (name,): (String,) = sqlx::query_as("SELECT name FROM areas")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(name, "Kitchen");
+    }
+
+    #[tokio::test]
+    async fn should_refuse_to_restore_over_existing_database() {
+        let backup = temp_path("existing_backup.db");
+        let existing = temp_path("existing.db");
+        let db = Config::new(format!("sqlite:{}?mode=rwc", existing.display()))
+            .build()
+            .await
+            .unwrap();
+        db.backup_to(&backup).await.unwrap();
+
+        let result = Config::new(format!("sqlite:{}", existing.display()))
+            .restore_from(&backup)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_apply_pragmas_on_every_connection() {
+        let path = temp_path("pragmas.db");
+        let db = Config {
+            busy_timeout_ms: 1_234,
+            ..Config::new(format!("sqlite:{}", path.display()))
+        }
+        .build()
+        .await
+        .unwrap();
+
+        for pool in [db.pools().reader(), db.pools().writer()] {
+            let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode")
+                .fetch_one(pool)
+                .await
+                .unwrap();
+            let (busy_timeout,): (i64,) = sqlx::query_as("PRAGMA busy_timeout")
+                .fetch_one(pool)
+                .await
+                .unwrap();
+            let (synchronous,): (i64,) = sqlx::query_as("PRAGMA synchronous")
+                .fetch_one(pool)
+                .await
+                .unwrap();
+            assert_eq!(journal_mode, "wal");
+            assert_eq!(busy_timeout, 1_234);
+            assert_eq!(synchronous, 1, "expected synchronous = NORMAL");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reject_writes_on_reader_pool() {
+        let path = temp_path("reader.db");
+        let db = Config::new(format!("sqlite:{}", path.display()))
+            .build()
+            .await
+            .unwrap();
+        let insert = "INSERT INTO areas (id, name) VALUES (?, ?)";
+
+        let on_reader = sqlx::query(insert)
+            .bind(uuid::Uuid::new_v4())
+            .bind("Kitchen")
+            .execute(db.pools().reader())
+            .await;
+        sqlx::query(insert)
+            .bind(uuid::Uuid::new_v4())
+            .bind("Office")
+            .execute(db.pools().writer())
+            .await
+            .unwrap();
+
+        assert!(on_reader.is_err());
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM areas")
+            .fetch_one(db.pools().reader())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn should_share_one_pool_when_in_memory() {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+
+        sqlx::query("INSERT INTO areas (id, name) VALUES (?, ?)")
+            .bind(uuid::Uuid::new_v4())
+            .bind("Kitchen")
+            .execute(db.pools().writer())
+            .await
+            .unwrap();
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM areas")
+            .fetch_one(db.pools().reader())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
     }
 }
@@ -0,0 +1,251 @@
+//! `SQLite` implementation of [`ReportRepository`].
+
+use std::collections::BTreeMap;
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::ReportRepository;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::report::{Overview, StorageStats};
+use minihub_domain::time::Timestamp;
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+/// Row of the `COUNTS` query. `entities_by_state` and `since` are filled in
+/// separately.
+struct Wrapper(Overview);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let count = |column: &str| -> Result<u64, sqlx::Error> {
+            let value: i64 = row.try_get(column)?;
+            Ok(value.unsigned_abs())
+        };
+
+        Ok(Self(Overview {
+            entities: count("entities")?,
+            devices: count("devices")?,
+            areas: count("areas")?,
+            automations: count("automations")?,
+            enabled_automations: count("enabled_automations")?,
+            events_since: count("events_since")?,
+            ..Overview::default()
+        }))
+    }
+}
+
+const COUNTS: &str = r"
+    SELECT
+        (SELECT COUNT(*) FROM entities) AS entities,
+        (SELECT COUNT(*) FROM devices) AS devices,
+        (SELECT COUNT(*) FROM areas) AS areas,
+        (SELECT COUNT(*) FROM automations) AS automations,
+        (SELECT COUNT(*) FROM automations WHERE enabled = 1) AS enabled_automations,
+        (SELECT COUNT(*) FROM events WHERE timestamp >= ?) AS events_since
+";
+
+const ENTITIES_BY_STATE: &str = "SELECT state, COUNT(*) FROM entities GROUP BY state";
+
+/// Size of the main database file, excluding the write-ahead log, and the
+/// row counts of the tables growing with activity.
+const STORAGE_STATS: &str = r"
+    SELECT
+        (SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size())
+            AS database_bytes,
+        (SELECT COUNT(*) FROM entities) AS entities,
+        (SELECT COUNT(*) FROM events) AS events,
+        (SELECT COUNT(*) FROM entity_history) AS entity_history
+";
+
+/// `SQLite`-backed report repository.
+pub struct SqliteReportRepository {
+    pools: Pools,
+}
+
+impl SqliteReportRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl ReportRepository for SqliteReportRepository {
+    async fn overview(&self, since: Timestamp) -> Result<Overview, MiniHubError> {
+        let Wrapper(mut overview) = sqlx::query_as(COUNTS)
+            .bind(since.to_rfc3339())
+            .fetch_one(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        let states: Vec<(String, i64)> = sqlx::query_as(ENTITIES_BY_STATE)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        overview.entities_by_state = states
+            .into_iter()
+            .map(|(state, count)| (state, count.unsigned_abs()))
+            .collect::<BTreeMap<_, _>>();
+        overview.since = since;
+
+        Ok(overview)
+    }
+
+    async fn storage_stats(&self) -> Result<StorageStats, MiniHubError> {
+        let (database_bytes, entities, events, entity_history): (i64, i64, i64, i64) =
+            sqlx::query_as(STORAGE_STATS)
+                .fetch_one(self.pools.reader())
+                .await
+                .map_err(StorageError::from)?;
+
+        Ok(StorageStats {
+            database_bytes: database_bytes.unsigned_abs(),
+            entities: entities.unsigned_abs(),
+            events: events.unsigned_abs(),
+            entity_history: entity_history.unsigned_abs(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+    use crate::{
+        SqliteAutomationRepository, SqliteDeviceRepository, SqliteEntityRepository,
+        SqliteEventStore,
+    };
+    use chrono::Duration;
+    use minihub_app::ports::{
+        AutomationRepository, DeviceRepository, EntityRepository, EventStore,
+    };
+    use minihub_domain::automation::{Action, Automation};
+    use minihub_domain::device::Device;
+    use minihub_domain::entity::{Entity, EntityState};
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::time::now;
+    use sqlx::SqlitePool;
+
+    async fn setup() -> (SqliteReportRepository, SqlitePool) {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        let pool = db.pool().clone();
+        (SqliteReportRepository::new(pool.clone()), pool)
+    }
+
+    #[tokio::test]
+    async fn should_return_empty_overview_when_database_is_empty() {
+        let (repo, _) = setup().await;
+        let since = now() - Duration::hours(24);
+
+        let overview = repo.overview(since).await.unwrap();
+
+        assert_eq!(overview.entities, 0);
+        assert_eq!(overview.devices, 0);
+        assert_eq!(overview.events_since, 0);
+        assert!(overview.entities_by_state.is_empty());
+        assert_eq!(overview.since, since);
+    }
+
+    #[tokio::test]
+    async fn should_aggregate_counts_across_tables() {
+        let (repo, pool) = setup().await;
+
+        let device = Device::builder()
+            .name("Hub")
+            .integration("virtual")
+            .unique_id("hub")
+            .build()
+            .unwrap();
+        let device_id = device.id;
+        SqliteDeviceRepository::new(pool.clone())
+            .create(device)
+            .await
+            .unwrap();
+
+        let entities = SqliteEntityRepository::new(pool.clone());
+        for (name, state) in [
+            ("light.a", EntityState::On),
+            ("light.b", EntityState::On),
+            ("light.c", EntityState::Unavailable),
+        ] {
+            let entity = Entity::builder()
+                .device_id(device_id)
+                .entity_id(name)
+                .friendly_name(name)
+                .state(state)
+                .build()
+                .unwrap();
+            entities.create(entity).await.unwrap();
+        }
+
+        let automations = SqliteAutomationRepository::new(pool.clone());
+        let mut disabled = Automation::builder()
+            .name("Disabled")
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap();
+        disabled.enabled = false;
+        automations.create(disabled).await.unwrap();
+        automations
+            .create(
+                Automation::builder()
+                    .name("Enabled")
+                    .action(Action::Delay { seconds: 1 })
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let events = SqliteEventStore::new(pool);
+        let mut old = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+        old.timestamp = now() - Duration::hours(48);
+        events.store(old).await.unwrap();
+        events
+            .store(Event::new(
+                EventType::StateChanged,
+                None,
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+
+        let overview = repo.overview(now() - Duration::hours(24)).await.unwrap();
+
+        assert_eq!(overview.devices, 1);
+        assert_eq!(overview.entities, 3);
+        assert_eq!(overview.automations, 2);
+        assert_eq!(overview.enabled_automations, 1);
+        assert_eq!(overview.events_since, 1);
+        assert_eq!(overview.entities_by_state.get("on"), Some(&2));
+        assert_eq!(overview.unavailable_entities(), 1);
+    }
+    #[tokio::test]
+    async fn should_measure_database_and_count_rows_when_storage_stats_requested() {
+        let (repo, pool) = setup().await;
+        let events = SqliteEventStore::new(pool);
+        for _ in 0..2 {
+            events
+                .store(Event::new(
+                    EventType::StateChanged,
+                    None,
+                    serde_json::json!({}),
+                ))
+                .await
+                .unwrap();
+        }
+
+        let stats = repo.storage_stats().await.unwrap();
+
+        assert!(stats.database_bytes > 0);
+        assert_eq!(stats.entities, 0);
+        assert_eq!(stats.events, 2);
+        assert_eq!(stats.entity_history, 0);
+    }
+}
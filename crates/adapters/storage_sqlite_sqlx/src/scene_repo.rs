@@ -0,0 +1,213 @@
+//! `SQLite` implementation of [`SceneRepository`].
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::SceneRepository;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::SceneId;
+use minihub_domain::scene::{Scene, SceneMember};
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+struct Wrapper(Scene);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: uuid::Uuid = row.try_get("id")?;
+        let name: String = row.try_get("name")?;
+        let members_json: String = row.try_get("members")?;
+
+        let members: Vec<SceneMember> = serde_json::from_str(&members_json)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+
+        Ok(Self(Scene {
+            id: SceneId::from_uuid(id),
+            name,
+            members,
+        }))
+    }
+}
+
+const INSERT: &str = r"
+    INSERT INTO scenes (id, name, members)
+    VALUES (?, ?, ?)
+";
+
+const SELECT_BY_ID: &str = r"
+    SELECT * FROM scenes
+    WHERE id = ?
+";
+
+const SELECT_ALL: &str = r"
+    SELECT * FROM scenes
+    ORDER BY name
+";
+
+const UPDATE: &str = r"
+    UPDATE scenes
+    SET name = ?, members = ?
+    WHERE id = ?
+";
+
+const DELETE: &str = r"
+    DELETE FROM scenes
+    WHERE id = ?
+";
+
+/// `SQLite`-backed scene repository.
+pub struct SqliteSceneRepository {
+    pools: Pools,
+}
+
+impl SqliteSceneRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl SceneRepository for SqliteSceneRepository {
+    async fn create(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        let members_json = serde_json::to_string(&scene.members).map_err(StorageError::from)?;
+
+        sqlx::query(INSERT)
+            .bind(scene.id.as_uuid())
+            .bind(&scene.name)
+            .bind(&members_json)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(scene)
+    }
+
+    async fn get_by_id(&self, id: SceneId) -> Result<Option<Scene>, MiniHubError> {
+        let row: Option<Wrapper> = sqlx::query_as(SELECT_BY_ID)
+            .bind(id.as_uuid())
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(row.map(|w| w.0))
+    }
+
+    async fn get_all(&self) -> Result<Vec<Scene>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(rows.into_iter().map(|w| w.0).collect())
+    }
+
+    async fn update(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        let members_json = serde_json::to_string(&scene.members).map_err(StorageError::from)?;
+
+        sqlx::query(UPDATE)
+            .bind(&scene.name)
+            .bind(&members_json)
+            .bind(scene.id.as_uuid())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(scene)
+    }
+
+    async fn delete(&self, id: SceneId) -> Result<(), MiniHubError> {
+        sqlx::query(DELETE)
+            .bind(id.as_uuid())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Config;
+    use minihub_domain::entity::{AttributeValue, EntityState};
+    use minihub_domain::id::EntityId;
+
+    async fn setup() -> SqliteSceneRepository {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        SqliteSceneRepository::new(db.pool().clone())
+    }
+
+    fn valid_scene(name: &str) -> Scene {
+        Scene::builder()
+            .name(name)
+            .member(
+                SceneMember::new(EntityId::new(), EntityState::On)
+                    .with_attribute("brightness", AttributeValue::Int(30)),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_create_and_retrieve_scene() {
+        let repo = setup().await;
+        let scene = valid_scene("Movie night");
+        let id = scene.id;
+
+        repo.create(scene.clone()).await.unwrap();
+        let fetched = repo.get_by_id(id).await.unwrap().unwrap();
+
+        assert_eq!(fetched.name, "Movie night");
+        assert_eq!(fetched.members, scene.members);
+    }
+
+    #[tokio::test]
+    async fn should_return_none_when_scene_not_found() {
+        let repo = setup().await;
+        let result = repo.get_by_id(SceneId::new()).await.unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_list_scenes_ordered_by_name() {
+        let repo = setup().await;
+        repo.create(valid_scene("Morning")).await.unwrap();
+        repo.create(valid_scene("Evening")).await.unwrap();
+
+        let all = repo.get_all().await.unwrap();
+        let names: Vec<&str> = all.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Evening", "Morning"]);
+    }
+
+    #[tokio::test]
+    async fn should_update_scene() {
+        let repo = setup().await;
+        let mut scene = valid_scene("Movie night");
+        repo.create(scene.clone()).await.unwrap();
+
+        scene.name = "Cinema".to_string();
+        scene
+            .members
+            .push(SceneMember::new(EntityId::new(), EntityState::Off));
+        repo.update(scene.clone()).await.unwrap();
+
+        let fetched = repo.get_by_id(scene.id).await.unwrap().unwrap();
+        assert_eq!(fetched.name, "Cinema");
+        assert_eq!(fetched.members.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_delete_scene() {
+        let repo = setup().await;
+        let scene = valid_scene("Movie night");
+        let id = scene.id;
+        repo.create(scene).await.unwrap();
+
+        repo.delete(id).await.unwrap();
+
+        assert!(repo.get_by_id(id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,343 @@
+//! `SQLite` implementation of [`SecretsRepository`], encrypting every value
+//! with ChaCha20-Poly1305 under a [`SecretKey`] held outside the database.
+
+use std::fmt;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
+
+use minihub_app::ports::SecretsRepository;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::secret::{SecretInfo, SecretValue};
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+/// Length of a key, in bytes.
+const KEY_LEN: usize = 32;
+
+/// Length of a nonce, in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Key encrypting the stored secrets. Its `Debug` output is redacted.
+#[derive(Clone)]
+pub struct SecretKey(Key);
+
+impl SecretKey {
+    /// A new random key.
+    #[must_use]
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng))
+    }
+
+    /// Parse a key written as 64 hexadecimal characters, surrounding
+    /// whitespace ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::InvalidSecretKey`] otherwise.
+    pub fn from_hex(hex: &str) -> Result<Self, StorageError> {
+        let mut bytes = [0_u8; KEY_LEN];
+        hex::decode_to_slice(hex.trim(), &mut bytes).map_err(|_| StorageError::InvalidSecretKey)?;
+        Ok(Self(bytes.into()))
+    }
+
+    /// The key written as 64 hexadecimal characters.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Read the key stored in `path`, or generate one and write it there,
+    /// readable by the owner only, when the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError::Io`] if the file cannot be read or written,
+    /// or [`StorageError::InvalidSecretKey`] if it holds something else than
+    /// a key.
+    pub fn load_or_create(path: &Path) -> Result<Self, StorageError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::from_hex(&content),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let key = Self::generate();
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                write_private(path, &key.to_hex())?;
+                Ok(key)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(***)")
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    std::fs::write(path, content)
+}
+
+/// Wrapper for converting database rows into domain types without polluting
+/// domain structs with database concerns.
+struct Wrapper(SecretInfo);
+
+impl<'r> FromRow<'r, SqliteRow> for Wrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let name: String = row.try_get("name")?;
+        let updated_at_str: String = row.try_get("updated_at")?;
+
+        let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?
+            .to_utc();
+
+        Ok(Self(SecretInfo { name, updated_at }))
+    }
+}
+
+const SELECT_BY_NAME: &str = "SELECT nonce, ciphertext FROM secrets WHERE name = ?";
+
+const SELECT_ALL: &str = "SELECT name, updated_at FROM secrets ORDER BY name ASC";
+
+const UPSERT: &str = r"
+    INSERT INTO secrets (name, nonce, ciphertext, updated_at) VALUES (?, ?, ?, ?)
+    ON CONFLICT(name) DO UPDATE
+    SET nonce = excluded.nonce, ciphertext = excluded.ciphertext, updated_at = excluded.updated_at
+";
+
+const DELETE: &str = "DELETE FROM secrets WHERE name = ?";
+
+/// `SQLite`-backed secrets repository. Each value is encrypted with a fresh
+/// random nonce, its name authenticated alongside.
+pub struct SqliteSecretsRepository {
+    pools: Pools,
+    cipher: ChaCha20Poly1305,
+}
+
+impl SqliteSecretsRepository {
+    /// Create a new repository encrypting with `key`, using the given
+    /// connection pools, or a single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>, key: &SecretKey) -> Self {
+        Self {
+            pools: pools.into(),
+            cipher: ChaCha20Poly1305::new(&key.0),
+        }
+    }
+
+    fn encrypt(&self, name: &str, value: &SecretValue) -> Result<(Vec<u8>, Vec<u8>), StorageError> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: value.expose().as_bytes(),
+            aad: name.as_bytes(),
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| StorageError::Crypto(name.to_string()))?;
+        Ok((nonce.to_vec(), ciphertext))
+    }
+
+    fn decrypt(
+        &self,
+        name: &str,
+        nonce: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<SecretValue, StorageError> {
+        let failed = || StorageError::Crypto(name.to_string());
+        if nonce.len() != NONCE_LEN {
+            return Err(failed());
+        }
+        let payload = Payload {
+            msg: ciphertext,
+            aad: name.as_bytes(),
+        };
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| failed())?;
+        let value = String::from_utf8(plaintext).map_err(|_| failed())?;
+        Ok(SecretValue::new(value))
+    }
+}
+
+impl SecretsRepository for SqliteSecretsRepository {
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>, MiniHubError> {
+        let row: Option<(Vec<u8>, Vec<u8>)> = sqlx::query_as(SELECT_BY_NAME)
+            .bind(name)
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        let Some((nonce, ciphertext)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(self.decrypt(name, &nonce, &ciphertext)?))
+    }
+
+    async fn list(&self) -> Result<Vec<SecretInfo>, MiniHubError> {
+        let rows: Vec<Wrapper> = sqlx::query_as(SELECT_ALL)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(rows.into_iter().map(|Wrapper(info)| info).collect())
+    }
+
+    async fn put(&self, name: &str, value: &SecretValue) -> Result<SecretInfo, MiniHubError> {
+        let (nonce, ciphertext) = self.encrypt(name, value)?;
+        let updated_at = minihub_domain::time::now();
+
+        sqlx::query(UPSERT)
+            .bind(name)
+            .bind(nonce)
+            .bind(ciphertext)
+            .bind(updated_at.to_rfc3339())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(SecretInfo {
+            name: name.to_string(),
+            updated_at,
+        })
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool, MiniHubError> {
+        let result = sqlx::query(DELETE)
+            .bind(name)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::{Config, Database};
+
+    async fn database() -> Database {
+        Config::new("sqlite::memory:").build().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_return_stored_value_and_list_names_only() {
+        let db = database().await;
+        let repo = SqliteSecretsRepository::new(db.pool().clone(), &SecretKey::generate());
+
+        repo.put("telegram.bot_token", &SecretValue::new("123:abc"))
+            .await
+            .unwrap();
+        repo.put("mqtt.password", &SecretValue::new("hunter2"))
+            .await
+            .unwrap();
+
+        let value = repo.get("telegram.bot_token").await.unwrap().unwrap();
+        assert_eq!(value.expose(), "123:abc");
+        let names: Vec<_> = repo
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.name)
+            .collect();
+        assert_eq!(names, ["mqtt.password", "telegram.bot_token"]);
+    }
+
+    #[tokio::test]
+    async fn should_not_store_value_in_clear() {
+        let db = database().await;
+        let repo = SqliteSecretsRepository::new(db.pool().clone(), &SecretKey::generate());
+
+        repo.put("mqtt.password", &SecretValue::new("hunter2"))
+            .await
+            .unwrap();
+
+        let ciphertext: Vec<u8> =
+            sqlx::query_scalar("SELECT ciphertext FROM secrets WHERE name = 'mqtt.password'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert!(
+            !ciphertext
+                .windows("hunter2".len())
+                .any(|window| window == b"hunter2")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_to_read_secret_with_another_key() {
+        let db = database().await;
+        SqliteSecretsRepository::new(db.pool().clone(), &SecretKey::generate())
+            .put("mqtt.password", &SecretValue::new("hunter2"))
+            .await
+            .unwrap();
+
+        let repo = SqliteSecretsRepository::new(db.pool().clone(), &SecretKey::generate());
+
+        assert!(repo.get("mqtt.password").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_report_whether_deleted_secret_existed() {
+        let db = database().await;
+        let repo = SqliteSecretsRepository::new(db.pool().clone(), &SecretKey::generate());
+        repo.put("mqtt.password", &SecretValue::new("hunter2"))
+            .await
+            .unwrap();
+
+        assert!(repo.delete("mqtt.password").await.unwrap());
+        assert!(!repo.delete("mqtt.password").await.unwrap());
+        assert!(repo.get("mqtt.password").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn should_round_trip_key_through_hex_and_reject_malformed_keys() {
+        let key = SecretKey::generate();
+
+        let parsed = SecretKey::from_hex(&format!("{}\n", key.to_hex())).unwrap();
+
+        assert_eq!(parsed.to_hex(), key.to_hex());
+        assert!(SecretKey::from_hex("abcd").is_err());
+        assert!(SecretKey::from_hex(&"zz".repeat(KEY_LEN)).is_err());
+    }
+
+    #[test]
+    fn should_create_keyfile_once_and_read_it_back() {
+        let path = std::env::temp_dir().join(format!(
+            "minihub_secret_key_{}/secret.key",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+        let created = SecretKey::load_or_create(&path).unwrap();
+        let loaded = SecretKey::load_or_create(&path).unwrap();
+
+        assert_eq!(created.to_hex(), loaded.to_hex());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,149 @@
+//! `SQLite` implementation of [`SettingsRepository`].
+
+use serde_json::Value;
+
+use minihub_app::ports::SettingsRepository;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::settings::Settings;
+
+use crate::error::StorageError;
+use crate::pool::Pools;
+
+const SELECT_BY_KEY: &str = "SELECT value FROM settings WHERE namespace = ? AND key = ?";
+
+const SELECT_BY_NAMESPACE: &str = "SELECT key, value FROM settings WHERE namespace = ?";
+
+const UPSERT: &str = r"
+    INSERT INTO settings (namespace, key, value, updated_at) VALUES (?, ?, ?, ?)
+    ON CONFLICT(namespace, key) DO UPDATE
+    SET value = excluded.value, updated_at = excluded.updated_at
+";
+
+const DELETE: &str = "DELETE FROM settings WHERE namespace = ? AND key = ?";
+
+/// `SQLite`-backed settings repository.
+pub struct SqliteSettingsRepository {
+    pools: Pools,
+}
+
+impl SqliteSettingsRepository {
+    /// Create a new repository using the given connection pools, or a
+    /// single pool serving both reads and writes.
+    #[must_use]
+    pub fn new(pools: impl Into<Pools>) -> Self {
+        Self {
+            pools: pools.into(),
+        }
+    }
+}
+
+impl SettingsRepository for SqliteSettingsRepository {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, MiniHubError> {
+        let value: Option<String> = sqlx::query_scalar(SELECT_BY_KEY)
+            .bind(namespace)
+            .bind(key)
+            .fetch_optional(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        Ok(Some(
+            serde_json::from_str(&value).map_err(StorageError::from)?,
+        ))
+    }
+
+    async fn list(&self, namespace: &str) -> Result<Settings, MiniHubError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(SELECT_BY_NAMESPACE)
+            .bind(namespace)
+            .fetch_all(self.pools.reader())
+            .await
+            .map_err(StorageError::from)?;
+
+        let mut settings = Settings::new();
+        for (key, value) in rows {
+            let value = serde_json::from_str(&value).map_err(StorageError::from)?;
+            settings.insert(key, value);
+        }
+        Ok(settings)
+    }
+
+    async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<(), MiniHubError> {
+        let json = serde_json::to_string(&value).map_err(StorageError::from)?;
+
+        sqlx::query(UPSERT)
+            .bind(namespace)
+            .bind(key)
+            .bind(json)
+            .bind(minihub_domain::time::now().to_rfc3339())
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, namespace: &str, key: &str) -> Result<(), MiniHubError> {
+        sqlx::query(DELETE)
+            .bind(namespace)
+            .bind(key)
+            .execute(self.pools.writer())
+            .await
+            .map_err(StorageError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::pool::Config;
+
+    async fn setup() -> SqliteSettingsRepository {
+        let db = Config::new("sqlite::memory:").build().await.unwrap();
+        SqliteSettingsRepository::new(db.pool().clone())
+    }
+
+    #[tokio::test]
+    async fn should_return_none_when_key_missing() {
+        let repo = setup().await;
+
+        assert!(repo.get("dashboard", "layout").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn should_replace_value_stored_under_key() {
+        let repo = setup().await;
+
+        repo.put("dashboard", "layout", json!({ "cards": [] }))
+            .await
+            .unwrap();
+        repo.put("dashboard", "layout", json!({ "cards": [1] }))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.get("dashboard", "layout").await.unwrap(),
+            Some(json!({ "cards": [1] }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_list_and_delete_keys_of_a_namespace_only() {
+        let repo = setup().await;
+        repo.put("mqtt", "host", json!("broker")).await.unwrap();
+        repo.put("mqtt", "port", json!(1883)).await.unwrap();
+        repo.put("dashboard", "host", json!("other")).await.unwrap();
+
+        repo.delete("mqtt", "port").await.unwrap();
+
+        let settings = repo.list("mqtt").await.unwrap();
+        assert_eq!(settings.len(), 1);
+        assert_eq!(settings["host"], json!("broker"));
+        assert!(repo.get("dashboard", "host").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,147 @@
+//! Minimal Telegram Bot API client.
+//!
+//! Only the two methods the adapter needs are implemented:
+//! `getUpdates` (long polling) and `sendMessage`.
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::TelegramConfig;
+use crate::error::TelegramError;
+
+/// Extra time granted to the HTTP client on top of the long-poll timeout.
+const HTTP_TIMEOUT_MARGIN_SECS: u64 = 10;
+
+/// Envelope wrapping every Bot API response.
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+/// An incoming update.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+}
+
+/// A chat message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    pub text: Option<String>,
+}
+
+/// The chat a message belongs to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+#[derive(Serialize)]
+struct GetUpdatesRequest<'a> {
+    offset: i64,
+    timeout: u64,
+    allowed_updates: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+struct SendMessageRequest<'a> {
+    chat_id: i64,
+    text: &'a str,
+}
+
+/// HTTP client bound to a single bot.
+#[derive(Debug, Clone)]
+pub struct TelegramApi {
+    client: reqwest::Client,
+    base_url: String,
+    poll_timeout_secs: u64,
+}
+
+impl TelegramApi {
+    /// Build a client for the bot described by `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TelegramError::Http`] if the HTTP client cannot be built.
+    pub fn new(config: &TelegramConfig) -> Result<Self, TelegramError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(
+                config.poll_timeout_secs + HTTP_TIMEOUT_MARGIN_SECS,
+            ))
+            .build()
+            .map_err(TelegramError::Http)?;
+        Ok(Self {
+            client,
+            base_url: format!(
+                "{}/bot{}",
+                config.api_url.trim_end_matches('/'),
+                config.bot_token
+            ),
+            poll_timeout_secs: config.poll_timeout_secs,
+        })
+    }
+
+    /// Long-poll for message updates with an id of at least `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TelegramError`] when the request fails or the API
+    /// reports an error.
+    pub async fn get_updates(&self, offset: i64) -> Result<Vec<Update>, TelegramError> {
+        self.call(
+            "getUpdates",
+            &GetUpdatesRequest {
+                offset,
+                timeout: self.poll_timeout_secs,
+                allowed_updates: &["message"],
+            },
+        )
+        .await
+    }
+
+    /// Send a plain-text message to `chat_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TelegramError`] when the request fails or the API
+    /// reports an error.
+    pub async fn send_message(&self, chat_id: i64, text: &str) -> Result<(), TelegramError> {
+        let _: serde_json::Value = self
+            .call("sendMessage", &SendMessageRequest { chat_id, text })
+            .await?;
+        Ok(())
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        body: &impl Serialize,
+    ) -> Result<T, TelegramError> {
+        let response: ApiResponse<T> = self
+            .client
+            .post(format!("{}/{method}", self.base_url))
+            .json(body)
+            .send()
+            .await
+            .map_err(TelegramError::Http)?
+            .json()
+            .await
+            .map_err(TelegramError::Http)?;
+        match response {
+            ApiResponse {
+                ok: true,
+                result: Some(result),
+                ..
+            } => Ok(result),
+            ApiResponse { description, .. } => Err(TelegramError::Api(
+                description.unwrap_or_else(|| format!("{method} failed")),
+            )),
+        }
+    }
+}
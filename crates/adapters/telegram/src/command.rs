@@ -0,0 +1,116 @@
+//! Chat command parsing.
+//!
+//! Commands take the form `/<domain>[s] <action> <name>`, e.g.
+//! `/lights on kitchen` or `/switch toggle coffee maker`. The domain and the
+//! slugged name form the target `entity_id` (`light.kitchen`,
+//! `switch.coffee_maker`) and the action selects the service.
+
+/// Reply sent when a message is not a recognised command.
+pub const USAGE: &str = "Usage: /<domain> <on|off|toggle> <name>, e.g. /lights on kitchen";
+
+/// A parsed service-call command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// Domain-level entity id, e.g. `light.kitchen`.
+    pub entity_id: String,
+    /// Service to request, e.g. `turn_on`.
+    pub service: &'static str,
+}
+
+/// Parse a chat message into a [`Command`].
+///
+/// Returns `None` when the message is not a well-formed command.
+#[must_use]
+pub fn parse(text: &str) -> Option<Command> {
+    let mut words = text.split_whitespace();
+    let command = words.next()?.strip_prefix('/')?;
+    // Group chats address commands as `/lights@my_bot`.
+    let command = command.split('@').next()?.to_lowercase();
+    let domain = domain(&command)?;
+    let service = match words.next()?.to_lowercase().as_str() {
+        "on" => "turn_on",
+        "off" => "turn_off",
+        "toggle" => "toggle",
+        _ => return None,
+    };
+    let name = words
+        .map(|word| {
+            word.to_lowercase()
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() {
+        return None;
+    }
+    Some(Command {
+        entity_id: format!("{domain}.{name}"),
+        service,
+    })
+}
+
+/// Map a command word to an entity domain, accepting plurals.
+fn domain(command: &str) -> Option<&str> {
+    let domain = match command {
+        "switches" => "switch",
+        other => other.strip_suffix('s').unwrap_or(other),
+    };
+    if domain.is_empty() || !domain.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+        return None;
+    }
+    Some(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_plural_domain_command() {
+        assert_eq!(
+            parse("/lights on kitchen"),
+            Some(Command {
+                entity_id: "light.kitchen".to_string(),
+                service: "turn_on",
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_singular_domain_command() {
+        let command = parse("/light off kitchen").unwrap();
+        assert_eq!(command.entity_id, "light.kitchen");
+        assert_eq!(command.service, "turn_off");
+    }
+
+    #[test]
+    fn should_slug_multi_word_names() {
+        let command = parse("/switches toggle Coffee Maker").unwrap();
+        assert_eq!(command.entity_id, "switch.coffee_maker");
+        assert_eq!(command.service, "toggle");
+    }
+
+    #[test]
+    fn should_strip_bot_mention_from_command() {
+        let command = parse("/lights@minihub_bot on kitchen").unwrap();
+        assert_eq!(command.entity_id, "light.kitchen");
+    }
+
+    #[test]
+    fn should_reject_unknown_action() {
+        assert!(parse("/lights dim kitchen").is_none());
+    }
+
+    #[test]
+    fn should_reject_missing_name() {
+        assert!(parse("/lights on").is_none());
+    }
+
+    #[test]
+    fn should_reject_plain_text() {
+        assert!(parse("lights on kitchen").is_none());
+        assert!(parse("").is_none());
+    }
+}
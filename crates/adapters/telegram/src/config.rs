@@ -0,0 +1,56 @@
+//! Telegram integration configuration.
+
+/// Configuration for the Telegram bot.
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    /// Bot token issued by `@BotFather`.
+    pub bot_token: String,
+    /// Chats allowed to receive notifications and send commands.
+    pub allowed_chat_ids: Vec<i64>,
+    /// Base URL of the Bot API.
+    pub api_url: String,
+    /// How long a single `getUpdates` long-poll waits, in seconds.
+    pub poll_timeout_secs: u64,
+}
+
+impl TelegramConfig {
+    /// Whether `chat_id` may talk to the bot.
+    #[must_use]
+    pub fn is_allowed(&self, chat_id: i64) -> bool {
+        self.allowed_chat_ids.contains(&chat_id)
+    }
+}
+
+impl Default for TelegramConfig {
+    fn default() -> Self {
+        Self {
+            bot_token: String::new(),
+            allowed_chat_ids: Vec::new(),
+            api_url: "https://api.telegram.org".to_string(),
+            poll_timeout_secs: 30,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_sensible_defaults() {
+        let config = TelegramConfig::default();
+        assert_eq!(config.api_url, "https://api.telegram.org");
+        assert_eq!(config.poll_timeout_secs, 30);
+        assert!(config.allowed_chat_ids.is_empty());
+    }
+
+    #[test]
+    fn should_only_allow_listed_chats() {
+        let config = TelegramConfig {
+            allowed_chat_ids: vec![42],
+            ..TelegramConfig::default()
+        };
+        assert!(config.is_allowed(42));
+        assert!(!config.is_allowed(7));
+    }
+}
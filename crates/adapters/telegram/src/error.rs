@@ -0,0 +1,40 @@
+//! Telegram adapter error types.
+
+use minihub_domain::error::MiniHubError;
+
+/// Errors specific to the Telegram adapter.
+#[derive(Debug, thiserror::Error)]
+pub enum TelegramError {
+    /// The HTTP client could not be built or the request failed to complete.
+    #[error("Telegram request failed")]
+    Http(#[source] reqwest::Error),
+
+    /// The Bot API answered with `ok: false`.
+    #[error("Telegram API error: {0}")]
+    Api(String),
+}
+
+/// Telegram failures surface as [`MiniHubError::Storage`] across port
+/// boundaries.
+impl From<TelegramError> for MiniHubError {
+    fn from(err: TelegramError) -> Self {
+        MiniHubError::Storage(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_api_error_description() {
+        let err = TelegramError::Api("Unauthorized".to_string());
+        assert_eq!(err.to_string(), "Telegram API error: Unauthorized");
+    }
+
+    #[test]
+    fn should_convert_api_error_to_storage_error() {
+        let err: MiniHubError = TelegramError::Api("Unauthorized".to_string()).into();
+        assert!(matches!(err, MiniHubError::Storage(_)));
+    }
+}
@@ -0,0 +1,384 @@
+//! # minihub-adapter-telegram
+//!
+//! Telegram bot integration, working in both directions:
+//!
+//! - [`TelegramNotifier`] implements the [`Notifier`] port and sends every
+//!   notification to the allowed chats.
+//! - [`TelegramIntegration`] long-polls the Bot API for messages and turns
+//!   commands such as `/lights on kitchen` into
+//!   [`EventType::ServiceCallRequested`] events for `light.kitchen`.
+//!
+//! Messages from chats outside `allowed_chat_ids` are ignored.
+//!
+//! ## Dependency rule
+//!
+//! Depends on `minihub-app` (port traits) and `minihub-domain` only.
+
+mod api;
+mod command;
+mod config;
+mod error;
+
+use std::future::Future;
+use std::time::Duration;
+
+use minihub_app::ports::Notifier;
+use minihub_app::ports::integration::{Integration, IntegrationContext};
+use minihub_domain::entity::Entity;
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::EntityId;
+use minihub_domain::notification::Notification;
+use tokio::task::JoinHandle;
+
+use api::TelegramApi;
+
+pub use config::TelegramConfig;
+pub use error::TelegramError;
+
+/// Delay before polling again after a failed `getUpdates` call.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Render a notification as a plain-text chat message.
+fn format_notification(notification: &Notification) -> String {
+    match &notification.title {
+        Some(title) => format!(
+            "[{}] {title}\n{}",
+            notification.severity, notification.message
+        ),
+        None => format!("[{}] {}", notification.severity, notification.message),
+    }
+}
+
+/// Notifier sending notifications to every allowed Telegram chat.
+#[derive(Debug, Clone)]
+pub struct TelegramNotifier {
+    api: TelegramApi,
+    chat_ids: Vec<i64>,
+}
+
+impl TelegramNotifier {
+    /// Build a notifier from the bot configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TelegramError::Http`] if the HTTP client cannot be built.
+    pub fn new(config: &TelegramConfig) -> Result<Self, TelegramError> {
+        Ok(Self {
+            api: TelegramApi::new(config)?,
+            chat_ids: config.allowed_chat_ids.clone(),
+        })
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn notify(
+        &self,
+        notification: &Notification,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        let text = format_notification(notification);
+        async move {
+            for chat_id in &self.chat_ids {
+                self.api.send_message(*chat_id, &text).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Telegram command listener integration.
+pub struct TelegramIntegration {
+    config: TelegramConfig,
+    listener_handle: Option<JoinHandle<()>>,
+}
+
+impl TelegramIntegration {
+    /// Create a new integration with the given configuration.
+    #[must_use]
+    pub fn new(config: TelegramConfig) -> Self {
+        Self {
+            config,
+            listener_handle: None,
+        }
+    }
+}
+
+impl Integration for TelegramIntegration {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn setup(&mut self, _ctx: &impl IntegrationContext) -> Result<(), MiniHubError> {
+        if self.config.allowed_chat_ids.is_empty() {
+            tracing::warn!("no allowed Telegram chats configured, all messages will be ignored");
+        }
+        Ok(())
+    }
+
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        let api = TelegramApi::new(&self.config)?;
+        let allowed_chat_ids = self.config.allowed_chat_ids.clone();
+        self.listener_handle = Some(tokio::spawn(run_listener(ctx, api, allowed_chat_ids)));
+        Ok(())
+    }
+
+    async fn handle_service_call(
+        &self,
+        entity_id: EntityId,
+        _service: &str,
+        _data: serde_json::Value,
+    ) -> Result<Entity, MiniHubError> {
+        Err(NotFoundError {
+            entity: "TelegramService",
+            id: entity_id.to_string(),
+        }
+        .into())
+    }
+
+    async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        if let Some(handle) = self.listener_handle.take() {
+            handle.abort();
+            tracing::debug!("telegram listener task aborted");
+        }
+        Ok(())
+    }
+}
+
+/// Background task: long-poll for messages and handle commands.
+async fn run_listener(
+    ctx: impl IntegrationContext + 'static,
+    api: TelegramApi,
+    allowed_chat_ids: Vec<i64>,
+) {
+    let mut offset = 0;
+    loop {
+        let updates = match api.get_updates(offset).await {
+            Ok(updates) => updates,
+            Err(err) => {
+                tracing::warn!(%err, "failed to poll Telegram updates");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            let Some(message) = update.message else {
+                continue;
+            };
+            if !allowed_chat_ids.contains(&message.chat.id) {
+                tracing::debug!(
+                    chat_id = message.chat.id,
+                    "ignoring message from unknown chat"
+                );
+                continue;
+            }
+            let Some(text) = message.text else {
+                continue;
+            };
+            let reply = handle_command(&ctx, &text).await;
+            if let Err(err) = api.send_message(message.chat.id, &reply).await {
+                tracing::warn!(%err, chat_id = message.chat.id, "failed to reply on Telegram");
+            }
+        }
+    }
+}
+
+/// Execute a chat command and return the reply text.
+async fn handle_command(ctx: &impl IntegrationContext, text: &str) -> String {
+    let Some(command) = command::parse(text) else {
+        return command::USAGE.to_string();
+    };
+    let entity = match ctx.find_entity_by_entity_id(&command.entity_id).await {
+        Ok(Some(entity)) => entity,
+        Ok(None) => return format!("Unknown entity {}", command.entity_id),
+        Err(err) => {
+            tracing::warn!(%err, entity_id = %command.entity_id, "failed to look up entity");
+            return format!("Failed to look up {}", command.entity_id);
+        }
+    };
+    let event = Event::new(
+        EventType::ServiceCallRequested,
+        Some(entity.id),
+        serde_json::json!({ "service": command.service, "data": {} }),
+    );
+    match ctx.publish(event).await {
+        Ok(()) => format!("{}: {} requested", command.entity_id, command.service),
+        Err(err) => {
+            tracing::warn!(%err, entity_id = %command.entity_id, "failed to request service call");
+            format!(
+                "Failed to request {} on {}",
+                command.service, command.entity_id
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use axum::extract::State;
+    use axum::routing::post;
+    use minihub_app::testing::FakeContext;
+    use minihub_domain::notification::Severity;
+    use std::sync::{Arc, Mutex};
+
+    type Sent = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// Fake Bot API recording `sendMessage` calls and serving `updates` once.
+    async fn spawn_bot_api(updates: serde_json::Value) -> (TelegramConfig, Sent) {
+        let sent: Sent = Arc::default();
+        let updates = Arc::new(Mutex::new(Some(updates)));
+        let app =
+            axum::Router::new()
+                .route(
+                    "/bottoken/sendMessage",
+                    post(
+                        |State((sent, _)): State<(Sent, Arc<Mutex<Option<serde_json::Value>>>)>,
+                         Json(body): Json<serde_json::Value>| async move {
+                            sent.lock().unwrap().push(body);
+                            Json(serde_json::json!({ "ok": true, "result": {} }))
+                        },
+                    ),
+                )
+                .route(
+                    "/bottoken/getUpdates",
+                    post(
+                        |State((_, updates)): State<(
+                            Sent,
+                            Arc<Mutex<Option<serde_json::Value>>>,
+                        )>| async move {
+                            let result = updates
+                                .lock()
+                                .unwrap()
+                                .take()
+                                .unwrap_or_else(|| serde_json::json!([]));
+                            Json(serde_json::json!({ "ok": true, "result": result }))
+                        },
+                    ),
+                )
+                .with_state((Arc::clone(&sent), updates));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let config = TelegramConfig {
+            bot_token: "token".to_string(),
+            allowed_chat_ids: vec![42],
+            api_url: format!("http://{addr}"),
+            poll_timeout_secs: 0,
+        };
+        (config, sent)
+    }
+
+    fn kitchen_light() -> Entity {
+        Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_format_notification_with_title() {
+        let notification = Notification::builder()
+            .title("Leak")
+            .message("Water detected")
+            .severity(Severity::Critical)
+            .build()
+            .unwrap();
+        assert_eq!(
+            format_notification(&notification),
+            "[critical] Leak\nWater detected"
+        );
+    }
+
+    #[test]
+    fn should_format_notification_without_title() {
+        let notification = Notification::builder()
+            .message("Door opened")
+            .build()
+            .unwrap();
+        assert_eq!(format_notification(&notification), "[info] Door opened");
+    }
+
+    #[tokio::test]
+    async fn should_send_notification_to_allowed_chats() {
+        let (config, sent) = spawn_bot_api(serde_json::json!([])).await;
+        let notifier = TelegramNotifier::new(&config).unwrap();
+        let notification = Notification::builder()
+            .message("Door opened")
+            .build()
+            .unwrap();
+
+        notifier.notify(&notification).await.unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0]["chat_id"], 42);
+        assert_eq!(sent[0]["text"], "[info] Door opened");
+    }
+
+    #[tokio::test]
+    async fn should_publish_service_call_when_command_targets_known_entity() {
+        let light = kitchen_light();
+        let ctx = FakeContext::with_entities(vec![light.clone()]);
+
+        let reply = handle_command(&ctx, "/lights on kitchen").await;
+
+        assert_eq!(reply, "light.kitchen: turn_on requested");
+        let published = ctx.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallRequested);
+        assert_eq!(published[0].entity_id, Some(light.id));
+        assert_eq!(published[0].data["service"], "turn_on");
+    }
+
+    #[tokio::test]
+    async fn should_reply_unknown_entity_when_command_targets_missing_entity() {
+        let ctx = FakeContext::with_entities(vec![]);
+
+        let reply = handle_command(&ctx, "/lights on attic").await;
+
+        assert_eq!(reply, "Unknown entity light.attic");
+        assert!(ctx.published().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reply_usage_when_message_is_not_a_command() {
+        let ctx = FakeContext::with_entities(vec![]);
+
+        let reply = handle_command(&ctx, "hello").await;
+
+        assert_eq!(reply, command::USAGE);
+    }
+
+    #[tokio::test]
+    async fn should_handle_commands_from_allowed_chats_only() {
+        let updates = serde_json::json!([
+            { "update_id": 1, "message": { "chat": { "id": 7 }, "text": "/lights off kitchen" } },
+            { "update_id": 2, "message": { "chat": { "id": 42 }, "text": "/lights on kitchen" } },
+        ]);
+        let (config, sent) = spawn_bot_api(updates).await;
+        let ctx = FakeContext::with_entities(vec![kitchen_light()]);
+        let mut integration = TelegramIntegration::new(config);
+
+        integration.setup(&ctx).await.unwrap();
+        integration.start_background(ctx.clone()).await.unwrap();
+        for _ in 0..50 {
+            if !sent.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        integration.teardown().await.unwrap();
+
+        let published = ctx.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].data["service"], "turn_on");
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[0]["chat_id"], 42);
+    }
+}
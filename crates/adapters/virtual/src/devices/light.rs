@@ -26,6 +26,9 @@ impl Default for VirtualLight {
 }
 
 impl VirtualLight {
+    /// The domain-level entity id string for this light.
+    pub const ENTITY_ID: &'static str = "light.virtual_light";
+
     /// The fixed entity id for this light.
     #[must_use]
     pub fn entity_id(&self) -> EntityId {
@@ -52,7 +55,7 @@ impl VirtualLight {
         let entity = Entity::builder()
             .id(self.entity_id)
             .device_id(self.device_id)
-            .entity_id("light.virtual_light")
+            .entity_id(Self::ENTITY_ID)
             .friendly_name("Virtual Light")
             .state(state)
             .build()?;
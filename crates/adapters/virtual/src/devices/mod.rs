@@ -36,6 +36,16 @@ impl VirtualDevice {
         }
     }
 
+    /// The domain-level entity id string (e.g. `"light.virtual_light"`).
+    #[must_use]
+    pub fn entity_id(&self) -> &'static str {
+        match self {
+            Self::Light(_) => VirtualLight::ENTITY_ID,
+            Self::Sensor(_) => VirtualSensor::ENTITY_ID,
+            Self::Switch(_) => VirtualSwitch::ENTITY_ID,
+        }
+    }
+
     /// Handle a service call, returning the resulting entity snapshot.
     ///
     /// # Errors
@@ -1,7 +1,7 @@
 //! Virtual temperature sensor — holds a numeric reading as an attribute.
 
 use minihub_domain::device::Device;
-use minihub_domain::entity::AttributeValue;
+use minihub_domain::entity::{AttributeMeta, AttributeValue, DeviceClass};
 use minihub_domain::entity::{Entity, EntityState};
 use minihub_domain::error::MiniHubError;
 use minihub_domain::id::{DeviceId, EntityId};
@@ -25,6 +25,9 @@ impl Default for VirtualSensor {
 }
 
 impl VirtualSensor {
+    /// The domain-level entity id string for this sensor.
+    pub const ENTITY_ID: &'static str = "sensor.virtual_temperature";
+
     /// The fixed entity id for this sensor.
     #[must_use]
     pub fn entity_id(&self) -> EntityId {
@@ -49,11 +52,19 @@ impl VirtualSensor {
         let entity = Entity::builder()
             .id(self.entity_id)
             .device_id(self.device_id)
-            .entity_id("sensor.virtual_temperature")
+            .entity_id(Self::ENTITY_ID)
             .friendly_name("Virtual Temperature")
             .state(EntityState::Unknown)
+            .device_class(DeviceClass::Temperature)
+            .unit_of_measurement("\u{b0}C")
             .attribute("temperature", AttributeValue::Float(21.5))
             .attribute("unit", AttributeValue::String("\u{b0}C".to_string()))
+            .attribute_meta(
+                "temperature",
+                AttributeMeta::default()
+                    .with_precision(1)
+                    .with_display_unit("\u{b0}C"),
+            )
             .build()?;
 
         Ok((device, entity))
@@ -90,6 +101,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_be_classified_as_temperature_sensor() {
+        let sensor = VirtualSensor::default();
+        let (_, entity) = sensor.discover().unwrap();
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.unit_of_measurement.as_deref(), Some("\u{b0}C"));
+    }
+
+    #[test]
+    fn should_declare_temperature_precision() {
+        let sensor = VirtualSensor::default();
+        let (_, entity) = sensor.discover().unwrap();
+        let meta = entity.get_attribute_meta("temperature").unwrap();
+        assert_eq!(meta.precision, Some(1));
+        assert_eq!(meta.display_unit.as_deref(), Some("\u{b0}C"));
+    }
+
     #[test]
     fn should_have_unit_attribute() {
         let sensor = VirtualSensor::default();
@@ -26,6 +26,9 @@ impl Default for VirtualSwitch {
 }
 
 impl VirtualSwitch {
+    /// The domain-level entity id string for this switch.
+    pub const ENTITY_ID: &'static str = "switch.virtual_switch";
+
     /// The fixed entity id for this switch.
     #[must_use]
     pub fn entity_id(&self) -> EntityId {
@@ -51,7 +54,7 @@ impl VirtualSwitch {
         let entity = Entity::builder()
             .id(self.entity_id)
             .device_id(self.device_id)
-            .entity_id("switch.virtual_switch")
+            .entity_id(Self::ENTITY_ID)
             .friendly_name("Virtual Switch")
             .state(state)
             .build()?;
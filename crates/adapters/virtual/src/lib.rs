@@ -18,17 +18,20 @@
 mod devices;
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use minihub_app::ports::integration::{DiscoveredDevice, Integration, IntegrationContext};
 use minihub_domain::entity::Entity;
 use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload, ServiceCallResultPayload};
 use minihub_domain::id::EntityId;
 
 use devices::{VirtualDevice, VirtualLight, VirtualSensor, VirtualSwitch};
 
 /// Virtual integration that creates simulated devices.
 pub struct VirtualIntegration {
-    devices: HashMap<EntityId, VirtualDevice>,
+    devices: Arc<HashMap<EntityId, VirtualDevice>>,
+    subscriber_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl Default for VirtualIntegration {
@@ -42,7 +45,10 @@ impl Default for VirtualIntegration {
         devices.insert(sensor.entity_id(), VirtualDevice::Sensor(sensor));
         devices.insert(switch.entity_id(), VirtualDevice::Switch(switch));
 
-        Self { devices }
+        Self {
+            devices: Arc::new(devices),
+            subscriber_handle: None,
+        }
     }
 }
 
@@ -57,12 +63,26 @@ impl Integration for VirtualIntegration {
             let dd = DiscoveredDevice {
                 device,
                 entities: vec![entity],
+                via_device: None,
             };
             ctx.persist_discovered(dd).await?;
         }
         Ok(())
     }
 
+    async fn start_background(
+        &mut self,
+        ctx: impl IntegrationContext + Clone + 'static,
+    ) -> Result<(), MiniHubError> {
+        // Subscribe before spawning so no request published after this
+        // call returns can be missed.
+        let rx = ctx.subscribe();
+        let devices = Arc::clone(&self.devices);
+        self.subscriber_handle = Some(tokio::spawn(run_event_subscriber(ctx, rx, devices)));
+        tracing::info!("virtual event subscriber started");
+        Ok(())
+    }
+
     async fn handle_service_call(
         &self,
         entity_id: EntityId,
@@ -78,6 +98,9 @@ impl Integration for VirtualIntegration {
     }
 
     async fn teardown(&mut self) -> Result<(), MiniHubError> {
+        if let Some(handle) = self.subscriber_handle.take() {
+            handle.abort();
+        }
         Ok(())
     }
 }
@@ -90,6 +113,96 @@ impl VirtualIntegration {
     }
 }
 
+/// Subscribe to the event bus and actuate virtual devices targeted by
+/// [`EventType::ServiceCallRequested`] events.
+///
+/// The resulting entity snapshot is persisted through the context (which
+/// publishes `StateChanged`), then a `ServiceCallCompleted` or
+/// `ServiceCallFailed` event reports the outcome.
+async fn run_event_subscriber(
+    ctx: impl IntegrationContext + 'static,
+    mut rx: tokio::sync::broadcast::Receiver<Event>,
+    devices: Arc<HashMap<EntityId, VirtualDevice>>,
+) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    skipped,
+                    "virtual event subscriber lagged, some events were missed"
+                );
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                tracing::info!("virtual event subscriber channel closed, stopping");
+                break;
+            }
+        };
+
+        if event.event_type != EventType::ServiceCallRequested {
+            continue;
+        }
+
+        let Some(entity_id) = event.entity_id else {
+            continue;
+        };
+
+        // Persisted ids differ from the in-memory ones across restarts, so
+        // resolve the target through its entity id string.
+        let vdev = match ctx.find_entity_by_id(entity_id).await {
+            Ok(Some(entity)) => devices
+                .values()
+                .find(|vdev| vdev.entity_id() == entity.entity_id),
+            Ok(None) => None,
+            Err(err) => {
+                tracing::warn!(%err, %entity_id, "failed to look up entity for service call");
+                continue;
+            }
+        };
+
+        let Some(vdev) = vdev else {
+            continue;
+        };
+
+        let Ok(ServiceCallPayload { service, .. }) = event.payload() else {
+            continue;
+        };
+        let service = service.as_str();
+
+        tracing::debug!(%entity_id, service, "virtual handling service call");
+
+        let result = match vdev.handle_service(service) {
+            Ok(snapshot) => ctx
+                .upsert_entity_caused_by(snapshot, &event)
+                .await
+                .map(|_| ()),
+            Err(err) => Err(err),
+        };
+        let result_event = match result {
+            Ok(()) => Event::with_payload(
+                EventType::ServiceCallCompleted,
+                Some(entity_id),
+                &ServiceCallResultPayload::completed(service),
+            )
+            .with_cause(&event),
+            Err(err) => {
+                tracing::warn!(%err, %entity_id, service, "virtual service call failed");
+                Event::with_payload(
+                    EventType::ServiceCallFailed,
+                    Some(entity_id),
+                    &ServiceCallResultPayload::failed(service, err.to_string()),
+                )
+                .with_cause(&event)
+            }
+        };
+
+        if let Err(err) = ctx.publish(result_event).await {
+            tracing::warn!(%err, "failed to publish service call result event");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,19 +210,42 @@ mod tests {
     use minihub_domain::entity::EntityState;
     use std::future::Future;
     use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::sync::broadcast;
 
+    #[derive(Clone)]
     struct InMemoryContext {
-        devices: Mutex<Vec<Device>>,
-        entities: Mutex<Vec<Entity>>,
+        tx: broadcast::Sender<Event>,
+        devices: Arc<Mutex<Vec<Device>>>,
+        entities: Arc<Mutex<Vec<Entity>>>,
+        published: Arc<Mutex<Vec<Event>>>,
     }
 
     impl InMemoryContext {
         fn new() -> Self {
+            let (tx, _) = broadcast::channel(16);
             Self {
-                devices: Mutex::new(Vec::new()),
-                entities: Mutex::new(Vec::new()),
+                tx,
+                devices: Arc::new(Mutex::new(Vec::new())),
+                entities: Arc::new(Mutex::new(Vec::new())),
+                published: Arc::new(Mutex::new(Vec::new())),
             }
         }
+
+        fn send(&self, event: Event) {
+            let _ = self.tx.send(event);
+        }
+
+        /// The latest persisted snapshot for the given entity id string.
+        fn latest(&self, entity_id: &str) -> Option<Entity> {
+            self.entities
+                .lock()
+                .unwrap()
+                .iter()
+                .rev()
+                .find(|e| e.entity_id == entity_id)
+                .cloned()
+        }
     }
 
     impl IntegrationContext for InMemoryContext {
@@ -129,11 +265,14 @@ mod tests {
             async { Ok(entity) }
         }
 
-        async fn find_entity_by_id(
-            &self,
-            _id: minihub_domain::id::EntityId,
-        ) -> Result<Option<Entity>, MiniHubError> {
-            Ok(None)
+        async fn find_entity_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self
+                .entities
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|e| e.id == id)
+                .cloned())
         }
 
         async fn find_entity_by_entity_id(
@@ -143,17 +282,30 @@ mod tests {
             Ok(None)
         }
 
-        async fn publish(&self, _event: minihub_domain::event::Event) -> Result<(), MiniHubError> {
+        async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+            self.published.lock().unwrap().push(event);
             Ok(())
         }
 
-        fn subscribe(&self) -> tokio::sync::broadcast::Receiver<minihub_domain::event::Event> {
-            let (tx, rx) = tokio::sync::broadcast::channel(1);
-            drop(tx);
-            rx
+        fn subscribe(&self) -> broadcast::Receiver<Event> {
+            self.tx.subscribe()
         }
     }
 
+    /// Run the setup + background lifecycle against the given context.
+    async fn start(integration: &mut VirtualIntegration, ctx: &InMemoryContext) {
+        integration.setup(ctx).await.unwrap();
+        integration.start_background(ctx.clone()).await.unwrap();
+    }
+
+    fn service_call(entity_id: EntityId, service: &str) -> Event {
+        Event::new(
+            EventType::ServiceCallRequested,
+            Some(entity_id),
+            serde_json::json!({ "service": service, "data": {} }),
+        )
+    }
+
     #[tokio::test]
     async fn should_discover_three_devices_on_setup() {
         let mut integration = VirtualIntegration::default();
@@ -292,6 +444,75 @@ mod tests {
         assert!(integration.teardown().await.is_ok());
     }
 
+    #[tokio::test]
+    async fn should_persist_new_state_when_service_call_requested() {
+        let mut integration = VirtualIntegration::default();
+        let ctx = InMemoryContext::new();
+        start(&mut integration, &ctx).await;
+        let light = ctx.latest(VirtualLight::ENTITY_ID).unwrap();
+
+        let call = service_call(light.id, "turn_on");
+        ctx.send(call.clone());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let light = ctx.latest(VirtualLight::ENTITY_ID).unwrap();
+        assert_eq!(light.state, EntityState::On);
+        let published = ctx.published.lock().unwrap().clone();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallCompleted);
+        assert_eq!(published[0].entity_id, Some(light.id));
+        assert_eq!(published[0].data["service"], "turn_on");
+        assert_eq!(published[0].caused_by, Some(call.id));
+
+        integration.teardown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_ignore_service_call_for_unknown_entity() {
+        let mut integration = VirtualIntegration::default();
+        let ctx = InMemoryContext::new();
+        start(&mut integration, &ctx).await;
+
+        ctx.send(service_call(EntityId::new(), "turn_on"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(ctx.published.lock().unwrap().is_empty());
+        integration.teardown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_ignore_non_service_call_events() {
+        let mut integration = VirtualIntegration::default();
+        let ctx = InMemoryContext::new();
+        start(&mut integration, &ctx).await;
+        let light = ctx.latest(VirtualLight::ENTITY_ID).unwrap();
+
+        ctx.send(Event::new(
+            EventType::StateChanged,
+            Some(light.id),
+            serde_json::json!({}),
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(ctx.published.lock().unwrap().is_empty());
+        assert_eq!(
+            ctx.latest(VirtualLight::ENTITY_ID).unwrap().state,
+            EntityState::Off
+        );
+        integration.teardown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_abort_subscriber_on_teardown() {
+        let mut integration = VirtualIntegration::default();
+        let ctx = InMemoryContext::new();
+        start(&mut integration, &ctx).await;
+        assert!(integration.subscriber_handle.is_some());
+
+        integration.teardown().await.unwrap();
+        assert!(integration.subscriber_handle.is_none());
+    }
+
     fn find_entity_id(integration: &VirtualIntegration, entity_id_str: &str) -> EntityId {
         integration
             .devices
@@ -0,0 +1,822 @@
+//! Audit log — every create, update and delete attributed to an [`Actor`].
+//!
+//! Writes are recorded by wrapping repositories in [`Audited`], so every
+//! service going through them is covered without knowing about the audit
+//! log. The actor is carried by the task doing the write: request handlers,
+//! integrations and the automation engine run their work inside
+//! [`act_as`], and writes made outside of it are attributed to
+//! [`Actor::System`].
+//!
+//! Recording is best effort: a failure to store an entry is logged and the
+//! write itself still succeeds.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use minihub_domain::area::Area;
+use minihub_domain::audit::{Actor, AuditEntry};
+use minihub_domain::automation::Automation;
+use minihub_domain::device::Device;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::group::Group;
+use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId, GroupId, SceneId};
+use minihub_domain::scene::Scene;
+use minihub_domain::secret::{SecretInfo, SecretValue};
+use minihub_domain::settings::Settings;
+use minihub_domain::time::Timestamp;
+
+use crate::ports::{
+    AreaRepository, AuditRepository, AutomationRepository, DeviceRepository, EntityRepository,
+    GroupRepository, SceneRepository, SecretsRepository, SettingsRepository,
+};
+
+tokio::task_local! {
+    static CURRENT_ACTOR: Actor;
+}
+
+/// Entity fields left out of audit entries whatever the actor: they change
+/// on every write.
+const ENTITY_TIMESTAMPS: &[&str] = &["last_changed", "last_updated"];
+
+/// Entity fields left out of audit entries when the hub writes them on its
+/// own: integrations report states continuously, and those reports belong
+/// to the event log and the entity history rather than to the audit log.
+const ENTITY_REPORTED: &[&str] = &["state", "attributes", "last_changed", "last_updated"];
+
+/// Automation fields left out of audit entries: the engine keeps them up
+/// to date, they are not part of the definition.
+const AUTOMATION_RUNTIME: &[&str] = &["last_triggered"];
+
+/// Run `future` on behalf of `actor`: the writes it makes are attributed
+/// to `actor`.
+pub async fn act_as<F: Future>(actor: Actor, future: F) -> F::Output {
+    CURRENT_ACTOR.scope(actor, future).await
+}
+
+/// The actor the current task acts for, [`Actor::System`] outside of
+/// [`act_as`].
+#[must_use]
+pub fn current_actor() -> Actor {
+    CURRENT_ACTOR
+        .try_with(Clone::clone)
+        .unwrap_or(Actor::System)
+}
+
+/// A repository recording its creates, updates and deletes in an
+/// [`AuditRepository`].
+///
+/// Reads are passed through untouched.
+pub struct Audited<R, AU> {
+    inner: R,
+    audit: Arc<AU>,
+}
+
+impl<R, AU> Audited<R, AU> {
+    /// Wrap `inner`, recording its writes in `audit`.
+    pub fn new(inner: R, audit: Arc<AU>) -> Self {
+        Self { inner, audit }
+    }
+}
+
+impl<R, AU> Audited<R, AU>
+where
+    AU: AuditRepository + Send + Sync,
+{
+    async fn record(&self, entry: AuditEntry) {
+        if let Err(err) = self.audit.record(entry).await {
+            tracing::warn!(%err, "failed to record audit entry");
+        }
+    }
+
+    async fn record_created(&self, actor: Actor, target_type: &str, id: String, after: Value) {
+        self.record(AuditEntry::created(actor, target_type, id, after))
+            .await;
+    }
+
+    async fn record_updated(
+        &self,
+        actor: Actor,
+        target_type: &str,
+        id: String,
+        before: Option<Value>,
+        after: Value,
+    ) {
+        let before = before.unwrap_or(Value::Null);
+        if let Some(entry) = AuditEntry::updated(actor, target_type, id, &before, &after) {
+            self.record(entry).await;
+        }
+    }
+
+    async fn record_deleted(&self, target_type: &str, id: String, before: Option<Value>) {
+        self.record(AuditEntry::deleted(
+            current_actor(),
+            target_type,
+            id,
+            before,
+        ))
+        .await;
+    }
+}
+
+/// Serialized form of `resource` as summarized in audit entries, without
+/// the `ignored` fields.
+fn snapshot<T: Serialize>(resource: &T, ignored: &[&str]) -> Value {
+    let mut value = serde_json::to_value(resource).unwrap_or(Value::Null);
+    if let Value::Object(fields) = &mut value {
+        for field in ignored {
+            fields.remove(*field);
+        }
+    }
+    value
+}
+
+/// Entity fields left out of the audit entries of writes made by `actor`.
+fn entity_ignored_fields(actor: &Actor) -> &'static [&'static str] {
+    if actor.is_user() {
+        ENTITY_TIMESTAMPS
+    } else {
+        ENTITY_REPORTED
+    }
+}
+
+impl<R, AU> EntityRepository for Audited<R, AU>
+where
+    R: EntityRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+        let created = self.inner.create(entity).await?;
+        let actor = current_actor();
+        let after = snapshot(&created, entity_ignored_fields(&actor));
+        self.record_created(actor, "entity", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+        self.inner.get_many(ids).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn find_by_device_id(&self, device_id: DeviceId) -> Result<Vec<Entity>, MiniHubError> {
+        self.inner.find_by_device_id(device_id).await
+    }
+
+    async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
+        self.inner.find_by_entity_id(entity_id).await
+    }
+
+    async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+        let before = self.inner.get_by_id(entity.id).await?;
+        let updated = self.inner.update(entity).await?;
+        let actor = current_actor();
+        let ignored = entity_ignored_fields(&actor);
+        let before = before.map(|before| snapshot(&before, ignored));
+        let after = snapshot(&updated, ignored);
+        self.record_updated(actor, "entity", updated.id.to_string(), before, after)
+            .await;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: EntityId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, ENTITY_TIMESTAMPS));
+        self.record_deleted("entity", id.to_string(), before).await;
+        Ok(())
+    }
+
+    async fn find_by_alias(&self, alias: &str) -> Result<Option<Entity>, MiniHubError> {
+        self.inner.find_by_alias(alias).await
+    }
+
+    async fn add_alias(&self, id: EntityId, alias: &str) -> Result<(), MiniHubError> {
+        self.inner.add_alias(id, alias).await
+    }
+}
+
+impl<R, AU> DeviceRepository for Audited<R, AU>
+where
+    R: DeviceRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, device: Device) -> Result<Device, MiniHubError> {
+        let created = self.inner.create(device).await?;
+        let after = snapshot(&created, &[]);
+        self.record_created(current_actor(), "device", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: DeviceId) -> Result<Option<Device>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn find_by_area(&self, area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+        self.inner.find_by_area(area_id).await
+    }
+
+    async fn find_by_integration(&self, integration: &str) -> Result<Vec<Device>, MiniHubError> {
+        self.inner.find_by_integration(integration).await
+    }
+
+    async fn find_by_integration_unique_id(
+        &self,
+        integration: &str,
+        unique_id: &str,
+    ) -> Result<Option<Device>, MiniHubError> {
+        self.inner
+            .find_by_integration_unique_id(integration, unique_id)
+            .await
+    }
+
+    async fn update(&self, device: Device) -> Result<Device, MiniHubError> {
+        let before = self.inner.get_by_id(device.id).await?;
+        let updated = self.inner.update(device).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        let after = snapshot(&updated, &[]);
+        self.record_updated(
+            current_actor(),
+            "device",
+            updated.id.to_string(),
+            before,
+            after,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: DeviceId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        self.record_deleted("device", id.to_string(), before).await;
+        Ok(())
+    }
+}
+
+impl<R, AU> AreaRepository for Audited<R, AU>
+where
+    R: AreaRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, area: Area) -> Result<Area, MiniHubError> {
+        let created = self.inner.create(area).await?;
+        let after = snapshot(&created, &[]);
+        self.record_created(current_actor(), "area", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: AreaId) -> Result<Option<Area>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Area>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn update(&self, area: Area) -> Result<Area, MiniHubError> {
+        let before = self.inner.get_by_id(area.id).await?;
+        let updated = self.inner.update(area).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        let after = snapshot(&updated, &[]);
+        self.record_updated(
+            current_actor(),
+            "area",
+            updated.id.to_string(),
+            before,
+            after,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: AreaId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        self.record_deleted("area", id.to_string(), before).await;
+        Ok(())
+    }
+}
+
+impl<R, AU> AutomationRepository for Audited<R, AU>
+where
+    R: AutomationRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+        let created = self.inner.create(automation).await?;
+        let after = snapshot(&created, AUTOMATION_RUNTIME);
+        self.record_created(current_actor(), "automation", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: AutomationId) -> Result<Option<Automation>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Automation>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn get_enabled(&self) -> Result<Vec<Automation>, MiniHubError> {
+        self.inner.get_enabled().await
+    }
+
+    async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+        let before = self.inner.get_by_id(automation.id).await?;
+        let updated = self.inner.update(automation).await?;
+        let before = before.map(|before| snapshot(&before, AUTOMATION_RUNTIME));
+        let after = snapshot(&updated, AUTOMATION_RUNTIME);
+        self.record_updated(
+            current_actor(),
+            "automation",
+            updated.id.to_string(),
+            before,
+            after,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn record_triggered(
+        &self,
+        id: AutomationId,
+        triggered_at: Timestamp,
+    ) -> Result<(), MiniHubError> {
+        self.inner.record_triggered(id, triggered_at).await
+    }
+
+    async fn delete(&self, id: AutomationId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, AUTOMATION_RUNTIME));
+        self.record_deleted("automation", id.to_string(), before)
+            .await;
+        Ok(())
+    }
+}
+
+impl<R, AU> SceneRepository for Audited<R, AU>
+where
+    R: SceneRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        let created = self.inner.create(scene).await?;
+        let after = snapshot(&created, &[]);
+        self.record_created(current_actor(), "scene", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: SceneId) -> Result<Option<Scene>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Scene>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn update(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        let before = self.inner.get_by_id(scene.id).await?;
+        let updated = self.inner.update(scene).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        let after = snapshot(&updated, &[]);
+        self.record_updated(
+            current_actor(),
+            "scene",
+            updated.id.to_string(),
+            before,
+            after,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: SceneId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        self.record_deleted("scene", id.to_string(), before).await;
+        Ok(())
+    }
+}
+
+impl<R, AU> GroupRepository for Audited<R, AU>
+where
+    R: GroupRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn create(&self, group: Group) -> Result<Group, MiniHubError> {
+        let created = self.inner.create(group).await?;
+        let after = snapshot(&created, &[]);
+        self.record_created(current_actor(), "group", created.id.to_string(), after)
+            .await;
+        Ok(created)
+    }
+
+    async fn get_by_id(&self, id: GroupId) -> Result<Option<Group>, MiniHubError> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Group>, MiniHubError> {
+        self.inner.get_all().await
+    }
+
+    async fn update(&self, group: Group) -> Result<Group, MiniHubError> {
+        let before = self.inner.get_by_id(group.id).await?;
+        let updated = self.inner.update(group).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        let after = snapshot(&updated, &[]);
+        self.record_updated(
+            current_actor(),
+            "group",
+            updated.id.to_string(),
+            before,
+            after,
+        )
+        .await;
+        Ok(updated)
+    }
+
+    async fn delete(&self, id: GroupId) -> Result<(), MiniHubError> {
+        let before = self.inner.get_by_id(id).await?;
+        self.inner.delete(id).await?;
+        let before = before.map(|before| snapshot(&before, &[]));
+        self.record_deleted("group", id.to_string(), before).await;
+        Ok(())
+    }
+}
+
+impl<R, AU> SettingsRepository for Audited<R, AU>
+where
+    R: SettingsRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, MiniHubError> {
+        self.inner.get(namespace, key).await
+    }
+
+    async fn list(&self, namespace: &str) -> Result<Settings, MiniHubError> {
+        self.inner.list(namespace).await
+    }
+
+    async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<(), MiniHubError> {
+        let before = self.inner.get(namespace, key).await?;
+        self.inner.put(namespace, key, value.clone()).await?;
+        let actor = current_actor();
+        let id = format!("{namespace}.{key}");
+        match before {
+            Some(before) => {
+                self.record_updated(actor, "setting", id, Some(before), value)
+                    .await;
+            }
+            None => self.record_created(actor, "setting", id, value).await,
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, namespace: &str, key: &str) -> Result<(), MiniHubError> {
+        let before = self.inner.get(namespace, key).await?;
+        self.inner.delete(namespace, key).await?;
+        if before.is_some() {
+            self.record_deleted("setting", format!("{namespace}.{key}"), before)
+                .await;
+        }
+        Ok(())
+    }
+}
+
+/// Secrets are recorded by name and write time only, never by value.
+impl<R, AU> SecretsRepository for Audited<R, AU>
+where
+    R: SecretsRepository + Send + Sync,
+    AU: AuditRepository + Send + Sync,
+{
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>, MiniHubError> {
+        self.inner.get(name).await
+    }
+
+    async fn list(&self) -> Result<Vec<SecretInfo>, MiniHubError> {
+        self.inner.list().await
+    }
+
+    async fn put(&self, name: &str, value: &SecretValue) -> Result<SecretInfo, MiniHubError> {
+        let before = self.secret_info(name).await?;
+        let info = self.inner.put(name, value).await?;
+        let actor = current_actor();
+        let after = snapshot(&info, &[]);
+        match before {
+            Some(before) => {
+                let before = snapshot(&before, &[]);
+                self.record_updated(actor, "secret", name.to_string(), Some(before), after)
+                    .await;
+            }
+            None => {
+                self.record_created(actor, "secret", name.to_string(), after)
+                    .await;
+            }
+        }
+        Ok(info)
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool, MiniHubError> {
+        let before = self.secret_info(name).await?;
+        let existed = self.inner.delete(name).await?;
+        if existed {
+            let before = before.map(|before| snapshot(&before, &[]));
+            self.record_deleted("secret", name.to_string(), before)
+                .await;
+        }
+        Ok(existed)
+    }
+}
+
+impl<R, AU> Audited<R, AU>
+where
+    R: SecretsRepository + Send + Sync,
+{
+    /// What is known about the secret `name` without decrypting it.
+    async fn secret_info(&self, name: &str) -> Result<Option<SecretInfo>, MiniHubError> {
+        let secrets = self.inner.list().await?;
+        Ok(secrets.into_iter().find(|info| info.name == name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use minihub_domain::audit::AuditAction;
+    use minihub_domain::entity::EntityState;
+
+    use super::*;
+    use crate::ports::AuditQuery;
+
+    #[derive(Default)]
+    struct InMemoryAuditRepo {
+        entries: Mutex<Vec<AuditEntry>>,
+    }
+
+    impl AuditRepository for InMemoryAuditRepo {
+        async fn record(&self, entry: AuditEntry) -> Result<AuditEntry, MiniHubError> {
+            self.entries.lock().unwrap().push(entry.clone());
+            Ok(entry)
+        }
+
+        async fn query(&self, query: AuditQuery) -> Result<Vec<AuditEntry>, MiniHubError> {
+            let entries = self.entries.lock().unwrap();
+            Ok(entries
+                .iter()
+                .rev()
+                .filter(|entry| query.matches(entry))
+                .take(query.limit)
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryAreaRepo {
+        store: Mutex<HashMap<AreaId, Area>>,
+    }
+
+    impl AreaRepository for InMemoryAreaRepo {
+        async fn create(&self, area: Area) -> Result<Area, MiniHubError> {
+            self.store.lock().unwrap().insert(area.id, area.clone());
+            Ok(area)
+        }
+        async fn get_by_id(&self, id: AreaId) -> Result<Option<Area>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn get_all(&self) -> Result<Vec<Area>, MiniHubError> {
+            Ok(self.store.lock().unwrap().values().cloned().collect())
+        }
+        async fn update(&self, area: Area) -> Result<Area, MiniHubError> {
+            self.create(area).await
+        }
+        async fn delete(&self, id: AreaId) -> Result<(), MiniHubError> {
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            let store = self.store.lock().unwrap();
+            Ok(ids.iter().filter_map(|id| store.get(id).cloned()).collect())
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self.store.lock().unwrap().values().cloned().collect())
+        }
+        async fn find_by_device_id(&self, _: DeviceId) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(Vec::new())
+        }
+        async fn find_by_entity_id(&self, _: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            self.create(entity).await
+        }
+        async fn delete(&self, id: EntityId) -> Result<(), MiniHubError> {
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_by_alias(&self, _: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _: EntityId, _: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemorySecretsRepo {
+        store: Mutex<HashMap<String, SecretValue>>,
+    }
+
+    impl SecretsRepository for InMemorySecretsRepo {
+        async fn get(&self, name: &str) -> Result<Option<SecretValue>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(name).cloned())
+        }
+        async fn list(&self) -> Result<Vec<SecretInfo>, MiniHubError> {
+            let names: Vec<_> = self.store.lock().unwrap().keys().cloned().collect();
+            Ok(names
+                .into_iter()
+                .map(|name| SecretInfo {
+                    name,
+                    updated_at: minihub_domain::time::now(),
+                })
+                .collect())
+        }
+        async fn put(&self, name: &str, value: &SecretValue) -> Result<SecretInfo, MiniHubError> {
+            self.store
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value.clone());
+            Ok(SecretInfo {
+                name: name.to_string(),
+                updated_at: minihub_domain::time::now(),
+            })
+        }
+        async fn delete(&self, name: &str) -> Result<bool, MiniHubError> {
+            Ok(self.store.lock().unwrap().remove(name).is_some())
+        }
+    }
+
+    fn audited<R: Default>() -> (Audited<R, InMemoryAuditRepo>, Arc<InMemoryAuditRepo>) {
+        let audit = Arc::new(InMemoryAuditRepo::default());
+        (Audited::new(R::default(), Arc::clone(&audit)), audit)
+    }
+
+    fn entries(audit: &InMemoryAuditRepo) -> Vec<AuditEntry> {
+        audit.entries.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn should_attribute_writes_to_system_outside_act_as() {
+        let (repo, audit) = audited::<InMemoryAreaRepo>();
+
+        repo.create(Area::builder().name("Kitchen").build().unwrap())
+            .await
+            .unwrap();
+
+        let entries = entries(&audit);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor, Actor::System);
+        assert_eq!(entries[0].action, AuditAction::Create);
+        assert_eq!(entries[0].target_type, "area");
+    }
+
+    #[tokio::test]
+    async fn should_record_create_update_and_delete_with_current_actor() {
+        let (repo, audit) = audited::<InMemoryAreaRepo>();
+
+        act_as(Actor::Dashboard, async {
+            let area = repo
+                .create(Area::builder().name("Kitchen").build().unwrap())
+                .await
+                .unwrap();
+            let mut renamed = area.clone();
+            renamed.name = "Cuisine".to_string();
+            repo.update(renamed).await.unwrap();
+            repo.delete(area.id).await.unwrap();
+        })
+        .await;
+
+        let entries = entries(&audit);
+        let actions: Vec<_> = entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, AuditAction::ALL);
+        assert!(entries.iter().all(|entry| entry.actor == Actor::Dashboard));
+        assert_eq!(
+            entries[1].before,
+            Some(serde_json::json!({ "name": "Kitchen" }))
+        );
+        assert_eq!(
+            entries[1].after,
+            Some(serde_json::json!({ "name": "Cuisine" }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_skip_state_reports_from_integrations() {
+        let (repo, audit) = audited::<InMemoryEntityRepo>();
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("sensor.kitchen_temperature")
+            .friendly_name("Kitchen temperature")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+
+        act_as(Actor::Integration("ble".to_string()), async {
+            let created = repo.create(entity).await.unwrap();
+            let mut reported = created.clone();
+            reported.state = EntityState::Off;
+            reported.last_updated = minihub_domain::time::now();
+            repo.update(reported).await.unwrap();
+        })
+        .await;
+
+        let entries = entries(&audit);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, AuditAction::Create);
+    }
+
+    #[tokio::test]
+    async fn should_record_state_changes_made_by_users() {
+        let (repo, audit) = audited::<InMemoryEntityRepo>();
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::Off)
+            .build()
+            .unwrap();
+        let created = repo.create(entity).await.unwrap();
+
+        act_as(Actor::Api, async {
+            let mut updated = created.clone();
+            updated.state = EntityState::On;
+            repo.update(updated).await.unwrap();
+        })
+        .await;
+
+        let entries = entries(&audit);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].actor, Actor::Api);
+        assert_eq!(entries[1].after, Some(serde_json::json!({ "state": "on" })));
+    }
+
+    #[tokio::test]
+    async fn should_record_secret_writes_without_their_value() {
+        let (repo, audit) = audited::<InMemorySecretsRepo>();
+
+        act_as(Actor::Dashboard, async {
+            repo.put("mqtt.password", &SecretValue::new("hunter2"))
+                .await
+                .unwrap();
+            assert!(repo.delete("mqtt.password").await.unwrap());
+        })
+        .await;
+
+        let entries = entries(&audit);
+        let actions: Vec<_> = entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, [AuditAction::Create, AuditAction::Delete]);
+        assert!(entries.iter().all(|entry| entry.target_type == "secret"));
+        let recorded = serde_json::to_string(&entries).unwrap();
+        assert!(!recorded.contains("hunter2"));
+    }
+}
@@ -3,182 +3,812 @@
 //! The engine subscribes to the event bus and, for each incoming event,
 //! checks all enabled automations. When a trigger matches, it evaluates
 //! conditions and—if all pass—executes the automation's actions in order.
+//! Service calls are published as [`EventType::ServiceCallRequested`] events
+//! so the owning integration actuates the device and reports the new state,
+//! and notifications as [`EventType::NotificationRequested`] events picked
+//! up by the notification service. `wait_for_state` actions poll the entity
+//! until it reaches the awaited state, and `if` and `repeat` actions run
+//! their nested actions like top-level ones.
+//! Every trigger match is recorded as an [`AutomationRun`] so the outcome
+//! of each activation can be inspected later.
+//!
+//! An automation runs at most once per causal chain: when the triggering
+//! event descends from an earlier run of the same automation (e.g. it
+//! toggles the entity its own trigger watches), the run is refused and an
+//! [`EventType::AutomationSuppressed`] event is published instead, so
+//! cascades cannot loop forever.
+//!
+//! Events are processed concurrently, so an automation may trigger again
+//! while a previous run of it is still in progress, e.g. sleeping in a
+//! `delay` action. Its [`ExecutionMode`] decides what happens then: the
+//! trigger is suppressed (`single`), the previous run is cancelled
+//! (`restart`) and recorded with its remaining actions cancelled, the new
+//! run waits for the previous ones (`queued`), or both run side by side
+//! (`parallel`). Queued runs start in the order their events reached the
+//! engine, even when the automations of a later event load first.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::pin::pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Either};
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio::sync::{Notify, broadcast, watch};
+
+use minihub_domain::audit::Actor;
+use minihub_domain::automation::{Action, Automation, ExecutionMode, Template, Trigger};
+use minihub_domain::automation_run::{ActionOutcome, ActionResult, AutomationRun};
+use minihub_domain::entity::Entity;
+use minihub_domain::error::{ConflictError, MiniHubError, NotFoundError};
+use minihub_domain::event::{AutomationPayload, Event, EventType, ServiceCallPayload};
+use minihub_domain::id::{AutomationId, AutomationRunId, EntityId, EventId};
+use minihub_domain::notification::Notification;
+
+use crate::audit::act_as;
+use crate::condition_evaluator::ConditionEvaluator;
+use crate::event_bus::EventFilter;
+use crate::ports::{
+    AutomationRepository, AutomationRunRepository, EntityRepository, EventPublisher,
+    EventSubscription,
+};
+
+/// How long the automations run within a causal chain are remembered
+/// after the chain was last active.
+const CASCADE_WINDOW: Duration = Duration::from_mins(10);
+
+/// How often a `wait_for_state` action reads the state of its entity.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Automations already run within each recently active causal chain.
+#[derive(Default)]
+struct CascadeTracker {
+    chains: HashMap<EventId, (Instant, Vec<AutomationId>)>,
+}
+
+impl CascadeTracker {
+    /// Record that `automation_id` runs within the chain `correlation_id`,
+    /// returning `false` when it already ran in it.
+    fn enter(
+        &mut self,
+        correlation_id: EventId,
+        automation_id: AutomationId,
+        now: Instant,
+    ) -> bool {
+        self.chains
+            .retain(|_, (active, _)| now.duration_since(*active) < CASCADE_WINDOW);
+        let (active, automations) = self
+            .chains
+            .entry(correlation_id)
+            .or_insert_with(|| (now, Vec::new()));
+        *active = now;
+        if automations.contains(&automation_id) {
+            return false;
+        }
+        automations.push(automation_id);
+        true
+    }
+}
+
+/// Runs in progress of the automations whose [`ExecutionMode`] depends on
+/// them.
+#[derive(Default)]
+struct RunTracker {
+    /// Automations in `single` mode with a run in progress.
+    single: HashSet<AutomationId>,
+    /// Number and cancellation signal of the run in progress of automations
+    /// in `restart` mode.
+    restartable: HashMap<AutomationId, (u64, Arc<Notify>)>,
+    /// Queue of the runs of automations in `queued` mode, removed once no
+    /// run holds or waits for its turn.
+    queues: HashMap<AutomationId, Turnstile>,
+    /// Events being processed, let through in the order they reached the
+    /// engine to join the queues of their automations.
+    admission: Turnstile,
+    /// Number of the next run of an automation in `restart` mode.
+    next_run: u64,
+}
+
+/// Hands out numbered tickets and lets their holders through one at a
+/// time, in ticket order.
+struct Turnstile {
+    next_ticket: u64,
+    /// Tickets given back ahead of the one being let through.
+    returned: BTreeSet<u64>,
+    /// Ticket being let through.
+    through: watch::Sender<u64>,
+}
+
+impl Default for Turnstile {
+    fn default() -> Self {
+        Self {
+            next_ticket: 0,
+            returned: BTreeSet::new(),
+            through: watch::Sender::new(0),
+        }
+    }
+}
+
+impl Turnstile {
+    /// Take the next ticket, with a receiver telling which ticket is let
+    /// through.
+    fn take(&mut self) -> (u64, watch::Receiver<u64>) {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        (ticket, self.through.subscribe())
+    }
+
+    /// Give `ticket` back, letting the next held ticket through once every
+    /// earlier one was given back.
+    fn give_back(&mut self, ticket: u64) {
+        self.returned.insert(ticket);
+        let mut through = *self.through.borrow();
+        while self.returned.remove(&through) {
+            through += 1;
+        }
+        self.through.send_replace(through);
+    }
+
+    /// Whether every ticket was given back.
+    fn is_idle(&self) -> bool {
+        *self.through.borrow() == self.next_ticket
+    }
+}
+
+/// Wait until `through` lets `ticket` through.
+async fn wait_turn(through: &mut watch::Receiver<u64>, ticket: u64) {
+    // The sender outlives every ticket it handed out.
+    let _ = through.wait_for(|through| *through >= ticket).await;
+}
+
+/// An event being processed, holding its place in the order events reached
+/// the engine until dropped.
+struct Admission<'a> {
+    runs: &'a Mutex<RunTracker>,
+    ticket: u64,
+    through: watch::Receiver<u64>,
+}
+
+impl<'a> Admission<'a> {
+    /// Take the place of a new event.
+    fn enter(runs: &'a Mutex<RunTracker>) -> Self {
+        let (ticket, through) = lock(runs).admission.take();
+        Self {
+            runs,
+            ticket,
+            through,
+        }
+    }
+
+    /// Wait for the events that reached the engine earlier to be admitted.
+    async fn turn(&mut self) {
+        wait_turn(&mut self.through, self.ticket).await;
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        lock(self.runs).admission.give_back(self.ticket);
+    }
+}
+
+/// A run of an automation in `single` mode, released on drop.
+struct SingleRun<'a> {
+    runs: &'a Mutex<RunTracker>,
+    automation_id: AutomationId,
+}
+
+impl<'a> SingleRun<'a> {
+    /// Start a run of `automation_id`, or return `None` when one is already
+    /// in progress.
+    fn enter(runs: &'a Mutex<RunTracker>, automation_id: AutomationId) -> Option<Self> {
+        if !lock(runs).single.insert(automation_id) {
+            return None;
+        }
+        Some(Self {
+            runs,
+            automation_id,
+        })
+    }
+}
+
+impl Drop for SingleRun<'_> {
+    fn drop(&mut self) {
+        lock(self.runs).single.remove(&self.automation_id);
+    }
+}
+
+/// A run of an automation in `restart` mode, forgotten on drop unless a
+/// newer run replaced it.
+struct RestartRun<'a> {
+    runs: &'a Mutex<RunTracker>,
+    automation_id: AutomationId,
+    number: u64,
+    cancel: Arc<Notify>,
+}
+
+impl<'a> RestartRun<'a> {
+    /// Start a run of `automation_id`, cancelling the one in progress.
+    fn enter(runs: &'a Mutex<RunTracker>, automation_id: AutomationId) -> Self {
+        let cancel = Arc::new(Notify::new());
+        let mut tracker = lock(runs);
+        let number = tracker.next_run;
+        tracker.next_run += 1;
+        if let Some((_, previous)) = tracker
+            .restartable
+            .insert(automation_id, (number, Arc::clone(&cancel)))
+        {
+            previous.notify_one();
+        }
+        Self {
+            runs,
+            automation_id,
+            number,
+            cancel,
+        }
+    }
+
+    /// Resolves once a newer run cancelled this one.
+    async fn cancelled(&self) {
+        self.cancel.notified().await;
+    }
+}
+
+impl Drop for RestartRun<'_> {
+    fn drop(&mut self) {
+        let mut tracker = lock(self.runs);
+        if tracker
+            .restartable
+            .get(&self.automation_id)
+            .is_some_and(|(number, _)| *number == self.number)
+        {
+            tracker.restartable.remove(&self.automation_id);
+        }
+    }
+}
+
+/// A run of an automation in `queued` mode, holding its place in the
+/// queue of the automation until dropped; the queue is forgotten once no
+/// other run holds or waits for its turn.
+struct QueuedRun<'a> {
+    runs: &'a Mutex<RunTracker>,
+    automation_id: AutomationId,
+    ticket: u64,
+    through: watch::Receiver<u64>,
+}
+
+impl<'a> QueuedRun<'a> {
+    /// Join the queue of `automation_id`.
+    fn enter(runs: &'a Mutex<RunTracker>, automation_id: AutomationId) -> Self {
+        let (ticket, through) = lock(runs).queues.entry(automation_id).or_default().take();
+        Self {
+            runs,
+            automation_id,
+            ticket,
+            through,
+        }
+    }
+
+    /// Wait for the runs queued before this one.
+    async fn turn(&mut self) {
+        wait_turn(&mut self.through, self.ticket).await;
+    }
+}
+
+impl Drop for QueuedRun<'_> {
+    fn drop(&mut self) {
+        let mut tracker = lock(self.runs);
+        if let Some(queue) = tracker.queues.get_mut(&self.automation_id) {
+            queue.give_back(self.ticket);
+            if queue.is_idle() {
+                tracker.queues.remove(&self.automation_id);
+            }
+        }
+    }
+}
 
-use minihub_domain::automation::{Action, Condition};
-use minihub_domain::entity::EntityState;
-use minihub_domain::error::MiniHubError;
-use minihub_domain::event::{Event, EventType};
-use minihub_domain::id::EntityId;
+fn lock(runs: &Mutex<RunTracker>) -> MutexGuard<'_, RunTracker> {
+    runs.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
-use crate::ports::{AutomationRepository, EntityRepository, EventPublisher};
+/// Why the actions of a run stopped before the last one.
+enum Failure {
+    /// An action failed.
+    Error(MiniHubError),
+    /// A newer run of the automation cancelled this one.
+    Cancelled,
+}
 
 /// Reactive automation engine that subscribes to domain events.
-pub struct AutomationEngine<AR, ER, P> {
+pub struct AutomationEngine<AR, ER, RR, P> {
     automation_repo: AR,
     entity_repo: ER,
+    run_repo: RR,
     publisher: P,
+    cascades: Mutex<CascadeTracker>,
+    runs: Mutex<RunTracker>,
 }
 
-impl<AR, ER, P> AutomationEngine<AR, ER, P>
+impl<AR, ER, RR, P> AutomationEngine<AR, ER, RR, P>
 where
     AR: AutomationRepository,
     ER: EntityRepository,
+    RR: AutomationRunRepository,
     P: EventPublisher,
 {
     /// Create a new engine.
-    pub fn new(automation_repo: AR, entity_repo: ER, publisher: P) -> Self {
+    pub fn new(automation_repo: AR, entity_repo: ER, run_repo: RR, publisher: P) -> Self {
         Self {
             automation_repo,
             entity_repo,
+            run_repo,
             publisher,
+            cascades: Mutex::default(),
+            runs: Mutex::default(),
+        }
+    }
+
+    /// The events [`Self::process_event`] may act on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all().with_event_types(Trigger::EVENT_TYPES)
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Events are processed concurrently, so a long-running automation does
+    /// not hold back the next events. Runs until the bus is closed, then
+    /// waits for the runs in progress. Lagging behind the bus only drops the
+    /// missed events, and a failing event is logged without stopping the loop.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        let mut in_progress = FuturesUnordered::new();
+        loop {
+            let next = if in_progress.is_empty() {
+                receiver.recv().await
+            } else {
+                match future::select(pin!(receiver.recv()), in_progress.next()).await {
+                    Either::Left((next, _)) => next,
+                    Either::Right(_) => continue,
+                }
+            };
+            match next {
+                Ok(event) => {
+                    let admission = Admission::enter(&self.runs);
+                    in_progress.push(self.process_logged(event, admission));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "automation engine lagged, some events were dropped"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        while in_progress.next().await.is_some() {}
+        tracing::debug!("automation engine stopped");
+    }
+
+    /// [`Self::process_event`] of an `admission` taken when `event` was
+    /// received, logging its failure.
+    async fn process_logged(&self, event: Event, admission: Admission<'_>) {
+        if let Err(err) = self.process_admitted(&event, admission).await {
+            tracing::warn!(%err, event_id = %event.id, "failed to process event for automations");
         }
     }
 
     /// Process a single event against all enabled automations.
     ///
-    /// For each automation whose trigger matches, conditions are evaluated.
-    /// If all conditions pass, the actions are executed in order.
+    /// Every automation whose trigger matches is run concurrently, as its
+    /// [`ExecutionMode`] allows given its runs in progress. Its conditions
+    /// are evaluated and, if they all pass, its actions are executed in
+    /// order and its `last_triggered` timestamp is persisted. Every trigger
+    /// match is recorded through the [`AutomationRunRepository`].
+    ///
+    /// A failing automation is logged and skipped so it cannot prevent the
+    /// other matching automations from running.
+    ///
+    /// The event takes its place among the events being processed when this
+    /// is called, so automations in `queued` mode run in call order.
     ///
     /// # Errors
     ///
-    /// Returns a storage error if loading automations or entities fails.
-    pub async fn process_event(&self, event: &Event) -> Result<Vec<AutomationId>, MiniHubError> {
-        let automations = self.automation_repo.get_enabled().await?;
-        let mut triggered = Vec::new();
-
-        for automation in &automations {
-            if !automation.trigger.matches_event(event) {
-                continue;
-            }
+    /// Returns a storage error if loading the enabled automations fails.
+    pub fn process_event<'a>(
+        &'a self,
+        event: &'a Event,
+    ) -> impl Future<Output = Result<Vec<AutomationId>, MiniHubError>> + 'a {
+        self.process_admitted(event, Admission::enter(&self.runs))
+    }
 
-            let conditions_met = self.evaluate_conditions(&automation.conditions).await?;
-            if !conditions_met {
-                continue;
-            }
+    /// [`Self::process_event`] of an event holding `admission`.
+    async fn process_admitted(
+        &self,
+        event: &Event,
+        mut admission: Admission<'_>,
+    ) -> Result<Vec<AutomationId>, MiniHubError> {
+        let matching: Vec<_> = self
+            .automation_repo
+            .get_enabled()
+            .await?
+            .into_iter()
+            .filter(|automation| automation.trigger.matches_event(event))
+            .collect();
+        let any_queued = matching
+            .iter()
+            .any(|automation| automation.execution_mode == ExecutionMode::Queued);
+        if any_queued {
+            admission.turn().await;
+        }
+        let runs: Vec<_> = matching
+            .into_iter()
+            .map(|automation| {
+                let queued = (automation.execution_mode == ExecutionMode::Queued)
+                    .then(|| QueuedRun::enter(&self.runs, automation.id));
+                async move {
+                    let automation_id = automation.id;
+                    match self.run_in_mode(automation, event, queued).await {
+                        Ok(true) => Some(automation_id),
+                        Ok(false) => None,
+                        Err(err) => {
+                            tracing::warn!(%err, %automation_id, "automation failed");
+                            None
+                        }
+                    }
+                }
+            })
+            .collect();
+        drop(admission);
 
-            self.execute_actions(&automation.actions).await?;
+        Ok(future::join_all(runs).await.into_iter().flatten().collect())
+    }
 
-            // Publish AutomationTriggered event (fire-and-forget)
-            let trigger_event = Event::new(
-                EventType::AutomationTriggered,
-                None,
-                serde_json::json!({
-                    "automation_id": automation.id,
-                    "automation_name": automation.name,
-                }),
-            );
-            let _ = self.publisher.publish(trigger_event).await;
+    /// Run `automation` as its [`ExecutionMode`] allows given its runs in
+    /// progress, in the place `queued` holds in the queue of an automation
+    /// in `queued` mode.
+    ///
+    /// Returns `true` when the run happened, its conditions passed and every
+    /// action succeeded.
+    async fn run_in_mode(
+        &self,
+        automation: Automation,
+        event: &Event,
+        queued: Option<QueuedRun<'_>>,
+    ) -> Result<bool, MiniHubError> {
+        match automation.execution_mode {
+            ExecutionMode::Parallel => {
+                self.run_automation(automation, event, future::pending())
+                    .await
+            }
+            ExecutionMode::Single => {
+                let Some(_running) = SingleRun::enter(&self.runs, automation.id) else {
+                    tracing::debug!(
+                        automation_id = %automation.id,
+                        "automation already running, ignoring the trigger"
+                    );
+                    self.suppress(&automation, event, "single").await;
+                    return Ok(false);
+                };
+                self.run_automation(automation, event, future::pending())
+                    .await
+            }
+            ExecutionMode::Queued => {
+                let mut queued =
+                    queued.unwrap_or_else(|| QueuedRun::enter(&self.runs, automation.id));
+                queued.turn().await;
+                self.run_automation(automation, event, future::pending())
+                    .await
+            }
+            ExecutionMode::Restart => {
+                let running = RestartRun::enter(&self.runs, automation.id);
+                self.run_automation(automation, event, running.cancelled())
+                    .await
+            }
+        }
+    }
 
-            triggered.push(automation.id);
+    /// Run a single automation whose trigger matched `event`, until
+    /// `cancelled` resolves.
+    ///
+    /// Returns `true` when the conditions passed and every action succeeded.
+    /// A cancelled run is recorded with its remaining actions cancelled.
+    async fn run_automation(
+        &self,
+        automation: Automation,
+        event: &Event,
+        cancelled: impl Future<Output = ()>,
+    ) -> Result<bool, MiniHubError> {
+        let started_at = minihub_domain::time::now();
+        let conditions = self
+            .conditions()
+            .evaluate_all(&automation.conditions)
+            .await?;
+        let conditions_met = conditions.iter().all(|result| result.passed);
+        if conditions_met && !self.enter_chain(&automation, event) {
+            tracing::warn!(
+                automation_id = %automation.id,
+                correlation_id = %event.correlation_id,
+                "automation already ran in this event chain, suppressing it"
+            );
+            self.suppress(&automation, event, "cascade").await;
+            return Ok(false);
         }
+        let (actions, failure) = if conditions_met {
+            act_as(
+                Actor::Automation(automation.id),
+                self.execute_actions(&automation.actions, event, cancelled),
+            )
+            .await
+        } else {
+            (Vec::new(), None)
+        };
+
+        self.run_repo
+            .record(AutomationRun {
+                id: AutomationRunId::new(),
+                automation_id: automation.id,
+                event_id: Some(event.id),
+                started_at,
+                conditions,
+                actions,
+            })
+            .await?;
 
-        Ok(triggered)
-    }
+        if !conditions_met {
+            return Ok(false);
+        }
 
-    /// Evaluate all conditions (logical AND). Returns `true` if empty.
-    async fn evaluate_conditions(&self, conditions: &[Condition]) -> Result<bool, MiniHubError> {
-        for condition in conditions {
-            if !self.evaluate_condition(condition).await? {
+        self.automation_repo
+            .record_triggered(automation.id, started_at)
+            .await?;
+
+        match failure {
+            Some(Failure::Error(err)) => return Err(err),
+            Some(Failure::Cancelled) => {
+                tracing::info!(
+                    automation_id = %automation.id,
+                    "automation run cancelled by a new trigger"
+                );
                 return Ok(false);
             }
+            None => {}
         }
+
+        // Publish AutomationTriggered event (fire-and-forget)
+        let trigger_event = Event::with_payload(
+            EventType::AutomationTriggered,
+            None,
+            &AutomationPayload {
+                automation_id: automation.id,
+                automation_name: automation.name.clone(),
+                reason: None,
+            },
+        )
+        .with_cause(event);
+        let _ = self.publisher.publish(trigger_event).await;
+
         Ok(true)
     }
 
-    /// Evaluate a single condition.
-    async fn evaluate_condition(&self, condition: &Condition) -> Result<bool, MiniHubError> {
-        match condition {
-            Condition::StateIs { entity_id, state } => {
-                let entity = self.entity_repo.get_by_id(*entity_id).await?;
-                match entity {
-                    Some(e) => Ok(e.state.to_string() == *state),
-                    None => Ok(false),
-                }
-            }
-            Condition::TimeRange { after, before } => {
-                let now = chrono::Utc::now().format("%H:%M").to_string();
-                if after <= before {
-                    // Same-day range: after <= now <= before
-                    Ok(now >= *after && now <= *before)
-                } else {
-                    // Overnight range (e.g., 22:00..06:00): now >= after OR now <= before
-                    Ok(now >= *after || now <= *before)
-                }
-            }
-        }
+    /// Record that `automation` runs within the causal chain of `event`,
+    /// returning `false` when it already ran in it.
+    fn enter_chain(&self, automation: &Automation, event: &Event) -> bool {
+        self.cascades
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .enter(event.correlation_id, automation.id, Instant::now())
+    }
+
+    /// Report that `automation` was not run, for `reason`: `"cascade"` to
+    /// stop a cascade, `"single"` since a run of it is in progress.
+    async fn suppress(&self, automation: &Automation, event: &Event, reason: &str) {
+        let suppressed = Event::with_payload(
+            EventType::AutomationSuppressed,
+            None,
+            &AutomationPayload {
+                automation_id: automation.id,
+                automation_name: automation.name.clone(),
+                reason: Some(reason.to_string()),
+            },
+        )
+        .with_cause(event);
+        let _ = self.publisher.publish(suppressed).await;
+    }
+
+    /// Evaluate the conditions against the current entity states.
+    fn conditions(&self) -> ConditionEvaluator<'_, ER> {
+        ConditionEvaluator::new(&self.entity_repo)
     }
 
-    /// Execute actions in order.
-    async fn execute_actions(&self, actions: &[Action]) -> Result<(), MiniHubError> {
+    /// Execute actions in order, stopping at the first failure or once
+    /// `cancelled` resolves.
+    ///
+    /// Actions after a failing one are reported as skipped, the interrupted
+    /// action and the ones after it as cancelled. The failure is returned
+    /// alongside the per-action results. Events published by the actions
+    /// are caused by the triggering `event`.
+    async fn execute_actions(
+        &self,
+        actions: &[Action],
+        event: &Event,
+        cancelled: impl Future<Output = ()>,
+    ) -> (Vec<ActionResult>, Option<Failure>) {
+        let mut cancelled = pin!(cancelled);
+        let mut results = Vec::with_capacity(actions.len());
+        let mut failure = None;
         for action in actions {
-            self.execute_action(action).await?;
+            let outcome = match failure {
+                Some(Failure::Error(_)) => ActionOutcome::Skipped,
+                Some(Failure::Cancelled) => ActionOutcome::Cancelled,
+                None => {
+                    let execution = pin!(self.execute_action(action, event));
+                    match future::select(execution, cancelled.as_mut()).await {
+                        Either::Left((Ok(()), _)) => ActionOutcome::Succeeded,
+                        Either::Left((Err(err), _)) => {
+                            let outcome = ActionOutcome::Failed {
+                                error: err.to_string(),
+                            };
+                            failure = Some(Failure::Error(err));
+                            outcome
+                        }
+                        Either::Right(((), _)) => {
+                            failure = Some(Failure::Cancelled);
+                            ActionOutcome::Cancelled
+                        }
+                    }
+                }
+            };
+            results.push(ActionResult {
+                action: action.clone(),
+                outcome,
+            });
         }
-        Ok(())
+        (results, failure)
     }
 
-    /// Execute a single action.
-    async fn execute_action(&self, action: &Action) -> Result<(), MiniHubError> {
+    /// Execute a single action in reaction to `cause`, rendering its
+    /// templates first.
+    async fn execute_action(&self, action: &Action, cause: &Event) -> Result<(), MiniHubError> {
+        let scope = self
+            .conditions()
+            .scope(action.templates_entity_ids()?)
+            .await?;
         match action {
             Action::CallService {
-                entity_id, service, ..
+                entity_id,
+                service,
+                data,
             } => {
-                let new_state = service_to_state(service, *entity_id, &self.entity_repo).await?;
-                if let Some(state) = new_state {
-                    let mut entity =
-                        self.entity_repo
-                            .get_by_id(*entity_id)
-                            .await?
-                            .ok_or_else(|| minihub_domain::error::NotFoundError {
-                                entity: "Entity",
-                                id: entity_id.to_string(),
-                            })?;
-                    entity.update_state(state, minihub_domain::time::now());
-                    self.entity_repo.update(entity).await?;
-                }
+                let entity = self.entity(*entity_id).await?;
+                let data = Template::render_in(data, &scope)?;
+                entity.validate_service_data(&data)?;
+                // The owning integration actuates the device and reports the
+                // resulting state back through the entity service.
+                let event = Event::with_payload(
+                    EventType::ServiceCallRequested,
+                    Some(*entity_id),
+                    &ServiceCallPayload {
+                        service: service.clone(),
+                        data,
+                    },
+                )
+                .with_cause(cause);
+                self.publisher.publish(event).await?;
             }
             Action::Delay { seconds } => {
                 tokio::time::sleep(tokio::time::Duration::from_secs(*seconds)).await;
             }
+            Action::Notify {
+                title,
+                message,
+                severity,
+            } => {
+                let mut builder = Notification::builder()
+                    .message(Template::parse(message)?.render_text(&scope)?)
+                    .severity(*severity);
+                if let Some(title) = title {
+                    builder = builder.title(Template::parse(title)?.render_text(&scope)?);
+                }
+                self.publisher
+                    .publish(builder.build()?.to_event().with_cause(cause))
+                    .await?;
+            }
+            Action::WaitForState {
+                entity_id,
+                state,
+                timeout_secs,
+            } => {
+                self.wait_for_state(*entity_id, state, Duration::from_secs(*timeout_secs))
+                    .await?;
+            }
+            Action::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if self.conditions().evaluate(condition).await?.passed {
+                    then
+                } else {
+                    otherwise
+                };
+                Box::pin(self.execute_nested(branch, cause)).await?;
+            }
+            Action::Repeat { count, actions } => {
+                for _ in 0..*count {
+                    Box::pin(self.execute_nested(actions, cause)).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the actions nested in an `if` or `repeat` action, failing
+    /// with the error of the first failing one.
+    ///
+    /// Called through a [`Box::pin`] since it recurses through
+    /// [`Self::execute_action`].
+    async fn execute_nested(&self, actions: &[Action], cause: &Event) -> Result<(), MiniHubError> {
+        for action in actions {
+            self.execute_action(action, cause).await?;
         }
         Ok(())
     }
-}
 
-use minihub_domain::id::AutomationId;
-
-/// Map a service name to the target state for an entity.
-async fn service_to_state<ER: EntityRepository>(
-    service: &str,
-    entity_id: EntityId,
-    repo: &ER,
-) -> Result<Option<EntityState>, MiniHubError> {
-    match service {
-        "turn_on" => Ok(Some(EntityState::On)),
-        "turn_off" => Ok(Some(EntityState::Off)),
-        "toggle" => {
-            let entity = repo.get_by_id(entity_id).await?;
-            match entity {
-                Some(e) => {
-                    let toggled = match e.state {
-                        EntityState::On => EntityState::Off,
-                        _ => EntityState::On,
-                    };
-                    Ok(Some(toggled))
+    /// Poll the entity `entity_id` until it is in `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConflictError`] when the entity is still in another state
+    /// after `timeout`, or a [`NotFoundError`] once it was removed.
+    async fn wait_for_state(
+        &self,
+        entity_id: EntityId,
+        state: &str,
+        timeout: Duration,
+    ) -> Result<(), MiniHubError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let entity = self.entity(entity_id).await?;
+            let current = entity.state.to_string();
+            if current == state {
+                return Ok(());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(ConflictError {
+                    entity: "Entity",
+                    id: entity_id.to_string(),
+                    expected: state.to_string(),
+                    actual: current,
                 }
-                None => Ok(None),
+                .into());
             }
+            tokio::time::sleep(WAIT_POLL_INTERVAL.min(deadline - now)).await;
         }
-        _ => Ok(None),
+    }
+
+    /// Load the entity `id`, which actions require to exist.
+    async fn entity(&self, id: EntityId) -> Result<Entity, MiniHubError> {
+        self.entity_repo.get_by_id(id).await?.ok_or_else(|| {
+            NotFoundError {
+                entity: "Entity",
+                id: id.to_string(),
+            }
+            .into()
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use minihub_domain::automation::{Action, Automation, Condition, Trigger};
-    use minihub_domain::entity::Entity;
-    use minihub_domain::event::Event;
+    use minihub_domain::automation::{Action, Automation, Condition, ExecutionMode, Trigger};
+    use minihub_domain::entity::{Entity, EntityState};
+    use minihub_domain::event::{Event, StateChangedPayload};
+    use minihub_domain::home_mode::HomeMode;
     use minihub_domain::id::{AutomationId, DeviceId, EntityId};
+    use minihub_domain::input_helper::InputHelper;
+    use minihub_domain::time::Timestamp;
     use std::collections::HashMap;
     use std::future::Future;
     use std::sync::Mutex;
@@ -187,6 +817,8 @@ mod tests {
 
     struct InMemoryAutomationRepo {
         store: Mutex<HashMap<AutomationId, Automation>>,
+        /// How long the next loads of the enabled automations take, in turn.
+        enabled_delays: Mutex<Vec<Duration>>,
     }
 
     impl InMemoryAutomationRepo {
@@ -194,6 +826,7 @@ mod tests {
             let map: HashMap<_, _> = automations.into_iter().map(|a| (a.id, a)).collect();
             Self {
                 store: Mutex::new(map),
+                enabled_delays: Mutex::default(),
             }
         }
     }
@@ -225,7 +858,14 @@ mod tests {
         ) -> impl Future<Output = Result<Vec<Automation>, MiniHubError>> + Send {
             let store = self.store.lock().unwrap();
             let r: Vec<_> = store.values().filter(|a| a.enabled).cloned().collect();
-            async { Ok(r) }
+            let mut delays = self.enabled_delays.lock().unwrap();
+            let delay = (!delays.is_empty()).then(|| delays.remove(0));
+            async move {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                Ok(r)
+            }
         }
         fn update(
             &self,
@@ -235,6 +875,17 @@ mod tests {
             store.insert(automation.id, automation.clone());
             async { Ok(automation) }
         }
+        fn record_triggered(
+            &self,
+            id: AutomationId,
+            triggered_at: Timestamp,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            let mut store = self.store.lock().unwrap();
+            if let Some(automation) = store.get_mut(&id) {
+                automation.last_triggered = Some(triggered_at);
+            }
+            async { Ok(()) }
+        }
         fn delete(
             &self,
             id: AutomationId,
@@ -277,6 +928,15 @@ mod tests {
             let r = store.get(&id).cloned();
             async { Ok(r) }
         }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
         fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
             let store = self.store.lock().unwrap();
             let r: Vec<_> = store.values().cloned().collect();
@@ -315,6 +975,14 @@ mod tests {
             store.remove(&id);
             async { Ok(()) }
         }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
     }
 
     // Spy publisher
@@ -338,6 +1006,38 @@ mod tests {
         }
     }
 
+    // In-memory run repo
+
+    #[derive(Default)]
+    struct InMemoryRunRepo {
+        runs: Mutex<Vec<AutomationRun>>,
+    }
+
+    impl AutomationRunRepository for InMemoryRunRepo {
+        fn record(
+            &self,
+            run: AutomationRun,
+        ) -> impl Future<Output = Result<AutomationRun, MiniHubError>> + Send {
+            self.runs.lock().unwrap().push(run.clone());
+            async { Ok(run) }
+        }
+        fn find_by_automation(
+            &self,
+            automation_id: AutomationId,
+            limit: usize,
+        ) -> impl Future<Output = Result<Vec<AutomationRun>, MiniHubError>> + Send {
+            let runs = self.runs.lock().unwrap();
+            let r: Vec<_> = runs
+                .iter()
+                .rev()
+                .filter(|run| run.automation_id == automation_id)
+                .take(limit)
+                .cloned()
+                .collect();
+            async { Ok(r) }
+        }
+    }
+
     // Helpers
 
     fn light_entity(id: EntityId, state: EntityState) -> Entity {
@@ -351,24 +1051,44 @@ mod tests {
     }
 
     fn state_changed_event(entity_id: EntityId, from: &str, to: &str) -> Event {
-        Event::new(
+        Event::with_payload(
             EventType::StateChanged,
             Some(entity_id),
-            serde_json::json!({"from": from, "to": to}),
+            &StateChangedPayload {
+                old_state: from.parse().unwrap(),
+                new_state: to.parse().unwrap(),
+            },
         )
     }
 
     fn make_engine(
         automations: Vec<Automation>,
         entities: Vec<Entity>,
-    ) -> AutomationEngine<InMemoryAutomationRepo, InMemoryEntityRepo, SpyPublisher> {
+    ) -> AutomationEngine<InMemoryAutomationRepo, InMemoryEntityRepo, InMemoryRunRepo, SpyPublisher>
+    {
         AutomationEngine::new(
             InMemoryAutomationRepo::with(automations),
             InMemoryEntityRepo::with(entities),
+            InMemoryRunRepo::default(),
             SpyPublisher::default(),
         )
     }
 
+    /// Service calls published by the engine, as `(entity_id, service)` pairs.
+    fn requested_service_calls(publisher: &SpyPublisher) -> Vec<(Option<EntityId>, String)> {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::ServiceCallRequested)
+            .map(|event| {
+                let service = event.data["service"].as_str().unwrap_or_default();
+                (event.entity_id, service.to_string())
+            })
+            .collect()
+    }
+
     // Tests
 
     #[tokio::test]
@@ -399,6 +1119,40 @@ mod tests {
         assert_eq!(triggered[0], auto.id);
     }
 
+    #[tokio::test]
+    async fn should_request_notification_when_notify_action_runs() {
+        let eid = EntityId::new();
+        let auto = Automation::builder()
+            .name("Door alert")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::Notify {
+                title: Some("Door".to_string()),
+                message: "Front door opened".to_string(),
+                severity: minihub_domain::notification::Severity::Warning,
+            })
+            .build()
+            .unwrap();
+
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+        engine
+            .process_event(&state_changed_event(eid, "off", "on"))
+            .await
+            .unwrap();
+
+        let events = engine.publisher.events.lock().unwrap().clone();
+        let notifications: Vec<Notification> = events
+            .iter()
+            .filter_map(|event| Notification::from_event(event).unwrap())
+            .collect();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].title.as_deref(), Some("Door"));
+        assert_eq!(notifications[0].message, "Front door opened");
+    }
+
     #[tokio::test]
     async fn should_not_trigger_when_event_does_not_match() {
         let trigger_eid = EntityId::new();
@@ -452,7 +1206,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn should_execute_turn_on_action() {
+    async fn should_request_turn_on_service_call() {
         let eid = EntityId::new();
         let auto = Automation::builder()
             .name("Turn on")
@@ -475,13 +1229,17 @@ mod tests {
         let event = state_changed_event(eid, "off", "on");
         engine.process_event(&event).await.unwrap();
 
-        // Verify entity state was updated
-        let updated = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
-        assert_eq!(updated.state, EntityState::On);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_on".to_string())]
+        );
+        // The repository is only updated once the integration reports back
+        let entity = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
+        assert_eq!(entity.state, EntityState::Off);
     }
 
     #[tokio::test]
-    async fn should_execute_turn_off_action() {
+    async fn should_request_turn_off_service_call() {
         let eid = EntityId::new();
         let auto = Automation::builder()
             .name("Turn off")
@@ -504,12 +1262,14 @@ mod tests {
         let event = state_changed_event(eid, "on", "off");
         engine.process_event(&event).await.unwrap();
 
-        let updated = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
-        assert_eq!(updated.state, EntityState::Off);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_off".to_string())]
+        );
     }
 
     #[tokio::test]
-    async fn should_execute_toggle_action() {
+    async fn should_request_toggle_service_call() {
         let eid = EntityId::new();
         let auto = Automation::builder()
             .name("Toggle")
@@ -532,8 +1292,10 @@ mod tests {
         let event = state_changed_event(eid, "off", "on");
         engine.process_event(&event).await.unwrap();
 
-        let updated = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
-        assert_eq!(updated.state, EntityState::Off);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "toggle".to_string())]
+        );
     }
 
     #[tokio::test]
@@ -562,9 +1324,12 @@ mod tests {
         engine.process_event(&event).await.unwrap();
 
         let published = engine.publisher.events.lock().unwrap();
-        assert_eq!(published.len(), 1);
-        assert_eq!(published[0].event_type, EventType::AutomationTriggered);
-        assert_eq!(published[0].data["automation_id"], auto_id.to_string());
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[0].event_type, EventType::ServiceCallRequested);
+        assert_eq!(published[1].event_type, EventType::AutomationTriggered);
+        assert_eq!(published[1].data["automation_id"], auto_id.to_string());
+        assert!(published.iter().all(|e| e.caused_by == Some(event.id)));
+        assert!(published.iter().all(|e| e.correlation_id == event.id));
     }
 
     #[tokio::test]
@@ -675,6 +1440,79 @@ mod tests {
         assert!(triggered.is_empty());
     }
 
+    fn toggling_automation(eid: EntityId) -> Automation {
+        Automation::builder()
+            .name("Ping-pong")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "toggle".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_suppress_automation_when_its_chain_already_ran_it() {
+        let eid = EntityId::new();
+        let auto = toggling_automation(eid);
+        let auto_id = auto.id;
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::On)]);
+
+        let root = state_changed_event(eid, "off", "on");
+        engine.process_event(&root).await.unwrap();
+        let call = engine.publisher.events.lock().unwrap()[0].clone();
+        let echo = state_changed_event(eid, "on", "off").with_cause(&call);
+        let triggered = engine.process_event(&echo).await.unwrap();
+
+        assert!(triggered.is_empty());
+        assert_eq!(requested_service_calls(&engine.publisher).len(), 1);
+        let published = engine.publisher.events.lock().unwrap();
+        let suppressed = published.last().unwrap();
+        assert_eq!(suppressed.event_type, EventType::AutomationSuppressed);
+        assert_eq!(suppressed.data["automation_id"], auto_id.to_string());
+        assert_eq!(suppressed.caused_by, Some(echo.id));
+        assert_eq!(suppressed.correlation_id, root.id);
+    }
+
+    #[tokio::test]
+    async fn should_run_automation_again_in_a_new_chain() {
+        let eid = EntityId::new();
+        let engine = make_engine(
+            vec![toggling_automation(eid)],
+            vec![light_entity(eid, EntityState::On)],
+        );
+
+        engine
+            .process_event(&state_changed_event(eid, "off", "on"))
+            .await
+            .unwrap();
+        let triggered = engine
+            .process_event(&state_changed_event(eid, "on", "off"))
+            .await
+            .unwrap();
+
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(requested_service_calls(&engine.publisher).len(), 2);
+    }
+
+    #[test]
+    fn should_forget_chains_after_cascade_window() {
+        let mut tracker = CascadeTracker::default();
+        let correlation_id = EventId::new();
+        let automation_id = AutomationId::new();
+        let start = Instant::now();
+
+        assert!(tracker.enter(correlation_id, automation_id, start));
+        assert!(!tracker.enter(correlation_id, automation_id, start));
+        assert!(tracker.enter(correlation_id, automation_id, start + CASCADE_WINDOW));
+    }
+
     #[tokio::test]
     async fn should_trigger_multiple_matching_automations() {
         let eid = EntityId::new();
@@ -716,7 +1554,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn should_ignore_unknown_service_name() {
+    async fn should_forward_unknown_service_name() {
         let eid = EntityId::new();
         let auto = Automation::builder()
             .name("Unknown service")
@@ -740,45 +1578,46 @@ mod tests {
         let triggered = engine.process_event(&event).await.unwrap();
         assert_eq!(triggered.len(), 1);
 
-        // Entity state should be unchanged
-        let entity = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
-        assert_eq!(entity.state, EntityState::Off);
+        // Unknown services are forwarded as-is; integrations decide what to do
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "unknown_service".to_string())]
+        );
     }
 
     #[tokio::test]
-    async fn should_toggle_off_to_on_when_entity_is_off() {
+    async fn should_not_request_service_call_when_toggling_missing_entity() {
         let eid = EntityId::new();
+        let missing = EntityId::new();
         let auto = Automation::builder()
-            .name("Toggle off→on")
+            .name("Toggle missing")
             .trigger(Trigger::StateChanged {
                 entity_id: eid,
                 from: None,
                 to: None,
             })
             .action(Action::CallService {
-                entity_id: eid,
+                entity_id: missing,
                 service: "toggle".to_string(),
                 data: serde_json::json!({}),
             })
             .build()
             .unwrap();
 
-        let entity = light_entity(eid, EntityState::Off);
-        let engine = make_engine(vec![auto], vec![entity]);
-
-        let event = state_changed_event(eid, "on", "off");
-        engine.process_event(&event).await.unwrap();
-
-        let updated = engine.entity_repo.get_by_id(eid).await.unwrap().unwrap();
-        assert_eq!(updated.state, EntityState::On);
-    }
+        let engine = make_engine(vec![auto], vec![]);
+
+        let event = state_changed_event(eid, "off", "on");
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert!(triggered.is_empty());
+        assert!(requested_service_calls(&engine.publisher).is_empty());
+    }
 
     #[tokio::test]
-    async fn should_return_none_when_toggling_missing_entity() {
+    async fn should_not_report_trigger_when_call_service_targets_missing_entity() {
         let eid = EntityId::new();
         let missing = EntityId::new();
         let auto = Automation::builder()
-            .name("Toggle missing")
+            .name("Turn on missing")
             .trigger(Trigger::StateChanged {
                 entity_id: eid,
                 from: None,
@@ -786,7 +1625,7 @@ mod tests {
             })
             .action(Action::CallService {
                 entity_id: missing,
-                service: "toggle".to_string(),
+                service: "turn_on".to_string(),
                 data: serde_json::json!({}),
             })
             .build()
@@ -795,35 +1634,39 @@ mod tests {
         let engine = make_engine(vec![auto], vec![]);
 
         let event = state_changed_event(eid, "off", "on");
-        // toggle on missing entity returns None → no-op, no error
         let triggered = engine.process_event(&event).await.unwrap();
-        assert_eq!(triggered.len(), 1);
+        assert!(triggered.is_empty());
     }
 
     #[tokio::test]
-    async fn should_error_when_call_service_targets_missing_entity() {
+    async fn should_not_request_service_call_when_data_out_of_range() {
         let eid = EntityId::new();
-        let missing = EntityId::new();
         let auto = Automation::builder()
-            .name("Turn on missing")
+            .name("Too bright")
             .trigger(Trigger::StateChanged {
                 entity_id: eid,
                 from: None,
                 to: None,
             })
             .action(Action::CallService {
-                entity_id: missing,
+                entity_id: eid,
                 service: "turn_on".to_string(),
-                data: serde_json::json!({}),
+                data: serde_json::json!({ "brightness": 300 }),
             })
             .build()
             .unwrap();
 
-        let engine = make_engine(vec![auto], vec![]);
+        let mut entity = light_entity(eid, EntityState::Off);
+        entity.attribute_meta.insert(
+            "brightness".to_string(),
+            minihub_domain::entity::AttributeMeta::default().with_range(0.0, 255.0),
+        );
+        let engine = make_engine(vec![auto], vec![entity]);
 
         let event = state_changed_event(eid, "off", "on");
-        let result = engine.process_event(&event).await;
-        assert!(result.is_err());
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert!(triggered.is_empty());
+        assert!(requested_service_calls(&engine.publisher).is_empty());
     }
 
     #[tokio::test]
@@ -920,4 +1763,749 @@ mod tests {
         // Unless we run at exactly 03:00 UTC, the condition should fail.
         let _ = engine.process_event(&event).await.unwrap();
     }
+
+    fn home_mode_automation(eid: EntityId, mode: HomeMode) -> Automation {
+        Automation::builder()
+            .name("Only when away")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .condition(Condition::HomeModeIs { mode })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_pass_home_mode_condition_when_mode_matches() {
+        let eid = EntityId::new();
+        let auto = home_mode_automation(eid, HomeMode::Away);
+        let home_mode = HomeMode::Away.to_entity(DeviceId::new()).unwrap();
+        let engine = make_engine(
+            vec![auto],
+            vec![light_entity(eid, EntityState::Off), home_mode],
+        );
+
+        let event = state_changed_event(eid, "off", "on");
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert_eq!(triggered.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_fail_home_mode_condition_when_mode_differs() {
+        let eid = EntityId::new();
+        let auto = home_mode_automation(eid, HomeMode::Away);
+        let home_mode = HomeMode::Night.to_entity(DeviceId::new()).unwrap();
+        let engine = make_engine(
+            vec![auto],
+            vec![light_entity(eid, EntityState::Off), home_mode],
+        );
+
+        let event = state_changed_event(eid, "off", "on");
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert!(triggered.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_treat_missing_home_mode_entity_as_home() {
+        let eid = EntityId::new();
+        let auto = home_mode_automation(eid, HomeMode::Home);
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+
+        let event = state_changed_event(eid, "off", "on");
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert_eq!(triggered.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_run_on_helper_value_change_when_value_condition_holds() {
+        let eid = EntityId::new();
+        let helper = InputHelper::Number {
+            min: 15.0,
+            max: 25.0,
+            step: 0.5,
+        };
+        let mut target = helper
+            .to_entity(
+                DeviceId::new(),
+                "Target temperature",
+                Some(&serde_json::json!(21)),
+            )
+            .unwrap();
+        target.id = eid;
+        let auto = Automation::builder()
+            .name("Heat to 21")
+            .trigger(Trigger::ValueChanged {
+                entity_id: eid,
+                to: None,
+            })
+            .condition(Condition::ValueIs {
+                entity_id: eid,
+                value: serde_json::json!(21),
+            })
+            .action(Action::Delay { seconds: 0 })
+            .build()
+            .unwrap();
+        let engine = make_engine(vec![auto], vec![target]);
+
+        let event = Event::new(
+            EventType::AttributeChanged,
+            Some(eid),
+            serde_json::json!({ "changed": { "value": 21.0 } }),
+        );
+        let triggered = engine.process_event(&event).await.unwrap();
+        assert_eq!(triggered.len(), 1);
+    }
+
+    fn expression_automation(eid: EntityId, expr: &str) -> Automation {
+        Automation::builder()
+            .name("Expression")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .condition(Condition::Expression {
+                expr: expr.to_string(),
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({
+                    "brightness": "{{ (time.hour + 1) * 0 + 128 }}",
+                }),
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_run_when_expression_condition_holds_and_render_data() {
+        let eid = EntityId::new();
+        let auto = expression_automation(eid, "states('light.test').state == 'off'");
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+
+        let triggered = engine
+            .process_event(&state_changed_event(eid, "on", "off"))
+            .await
+            .unwrap();
+
+        assert_eq!(triggered.len(), 1);
+        let events = engine.publisher.events.lock().unwrap().clone();
+        let call = events
+            .iter()
+            .find(|event| event.event_type == EventType::ServiceCallRequested)
+            .unwrap();
+        assert_eq!(call.data["data"]["brightness"], serde_json::json!(128));
+    }
+
+    #[tokio::test]
+    async fn should_skip_when_expression_condition_fails_to_evaluate() {
+        let eid = EntityId::new();
+        let auto = expression_automation(eid, "states('light.test').state * 2");
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+
+        let triggered = engine
+            .process_event(&state_changed_event(eid, "on", "off"))
+            .await
+            .unwrap();
+
+        assert!(triggered.is_empty());
+        assert!(requested_service_calls(&engine.publisher).is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_persist_last_triggered_when_automation_fires() {
+        let eid = EntityId::new();
+        let auto = Automation::builder()
+            .name("Persist last triggered")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let auto_id = auto.id;
+
+        let entity = light_entity(eid, EntityState::Off);
+        let engine = make_engine(vec![auto], vec![entity]);
+
+        let event = state_changed_event(eid, "off", "on");
+        engine.process_event(&event).await.unwrap();
+
+        let stored = engine
+            .automation_repo
+            .get_by_id(auto_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.last_triggered.is_some());
+    }
+
+    #[tokio::test]
+    async fn should_record_run_with_action_outcomes_when_automation_fires() {
+        let eid = EntityId::new();
+        let auto = Automation::builder()
+            .name("Recorded run")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let auto_id = auto.id;
+
+        let entity = light_entity(eid, EntityState::Off);
+        let engine = make_engine(vec![auto], vec![entity]);
+
+        let event = state_changed_event(eid, "off", "on");
+        engine.process_event(&event).await.unwrap();
+
+        let runs = engine
+            .run_repo
+            .find_by_automation(auto_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].event_id, Some(event.id));
+        assert_eq!(runs[0].actions.len(), 1);
+        assert_eq!(runs[0].actions[0].outcome, ActionOutcome::Succeeded);
+        assert!(runs[0].succeeded());
+    }
+
+    #[tokio::test]
+    async fn should_record_failed_condition_without_updating_last_triggered() {
+        let trigger_eid = EntityId::new();
+        let condition_eid = EntityId::new();
+        let auto = Automation::builder()
+            .name("Failed condition")
+            .trigger(Trigger::StateChanged {
+                entity_id: trigger_eid,
+                from: None,
+                to: None,
+            })
+            .condition(Condition::StateIs {
+                entity_id: condition_eid,
+                state: "on".to_string(),
+            })
+            .action(Action::CallService {
+                entity_id: trigger_eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let auto_id = auto.id;
+
+        let condition_entity = light_entity(condition_eid, EntityState::Off);
+        let engine = make_engine(vec![auto], vec![condition_entity]);
+
+        let event = state_changed_event(trigger_eid, "off", "on");
+        engine.process_event(&event).await.unwrap();
+
+        let runs = engine
+            .run_repo
+            .find_by_automation(auto_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].conditions.len(), 1);
+        assert!(!runs[0].conditions[0].passed);
+        assert!(runs[0].actions.is_empty());
+
+        let stored = engine
+            .automation_repo
+            .get_by_id(auto_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.last_triggered.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_record_skipped_actions_when_an_action_fails() {
+        let eid = EntityId::new();
+        let missing = EntityId::new();
+        let auto = Automation::builder()
+            .name("Failing action")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: missing,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let auto_id = auto.id;
+
+        let entity = light_entity(eid, EntityState::Off);
+        let engine = make_engine(vec![auto], vec![entity]);
+
+        let event = state_changed_event(eid, "off", "on");
+        engine.process_event(&event).await.unwrap();
+
+        let runs = engine
+            .run_repo
+            .find_by_automation(auto_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(matches!(
+            runs[0].actions[0].outcome,
+            ActionOutcome::Failed { .. }
+        ));
+        assert_eq!(runs[0].actions[1].outcome, ActionOutcome::Skipped);
+
+        assert!(requested_service_calls(&engine.publisher).is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_run_other_automations_when_one_fails() {
+        let eid = EntityId::new();
+        let failing = Automation::builder()
+            .name("Failing")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: EntityId::new(),
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let working = Automation::builder()
+            .name("Working")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        let working_id = working.id;
+
+        let entity = light_entity(eid, EntityState::Off);
+        let engine = make_engine(vec![failing, working], vec![entity]);
+
+        let event = state_changed_event(eid, "off", "on");
+        let triggered = engine.process_event(&event).await.unwrap();
+
+        assert_eq!(triggered, vec![working_id]);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_on".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_process_bus_events_until_closed_when_running() {
+        let eid = EntityId::new();
+        let auto = Automation::builder()
+            .name("Bus driven")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: None,
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+
+        let entity = light_entity(eid, EntityState::Off);
+        let engine = make_engine(vec![auto], vec![entity]);
+
+        let (sender, receiver) = broadcast::channel(16);
+        sender.send(state_changed_event(eid, "off", "on")).unwrap();
+        drop(sender);
+
+        engine.run(receiver).await;
+
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_on".to_string())]
+        );
+    }
+
+    fn triggered_by(eid: EntityId, actions: Vec<Action>) -> Automation {
+        let mut builder =
+            Automation::builder()
+                .name("Control flow")
+                .trigger(Trigger::StateChanged {
+                    entity_id: eid,
+                    from: None,
+                    to: None,
+                });
+        for action in actions {
+            builder = builder.action(action);
+        }
+        builder.build().unwrap()
+    }
+
+    fn call(eid: EntityId, service: &str) -> Action {
+        Action::CallService {
+            entity_id: eid,
+            service: service.to_string(),
+            data: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_run_else_branch_when_if_condition_fails() {
+        let eid = EntityId::new();
+        let auto = triggered_by(
+            eid,
+            vec![Action::If {
+                condition: Condition::StateIs {
+                    entity_id: eid,
+                    state: "on".to_string(),
+                },
+                then: vec![call(eid, "turn_off")],
+                otherwise: vec![call(eid, "turn_on")],
+            }],
+        );
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+
+        let triggered = engine
+            .process_event(&state_changed_event(eid, "on", "off"))
+            .await
+            .unwrap();
+
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_on".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_run_nested_actions_count_times_when_repeating() {
+        let eid = EntityId::new();
+        let auto = triggered_by(
+            eid,
+            vec![Action::Repeat {
+                count: 3,
+                actions: vec![call(eid, "toggle")],
+            }],
+        );
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::Off)]);
+
+        engine
+            .process_event(&state_changed_event(eid, "off", "on"))
+            .await
+            .unwrap();
+
+        assert_eq!(requested_service_calls(&engine.publisher).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_continue_once_entity_reaches_awaited_state() {
+        let eid = EntityId::new();
+        let door = EntityId::new();
+        let auto = triggered_by(
+            eid,
+            vec![
+                Action::WaitForState {
+                    entity_id: door,
+                    state: "off".to_string(),
+                    timeout_secs: 5,
+                },
+                call(eid, "turn_off"),
+            ],
+        );
+        let engine = make_engine(
+            vec![auto],
+            vec![
+                light_entity(eid, EntityState::On),
+                light_entity(door, EntityState::On),
+            ],
+        );
+        let close_door = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            engine
+                .entity_repo
+                .update(light_entity(door, EntityState::Off))
+                .await
+                .unwrap();
+        };
+
+        let event = state_changed_event(eid, "off", "on");
+
+        let (triggered, ()) = tokio::join!(engine.process_event(&event), close_door);
+
+        assert_eq!(triggered.unwrap().len(), 1);
+        assert_eq!(
+            requested_service_calls(&engine.publisher),
+            vec![(Some(eid), "turn_off".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_and_skip_remaining_actions_when_wait_times_out() {
+        let eid = EntityId::new();
+        let auto = triggered_by(
+            eid,
+            vec![
+                Action::WaitForState {
+                    entity_id: eid,
+                    state: "off".to_string(),
+                    timeout_secs: 0,
+                },
+                call(eid, "turn_off"),
+            ],
+        );
+        let automation_id = auto.id;
+        let engine = make_engine(vec![auto], vec![light_entity(eid, EntityState::On)]);
+
+        let triggered = engine
+            .process_event(&state_changed_event(eid, "off", "on"))
+            .await
+            .unwrap();
+
+        assert!(triggered.is_empty());
+        assert!(requested_service_calls(&engine.publisher).is_empty());
+        let runs = engine
+            .run_repo
+            .find_by_automation(automation_id, 1)
+            .await
+            .unwrap();
+        assert!(matches!(
+            runs[0].actions[0].outcome,
+            ActionOutcome::Failed { .. }
+        ));
+        assert_eq!(runs[0].actions[1].outcome, ActionOutcome::Skipped);
+    }
+
+    fn notify(message: &str) -> Action {
+        Action::Notify {
+            title: None,
+            message: message.to_string(),
+            severity: minihub_domain::notification::Severity::Info,
+        }
+    }
+
+    /// Messages of the notifications requested by the engine, in order.
+    fn notified(publisher: &SpyPublisher) -> Vec<String> {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| Notification::from_event(event).unwrap())
+            .map(|notification| notification.message)
+            .collect()
+    }
+
+    /// Engine running one automation that notifies `"start"`, sleeps a
+    /// minute, then notifies `"end"`.
+    fn sleeping_engine(
+        eid: EntityId,
+        mode: ExecutionMode,
+    ) -> AutomationEngine<InMemoryAutomationRepo, InMemoryEntityRepo, InMemoryRunRepo, SpyPublisher>
+    {
+        let mut auto = triggered_by(
+            eid,
+            vec![
+                notify("start"),
+                Action::Delay { seconds: 60 },
+                notify("end"),
+            ],
+        );
+        auto.execution_mode = mode;
+        make_engine(vec![auto], vec![light_entity(eid, EntityState::On)])
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_ignore_trigger_while_running_in_single_mode() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Single);
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let (first, second) =
+            tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        assert_eq!(first.unwrap().len(), 1);
+        assert!(second.unwrap().is_empty());
+        assert_eq!(notified(&engine.publisher), ["start", "end"]);
+        let suppressed: Vec<_> = engine
+            .publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::AutomationSuppressed)
+            .map(|event| event.data["reason"].clone())
+            .collect();
+        assert_eq!(suppressed, [serde_json::json!("single")]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_cancel_previous_run_in_restart_mode() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Restart);
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let (first, second) =
+            tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        assert!(first.unwrap().is_empty());
+        assert_eq!(second.unwrap().len(), 1);
+        assert_eq!(notified(&engine.publisher), ["start", "start", "end"]);
+        assert!(lock(&engine.runs).restartable.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_record_cancelled_run_in_restart_mode() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Restart);
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let _ = tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        let runs = engine.run_repo.runs.lock().unwrap().clone();
+        let outcomes = |run: &AutomationRun| {
+            run.actions
+                .iter()
+                .map(|result| result.outcome.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(runs.len(), 2);
+        let cancelled = runs
+            .iter()
+            .find(|run| run.event_id == Some(first.id))
+            .unwrap();
+        assert_eq!(
+            outcomes(cancelled),
+            [
+                ActionOutcome::Succeeded,
+                ActionOutcome::Cancelled,
+                ActionOutcome::Cancelled
+            ]
+        );
+        let completed = runs
+            .iter()
+            .find(|run| run.event_id == Some(second.id))
+            .unwrap();
+        assert!(completed.succeeded());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_run_one_after_the_other_in_queued_mode() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Queued);
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let (first, second) =
+            tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        assert_eq!(first.unwrap().len(), 1);
+        assert_eq!(second.unwrap().len(), 1);
+        assert_eq!(
+            notified(&engine.publisher),
+            ["start", "end", "start", "end"]
+        );
+        assert!(lock(&engine.runs).queues.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_queue_runs_in_trigger_order_when_automations_load_out_of_order() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Queued);
+        *engine.automation_repo.enabled_delays.lock().unwrap() =
+            vec![Duration::from_secs(1), Duration::ZERO];
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let _ = tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        let runs: Vec<_> = engine
+            .run_repo
+            .runs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|run| run.event_id)
+            .collect();
+        assert_eq!(runs, [Some(first.id), Some(second.id)]);
+        let tracker = lock(&engine.runs);
+        assert!(tracker.queues.is_empty());
+        assert!(tracker.admission.is_idle());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_overlap_runs_in_parallel_mode() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Parallel);
+        let first = state_changed_event(eid, "off", "on");
+        let second = state_changed_event(eid, "on", "off");
+
+        let (first, second) =
+            tokio::join!(engine.process_event(&first), engine.process_event(&second));
+
+        assert_eq!(first.unwrap().len(), 1);
+        assert_eq!(second.unwrap().len(), 1);
+        assert_eq!(
+            notified(&engine.publisher),
+            ["start", "start", "end", "end"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_process_next_events_while_a_run_sleeps() {
+        let eid = EntityId::new();
+        let engine = sleeping_engine(eid, ExecutionMode::Parallel);
+        let (sender, receiver) = broadcast::channel(16);
+        sender.send(state_changed_event(eid, "off", "on")).unwrap();
+        sender.send(state_changed_event(eid, "on", "off")).unwrap();
+        drop(sender);
+
+        engine.run(receiver).await;
+
+        assert_eq!(
+            notified(&engine.publisher),
+            ["start", "start", "end", "end"]
+        );
+    }
 }
@@ -0,0 +1,375 @@
+//! Automations defined as YAML files — configuration as code.
+//!
+//! Each file holds one automation, written like the body of
+//! `POST /api/automations`:
+//!
+//! ```yaml
+//! name: Evening lights
+//! trigger:
+//!   type: sun
+//!   event: sunset
+//! actions:
+//!   - type: call_service
+//!     entity_id: 6f1c…
+//!     service: turn_on
+//! ```
+//!
+//! The file name without its extension is the automation's `source_key`,
+//! which stays the same across restarts: [`reconcile`] creates, updates and
+//! removes stored automations so they match the files, keeping their id and
+//! history. File-sourced automations are read-only through the API.
+//!
+//! Reading the files is left to the composition root; this module only
+//! parses their content.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Deserialize;
+
+use minihub_domain::automation::{Action, Automation, Condition, ExecutionMode, Trigger};
+use minihub_domain::error::MiniHubError;
+
+use crate::ports::AutomationRepository;
+
+/// Content of an automation file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Definition {
+    name: String,
+    enabled: Option<bool>,
+    trigger: Trigger,
+    #[serde(default)]
+    conditions: Vec<Condition>,
+    actions: Vec<Action>,
+    #[serde(default)]
+    execution_mode: ExecutionMode,
+}
+
+/// An automation file that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub source_key: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "automation file {}: {}", self.source_key, self.reason)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Parse the automation defined by `yaml`, tagged with `source_key`.
+///
+/// # Errors
+///
+/// Returns an [`ImportError`] when the YAML does not describe an automation
+/// or the automation fails validation.
+pub fn parse(source_key: &str, yaml: &str) -> Result<Automation, ImportError> {
+    let error = |reason: String| ImportError {
+        source_key: source_key.to_string(),
+        reason,
+    };
+    let definition: Definition =
+        serde_yaml::from_str(yaml).map_err(|err| error(err.to_string()))?;
+    let mut builder = Automation::builder()
+        .name(definition.name)
+        .trigger(definition.trigger)
+        .execution_mode(definition.execution_mode)
+        .source_key(source_key);
+    if let Some(enabled) = definition.enabled {
+        builder = builder.enabled(enabled);
+    }
+    for condition in definition.conditions {
+        builder = builder.condition(condition);
+    }
+    for action in definition.actions {
+        builder = builder.action(action);
+    }
+    builder.build().map_err(|err| error(err.to_string()))
+}
+
+/// Automations parsed from a directory of files.
+#[derive(Debug, Default)]
+pub struct AutomationFiles {
+    automations: Vec<Automation>,
+    errors: Vec<ImportError>,
+}
+
+impl AutomationFiles {
+    /// Parse every `(source_key, yaml)` pair, keeping the failures aside.
+    pub fn parse<K, Y>(files: impl IntoIterator<Item = (K, Y)>) -> Self
+    where
+        K: AsRef<str>,
+        Y: AsRef<str>,
+    {
+        let mut parsed = Self::default();
+        for (source_key, yaml) in files {
+            match parse(source_key.as_ref(), yaml.as_ref()) {
+                Ok(automation) => parsed.automations.push(automation),
+                Err(err) => parsed.errors.push(err),
+            }
+        }
+        parsed
+    }
+
+    /// Files that could not be imported.
+    #[must_use]
+    pub fn errors(&self) -> &[ImportError] {
+        &self.errors
+    }
+}
+
+/// What [`reconcile`] changed, by source key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Whether `stored` already matches the file definition `imported`.
+fn same_definition(stored: &Automation, imported: &Automation) -> bool {
+    stored.name == imported.name
+        && stored.enabled == imported.enabled
+        && stored.trigger == imported.trigger
+        && stored.conditions == imported.conditions
+        && stored.actions == imported.actions
+        && stored.execution_mode == imported.execution_mode
+}
+
+/// Make the stored file-sourced automations match `files`.
+///
+/// New files are created, changed files update the automation with the
+/// same source key and removed files delete it. Automations whose file
+/// failed to parse are left as they are, so a typo does not wipe them.
+/// Automations created through the API are never touched.
+///
+/// # Errors
+///
+/// Returns a storage error propagated from the repository.
+pub async fn reconcile<R: AutomationRepository>(
+    repo: &R,
+    files: AutomationFiles,
+) -> Result<ImportReport, MiniHubError> {
+    let mut stored: HashMap<String, Automation> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .filter_map(|automation| Some((automation.source_key.clone()?, automation)))
+        .collect();
+    let failed: HashSet<&str> = files
+        .errors
+        .iter()
+        .map(|err| err.source_key.as_str())
+        .collect();
+
+    let mut report = ImportReport::default();
+    for mut automation in files.automations {
+        let source_key = automation.source_key.clone().unwrap_or_default();
+        match stored.remove(&source_key) {
+            Some(current) if same_definition(&current, &automation) => {}
+            Some(current) => {
+                automation.id = current.id;
+                automation.version = current.version;
+                automation.last_triggered = current.last_triggered;
+                repo.update(automation).await?;
+                report.updated.push(source_key);
+            }
+            None => {
+                repo.create(automation).await?;
+                report.created.push(source_key);
+            }
+        }
+    }
+    for (source_key, automation) in stored {
+        if !failed.contains(source_key.as_str()) {
+            repo.delete(automation.id).await?;
+            report.removed.push(source_key);
+        }
+    }
+    report.removed.sort();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::id::AutomationId;
+    use minihub_domain::time::Timestamp;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryAutomationRepo {
+        store: Mutex<HashMap<AutomationId, Automation>>,
+    }
+
+    impl InMemoryAutomationRepo {
+        fn by_key(&self, source_key: &str) -> Option<Automation> {
+            let store = self.store.lock().unwrap();
+            store
+                .values()
+                .find(|automation| automation.source_key.as_deref() == Some(source_key))
+                .cloned()
+        }
+    }
+
+    impl AutomationRepository for InMemoryAutomationRepo {
+        fn create(
+            &self,
+            automation: Automation,
+        ) -> impl Future<Output = Result<Automation, MiniHubError>> + Send {
+            let mut store = self.store.lock().unwrap();
+            store.insert(automation.id, automation.clone());
+            async { Ok(automation) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: AutomationId,
+        ) -> impl Future<Output = Result<Option<Automation>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Automation>, MiniHubError>> + Send {
+            let result: Vec<Automation> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn get_enabled(
+            &self,
+        ) -> impl Future<Output = Result<Vec<Automation>, MiniHubError>> + Send {
+            let result: Vec<Automation> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|automation| automation.enabled)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            mut automation: Automation,
+        ) -> impl Future<Output = Result<Automation, MiniHubError>> + Send {
+            automation.version += 1;
+            let mut store = self.store.lock().unwrap();
+            store.insert(automation.id, automation.clone());
+            async { Ok(automation) }
+        }
+
+        fn record_triggered(
+            &self,
+            id: AutomationId,
+            triggered_at: Timestamp,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            if let Some(automation) = self.store.lock().unwrap().get_mut(&id) {
+                automation.last_triggered = Some(triggered_at);
+            }
+            async { Ok(()) }
+        }
+
+        fn delete(
+            &self,
+            id: AutomationId,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    fn yaml(name: &str) -> String {
+        format!(
+            "name: {name}
+trigger:
+  type: sun
+  event: sunset
+actions:
+  - type: delay
+    seconds: 5
+"
+        )
+    }
+
+    #[test]
+    fn should_parse_automation_tagged_with_source_key() {
+        let automation = parse("evening", &yaml("Evening lights")).unwrap();
+
+        assert_eq!(automation.name, "Evening lights");
+        assert_eq!(automation.source_key.as_deref(), Some("evening"));
+        assert!(automation.enabled);
+        assert!(automation.is_read_only());
+    }
+
+    #[test]
+    fn should_reject_unknown_fields_and_invalid_automations() {
+        let unknown = parse("typo", "name: x\ntriger:\n  type: manual\nactions: []\n");
+        let invalid = parse("empty", "name: x\ntrigger:\n  type: manual\nactions: []\n");
+
+        assert!(unknown.unwrap_err().reason.contains("triger"));
+        assert_eq!(
+            invalid.unwrap_err().to_string(),
+            "automation file empty: at least one action is required"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_create_update_and_remove_file_automations() {
+        let repo = InMemoryAutomationRepo::default();
+        let files =
+            AutomationFiles::parse([("evening", yaml("Evening")), ("morning", yaml("Morning"))]);
+        let report = reconcile(&repo, files).await.unwrap();
+        assert_eq!(report.created.len(), 2);
+        let evening = repo.by_key("evening").unwrap();
+
+        let files = AutomationFiles::parse([("evening", yaml("Evening lights"))]);
+        let report = reconcile(&repo, files).await.unwrap();
+
+        assert_eq!(
+            report,
+            ImportReport {
+                created: vec![],
+                updated: vec!["evening".to_string()],
+                removed: vec!["morning".to_string()],
+            }
+        );
+        let updated = repo.by_key("evening").unwrap();
+        assert_eq!(updated.id, evening.id);
+        assert_eq!(updated.name, "Evening lights");
+        assert!(repo.by_key("morning").is_none());
+    }
+
+    #[tokio::test]
+    async fn should_keep_automations_when_file_fails_or_was_created_through_api() {
+        let repo = InMemoryAutomationRepo::default();
+        let from_api = Automation::builder()
+            .name("From the API")
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap();
+        repo.create(from_api.clone()).await.unwrap();
+        reconcile(
+            &repo,
+            AutomationFiles::parse([("evening", yaml("Evening"))]),
+        )
+        .await
+        .unwrap();
+
+        let files = AutomationFiles::parse([("evening", "name: [")]);
+        assert_eq!(files.errors().len(), 1);
+        let report = reconcile(&repo, files).await.unwrap();
+
+        assert_eq!(report, ImportReport::default());
+        assert!(repo.by_key("evening").is_some());
+        assert!(repo.get_by_id(from_api.id).await.unwrap().is_some());
+    }
+}
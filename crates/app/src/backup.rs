@@ -0,0 +1,138 @@
+//! Database backup requests — lets driving adapters ask the daemon for a
+//! database snapshot without knowing how or where it is written.
+//!
+//! [`channel`] returns a cloneable [`BackupHandle`], handed to adapters such
+//! as the HTTP API, and the [`BackupRequests`] stream consumed by the
+//! composition root, which writes the snapshot and answers with a
+//! [`BackupReport`].
+
+use std::fmt;
+use std::path::PathBuf;
+
+use tokio::sync::{mpsc, oneshot};
+
+use minihub_domain::time::Timestamp;
+
+/// Number of backup requests waiting to be served before callers wait.
+const PENDING_REQUESTS: usize = 4;
+
+/// Snapshot written by a successful backup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Location of the snapshot file.
+    pub path: PathBuf,
+    /// Size of the snapshot file, in bytes.
+    pub size_bytes: u64,
+    /// When the snapshot was taken.
+    pub created_at: Timestamp,
+    /// Older snapshots removed to stay within the retention count.
+    pub pruned: Vec<PathBuf>,
+}
+
+/// Why a backup did not happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupError {
+    /// Nothing is listening for backup requests anymore.
+    Unavailable,
+    /// The snapshot could not be written.
+    Failed(String),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unavailable => f.write_str("database backup is unavailable"),
+            Self::Failed(reason) => write!(f, "database backup failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+type Reply = oneshot::Sender<Result<BackupReport, BackupError>>;
+
+/// Create a connected [`BackupHandle`] and [`BackupRequests`] pair.
+#[must_use]
+pub fn channel() -> (BackupHandle, BackupRequests) {
+    let (sender, receiver) = mpsc::channel(PENDING_REQUESTS);
+    (BackupHandle { sender }, BackupRequests { receiver })
+}
+
+/// Requests a database backup from the daemon.
+#[derive(Debug, Clone)]
+pub struct BackupHandle {
+    sender: mpsc::Sender<Reply>,
+}
+
+impl BackupHandle {
+    /// Ask for a backup and wait until the snapshot is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BackupError::Unavailable`] when the daemon stopped
+    /// listening, or the error it reported while writing the snapshot.
+    pub async fn backup(&self) -> Result<BackupReport, BackupError> {
+        let (reply, outcome) = oneshot::channel();
+        self.sender
+            .send(reply)
+            .await
+            .map_err(|_| BackupError::Unavailable)?;
+        outcome.await.map_err(|_| BackupError::Unavailable)?
+    }
+}
+
+/// Stream of backup requests made through [`BackupHandle`]s.
+#[derive(Debug)]
+pub struct BackupRequests {
+    receiver: mpsc::Receiver<Reply>,
+}
+
+impl BackupRequests {
+    /// Wait for the next request, or `None` once every handle is dropped.
+    pub async fn recv(&mut self) -> Option<BackupRequest> {
+        self.receiver.recv().await.map(BackupRequest)
+    }
+}
+
+/// A pending backup, answered with [`respond`](Self::respond).
+#[derive(Debug)]
+pub struct BackupRequest(Reply);
+
+impl BackupRequest {
+    /// Report the outcome of the backup to the caller, if it still waits.
+    pub fn respond(self, outcome: Result<BackupReport, BackupError>) {
+        let _ = self.0.send(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_return_report_when_request_is_answered() {
+        let (handle, mut requests) = channel();
+        let responder = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            request.respond(Ok(BackupReport {
+                path: PathBuf::from("backups/minihub-20240101T000000000Z.db"),
+                size_bytes: 4096,
+                created_at: minihub_domain::time::now(),
+                pruned: Vec::new(),
+            }));
+        });
+
+        let report = handle.backup().await.unwrap();
+
+        assert_eq!(report.size_bytes, 4096);
+        responder.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_return_unavailable_when_requests_are_dropped() {
+        let (handle, requests) = channel();
+        drop(requests);
+
+        assert_eq!(handle.backup().await, Err(BackupError::Unavailable));
+    }
+}
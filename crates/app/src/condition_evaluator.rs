@@ -0,0 +1,392 @@
+//! Condition evaluator — checks automation conditions against the current
+//! entity states.
+//!
+//! The [`AutomationEngine`](crate::automation_engine::AutomationEngine)
+//! evaluates conditions before running actions, stopping at the first one
+//! that fails. Dry runs use [`ConditionEvaluator::trace`] instead, which
+//! evaluates every condition and reports the entities each one read, so
+//! users can see why an automation would or would not run.
+
+use minihub_domain::automation::{Automation, Condition, Expression, Scope};
+use minihub_domain::automation_run::{AutomationTrace, ConditionResult, ConditionTrace};
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::Event;
+use minihub_domain::home_mode::{HOME_MODE_ENTITY_ID, HomeMode};
+use minihub_domain::input_helper::{self, VALUE_ATTRIBUTE};
+use minihub_domain::sun::{self, SUN_ENTITY_ID};
+
+use crate::ports::EntityRepository;
+
+/// Evaluates conditions against the entities of a repository.
+pub struct ConditionEvaluator<'a, R> {
+    entity_repo: &'a R,
+}
+
+impl<'a, R: EntityRepository> ConditionEvaluator<'a, R> {
+    /// Create an evaluator reading entities from `entity_repo`.
+    pub fn new(entity_repo: &'a R) -> Self {
+        Self { entity_repo }
+    }
+
+    /// Evaluate conditions (logical AND), stopping at the first failure.
+    ///
+    /// Returns the result of every evaluated condition; an empty list means
+    /// there was nothing to check.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if reading an entity fails, or a validation
+    /// error if an expression condition does not parse.
+    pub async fn evaluate_all(
+        &self,
+        conditions: &[Condition],
+    ) -> Result<Vec<ConditionResult>, MiniHubError> {
+        let mut results = Vec::with_capacity(conditions.len());
+        for condition in conditions {
+            let trace = self.evaluate(condition).await?;
+            if let Some(err) = &trace.error {
+                // A runtime type error fails the condition rather than the
+                // run, so it still shows up in the run history.
+                tracing::warn!(%err, "condition failed to evaluate");
+            }
+            results.push(ConditionResult {
+                condition: trace.condition,
+                passed: trace.passed,
+            });
+            if !trace.passed {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Test `automation` against `event` without executing its actions.
+    ///
+    /// Conditions are evaluated even when the trigger does not match, so the
+    /// trace also explains automations tested with an unrelated event.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if reading an entity fails, or a validation
+    /// error if an expression condition does not parse.
+    pub async fn trace(
+        &self,
+        automation: &Automation,
+        event: &Event,
+    ) -> Result<AutomationTrace, MiniHubError> {
+        let matched = automation.trigger.matches_event(event);
+        let mut conditions = Vec::with_capacity(automation.conditions.len());
+        for condition in &automation.conditions {
+            conditions.push(self.evaluate(condition).await?);
+        }
+        Ok(AutomationTrace::new(automation.id, matched, conditions))
+    }
+
+    /// Evaluate a single condition, keeping the entities it read.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if reading an entity fails, or a validation
+    /// error if an expression condition does not parse.
+    pub async fn evaluate(&self, condition: &Condition) -> Result<ConditionTrace, MiniHubError> {
+        let mut entities = Vec::new();
+        let mut error = None;
+        let passed = match condition {
+            Condition::StateIs { entity_id, state } => {
+                let entity = self.entity_repo.get_by_id(*entity_id).await?;
+                let passed = entity
+                    .as_ref()
+                    .is_some_and(|entity| entity.state.to_string() == *state);
+                entities.extend(entity);
+                passed
+            }
+            Condition::TimeRange { after, before } => {
+                let now = chrono::Utc::now().format("%H:%M").to_string();
+                if after <= before {
+                    // Same-day range: after <= now <= before
+                    now >= *after && now <= *before
+                } else {
+                    // Overnight range (e.g., 22:00..06:00): now >= after OR now <= before
+                    now >= *after || now <= *before
+                }
+            }
+            Condition::HomeModeIs { mode } => {
+                let entity = self
+                    .entity_repo
+                    .find_by_entity_id(HOME_MODE_ENTITY_ID)
+                    .await?;
+                let current = entity.as_ref().and_then(HomeMode::of).unwrap_or_default();
+                entities.extend(entity);
+                current == *mode
+            }
+            Condition::ValueIs { entity_id, value } => {
+                let entity = self.entity_repo.get_by_id(*entity_id).await?;
+                let passed = entity
+                    .as_ref()
+                    .and_then(|entity| entity.get_attribute(VALUE_ATTRIBUTE))
+                    .map(|current| serde_json::json!(current))
+                    .is_some_and(|current| input_helper::value_eq(&current, value));
+                entities.extend(entity);
+                passed
+            }
+            Condition::SunAboveHorizon | Condition::SunBelowHorizon => {
+                let entity = self.entity_repo.find_by_entity_id(SUN_ENTITY_ID).await?;
+                let above = entity.as_ref().and_then(sun::is_above_horizon);
+                entities.extend(entity);
+                above == Some(matches!(condition, Condition::SunAboveHorizon))
+            }
+            Condition::Expression { expr } => {
+                let expression = Expression::parse(expr)?;
+                entities = self.entities(expression.entity_ids()).await?;
+                let scope = Self::scope_of(&entities);
+                expression.is_true(&scope).unwrap_or_else(|err| {
+                    error = Some(err.to_string());
+                    false
+                })
+            }
+        };
+        Ok(ConditionTrace {
+            condition: condition.clone(),
+            passed,
+            entities,
+            error,
+        })
+    }
+
+    /// Load the entities read by an expression or template into a [`Scope`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if reading an entity fails.
+    pub async fn scope(
+        &self,
+        entity_ids: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Scope, MiniHubError> {
+        let entities = self.entities(entity_ids).await?;
+        Ok(Self::scope_of(&entities))
+    }
+
+    /// Load the existing entities among `entity_ids`.
+    async fn entities(
+        &self,
+        entity_ids: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Vec<Entity>, MiniHubError> {
+        let mut entities = Vec::new();
+        for entity_id in entity_ids {
+            if let Some(entity) = self
+                .entity_repo
+                .find_by_entity_id(entity_id.as_ref())
+                .await?
+            {
+                entities.push(entity);
+            }
+        }
+        Ok(entities)
+    }
+
+    fn scope_of(entities: &[Entity]) -> Scope {
+        entities
+            .iter()
+            .fold(Scope::new(minihub_domain::time::now()), Scope::with_entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::automation::{Action, Trigger};
+    use minihub_domain::entity::EntityState;
+    use minihub_domain::event::EventType;
+    use minihub_domain::id::{DeviceId, EntityId};
+
+    use super::*;
+
+    struct FixedEntityRepo(Vec<Entity>);
+
+    impl EntityRepository for FixedEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self.0.iter().find(|entity| entity.id == id).cloned())
+        }
+
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|entity| ids.contains(&entity.id))
+                .cloned()
+                .collect())
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self.0.clone())
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|entity| entity.entity_id == entity_id)
+                .cloned())
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    fn light(id: EntityId, state: EntityState) -> Entity {
+        Entity::builder()
+            .id(id)
+            .entity_id("light.test")
+            .friendly_name("Test Light")
+            .state(state)
+            .build()
+            .unwrap()
+    }
+
+    fn automation(eid: EntityId, conditions: Vec<Condition>) -> Automation {
+        let mut builder = Automation::builder()
+            .name("Traced")
+            .trigger(Trigger::StateChanged {
+                entity_id: eid,
+                from: None,
+                to: Some(EntityState::On),
+            })
+            .action(Action::CallService {
+                entity_id: eid,
+                service: "turn_off".to_string(),
+                data: serde_json::json!({}),
+            });
+        for condition in conditions {
+            builder = builder.condition(condition);
+        }
+        builder.build().unwrap()
+    }
+
+    fn turned_on(eid: EntityId) -> Event {
+        Event::new(
+            EventType::StateChanged,
+            Some(eid),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_trace_every_condition_with_the_entities_read() {
+        let eid = EntityId::new();
+        let repo = FixedEntityRepo(vec![light(eid, EntityState::On)]);
+        let automation = automation(
+            eid,
+            vec![
+                Condition::StateIs {
+                    entity_id: eid,
+                    state: "off".to_string(),
+                },
+                Condition::Expression {
+                    expr: "states('light.test').state == 'on'".to_string(),
+                },
+            ],
+        );
+
+        let trace = ConditionEvaluator::new(&repo)
+            .trace(&automation, &turned_on(eid))
+            .await
+            .unwrap();
+
+        assert!(trace.matched);
+        assert!(!trace.would_run);
+        assert_eq!(trace.conditions.len(), 2);
+        assert!(!trace.conditions[0].passed);
+        assert_eq!(trace.conditions[0].entities[0].id, eid);
+        assert!(trace.conditions[1].passed);
+        assert_eq!(trace.conditions[1].entities[0].entity_id, "light.test");
+    }
+
+    #[tokio::test]
+    async fn should_trace_unmatched_trigger_and_evaluation_errors() {
+        let eid = EntityId::new();
+        let repo = FixedEntityRepo(vec![light(eid, EntityState::On)]);
+        let automation = automation(
+            eid,
+            vec![Condition::Expression {
+                expr: "states('light.test').state * 2".to_string(),
+            }],
+        );
+
+        let trace = ConditionEvaluator::new(&repo)
+            .trace(&automation, &turned_on(EntityId::new()))
+            .await
+            .unwrap();
+
+        assert!(!trace.matched);
+        assert!(!trace.conditions[0].passed);
+        assert!(trace.conditions[0].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn should_stop_at_first_failing_condition_when_evaluating_all() {
+        let eid = EntityId::new();
+        let repo = FixedEntityRepo(vec![light(eid, EntityState::On)]);
+        let failing = Condition::StateIs {
+            entity_id: eid,
+            state: "off".to_string(),
+        };
+        let passing = Condition::StateIs {
+            entity_id: eid,
+            state: "on".to_string(),
+        };
+
+        let results = ConditionEvaluator::new(&repo)
+            .evaluate_all(&[failing, passing])
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].passed);
+    }
+
+    #[tokio::test]
+    async fn should_evaluate_sun_conditions_from_sun_entity() {
+        let sun = Entity::builder()
+            .entity_id(SUN_ENTITY_ID)
+            .friendly_name("Sun")
+            .state(EntityState::Text(sun::BELOW_HORIZON.to_string()))
+            .build()
+            .unwrap();
+        let repo = FixedEntityRepo(vec![sun]);
+        let evaluator = ConditionEvaluator::new(&repo);
+
+        let below = evaluator
+            .evaluate(&Condition::SunBelowHorizon)
+            .await
+            .unwrap();
+        let above = evaluator
+            .evaluate(&Condition::SunAboveHorizon)
+            .await
+            .unwrap();
+        let missing = ConditionEvaluator::new(&FixedEntityRepo(vec![]))
+            .evaluate(&Condition::SunBelowHorizon)
+            .await
+            .unwrap();
+
+        assert!(below.passed);
+        assert_eq!(below.entities.len(), 1);
+        assert!(!above.passed);
+        assert!(!missing.passed);
+    }
+}
@@ -0,0 +1,141 @@
+//! Configuration reload requests — lets driving adapters ask the daemon to
+//! re-read its configuration without knowing where or how it is stored.
+//!
+//! [`channel`] returns a cloneable [`ReloadHandle`], handed to adapters such
+//! as the HTTP API, and the [`ReloadRequests`] stream consumed by the
+//! composition root, which applies the new configuration and answers with a
+//! [`ReloadReport`].
+
+use std::fmt;
+
+use tokio::sync::{mpsc, oneshot};
+
+/// Number of reload requests waiting to be applied before callers wait.
+const PENDING_REQUESTS: usize = 8;
+
+/// Outcome of a successful configuration reload.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Settings applied to the running daemon, e.g. `"logging.filter"`.
+    pub applied: Vec<String>,
+    /// Changed settings that only take effect after a restart.
+    pub requires_restart: Vec<String>,
+}
+
+/// Why a configuration reload did not happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadError {
+    /// Nothing is listening for reload requests anymore.
+    Unavailable,
+    /// The new configuration could not be loaded; the running one is kept.
+    InvalidConfig(String),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unavailable => f.write_str("configuration reload is unavailable"),
+            Self::InvalidConfig(reason) => write!(f, "invalid configuration: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+type Reply = oneshot::Sender<Result<ReloadReport, ReloadError>>;
+
+/// Create a connected [`ReloadHandle`] and [`ReloadRequests`] pair.
+#[must_use]
+pub fn channel() -> (ReloadHandle, ReloadRequests) {
+    let (sender, receiver) = mpsc::channel(PENDING_REQUESTS);
+    (ReloadHandle { sender }, ReloadRequests { receiver })
+}
+
+/// Requests a configuration reload from the daemon.
+#[derive(Debug, Clone)]
+pub struct ReloadHandle {
+    sender: mpsc::Sender<Reply>,
+}
+
+impl ReloadHandle {
+    /// Ask for a reload and wait until it has been applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReloadError::Unavailable`] when the daemon stopped
+    /// listening, or the error it reported while reloading.
+    pub async fn reload(&self) -> Result<ReloadReport, ReloadError> {
+        let (reply, outcome) = oneshot::channel();
+        self.sender
+            .send(reply)
+            .await
+            .map_err(|_| ReloadError::Unavailable)?;
+        outcome.await.map_err(|_| ReloadError::Unavailable)?
+    }
+}
+
+/// Stream of reload requests made through [`ReloadHandle`]s.
+#[derive(Debug)]
+pub struct ReloadRequests {
+    receiver: mpsc::Receiver<Reply>,
+}
+
+impl ReloadRequests {
+    /// Wait for the next request, or `None` once every handle is dropped.
+    pub async fn recv(&mut self) -> Option<ReloadRequest> {
+        self.receiver.recv().await.map(ReloadRequest)
+    }
+}
+
+/// A pending reload, answered with [`respond`](Self::respond).
+#[derive(Debug)]
+pub struct ReloadRequest(Reply);
+
+impl ReloadRequest {
+    /// Report the outcome of the reload to the caller, if it still waits.
+    pub fn respond(self, outcome: Result<ReloadReport, ReloadError>) {
+        let _ = self.0.send(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_return_report_when_request_is_answered() {
+        let (handle, mut requests) = channel();
+        let responder = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            request.respond(Ok(ReloadReport {
+                applied: vec!["logging.filter".to_string()],
+                requires_restart: vec![],
+            }));
+        });
+
+        let report = handle.reload().await.unwrap();
+
+        assert_eq!(report.applied, vec!["logging.filter".to_string()]);
+        responder.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_return_unavailable_when_requests_are_dropped() {
+        let (handle, requests) = channel();
+        drop(requests);
+
+        let err = handle.reload().await.unwrap_err();
+
+        assert_eq!(err, ReloadError::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn should_return_unavailable_when_request_is_dropped_unanswered() {
+        let (handle, mut requests) = channel();
+        tokio::spawn(async move { drop(requests.recv().await) });
+
+        let err = handle.reload().await.unwrap_err();
+
+        assert_eq!(err, ReloadError::Unavailable);
+    }
+}
@@ -1,18 +1,94 @@
 //! In-process event bus backed by a tokio broadcast channel.
+//!
+//! Subscribers interested in a subset of the traffic describe it with an
+//! [`EventFilter`]; filtered subscriptions skip the other events before
+//! handing them out, so busy hubs don't wake services for irrelevant events.
+
+use std::collections::HashSet;
+use std::future::Future;
 
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 
 use minihub_domain::error::MiniHubError;
-use minihub_domain::event::Event;
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::EntityId;
+
+use crate::metrics::{EVENTS_PUBLISHED, Metrics};
+use crate::ports::{EventPublisher, EventSubscription};
+
+/// Which events a subscription receives.
+///
+/// Every criterion left unset matches all events; set criteria must all
+/// match, and each matches when the event has any of the listed values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    event_types: Option<Vec<EventType>>,
+    entity_ids: Option<HashSet<EntityId>>,
+    integrations: Option<HashSet<String>>,
+}
 
-use crate::ports::EventPublisher;
+impl EventFilter {
+    /// A filter matching every event.
+    #[must_use]
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only match events of one of `event_types`.
+    #[must_use]
+    pub fn with_event_types(mut self, event_types: impl IntoIterator<Item = EventType>) -> Self {
+        self.event_types = Some(event_types.into_iter().collect());
+        self
+    }
+
+    /// Only match events about one of `entity_ids`.
+    #[must_use]
+    pub fn with_entity_ids(mut self, entity_ids: impl IntoIterator<Item = EntityId>) -> Self {
+        self.entity_ids = Some(entity_ids.into_iter().collect());
+        self
+    }
+
+    /// Only match events whose payload names one of `integrations` in its
+    /// `integration` field, such as device discovery events.
+    #[must_use]
+    pub fn with_integrations<S: Into<String>>(
+        mut self,
+        integrations: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.integrations = Some(integrations.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Whether `event` passes the filter.
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        self.event_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&event.event_type))
+            && self
+                .entity_ids
+                .as_ref()
+                .is_none_or(|ids| event.entity_id.is_some_and(|id| ids.contains(&id)))
+            && self.integrations.as_ref().is_none_or(|integrations| {
+                event
+                    .data
+                    .get("integration")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|integration| integrations.contains(integration))
+            })
+    }
+}
 
 /// In-process event bus using a tokio [`broadcast`] channel.
 ///
 /// Publishing succeeds even when there are no active subscribers
-/// (the event is simply dropped).
+/// (the event is simply dropped). Every published event is counted in the
+/// [`Metrics`] set with [`with_metrics`](Self::with_metrics).
 pub struct InProcessEventBus {
     sender: broadcast::Sender<Event>,
+    capacity: usize,
+    metrics: Metrics,
 }
 
 impl InProcessEventBus {
@@ -20,7 +96,18 @@ impl InProcessEventBus {
     #[must_use]
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            capacity,
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Count published events in `metrics`.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
     }
 
     /// Subscribe to events on this bus.
@@ -31,10 +118,42 @@ impl InProcessEventBus {
     pub fn subscribe(&self) -> broadcast::Receiver<Event> {
         self.sender.subscribe()
     }
+
+    /// Subscribe to the events published *after* this call that pass
+    /// `filter`.
+    #[must_use]
+    pub fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver {
+        FilteredReceiver {
+            receiver: self.sender.subscribe(),
+            filter,
+        }
+    }
+
+    /// Number of events kept for slow subscribers before they lag.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of events not yet received by the slowest subscriber.
+    #[must_use]
+    pub fn queued(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Number of active subscribers.
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
 }
 
 impl EventPublisher for InProcessEventBus {
     fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        self.metrics.increment(
+            &EVENTS_PUBLISHED,
+            &[("event_type", event.event_type.as_str())],
+        );
         // broadcast::send fails only when there are zero receivers,
         // which is fine — we simply ignore the error.
         let _ = self.sender.send(event);
@@ -42,7 +161,36 @@ impl EventPublisher for InProcessEventBus {
     }
 }
 
-use std::future::Future;
+/// Receiver of the events passing an [`EventFilter`], returned by
+/// [`InProcessEventBus::subscribe_filtered`].
+pub struct FilteredReceiver {
+    receiver: broadcast::Receiver<Event>,
+    filter: EventFilter,
+}
+
+impl FilteredReceiver {
+    /// Wait for the next event passing the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Lagged`] when events were missed, whether they
+    /// would have passed the filter or not, and [`RecvError::Closed`] once
+    /// the bus is gone.
+    pub async fn recv(&mut self) -> Result<Event, RecvError> {
+        loop {
+            let event = self.receiver.recv().await?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl EventSubscription for FilteredReceiver {
+    fn recv(&mut self) -> impl Future<Output = Result<Event, RecvError>> + Send {
+        FilteredReceiver::recv(self)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -58,7 +206,7 @@ mod tests {
         let event = Event::new(
             EventType::StateChanged,
             Some(EntityId::new()),
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         );
         let event_id = event.id;
 
@@ -93,6 +241,100 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn should_count_published_events_per_type() {
+        let metrics = Metrics::new();
+        let bus = InProcessEventBus::new(16).with_metrics(metrics.clone());
+        let _rx = bus.subscribe();
+
+        let event = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+        bus.publish(event).await.unwrap();
+
+        assert!(
+            metrics
+                .render()
+                .contains("minihub_events_published_total{event_type=\"state_changed\"} 1\n")
+        );
+        assert_eq!(bus.queued(), 1);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn should_match_every_event_when_filter_is_empty() {
+        let event = Event::new(EventType::DeviceDetected, None, serde_json::json!({}));
+
+        assert!(EventFilter::all().matches(&event));
+    }
+
+    #[test]
+    fn should_require_every_filter_criterion() {
+        let entity_id = EntityId::new();
+        let filter = EventFilter::all()
+            .with_event_types([EventType::StateChanged])
+            .with_entity_ids([entity_id]);
+
+        let matching = Event::new(
+            EventType::StateChanged,
+            Some(entity_id),
+            serde_json::json!({}),
+        );
+        let other_type = Event::new(
+            EventType::AttributeChanged,
+            Some(entity_id),
+            serde_json::json!({}),
+        );
+        let other_entity = Event::new(
+            EventType::StateChanged,
+            Some(EntityId::new()),
+            serde_json::json!({}),
+        );
+
+        assert!(filter.matches(&matching));
+        assert!(!filter.matches(&other_type));
+        assert!(!filter.matches(&other_entity));
+    }
+
+    #[test]
+    fn should_match_integration_named_in_payload() {
+        let filter = EventFilter::all().with_integrations(["mqtt"]);
+
+        let mqtt = Event::new(
+            EventType::DeviceUpdated,
+            None,
+            serde_json::json!({"integration": "mqtt"}),
+        );
+        let ble = Event::new(
+            EventType::DeviceUpdated,
+            None,
+            serde_json::json!({"integration": "ble"}),
+        );
+        let none = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+
+        assert!(filter.matches(&mqtt));
+        assert!(!filter.matches(&ble));
+        assert!(!filter.matches(&none));
+    }
+
+    #[tokio::test]
+    async fn should_skip_events_not_passing_subscription_filter() {
+        let bus = InProcessEventBus::new(16);
+        let mut rx =
+            bus.subscribe_filtered(EventFilter::all().with_event_types([EventType::EntityCreated]));
+
+        bus.publish(Event::new(
+            EventType::StateChanged,
+            None,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+        let wanted = Event::new(EventType::EntityCreated, None, serde_json::json!({}));
+        let wanted_id = wanted.id;
+        bus.publish(wanted).await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().id, wanted_id);
+    }
+
     #[tokio::test]
     async fn should_not_deliver_events_published_before_subscription() {
         let bus = InProcessEventBus::new(16);
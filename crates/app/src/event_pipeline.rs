@@ -0,0 +1,404 @@
+//! Event pipeline — composable hooks around publishing and persisting events.
+//!
+//! Cross-cutting concerns (enrichment, redaction, metrics, history
+//! recording, …) are written as [`EventHook`]s and registered on an
+//! [`EventPipeline`] instead of being hard-coded into services or the event
+//! worker. The pipeline wraps an [`EventPublisher`] and exposes two
+//! extension points:
+//!
+//! - **before publish** — every hook may rewrite the event, or drop it by
+//!   returning `None`, before it reaches the wrapped publisher;
+//! - **after persist** — the event worker calls
+//!   [`EventPipeline::after_persist`] once an event is safely stored.
+//!
+//! Hooks run in registration order. They are composed statically: each call
+//! to [`EventPipeline::hook`] nests the chain in a tuple, so no dynamic
+//! dispatch is involved.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use minihub_domain::entity_history::EntityHistory;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{Event, EventType};
+
+use crate::ports::{EntityHistoryRepository, EntityRepository, EventPublisher};
+
+/// A middleware around the event pipeline.
+///
+/// Both methods default to a no-op so a hook only implements the extension
+/// point it cares about.
+pub trait EventHook: Send + Sync {
+    /// Inspect or rewrite `event` before it is published.
+    ///
+    /// Returning `None` drops the event: later hooks and subscribers never
+    /// see it.
+    fn before_publish(&self, event: Event) -> impl Future<Output = Option<Event>> + Send {
+        async { Some(event) }
+    }
+
+    /// React to `event` once it has been persisted to the event store.
+    ///
+    /// Failures should be logged by the hook; they never stop the pipeline.
+    fn after_persist(&self, _event: &Event) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// The empty chain.
+impl EventHook for () {}
+
+/// Two hooks run in sequence.
+impl<A: EventHook, B: EventHook> EventHook for (A, B) {
+    async fn before_publish(&self, event: Event) -> Option<Event> {
+        let event = self.0.before_publish(event).await?;
+        self.1.before_publish(event).await
+    }
+
+    async fn after_persist(&self, event: &Event) {
+        self.0.after_persist(event).await;
+        self.1.after_persist(event).await;
+    }
+}
+
+impl<T: EventHook> EventHook for Arc<T> {
+    fn before_publish(&self, event: Event) -> impl Future<Output = Option<Event>> + Send {
+        (**self).before_publish(event)
+    }
+
+    fn after_persist(&self, event: &Event) -> impl Future<Output = ()> + Send {
+        (**self).after_persist(event)
+    }
+}
+
+/// An [`EventPublisher`] running registered hooks around a wrapped publisher.
+pub struct EventPipeline<P, H = ()> {
+    publisher: P,
+    hooks: H,
+}
+
+impl<P> EventPipeline<P> {
+    /// Create a pipeline without hooks in front of `publisher`.
+    pub fn new(publisher: P) -> Self {
+        Self {
+            publisher,
+            hooks: (),
+        }
+    }
+}
+
+impl<P, H: EventHook> EventPipeline<P, H> {
+    /// Register `hook` after the hooks already in the pipeline.
+    pub fn hook<N: EventHook>(self, hook: N) -> EventPipeline<P, (H, N)> {
+        EventPipeline {
+            publisher: self.publisher,
+            hooks: (self.hooks, hook),
+        }
+    }
+
+    /// Run the after-persist hooks for an event that has just been stored.
+    pub async fn after_persist(&self, event: &Event) {
+        self.hooks.after_persist(event).await;
+    }
+}
+
+impl<P, H> EventPublisher for EventPipeline<P, H>
+where
+    P: EventPublisher + Send + Sync,
+    H: EventHook,
+{
+    async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+        let Some(event) = self.hooks.before_publish(event).await else {
+            tracing::debug!("event dropped by pipeline hook");
+            return Ok(());
+        };
+        self.publisher.publish(event).await
+    }
+}
+
+/// After-persist hook recording an [`EntityHistory`] snapshot for every
+/// state or attribute change.
+pub struct EntityHistoryRecorder<ER, HR> {
+    entity_repo: ER,
+    history_repo: HR,
+}
+
+impl<ER, HR> EntityHistoryRecorder<ER, HR> {
+    /// Create a recorder reading entities from `entity_repo` and writing
+    /// snapshots to `history_repo`.
+    pub fn new(entity_repo: ER, history_repo: HR) -> Self {
+        Self {
+            entity_repo,
+            history_repo,
+        }
+    }
+}
+
+impl<ER, HR> EventHook for EntityHistoryRecorder<ER, HR>
+where
+    ER: EntityRepository + Send + Sync,
+    HR: EntityHistoryRepository + Send + Sync,
+{
+    async fn after_persist(&self, event: &Event) {
+        if !matches!(
+            event.event_type,
+            EventType::StateChanged | EventType::AttributeChanged
+        ) {
+            return;
+        }
+        let Some(entity_id) = event.entity_id else {
+            return;
+        };
+        let entity = match self.entity_repo.get_by_id(entity_id).await {
+            Ok(Some(entity)) => entity,
+            Ok(None) => {
+                tracing::debug!(%entity_id, "entity gone before history could be recorded");
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(%err, %entity_id, "failed to fetch entity for history recording");
+                return;
+            }
+        };
+        let history = EntityHistory::builder()
+            .entity_id(entity.id)
+            .state(entity.state)
+            .attributes(entity.attributes)
+            .recorded_at(event.timestamp)
+            .build();
+        if let Err(err) = self.history_repo.record(history).await {
+            tracing::warn!(%err, %entity_id, "failed to record entity history");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minihub_domain::entity::{Entity, EntityState};
+    use minihub_domain::id::{DeviceId, EntityId};
+    use minihub_domain::time::Timestamp;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    /// Appends its tag to `data.trail`.
+    struct Tag(&'static str);
+
+    impl EventHook for Tag {
+        async fn before_publish(&self, mut event: Event) -> Option<Event> {
+            let trail = event.data["trail"].as_str().unwrap_or_default().to_string();
+            event.data["trail"] = serde_json::json!(format!("{trail}{}", self.0));
+            Some(event)
+        }
+    }
+
+    /// Drops every `AttributeChanged` event.
+    struct DropAttributeChanges;
+
+    impl EventHook for DropAttributeChanges {
+        async fn before_publish(&self, event: Event) -> Option<Event> {
+            (event.event_type != EventType::AttributeChanged).then_some(event)
+        }
+    }
+
+    #[derive(Default)]
+    struct CountPersisted(Mutex<usize>);
+
+    impl EventHook for CountPersisted {
+        async fn after_persist(&self, _event: &Event) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    fn event(event_type: EventType) -> Event {
+        Event::new(event_type, None, serde_json::json!({}))
+    }
+
+    #[tokio::test]
+    async fn should_publish_unchanged_when_no_hooks() {
+        let pipeline = EventPipeline::new(SpyPublisher::default());
+        let event = event(EventType::StateChanged);
+        let event_id = event.id;
+
+        pipeline.publish(event).await.unwrap();
+
+        let published = pipeline.publisher.events.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].id, event_id);
+    }
+
+    #[tokio::test]
+    async fn should_run_before_publish_hooks_in_registration_order() {
+        let pipeline = EventPipeline::new(SpyPublisher::default())
+            .hook(Tag("a"))
+            .hook(Tag("b"));
+
+        pipeline
+            .publish(event(EventType::StateChanged))
+            .await
+            .unwrap();
+
+        let published = pipeline.publisher.events.lock().unwrap();
+        assert_eq!(published[0].data["trail"], "ab");
+    }
+
+    #[tokio::test]
+    async fn should_drop_event_when_hook_returns_none() {
+        let pipeline = EventPipeline::new(SpyPublisher::default())
+            .hook(DropAttributeChanges)
+            .hook(Tag("never"));
+
+        pipeline
+            .publish(event(EventType::AttributeChanged))
+            .await
+            .unwrap();
+        pipeline
+            .publish(event(EventType::StateChanged))
+            .await
+            .unwrap();
+
+        let published = pipeline.publisher.events.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::StateChanged);
+    }
+
+    #[tokio::test]
+    async fn should_run_after_persist_hooks() {
+        let counter = Arc::new(CountPersisted::default());
+        let pipeline = EventPipeline::new(SpyPublisher::default())
+            .hook(Arc::clone(&counter))
+            .hook(Arc::clone(&counter));
+
+        pipeline
+            .after_persist(&event(EventType::StateChanged))
+            .await;
+
+        assert_eq!(*counter.0.lock().unwrap(), 2);
+    }
+
+    struct SingleEntityRepo(Entity);
+
+    impl EntityRepository for SingleEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok((self.0.id == id).then(|| self.0.clone()))
+        }
+
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(ids
+                .contains(&self.0.id)
+                .then(|| self.0.clone())
+                .into_iter()
+                .collect())
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![self.0.clone()])
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(
+            &self,
+            _entity_id: &str,
+        ) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyHistoryRepo {
+        records: Mutex<Vec<EntityHistory>>,
+    }
+
+    impl EntityHistoryRepository for SpyHistoryRepo {
+        async fn record(&self, history: EntityHistory) -> Result<EntityHistory, MiniHubError> {
+            self.records.lock().unwrap().push(history.clone());
+            Ok(history)
+        }
+        async fn find_by_entity_in_range(
+            &self,
+            _entity_id: EntityId,
+            _from: Timestamp,
+            _to: Timestamp,
+            _limit: Option<usize>,
+        ) -> Result<Vec<EntityHistory>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn purge_before(&self, _before: Timestamp) -> Result<usize, MiniHubError> {
+            Ok(0)
+        }
+    }
+
+    fn light() -> Entity {
+        Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::On)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_record_history_when_state_changed_is_persisted() {
+        let light = light();
+        let recorder =
+            EntityHistoryRecorder::new(SingleEntityRepo(light.clone()), SpyHistoryRepo::default());
+        let event = Event::new(
+            EventType::StateChanged,
+            Some(light.id),
+            serde_json::json!({}),
+        );
+
+        recorder.after_persist(&event).await;
+
+        let records = recorder.history_repo.records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].entity_id, light.id);
+        assert_eq!(records[0].state, EntityState::On);
+        assert_eq!(records[0].recorded_at, event.timestamp);
+    }
+
+    #[tokio::test]
+    async fn should_not_record_history_for_other_events() {
+        let light = light();
+        let recorder =
+            EntityHistoryRecorder::new(SingleEntityRepo(light.clone()), SpyHistoryRepo::default());
+        let event = Event::new(
+            EventType::EntityCreated,
+            Some(light.id),
+            serde_json::json!({}),
+        );
+
+        recorder.after_persist(&event).await;
+
+        assert!(recorder.history_repo.records.lock().unwrap().is_empty());
+    }
+}
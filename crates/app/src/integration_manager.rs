@@ -0,0 +1,924 @@
+//! Integration manager — starts integrations and tracks their status.
+//!
+//! Integrations are started concurrently once the daemon is serving. Each
+//! startup (`setup` followed by `start_background`) gets a deadline: an
+//! integration that misses it is reported as [`IntegrationStatus::TimedOut`]
+//! but keeps starting in the background, and is reported as running if it
+//! eventually succeeds. A failing integration never aborts the daemon: it is
+//! torn down and started again with an exponential backoff, as set by its
+//! [`RestartPolicy`], before being reported as failed.
+//!
+//! Integrations started with [`IntegrationManager::supervise`] stay owned by
+//! their task once started, so they can be restarted, disabled and enabled
+//! again at runtime through the manager, and are torn down when the daemon
+//! shuts down.
+//!
+//! Running integrations forward the service calls requested on the event
+//! bus to their devices with [`forward_service_calls`].
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc};
+
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload, ServiceCallResultPayload};
+
+use crate::ports::integration::{Integration, IntegrationContext};
+use crate::shutdown::ShutdownSignal;
+
+/// Lifecycle status of an integration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrationStatus {
+    /// Turned off in the configuration.
+    Disabled,
+    /// Enabled, startup still in progress.
+    Starting,
+    /// Startup completed successfully.
+    Running,
+    /// Startup missed its deadline and is still in progress.
+    TimedOut,
+    /// Startup failed with the given error and will be retried.
+    Restarting {
+        /// Number of the upcoming restart, starting at 1.
+        attempt: u32,
+        /// Error of the failed startup.
+        error: String,
+    },
+    /// Startup failed with the given error.
+    Failed(String),
+}
+
+impl IntegrationStatus {
+    /// Machine-readable name of the status, e.g. `"timed_out"`.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Starting => "starting",
+            Self::Running => "running",
+            Self::TimedOut => "timed_out",
+            Self::Restarting { .. } => "restarting",
+            Self::Failed(_) => "failed",
+        }
+    }
+
+    /// The last startup error, if the integration failed.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Self::Restarting { error, .. } | Self::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An integration known to the manager and its current status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrationReport {
+    /// Integration name, e.g. `"mqtt"`.
+    pub name: String,
+    /// Whether the integration is enabled, in the configuration or at
+    /// runtime.
+    pub enabled: bool,
+    /// Current lifecycle status.
+    pub status: IntegrationStatus,
+    /// Number of times the integration was restarted after a failure.
+    pub restarts: u32,
+}
+
+/// How failed integrations are restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Restarts attempted before giving up; `0` never restarts.
+    pub max_restarts: u32,
+    /// Delay before the first restart, doubled after every failure.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two restarts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_mins(1),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restart number `attempt`, starting at 1.
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Runtime request sent to a supervised integration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Restart,
+    Disable,
+    Enable,
+}
+
+/// Starts integrations and keeps track of their status.
+///
+/// Cloning is cheap: clones share the same status table and control
+/// handles, so the HTTP layer can read what the startup tasks write and
+/// send them commands.
+#[derive(Debug, Clone)]
+pub struct IntegrationManager {
+    reports: Arc<RwLock<Vec<IntegrationReport>>>,
+    controls: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Command>>>>,
+    setup_timeout: Duration,
+    restart_policy: RestartPolicy,
+}
+
+impl Default for IntegrationManager {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+impl IntegrationManager {
+    /// Create a manager giving every integration `setup_timeout` to start.
+    #[must_use]
+    pub fn new(setup_timeout: Duration) -> Self {
+        Self {
+            reports: Arc::default(),
+            controls: Arc::default(),
+            setup_timeout,
+            restart_policy: RestartPolicy::default(),
+        }
+    }
+
+    /// Restart failed integrations according to `policy`.
+    #[must_use]
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Declare the integration `name`.
+    ///
+    /// Enabled integrations start as [`IntegrationStatus::Starting`],
+    /// disabled ones as [`IntegrationStatus::Disabled`]. Registering a name
+    /// twice replaces the previous entry.
+    pub fn register(&self, name: impl Into<String>, enabled: bool) {
+        let name = name.into();
+        let status = if enabled {
+            IntegrationStatus::Starting
+        } else {
+            IntegrationStatus::Disabled
+        };
+        let mut reports = self.reports.write().unwrap_or_else(PoisonError::into_inner);
+        match reports.iter_mut().find(|report| report.name == name) {
+            Some(report) => {
+                report.enabled = enabled;
+                report.status = status;
+                report.restarts = 0;
+            }
+            None => reports.push(IntegrationReport {
+                name,
+                enabled,
+                status,
+                restarts: 0,
+            }),
+        }
+    }
+
+    /// Set the status of the integration `name`, if registered.
+    pub fn set_status(&self, name: &str, status: IntegrationStatus) {
+        let mut reports = self.reports.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(report) = reports.iter_mut().find(|report| report.name == name) {
+            report.status = status;
+        } else {
+            tracing::warn!(integration = name, "status update for unknown integration");
+        }
+    }
+
+    /// Snapshot of every registered integration, in registration order.
+    #[must_use]
+    pub fn reports(&self) -> Vec<IntegrationReport> {
+        self.reports
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Run `setup` then `start_background` on `integration`, recording the
+    /// outcome.
+    ///
+    /// A failed startup is followed by `teardown` and a new attempt after
+    /// the policy backoff, until it succeeds or the restarts run out.
+    /// Resolves once startup has finished, even past the deadline. Meant to
+    /// be spawned as its own task so a slow integration does not hold back
+    /// the others.
+    pub async fn start<I, C>(&self, mut integration: I, ctx: C)
+    where
+        I: Integration + Send,
+        C: IntegrationContext + Clone + 'static,
+    {
+        self.run(&mut integration, &ctx).await;
+    }
+
+    /// [`start`](Self::start) `integration`, then keep it and apply the
+    /// [`restart`](Self::restart), [`disable`](Self::disable) and
+    /// [`enable`](Self::enable) requests made for it.
+    ///
+    /// Requests made while a startup is in progress are applied once it
+    /// finishes. Runs until `shutdown` is requested, then tears the
+    /// integration down, so it is meant to be spawned as its own task.
+    pub async fn supervise<I, C>(&self, mut integration: I, ctx: C, mut shutdown: ShutdownSignal)
+    where
+        I: Integration + Send,
+        C: IntegrationContext + Clone + 'static,
+    {
+        let name = integration.name();
+        let (sender, mut commands) = mpsc::unbounded_channel();
+        self.controls
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.to_string(), sender);
+
+        tokio::select! {
+            () = self.run(&mut integration, &ctx) => {}
+            () = shutdown.requested() => {
+                tracing::info!(integration = name, "stopping integration during startup");
+                self.teardown(&mut integration).await;
+                return;
+            }
+        }
+        let mut active = true;
+        loop {
+            let command = tokio::select! {
+                command = commands.recv() => command,
+                () = shutdown.requested() => None,
+            };
+            let Some(command) = command else {
+                break;
+            };
+            match command {
+                Command::Restart | Command::Enable if !active => {
+                    tracing::info!(integration = name, "enabling integration");
+                    self.set_enabled(name, true);
+                    active = true;
+                    self.run(&mut integration, &ctx).await;
+                }
+                Command::Restart => {
+                    tracing::info!(integration = name, "restarting integration");
+                    self.teardown(&mut integration).await;
+                    self.run(&mut integration, &ctx).await;
+                }
+                Command::Disable if active => {
+                    tracing::info!(integration = name, "disabling integration");
+                    self.teardown(&mut integration).await;
+                    self.set_enabled(name, false);
+                    active = false;
+                }
+                Command::Disable | Command::Enable => {}
+            }
+        }
+        if active {
+            tracing::info!(integration = name, "stopping integration");
+            self.teardown(&mut integration).await;
+        }
+    }
+
+    /// Restart the supervised integration `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no integration `name` is
+    /// supervised.
+    pub fn restart(&self, name: &str) -> Result<(), MiniHubError> {
+        self.send(name, Command::Restart)
+    }
+
+    /// Tear down the supervised integration `name` and report it as
+    /// disabled until it is enabled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no integration `name` is
+    /// supervised.
+    pub fn disable(&self, name: &str) -> Result<(), MiniHubError> {
+        self.send(name, Command::Disable)
+    }
+
+    /// Start again the supervised integration `name` after it was disabled.
+    ///
+    /// Integrations disabled in the configuration were never created, so
+    /// they cannot be enabled at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no integration `name` is
+    /// supervised.
+    pub fn enable(&self, name: &str) -> Result<(), MiniHubError> {
+        self.send(name, Command::Enable)
+    }
+
+    fn send(&self, name: &str, command: Command) -> Result<(), MiniHubError> {
+        let controls = self.controls.read().unwrap_or_else(PoisonError::into_inner);
+        controls
+            .get(name)
+            .and_then(|sender| sender.send(command).ok())
+            .ok_or_else(|| {
+                NotFoundError {
+                    entity: "Integration",
+                    id: name.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Startup with restarts, see [`start`](Self::start).
+    async fn run<I, C>(&self, integration: &mut I, ctx: &C)
+    where
+        I: Integration + Send,
+        C: IntegrationContext + Clone + 'static,
+    {
+        let name = integration.name();
+        self.set_status(name, IntegrationStatus::Starting);
+
+        let mut attempt = 0;
+        loop {
+            let err = match self.start_once(integration, ctx).await {
+                Ok(()) => {
+                    tracing::info!(integration = name, "integration ready");
+                    self.set_status(name, IntegrationStatus::Running);
+                    return;
+                }
+                Err(err) => err,
+            };
+            if attempt >= self.restart_policy.max_restarts {
+                tracing::error!(integration = name, %err, "integration failed to start");
+                self.set_status(name, IntegrationStatus::Failed(err.to_string()));
+                return;
+            }
+
+            attempt += 1;
+            let backoff = self.restart_policy.backoff(attempt);
+            tracing::warn!(
+                integration = name,
+                %err,
+                attempt,
+                backoff_secs = backoff.as_secs_f64(),
+                "integration failed to start, restarting"
+            );
+            self.set_status(
+                name,
+                IntegrationStatus::Restarting {
+                    attempt,
+                    error: err.to_string(),
+                },
+            );
+            self.teardown(integration).await;
+            tokio::time::sleep(backoff).await;
+            self.record_restart(name);
+        }
+    }
+
+    /// One startup attempt, reporting [`IntegrationStatus::TimedOut`] when
+    /// it misses the deadline.
+    async fn start_once<I, C>(&self, integration: &mut I, ctx: &C) -> Result<(), MiniHubError>
+    where
+        I: Integration + Send,
+        C: IntegrationContext + Clone + 'static,
+    {
+        let name = integration.name();
+        let mut startup = std::pin::pin!(async {
+            integration.setup(ctx).await?;
+            integration.start_background(ctx.clone()).await
+        });
+        if let Ok(result) = tokio::time::timeout(self.setup_timeout, &mut startup).await {
+            result
+        } else {
+            tracing::warn!(
+                integration = name,
+                timeout_secs = self.setup_timeout.as_secs(),
+                "integration setup timed out, still waiting for it in the background"
+            );
+            self.set_status(name, IntegrationStatus::TimedOut);
+            startup.await
+        }
+    }
+
+    async fn teardown<I: Integration + Send>(&self, integration: &mut I) {
+        if let Err(err) = integration.teardown().await {
+            tracing::warn!(integration = integration.name(), %err, "integration teardown failed");
+        }
+    }
+
+    /// Report `name` as enabled and starting, or as disabled.
+    fn set_enabled(&self, name: &str, enabled: bool) {
+        let mut reports = self.reports.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(report) = reports.iter_mut().find(|report| report.name == name) {
+            report.enabled = enabled;
+            report.status = if enabled {
+                IntegrationStatus::Starting
+            } else {
+                IntegrationStatus::Disabled
+            };
+        }
+    }
+
+    /// Count a restart of `name` and mark it as starting again.
+    fn record_restart(&self, name: &str) {
+        let mut reports = self.reports.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(report) = reports.iter_mut().find(|report| report.name == name) {
+            report.restarts += 1;
+            report.status = IntegrationStatus::Starting;
+        }
+    }
+}
+
+/// Forward the [`EventType::ServiceCallRequested`] events of `rx` to
+/// `call` until the bus closes, publishing the outcome of each call as a
+/// `ServiceCallCompleted` or `ServiceCallFailed` event caused by the
+/// request. `integration` names the caller in the logs.
+///
+/// Requests are resolved through the persisted entity's `entity_id`
+/// string, since persisted ids differ from the ones integrations discover
+/// locally. `call` gets that string with the requested service, and
+/// answers `None` for the entities its integration does not own.
+pub async fn forward_service_calls<C, F, Fut, E>(
+    integration: &'static str,
+    mut rx: broadcast::Receiver<Event>,
+    ctx: C,
+    mut call: F,
+) where
+    C: IntegrationContext,
+    F: FnMut(String, ServiceCallPayload) -> Fut,
+    Fut: Future<Output = Option<Result<(), E>>>,
+    E: Display,
+{
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    integration,
+                    skipped,
+                    "event subscriber lagged, some events were missed"
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => {
+                tracing::info!(integration, "event subscriber channel closed, stopping");
+                break;
+            }
+        };
+
+        if event.event_type != EventType::ServiceCallRequested {
+            continue;
+        }
+        let Some(entity_id) = event.entity_id else {
+            continue;
+        };
+        let key = match ctx.find_entity_by_id(entity_id).await {
+            Ok(Some(entity)) => entity.entity_id,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!(%err, %entity_id, "failed to look up entity for service call");
+                continue;
+            }
+        };
+        let Ok(request) = event.payload::<ServiceCallPayload>() else {
+            continue;
+        };
+        let service = request.service.clone();
+
+        let (event_type, payload) = match call(key, request).await {
+            None => continue,
+            Some(Ok(())) => (
+                EventType::ServiceCallCompleted,
+                ServiceCallResultPayload::completed(&service),
+            ),
+            Some(Err(err)) => {
+                tracing::warn!(%err, %entity_id, service, integration, "service call failed");
+                (
+                    EventType::ServiceCallFailed,
+                    ServiceCallResultPayload::failed(&service, err.to_string()),
+                )
+            }
+        };
+        let result_event =
+            Event::with_payload(event_type, Some(entity_id), &payload).with_cause(&event);
+        if let Err(err) = ctx.publish(result_event).await {
+            tracing::warn!(%err, "failed to publish service call result event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use minihub_domain::entity::Entity;
+    use minihub_domain::id::EntityId;
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::shutdown::ShutdownCoordinator;
+    use crate::testing::FakeContext;
+
+    /// Integration whose setup waits for `release` (when set) and then
+    /// fails when `fail` is set, or for its first `failures` attempts.
+    #[derive(Default)]
+    struct StubIntegration {
+        release: Option<Arc<Notify>>,
+        fail: bool,
+        failures: u32,
+        setups: Arc<AtomicU32>,
+        teardowns: Arc<AtomicU32>,
+    }
+
+    impl Integration for StubIntegration {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn setup(
+            &mut self,
+            _ctx: &impl IntegrationContext,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.setups.fetch_add(1, Ordering::SeqCst);
+            let release = self.release.clone();
+            let fail = self.fail || self.failures > 0;
+            self.failures = self.failures.saturating_sub(1);
+            async move {
+                if let Some(release) = release {
+                    release.notified().await;
+                }
+                if fail {
+                    return Err(MiniHubError::NotFound(NotFoundError {
+                        entity: "broker",
+                        id: "localhost:1883".to_string(),
+                    }));
+                }
+                Ok(())
+            }
+        }
+
+        async fn handle_service_call(
+            &self,
+            entity_id: EntityId,
+            _service: &str,
+            _data: serde_json::Value,
+        ) -> Result<Entity, MiniHubError> {
+            Err(MiniHubError::NotFound(NotFoundError {
+                entity: "entity",
+                id: entity_id.to_string(),
+            }))
+        }
+
+        async fn teardown(&mut self) -> Result<(), MiniHubError> {
+            self.teardowns.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn status_of(manager: &IntegrationManager, name: &str) -> IntegrationStatus {
+        manager
+            .reports()
+            .into_iter()
+            .find(|report| report.name == name)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn should_register_disabled_and_starting_integrations_in_order() {
+        let manager = IntegrationManager::default();
+
+        manager.register("virtual", true);
+        manager.register("mqtt", false);
+
+        let reports = manager.reports();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].name, "virtual");
+        assert_eq!(reports[0].status, IntegrationStatus::Starting);
+        assert_eq!(reports[1].name, "mqtt");
+        assert!(!reports[1].enabled);
+        assert_eq!(reports[1].status, IntegrationStatus::Disabled);
+    }
+
+    #[tokio::test]
+    async fn should_report_running_when_startup_succeeds() {
+        let manager = IntegrationManager::default();
+        manager.register("stub", true);
+
+        manager
+            .start(StubIntegration::default(), FakeContext::new())
+            .await;
+
+        assert_eq!(status_of(&manager, "stub"), IntegrationStatus::Running);
+    }
+
+    fn fast_restarts(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_report_failed_when_setup_keeps_returning_error() {
+        let manager = IntegrationManager::default().with_restart_policy(fast_restarts(2));
+        manager.register("stub", true);
+        let integration = StubIntegration {
+            fail: true,
+            ..StubIntegration::default()
+        };
+
+        manager.start(integration, FakeContext::new()).await;
+
+        let report = manager.reports().remove(0);
+        assert_eq!(report.status.as_str(), "failed");
+        assert!(report.status.error().unwrap().contains("localhost:1883"));
+        assert_eq!(report.restarts, 2);
+    }
+
+    #[tokio::test]
+    async fn should_report_running_when_restart_succeeds() {
+        let manager = IntegrationManager::default().with_restart_policy(fast_restarts(3));
+        manager.register("stub", true);
+        let integration = StubIntegration {
+            failures: 1,
+            ..StubIntegration::default()
+        };
+
+        manager.start(integration, FakeContext::new()).await;
+
+        let report = manager.reports().remove(0);
+        assert_eq!(report.status, IntegrationStatus::Running);
+        assert_eq!(report.restarts, 1);
+    }
+
+    #[tokio::test]
+    async fn should_report_restarting_while_waiting_for_backoff() {
+        let manager = IntegrationManager::default().with_restart_policy(RestartPolicy {
+            max_restarts: 1,
+            initial_backoff: Duration::from_hours(1),
+            max_backoff: Duration::from_hours(1),
+        });
+        manager.register("stub", true);
+        let integration = StubIntegration {
+            failures: 1,
+            ..StubIntegration::default()
+        };
+
+        let task = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.start(integration, FakeContext::new()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let status = status_of(&manager, "stub");
+        assert_eq!(status.as_str(), "restarting");
+        assert!(matches!(
+            status,
+            IntegrationStatus::Restarting { attempt: 1, .. }
+        ));
+        task.abort();
+    }
+
+    #[test]
+    fn should_double_backoff_up_to_the_maximum() {
+        let policy = RestartPolicy {
+            max_restarts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10));
+        assert_eq!(policy.backoff(40), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn should_report_timed_out_then_running_when_setup_is_late() {
+        let manager = IntegrationManager::new(Duration::from_millis(10));
+        manager.register("stub", true);
+        let release = Arc::new(Notify::new());
+        let integration = StubIntegration {
+            release: Some(Arc::clone(&release)),
+            ..StubIntegration::default()
+        };
+
+        let task = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.start(integration, FakeContext::new()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(status_of(&manager, "stub"), IntegrationStatus::TimedOut);
+
+        release.notify_one();
+        task.await.unwrap();
+        assert_eq!(status_of(&manager, "stub"), IntegrationStatus::Running);
+    }
+
+    async fn wait_for_status(manager: &IntegrationManager, status: &IntegrationStatus) {
+        for _ in 0..100 {
+            if &status_of(manager, "stub") == status {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("expected {status:?}, got {:?}", status_of(manager, "stub"));
+    }
+
+    #[tokio::test]
+    async fn should_disable_and_enable_supervised_integration() {
+        let manager = IntegrationManager::default();
+        manager.register("stub", true);
+        let setups = Arc::new(AtomicU32::new(0));
+        let integration = StubIntegration {
+            setups: Arc::clone(&setups),
+            ..StubIntegration::default()
+        };
+        let task = tokio::spawn({
+            let manager = manager.clone();
+            let signal = ShutdownCoordinator::new().signal();
+            async move {
+                manager
+                    .supervise(integration, FakeContext::new(), signal)
+                    .await;
+            }
+        });
+        wait_for_status(&manager, &IntegrationStatus::Running).await;
+
+        manager.disable("stub").unwrap();
+        wait_for_status(&manager, &IntegrationStatus::Disabled).await;
+        assert!(!manager.reports()[0].enabled);
+
+        manager.enable("stub").unwrap();
+        wait_for_status(&manager, &IntegrationStatus::Running).await;
+        assert!(manager.reports()[0].enabled);
+        assert_eq!(setups.load(Ordering::SeqCst), 2);
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn should_restart_supervised_integration_after_it_failed() {
+        let manager = IntegrationManager::default().with_restart_policy(fast_restarts(0));
+        manager.register("stub", true);
+        let integration = StubIntegration {
+            failures: 1,
+            ..StubIntegration::default()
+        };
+        let task = tokio::spawn({
+            let manager = manager.clone();
+            let signal = ShutdownCoordinator::new().signal();
+            async move {
+                manager
+                    .supervise(integration, FakeContext::new(), signal)
+                    .await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(status_of(&manager, "stub").as_str(), "failed");
+
+        manager.restart("stub").unwrap();
+        wait_for_status(&manager, &IntegrationStatus::Running).await;
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn should_tear_down_supervised_integration_on_shutdown() {
+        let manager = IntegrationManager::default();
+        manager.register("stub", true);
+        let teardowns = Arc::new(AtomicU32::new(0));
+        let integration = StubIntegration {
+            teardowns: Arc::clone(&teardowns),
+            ..StubIntegration::default()
+        };
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.spawn_graceful("stub", {
+            let manager = manager.clone();
+            |signal| async move {
+                manager
+                    .supervise(integration, FakeContext::new(), signal)
+                    .await;
+            }
+        });
+        wait_for_status(&manager, &IntegrationStatus::Running).await;
+
+        let aborted = coordinator.shutdown(Duration::from_secs(1)).await;
+
+        assert!(aborted.is_empty());
+        assert_eq!(teardowns.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn should_return_not_found_when_controlling_unsupervised_integration() {
+        let manager = IntegrationManager::default();
+        manager.register("mqtt", false);
+
+        assert!(matches!(
+            manager.restart("mqtt"),
+            Err(MiniHubError::NotFound(_))
+        ));
+        assert!(matches!(
+            manager.disable("unknown"),
+            Err(MiniHubError::NotFound(_))
+        ));
+    }
+
+    /// Context storing `light.kitchen`, and the id it was persisted with.
+    fn kitchen_context() -> (FakeContext, EntityId) {
+        let light = Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .build()
+            .unwrap();
+        let id = light.id;
+        (FakeContext::with_entities(vec![light]), id)
+    }
+
+    fn service_call(entity_id: EntityId) -> Event {
+        Event::with_payload(
+            EventType::ServiceCallRequested,
+            Some(entity_id),
+            &ServiceCallPayload {
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            },
+        )
+    }
+
+    /// Forward `request` to a `call` owning `light.kitchen` and answering
+    /// `outcome`, returning what was published.
+    async fn forward(
+        ctx: FakeContext,
+        request: Event,
+        outcome: Result<(), &'static str>,
+    ) -> Vec<Event> {
+        let task = tokio::spawn(forward_service_calls(
+            "stub",
+            ctx.subscribe(),
+            ctx.clone(),
+            move |key, _request| async move { (key == "light.kitchen").then_some(outcome) },
+        ));
+        ctx.send(request);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        task.abort();
+        ctx.published()
+    }
+
+    #[tokio::test]
+    async fn should_publish_completed_service_call_caused_by_request() {
+        let (ctx, id) = kitchen_context();
+        let request = service_call(id);
+
+        let published = forward(ctx, request.clone(), Ok(())).await;
+
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallCompleted);
+        assert_eq!(published[0].entity_id, Some(id));
+        assert_eq!(published[0].caused_by, Some(request.id));
+    }
+
+    #[tokio::test]
+    async fn should_publish_failed_service_call_with_its_error() {
+        let (ctx, id) = kitchen_context();
+
+        let published = forward(ctx, service_call(id), Err("device offline")).await;
+
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].event_type, EventType::ServiceCallFailed);
+        let payload: ServiceCallResultPayload = published[0].payload().unwrap();
+        assert_eq!(payload.error.as_deref(), Some("device offline"));
+    }
+
+    #[tokio::test]
+    async fn should_ignore_service_call_for_entity_of_other_integration() {
+        let (ctx, _) = kitchen_context();
+        let other = Entity::builder()
+            .entity_id("light.porch")
+            .friendly_name("Porch")
+            .build()
+            .unwrap();
+        let other_id = other.id;
+        ctx.upsert_entity(other).await.unwrap();
+
+        let published = forward(ctx, service_call(other_id), Ok(())).await;
+
+        assert!(published.is_empty());
+    }
+}
@@ -9,18 +9,62 @@
 //!   - `AreaRepository` — CRUD for areas
 //!   - `EventStore` — append & query events
 //!   - `AutomationRepository` — CRUD for automations
+//!   - `AutomationRunRepository` — append & query automation execution logs
+//!   - `ReportRepository` — aggregated read models across repositories
+//!   - `SceneRepository` — CRUD for scenes
+//!   - `GroupRepository` — CRUD for entity groups
+//!   - `AuditRepository` — append & query the audit log of write operations
+//!   - `SettingsRepository` — hub-wide settings stored as JSON values under namespaced keys
+//!   - `Notifier` — deliver notifications to humans
+//!   - `Storage` — reachability of the storage backend
 //! - Define **driving/inbound ports** as use-case structs/traits:
 //!   - `EntityService` — register, update state, list, get
 //!   - `DeviceService` — register, list, get
+//!   - `DeviceRegistry` — deduplicate devices reported by integrations
+//!   - `DiscoveryService` — inbox of detected devices, adopted as registered devices
+//!   - `SceneService` — CRUD for scenes, activate a scene
+//!   - `GroupService` — CRUD for groups, aggregate their state, fan out their service calls
+//!   - `DashboardService` — read and replace the dashboard layout
+//!   - `SettingsService` — read and replace the settings of a namespace
 //!   - `AutomationEngine` — evaluate triggers, run actions
-//! - Provide **in-process infrastructure** (event bus) that doesn't need IO
+//!   - `ConditionEvaluator` — check automation conditions, trace dry runs
+//!   - `NotificationService` — forward requested notifications to a `Notifier`
+//!   - `IntegrationManager` — start integrations concurrently and track their status
+//!   - `ReloadHandle` — ask the daemon to re-read its configuration
+//!   - `BackupHandle` — ask the daemon for a database snapshot
+//!   - `HealthService` — liveness and readiness of the daemon's components
+//!   - `HistorySnapshotService` — record every entity into history at a regular interval
+//!   - `ShutdownCoordinator` — stop background tasks within a grace period on shutdown
+//! - Provide the **audit log**: `Audited` repositories recording every create,
+//!   update and delete with the `Actor` the current task acts for
+//! - Provide **in-process infrastructure** (event bus, metrics registry) that doesn't need IO
+//! - Provide **durable event dispatch** (`ReplayableEventBus`): events stored
+//!   before being broadcast, replayed from the `EventStore` to lagging subscribers
+//! - Import **automations defined as YAML files**, reconciled with the
+//!   stored ones by a stable source key
+//! - Provide the **event pipeline**: composable `EventHook` middlewares run
+//!   before events are published and after they are persisted
 //! - Orchestrate domain objects without knowing *how* persistence or IO works
+//! - Provide **test doubles** of the ports (`testing`, behind the
+//!   `test-util` feature) shared by the adapter tests
 //!
 //! ## Dependency rule
 //! Depends on `minihub-domain` only (plus `tokio::sync` for channels).
 //! Never imports adapter crates. Adapters depend on *this* crate, not the reverse.
 
+pub mod audit;
 pub mod automation_engine;
+pub mod automation_import;
+pub mod backup;
+pub mod condition_evaluator;
+pub mod config_reload;
 pub mod event_bus;
+pub mod event_pipeline;
+pub mod integration_manager;
+pub mod metrics;
 pub mod ports;
+pub mod replayable_event_bus;
 pub mod services;
+pub mod shutdown;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
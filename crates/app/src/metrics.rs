@@ -0,0 +1,304 @@
+//! Runtime metrics — counters, gauges and summaries rendered in the
+//! Prometheus text exposition format.
+//!
+//! [`Metrics`] is a cheaply cloneable registry shared by every component
+//! that records something: the HTTP layer, the event bus, the integration
+//! context and the daemon itself. Every metric it knows is declared below,
+//! so the catalogue stays in one place.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// How a metric's values combine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// Monotonic total, only ever incremented.
+    Counter,
+    /// Current value, set on every sample.
+    Gauge,
+    /// Sum and count of observations, e.g. durations.
+    Summary,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Summary => "summary",
+        }
+    }
+}
+
+/// Name, help text and kind of a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metric {
+    /// Prometheus metric name, e.g. `"minihub_http_requests_total"`.
+    pub name: &'static str,
+    /// One-line description shown by `# HELP`.
+    pub help: &'static str,
+    /// How values combine.
+    pub kind: MetricKind,
+}
+
+/// HTTP requests served, by `method`, `route` and `status`.
+pub const HTTP_REQUESTS: Metric = Metric {
+    name: "minihub_http_requests_total",
+    help: "HTTP requests served.",
+    kind: MetricKind::Counter,
+};
+
+/// HTTP request latency, by `method` and `route`.
+pub const HTTP_REQUEST_DURATION: Metric = Metric {
+    name: "minihub_http_request_duration_seconds",
+    help: "Time spent answering HTTP requests.",
+    kind: MetricKind::Summary,
+};
+
+/// Events published on the event bus, by `event_type`.
+pub const EVENTS_PUBLISHED: Metric = Metric {
+    name: "minihub_events_published_total",
+    help: "Events published on the event bus.",
+    kind: MetricKind::Counter,
+};
+
+/// Events waiting in the event bus for the slowest subscriber.
+pub const EVENT_BUS_QUEUED: Metric = Metric {
+    name: "minihub_event_bus_queued_events",
+    help: "Events waiting in the event bus for the slowest subscriber.",
+    kind: MetricKind::Gauge,
+};
+
+/// Active event bus subscribers.
+pub const EVENT_BUS_SUBSCRIBERS: Metric = Metric {
+    name: "minihub_event_bus_subscribers",
+    help: "Active event bus subscribers.",
+    kind: MetricKind::Gauge,
+};
+
+/// Events a subscriber missed because it lagged behind, by `subscriber`.
+pub const EVENT_BUS_LAGGED: Metric = Metric {
+    name: "minihub_event_bus_lagged_events_total",
+    help: "Events dropped for a subscriber that lagged behind the event bus.",
+    kind: MetricKind::Counter,
+};
+
+/// Stored entities, by the `integration` of their device.
+pub const ENTITIES: Metric = Metric {
+    name: "minihub_entities",
+    help: "Stored entities.",
+    kind: MetricKind::Gauge,
+};
+
+/// Entity updates reported by integrations, by `integration`.
+pub const INTEGRATION_UPDATES: Metric = Metric {
+    name: "minihub_integration_updates_total",
+    help: "Entity updates reported by integrations.",
+    kind: MetricKind::Counter,
+};
+
+/// Entity updates dropped by the update throttle, by `integration`.
+pub const INTEGRATION_UPDATES_THROTTLED: Metric = Metric {
+    name: "minihub_integration_updates_throttled_total",
+    help: "Entity updates dropped by the update throttle.",
+    kind: MetricKind::Counter,
+};
+
+/// Open database connections.
+pub const DB_POOL_CONNECTIONS: Metric = Metric {
+    name: "minihub_db_pool_connections",
+    help: "Open database connections.",
+    kind: MetricKind::Gauge,
+};
+
+/// Open database connections not currently in use.
+pub const DB_POOL_IDLE_CONNECTIONS: Metric = Metric {
+    name: "minihub_db_pool_idle_connections",
+    help: "Open database connections not currently in use.",
+    kind: MetricKind::Gauge,
+};
+
+type Labels = Vec<(&'static str, String)>;
+
+/// Value of one labelled series.
+#[derive(Debug, Default, Clone, Copy)]
+struct Series {
+    /// Counter total, gauge value or summary sum.
+    value: f64,
+    /// Number of observations, for summaries.
+    count: u64,
+}
+
+#[derive(Debug)]
+struct Family {
+    metric: Metric,
+    series: BTreeMap<Labels, Series>,
+}
+
+/// Shared registry of metric values.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    families: Arc<Mutex<BTreeMap<&'static str, Family>>>,
+}
+
+impl Metrics {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one to the counter `metric`.
+    pub fn increment(&self, metric: &Metric, labels: &[(&'static str, &str)]) {
+        self.add(metric, labels, 1.0);
+    }
+
+    /// Add `value` to the counter `metric`.
+    pub fn add(&self, metric: &Metric, labels: &[(&'static str, &str)], value: f64) {
+        self.update(metric, labels, |series| series.value += value);
+    }
+
+    /// Set the gauge `metric` to `value`.
+    pub fn set(&self, metric: &Metric, labels: &[(&'static str, &str)], value: f64) {
+        self.update(metric, labels, |series| series.value = value);
+    }
+
+    /// Record one observation of `value` in the summary `metric`.
+    pub fn observe(&self, metric: &Metric, labels: &[(&'static str, &str)], value: f64) {
+        self.update(metric, labels, |series| {
+            series.value += value;
+            series.count += 1;
+        });
+    }
+
+    /// Forget every series of `metric`, e.g. before setting a gauge whose
+    /// label values may have disappeared.
+    pub fn reset(&self, metric: &Metric) {
+        let mut families = self.families.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(family) = families.get_mut(metric.name) {
+            family.series.clear();
+        }
+    }
+
+    fn update(
+        &self,
+        metric: &Metric,
+        labels: &[(&'static str, &str)],
+        apply: impl FnOnce(&mut Series),
+    ) {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| (*name, (*value).to_string()))
+            .collect();
+        let mut families = self.families.lock().unwrap_or_else(PoisonError::into_inner);
+        let family = families.entry(metric.name).or_insert_with(|| Family {
+            metric: *metric,
+            series: BTreeMap::new(),
+        });
+        apply(family.series.entry(labels).or_default());
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+        for family in families.values() {
+            let Metric { name, help, kind } = family.metric;
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {}", kind.as_str());
+            for (labels, series) in &family.series {
+                let labels = format_labels(labels);
+                if kind == MetricKind::Summary {
+                    let _ = writeln!(out, "{name}_sum{labels} {}", series.value);
+                    let _ = writeln!(out, "{name}_count{labels} {}", series.count);
+                } else {
+                    let _ = writeln!(out, "{name}{labels} {}", series.value);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// `{name="value",…}`, or nothing for an unlabelled series.
+fn format_labels(labels: &Labels) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_counter_with_help_and_type() {
+        let metrics = Metrics::new();
+
+        metrics.increment(&EVENTS_PUBLISHED, &[("event_type", "state_changed")]);
+        metrics.increment(&EVENTS_PUBLISHED, &[("event_type", "state_changed")]);
+
+        assert_eq!(
+            metrics.render(),
+            "# HELP minihub_events_published_total Events published on the event bus.\n\
+             # TYPE minihub_events_published_total counter\n\
+             minihub_events_published_total{event_type=\"state_changed\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn should_render_summary_as_sum_and_count() {
+        let metrics = Metrics::new();
+        let labels = [("method", "GET"), ("route", "/health")];
+
+        metrics.observe(&HTTP_REQUEST_DURATION, &labels, 0.25);
+        metrics.observe(&HTTP_REQUEST_DURATION, &labels, 0.5);
+
+        let rendered = metrics.render();
+        assert!(rendered.contains(
+            "minihub_http_request_duration_seconds_sum{method=\"GET\",route=\"/health\"} 0.75\n"
+        ));
+        assert!(rendered.contains(
+            "minihub_http_request_duration_seconds_count{method=\"GET\",route=\"/health\"} 2\n"
+        ));
+    }
+
+    #[test]
+    fn should_replace_gauge_series_after_reset() {
+        let metrics = Metrics::new();
+        metrics.set(&ENTITIES, &[("integration", "mqtt")], 3.0);
+
+        metrics.reset(&ENTITIES);
+        metrics.set(&ENTITIES, &[("integration", "ble")], 2.0);
+
+        let rendered = metrics.render();
+        assert!(!rendered.contains("mqtt"));
+        assert!(rendered.contains("minihub_entities{integration=\"ble\"} 2\n"));
+    }
+
+    #[test]
+    fn should_escape_label_values() {
+        let metrics = Metrics::new();
+
+        metrics.set(&ENTITIES, &[("integration", "a\"b\\c")], 1.0);
+
+        assert!(
+            metrics
+                .render()
+                .contains("minihub_entities{integration=\"a\\\"b\\\\c\"} 1\n")
+        );
+    }
+}
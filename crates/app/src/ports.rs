@@ -4,14 +4,35 @@
 //! They are defined here (in `app`) so that both the use-case layer and the
 //! adapter layer can depend on them without creating circular dependencies.
 
+pub mod audit_repo;
 pub mod automation_repo;
+pub mod automation_run_repo;
+pub mod energy_usage_repo;
 pub mod event_bus;
 pub mod event_store;
+pub mod group_repo;
 pub mod integration;
+pub mod notifier;
+pub mod report_repo;
+pub mod scene_repo;
+pub mod secrets_repo;
+pub mod settings_repo;
 pub mod storage;
 
+pub use audit_repo::{AuditQuery, AuditRepository};
 pub use automation_repo::AutomationRepository;
-pub use event_bus::EventPublisher;
-pub use event_store::EventStore;
+pub use automation_run_repo::AutomationRunRepository;
+pub use energy_usage_repo::EnergyUsageRepository;
+pub use event_bus::{EventPublisher, EventSubscription};
+pub use event_store::{EventQuery, EventStore};
+pub use group_repo::GroupRepository;
 pub use integration::{DiscoveredDevice, Integration, IntegrationContext};
-pub use storage::{AreaRepository, DeviceRepository, EntityHistoryRepository, EntityRepository};
+pub use notifier::Notifier;
+pub use report_repo::ReportRepository;
+pub use scene_repo::SceneRepository;
+pub use secrets_repo::SecretsRepository;
+pub use settings_repo::SettingsRepository;
+pub use storage::{
+    AreaRepository, DeviceRepository, DiscoveryRepository, EntityHistoryRepository,
+    EntityRepository, Storage,
+};
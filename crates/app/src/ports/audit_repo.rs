@@ -0,0 +1,140 @@
+//! Audit repository port — persistence for the audit log of write operations.
+
+use std::future::Future;
+
+use minihub_domain::audit::AuditEntry;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::AuditEntryId;
+use minihub_domain::time::Timestamp;
+
+/// Filters and page bounds of an [`AuditRepository::query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditQuery {
+    /// Only entries of this actor, e.g. `"integration:mqtt"`, or of this
+    /// kind of actor, e.g. `"integration"`.
+    pub actor: Option<String>,
+    /// Only entries about this kind of resource, e.g. `"automation"`.
+    pub target_type: Option<String>,
+    /// Only entries about the resource with this identifier.
+    pub target_id: Option<String>,
+    /// `(timestamp, id)` of the last entry of the previous page: only
+    /// entries sorting strictly before it.
+    pub before: Option<(Timestamp, AuditEntryId)>,
+    /// Maximum number of entries returned.
+    pub limit: usize,
+}
+
+impl AuditQuery {
+    /// A query matching every entry, returning at most `limit` of them.
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        Self {
+            actor: None,
+            target_type: None,
+            target_id: None,
+            before: None,
+            limit,
+        }
+    }
+
+    /// Only match entries of `actor`, either a full actor or a kind.
+    #[must_use]
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Only match entries about resources of `target_type`.
+    #[must_use]
+    pub fn with_target_type(mut self, target_type: impl Into<String>) -> Self {
+        self.target_type = Some(target_type.into());
+        self
+    }
+
+    /// Only match entries about the resource `target_id`.
+    #[must_use]
+    pub fn with_target_id(mut self, target_id: impl Into<String>) -> Self {
+        self.target_id = Some(target_id.into());
+        self
+    }
+
+    /// Continue after the page ending with the entry at `(timestamp, id)`.
+    #[must_use]
+    pub fn with_before(mut self, timestamp: Timestamp, id: AuditEntryId) -> Self {
+        self.before = Some((timestamp, id));
+        self
+    }
+
+    /// Whether `entry` passes the filters and page bound.
+    #[must_use]
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor
+            .as_ref()
+            .is_none_or(|actor| *actor == entry.actor.kind() || *actor == entry.actor.to_string())
+            && self
+                .target_type
+                .as_ref()
+                .is_none_or(|target_type| entry.target_type == *target_type)
+            && self
+                .target_id
+                .as_ref()
+                .is_none_or(|target_id| entry.target_id == *target_id)
+            && self.before.is_none_or(|(timestamp, id)| {
+                (entry.timestamp, entry.id.as_uuid()) < (timestamp, id.as_uuid())
+            })
+    }
+}
+
+/// Repository for persisting and querying [`AuditEntry`]s.
+pub trait AuditRepository {
+    /// Persist a new audit entry.
+    fn record(
+        &self,
+        entry: AuditEntry,
+    ) -> impl Future<Output = Result<AuditEntry, MiniHubError>> + Send;
+
+    /// Find the entries matching `query`, ordered newest-first.
+    fn query(
+        &self,
+        query: AuditQuery,
+    ) -> impl Future<Output = Result<Vec<AuditEntry>, MiniHubError>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::audit::Actor;
+    use minihub_domain::id::AutomationId;
+
+    use super::*;
+
+    fn entry(actor: Actor) -> AuditEntry {
+        AuditEntry::created(actor, "area", "kitchen", serde_json::json!({}))
+    }
+
+    #[test]
+    fn should_match_actor_by_kind_or_in_full() {
+        let actor = Actor::Automation(AutomationId::new());
+        let entry = entry(actor.clone());
+
+        assert!(AuditQuery::new(10).with_actor("automation").matches(&entry));
+        assert!(
+            AuditQuery::new(10)
+                .with_actor(actor.to_string())
+                .matches(&entry)
+        );
+        assert!(!AuditQuery::new(10).with_actor("api").matches(&entry));
+    }
+
+    #[test]
+    fn should_match_target_filters() {
+        let entry = entry(Actor::Api);
+
+        assert!(
+            AuditQuery::new(10)
+                .with_target_type("area")
+                .with_target_id("kitchen")
+                .matches(&entry)
+        );
+        assert!(!AuditQuery::new(10).with_target_id("garage").matches(&entry));
+    }
+}
@@ -5,6 +5,7 @@ use std::future::Future;
 use minihub_domain::automation::Automation;
 use minihub_domain::error::MiniHubError;
 use minihub_domain::id::AutomationId;
+use minihub_domain::time::Timestamp;
 
 /// Repository for persisting and querying [`Automation`]s.
 pub trait AutomationRepository {
@@ -26,12 +27,28 @@ pub trait AutomationRepository {
     /// Get all enabled automations.
     fn get_enabled(&self) -> impl Future<Output = Result<Vec<Automation>, MiniHubError>> + Send;
 
-    /// Update an existing automation.
+    /// Update the definition of an existing automation.
+    ///
+    /// The update is a compare-and-set on [`Automation::version`]: it is only
+    /// applied if the stored version still matches, and stores the next
+    /// version. `last_triggered` is left untouched; use
+    /// [`record_triggered`](Self::record_triggered) for it.
+    ///
+    /// Returns the stored automation. Fails with [`MiniHubError::Conflict`]
+    /// when the stored version differs and [`MiniHubError::NotFound`] when
+    /// the automation does not exist.
     fn update(
         &self,
         automation: Automation,
     ) -> impl Future<Output = Result<Automation, MiniHubError>> + Send;
 
+    /// Set `last_triggered` without bumping the version.
+    fn record_triggered(
+        &self,
+        id: AutomationId,
+        triggered_at: Timestamp,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+
     /// Delete an automation by its unique identifier.
     fn delete(&self, id: AutomationId) -> impl Future<Output = Result<(), MiniHubError>> + Send;
 }
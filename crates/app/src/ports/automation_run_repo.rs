@@ -0,0 +1,23 @@
+//! Automation run repository port — persistence for automation execution logs.
+
+use std::future::Future;
+
+use minihub_domain::automation_run::AutomationRun;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::AutomationId;
+
+/// Repository for persisting and querying [`AutomationRun`]s.
+pub trait AutomationRunRepository {
+    /// Persist a new run record.
+    fn record(
+        &self,
+        run: AutomationRun,
+    ) -> impl Future<Output = Result<AutomationRun, MiniHubError>> + Send;
+
+    /// Find the runs of a specific automation, ordered newest-first.
+    fn find_by_automation(
+        &self,
+        automation_id: AutomationId,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<AutomationRun>, MiniHubError>> + Send;
+}
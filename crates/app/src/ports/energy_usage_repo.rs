@@ -0,0 +1,25 @@
+//! Energy usage repository port — hourly consumption of metered entities.
+
+use std::future::Future;
+
+use minihub_domain::energy::EnergyUsage;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::EntityId;
+use minihub_domain::time::Timestamp;
+
+/// Repository accumulating the [`EnergyUsage`] of entities hour by hour.
+pub trait EnergyUsageRepository {
+    /// Add each of `usage` to the consumption already recorded for its
+    /// entity and hour.
+    fn add(&self, usage: Vec<EnergyUsage>)
+    -> impl Future<Output = Result<(), MiniHubError>> + Send;
+
+    /// Find the hourly consumption of `entity_id` for the hours starting
+    /// within `from..to`, oldest first.
+    fn find_by_entity_in_range(
+        &self,
+        entity_id: EntityId,
+        from: Timestamp,
+        to: Timestamp,
+    ) -> impl Future<Output = Result<Vec<EnergyUsage>, MiniHubError>> + Send;
+}
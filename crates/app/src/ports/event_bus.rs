@@ -2,6 +2,9 @@
 
 use std::future::Future;
 
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
 use minihub_domain::error::MiniHubError;
 use minihub_domain::event::Event;
 
@@ -16,3 +19,22 @@ impl<T: EventPublisher + Send + Sync> EventPublisher for std::sync::Arc<T> {
         (**self).publish(event)
     }
 }
+
+/// Receives the events published on a bus, in order.
+///
+/// Mirrors [`broadcast::Receiver::recv`] so background services can consume
+/// either a plain receiver or a replaying
+/// [`EventStream`](crate::replayable_event_bus::EventStream).
+pub trait EventSubscription: Send {
+    /// Wait for the next event.
+    ///
+    /// Returns [`RecvError::Lagged`] when events were missed and
+    /// [`RecvError::Closed`] once the bus is gone.
+    fn recv(&mut self) -> impl Future<Output = Result<Event, RecvError>> + Send;
+}
+
+impl EventSubscription for broadcast::Receiver<Event> {
+    fn recv(&mut self) -> impl Future<Output = Result<Event, RecvError>> + Send {
+        broadcast::Receiver::recv(self)
+    }
+}
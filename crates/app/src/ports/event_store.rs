@@ -3,8 +3,106 @@
 use std::future::Future;
 
 use minihub_domain::error::MiniHubError;
-use minihub_domain::event::Event;
+use minihub_domain::event::{Event, EventType};
 use minihub_domain::id::{EntityId, EventId};
+use minihub_domain::time::Timestamp;
+
+/// Filters and page bounds of an [`EventStore::query`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventQuery {
+    /// Only events of this type.
+    pub event_type: Option<EventType>,
+    /// Only events about this entity.
+    pub entity_id: Option<EntityId>,
+    /// Only events of this causal chain.
+    pub correlation_id: Option<EventId>,
+    /// Only events at or after this time.
+    pub from: Option<Timestamp>,
+    /// Only events strictly before this time.
+    pub to: Option<Timestamp>,
+    /// `(timestamp, id)` of the last event of the previous page: only
+    /// events sorting strictly before it.
+    pub before: Option<(Timestamp, EventId)>,
+    /// Maximum number of events returned.
+    pub limit: usize,
+}
+
+impl EventQuery {
+    /// A query matching every event, returning at most `limit` of them.
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        Self {
+            event_type: None,
+            entity_id: None,
+            correlation_id: None,
+            from: None,
+            to: None,
+            before: None,
+            limit,
+        }
+    }
+
+    /// Only match events of `event_type`.
+    #[must_use]
+    pub fn with_event_type(mut self, event_type: EventType) -> Self {
+        self.event_type = Some(event_type);
+        self
+    }
+
+    /// Only match events about `entity_id`.
+    #[must_use]
+    pub fn with_entity_id(mut self, entity_id: EntityId) -> Self {
+        self.entity_id = Some(entity_id);
+        self
+    }
+
+    /// Only match events of the causal chain `correlation_id`.
+    #[must_use]
+    pub fn with_correlation_id(mut self, correlation_id: EventId) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+
+    /// Only match events with `from <= timestamp`.
+    #[must_use]
+    pub fn with_from(mut self, from: Timestamp) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Only match events with `timestamp < to`.
+    #[must_use]
+    pub fn with_to(mut self, to: Timestamp) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Continue after the page ending with the event at `(timestamp, id)`.
+    #[must_use]
+    pub fn with_before(mut self, timestamp: Timestamp, id: EventId) -> Self {
+        self.before = Some((timestamp, id));
+        self
+    }
+
+    /// Whether `event` passes the filters and page bound.
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        self.event_type
+            .as_ref()
+            .is_none_or(|event_type| event.event_type == *event_type)
+            && self
+                .entity_id
+                .is_none_or(|entity_id| event.entity_id == Some(entity_id))
+            && self
+                .correlation_id
+                .is_none_or(|correlation_id| event.correlation_id == correlation_id)
+            && self.from.is_none_or(|from| event.timestamp >= from)
+            && self.to.is_none_or(|to| event.timestamp < to)
+            && self.before.is_none_or(|(timestamp, id)| {
+                (event.timestamp, event.id.as_uuid()) < (timestamp, id.as_uuid())
+            })
+    }
+}
 
 /// Repository for persisting and querying [`Event`]s.
 pub trait EventStore {
@@ -29,4 +127,85 @@ pub trait EventStore {
         entity_id: EntityId,
         limit: usize,
     ) -> impl Future<Output = Result<Vec<Event>, MiniHubError>> + Send;
+
+    /// Find events with `from <= timestamp < to`, ordered oldest-first.
+    ///
+    /// Results are paged by keyset: pass the `(timestamp, id)` of the last
+    /// event of the previous page as `after` to fetch the next one.
+    fn find_in_range(
+        &self,
+        from: Timestamp,
+        to: Timestamp,
+        after: Option<(Timestamp, EventId)>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Event>, MiniHubError>> + Send;
+
+    /// Find the events matching `query`, ordered newest-first by
+    /// `(timestamp, id)`.
+    ///
+    /// Results are paged by keyset: pass the `(timestamp, id)` of the last
+    /// event of the previous page as [`EventQuery::before`] to fetch the
+    /// next one.
+    fn query(
+        &self,
+        query: EventQuery,
+    ) -> impl Future<Output = Result<Vec<Event>, MiniHubError>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::time;
+
+    use super::*;
+
+    fn event(event_type: EventType, entity_id: Option<EntityId>) -> Event {
+        Event::new(event_type, entity_id, serde_json::json!({}))
+    }
+
+    #[test]
+    fn should_match_every_event_when_unfiltered() {
+        let query = EventQuery::new(10);
+        assert!(query.matches(&event(EventType::StateChanged, None)));
+        assert!(query.matches(&event(EventType::EntityCreated, Some(EntityId::new()))));
+    }
+
+    #[test]
+    fn should_match_event_type_and_entity() {
+        let entity_id = EntityId::new();
+        let query = EventQuery::new(10)
+            .with_event_type(EventType::StateChanged)
+            .with_entity_id(entity_id);
+
+        assert!(query.matches(&event(EventType::StateChanged, Some(entity_id))));
+        assert!(!query.matches(&event(EventType::EntityCreated, Some(entity_id))));
+        assert!(!query.matches(&event(EventType::StateChanged, None)));
+    }
+
+    #[test]
+    fn should_match_events_of_a_causal_chain() {
+        let root = event(EventType::StateChanged, None);
+        let effect = event(EventType::ServiceCallRequested, None).with_cause(&root);
+        let query = EventQuery::new(10).with_correlation_id(root.id);
+
+        assert!(query.matches(&root));
+        assert!(query.matches(&effect));
+        assert!(!query.matches(&event(EventType::StateChanged, None)));
+    }
+
+    #[test]
+    fn should_match_time_range_and_page_bound() {
+        let now = time::now();
+        let mut earlier = event(EventType::StateChanged, None);
+        earlier.timestamp = now - chrono::Duration::seconds(10);
+        let mut later = event(EventType::StateChanged, None);
+        later.timestamp = now;
+
+        let ranged = EventQuery::new(10).with_from(now - chrono::Duration::seconds(5));
+        assert!(!ranged.matches(&earlier));
+        assert!(ranged.matches(&later));
+
+        let paged = EventQuery::new(10).with_before(later.timestamp, later.id);
+        assert!(paged.matches(&earlier));
+        assert!(!paged.matches(&later));
+    }
 }
@@ -0,0 +1,28 @@
+//! Group repository port — persistence for entity groups.
+
+use std::future::Future;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::group::Group;
+use minihub_domain::id::GroupId;
+
+/// Repository for persisting and querying [`Group`]s.
+pub trait GroupRepository {
+    /// Create a new group in storage.
+    fn create(&self, group: Group) -> impl Future<Output = Result<Group, MiniHubError>> + Send;
+
+    /// Get a group by its unique identifier.
+    fn get_by_id(
+        &self,
+        id: GroupId,
+    ) -> impl Future<Output = Result<Option<Group>, MiniHubError>> + Send;
+
+    /// Get all groups.
+    fn get_all(&self) -> impl Future<Output = Result<Vec<Group>, MiniHubError>> + Send;
+
+    /// Update an existing group.
+    fn update(&self, group: Group) -> impl Future<Output = Result<Group, MiniHubError>> + Send;
+
+    /// Delete a group by its unique identifier.
+    fn delete(&self, id: GroupId) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
@@ -35,6 +35,18 @@ pub trait IntegrationContext: Send + Sync {
         entity: Entity,
     ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send;
 
+    /// Like [`Self::upsert_entity`], recording the published events as a
+    /// consequence of `cause` — typically the service call being handled.
+    ///
+    /// Defaults to [`Self::upsert_entity`], dropping the cause.
+    fn upsert_entity_caused_by(
+        &self,
+        entity: Entity,
+        _cause: &Event,
+    ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+        self.upsert_entity(entity)
+    }
+
     /// Publish a domain event to the event bus.
     fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send;
 
@@ -50,6 +62,13 @@ pub trait IntegrationContext: Send + Sync {
         entity_id: &str,
     ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send;
 
+    /// List every stored entity, whatever its integration.
+    ///
+    /// Defaults to an empty list, for contexts without a registry.
+    fn list_entities(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+        async { Ok(Vec::new()) }
+    }
+
     /// Subscribe to domain events on the event bus.
     ///
     /// Returns a concrete [`broadcast::Receiver`] — there is only one
@@ -128,4 +147,8 @@ pub trait Integration {
 pub struct DiscoveredDevice {
     pub device: Device,
     pub entities: Vec<Entity>,
+    /// `unique_id` of the device this one is reached through, in the same
+    /// integration. Resolved into [`Device::via_device_id`] on registration
+    /// once that device is registered.
+    pub via_device: Option<String>,
 }
@@ -0,0 +1,24 @@
+//! Notifier port — delivery of notifications to humans.
+
+use std::future::Future;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::notification::Notification;
+
+/// Delivers [`Notification`]s through an outbound channel (webhook, chat, …).
+pub trait Notifier {
+    /// Deliver a single notification.
+    fn notify(
+        &self,
+        notification: &Notification,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
+
+impl<T: Notifier + Send + Sync> Notifier for std::sync::Arc<T> {
+    fn notify(
+        &self,
+        notification: &Notification,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        (**self).notify(notification)
+    }
+}
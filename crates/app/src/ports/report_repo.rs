@@ -0,0 +1,23 @@
+//! Report repository port — cross-aggregate read models.
+//!
+//! Reports join data owned by several repositories (entities, devices,
+//! events, …). Implementations are expected to compute each report with
+//! dedicated queries instead of composing calls to the other ports.
+
+use std::future::Future;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::report::{Overview, StorageStats};
+use minihub_domain::time::Timestamp;
+
+/// Read-only repository computing aggregated [reports](minihub_domain::report).
+pub trait ReportRepository {
+    /// Compute the system overview, counting events recorded since `since`.
+    fn overview(
+        &self,
+        since: Timestamp,
+    ) -> impl Future<Output = Result<Overview, MiniHubError>> + Send;
+
+    /// Measure the database and count the rows of its largest tables.
+    fn storage_stats(&self) -> impl Future<Output = Result<StorageStats, MiniHubError>> + Send;
+}
@@ -0,0 +1,28 @@
+//! Scene repository port — persistence for scenes.
+
+use std::future::Future;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::id::SceneId;
+use minihub_domain::scene::Scene;
+
+/// Repository for persisting and querying [`Scene`]s.
+pub trait SceneRepository {
+    /// Create a new scene in storage.
+    fn create(&self, scene: Scene) -> impl Future<Output = Result<Scene, MiniHubError>> + Send;
+
+    /// Get a scene by its unique identifier.
+    fn get_by_id(
+        &self,
+        id: SceneId,
+    ) -> impl Future<Output = Result<Option<Scene>, MiniHubError>> + Send;
+
+    /// Get all scenes.
+    fn get_all(&self) -> impl Future<Output = Result<Vec<Scene>, MiniHubError>> + Send;
+
+    /// Update an existing scene.
+    fn update(&self, scene: Scene) -> impl Future<Output = Result<Scene, MiniHubError>> + Send;
+
+    /// Delete a scene by its unique identifier.
+    fn delete(&self, id: SceneId) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
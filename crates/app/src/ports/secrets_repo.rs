@@ -0,0 +1,57 @@
+//! Secrets repository port — persistence for integration credentials.
+//!
+//! Implementations are expected to keep the values encrypted at rest; only
+//! [`get`](SecretsRepository::get) ever returns one.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::secret::{SecretInfo, SecretValue};
+
+/// Repository storing secret values under their name.
+pub trait SecretsRepository {
+    /// Get the value stored under `name`, if any.
+    fn get(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<SecretValue>, MiniHubError>> + Send;
+
+    /// Every stored secret, without its value, ordered by name.
+    fn list(&self) -> impl Future<Output = Result<Vec<SecretInfo>, MiniHubError>> + Send;
+
+    /// Store `value` under `name`, replacing any previous value.
+    fn put(
+        &self,
+        name: &str,
+        value: &SecretValue,
+    ) -> impl Future<Output = Result<SecretInfo, MiniHubError>> + Send;
+
+    /// Remove the secret stored under `name`, returning whether one existed.
+    fn delete(&self, name: &str) -> impl Future<Output = Result<bool, MiniHubError>> + Send;
+}
+
+impl<T: SecretsRepository + Send + Sync> SecretsRepository for Arc<T> {
+    fn get(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<SecretValue>, MiniHubError>> + Send {
+        (**self).get(name)
+    }
+
+    fn list(&self) -> impl Future<Output = Result<Vec<SecretInfo>, MiniHubError>> + Send {
+        (**self).list()
+    }
+
+    fn put(
+        &self,
+        name: &str,
+        value: &SecretValue,
+    ) -> impl Future<Output = Result<SecretInfo, MiniHubError>> + Send {
+        (**self).put(name, value)
+    }
+
+    fn delete(&self, name: &str) -> impl Future<Output = Result<bool, MiniHubError>> + Send {
+        (**self).delete(name)
+    }
+}
@@ -0,0 +1,70 @@
+//! Settings repository port — persistence for hub-wide settings.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::settings::Settings;
+
+/// Repository storing settings as JSON values under a key of a namespace,
+/// e.g. the dashboard layout under `layout` in `dashboard`.
+pub trait SettingsRepository {
+    /// Get the value stored under `key` in `namespace`, if any.
+    fn get(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<Value>, MiniHubError>> + Send;
+
+    /// Get every key of `namespace` with its value; empty for an unknown
+    /// namespace.
+    fn list(&self, namespace: &str) -> impl Future<Output = Result<Settings, MiniHubError>> + Send;
+
+    /// Store `value` under `key` in `namespace`, replacing any previous value.
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+
+    /// Remove `key` from `namespace`. Removing a missing key is a no-op.
+    fn delete(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
+
+impl<T: SettingsRepository + Send + Sync> SettingsRepository for Arc<T> {
+    fn get(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<Value>, MiniHubError>> + Send {
+        (**self).get(namespace, key)
+    }
+
+    fn list(&self, namespace: &str) -> impl Future<Output = Result<Settings, MiniHubError>> + Send {
+        (**self).list(namespace)
+    }
+
+    fn put(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: Value,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        (**self).put(namespace, key, value)
+    }
+
+    fn delete(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        (**self).delete(namespace, key)
+    }
+}
@@ -1,6 +1,7 @@
 //! Storage port — repository traits for persistence.
 
 use std::future::Future;
+use std::sync::Arc;
 
 use minihub_domain::area::Area;
 use minihub_domain::device::Device;
@@ -10,6 +11,20 @@ use minihub_domain::error::MiniHubError;
 use minihub_domain::id::{AreaId, DeviceId, EntityId};
 use minihub_domain::time::Timestamp;
 
+use crate::ports::integration::DiscoveredDevice;
+
+/// The storage backend as a whole, behind the repositories.
+pub trait Storage {
+    /// Check that the backend answers queries.
+    fn ping(&self) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
+
+impl<T: Storage + Send + Sync> Storage for Arc<T> {
+    fn ping(&self) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+        (**self).ping()
+    }
+}
+
 /// Repository for [`Entity`] persistence.
 pub trait EntityRepository {
     /// Create a new entity in storage.
@@ -21,6 +36,13 @@ pub trait EntityRepository {
         id: EntityId,
     ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send;
 
+    /// Get the entities among `ids` in a single lookup, skipping the ids of
+    /// missing entities. The order of the entities is unspecified.
+    fn get_many(
+        &self,
+        ids: &[EntityId],
+    ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send;
+
     /// Get all entities.
     fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send;
 
@@ -37,10 +59,32 @@ pub trait EntityRepository {
     ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send;
 
     /// Update an existing entity.
+    ///
+    /// The update is a compare-and-set on [`Entity::version`]: it is only
+    /// applied if the stored version still matches, and stores the next
+    /// version.
+    ///
+    /// Returns the stored entity. Fails with [`MiniHubError::Conflict`] when
+    /// the stored version differs and [`MiniHubError::NotFound`] when the
+    /// entity does not exist.
     fn update(&self, entity: Entity) -> impl Future<Output = Result<Entity, MiniHubError>> + Send;
 
     /// Delete an entity by its unique identifier.
     fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+
+    /// Find an entity by a former `entity_id` it was renamed from.
+    fn find_by_alias(
+        &self,
+        alias: &str,
+    ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send;
+
+    /// Record `alias` as a former `entity_id` of the entity `id`, replacing
+    /// any entity it previously pointed to.
+    fn add_alias(
+        &self,
+        id: EntityId,
+        alias: &str,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
 }
 
 /// Repository for [`Device`] persistence.
@@ -57,6 +101,18 @@ pub trait DeviceRepository {
     /// Get all devices.
     fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send;
 
+    /// Get the devices placed in the area `area_id`, sorted by name.
+    fn find_by_area(
+        &self,
+        area_id: AreaId,
+    ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send;
+
+    /// Get the devices reported by `integration`, sorted by name.
+    fn find_by_integration(
+        &self,
+        integration: &str,
+    ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send;
+
     /// Find a device by its integration source and unique id pair.
     fn find_by_integration_unique_id(
         &self,
@@ -71,6 +127,17 @@ pub trait DeviceRepository {
     fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send;
 }
 
+/// Repository writing a discovered device and its entities together.
+pub trait DiscoveryRepository {
+    /// Insert or replace the device and every entity of `discovered`, keyed
+    /// on their ids, in a single transaction: either all of them are stored
+    /// or none is. Replaced entities get their next [`Entity::version`].
+    fn persist(
+        &self,
+        discovered: &DiscoveredDevice,
+    ) -> impl Future<Output = Result<(), MiniHubError>> + Send;
+}
+
 /// Repository for [`Area`] persistence.
 pub trait AreaRepository {
     /// Create a new area in storage.
@@ -0,0 +1,425 @@
+//! Durable event dispatch — the in-process bus backed by the event store.
+//!
+//! The plain [`InProcessEventBus`] drops the events a slow subscriber lags
+//! behind on. In durable mode, [`ReplayableEventBus`] stores every event
+//! before broadcasting it, and each [`EventStream`] remembers the last event
+//! it delivered: after lagging, it catches up from the [`EventStore`] in
+//! pages instead of skipping the missed events. Streams may be restricted
+//! to the events passing an [`EventFilter`], replayed events included.
+//!
+//! Live events are delivered in the order they are broadcast, even when
+//! their timestamps are not: only the live events a catch-up already
+//! replayed are skipped.
+
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::Event;
+use minihub_domain::id::EventId;
+use minihub_domain::time::{self, Timestamp};
+
+use crate::event_bus::{EventFilter, InProcessEventBus};
+use crate::ports::{EventPublisher, EventStore, EventSubscription};
+
+/// Number of events read from the store per catch-up query.
+const REPLAY_PAGE_SIZE: usize = 256;
+
+/// Event publisher storing events before broadcasting them, so subscribers
+/// can replay what they missed.
+pub struct ReplayableEventBus<ES> {
+    bus: Arc<InProcessEventBus>,
+    store: Arc<ES>,
+    durable: bool,
+}
+
+impl<ES> Clone for ReplayableEventBus<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            bus: Arc::clone(&self.bus),
+            store: Arc::clone(&self.store),
+            durable: self.durable,
+        }
+    }
+}
+
+impl<ES: EventStore> ReplayableEventBus<ES> {
+    /// Create a durable bus broadcasting on `bus` the events stored in
+    /// `store`.
+    #[must_use]
+    pub fn new(bus: Arc<InProcessEventBus>, store: Arc<ES>) -> Self {
+        Self {
+            bus,
+            store,
+            durable: true,
+        }
+    }
+
+    /// Whether events are stored before being broadcast (default). When
+    /// disabled, publishing only broadcasts and subscribers drop the events
+    /// they lag behind on, like the plain bus.
+    #[must_use]
+    pub fn with_durable(mut self, durable: bool) -> Self {
+        self.durable = durable;
+        self
+    }
+
+    /// Whether events are stored before being broadcast.
+    #[must_use]
+    pub fn is_durable(&self) -> bool {
+        self.durable
+    }
+
+    /// Subscribe to the events published *after* this call.
+    #[must_use]
+    pub fn subscribe(&self) -> EventStream<ES> {
+        self.subscribe_filtered(EventFilter::all())
+    }
+
+    /// Subscribe to the events published *after* this call that pass
+    /// `filter`.
+    #[must_use]
+    pub fn subscribe_filtered(&self, filter: EventFilter) -> EventStream<ES> {
+        EventStream {
+            filter,
+            live: self.bus.subscribe(),
+            store: Arc::clone(&self.store),
+            durable: self.durable,
+            since: time::now(),
+            cursor: None,
+            backlog: VecDeque::new(),
+            missed: None,
+            replayed: HashSet::new(),
+        }
+    }
+}
+
+impl<ES: EventStore + Send + Sync> EventPublisher for ReplayableEventBus<ES> {
+    async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+        let event = if self.durable {
+            self.store.store(event).await?
+        } else {
+            event
+        };
+        self.bus.publish(event).await
+    }
+}
+
+/// Events received from a [`ReplayableEventBus`], replayed from the store
+/// after lagging.
+pub struct EventStream<ES> {
+    filter: EventFilter,
+    live: broadcast::Receiver<Event>,
+    store: Arc<ES>,
+    durable: bool,
+    /// Subscription time, where the first catch-up starts when nothing was
+    /// delivered yet.
+    since: Timestamp,
+    /// Greatest `(timestamp, id)` of the delivered events, where the next
+    /// catch-up resumes.
+    cursor: Option<(Timestamp, EventId)>,
+    /// Events read from the store and not delivered yet.
+    backlog: VecDeque<Event>,
+    /// Events skipped by the live receiver while a catch-up is in progress.
+    missed: Option<u64>,
+    /// Ids of the replayed events the live receiver may still yield,
+    /// forgotten once it yields an event sorting past all of them.
+    replayed: HashSet<EventId>,
+}
+
+impl<ES: EventStore> EventStream<ES> {
+    /// Wait for the next event passing the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`] once the bus is gone, and
+    /// [`RecvError::Lagged`] when events were missed and could not be
+    /// replayed: the bus is not durable or the store failed.
+    pub async fn recv(&mut self) -> Result<Event, RecvError> {
+        loop {
+            let event = self.next().await?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Next event, replayed or live, whether it passes the filter or not.
+    async fn next(&mut self) -> Result<Event, RecvError> {
+        loop {
+            if let Some(event) = self.backlog.pop_front() {
+                self.replayed.insert(event.id);
+                return Ok(self.deliver(event));
+            }
+            if let Some(skipped) = self.missed {
+                match self.fetch_page().await {
+                    Ok(true) => {}
+                    Ok(false) => self.missed = None,
+                    Err(err) => {
+                        tracing::warn!(%err, skipped, "failed to replay missed events");
+                        self.missed = None;
+                        return Err(RecvError::Lagged(skipped));
+                    }
+                }
+                continue;
+            }
+            match self.live.recv().await {
+                Ok(event) if self.replayed.remove(&event.id) => {}
+                Ok(event) => {
+                    if self.is_past_cursor(&event) {
+                        self.replayed.clear();
+                    }
+                    return Ok(self.deliver(event));
+                }
+                Err(RecvError::Lagged(skipped)) if self.durable => {
+                    tracing::debug!(skipped, "event subscriber lagged, replaying from the store");
+                    self.missed = Some(skipped);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Read the next page of missed events into the backlog, returning
+    /// whether more may follow.
+    async fn fetch_page(&mut self) -> Result<bool, MiniHubError> {
+        let from = self.cursor.map_or(self.since, |(timestamp, _)| timestamp);
+        let page = self
+            .store
+            .find_in_range(from, time::now(), self.cursor, REPLAY_PAGE_SIZE)
+            .await?;
+        let more = page.len() == REPLAY_PAGE_SIZE;
+        self.backlog.extend(page);
+        Ok(more)
+    }
+
+    /// Whether `event` sorts after every delivered event.
+    fn is_past_cursor(&self, event: &Event) -> bool {
+        self.cursor.is_none_or(|(timestamp, id)| {
+            (event.timestamp, event.id.as_uuid()) > (timestamp, id.as_uuid())
+        })
+    }
+
+    fn deliver(&mut self, event: Event) -> Event {
+        if self.is_past_cursor(&event) {
+            self.cursor = Some((event.timestamp, event.id));
+        }
+        event
+    }
+}
+
+impl<ES: EventStore + Send + Sync> EventSubscription for EventStream<ES> {
+    fn recv(&mut self) -> impl Future<Output = Result<Event, RecvError>> + Send {
+        EventStream::recv(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, PoisonError};
+
+    use minihub_domain::event::EventType;
+    use minihub_domain::id::EntityId;
+
+    use crate::ports::EventQuery;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryEventStore {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventStore for InMemoryEventStore {
+        async fn store(&self, event: Event) -> Result<Event, MiniHubError> {
+            self.events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(event.clone());
+            Ok(event)
+        }
+
+        async fn get_by_id(&self, id: EventId) -> Result<Option<Event>, MiniHubError> {
+            let events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+            Ok(events.iter().find(|event| event.id == id).cloned())
+        }
+
+        async fn get_recent(&self, _limit: usize) -> Result<Vec<Event>, MiniHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn find_by_entity(
+            &self,
+            _entity_id: EntityId,
+            _limit: usize,
+        ) -> Result<Vec<Event>, MiniHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn find_in_range(
+            &self,
+            from: Timestamp,
+            to: Timestamp,
+            after: Option<(Timestamp, EventId)>,
+            limit: usize,
+        ) -> Result<Vec<Event>, MiniHubError> {
+            let key = |event: &Event| (event.timestamp, event.id.as_uuid());
+            let mut events: Vec<Event> = self
+                .events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .filter(|event| event.timestamp >= from && event.timestamp < to)
+                .filter(|event| {
+                    after.is_none_or(|(timestamp, id)| key(event) > (timestamp, id.as_uuid()))
+                })
+                .cloned()
+                .collect();
+            events.sort_by_key(key);
+            events.truncate(limit);
+            Ok(events)
+        }
+
+        async fn query(&self, query: EventQuery) -> Result<Vec<Event>, MiniHubError> {
+            let key = |event: &Event| (event.timestamp, event.id.as_uuid());
+            let mut events: Vec<Event> = self
+                .events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .filter(|event| query.matches(event))
+                .cloned()
+                .collect();
+            events.sort_by_key(|event| std::cmp::Reverse(key(event)));
+            events.truncate(query.limit);
+            Ok(events)
+        }
+    }
+
+    fn event() -> Event {
+        Event::new(EventType::StateChanged, None, serde_json::json!({}))
+    }
+
+    fn bus(
+        capacity: usize,
+    ) -> (
+        ReplayableEventBus<InMemoryEventStore>,
+        Arc<InMemoryEventStore>,
+    ) {
+        let store = Arc::new(InMemoryEventStore::default());
+        let bus = ReplayableEventBus::new(
+            Arc::new(InProcessEventBus::new(capacity)),
+            Arc::clone(&store),
+        );
+        (bus, store)
+    }
+
+    #[tokio::test]
+    async fn should_store_event_before_broadcasting() {
+        let (bus, store) = bus(16);
+        let mut stream = bus.subscribe();
+        let event = event();
+        let id = event.id;
+
+        bus.publish(event).await.unwrap();
+
+        assert_eq!(stream.recv().await.unwrap().id, id);
+        assert!(store.get_by_id(id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn should_replay_events_missed_after_lagging() {
+        let (bus, _store) = bus(2);
+        let mut stream = bus.subscribe();
+        let mut published = Vec::new();
+        for _ in 0..5 {
+            let event = event();
+            published.push((event.timestamp, event.id.as_uuid()));
+            bus.publish(event).await.unwrap();
+        }
+        published.sort_unstable();
+
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            let event = stream.recv().await.unwrap();
+            received.push((event.timestamp, event.id.as_uuid()));
+        }
+
+        assert_eq!(received, published);
+    }
+
+    #[tokio::test]
+    async fn should_not_deliver_replayed_events_twice() {
+        let (bus, _store) = bus(2);
+        let mut stream = bus.subscribe();
+        for _ in 0..3 {
+            bus.publish(event()).await.unwrap();
+        }
+        for _ in 0..3 {
+            stream.recv().await.unwrap();
+        }
+
+        let later = event();
+        let later_id = later.id;
+        bus.publish(later).await.unwrap();
+
+        assert_eq!(stream.recv().await.unwrap().id, later_id);
+    }
+
+    #[tokio::test]
+    async fn should_deliver_live_events_published_out_of_timestamp_order() {
+        for durable in [true, false] {
+            let (bus, _store) = bus(16);
+            let bus = bus.with_durable(durable);
+            let mut stream = bus.subscribe();
+            let later = event();
+            let mut earlier = event();
+            earlier.timestamp = later.timestamp - chrono::Duration::seconds(1);
+            let (later_id, earlier_id) = (later.id, earlier.id);
+
+            bus.publish(later).await.unwrap();
+            bus.publish(earlier).await.unwrap();
+
+            assert_eq!(stream.recv().await.unwrap().id, later_id);
+            assert_eq!(stream.recv().await.unwrap().id, earlier_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_filter_replayed_events() {
+        let (bus, _store) = bus(1);
+        let mut stream =
+            bus.subscribe_filtered(EventFilter::all().with_event_types([EventType::EntityCreated]));
+        bus.publish(Event::new(
+            EventType::EntityCreated,
+            None,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+        for _ in 0..3 {
+            bus.publish(event()).await.unwrap();
+        }
+
+        let received = stream.recv().await.unwrap();
+
+        assert_eq!(received.event_type, EventType::EntityCreated);
+    }
+
+    #[tokio::test]
+    async fn should_report_lag_when_not_durable() {
+        let (bus, store) = bus(2);
+        let bus = bus.with_durable(false);
+        let mut stream = bus.subscribe();
+        for _ in 0..3 {
+            bus.publish(event()).await.unwrap();
+        }
+
+        assert!(matches!(stream.recv().await, Err(RecvError::Lagged(1))));
+        assert!(store.events.lock().unwrap().is_empty());
+    }
+}
@@ -5,6 +5,24 @@
 
 pub mod area_service;
 pub mod automation_service;
+pub mod dashboard_service;
+pub mod device_availability_service;
+pub mod device_registry;
 pub mod device_service;
+pub mod discovery_service;
+pub mod energy_service;
 pub mod entity_service;
+pub mod group_service;
+pub mod health_service;
+pub mod history_snapshot_service;
+pub mod home_mode_service;
+pub mod input_helper_service;
 pub mod integration_context;
+pub mod notification_service;
+pub mod scene_service;
+pub mod schedule_service;
+pub mod secrets_service;
+pub mod settings_service;
+pub mod sun_service;
+pub mod update_throttle;
+pub mod webhook_service;
@@ -1,7 +1,11 @@
 //! Automation service — use-cases for managing automations.
 
-use minihub_domain::automation::Automation;
-use minihub_domain::error::{MiniHubError, NotFoundError};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use minihub_domain::automation::{Automation, Trigger};
+use minihub_domain::error::{MiniHubError, NotFoundError, ValidationError};
 use minihub_domain::id::AutomationId;
 
 use crate::ports::AutomationRepository;
@@ -9,12 +13,18 @@ use crate::ports::AutomationRepository;
 /// Application service for automation CRUD operations.
 pub struct AutomationService<R> {
     repo: R,
+    /// Webhook ids the enabled automations listen on, loaded on first use
+    /// and dropped by every write through this service.
+    webhook_ids: Mutex<Option<Arc<[String]>>>,
 }
 
 impl<R: AutomationRepository> AutomationService<R> {
     /// Create a new service backed by the given repository.
     pub fn new(repo: R) -> Self {
-        Self { repo }
+        Self {
+            repo,
+            webhook_ids: Mutex::new(None),
+        }
     }
 
     /// Create a new automation after validating domain invariants.
@@ -29,7 +39,9 @@ impl<R: AutomationRepository> AutomationService<R> {
         automation: Automation,
     ) -> Result<Automation, MiniHubError> {
         automation.validate()?;
-        self.repo.create(automation).await
+        let created = self.repo.create(automation).await;
+        self.forget_webhook_ids().await;
+        created
     }
 
     /// Look up an automation by id, returning an error if not found.
@@ -67,29 +79,87 @@ impl<R: AutomationRepository> AutomationService<R> {
         self.repo.get_enabled().await
     }
 
-    /// Update an existing automation.
+    /// Webhook ids the enabled automations listen on.
+    ///
+    /// They are read from the repository once and kept until the next
+    /// create, update or delete, so unauthenticated webhook calls do not
+    /// load every automation.
     ///
     /// # Errors
     ///
-    /// Returns [`MiniHubError::Validation`] if invariants fail, or a
-    /// storage error from the repository.
+    /// Returns a storage error propagated from the repository.
+    pub async fn webhook_ids(&self) -> Result<Arc<[String]>, MiniHubError> {
+        let mut cached = self.webhook_ids.lock().await;
+        if let Some(ids) = cached.as_ref() {
+            return Ok(Arc::clone(ids));
+        }
+        let ids: Arc<[String]> = self
+            .list_enabled()
+            .await?
+            .into_iter()
+            .filter_map(|automation| match automation.trigger {
+                Trigger::Webhook { webhook_id } => Some(webhook_id),
+                _ => None,
+            })
+            .collect();
+        *cached = Some(Arc::clone(&ids));
+        Ok(ids)
+    }
+
+    /// Drop the cached [`webhook_ids`](Self::webhook_ids) once a write
+    /// went through. A load racing the write holds the lock until it has
+    /// cached its result, so that result is dropped too.
+    async fn forget_webhook_ids(&self) {
+        *self.webhook_ids.lock().await = None;
+    }
+
+    /// Look up an automation that may be changed through the API.
+    async fn get_editable(&self, id: AutomationId) -> Result<Automation, MiniHubError> {
+        let automation = self.get_automation(id).await?;
+        if automation.is_read_only() {
+            return Err(ValidationError::ReadOnlyAutomation(automation.name).into());
+        }
+        Ok(automation)
+    }
+
+    /// Update an existing automation, provided its stored version is still
+    /// `automation.version`. The returned automation carries the bumped
+    /// version. Automations imported from files cannot be updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, as checked
+    /// by [`Automation::validate_edit`], or the automation is read-only,
+    /// [`MiniHubError::Conflict`] if the automation was modified since
+    /// `automation.version`, [`MiniHubError::NotFound`] if it does not
+    /// exist, or a storage error from the repository.
     #[tracing::instrument(skip(self, automation))]
     pub async fn update_automation(
         &self,
         automation: Automation,
     ) -> Result<Automation, MiniHubError> {
-        automation.validate()?;
-        self.repo.update(automation).await
+        let stored = self.get_editable(automation.id).await?;
+        automation.validate_edit(&stored)?;
+        let updated = self.repo.update(automation).await;
+        self.forget_webhook_ids().await;
+        updated
     }
 
-    /// Delete an automation by id.
+    /// Delete an automation by id. Automations imported from files cannot be
+    /// deleted; removing their file does.
     ///
     /// # Errors
     ///
-    /// Returns a storage error propagated from the repository.
+    /// Returns [`MiniHubError::Validation`] if the automation is read-only,
+    /// or a storage error propagated from the repository.
     #[tracing::instrument(skip(self))]
     pub async fn delete_automation(&self, id: AutomationId) -> Result<(), MiniHubError> {
-        self.repo.delete(id).await
+        let deleted = match self.get_editable(id).await {
+            Ok(_) | Err(MiniHubError::NotFound(_)) => self.repo.delete(id).await,
+            Err(err) => return Err(err),
+        };
+        self.forget_webhook_ids().await;
+        deleted
     }
 }
 
@@ -99,6 +169,7 @@ mod tests {
     use minihub_domain::automation::{Action, Trigger};
     use minihub_domain::error::ValidationError;
     use minihub_domain::id::EntityId;
+    use minihub_domain::time::Timestamp;
     use std::collections::HashMap;
     use std::future::Future;
     use std::sync::Mutex;
@@ -157,6 +228,18 @@ mod tests {
             async { Ok(automation) }
         }
 
+        fn record_triggered(
+            &self,
+            id: AutomationId,
+            triggered_at: Timestamp,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            let mut store = self.store.lock().unwrap();
+            if let Some(automation) = store.get_mut(&id) {
+                automation.last_triggered = Some(triggered_at);
+            }
+            async { Ok(()) }
+        }
+
         fn delete(
             &self,
             id: AutomationId,
@@ -257,6 +340,23 @@ mod tests {
         assert_eq!(saved.name, "Updated name");
     }
 
+    #[tokio::test]
+    async fn should_update_automation_keeping_a_stored_cron_pattern_that_does_not_parse() {
+        let svc = make_service();
+        let mut stored = valid_automation();
+        stored.trigger = Trigger::TimePattern {
+            cron: "every morning".to_string(),
+        };
+        let id = stored.id;
+        svc.repo.create(stored).await.unwrap();
+
+        let mut disabled = svc.get_automation(id).await.unwrap();
+        disabled.enabled = false;
+        let saved = svc.update_automation(disabled).await.unwrap();
+
+        assert!(!saved.enabled);
+    }
+
     #[tokio::test]
     async fn should_delete_automation() {
         let svc = make_service();
@@ -269,4 +369,71 @@ mod tests {
         let result = svc.get_automation(id).await;
         assert!(matches!(result, Err(MiniHubError::NotFound(_))));
     }
+
+    fn webhook_automation(webhook_id: &str) -> Automation {
+        let mut automation = valid_automation();
+        automation.trigger = Trigger::Webhook {
+            webhook_id: webhook_id.to_string(),
+        };
+        automation
+    }
+
+    #[tokio::test]
+    async fn should_cache_webhook_ids_until_next_write() {
+        let svc = make_service();
+        let automation = webhook_automation("front-door-0123456789");
+        svc.create_automation(automation.clone()).await.unwrap();
+        assert_eq!(
+            &*svc.webhook_ids().await.unwrap(),
+            ["front-door-0123456789"]
+        );
+
+        let hidden = webhook_automation("written-behind-the-cache");
+        svc.repo.store.lock().unwrap().insert(hidden.id, hidden);
+        assert_eq!(svc.webhook_ids().await.unwrap().len(), 1);
+
+        let mut disabled = svc.get_automation(automation.id).await.unwrap();
+        disabled.enabled = false;
+        svc.update_automation(disabled).await.unwrap();
+        assert_eq!(
+            &*svc.webhook_ids().await.unwrap(),
+            ["written-behind-the-cache"]
+        );
+
+        svc.delete_automation(automation.id).await.unwrap();
+        svc.create_automation(webhook_automation("garage-0123456789ab"))
+            .await
+            .unwrap();
+        let mut ids = svc.webhook_ids().await.unwrap().to_vec();
+        ids.sort();
+        assert_eq!(ids, ["garage-0123456789ab", "written-behind-the-cache"]);
+    }
+
+    #[tokio::test]
+    async fn should_reject_update_and_delete_when_imported_from_file() {
+        let svc = make_service();
+        let mut auto = valid_automation();
+        auto.source_key = Some("evening".to_string());
+        let id = auto.id;
+        svc.create_automation(auto).await.unwrap();
+
+        let mut updated = svc.get_automation(id).await.unwrap();
+        updated.name = "Updated name".to_string();
+        let update = svc.update_automation(updated).await;
+        let delete = svc.delete_automation(id).await;
+
+        assert!(matches!(
+            update,
+            Err(MiniHubError::Validation(
+                ValidationError::ReadOnlyAutomation(_)
+            ))
+        ));
+        assert!(matches!(
+            delete,
+            Err(MiniHubError::Validation(
+                ValidationError::ReadOnlyAutomation(_)
+            ))
+        ));
+        assert!(svc.get_automation(id).await.is_ok());
+    }
 }
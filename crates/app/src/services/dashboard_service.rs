@@ -0,0 +1,146 @@
+//! Dashboard service — use-cases for the layout of the dashboard home page.
+
+use minihub_domain::dashboard::DashboardConfig;
+use minihub_domain::error::MiniHubError;
+
+use crate::ports::SettingsRepository;
+
+/// Settings namespace owned by the dashboard.
+pub const DASHBOARD_NAMESPACE: &str = "dashboard";
+
+/// Key of the layout in [`DASHBOARD_NAMESPACE`].
+pub const LAYOUT_KEY: &str = "layout";
+
+/// Application service reading and replacing the dashboard layout.
+pub struct DashboardService<R> {
+    repo: R,
+}
+
+impl<R: SettingsRepository> DashboardService<R> {
+    /// Create a new service backed by the given settings repository.
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// The stored layout, empty until one is saved.
+    ///
+    /// A stored layout that no longer parses, e.g. written by a newer
+    /// version, is logged and replaced by the empty layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn get_config(&self) -> Result<DashboardConfig, MiniHubError> {
+        let Some(value) = self.repo.get(DASHBOARD_NAMESPACE, LAYOUT_KEY).await? else {
+            return Ok(DashboardConfig::default());
+        };
+        Ok(serde_json::from_value(value).unwrap_or_else(|err| {
+            tracing::warn!(%err, "stored dashboard layout is invalid, using an empty one");
+            DashboardConfig::default()
+        }))
+    }
+
+    /// Replace the layout after validating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the layout is invalid, or a
+    /// storage error propagated from the repository.
+    #[tracing::instrument(skip(self, config), fields(cards = config.cards.len()))]
+    pub async fn update_config(
+        &self,
+        config: DashboardConfig,
+    ) -> Result<DashboardConfig, MiniHubError> {
+        config.validate()?;
+        self.repo
+            .put(DASHBOARD_NAMESPACE, LAYOUT_KEY, serde_json::json!(config))
+            .await?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use minihub_domain::dashboard::Card;
+    use minihub_domain::id::EntityId;
+    use minihub_domain::settings::Settings;
+    use serde_json::Value;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemorySettingsRepo {
+        store: Mutex<HashMap<(String, String), Value>>,
+    }
+
+    impl SettingsRepository for InMemorySettingsRepo {
+        async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, MiniHubError> {
+            let id = (namespace.to_string(), key.to_string());
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn list(&self, _namespace: &str) -> Result<Settings, MiniHubError> {
+            Ok(Settings::new())
+        }
+        async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<(), MiniHubError> {
+            let id = (namespace.to_string(), key.to_string());
+            self.store.lock().unwrap().insert(id, value);
+            Ok(())
+        }
+        async fn delete(&self, _namespace: &str, _key: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_empty_layout_when_none_saved() {
+        let service = DashboardService::new(InMemorySettingsRepo::default());
+
+        let config = service.get_config().await.unwrap();
+
+        assert!(config.cards.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_return_saved_layout() {
+        let service = DashboardService::new(InMemorySettingsRepo::default());
+        let config = DashboardConfig::default().with_card(Card::Entity {
+            entity_id: EntityId::new(),
+        });
+
+        service.update_config(config.clone()).await.unwrap();
+
+        assert_eq!(service.get_config().await.unwrap(), config);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_layout() {
+        let service = DashboardService::new(InMemorySettingsRepo::default());
+        let config = DashboardConfig::default().with_card(Card::Chart {
+            entity_id: EntityId::new(),
+            hours: 1000,
+        });
+
+        let result = service.update_config(config).await;
+
+        assert!(matches!(result, Err(MiniHubError::Validation(_))));
+        assert!(service.get_config().await.unwrap().cards.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_to_empty_layout_when_stored_one_is_invalid() {
+        let repo = InMemorySettingsRepo::default();
+        repo.put(
+            DASHBOARD_NAMESPACE,
+            LAYOUT_KEY,
+            serde_json::json!({ "cards": 42 }),
+        )
+        .await
+        .unwrap();
+        let service = DashboardService::new(repo);
+
+        assert!(service.get_config().await.unwrap().cards.is_empty());
+    }
+}
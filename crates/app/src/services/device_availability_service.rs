@@ -0,0 +1,459 @@
+//! Device availability service — turns entity state changes into device
+//! availability transitions.
+//!
+//! A device is online as long as one of its entities is available (see
+//! [`DeviceStatus::from_entities`]). The service keeps the last known status
+//! of every device and publishes [`EventType::DeviceUnavailable`] or
+//! [`EventType::DeviceBackOnline`] when it flips, so automations can react
+//! to a device dropping off the network.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use tokio::sync::broadcast;
+
+use minihub_domain::device::DeviceStatus;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{DeviceAvailabilityPayload, Event, EventType};
+use minihub_domain::id::DeviceId;
+
+use crate::event_bus::EventFilter;
+use crate::ports::{DeviceRepository, EntityRepository, EventPublisher, EventSubscription};
+
+/// Application service tracking device availability transitions.
+pub struct DeviceAvailabilityService<ER, DR, P> {
+    entity_repo: ER,
+    device_repo: DR,
+    publisher: P,
+    statuses: Mutex<HashMap<DeviceId, DeviceStatus>>,
+}
+
+impl<ER, DR, P> DeviceAvailabilityService<ER, DR, P>
+where
+    ER: EntityRepository,
+    DR: DeviceRepository,
+    P: EventPublisher,
+{
+    /// Create a new service with an empty status cache.
+    pub fn new(entity_repo: ER, device_repo: DR, publisher: P) -> Self {
+        Self {
+            entity_repo,
+            device_repo,
+            publisher,
+            statuses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seed the status cache from the stored entities.
+    ///
+    /// Without it, a device that is already unavailable at startup would be
+    /// reported as going unavailable on its first state change.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn prime(&self) -> Result<(), MiniHubError> {
+        let entities = self.entity_repo.get_all().await?;
+        let mut by_device: HashMap<DeviceId, Vec<Entity>> = HashMap::new();
+        for entity in entities {
+            by_device.entry(entity.device_id).or_default().push(entity);
+        }
+        let mut statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        for (device_id, entities) in by_device {
+            statuses.insert(device_id, DeviceStatus::from_entities(&entities));
+        }
+        Ok(())
+    }
+
+    /// The events [`Self::process_event`] may act on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all().with_event_types([EventType::StateChanged, EventType::EntityCreated])
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Runs until the bus is closed. Failures are logged and do not stop the
+    /// loop.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Err(err) = self.process_event(&event).await {
+                        tracing::warn!(%err, event_id = %event.id, "failed to track device availability");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "device availability service lagged, some events were dropped"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        tracing::debug!("device availability service stopped");
+    }
+
+    /// Recompute the status of the device owning the entity in `event`.
+    ///
+    /// Returns the published transition event, or `None` when the event is
+    /// not an entity state change or the device status did not flip.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self, event), fields(event_id = %event.id))]
+    pub async fn process_event(&self, event: &Event) -> Result<Option<Event>, MiniHubError> {
+        if !matches!(
+            event.event_type,
+            EventType::StateChanged | EventType::EntityCreated
+        ) {
+            return Ok(None);
+        }
+        let Some(entity_id) = event.entity_id else {
+            return Ok(None);
+        };
+        let Some(entity) = self.entity_repo.get_by_id(entity_id).await? else {
+            return Ok(None);
+        };
+        let siblings = self.entity_repo.find_by_device_id(entity.device_id).await?;
+        let status = DeviceStatus::from_entities(&siblings);
+
+        let previous = self
+            .statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(entity.device_id, status);
+        let event_type = match (previous, status) {
+            (
+                None | Some(DeviceStatus::Online | DeviceStatus::Unknown),
+                DeviceStatus::Unavailable,
+            ) => EventType::DeviceUnavailable,
+            (Some(DeviceStatus::Unavailable), DeviceStatus::Online) => EventType::DeviceBackOnline,
+            _ => return Ok(None),
+        };
+
+        let Some(device) = self.device_repo.get_by_id(entity.device_id).await? else {
+            return Ok(None);
+        };
+        let transition = Event::with_payload(
+            event_type,
+            None,
+            &DeviceAvailabilityPayload {
+                device_id: device.id,
+                device_name: device.name,
+                status,
+            },
+        );
+        self.publisher.publish(transition.clone()).await?;
+        Ok(Some(transition))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minihub_domain::device::Device;
+    use minihub_domain::entity::EntityState;
+    use minihub_domain::id::{AreaId, EntityId};
+    use std::future::Future;
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        fn create(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: EntityId,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|ent| ent.device_id == device_id)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_entity_id(
+            &self,
+            entity_id: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|ent| ent.entity_id == entity_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        fn create(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: DeviceId,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result: Vec<Device> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Service = DeviceAvailabilityService<InMemoryEntityRepo, InMemoryDeviceRepo, SpyPublisher>;
+
+    /// A service knowing one device with a relay and a power sensor.
+    async fn setup() -> (Service, Device, Entity, Entity) {
+        let svc = DeviceAvailabilityService::new(
+            InMemoryEntityRepo::default(),
+            InMemoryDeviceRepo::default(),
+            SpyPublisher::default(),
+        );
+        let device = Device::builder()
+            .name("Smart Plug")
+            .integration("test")
+            .unique_id("plug_1")
+            .build()
+            .unwrap();
+        svc.device_repo.create(device.clone()).await.unwrap();
+        let relay = Entity::builder()
+            .device_id(device.id)
+            .entity_id("switch.plug")
+            .friendly_name("Plug")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        let power = Entity::builder()
+            .device_id(device.id)
+            .entity_id("sensor.plug_power")
+            .friendly_name("Plug Power")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        svc.entity_repo.create(relay.clone()).await.unwrap();
+        svc.entity_repo.create(power.clone()).await.unwrap();
+        svc.prime().await.unwrap();
+        (svc, device, relay, power)
+    }
+
+    async fn set_state(svc: &Service, entity: &Entity, state: EntityState) -> Option<Event> {
+        let mut updated = entity.clone();
+        updated.state = state;
+        svc.entity_repo.update(updated).await.unwrap();
+        let event = Event::new(
+            EventType::StateChanged,
+            Some(entity.id),
+            serde_json::json!({}),
+        );
+        svc.process_event(&event).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_not_publish_when_some_entity_is_still_available() {
+        let (svc, _, relay, _) = setup().await;
+
+        let transition = set_state(&svc, &relay, EntityState::Unavailable).await;
+
+        assert!(transition.is_none());
+        assert!(svc.publisher.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_publish_device_unavailable_when_every_entity_is_unavailable() {
+        let (svc, device, relay, power) = setup().await;
+
+        set_state(&svc, &relay, EntityState::Unavailable).await;
+        let transition = set_state(&svc, &power, EntityState::Unavailable)
+            .await
+            .unwrap();
+
+        assert_eq!(transition.event_type, EventType::DeviceUnavailable);
+        assert_eq!(transition.data["device_id"], device.id.to_string());
+        assert_eq!(transition.data["device_name"], "Smart Plug");
+        assert_eq!(transition.data["status"], "unavailable");
+        assert_eq!(svc.publisher.events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_publish_device_back_online_when_an_entity_recovers() {
+        let (svc, _, relay, power) = setup().await;
+        set_state(&svc, &relay, EntityState::Unavailable).await;
+        set_state(&svc, &power, EntityState::Unavailable).await;
+
+        let transition = set_state(&svc, &relay, EntityState::Off).await.unwrap();
+
+        assert_eq!(transition.event_type, EventType::DeviceBackOnline);
+        assert_eq!(transition.data["status"], "online");
+    }
+
+    #[tokio::test]
+    async fn should_not_publish_twice_while_device_stays_unavailable() {
+        let (svc, _, relay, power) = setup().await;
+        set_state(&svc, &relay, EntityState::Unavailable).await;
+        set_state(&svc, &power, EntityState::Unavailable).await;
+
+        let transition = set_state(&svc, &power, EntityState::Unavailable).await;
+
+        assert!(transition.is_none());
+        assert_eq!(svc.publisher.events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_ignore_events_when_not_about_entity_state() {
+        let (svc, _, relay, _) = setup().await;
+        let event = Event::new(
+            EventType::AttributeChanged,
+            Some(relay.id),
+            serde_json::json!({}),
+        );
+
+        let transition = svc.process_event(&event).await.unwrap();
+
+        assert!(transition.is_none());
+    }
+}
@@ -0,0 +1,833 @@
+//! Device registry — deduplicates devices reported by integrations.
+//!
+//! Integrations re-report the same devices over and over (the BLE scanner
+//! does so for every advertisement). The registry keys devices on their
+//! `(integration, unique_id)` pair so each physical device is stored once,
+//! merges metadata updates into the stored device, and attaches discovered
+//! entities to it. [`EventType::DeviceUpdated`] is only published when the
+//! stored metadata actually changes.
+//!
+//! Entities are likewise keyed on their `entity_id`: adapters rebuild them
+//! with a fresh [`EntityId`](minihub_domain::id::EntityId) on every report,
+//! and the report is merged into the stored entity, which keeps its id.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use minihub_domain::device::Device;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{DeviceUpdatedPayload, Event, EventType};
+
+use crate::ports::{
+    DeviceRepository, DiscoveredDevice, DiscoveryRepository, EntityRepository, EventPublisher,
+};
+use crate::services::device_service::DeviceService;
+use crate::services::entity_service::EntityService;
+
+/// Application service registering discovered devices and their entities.
+pub struct DeviceRegistry<DR, ER, EP, DSR> {
+    device_service: Arc<DeviceService<DR>>,
+    entity_service: Arc<EntityService<ER, EP>>,
+    discovery: DSR,
+    publisher: EP,
+    /// Serializes lookups and writes so concurrent reports of the same
+    /// device or entity cannot both create it.
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl<DR, ER, EP, DSR> DeviceRegistry<DR, ER, EP, DSR> {
+    /// Create a new registry on top of the shared device and entity
+    /// services, writing discovered devices through `discovery`.
+    pub fn new(
+        device_service: Arc<DeviceService<DR>>,
+        entity_service: Arc<EntityService<ER, EP>>,
+        discovery: DSR,
+        publisher: EP,
+    ) -> Self {
+        Self {
+            device_service,
+            entity_service,
+            discovery,
+            publisher,
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+/// A device registration computed but not written yet.
+struct PendingDevice {
+    device: Device,
+    /// Whether no device is registered yet for its `(integration, unique_id)`.
+    new: bool,
+    /// Whether `device` differs from the registered one.
+    dirty: bool,
+    /// [`EventType::DeviceUpdated`] to publish once `device` is stored.
+    event: Option<Event>,
+}
+
+impl<DR, ER, EP, DSR> DeviceRegistry<DR, ER, EP, DSR>
+where
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+    DSR: DiscoveryRepository,
+{
+    /// Store `device`, or merge it into the device already registered with
+    /// the same `(integration, unique_id)`.
+    ///
+    /// The stored device keeps its id and area. Its name is replaced, and
+    /// its manufacturer, model, versions, suggested area and via device are
+    /// replaced when `device` sets them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, or a
+    /// storage error propagated from the repository.
+    #[tracing::instrument(skip(self, device), fields(integration = %device.integration, unique_id = %device.unique_id))]
+    pub async fn register_device(&self, device: Device) -> Result<Device, MiniHubError> {
+        let _guard = self.lock.lock().await;
+        let pending = self.prepare_device(device).await?;
+        if !pending.dirty {
+            return Ok(pending.device);
+        }
+        let saved = if pending.new {
+            self.device_service.create_device(pending.device).await?
+        } else {
+            self.device_service.update_device(pending.device).await?
+        };
+        if let Some(event) = pending.event {
+            let _ = self.publisher.publish(event).await;
+        }
+        Ok(saved)
+    }
+
+    /// Register a discovered device, then upsert its entities under the
+    /// registered device.
+    ///
+    /// The device's `via_device` is resolved among the devices of the same
+    /// integration; it is left unset while that device is not registered.
+    ///
+    /// Entities are matched on their `entity_id`, so re-discovered entities
+    /// keep their existing [`EntityId`](minihub_domain::id::EntityId). When
+    /// the same `entity_id` is reported twice, the last report wins. The
+    /// device and its entities are written in one transaction, and the
+    /// resulting events are only published once it committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, or a
+    /// storage error propagated from the repositories, in which case
+    /// nothing was written.
+    pub async fn register(&self, discovered: DiscoveredDevice) -> Result<Device, MiniHubError> {
+        let _guard = self.lock.lock().await;
+        let mut device = discovered.device;
+        if let Some(parent) = discovered.via_device.as_deref() {
+            device.via_device_id = self
+                .device_service
+                .find_by_integration_unique_id(&device.integration, parent)
+                .await?
+                .map(|parent| parent.id);
+        }
+        let pending = self.prepare_device(device).await?;
+        if !pending.dirty && discovered.entities.is_empty() {
+            return Ok(pending.device);
+        }
+
+        let mut events: Vec<Event> = pending.event.into_iter().collect();
+        let reported = last_reports(discovered.entities);
+        let _state_guard = self.entity_service.lock_state().await;
+        let mut entities = Vec::with_capacity(reported.len());
+        for mut entity in reported {
+            entity.device_id = pending.device.id;
+            let upsert = self.entity_service.prepare_upsert(entity).await?;
+            entities.push(upsert.entity);
+            events.extend(upsert.event);
+        }
+        let discovered = DiscoveredDevice {
+            device: pending.device,
+            entities,
+            via_device: None,
+        };
+        self.discovery.persist(&discovered).await?;
+
+        for event in events {
+            let _ = self.publisher.publish(event).await;
+        }
+        Ok(discovered.device)
+    }
+
+    /// Upsert an entity reported outside of a discovery, e.g. a state update,
+    /// matching it on its `entity_id` like [`Self::register`] does.
+    ///
+    /// Serialized with registrations, so an entity reported by both a
+    /// discovery and a state update is only created once. The published
+    /// event is recorded as a consequence of `cause` when given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if a new entity is invalid, or a
+    /// storage error propagated from the repository.
+    pub async fn upsert_entity(
+        &self,
+        entity: Entity,
+        cause: Option<&Event>,
+    ) -> Result<Entity, MiniHubError> {
+        let _guard = self.lock.lock().await;
+        match cause {
+            Some(cause) => {
+                self.entity_service
+                    .upsert_entity_caused_by(entity, cause)
+                    .await
+            }
+            None => self.entity_service.upsert_entity(entity).await,
+        }
+    }
+
+    /// Resolve `device` against the registered devices, validating it when
+    /// it is new and merging it into the registered one otherwise.
+    async fn prepare_device(&self, device: Device) -> Result<PendingDevice, MiniHubError> {
+        let Some(existing) = self
+            .device_service
+            .find_by_integration_unique_id(&device.integration, &device.unique_id)
+            .await?
+        else {
+            device.validate()?;
+            return Ok(PendingDevice {
+                device,
+                new: true,
+                dirty: true,
+                event: None,
+            });
+        };
+
+        let (merged, changed) = merge(existing, device);
+        if changed.is_empty() {
+            return Ok(PendingDevice {
+                device: merged,
+                new: false,
+                dirty: false,
+                event: None,
+            });
+        }
+        merged.validate()?;
+        let event = Event::with_payload(
+            EventType::DeviceUpdated,
+            None,
+            &DeviceUpdatedPayload {
+                device_id: merged.id,
+                integration: merged.integration.clone(),
+                unique_id: merged.unique_id.clone(),
+                changed: changed.into_iter().map(str::to_string).collect(),
+            },
+        );
+        Ok(PendingDevice {
+            device: merged,
+            new: false,
+            dirty: true,
+            event: Some(event),
+        })
+    }
+}
+
+/// Keep the last report of every `entity_id` in `entities`, in the order
+/// of their last occurrence.
+fn last_reports(entities: Vec<Entity>) -> Vec<Entity> {
+    let mut seen = HashSet::new();
+    let mut reported: Vec<Entity> = entities
+        .into_iter()
+        .rev()
+        .filter(|entity| seen.insert(entity.entity_id.clone()))
+        .collect();
+    reported.reverse();
+    reported
+}
+
+/// Merge the metadata of `discovered` into `existing`, returning the merged
+/// device and the names of the fields that changed.
+fn merge(existing: Device, discovered: Device) -> (Device, Vec<&'static str>) {
+    let mut changed = Vec::new();
+    let mut merged = existing;
+    if merged.name != discovered.name {
+        merged.name = discovered.name;
+        changed.push("name");
+    }
+    if discovered.manufacturer.is_some() && merged.manufacturer != discovered.manufacturer {
+        merged.manufacturer = discovered.manufacturer;
+        changed.push("manufacturer");
+    }
+    if discovered.model.is_some() && merged.model != discovered.model {
+        merged.model = discovered.model;
+        changed.push("model");
+    }
+    if discovered.via_device_id.is_some() && merged.via_device_id != discovered.via_device_id {
+        merged.via_device_id = discovered.via_device_id;
+        changed.push("via_device_id");
+    }
+    if discovered.sw_version.is_some() && merged.sw_version != discovered.sw_version {
+        merged.sw_version = discovered.sw_version;
+        changed.push("sw_version");
+    }
+    if discovered.hw_version.is_some() && merged.hw_version != discovered.hw_version {
+        merged.hw_version = discovered.hw_version;
+        changed.push("hw_version");
+    }
+    if discovered.suggested_area.is_some() && merged.suggested_area != discovered.suggested_area {
+        merged.suggested_area = discovered.suggested_area;
+        changed.push("suggested_area");
+    }
+    (merged, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::entity::EntityState;
+    use minihub_domain::id::{AreaId, DeviceId, EntityId};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Arc<Mutex<HashMap<EntityId, Entity>>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        fn create(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: EntityId,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|ent| ent.device_id == device_id)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_entity_id(
+            &self,
+            entity_id: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|ent| ent.entity_id == entity_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Arc<Mutex<HashMap<DeviceId, Device>>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        fn create(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: DeviceId,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result: Vec<Device> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    struct InMemoryDiscoveryRepo {
+        devices: Arc<Mutex<HashMap<DeviceId, Device>>>,
+        entities: Arc<Mutex<HashMap<EntityId, Entity>>>,
+        failing: bool,
+    }
+
+    impl DiscoveryRepository for InMemoryDiscoveryRepo {
+        async fn persist(&self, discovered: &DiscoveredDevice) -> Result<(), MiniHubError> {
+            if self.failing {
+                return Err(anyhow::anyhow!("disk full").into());
+            }
+            self.devices
+                .lock()
+                .unwrap()
+                .insert(discovered.device.id, discovered.device.clone());
+            let mut entities = self.entities.lock().unwrap();
+            for entity in &discovered.entities {
+                entities.insert(entity.id, entity.clone());
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Registry = DeviceRegistry<
+        InMemoryDeviceRepo,
+        InMemoryEntityRepo,
+        Arc<SpyPublisher>,
+        InMemoryDiscoveryRepo,
+    >;
+
+    fn setup_with(failing: bool) -> (Registry, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let device_repo = InMemoryDeviceRepo::default();
+        let entity_repo = InMemoryEntityRepo::default();
+        let discovery = InMemoryDiscoveryRepo {
+            devices: Arc::clone(&device_repo.store),
+            entities: Arc::clone(&entity_repo.store),
+            failing,
+        };
+        let registry = DeviceRegistry::new(
+            Arc::new(DeviceService::new(device_repo)),
+            Arc::new(EntityService::new(entity_repo, Arc::clone(&publisher))),
+            discovery,
+            Arc::clone(&publisher),
+        );
+        (registry, publisher)
+    }
+
+    fn setup() -> (Registry, Arc<SpyPublisher>) {
+        setup_with(false)
+    }
+
+    fn thermometer(name: &str) -> Device {
+        Device::builder()
+            .name(name)
+            .manufacturer("Xiaomi")
+            .integration("ble")
+            .unique_id("a4:c1:38:00:00:01")
+            .build()
+            .unwrap()
+    }
+
+    fn discovered(device: Device) -> DiscoveredDevice {
+        let entity = Entity::builder()
+            .device_id(device.id)
+            .entity_id("sensor.ble_a4c138000001_temperature")
+            .friendly_name("Temperature")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        DiscoveredDevice {
+            device,
+            entities: vec![entity],
+            via_device: None,
+        }
+    }
+
+    fn device_updated_count(publisher: &SpyPublisher) -> usize {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::DeviceUpdated)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn should_create_device_when_not_registered() {
+        let (registry, publisher) = setup();
+
+        let device = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+
+        let all = registry.device_service.list_devices().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, device.id);
+        assert_eq!(device_updated_count(&publisher), 0);
+    }
+
+    #[tokio::test]
+    async fn should_remove_entities_when_device_is_deleted_with_cascade() {
+        let (registry, publisher) = setup();
+        let device = registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await
+            .unwrap();
+
+        registry
+            .device_service
+            .delete_device_cascade(device.id, &registry.entity_service)
+            .await
+            .unwrap();
+
+        assert!(
+            registry
+                .device_service
+                .list_devices()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            registry
+                .entity_service
+                .list_entities()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let removed = publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::EntityRemoved)
+            .count();
+        assert_eq!(removed, 1);
+    }
+
+    #[tokio::test]
+    async fn should_list_devices_of_an_integration() {
+        let (registry, _) = setup();
+        let device = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+
+        let ble = registry
+            .device_service
+            .find_by_integration("ble")
+            .await
+            .unwrap();
+        let mqtt = registry
+            .device_service
+            .find_by_integration("mqtt")
+            .await
+            .unwrap();
+
+        assert_eq!(ble.len(), 1);
+        assert_eq!(ble[0].id, device.id);
+        assert!(mqtt.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_keep_single_device_when_rediscovered_unchanged() {
+        let (registry, publisher) = setup();
+        let first = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+
+        let second = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+
+        assert_eq!(second.id, first.id);
+        assert_eq!(
+            registry.device_service.list_devices().await.unwrap().len(),
+            1
+        );
+        assert_eq!(device_updated_count(&publisher), 0);
+    }
+
+    #[tokio::test]
+    async fn should_merge_metadata_and_publish_when_rediscovered_with_changes() {
+        let (registry, publisher) = setup();
+        let first = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+        let area_id = AreaId::new();
+        registry
+            .device_service
+            .assign_area(first.id, Some(area_id))
+            .await
+            .unwrap();
+
+        let mut renamed = thermometer("Bedroom thermometer");
+        renamed.manufacturer = None;
+        renamed.model = Some("LYWSD03MMC".to_string());
+        let merged = registry.register_device(renamed).await.unwrap();
+
+        assert_eq!(merged.id, first.id);
+        assert_eq!(merged.name, "Bedroom thermometer");
+        assert_eq!(merged.manufacturer.as_deref(), Some("Xiaomi"));
+        assert_eq!(merged.model.as_deref(), Some("LYWSD03MMC"));
+        assert_eq!(merged.area_id, Some(area_id));
+        assert_eq!(device_updated_count(&publisher), 1);
+        let events = publisher.events.lock().unwrap();
+        let event = events
+            .iter()
+            .find(|event| event.event_type == EventType::DeviceUpdated)
+            .unwrap();
+        assert_eq!(event.data["changed"], serde_json::json!(["name", "model"]));
+    }
+
+    #[tokio::test]
+    async fn should_attach_entities_to_registered_device_when_rediscovered() {
+        let (registry, _) = setup();
+        let first = registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await
+            .unwrap();
+        let entity_id = registry
+            .entity_service
+            .find_by_entity_id("sensor.ble_a4c138000001_temperature")
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+
+        let second = registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await
+            .unwrap();
+
+        assert_eq!(second.id, first.id);
+        let entities = registry.entity_service.list_entities().await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].id, entity_id);
+        assert_eq!(entities[0].device_id, first.id);
+    }
+
+    #[tokio::test]
+    async fn should_keep_last_report_when_entity_is_reported_twice() {
+        let (registry, _) = setup();
+        let mut dd = discovered(thermometer("LYWSD03MMC"));
+        let mut again = dd.entities[0].clone();
+        again.id = EntityId::new();
+        again.state = EntityState::Off;
+        dd.entities.push(again);
+
+        registry.register(dd).await.unwrap();
+
+        let entities = registry.entity_service.list_entities().await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].state, EntityState::Off);
+    }
+
+    #[tokio::test]
+    async fn should_attach_new_entities_to_existing_device() {
+        let (registry, _) = setup();
+        let first = registry
+            .register_device(thermometer("LYWSD03MMC"))
+            .await
+            .unwrap();
+
+        registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await
+            .unwrap();
+
+        let entities = registry.entity_service.list_entities().await.unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].device_id, first.id);
+    }
+
+    #[tokio::test]
+    async fn should_publish_entity_events_after_persisting_discovery() {
+        let (registry, publisher) = setup();
+
+        registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            registry.device_service.list_devices().await.unwrap().len(),
+            1
+        );
+        let events = publisher.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, EventType::EntityCreated);
+    }
+
+    #[tokio::test]
+    async fn should_write_nothing_nor_publish_when_persisting_discovery_fails() {
+        let (registry, publisher) = setup_with(true);
+
+        let result = registry
+            .register(discovered(thermometer("LYWSD03MMC")))
+            .await;
+
+        assert!(matches!(result, Err(MiniHubError::Storage(_))));
+        assert!(
+            registry
+                .device_service
+                .list_devices()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            registry
+                .entity_service
+                .list_entities()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(publisher.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_resolve_via_device_among_registered_devices() {
+        let (registry, _) = setup();
+        let bridge = Device::builder()
+            .name("Gateway")
+            .integration("ble")
+            .unique_id("gateway")
+            .build()
+            .unwrap();
+        let bridge = registry.register_device(bridge).await.unwrap();
+        let mut child = discovered(thermometer("LYWSD03MMC"));
+        child.via_device = Some("gateway".to_string());
+
+        let device = registry.register(child).await.unwrap();
+
+        assert_eq!(device.via_device_id, Some(bridge.id));
+    }
+
+    #[tokio::test]
+    async fn should_leave_via_device_unset_when_not_registered() {
+        let (registry, _) = setup();
+        let mut child = discovered(thermometer("LYWSD03MMC"));
+        child.via_device = Some("gateway".to_string());
+
+        let device = registry.register(child).await.unwrap();
+
+        assert!(device.via_device_id.is_none());
+    }
+}
@@ -2,9 +2,16 @@
 
 use minihub_domain::device::Device;
 use minihub_domain::error::{MiniHubError, NotFoundError};
-use minihub_domain::id::DeviceId;
+use minihub_domain::id::{AreaId, DeviceId};
 
-use crate::ports::DeviceRepository;
+use crate::ports::{DeviceRepository, EntityRepository, EventPublisher};
+use crate::services::entity_service::EntityService;
+
+/// Integration name of the built-in hub device.
+const HUB_INTEGRATION: &str = "minihub";
+
+/// Unique id of the built-in hub device.
+const HUB_UNIQUE_ID: &str = "hub";
 
 /// Application service for device CRUD operations.
 pub struct DeviceService<R> {
@@ -55,6 +62,42 @@ impl<R: DeviceRepository> DeviceService<R> {
         self.repo.get_all().await
     }
 
+    /// List the devices placed in the area `area_id`, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list_devices_in_area(&self, area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+        self.repo.find_by_area(area_id).await
+    }
+
+    /// List the devices reported by `integration`, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn find_by_integration(
+        &self,
+        integration: &str,
+    ) -> Result<Vec<Device>, MiniHubError> {
+        self.repo.find_by_integration(integration).await
+    }
+
+    /// Look up a device by its `(integration, unique_id)` pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn find_by_integration_unique_id(
+        &self,
+        integration: &str,
+        unique_id: &str,
+    ) -> Result<Option<Device>, MiniHubError> {
+        self.repo
+            .find_by_integration_unique_id(integration, unique_id)
+            .await
+    }
+
     /// Update an existing device.
     ///
     /// # Errors
@@ -67,6 +110,28 @@ impl<R: DeviceRepository> DeviceService<R> {
         self.repo.update(device).await
     }
 
+    /// Move the device `id` into `area_id`, or out of any area when `None`.
+    ///
+    /// The caller is responsible for checking that the area exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no device with `id` exists,
+    /// or a storage error from the repository.
+    #[tracing::instrument(skip(self))]
+    pub async fn assign_area(
+        &self,
+        id: DeviceId,
+        area_id: Option<AreaId>,
+    ) -> Result<Device, MiniHubError> {
+        let mut device = self.get_device(id).await?;
+        if device.area_id == area_id {
+            return Ok(device);
+        }
+        device.area_id = area_id;
+        self.update_device(device).await
+    }
+
     /// Create or update a device by its `(integration, unique_id)` pair.
     ///
     /// If a device with the same integration and unique id already exists, its
@@ -92,12 +157,42 @@ impl<R: DeviceRepository> DeviceService<R> {
                 area_id: existing.area_id,
                 integration: device.integration,
                 unique_id: device.unique_id,
+                via_device_id: device.via_device_id,
+                sw_version: device.sw_version,
+                hw_version: device.hw_version,
+                suggested_area: device.suggested_area,
             };
             return self.update_device(updated).await;
         }
         self.create_device(device).await
     }
 
+    /// Return the built-in hub device, creating it when it does not exist
+    /// yet.
+    ///
+    /// The hub device carries the entities minihub defines itself, such as
+    /// the home mode and input helpers.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn ensure_hub_device(&self) -> Result<Device, MiniHubError> {
+        if let Some(hub) = self
+            .repo
+            .find_by_integration_unique_id(HUB_INTEGRATION, HUB_UNIQUE_ID)
+            .await?
+        {
+            return Ok(hub);
+        }
+        let hub = Device::builder()
+            .name("minihub")
+            .manufacturer("minihub")
+            .integration(HUB_INTEGRATION)
+            .unique_id(HUB_UNIQUE_ID)
+            .build()?;
+        self.create_device(hub).await
+    }
+
     /// Delete a device by id.
     ///
     /// # Errors
@@ -107,6 +202,33 @@ impl<R: DeviceRepository> DeviceService<R> {
     pub async fn delete_device(&self, id: DeviceId) -> Result<(), MiniHubError> {
         self.repo.delete(id).await
     }
+
+    /// Delete the device `id` along with its entities, and their history,
+    /// through `entities` so an [`EntityRemoved`] event is published for
+    /// each of them.
+    ///
+    /// [`EntityRemoved`]: minihub_domain::event::EventType::EntityRemoved
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no device with `id` exists,
+    /// or a storage error from the repositories.
+    #[tracing::instrument(skip(self, entities))]
+    pub async fn delete_device_cascade<ER, EP>(
+        &self,
+        id: DeviceId,
+        entities: &EntityService<ER, EP>,
+    ) -> Result<(), MiniHubError>
+    where
+        ER: EntityRepository,
+        EP: EventPublisher,
+    {
+        let device = self.get_device(id).await?;
+        for entity in entities.list_entities_by_device(device.id).await? {
+            entities.delete_entity(entity.id).await?;
+        }
+        self.repo.delete(device.id).await
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +276,32 @@ mod tests {
             async { Ok(result) }
         }
 
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result: Vec<Device> = store
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result: Vec<Device> = store
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
         fn find_by_integration_unique_id(
             &self,
             integration: &str,
@@ -261,6 +409,29 @@ mod tests {
         assert_eq!(saved.name, "Updated Bridge");
     }
 
+    #[tokio::test]
+    async fn should_assign_and_clear_area() {
+        let svc = make_service();
+        let device = valid_device();
+        let id = device.id;
+        svc.create_device(device).await.unwrap();
+        let area_id = AreaId::new();
+
+        let assigned = svc.assign_area(id, Some(area_id)).await.unwrap();
+        assert_eq!(assigned.area_id, Some(area_id));
+        assert_eq!(svc.get_device(id).await.unwrap().area_id, Some(area_id));
+
+        let cleared = svc.assign_area(id, None).await.unwrap();
+        assert_eq!(cleared.area_id, None);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_assigning_missing_device() {
+        let svc = make_service();
+        let result = svc.assign_area(DeviceId::new(), Some(AreaId::new())).await;
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
     #[tokio::test]
     async fn should_delete_device() {
         let svc = make_service();
@@ -0,0 +1,364 @@
+//! Discovery service — the inbox of devices detected but not registered.
+//!
+//! Integrations publish [`EventType::DeviceDetected`] for every device they
+//! notice. The service keeps one [`PendingDevice`] per detected device,
+//! hides those already registered, and adopts a pending device by
+//! registering it as a regular [`Device`]. The inbox lives in memory and is
+//! rebuilt from the stored detection events at startup.
+
+use std::cmp::Reverse;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::broadcast;
+
+use minihub_domain::device::Device;
+use minihub_domain::error::{ConflictError, MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::PendingDeviceId;
+use minihub_domain::pending_device::{Adoption, PendingDevice};
+
+use crate::event_bus::EventFilter;
+use crate::ports::{DeviceRepository, EventQuery, EventStore, EventSubscription};
+use crate::services::device_service::DeviceService;
+
+/// Detection events read back from the event store at startup.
+const PRIME_LIMIT: usize = 1000;
+
+/// Pending devices kept at most; the least recently seen are dropped first.
+const MAX_PENDING: usize = 500;
+
+/// Application service for detected devices waiting to be adopted.
+pub struct DiscoveryService<DR> {
+    device_service: Arc<DeviceService<DR>>,
+    pending: Mutex<Vec<PendingDevice>>,
+}
+
+impl<DR> DiscoveryService<DR> {
+    /// Create a new service registering adopted devices through
+    /// `device_service`.
+    pub fn new(device_service: Arc<DeviceService<DR>>) -> Self {
+        Self {
+            device_service,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The events [`Self::process_event`] acts on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all().with_event_types([EventType::DeviceDetected])
+    }
+
+    /// Record the device reported by a [`EventType::DeviceDetected`] event,
+    /// or refresh it when it is already pending.
+    ///
+    /// Returns `false` when the event does not describe a device.
+    pub fn process_event(&self, event: &Event) -> bool {
+        let Some(detection) = PendingDevice::from_event(event) else {
+            return false;
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(known) = pending
+            .iter_mut()
+            .find(|known| known.same_device(&detection))
+        {
+            known.refresh(detection);
+        } else {
+            pending.push(detection);
+        }
+        if pending.len() > MAX_PENDING {
+            pending.sort_by_key(|pending| Reverse(pending.last_seen));
+            pending.truncate(MAX_PENDING);
+        }
+        true
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Runs until the bus is closed.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    self.process_event(&event);
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "discovery service lagged, some detections were dropped"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        tracing::debug!("discovery service stopped");
+    }
+}
+
+impl<DR> DiscoveryService<DR>
+where
+    DR: DeviceRepository,
+{
+    /// Rebuild the inbox from the most recent detection events of `store`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the event store.
+    pub async fn prime<ES: EventStore>(&self, store: &ES) -> Result<(), MiniHubError> {
+        let events = store
+            .query(EventQuery::new(PRIME_LIMIT).with_event_type(EventType::DeviceDetected))
+            .await?;
+        // Oldest first, so later detections refresh earlier ones.
+        for event in events.iter().rev() {
+            self.process_event(event);
+        }
+        Ok(())
+    }
+
+    /// List the pending devices not registered yet, most recently seen
+    /// first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the device repository.
+    pub async fn list_pending(&self) -> Result<Vec<PendingDevice>, MiniHubError> {
+        let devices = self.device_service.list_devices().await?;
+        let mut pending: Vec<PendingDevice> = self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|pending| {
+                let (integration, unique_id) = pending.registration_key(&Adoption::default());
+                !devices.iter().any(|device| {
+                    device.integration == integration
+                        && device.unique_id.eq_ignore_ascii_case(&unique_id)
+                })
+            })
+            .cloned()
+            .collect();
+        pending.sort_by_key(|pending| Reverse(pending.last_seen));
+        Ok(pending)
+    }
+
+    /// Register the pending device `id` as a regular device and remove it
+    /// from the inbox.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] if no device with `id` is
+    /// pending, [`MiniHubError::Validation`] if the adoption leaves a
+    /// required field empty, [`MiniHubError::Conflict`] if a device is
+    /// already registered with the same integration and unique id, or a
+    /// storage error propagated from the device repository.
+    #[tracing::instrument(skip(self, adoption))]
+    pub async fn adopt(
+        &self,
+        id: PendingDeviceId,
+        adoption: Adoption,
+    ) -> Result<Device, MiniHubError> {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|pending| pending.id == id)
+            .cloned()
+            .ok_or_else(|| NotFoundError {
+                entity: "Pending device",
+                id: id.to_string(),
+            })?;
+
+        let device = pending.adopt(adoption)?;
+        if self
+            .device_service
+            .find_by_integration_unique_id(&device.integration, &device.unique_id)
+            .await?
+            .is_some()
+        {
+            return Err(ConflictError {
+                entity: "Device",
+                id: device.unique_id,
+                expected: "unregistered".to_string(),
+                actual: "registered".to_string(),
+            }
+            .into());
+        }
+        let device = self.device_service.create_device(device).await?;
+
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|pending| pending.id != id);
+        tracing::info!(
+            device_id = %device.id,
+            integration = %device.integration,
+            unique_id = %device.unique_id,
+            "adopted detected device"
+        );
+        Ok(device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minihub_domain::id::{AreaId, DeviceId};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        async fn create(&self, device: Device) -> Result<Device, MiniHubError> {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            Ok(device)
+        }
+        async fn get_by_id(&self, id: DeviceId) -> Result<Option<Device>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
+            Ok(self.store.lock().unwrap().values().cloned().collect())
+        }
+        async fn find_by_area(&self, area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect())
+        }
+        async fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> Result<Vec<Device>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect())
+        }
+        async fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> Result<Option<Device>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned())
+        }
+        async fn update(&self, device: Device) -> Result<Device, MiniHubError> {
+            self.create(device).await
+        }
+        async fn delete(&self, id: DeviceId) -> Result<(), MiniHubError> {
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    fn service() -> DiscoveryService<InMemoryDeviceRepo> {
+        DiscoveryService::new(Arc::new(DeviceService::new(InMemoryDeviceRepo::default())))
+    }
+
+    fn detected(mac: &str, name: Option<&str>) -> Event {
+        Event::new(
+            EventType::DeviceDetected,
+            None,
+            serde_json::json!({ "integration": "ble", "mac": mac, "name": name }),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_list_each_detected_device_once() {
+        let service = service();
+        service.process_event(&detected("A4:C1:38:00:00:01", Some("Thermometer")));
+        service.process_event(&detected("A4:C1:38:00:00:02", None));
+        service.process_event(&detected("A4:C1:38:00:00:01", None));
+
+        let pending = service.list_pending().await.unwrap();
+
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].address, "A4:C1:38:00:00:01");
+        assert_eq!(pending[0].name.as_deref(), Some("Thermometer"));
+    }
+
+    #[tokio::test]
+    async fn should_ignore_events_without_device() {
+        let service = service();
+        let event = Event::new(EventType::DeviceDetected, None, serde_json::json!({}));
+
+        assert!(!service.process_event(&event));
+        assert!(service.list_pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_register_device_and_clear_it_when_adopted() {
+        let service = service();
+        service.process_event(&detected("A4:C1:38:00:00:01", Some("Thermometer")));
+        let id = service.list_pending().await.unwrap()[0].id;
+
+        let device = service
+            .adopt(
+                id,
+                Adoption {
+                    name: Some("Bedroom thermometer".to_string()),
+                    ..Adoption::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(device.name, "Bedroom thermometer");
+        assert_eq!(device.integration, "ble");
+        assert_eq!(device.unique_id, "A4:C1:38:00:00:01");
+        assert!(service.list_pending().await.unwrap().is_empty());
+        // A later detection of the registered device stays out of the inbox.
+        service.process_event(&detected("A4:C1:38:00:00:01", None));
+        assert!(service.list_pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_adopting_unknown_device() {
+        let result = service()
+            .adopt(PendingDeviceId::new(), Adoption::default())
+            .await;
+
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_return_conflict_when_device_is_already_registered() {
+        let service = service();
+        service.process_event(&detected("A4:C1:38:00:00:01", None));
+        let id = service.list_pending().await.unwrap()[0].id;
+        service
+            .device_service
+            .create_device(
+                Device::builder()
+                    .name("Thermometer")
+                    .integration("ble")
+                    .unique_id("A4:C1:38:00:00:01")
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let result = service.adopt(id, Adoption::default()).await;
+
+        assert!(matches!(result, Err(MiniHubError::Conflict(_))));
+    }
+}
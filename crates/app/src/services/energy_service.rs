@@ -0,0 +1,390 @@
+//! Energy service — turns the readings of power and energy sensors into
+//! hourly consumption.
+//!
+//! Entities with the [`Power`](DeviceClass::Power) class report watts: the
+//! previous reading is assumed to hold until the next one, and the
+//! watt-hours in between are recorded. Entities with the
+//! [`Energy`](DeviceClass::Energy) class report a growing counter, whose
+//! increase between two readings is recorded. The reading is the numeric
+//! state, or the `power` / `energy` attribute when the state is not a
+//! number. Readings are kept in memory, so the first one after a restart,
+//! or after the entity was unavailable, only sets the baseline.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use tokio::sync::broadcast;
+
+use minihub_domain::energy::EnergyUsage;
+use minihub_domain::entity::{AttributeValue, DeviceClass, Entity};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::id::EntityId;
+use minihub_domain::time::Timestamp;
+
+use crate::event_bus::EventFilter;
+use crate::ports::{EnergyUsageRepository, EntityRepository, EventSubscription};
+
+/// Last reading of a metered entity, in watts or watt-hours.
+#[derive(Debug, Clone, Copy)]
+struct Reading {
+    class: DeviceClass,
+    value: f64,
+    at: Timestamp,
+}
+
+impl Reading {
+    /// The reading of `entity` at `at`, converted from kilo-units, or
+    /// `None` when it is not metered or has no numeric reading.
+    fn of(entity: &Entity, at: Timestamp) -> Option<Self> {
+        let class = entity.device_class?;
+        let key = match class {
+            DeviceClass::Power => "power",
+            DeviceClass::Energy => "energy",
+            _ => return None,
+        };
+        let value = entity
+            .state
+            .as_f64()
+            .or_else(|| match entity.attributes.get(key)? {
+                AttributeValue::Float(value) => Some(*value),
+                #[allow(clippy::cast_precision_loss)]
+                AttributeValue::Int(value) => Some(*value as f64),
+                _ => None,
+            })?;
+        let scale = match entity.unit_of_measurement.as_deref() {
+            Some("kW" | "kWh") => 1000.0,
+            _ => 1.0,
+        };
+        Some(Self {
+            class,
+            value: value * scale,
+            at,
+        })
+    }
+
+    /// Watt-hours consumed between `self` and the `next` reading, or
+    /// `None` when nothing can be told, e.g. after a counter reset.
+    fn consumed_until(self, next: Self) -> Option<f64> {
+        if next.class != self.class || next.at < self.at {
+            return None;
+        }
+        let energy_wh = match self.class {
+            DeviceClass::Power => self.value * (next.at - self.at).as_seconds_f64() / 3600.0,
+            _ => next.value - self.value,
+        };
+        (energy_wh > 0.0).then_some(energy_wh)
+    }
+}
+
+/// Application service recording the consumption of metered entities.
+pub struct EnergyService<ER, EUR> {
+    entity_repo: ER,
+    usage_repo: EUR,
+    readings: Mutex<HashMap<EntityId, Reading>>,
+}
+
+impl<ER, EUR> EnergyService<ER, EUR>
+where
+    ER: EntityRepository,
+    EUR: EnergyUsageRepository,
+{
+    /// Create a new service without any previous reading.
+    pub fn new(entity_repo: ER, usage_repo: EUR) -> Self {
+        Self {
+            entity_repo,
+            usage_repo,
+            readings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The events [`Self::process_event`] may act on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all().with_event_types([EventType::StateChanged, EventType::AttributeChanged])
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Runs until the bus is closed. Failures are logged and do not stop the
+    /// loop.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Err(err) = self.process_event(&event).await {
+                        tracing::warn!(%err, event_id = %event.id, "failed to record energy usage");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "energy service lagged, some events were dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        tracing::debug!("energy service stopped");
+    }
+
+    /// Record the consumption of the entity in `event` since its previous
+    /// reading.
+    ///
+    /// Returns the recorded watt-hours, or `None` when the event is not a
+    /// reading of a metered entity or follows no usable reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self, event), fields(event_id = %event.id))]
+    pub async fn process_event(&self, event: &Event) -> Result<Option<f64>, MiniHubError> {
+        if !matches!(
+            event.event_type,
+            EventType::StateChanged | EventType::AttributeChanged
+        ) {
+            return Ok(None);
+        }
+        let Some(entity_id) = event.entity_id else {
+            return Ok(None);
+        };
+        let Some(entity) = self.entity_repo.get_by_id(entity_id).await? else {
+            return Ok(None);
+        };
+
+        let reading = Reading::of(&entity, event.timestamp);
+        let previous = {
+            let mut readings = self.readings.lock().unwrap_or_else(PoisonError::into_inner);
+            match reading {
+                Some(reading) => readings.insert(entity_id, reading),
+                None => readings.remove(&entity_id),
+            }
+        };
+        let (Some(previous), Some(reading)) = (previous, reading) else {
+            return Ok(None);
+        };
+        let Some(energy_wh) = previous.consumed_until(reading) else {
+            return Ok(None);
+        };
+
+        self.usage_repo
+            .add(EnergyUsage::spread(
+                entity_id,
+                previous.at,
+                reading.at,
+                energy_wh,
+            ))
+            .await?;
+        Ok(Some(energy_wh))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use minihub_domain::entity::EntityState;
+    use minihub_domain::id::DeviceId;
+
+    use super::*;
+
+    struct SingleEntityRepo(Mutex<Entity>);
+
+    impl SingleEntityRepo {
+        fn set(&self, state: EntityState) {
+            self.0.lock().unwrap().state = state;
+        }
+    }
+
+    impl EntityRepository for SingleEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            let entity = self.0.lock().unwrap().clone();
+            Ok((entity.id == id).then_some(entity))
+        }
+        async fn get_many(&self, _ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(
+            &self,
+            _entity_id: &str,
+        ) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyUsageRepo(Mutex<Vec<EnergyUsage>>);
+
+    impl EnergyUsageRepository for SpyUsageRepo {
+        async fn add(&self, usage: Vec<EnergyUsage>) -> Result<(), MiniHubError> {
+            self.0.lock().unwrap().extend(usage);
+            Ok(())
+        }
+        async fn find_by_entity_in_range(
+            &self,
+            _entity_id: EntityId,
+            _from: Timestamp,
+            _to: Timestamp,
+        ) -> Result<Vec<EnergyUsage>, MiniHubError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn service(
+        class: DeviceClass,
+        unit: &str,
+    ) -> (EntityId, EnergyService<SingleEntityRepo, SpyUsageRepo>) {
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("sensor.washer_power")
+            .friendly_name("Washer power")
+            .device_class(class)
+            .unit_of_measurement(unit)
+            .build()
+            .unwrap();
+        let id = entity.id;
+        let repo = SingleEntityRepo(Mutex::new(entity));
+        (id, EnergyService::new(repo, SpyUsageRepo::default()))
+    }
+
+    fn reading(id: EntityId, at: Timestamp) -> Event {
+        let mut event = Event::new(EventType::StateChanged, Some(id), serde_json::json!({}));
+        event.timestamp = at;
+        event
+    }
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_integrate_power_held_since_previous_reading() {
+        let (id, service) = service(DeviceClass::Power, "W");
+        service.entity_repo.set(EntityState::Numeric(200.0));
+        let first = service
+            .process_event(&reading(id, at("2026-03-02T10:30:00Z")))
+            .await
+            .unwrap();
+        service.entity_repo.set(EntityState::Numeric(50.0));
+        let second = service
+            .process_event(&reading(id, at("2026-03-02T12:00:00Z")))
+            .await
+            .unwrap();
+
+        assert_eq!(first, None);
+        assert_eq!(second, Some(300.0));
+        let hours: Vec<Timestamp> = service
+            .usage_repo
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|usage| usage.hour)
+            .collect();
+        assert_eq!(
+            hours,
+            vec![at("2026-03-02T10:00:00Z"), at("2026-03-02T11:00:00Z")]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_record_energy_counter_increase_in_watt_hours() {
+        let (id, service) = service(DeviceClass::Energy, "kWh");
+        let start = at("2026-03-02T10:00:00Z");
+        service.entity_repo.set(EntityState::Numeric(12.5));
+        service.process_event(&reading(id, start)).await.unwrap();
+        service.entity_repo.set(EntityState::Numeric(12.75));
+
+        let recorded = service
+            .process_event(&reading(id, start + TimeDelta::minutes(20)))
+            .await
+            .unwrap();
+
+        assert_eq!(recorded, Some(250.0));
+    }
+
+    #[tokio::test]
+    async fn should_restart_from_new_baseline_when_counter_resets() {
+        let (id, service) = service(DeviceClass::Energy, "Wh");
+        let start = at("2026-03-02T10:00:00Z");
+        service.entity_repo.set(EntityState::Numeric(900.0));
+        service.process_event(&reading(id, start)).await.unwrap();
+        service.entity_repo.set(EntityState::Numeric(10.0));
+        let reset = service
+            .process_event(&reading(id, start + TimeDelta::minutes(5)))
+            .await
+            .unwrap();
+        service.entity_repo.set(EntityState::Numeric(30.0));
+        let after = service
+            .process_event(&reading(id, start + TimeDelta::minutes(10)))
+            .await
+            .unwrap();
+
+        assert_eq!(reset, None);
+        assert_eq!(after, Some(20.0));
+    }
+
+    #[tokio::test]
+    async fn should_forget_reading_while_entity_is_unavailable() {
+        let (id, service) = service(DeviceClass::Power, "W");
+        let start = at("2026-03-02T10:00:00Z");
+        service.entity_repo.set(EntityState::Numeric(1000.0));
+        service.process_event(&reading(id, start)).await.unwrap();
+        service.entity_repo.set(EntityState::Unavailable);
+        service
+            .process_event(&reading(id, start + TimeDelta::hours(5)))
+            .await
+            .unwrap();
+        service.entity_repo.set(EntityState::Numeric(1000.0));
+
+        let recorded = service
+            .process_event(&reading(id, start + TimeDelta::hours(6)))
+            .await
+            .unwrap();
+
+        assert_eq!(recorded, None);
+        assert!(service.usage_repo.0.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_read_power_attribute_when_state_is_not_numeric() {
+        let (id, service) = service(DeviceClass::Power, "W");
+        let start = at("2026-03-02T10:00:00Z");
+        {
+            let mut entity = service.entity_repo.0.lock().unwrap();
+            entity.state = EntityState::On;
+            entity
+                .attributes
+                .insert("power".to_string(), AttributeValue::Int(60));
+        }
+        service.process_event(&reading(id, start)).await.unwrap();
+
+        let recorded = service
+            .process_event(&reading(id, start + TimeDelta::minutes(30)))
+            .await
+            .unwrap();
+
+        assert_eq!(recorded, Some(30.0));
+    }
+}
@@ -1,23 +1,46 @@
 //! Entity service — use-cases for managing entities.
 
 use minihub_domain::entity::{Entity, EntityState};
-use minihub_domain::error::{MiniHubError, NotFoundError};
-use minihub_domain::event::{Event, EventType};
-use minihub_domain::id::EntityId;
+use minihub_domain::error::{ConflictError, MiniHubError, NotFoundError, ValidationError};
+use minihub_domain::event::{
+    AttributeChangedPayload, EntityCreatedPayload, EntityRenamedPayload, Event, EventType,
+    StateChangedPayload,
+};
+use minihub_domain::id::{DeviceId, EntityId};
+use minihub_domain::service::ServiceCall;
 use minihub_domain::time::now;
 
+use crate::condition_evaluator::ConditionEvaluator;
 use crate::ports::{EntityRepository, EventPublisher};
 
+/// An entity upsert computed by [`EntityService::prepare_upsert`] but not
+/// written yet.
+pub(crate) struct PendingUpsert {
+    /// The entity to store.
+    pub entity: Entity,
+    /// Whether `entity` replaces a stored entity rather than creating one.
+    pub exists: bool,
+    /// Event to publish once `entity` is stored.
+    pub event: Option<Event>,
+}
+
 /// Application service for entity CRUD and state management.
 pub struct EntityService<R, P> {
     repo: R,
     publisher: P,
+    /// Serializes state read-modify-write cycles so conditional updates
+    /// cannot interleave.
+    state_lock: tokio::sync::Mutex<()>,
 }
 
 impl<R: EntityRepository, P: EventPublisher> EntityService<R, P> {
     /// Create a new service backed by the given repository and event publisher.
     pub fn new(repo: R, publisher: P) -> Self {
-        Self { repo, publisher }
+        Self {
+            repo,
+            publisher,
+            state_lock: tokio::sync::Mutex::new(()),
+        }
     }
 
     /// Create a new entity after validating domain invariants.
@@ -34,10 +57,12 @@ impl<R: EntityRepository, P: EventPublisher> EntityService<R, P> {
         entity.last_changed = ts;
         let created = self.repo.create(entity).await?;
 
-        let event = Event::new(
+        let event = Event::with_payload(
             EventType::EntityCreated,
             Some(created.id),
-            serde_json::json!({ "entity_id": created.entity_id }),
+            &EntityCreatedPayload {
+                entity_id: created.entity_id.clone(),
+            },
         );
         let _ = self.publisher.publish(event).await;
 
@@ -70,55 +95,269 @@ impl<R: EntityRepository, P: EventPublisher> EntityService<R, P> {
         self.repo.get_all().await
     }
 
-    /// Find an entity by its domain-level `entity_id` string.
+    /// Get the entities among `ids`, skipping the missing ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn get_entities(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+        self.repo.get_many(ids).await
+    }
+
+    /// List the entities exposed by a device.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list_entities_by_device(
+        &self,
+        device_id: DeviceId,
+    ) -> Result<Vec<Entity>, MiniHubError> {
+        self.repo.find_by_device_id(device_id).await
+    }
+
+    /// Find an entity by its domain-level `entity_id` string, falling back
+    /// to the former ids of renamed entities.
     ///
     /// # Errors
     ///
     /// Returns a storage error propagated from the repository.
     pub async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
-        self.repo.find_by_entity_id(entity_id).await
+        match self.repo.find_by_entity_id(entity_id).await? {
+            Some(entity) => Ok(Some(entity)),
+            None => self.repo.find_by_alias(entity_id).await,
+        }
+    }
+
+    /// Evaluate automation conditions against the stored entities.
+    pub fn condition_evaluator(&self) -> ConditionEvaluator<'_, R> {
+        ConditionEvaluator::new(&self.repo)
+    }
+
+    /// Change the `entity_id` of an entity, keeping the previous one as an
+    /// alias so integrations and lookups using it still reach the entity.
+    ///
+    /// Publishes an [`EventType::EntityRenamed`] event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] if the entity does not exist,
+    /// [`MiniHubError::Validation`] if the new id is malformed or already
+    /// used, currently or formerly, by another entity, or a storage error
+    /// from the repository.
+    #[tracing::instrument(skip(self))]
+    pub async fn rename_entity(
+        &self,
+        id: EntityId,
+        new_entity_id: &str,
+    ) -> Result<Entity, MiniHubError> {
+        let _guard = self.state_lock.lock().await;
+        let mut entity = self.get_entity(id).await?;
+        if entity.entity_id == new_entity_id {
+            return Ok(entity);
+        }
+        if let Some(other) = self.find_by_entity_id(new_entity_id).await?
+            && other.id != id
+        {
+            return Err(ValidationError::DuplicateEntityId(new_entity_id.to_string()).into());
+        }
+
+        let old_entity_id = entity.rename(new_entity_id)?;
+        entity.last_updated = now();
+        let saved = self.repo.update(entity).await?;
+        self.repo.add_alias(id, &old_entity_id).await?;
+
+        let event = Event::with_payload(
+            EventType::EntityRenamed,
+            Some(id),
+            &EntityRenamedPayload {
+                old_entity_id,
+                new_entity_id: saved.entity_id.clone(),
+            },
+        );
+        let _ = self.publisher.publish(event).await;
+
+        Ok(saved)
     }
 
     /// Update the state of an existing entity.
     ///
-    /// Publishes a [`EventType::StateChanged`] event when the state differs.
+    /// When `expected_state` is set, the update is a compare-and-set: it is
+    /// only applied if the current state matches. Publishes a
+    /// [`EventType::StateChanged`] event when the state differs.
     ///
     /// # Errors
     ///
     /// Returns [`MiniHubError::NotFound`] if the entity does not exist,
-    /// or a storage error from the repository.
+    /// [`MiniHubError::Conflict`] if the current state is not
+    /// `expected_state`, or a storage error from the repository.
     #[tracing::instrument(skip(self))]
     pub async fn update_entity_state(
         &self,
         id: EntityId,
         new_state: EntityState,
+        expected_state: Option<EntityState>,
     ) -> Result<Entity, MiniHubError> {
+        self.set_state(id, new_state, expected_state, None, None)
+            .await
+    }
+
+    /// Like [`Self::update_entity_state`], only applied if the entity is
+    /// still at `version`, e.g. the one a client read before editing it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::update_entity_state`], with
+    /// [`MiniHubError::Conflict`] if the entity was updated since `version`.
+    #[tracing::instrument(skip(self))]
+    pub async fn update_entity_state_at_version(
+        &self,
+        id: EntityId,
+        new_state: EntityState,
+        expected_state: Option<EntityState>,
+        version: u32,
+    ) -> Result<Entity, MiniHubError> {
+        self.set_state(id, new_state, expected_state, Some(version), None)
+            .await
+    }
+
+    /// Like [`Self::update_entity_state`], recording the state change as a
+    /// consequence of `cause`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::update_entity_state`].
+    #[tracing::instrument(skip(self, cause), fields(cause = %cause.id))]
+    pub async fn update_entity_state_caused_by(
+        &self,
+        id: EntityId,
+        new_state: EntityState,
+        expected_state: Option<EntityState>,
+        cause: &Event,
+    ) -> Result<Entity, MiniHubError> {
+        self.set_state(id, new_state, expected_state, None, Some(cause))
+            .await
+    }
+
+    async fn set_state(
+        &self,
+        id: EntityId,
+        new_state: EntityState,
+        expected_state: Option<EntityState>,
+        expected_version: Option<u32>,
+        cause: Option<&Event>,
+    ) -> Result<Entity, MiniHubError> {
+        let _guard = self.state_lock.lock().await;
         let mut entity = self.get_entity(id).await?;
+        if let Some(expected) = expected_version
+            && entity.version != expected
+        {
+            return Err(ConflictError {
+                entity: "Entity",
+                id: id.to_string(),
+                expected: format!("version {expected}"),
+                actual: format!("version {}", entity.version),
+            }
+            .into());
+        }
+        if let Some(expected) = expected_state
+            && entity.state != expected
+        {
+            return Err(ConflictError {
+                entity: "Entity",
+                id: id.to_string(),
+                expected: expected.to_string(),
+                actual: entity.state.to_string(),
+            }
+            .into());
+        }
+        entity.check_state(&new_state)?;
         let old_state = entity.state.clone();
         entity.update_state(new_state.clone(), now());
         let updated = self.repo.update(entity).await?;
 
         if old_state != new_state {
-            let event = Event::new(
+            let event = Event::with_payload(
                 EventType::StateChanged,
                 Some(id),
-                serde_json::json!({
-                    "old_state": old_state,
-                    "new_state": new_state,
-                }),
-            );
+                &StateChangedPayload {
+                    old_state,
+                    new_state,
+                },
+            )
+            .with_cause_opt(cause);
             let _ = self.publisher.publish(event).await;
         }
 
         Ok(updated)
     }
 
+    /// Request a service call on an existing entity.
+    ///
+    /// Validates `data` as a [`ServiceCall`], against the definition of the
+    /// service for the entity's domain and the entity's attribute metadata,
+    /// and publishes a [`EventType::ServiceCallRequested`] event; the owning
+    /// integration actuates the device asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] if the entity does not exist,
+    /// [`MiniHubError::Validation`] if `data` does not match the service
+    /// definition or is out of range, or an error from the repository or the
+    /// event publisher.
+    #[tracing::instrument(skip(self, data))]
+    pub async fn call_service(
+        &self,
+        id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+    ) -> Result<(), MiniHubError> {
+        self.request_service(id, service, data, None).await
+    }
+
+    /// Like [`Self::call_service`], recording the request as a consequence
+    /// of `cause`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::call_service`].
+    #[tracing::instrument(skip(self, data, cause), fields(cause = %cause.id))]
+    pub async fn call_service_caused_by(
+        &self,
+        id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+        cause: &Event,
+    ) -> Result<(), MiniHubError> {
+        self.request_service(id, service, data, Some(cause)).await
+    }
+
+    async fn request_service(
+        &self,
+        id: EntityId,
+        service: &str,
+        data: serde_json::Value,
+        cause: Option<&Event>,
+    ) -> Result<(), MiniHubError> {
+        let entity = self.get_entity(id).await?;
+        let call = ServiceCall::new(&entity, service, data)?;
+        let event = call.requested_event().with_cause_opt(cause);
+        self.publisher.publish(event).await
+    }
+
+    /// The publisher of entity events, for events that are not about an
+    /// entity but are stored and streamed like them.
+    pub fn publisher(&self) -> &P {
+        &self.publisher
+    }
+
     /// Create or update an entity by its string `entity_id`.
     ///
     /// If an entity with the same `entity_id` already exists, its state and
     /// attributes are updated (preserving the original UUID). Otherwise a new
     /// entity is created. Publishes [`EventType::StateChanged`] when the state
-    /// differs, or [`EventType::AttributeChanged`] when only attributes differ.
+    /// differs, or [`EventType::AttributeChanged`] listing the new values of
+    /// the `changed` attributes when only attributes differ.
     ///
     /// # Errors
     ///
@@ -126,40 +365,116 @@ impl<R: EntityRepository, P: EventPublisher> EntityService<R, P> {
     /// storage error propagated from the repository.
     #[tracing::instrument(skip(self, entity), fields(entity_id = %entity.entity_id))]
     pub async fn upsert_entity(&self, entity: Entity) -> Result<Entity, MiniHubError> {
-        if let Some(existing) = self.repo.find_by_entity_id(&entity.entity_id).await? {
-            let mut updated = existing;
-            let old_state = updated.state.clone();
-            let old_attributes = updated.attributes.clone();
-            updated.state.clone_from(&entity.state);
-            updated.attributes.clone_from(&entity.attributes);
-            updated.mac_address.clone_from(&entity.mac_address);
-            updated.last_updated = now();
-            if old_state != entity.state {
-                updated.last_changed = now();
-            }
-            let saved = self.repo.update(updated).await?;
-            if old_state != entity.state {
-                let event = Event::new(
-                    EventType::StateChanged,
-                    Some(saved.id),
-                    serde_json::json!({
-                        "old_state": old_state,
-                        "new_state": entity.state,
-                    }),
-                );
-                let _ = self.publisher.publish(event).await;
-            } else if old_attributes != entity.attributes {
-                let event = Event::new(
-                    EventType::AttributeChanged,
-                    Some(saved.id),
-                    serde_json::json!({}),
-                );
-                let _ = self.publisher.publish(event).await;
-            }
-            Ok(saved)
+        self.upsert(entity, None).await
+    }
+
+    /// Like [`Self::upsert_entity`], recording the published event as a
+    /// consequence of `cause`, e.g. the service call that led the
+    /// integration to report the new state.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::upsert_entity`].
+    #[tracing::instrument(
+        skip(self, entity, cause),
+        fields(entity_id = %entity.entity_id, cause = %cause.id)
+    )]
+    pub async fn upsert_entity_caused_by(
+        &self,
+        entity: Entity,
+        cause: &Event,
+    ) -> Result<Entity, MiniHubError> {
+        self.upsert(entity, Some(cause)).await
+    }
+
+    async fn upsert(&self, entity: Entity, cause: Option<&Event>) -> Result<Entity, MiniHubError> {
+        let _guard = self.state_lock.lock().await;
+        let pending = self.prepare_upsert(entity).await?;
+        let saved = if pending.exists {
+            self.repo.update(pending.entity).await?
         } else {
-            self.create_entity(entity).await
+            self.repo.create(pending.entity).await?
+        };
+        if let Some(event) = pending.event {
+            let _ = self.publisher.publish(event.with_cause_opt(cause)).await;
         }
+        Ok(saved)
+    }
+
+    /// Hold off the state updates, renames and upserts of this service,
+    /// e.g. while writing what [`Self::prepare_upsert`] computed.
+    pub(crate) async fn lock_state(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.state_lock.lock().await
+    }
+
+    /// Compute what [`Self::upsert_entity`] would write and publish, without
+    /// writing it, so callers can store it along with other rows. Callers
+    /// hold [`Self::lock_state`] until it is written.
+    pub(crate) async fn prepare_upsert(
+        &self,
+        entity: Entity,
+    ) -> Result<PendingUpsert, MiniHubError> {
+        let Some(mut updated) = self.find_by_entity_id(&entity.entity_id).await? else {
+            let mut created = entity;
+            created.validate()?;
+            let ts = now();
+            created.last_updated = ts;
+            created.last_changed = ts;
+            let event = Event::with_payload(
+                EventType::EntityCreated,
+                Some(created.id),
+                &EntityCreatedPayload {
+                    entity_id: created.entity_id.clone(),
+                },
+            );
+            return Ok(PendingUpsert {
+                entity: created,
+                exists: false,
+                event: Some(event),
+            });
+        };
+
+        let old_state = updated.state.clone();
+        let old_attributes = updated.attributes.clone();
+        updated.state.clone_from(&entity.state);
+        updated.device_class = entity.device_class;
+        updated
+            .unit_of_measurement
+            .clone_from(&entity.unit_of_measurement);
+        updated.attributes.clone_from(&entity.attributes);
+        updated.attribute_meta.clone_from(&entity.attribute_meta);
+        updated.mac_address.clone_from(&entity.mac_address);
+        updated.last_updated = now();
+        let event = if old_state != entity.state {
+            updated.last_changed = now();
+            Some(Event::with_payload(
+                EventType::StateChanged,
+                Some(updated.id),
+                &StateChangedPayload {
+                    old_state,
+                    new_state: entity.state.clone(),
+                },
+            ))
+        } else if old_attributes != entity.attributes {
+            let changed = entity
+                .attributes
+                .iter()
+                .filter(|(key, value)| old_attributes.get(*key) != Some(*value))
+                .map(|(key, value)| (key.clone(), serde_json::json!(value)))
+                .collect();
+            Some(Event::with_payload(
+                EventType::AttributeChanged,
+                Some(updated.id),
+                &AttributeChangedPayload { changed },
+            ))
+        } else {
+            None
+        };
+        Ok(PendingUpsert {
+            entity: updated,
+            exists: true,
+            event,
+        })
     }
 
     /// Delete an entity by id.
@@ -182,21 +497,21 @@ impl<R: EntityRepository, P: EventPublisher> EntityService<R, P> {
 mod tests {
     use super::*;
     use minihub_domain::entity::EntityState;
-    use minihub_domain::error::ValidationError;
     use minihub_domain::event::Event;
-    use minihub_domain::id::DeviceId;
     use std::collections::HashMap;
     use std::future::Future;
     use std::sync::Mutex;
 
     struct InMemoryEntityRepo {
         store: Mutex<HashMap<EntityId, Entity>>,
+        aliases: Mutex<HashMap<String, EntityId>>,
     }
 
     impl Default for InMemoryEntityRepo {
         fn default() -> Self {
             Self {
                 store: Mutex::new(HashMap::new()),
+                aliases: Mutex::new(HashMap::new()),
             }
         }
     }
@@ -217,6 +532,20 @@ mod tests {
         ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
             let store = self.store.lock().unwrap();
             let result = store.get(&id).cloned();
+            // Yield after the read so concurrent read-modify-writes can
+            // interleave as they would against a real database.
+            async {
+                tokio::task::yield_now().await;
+                Ok(result)
+            }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
             async { Ok(result) }
         }
 
@@ -248,16 +577,37 @@ mod tests {
                 .values()
                 .find(|ent| ent.entity_id == entity_id)
                 .cloned();
-            async { Ok(result) }
+            async {
+                tokio::task::yield_now().await;
+                Ok(result)
+            }
         }
 
         fn update(
             &self,
-            entity: Entity,
+            mut entity: Entity,
         ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
             let mut store = self.store.lock().unwrap();
-            store.insert(entity.id, entity.clone());
-            async { Ok(entity) }
+            let result = match store.get(&entity.id) {
+                Some(stored) if stored.version == entity.version => {
+                    entity.version += 1;
+                    store.insert(entity.id, entity.clone());
+                    Ok(entity)
+                }
+                Some(stored) => Err(ConflictError {
+                    entity: "Entity",
+                    id: entity.id.to_string(),
+                    expected: format!("version {}", entity.version),
+                    actual: format!("version {}", stored.version),
+                }
+                .into()),
+                None => Err(NotFoundError {
+                    entity: "Entity",
+                    id: entity.id.to_string(),
+                }
+                .into()),
+            };
+            async { result }
         }
 
         fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
@@ -265,6 +615,25 @@ mod tests {
             store.remove(&id);
             async { Ok(()) }
         }
+
+        fn find_by_alias(
+            &self,
+            alias: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let id = self.aliases.lock().unwrap().get(alias).copied();
+            let store = self.store.lock().unwrap();
+            let result = id.and_then(|id| store.get(&id).cloned());
+            async { Ok(result) }
+        }
+
+        fn add_alias(
+            &self,
+            id: EntityId,
+            alias: &str,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.aliases.lock().unwrap().insert(alias.to_string(), id);
+            async { Ok(()) }
+        }
     }
 
     struct SpyPublisher {
@@ -353,7 +722,10 @@ mod tests {
         let id = entity.id;
         svc.create_entity(entity).await.unwrap();
 
-        let updated = svc.update_entity_state(id, EntityState::On).await.unwrap();
+        let updated = svc
+            .update_entity_state(id, EntityState::On, None)
+            .await
+            .unwrap();
         assert_eq!(updated.state, EntityState::On);
 
         let fetched = svc.get_entity(id).await.unwrap();
@@ -364,7 +736,157 @@ mod tests {
     async fn should_return_not_found_when_updating_missing_entity() {
         let svc = make_service();
         let result = svc
-            .update_entity_state(EntityId::new(), EntityState::On)
+            .update_entity_state(EntityId::new(), EntityState::On, None)
+            .await;
+
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_update_entity_state_when_expected_state_matches() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+
+        let updated = svc
+            .update_entity_state(id, EntityState::On, Some(EntityState::Off))
+            .await
+            .unwrap();
+
+        assert_eq!(updated.state, EntityState::On);
+    }
+
+    #[tokio::test]
+    async fn should_bump_version_when_entity_state_is_updated() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+
+        let updated = svc
+            .update_entity_state_at_version(id, EntityState::On, None, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(updated.version, 2);
+    }
+
+    #[tokio::test]
+    async fn should_return_conflict_when_entity_version_is_stale() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+        svc.update_entity_state(id, EntityState::On, None)
+            .await
+            .unwrap();
+
+        let result = svc
+            .update_entity_state_at_version(id, EntityState::Off, None, 1)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Conflict(ConflictError { ref actual, .. })) if actual == "version 2"
+        ));
+        assert_eq!(svc.get_entity(id).await.unwrap().state, EntityState::On);
+    }
+
+    #[tokio::test]
+    async fn should_apply_reported_state_racing_a_state_update() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+        let mut report = valid_entity();
+        report.state = EntityState::Unavailable;
+
+        let (set, reported) = tokio::join!(
+            svc.update_entity_state(id, EntityState::On, None),
+            svc.upsert_entity(report),
+        );
+
+        assert!(set.is_ok());
+        assert_eq!(reported.unwrap().state, EntityState::Unavailable);
+        assert_eq!(svc.get_entity(id).await.unwrap().version, 3);
+    }
+
+    #[tokio::test]
+    async fn should_return_conflict_when_expected_state_differs() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+
+        let result = svc
+            .update_entity_state(id, EntityState::Off, Some(EntityState::On))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Conflict(ConflictError { ref actual, .. })) if actual == "off"
+        ));
+        let fetched = svc.get_entity(id).await.unwrap();
+        assert_eq!(fetched.state, EntityState::Off);
+        let events = svc.publisher.events.lock().unwrap();
+        assert!(
+            events
+                .iter()
+                .all(|evt| evt.event_type != EventType::StateChanged)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_publish_service_call_requested_when_calling_service() {
+        let svc = make_service();
+        let entity = valid_entity();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+
+        svc.call_service(id, "turn_on", serde_json::json!({}))
+            .await
+            .unwrap();
+
+        let events = svc.publisher.events.lock().unwrap();
+        let calls: Vec<_> = events
+            .iter()
+            .filter(|evt| evt.event_type == EventType::ServiceCallRequested)
+            .collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].entity_id, Some(id));
+        assert_eq!(calls[0].data["service"], "turn_on");
+    }
+
+    #[tokio::test]
+    async fn should_reject_service_call_when_data_does_not_match_definition() {
+        let svc = make_service();
+        let entity = Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .build()
+            .unwrap();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+        svc.publisher.events.lock().unwrap().clear();
+
+        let result = svc
+            .call_service(id, "turn_on", serde_json::json!({ "brightness": "max" }))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::InvalidServiceData { ref errors, .. }))
+                if errors[0].parameter == "brightness"
+        ));
+        assert!(svc.publisher.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_calling_service_on_missing_entity() {
+        let svc = make_service();
+        let result = svc
+            .call_service(EntityId::new(), "turn_on", serde_json::json!({}))
             .await;
 
         assert!(matches!(result, Err(MiniHubError::NotFound(_))));
@@ -390,7 +912,9 @@ mod tests {
         let id = entity.id;
         svc.create_entity(entity).await.unwrap();
 
-        svc.update_entity_state(id, EntityState::On).await.unwrap();
+        svc.update_entity_state(id, EntityState::On, None)
+            .await
+            .unwrap();
 
         let events = svc.publisher.events.lock().unwrap();
         let state_events: Vec<_> = events
@@ -410,7 +934,9 @@ mod tests {
         let id = entity.id;
         svc.create_entity(entity).await.unwrap();
 
-        svc.update_entity_state(id, EntityState::Off).await.unwrap();
+        svc.update_entity_state(id, EntityState::Off, None)
+            .await
+            .unwrap();
 
         let events = svc.publisher.events.lock().unwrap();
         let state_events: Vec<_> = events
@@ -420,6 +946,30 @@ mod tests {
         assert_eq!(state_events.len(), 0);
     }
 
+    #[tokio::test]
+    async fn should_reject_state_when_domain_does_not_allow_it() {
+        let svc = make_service();
+        let entity = Entity::builder()
+            .entity_id("input_number.target")
+            .friendly_name("Target")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        let id = entity.id;
+        svc.create_entity(entity).await.unwrap();
+
+        let err = svc
+            .update_entity_state(id, EntityState::Off, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidStateForDomain { .. })
+        ));
+        assert_eq!(svc.get_entity(id).await.unwrap().state, EntityState::On);
+    }
+
     #[tokio::test]
     async fn should_publish_entity_created_event_on_create() {
         let svc = make_service();
@@ -478,6 +1028,27 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn should_upsert_device_class_and_unit_set_by_integration() {
+        let svc = make_service();
+        svc.create_entity(valid_entity()).await.unwrap();
+
+        let updated = Entity::builder()
+            .entity_id("light.living_room")
+            .friendly_name("Living Room Light")
+            .device_class(minihub_domain::entity::DeviceClass::Illuminance)
+            .unit_of_measurement("lx")
+            .build()
+            .unwrap();
+
+        let result = svc.upsert_entity(updated).await.unwrap();
+        assert_eq!(
+            result.device_class,
+            Some(minihub_domain::entity::DeviceClass::Illuminance)
+        );
+        assert_eq!(result.unit_of_measurement.as_deref(), Some("lx"));
+    }
+
     #[tokio::test]
     async fn should_publish_state_changed_on_upsert_when_state_differs() {
         let svc = make_service();
@@ -503,6 +1074,34 @@ mod tests {
         assert_eq!(state_events[0].entity_id, Some(original_id));
     }
 
+    #[tokio::test]
+    async fn should_link_state_change_to_cause_when_upserted_with_cause() {
+        let svc = make_service();
+        let entity = valid_entity();
+        svc.create_entity(entity).await.unwrap();
+        let cause = Event::new(
+            EventType::ServiceCallRequested,
+            None,
+            serde_json::json!({ "service": "turn_on" }),
+        );
+
+        let updated = Entity::builder()
+            .entity_id("light.living_room")
+            .friendly_name("Living Room Light")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        svc.upsert_entity_caused_by(updated, &cause).await.unwrap();
+
+        let events = svc.publisher.events.lock().unwrap();
+        let changed = events
+            .iter()
+            .find(|evt| evt.event_type == EventType::StateChanged)
+            .unwrap();
+        assert_eq!(changed.caused_by, Some(cause.id));
+        assert_eq!(changed.correlation_id, cause.correlation_id);
+    }
+
     #[tokio::test]
     async fn should_not_publish_state_changed_on_upsert_when_state_same() {
         let svc = make_service();
@@ -553,6 +1152,10 @@ mod tests {
             .collect();
         assert_eq!(attr_events.len(), 1);
         assert_eq!(attr_events[0].entity_id, Some(original_id));
+        assert_eq!(
+            attr_events[0].data,
+            serde_json::json!({ "changed": { "temperature": 23.1 } })
+        );
     }
 
     #[tokio::test]
@@ -599,4 +1202,77 @@ mod tests {
         assert_eq!(removed_events.len(), 1);
         assert_eq!(removed_events[0].entity_id, Some(id));
     }
+
+    #[tokio::test]
+    async fn should_rename_entity_and_resolve_old_id_as_alias() {
+        let svc = make_service();
+        let created = svc.create_entity(valid_entity()).await.unwrap();
+
+        let renamed = svc.rename_entity(created.id, "light.lounge").await.unwrap();
+
+        assert_eq!(renamed.entity_id, "light.lounge");
+        let found = svc.find_by_entity_id("light.living_room").await.unwrap();
+        assert_eq!(found.unwrap().id, created.id);
+        let events = svc.publisher.events.lock().unwrap();
+        let event = events.last().unwrap();
+        assert_eq!(event.event_type, EventType::EntityRenamed);
+        assert_eq!(event.data["old_entity_id"], "light.living_room");
+        assert_eq!(event.data["new_entity_id"], "light.lounge");
+    }
+
+    #[tokio::test]
+    async fn should_upsert_renamed_entity_when_integration_uses_old_id() {
+        let svc = make_service();
+        let created = svc.create_entity(valid_entity()).await.unwrap();
+        svc.rename_entity(created.id, "light.lounge").await.unwrap();
+
+        let mut reported = valid_entity();
+        reported.state = EntityState::On;
+        let upserted = svc.upsert_entity(reported).await.unwrap();
+
+        assert_eq!(upserted.id, created.id);
+        assert_eq!(upserted.entity_id, "light.lounge");
+        assert_eq!(upserted.state, EntityState::On);
+        assert_eq!(svc.list_entities().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_reject_rename_when_entity_id_is_taken() {
+        let svc = make_service();
+        let first = svc.create_entity(valid_entity()).await.unwrap();
+        let mut other = valid_entity();
+        other.id = EntityId::new();
+        other.entity_id = "light.kitchen".to_string();
+        let second = svc.create_entity(other).await.unwrap();
+        svc.rename_entity(first.id, "light.lounge").await.unwrap();
+
+        for (id, taken) in [
+            (first.id, "light.kitchen"),
+            (second.id, "light.living_room"),
+        ] {
+            let result = svc.rename_entity(id, taken).await;
+            assert!(matches!(
+                result,
+                Err(MiniHubError::Validation(
+                    ValidationError::DuplicateEntityId(_)
+                ))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_allow_renaming_back_to_former_entity_id() {
+        let svc = make_service();
+        let created = svc.create_entity(valid_entity()).await.unwrap();
+        svc.rename_entity(created.id, "light.lounge").await.unwrap();
+
+        let renamed = svc
+            .rename_entity(created.id, "light.living_room")
+            .await
+            .unwrap();
+
+        assert_eq!(renamed.entity_id, "light.living_room");
+        let found = svc.find_by_entity_id("light.lounge").await.unwrap();
+        assert_eq!(found.unwrap().id, created.id);
+    }
 }
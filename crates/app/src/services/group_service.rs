@@ -0,0 +1,774 @@
+//! Group service — manages entity groups and keeps their entity in sync.
+//!
+//! Group entities are attached to the built-in hub device. The service
+//! listens to the bus: a [`EventType::StateChanged`] of a member recomputes
+//! the group state, and a [`EventType::ServiceCallRequested`] targeting the
+//! group entity is requested again on every member.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use minihub_domain::entity::{Entity, EntityState};
+use minihub_domain::error::{MiniHubError, NotFoundError, ValidationError};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload};
+use minihub_domain::group::Group;
+use minihub_domain::id::{EntityId, GroupId};
+
+use crate::event_bus::EventFilter;
+use crate::ports::{
+    DeviceRepository, EntityRepository, EventPublisher, EventSubscription, GroupRepository,
+};
+use crate::services::device_service::DeviceService;
+use crate::services::entity_service::EntityService;
+
+/// Application service for entity groups.
+pub struct GroupService<GR, DR, ER, EP> {
+    repo: GR,
+    device_service: Arc<DeviceService<DR>>,
+    entity_service: Arc<EntityService<ER, EP>>,
+}
+
+impl<GR, DR, ER, EP> GroupService<GR, DR, ER, EP>
+where
+    GR: GroupRepository,
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+{
+    /// Create a new service on top of the shared device and entity services.
+    pub fn new(
+        repo: GR,
+        device_service: Arc<DeviceService<DR>>,
+        entity_service: Arc<EntityService<ER, EP>>,
+    ) -> Self {
+        Self {
+            repo,
+            device_service,
+            entity_service,
+        }
+    }
+
+    /// Create a group and its entity, in the state aggregated from the
+    /// members.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, if a member
+    /// does not exist or is not of the group kind, or if an entity already
+    /// uses the derived `entity_id`, or a storage error from the
+    /// repositories.
+    #[tracing::instrument(skip(self, group), fields(group_name = %group.name))]
+    pub async fn create_group(&self, group: Group) -> Result<Group, MiniHubError> {
+        group.validate()?;
+        let entity_id = group.entity_id_string()?;
+        if self
+            .entity_service
+            .find_by_entity_id(&entity_id)
+            .await?
+            .is_some()
+        {
+            return Err(ValidationError::DuplicateEntityId(entity_id).into());
+        }
+        let members = self.members(&group).await?;
+        let hub = self.device_service.ensure_hub_device().await?;
+        let entity = group.to_entity(hub.id, aggregate(&members))?;
+        self.entity_service.create_entity(entity).await?;
+        self.repo.create(group).await
+    }
+
+    /// Look up a group by id, returning an error if not found.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no group with `id` exists,
+    /// or a storage error from the repository.
+    pub async fn get_group(&self, id: GroupId) -> Result<Group, MiniHubError> {
+        self.repo.get_by_id(id).await?.ok_or_else(|| {
+            NotFoundError {
+                entity: "Group",
+                id: id.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// List all groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list_groups(&self) -> Result<Vec<Group>, MiniHubError> {
+        self.repo.get_all().await
+    }
+
+    /// Replace the name and members of an existing group, and refresh the
+    /// members and state of its entity.
+    ///
+    /// The group keeps its kind and its entity: the entity's `entity_id`
+    /// and friendly name are left as they are, since they may have been
+    /// renamed on their own.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when the group does not exist,
+    /// [`MiniHubError::Validation`] if invariants fail, if a member does not
+    /// exist or is not of the group kind, or if the group would end up
+    /// containing itself through another group, or a storage error from the
+    /// repositories.
+    #[tracing::instrument(skip(self, group), fields(group_id = %group.id))]
+    pub async fn update_group(&self, mut group: Group) -> Result<Group, MiniHubError> {
+        let existing = self.get_group(group.id).await?;
+        group.kind = existing.kind;
+        group.entity_id = existing.entity_id;
+        group.validate()?;
+        self.check_cycles(&group).await?;
+        let members = self.members(&group).await?;
+
+        let current = self.entity_service.get_entity(group.entity_id).await?;
+        let mut entity = group.to_entity(current.device_id, aggregate(&members))?;
+        entity.entity_id = current.entity_id;
+        self.entity_service.upsert_entity(entity).await?;
+        self.repo.update(group).await
+    }
+
+    /// Delete a group and its entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when the group does not exist, or
+    /// a storage error from the repositories.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_group(&self, id: GroupId) -> Result<(), MiniHubError> {
+        let group = self.get_group(id).await?;
+        self.repo.delete(id).await?;
+        self.entity_service.delete_entity(group.entity_id).await
+    }
+
+    /// Recompute the state of every group, e.g. at startup after members
+    /// changed while the hub was down.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    pub async fn prime(&self) -> Result<(), MiniHubError> {
+        for group in self.repo.get_all().await? {
+            self.refresh(&group, None).await?;
+        }
+        Ok(())
+    }
+
+    /// The events [`Self::process_event`] may act on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all()
+            .with_event_types([EventType::StateChanged, EventType::ServiceCallRequested])
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Runs until the bus is closed. Failures are logged and do not stop the
+    /// loop.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Err(err) = self.process_event(&event).await {
+                        tracing::warn!(%err, event_id = %event.id, "failed to update groups");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "group service lagged, some events were dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        tracing::debug!("group service stopped");
+    }
+
+    /// Refresh the groups a state change affects, or fan out a service call
+    /// targeting a group entity to its members.
+    ///
+    /// A `toggle` is fanned out as `turn_off` when the group is on and
+    /// `turn_on` otherwise, so the members end up in the same state.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self, event), fields(event_id = %event.id))]
+    pub async fn process_event(&self, event: &Event) -> Result<(), MiniHubError> {
+        let Some(entity_id) = event.entity_id else {
+            return Ok(());
+        };
+        match event.event_type {
+            EventType::StateChanged => {
+                for group in self.repo.get_all().await? {
+                    if group.members.contains(&entity_id) {
+                        self.refresh(&group, Some(event)).await?;
+                    }
+                }
+            }
+            EventType::ServiceCallRequested => {
+                let groups = self.repo.get_all().await?;
+                let Some(group) = groups.iter().find(|group| group.entity_id == entity_id) else {
+                    return Ok(());
+                };
+                let Ok(ServiceCallPayload { service, data }) = event.payload() else {
+                    return Ok(());
+                };
+                let mut service = service.as_str();
+                if service == "toggle" {
+                    let entity = self.entity_service.get_entity(group.entity_id).await?;
+                    service = if entity.state == EntityState::On {
+                        "turn_off"
+                    } else {
+                        "turn_on"
+                    };
+                }
+                for member in &group.members {
+                    if let Err(err) = self
+                        .entity_service
+                        .call_service_caused_by(*member, service, data.clone(), event)
+                        .await
+                    {
+                        tracing::warn!(%err, %member, service, "failed to forward group service call");
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Update the state of the group entity from its members, publishing
+    /// a state change when it differs, as a consequence of `cause` if any.
+    async fn refresh(&self, group: &Group, cause: Option<&Event>) -> Result<(), MiniHubError> {
+        let mut members = Vec::with_capacity(group.members.len());
+        for member in &group.members {
+            match self.entity_service.get_entity(*member).await {
+                Ok(entity) => members.push(entity),
+                Err(MiniHubError::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let state = aggregate(&members);
+        let current = self.entity_service.get_entity(group.entity_id).await?;
+        if current.state != state {
+            match cause {
+                Some(cause) => {
+                    self.entity_service
+                        .update_entity_state_caused_by(group.entity_id, state, None, cause)
+                        .await?;
+                }
+                None => {
+                    self.entity_service
+                        .update_entity_state(group.entity_id, state, None)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the members of `group`, checking they exist and match its kind.
+    async fn members(&self, group: &Group) -> Result<Vec<Entity>, MiniHubError> {
+        let mut members = Vec::with_capacity(group.members.len());
+        for member in &group.members {
+            let entity = match self.entity_service.get_entity(*member).await {
+                Ok(entity) => entity,
+                Err(MiniHubError::NotFound(_)) => {
+                    return Err(ValidationError::InvalidGroupMember(format!(
+                        "entity {member} does not exist"
+                    ))
+                    .into());
+                }
+                Err(err) => return Err(err),
+            };
+            if !group.kind.accepts(&entity.entity_id) {
+                return Err(ValidationError::InvalidGroupMember(format!(
+                    "{} is not a {}",
+                    entity.entity_id,
+                    group.kind.domain()
+                ))
+                .into());
+            }
+            members.push(entity);
+        }
+        Ok(members)
+    }
+
+    /// Reject members reaching back to `group` through nested groups, which
+    /// would fan service calls out forever.
+    async fn check_cycles(&self, group: &Group) -> Result<(), MiniHubError> {
+        let nested: HashMap<EntityId, Vec<EntityId>> = self
+            .repo
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|other| other.id != group.id)
+            .map(|other| (other.entity_id, other.members))
+            .collect();
+        let mut seen = HashSet::new();
+        let mut pending = group.members.clone();
+        while let Some(member) = pending.pop() {
+            if member == group.entity_id {
+                return Err(ValidationError::InvalidGroupMember(
+                    "a group cannot contain itself".to_string(),
+                )
+                .into());
+            }
+            if seen.insert(member)
+                && let Some(members) = nested.get(&member)
+            {
+                pending.extend(members);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn aggregate(members: &[Entity]) -> EntityState {
+    Group::aggregate_state(members.iter().map(|entity| &entity.state))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::device::Device;
+    use minihub_domain::group::GroupKind;
+    use minihub_domain::id::{AreaId, DeviceId};
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryGroupRepo {
+        store: Mutex<HashMap<GroupId, Group>>,
+    }
+
+    impl GroupRepository for InMemoryGroupRepo {
+        fn create(&self, group: Group) -> impl Future<Output = Result<Group, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(group.id, group.clone());
+            async { Ok(group) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: GroupId,
+        ) -> impl Future<Output = Result<Option<Group>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Group>, MiniHubError>> + Send {
+            let result: Vec<Group> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn update(&self, group: Group) -> impl Future<Output = Result<Group, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(group.id, group.clone());
+            async { Ok(group) }
+        }
+
+        fn delete(&self, id: GroupId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        fn create(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: EntityId,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|ent| ent.device_id == device_id)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_entity_id(
+            &self,
+            entity_id: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|ent| ent.entity_id == entity_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        fn create(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: DeviceId,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result: Vec<Device> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Service =
+        GroupService<InMemoryGroupRepo, InMemoryDeviceRepo, InMemoryEntityRepo, Arc<SpyPublisher>>;
+
+    fn setup() -> (Service, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = GroupService::new(
+            InMemoryGroupRepo::default(),
+            Arc::new(DeviceService::new(InMemoryDeviceRepo::default())),
+            Arc::new(EntityService::new(
+                InMemoryEntityRepo::default(),
+                Arc::clone(&publisher),
+            )),
+        );
+        (svc, publisher)
+    }
+
+    async fn light(svc: &Service, entity_id: &str, state: EntityState) -> EntityId {
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id(entity_id)
+            .friendly_name(entity_id)
+            .state(state)
+            .build()
+            .unwrap();
+        svc.entity_service.create_entity(entity).await.unwrap().id
+    }
+
+    async fn living_room(svc: &Service) -> (Group, EntityId, EntityId) {
+        let ceiling = light(svc, "light.ceiling", EntityState::Off).await;
+        let lamp = light(svc, "light.lamp", EntityState::Off).await;
+        let group = Group::builder()
+            .name("Living room")
+            .kind(GroupKind::Light)
+            .member(ceiling)
+            .member(lamp)
+            .build()
+            .unwrap();
+        let group = svc.create_group(group).await.unwrap();
+        (group, ceiling, lamp)
+    }
+
+    fn state_changed(entity_id: EntityId) -> Event {
+        Event::new(EventType::StateChanged, Some(entity_id), json!({}))
+    }
+
+    fn service_calls(publisher: &SpyPublisher) -> Vec<(EntityId, String)> {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::ServiceCallRequested)
+            .map(|event| {
+                (
+                    event.entity_id.unwrap(),
+                    event.data["service"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_create_group_entity_on_hub_device() {
+        let (svc, _) = setup();
+
+        let (group, _, _) = living_room(&svc).await;
+
+        let entity = svc
+            .entity_service
+            .get_entity(group.entity_id)
+            .await
+            .unwrap();
+        assert_eq!(entity.entity_id, "light.living_room");
+        assert_eq!(entity.state, EntityState::Off);
+        let hub = svc.device_service.ensure_hub_device().await.unwrap();
+        assert_eq!(entity.device_id, hub.id);
+    }
+
+    #[tokio::test]
+    async fn should_reject_member_of_another_kind() {
+        let (svc, _) = setup();
+        let plug = light(&svc, "switch.plug", EntityState::Off).await;
+        let group = Group::builder()
+            .name("Lights")
+            .kind(GroupKind::Light)
+            .member(plug)
+            .build()
+            .unwrap();
+
+        let err = svc.create_group(group).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidGroupMember(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reject_unknown_member() {
+        let (svc, _) = setup();
+        let group = Group::builder()
+            .name("Lights")
+            .member(EntityId::new())
+            .build()
+            .unwrap();
+
+        let err = svc.create_group(group).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidGroupMember(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_turn_group_on_when_a_member_turns_on() {
+        let (svc, _) = setup();
+        let (group, ceiling, _) = living_room(&svc).await;
+
+        svc.entity_service
+            .update_entity_state(ceiling, EntityState::On, None)
+            .await
+            .unwrap();
+        svc.process_event(&state_changed(ceiling)).await.unwrap();
+
+        let entity = svc
+            .entity_service
+            .get_entity(group.entity_id)
+            .await
+            .unwrap();
+        assert_eq!(entity.state, EntityState::On);
+    }
+
+    #[tokio::test]
+    async fn should_fan_out_service_call_to_members() {
+        let (svc, publisher) = setup();
+        let (group, ceiling, lamp) = living_room(&svc).await;
+        let call = Event::new(
+            EventType::ServiceCallRequested,
+            Some(group.entity_id),
+            json!({ "service": "turn_on", "data": {} }),
+        );
+
+        svc.process_event(&call).await.unwrap();
+
+        let calls = service_calls(&publisher);
+        assert_eq!(calls.len(), 2);
+        assert!(calls.contains(&(ceiling, "turn_on".to_string())));
+        assert!(calls.contains(&(lamp, "turn_on".to_string())));
+    }
+
+    #[tokio::test]
+    async fn should_toggle_members_to_the_same_state() {
+        let (svc, publisher) = setup();
+        let (group, ceiling, _) = living_room(&svc).await;
+        svc.entity_service
+            .update_entity_state(ceiling, EntityState::On, None)
+            .await
+            .unwrap();
+        svc.process_event(&state_changed(ceiling)).await.unwrap();
+        let call = Event::new(
+            EventType::ServiceCallRequested,
+            Some(group.entity_id),
+            json!({ "service": "toggle", "data": {} }),
+        );
+
+        svc.process_event(&call).await.unwrap();
+
+        let calls = service_calls(&publisher);
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|(_, service)| service == "turn_off"));
+    }
+
+    #[tokio::test]
+    async fn should_reject_update_creating_a_cycle() {
+        let (svc, _) = setup();
+        let (inner, _, lamp) = living_room(&svc).await;
+        let outer = Group::builder()
+            .name("Downstairs")
+            .member(inner.entity_id)
+            .build()
+            .unwrap();
+        let outer = svc.create_group(outer).await.unwrap();
+
+        let mut looping = inner.clone();
+        looping.members = vec![lamp, outer.entity_id];
+        let err = svc.update_group(looping).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidGroupMember(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_delete_group_entity_with_group() {
+        let (svc, _) = setup();
+        let (group, _, _) = living_room(&svc).await;
+
+        svc.delete_group(group.id).await.unwrap();
+
+        assert!(svc.list_groups().await.unwrap().is_empty());
+        assert!(matches!(
+            svc.entity_service.get_entity(group.entity_id).await,
+            Err(MiniHubError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+//! Health service — liveness and readiness of the daemon's components.
+//!
+//! Liveness only tells that the process answers. Readiness checks every
+//! component the hub depends on and reports each one, so an orchestrator
+//! can stop routing traffic to a hub whose storage is unreachable while an
+//! operator can still see which integration is struggling.
+
+use std::sync::Arc;
+
+use crate::event_bus::InProcessEventBus;
+use crate::integration_manager::{IntegrationManager, IntegrationStatus};
+use crate::ports::Storage;
+
+/// Health of a component, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    /// Working as expected.
+    Up,
+    /// Working, but part of the hub is unavailable.
+    Degraded,
+    /// Not working; the hub cannot serve requests reliably.
+    Down,
+}
+
+impl HealthStatus {
+    /// Machine-readable name of the status, e.g. `"degraded"`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Degraded => "degraded",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// Outcome of checking one component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentHealth {
+    /// Component name, e.g. `"database"` or `"integration.mqtt"`.
+    pub name: String,
+    /// Component status.
+    pub status: HealthStatus,
+    /// Why the component is not up, or what was measured.
+    pub detail: Option<String>,
+}
+
+impl ComponentHealth {
+    fn new(name: impl Into<String>, status: HealthStatus, detail: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail,
+        }
+    }
+}
+
+/// Health of the daemon, as the worst status of its components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Overall status.
+    pub status: HealthStatus,
+    /// Every component checked, in a stable order.
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    fn from_components(components: Vec<ComponentHealth>) -> Self {
+        let status = components
+            .iter()
+            .map(|component| component.status)
+            .max()
+            .unwrap_or(HealthStatus::Up);
+        Self { status, components }
+    }
+
+    /// Whether the daemon can serve requests, i.e. no component is down.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.status != HealthStatus::Down
+    }
+}
+
+/// Application service checking the daemon's components.
+pub struct HealthService<S> {
+    storage: S,
+    event_bus: Arc<InProcessEventBus>,
+    integrations: IntegrationManager,
+}
+
+impl<S: Storage> HealthService<S> {
+    /// Create a service checking `storage` and `event_bus`.
+    pub fn new(storage: S, event_bus: Arc<InProcessEventBus>) -> Self {
+        Self {
+            storage,
+            event_bus,
+            integrations: IntegrationManager::default(),
+        }
+    }
+
+    /// Also report the status of every integration known to `manager`.
+    #[must_use]
+    pub fn with_integrations(mut self, manager: IntegrationManager) -> Self {
+        self.integrations = manager;
+        self
+    }
+
+    /// Liveness — the process is running and answering.
+    #[must_use]
+    pub fn live(&self) -> HealthReport {
+        HealthReport::from_components(Vec::new())
+    }
+
+    /// Readiness — every component the hub depends on, checked now.
+    ///
+    /// The storage and the event bus are required: the hub is down without
+    /// them. Integrations that are not running only degrade it.
+    pub async fn ready(&self) -> HealthReport {
+        let mut components = vec![self.check_storage().await, self.check_event_bus()];
+        components.extend(self.integrations.reports().into_iter().map(|report| {
+            let status = match report.status {
+                IntegrationStatus::Disabled | IntegrationStatus::Running => HealthStatus::Up,
+                _ => HealthStatus::Degraded,
+            };
+            let detail = (status != HealthStatus::Up).then(|| {
+                report
+                    .status
+                    .error()
+                    .map_or_else(|| report.status.as_str().to_string(), str::to_string)
+            });
+            ComponentHealth::new(format!("integration.{}", report.name), status, detail)
+        }));
+        HealthReport::from_components(components)
+    }
+
+    async fn check_storage(&self) -> ComponentHealth {
+        match self.storage.ping().await {
+            Ok(()) => ComponentHealth::new("database", HealthStatus::Up, None),
+            Err(err) => ComponentHealth::new("database", HealthStatus::Down, Some(err.to_string())),
+        }
+    }
+
+    /// The bus is down without subscribers: published events would reach
+    /// neither the event store nor the automation engine.
+    fn check_event_bus(&self) -> ComponentHealth {
+        let subscribers = self.event_bus.subscriber_count();
+        let status = if subscribers == 0 {
+            HealthStatus::Down
+        } else {
+            HealthStatus::Up
+        };
+        ComponentHealth::new(
+            "event_bus",
+            status,
+            Some(format!("{subscribers} subscribers")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minihub_domain::error::MiniHubError;
+
+    use super::*;
+
+    struct StubStorage(bool);
+
+    impl Storage for StubStorage {
+        async fn ping(&self) -> Result<(), MiniHubError> {
+            if self.0 {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("connection refused").into())
+            }
+        }
+    }
+
+    fn bus_with_subscriber() -> (Arc<InProcessEventBus>, impl Sized) {
+        let bus = Arc::new(InProcessEventBus::new(8));
+        let rx = bus.subscribe();
+        (bus, rx)
+    }
+
+    #[test]
+    fn should_be_live_without_checking_components() {
+        let service = HealthService::new(StubStorage(false), Arc::new(InProcessEventBus::new(8)));
+
+        let report = service.live();
+
+        assert_eq!(report.status, HealthStatus::Up);
+        assert!(report.components.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_be_ready_when_storage_and_bus_are_up() {
+        let (bus, _rx) = bus_with_subscriber();
+        let service = HealthService::new(StubStorage(true), bus);
+
+        let report = service.ready().await;
+
+        assert!(report.is_healthy());
+        assert_eq!(report.status, HealthStatus::Up);
+        assert_eq!(report.components[0].name, "database");
+        assert_eq!(
+            report.components[1].detail.as_deref(),
+            Some("1 subscribers")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_be_down_when_storage_ping_fails() {
+        let (bus, _rx) = bus_with_subscriber();
+        let service = HealthService::new(StubStorage(false), bus);
+
+        let report = service.ready().await;
+
+        assert!(!report.is_healthy());
+        assert_eq!(report.components[0].status, HealthStatus::Down);
+        assert_eq!(
+            report.components[0].detail.as_deref(),
+            Some("connection refused")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_be_down_when_bus_has_no_subscriber() {
+        let service = HealthService::new(StubStorage(true), Arc::new(InProcessEventBus::new(8)));
+
+        let report = service.ready().await;
+
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.components[1].status, HealthStatus::Down);
+    }
+
+    #[tokio::test]
+    async fn should_be_degraded_when_an_integration_failed() {
+        let (bus, _rx) = bus_with_subscriber();
+        let integrations = IntegrationManager::default();
+        integrations.register("mqtt", true);
+        integrations.set_status("mqtt", IntegrationStatus::Failed("broker down".to_string()));
+        integrations.register("ble", false);
+        let service = HealthService::new(StubStorage(true), bus).with_integrations(integrations);
+
+        let report = service.ready().await;
+
+        assert!(report.is_healthy());
+        assert_eq!(report.status, HealthStatus::Degraded);
+        let mqtt = report
+            .components
+            .iter()
+            .find(|component| component.name == "integration.mqtt")
+            .unwrap();
+        assert_eq!(mqtt.detail.as_deref(), Some("broker down"));
+        let ble = report
+            .components
+            .iter()
+            .find(|component| component.name == "integration.ble")
+            .unwrap();
+        assert_eq!(ble.status, HealthStatus::Up);
+    }
+}
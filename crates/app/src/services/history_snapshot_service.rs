@@ -0,0 +1,211 @@
+//! History snapshot service — periodically records every entity into history.
+//!
+//! History is otherwise only recorded when an entity changes, so a sensor
+//! holding the same value, or changes dropped by a lagging subscriber, leave
+//! gaps in time-series queries. Snapshots add a sample of every entity at a
+//! regular interval.
+
+use std::time::Duration;
+
+use minihub_domain::entity_history::EntityHistory;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::time;
+
+use crate::ports::{EntityHistoryRepository, EntityRepository};
+
+/// Application service recording the current state of every entity into
+/// history at a fixed interval.
+pub struct HistorySnapshotService<ER, HR> {
+    entity_repo: ER,
+    history_repo: HR,
+    interval: Duration,
+}
+
+impl<ER, HR> HistorySnapshotService<ER, HR>
+where
+    ER: EntityRepository,
+    HR: EntityHistoryRepository,
+{
+    /// Create a service reading entities from `entity_repo` and recording
+    /// a snapshot to `history_repo` every `interval`.
+    pub fn new(entity_repo: ER, history_repo: HR, interval: Duration) -> Self {
+        Self {
+            entity_repo,
+            history_repo,
+            interval,
+        }
+    }
+
+    /// Record the current state and attributes of every entity, all with
+    /// the same timestamp, returning how many were recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error when the entities cannot be listed. Failing
+    /// to record one entity is logged and does not stop the others.
+    pub async fn snapshot(&self) -> Result<usize, MiniHubError> {
+        let recorded_at = time::now();
+        let mut count = 0;
+        for entity in self.entity_repo.get_all().await? {
+            let entity_id = entity.id;
+            let history = EntityHistory::builder()
+                .entity_id(entity_id)
+                .state(entity.state)
+                .attributes(entity.attributes)
+                .recorded_at(recorded_at)
+                .build();
+            match self.history_repo.record(history).await {
+                Ok(_) => count += 1,
+                Err(err) => {
+                    tracing::warn!(%err, %entity_id, "failed to record entity snapshot");
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Take a snapshot every interval, forever. Failures are logged and do
+    /// not stop the loop.
+    pub async fn run(&self) {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match self.snapshot().await {
+                Ok(count) => tracing::debug!(count, "recorded entity history snapshot"),
+                Err(err) => tracing::warn!(%err, "failed to record entity history snapshot"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, PoisonError};
+
+    use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+    use minihub_domain::id::{DeviceId, EntityId};
+    use minihub_domain::time::Timestamp;
+
+    use super::*;
+
+    struct FixedEntityRepo(Vec<Entity>);
+
+    impl EntityRepository for FixedEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self.0.iter().find(|entity| entity.id == id).cloned())
+        }
+
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|entity| ids.contains(&entity.id))
+                .cloned()
+                .collect())
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self.0.clone())
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(
+            &self,
+            _entity_id: &str,
+        ) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            Ok(entity)
+        }
+        async fn delete(&self, _id: EntityId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyHistoryRepo {
+        records: Mutex<Vec<EntityHistory>>,
+    }
+
+    impl EntityHistoryRepository for SpyHistoryRepo {
+        async fn record(&self, history: EntityHistory) -> Result<EntityHistory, MiniHubError> {
+            self.records
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(history.clone());
+            Ok(history)
+        }
+        async fn find_by_entity_in_range(
+            &self,
+            _entity_id: EntityId,
+            _from: Timestamp,
+            _to: Timestamp,
+            _limit: Option<usize>,
+        ) -> Result<Vec<EntityHistory>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn purge_before(&self, _before: Timestamp) -> Result<usize, MiniHubError> {
+            Ok(0)
+        }
+    }
+
+    fn sensor(entity_id: &str, temperature: f64) -> Entity {
+        Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id(entity_id)
+            .friendly_name(entity_id)
+            .state(EntityState::On)
+            .attribute("temperature", AttributeValue::Float(temperature))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_record_every_entity_when_snapshot_taken() {
+        let entities = vec![
+            sensor("sensor.kitchen", 21.5),
+            sensor("sensor.bedroom", 19.0),
+        ];
+        let service = HistorySnapshotService::new(
+            FixedEntityRepo(entities.clone()),
+            SpyHistoryRepo::default(),
+            Duration::from_mins(1),
+        );
+
+        let count = service.snapshot().await.unwrap();
+
+        assert_eq!(count, 2);
+        let records = service.history_repo.records.lock().unwrap();
+        assert_eq!(records[0].entity_id, entities[0].id);
+        assert_eq!(records[1].entity_id, entities[1].id);
+        assert_eq!(
+            records[1].get_attribute("temperature"),
+            Some(&AttributeValue::Float(19.0))
+        );
+        assert_eq!(records[0].recorded_at, records[1].recorded_at);
+    }
+
+    #[tokio::test]
+    async fn should_record_nothing_when_there_are_no_entities() {
+        let service = HistorySnapshotService::new(
+            FixedEntityRepo(vec![]),
+            SpyHistoryRepo::default(),
+            Duration::from_mins(1),
+        );
+
+        assert_eq!(service.snapshot().await.unwrap(), 0);
+        assert!(service.history_repo.records.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+//! Home mode service — reads and changes the hub-wide home mode.
+//!
+//! The mode is stored on the [`HOME_MODE_ENTITY_ID`] entity, attached to a
+//! built-in hub device. Changes go through [`EntityService::upsert_entity`],
+//! so every mode switch is persisted and published as an
+//! [`AttributeChanged`](minihub_domain::event::EventType::AttributeChanged)
+//! event.
+
+use std::sync::Arc;
+
+use minihub_domain::entity::{AttributeValue, Entity};
+use minihub_domain::error::MiniHubError;
+use minihub_domain::home_mode::{HOME_MODE_ENTITY_ID, HomeMode, MODE_ATTRIBUTE};
+
+use crate::ports::{DeviceRepository, EntityRepository, EventPublisher};
+use crate::services::device_service::DeviceService;
+use crate::services::entity_service::EntityService;
+
+/// Application service for the hub-wide home mode.
+pub struct HomeModeService<DR, ER, EP> {
+    device_service: Arc<DeviceService<DR>>,
+    entity_service: Arc<EntityService<ER, EP>>,
+}
+
+impl<DR, ER, EP> HomeModeService<DR, ER, EP>
+where
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+{
+    /// Create a new service on top of the shared device and entity services.
+    pub fn new(
+        device_service: Arc<DeviceService<DR>>,
+        entity_service: Arc<EntityService<ER, EP>>,
+    ) -> Self {
+        Self {
+            device_service,
+            entity_service,
+        }
+    }
+
+    /// Return the home mode entity, creating it in [`HomeMode::Home`] when
+    /// it does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self))]
+    pub async fn ensure_entity(&self) -> Result<Entity, MiniHubError> {
+        if let Some(entity) = self
+            .entity_service
+            .find_by_entity_id(HOME_MODE_ENTITY_ID)
+            .await?
+        {
+            return Ok(entity);
+        }
+        let hub = self.device_service.ensure_hub_device().await?;
+        let entity = HomeMode::default().to_entity(hub.id)?;
+        self.entity_service.create_entity(entity).await
+    }
+
+    /// Current home mode, [`HomeMode::Home`] when it was never set.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn get_mode(&self) -> Result<HomeMode, MiniHubError> {
+        let entity = self
+            .entity_service
+            .find_by_entity_id(HOME_MODE_ENTITY_ID)
+            .await?;
+        Ok(entity
+            .and_then(|entity| HomeMode::of(&entity))
+            .unwrap_or_default())
+    }
+
+    /// Switch the home mode to `mode`.
+    ///
+    /// An event is only published when the mode actually changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_mode(&self, mode: HomeMode) -> Result<HomeMode, MiniHubError> {
+        let mut entity = self.ensure_entity().await?;
+        entity.set_attribute(
+            MODE_ATTRIBUTE.to_string(),
+            AttributeValue::String(mode.to_string()),
+        );
+        let entity = self.entity_service.upsert_entity(entity).await?;
+        Ok(HomeMode::of(&entity).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::device::Device;
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::id::{AreaId, DeviceId, EntityId};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        fn create(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: EntityId,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|ent| ent.device_id == device_id)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_entity_id(
+            &self,
+            entity_id: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|ent| ent.entity_id == entity_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        fn create(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: DeviceId,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result: Vec<Device> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Service = HomeModeService<InMemoryDeviceRepo, InMemoryEntityRepo, Arc<SpyPublisher>>;
+
+    fn setup() -> (Service, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = HomeModeService::new(
+            Arc::new(DeviceService::new(InMemoryDeviceRepo::default())),
+            Arc::new(EntityService::new(
+                InMemoryEntityRepo::default(),
+                Arc::clone(&publisher),
+            )),
+        );
+        (svc, publisher)
+    }
+
+    fn attribute_changes(publisher: &SpyPublisher) -> usize {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::AttributeChanged)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn should_default_to_home_when_never_set() {
+        let (svc, _) = setup();
+
+        assert_eq!(svc.get_mode().await.unwrap(), HomeMode::Home);
+    }
+
+    #[tokio::test]
+    async fn should_create_entity_once_when_ensured_twice() {
+        let (svc, _) = setup();
+
+        let first = svc.ensure_entity().await.unwrap();
+        let second = svc.ensure_entity().await.unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(svc.entity_service.list_entities().await.unwrap().len(), 1);
+        assert_eq!(HomeMode::of(&first), Some(HomeMode::Home));
+    }
+
+    #[tokio::test]
+    async fn should_persist_and_publish_when_mode_changes() {
+        let (svc, publisher) = setup();
+
+        let mode = svc.set_mode(HomeMode::Away).await.unwrap();
+
+        assert_eq!(mode, HomeMode::Away);
+        assert_eq!(svc.get_mode().await.unwrap(), HomeMode::Away);
+        assert_eq!(attribute_changes(&publisher), 1);
+    }
+
+    #[tokio::test]
+    async fn should_not_publish_when_mode_is_unchanged() {
+        let (svc, publisher) = setup();
+        svc.set_mode(HomeMode::Night).await.unwrap();
+
+        svc.set_mode(HomeMode::Night).await.unwrap();
+
+        assert_eq!(attribute_changes(&publisher), 1);
+    }
+}
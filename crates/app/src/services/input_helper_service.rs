@@ -0,0 +1,436 @@
+//! Input helper service — creates input helpers and changes their value.
+//!
+//! Helpers are attached to the built-in hub device. Value changes go through
+//! [`EntityService::upsert_entity`], so they are persisted and published as
+//! [`AttributeChanged`](minihub_domain::event::EventType::AttributeChanged)
+//! events that `value_changed` triggers react to.
+
+use std::sync::Arc;
+
+use minihub_domain::entity::Entity;
+use minihub_domain::error::{ConflictError, MiniHubError, ValidationError};
+use minihub_domain::id::EntityId;
+use minihub_domain::input_helper::{InputHelper, VALUE_ATTRIBUTE};
+
+use crate::ports::{DeviceRepository, EntityRepository, EventPublisher};
+use crate::services::device_service::DeviceService;
+use crate::services::entity_service::EntityService;
+
+/// Application service for user-defined input helpers.
+pub struct InputHelperService<DR, ER, EP> {
+    device_service: Arc<DeviceService<DR>>,
+    entity_service: Arc<EntityService<ER, EP>>,
+}
+
+impl<DR, ER, EP> InputHelperService<DR, ER, EP>
+where
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+{
+    /// Create a new service on top of the shared device and entity services.
+    pub fn new(
+        device_service: Arc<DeviceService<DR>>,
+        entity_service: Arc<EntityService<ER, EP>>,
+    ) -> Self {
+        Self {
+            device_service,
+            entity_service,
+        }
+    }
+
+    /// Create a helper called `name`, starting from `initial` when given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the constraints or the
+    /// initial value are invalid, or if an entity already uses the derived
+    /// `entity_id`, or a storage error from the repositories.
+    #[tracing::instrument(skip(self, initial))]
+    pub async fn create(
+        &self,
+        name: &str,
+        helper: &InputHelper,
+        initial: Option<&serde_json::Value>,
+    ) -> Result<Entity, MiniHubError> {
+        let hub = self.device_service.ensure_hub_device().await?;
+        let entity = helper.to_entity(hub.id, name, initial)?;
+        if self
+            .entity_service
+            .find_by_entity_id(&entity.entity_id)
+            .await?
+            .is_some()
+        {
+            return Err(ValidationError::InvalidInputHelper(format!(
+                "{} already exists",
+                entity.entity_id
+            ))
+            .into());
+        }
+        self.entity_service.create_entity(entity).await
+    }
+
+    /// List every input helper entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list(&self) -> Result<Vec<Entity>, MiniHubError> {
+        let entities = self.entity_service.list_entities().await?;
+        Ok(entities
+            .into_iter()
+            .filter(|entity| InputHelper::of(entity).is_some())
+            .collect())
+    }
+
+    /// Set the value of the number or select helper `id`, only if it is
+    /// still at `expected_version` when given.
+    ///
+    /// An event is only published when the value actually changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] if the entity does not exist,
+    /// [`MiniHubError::Conflict`] if it is not at `expected_version`,
+    /// [`MiniHubError::Validation`] if it is not a number or select helper
+    /// or `value` breaks its constraints, or a storage error from the
+    /// repository.
+    #[tracing::instrument(skip(self, value))]
+    pub async fn set_value(
+        &self,
+        id: EntityId,
+        value: &serde_json::Value,
+        expected_version: Option<u32>,
+    ) -> Result<Entity, MiniHubError> {
+        let mut entity = self.entity_service.get_entity(id).await?;
+        if let Some(expected) = expected_version
+            && entity.version != expected
+        {
+            return Err(ConflictError {
+                entity: "Entity",
+                id: id.to_string(),
+                expected: format!("version {expected}"),
+                actual: format!("version {}", entity.version),
+            }
+            .into());
+        }
+        let helper = InputHelper::of(&entity).ok_or_else(|| {
+            ValidationError::InvalidInputValue(format!(
+                "{} is not an input helper",
+                entity.entity_id
+            ))
+        })?;
+        let value = helper.parse_value(value)?;
+        entity.set_attribute(VALUE_ATTRIBUTE.to_string(), value);
+        self.entity_service.upsert_entity(entity).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::device::Device;
+    use minihub_domain::entity::{AttributeValue, EntityState};
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::id::{AreaId, DeviceId};
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        fn create(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: EntityId,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_device_id(
+            &self,
+            device_id: DeviceId,
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let result: Vec<Entity> = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|ent| ent.device_id == device_id)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_entity_id(
+            &self,
+            entity_id: &str,
+        ) -> impl Future<Output = Result<Option<Entity>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|ent| ent.entity_id == entity_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            entity: Entity,
+        ) -> impl Future<Output = Result<Entity, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            async { Ok(entity) }
+        }
+
+        fn delete(&self, id: EntityId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        fn create(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: DeviceId,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result: Vec<Device> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_area(
+            &self,
+            area_id: AreaId,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.area_id == Some(area_id))
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> impl Future<Output = Result<Vec<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|d| d.integration == integration)
+                .cloned()
+                .collect();
+            async { Ok(result) }
+        }
+
+        fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> impl Future<Output = Result<Option<Device>, MiniHubError>> + Send {
+            let result = self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|d| d.integration == integration && d.unique_id == unique_id)
+                .cloned();
+            async { Ok(result) }
+        }
+
+        fn update(
+            &self,
+            device: Device,
+        ) -> impl Future<Output = Result<Device, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            async { Ok(device) }
+        }
+
+        fn delete(&self, id: DeviceId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Service = InputHelperService<InMemoryDeviceRepo, InMemoryEntityRepo, Arc<SpyPublisher>>;
+
+    fn setup() -> (Service, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = InputHelperService::new(
+            Arc::new(DeviceService::new(InMemoryDeviceRepo::default())),
+            Arc::new(EntityService::new(
+                InMemoryEntityRepo::default(),
+                Arc::clone(&publisher),
+            )),
+        );
+        (svc, publisher)
+    }
+
+    fn thermostat() -> InputHelper {
+        InputHelper::Number {
+            min: 15.0,
+            max: 25.0,
+            step: 0.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_create_helper_on_hub_device() {
+        let (svc, _) = setup();
+
+        let entity = svc
+            .create("Guest mode", &InputHelper::Boolean, None)
+            .await
+            .unwrap();
+
+        assert_eq!(entity.entity_id, "input_boolean.guest_mode");
+        assert_eq!(entity.state, EntityState::Off);
+        let hub = svc.device_service.ensure_hub_device().await.unwrap();
+        assert_eq!(entity.device_id, hub.id);
+    }
+
+    #[tokio::test]
+    async fn should_reject_helper_when_entity_id_is_taken() {
+        let (svc, _) = setup();
+        svc.create("Guest mode", &InputHelper::Boolean, None)
+            .await
+            .unwrap();
+
+        let err = svc
+            .create("Guest Mode", &InputHelper::Boolean, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidInputHelper(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_publish_value_change_when_value_is_valid() {
+        let (svc, publisher) = setup();
+        let entity = svc.create("Target", &thermostat(), None).await.unwrap();
+
+        let updated = svc.set_value(entity.id, &json!(21.5), None).await.unwrap();
+
+        assert_eq!(
+            updated.get_attribute(VALUE_ATTRIBUTE),
+            Some(&AttributeValue::Float(21.5))
+        );
+        let events = publisher.events.lock().unwrap();
+        let changed: Vec<_> = events
+            .iter()
+            .filter(|event| event.event_type == EventType::AttributeChanged)
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].data["changed"]["value"], json!(21.5));
+    }
+
+    #[tokio::test]
+    async fn should_reject_value_breaking_constraints() {
+        let (svc, _) = setup();
+        let entity = svc.create("Target", &thermostat(), None).await.unwrap();
+
+        let err = svc
+            .set_value(entity.id, &json!(40), None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidInputValue(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_only_list_helpers() {
+        let (svc, _) = setup();
+        svc.create("Target", &thermostat(), None).await.unwrap();
+        let light = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .build()
+            .unwrap();
+        svc.entity_service.create_entity(light).await.unwrap();
+
+        let helpers = svc.list().await.unwrap();
+
+        assert_eq!(helpers.len(), 1);
+        assert_eq!(helpers[0].entity_id, "input_number.target");
+    }
+}
@@ -1,73 +1,196 @@
 //! Concrete [`IntegrationContext`] backed by application services.
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use tokio::sync::broadcast;
 
+use minihub_domain::audit::Actor;
 use minihub_domain::device::Device;
 use minihub_domain::entity::Entity;
 use minihub_domain::error::MiniHubError;
 use minihub_domain::event::Event;
 
+use crate::audit::act_as;
 use crate::event_bus::InProcessEventBus;
-use crate::ports::{DeviceRepository, EntityRepository, EventPublisher, IntegrationContext};
+use crate::metrics::{INTEGRATION_UPDATES, INTEGRATION_UPDATES_THROTTLED, Metrics};
+use crate::ports::{
+    DeviceRepository, DiscoveredDevice, DiscoveryRepository, EntityRepository, EventPublisher,
+    IntegrationContext,
+};
+use crate::services::device_registry::DeviceRegistry;
 use crate::services::device_service::DeviceService;
 use crate::services::entity_service::EntityService;
+use crate::services::update_throttle::{ThrottleConfig, UpdateThrottle};
 
-/// [`IntegrationContext`] implementation that delegates to `DeviceService`,
-/// `EntityService`, and an `EventPublisher`.
+/// [`IntegrationContext`] implementation that delegates to a
+/// [`DeviceRegistry`], `EntityService`, and an `EventPublisher`.
+///
+/// Discovered devices are written with their entities in one transaction
+/// through a [`DiscoveryRepository`]. Whether discovered or upserted, an
+/// entity is matched on its `entity_id`: a report carrying a fresh
+/// [`EntityId`](minihub_domain::id::EntityId) updates the stored entity,
+/// which keeps its id, and publishes the resulting state or attribute
+/// change.
+///
+/// Entity updates go through an [`UpdateThrottle`] first, disabled unless
+/// configured with [`ServiceContext::with_throttle`]. They are counted in
+/// the [`Metrics`] set with [`ServiceContext::with_metrics`], under the
+/// integration named with [`ServiceContext::for_integration`]. Writes are
+/// attributed to that integration in the audit log.
 ///
 /// Wraps `Arc`-ed services so it is cheaply cloneable and `Send + Sync`.
 /// The generic parameters are confined to this struct — integrations see
 /// only the [`IntegrationContext`] trait.
-pub struct ServiceContext<DR, ER, EP> {
-    device_service: Arc<DeviceService<DR>>,
+pub struct ServiceContext<DR, ER, EP, DSR> {
+    registry: Arc<DeviceRegistry<DR, ER, EP, DSR>>,
     entity_service: Arc<EntityService<ER, EP>>,
     event_publisher: EP,
     event_bus: Arc<InProcessEventBus>,
+    throttle: Arc<UpdateThrottle>,
+    metrics: Metrics,
+    integration: Arc<str>,
 }
 
-impl<DR, ER, EP> ServiceContext<DR, ER, EP> {
-    /// Create a new context backed by the given services, event publisher,
-    /// and event bus (for subscriptions).
+impl<DR, ER, EP: Clone, DSR> ServiceContext<DR, ER, EP, DSR> {
+    /// Create a new context backed by the given services, discovery
+    /// repository (to persist discovered devices atomically), event
+    /// publisher, and event bus (for subscriptions).
     pub fn new(
         device_service: Arc<DeviceService<DR>>,
         entity_service: Arc<EntityService<ER, EP>>,
+        discovery: DSR,
         event_publisher: EP,
         event_bus: Arc<InProcessEventBus>,
     ) -> Self {
-        Self {
+        let registry = DeviceRegistry::new(
             device_service,
+            Arc::clone(&entity_service),
+            discovery,
+            event_publisher.clone(),
+        );
+        Self {
+            registry: Arc::new(registry),
             entity_service,
             event_publisher,
             event_bus,
+            throttle: Arc::default(),
+            metrics: Metrics::default(),
+            integration: Arc::from("unknown"),
+        }
+    }
+
+    /// Drop high-frequency entity updates according to `config`.
+    #[must_use]
+    pub fn with_throttle(mut self, config: ThrottleConfig) -> Self {
+        self.throttle = Arc::new(UpdateThrottle::new(config));
+        self
+    }
+
+    /// Count entity updates in `metrics`.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// A context sharing these services, counting updates under the
+    /// integration `name`.
+    #[must_use]
+    pub fn for_integration(&self, name: &str) -> Self {
+        Self {
+            integration: Arc::from(name),
+            ..self.clone()
+        }
+    }
+}
+
+impl<DR, ER, EP, DSR> ServiceContext<DR, ER, EP, DSR> {
+    /// The audit actor of the writes made through this context.
+    fn actor(&self) -> Actor {
+        Actor::Integration(self.integration.to_string())
+    }
+
+    /// Count `received` entity updates, of which `admitted` passed the
+    /// throttle.
+    #[allow(clippy::cast_precision_loss)]
+    fn count_updates(&self, received: usize, admitted: usize) {
+        let labels = [("integration", &*self.integration)];
+        self.metrics
+            .add(&INTEGRATION_UPDATES, &labels, received as f64);
+        if admitted < received {
+            self.metrics.add(
+                &INTEGRATION_UPDATES_THROTTLED,
+                &labels,
+                (received - admitted) as f64,
+            );
+        }
+    }
+}
+
+impl<DR, ER, EP, DSR> ServiceContext<DR, ER, EP, DSR>
+where
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+    DSR: DiscoveryRepository,
+{
+    /// Upsert `entity` through the throttle, returning the stored entity
+    /// when the update is dropped.
+    async fn upsert_throttled(
+        &self,
+        entity: Entity,
+        cause: Option<&Event>,
+    ) -> Result<Entity, MiniHubError> {
+        let admitted = self.throttle.admit(&entity, Instant::now());
+        self.count_updates(1, usize::from(admitted));
+        if !admitted
+            && let Some(stored) = self
+                .entity_service
+                .find_by_entity_id(&entity.entity_id)
+                .await?
+        {
+            return Ok(stored);
         }
+        self.registry.upsert_entity(entity, cause).await
     }
 }
 
-impl<DR, ER, EP: Clone> Clone for ServiceContext<DR, ER, EP> {
+impl<DR, ER, EP: Clone, DSR> Clone for ServiceContext<DR, ER, EP, DSR> {
     fn clone(&self) -> Self {
         Self {
-            device_service: Arc::clone(&self.device_service),
+            registry: Arc::clone(&self.registry),
             entity_service: Arc::clone(&self.entity_service),
             event_publisher: self.event_publisher.clone(),
             event_bus: Arc::clone(&self.event_bus),
+            throttle: Arc::clone(&self.throttle),
+            metrics: self.metrics.clone(),
+            integration: Arc::clone(&self.integration),
         }
     }
 }
 
-impl<DR, ER, EP> IntegrationContext for ServiceContext<DR, ER, EP>
+impl<DR, ER, EP, DSR> IntegrationContext for ServiceContext<DR, ER, EP, DSR>
 where
     DR: DeviceRepository + Send + Sync + 'static,
     ER: EntityRepository + Send + Sync + 'static,
     EP: EventPublisher + Send + Sync + 'static,
+    DSR: DiscoveryRepository + Send + Sync + 'static,
 {
     async fn upsert_device(&self, device: Device) -> Result<Device, MiniHubError> {
-        self.device_service.upsert_device(device).await
+        act_as(self.actor(), self.registry.register_device(device)).await
     }
 
     async fn upsert_entity(&self, entity: Entity) -> Result<Entity, MiniHubError> {
-        self.entity_service.upsert_entity(entity).await
+        act_as(self.actor(), self.upsert_throttled(entity, None)).await
+    }
+
+    async fn upsert_entity_caused_by(
+        &self,
+        entity: Entity,
+        cause: &Event,
+    ) -> Result<Entity, MiniHubError> {
+        act_as(self.actor(), self.upsert_throttled(entity, Some(cause))).await
     }
 
     async fn find_entity_by_id(
@@ -88,6 +211,10 @@ where
         self.entity_service.find_by_entity_id(entity_id).await
     }
 
+    async fn list_entities(&self) -> Result<Vec<Entity>, MiniHubError> {
+        self.entity_service.list_entities().await
+    }
+
     async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
         self.event_publisher.publish(event).await
     }
@@ -95,6 +222,16 @@ where
     fn subscribe(&self) -> broadcast::Receiver<Event> {
         self.event_bus.subscribe()
     }
+
+    async fn persist_discovered(&self, mut dd: DiscoveredDevice) -> Result<(), MiniHubError> {
+        let now = Instant::now();
+        let received = dd.entities.len();
+        dd.entities
+            .retain(|entity| self.throttle.admit(entity, now));
+        self.count_updates(received, dd.entities.len());
+        act_as(self.actor(), self.registry.register(dd)).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -104,20 +241,13 @@ mod tests {
     use std::future::Future;
     use std::sync::Mutex;
 
-    use minihub_domain::entity::EntityState;
+    use minihub_domain::entity::{AttributeValue, EntityState};
     use minihub_domain::event::EventType;
-    use minihub_domain::id::{DeviceId, EntityId};
+    use minihub_domain::id::{AreaId, DeviceId, EntityId};
 
+    #[derive(Default)]
     struct StubDeviceRepo {
-        store: Mutex<HashMap<DeviceId, Device>>,
-    }
-
-    impl Default for StubDeviceRepo {
-        fn default() -> Self {
-            Self {
-                store: Mutex::new(HashMap::new()),
-            }
-        }
+        store: Arc<Mutex<HashMap<DeviceId, Device>>>,
     }
 
     impl DeviceRepository for StubDeviceRepo {
@@ -142,6 +272,17 @@ mod tests {
             async { Ok(result) }
         }
 
+        async fn find_by_area(&self, _area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
+
+        async fn find_by_integration(
+            &self,
+            _integration: &str,
+        ) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
+
         async fn find_by_integration_unique_id(
             &self,
             _integration: &str,
@@ -164,16 +305,9 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
     struct StubEntityRepo {
-        store: Mutex<HashMap<EntityId, Entity>>,
-    }
-
-    impl Default for StubEntityRepo {
-        fn default() -> Self {
-            Self {
-                store: Mutex::new(HashMap::new()),
-            }
-        }
+        store: Arc<Mutex<HashMap<EntityId, Entity>>>,
     }
 
     impl EntityRepository for StubEntityRepo {
@@ -193,6 +327,15 @@ mod tests {
             async { Ok(result) }
         }
 
+        fn get_many(
+            &self,
+            ids: &[EntityId],
+        ) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
+            let store = self.store.lock().unwrap();
+            let result = ids.iter().filter_map(|id| store.get(id).cloned()).collect();
+            async { Ok(result) }
+        }
+
         fn get_all(&self) -> impl Future<Output = Result<Vec<Entity>, MiniHubError>> + Send {
             let result: Vec<Entity> = self.store.lock().unwrap().values().cloned().collect();
             async { Ok(result) }
@@ -239,16 +382,50 @@ mod tests {
             self.store.lock().unwrap().remove(&id);
             async { Ok(()) }
         }
+
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    struct StubDiscoveryRepo {
+        devices: Arc<Mutex<HashMap<DeviceId, Device>>>,
+        entities: Arc<Mutex<HashMap<EntityId, Entity>>>,
+    }
+
+    impl DiscoveryRepository for StubDiscoveryRepo {
+        async fn persist(&self, discovered: &DiscoveredDevice) -> Result<(), MiniHubError> {
+            self.devices
+                .lock()
+                .unwrap()
+                .insert(discovered.device.id, discovered.device.clone());
+            let mut entities = self.entities.lock().unwrap();
+            for entity in &discovered.entities {
+                entities.insert(entity.id, entity.clone());
+            }
+            Ok(())
+        }
     }
 
-    fn make_context() -> ServiceContext<StubDeviceRepo, StubEntityRepo, Arc<InProcessEventBus>> {
+    type StubContext =
+        ServiceContext<StubDeviceRepo, StubEntityRepo, Arc<InProcessEventBus>, StubDiscoveryRepo>;
+
+    fn make_context() -> StubContext {
         let event_bus = Arc::new(InProcessEventBus::new(16));
+        let device_repo = StubDeviceRepo::default();
+        let entity_repo = StubEntityRepo::default();
+        let discovery = StubDiscoveryRepo {
+            devices: Arc::clone(&device_repo.store),
+            entities: Arc::clone(&entity_repo.store),
+        };
         ServiceContext::new(
-            Arc::new(DeviceService::new(StubDeviceRepo::default())),
-            Arc::new(EntityService::new(
-                StubEntityRepo::default(),
-                Arc::clone(&event_bus),
-            )),
+            Arc::new(DeviceService::new(device_repo)),
+            Arc::new(EntityService::new(entity_repo, Arc::clone(&event_bus))),
+            discovery,
             Arc::clone(&event_bus),
             event_bus,
         )
@@ -262,7 +439,7 @@ mod tests {
         let event = Event::new(
             EventType::StateChanged,
             Some(EntityId::new()),
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         );
         let event_id = event.id;
 
@@ -298,6 +475,10 @@ mod tests {
             integration: "virtual".into(),
             unique_id: "dev-1".into(),
             area_id: None,
+            via_device_id: None,
+            sw_version: None,
+            hw_version: None,
+            suggested_area: None,
         };
         let result = ctx.upsert_device(device.clone()).await.unwrap();
         assert_eq!(result.id, device.id);
@@ -312,12 +493,167 @@ mod tests {
             device_id: DeviceId::new(),
             friendly_name: "Test light".into(),
             state: EntityState::default(),
+            device_class: None,
+            unit_of_measurement: None,
             attributes: HashMap::new(),
+            attribute_meta: HashMap::new(),
             mac_address: None,
             last_changed: minihub_domain::time::now(),
             last_updated: minihub_domain::time::now(),
+            version: 1,
         };
         let result = ctx.upsert_entity(entity.clone()).await.unwrap();
         assert_eq!(result.id, entity.id);
     }
+
+    fn temperature(state: f64) -> Entity {
+        Entity::builder()
+            .entity_id("sensor.temperature")
+            .friendly_name("Temperature")
+            .state(EntityState::Numeric(state))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_update_existing_entity_when_upserted_with_new_id() {
+        let ctx = make_context();
+        let first = ctx.upsert_entity(temperature(21.0)).await.unwrap();
+        let mut rx = ctx.subscribe();
+
+        let second = ctx.upsert_entity(temperature(22.0)).await.unwrap();
+
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.state, EntityState::Numeric(22.0));
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.event_type, EventType::StateChanged);
+        assert_eq!(event.entity_id, Some(first.id));
+    }
+
+    #[tokio::test]
+    async fn should_update_existing_entity_when_rediscovered_with_new_id() {
+        let ctx = make_context();
+        let discovered = |state: f64| {
+            let device = Device::builder()
+                .name("Thermometer")
+                .integration("ble")
+                .unique_id("a4:c1:38:00:00:01")
+                .build()
+                .unwrap();
+            DiscoveredDevice {
+                device,
+                entities: vec![temperature(state)],
+                via_device: None,
+            }
+        };
+        ctx.persist_discovered(discovered(21.0)).await.unwrap();
+        let first = ctx
+            .find_entity_by_entity_id("sensor.temperature")
+            .await
+            .unwrap()
+            .unwrap();
+        let mut rx = ctx.subscribe();
+
+        ctx.persist_discovered(discovered(22.0)).await.unwrap();
+
+        let stored = ctx
+            .find_entity_by_entity_id("sensor.temperature")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, first.id);
+        assert_eq!(stored.state, EntityState::Numeric(22.0));
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.event_type, EventType::StateChanged);
+        assert_eq!(event.entity_id, Some(first.id));
+    }
+
+    #[tokio::test]
+    async fn should_return_stored_entity_when_update_is_throttled() {
+        let ctx = make_context().with_throttle(ThrottleConfig {
+            min_interval: std::time::Duration::from_mins(1),
+            min_delta: 0.0,
+        });
+        let reading = |temperature: f64| {
+            Entity::builder()
+                .entity_id("sensor.temperature")
+                .friendly_name("Temperature")
+                .attribute("temperature", AttributeValue::Float(temperature))
+                .build()
+                .unwrap()
+        };
+
+        let first = ctx.upsert_entity(reading(21.0)).await.unwrap();
+        let second = ctx.upsert_entity(reading(22.0)).await.unwrap();
+
+        assert_eq!(second.id, first.id);
+        assert_eq!(
+            second.get_attribute("temperature"),
+            Some(&AttributeValue::Float(21.0))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_count_updates_under_integration_name() {
+        let metrics = Metrics::new();
+        let ctx = make_context()
+            .with_metrics(metrics.clone())
+            .with_throttle(ThrottleConfig {
+                min_interval: std::time::Duration::from_mins(1),
+                min_delta: 0.0,
+            })
+            .for_integration("ble");
+        let reading = |temperature: f64| {
+            Entity::builder()
+                .entity_id("sensor.temperature")
+                .friendly_name("Temperature")
+                .attribute("temperature", AttributeValue::Float(temperature))
+                .build()
+                .unwrap()
+        };
+
+        ctx.upsert_entity(reading(21.0)).await.unwrap();
+        ctx.upsert_entity(reading(22.0)).await.unwrap();
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("minihub_integration_updates_total{integration=\"ble\"} 2\n"));
+        assert!(
+            rendered
+                .contains("minihub_integration_updates_throttled_total{integration=\"ble\"} 1\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_persist_discovered_device_with_its_entities() {
+        let ctx = make_context();
+        let device = Device::builder()
+            .name("Thermometer")
+            .integration("ble")
+            .unique_id("a4:c1:38:00:00:01")
+            .build()
+            .unwrap();
+        let entity = Entity::builder()
+            .device_id(device.id)
+            .entity_id("sensor.temperature")
+            .friendly_name("Temperature")
+            .state(EntityState::On)
+            .build()
+            .unwrap();
+        let device_id = device.id;
+
+        ctx.persist_discovered(DiscoveredDevice {
+            device,
+            entities: vec![entity],
+            via_device: None,
+        })
+        .await
+        .unwrap();
+
+        let stored = ctx
+            .find_entity_by_entity_id("sensor.temperature")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.device_id, device_id);
+    }
 }
@@ -0,0 +1,180 @@
+//! Notification service — forwards requested notifications to a notifier.
+//!
+//! The service subscribes to the event bus and, for every
+//! [`EventType::NotificationRequested`] event, decodes the carried
+//! [`Notification`] and hands it to the configured [`Notifier`].
+//!
+//! [`EventType::NotificationRequested`]: minihub_domain::event::EventType::NotificationRequested
+
+use tokio::sync::broadcast;
+
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::{Event, EventType};
+use minihub_domain::notification::Notification;
+
+use crate::event_bus::EventFilter;
+use crate::ports::{EventSubscription, Notifier};
+
+/// Application service delivering notifications requested on the event bus.
+pub struct NotificationService<N> {
+    notifier: N,
+}
+
+impl<N: Notifier> NotificationService<N> {
+    /// Create a new service delivering through `notifier`.
+    pub fn new(notifier: N) -> Self {
+        Self { notifier }
+    }
+
+    /// The events [`Self::process_event`] may act on, to subscribe with.
+    #[must_use]
+    pub fn event_filter(&self) -> EventFilter {
+        EventFilter::all().with_event_types([EventType::NotificationRequested])
+    }
+
+    /// Feed every event received from the bus through [`Self::process_event`].
+    ///
+    /// Runs until the bus is closed. Lagging behind the bus only drops the
+    /// skipped notifications; delivery failures are logged and do not stop
+    /// the loop.
+    pub async fn run<S: EventSubscription>(&self, mut receiver: S) {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Err(err) = self.process_event(&event).await {
+                        tracing::warn!(%err, event_id = %event.id, "failed to deliver notification");
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        skipped,
+                        "notification service lagged, some events were dropped"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        tracing::debug!("notification service stopped");
+    }
+
+    /// Deliver the notification carried by `event`, if any.
+    ///
+    /// Returns `true` when a notification was delivered and `false` when the
+    /// event is not a notification request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when the event payload is not a
+    /// valid notification, or the error reported by the notifier.
+    #[tracing::instrument(skip(self, event), fields(event_id = %event.id))]
+    pub async fn process_event(&self, event: &Event) -> Result<bool, MiniHubError> {
+        let Some(notification) = Notification::from_event(event)? else {
+            return Ok(false);
+        };
+        self.notifier.notify(&notification).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minihub_domain::error::NotFoundError;
+    use minihub_domain::notification::Severity;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct SpyNotifier {
+        delivered: Mutex<Vec<Notification>>,
+    }
+
+    impl Notifier for SpyNotifier {
+        fn notify(
+            &self,
+            notification: &Notification,
+        ) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.delivered.lock().unwrap().push(notification.clone());
+            async { Ok(()) }
+        }
+    }
+
+    struct FailingNotifier;
+
+    impl Notifier for FailingNotifier {
+        async fn notify(&self, _notification: &Notification) -> Result<(), MiniHubError> {
+            Err(NotFoundError {
+                entity: "Webhook",
+                id: "unreachable".to_string(),
+            }
+            .into())
+        }
+    }
+
+    fn notification() -> Notification {
+        Notification::builder()
+            .title("Leak")
+            .message("Water detected in the basement")
+            .severity(Severity::Critical)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_deliver_notification_when_requested() {
+        let svc = NotificationService::new(SpyNotifier::default());
+        let notification = notification();
+
+        let delivered = svc.process_event(&notification.to_event()).await.unwrap();
+
+        assert!(delivered);
+        assert_eq!(*svc.notifier.delivered.lock().unwrap(), vec![notification]);
+    }
+
+    #[tokio::test]
+    async fn should_ignore_event_when_not_a_notification() {
+        let svc = NotificationService::new(SpyNotifier::default());
+        let event = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+
+        let delivered = svc.process_event(&event).await.unwrap();
+
+        assert!(!delivered);
+        assert!(svc.notifier.delivered.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_return_validation_error_when_payload_is_invalid() {
+        let svc = NotificationService::new(SpyNotifier::default());
+        let event = Event::new(
+            EventType::NotificationRequested,
+            None,
+            serde_json::json!({ "message": 42 }),
+        );
+
+        let result = svc.process_event(&event).await;
+
+        assert!(matches!(result, Err(MiniHubError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn should_propagate_error_when_notifier_fails() {
+        let svc = NotificationService::new(FailingNotifier);
+
+        let result = svc.process_event(&notification().to_event()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_deliver_notifications_from_bus_until_closed() {
+        let (sender, receiver) = broadcast::channel(8);
+        let svc = NotificationService::new(SpyNotifier::default());
+        let notification = notification();
+
+        sender.send(notification.to_event()).unwrap();
+        drop(sender);
+        svc.run(receiver).await;
+
+        assert_eq!(svc.notifier.delivered.lock().unwrap().len(), 1);
+    }
+}
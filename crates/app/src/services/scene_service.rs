@@ -0,0 +1,255 @@
+//! Scene service — use-cases for managing and activating scenes.
+
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::{Event, EventType, ServiceCallPayload};
+use minihub_domain::id::SceneId;
+use minihub_domain::scene::Scene;
+
+use crate::ports::{EventPublisher, SceneRepository};
+
+/// Application service for scene CRUD and activation.
+pub struct SceneService<R, P> {
+    repo: R,
+    publisher: P,
+}
+
+impl<R: SceneRepository, P: EventPublisher> SceneService<R, P> {
+    /// Create a new service backed by the given repository and publisher.
+    pub fn new(repo: R, publisher: P) -> Self {
+        Self { repo, publisher }
+    }
+
+    /// Create a new scene after validating domain invariants.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, or a
+    /// storage error propagated from the repository.
+    #[tracing::instrument(skip(self, scene), fields(scene_name = %scene.name))]
+    pub async fn create_scene(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        scene.validate()?;
+        self.repo.create(scene).await
+    }
+
+    /// Look up a scene by id, returning an error if not found.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no scene with `id` exists,
+    /// or a storage error from the repository.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_scene(&self, id: SceneId) -> Result<Scene, MiniHubError> {
+        self.repo.get_by_id(id).await?.ok_or_else(|| {
+            NotFoundError {
+                entity: "Scene",
+                id: id.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// List all scenes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list_scenes(&self) -> Result<Vec<Scene>, MiniHubError> {
+        self.repo.get_all().await
+    }
+
+    /// Update an existing scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if invariants fail, or a
+    /// storage error from the repository.
+    #[tracing::instrument(skip(self, scene))]
+    pub async fn update_scene(&self, scene: Scene) -> Result<Scene, MiniHubError> {
+        scene.validate()?;
+        self.repo.update(scene).await
+    }
+
+    /// Delete a scene by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete_scene(&self, id: SceneId) -> Result<(), MiniHubError> {
+        self.repo.delete(id).await
+    }
+
+    /// Activate a scene by requesting a service call for each member.
+    ///
+    /// Calls are published as [`EventType::ServiceCallRequested`] events; the
+    /// owning integrations actuate the devices asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no scene with `id` exists,
+    /// or an error from the repository or the event publisher.
+    #[tracing::instrument(skip(self))]
+    pub async fn activate_scene(&self, id: SceneId) -> Result<Scene, MiniHubError> {
+        let scene = self.get_scene(id).await?;
+        for member in &scene.members {
+            let Some(service) = member.service() else {
+                continue;
+            };
+            let event = Event::with_payload(
+                EventType::ServiceCallRequested,
+                Some(member.entity_id),
+                &ServiceCallPayload {
+                    service: service.to_string(),
+                    data: serde_json::json!(member.attributes),
+                },
+            );
+            self.publisher.publish(event).await?;
+        }
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minihub_domain::entity::{AttributeValue, EntityState};
+    use minihub_domain::id::EntityId;
+    use minihub_domain::scene::SceneMember;
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct InMemorySceneRepo {
+        store: Mutex<HashMap<SceneId, Scene>>,
+    }
+
+    impl SceneRepository for InMemorySceneRepo {
+        fn create(&self, scene: Scene) -> impl Future<Output = Result<Scene, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(scene.id, scene.clone());
+            async { Ok(scene) }
+        }
+
+        fn get_by_id(
+            &self,
+            id: SceneId,
+        ) -> impl Future<Output = Result<Option<Scene>, MiniHubError>> + Send {
+            let result = self.store.lock().unwrap().get(&id).cloned();
+            async { Ok(result) }
+        }
+
+        fn get_all(&self) -> impl Future<Output = Result<Vec<Scene>, MiniHubError>> + Send {
+            let result: Vec<Scene> = self.store.lock().unwrap().values().cloned().collect();
+            async { Ok(result) }
+        }
+
+        fn update(&self, scene: Scene) -> impl Future<Output = Result<Scene, MiniHubError>> + Send {
+            self.store.lock().unwrap().insert(scene.id, scene.clone());
+            async { Ok(scene) }
+        }
+
+        fn delete(&self, id: SceneId) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.store.lock().unwrap().remove(&id);
+            async { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    fn make_service() -> SceneService<InMemorySceneRepo, SpyPublisher> {
+        SceneService::new(InMemorySceneRepo::default(), SpyPublisher::default())
+    }
+
+    fn valid_scene() -> Scene {
+        Scene::builder()
+            .name("Movie night")
+            .member(SceneMember::new(EntityId::new(), EntityState::Off))
+            .member(
+                SceneMember::new(EntityId::new(), EntityState::On)
+                    .with_attribute("brightness", AttributeValue::Int(30)),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_create_scene_when_valid() {
+        let svc = make_service();
+        let scene = valid_scene();
+        let id = scene.id;
+
+        svc.create_scene(scene).await.unwrap();
+
+        let found = svc.get_scene(id).await.unwrap();
+        assert_eq!(found.name, "Movie night");
+        assert_eq!(found.members.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_return_validation_error_when_creating_scene_without_members() {
+        let svc = make_service();
+        let mut scene = valid_scene();
+        scene.members.clear();
+
+        let result = svc.create_scene(scene).await;
+        assert!(matches!(result, Err(MiniHubError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_scene_missing() {
+        let svc = make_service();
+        let result = svc.get_scene(SceneId::new()).await;
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn should_delete_scene() {
+        let svc = make_service();
+        let scene = svc.create_scene(valid_scene()).await.unwrap();
+
+        svc.delete_scene(scene.id).await.unwrap();
+
+        assert!(svc.list_scenes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_request_service_call_per_member_when_activated() {
+        let svc = make_service();
+        let scene = svc.create_scene(valid_scene()).await.unwrap();
+
+        svc.activate_scene(scene.id).await.unwrap();
+
+        let events = svc.publisher.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 2);
+        assert!(
+            events
+                .iter()
+                .all(|event| event.event_type == EventType::ServiceCallRequested)
+        );
+        assert_eq!(events[0].entity_id, Some(scene.members[0].entity_id));
+        assert_eq!(events[0].data["service"], "turn_off");
+        assert_eq!(events[1].entity_id, Some(scene.members[1].entity_id));
+        assert_eq!(events[1].data["service"], "turn_on");
+        assert_eq!(events[1].data["data"]["brightness"], 30);
+    }
+
+    #[tokio::test]
+    async fn should_not_publish_when_activating_missing_scene() {
+        let svc = make_service();
+
+        let result = svc.activate_scene(SceneId::new()).await;
+
+        assert!(matches!(result, Err(MiniHubError::NotFound(_))));
+        assert!(svc.publisher.events.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Schedule service — fires the `time_pattern` triggers of automations.
+//!
+//! At the start of every minute, each distinct pattern of the enabled
+//! `time_pattern` triggers that came due since the previous tick is
+//! published as an
+//! [`EventType::TimePatternFired`](minihub_domain::event::EventType::TimePatternFired)
+//! event for the automation engine to match. Patterns stored before they
+//! were checked may not parse: they are skipped, with a warning when the
+//! service starts.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::Timelike;
+
+use minihub_domain::automation::{Automation, CronSchedule, Trigger, time_pattern_fired};
+use minihub_domain::error::{MiniHubError, ValidationError};
+use minihub_domain::time::{self, Timestamp};
+
+use crate::ports::{AutomationRepository, EventPublisher};
+
+/// Application service publishing the due `time_pattern` triggers.
+pub struct ScheduleService<AR, EP> {
+    automation_repo: AR,
+    publisher: EP,
+}
+
+impl<AR, EP> ScheduleService<AR, EP>
+where
+    AR: AutomationRepository,
+    EP: EventPublisher,
+{
+    /// Create a service reading `time_pattern` triggers from
+    /// `automation_repo` and publishing their firings to `publisher`.
+    pub fn new(automation_repo: AR, publisher: EP) -> Self {
+        Self {
+            automation_repo,
+            publisher,
+        }
+    }
+
+    /// The enabled automations whose `time_pattern` does not parse, and so
+    /// never fire, with the reason.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error when the automations cannot be listed.
+    pub async fn invalid_patterns(
+        &self,
+    ) -> Result<Vec<(Automation, ValidationError)>, MiniHubError> {
+        Ok(self
+            .automation_repo
+            .get_enabled()
+            .await?
+            .into_iter()
+            .filter_map(|automation| match &automation.trigger {
+                Trigger::TimePattern { cron } => {
+                    let err = cron.parse::<CronSchedule>().err()?;
+                    Some((automation, err))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Publish a time pattern event for every distinct pattern of the
+    /// enabled `time_pattern` triggers coming due within `(after, until]`,
+    /// returning how many were published.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error when the automations cannot be listed, or an
+    /// error from the event publisher.
+    #[tracing::instrument(skip(self))]
+    pub async fn fire_due(
+        &self,
+        after: Timestamp,
+        until: Timestamp,
+    ) -> Result<usize, MiniHubError> {
+        let patterns: HashSet<String> = self
+            .automation_repo
+            .get_enabled()
+            .await?
+            .into_iter()
+            .filter_map(|automation| match automation.trigger {
+                Trigger::TimePattern { cron } => Some(cron),
+                _ => None,
+            })
+            .collect();
+        let mut count = 0;
+        for cron in patterns {
+            let due = cron
+                .parse::<CronSchedule>()
+                .ok()
+                .and_then(|schedule| schedule.next_after(after))
+                .is_some_and(|at| at <= until);
+            if due {
+                self.publisher.publish(time_pattern_fired(&cron)).await?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Fire due time patterns at the start of every minute, forever.
+    /// Failures are logged and do not stop the loop.
+    pub async fn run(&self) {
+        match self.invalid_patterns().await {
+            Ok(invalid) => {
+                for (automation, err) in invalid {
+                    tracing::warn!(
+                        automation_id = %automation.id,
+                        automation = %automation.name,
+                        %err,
+                        "time pattern does not parse, the automation never fires"
+                    );
+                }
+            }
+            Err(err) => tracing::warn!(%err, "failed to check time patterns"),
+        }
+        let mut last = time::now();
+        loop {
+            let seconds_left = 60 - u64::from(last.second());
+            tokio::time::sleep(Duration::from_secs(seconds_left)).await;
+            let now = time::now();
+            match self.fire_due(last, now).await {
+                Ok(count) if count > 0 => tracing::debug!(count, "fired time patterns"),
+                Ok(_) => {}
+                Err(err) => tracing::warn!(%err, "failed to fire time patterns"),
+            }
+            last = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use minihub_domain::automation::Action;
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::id::{AutomationId, EntityId};
+
+    use super::*;
+
+    struct FixedAutomationRepo(Vec<Automation>);
+
+    impl AutomationRepository for FixedAutomationRepo {
+        async fn create(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+        async fn get_by_id(&self, id: AutomationId) -> Result<Option<Automation>, MiniHubError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|automation| automation.id == id)
+                .cloned())
+        }
+        async fn get_all(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self.0.clone())
+        }
+        async fn get_enabled(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self.0.iter().filter(|a| a.enabled).cloned().collect())
+        }
+        async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+        async fn record_triggered(
+            &self,
+            _id: AutomationId,
+            _triggered_at: Timestamp,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn delete(&self, _id: AutomationId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn scheduled(cron: &str, enabled: bool) -> Automation {
+        let mut automation = Automation::builder()
+            .name("Morning lights")
+            .enabled(enabled)
+            .trigger(Trigger::Manual)
+            .action(Action::CallService {
+                entity_id: EntityId::new(),
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap();
+        automation.trigger = Trigger::TimePattern {
+            cron: cron.to_string(),
+        };
+        automation
+    }
+
+    fn setup(
+        automations: Vec<Automation>,
+    ) -> (
+        ScheduleService<FixedAutomationRepo, Arc<SpyPublisher>>,
+        Arc<SpyPublisher>,
+    ) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = ScheduleService::new(FixedAutomationRepo(automations), Arc::clone(&publisher));
+        (svc, publisher)
+    }
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_fire_each_due_pattern_once() {
+        let (svc, publisher) = setup(vec![
+            scheduled("0 8 * * *", true),
+            scheduled("0 8 * * *", true),
+            scheduled("30 8 * * *", true),
+            scheduled("0 8 * * *", false),
+        ]);
+
+        let count = svc
+            .fire_due(at("2026-03-01T07:59:00Z"), at("2026-03-01T08:00:01Z"))
+            .await
+            .unwrap();
+
+        let events = publisher.events.lock().unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, EventType::TimePatternFired);
+        assert_eq!(events[0].data["cron"], "0 8 * * *");
+    }
+
+    #[tokio::test]
+    async fn should_skip_patterns_that_do_not_parse() {
+        let (svc, publisher) = setup(vec![
+            scheduled("every morning", true),
+            scheduled("* * * * *", true),
+        ]);
+
+        let count = svc
+            .fire_due(at("2026-03-01T07:59:00Z"), at("2026-03-01T08:00:01Z"))
+            .await
+            .unwrap();
+        let invalid = svc.invalid_patterns().await.unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            publisher.events.lock().unwrap()[0].data["cron"],
+            "* * * * *"
+        );
+        assert_eq!(invalid.len(), 1);
+        assert!(matches!(invalid[0].1, ValidationError::InvalidCron(_)));
+    }
+}
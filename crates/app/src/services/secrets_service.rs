@@ -0,0 +1,176 @@
+//! Secrets service — use-cases for the credentials of integrations.
+//!
+//! Management goes through [`store`](SecretsService::store),
+//! [`list`](SecretsService::list) and [`delete`](SecretsService::delete),
+//! none of which hands a value back; adapters needing a credential read it
+//! with [`get`](SecretsService::get).
+
+use minihub_domain::error::{MiniHubError, NotFoundError, ValidationError};
+use minihub_domain::secret::{self, SecretInfo, SecretValue};
+
+use crate::ports::SecretsRepository;
+
+/// Application service storing and retrieving secrets.
+pub struct SecretsService<R> {
+    repo: R,
+}
+
+impl<R: SecretsRepository> SecretsService<R> {
+    /// Create a new service backed by the given secrets repository.
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// Store `value` under `name`, replacing any previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the name is invalid or the
+    /// value empty, or a storage error propagated from the repository.
+    #[tracing::instrument(skip(self, value))]
+    pub async fn store(&self, name: &str, value: SecretValue) -> Result<SecretInfo, MiniHubError> {
+        secret::validate_name(name)?;
+        if value.expose().is_empty() {
+            return Err(ValidationError::EmptySecretValue.into());
+        }
+        self.repo.put(name, &value).await
+    }
+
+    /// The value stored under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no secret is stored under
+    /// `name`, or a storage error propagated from the repository.
+    pub async fn get(&self, name: &str) -> Result<SecretValue, MiniHubError> {
+        self.repo.get(name).await?.ok_or_else(|| not_found(name))
+    }
+
+    /// Every stored secret, without its value.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repository.
+    pub async fn list(&self) -> Result<Vec<SecretInfo>, MiniHubError> {
+        self.repo.list().await
+    }
+
+    /// Remove the secret stored under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::NotFound`] when no secret is stored under
+    /// `name`, or a storage error propagated from the repository.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete(&self, name: &str) -> Result<(), MiniHubError> {
+        if self.repo.delete(name).await? {
+            Ok(())
+        } else {
+            Err(not_found(name))
+        }
+    }
+}
+
+fn not_found(name: &str) -> MiniHubError {
+    NotFoundError {
+        entity: "Secret",
+        id: name.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemorySecretsRepo {
+        store: Mutex<BTreeMap<String, SecretValue>>,
+    }
+
+    impl SecretsRepository for InMemorySecretsRepo {
+        async fn get(&self, name: &str) -> Result<Option<SecretValue>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(name).cloned())
+        }
+        async fn list(&self) -> Result<Vec<SecretInfo>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .keys()
+                .map(|name| SecretInfo {
+                    name: name.clone(),
+                    updated_at: minihub_domain::time::now(),
+                })
+                .collect())
+        }
+        async fn put(&self, name: &str, value: &SecretValue) -> Result<SecretInfo, MiniHubError> {
+            self.store
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value.clone());
+            Ok(SecretInfo {
+                name: name.to_string(),
+                updated_at: minihub_domain::time::now(),
+            })
+        }
+        async fn delete(&self, name: &str) -> Result<bool, MiniHubError> {
+            Ok(self.store.lock().unwrap().remove(name).is_some())
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_stored_value_when_getting_secret() {
+        let service = SecretsService::new(InMemorySecretsRepo::default());
+
+        let info = service
+            .store("telegram.bot_token", SecretValue::new("123:abc"))
+            .await
+            .unwrap();
+
+        assert_eq!(info.name, "telegram.bot_token");
+        assert_eq!(
+            service.get("telegram.bot_token").await.unwrap().expose(),
+            "123:abc"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_name_or_empty_value() {
+        let service = SecretsService::new(InMemorySecretsRepo::default());
+
+        let err = service
+            .store("Bot Token", SecretValue::new("123:abc"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::InvalidSecretName(_))
+        ));
+        let err = service
+            .store("telegram.bot_token", SecretValue::new(""))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MiniHubError::Validation(ValidationError::EmptySecretValue)
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_secret_missing() {
+        let service = SecretsService::new(InMemorySecretsRepo::default());
+
+        assert!(matches!(
+            service.get("mqtt.password").await.unwrap_err(),
+            MiniHubError::NotFound(_)
+        ));
+        assert!(matches!(
+            service.delete("mqtt.password").await.unwrap_err(),
+            MiniHubError::NotFound(_)
+        ));
+    }
+}
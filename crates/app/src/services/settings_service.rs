@@ -0,0 +1,183 @@
+//! Settings service — use-cases for the hub-wide settings.
+
+use minihub_domain::error::{MiniHubError, ValidationError};
+use minihub_domain::settings::{self, Settings};
+
+use crate::ports::SettingsRepository;
+use crate::services::dashboard_service::DASHBOARD_NAMESPACE;
+
+/// Namespaces written through a dedicated service validating their values,
+/// readable but not writable here.
+pub const RESERVED_NAMESPACES: &[&str] = &[DASHBOARD_NAMESPACE];
+
+/// Application service reading and replacing the settings of a namespace.
+pub struct SettingsService<R> {
+    repo: R,
+}
+
+impl<R: SettingsRepository> SettingsService<R> {
+    /// Create a new service backed by the given settings repository.
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// The settings of `namespace`, empty until some are stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the namespace name is invalid,
+    /// or a storage error propagated from the repository.
+    pub async fn get_namespace(&self, namespace: &str) -> Result<Settings, MiniHubError> {
+        settings::validate_name(namespace)?;
+        self.repo.list(namespace).await
+    }
+
+    /// Replace the settings of `namespace`: keys missing from `values` are
+    /// removed, the others stored when their value changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if a name is invalid or the
+    /// namespace is one of [`RESERVED_NAMESPACES`], or a storage error
+    /// propagated from the repository.
+    #[tracing::instrument(skip(self, values), fields(keys = values.len()))]
+    pub async fn replace_namespace(
+        &self,
+        namespace: &str,
+        values: Settings,
+    ) -> Result<Settings, MiniHubError> {
+        settings::validate_name(namespace)?;
+        if RESERVED_NAMESPACES.contains(&namespace) {
+            return Err(ValidationError::ReservedSettingsNamespace(namespace.to_string()).into());
+        }
+        for key in values.keys() {
+            settings::validate_name(key)?;
+        }
+
+        let current = self.repo.list(namespace).await?;
+        for key in current.keys().filter(|key| !values.contains_key(*key)) {
+            self.repo.delete(namespace, key).await?;
+        }
+        for (key, value) in &values {
+            if current.get(key) != Some(value) {
+                self.repo.put(namespace, key, value.clone()).await?;
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemorySettingsRepo {
+        store: Mutex<BTreeMap<(String, String), Value>>,
+        writes: Mutex<usize>,
+    }
+
+    impl SettingsRepository for InMemorySettingsRepo {
+        async fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, MiniHubError> {
+            let id = (namespace.to_string(), key.to_string());
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn list(&self, namespace: &str) -> Result<Settings, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|((ns, _), _)| ns == namespace)
+                .map(|((_, key), value)| (key.clone(), value.clone()))
+                .collect())
+        }
+        async fn put(&self, namespace: &str, key: &str, value: Value) -> Result<(), MiniHubError> {
+            *self.writes.lock().unwrap() += 1;
+            let id = (namespace.to_string(), key.to_string());
+            self.store.lock().unwrap().insert(id, value);
+            Ok(())
+        }
+        async fn delete(&self, namespace: &str, key: &str) -> Result<(), MiniHubError> {
+            *self.writes.lock().unwrap() += 1;
+            let id = (namespace.to_string(), key.to_string());
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    fn settings(values: Value) -> Settings {
+        serde_json::from_value(values).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_return_empty_settings_for_unknown_namespace() {
+        let service = SettingsService::new(InMemorySettingsRepo::default());
+
+        assert!(service.get_namespace("mqtt").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_replace_namespace_removing_missing_keys() {
+        let service = SettingsService::new(InMemorySettingsRepo::default());
+        service
+            .replace_namespace("mqtt", settings(json!({ "host": "broker", "port": 1883 })))
+            .await
+            .unwrap();
+
+        service
+            .replace_namespace("mqtt", settings(json!({ "host": "broker.lan" })))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.get_namespace("mqtt").await.unwrap(),
+            settings(json!({ "host": "broker.lan" }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_skip_writes_of_unchanged_values() {
+        let service = SettingsService::new(InMemorySettingsRepo::default());
+        let values = settings(json!({ "host": "broker", "port": 1883 }));
+        service
+            .replace_namespace("mqtt", values.clone())
+            .await
+            .unwrap();
+
+        service.replace_namespace("mqtt", values).await.unwrap();
+
+        assert_eq!(*service.repo.writes.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_names_and_reserved_namespaces() {
+        let service = SettingsService::new(InMemorySettingsRepo::default());
+
+        let invalid_key = service
+            .replace_namespace("mqtt", settings(json!({ "Host": "broker" })))
+            .await;
+        let reserved = service
+            .replace_namespace(DASHBOARD_NAMESPACE, Settings::new())
+            .await;
+
+        assert!(matches!(
+            invalid_key,
+            Err(MiniHubError::Validation(
+                ValidationError::InvalidSettingsName(_)
+            ))
+        ));
+        assert!(matches!(
+            reserved,
+            Err(MiniHubError::Validation(
+                ValidationError::ReservedSettingsNamespace(_)
+            ))
+        ));
+        assert!(service.get_namespace("mqtt").await.unwrap().is_empty());
+    }
+}
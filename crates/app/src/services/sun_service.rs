@@ -0,0 +1,396 @@
+//! Sun service — keeps the `sun.sun` entity current and fires sun triggers.
+//!
+//! Every tick the [`SUN_ENTITY_ID`] entity, attached to the built-in hub
+//! device, is recomputed for the home [`Location`], so conditions and
+//! dashboards see the current elevation and next sunrise and sunset. Sunrises
+//! and sunsets that happened since the previous tick, shifted by the offset of
+//! each enabled `sun` trigger, are published as
+//! [`EventType::SunEvent`](minihub_domain::event::EventType::SunEvent) events
+//! for the automation engine to match.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use minihub_domain::automation::Trigger;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::sun::{self, Location, SUN_ENTITY_ID, SunEvent};
+use minihub_domain::time::{self, Timestamp};
+
+use crate::ports::{AutomationRepository, DeviceRepository, EntityRepository, EventPublisher};
+use crate::services::device_service::DeviceService;
+use crate::services::entity_service::EntityService;
+
+/// Interval between two updates of the sun entity.
+const TICK: Duration = Duration::from_mins(1);
+
+/// Application service tracking the sun at the home location.
+pub struct SunService<DR, ER, EP, AR> {
+    device_service: Arc<DeviceService<DR>>,
+    entity_service: Arc<EntityService<ER, EP>>,
+    automation_repo: AR,
+    publisher: EP,
+    location: Location,
+}
+
+impl<DR, ER, EP, AR> SunService<DR, ER, EP, AR>
+where
+    DR: DeviceRepository,
+    ER: EntityRepository,
+    EP: EventPublisher,
+    AR: AutomationRepository,
+{
+    /// Create a service for `location`, reading sun triggers from
+    /// `automation_repo` and publishing sun events to `publisher`.
+    pub fn new(
+        device_service: Arc<DeviceService<DR>>,
+        entity_service: Arc<EntityService<ER, EP>>,
+        automation_repo: AR,
+        publisher: EP,
+        location: Location,
+    ) -> Self {
+        Self {
+            device_service,
+            entity_service,
+            automation_repo,
+            publisher,
+            location,
+        }
+    }
+
+    /// Write the position of the sun at `now` to the sun entity, creating
+    /// it on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error propagated from the repositories.
+    #[tracing::instrument(skip(self))]
+    pub async fn update_entity(&self, now: Timestamp) -> Result<Entity, MiniHubError> {
+        let hub = self.device_service.ensure_hub_device().await?;
+        let entity = sun::to_entity(self.location, hub.id, now)?;
+        self.entity_service.upsert_entity(entity).await
+    }
+
+    /// Publish a sun event for every distinct event and offset of the
+    /// enabled `sun` triggers falling within `(after, until]`, returning how
+    /// many were published.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error when the automations cannot be listed, or an
+    /// error from the event publisher.
+    #[tracing::instrument(skip(self))]
+    pub async fn fire_due(
+        &self,
+        after: Timestamp,
+        until: Timestamp,
+    ) -> Result<usize, MiniHubError> {
+        let triggers: HashSet<(SunEvent, i32)> = self
+            .automation_repo
+            .get_enabled()
+            .await?
+            .into_iter()
+            .filter_map(|automation| match automation.trigger {
+                Trigger::Sun {
+                    event,
+                    offset_minutes,
+                } => Some((event, offset_minutes)),
+                _ => None,
+            })
+            .collect();
+        let mut count = 0;
+        for (event, offset_minutes) in triggers {
+            let due = sun::occurrences(self.location, event, offset_minutes, after, until);
+            for _ in due {
+                self.publisher
+                    .publish(sun::fired_event(event, offset_minutes))
+                    .await?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Update the sun entity and fire due sun triggers every minute,
+    /// forever. Failures are logged and do not stop the loop.
+    pub async fn run(&self) {
+        let mut last = time::now();
+        loop {
+            let now = time::now();
+            if let Err(err) = self.update_entity(now).await {
+                tracing::warn!(%err, entity_id = SUN_ENTITY_ID, "failed to update sun entity");
+            }
+            match self.fire_due(last, now).await {
+                Ok(count) if count > 0 => tracing::debug!(count, "fired sun triggers"),
+                Ok(_) => {}
+                Err(err) => tracing::warn!(%err, "failed to fire sun triggers"),
+            }
+            last = now;
+            tokio::time::sleep(TICK).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use chrono::TimeZone;
+    use minihub_domain::automation::{Action, Automation};
+    use minihub_domain::device::Device;
+    use minihub_domain::event::{Event, EventType};
+    use minihub_domain::id::{AreaId, AutomationId, DeviceId, EntityId};
+
+    use super::*;
+
+    const PARIS: Location = Location {
+        latitude: 48.8566,
+        longitude: 2.3522,
+    };
+
+    #[derive(Default)]
+    struct InMemoryEntityRepo {
+        store: Mutex<HashMap<EntityId, Entity>>,
+    }
+
+    impl EntityRepository for InMemoryEntityRepo {
+        async fn create(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+        async fn get_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn get_many(&self, ids: &[EntityId]) -> Result<Vec<Entity>, MiniHubError> {
+            let store = self.store.lock().unwrap();
+            Ok(ids.iter().filter_map(|id| store.get(id).cloned()).collect())
+        }
+        async fn get_all(&self) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(self.store.lock().unwrap().values().cloned().collect())
+        }
+        async fn find_by_device_id(
+            &self,
+            _device_id: DeviceId,
+        ) -> Result<Vec<Entity>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_entity_id(&self, entity_id: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|entity| entity.entity_id == entity_id)
+                .cloned())
+        }
+        async fn update(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+            self.store.lock().unwrap().insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+        async fn delete(&self, id: EntityId) -> Result<(), MiniHubError> {
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_by_alias(&self, _alias: &str) -> Result<Option<Entity>, MiniHubError> {
+            Ok(None)
+        }
+        async fn add_alias(&self, _id: EntityId, _alias: &str) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeviceRepo {
+        store: Mutex<HashMap<DeviceId, Device>>,
+    }
+
+    impl DeviceRepository for InMemoryDeviceRepo {
+        async fn create(&self, device: Device) -> Result<Device, MiniHubError> {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            Ok(device)
+        }
+        async fn get_by_id(&self, id: DeviceId) -> Result<Option<Device>, MiniHubError> {
+            Ok(self.store.lock().unwrap().get(&id).cloned())
+        }
+        async fn get_all(&self) -> Result<Vec<Device>, MiniHubError> {
+            Ok(self.store.lock().unwrap().values().cloned().collect())
+        }
+        async fn find_by_area(&self, _area_id: AreaId) -> Result<Vec<Device>, MiniHubError> {
+            Ok(vec![])
+        }
+        async fn find_by_integration(
+            &self,
+            integration: &str,
+        ) -> Result<Vec<Device>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|device| device.integration == integration)
+                .cloned()
+                .collect())
+        }
+        async fn find_by_integration_unique_id(
+            &self,
+            integration: &str,
+            unique_id: &str,
+        ) -> Result<Option<Device>, MiniHubError> {
+            Ok(self
+                .store
+                .lock()
+                .unwrap()
+                .values()
+                .find(|device| device.integration == integration && device.unique_id == unique_id)
+                .cloned())
+        }
+        async fn update(&self, device: Device) -> Result<Device, MiniHubError> {
+            self.store.lock().unwrap().insert(device.id, device.clone());
+            Ok(device)
+        }
+        async fn delete(&self, id: DeviceId) -> Result<(), MiniHubError> {
+            self.store.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    struct FixedAutomationRepo(Vec<Automation>);
+
+    impl AutomationRepository for FixedAutomationRepo {
+        async fn create(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+        async fn get_by_id(&self, id: AutomationId) -> Result<Option<Automation>, MiniHubError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|automation| automation.id == id)
+                .cloned())
+        }
+        async fn get_all(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self.0.clone())
+        }
+        async fn get_enabled(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self.0.iter().filter(|a| a.enabled).cloned().collect())
+        }
+        async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+        async fn record_triggered(
+            &self,
+            _id: AutomationId,
+            _triggered_at: Timestamp,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+        async fn delete(&self, _id: AutomationId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    type Service =
+        SunService<InMemoryDeviceRepo, InMemoryEntityRepo, Arc<SpyPublisher>, FixedAutomationRepo>;
+
+    fn sun_automation(event: SunEvent, offset_minutes: i32, enabled: bool) -> Automation {
+        Automation::builder()
+            .name("Porch light")
+            .enabled(enabled)
+            .trigger(Trigger::Sun {
+                event,
+                offset_minutes,
+            })
+            .action(Action::CallService {
+                entity_id: EntityId::new(),
+                service: "turn_on".to_string(),
+                data: serde_json::json!({}),
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn setup(automations: Vec<Automation>) -> (Service, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = SunService::new(
+            Arc::new(DeviceService::new(InMemoryDeviceRepo::default())),
+            Arc::new(EntityService::new(
+                InMemoryEntityRepo::default(),
+                Arc::clone(&publisher),
+            )),
+            FixedAutomationRepo(automations),
+            Arc::clone(&publisher),
+            PARIS,
+        );
+        (svc, publisher)
+    }
+
+    fn at(hour: u32, minute: u32) -> Timestamp {
+        chrono::Utc
+            .with_ymd_and_hms(2026, 6, 21, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn sun_events(publisher: &SpyPublisher) -> Vec<Event> {
+        publisher
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event_type == EventType::SunEvent)
+            .cloned()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_create_then_update_sun_entity() {
+        let (svc, _) = setup(vec![]);
+
+        let night = svc.update_entity(at(0, 0)).await.unwrap();
+        let noon = svc.update_entity(at(12, 0)).await.unwrap();
+
+        assert_eq!(night.id, noon.id);
+        assert_eq!(sun::is_above_horizon(&night), Some(false));
+        assert_eq!(sun::is_above_horizon(&noon), Some(true));
+    }
+
+    #[tokio::test]
+    async fn should_fire_each_enabled_sun_trigger_once_when_due() {
+        let (svc, publisher) = setup(vec![
+            sun_automation(SunEvent::Sunset, -30, true),
+            sun_automation(SunEvent::Sunset, -30, true),
+            sun_automation(SunEvent::Sunset, 0, false),
+            sun_automation(SunEvent::Sunrise, 0, true),
+        ]);
+
+        let count = svc.fire_due(at(19, 0), at(19, 45)).await.unwrap();
+
+        let events = sun_events(&publisher);
+        assert_eq!(count, 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data["event"], "sunset");
+        assert_eq!(events[0].data["offset_minutes"], -30);
+    }
+
+    #[tokio::test]
+    async fn should_not_fire_when_no_sun_event_is_due() {
+        let (svc, publisher) = setup(vec![sun_automation(SunEvent::Sunrise, 0, true)]);
+
+        let count = svc.fire_due(at(12, 0), at(12, 1)).await.unwrap();
+
+        assert_eq!(count, 0);
+        assert!(sun_events(&publisher).is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! Update throttle — drops high-frequency sensor updates before they reach
+//! storage.
+//!
+//! A chatty sensor re-reporting the same readings every couple of seconds
+//! would otherwise flood the event store and the history table. An update is
+//! dropped when it only moves its numeric state or numeric attributes and
+//! either arrives within [`ThrottleConfig::min_interval`] of the last
+//! forwarded update of the entity, or moves none of them by at least
+//! [`ThrottleConfig::min_delta`]. Other state changes, new or removed
+//! attributes and non-numeric changes always go through.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use minihub_domain::entity::{AttributeValue, Entity, EntityState};
+
+/// Throttling thresholds, both disabled by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThrottleConfig {
+    /// Minimum time between two forwarded updates of the same entity.
+    pub min_interval: Duration,
+    /// Minimum change of a numeric attribute for an update to be forwarded.
+    pub min_delta: f64,
+}
+
+impl ThrottleConfig {
+    fn is_disabled(&self) -> bool {
+        self.min_interval.is_zero() && self.min_delta <= 0.0
+    }
+}
+
+/// Last update forwarded for an entity.
+struct Forwarded {
+    at: Instant,
+    state: EntityState,
+    attributes: HashMap<String, AttributeValue>,
+}
+
+/// Per-entity filter applying a [`ThrottleConfig`], keyed by `entity_id`.
+#[derive(Default)]
+pub struct UpdateThrottle {
+    config: ThrottleConfig,
+    forwarded: Mutex<HashMap<String, Forwarded>>,
+}
+
+impl UpdateThrottle {
+    /// Create a throttle with no update forwarded yet.
+    #[must_use]
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            forwarded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `entity` should be stored, remembering it as the last
+    /// forwarded update when it is.
+    pub fn admit(&self, entity: &Entity, now: Instant) -> bool {
+        if self.config.is_disabled() {
+            return true;
+        }
+        let mut forwarded = self
+            .forwarded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(last) = forwarded.get(&entity.entity_id)
+            && self.is_minor(last, entity, now)
+        {
+            return false;
+        }
+        forwarded.insert(
+            entity.entity_id.clone(),
+            Forwarded {
+                at: now,
+                state: entity.state.clone(),
+                attributes: entity.attributes.clone(),
+            },
+        );
+        true
+    }
+
+    /// Whether `entity` only carries numeric changes too early or too small
+    /// to be worth storing.
+    fn is_minor(&self, last: &Forwarded, entity: &Entity, now: Instant) -> bool {
+        if last.attributes.len() != entity.attributes.len() {
+            return false;
+        }
+        let mut max_delta = match (last.state.as_f64(), entity.state.as_f64()) {
+            (Some(previous), Some(current)) => (current - previous).abs(),
+            _ if last.state != entity.state => return false,
+            _ => 0.0_f64,
+        };
+        for (key, value) in &entity.attributes {
+            let Some(previous) = last.attributes.get(key) else {
+                return false;
+            };
+            match (as_number(previous), as_number(value)) {
+                (Some(previous), Some(current)) => {
+                    max_delta = max_delta.max((current - previous).abs());
+                }
+                _ if previous != value => return false,
+                _ => {}
+            }
+        }
+        now.duration_since(last.at) < self.config.min_interval || max_delta < self.config.min_delta
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn as_number(value: &AttributeValue) -> Option<f64> {
+    match value {
+        AttributeValue::Int(number) => Some(*number as f64),
+        AttributeValue::Float(number) => Some(*number),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(temperature: f64) -> Entity {
+        Entity::builder()
+            .entity_id("sensor.ble_temperature")
+            .friendly_name("Temperature")
+            .state(EntityState::On)
+            .attribute("temperature", AttributeValue::Float(temperature))
+            .build()
+            .unwrap()
+    }
+
+    fn throttle(min_interval_secs: u64, min_delta: f64) -> UpdateThrottle {
+        UpdateThrottle::new(ThrottleConfig {
+            min_interval: Duration::from_secs(min_interval_secs),
+            min_delta,
+        })
+    }
+
+    #[test]
+    fn should_admit_every_update_when_disabled() {
+        let throttle = UpdateThrottle::default();
+        let now = Instant::now();
+
+        assert!(throttle.admit(&reading(21.0), now));
+        assert!(throttle.admit(&reading(21.0), now));
+    }
+
+    #[test]
+    fn should_drop_numeric_update_within_min_interval() {
+        let throttle = throttle(60, 0.0);
+        let start = Instant::now();
+
+        assert!(throttle.admit(&reading(21.0), start));
+        assert!(!throttle.admit(&reading(22.0), start + Duration::from_secs(2)));
+        assert!(throttle.admit(&reading(22.0), start + Duration::from_mins(1)));
+    }
+
+    #[test]
+    fn should_drop_numeric_update_below_min_delta() {
+        let throttle = throttle(0, 0.5);
+        let start = Instant::now();
+
+        assert!(throttle.admit(&reading(21.0), start));
+        assert!(!throttle.admit(&reading(21.2), start));
+        assert!(!throttle.admit(&reading(21.4), start));
+        assert!(throttle.admit(&reading(21.5), start));
+    }
+
+    #[test]
+    fn should_admit_state_change_within_min_interval() {
+        let throttle = throttle(60, 0.0);
+        let start = Instant::now();
+        let mut unavailable = reading(21.0);
+        unavailable.state = EntityState::Unavailable;
+
+        assert!(throttle.admit(&reading(21.0), start));
+        assert!(throttle.admit(&unavailable, start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn should_drop_numeric_state_change_below_min_delta() {
+        let throttle = throttle(0, 0.5);
+        let start = Instant::now();
+        let measured = |value| {
+            let mut entity = reading(21.0);
+            entity.state = EntityState::Numeric(value);
+            entity
+        };
+
+        assert!(throttle.admit(&measured(21.0), start));
+        assert!(!throttle.admit(&measured(21.2), start));
+        assert!(throttle.admit(&measured(21.5), start));
+    }
+
+    #[test]
+    fn should_admit_non_numeric_or_new_attribute_within_min_interval() {
+        let throttle = throttle(60, 0.0);
+        let start = Instant::now();
+        let mut labelled = reading(21.0);
+        labelled.set_attribute("label".to_string(), AttributeValue::String("a".into()));
+        let mut relabelled = reading(21.0);
+        relabelled.set_attribute("label".to_string(), AttributeValue::String("b".into()));
+
+        assert!(throttle.admit(&reading(21.0), start));
+        assert!(throttle.admit(&labelled, start));
+        assert!(throttle.admit(&relabelled, start));
+    }
+
+    #[test]
+    fn should_throttle_each_entity_independently() {
+        let throttle = throttle(60, 0.0);
+        let start = Instant::now();
+        let mut other = reading(21.0);
+        other.entity_id = "sensor.ble_humidity".to_string();
+
+        assert!(throttle.admit(&reading(21.0), start));
+        assert!(throttle.admit(&other, start));
+    }
+}
@@ -0,0 +1,208 @@
+//! Webhook service — receives inbound webhook calls.
+//!
+//! A call to a webhook id is published as a
+//! [`WebhookReceived`](minihub_domain::event::EventType::WebhookReceived)
+//! event, which automations with a matching `webhook` trigger react to.
+//! Calls to an id no enabled automation listens on are rejected before
+//! anything is published or stored, so anonymous callers cannot fill the
+//! event log.
+
+use std::sync::Arc;
+
+use minihub_domain::error::{MiniHubError, NotFoundError};
+use minihub_domain::event::Event;
+use minihub_domain::secret::constant_time_eq;
+use minihub_domain::webhook;
+
+use crate::ports::{AutomationRepository, EventPublisher};
+use crate::services::automation_service::AutomationService;
+
+/// Application service for inbound webhook calls.
+pub struct WebhookService<AUR, EP> {
+    automation_service: Arc<AutomationService<AUR>>,
+    publisher: EP,
+}
+
+impl<AUR, EP> WebhookService<AUR, EP>
+where
+    AUR: AutomationRepository,
+    EP: EventPublisher,
+{
+    /// Create a new service checking calls against the shared automation
+    /// service and publishing them to `publisher`.
+    pub fn new(automation_service: Arc<AutomationService<AUR>>, publisher: EP) -> Self {
+        Self {
+            automation_service,
+            publisher,
+        }
+    }
+
+    /// Publish the `WebhookReceived` event of an inbound call to
+    /// `webhook_id` carrying `body`, so automations with a matching webhook
+    /// trigger run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if `webhook_id` is not a valid
+    /// webhook id, [`MiniHubError::NotFound`] when no enabled automation
+    /// has a webhook trigger with that id, or an error from the event
+    /// publisher.
+    #[tracing::instrument(skip(self, body))]
+    pub async fn receive(
+        &self,
+        webhook_id: &str,
+        body: serde_json::Value,
+    ) -> Result<Event, MiniHubError> {
+        webhook::validate_id(webhook_id)?;
+        let listened = self
+            .automation_service
+            .webhook_ids()
+            .await?
+            .iter()
+            .any(|id| constant_time_eq(id.as_bytes(), webhook_id.as_bytes()));
+        if !listened {
+            return Err(NotFoundError {
+                entity: "Webhook",
+                id: webhook_id.to_string(),
+            }
+            .into());
+        }
+        let event = webhook::received_event(webhook_id, body);
+        self.publisher.publish(event.clone()).await?;
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Mutex;
+
+    use minihub_domain::automation::{Action, Automation, Trigger};
+    use minihub_domain::event::EventType;
+    use minihub_domain::id::AutomationId;
+    use minihub_domain::time::Timestamp;
+
+    use super::*;
+
+    const WEBHOOK_ID: &str = "doorbell-7f3c9a1e52";
+
+    struct StubAutomationRepo {
+        automations: Vec<Automation>,
+    }
+
+    impl AutomationRepository for StubAutomationRepo {
+        async fn create(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+
+        async fn get_by_id(&self, _id: AutomationId) -> Result<Option<Automation>, MiniHubError> {
+            Ok(None)
+        }
+
+        async fn get_all(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self.automations.clone())
+        }
+
+        async fn get_enabled(&self) -> Result<Vec<Automation>, MiniHubError> {
+            Ok(self
+                .automations
+                .iter()
+                .filter(|automation| automation.enabled)
+                .cloned()
+                .collect())
+        }
+
+        async fn update(&self, automation: Automation) -> Result<Automation, MiniHubError> {
+            Ok(automation)
+        }
+
+        async fn record_triggered(
+            &self,
+            _id: AutomationId,
+            _triggered_at: Timestamp,
+        ) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+
+        async fn delete(&self, _id: AutomationId) -> Result<(), MiniHubError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SpyPublisher {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventPublisher for SpyPublisher {
+        fn publish(&self, event: Event) -> impl Future<Output = Result<(), MiniHubError>> + Send {
+            self.events.lock().unwrap().push(event);
+            async { Ok(()) }
+        }
+    }
+
+    type Service = WebhookService<StubAutomationRepo, Arc<SpyPublisher>>;
+
+    fn webhook_automation(enabled: bool) -> Automation {
+        let mut automation = Automation::builder()
+            .name("Doorbell")
+            .trigger(Trigger::Webhook {
+                webhook_id: WEBHOOK_ID.to_string(),
+            })
+            .action(Action::Delay { seconds: 1 })
+            .build()
+            .unwrap();
+        automation.enabled = enabled;
+        automation
+    }
+
+    fn setup(automations: Vec<Automation>) -> (Service, Arc<SpyPublisher>) {
+        let publisher = Arc::new(SpyPublisher::default());
+        let svc = WebhookService::new(
+            Arc::new(AutomationService::new(StubAutomationRepo { automations })),
+            Arc::clone(&publisher),
+        );
+        (svc, publisher)
+    }
+
+    #[tokio::test]
+    async fn should_publish_webhook_received_when_an_automation_listens() {
+        let (svc, publisher) = setup(vec![webhook_automation(true)]);
+
+        let event = svc
+            .receive(WEBHOOK_ID, serde_json::json!({"button": 1}))
+            .await
+            .unwrap();
+
+        let events = publisher.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, event.id);
+        assert_eq!(events[0].event_type, EventType::WebhookReceived);
+        assert_eq!(events[0].data["body"]["button"], 1);
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_no_enabled_automation_listens() {
+        let (svc, publisher) = setup(vec![webhook_automation(false)]);
+
+        let disabled = svc.receive(WEBHOOK_ID, serde_json::Value::Null).await;
+        let unknown = svc
+            .receive("unknown-4b1d8e6a2c", serde_json::Value::Null)
+            .await;
+
+        assert!(matches!(disabled, Err(MiniHubError::NotFound(_))));
+        assert!(matches!(unknown, Err(MiniHubError::NotFound(_))));
+        assert!(publisher.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_webhook_with_invalid_id() {
+        let (svc, publisher) = setup(vec![webhook_automation(true)]);
+
+        let result = svc.receive("front door", serde_json::Value::Null).await;
+
+        assert!(matches!(result, Err(MiniHubError::Validation(_))));
+        assert!(publisher.events.lock().unwrap().is_empty());
+    }
+}
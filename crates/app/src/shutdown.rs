@@ -0,0 +1,228 @@
+//! Graceful shutdown of the daemon's background tasks.
+//!
+//! The composition root spawns its workers and integration supervisors
+//! through a [`ShutdownCoordinator`]. On shutdown, the coordinator raises
+//! the [`ShutdownSignal`] every task can watch, gives the tasks a bounded
+//! window to flush their work and tear their integration down, then aborts
+//! the ones still running.
+//!
+//! Tasks spawned with [`ShutdownCoordinator::spawn`] are simply stopped at
+//! their next await point once shutdown is requested; tasks spawned with
+//! [`ShutdownCoordinator::spawn_graceful`] receive the signal and finish on
+//! their own.
+
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use futures::FutureExt as _;
+use tokio::sync::{broadcast::error::RecvError, watch};
+use tokio::task::JoinHandle;
+
+use minihub_domain::event::Event;
+
+use crate::ports::EventSubscription;
+
+/// Raised once the daemon starts shutting down.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+impl ShutdownSignal {
+    /// Whether shutdown was requested.
+    #[must_use]
+    pub fn is_requested(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Wait until shutdown is requested.
+    ///
+    /// Never completes when the coordinator is dropped without requesting
+    /// shutdown.
+    pub async fn requested(&mut self) {
+        if self
+            .receiver
+            .wait_for(|requested| *requested)
+            .await
+            .is_err()
+        {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// The next event of `subscription` until shutdown is requested, then
+    /// only the events already queued, and `None` once they are drained.
+    ///
+    /// Lets event workers flush what was published before the shutdown
+    /// instead of dropping it.
+    pub async fn recv<S: EventSubscription>(
+        &mut self,
+        subscription: &mut S,
+    ) -> Option<Result<Event, RecvError>> {
+        if !self.is_requested() {
+            tokio::select! {
+                biased;
+                result = subscription.recv() => return Some(result),
+                () = self.requested() => {}
+            }
+        }
+        subscription.recv().now_or_never()
+    }
+}
+
+/// Registry of the background tasks to stop on shutdown.
+#[derive(Debug)]
+pub struct ShutdownCoordinator {
+    sender: watch::Sender<bool>,
+    tasks: Mutex<Vec<(String, JoinHandle<()>)>>,
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    /// Create a coordinator with no task registered.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            sender: watch::Sender::new(false),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The signal raised by [`shutdown`](Self::shutdown).
+    #[must_use]
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Spawn `task`, dropped at its next await point once shutdown is
+    /// requested.
+    pub fn spawn<F>(&self, name: impl Into<String>, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_graceful(name, |mut signal| async move {
+            tokio::select! {
+                () = task => {}
+                () = signal.requested() => {}
+            }
+        });
+    }
+
+    /// Spawn the task built by `make` from the [`ShutdownSignal`]; it is
+    /// expected to return on its own once the signal is raised.
+    pub fn spawn_graceful<F, Fut>(&self, name: impl Into<String>, make: F)
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(make(self.signal()));
+        self.tasks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name.into(), handle));
+    }
+
+    /// Raise the shutdown signal and wait up to `grace` for every task to
+    /// return, aborting the ones still running.
+    ///
+    /// Returns the names of the aborted tasks.
+    pub async fn shutdown(&self, grace: Duration) -> Vec<String> {
+        self.sender.send_replace(true);
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap_or_else(PoisonError::into_inner));
+        let deadline = tokio::time::Instant::now() + grace;
+
+        let mut aborted = Vec::new();
+        for (name, mut handle) in tasks {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(())) => tracing::debug!(task = %name, "task stopped"),
+                Ok(Err(err)) => tracing::warn!(%err, task = %name, "task failed while stopping"),
+                Err(_) => {
+                    handle.abort();
+                    tracing::warn!(task = %name, "task did not stop in time, aborted");
+                    aborted.push(name);
+                }
+            }
+        }
+        aborted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use minihub_domain::event::EventType;
+    use tokio::sync::broadcast;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_stop_plain_tasks_at_shutdown() {
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.spawn("forever", std::future::pending());
+
+        let aborted = coordinator.shutdown(Duration::from_secs(1)).await;
+
+        assert!(aborted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_let_graceful_tasks_finish_their_work() {
+        let coordinator = ShutdownCoordinator::new();
+        let flushed = Arc::new(AtomicBool::new(false));
+        coordinator.spawn_graceful("worker", {
+            let flushed = Arc::clone(&flushed);
+            |mut signal| async move {
+                signal.requested().await;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                flushed.store(true, Ordering::SeqCst);
+            }
+        });
+
+        let aborted = coordinator.shutdown(Duration::from_secs(1)).await;
+
+        assert!(aborted.is_empty());
+        assert!(flushed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn should_abort_tasks_exceeding_the_grace_period() {
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.spawn_graceful("stuck", |_| std::future::pending());
+
+        let aborted = coordinator.shutdown(Duration::from_millis(10)).await;
+
+        assert_eq!(aborted, vec!["stuck".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn should_drain_queued_events_after_shutdown() {
+        let coordinator = ShutdownCoordinator::new();
+        let mut signal = coordinator.signal();
+        let (sender, mut receiver) = broadcast::channel(8);
+        for _ in 0..2 {
+            sender
+                .send(Event::new(
+                    EventType::StateChanged,
+                    None,
+                    serde_json::Value::Null,
+                ))
+                .unwrap();
+        }
+
+        let _ = coordinator.shutdown(Duration::ZERO).await;
+
+        assert!(signal.recv(&mut receiver).await.is_some_and(|r| r.is_ok()));
+        assert!(signal.recv(&mut receiver).await.is_some_and(|r| r.is_ok()));
+        assert!(signal.recv(&mut receiver).await.is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Test doubles of the ports, shared with the adapter crates through the
+//! `test-util` feature.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::broadcast;
+
+use minihub_domain::device::Device;
+use minihub_domain::entity::Entity;
+use minihub_domain::error::MiniHubError;
+use minihub_domain::event::Event;
+use minihub_domain::id::EntityId;
+
+use crate::ports::integration::IntegrationContext;
+
+/// In-memory [`IntegrationContext`] recording what an integration stores
+/// and publishes.
+///
+/// Clones share their state. Published events are recorded and broadcast
+/// to the subscribers, so background loops can be driven end to end;
+/// [`send`](Self::send) broadcasts an event without recording it, as if
+/// another component published it.
+#[derive(Clone)]
+pub struct FakeContext {
+    tx: broadcast::Sender<Event>,
+    entities: Arc<Mutex<Vec<Entity>>>,
+    published: Arc<Mutex<Vec<Event>>>,
+}
+
+impl Default for FakeContext {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(16);
+        Self {
+            tx,
+            entities: Arc::default(),
+            published: Arc::default(),
+        }
+    }
+}
+
+impl FakeContext {
+    /// A context storing no entity yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A context already storing `entities`, as persisted by earlier runs.
+    #[must_use]
+    pub fn with_entities(entities: Vec<Entity>) -> Self {
+        let ctx = Self::default();
+        *ctx.entities.lock().unwrap_or_else(PoisonError::into_inner) = entities;
+        ctx
+    }
+
+    /// Every entity given or upserted, in order; an entity upserted twice
+    /// appears twice.
+    #[must_use]
+    pub fn entities(&self) -> Vec<Entity> {
+        self.entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Every event published through the context, in order.
+    #[must_use]
+    pub fn published(&self) -> Vec<Event> {
+        self.published
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Broadcast `event` to the subscribers without recording it.
+    pub fn send(&self, event: Event) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Latest stored version of the entity matching `matches`.
+    fn find(&self, matches: impl Fn(&Entity) -> bool) -> Option<Entity> {
+        self.entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .rev()
+            .find(|entity| matches(entity))
+            .cloned()
+    }
+}
+
+impl IntegrationContext for FakeContext {
+    async fn upsert_device(&self, device: Device) -> Result<Device, MiniHubError> {
+        Ok(device)
+    }
+
+    async fn upsert_entity(&self, entity: Entity) -> Result<Entity, MiniHubError> {
+        self.entities
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(entity.clone());
+        Ok(entity)
+    }
+
+    async fn publish(&self, event: Event) -> Result<(), MiniHubError> {
+        self.published
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event.clone());
+        let _ = self.tx.send(event);
+        Ok(())
+    }
+
+    async fn find_entity_by_id(&self, id: EntityId) -> Result<Option<Entity>, MiniHubError> {
+        Ok(self.find(|entity| entity.id == id))
+    }
+
+    async fn find_entity_by_entity_id(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<Entity>, MiniHubError> {
+        Ok(self.find(|entity| entity.entity_id == entity_id))
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+}
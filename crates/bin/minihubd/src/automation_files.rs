@@ -0,0 +1,86 @@
+//! Automation definitions read from the configured `automations_dir` and
+//! reconciled with the stored automations at startup.
+
+use std::io;
+use std::path::Path;
+
+use minihub_app::automation_import::{self, AutomationFiles};
+use minihub_app::ports::AutomationRepository;
+use minihub_domain::error::MiniHubError;
+
+/// Extension of automation definition files.
+const EXTENSION: &str = "yaml";
+
+/// Read every `*.yaml` file of `dir` as `(source_key, content)`, the key
+/// being the file name without its extension. Sorted by key.
+fn read(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != EXTENSION) {
+            continue;
+        }
+        let Some(source_key) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        files.push((source_key.to_string(), std::fs::read_to_string(&path)?));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Import the automations defined in `dir` into `repo`.
+///
+/// A directory that cannot be read is logged and leaves the stored
+/// automations untouched, as does a file that fails to parse.
+///
+/// # Errors
+///
+/// Returns a storage error propagated from the repository.
+pub async fn import<R: AutomationRepository>(dir: &Path, repo: &R) -> Result<(), MiniHubError> {
+    let files = match read(dir) {
+        Ok(files) => files,
+        Err(err) => {
+            tracing::error!(%err, dir = %dir.display(), "failed to read automation files");
+            return Ok(());
+        }
+    };
+    let files = AutomationFiles::parse(files);
+    for err in files.errors() {
+        tracing::error!(%err, "automation file ignored");
+    }
+    let report = automation_import::reconcile(repo, files).await?;
+    tracing::info!(
+        dir = %dir.display(),
+        created = ?report.created,
+        updated = ?report.updated,
+        removed = ?report.removed,
+        "automation files imported"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_yaml_files_keyed_by_file_stem() {
+        let dir = std::env::temp_dir().join(format!("minihub_automations_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.yaml")).unwrap();
+        std::fs::write(dir.join("morning.yaml"), "name: Morning").unwrap();
+        std::fs::write(dir.join("evening.yaml"), "name: Evening").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an automation").unwrap();
+
+        let files = read(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            files,
+            [
+                ("evening".to_string(), "name: Evening".to_string()),
+                ("morning".to_string(), "name: Morning".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Database backups — snapshots taken at the configured interval and on
+//! `POST /api/system/backup`, rotated so only the most recent ones are kept.
+
+use std::time::Duration;
+
+use minihub_adapter_storage_sqlite_sqlx::{BackupRotation, Database};
+use minihub_app::backup::{BackupError, BackupReport, BackupRequests};
+
+/// Take a snapshot of `db` every `interval`, if any, and for every request
+/// received on `requests`, until every handle is dropped.
+pub async fn run(
+    db: Database,
+    rotation: BackupRotation,
+    interval: Option<Duration>,
+    mut requests: BackupRequests,
+) {
+    let mut ticks = interval.map(|period| {
+        let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticks
+    });
+    loop {
+        let scheduled = async {
+            match ticks.as_mut() {
+                Some(ticks) => ticks.tick().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = scheduled => {
+                let _ = snapshot(&db, &rotation).await;
+            }
+            request = requests.recv() => match request {
+                Some(request) => request.respond(snapshot(&db, &rotation).await),
+                None => break,
+            },
+        }
+    }
+}
+
+/// Write one snapshot, logging the outcome.
+async fn snapshot(db: &Database, rotation: &BackupRotation) -> Result<BackupReport, BackupError> {
+    match rotation.run(db).await {
+        Ok(file) => {
+            tracing::info!(
+                path = %file.path.display(),
+                size_bytes = file.size_bytes,
+                pruned = file.pruned.len(),
+                "database backup written"
+            );
+            Ok(BackupReport {
+                path: file.path,
+                size_bytes: file.size_bytes,
+                created_at: file.created_at,
+                pruned: file.pruned,
+            })
+        }
+        Err(err) => {
+            tracing::warn!(%err, dir = %rotation.dir().display(), "database backup failed");
+            Err(BackupError::Failed(err.to_string()))
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Command-line interface — `minihubd [COMMAND]`.
+//!
+//! Running without a command serves the hub, so existing deployments keep
+//! working. The other commands run one maintenance task against the
+//! configured database and exit, which suits containers and scripts.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use minihub_domain::role::Role;
+
+/// minihub home automation daemon.
+#[derive(Debug, Parser)]
+#[command(name = "minihubd", version)]
+pub struct Cli {
+    /// Print a commented configuration listing every field with its
+    /// default, then exit.
+    #[arg(long)]
+    pub print_default_config: bool,
+    /// Task to run; serves the hub when omitted.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// A `minihubd` subcommand.
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Serve the API, the dashboard and the integrations (default).
+    Serve,
+    /// Run the pending database migrations, then exit.
+    Migrate,
+    /// Validate the configuration and print the effective settings,
+    /// environment overrides included.
+    CheckConfig,
+    /// Write a consistent copy of the database to PATH. Safe to run while
+    /// the hub is serving.
+    Export {
+        /// Destination file; must not exist yet.
+        path: PathBuf,
+    },
+    /// Create the configured database from a copy written by `export`.
+    /// Refuses to overwrite an existing database.
+    Import {
+        /// Copy to restore.
+        path: PathBuf,
+    },
+    /// Print a random API token, and the `[[server.tokens]]` entry to add
+    /// to the configuration for the API to accept it.
+    CreateToken {
+        /// Name the token's writes are attributed to in the audit log.
+        name: String,
+        /// `viewer`, `controller` or `admin`.
+        #[arg(long, default_value = "viewer")]
+        role: Role,
+    },
+}
+
+impl Cli {
+    /// The command to run, [`Command::Serve`] when none was given.
+    #[must_use]
+    pub fn command(self) -> Command {
+        self.command.unwrap_or(Command::Serve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("minihubd").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn should_serve_when_no_command_is_given() {
+        assert_eq!(parse(&[]).command(), Command::Serve);
+    }
+
+    #[test]
+    fn should_parse_maintenance_commands() {
+        assert_eq!(parse(&["migrate"]).command(), Command::Migrate);
+        assert_eq!(parse(&["check-config"]).command(), Command::CheckConfig);
+        assert_eq!(
+            parse(&["export", "backup.db"]).command(),
+            Command::Export {
+                path: PathBuf::from("backup.db")
+            }
+        );
+    }
+
+    #[test]
+    fn should_parse_create_token_command() {
+        assert_eq!(
+            parse(&["create-token", "panel", "--role", "controller"]).command(),
+            Command::CreateToken {
+                name: "panel".to_string(),
+                role: Role::Controller,
+            }
+        );
+        assert_eq!(
+            parse(&["create-token", "grafana"]).command(),
+            Command::CreateToken {
+                name: "grafana".to_string(),
+                role: Role::Viewer,
+            }
+        );
+        assert!(Cli::try_parse_from(["minihubd", "create-token", "x", "--role", "root"]).is_err());
+    }
+
+    #[test]
+    fn should_keep_print_default_config_flag() {
+        assert!(parse(&["--print-default-config"]).print_default_config);
+    }
+
+    #[test]
+    fn should_reject_import_without_path() {
+        assert!(Cli::try_parse_from(["minihubd", "import"]).is_err());
+    }
+}
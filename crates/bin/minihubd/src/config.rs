@@ -1,15 +1,51 @@
 //! Configuration loading — TOML file with environment variable overrides.
 //!
-//! Looks for `minihub.toml` in the working directory. Every field has a
-//! sensible default so the file is optional. Environment variables take
-//! precedence over file values.
+//! Looks for `minihub.toml` in the data directory when `MINIHUB_DATA_DIR`
+//! is set, otherwise in the working directory. Every field has a sensible
+//! default so the file is optional. Environment variables take precedence
+//! over file values.
+//!
+//! The data directory roots all persistent state: unless configured
+//! otherwise, the database lives at `{data_dir}/minihub.db` and its
+//! snapshots under `{data_dir}/backups`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use minihub_adapter_mqtt::{BufferConfig, TopicClasses};
+use minihub_adapter_storage_sqlite_sqlx::{Config as DbConfig, JournalMode, Synchronous};
+use minihub_domain::role::Role;
+use minihub_domain::secret;
+use minihub_domain::sun::Location;
+
+/// Name of the configuration file.
+const CONFIG_FILE: &str = "minihub.toml";
+
+/// Name of the `SQLite` database file created inside the data directory.
+const DATABASE_FILE: &str = "minihub.db";
+
+/// Name of the directory holding database snapshots, inside the data
+/// directory or the working directory.
+const BACKUP_DIR: &str = "backups";
+
+/// Name of the file holding the key encrypting secrets, inside the data
+/// directory or the working directory.
+const SECRET_KEY_FILE: &str = "secret.key";
 
-use serde::Deserialize;
+/// Fully commented configuration listing every field with its default,
+/// printed by `minihubd --print-default-config`.
+pub const DEFAULT_CONFIG: &str = include_str!("../default-config.toml");
 
 /// Top-level configuration.
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
+    /// Directory holding all persistent state (database, runtime config).
+    pub data_dir: Option<String>,
+    /// Directory of `*.yaml` automation definitions imported at startup.
+    pub automations_dir: Option<String>,
     /// HTTP server settings.
     pub server: ServerConfig,
     /// Database settings.
@@ -20,12 +56,20 @@ pub struct Config {
     pub integrations: IntegrationsConfig,
     /// Entity history retention settings.
     pub history: HistoryConfig,
+    /// Event dispatch settings.
+    pub events: EventsConfig,
+    /// Notification delivery settings.
+    pub notifications: NotificationsConfig,
+    /// Home coordinates, used to track the sun.
+    pub location: LocationConfig,
+    /// Encryption of the secrets stored for integrations.
+    pub secrets: SecretsConfig,
     /// Plant definitions linking Mi Flora sensors to named plants.
     pub plants: Vec<PlantConfig>,
 }
 
 /// A named plant associated with a Mi Flora sensor.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct PlantConfig {
     /// User-facing plant name (e.g. "Monstera").
@@ -62,7 +106,7 @@ impl Default for PlantConfig {
 }
 
 /// HTTP listener configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ServerConfig {
     /// Address to bind to (e.g. `0.0.0.0`).
@@ -71,18 +115,115 @@ pub struct ServerConfig {
     pub port: u16,
     /// Path to the dashboard static assets directory (trunk build output).
     pub dashboard_dir: Option<String>,
+    /// Serve a Swagger UI page for the OpenAPI document at `/api/docs`.
+    pub swagger_ui: bool,
+    /// Origins allowed to call the API from a browser, e.g.
+    /// `http://localhost:8080` for a dashboard dev server; `"*"` allows any.
+    /// Empty allows same-origin calls only.
+    pub cors_allowed_origins: Vec<String>,
+    /// How long an API request may run before failing with `408`, in
+    /// seconds.
+    pub request_timeout_secs: u64,
+    /// How many API requests are served at once; the others fail with
+    /// `429`.
+    pub max_concurrent_requests: usize,
+    /// Largest accepted API request body, in bytes; larger ones fail with
+    /// `413`.
+    pub max_body_bytes: usize,
+    /// How many calls the public webhook route serves per minute for each
+    /// webhook id, apart from the other API requests; the others fail with
+    /// `429`.
+    pub webhook_requests_per_minute: u32,
+    /// How long background tasks and integrations get to flush and tear
+    /// down once the HTTP server stopped, in seconds; the ones still
+    /// running are then aborted.
+    pub shutdown_timeout_secs: u64,
+    /// Tokens API and `/metrics` requests authenticate with, as
+    /// `Authorization: Bearer <token>`. Empty serves every request, as
+    /// without authentication.
+    pub tokens: Vec<ApiTokenConfig>,
+    /// Role of the API requests without a token once `tokens` is set, e.g.
+    /// `viewer` to keep a read-only dashboard open; unset rejects them.
+    pub anonymous_role: Option<Role>,
+}
+
+/// A token granting a role to the API requests presenting it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ApiTokenConfig {
+    /// Name the token's writes are attributed to in the audit log.
+    pub name: String,
+    /// Secret value, at least [`MIN_TOKEN_LEN`] characters long.
+    pub token: String,
+    /// `viewer`, `controller` or `admin`.
+    pub role: Role,
+}
+
+/// Shortest accepted API token, so it cannot be guessed.
+pub const MIN_TOKEN_LEN: usize = 16;
+
+impl ApiTokenConfig {
+    /// A token named `name` granting `role`, with a random 64-character
+    /// hexadecimal value.
+    #[must_use]
+    pub fn generate(name: String, role: Role) -> Self {
+        let token = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        Self { name, token, role }
+    }
+
+    /// The `[[server.tokens]]` entry declaring this token, to paste into
+    /// the configuration file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value cannot be represented in TOML.
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        #[derive(Serialize)]
+        struct Server<'a> {
+            tokens: [&'a ApiTokenConfig; 1],
+        }
+        #[derive(Serialize)]
+        struct Snippet<'a> {
+            server: Server<'a>,
+        }
+        Ok(toml::to_string(&Snippet {
+            server: Server { tokens: [self] },
+        })?)
+    }
 }
 
 /// `SQLite` database configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct DatabaseConfig {
     /// `SQLite` connection URL or file path.
     pub url: String,
+    /// Maximum number of connections serving reads; writes always go
+    /// through a single connection.
+    pub max_connections: u32,
+    /// How long a query waits for the database lock before failing with
+    /// `database is locked`, in milliseconds.
+    pub busy_timeout_ms: u64,
+    /// Journal mode: `wal`, `delete`, `truncate`, `persist`, `memory` or
+    /// `off`.
+    pub journal_mode: JournalMode,
+    /// When writes wait for the disk: `off`, `normal`, `full` or `extra`.
+    pub synchronous: Synchronous,
+    /// Interval between two automatic snapshots, in hours; `0` only takes
+    /// snapshots on demand through `POST /api/system/backup`.
+    pub backup_interval_hours: u32,
+    /// Directory the snapshots are written to; defaults to `backups` in the
+    /// data directory, or in the working directory without one.
+    pub backup_dir: Option<String>,
+    /// Number of most recent snapshots kept; older ones are removed.
+    pub backup_keep: usize,
 }
 
 /// Logging configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct LoggingConfig {
     /// Filter directive (`RUST_LOG` syntax).
@@ -90,29 +231,119 @@ pub struct LoggingConfig {
 }
 
 /// Per-integration toggles.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct IntegrationsConfig {
+    /// Time every integration gets to start before it is reported as timed
+    /// out, in seconds.
+    pub setup_timeout_secs: u64,
+    /// Restarts attempted after a failed startup before an integration is
+    /// reported as failed.
+    pub restart_max_attempts: u32,
+    /// Upper bound of the delay between two restarts, in seconds. The delay
+    /// starts at one second and doubles after every failure.
+    pub restart_max_backoff_secs: u64,
     /// Enable the virtual/demo integration.
     pub virtual_enabled: bool,
     /// MQTT integration settings (disabled by default).
     pub mqtt: MqttIntegrationConfig,
+    /// zigbee2mqtt bridge settings (disabled by default).
+    pub zigbee2mqtt: Zigbee2MqttIntegrationConfig,
+    /// Federated remote minihub settings (disabled by default).
+    pub remote: RemoteIntegrationConfig,
     /// BLE integration settings (disabled by default).
     pub ble: BleIntegrationConfig,
+    /// ESPHome native API settings (disabled by default).
+    pub esphome: EsphomeIntegrationConfig,
+    /// Shelly Gen2 RPC settings (disabled by default).
+    pub shelly: ShellyIntegrationConfig,
+    /// HTTP/REST polling settings (disabled by default).
+    pub rest: RestIntegrationConfig,
+    /// Telegram bot settings (disabled by default).
+    pub telegram: TelegramIntegrationConfig,
+    /// mDNS announcement and discovery settings (disabled by default).
+    pub mdns: MdnsIntegrationConfig,
 }
 
 /// Entity history retention settings.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct HistoryConfig {
     /// Number of days to retain entity history (default: 30).
     pub retention_days: u16,
     /// Interval between purge operations, in hours (default: 24).
     pub purge_interval_hours: u16,
+    /// Minimum time between two stored updates of an entity that only move
+    /// numeric attributes, in seconds (default: 0, disabled).
+    pub min_interval_secs: u64,
+    /// Minimum change of a numeric attribute for an update to be stored
+    /// (default: 0, disabled).
+    pub min_delta: f64,
+    /// Interval between snapshots of every entity into history, in seconds
+    /// (default: 0, disabled).
+    pub snapshot_interval_secs: u64,
+}
+
+/// Event dispatch settings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Store every event before broadcasting it, so subscribers that lag
+    /// behind replay the missed events from the database instead of
+    /// dropping them (default: false).
+    pub durable: bool,
+}
+
+/// Home coordinates. Sunrise, sunset and the `sun.sun` entity are only
+/// tracked when both are set.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LocationConfig {
+    /// Latitude in decimal degrees, north positive.
+    pub latitude: Option<f64>,
+    /// Longitude in decimal degrees, east positive.
+    pub longitude: Option<f64>,
+}
+
+impl LocationConfig {
+    /// The configured location, `None` unless both coordinates are set and
+    /// in range.
+    pub fn location(&self) -> Option<Location> {
+        Location::new(self.latitude?, self.longitude?).ok()
+    }
+}
+
+/// Secrets storage. The key comes from `MINIHUB_SECRET_KEY` when set,
+/// from the key file otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// File holding the key as 64 hexadecimal characters, generated on first
+    /// start when missing; defaults to `secret.key` in the data directory,
+    /// or in the working directory without one.
+    pub key_file: Option<String>,
+}
+
+/// Notification delivery channels.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Webhook notifier settings (disabled unless a URL is set).
+    pub webhook: WebhookNotificationConfig,
+}
+
+/// Webhook notifier configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WebhookNotificationConfig {
+    /// URL receiving a JSON `POST` for every notification.
+    pub url: Option<String>,
+    /// Request timeout, in seconds.
+    pub timeout_secs: u64,
 }
 
 /// MQTT integration configuration within the main config file.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct MqttIntegrationConfig {
     /// Whether the MQTT integration is enabled.
@@ -123,14 +354,77 @@ pub struct MqttIntegrationConfig {
     pub broker_port: u16,
     /// MQTT client identifier.
     pub client_id: String,
+    /// User name to authenticate with, when the broker requires one.
+    pub username: Option<String>,
+    /// Name of the stored secret holding the password of `username`.
+    pub password_secret: Option<String>,
     /// Base topic prefix for all minihub MQTT communication.
     pub base_topic: String,
     /// Keep-alive interval in seconds.
     pub keep_alive_secs: u16,
+    /// Publish every entity state change to `{base_topic}/states/{entity_id}`.
+    pub bridge_enabled: bool,
+    /// Federate into a primary minihub: publish every entity at startup,
+    /// announce the hub on `{base_topic}/status` and run the service calls
+    /// received on `{base_topic}/states/{entity_id}/set`. Requires
+    /// `bridge_enabled`.
+    pub federation_enabled: bool,
+    /// `QoS` and retain flag per class of topics.
+    pub topics: TopicClasses,
+    /// Buffer of the commands issued while the broker is unreachable.
+    pub buffer: BufferConfig,
+}
+
+/// zigbee2mqtt integration configuration within the main config file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Zigbee2MqttIntegrationConfig {
+    /// Whether the zigbee2mqtt integration is enabled.
+    pub enabled: bool,
+    /// MQTT broker hostname or IP address.
+    pub broker_host: String,
+    /// MQTT broker port.
+    pub broker_port: u16,
+    /// MQTT client identifier; must differ from the MQTT integration's.
+    pub client_id: String,
+    /// User name to authenticate with, when the broker requires one.
+    pub username: Option<String>,
+    /// Name of the stored secret holding the password of `username`.
+    pub password_secret: Option<String>,
+    /// zigbee2mqtt base topic (its `mqtt.base_topic` setting).
+    pub base_topic: String,
+    /// Keep-alive interval in seconds.
+    pub keep_alive_secs: u16,
+}
+
+/// Remote minihub integration configuration: entities of a secondary hub
+/// federated over MQTT.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RemoteIntegrationConfig {
+    /// Whether the remote integration is enabled.
+    pub enabled: bool,
+    /// MQTT broker hostname or IP address.
+    pub broker_host: String,
+    /// MQTT broker port.
+    pub broker_port: u16,
+    /// MQTT client identifier; must differ from the MQTT integration's.
+    pub client_id: String,
+    /// User name to authenticate with, when the broker requires one.
+    pub username: Option<String>,
+    /// Name of the stored secret holding the password of `username`.
+    pub password_secret: Option<String>,
+    /// Base topic of the secondary hub (its `integrations.mqtt.base_topic`).
+    pub base_topic: String,
+    /// Name of the device standing for the secondary hub, also prefixed to
+    /// its entity ids.
+    pub name: String,
+    /// Keep-alive interval in seconds.
+    pub keep_alive_secs: u16,
 }
 
 /// BLE passive scanner integration configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(default)]
 pub struct BleIntegrationConfig {
     /// Whether the BLE integration is enabled.
@@ -147,23 +441,209 @@ pub struct BleIntegrationConfig {
     pub miflora_filter: Vec<String>,
     /// Per-device GATT connection timeout, in seconds.
     pub miflora_connect_timeout_secs: u16,
+    /// How many Mi Flora sensors are read at the same time.
+    pub max_concurrent_connections: usize,
+    /// Enable active GATT readout for LYWSD03MMC sensors on stock firmware.
+    pub lywsd_stock_enabled: bool,
+    /// Temperature/humidity offsets keyed by MAC address.
+    pub calibration: BTreeMap<String, BleCalibrationEntry>,
+    /// iBeacon and Eddystone presence tracking.
+    pub beacons: BleBeaconConfig,
+}
+
+/// `[integrations.ble.beacons]` — iBeacon and Eddystone beacons tracked as
+/// `device_tracker` entities.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BleBeaconConfig {
+    /// Whether beacons are tracked.
+    pub enabled: bool,
+    /// Optional beacon identifier allowlist.
+    pub filter: Vec<String>,
+    /// Seconds a beacon may go unheard before it is reported `not_home`.
+    pub away_timeout_secs: u16,
+}
+
+/// One `[integrations.ble.calibration]` entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BleCalibrationEntry {
+    /// Added to the temperature, in degrees Celsius.
+    pub temperature: f64,
+    /// Added to the relative humidity, in percent.
+    pub humidity: f64,
+}
+
+/// ESPHome integration configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EsphomeIntegrationConfig {
+    /// Whether the ESPHome integration is enabled.
+    pub enabled: bool,
+    /// Devices to connect to.
+    pub devices: Vec<EsphomeDeviceEntry>,
+    /// Delay before reconnecting to a device that dropped, in seconds.
+    pub reconnect_interval_secs: u64,
+}
+
+/// One `[[integrations.esphome.devices]]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EsphomeDeviceEntry {
+    /// Hostname or IP address of the device.
+    pub host: String,
+    /// Native API port.
+    #[serde(default = "default_esphome_port")]
+    pub port: u16,
+    /// API password, when the device sets one.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Name of the stored secret holding the API password, instead of
+    /// `password`.
+    #[serde(default)]
+    pub password_secret: Option<String>,
+}
+
+fn default_esphome_port() -> u16 {
+    6053
+}
+
+/// Shelly Gen2 integration configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ShellyIntegrationConfig {
+    /// Whether the Shelly integration is enabled.
+    pub enabled: bool,
+    /// Devices to connect to.
+    pub devices: Vec<ShellyDeviceEntry>,
+    /// Also connect to the Shelly devices found by the mDNS integration.
+    pub discover_mdns: bool,
+    /// Delay before reconnecting to a device that dropped, in seconds.
+    pub reconnect_interval_secs: u64,
+}
+
+/// One `[[integrations.shelly.devices]]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ShellyDeviceEntry {
+    /// Hostname or IP address of the device.
+    pub host: String,
+    /// HTTP port.
+    #[serde(default = "default_shelly_port")]
+    pub port: u16,
+}
+
+fn default_shelly_port() -> u16 {
+    80
+}
+
+/// REST polling integration configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RestIntegrationConfig {
+    /// Whether the REST integration is enabled.
+    pub enabled: bool,
+    /// Delay between two polls of the same device, in seconds.
+    pub poll_interval_secs: u64,
+    /// Timeout of every HTTP request, in seconds.
+    pub timeout_secs: u64,
+    /// Devices to poll.
+    pub devices: Vec<RestDeviceEntry>,
+}
+
+/// One `[[integrations.rest.devices]]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RestDeviceEntry {
+    /// Human-readable name of the device and its entity.
+    pub name: String,
+    /// Entity id exposed to minihub, e.g. `"switch.garage_plug"`.
+    pub entity_id: String,
+    /// URL answering the device status as JSON.
+    pub poll_url: String,
+    /// JSONPath to the on/off state, e.g. `"$.relays[0].ison"`.
+    #[serde(default)]
+    pub state_path: Option<String>,
+    /// Attribute name to the JSONPath of its value.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Service name to the request performing it.
+    #[serde(default)]
+    pub commands: BTreeMap<String, RestCommandEntry>,
+}
+
+/// A `[integrations.rest.devices.commands.<service>]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RestCommandEntry {
+    /// HTTP method.
+    #[serde(default = "default_rest_method")]
+    pub method: String,
+    /// URL template, with `{field}` placeholders filled from service data.
+    pub url: String,
+    /// Optional body template.
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+fn default_rest_method() -> String {
+    "GET".to_string()
+}
+
+/// Telegram bot integration configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TelegramIntegrationConfig {
+    /// Whether the Telegram integration is enabled.
+    pub enabled: bool,
+    /// Bot token issued by `@BotFather`.
+    pub bot_token: String,
+    /// Name of the stored secret holding the bot token, instead of
+    /// `bot_token`.
+    pub bot_token_secret: Option<String>,
+    /// Chats allowed to receive notifications and send commands.
+    pub allowed_chat_ids: Vec<i64>,
+    /// How long a single `getUpdates` long-poll waits, in seconds.
+    pub poll_timeout_secs: u64,
+}
+
+/// mDNS integration configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MdnsIntegrationConfig {
+    /// Whether the mDNS integration is enabled.
+    pub enabled: bool,
+    /// Announce the HTTP API as a `_minihub._tcp` service.
+    pub announce: bool,
+    /// Name the HTTP API is announced under.
+    pub instance_name: String,
+    /// IPv4 address to announce; detected from the routing table when unset.
+    pub address: Option<std::net::Ipv4Addr>,
+    /// Service types to browse for.
+    pub service_types: Vec<String>,
+    /// Delay between two browse queries, in seconds.
+    pub browse_interval_secs: u64,
 }
 
 impl Config {
     /// Load configuration from `minihub.toml` (if present) then apply
     /// environment-variable overrides.
     ///
+    /// The file is read from `MINIHUB_DATA_DIR` when that variable is set,
+    /// otherwise from the working directory.
+    ///
     /// # Errors
     ///
     /// Returns an error if the TOML file exists but is malformed.
     pub fn load() -> Result<Self, ConfigError> {
-        let mut config = Self::from_file("minihub.toml")?;
+        let path = std::env::var("MINIHUB_DATA_DIR").map_or_else(
+            |_| PathBuf::from(CONFIG_FILE),
+            |dir| Path::new(&dir).join(CONFIG_FILE),
+        );
+        let mut config = Self::from_file(path)?;
         config.apply_env_overrides();
+        config.apply_data_dir();
         config.validate()?;
         Ok(config)
     }
 
-    fn from_file(path: &str) -> Result<Self, ConfigError> {
+    fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
         match std::fs::read_to_string(path) {
             Ok(content) => toml::from_str(&content).map_err(ConfigError::Parse),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
@@ -172,6 +652,13 @@ impl Config {
     }
 
     fn apply_env_overrides(&mut self) {
+        if let Ok(val) = std::env::var("MINIHUB_DATA_DIR") {
+            self.data_dir = Some(val);
+        }
+        if let Ok(val) = std::env::var("MINIHUB_AUTOMATIONS_DIR") {
+            self.automations_dir = Some(val);
+        }
+        self.secrets.apply_env_overrides();
         if let Ok(val) = std::env::var("MINIHUB_HOST") {
             self.server.host = val;
         }
@@ -191,6 +678,17 @@ impl Config {
         if let Ok(val) = std::env::var("MINIHUB_DASHBOARD_DIR") {
             self.server.dashboard_dir = Some(val);
         }
+        if let Ok(val) = std::env::var("MINIHUB_SWAGGER_UI") {
+            self.server.swagger_ui = val == "1" || val.eq_ignore_ascii_case("true");
+        }
+        if let Ok(val) = std::env::var("MINIHUB_CORS_ALLOWED_ORIGINS") {
+            self.server.cors_allowed_origins = val
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
         if let Ok(val) = std::env::var("MINIHUB_DATABASE_URL") {
             self.database.url = val;
         }
@@ -200,9 +698,17 @@ impl Config {
         if let Ok(val) = std::env::var("RUST_LOG") {
             self.logging.filter = val;
         }
+        self.events.apply_env_overrides();
         if let Ok(val) = std::env::var("MINIHUB_MQTT_ENABLED") {
             self.integrations.mqtt.enabled = val == "1" || val.eq_ignore_ascii_case("true");
         }
+        if let Ok(val) = std::env::var("MINIHUB_MQTT_BRIDGE_ENABLED") {
+            self.integrations.mqtt.bridge_enabled = val == "1" || val.eq_ignore_ascii_case("true");
+        }
+        if let Ok(val) = std::env::var("MINIHUB_MQTT_FEDERATION_ENABLED") {
+            self.integrations.mqtt.federation_enabled =
+                val == "1" || val.eq_ignore_ascii_case("true");
+        }
         if let Ok(val) = std::env::var("MINIHUB_MQTT_BROKER_HOST") {
             self.integrations.mqtt.broker_host = val;
         }
@@ -211,6 +717,17 @@ impl Config {
         {
             self.integrations.mqtt.broker_port = port;
         }
+        if let Ok(val) = std::env::var("MINIHUB_ZIGBEE2MQTT_ENABLED") {
+            self.integrations.zigbee2mqtt.enabled = val == "1" || val.eq_ignore_ascii_case("true");
+        }
+        if let Ok(val) = std::env::var("MINIHUB_ZIGBEE2MQTT_BROKER_HOST") {
+            self.integrations.zigbee2mqtt.broker_host = val;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_ZIGBEE2MQTT_BROKER_PORT")
+            && let Ok(port) = val.parse()
+        {
+            self.integrations.zigbee2mqtt.broker_port = port;
+        }
         if let Ok(val) = std::env::var("MINIHUB_BLE_ENABLED") {
             self.integrations.ble.enabled = val == "1" || val.eq_ignore_ascii_case("true");
         }
@@ -222,15 +739,29 @@ impl Config {
         if let Ok(val) = std::env::var("MINIHUB_BLE_MIFLORA_ENABLED") {
             self.integrations.ble.miflora_enabled = val == "1" || val.eq_ignore_ascii_case("true");
         }
-        if let Ok(val) = std::env::var("MINIHUB_HISTORY_RETENTION_DAYS")
-            && let Ok(days) = val.parse()
-        {
-            self.history.retention_days = days;
+        self.integrations.apply_device_env_overrides();
+        self.history.apply_env_overrides();
+        self.integrations.remote.apply_env_overrides();
+        if let Ok(val) = std::env::var("MINIHUB_TELEGRAM_ENABLED") {
+            self.integrations.telegram.enabled = val == "1" || val.eq_ignore_ascii_case("true");
         }
-        if let Ok(val) = std::env::var("MINIHUB_HISTORY_PURGE_INTERVAL_HOURS")
-            && let Ok(hours) = val.parse()
-        {
-            self.history.purge_interval_hours = hours;
+        if let Ok(val) = std::env::var("MINIHUB_TELEGRAM_BOT_TOKEN") {
+            self.integrations.telegram.bot_token = val;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_NOTIFY_WEBHOOK_URL") {
+            self.notifications.webhook.url = Some(val);
+        }
+    }
+
+    /// Root the database under the data directory, unless a custom
+    /// database URL was configured.
+    fn apply_data_dir(&mut self) {
+        let Some(data_dir) = self.data_dir() else {
+            return;
+        };
+        if self.database.url == DatabaseConfig::default().url {
+            let path = data_dir.join(DATABASE_FILE);
+            self.database.url = format!("sqlite:{}?mode=rwc", path.display());
         }
     }
 
@@ -242,10 +773,128 @@ impl Config {
             .collect()
     }
 
+    fn validate_server_limits(&self) -> Result<(), ConfigError> {
+        let limits = [
+            (
+                "request_timeout_secs",
+                self.server.request_timeout_secs == 0,
+            ),
+            (
+                "max_concurrent_requests",
+                self.server.max_concurrent_requests == 0,
+            ),
+            ("max_body_bytes", self.server.max_body_bytes == 0),
+            (
+                "webhook_requests_per_minute",
+                self.server.webhook_requests_per_minute == 0,
+            ),
+            (
+                "shutdown_timeout_secs",
+                self.server.shutdown_timeout_secs == 0,
+            ),
+        ];
+        match limits.into_iter().find(|(_, zero)| *zero) {
+            Some((name, _)) => Err(ConfigError::Validation(format!(
+                "server: {name} must be non-zero"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_tokens(&self) -> Result<(), ConfigError> {
+        let mut seen_names = std::collections::HashSet::new();
+        let mut seen_tokens = std::collections::HashSet::new();
+        for (idx, token) in self.server.tokens.iter().enumerate() {
+            if token.name.trim().is_empty() {
+                return Err(ConfigError::Validation(format!(
+                    "server.tokens[{idx}]: name must not be empty"
+                )));
+            }
+            if token.token.len() < MIN_TOKEN_LEN {
+                return Err(ConfigError::Validation(format!(
+                    "server.tokens[{idx}]: token must be at least {MIN_TOKEN_LEN} characters long"
+                )));
+            }
+            if !seen_names.insert(&token.name) {
+                return Err(ConfigError::Validation(format!(
+                    "server.tokens[{idx}]: duplicate name {:?}",
+                    token.name
+                )));
+            }
+            if !seen_tokens.insert(&token.token) {
+                return Err(ConfigError::Validation(format!(
+                    "server.tokens[{idx}]: token already used by another entry"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn validate(&self) -> Result<(), ConfigError> {
         if self.server.port == 0 {
             return Err(ConfigError::Validation("port must be non-zero".to_string()));
         }
+        for origin in &self.server.cors_allowed_origins {
+            let host = origin
+                .strip_prefix("http://")
+                .or_else(|| origin.strip_prefix("https://"));
+            let valid = origin == "*"
+                || host.is_some_and(|host| {
+                    !host.is_empty() && !host.contains(|c: char| c == '/' || c.is_whitespace())
+                });
+            if !valid {
+                return Err(ConfigError::Validation(format!(
+                    "server: cors_allowed_origins entry {origin:?} must be \"*\" or a \
+                     scheme://host[:port] origin"
+                )));
+            }
+        }
+        self.validate_server_limits()?;
+        self.validate_tokens()?;
+        if self.database.max_connections == 0 {
+            return Err(ConfigError::Validation(
+                "database: max_connections must be non-zero".to_string(),
+            ));
+        }
+        if self.database.backup_keep == 0 {
+            return Err(ConfigError::Validation(
+                "database: backup_keep must be non-zero".to_string(),
+            ));
+        }
+        if self.integrations.setup_timeout_secs == 0 {
+            return Err(ConfigError::Validation(
+                "integrations: setup_timeout_secs must be non-zero".to_string(),
+            ));
+        }
+        self.validate_telegram()?;
+        self.validate_mqtt()?;
+        let mut seen_hosts = std::collections::HashSet::new();
+        for (idx, device) in self.integrations.esphome.devices.iter().enumerate() {
+            if device.host.is_empty() {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.esphome.devices[{idx}]: host must not be empty"
+                )));
+            }
+            if !seen_hosts.insert((&device.host, device.port)) {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.esphome.devices[{idx}]: duplicate device {}:{}",
+                    device.host, device.port
+                )));
+            }
+            if device.password.is_some() && device.password_secret.is_some() {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.esphome.devices[{idx}]: set password or password_secret, \
+                     not both"
+                )));
+            }
+            validate_secret_name(
+                &format!("integrations.esphome.devices[{idx}]"),
+                device.password_secret.as_deref(),
+            )?;
+        }
+        self.validate_shelly_devices()?;
+        self.validate_rest_devices()?;
+        self.validate_location()?;
         let mut seen_entity_ids = std::collections::HashSet::new();
         let mut seen_slugs = std::collections::HashSet::new();
         for (idx, plant) in self.plants.iter().enumerate() {
@@ -276,6 +925,190 @@ impl Config {
         Ok(())
     }
 
+    /// Check the integrations sharing an MQTT broker do not collide.
+    fn validate_telegram(&self) -> Result<(), ConfigError> {
+        let telegram = &self.integrations.telegram;
+        if telegram.enabled && telegram.bot_token.is_empty() && telegram.bot_token_secret.is_none()
+        {
+            return Err(ConfigError::Validation(
+                "integrations.telegram: bot_token must not be empty unless bot_token_secret is set"
+                    .to_string(),
+            ));
+        }
+        if !telegram.bot_token.is_empty() && telegram.bot_token_secret.is_some() {
+            return Err(ConfigError::Validation(
+                "integrations.telegram: set bot_token or bot_token_secret, not both".to_string(),
+            ));
+        }
+        validate_secret_name(
+            "integrations.telegram",
+            telegram.bot_token_secret.as_deref(),
+        )
+    }
+
+    fn validate_mqtt(&self) -> Result<(), ConfigError> {
+        let mqtt = &self.integrations.mqtt;
+        let zigbee2mqtt = &self.integrations.zigbee2mqtt;
+        let remote = &self.integrations.remote;
+        for (section, username, password_secret) in [
+            ("mqtt", &mqtt.username, &mqtt.password_secret),
+            (
+                "zigbee2mqtt",
+                &zigbee2mqtt.username,
+                &zigbee2mqtt.password_secret,
+            ),
+            ("remote", &remote.username, &remote.password_secret),
+        ] {
+            if username.is_none() && password_secret.is_some() {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.{section}: password_secret requires username"
+                )));
+            }
+            validate_secret_name(
+                &format!("integrations.{section}"),
+                password_secret.as_deref(),
+            )?;
+        }
+        if mqtt.enabled
+            && zigbee2mqtt.enabled
+            && mqtt.broker_host == zigbee2mqtt.broker_host
+            && mqtt.broker_port == zigbee2mqtt.broker_port
+            && mqtt.client_id == zigbee2mqtt.client_id
+        {
+            return Err(ConfigError::Validation(
+                "integrations.zigbee2mqtt: client_id must differ from integrations.mqtt"
+                    .to_string(),
+            ));
+        }
+        if mqtt.federation_enabled && !mqtt.bridge_enabled {
+            return Err(ConfigError::Validation(
+                "integrations.mqtt: federation_enabled requires bridge_enabled".to_string(),
+            ));
+        }
+        if remote.enabled && remote.name.trim().is_empty() {
+            return Err(ConfigError::Validation(
+                "integrations.remote: name must not be empty".to_string(),
+            ));
+        }
+        if mqtt.enabled
+            && remote.enabled
+            && mqtt.broker_host == remote.broker_host
+            && mqtt.broker_port == remote.broker_port
+        {
+            if mqtt.client_id == remote.client_id {
+                return Err(ConfigError::Validation(
+                    "integrations.remote: client_id must differ from integrations.mqtt".to_string(),
+                ));
+            }
+            if mqtt.base_topic == remote.base_topic {
+                return Err(ConfigError::Validation(
+                    "integrations.remote: base_topic must differ from integrations.mqtt"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_location(&self) -> Result<(), ConfigError> {
+        match (self.location.latitude, self.location.longitude) {
+            (None, None) => Ok(()),
+            (Some(latitude), Some(longitude)) => Location::new(latitude, longitude)
+                .map(drop)
+                .map_err(|err| ConfigError::Validation(format!("location: {err}"))),
+            _ => Err(ConfigError::Validation(
+                "location: latitude and longitude must be set together".to_string(),
+            )),
+        }
+    }
+
+    fn validate_shelly_devices(&self) -> Result<(), ConfigError> {
+        let mut seen_hosts = std::collections::HashSet::new();
+        for (idx, device) in self.integrations.shelly.devices.iter().enumerate() {
+            if device.host.is_empty() {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.shelly.devices[{idx}]: host must not be empty"
+                )));
+            }
+            if !seen_hosts.insert((&device.host, device.port)) {
+                return Err(ConfigError::Validation(format!(
+                    "integrations.shelly.devices[{idx}]: duplicate device {}:{}",
+                    device.host, device.port
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_rest_devices(&self) -> Result<(), ConfigError> {
+        let mut seen_rest_ids = std::collections::HashSet::new();
+        for (idx, device) in self.integrations.rest.devices.iter().enumerate() {
+            let prefix = format!("integrations.rest.devices[{idx}]");
+            if device.name.is_empty() {
+                return Err(ConfigError::Validation(format!(
+                    "{prefix}: name must not be empty"
+                )));
+            }
+            if !device.entity_id.contains('.') {
+                return Err(ConfigError::Validation(format!(
+                    "{prefix}: entity_id {:?} must look like \"domain.name\"",
+                    device.entity_id
+                )));
+            }
+            if device.poll_url.is_empty() {
+                return Err(ConfigError::Validation(format!(
+                    "{prefix}: poll_url must not be empty"
+                )));
+            }
+            if !seen_rest_ids.insert(&device.entity_id) {
+                return Err(ConfigError::Validation(format!(
+                    "{prefix}: duplicate entity_id {:?}",
+                    device.entity_id
+                )));
+            }
+            for (service, command) in &device.commands {
+                if !matches!(
+                    command.method.to_ascii_uppercase().as_str(),
+                    "GET" | "POST" | "PUT" | "PATCH" | "DELETE"
+                ) {
+                    return Err(ConfigError::Validation(format!(
+                        "{prefix}.commands.{service}: unsupported method {:?}",
+                        command.method
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the configuration as TOML, as printed by
+    /// `minihubd check-config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value cannot be represented in TOML.
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Whether each integration is enabled, keyed by integration name.
+    #[must_use]
+    pub fn integration_toggles(&self) -> [(&'static str, bool); 11] {
+        [
+            ("virtual", self.integrations.virtual_enabled),
+            ("mqtt", self.integrations.mqtt.enabled),
+            ("zigbee2mqtt", self.integrations.zigbee2mqtt.enabled),
+            ("remote", self.integrations.remote.enabled),
+            ("ble", self.integrations.ble.enabled),
+            ("esphome", self.integrations.esphome.enabled),
+            ("shelly", self.integrations.shelly.enabled),
+            ("rest", self.integrations.rest.enabled),
+            ("telegram", self.integrations.telegram.enabled),
+            ("mdns", self.integrations.mdns.enabled),
+            ("plants", !self.plants.is_empty()),
+        ]
+    }
+
     /// Return the `host:port` bind address.
     #[must_use]
     pub fn bind_addr(&self) -> String {
@@ -288,6 +1121,86 @@ impl Config {
         &self.database.url
     }
 
+    /// Return the data directory, if configured.
+    #[must_use]
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(PathBuf::from)
+    }
+
+    /// Return the directory database snapshots are written to.
+    #[must_use]
+    pub fn backup_dir(&self) -> PathBuf {
+        match (&self.database.backup_dir, self.data_dir()) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(data_dir)) => data_dir.join(BACKUP_DIR),
+            (None, None) => PathBuf::from(BACKUP_DIR),
+        }
+    }
+
+    /// Return the file holding the key encrypting secrets.
+    #[must_use]
+    pub fn secret_key_file(&self) -> PathBuf {
+        match (&self.secrets.key_file, self.data_dir()) {
+            (Some(file), _) => PathBuf::from(file),
+            (None, Some(data_dir)) => data_dir.join(SECRET_KEY_FILE),
+            (None, None) => PathBuf::from(SECRET_KEY_FILE),
+        }
+    }
+
+    /// Names of the stored secrets the enabled integrations read their
+    /// credentials from, each with the configuration key naming it.
+    #[must_use]
+    pub fn secret_references(&self) -> Vec<(String, &str)> {
+        let integrations = &self.integrations;
+        let mut references = Vec::new();
+        for (section, enabled, name) in [
+            (
+                "mqtt",
+                integrations.mqtt.enabled,
+                &integrations.mqtt.password_secret,
+            ),
+            (
+                "zigbee2mqtt",
+                integrations.zigbee2mqtt.enabled,
+                &integrations.zigbee2mqtt.password_secret,
+            ),
+            (
+                "remote",
+                integrations.remote.enabled,
+                &integrations.remote.password_secret,
+            ),
+        ] {
+            if let Some(name) = name.as_deref().filter(|_| enabled) {
+                references.push((format!("integrations.{section}.password_secret"), name));
+            }
+        }
+        if integrations.esphome.enabled {
+            for (idx, device) in integrations.esphome.devices.iter().enumerate() {
+                if let Some(name) = device.password_secret.as_deref() {
+                    references.push((
+                        format!("integrations.esphome.devices[{idx}].password_secret"),
+                        name,
+                    ));
+                }
+            }
+        }
+        if let Some(name) = integrations
+            .telegram
+            .bot_token_secret
+            .as_deref()
+            .filter(|_| integrations.telegram.enabled)
+        {
+            references.push(("integrations.telegram.bot_token_secret".to_string(), name));
+        }
+        references
+    }
+
+    /// Return the directory of automation definitions, if configured.
+    #[must_use]
+    pub fn automations_dir(&self) -> Option<PathBuf> {
+        self.automations_dir.as_ref().map(PathBuf::from)
+    }
+
     /// Return the dashboard assets directory, if configured.
     #[must_use]
     pub fn dashboard_dir(&self) -> Option<std::path::PathBuf> {
@@ -298,12 +1211,30 @@ impl Config {
     }
 }
 
+/// Check `name`, when set, is a valid name for the stored secret a
+/// credential of `section` is read from.
+fn validate_secret_name(section: &str, name: Option<&str>) -> Result<(), ConfigError> {
+    name.map_or(Ok(()), |name| {
+        secret::validate_name(name)
+            .map_err(|err| ConfigError::Validation(format!("{section}: {err}")))
+    })
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             host: "0.0.0.0".to_string(),
             port: 3000,
             dashboard_dir: None,
+            swagger_ui: false,
+            cors_allowed_origins: Vec::new(),
+            request_timeout_secs: 30,
+            max_concurrent_requests: 64,
+            max_body_bytes: 2 * 1024 * 1024,
+            webhook_requests_per_minute: 60,
+            shutdown_timeout_secs: 10,
+            tokens: Vec::new(),
+            anonymous_role: None,
         }
     }
 }
@@ -312,6 +1243,13 @@ impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
             url: "sqlite:minihub.db?mode=rwc".to_string(),
+            max_connections: DbConfig::DEFAULT_MAX_CONNECTIONS,
+            busy_timeout_ms: DbConfig::DEFAULT_BUSY_TIMEOUT_MS,
+            journal_mode: JournalMode::default(),
+            synchronous: Synchronous::default(),
+            backup_interval_hours: 24,
+            backup_dir: None,
+            backup_keep: 7,
         }
     }
 }
@@ -324,12 +1262,39 @@ impl Default for LoggingConfig {
     }
 }
 
+impl IntegrationsConfig {
+    /// Toggle the device integrations from `MINIHUB_{NAME}_ENABLED`.
+    fn apply_device_env_overrides(&mut self) {
+        let toggles = [
+            ("MINIHUB_ESPHOME_ENABLED", &mut self.esphome.enabled),
+            ("MINIHUB_SHELLY_ENABLED", &mut self.shelly.enabled),
+            ("MINIHUB_REST_ENABLED", &mut self.rest.enabled),
+            ("MINIHUB_MDNS_ENABLED", &mut self.mdns.enabled),
+        ];
+        for (name, enabled) in toggles {
+            if let Ok(val) = std::env::var(name) {
+                *enabled = val == "1" || val.eq_ignore_ascii_case("true");
+            }
+        }
+    }
+}
+
 impl Default for IntegrationsConfig {
     fn default() -> Self {
         Self {
+            setup_timeout_secs: 30,
+            restart_max_attempts: 5,
+            restart_max_backoff_secs: 60,
             virtual_enabled: true,
             mqtt: MqttIntegrationConfig::default(),
+            zigbee2mqtt: Zigbee2MqttIntegrationConfig::default(),
+            remote: RemoteIntegrationConfig::default(),
             ble: BleIntegrationConfig::default(),
+            esphome: EsphomeIntegrationConfig::default(),
+            shelly: ShellyIntegrationConfig::default(),
+            rest: RestIntegrationConfig::default(),
+            telegram: TelegramIntegrationConfig::default(),
+            mdns: MdnsIntegrationConfig::default(),
         }
     }
 }
@@ -341,8 +1306,59 @@ impl Default for MqttIntegrationConfig {
             broker_host: "localhost".to_string(),
             broker_port: 1883,
             client_id: "minihub".to_string(),
+            username: None,
+            password_secret: None,
             base_topic: "minihub".to_string(),
             keep_alive_secs: 30,
+            bridge_enabled: false,
+            federation_enabled: false,
+            topics: TopicClasses::default(),
+            buffer: BufferConfig::default(),
+        }
+    }
+}
+
+impl Default for Zigbee2MqttIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker_host: "localhost".to_string(),
+            broker_port: 1883,
+            client_id: "minihub-zigbee2mqtt".to_string(),
+            username: None,
+            password_secret: None,
+            base_topic: "zigbee2mqtt".to_string(),
+            keep_alive_secs: 30,
+        }
+    }
+}
+
+impl RemoteIntegrationConfig {
+    fn apply_env_overrides(&mut self) {
+        if let Ok(val) = std::env::var("MINIHUB_REMOTE_ENABLED") {
+            self.enabled = val == "1" || val.eq_ignore_ascii_case("true");
+        }
+        if let Ok(val) = std::env::var("MINIHUB_REMOTE_BROKER_HOST") {
+            self.broker_host = val;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_REMOTE_BASE_TOPIC") {
+            self.base_topic = val;
+        }
+    }
+}
+
+impl Default for RemoteIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker_host: "localhost".to_string(),
+            broker_port: 1883,
+            client_id: "minihub-remote".to_string(),
+            username: None,
+            password_secret: None,
+            base_topic: "minihub-remote".to_string(),
+            name: "Remote hub".to_string(),
+            keep_alive_secs: 30,
         }
     }
 }
@@ -357,6 +1373,136 @@ impl Default for BleIntegrationConfig {
             miflora_enabled: false,
             miflora_filter: Vec::new(),
             miflora_connect_timeout_secs: 10,
+            max_concurrent_connections: 3,
+            lywsd_stock_enabled: false,
+            calibration: BTreeMap::new(),
+            beacons: BleBeaconConfig::default(),
+        }
+    }
+}
+
+impl Default for BleBeaconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            filter: Vec::new(),
+            away_timeout_secs: 180,
+        }
+    }
+}
+
+impl Default for EsphomeIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            devices: Vec::new(),
+            reconnect_interval_secs: 30,
+        }
+    }
+}
+
+impl Default for ShellyIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            devices: Vec::new(),
+            discover_mdns: true,
+            reconnect_interval_secs: 30,
+        }
+    }
+}
+
+impl Default for RestIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 30,
+            timeout_secs: 10,
+            devices: Vec::new(),
+        }
+    }
+}
+
+impl Default for WebhookNotificationConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for TelegramIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bot_token: String::new(),
+            bot_token_secret: None,
+            allowed_chat_ids: Vec::new(),
+            poll_timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for MdnsIntegrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            announce: true,
+            instance_name: "minihub".to_string(),
+            address: None,
+            service_types: vec![
+                "_esphomelib._tcp.local".to_string(),
+                "_hap._tcp.local".to_string(),
+                "_shelly._tcp.local".to_string(),
+            ],
+            browse_interval_secs: 60,
+        }
+    }
+}
+
+impl EventsConfig {
+    fn apply_env_overrides(&mut self) {
+        if let Ok(val) = std::env::var("MINIHUB_EVENTS_DURABLE") {
+            self.durable = val == "1" || val.eq_ignore_ascii_case("true");
+        }
+    }
+}
+
+impl SecretsConfig {
+    fn apply_env_overrides(&mut self) {
+        if let Ok(val) = std::env::var("MINIHUB_SECRET_KEY_FILE") {
+            self.key_file = Some(val);
+        }
+    }
+}
+
+impl HistoryConfig {
+    fn apply_env_overrides(&mut self) {
+        if let Ok(val) = std::env::var("MINIHUB_HISTORY_RETENTION_DAYS")
+            && let Ok(days) = val.parse()
+        {
+            self.retention_days = days;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_HISTORY_PURGE_INTERVAL_HOURS")
+            && let Ok(hours) = val.parse()
+        {
+            self.purge_interval_hours = hours;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_HISTORY_MIN_INTERVAL_SECS")
+            && let Ok(secs) = val.parse()
+        {
+            self.min_interval_secs = secs;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_HISTORY_MIN_DELTA")
+            && let Ok(delta) = val.parse()
+        {
+            self.min_delta = delta;
+        }
+        if let Ok(val) = std::env::var("MINIHUB_HISTORY_SNAPSHOT_INTERVAL_SECS")
+            && let Ok(secs) = val.parse()
+        {
+            self.snapshot_interval_secs = secs;
         }
     }
 }
@@ -366,6 +1512,9 @@ impl Default for HistoryConfig {
         Self {
             retention_days: 30,
             purge_interval_hours: 24,
+            min_interval_secs: 0,
+            min_delta: 0.0,
+            snapshot_interval_secs: 0,
         }
     }
 }
@@ -382,10 +1531,15 @@ pub enum ConfigError {
     /// Semantic validation failure.
     #[error("invalid configuration: {0}")]
     Validation(String),
+    /// TOML serialization failure.
+    #[error("failed to serialize config")]
+    Serialize(#[from] toml::ser::Error),
 }
 
 #[cfg(test)]
 mod tests {
+    use minihub_adapter_mqtt::Qos;
+
     use super::*;
 
     #[test]
@@ -394,6 +1548,7 @@ mod tests {
         assert_eq!(config.server.host, "0.0.0.0");
         assert_eq!(config.server.port, 3000);
         assert_eq!(config.database.url, "sqlite:minihub.db?mode=rwc");
+        assert_eq!(config.integrations.setup_timeout_secs, 30);
         assert!(config.integrations.virtual_enabled);
         assert!(!config.integrations.mqtt.enabled);
         assert_eq!(config.integrations.mqtt.broker_host, "localhost");
@@ -405,6 +1560,9 @@ mod tests {
         assert!(!config.integrations.ble.miflora_enabled);
         assert!(config.integrations.ble.miflora_filter.is_empty());
         assert_eq!(config.integrations.ble.miflora_connect_timeout_secs, 10);
+        assert!(config.integrations.ble.calibration.is_empty());
+        assert!(!config.integrations.ble.beacons.enabled);
+        assert_eq!(config.integrations.ble.beacons.away_timeout_secs, 180);
     }
 
     #[test]
@@ -446,6 +1604,10 @@ mod tests {
             miflora_enabled = true
             miflora_filter = ['C4:7C:8D:6A:XX:YY']
             miflora_connect_timeout_secs = 15
+            max_concurrent_connections = 2
+            lywsd_stock_enabled = true
+            calibration = { 'A4:C1:38:AA:BB:CC' = { temperature = -1.2, humidity = 3.0 } }
+            beacons = { enabled = true, filter = ['edd1ebeac04e5defa017:0123456789ab'], away_timeout_secs = 240 }
         ";
         let config: Config = toml::from_str(toml).unwrap();
         assert_eq!(config.server.host, "127.0.0.1");
@@ -472,6 +1634,21 @@ mod tests {
             vec!["C4:7C:8D:6A:XX:YY"]
         );
         assert_eq!(config.integrations.ble.miflora_connect_timeout_secs, 15);
+        assert_eq!(config.integrations.ble.max_concurrent_connections, 2);
+        assert!(config.integrations.ble.lywsd_stock_enabled);
+        assert!(config.integrations.ble.beacons.enabled);
+        assert_eq!(
+            config.integrations.ble.beacons.filter,
+            vec!["edd1ebeac04e5defa017:0123456789ab"]
+        );
+        assert_eq!(config.integrations.ble.beacons.away_timeout_secs, 240);
+        assert_eq!(
+            config.integrations.ble.calibration["A4:C1:38:AA:BB:CC"],
+            BleCalibrationEntry {
+                temperature: -1.2,
+                humidity: 3.0,
+            }
+        );
     }
 
     #[test]
@@ -487,6 +1664,108 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn should_reject_zero_database_max_connections() {
+        let mut config = Config::default();
+        config.database.max_connections = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("max_connections must be non-zero"));
+    }
+
+    #[test]
+    fn should_reject_zero_request_limits() {
+        let mut config = Config::default();
+        config.server.max_body_bytes = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("max_body_bytes must be non-zero"));
+    }
+
+    #[test]
+    fn should_reject_zero_shutdown_timeout() {
+        let mut config = Config::default();
+        config.server.shutdown_timeout_secs = 0;
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("shutdown_timeout_secs must be non-zero")
+        );
+    }
+
+    #[test]
+    fn should_parse_request_limits_from_toml() {
+        let toml = r"
+            [server]
+            request_timeout_secs = 10
+            max_concurrent_requests = 8
+            max_body_bytes = 65536
+            webhook_requests_per_minute = 10
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.server.request_timeout_secs, 10);
+        assert_eq!(config.server.max_concurrent_requests, 8);
+        assert_eq!(config.server.max_body_bytes, 65536);
+        assert_eq!(config.server.webhook_requests_per_minute, 10);
+    }
+
+    #[test]
+    fn should_parse_database_pool_options() {
+        let toml = "
+            [database]
+            max_connections = 4
+            busy_timeout_ms = 250
+            journal_mode = 'delete'
+            synchronous = 'full'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.database.max_connections, 4);
+        assert_eq!(config.database.busy_timeout_ms, 250);
+        assert_eq!(config.database.journal_mode, JournalMode::Delete);
+        assert_eq!(config.database.synchronous, Synchronous::Full);
+    }
+
+    #[test]
+    fn should_parse_database_backup_options() {
+        let toml = "
+            [database]
+            backup_interval_hours = 6
+            backup_dir = '/srv/backups'
+            backup_keep = 3
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.database.backup_interval_hours, 6);
+        assert_eq!(config.database.backup_keep, 3);
+        assert_eq!(config.backup_dir(), PathBuf::from("/srv/backups"));
+    }
+
+    #[test]
+    fn should_default_backup_dir_under_data_dir() {
+        let config: Config = toml::from_str("data_dir = '/data'").unwrap();
+        assert_eq!(config.backup_dir(), PathBuf::from("/data/backups"));
+        assert_eq!(Config::default().backup_dir(), PathBuf::from("backups"));
+    }
+
+    #[test]
+    fn should_default_secret_key_file_under_data_dir() {
+        let config: Config = toml::from_str("data_dir = '/data'").unwrap();
+        assert_eq!(config.secret_key_file(), PathBuf::from("/data/secret.key"));
+        assert_eq!(
+            Config::default().secret_key_file(),
+            PathBuf::from("secret.key")
+        );
+
+        let config: Config =
+            toml::from_str("data_dir = '/data'\n[secrets]\nkey_file = '/etc/minihub/key'").unwrap();
+        assert_eq!(config.secret_key_file(), PathBuf::from("/etc/minihub/key"));
+    }
+
+    #[test]
+    fn should_reject_zero_backup_keep() {
+        let mut config = Config::default();
+        config.database.backup_keep = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("backup_keep must be non-zero"));
+    }
+
     #[test]
     fn should_accept_valid_port() {
         let config = Config::default();
@@ -617,6 +1896,100 @@ mod tests {
         assert!(config.dashboard_dir().is_none());
     }
 
+    #[test]
+    fn should_parse_swagger_ui_from_toml() {
+        let toml = r"
+            [server]
+            swagger_ui = true
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.server.swagger_ui);
+        assert!(!Config::default().server.swagger_ui);
+    }
+
+    #[test]
+    fn should_parse_cors_allowed_origins_from_toml() {
+        let toml = r#"
+            [server]
+            cors_allowed_origins = ["http://localhost:8080", "https://dash.example.com"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.server.cors_allowed_origins,
+            vec!["http://localhost:8080", "https://dash.example.com"]
+        );
+        assert!(config.validate().is_ok());
+        assert!(Config::default().server.cors_allowed_origins.is_empty());
+    }
+
+    #[test]
+    fn should_reject_cors_origin_with_path() {
+        let mut config = Config::default();
+        config.server.cors_allowed_origins = vec!["http://localhost:8080/".to_string()];
+
+        let err = config.validate().unwrap_err();
+
+        assert!(err.to_string().contains("cors_allowed_origins"));
+    }
+
+    #[test]
+    fn should_parse_api_tokens_from_toml() {
+        let toml = r#"
+            [server]
+            anonymous_role = "viewer"
+
+            [[server.tokens]]
+            name = "panel"
+            token = "0123456789abcdef"
+            role = "controller"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.server.anonymous_role, Some(Role::Viewer));
+        assert_eq!(
+            config.server.tokens,
+            vec![ApiTokenConfig {
+                name: "panel".to_string(),
+                token: "0123456789abcdef".to_string(),
+                role: Role::Controller,
+            }]
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_reject_short_or_duplicate_api_tokens() {
+        let token = |name: &str, token: &str| ApiTokenConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+            role: Role::Viewer,
+        };
+        let mut config = Config::default();
+
+        config.server.tokens = vec![token("panel", "short")];
+        let short = config.validate().unwrap_err();
+        config.server.tokens = vec![
+            token("panel", "0123456789abcdef"),
+            token("panel", "fedcba9876543210"),
+        ];
+        let duplicate = config.validate().unwrap_err();
+
+        assert!(short.to_string().contains("at least 16 characters"));
+        assert!(duplicate.to_string().contains("duplicate name"));
+    }
+
+    #[test]
+    fn should_generate_token_declared_by_its_snippet() {
+        let token = ApiTokenConfig::generate("grafana".to_string(), Role::Viewer);
+        let other = ApiTokenConfig::generate("grafana".to_string(), Role::Viewer);
+
+        let config: Config = toml::from_str(&token.to_toml().unwrap()).unwrap();
+
+        assert_eq!(token.token.len(), 64);
+        assert_ne!(token.token, other.token);
+        assert_eq!(config.server.tokens, vec![token]);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn should_parse_dashboard_dir_from_toml() {
         let toml = r#"
@@ -838,4 +2211,512 @@ mod tests {
         );
         assert_eq!(config.database.url, "sqlite:test.db");
     }
+    #[test]
+    fn should_parse_default_config_to_defaults() {
+        let parsed: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        let defaults = Config::default();
+        assert_eq!(format!("{parsed:?}"), format!("{defaults:?}"));
+    }
+
+    #[test]
+    fn should_render_config_that_parses_back_to_itself() {
+        let toml = r#"
+            plants = [{ name = "Monstera", entity_id = "sensor.miflora_1" }]
+
+            [integrations.rest]
+            enabled = true
+
+            [[integrations.rest.devices]]
+            name = "Garage plug"
+            entity_id = "switch.garage_plug"
+            poll_url = "http://192.168.1.50/status"
+            attributes = { power = "$.meters[0].power" }
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+
+        let rendered = config.to_toml().unwrap();
+
+        assert_eq!(toml::from_str::<Config>(&rendered).unwrap(), config);
+    }
+
+    #[test]
+    fn should_parse_history_throttle_from_toml() {
+        let toml = "
+            [history]
+            min_interval_secs = 30
+            min_delta = 0.5
+            snapshot_interval_secs = 300
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.history.min_interval_secs, 30);
+        assert!((config.history.min_delta - 0.5).abs() < f64::EPSILON);
+        assert_eq!(config.history.snapshot_interval_secs, 300);
+        assert_eq!(config.history.retention_days, 30);
+    }
+
+    #[test]
+    fn should_parse_automations_dir_from_toml() {
+        let config: Config =
+            toml::from_str("automations_dir = \"/etc/minihub/automations\"").unwrap();
+        assert_eq!(
+            config.automations_dir(),
+            Some(PathBuf::from("/etc/minihub/automations"))
+        );
+        assert_eq!(Config::default().automations_dir(), None);
+    }
+
+    #[test]
+    fn should_parse_location_from_toml() {
+        let toml = "
+            [location]
+            latitude = 48.8566
+            longitude = 2.3522
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+        let location = config.location.location().unwrap();
+        assert!((location.latitude - 48.8566).abs() < f64::EPSILON);
+        assert!((location.longitude - 2.3522).abs() < f64::EPSILON);
+        assert_eq!(Config::default().location.location(), None);
+    }
+
+    #[test]
+    fn should_reject_partial_or_out_of_range_location() {
+        let mut config = Config::default();
+        config.location.latitude = Some(48.8566);
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("must be set together"));
+
+        config.location.longitude = Some(200.0);
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("longitude 200 must be between"));
+    }
+
+    #[test]
+    fn should_parse_telegram_integration_from_toml() {
+        let toml = "
+            [integrations.telegram]
+            enabled = true
+            bot_token = '123:abc'
+            allowed_chat_ids = [42, -100123]
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.integrations.telegram.enabled);
+        assert_eq!(config.integrations.telegram.bot_token, "123:abc");
+        assert_eq!(
+            config.integrations.telegram.allowed_chat_ids,
+            vec![42, -100_123]
+        );
+        assert_eq!(config.integrations.telegram.poll_timeout_secs, 30);
+    }
+
+    #[test]
+    fn should_reject_enabled_telegram_without_token() {
+        let mut config = Config::default();
+        config.integrations.telegram.enabled = true;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("bot_token must not be empty"));
+    }
+
+    #[test]
+    fn should_accept_telegram_token_from_secret() {
+        let toml = "
+            [integrations.telegram]
+            enabled = true
+            bot_token_secret = 'telegram'
+        ";
+        let mut config: Config = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(
+            config.integrations.telegram.bot_token_secret.as_deref(),
+            Some("telegram")
+        );
+
+        config.integrations.telegram.bot_token = "123:abc".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("not both"));
+    }
+
+    #[test]
+    fn should_list_secrets_referenced_by_enabled_integrations() {
+        let toml = "
+            [integrations.mqtt]
+            enabled = true
+            username = 'minihub'
+            password_secret = 'mqtt'
+
+            [integrations.remote]
+            username = 'minihub'
+            password_secret = 'remote'
+
+            [integrations.esphome]
+            enabled = true
+
+            [[integrations.esphome.devices]]
+            host = 'living-room.local'
+            password_secret = 'esphome.living-room'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+
+        assert_eq!(
+            config.secret_references(),
+            vec![
+                ("integrations.mqtt.password_secret".to_string(), "mqtt"),
+                (
+                    "integrations.esphome.devices[0].password_secret".to_string(),
+                    "esphome.living-room"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_secret_name() {
+        let mut config = Config::default();
+        config.integrations.telegram.bot_token_secret = Some("Telegram Bot".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("integrations.telegram: invalid secret name")
+        );
+    }
+
+    #[test]
+    fn should_require_mqtt_username_with_password_secret() {
+        let mut config = Config::default();
+        config.integrations.zigbee2mqtt.password_secret = Some("mqtt".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("integrations.zigbee2mqtt: password_secret requires username")
+        );
+
+        config.integrations.zigbee2mqtt.username = Some("minihub".to_string());
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn should_reject_zero_setup_timeout() {
+        let mut config = Config::default();
+        config.integrations.setup_timeout_secs = 0;
+        let err = config.validate().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("setup_timeout_secs must be non-zero")
+        );
+    }
+
+    #[test]
+    fn should_parse_mqtt_delivery_settings_from_toml() {
+        let toml = "
+            [integrations.mqtt.topics]
+            commands = { qos = 2 }
+            subscriptions = 0
+
+            [integrations.mqtt.buffer]
+            ttl_secs = 60
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let mqtt = &config.integrations.mqtt;
+        assert_eq!(mqtt.topics.commands.qos, Qos::ExactlyOnce);
+        assert!(!mqtt.topics.commands.retain);
+        assert!(mqtt.topics.states.retain);
+        assert_eq!(mqtt.topics.subscriptions, Qos::AtMostOnce);
+        assert_eq!(mqtt.buffer.capacity, 100);
+        assert_eq!(mqtt.buffer.ttl_secs, 60);
+    }
+
+    #[test]
+    fn should_parse_zigbee2mqtt_integration_from_toml() {
+        let toml = "
+            [integrations.zigbee2mqtt]
+            enabled = true
+            broker_host = 'mqtt.local'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.integrations.zigbee2mqtt.enabled);
+        assert_eq!(config.integrations.zigbee2mqtt.broker_host, "mqtt.local");
+        assert_eq!(config.integrations.zigbee2mqtt.base_topic, "zigbee2mqtt");
+        assert_eq!(
+            config.integrations.zigbee2mqtt.client_id,
+            "minihub-zigbee2mqtt"
+        );
+    }
+
+    #[test]
+    fn should_parse_remote_integration_from_toml() {
+        let toml = "
+            [integrations.remote]
+            enabled = true
+            base_topic = 'garage'
+            name = 'Garage'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let remote = &config.integrations.remote;
+        assert!(remote.enabled);
+        assert_eq!(remote.base_topic, "garage");
+        assert_eq!(remote.name, "Garage");
+        assert_eq!(remote.client_id, "minihub-remote");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_reject_remote_sharing_mqtt_base_topic() {
+        let mut config = Config::default();
+        config.integrations.mqtt.enabled = true;
+        config.integrations.remote.enabled = true;
+        config.integrations.remote.base_topic = "minihub".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("base_topic must differ"));
+    }
+
+    #[test]
+    fn should_reject_federation_without_bridge() {
+        let mut config = Config::default();
+        config.integrations.mqtt.federation_enabled = true;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("requires bridge_enabled"));
+
+        config.integrations.mqtt.bridge_enabled = true;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_parse_mdns_settings_from_toml() {
+        let toml = r#"
+            [integrations.mdns]
+            enabled = true
+            announce = false
+            address = "192.168.1.10"
+            service_types = ["_esphomelib._tcp.local"]
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+
+        let mdns = &config.integrations.mdns;
+        assert!(mdns.enabled);
+        assert!(!mdns.announce);
+        assert_eq!(mdns.address, Some(std::net::Ipv4Addr::new(192, 168, 1, 10)));
+        assert_eq!(mdns.service_types, vec!["_esphomelib._tcp.local"]);
+        assert_eq!(mdns.instance_name, "minihub");
+        assert!(!MdnsIntegrationConfig::default().enabled);
+    }
+
+    #[test]
+    fn should_reject_zigbee2mqtt_sharing_mqtt_client_id() {
+        let mut config = Config::default();
+        config.integrations.mqtt.enabled = true;
+        config.integrations.zigbee2mqtt.enabled = true;
+        config.integrations.zigbee2mqtt.client_id = "minihub".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("client_id must differ"));
+    }
+
+    #[test]
+    fn should_parse_esphome_devices_from_toml() {
+        let toml = "
+            [integrations.esphome]
+            enabled = true
+
+            [[integrations.esphome.devices]]
+            host = 'living-room.local'
+
+            [[integrations.esphome.devices]]
+            host = '10.0.0.5'
+            port = 6054
+            password = 'secret'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let esphome = &config.integrations.esphome;
+        assert!(esphome.enabled);
+        assert_eq!(esphome.reconnect_interval_secs, 30);
+        assert_eq!(esphome.devices.len(), 2);
+        assert_eq!(esphome.devices[0].port, 6053);
+        assert!(esphome.devices[0].password.is_none());
+        assert_eq!(esphome.devices[1].port, 6054);
+        assert_eq!(esphome.devices[1].password.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn should_reject_duplicate_esphome_devices() {
+        let toml = "
+            [[integrations.esphome.devices]]
+            host = 'living-room.local'
+
+            [[integrations.esphome.devices]]
+            host = 'living-room.local'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("duplicate device"));
+    }
+
+    #[test]
+    fn should_reject_esphome_password_given_twice() {
+        let toml = "
+            [[integrations.esphome.devices]]
+            host = 'living-room.local'
+            password = 'secret'
+            password_secret = 'esphome'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("password or password_secret"));
+    }
+
+    #[test]
+    fn should_parse_shelly_devices_from_toml() {
+        let toml = "
+            [integrations.shelly]
+            enabled = true
+            discover_mdns = false
+
+            [[integrations.shelly.devices]]
+            host = '192.168.1.30'
+
+            [[integrations.shelly.devices]]
+            host = 'plug.local'
+            port = 8080
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        let shelly = &config.integrations.shelly;
+        assert!(shelly.enabled);
+        assert!(!shelly.discover_mdns);
+        assert_eq!(shelly.reconnect_interval_secs, 30);
+        assert_eq!(shelly.devices.len(), 2);
+        assert_eq!(shelly.devices[0].port, 80);
+        assert_eq!(shelly.devices[1].port, 8080);
+    }
+
+    #[test]
+    fn should_reject_duplicate_shelly_devices() {
+        let toml = "
+            [[integrations.shelly.devices]]
+            host = '192.168.1.30'
+
+            [[integrations.shelly.devices]]
+            host = '192.168.1.30'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("integrations.shelly.devices[1]"));
+    }
+
+    #[test]
+    fn should_parse_rest_devices_from_toml() {
+        let toml = r#"
+            [integrations.rest]
+            enabled = true
+
+            [[integrations.rest.devices]]
+            name = 'Garage Plug'
+            entity_id = 'switch.garage_plug'
+            poll_url = 'http://10.0.0.20/status'
+            state_path = '$.relays[0].ison'
+            attributes = { power = '$.meters[0].power' }
+
+            [integrations.rest.devices.commands.turn_on]
+            url = 'http://10.0.0.20/relay/0?turn=on'
+
+            [integrations.rest.devices.commands.set_level]
+            method = 'POST'
+            url = 'http://10.0.0.20/light/0'
+            body = '{"brightness": {brightness}}'
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let rest = &config.integrations.rest;
+        assert!(rest.enabled);
+        assert_eq!(rest.poll_interval_secs, 30);
+        assert_eq!(rest.devices.len(), 1);
+        let device = &rest.devices[0];
+        assert_eq!(device.attributes["power"], "$.meters[0].power");
+        assert_eq!(device.commands["turn_on"].method, "GET");
+        assert_eq!(device.commands["set_level"].method, "POST");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_reject_rest_device_with_unsupported_method() {
+        let toml = "
+            [[integrations.rest.devices]]
+            name = 'Plug'
+            entity_id = 'switch.plug'
+            poll_url = 'http://10.0.0.20/status'
+
+            [integrations.rest.devices.commands.turn_on]
+            method = 'FETCH'
+            url = 'http://10.0.0.20/on'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("unsupported method"));
+    }
+
+    #[test]
+    fn should_reject_duplicate_rest_entity_ids() {
+        let toml = "
+            [[integrations.rest.devices]]
+            name = 'Plug'
+            entity_id = 'switch.plug'
+            poll_url = 'http://10.0.0.20/status'
+
+            [[integrations.rest.devices]]
+            name = 'Other Plug'
+            entity_id = 'switch.plug'
+            poll_url = 'http://10.0.0.21/status'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("duplicate entity_id"));
+    }
+
+    #[test]
+    fn should_parse_webhook_notifications_from_toml() {
+        let toml = "
+            [notifications.webhook]
+            url = 'https://hooks.example.com/minihub'
+        ";
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.notifications.webhook.url.as_deref(),
+            Some("https://hooks.example.com/minihub")
+        );
+        assert_eq!(config.notifications.webhook.timeout_secs, 10);
+    }
+
+    #[test]
+    fn should_disable_webhook_notifications_by_default() {
+        let config = Config::default();
+        assert!(config.notifications.webhook.url.is_none());
+    }
+
+    #[test]
+    fn should_default_data_dir_to_none() {
+        let mut config = Config::default();
+        config.apply_data_dir();
+        assert!(config.data_dir().is_none());
+        assert_eq!(config.database_url(), "sqlite:minihub.db?mode=rwc");
+    }
+
+    #[test]
+    fn should_root_database_in_data_dir() {
+        let mut config: Config = toml::from_str("data_dir = '/data'").unwrap();
+        config.apply_data_dir();
+        assert_eq!(config.data_dir(), Some(PathBuf::from("/data")));
+        assert_eq!(config.database_url(), "sqlite:/data/minihub.db?mode=rwc");
+    }
+
+    #[test]
+    fn should_keep_custom_database_url_when_data_dir_is_set() {
+        let toml = "
+            data_dir = '/data'
+
+            [database]
+            url = 'sqlite:/elsewhere/hub.db'
+        ";
+        let mut config: Config = toml::from_str(toml).unwrap();
+        config.apply_data_dir();
+        assert_eq!(config.database_url(), "sqlite:/elsewhere/hub.db");
+    }
 }
@@ -4,123 +4,390 @@
 //!
 //! ## Responsibilities
 //! - Parse configuration (CLI args, env vars, config file)
-//! - Initialize the `SQLite` connection pool and run migrations
+//! - Print a commented default configuration (`--print-default-config`)
+//! - Run maintenance subcommands (`migrate`, `check-config`, `export`,
+//!   `import`, `create-token`)
+//! - Initialize the `SQLite` connection pools and run migrations
 //! - Construct repository implementations (adapters)
 //! - Construct application services, injecting repositories via port traits
+//! - Import the automations defined as YAML files in `automations_dir`
 //! - Build the axum router, injecting application services
-//! - Bind to a TCP port and serve
-//! - Handle graceful shutdown (SIGTERM/SIGINT)
+//! - Bind to a TCP port, start integrations concurrently, and serve
+//! - Reload the configuration on SIGHUP or `POST /api/config/reload`
+//! - Load the key encrypting secrets from `MINIHUB_SECRET_KEY` or a key file
+//! - Read the integration credentials the configuration names as stored
+//!   secrets
+//! - Take rotated database snapshots at an interval and on
+//!   `POST /api/system/backup`
+//! - Handle graceful shutdown (SIGTERM/SIGINT), giving background tasks and
+//!   integrations a bounded window to flush and tear down
+//! - Report readiness, watchdog heartbeats and shutdown to systemd (with
+//!   the `systemd` feature)
 //!
 //! ## Dependency rule
 //! This is the **only** crate that depends on all other crates.
 //! It is the wiring layer — no domain logic belongs here.
 
+mod automation_files;
+mod backup;
+mod cli;
 mod config;
+mod reload;
+#[cfg(feature = "systemd")]
+mod systemd;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use minihub_adapter_ble::{BleConfig, BleIntegration};
-use minihub_adapter_http_axum::state::AppState;
-use minihub_adapter_mqtt::{MqttConfig, MqttIntegration};
+use clap::Parser;
+
+use minihub_adapter_ble::{BleConfig, BleIntegration, Calibration};
+use minihub_adapter_esphome::{EsphomeConfig, EsphomeDeviceConfig, EsphomeIntegration};
+use minihub_adapter_http_axum::auth::{AccessControl, ApiToken};
+use minihub_adapter_http_axum::limits::{RateLimit, RequestLimits};
+use minihub_adapter_http_axum::state::{AppState, BuildInfo, Ports};
+use minihub_adapter_mdns::{MdnsConfig, MdnsIntegration};
+use minihub_adapter_mqtt::{
+    MqttConfig, MqttIntegration, RemoteIntegration, Zigbee2MqttIntegration,
+};
+use minihub_adapter_notify_webhook::{WebhookConfig, WebhookNotifier};
 use minihub_adapter_plants::PlantIntegration;
+use minihub_adapter_rest::{RestCommandConfig, RestConfig, RestDeviceConfig, RestIntegration};
+use minihub_adapter_shelly::{ShellyConfig, ShellyDeviceConfig, ShellyIntegration};
 use minihub_adapter_storage_sqlite_sqlx::{
-    Config as DbConfig, SqliteAreaRepository, SqliteAutomationRepository, SqliteDeviceRepository,
-    SqliteEntityHistoryRepository, SqliteEntityRepository, SqliteEventStore,
+    BackupRotation, Config as DbConfig, Database, SecretKey, SqliteAreaRepository,
+    SqliteAuditRepository, SqliteAutomationRepository, SqliteAutomationRunRepository,
+    SqliteDeviceRepository, SqliteDiscoveryRepository, SqliteEnergyUsageRepository,
+    SqliteEntityHistoryRepository, SqliteEntityRepository, SqliteEventStore, SqliteGroupRepository,
+    SqliteReportRepository, SqliteSceneRepository, SqliteSecretsRepository,
+    SqliteSettingsRepository, StorageError,
 };
+use minihub_adapter_telegram::{TelegramConfig, TelegramIntegration, TelegramNotifier};
 use minihub_adapter_virtual::VirtualIntegration;
+use minihub_app::audit::Audited;
+use minihub_app::automation_engine::AutomationEngine;
+use minihub_app::config_reload;
 use minihub_app::event_bus::InProcessEventBus;
+use minihub_app::event_pipeline::{EntityHistoryRecorder, EventPipeline};
+use minihub_app::integration_manager::{IntegrationManager, IntegrationStatus, RestartPolicy};
+use minihub_app::metrics::{
+    DB_POOL_CONNECTIONS, DB_POOL_IDLE_CONNECTIONS, EVENT_BUS_LAGGED, Metrics,
+};
 use minihub_app::ports::storage::EntityHistoryRepository;
-use minihub_app::ports::{EventStore, Integration};
+use minihub_app::ports::{EventStore, Integration, IntegrationContext, SecretsRepository};
+use minihub_app::replayable_event_bus::ReplayableEventBus;
 use minihub_app::services::area_service::AreaService;
 use minihub_app::services::automation_service::AutomationService;
+use minihub_app::services::device_availability_service::DeviceAvailabilityService;
 use minihub_app::services::device_service::DeviceService;
+use minihub_app::services::discovery_service::DiscoveryService;
+use minihub_app::services::energy_service::EnergyService;
 use minihub_app::services::entity_service::EntityService;
+use minihub_app::services::group_service::GroupService;
+use minihub_app::services::history_snapshot_service::HistorySnapshotService;
+use minihub_app::services::home_mode_service::HomeModeService;
 use minihub_app::services::integration_context::ServiceContext;
+use minihub_app::services::notification_service::NotificationService;
+use minihub_app::services::scene_service::SceneService;
+use minihub_app::services::schedule_service::ScheduleService;
+use minihub_app::services::secrets_service::SecretsService;
+use minihub_app::services::sun_service::SunService;
+use minihub_app::services::update_throttle::ThrottleConfig;
+use minihub_app::shutdown::ShutdownCoordinator;
 use tracing_subscriber::EnvFilter;
 
-use crate::config::Config;
+use crate::cli::{Cli, Command};
+use crate::config::{ApiTokenConfig, Config, ServerConfig};
+use crate::reload::Reloader;
+
+/// `SQLite` repositories, audited where they hold configuration, served by
+/// the HTTP API.
+struct SqlitePorts;
+
+impl Ports for SqlitePorts {
+    type Entities = Audited<SqliteEntityRepository, SqliteAuditRepository>;
+    type Devices = Audited<SqliteDeviceRepository, SqliteAuditRepository>;
+    type Areas = Audited<SqliteAreaRepository, SqliteAuditRepository>;
+    type Publisher = Arc<
+        EventPipeline<
+            ReplayableEventBus<SqliteEventStore>,
+            (
+                (),
+                EntityHistoryRecorder<SqliteEntityRepository, SqliteEntityHistoryRepository>,
+            ),
+        >,
+    >;
+    type EventStore = SqliteEventStore;
+    type Automations = Audited<SqliteAutomationRepository, SqliteAuditRepository>;
+    type EntityHistory = SqliteEntityHistoryRepository;
+    type AutomationRuns = SqliteAutomationRunRepository;
+    type Reports = SqliteReportRepository;
+    type Scenes = Audited<SqliteSceneRepository, SqliteAuditRepository>;
+    type Groups = Audited<SqliteGroupRepository, SqliteAuditRepository>;
+    type Audit = SqliteAuditRepository;
+    type Settings = Audited<SqliteSettingsRepository, SqliteAuditRepository>;
+    type EnergyUsage = SqliteEnergyUsageRepository;
+    type Secrets = Audited<SqliteSecretsRepository, SqliteAuditRepository>;
+}
 
 #[tokio::main]
-#[allow(clippy::too_many_lines)]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    if cli.print_default_config {
+        print!("{}", config::DEFAULT_CONFIG);
+        return Ok(());
+    }
+
     // Configuration
     let config = Config::load()?;
 
-    // Logging
-    tracing_subscriber::fmt()
+    match cli.command() {
+        Command::Serve => {
+            let set_log_filter = init(&config)?;
+            serve(config, set_log_filter).await
+        }
+        Command::Migrate => {
+            let _ = init(&config)?;
+            db_config(&config).build().await?;
+            tracing::info!("database migrations applied");
+            Ok(())
+        }
+        Command::CheckConfig => {
+            print!("{}", config.to_toml()?);
+            Ok(())
+        }
+        Command::Export { path } => {
+            let _ = init(&config)?;
+            let db = db_config(&config).build().await?;
+            db.backup_to(&path).await?;
+            tracing::info!(path = %path.display(), "database exported");
+            Ok(())
+        }
+        Command::Import { path } => {
+            let _ = init(&config)?;
+            db_config(&config).restore_from(&path).await?;
+            tracing::info!(path = %path.display(), "database imported");
+            Ok(())
+        }
+        Command::CreateToken { name, role } => {
+            let token = ApiTokenConfig::generate(name, role);
+            println!("{}", token.token);
+            println!();
+            print!("{}", token.to_toml()?);
+            Ok(())
+        }
+    }
+}
+
+/// Install the log subscriber and create the data directory.
+///
+/// Returns a function replacing the log filter, used on configuration
+/// reloads.
+fn init(
+    config: &Config,
+) -> Result<impl Fn(EnvFilter) -> Result<(), String> + Send + 'static, std::io::Error> {
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::new(&config.logging.filter))
-        .init();
+        .with_filter_reloading();
+    let log_filter = subscriber.reload_handle();
+    subscriber.init();
 
     tracing::info!("configuration loaded");
 
-    // Database
-    let db_config = DbConfig {
+    if let Some(data_dir) = config.data_dir() {
+        std::fs::create_dir_all(&data_dir)?;
+        tracing::info!(data_dir = %data_dir.display(), "using data directory");
+    }
+
+    Ok(move |filter| log_filter.reload(filter).map_err(|err| err.to_string()))
+}
+
+fn db_config(config: &Config) -> DbConfig {
+    DbConfig {
         database_url: config.database_url().to_string(),
-    };
-    let db = db_config.build().await?;
-    let pool = db.pool().clone();
+        max_connections: config.database.max_connections,
+        busy_timeout_ms: config.database.busy_timeout_ms,
+        journal_mode: config.database.journal_mode,
+        synchronous: config.database.synchronous,
+    }
+}
+
+/// The API tokens and the role of requests without one.
+fn access_control(config: &ServerConfig) -> AccessControl {
+    let tokens = config
+        .tokens
+        .iter()
+        .map(|token| ApiToken {
+            name: token.name.clone(),
+            token: token.token.clone(),
+            role: token.role,
+        })
+        .collect();
+    AccessControl::new(tokens).with_anonymous_role(config.anonymous_role)
+}
+
+/// The key encrypting secrets: `MINIHUB_SECRET_KEY` when set, otherwise
+/// the key file, generated when missing.
+fn secret_key(config: &Config) -> Result<SecretKey, StorageError> {
+    if let Ok(hex) = std::env::var("MINIHUB_SECRET_KEY") {
+        return SecretKey::from_hex(&hex);
+    }
+    let path = config.secret_key_file();
+    let key = SecretKey::load_or_create(&path)?;
+    tracing::info!(file = %path.display(), "secret key loaded");
+    Ok(key)
+}
+
+/// The value of every stored secret the enabled integrations of `config`
+/// read their credentials from, by name.
+///
+/// # Errors
+///
+/// Fails on the first secret that cannot be read, naming the
+/// configuration key referencing it.
+async fn integration_secrets<R: SecretsRepository>(
+    config: &Config,
+    secrets: &SecretsService<R>,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let mut values = HashMap::new();
+    for (key, name) in config.secret_references() {
+        let value = secrets
+            .get(name)
+            .await
+            .map_err(|err| format!("{key}: {err}"))?;
+        values.insert(name.to_string(), value.expose().to_string());
+    }
+    Ok(values)
+}
+
+/// Serve the API, the dashboard and the integrations until a shutdown
+/// signal, replacing the log filter with `set_log_filter` on reloads.
+#[allow(clippy::too_many_lines)]
+async fn serve<F>(config: Config, set_log_filter: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(EnvFilter) -> Result<(), String> + Send + 'static,
+{
+    // Database
+    let db = db_config(&config).build().await?;
+    let pools = db.pools().clone();
     tracing::info!("database ready");
 
-    // Repositories
-    let entity_repo = SqliteEntityRepository::new(pool.clone());
-    let device_repo = SqliteDeviceRepository::new(pool.clone());
-    let area_repo = SqliteAreaRepository::new(pool.clone());
-    let event_store = SqliteEventStore::new(pool.clone());
-    let automation_repo = SqliteAutomationRepository::new(pool.clone());
-    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pool));
+    // Repositories — writes to the configuration are recorded in the audit log
+    let audit_repo = Arc::new(SqliteAuditRepository::new(pools.clone()));
+    let entity_repo = Audited::new(
+        SqliteEntityRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let device_repo = Audited::new(
+        SqliteDeviceRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let area_repo = Audited::new(
+        SqliteAreaRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let event_store = Arc::new(SqliteEventStore::new(pools.clone()));
+    let automation_repo = Audited::new(
+        SqliteAutomationRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pools.clone()));
+    let automation_run_repo = Arc::new(SqliteAutomationRunRepository::new(pools.clone()));
+    let report_repo = Arc::new(SqliteReportRepository::new(pools.clone()));
+    let scene_repo = Audited::new(
+        SqliteSceneRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let group_repo = Audited::new(
+        SqliteGroupRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let settings_repo = Audited::new(
+        SqliteSettingsRepository::new(pools.clone()),
+        Arc::clone(&audit_repo),
+    );
+
+    // Secrets — credentials of integrations, encrypted at rest
+    let secrets_service = Arc::new(SecretsService::new(Audited::new(
+        SqliteSecretsRepository::new(pools.clone(), &secret_key(&config)?),
+        Arc::clone(&audit_repo),
+    )));
+    let credentials = integration_secrets(&config, &secrets_service).await?;
+    let secret = |name: &Option<String>| {
+        name.as_ref()
+            .and_then(|name| credentials.get(name))
+            .cloned()
+    };
+
+    // Shutdown — background tasks are registered here so they get a bounded
+    // window to flush their work once the HTTP server has stopped
+    let coordinator = ShutdownCoordinator::new();
+
+    // Metrics — shared by every component recording something, exposed at /metrics
+    let metrics = Metrics::new();
+    spawn_pool_sampler(&coordinator, db.clone(), metrics.clone());
 
     // Event bus (Arc-wrapped so it can be shared with ServiceContext)
-    let event_bus = Arc::new(InProcessEventBus::new(256));
-    let mut event_rx = event_bus.subscribe();
+    let event_bus = Arc::new(InProcessEventBus::new(256).with_metrics(metrics.clone()));
+
+    // Event dispatch — in durable mode events are stored before being
+    // broadcast, and lagging subscribers replay them from the store
+    let dispatcher = ReplayableEventBus::new(Arc::clone(&event_bus), Arc::clone(&event_store))
+        .with_durable(config.events.durable);
+    let mut event_rx = dispatcher.subscribe();
+
+    // Event pipeline — hooks run before publishing and after persisting events
+    let event_pipeline = Arc::new(EventPipeline::new(dispatcher.clone()).hook(
+        EntityHistoryRecorder::new(
+            SqliteEntityRepository::new(pools.clone()),
+            SqliteEntityHistoryRepository::new(pools.clone()),
+        ),
+    ));
 
     // Services (Arc-wrapped early so they can be shared with background tasks)
-    let entity_service = Arc::new(EntityService::new(entity_repo, Arc::clone(&event_bus)));
+    let entity_service = Arc::new(EntityService::new(entity_repo, Arc::clone(&event_pipeline)));
+    let scene_service = Arc::new(SceneService::new(scene_repo, Arc::clone(&event_pipeline)));
     let device_service = Arc::new(DeviceService::new(device_repo));
     let area_service = Arc::new(AreaService::new(area_repo));
+    // Automation files — imported before the engine starts evaluating them
+    if let Some(dir) = config.automations_dir() {
+        automation_files::import(&dir, &automation_repo).await?;
+    }
     let automation_service = Arc::new(AutomationService::new(automation_repo));
-    let event_store = Arc::new(event_store);
 
-    // Event worker — persists events from the bus to the store and records entity history
+    // Home mode — make sure the hub-wide mode entity exists before automations run
+    HomeModeService::new(Arc::clone(&device_service), Arc::clone(&entity_service))
+        .ensure_entity()
+        .await?;
+
+    // Event worker — persists events from the bus, unless durable dispatch
+    // already did, then runs after-persist hooks; on shutdown it drains the
+    // events still queued before stopping
     let es = Arc::clone(&event_store);
-    let hr = Arc::clone(&history_repo);
-    let entity_svc_for_history = Arc::clone(&entity_service);
-    tokio::spawn(async move {
-        loop {
-            match event_rx.recv().await {
+    let pipeline = Arc::clone(&event_pipeline);
+    let worker_metrics = metrics.clone();
+    let persist = !dispatcher.is_durable();
+    coordinator.spawn_graceful("event_store", |mut signal| async move {
+        while let Some(received) = signal.recv(&mut event_rx).await {
+            match received {
                 Ok(event) => {
-                    // Persist event to store
-                    if let Err(err) = es.store(event.clone()).await {
-                        tracing::warn!(%err, "failed to persist event");
-                    }
-
-                    // Record entity history for state/attribute changes
-                    if matches!(
-                        event.event_type,
-                        minihub_domain::event::EventType::StateChanged
-                            | minihub_domain::event::EventType::AttributeChanged
-                    ) && let Some(entity_id) = event.entity_id
-                    {
-                        match entity_svc_for_history.get_entity(entity_id).await {
-                            Ok(entity) => {
-                                let history =
-                                    minihub_domain::entity_history::EntityHistory::builder()
-                                        .entity_id(entity.id)
-                                        .state(entity.state.clone())
-                                        .attributes(entity.attributes.clone())
-                                        .recorded_at(event.timestamp)
-                                        .build();
-
-                                if let Err(err) = hr.record(history).await {
-                                    tracing::warn!(%err, entity_id = %entity_id, "failed to record entity history");
-                                }
-                            }
-                            Err(err) => {
-                                tracing::warn!(%err, entity_id = %entity_id, "failed to fetch entity for history recording");
-                            }
-                        }
+                    let stored = if persist {
+                        es.store(event.clone()).await.map(drop)
+                    } else {
+                        Ok(())
+                    };
+                    match stored {
+                        Ok(()) => pipeline.after_persist(&event).await,
+                        Err(err) => tracing::warn!(%err, "failed to persist event"),
                     }
                 }
                 Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    #[allow(clippy::cast_precision_loss)]
+                    worker_metrics.add(
+                        &EVENT_BUS_LAGGED,
+                        &[("subscriber", "event_store")],
+                        n as f64,
+                    );
                     tracing::warn!(
                         skipped = n,
                         "event store subscriber lagged, some events were dropped"
@@ -132,21 +399,269 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tracing::debug!("event store subscriber stopped");
     });
 
+    // Automation engine — evaluates every bus event against enabled automations
+    let automation_engine = AutomationEngine::new(
+        SqliteAutomationRepository::new(pools.clone()),
+        SqliteEntityRepository::new(pools.clone()),
+        SqliteAutomationRunRepository::new(pools.clone()),
+        Arc::clone(&event_pipeline),
+    );
+    let automation_rx = dispatcher.subscribe_filtered(automation_engine.event_filter());
+    coordinator.spawn("automation_engine", async move {
+        automation_engine.run(automation_rx).await;
+    });
+
+    // Device availability — derives device online/offline transitions from entity states
+    let availability_service = DeviceAvailabilityService::new(
+        SqliteEntityRepository::new(pools.clone()),
+        SqliteDeviceRepository::new(pools.clone()),
+        Arc::clone(&event_pipeline),
+    );
+    availability_service.prime().await?;
+    let availability_rx = dispatcher.subscribe_filtered(availability_service.event_filter());
+    coordinator.spawn("availability", async move {
+        availability_service.run(availability_rx).await;
+    });
+
+    // Energy — integrates power and energy sensor readings into hourly consumption
+    let energy_service = EnergyService::new(
+        SqliteEntityRepository::new(pools.clone()),
+        SqliteEnergyUsageRepository::new(pools.clone()),
+    );
+    let energy_rx = dispatcher.subscribe_filtered(energy_service.event_filter());
+    coordinator.spawn("energy", async move { energy_service.run(energy_rx).await });
+
+    // Groups — keep group states in sync and fan service calls out to members
+    let group_service = Arc::new(GroupService::new(
+        group_repo,
+        Arc::clone(&device_service),
+        Arc::clone(&entity_service),
+    ));
+    group_service.prime().await?;
+    let group_rx = dispatcher.subscribe_filtered(group_service.event_filter());
+    let group_runner = Arc::clone(&group_service);
+    coordinator.spawn("groups", async move { group_runner.run(group_rx).await });
+
+    // Discovery — collect detected devices until they are adopted
+    let discovery_service = Arc::new(DiscoveryService::new(Arc::clone(&device_service)));
+    discovery_service.prime(&*event_store).await?;
+    let discovery_rx = dispatcher.subscribe_filtered(discovery_service.event_filter());
+    let discovery_runner = Arc::clone(&discovery_service);
+    coordinator.spawn("discovery", async move {
+        discovery_runner.run(discovery_rx).await;
+    });
+
+    // Notifications — forward requested notifications to the webhook
+    if let Some(url) = config.notifications.webhook.url.clone() {
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            url,
+            timeout_secs: config.notifications.webhook.timeout_secs,
+        })?;
+        let notification_service = NotificationService::new(notifier);
+        let notification_rx = dispatcher.subscribe_filtered(notification_service.event_filter());
+        coordinator.spawn("webhook_notifications", async move {
+            notification_service.run(notification_rx).await;
+        });
+        tracing::info!("webhook notifier ready");
+    }
+
     // Integration context — shared by all integrations
     let ctx = ServiceContext::new(
         Arc::clone(&device_service),
         Arc::clone(&entity_service),
+        SqliteDiscoveryRepository::new(pools.clone()),
+        Arc::clone(&event_pipeline),
         Arc::clone(&event_bus),
-        Arc::clone(&event_bus),
+    )
+    .with_throttle(ThrottleConfig {
+        min_interval: std::time::Duration::from_secs(config.history.min_interval_secs),
+        min_delta: config.history.min_delta,
+    })
+    .with_metrics(metrics.clone());
+
+    // Integration manager — tracks the startup status reported by the API and
+    // restarts integrations that fail to start
+    let integrations = IntegrationManager::new(std::time::Duration::from_secs(
+        config.integrations.setup_timeout_secs,
+    ))
+    .with_restart_policy(RestartPolicy {
+        max_restarts: config.integrations.restart_max_attempts,
+        max_backoff: std::time::Duration::from_secs(config.integrations.restart_max_backoff_secs),
+        ..RestartPolicy::default()
+    });
+    for (name, enabled) in config.integration_toggles() {
+        integrations.register(name, enabled);
+    }
+
+    // Background purge task — removes old entity history records, following
+    // the retention settings published on configuration reloads
+    let hr_purge = Arc::clone(&history_repo);
+    let retention_days = config.history.retention_days;
+    let purge_interval_hours = config.history.purge_interval_hours;
+    let (history_settings, mut history_rx) = tokio::sync::watch::channel(config.history.clone());
+    coordinator.spawn("history_purge", async move {
+        loop {
+            let interval_hours = u64::from(history_rx.borrow().purge_interval_hours);
+            tokio::select! {
+                () = tokio::time::sleep(std::time::Duration::from_hours(interval_hours)) => {}
+                Ok(()) = history_rx.changed() => continue,
+            }
+
+            let retention_days = history_rx.borrow().retention_days;
+            let retention_secs = i64::from(retention_days) * 24 * 3600;
+            let cutoff = minihub_domain::time::now()
+                - std::time::Duration::from_secs(retention_secs.unsigned_abs());
+            match hr_purge.purge_before(cutoff).await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::info!(count, retention_days, "purged old entity history records");
+                    } else {
+                        tracing::debug!(retention_days, "no old entity history records to purge");
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "failed to purge old entity history");
+                }
+            }
+        }
+    });
+    tracing::info!(
+        retention_days,
+        purge_interval_hours,
+        "entity history retention configured"
     );
 
-    // Integrations
-    if config.integrations.virtual_enabled {
-        let mut integration = VirtualIntegration::default();
-        integration.setup(&ctx).await?;
+    // History snapshots — regular samples of every entity, changed or not
+    if config.history.snapshot_interval_secs > 0 {
+        let snapshots = HistorySnapshotService::new(
+            SqliteEntityRepository::new(pools.clone()),
+            SqliteEntityHistoryRepository::new(pools.clone()),
+            std::time::Duration::from_secs(config.history.snapshot_interval_secs),
+        );
+        coordinator.spawn("history_snapshots", async move { snapshots.run().await });
         tracing::info!(
-            integration = integration.name(),
-            "virtual integration ready"
+            interval_secs = config.history.snapshot_interval_secs,
+            "entity history snapshots enabled"
+        );
+    }
+
+    // Sun — keeps `sun.sun` current and fires sun triggers at the home location
+    if let Some(location) = config.location.location() {
+        let sun_service = SunService::new(
+            Arc::clone(&device_service),
+            Arc::clone(&entity_service),
+            SqliteAutomationRepository::new(pools.clone()),
+            Arc::clone(&event_pipeline),
+            location,
+        );
+        coordinator.spawn("sun", async move { sun_service.run().await });
+        tracing::info!(
+            latitude = location.latitude,
+            longitude = location.longitude,
+            "sun tracking enabled"
+        );
+    }
+
+    // Schedules — fires the time patterns of automations
+    let schedule_service = ScheduleService::new(
+        SqliteAutomationRepository::new(pools.clone()),
+        Arc::clone(&event_pipeline),
+    );
+    coordinator.spawn("schedules", async move { schedule_service.run().await });
+
+    // Configuration reload — requested on SIGHUP or through the HTTP API
+    let (reload_handle, reload_requests) = config_reload::channel();
+    let reloader = Reloader::new(
+        config.clone(),
+        integrations.clone(),
+        history_settings,
+        set_log_filter,
+    );
+    coordinator.spawn("config_reload", reloader.run(reload_requests));
+
+    // Database backups — rotated snapshots, at an interval and on request
+    let (backup_handle, backup_requests) = minihub_app::backup::channel();
+    let backup_interval = (config.database.backup_interval_hours > 0)
+        .then(|| std::time::Duration::from_hours(u64::from(config.database.backup_interval_hours)));
+    coordinator.spawn(
+        "database_backup",
+        backup::run(
+            db.clone(),
+            BackupRotation::new(config.backup_dir(), config.database.backup_keep),
+            backup_interval,
+            backup_requests,
+        ),
+    );
+    tracing::info!(
+        dir = %config.backup_dir().display(),
+        interval_hours = config.database.backup_interval_hours,
+        keep = config.database.backup_keep,
+        "database backups configured"
+    );
+
+    // HTTP
+    let state = AppState::<SqlitePorts>::from_arcs(
+        entity_service,
+        device_service,
+        area_service,
+        event_store,
+        automation_service,
+        history_repo,
+        automation_run_repo,
+        report_repo,
+        scene_service,
+        group_service,
+        audit_repo,
+        Arc::new(settings_repo),
+        Arc::new(SqliteEnergyUsageRepository::new(pools.clone())),
+        secrets_service,
+        Arc::clone(&event_bus),
+    )
+    .with_discovery_service(discovery_service)
+    .with_integrations(integrations.clone())
+    .with_swagger_ui(config.server.swagger_ui)
+    .with_cors_allowed_origins(config.server.cors_allowed_origins.clone())
+    .with_request_limits(RequestLimits {
+        timeout: std::time::Duration::from_secs(config.server.request_timeout_secs),
+        max_concurrent_requests: config.server.max_concurrent_requests,
+        max_body_bytes: config.server.max_body_bytes,
+    })
+    .with_webhook_rate_limit(RateLimit {
+        max_requests: config.server.webhook_requests_per_minute,
+        period: std::time::Duration::from_mins(1),
+    })
+    .with_access_control(access_control(&config.server))
+    .with_config_reload(reload_handle)
+    .with_backup(backup_handle)
+    .with_metrics(metrics)
+    .with_build_info(BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: option_env!("MINIHUB_GIT_HASH"),
+    });
+    let dashboard_dir = config.dashboard_dir();
+    let app = minihub_adapter_http_axum::router::build(state, dashboard_dir.as_deref());
+
+    let bind_addr = config.bind_addr();
+    tracing::info!(addr = %bind_addr, "minihubd listening");
+
+    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
+
+    #[cfg(feature = "systemd")]
+    let notifier = systemd::Notifier::from_env().map(Arc::new);
+    #[cfg(feature = "systemd")]
+    if let Some(notifier) = &notifier {
+        notifier.ready();
+        Arc::clone(notifier).spawn_watchdog();
+    }
+
+    // Integrations — started in the background once the listener is bound, so
+    // a slow or failing integration neither delays nor aborts the daemon
+    if config.integrations.virtual_enabled {
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            VirtualIntegration::default(),
+            &ctx,
         );
     }
 
@@ -155,17 +670,77 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             broker_host: config.integrations.mqtt.broker_host.clone(),
             broker_port: config.integrations.mqtt.broker_port,
             client_id: config.integrations.mqtt.client_id.clone(),
+            username: config.integrations.mqtt.username.clone(),
+            password: secret(&config.integrations.mqtt.password_secret),
             base_topic: config.integrations.mqtt.base_topic.clone(),
             keep_alive_secs: config.integrations.mqtt.keep_alive_secs,
+            bridge_enabled: config.integrations.mqtt.bridge_enabled,
+            federation_enabled: config.integrations.mqtt.federation_enabled,
+            topics: config.integrations.mqtt.topics,
+            buffer: config.integrations.mqtt.buffer,
         };
-        let mut integration = MqttIntegration::new(mqtt_config);
-        integration.setup(&ctx).await?;
-        integration.start_background(ctx.clone()).await?;
         tracing::info!(
-            integration = integration.name(),
             broker = %config.integrations.mqtt.broker_host,
             port = config.integrations.mqtt.broker_port,
-            "MQTT integration ready"
+            bridge = config.integrations.mqtt.bridge_enabled,
+            federation = config.integrations.mqtt.federation_enabled,
+            "starting MQTT integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            MqttIntegration::new(mqtt_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.zigbee2mqtt.enabled {
+        let z2m_config = MqttConfig {
+            broker_host: config.integrations.zigbee2mqtt.broker_host.clone(),
+            broker_port: config.integrations.zigbee2mqtt.broker_port,
+            client_id: config.integrations.zigbee2mqtt.client_id.clone(),
+            username: config.integrations.zigbee2mqtt.username.clone(),
+            password: secret(&config.integrations.zigbee2mqtt.password_secret),
+            base_topic: config.integrations.zigbee2mqtt.base_topic.clone(),
+            keep_alive_secs: config.integrations.zigbee2mqtt.keep_alive_secs,
+            ..MqttConfig::default()
+        };
+        tracing::info!(
+            broker = %config.integrations.zigbee2mqtt.broker_host,
+            base_topic = %config.integrations.zigbee2mqtt.base_topic,
+            "starting zigbee2mqtt integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            Zigbee2MqttIntegration::new(z2m_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.remote.enabled {
+        let remote = &config.integrations.remote;
+        let remote_config = MqttConfig {
+            broker_host: remote.broker_host.clone(),
+            broker_port: remote.broker_port,
+            client_id: remote.client_id.clone(),
+            username: remote.username.clone(),
+            password: secret(&remote.password_secret),
+            base_topic: remote.base_topic.clone(),
+            keep_alive_secs: remote.keep_alive_secs,
+            ..MqttConfig::default()
+        };
+        tracing::info!(
+            broker = %remote.broker_host,
+            base_topic = %remote.base_topic,
+            name = %remote.name,
+            "starting remote integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            RemoteIntegration::new(remote_config, &remote.name),
+            &ctx,
         );
     }
 
@@ -177,11 +752,183 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             miflora_enabled: config.integrations.ble.miflora_enabled,
             miflora_filter: config.integrations.ble.miflora_filter.clone(),
             miflora_connect_timeout_secs: config.integrations.ble.miflora_connect_timeout_secs,
+            max_concurrent_connections: config.integrations.ble.max_concurrent_connections,
+            lywsd_stock_enabled: config.integrations.ble.lywsd_stock_enabled,
+            calibration: config
+                .integrations
+                .ble
+                .calibration
+                .iter()
+                .map(|(mac, offsets)| {
+                    (
+                        mac.clone(),
+                        Calibration {
+                            temperature: offsets.temperature,
+                            humidity: offsets.humidity,
+                        },
+                    )
+                })
+                .collect(),
+            beacons_enabled: config.integrations.ble.beacons.enabled,
+            beacon_filter: config.integrations.ble.beacons.filter.clone(),
+            beacon_away_timeout_secs: config.integrations.ble.beacons.away_timeout_secs,
+        };
+        tracing::info!("starting BLE integration");
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            BleIntegration::new(ble_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.esphome.enabled {
+        let esphome_config = EsphomeConfig {
+            devices: config
+                .integrations
+                .esphome
+                .devices
+                .iter()
+                .map(|device| EsphomeDeviceConfig {
+                    host: device.host.clone(),
+                    port: device.port,
+                    password: secret(&device.password_secret).or_else(|| device.password.clone()),
+                })
+                .collect(),
+            reconnect_interval_secs: config.integrations.esphome.reconnect_interval_secs,
+            ..EsphomeConfig::default()
+        };
+        tracing::info!(
+            devices = config.integrations.esphome.devices.len(),
+            "starting ESPHome integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            EsphomeIntegration::new(esphome_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.shelly.enabled {
+        let shelly_config = ShellyConfig {
+            devices: config
+                .integrations
+                .shelly
+                .devices
+                .iter()
+                .map(|device| ShellyDeviceConfig {
+                    host: device.host.clone(),
+                    port: device.port,
+                })
+                .collect(),
+            discover_mdns: config.integrations.shelly.discover_mdns,
+            reconnect_interval_secs: config.integrations.shelly.reconnect_interval_secs,
+            ..ShellyConfig::default()
         };
-        let mut integration = BleIntegration::new(ble_config);
-        integration.setup(&ctx).await?;
-        integration.start_background(ctx.clone()).await?;
-        tracing::info!(integration = integration.name(), "BLE integration ready");
+        tracing::info!(
+            devices = config.integrations.shelly.devices.len(),
+            discover_mdns = config.integrations.shelly.discover_mdns,
+            "starting Shelly integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            ShellyIntegration::new(shelly_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.rest.enabled {
+        let rest_config = RestConfig {
+            devices: config
+                .integrations
+                .rest
+                .devices
+                .iter()
+                .map(|device| RestDeviceConfig {
+                    name: device.name.clone(),
+                    entity_id: device.entity_id.clone(),
+                    poll_url: device.poll_url.clone(),
+                    state_path: device.state_path.clone(),
+                    attributes: device.attributes.clone(),
+                    commands: device
+                        .commands
+                        .iter()
+                        .map(|(service, command)| {
+                            (
+                                service.clone(),
+                                RestCommandConfig {
+                                    method: command.method.clone(),
+                                    url: command.url.clone(),
+                                    body: command.body.clone(),
+                                },
+                            )
+                        })
+                        .collect(),
+                })
+                .collect(),
+            poll_interval_secs: config.integrations.rest.poll_interval_secs,
+            timeout_secs: config.integrations.rest.timeout_secs,
+        };
+        tracing::info!(
+            devices = config.integrations.rest.devices.len(),
+            "starting REST integration"
+        );
+        match RestIntegration::new(rest_config) {
+            Ok(integration) => spawn_integration(&coordinator, &integrations, integration, &ctx),
+            Err(err) => {
+                tracing::error!(%err, "REST integration failed to start");
+                integrations.set_status("rest", IntegrationStatus::Failed(err.to_string()));
+            }
+        }
+    }
+
+    if config.integrations.mdns.enabled {
+        let mdns_config = MdnsConfig {
+            announce: config.integrations.mdns.announce,
+            instance_name: config.integrations.mdns.instance_name.clone(),
+            port: config.server.port,
+            address: config.integrations.mdns.address,
+            service_types: config.integrations.mdns.service_types.clone(),
+            browse_interval_secs: config.integrations.mdns.browse_interval_secs,
+        };
+        tracing::info!(
+            service_types = config.integrations.mdns.service_types.len(),
+            "starting mDNS integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            MdnsIntegration::new(mdns_config),
+            &ctx,
+        );
+    }
+
+    if config.integrations.telegram.enabled {
+        let telegram_config = TelegramConfig {
+            bot_token: secret(&config.integrations.telegram.bot_token_secret)
+                .unwrap_or_else(|| config.integrations.telegram.bot_token.clone()),
+            allowed_chat_ids: config.integrations.telegram.allowed_chat_ids.clone(),
+            poll_timeout_secs: config.integrations.telegram.poll_timeout_secs,
+            ..TelegramConfig::default()
+        };
+        let notification_service =
+            NotificationService::new(TelegramNotifier::new(&telegram_config)?);
+        let notification_rx = dispatcher.subscribe_filtered(notification_service.event_filter());
+        coordinator.spawn("telegram_notifications", async move {
+            notification_service.run(notification_rx).await;
+        });
+        tracing::info!(
+            chats = config.integrations.telegram.allowed_chat_ids.len(),
+            "starting Telegram integration"
+        );
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            TelegramIntegration::new(telegram_config),
+            &ctx,
+        );
     }
 
     if !config.plants.is_empty() {
@@ -199,73 +946,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 conductivity_high: pc.conductivity_high,
             })
             .collect();
-        let mut integration = PlantIntegration::new(plant_configs);
-        integration.setup(&ctx).await?;
-        integration.start_background(ctx.clone()).await?;
-        tracing::info!(count = config.plants.len(), "plant integration ready");
+        tracing::info!(count = config.plants.len(), "starting plant integration");
+        spawn_integration(
+            &coordinator,
+            &integrations,
+            PlantIntegration::new(plant_configs),
+            &ctx,
+        );
     }
 
-    // Background purge task — removes old entity history records
-    let hr_purge = Arc::clone(&history_repo);
-    let retention_days = config.history.retention_days;
-    let purge_interval_hours = config.history.purge_interval_hours;
-    tokio::spawn(async move {
-        let interval_duration =
-            std::time::Duration::from_secs(u64::from(purge_interval_hours) * 3600);
-        let mut interval = tokio::time::interval(interval_duration);
-        interval.tick().await; // First tick completes immediately
-
-        loop {
-            interval.tick().await;
-
-            let retention_secs = i64::from(retention_days) * 24 * 3600;
-            let cutoff = minihub_domain::time::now()
-                - std::time::Duration::from_secs(retention_secs.unsigned_abs());
-            match hr_purge.purge_before(cutoff).await {
-                Ok(count) => {
-                    if count > 0 {
-                        tracing::info!(count, retention_days, "purged old entity history records");
-                    } else {
-                        tracing::debug!(retention_days, "no old entity history records to purge");
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!(%err, "failed to purge old entity history");
-                }
-            }
+    let shutdown = async move {
+        shutdown_signal().await;
+        #[cfg(feature = "systemd")]
+        if let Some(notifier) = notifier {
+            notifier.stopping();
         }
-    });
-    tracing::info!(
-        retention_days,
-        purge_interval_hours,
-        "entity history retention configured"
-    );
-
-    // HTTP
-    let state = AppState::from_arcs(
-        entity_service,
-        device_service,
-        area_service,
-        event_store,
-        automation_service,
-        history_repo,
-        event_bus,
-    );
-    let dashboard_dir = config.dashboard_dir();
-    let app = minihub_adapter_http_axum::router::build(state, dashboard_dir.as_deref());
-
-    let bind_addr = config.bind_addr();
-    tracing::info!(addr = %bind_addr, "minihubd listening");
-
-    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
+    };
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown)
         .await?;
 
+    let grace = std::time::Duration::from_secs(config.server.shutdown_timeout_secs);
+    let aborted = coordinator.shutdown(grace).await;
+    if !aborted.is_empty() {
+        tracing::warn!(
+            ?aborted,
+            "some tasks did not stop within the shutdown timeout"
+        );
+    }
+
     tracing::info!("shutdown complete");
     Ok(())
 }
 
+/// Start `integration` in its own task, reporting its status to `manager`
+/// and applying the restart, disable and enable requests made through it
+/// until `coordinator` shuts it down.
+fn spawn_integration<I, DR, ER, EP, DSR>(
+    coordinator: &ShutdownCoordinator,
+    manager: &IntegrationManager,
+    integration: I,
+    ctx: &ServiceContext<DR, ER, EP, DSR>,
+) where
+    I: Integration + Send + 'static,
+    EP: Clone,
+    ServiceContext<DR, ER, EP, DSR>: IntegrationContext + 'static,
+{
+    let manager = manager.clone();
+    let ctx = ctx.for_integration(integration.name());
+    coordinator.spawn_graceful(integration.name(), |signal| async move {
+        manager.supervise(integration, ctx, signal).await;
+    });
+}
+
+/// Sample the size of the connection pools of `db` into `metrics` every few
+/// seconds.
+fn spawn_pool_sampler(coordinator: &ShutdownCoordinator, db: Database, metrics: Metrics) {
+    #[allow(clippy::cast_precision_loss)]
+    coordinator.spawn("pool_sampler", async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            metrics.set(&DB_POOL_CONNECTIONS, &[], f64::from(db.connections()));
+            metrics.set(&DB_POOL_IDLE_CONNECTIONS, &[], db.idle_connections() as f64);
+        }
+    });
+}
+
 /// Wait for a shutdown signal (Ctrl-C or SIGTERM).
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -0,0 +1,477 @@
+//! Configuration hot reload — re-reads the configuration on SIGHUP or
+//! `POST /api/config/reload` and applies what can change while the daemon
+//! runs.
+//!
+//! The log filter, the history retention and the integration `enabled`
+//! flags are applied in place. Everything else — the listener, the
+//! database, integration settings — is reported as requiring a restart.
+//! Integrations disabled at startup were never created, so enabling one
+//! also requires a restart.
+
+use minihub_app::config_reload::{ReloadError, ReloadReport, ReloadRequests};
+use minihub_app::integration_manager::IntegrationManager;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{Config, HistoryConfig, IntegrationsConfig};
+
+/// A setting that can be applied to the running daemon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    LogFilter,
+    History(&'static str),
+    Enable(&'static str),
+    Disable(&'static str),
+}
+
+impl Setting {
+    fn key(self) -> String {
+        match self {
+            Self::LogFilter => "logging.filter".to_string(),
+            Self::History(field) => format!("history.{field}"),
+            Self::Enable(name) | Self::Disable(name) => section(name),
+        }
+    }
+}
+
+/// Settings changed by a new configuration.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Plan {
+    apply: Vec<Setting>,
+    requires_restart: Vec<String>,
+}
+
+/// Configuration key of the integration `name`.
+fn section(name: &str) -> String {
+    if name == "plants" {
+        name.to_string()
+    } else {
+        format!("integrations.{name}")
+    }
+}
+
+/// Whether `old` and `new` differ in anything but their `enabled` flag.
+fn settings_changed<T: Clone + PartialEq>(
+    old: &T,
+    new: &T,
+    enabled: fn(&mut T) -> &mut bool,
+) -> bool {
+    let mut old = old.clone();
+    let mut new = new.clone();
+    *enabled(&mut old) = false;
+    *enabled(&mut new) = false;
+    old != new
+}
+
+/// Whether the settings of the integration `name` differ between `old` and
+/// `new`, its `enabled` flag aside.
+fn integration_changed(name: &str, old: &Config, new: &Config) -> bool {
+    if name == "plants" {
+        return old.plants != new.plants;
+    }
+    let (old, new) = (&old.integrations, &new.integrations);
+    match name {
+        "mqtt" => settings_changed(&old.mqtt, &new.mqtt, |c| &mut c.enabled),
+        "zigbee2mqtt" => settings_changed(&old.zigbee2mqtt, &new.zigbee2mqtt, |c| &mut c.enabled),
+        "remote" => settings_changed(&old.remote, &new.remote, |c| &mut c.enabled),
+        "ble" => settings_changed(&old.ble, &new.ble, |c| &mut c.enabled),
+        "esphome" => settings_changed(&old.esphome, &new.esphome, |c| &mut c.enabled),
+        "shelly" => settings_changed(&old.shelly, &new.shelly, |c| &mut c.enabled),
+        "rest" => settings_changed(&old.rest, &new.rest, |c| &mut c.enabled),
+        "telegram" => settings_changed(&old.telegram, &new.telegram, |c| &mut c.enabled),
+        "mdns" => settings_changed(&old.mdns, &new.mdns, |c| &mut c.enabled),
+        _ => false,
+    }
+}
+
+/// Changed settings that only take effect after a restart.
+///
+/// Every setting is destructured, so a new one does not compile until it
+/// is either listed here or applied in place by [`plan`].
+fn restart_sections(startup: &Config, new: &Config) -> Vec<String> {
+    let Config {
+        data_dir,
+        automations_dir,
+        server,
+        database,
+        // Applied in place.
+        logging: _,
+        integrations,
+        history,
+        events,
+        notifications,
+        location,
+        secrets,
+        // Restarts the `plants` integration.
+        plants: _,
+    } = startup;
+    let HistoryConfig {
+        // Applied in place.
+        retention_days: _,
+        purge_interval_hours: _,
+        min_interval_secs,
+        min_delta,
+        snapshot_interval_secs,
+    } = history;
+    let IntegrationsConfig {
+        setup_timeout_secs,
+        restart_max_attempts,
+        restart_max_backoff_secs,
+        // Toggled in place, and restarted when their settings change.
+        virtual_enabled: _,
+        mqtt: _,
+        zigbee2mqtt: _,
+        remote: _,
+        ble: _,
+        esphome: _,
+        shelly: _,
+        rest: _,
+        telegram: _,
+        mdns: _,
+    } = integrations;
+    let (new_history, new_integrations) = (&new.history, &new.integrations);
+    [
+        ("data_dir", *data_dir != new.data_dir),
+        ("automations_dir", *automations_dir != new.automations_dir),
+        ("server", *server != new.server),
+        ("database", *database != new.database),
+        ("notifications", *notifications != new.notifications),
+        ("events", *events != new.events),
+        ("location", *location != new.location),
+        ("secrets", *secrets != new.secrets),
+        (
+            "history.min_interval_secs",
+            *min_interval_secs != new_history.min_interval_secs,
+        ),
+        (
+            "history.min_delta",
+            min_delta.to_bits() != new_history.min_delta.to_bits(),
+        ),
+        (
+            "history.snapshot_interval_secs",
+            *snapshot_interval_secs != new_history.snapshot_interval_secs,
+        ),
+        (
+            "integrations.setup_timeout_secs",
+            *setup_timeout_secs != new_integrations.setup_timeout_secs,
+        ),
+        (
+            "integrations.restart_max_attempts",
+            *restart_max_attempts != new_integrations.restart_max_attempts,
+        ),
+        (
+            "integrations.restart_max_backoff_secs",
+            *restart_max_backoff_secs != new_integrations.restart_max_backoff_secs,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(key, _)| key.to_string())
+    .collect()
+}
+
+/// Compare `new` with the `current` configuration for the settings applied
+/// in place, and with the `startup` one for those needing a restart.
+fn plan(startup: &Config, current: &Config, new: &Config) -> Plan {
+    let mut plan = Plan {
+        requires_restart: restart_sections(startup, new),
+        ..Plan::default()
+    };
+    if current.logging.filter != new.logging.filter {
+        plan.apply.push(Setting::LogFilter);
+    }
+    if current.history.retention_days != new.history.retention_days {
+        plan.apply.push(Setting::History("retention_days"));
+    }
+    if current.history.purge_interval_hours != new.history.purge_interval_hours {
+        plan.apply.push(Setting::History("purge_interval_hours"));
+    }
+    let toggles = startup
+        .integration_toggles()
+        .into_iter()
+        .zip(current.integration_toggles())
+        .zip(new.integration_toggles());
+    for (((name, started), (_, was)), (_, is)) in toggles {
+        if !started {
+            if is {
+                plan.requires_restart.push(section(name));
+            }
+            continue;
+        }
+        match (was, is) {
+            (false, true) => plan.apply.push(Setting::Enable(name)),
+            (true, false) => plan.apply.push(Setting::Disable(name)),
+            _ => {}
+        }
+        if is && integration_changed(name, startup, new) {
+            plan.requires_restart.push(section(name));
+        }
+    }
+    plan
+}
+
+/// Applies reloaded configurations to the running daemon.
+pub struct Reloader<F> {
+    startup: Config,
+    current: Config,
+    integrations: IntegrationManager,
+    history: watch::Sender<HistoryConfig>,
+    set_log_filter: F,
+}
+
+impl<F> Reloader<F>
+where
+    F: Fn(EnvFilter) -> Result<(), String> + Send + 'static,
+{
+    /// Create a reloader for a daemon started with `config`.
+    ///
+    /// `history` carries the retention settings to the purge task and
+    /// `set_log_filter` replaces the filter of the installed subscriber.
+    pub fn new(
+        config: Config,
+        integrations: IntegrationManager,
+        history: watch::Sender<HistoryConfig>,
+        set_log_filter: F,
+    ) -> Self {
+        Self {
+            startup: config.clone(),
+            current: config,
+            integrations,
+            history,
+            set_log_filter,
+        }
+    }
+
+    /// Reload the configuration on every SIGHUP and every request received
+    /// on `requests`, until the request channel closes.
+    pub async fn run(mut self, mut requests: ReloadRequests) {
+        #[cfg(unix)]
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("failed to install SIGHUP handler");
+
+        loop {
+            #[cfg(unix)]
+            let hangup_received = hangup.recv();
+            #[cfg(not(unix))]
+            let hangup_received = std::future::pending::<Option<()>>();
+
+            tokio::select! {
+                request = requests.recv() => match request {
+                    Some(request) => request.respond(self.reload_from_file()),
+                    None => break,
+                },
+                Some(()) = hangup_received => {
+                    tracing::info!("received SIGHUP, reloading configuration");
+                    if let Err(err) = self.reload_from_file() {
+                        tracing::warn!(%err, "configuration reload failed");
+                    }
+                }
+            }
+        }
+    }
+
+    fn reload_from_file(&mut self) -> Result<ReloadReport, ReloadError> {
+        let config = Config::load().map_err(|err| ReloadError::InvalidConfig(err.to_string()))?;
+        self.reload(config)
+    }
+
+    /// Apply the settings of `config` that changed since the last reload.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReloadError::InvalidConfig`] when the log filter does not
+    /// parse; nothing is applied then.
+    pub fn reload(&mut self, config: Config) -> Result<ReloadReport, ReloadError> {
+        let plan = plan(&self.startup, &self.current, &config);
+        let mut filter = if plan.apply.contains(&Setting::LogFilter) {
+            let filter = EnvFilter::try_new(&config.logging.filter).map_err(|err| {
+                ReloadError::InvalidConfig(format!("logging: invalid filter: {err}"))
+            })?;
+            Some(filter)
+        } else {
+            None
+        };
+
+        let mut report = ReloadReport {
+            applied: Vec::new(),
+            requires_restart: plan.requires_restart,
+        };
+        for setting in plan.apply {
+            let outcome = match setting {
+                Setting::LogFilter => match filter.take() {
+                    Some(filter) => (self.set_log_filter)(filter),
+                    None => Ok(()),
+                },
+                Setting::History(_) => {
+                    self.history.send_replace(config.history.clone());
+                    Ok(())
+                }
+                Setting::Enable(name) => self
+                    .integrations
+                    .enable(name)
+                    .map_err(|err| err.to_string()),
+                Setting::Disable(name) => self
+                    .integrations
+                    .disable(name)
+                    .map_err(|err| err.to_string()),
+            };
+            match outcome {
+                Ok(()) => report.applied.push(setting.key()),
+                Err(err) => {
+                    tracing::warn!(setting = %setting.key(), %err, "failed to apply setting");
+                    if !report.requires_restart.contains(&setting.key()) {
+                        report.requires_restart.push(setting.key());
+                    }
+                }
+            }
+        }
+        self.current = config;
+
+        tracing::info!(
+            applied = ?report.applied,
+            requires_restart = ?report.requires_restart,
+            "configuration reloaded"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reloader(config: Config) -> Reloader<impl Fn(EnvFilter) -> Result<(), String>> {
+        let (history, _) = watch::channel(config.history.clone());
+        Reloader::new(config, IntegrationManager::default(), history, |_| Ok(()))
+    }
+
+    #[test]
+    fn should_plan_nothing_when_config_is_unchanged() {
+        let config = Config::default();
+
+        assert_eq!(plan(&config, &config, &config), Plan::default());
+    }
+
+    #[test]
+    fn should_apply_log_filter_and_retention_in_place() {
+        let config = Config::default();
+        let mut new = Config::default();
+        new.logging.filter = "debug".to_string();
+        new.history.retention_days = 7;
+
+        let plan = plan(&config, &config, &new);
+
+        assert_eq!(
+            plan.apply,
+            vec![Setting::LogFilter, Setting::History("retention_days")]
+        );
+        assert!(plan.requires_restart.is_empty());
+    }
+
+    #[test]
+    fn should_require_restart_when_server_changes() {
+        let config = Config::default();
+        let mut new = Config::default();
+        new.server.port = 9090;
+
+        let plan = plan(&config, &config, &new);
+
+        assert!(plan.apply.is_empty());
+        assert_eq!(plan.requires_restart, vec!["server".to_string()]);
+    }
+
+    #[test]
+    fn should_require_restart_when_location_changes() {
+        let config = Config::default();
+        let mut new = Config::default();
+        new.location.latitude = Some(48.8566);
+        new.location.longitude = Some(2.3522);
+
+        let plan = plan(&config, &config, &new);
+
+        assert!(plan.apply.is_empty());
+        assert_eq!(plan.requires_restart, vec!["location".to_string()]);
+    }
+
+    #[test]
+    fn should_require_restart_when_secret_key_file_changes() {
+        let config = Config::default();
+        let mut new = Config::default();
+        new.secrets.key_file = Some("/etc/minihub/secret.key".to_string());
+
+        let plan = plan(&config, &config, &new);
+
+        assert!(plan.apply.is_empty());
+        assert_eq!(plan.requires_restart, vec!["secrets".to_string()]);
+    }
+
+    #[test]
+    fn should_toggle_integration_enabled_at_startup() {
+        let mut config = Config::default();
+        config.integrations.mqtt.enabled = true;
+        let mut disabled = config.clone();
+        disabled.integrations.mqtt.enabled = false;
+
+        assert_eq!(
+            plan(&config, &config, &disabled).apply,
+            vec![Setting::Disable("mqtt")]
+        );
+        assert_eq!(
+            plan(&config, &disabled, &config).apply,
+            vec![Setting::Enable("mqtt")]
+        );
+    }
+
+    #[test]
+    fn should_require_restart_when_enabling_integration_disabled_at_startup() {
+        let config = Config::default();
+        let mut new = Config::default();
+        new.integrations.ble.enabled = true;
+
+        let plan = plan(&config, &config, &new);
+
+        assert!(plan.apply.is_empty());
+        assert_eq!(plan.requires_restart, vec!["integrations.ble".to_string()]);
+    }
+
+    #[test]
+    fn should_require_restart_when_integration_settings_change() {
+        let mut config = Config::default();
+        config.integrations.mqtt.enabled = true;
+        let mut new = config.clone();
+        new.integrations.mqtt.broker_port = 8883;
+
+        let plan = plan(&config, &config, &new);
+
+        assert!(plan.apply.is_empty());
+        assert_eq!(plan.requires_restart, vec!["integrations.mqtt".to_string()]);
+    }
+
+    #[test]
+    fn should_reject_invalid_log_filter_without_applying_anything() {
+        let mut reloader = reloader(Config::default());
+        let mut new = Config::default();
+        new.logging.filter = "[invalid".to_string();
+        new.history.retention_days = 7;
+
+        let err = reloader.reload(new).unwrap_err();
+
+        assert!(matches!(err, ReloadError::InvalidConfig(_)));
+        assert_eq!(reloader.current, Config::default());
+    }
+
+    #[test]
+    fn should_publish_history_settings_when_retention_changes() {
+        let config = Config::default();
+        let (history, receiver) = watch::channel(config.history.clone());
+        let mut reloader =
+            Reloader::new(config, IntegrationManager::default(), history, |_| Ok(()));
+        let mut new = Config::default();
+        new.history.retention_days = 7;
+
+        let report = reloader.reload(new).unwrap();
+
+        assert_eq!(report.applied, vec!["history.retention_days".to_string()]);
+        assert_eq!(receiver.borrow().retention_days, 7);
+    }
+}
@@ -0,0 +1,167 @@
+//! systemd service notifications (`sd_notify` protocol).
+//!
+//! When started by systemd with `Type=notify`, the daemon reports
+//! `READY=1` once the listener is bound and `STOPPING=1` when it starts
+//! shutting down. With `WatchdogSec=` set, it also sends `WATCHDOG=1`
+//! heartbeats at half the watchdog interval, so a wedged runtime gets
+//! restarted.
+//!
+//! Notifications are datagrams sent to the socket named by
+//! `NOTIFY_SOCKET`; outside of systemd the variable is unset and every
+//! notification is a no-op.
+
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::time::Duration;
+
+/// Sends state notifications to the service manager.
+#[derive(Debug)]
+pub struct Notifier {
+    socket: UnixDatagram,
+    addr: SocketAddr,
+}
+
+impl Notifier {
+    /// Connect to the socket named by `NOTIFY_SOCKET`, or `None` when not
+    /// running under systemd.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("NOTIFY_SOCKET").ok()?;
+        match Self::connect(&path) {
+            Ok(notifier) => Some(notifier),
+            Err(err) => {
+                tracing::warn!(%err, path, "cannot use systemd notification socket");
+                None
+            }
+        }
+    }
+
+    /// Connect to `path`, a filesystem path or, when it starts with `@`, a
+    /// Linux abstract socket name.
+    fn connect(path: &str) -> std::io::Result<Self> {
+        let addr = match path.strip_prefix('@') {
+            Some(name) => abstract_addr(name)?,
+            None => SocketAddr::from_pathname(path)?,
+        };
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            addr,
+        })
+    }
+
+    /// Send `state`, e.g. `"READY=1"`, logging failures.
+    fn notify(&self, state: &str) {
+        if let Err(err) = self.socket.send_to_addr(state.as_bytes(), &self.addr) {
+            tracing::warn!(%err, state, "failed to notify systemd");
+        }
+    }
+
+    /// The daemon finished starting up.
+    pub fn ready(&self) {
+        self.notify("READY=1");
+    }
+
+    /// The daemon is shutting down.
+    pub fn stopping(&self) {
+        self.notify("STOPPING=1");
+    }
+
+    /// Keep-alive heartbeat for the service watchdog.
+    pub fn watchdog(&self) {
+        self.notify("WATCHDOG=1");
+    }
+
+    /// Send [`watchdog`](Self::watchdog) heartbeats from the runtime at half
+    /// the interval systemd expects, when `WatchdogSec=` is set for this
+    /// process.
+    pub fn spawn_watchdog(self: std::sync::Arc<Self>) {
+        let usec = std::env::var("WATCHDOG_USEC").ok();
+        let pid = std::env::var("WATCHDOG_PID").ok();
+        let Some(interval) =
+            heartbeat_interval(usec.as_deref(), pid.as_deref(), std::process::id())
+        else {
+            return;
+        };
+        tracing::info!(
+            interval_ms = interval.as_millis(),
+            "systemd watchdog enabled"
+        );
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                self.watchdog();
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn abstract_addr(name: &str) -> std::io::Result<SocketAddr> {
+    use std::os::linux::net::SocketAddrExt as _;
+
+    SocketAddr::from_abstract_name(name.as_bytes())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn abstract_addr(_name: &str) -> std::io::Result<SocketAddr> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "abstract sockets are only supported on Linux",
+    ))
+}
+
+/// Heartbeat interval for `WATCHDOG_USEC` and `WATCHDOG_PID`: half the
+/// watchdog timeout, or `None` when the watchdog is off or meant for another
+/// process.
+fn heartbeat_interval(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid
+        && pid.parse::<u32>().ok() != Some(own_pid)
+    {
+        return None;
+    }
+    let usec = usec?.parse::<u64>().ok().filter(|usec| *usec > 0)?;
+    Some(Duration::from_micros(usec) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_send_states_to_notify_socket() {
+        let dir = std::env::temp_dir().join(format!("minihubd-notify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notify.sock");
+        let _ = std::fs::remove_file(&path);
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        let notifier = Notifier::connect(path.to_str().unwrap()).unwrap();
+        notifier.ready();
+        notifier.stopping();
+
+        let mut buf = [0u8; 64];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"STOPPING=1");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn should_beat_at_half_the_watchdog_timeout() {
+        assert_eq!(
+            heartbeat_interval(Some("10000000"), None, 42),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            heartbeat_interval(Some("10000000"), Some("42"), 42),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn should_skip_watchdog_when_disabled_or_for_another_process() {
+        assert_eq!(heartbeat_interval(None, None, 42), None);
+        assert_eq!(heartbeat_interval(Some("0"), None, 42), None);
+        assert_eq!(heartbeat_interval(Some("10000000"), Some("7"), 42), None);
+    }
+}
@@ -7,50 +7,121 @@
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
+use minihub_adapter_http_axum::auth::{AccessControl, ApiToken};
 use minihub_adapter_http_axum::router;
-use minihub_adapter_http_axum::state::AppState;
+use minihub_adapter_http_axum::state::{AppState, Ports};
 use minihub_adapter_storage_sqlite_sqlx::{
-    Config, SqliteAreaRepository, SqliteAutomationRepository, SqliteDeviceRepository,
-    SqliteEntityHistoryRepository, SqliteEntityRepository, SqliteEventStore,
+    Config, SecretKey, SqliteAreaRepository, SqliteAuditRepository, SqliteAutomationRepository,
+    SqliteAutomationRunRepository, SqliteDeviceRepository, SqliteDiscoveryRepository,
+    SqliteEnergyUsageRepository, SqliteEntityHistoryRepository, SqliteEntityRepository,
+    SqliteEventStore, SqliteGroupRepository, SqliteReportRepository, SqliteSceneRepository,
+    SqliteSecretsRepository, SqliteSettingsRepository,
 };
 use minihub_adapter_virtual::VirtualIntegration;
+use minihub_app::audit::Audited;
 use minihub_app::event_bus::InProcessEventBus;
 use minihub_app::ports::{EventStore, Integration};
 use minihub_app::services::area_service::AreaService;
 use minihub_app::services::automation_service::AutomationService;
 use minihub_app::services::device_service::DeviceService;
 use minihub_app::services::entity_service::EntityService;
+use minihub_app::services::group_service::GroupService;
 use minihub_app::services::integration_context::ServiceContext;
+use minihub_app::services::scene_service::SceneService;
+use minihub_app::services::secrets_service::SecretsService;
+use minihub_domain::role::Role;
 use std::sync::Arc;
 use tower::ServiceExt;
 
+/// `SQLite` repositories wired as in `main.rs`.
+struct SqlitePorts;
+
+impl Ports for SqlitePorts {
+    type Entities = Audited<SqliteEntityRepository, SqliteAuditRepository>;
+    type Devices = Audited<SqliteDeviceRepository, SqliteAuditRepository>;
+    type Areas = Audited<SqliteAreaRepository, SqliteAuditRepository>;
+    type Publisher = Arc<InProcessEventBus>;
+    type EventStore = SqliteEventStore;
+    type Automations = Audited<SqliteAutomationRepository, SqliteAuditRepository>;
+    type EntityHistory = SqliteEntityHistoryRepository;
+    type AutomationRuns = SqliteAutomationRunRepository;
+    type Reports = SqliteReportRepository;
+    type Scenes = Audited<SqliteSceneRepository, SqliteAuditRepository>;
+    type Groups = Audited<SqliteGroupRepository, SqliteAuditRepository>;
+    type Audit = SqliteAuditRepository;
+    type Settings = Audited<SqliteSettingsRepository, SqliteAuditRepository>;
+    type EnergyUsage = SqliteEnergyUsageRepository;
+    type Secrets = Audited<SqliteSecretsRepository, SqliteAuditRepository>;
+}
+
 /// Build a fully-wired router backed by an in-memory `SQLite` database,
 /// including an event-bus → event-store subscriber (mirroring `main.rs`).
 async fn app() -> axum::Router {
-    let db = Config {
-        database_url: "sqlite::memory:".to_string(),
-    }
-    .build()
-    .await
-    .expect("in-memory database should initialise");
+    app_with_access_control(AccessControl::default()).await
+}
+
+/// Build the same router as [`app`], authorizing requests against `access`.
+async fn app_with_access_control(access: AccessControl) -> axum::Router {
+    let db = Config::new("sqlite::memory:".to_string())
+        .build()
+        .await
+        .expect("in-memory database should initialise");
 
     let pool = db.pool().clone();
 
-    let entity_repo = SqliteEntityRepository::new(pool.clone());
-    let device_repo = SqliteDeviceRepository::new(pool.clone());
-    let area_repo = SqliteAreaRepository::new(pool.clone());
+    let audit_repo = Arc::new(SqliteAuditRepository::new(pool.clone()));
+    let entity_repo = Audited::new(
+        SqliteEntityRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let device_repo = Audited::new(
+        SqliteDeviceRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let area_repo = Audited::new(
+        SqliteAreaRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
     let event_store = SqliteEventStore::new(pool.clone());
-    let automation_repo = SqliteAutomationRepository::new(pool.clone());
-    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pool));
+    let automation_repo = Audited::new(
+        SqliteAutomationRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pool.clone()));
+    let automation_run_repo = Arc::new(SqliteAutomationRunRepository::new(pool.clone()));
+    let report_repo = Arc::new(SqliteReportRepository::new(pool.clone()));
+    let scene_repo = Audited::new(
+        SqliteSceneRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let group_repo = Audited::new(
+        SqliteGroupRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let settings_repo = Audited::new(
+        SqliteSettingsRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let energy_usage_repo = Arc::new(SqliteEnergyUsageRepository::new(pool.clone()));
+    let secrets_service = Arc::new(SecretsService::new(Audited::new(
+        SqliteSecretsRepository::new(pool, &SecretKey::generate()),
+        Arc::clone(&audit_repo),
+    )));
 
     let event_bus = Arc::new(InProcessEventBus::new(256));
     let mut event_rx = event_bus.subscribe();
 
     let entity_service = Arc::new(EntityService::new(entity_repo, Arc::clone(&event_bus)));
+    let scene_service = Arc::new(SceneService::new(scene_repo, Arc::clone(&event_bus)));
     let device_service = Arc::new(DeviceService::new(device_repo));
     let area_service = Arc::new(AreaService::new(area_repo));
     let event_store = Arc::new(event_store);
     let automation_service = Arc::new(AutomationService::new(automation_repo));
+    let group_service = Arc::new(GroupService::new(
+        group_repo,
+        Arc::clone(&device_service),
+        Arc::clone(&entity_service),
+    ));
 
     // Wire event-bus → event-store subscriber (same as main.rs)
     let es = Arc::clone(&event_store);
@@ -60,15 +131,24 @@ async fn app() -> axum::Router {
         }
     });
 
-    let state = AppState::from_arcs(
+    let state = AppState::<SqlitePorts>::from_arcs(
         entity_service,
         device_service,
         area_service,
         event_store,
         automation_service,
         history_repo,
+        automation_run_repo,
+        report_repo,
+        scene_service,
+        group_service,
+        audit_repo,
+        Arc::new(settings_repo),
+        energy_usage_repo,
+        secrets_service,
         event_bus,
-    );
+    )
+    .with_access_control(access);
 
     router::build(state, None)
 }
@@ -370,6 +450,169 @@ async fn should_complete_area_crud_cycle() {
     assert_eq!(resp.status(), StatusCode::NO_CONTENT);
 }
 
+#[tokio::test]
+async fn should_record_area_writes_in_audit_log_with_actor() {
+    let app = app().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/areas")
+                .header("content-type", "application/json")
+                .header("sec-fetch-site", "same-origin")
+                .body(Body::from(r#"{"name":"Kitchen"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let area_id = body["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/areas/{area_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/audit?target_type=area&target_id={area_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "delete");
+    assert_eq!(entries[0]["actor"], "api");
+    assert_eq!(entries[0]["before"]["name"], "Kitchen");
+    assert_eq!(entries[1]["action"], "create");
+    assert_eq!(entries[1]["actor"], "dashboard");
+}
+
+#[tokio::test]
+async fn should_authorize_writes_by_token_role_and_audit_the_token() {
+    let token = |name: &str, role| ApiToken {
+        name: name.to_string(),
+        token: format!("{name}-0123456789abcdef"),
+        role,
+    };
+    let app = app_with_access_control(
+        AccessControl::new(vec![
+            token("grafana", Role::Viewer),
+            token("ops", Role::Admin),
+        ])
+        .with_anonymous_role(Some(Role::Viewer)),
+    )
+    .await;
+    let create_area = |token: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/areas")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::from(r#"{"name":"Garage"}"#))
+            .unwrap()
+    };
+
+    let resp = app
+        .clone()
+        .oneshot(create_area("grafana-0123456789abcdef"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .clone()
+        .oneshot(create_area("ops-0123456789abcdef"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/audit?target_type=area&actor=token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "create");
+    assert_eq!(entries[0]["actor"], "token:ops:admin");
+}
+
+// ---------------------------------------------------------------------------
+// API: secrets
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn should_never_echo_secret_values() {
+    let app = app().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets/telegram.bot_token")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"value":"123:abc"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(info["name"], "telegram.bot_token");
+    assert!(info.get("value").is_none());
+
+    for uri in ["/api/secrets", "/api/audit?target_type=secret"] {
+        let resp = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("telegram.bot_token"));
+        assert!(!body.contains("123:abc"));
+    }
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/secrets/telegram.bot_token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+}
+
 // ---------------------------------------------------------------------------
 // API: automation CRUD cycle
 // ---------------------------------------------------------------------------
@@ -499,6 +742,65 @@ async fn should_complete_automation_crud_cycle() {
     assert_eq!(body.len(), 0);
 }
 
+#[tokio::test]
+async fn should_list_automation_runs() {
+    let app = app().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/automations")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{
+                        "name": "Night mode",
+                        "trigger": {"type": "manual"},
+                        "actions": [{"type": "delay", "seconds": 5}]
+                    }"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let automation_id = body["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/automations/{automation_id}/runs"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert!(body.is_empty());
+
+    // Unknown automation
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/api/automations/{}/runs",
+                    minihub_domain::id::AutomationId::new()
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
 // ---------------------------------------------------------------------------
 // API: events are visible after entity state changes
 // ---------------------------------------------------------------------------
@@ -587,77 +889,17 @@ async fn should_list_events_after_entity_state_change() {
     assert!(types.contains(&"state_changed"));
 }
 
-// ---------------------------------------------------------------------------
-// Virtual integration — full lifecycle through the stack
-// ---------------------------------------------------------------------------
-
-/// Build a fully-wired router that also runs the virtual integration setup,
-/// mirroring what `minihubd` does on startup.
-async fn app_with_virtual() -> axum::Router {
-    let db = Config {
-        database_url: "sqlite::memory:".to_string(),
-    }
-    .build()
-    .await
-    .expect("in-memory database should initialise");
-
-    let pool = db.pool().clone();
-
-    let entity_repo = SqliteEntityRepository::new(pool.clone());
-    let device_repo = SqliteDeviceRepository::new(pool.clone());
-    let area_repo = SqliteAreaRepository::new(pool.clone());
-    let event_store = SqliteEventStore::new(pool.clone());
-    let automation_repo = SqliteAutomationRepository::new(pool.clone());
-    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pool));
-
-    let event_bus = Arc::new(InProcessEventBus::new(256));
-    let mut event_rx = event_bus.subscribe();
-
-    let entity_service = Arc::new(EntityService::new(entity_repo, Arc::clone(&event_bus)));
-    let device_service = Arc::new(DeviceService::new(device_repo));
-    let area_service = Arc::new(AreaService::new(area_repo));
-    let event_store = Arc::new(event_store);
-    let automation_service = Arc::new(AutomationService::new(automation_repo));
-
-    // Wire event-bus → event-store subscriber
-    let es = Arc::clone(&event_store);
-    tokio::spawn(async move {
-        while let Ok(event) = event_rx.recv().await {
-            let _ = es.store(event).await;
-        }
-    });
-
-    // Run virtual integration setup — same as minihubd main()
-    let ctx = ServiceContext::new(
-        Arc::clone(&device_service),
-        Arc::clone(&entity_service),
-        Arc::clone(&event_bus),
-        Arc::clone(&event_bus),
-    );
-    let mut virtual_integration = VirtualIntegration::default();
-    virtual_integration.setup(&ctx).await.unwrap();
-
-    let state = AppState::from_arcs(
-        entity_service,
-        device_service,
-        area_service,
-        event_store,
-        automation_service,
-        history_repo,
-        event_bus,
-    );
-
-    router::build(state, None)
-}
-
 #[tokio::test]
-async fn should_list_virtual_entities_via_api() {
+async fn should_export_events_as_ndjson() {
     let app = app_with_virtual().await;
 
+    // Give the subscriber task time to persist the discovery events
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
     let resp = app
         .oneshot(
             Request::builder()
-                .uri("/api/entities")
+                .uri("/api/events/export?format=ndjson")
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -665,53 +907,414 @@ async fn should_list_virtual_entities_via_api() {
         .unwrap();
 
     assert_eq!(resp.status(), StatusCode::OK);
-    let body: Vec<serde_json::Value> =
-        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
-
-    assert_eq!(body.len(), 3);
-
-    let entity_ids: Vec<&str> = body
-        .iter()
-        .map(|e| e["entity_id"].as_str().unwrap())
+    assert_eq!(resp.headers()["content-type"], "application/x-ndjson");
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let events: Vec<serde_json::Value> = std::str::from_utf8(&bytes)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
         .collect();
-    assert!(entity_ids.contains(&"light.virtual_light"));
-    assert!(entity_ids.contains(&"sensor.virtual_temperature"));
-    assert!(entity_ids.contains(&"switch.virtual_switch"));
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|e| e["event_type"] == "entity_created"));
 }
 
 #[tokio::test]
-async fn should_list_virtual_devices_via_api() {
+async fn should_page_events_with_cursor() {
     let app = app_with_virtual().await;
 
+    // Give the subscriber task time to persist the discovery events
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
     let resp = app
+        .clone()
         .oneshot(
             Request::builder()
-                .uri("/api/devices")
+                .uri("/api/events?event_type=entity_created&limit=2")
                 .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
-
     assert_eq!(resp.status(), StatusCode::OK);
-    let body: Vec<serde_json::Value> =
-        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
-
-    assert_eq!(body.len(), 3);
-
-    let names: Vec<&str> = body.iter().map(|d| d["name"].as_str().unwrap()).collect();
-    assert!(names.contains(&"Virtual Light"));
-    assert!(names.contains(&"Virtual Sensor"));
-    assert!(names.contains(&"Virtual Switch"));
-}
-
-#[tokio::test]
-async fn should_update_virtual_entity_state_via_api() {
-    let app = app_with_virtual().await;
+    let cursor = resp.headers()["x-next-cursor"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let first: Vec<serde_json::Value> = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(first.len(), 2);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/api/events?event_type=entity_created&limit=2&cursor={cursor}"
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get("x-next-cursor").is_none());
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let second: Vec<serde_json::Value> = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(second.len(), 1);
+    assert!(first.iter().all(|e| e["id"] != second[0]["id"]));
+}
+
+#[tokio::test]
+async fn should_reject_unknown_export_format() {
+    let app = app().await;
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/events/export?format=csv")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_export_nothing_when_range_is_empty() {
+    let app = app_with_virtual().await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/events/export?to=2000-01-01T00:00:00Z")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    assert!(bytes.is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// API: reports
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn should_report_overview_counts() {
+    let app = app().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/areas")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"name": "Kitchen"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/reports/overview?hours=1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["areas"], 1);
+    assert_eq!(body["entities"], 0);
+}
+
+#[tokio::test]
+async fn should_complete_scene_crud_cycle() {
+    let app = app().await;
+    let entity_id = minihub_domain::id::EntityId::new();
+
+    // Create
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/scenes")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"name":"Movie night","members":[{{"entity_id":"{entity_id}","state":"on","attributes":{{"brightness":30}}}}]}}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let scene_id = body["id"].as_str().unwrap().to_string();
+    assert_eq!(body["members"][0]["attributes"]["brightness"], 30);
+
+    // Update
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/api/scenes/{scene_id}"))
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"name":"Cinema","members":[{{"entity_id":"{entity_id}","state":"off"}}]}}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Get
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/scenes/{scene_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["name"], "Cinema");
+    assert_eq!(body["members"][0]["state"], "off");
+
+    // Reject a scene without members
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/scenes")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"name":"Empty","members":[]}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Delete
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/scenes/{scene_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/scenes/{scene_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+// ---------------------------------------------------------------------------
+// Virtual integration — full lifecycle through the stack
+// ---------------------------------------------------------------------------
+
+/// Build a fully-wired router that also runs the virtual integration setup,
+/// mirroring what `minihubd` does on startup.
+async fn app_with_virtual() -> axum::Router {
+    let db = Config::new("sqlite::memory:".to_string())
+        .build()
+        .await
+        .expect("in-memory database should initialise");
+
+    let pool = db.pool().clone();
+
+    let audit_repo = Arc::new(SqliteAuditRepository::new(pool.clone()));
+    let entity_repo = Audited::new(
+        SqliteEntityRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let device_repo = Audited::new(
+        SqliteDeviceRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let area_repo = Audited::new(
+        SqliteAreaRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let event_store = SqliteEventStore::new(pool.clone());
+    let automation_repo = Audited::new(
+        SqliteAutomationRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let history_repo = Arc::new(SqliteEntityHistoryRepository::new(pool.clone()));
+    let automation_run_repo = Arc::new(SqliteAutomationRunRepository::new(pool.clone()));
+    let report_repo = Arc::new(SqliteReportRepository::new(pool.clone()));
+    let scene_repo = Audited::new(
+        SqliteSceneRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+    let group_repo = Audited::new(
+        SqliteGroupRepository::new(pool.clone()),
+        Arc::clone(&audit_repo),
+    );
+
+    let event_bus = Arc::new(InProcessEventBus::new(256));
+    let mut event_rx = event_bus.subscribe();
+
+    let entity_service = Arc::new(EntityService::new(entity_repo, Arc::clone(&event_bus)));
+    let scene_service = Arc::new(SceneService::new(scene_repo, Arc::clone(&event_bus)));
+    let device_service = Arc::new(DeviceService::new(device_repo));
+    let area_service = Arc::new(AreaService::new(area_repo));
+    let event_store = Arc::new(event_store);
+    let automation_service = Arc::new(AutomationService::new(automation_repo));
+    let group_service = Arc::new(GroupService::new(
+        group_repo,
+        Arc::clone(&device_service),
+        Arc::clone(&entity_service),
+    ));
+
+    // Wire event-bus → event-store subscriber
+    let es = Arc::clone(&event_store);
+    tokio::spawn(async move {
+        while let Ok(event) = event_rx.recv().await {
+            let _ = es.store(event).await;
+        }
+    });
+
+    // Run virtual integration setup — same as minihubd main()
+    let ctx = ServiceContext::new(
+        Arc::clone(&device_service),
+        Arc::clone(&entity_service),
+        SqliteDiscoveryRepository::new(pool.clone()),
+        Arc::clone(&event_bus),
+        Arc::clone(&event_bus),
+    );
+    let mut virtual_integration = VirtualIntegration::default();
+    virtual_integration.setup(&ctx).await.unwrap();
+    virtual_integration
+        .start_background(ctx.clone())
+        .await
+        .unwrap();
+
+    // Keep groups in sync — same as minihubd main()
+    let group_rx = event_bus.subscribe_filtered(group_service.event_filter());
+    let group_runner = Arc::clone(&group_service);
+    tokio::spawn(async move { group_runner.run(group_rx).await });
+
+    let state = AppState::<SqlitePorts>::from_arcs(
+        entity_service,
+        device_service,
+        area_service,
+        event_store,
+        automation_service,
+        history_repo,
+        automation_run_repo,
+        report_repo,
+        scene_service,
+        group_service,
+        Arc::clone(&audit_repo),
+        Arc::new(Audited::new(
+            SqliteSettingsRepository::new(pool.clone()),
+            Arc::clone(&audit_repo),
+        )),
+        Arc::new(SqliteEnergyUsageRepository::new(pool.clone())),
+        Arc::new(SecretsService::new(Audited::new(
+            SqliteSecretsRepository::new(pool, &SecretKey::generate()),
+            audit_repo,
+        ))),
+        event_bus,
+    );
+
+    router::build(state, None)
+}
+
+#[tokio::test]
+async fn should_list_virtual_entities_via_api() {
+    let app = app_with_virtual().await;
 
-    // Get the light entity id
     let resp = app
-        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/entities")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+
+    assert_eq!(body.len(), 3);
+
+    let entity_ids: Vec<&str> = body
+        .iter()
+        .map(|e| e["entity_id"].as_str().unwrap())
+        .collect();
+    assert!(entity_ids.contains(&"light.virtual_light"));
+    assert!(entity_ids.contains(&"sensor.virtual_temperature"));
+    assert!(entity_ids.contains(&"switch.virtual_switch"));
+}
+
+#[tokio::test]
+async fn should_list_virtual_devices_via_api() {
+    let app = app_with_virtual().await;
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/devices")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+
+    assert_eq!(body.len(), 3);
+
+    let names: Vec<&str> = body.iter().map(|d| d["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"Virtual Light"));
+    assert!(names.contains(&"Virtual Sensor"));
+    assert!(names.contains(&"Virtual Switch"));
+}
+
+#[tokio::test]
+async fn should_update_virtual_entity_state_via_api() {
+    let app = app_with_virtual().await;
+
+    // Get the light entity id
+    let resp = app
+        .clone()
         .oneshot(
             Request::builder()
                 .uri("/api/entities")
@@ -766,6 +1369,290 @@ async fn should_update_virtual_entity_state_via_api() {
     assert_eq!(body["state"], "on");
 }
 
+#[tokio::test]
+async fn should_actuate_virtual_entity_via_service_call() {
+    let app = app_with_virtual().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/entities")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let entities: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let light = entities
+        .iter()
+        .find(|e| e["entity_id"] == "light.virtual_light")
+        .unwrap();
+    let light_id = light["id"].as_str().unwrap();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/entities/{light_id}/service"))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"service":"turn_on"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // The integration handles the request asynchronously
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/entities/{light_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["state"], "on");
+}
+
+#[tokio::test]
+async fn should_trace_service_call_chain() {
+    let app = app_with_virtual().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/entities")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let entities: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let light_id = entities
+        .iter()
+        .find(|e| e["entity_id"] == "light.virtual_light")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/entities/{light_id}/service"))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"service":"turn_on"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // The integration handles the request asynchronously
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/events?event_type=service_call_completed")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let completed: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let completed_id = completed[0]["id"].as_str().unwrap();
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/events/{completed_id}/chain"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let chain: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let types: Vec<&str> = chain
+        .iter()
+        .map(|e| e["event_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types[0], "service_call_requested");
+    assert!(types.contains(&"state_changed"));
+    assert!(types.contains(&"service_call_completed"));
+    let root_id = &chain[0]["id"];
+    assert!(chain.iter().all(|e| &e["correlation_id"] == root_id));
+    assert!(chain[1..].iter().all(|e| &e["caused_by"] == root_id));
+}
+
+#[tokio::test]
+async fn should_actuate_virtual_entities_when_scene_activated() {
+    let app = app_with_virtual().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/entities")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let entities: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let light_id = entities
+        .iter()
+        .find(|e| e["entity_id"] == "light.virtual_light")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/scenes")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"name":"Lights on","members":[{{"entity_id":"{light_id}","state":"on"}}]}}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let scene_id = body["id"].as_str().unwrap();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/scenes/{scene_id}/activate"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // The integration handles the request asynchronously
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/entities/{light_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["state"], "on");
+}
+
+#[tokio::test]
+async fn should_fan_out_service_calls_when_group_toggled() {
+    let app = app_with_virtual().await;
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/entities")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let entities: Vec<serde_json::Value> =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let light_id = entities
+        .iter()
+        .find(|e| e["entity_id"] == "light.virtual_light")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/groups")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"name":"All lights","kind":"light","members":["{light_id}"]}}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value =
+        serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let group_entity_id = body["entity_id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/entities/{group_entity_id}/service"))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"service":"turn_on"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // The group service and the integration handle the request asynchronously
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    for id in [&light_id, &group_entity_id] {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/entities/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body: serde_json::Value =
+            serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+        assert_eq!(body["state"], "on");
+        if id == &group_entity_id {
+            assert_eq!(body["entity_id"], "light.all_lights");
+        }
+    }
+}
+
 #[tokio::test]
 async fn should_get_virtual_entity_with_sensor_attributes() {
     let app = app_with_virtual().await;
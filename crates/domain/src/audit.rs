@@ -0,0 +1,362 @@
+//! Audit entries — who created, updated or deleted what, and when.
+//!
+//! Every write to the hub's configuration is recorded with the [`Actor`]
+//! behind it, so users can tell whether a change came from the dashboard,
+//! a script calling the API, an automation or an integration. Entries keep
+//! a summary of the change: the fields that differ between the resource
+//! before and after it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::ValidationError;
+use crate::id::{AuditEntryId, AutomationId};
+use crate::role::Role;
+use crate::time::{Timestamp, now};
+
+/// Who made a change.
+///
+/// Serialized as a string: `"api"`, `"dashboard"`, `"system"`,
+/// `"automation:<id>"`, `"integration:<name>"` or `"token:<name>:<role>"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Actor {
+    /// A client of the REST API other than the dashboard.
+    Api,
+    /// The dashboard served by the hub.
+    Dashboard,
+    /// An automation running its actions.
+    Automation(AutomationId),
+    /// An integration reporting devices and entities.
+    Integration(String),
+    /// The hub itself, e.g. at startup.
+    System,
+    /// A client of the REST API authenticated by the token `name`, allowed
+    /// to make the change by its `role`.
+    Token { name: String, role: Role },
+}
+
+impl Actor {
+    /// Every kind of actor, as returned by [`Actor::kind`].
+    pub const KINDS: [&'static str; 6] = [
+        "api",
+        "dashboard",
+        "automation",
+        "integration",
+        "system",
+        "token",
+    ];
+
+    /// Kind of actor, e.g. `"automation"`, without the automation id or
+    /// integration name.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Api => "api",
+            Self::Dashboard => "dashboard",
+            Self::Automation(_) => "automation",
+            Self::Integration(_) => "integration",
+            Self::System => "system",
+            Self::Token { .. } => "token",
+        }
+    }
+
+    /// Whether the actor is a person, through the dashboard or the API,
+    /// rather than the hub reacting on its own.
+    #[must_use]
+    pub fn is_user(&self) -> bool {
+        matches!(self, Self::Api | Self::Dashboard | Self::Token { .. })
+    }
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Automation(id) => write!(f, "automation:{id}"),
+            Self::Integration(name) => write!(f, "integration:{name}"),
+            Self::Token { name, role } => write!(f, "token:{name}:{role}"),
+            other => f.write_str(other.kind()),
+        }
+    }
+}
+
+impl FromStr for Actor {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let unknown = || ValidationError::UnknownActor(value.to_string());
+        match value.split_once(':') {
+            None => match value {
+                "api" => Ok(Self::Api),
+                "dashboard" => Ok(Self::Dashboard),
+                "system" => Ok(Self::System),
+                _ => Err(unknown()),
+            },
+            Some(("automation", id)) => id.parse().map(Self::Automation).map_err(|_| unknown()),
+            Some(("integration", name)) if !name.is_empty() => {
+                Ok(Self::Integration(name.to_string()))
+            }
+            Some(("token", token)) => match token.rsplit_once(':') {
+                Some((name, role)) if !name.is_empty() => Ok(Self::Token {
+                    name: name.to_string(),
+                    role: role.parse().map_err(|_| unknown())?,
+                }),
+                _ => Err(unknown()),
+            },
+            Some(_) => Err(unknown()),
+        }
+    }
+}
+
+impl From<Actor> for String {
+    fn from(actor: Actor) -> Self {
+        actor.to_string()
+    }
+}
+
+impl TryFrom<String> for Actor {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Kind of change recorded by an [`AuditEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    /// Every action, in lifecycle order.
+    pub const ALL: [Self; 3] = [Self::Create, Self::Update, Self::Delete];
+
+    /// The action as stored and serialized, e.g. `"create"`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single write to a resource of the hub.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: AuditEntryId,
+    pub timestamp: Timestamp,
+    pub actor: Actor,
+    pub action: AuditAction,
+    /// Kind of the resource written, e.g. `"entity"` or `"automation"`.
+    pub target_type: String,
+    /// Identifier of the resource written.
+    pub target_id: String,
+    /// Fields of the resource that changed, as they were before the
+    /// change; `None` for a creation.
+    pub before: Option<Value>,
+    /// Fields of the resource that changed, as they are after the change;
+    /// `None` for a deletion.
+    pub after: Option<Value>,
+}
+
+impl AuditEntry {
+    /// Record that `actor` created the resource `after`.
+    #[must_use]
+    pub fn created(
+        actor: Actor,
+        target_type: impl Into<String>,
+        target_id: impl Into<String>,
+        after: Value,
+    ) -> Self {
+        Self::new(
+            actor,
+            AuditAction::Create,
+            target_type,
+            target_id,
+            None,
+            Some(after),
+        )
+    }
+
+    /// Record that `actor` changed the resource `before` into `after`,
+    /// keeping only the fields that differ.
+    ///
+    /// Returns `None` when nothing changed.
+    #[must_use]
+    pub fn updated(
+        actor: Actor,
+        target_type: impl Into<String>,
+        target_id: impl Into<String>,
+        before: &Value,
+        after: &Value,
+    ) -> Option<Self> {
+        let (before, after) = changed_fields(before, after)?;
+        Some(Self::new(
+            actor,
+            AuditAction::Update,
+            target_type,
+            target_id,
+            Some(before),
+            Some(after),
+        ))
+    }
+
+    /// Record that `actor` deleted the resource `before`, when it was
+    /// known.
+    #[must_use]
+    pub fn deleted(
+        actor: Actor,
+        target_type: impl Into<String>,
+        target_id: impl Into<String>,
+        before: Option<Value>,
+    ) -> Self {
+        Self::new(
+            actor,
+            AuditAction::Delete,
+            target_type,
+            target_id,
+            before,
+            None,
+        )
+    }
+
+    fn new(
+        actor: Actor,
+        action: AuditAction,
+        target_type: impl Into<String>,
+        target_id: impl Into<String>,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Self {
+        Self {
+            id: AuditEntryId::new(),
+            timestamp: now(),
+            actor,
+            action,
+            target_type: target_type.into(),
+            target_id: target_id.into(),
+            before,
+            after,
+        }
+    }
+}
+
+/// The top-level fields whose value differs between `before` and `after`,
+/// as `(before, after)` objects; a field missing on one side is `null`
+/// there. Values other than objects are compared as a whole.
+///
+/// Returns `None` when both are equal.
+#[must_use]
+pub fn changed_fields(before: &Value, after: &Value) -> Option<(Value, Value)> {
+    if before == after {
+        return None;
+    }
+    let (Value::Object(before), Value::Object(after)) = (before, after) else {
+        return Some((before.clone(), after.clone()));
+    };
+    let mut old = Map::new();
+    let mut new = Map::new();
+    for key in before
+        .keys()
+        .chain(after.keys().filter(|key| !before.contains_key(*key)))
+    {
+        let previous = before.get(key).unwrap_or(&Value::Null);
+        let current = after.get(key).unwrap_or(&Value::Null);
+        if previous != current {
+            old.insert(key.clone(), previous.clone());
+            new.insert(key.clone(), current.clone());
+        }
+    }
+    Some((Value::Object(old), Value::Object(new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_every_actor_through_from_str() {
+        let actors = [
+            Actor::Api,
+            Actor::Dashboard,
+            Actor::System,
+            Actor::Automation(AutomationId::new()),
+            Actor::Integration("mqtt".to_string()),
+            Actor::Token {
+                name: "ci".to_string(),
+                role: Role::Controller,
+            },
+        ];
+        for actor in actors {
+            assert_eq!(actor.to_string().parse::<Actor>().unwrap(), actor);
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_actor() {
+        for value in [
+            "robot",
+            "automation:not-a-uuid",
+            "integration:",
+            "api:x",
+            "token:ci",
+            "token:ci:root",
+        ] {
+            assert!(value.parse::<Actor>().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn should_serialize_actor_as_string() {
+        assert_eq!(
+            serde_json::to_value(Actor::Integration("ble".to_string())).unwrap(),
+            json!("integration:ble")
+        );
+    }
+
+    #[test]
+    fn should_keep_only_changed_fields_when_updated() {
+        let entry = AuditEntry::updated(
+            Actor::Dashboard,
+            "area",
+            "kitchen",
+            &json!({ "id": 1, "name": "Kitchen", "floor": null }),
+            &json!({ "id": 1, "name": "Cuisine", "icon": "pot" }),
+        )
+        .unwrap();
+
+        assert_eq!(entry.action, AuditAction::Update);
+        assert_eq!(
+            entry.before,
+            Some(json!({ "name": "Kitchen", "icon": null }))
+        );
+        assert_eq!(
+            entry.after,
+            Some(json!({ "name": "Cuisine", "icon": "pot" }))
+        );
+    }
+
+    #[test]
+    fn should_skip_update_when_nothing_changed() {
+        let value = json!({ "name": "Kitchen" });
+
+        assert!(AuditEntry::updated(Actor::Api, "area", "kitchen", &value, &value).is_none());
+    }
+}
@@ -2,7 +2,18 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::ValidationError;
 use crate::id::EntityId;
+use crate::notification::Severity;
+
+use super::{Condition, Expression, Template};
+
+/// Most times the actions of a `repeat` may run, counting the repeats it is
+/// nested in: a `repeat` of 10 inside a `repeat` of 10 runs 100 times.
+pub const MAX_REPEAT_RUNS: u32 = 100;
+
+/// Deepest nesting of `if` and `repeat` actions.
+pub const MAX_NESTING_DEPTH: usize = 8;
 
 /// An operation to execute when the automation's trigger fires and
 /// all conditions are satisfied.
@@ -14,7 +25,8 @@ pub enum Action {
         entity_id: EntityId,
         /// Service name, e.g. `"turn_on"`, `"turn_off"`, `"toggle"`.
         service: String,
-        /// Additional parameters for the service call.
+        /// Additional parameters for the service call, whose strings may
+        /// hold `{{ expression }}` [`Template`]s.
         #[serde(default)]
         data: serde_json::Value,
     },
@@ -23,6 +35,110 @@ pub enum Action {
         /// Number of seconds to wait.
         seconds: u64,
     },
+    /// Request a notification through the configured notifiers. The title
+    /// and message may hold `{{ expression }}` [`Template`]s.
+    Notify {
+        #[serde(default)]
+        title: Option<String>,
+        message: String,
+        #[serde(default)]
+        severity: Severity,
+    },
+    /// Wait until an entity reaches `state`, failing the action when it is
+    /// still in another state after `timeout_secs`.
+    WaitForState {
+        entity_id: EntityId,
+        /// Awaited state value, e.g. `"off"`.
+        state: String,
+        timeout_secs: u64,
+    },
+    /// Run `then` when `condition` holds, `else` otherwise.
+    If {
+        condition: Condition,
+        #[serde(default)]
+        then: Vec<Action>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<Action>,
+    },
+    /// Run `actions` in order, `count` times, from 1 to
+    /// [`MAX_REPEAT_RUNS`].
+    Repeat { count: u32, actions: Vec<Action> },
+}
+
+impl Action {
+    /// The `entity_id`s read by the templates of the action.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] for the first template
+    /// that does not parse.
+    pub fn templates_entity_ids(&self) -> Result<Vec<String>, ValidationError> {
+        match self {
+            Self::CallService { data, .. } => Template::entity_ids_in(data),
+            // The nested actions of `If` and `Repeat` read their own.
+            Self::Delay { .. }
+            | Self::WaitForState { .. }
+            | Self::If { .. }
+            | Self::Repeat { .. } => Ok(Vec::new()),
+            Self::Notify { title, message, .. } => {
+                let mut ids = Vec::new();
+                for text in title.iter().chain(std::iter::once(message)) {
+                    let template = Template::parse(text)?;
+                    ids.extend(template.entity_ids().into_iter().map(str::to_string));
+                }
+                Ok(ids)
+            }
+        }
+    }
+
+    /// Check that the templates of the action, and of the actions nested in
+    /// it, parse, as well as the expression conditions of `If` actions, and
+    /// that `If` and `Repeat` actions stay within bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] for the first template
+    /// or expression that does not parse,
+    /// [`ValidationError::InvalidRepeatCount`] for a `Repeat` of 0 or running
+    /// more than [`MAX_REPEAT_RUNS`] times, and
+    /// [`ValidationError::ActionsTooDeep`] for actions nested deeper than
+    /// [`MAX_NESTING_DEPTH`].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_nested(0, 1)
+    }
+
+    /// [`validate`](Self::validate) for an action nested in `depth` `If` or
+    /// `Repeat` actions, whose repeats run it `runs` times.
+    fn validate_nested(&self, depth: usize, runs: u32) -> Result<(), ValidationError> {
+        self.templates_entity_ids()?;
+        if depth >= MAX_NESTING_DEPTH && matches!(self, Self::If { .. } | Self::Repeat { .. }) {
+            return Err(ValidationError::ActionsTooDeep(MAX_NESTING_DEPTH));
+        }
+        match self {
+            Self::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if let Condition::Expression { expr } = condition {
+                    Expression::parse(expr)?;
+                }
+                then.iter()
+                    .chain(otherwise)
+                    .try_for_each(|action| action.validate_nested(depth + 1, runs))
+            }
+            Self::Repeat { count, actions } => {
+                let runs = runs.saturating_mul(*count);
+                if *count == 0 || runs > MAX_REPEAT_RUNS {
+                    return Err(ValidationError::InvalidRepeatCount(*count));
+                }
+                actions
+                    .iter()
+                    .try_for_each(|action| action.validate_nested(depth + 1, runs))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl std::fmt::Display for Action {
@@ -32,6 +148,25 @@ impl std::fmt::Display for Action {
                 entity_id, service, ..
             } => write!(f, "call_service({service}, {entity_id})"),
             Self::Delay { seconds } => write!(f, "delay({seconds}s)"),
+            Self::Notify { severity, .. } => write!(f, "notify({severity})"),
+            Self::WaitForState {
+                entity_id,
+                state,
+                timeout_secs,
+            } => write!(f, "wait_for_state({entity_id}, {state}, {timeout_secs}s)"),
+            Self::If {
+                condition,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "if({condition}, {} then, {} else)",
+                then.len(),
+                otherwise.len()
+            ),
+            Self::Repeat { count, actions } => {
+                write!(f, "repeat({count}x, {} actions)", actions.len())
+            }
         }
     }
 }
@@ -58,6 +193,30 @@ mod tests {
         assert_eq!(a.to_string(), "delay(30s)");
     }
 
+    #[test]
+    fn should_display_notify_action() {
+        let a = Action::Notify {
+            title: None,
+            message: "Door left open".to_string(),
+            severity: Severity::Warning,
+        };
+        assert_eq!(a.to_string(), "notify(warning)");
+    }
+
+    #[test]
+    fn should_default_notify_severity_when_missing_from_json() {
+        let json = r#"{"type":"notify","message":"Door left open"}"#;
+        let parsed: Action = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            parsed,
+            Action::Notify {
+                title: None,
+                severity: Severity::Info,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn should_roundtrip_actions_through_serde_json() {
         let eid = EntityId::new();
@@ -68,6 +227,11 @@ mod tests {
                 data: serde_json::json!({"brightness": 255}),
             },
             Action::Delay { seconds: 5 },
+            Action::Notify {
+                title: Some("Door".to_string()),
+                message: "Door left open".to_string(),
+                severity: Severity::Critical,
+            },
         ];
 
         for action in &actions {
@@ -101,10 +265,23 @@ mod tests {
         let a: Action = serde_json::from_value(json).unwrap();
         match a {
             Action::CallService { data, .. } => assert!(data.is_null()),
-            Action::Delay { .. } => panic!("expected CallService"),
+            _ => panic!("expected CallService"),
         }
     }
 
+    #[test]
+    fn should_list_entities_read_by_notify_templates() {
+        let a = Action::Notify {
+            title: Some("{{ states('sensor.door').state }}".to_string()),
+            message: "It is {{ states('sensor.kitchen').temperature }}°C".to_string(),
+            severity: Severity::Info,
+        };
+        assert_eq!(
+            a.templates_entity_ids().unwrap(),
+            vec!["sensor.door", "sensor.kitchen"]
+        );
+    }
+
     #[test]
     fn should_deserialize_delay_from_tagged_json() {
         let json = serde_json::json!({
@@ -114,4 +291,122 @@ mod tests {
         let a: Action = serde_json::from_value(json).unwrap();
         assert!(matches!(a, Action::Delay { seconds: 10 }));
     }
+
+    #[test]
+    fn should_deserialize_if_with_else_branch_from_tagged_json() {
+        let eid = EntityId::new();
+        let json = serde_json::json!({
+            "type": "if",
+            "condition": {"type": "state_is", "entity_id": eid, "state": "on"},
+            "then": [{"type": "delay", "seconds": 1}],
+            "else": [{"type": "call_service", "entity_id": eid, "service": "turn_on"}]
+        });
+        let a: Action = serde_json::from_value(json).unwrap();
+        match a {
+            Action::If {
+                then, otherwise, ..
+            } => {
+                assert_eq!(then, vec![Action::Delay { seconds: 1 }]);
+                assert_eq!(otherwise.len(), 1);
+            }
+            _ => panic!("expected If"),
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_control_flow_actions_through_serde_json() {
+        let eid = EntityId::new();
+        let action = Action::Repeat {
+            count: 3,
+            actions: vec![
+                Action::WaitForState {
+                    entity_id: eid,
+                    state: "off".to_string(),
+                    timeout_secs: 60,
+                },
+                Action::If {
+                    condition: Condition::StateIs {
+                        entity_id: eid,
+                        state: "off".to_string(),
+                    },
+                    then: vec![Action::Delay { seconds: 1 }],
+                    otherwise: Vec::new(),
+                },
+            ],
+        };
+
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(json["actions"][1]["else"], serde_json::json!([]));
+        let parsed: Action = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, action);
+    }
+
+    #[test]
+    fn should_reject_invalid_template_nested_in_repeat() {
+        let a = Action::Repeat {
+            count: 2,
+            actions: vec![Action::If {
+                condition: Condition::Expression {
+                    expr: "true".to_string(),
+                },
+                then: vec![Action::Notify {
+                    title: None,
+                    message: "{{ states( }}".to_string(),
+                    severity: Severity::Info,
+                }],
+                otherwise: Vec::new(),
+            }],
+        };
+        assert!(matches!(
+            a.validate(),
+            Err(ValidationError::InvalidExpression(_))
+        ));
+    }
+
+    fn repeat(count: u32, actions: Vec<Action>) -> Action {
+        Action::Repeat { count, actions }
+    }
+
+    #[test]
+    fn should_reject_repeat_of_zero_or_running_too_many_times() {
+        let delay = || vec![Action::Delay { seconds: 1 }];
+
+        assert!(repeat(MAX_REPEAT_RUNS, delay()).validate().is_ok());
+        assert!(repeat(10, vec![repeat(10, delay())]).validate().is_ok());
+        assert!(matches!(
+            repeat(0, delay()).validate(),
+            Err(ValidationError::InvalidRepeatCount(0))
+        ));
+        assert!(matches!(
+            repeat(MAX_REPEAT_RUNS + 1, delay()).validate(),
+            Err(ValidationError::InvalidRepeatCount(_))
+        ));
+        assert!(matches!(
+            repeat(u32::MAX, delay()).validate(),
+            Err(ValidationError::InvalidRepeatCount(_))
+        ));
+        assert!(matches!(
+            repeat(10, vec![repeat(11, delay())]).validate(),
+            Err(ValidationError::InvalidRepeatCount(11))
+        ));
+    }
+
+    #[test]
+    fn should_reject_actions_nested_too_deep() {
+        let nest = |depth| {
+            (0..depth).fold(Action::Delay { seconds: 1 }, |action, _| Action::If {
+                condition: Condition::Expression {
+                    expr: "true".to_string(),
+                },
+                then: vec![action],
+                otherwise: Vec::new(),
+            })
+        };
+
+        assert!(nest(MAX_NESTING_DEPTH).validate().is_ok());
+        assert!(matches!(
+            nest(MAX_NESTING_DEPTH + 1).validate(),
+            Err(ValidationError::ActionsTooDeep(MAX_NESTING_DEPTH))
+        ));
+    }
 }
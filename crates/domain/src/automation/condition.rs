@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::home_mode::HomeMode;
 use crate::id::EntityId;
 
 /// A predicate that must hold for the automation actions to execute.
@@ -24,6 +25,23 @@ pub enum Condition {
         /// End of the window, `HH:MM` in 24-hour format.
         before: String,
     },
+    /// Requires the hub-wide home mode to be `mode`.
+    HomeModeIs { mode: HomeMode },
+    /// Requires the `value` attribute of an entity, typically a number or
+    /// select input helper, to equal `value`.
+    ValueIs {
+        entity_id: EntityId,
+        value: serde_json::Value,
+    },
+    /// Requires an [`Expression`](super::Expression) to be truthy, e.g.
+    /// `states('sensor.kitchen').temperature > 25 && time.hour >= 22`.
+    Expression { expr: String },
+    /// Requires the sun to be above the horizon, i.e. between sunrise and
+    /// sunset.
+    SunAboveHorizon,
+    /// Requires the sun to be below the horizon, i.e. between sunset and
+    /// sunrise.
+    SunBelowHorizon,
 }
 
 impl std::fmt::Display for Condition {
@@ -35,6 +53,11 @@ impl std::fmt::Display for Condition {
             Self::TimeRange { after, before } => {
                 write!(f, "time_range({after}..{before})")
             }
+            Self::HomeModeIs { mode } => write!(f, "home_mode_is({mode})"),
+            Self::ValueIs { entity_id, value } => write!(f, "value_is({entity_id}, {value})"),
+            Self::Expression { expr } => write!(f, "expression({expr})"),
+            Self::SunAboveHorizon => f.write_str("sun_above_horizon"),
+            Self::SunBelowHorizon => f.write_str("sun_below_horizon"),
         }
     }
 }
@@ -64,6 +87,21 @@ mod tests {
         assert_eq!(c.to_string(), "time_range(08:00..22:00)");
     }
 
+    #[test]
+    fn should_display_home_mode_is_condition() {
+        let c = Condition::HomeModeIs {
+            mode: HomeMode::Away,
+        };
+        assert_eq!(c.to_string(), "home_mode_is(away)");
+    }
+
+    #[test]
+    fn should_deserialize_expression_from_tagged_json() {
+        let json = serde_json::json!({ "type": "expression", "expr": "time.hour >= 22" });
+        let c: Condition = serde_json::from_value(json).unwrap();
+        assert_eq!(c.to_string(), "expression(time.hour >= 22)");
+    }
+
     #[test]
     fn should_roundtrip_conditions_through_serde_json() {
         let eid = EntityId::new();
@@ -76,6 +114,12 @@ mod tests {
                 after: "08:00".to_string(),
                 before: "22:00".to_string(),
             },
+            Condition::ValueIs {
+                entity_id: eid,
+                value: serde_json::json!(21.5),
+            },
+            Condition::SunAboveHorizon,
+            Condition::SunBelowHorizon,
         ];
 
         for condition in &conditions {
@@ -109,4 +153,19 @@ mod tests {
             matches!(c, Condition::TimeRange { after, before } if after == "06:00" && before == "09:00")
         );
     }
+
+    #[test]
+    fn should_deserialize_home_mode_is_from_tagged_json() {
+        let json = serde_json::json!({
+            "type": "home_mode_is",
+            "mode": "vacation"
+        });
+        let c: Condition = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            c,
+            Condition::HomeModeIs {
+                mode: HomeMode::Vacation
+            }
+        );
+    }
 }
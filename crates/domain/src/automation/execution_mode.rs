@@ -0,0 +1,86 @@
+//! Execution mode — what happens when an automation triggers while a
+//! previous run of it is still in progress.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ValidationError;
+
+/// How overlapping runs of the same automation are handled, e.g. a motion
+/// automation triggered again while its `delay` action is still sleeping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    /// Ignore the new trigger while a run is in progress.
+    #[default]
+    Single,
+    /// Cancel the run in progress and start a new one.
+    Restart,
+    /// Start the new run once the previous ones finished, in trigger order.
+    Queued,
+    /// Start the new run alongside the ones in progress.
+    Parallel,
+}
+
+impl ExecutionMode {
+    /// Every mode, in display order.
+    pub const ALL: [Self; 4] = [Self::Single, Self::Restart, Self::Queued, Self::Parallel];
+
+    /// The `snake_case` name used in JSON and storage.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Restart => "restart",
+            Self::Queued => "queued",
+            Self::Parallel => "parallel",
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExecutionMode {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+            .ok_or_else(|| ValidationError::UnknownExecutionMode(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_default_to_single() {
+        assert_eq!(ExecutionMode::default(), ExecutionMode::Single);
+    }
+
+    #[test]
+    fn should_parse_every_mode_from_its_name() {
+        for mode in ExecutionMode::ALL {
+            assert_eq!(mode.as_str().parse::<ExecutionMode>().unwrap(), mode);
+            assert_eq!(
+                serde_json::to_value(mode).unwrap(),
+                serde_json::json!(mode.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_mode() {
+        let err = "sometimes".parse::<ExecutionMode>().unwrap_err();
+        assert!(
+            matches!(err, ValidationError::UnknownExecutionMode(ref mode) if mode == "sometimes")
+        );
+    }
+}
@@ -0,0 +1,800 @@
+//! Expression — a small language for automation conditions and templated
+//! action data.
+//!
+//! ```text
+//! states('sensor.ble_kitchen').temperature > 25 && time.hour >= 22
+//! ```
+//!
+//! Expressions combine literals (numbers, `'strings'`, `true`, `false`,
+//! `null`), `states('<entity_id>')` — the entity's attributes plus its
+//! `state`, or `null` when it does not exist — and `time` (`hour`, `minute`,
+//! `second`, `weekday` from 1 for Monday to 7, `day`, `month` and `year`, in
+//! UTC like [`Condition::TimeRange`](super::Condition::TimeRange)) with
+//! member access, arithmetic (`+ - * / %`), comparisons
+//! (`== != < <= > >=`), `!`, `&&` and `||`.
+//!
+//! Entity ids must be string literals so the entities an expression reads
+//! are known up front ([`Expression::entity_ids`]): the caller loads them
+//! into a [`Scope`] and evaluation itself stays pure.
+//!
+//! A [`Template`] embeds expressions in text between `{{` and `}}`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{Datelike, Timelike};
+use serde_json::{Map, Number, Value};
+
+use crate::entity::Entity;
+use crate::error::ValidationError;
+use crate::input_helper::value_eq;
+use crate::time::Timestamp;
+
+/// The values an expression can read: entity states and the current time.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    states: HashMap<String, Value>,
+    now: Timestamp,
+}
+
+impl Scope {
+    /// A scope at `now` without any entity.
+    #[must_use]
+    pub fn new(now: Timestamp) -> Self {
+        Self {
+            states: HashMap::new(),
+            now,
+        }
+    }
+
+    /// Make `entity` readable through `states('<entity_id>')`.
+    #[must_use]
+    pub fn with_entity(mut self, entity: &Entity) -> Self {
+        let mut object: Map<String, Value> = entity
+            .attributes
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), serde_json::to_value(value).ok()?)))
+            .collect();
+        if let Ok(state) = serde_json::to_value(&entity.state) {
+            object.insert("state".to_string(), state);
+        }
+        self.states
+            .insert(entity.entity_id.clone(), Value::Object(object));
+        self
+    }
+
+    fn time(&self) -> Value {
+        serde_json::json!({
+            "hour": self.now.hour(),
+            "minute": self.now.minute(),
+            "second": self.now.second(),
+            "weekday": self.now.weekday().number_from_monday(),
+            "day": self.now.day(),
+            "month": self.now.month(),
+            "year": self.now.year(),
+        })
+    }
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    ast: Expr,
+}
+
+impl Expression {
+    /// Parse `source`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] describing the first
+    /// syntax error.
+    pub fn parse(source: &str) -> Result<Self, ValidationError> {
+        let invalid =
+            |reason: String| ValidationError::InvalidExpression(format!("{source}: {reason}"));
+        let tokens = tokenize(source).map_err(invalid)?;
+        if tokens.len() > MAX_TOKENS {
+            return Err(invalid(format!("longer than {MAX_TOKENS} tokens")));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let ast = parser.parse_level(0).map_err(invalid)?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(invalid(format!("unexpected {token}")));
+        }
+        Ok(Self { ast })
+    }
+
+    /// The `entity_id`s read through `states(...)`.
+    #[must_use]
+    pub fn entity_ids(&self) -> Vec<&str> {
+        let mut ids = Vec::new();
+        self.ast.collect_entity_ids(&mut ids);
+        ids
+    }
+
+    /// Evaluate against `scope`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] when an operator is
+    /// applied to values it does not support, e.g. `'a' * 2`.
+    pub fn evaluate(&self, scope: &Scope) -> Result<Value, ValidationError> {
+        self.ast
+            .evaluate(scope)
+            .map_err(ValidationError::InvalidExpression)
+    }
+
+    /// Evaluate against `scope` and tell whether the result is truthy:
+    /// anything but `null`, `false`, `0` and empty strings, arrays or objects.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::evaluate`].
+    pub fn is_true(&self, scope: &Scope) -> Result<bool, ValidationError> {
+        self.evaluate(scope).map(|value| truthy(&value))
+    }
+}
+
+/// Text with embedded `{{ expression }}` placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Expression(Expression),
+}
+
+impl Template {
+    /// Parse `source`, splitting it around `{{ ... }}` placeholders.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] when a placeholder is
+    /// not closed or holds an invalid expression.
+    pub fn parse(source: &str) -> Result<Self, ValidationError> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(ValidationError::InvalidExpression(format!(
+                    "{source}: unclosed `{{{{`"
+                )));
+            };
+            let expression = Expression::parse(rest[start + 2..start + end].trim())?;
+            parts.push(Part::Expression(expression));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The `entity_id`s read by the placeholders.
+    #[must_use]
+    pub fn entity_ids(&self) -> Vec<&str> {
+        self.parts
+            .iter()
+            .flat_map(|part| match part {
+                Part::Text(_) => Vec::new(),
+                Part::Expression(expression) => expression.entity_ids(),
+            })
+            .collect()
+    }
+
+    /// Render against `scope`.
+    ///
+    /// A template made of a single placeholder renders to the value of its
+    /// expression, keeping numbers and booleans typed; anything else renders
+    /// to a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] when a placeholder fails
+    /// to evaluate.
+    pub fn render(&self, scope: &Scope) -> Result<Value, ValidationError> {
+        if let [Part::Expression(expression)] = self.parts.as_slice() {
+            return expression.evaluate(scope);
+        }
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => rendered.push_str(text),
+                Part::Expression(expression) => {
+                    rendered.push_str(&to_text(&expression.evaluate(scope)?));
+                }
+            }
+        }
+        Ok(Value::String(rendered))
+    }
+
+    /// Render against `scope` as text.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::render`].
+    pub fn render_text(&self, scope: &Scope) -> Result<String, ValidationError> {
+        self.render(scope).map(|value| to_text(&value))
+    }
+
+    /// Parse every string nested in `value` as a template, returning the
+    /// `entity_id`s they read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] for the first invalid
+    /// template.
+    pub fn entity_ids_in(value: &Value) -> Result<Vec<String>, ValidationError> {
+        let mut ids = Vec::new();
+        visit_strings(value, &mut |text| {
+            let template = Self::parse(text)?;
+            ids.extend(template.entity_ids().into_iter().map(str::to_string));
+            Ok(())
+        })?;
+        Ok(ids)
+    }
+
+    /// Render every string nested in `value` as a template.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidExpression`] for the first template
+    /// that fails to parse or evaluate.
+    pub fn render_in(value: &Value, scope: &Scope) -> Result<Value, ValidationError> {
+        Ok(match value {
+            Value::String(text) => Self::parse(text)?.render(scope)?,
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| Self::render_in(item, scope))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, item)| Ok((key.clone(), Self::render_in(item, scope)?)))
+                    .collect::<Result<_, ValidationError>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+}
+
+fn visit_strings(
+    value: &Value,
+    visit: &mut impl FnMut(&str) -> Result<(), ValidationError>,
+) -> Result<(), ValidationError> {
+    match value {
+        Value::String(text) => visit(text),
+        Value::Array(items) => items.iter().try_for_each(|item| visit_strings(item, visit)),
+        Value::Object(fields) => fields
+            .values()
+            .try_for_each(|item| visit_strings(item, visit)),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    States(String),
+    Time,
+    Member(Box<Expr>, String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Most tokens in an expression, bounding the depth of operator and member
+/// chains, which are evaluated recursively.
+const MAX_TOKENS: usize = 512;
+
+/// Deepest nesting of parentheses and unary operators, so parsing a hostile
+/// expression cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Binary operators from the loosest to the tightest binding.
+const PRECEDENCE: &[&[(&str, BinaryOp)]] = &[
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("==", BinaryOp::Eq), ("!=", BinaryOp::Ne)],
+    &[
+        ("<", BinaryOp::Lt),
+        ("<=", BinaryOp::Le),
+        (">", BinaryOp::Gt),
+        (">=", BinaryOp::Ge),
+    ],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+    &[
+        ("*", BinaryOp::Mul),
+        ("/", BinaryOp::Div),
+        ("%", BinaryOp::Rem),
+    ],
+];
+
+impl Expr {
+    fn collect_entity_ids<'a>(&'a self, ids: &mut Vec<&'a str>) {
+        match self {
+            Self::States(entity_id) => ids.push(entity_id),
+            Self::Member(inner, _) | Self::Not(inner) | Self::Neg(inner) => {
+                inner.collect_entity_ids(ids);
+            }
+            Self::Binary(_, lhs, rhs) => {
+                lhs.collect_entity_ids(ids);
+                rhs.collect_entity_ids(ids);
+            }
+            Self::Literal(_) | Self::Time => {}
+        }
+    }
+
+    fn evaluate(&self, scope: &Scope) -> Result<Value, String> {
+        match self {
+            Self::Literal(value) => Ok(value.clone()),
+            Self::States(entity_id) => Ok(scope.states.get(entity_id).cloned().unwrap_or_default()),
+            Self::Time => Ok(scope.time()),
+            Self::Member(inner, name) => match inner.evaluate(scope)? {
+                Value::Object(mut fields) => Ok(fields.remove(name).unwrap_or_default()),
+                Value::Null => Ok(Value::Null),
+                other => Err(format!("{other} has no member `{name}`")),
+            },
+            Self::Not(inner) => Ok(Value::Bool(!truthy(&inner.evaluate(scope)?))),
+            Self::Neg(inner) => match inner.evaluate(scope)? {
+                Value::Number(number) => number_value(-as_f64(&number)),
+                other => Err(format!("cannot negate {other}")),
+            },
+            Self::Binary(BinaryOp::And, lhs, rhs) => Ok(Value::Bool(
+                truthy(&lhs.evaluate(scope)?) && truthy(&rhs.evaluate(scope)?),
+            )),
+            Self::Binary(BinaryOp::Or, lhs, rhs) => Ok(Value::Bool(
+                truthy(&lhs.evaluate(scope)?) || truthy(&rhs.evaluate(scope)?),
+            )),
+            Self::Binary(op, lhs, rhs) => apply(*op, &lhs.evaluate(scope)?, &rhs.evaluate(scope)?),
+        }
+    }
+}
+
+fn apply(op: BinaryOp, lhs: &Value, rhs: &Value) -> Result<Value, String> {
+    let ordering = || match (lhs, rhs) {
+        (Value::Number(a), Value::Number(b)) => Ok(as_f64(a).partial_cmp(&as_f64(b))),
+        (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+        // A missing entity or attribute compares as neither lower nor higher.
+        (Value::Null, _) | (_, Value::Null) => Ok(None),
+        _ => Err(format!("cannot compare {lhs} with {rhs}")),
+    };
+    let numbers = || match (lhs, rhs) {
+        (Value::Number(a), Value::Number(b)) => Ok((as_f64(a), as_f64(b))),
+        _ => Err(format!("{lhs} and {rhs} are not both numbers")),
+    };
+    match op {
+        BinaryOp::Eq => Ok(Value::Bool(value_eq(lhs, rhs))),
+        BinaryOp::Ne => Ok(Value::Bool(!value_eq(lhs, rhs))),
+        BinaryOp::Lt => Ok(Value::Bool(ordering()? == Some(Ordering::Less))),
+        BinaryOp::Le => Ok(Value::Bool(matches!(
+            ordering()?,
+            Some(Ordering::Less | Ordering::Equal)
+        ))),
+        BinaryOp::Gt => Ok(Value::Bool(ordering()? == Some(Ordering::Greater))),
+        BinaryOp::Ge => Ok(Value::Bool(matches!(
+            ordering()?,
+            Some(Ordering::Greater | Ordering::Equal)
+        ))),
+        BinaryOp::Add => match (lhs, rhs) {
+            (Value::String(_), _) | (_, Value::String(_)) => {
+                Ok(Value::String(format!("{}{}", to_text(lhs), to_text(rhs))))
+            }
+            _ => numbers().and_then(|(a, b)| number_value(a + b)),
+        },
+        BinaryOp::Sub => numbers().and_then(|(a, b)| number_value(a - b)),
+        BinaryOp::Mul => numbers().and_then(|(a, b)| number_value(a * b)),
+        BinaryOp::Div | BinaryOp::Rem => {
+            let (a, b) = numbers()?;
+            if b == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            number_value(if op == BinaryOp::Div { a / b } else { a % b })
+        }
+        BinaryOp::And => Ok(Value::Bool(truthy(lhs) && truthy(rhs))),
+        BinaryOp::Or => Ok(Value::Bool(truthy(lhs) || truthy(rhs))),
+    }
+}
+
+fn as_f64(number: &Number) -> f64 {
+    number.as_f64().unwrap_or_default()
+}
+
+/// Wrap `value`, as an integer when it has no fractional part so rendered
+/// service data keeps integer fields integral.
+#[allow(clippy::cast_possible_truncation)]
+fn number_value(value: f64) -> Result<Value, String> {
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+    if value.fract() == 0.0 && value.abs() < MAX_EXACT {
+        return Ok(Value::from(value as i64));
+    }
+    Number::from_f64(value)
+        .map(Value::Number)
+        .ok_or_else(|| format!("{value} is not a finite number"))
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => as_f64(number) != 0.0,
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+/// Text of a value inside a rendered template: strings unquoted, `null`
+/// empty, anything else as JSON.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "number {number}"),
+            Self::Str(text) => write!(f, "string '{text}'"),
+            Self::Ident(name) => write!(f, "`{name}`"),
+            Self::Symbol(symbol) => write!(f, "`{symbol}`"),
+        }
+    }
+}
+
+/// Symbols, two-character ones first so `<=` is not read as `<` then `=`.
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")", ".",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let consumed = if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("invalid number {}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            end
+        } else if c == '\'' || c == '"' {
+            let (text, len) = string_literal(rest, c)?;
+            tokens.push(Token::Str(text));
+            len
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            end
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else {
+            return Err(format!("unexpected character `{c}`"));
+        };
+        rest = rest[consumed..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Read the string literal opening `source` with `quote`, returning its
+/// text and length. A backslash escapes the next character.
+fn string_literal(source: &str, quote: char) -> Result<(String, usize), String> {
+    let mut text = String::new();
+    let mut chars = source.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, escaped)) => text.push(escaped),
+                None => break,
+            },
+            c if c == quote => return Ok((text, index + c.len_utf8())),
+            c => text.push(c),
+        }
+    }
+    Err(format!("unterminated string {source}"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Parentheses and unary operators currently open.
+    depth: usize,
+}
+
+impl Parser {
+    /// Run `parse` one nesting level deeper, failing past [`MAX_DEPTH`].
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nested deeper than {MAX_DEPTH} levels"));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found =
+            matches!(self.tokens.get(self.pos), Some(Token::Symbol(found)) if *found == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(match self.tokens.get(self.pos) {
+                Some(token) => format!("expected `{symbol}`, found {token}"),
+                None => format!("expected `{symbol}` at the end"),
+            })
+        }
+    }
+
+    fn parse_level(&mut self, level: usize) -> Result<Expr, String> {
+        let Some(operators) = PRECEDENCE.get(level) else {
+            return self.parse_unary();
+        };
+        let mut lhs = self.parse_level(level + 1)?;
+        'operators: loop {
+            for (symbol, op) in *operators {
+                if self.eat(symbol) {
+                    let rhs = self.parse_level(level + 1)?;
+                    lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+                    continue 'operators;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            let operand = self.nested(Self::parse_unary)?;
+            return Ok(Expr::Not(Box::new(operand)));
+        }
+        if self.eat("-") {
+            let operand = self.nested(Self::parse_unary)?;
+            return Ok(Expr::Neg(Box::new(operand)));
+        }
+        let mut expr = self.parse_primary()?;
+        while self.eat(".") {
+            match self.next() {
+                Some(Token::Ident(name)) => expr = Expr::Member(Box::new(expr), name),
+                Some(token) => return Err(format!("expected a member name, found {token}")),
+                None => return Err("expected a member name at the end".to_string()),
+            }
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => number_value(number).map(Expr::Literal),
+            Some(Token::Str(text)) => Ok(Expr::Literal(Value::String(text))),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "time" => Ok(Expr::Time),
+                "states" => {
+                    self.expect("(")?;
+                    let Some(Token::Str(entity_id)) = self.next() else {
+                        return Err("states() takes an entity_id string literal".to_string());
+                    };
+                    self.expect(")")?;
+                    Ok(Expr::States(entity_id))
+                }
+                _ => Err(format!("unknown name `{name}`")),
+            },
+            Some(Token::Symbol("(")) => {
+                let expr = self.nested(|parser| parser.parse_level(0))?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("unexpected {token}")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{AttributeValue, EntityState};
+
+    fn scope() -> Scope {
+        let sensor = Entity::builder()
+            .entity_id("sensor.ble_kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::Numeric(26.5))
+            .attribute("temperature", AttributeValue::Float(26.5))
+            .attribute("humidity", AttributeValue::Int(40))
+            .build()
+            .unwrap();
+        let now = "2026-10-16T22:30:00Z".parse().unwrap();
+        Scope::new(now).with_entity(&sensor)
+    }
+
+    fn eval(source: &str) -> Value {
+        Expression::parse(source)
+            .unwrap()
+            .evaluate(&scope())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_combine_entity_attributes_and_time() {
+        let expression =
+            Expression::parse("states('sensor.ble_kitchen').temperature > 25 && time.hour >= 22")
+                .unwrap();
+
+        assert!(expression.is_true(&scope()).unwrap());
+        assert_eq!(expression.entity_ids(), vec!["sensor.ble_kitchen"]);
+    }
+
+    #[test]
+    fn should_respect_operator_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Value::from(7));
+        assert_eq!(eval("(1 + 2) * 3"), Value::from(9));
+        assert_eq!(eval("!false && 7 % 4 == 3"), Value::Bool(true));
+        assert_eq!(
+            eval("-states('sensor.ble_kitchen').humidity / 8"),
+            Value::from(-5)
+        );
+    }
+
+    #[test]
+    fn should_read_entity_state_and_missing_entities_as_null() {
+        assert_eq!(
+            eval("states('sensor.ble_kitchen').state"),
+            Value::from(26.5)
+        );
+        assert_eq!(eval("states('sensor.missing').temperature"), Value::Null);
+        assert_eq!(
+            eval("states('sensor.missing').temperature > 25"),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_syntax() {
+        for source in [
+            "1 +",
+            "states(entity)",
+            "foo > 1",
+            "'open",
+            "(1",
+            "1 2",
+            "a & b",
+        ] {
+            assert!(
+                matches!(
+                    Expression::parse(source),
+                    Err(ValidationError::InvalidExpression(_))
+                ),
+                "{source} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_deeply_nested_or_long_expressions() {
+        let parentheses = format!("{}1{}", "(".repeat(50_000), ")".repeat(50_000));
+        let negations = format!("{}true", "!".repeat(50_000));
+        let chain = vec!["1"; 50_000].join(" + ");
+
+        for source in [parentheses, negations, chain] {
+            assert!(matches!(
+                Expression::parse(&source),
+                Err(ValidationError::InvalidExpression(_))
+            ));
+        }
+        let nested = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert!(Expression::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn should_fail_evaluation_when_types_do_not_match() {
+        let expression = Expression::parse("'warm' * 2").unwrap();
+        assert!(matches!(
+            expression.evaluate(&scope()),
+            Err(ValidationError::InvalidExpression(_))
+        ));
+    }
+
+    #[test]
+    fn should_render_single_placeholder_as_typed_value() {
+        let template = Template::parse("{{ states('sensor.ble_kitchen').humidity * 2 }}").unwrap();
+        assert_eq!(template.render(&scope()).unwrap(), Value::from(80));
+    }
+
+    #[test]
+    fn should_interpolate_placeholders_in_text() {
+        let template = Template::parse(
+            "It is {{ states('sensor.ble_kitchen').temperature }}°C at {{ time.hour }}h",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render_text(&scope()).unwrap(),
+            "It is 26.5°C at 22h"
+        );
+        assert!(matches!(
+            Template::parse("{{ time.hour"),
+            Err(ValidationError::InvalidExpression(_))
+        ));
+    }
+
+    #[test]
+    fn should_render_templates_nested_in_json() {
+        let data = serde_json::json!({
+            "brightness": "{{ time.hour * 10 }}",
+            "transition": 2,
+            "label": ["{{ 'a' + 'b' }}"],
+        });
+
+        let rendered = Template::render_in(&data, &scope()).unwrap();
+
+        assert_eq!(
+            rendered,
+            serde_json::json!({ "brightness": 220, "transition": 2, "label": ["ab"] })
+        );
+        assert!(Template::entity_ids_in(&data).unwrap().is_empty());
+    }
+}
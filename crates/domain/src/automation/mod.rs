@@ -7,17 +7,24 @@
 
 mod action;
 mod condition;
+mod execution_mode;
+mod expression;
+mod schedule;
 mod trigger;
 
 pub use action::Action;
 pub use condition::Condition;
+pub use execution_mode::ExecutionMode;
+pub use expression::{Expression, Scope, Template};
+pub use schedule::{CronSchedule, ScheduledFiring, time_pattern_fired, upcoming_firings};
 pub use trigger::Trigger;
 
 use serde::{Deserialize, Serialize};
 
-use crate::error::{MiniHubError, ValidationError};
+use crate::error::{FieldError, MiniHubError, ValidationError};
 use crate::id::AutomationId;
 use crate::time::Timestamp;
+use crate::{sun, webhook};
 
 /// A rule that reacts to events by executing actions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,7 +35,18 @@ pub struct Automation {
     pub trigger: Trigger,
     pub conditions: Vec<Condition>,
     pub actions: Vec<Action>,
+    /// How a trigger is handled while a previous run is in progress.
+    #[serde(default)]
+    pub execution_mode: ExecutionMode,
     pub last_triggered: Option<Timestamp>,
+    /// Revision of the definition, bumped by every update and used for
+    /// optimistic locking. Starts at 1.
+    pub version: u32,
+    /// Key of the file definition the automation was imported from, e.g.
+    /// `evening_lights`. File-sourced automations are read-only: they are
+    /// changed by editing the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_key: Option<String>,
 }
 
 impl Automation {
@@ -38,6 +56,13 @@ impl Automation {
         AutomationBuilder::default()
     }
 
+    /// Whether the automation is managed by a file definition rather than
+    /// through the API.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.source_key.is_some()
+    }
+
     /// Check domain invariants.
     ///
     /// # Errors
@@ -45,14 +70,86 @@ impl Automation {
     /// Returns [`MiniHubError::Validation`] when:
     /// - `name` is empty ([`ValidationError::EmptyName`])
     /// - `actions` is empty ([`ValidationError::NoActions`])
+    /// - a `time_pattern` trigger does not parse
+    ///   ([`ValidationError::InvalidCron`])
+    /// - a `webhook` trigger has an id unfit for a URL path or too easy to
+    ///   guess
+    ///   ([`ValidationError::InvalidWebhookId`])
+    /// - a `sun` trigger is offset by more than half a day
+    ///   ([`ValidationError::InvalidSunOffset`])
+    /// - an expression condition or an action template, including those
+    ///   nested in `if` and `repeat` actions, does not parse
+    ///   ([`ValidationError::InvalidExpression`])
+    /// - a `repeat` action runs 0 or more than 100 times
+    ///   ([`ValidationError::InvalidRepeatCount`])
+    /// - `if` and `repeat` actions are nested more than 8 levels deep
+    ///   ([`ValidationError::ActionsTooDeep`])
     pub fn validate(&self) -> Result<(), MiniHubError> {
+        match self.field_errors().into_iter().next() {
+            Some(FieldError { error, .. }) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the domain invariants of `self`, an edit of the stored
+    /// `previous` automation.
+    ///
+    /// Like [`validate`](Self::validate), except that a `time_pattern`
+    /// trigger kept as it was may not parse: patterns were stored unchecked
+    /// before they were scheduled, and such automations must stay editable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when an invariant does not hold.
+    pub fn validate_edit(&self, previous: &Self) -> Result<(), MiniHubError> {
+        let kept_trigger = self.trigger == previous.trigger;
+        let error = self
+            .field_errors()
+            .into_iter()
+            .find(|FieldError { field, error }| {
+                !(kept_trigger
+                    && field == "trigger"
+                    && matches!(error, ValidationError::InvalidCron(_)))
+            });
+        match error {
+            Some(FieldError { error, .. }) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Every invariant [`validate`](Self::validate) checks that does not
+    /// hold, each with the field it concerns, e.g. `conditions[1]`.
+    #[must_use]
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
         if self.name.is_empty() {
-            return Err(ValidationError::EmptyName.into());
+            errors.push(FieldError::new("name", ValidationError::EmptyName));
         }
         if self.actions.is_empty() {
-            return Err(ValidationError::NoActions.into());
+            errors.push(FieldError::new("actions", ValidationError::NoActions));
+        }
+        let trigger = match &self.trigger {
+            Trigger::TimePattern { cron } => cron.parse::<CronSchedule>().map(drop),
+            Trigger::Webhook { webhook_id } => webhook::validate_id(webhook_id),
+            Trigger::Sun { offset_minutes, .. } => sun::validate_offset(*offset_minutes),
+            _ => Ok(()),
+        };
+        if let Err(err) = trigger {
+            errors.push(FieldError::new("trigger", err));
+        }
+        for (index, condition) in self.conditions.iter().enumerate() {
+            if let Condition::Expression { expr } = condition
+                && let Err(err) = Expression::parse(expr)
+            {
+                errors.push(FieldError::new(format!("conditions[{index}]"), err));
+            }
         }
-        Ok(())
+        for (index, action) in self.actions.iter().enumerate() {
+            if let Err(err) = action.validate() {
+                errors.push(FieldError::new(format!("actions[{index}]"), err));
+            }
+        }
+        errors
     }
 }
 
@@ -65,7 +162,10 @@ pub struct AutomationBuilder {
     trigger: Option<Trigger>,
     conditions: Vec<Condition>,
     actions: Vec<Action>,
+    execution_mode: ExecutionMode,
     last_triggered: Option<Timestamp>,
+    version: Option<u32>,
+    source_key: Option<String>,
 }
 
 impl AutomationBuilder {
@@ -105,29 +205,61 @@ impl AutomationBuilder {
         self
     }
 
+    #[must_use]
+    pub fn execution_mode(mut self, execution_mode: ExecutionMode) -> Self {
+        self.execution_mode = execution_mode;
+        self
+    }
+
     #[must_use]
     pub fn last_triggered(mut self, ts: Timestamp) -> Self {
         self.last_triggered = Some(ts);
         self
     }
 
+    #[must_use]
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    #[must_use]
+    pub fn source_key(mut self, source_key: impl Into<String>) -> Self {
+        self.source_key = Some(source_key.into());
+        self
+    }
+
     /// Consume the builder, validate, and return an [`Automation`].
     ///
     /// # Errors
     ///
     /// Returns [`MiniHubError::Validation`] if required fields are missing or empty.
     pub fn build(self) -> Result<Automation, MiniHubError> {
-        let automation = Automation {
+        let automation = self.assemble();
+        automation.validate()?;
+        Ok(automation)
+    }
+
+    /// Consume the builder and list every field failing validation, without
+    /// building anything.
+    #[must_use]
+    pub fn field_errors(self) -> Vec<FieldError> {
+        self.assemble().field_errors()
+    }
+
+    fn assemble(self) -> Automation {
+        Automation {
             id: self.id.unwrap_or_default(),
             name: self.name.unwrap_or_default(),
             enabled: self.enabled.unwrap_or(true),
             trigger: self.trigger.unwrap_or(Trigger::Manual),
             conditions: self.conditions,
             actions: self.actions,
+            execution_mode: self.execution_mode,
             last_triggered: self.last_triggered,
-        };
-        automation.validate()?;
-        Ok(automation)
+            version: self.version.unwrap_or(1),
+            source_key: self.source_key,
+        }
     }
 }
 
@@ -167,6 +299,7 @@ mod tests {
         assert!(auto.conditions.is_empty());
         assert_eq!(auto.actions.len(), 1);
         assert!(auto.last_triggered.is_none());
+        assert_eq!(auto.version, 1);
     }
 
     #[test]
@@ -214,6 +347,116 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn should_return_validation_error_when_expression_does_not_parse() {
+        let result = Automation::builder()
+            .name("Broken expression")
+            .action(valid_action())
+            .condition(Condition::Expression {
+                expr: "time.hour >=".to_string(),
+            })
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(
+                ValidationError::InvalidExpression(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_list_every_invalid_field_when_checking_builder() {
+        let errors = Automation::builder()
+            .condition(Condition::Expression {
+                expr: "true".to_string(),
+            })
+            .condition(Condition::Expression {
+                expr: "time.hour >=".to_string(),
+            })
+            .field_errors();
+        let fields: Vec<&str> = errors.iter().map(|err| err.field.as_str()).collect();
+        assert_eq!(fields, ["name", "actions", "conditions[1]"]);
+        assert!(matches!(
+            errors[2].error,
+            ValidationError::InvalidExpression(_)
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_cron_pattern_does_not_parse() {
+        let result = Automation::builder()
+            .name("Broken schedule")
+            .trigger(Trigger::TimePattern {
+                cron: "every morning".to_string(),
+            })
+            .action(valid_action())
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::InvalidCron(_)))
+        ));
+    }
+
+    #[test]
+    fn should_accept_edit_keeping_a_stored_cron_pattern_that_does_not_parse() {
+        let mut stored = valid_automation();
+        stored.trigger = Trigger::TimePattern {
+            cron: "every morning".to_string(),
+        };
+        let mut renamed = stored.clone();
+        renamed.name = "Morning".to_string();
+        let mut rescheduled = stored.clone();
+        rescheduled.trigger = Trigger::TimePattern {
+            cron: "every evening".to_string(),
+        };
+
+        assert!(renamed.validate_edit(&stored).is_ok());
+        assert!(matches!(
+            rescheduled.validate_edit(&stored),
+            Err(MiniHubError::Validation(ValidationError::InvalidCron(_)))
+        ));
+        renamed.actions.clear();
+        assert!(matches!(
+            renamed.validate_edit(&stored),
+            Err(MiniHubError::Validation(ValidationError::NoActions))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_webhook_id_is_not_url_safe() {
+        let result = Automation::builder()
+            .name("Doorbell")
+            .trigger(Trigger::Webhook {
+                webhook_id: "front door".to_string(),
+            })
+            .action(valid_action())
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::InvalidWebhookId(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_sun_offset_exceeds_half_a_day() {
+        let result = Automation::builder()
+            .name("Porch light")
+            .trigger(Trigger::Sun {
+                event: sun::SunEvent::Sunset,
+                offset_minutes: 721,
+            })
+            .action(valid_action())
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::InvalidSunOffset(
+                721
+            )))
+        ));
+    }
+
     #[test]
     fn should_accumulate_multiple_conditions() {
         let eid = EntityId::new();
@@ -257,6 +500,17 @@ mod tests {
         assert_eq!(auto.last_triggered, Some(ts));
     }
 
+    #[test]
+    fn should_set_version_via_builder() {
+        let auto = Automation::builder()
+            .name("Edited rule")
+            .action(valid_action())
+            .version(4)
+            .build()
+            .unwrap();
+        assert_eq!(auto.version, 4);
+    }
+
     #[test]
     fn should_set_custom_id_via_builder() {
         let id = AutomationId::new();
@@ -278,6 +532,17 @@ mod tests {
         assert_eq!(parsed.name, auto.name);
         assert_eq!(parsed.enabled, auto.enabled);
         assert_eq!(parsed.actions.len(), auto.actions.len());
+        assert_eq!(parsed.version, auto.version);
+    }
+
+    #[test]
+    fn should_default_execution_mode_to_single_when_missing_from_json() {
+        let mut json = serde_json::to_value(valid_automation()).unwrap();
+        json.as_object_mut().unwrap().remove("execution_mode");
+
+        let parsed: Automation = serde_json::from_value(json).unwrap();
+
+        assert_eq!(parsed.execution_mode, ExecutionMode::Single);
     }
 
     #[test]
@@ -297,7 +562,7 @@ mod tests {
         let event = Event::new(
             EventType::StateChanged,
             Some(eid),
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         );
         assert!(auto.trigger.matches_event(&event));
     }
@@ -318,7 +583,7 @@ mod tests {
         let event = Event::new(
             EventType::StateChanged,
             Some(EntityId::new()),
-            serde_json::json!({"from": "off", "to": "on"}),
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
         );
         assert!(!auto.trigger.matches_event(&event));
     }
@@ -0,0 +1,369 @@
+//! Schedules — when the `time_pattern` triggers of automations fire.
+//!
+//! A scheduler publishes a [`time_pattern_fired`] event whenever a pattern
+//! comes due, for the automation engine to match.
+//!
+//! Patterns use the classic five cron fields, evaluated in UTC:
+//! `minute hour day-of-month month day-of-week`. Each field is `*`, a
+//! value, a range `a-b` or a comma-separated list of them, optionally
+//! followed by a step (`*/15`, `8-18/2`). Day-of-week runs from `0`
+//! (Sunday) to `6`, `7` being accepted for Sunday too. As in cron, when
+//! both day fields are restricted a day matching either of them fires.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use serde::Serialize;
+
+use super::{Automation, Trigger};
+use crate::error::ValidationError;
+use crate::event::{Event, EventType, TimePatternFiredPayload};
+use crate::id::AutomationId;
+use crate::time::Timestamp;
+
+/// Number of days searched for the next firing before giving up: enough
+/// for a 29th of February falling on a given weekday.
+const MAX_SEARCH_DAYS: u32 = 366 * 28;
+
+/// A parsed cron pattern, each field stored as a bit set of its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day-of-month field is not `*`.
+    days_restricted: bool,
+    /// Whether the day-of-week field is not `*`.
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// The first firing strictly after `after`.
+    ///
+    /// Returns `None` when the pattern never fires, e.g. `0 0 31 2 *`.
+    #[must_use]
+    pub fn next_after(&self, after: Timestamp) -> Option<Timestamp> {
+        let start = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(chrono::Duration::minutes(1))?;
+        let mut date = start.date_naive();
+        let (mut hour, mut minute) = (start.hour(), start.minute());
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date)
+                && let Some((hour, minute)) = self.first_time_from(hour, minute)
+            {
+                return Some(Utc.from_utc_datetime(&date.and_hms_opt(hour, minute, 0)?));
+            }
+            date = date.succ_opt()?;
+            (hour, minute) = (0, 0);
+        }
+        None
+    }
+
+    /// Firings strictly after `after` and strictly before `until`, in order.
+    pub fn between(&self, after: Timestamp, until: Timestamp) -> impl Iterator<Item = Timestamp> {
+        std::iter::successors(self.next_after(after), |at| self.next_after(*at))
+            .take_while(move |at| *at < until)
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !contains(self.months, date.month()) {
+            return false;
+        }
+        let day = contains(self.days, date.day());
+        let weekday = contains(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching time of day at or after `hour:minute`.
+    fn first_time_from(&self, hour: u32, minute: u32) -> Option<(u32, u32)> {
+        (hour..24)
+            .filter(|hour| contains(self.hours, *hour))
+            .find_map(|candidate| {
+                let from = if candidate == hour { minute } else { 0 };
+                (from..60)
+                    .find(|minute| contains(self.minutes, *minute))
+                    .map(|minute| (candidate, minute))
+            })
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = ValidationError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| ValidationError::InvalidCron(format!("{pattern}: {reason}"));
+        let fields: Vec<&str> = pattern.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(invalid(format!("expected 5 fields, got {}", fields.len())));
+        };
+        let mut weekday_bits = parse_field(weekdays, 0, 7).map_err(invalid)?;
+        if contains(weekday_bits, 7) {
+            weekday_bits = weekday_bits & !(1 << 7) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minutes, 0, 59).map_err(invalid)?,
+            hours: parse_field(hours, 0, 23).map_err(invalid)?,
+            days: parse_field(days, 1, 31).map_err(invalid)?,
+            months: parse_field(months, 1, 12).map_err(invalid)?,
+            weekdays: weekday_bits,
+            days_restricted: !days.starts_with('*'),
+            weekdays_restricted: !weekdays.starts_with('*'),
+        })
+    }
+}
+
+fn contains(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// Parse one cron field into the bit set of the values it allows.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let parse_value = |value: &str| {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(|| format!("{value:?} is not a number from {min} to {max}"))
+    };
+    let mut bits = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("{step:?} is not a positive step"))?,
+            ),
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start)?, parse_value(end)?)
+        } else {
+            let value = parse_value(range)?;
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            return Err(format!("range {range:?} is reversed"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// The [`EventType::TimePatternFired`] event published when `cron` comes
+/// due.
+#[must_use]
+pub fn time_pattern_fired(cron: &str) -> Event {
+    Event::with_payload(
+        EventType::TimePatternFired,
+        None,
+        &TimePatternFiredPayload {
+            cron: cron.to_string(),
+        },
+    )
+}
+
+/// An upcoming firing of an automation's `time_pattern` trigger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScheduledFiring {
+    pub automation_id: AutomationId,
+    pub name: String,
+    pub at: Timestamp,
+}
+
+/// Firings of the enabled `time_pattern` automations strictly after `after`
+/// and before `until`, soonest first, keeping the first `limit` of them.
+///
+/// Automations whose pattern does not parse are skipped.
+#[must_use]
+pub fn upcoming_firings(
+    automations: &[Automation],
+    after: Timestamp,
+    until: Timestamp,
+    limit: usize,
+) -> Vec<ScheduledFiring> {
+    let mut firings: Vec<ScheduledFiring> = automations
+        .iter()
+        .filter(|automation| automation.enabled)
+        .filter_map(|automation| match &automation.trigger {
+            Trigger::TimePattern { cron } => cron
+                .parse::<CronSchedule>()
+                .ok()
+                .map(|schedule| (automation, schedule)),
+            _ => None,
+        })
+        .flat_map(|(automation, schedule)| {
+            schedule
+                .between(after, until)
+                .take(limit)
+                .map(|at| ScheduledFiring {
+                    automation_id: automation.id,
+                    name: automation.name.clone(),
+                    at,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    firings.sort_by_key(|firing| firing.at);
+    firings.truncate(limit);
+    firings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::Action;
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    fn schedule(pattern: &str) -> CronSchedule {
+        pattern.parse().unwrap()
+    }
+
+    #[test]
+    fn should_fire_every_minute_when_every_field_is_a_wildcard() {
+        let next = schedule("* * * * *").next_after(at("2026-03-01T10:15:30Z"));
+
+        assert_eq!(next, Some(at("2026-03-01T10:16:00Z")));
+    }
+
+    #[test]
+    fn should_fire_next_day_when_time_already_passed() {
+        let next = schedule("0 8 * * *").next_after(at("2026-03-01T08:00:00Z"));
+
+        assert_eq!(next, Some(at("2026-03-02T08:00:00Z")));
+    }
+
+    #[test]
+    fn should_follow_steps_ranges_and_lists() {
+        let firings: Vec<_> = schedule("*/20 9-10 * * *")
+            .between(at("2026-03-01T00:00:00Z"), at("2026-03-02T00:00:00Z"))
+            .collect();
+
+        assert_eq!(
+            firings,
+            [
+                "2026-03-01T09:00:00Z",
+                "2026-03-01T09:20:00Z",
+                "2026-03-01T09:40:00Z",
+                "2026-03-01T10:00:00Z",
+                "2026-03-01T10:20:00Z",
+                "2026-03-01T10:40:00Z",
+            ]
+            .map(at)
+        );
+    }
+
+    #[test]
+    fn should_fire_on_weekdays_only_when_day_of_week_is_restricted() {
+        // 2026-03-06 is a Friday.
+        let next = schedule("30 7 * * 1-5").next_after(at("2026-03-06T08:00:00Z"));
+
+        assert_eq!(next, Some(at("2026-03-09T07:30:00Z")));
+    }
+
+    #[test]
+    fn should_accept_seven_as_sunday() {
+        // 2026-03-08 is a Sunday.
+        let next = schedule("0 12 * * 7").next_after(at("2026-03-06T00:00:00Z"));
+
+        assert_eq!(next, Some(at("2026-03-08T12:00:00Z")));
+    }
+
+    #[test]
+    fn should_fire_on_either_day_field_when_both_are_restricted() {
+        // 2026-03-02 is a Monday, before the 15th.
+        let next = schedule("0 0 15 * 1").next_after(at("2026-03-01T12:00:00Z"));
+
+        assert_eq!(next, Some(at("2026-03-02T00:00:00Z")));
+    }
+
+    #[test]
+    fn should_never_fire_when_date_does_not_exist() {
+        assert_eq!(
+            schedule("0 0 31 2 *").next_after(at("2026-01-01T00:00:00Z")),
+            None
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_patterns() {
+        for pattern in [
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            let err = pattern.parse::<CronSchedule>().unwrap_err();
+            assert!(
+                matches!(err, ValidationError::InvalidCron(_)),
+                "{pattern} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn should_list_firings_of_enabled_time_pattern_automations_soonest_first() {
+        let automation = |name: &str, enabled: bool, trigger: Trigger| {
+            Automation::builder()
+                .name(name)
+                .enabled(enabled)
+                .trigger(trigger)
+                .action(Action::Delay { seconds: 1 })
+                .build()
+                .unwrap()
+        };
+        let automations = [
+            automation(
+                "evening",
+                true,
+                Trigger::TimePattern {
+                    cron: "0 20 * * *".to_string(),
+                },
+            ),
+            automation(
+                "morning",
+                true,
+                Trigger::TimePattern {
+                    cron: "0 8 * * *".to_string(),
+                },
+            ),
+            automation(
+                "disabled",
+                false,
+                Trigger::TimePattern {
+                    cron: "0 9 * * *".to_string(),
+                },
+            ),
+            automation("manual", true, Trigger::Manual),
+        ];
+
+        let firings = upcoming_firings(
+            &automations,
+            at("2026-03-01T00:00:00Z"),
+            at("2026-03-03T00:00:00Z"),
+            3,
+        );
+
+        let names: Vec<_> = firings.iter().map(|firing| firing.name.as_str()).collect();
+        assert_eq!(names, ["morning", "evening", "morning"]);
+        assert_eq!(firings[2].at, at("2026-03-02T08:00:00Z"));
+    }
+}
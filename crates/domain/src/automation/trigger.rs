@@ -3,11 +3,16 @@
 use serde::{Deserialize, Serialize};
 
 use crate::entity::EntityState;
-use crate::event::{Event, EventType};
-use crate::id::EntityId;
+use crate::event::{
+    AttributeChangedPayload, DeviceAvailabilityPayload, Event, EventType, StateChangedPayload,
+    SunEventPayload, TimePatternFiredPayload, WebhookReceivedPayload,
+};
+use crate::id::{DeviceId, EntityId};
+use crate::input_helper::{self, VALUE_ATTRIBUTE};
+use crate::sun::SunEvent;
 
 /// Describes what event pattern should activate an automation.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Trigger {
     /// Fires when a specific entity changes state.
@@ -18,17 +23,51 @@ pub enum Trigger {
         /// Optional: only match if transitioning *to* this state.
         to: Option<EntityState>,
     },
+    /// Fires when the `value` attribute of an entity changes, typically a
+    /// number or select input helper.
+    ValueChanged {
+        entity_id: EntityId,
+        /// Optional: only match if the new value is this one.
+        #[serde(default)]
+        to: Option<serde_json::Value>,
+    },
+    /// Fires when every entity of a device becomes unavailable.
+    DeviceUnavailable { device_id: DeviceId },
+    /// Fires when an unavailable device has an available entity again.
+    DeviceBackOnline { device_id: DeviceId },
     /// Fires on a cron-like time pattern (e.g. `"0 8 * * *"`).
     TimePattern { cron: String },
+    /// Fires when an external system calls the inbound webhook
+    /// `POST /api/webhook/{webhook_id}`.
+    Webhook { webhook_id: String },
+    /// Fires at sunrise or sunset, shifted by `offset_minutes`: negative
+    /// before the event, positive after.
+    Sun {
+        event: SunEvent,
+        #[serde(default)]
+        offset_minutes: i32,
+    },
     /// Fires only when triggered manually via the API.
     Manual,
 }
 
 impl Trigger {
+    /// Types of the events a trigger can match; other events never
+    /// activate an automation.
+    pub const EVENT_TYPES: [EventType; 7] = [
+        EventType::StateChanged,
+        EventType::AttributeChanged,
+        EventType::DeviceUnavailable,
+        EventType::DeviceBackOnline,
+        EventType::WebhookReceived,
+        EventType::SunEvent,
+        EventType::TimePatternFired,
+    ];
+
     /// Check whether this trigger matches a given event.
     ///
-    /// `TimePattern` and `Manual` triggers never match broadcast events;
-    /// they are activated through other mechanisms.
+    /// `Manual` triggers never match broadcast events; they are activated
+    /// through other mechanisms.
     #[must_use]
     pub fn matches_event(&self, event: &Event) -> bool {
         match self {
@@ -37,36 +76,72 @@ impl Trigger {
                 from,
                 to,
             } => {
-                if event.event_type != EventType::StateChanged {
+                if event.entity_id != Some(*entity_id) {
                     return false;
                 }
+                let Ok(change) = event.payload::<StateChangedPayload>() else {
+                    return false;
+                };
+                from.as_ref().is_none_or(|from| *from == change.old_state)
+                    && to.as_ref().is_none_or(|to| *to == change.new_state)
+            }
+            Self::ValueChanged { entity_id, to } => {
                 if event.entity_id != Some(*entity_id) {
                     return false;
                 }
-                if let Some(expected_from) = from {
-                    let actual = event.data.get("from").and_then(|v| v.as_str());
-                    if actual != Some(&expected_from.to_string()) {
-                        return false;
-                    }
-                }
-                if let Some(expected_to) = to {
-                    let actual = event.data.get("to").and_then(|v| v.as_str());
-                    if actual != Some(&expected_to.to_string()) {
-                        return false;
-                    }
-                }
-                true
+                let Ok(AttributeChangedPayload { changed }) = event.payload() else {
+                    return false;
+                };
+                let Some(actual) = changed.get(VALUE_ATTRIBUTE) else {
+                    return false;
+                };
+                to.as_ref()
+                    .is_none_or(|expected| input_helper::value_eq(actual, expected))
+            }
+            Self::DeviceUnavailable { device_id } => {
+                matches_device_event(event, &EventType::DeviceUnavailable, *device_id)
+            }
+            Self::DeviceBackOnline { device_id } => {
+                matches_device_event(event, &EventType::DeviceBackOnline, *device_id)
             }
-            Self::TimePattern { .. } | Self::Manual => false,
+            Self::Webhook { webhook_id } => event
+                .payload::<WebhookReceivedPayload>()
+                .is_ok_and(|call| call.webhook_id == *webhook_id),
+            Self::Sun {
+                event: sun_event,
+                offset_minutes,
+            } => event.payload::<SunEventPayload>().is_ok_and(|fired| {
+                fired.event == *sun_event && fired.offset_minutes == *offset_minutes
+            }),
+            Self::TimePattern { cron } => event
+                .payload::<TimePatternFiredPayload>()
+                .is_ok_and(|fired| fired.cron == *cron),
+            Self::Manual => false,
         }
     }
 }
 
+/// Whether `event` is of type `event_type` and concerns `device_id`.
+fn matches_device_event(event: &Event, event_type: &EventType, device_id: DeviceId) -> bool {
+    event.event_type == *event_type
+        && event
+            .payload::<DeviceAvailabilityPayload>()
+            .is_ok_and(|transition| transition.device_id == device_id)
+}
+
 impl std::fmt::Display for Trigger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::StateChanged { entity_id, .. } => write!(f, "state_changed({entity_id})"),
+            Self::ValueChanged { entity_id, .. } => write!(f, "value_changed({entity_id})"),
+            Self::DeviceUnavailable { device_id } => write!(f, "device_unavailable({device_id})"),
+            Self::DeviceBackOnline { device_id } => write!(f, "device_back_online({device_id})"),
             Self::TimePattern { cron } => write!(f, "time_pattern({cron})"),
+            Self::Webhook { webhook_id } => write!(f, "webhook({webhook_id})"),
+            Self::Sun {
+                event,
+                offset_minutes,
+            } => write!(f, "sun({event}, {offset_minutes:+}min)"),
             Self::Manual => f.write_str("manual"),
         }
     }
@@ -77,13 +152,31 @@ mod tests {
     use super::*;
 
     fn state_changed_event(entity_id: EntityId, from: &str, to: &str) -> Event {
-        Event::new(
+        Event::with_payload(
             EventType::StateChanged,
             Some(entity_id),
-            serde_json::json!({"from": from, "to": to}),
+            &StateChangedPayload {
+                old_state: from.parse().unwrap(),
+                new_state: to.parse().unwrap(),
+            },
         )
     }
 
+    #[test]
+    fn should_only_match_event_types_listed_in_event_types() {
+        let eid = EntityId::new();
+        let trigger = Trigger::StateChanged {
+            entity_id: eid,
+            from: None,
+            to: None,
+        };
+        let mut event = state_changed_event(eid, "off", "on");
+        event.event_type = EventType::EntityCreated;
+
+        assert!(!Trigger::EVENT_TYPES.contains(&event.event_type));
+        assert!(!trigger.matches_event(&event));
+    }
+
     #[test]
     fn should_match_when_entity_and_type_match_without_from_to() {
         let eid = EntityId::new();
@@ -157,13 +250,87 @@ mod tests {
         assert!(!trigger.matches_event(&event));
     }
 
+    fn device_event(event_type: EventType, device_id: DeviceId) -> Event {
+        Event::with_payload(
+            event_type,
+            None,
+            &DeviceAvailabilityPayload {
+                device_id,
+                device_name: "Plug".to_string(),
+                status: crate::device::DeviceStatus::Unavailable,
+            },
+        )
+    }
+
+    #[test]
+    fn should_match_device_unavailable_for_same_device() {
+        let did = DeviceId::new();
+        let trigger = Trigger::DeviceUnavailable { device_id: did };
+        assert!(trigger.matches_event(&device_event(EventType::DeviceUnavailable, did)));
+        assert!(
+            !trigger.matches_event(&device_event(EventType::DeviceUnavailable, DeviceId::new()))
+        );
+        assert!(!trigger.matches_event(&device_event(EventType::DeviceBackOnline, did)));
+    }
+
     #[test]
-    fn should_not_match_time_pattern_trigger_against_events() {
+    fn should_match_device_back_online_for_same_device() {
+        let did = DeviceId::new();
+        let trigger = Trigger::DeviceBackOnline { device_id: did };
+        assert!(trigger.matches_event(&device_event(EventType::DeviceBackOnline, did)));
+        assert!(!trigger.matches_event(&device_event(EventType::DeviceUnavailable, did)));
+    }
+
+    fn value_changed_event(entity_id: EntityId, value: &serde_json::Value) -> Event {
+        Event::new(
+            EventType::AttributeChanged,
+            Some(entity_id),
+            serde_json::json!({ "changed": { "value": value } }),
+        )
+    }
+
+    #[test]
+    fn should_match_value_changed_when_value_matches() {
+        let eid = EntityId::new();
+        let trigger = Trigger::ValueChanged {
+            entity_id: eid,
+            to: Some(serde_json::json!(21)),
+        };
+        assert!(trigger.matches_event(&value_changed_event(eid, &serde_json::json!(21.0))));
+        assert!(!trigger.matches_event(&value_changed_event(eid, &serde_json::json!(20.5))));
+        assert!(!trigger.matches_event(&value_changed_event(
+            EntityId::new(),
+            &serde_json::json!(21)
+        )));
+    }
+
+    #[test]
+    fn should_not_match_value_changed_when_value_attribute_is_untouched() {
+        let eid = EntityId::new();
+        let trigger = Trigger::ValueChanged {
+            entity_id: eid,
+            to: None,
+        };
+        let event = Event::new(
+            EventType::AttributeChanged,
+            Some(eid),
+            serde_json::json!({ "changed": { "brightness": 10 } }),
+        );
+        assert!(!trigger.matches_event(&event));
+        assert!(trigger.matches_event(&value_changed_event(eid, &serde_json::json!("movie"))));
+    }
+
+    #[test]
+    fn should_match_time_pattern_fired_with_same_cron() {
         let trigger = Trigger::TimePattern {
             cron: "0 8 * * *".to_string(),
         };
-        let event = state_changed_event(EntityId::new(), "off", "on");
-        assert!(!trigger.matches_event(&event));
+        let event = crate::automation::time_pattern_fired("0 8 * * *");
+
+        assert!(Trigger::EVENT_TYPES.contains(&event.event_type));
+        assert!(trigger.matches_event(&event));
+        assert!(!trigger.matches_event(&crate::automation::time_pattern_fired("0 9 * * *")));
+        assert!(!trigger.matches_event(&state_changed_event(EntityId::new(), "off", "on")));
     }
 
     #[test]
@@ -203,6 +370,16 @@ mod tests {
             Trigger::TimePattern {
                 cron: "0 8 * * *".to_string(),
             },
+            Trigger::ValueChanged {
+                entity_id: eid,
+                to: Some(serde_json::json!("away")),
+            },
+            Trigger::DeviceUnavailable {
+                device_id: DeviceId::new(),
+            },
+            Trigger::DeviceBackOnline {
+                device_id: DeviceId::new(),
+            },
             Trigger::Manual,
         ];
 
@@ -212,4 +389,35 @@ mod tests {
             assert_eq!(&parsed, trigger);
         }
     }
+
+    #[test]
+    fn should_match_webhook_received_with_same_webhook_id() {
+        let trigger = Trigger::Webhook {
+            webhook_id: "doorbell-7f3c9a1e52".to_string(),
+        };
+        let event =
+            crate::webhook::received_event("doorbell-7f3c9a1e52", serde_json::json!({"button": 1}));
+
+        assert!(Trigger::EVENT_TYPES.contains(&event.event_type));
+        assert!(trigger.matches_event(&event));
+        assert!(!trigger.matches_event(&crate::webhook::received_event(
+            "garage",
+            serde_json::Value::Null
+        )));
+    }
+
+    #[test]
+    fn should_match_sun_event_with_same_event_and_offset() {
+        let trigger = Trigger::Sun {
+            event: SunEvent::Sunset,
+            offset_minutes: -30,
+        };
+        let event = crate::sun::fired_event(SunEvent::Sunset, -30);
+
+        assert!(Trigger::EVENT_TYPES.contains(&event.event_type));
+        assert!(trigger.matches_event(&event));
+        assert!(!trigger.matches_event(&crate::sun::fired_event(SunEvent::Sunset, 0)));
+        assert!(!trigger.matches_event(&crate::sun::fired_event(SunEvent::Sunrise, -30)));
+        assert_eq!(trigger.to_string(), "sun(sunset, -30min)");
+    }
 }
@@ -0,0 +1,311 @@
+//! Automation run — execution log entry for a single automation activation.
+//!
+//! A run is recorded every time an automation's trigger matches an event.
+//! It captures which conditions were evaluated (and whether they passed)
+//! and the outcome of each action, so users can understand why an
+//! automation did or did not do what they expected.
+
+use serde::{Deserialize, Serialize};
+
+use crate::automation::{Action, Condition};
+use crate::entity::Entity;
+use crate::id::{AutomationId, AutomationRunId, EventId};
+use crate::time::Timestamp;
+
+/// The result of evaluating a single [`Condition`] during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConditionResult {
+    pub condition: Condition,
+    pub passed: bool,
+}
+
+/// What happened to a single [`Action`] during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ActionOutcome {
+    /// The action completed successfully.
+    Succeeded,
+    /// The action failed with the given error message.
+    Failed { error: String },
+    /// The action was not executed because a previous action failed.
+    Skipped,
+    /// The action was interrupted, or not executed, because a new trigger
+    /// restarted the automation.
+    Cancelled,
+}
+
+/// The outcome of a single [`Action`] during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub action: Action,
+    pub outcome: ActionOutcome,
+}
+
+/// How a single [`Condition`] evaluated during a dry run, with the
+/// entities it read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionTrace {
+    pub condition: Condition,
+    pub passed: bool,
+    /// Entities fetched to evaluate the condition, as they were read.
+    pub entities: Vec<Entity>,
+    /// Why the condition could not be evaluated, making it fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The outcome of testing an automation against a sample event without
+/// executing its actions.
+///
+/// Every condition is evaluated, even after one fails, so a single trace
+/// shows everything that keeps the automation from running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationTrace {
+    pub automation_id: AutomationId,
+    /// Whether the trigger matched the event.
+    pub matched: bool,
+    /// Whether the automation would have executed its actions: the trigger
+    /// matched and every condition passed.
+    pub would_run: bool,
+    pub conditions: Vec<ConditionTrace>,
+}
+
+impl AutomationTrace {
+    /// Create a trace, deriving [`Self::would_run`] from the trigger match
+    /// and the condition results.
+    #[must_use]
+    pub fn new(
+        automation_id: AutomationId,
+        matched: bool,
+        conditions: Vec<ConditionTrace>,
+    ) -> Self {
+        let would_run = matched && conditions.iter().all(|trace| trace.passed);
+        Self {
+            automation_id,
+            matched,
+            would_run,
+            conditions,
+        }
+    }
+}
+
+/// A record of one automation activation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationRun {
+    pub id: AutomationRunId,
+    pub automation_id: AutomationId,
+    /// The event that matched the trigger, if any.
+    pub event_id: Option<EventId>,
+    pub started_at: Timestamp,
+    pub conditions: Vec<ConditionResult>,
+    pub actions: Vec<ActionResult>,
+}
+
+impl AutomationRun {
+    /// Create a builder for constructing an [`AutomationRun`].
+    #[must_use]
+    pub fn builder() -> AutomationRunBuilder {
+        AutomationRunBuilder::default()
+    }
+
+    /// Whether every evaluated condition passed.
+    #[must_use]
+    pub fn conditions_passed(&self) -> bool {
+        self.conditions.iter().all(|result| result.passed)
+    }
+
+    /// Whether every action completed successfully.
+    ///
+    /// Returns `false` when no action was executed.
+    #[must_use]
+    pub fn succeeded(&self) -> bool {
+        !self.actions.is_empty()
+            && self
+                .actions
+                .iter()
+                .all(|result| result.outcome == ActionOutcome::Succeeded)
+    }
+}
+
+/// Step-by-step builder for [`AutomationRun`].
+#[derive(Debug, Default)]
+pub struct AutomationRunBuilder {
+    id: Option<AutomationRunId>,
+    automation_id: Option<AutomationId>,
+    event_id: Option<EventId>,
+    started_at: Option<Timestamp>,
+    conditions: Vec<ConditionResult>,
+    actions: Vec<ActionResult>,
+}
+
+impl AutomationRunBuilder {
+    #[must_use]
+    pub fn id(mut self, id: AutomationRunId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub fn automation_id(mut self, automation_id: AutomationId) -> Self {
+        self.automation_id = Some(automation_id);
+        self
+    }
+
+    #[must_use]
+    pub fn event_id(mut self, event_id: EventId) -> Self {
+        self.event_id = Some(event_id);
+        self
+    }
+
+    #[must_use]
+    pub fn started_at(mut self, started_at: Timestamp) -> Self {
+        self.started_at = Some(started_at);
+        self
+    }
+
+    #[must_use]
+    pub fn condition(mut self, condition: Condition, passed: bool) -> Self {
+        self.conditions.push(ConditionResult { condition, passed });
+        self
+    }
+
+    #[must_use]
+    pub fn action(mut self, action: Action, outcome: ActionOutcome) -> Self {
+        self.actions.push(ActionResult { action, outcome });
+        self
+    }
+
+    /// Consume the builder and return an [`AutomationRun`].
+    #[must_use]
+    pub fn build(self) -> AutomationRun {
+        AutomationRun {
+            id: self.id.unwrap_or_default(),
+            automation_id: self.automation_id.unwrap_or_default(),
+            event_id: self.event_id,
+            started_at: self.started_at.unwrap_or_else(crate::time::now),
+            conditions: self.conditions,
+            actions: self.actions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::EntityId;
+
+    fn turn_on() -> Action {
+        Action::CallService {
+            entity_id: EntityId::new(),
+            service: "turn_on".to_string(),
+            data: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn should_build_run_with_all_fields() {
+        let automation_id = AutomationId::new();
+        let event_id = EventId::new();
+        let started_at = crate::time::now();
+
+        let run = AutomationRun::builder()
+            .automation_id(automation_id)
+            .event_id(event_id)
+            .started_at(started_at)
+            .condition(
+                Condition::TimeRange {
+                    after: "00:00".to_string(),
+                    before: "23:59".to_string(),
+                },
+                true,
+            )
+            .action(turn_on(), ActionOutcome::Succeeded)
+            .build();
+
+        assert_eq!(run.automation_id, automation_id);
+        assert_eq!(run.event_id, Some(event_id));
+        assert_eq!(run.started_at, started_at);
+        assert!(run.conditions_passed());
+        assert!(run.succeeded());
+    }
+
+    #[test]
+    fn should_not_succeed_when_conditions_failed() {
+        let run = AutomationRun::builder()
+            .condition(
+                Condition::StateIs {
+                    entity_id: EntityId::new(),
+                    state: "on".to_string(),
+                },
+                false,
+            )
+            .build();
+
+        assert!(!run.conditions_passed());
+        assert!(!run.succeeded());
+    }
+
+    #[test]
+    fn should_not_succeed_when_an_action_failed() {
+        let run = AutomationRun::builder()
+            .action(
+                turn_on(),
+                ActionOutcome::Failed {
+                    error: "boom".to_string(),
+                },
+            )
+            .action(turn_on(), ActionOutcome::Skipped)
+            .build();
+
+        assert!(!run.succeeded());
+    }
+
+    #[test]
+    fn should_not_run_trace_when_a_condition_failed() {
+        let condition = |passed| ConditionTrace {
+            condition: Condition::HomeModeIs {
+                mode: crate::home_mode::HomeMode::Away,
+            },
+            passed,
+            entities: Vec::new(),
+            error: None,
+        };
+
+        let trace = AutomationTrace::new(AutomationId::new(), true, vec![condition(true)]);
+        assert!(trace.would_run);
+
+        let trace = AutomationTrace::new(
+            AutomationId::new(),
+            true,
+            vec![condition(true), condition(false)],
+        );
+        assert!(!trace.would_run);
+
+        let trace = AutomationTrace::new(AutomationId::new(), false, Vec::new());
+        assert!(!trace.would_run);
+    }
+
+    #[test]
+    fn should_serialize_action_outcome_with_status_tag() {
+        let json = serde_json::to_value(ActionOutcome::Failed {
+            error: "boom".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["error"], "boom");
+    }
+
+    #[test]
+    fn should_roundtrip_through_serde_json() {
+        let run = AutomationRun::builder()
+            .automation_id(AutomationId::new())
+            .action(turn_on(), ActionOutcome::Skipped)
+            .build();
+
+        let json = serde_json::to_string(&run).unwrap();
+        let parsed: AutomationRun = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.id, run.id);
+        assert_eq!(parsed.actions, run.actions);
+    }
+}
@@ -0,0 +1,125 @@
+//! Dashboard layout — the cards users pin to the home page of the dashboard.
+//!
+//! The layout is a single document: the cards are shown in the order they
+//! are listed, so reordering them is replacing the list.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ValidationError;
+use crate::id::{AreaId, EntityId};
+
+/// Maximum number of cards a dashboard holds.
+pub const MAX_CARDS: usize = 64;
+
+/// Longest time range a chart card covers, one week.
+pub const MAX_CHART_HOURS: u32 = 168;
+
+fn default_chart_hours() -> u32 {
+    24
+}
+
+/// A card pinned to the dashboard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Card {
+    /// The current state of an entity, with its controls.
+    Entity { entity_id: EntityId },
+    /// The history of an entity over the last `hours`.
+    Chart {
+        entity_id: EntityId,
+        #[serde(default = "default_chart_hours")]
+        hours: u32,
+    },
+    /// The entities of an area and how many of them are on.
+    Area { area_id: AreaId },
+}
+
+/// The layout of the dashboard home page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    /// Cards in display order.
+    #[serde(default)]
+    pub cards: Vec<Card>,
+}
+
+impl DashboardConfig {
+    /// Append `card` to the layout.
+    #[must_use]
+    pub fn with_card(mut self, card: Card) -> Self {
+        self.cards.push(card);
+        self
+    }
+
+    /// Validate the layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidDashboard`] when the layout holds
+    /// more than [`MAX_CARDS`] cards or a chart covers no time or more than
+    /// [`MAX_CHART_HOURS`].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.cards.len() > MAX_CARDS {
+            return Err(ValidationError::InvalidDashboard(format!(
+                "{} cards, at most {MAX_CARDS} are allowed",
+                self.cards.len()
+            )));
+        }
+        for card in &self.cards {
+            if let Card::Chart { hours, .. } = card
+                && !(1..=MAX_CHART_HOURS).contains(hours)
+            {
+                return Err(ValidationError::InvalidDashboard(format!(
+                    "chart covers {hours} hours, expected 1 to {MAX_CHART_HOURS}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_default_chart_to_a_day_when_hours_missing() {
+        let entity_id = EntityId::new();
+        let config: DashboardConfig = serde_json::from_value(serde_json::json!({
+            "cards": [{ "type": "chart", "entity_id": entity_id }],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            config.cards,
+            vec![Card::Chart {
+                entity_id,
+                hours: 24
+            }]
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_reject_chart_when_hours_out_of_range() {
+        let config = DashboardConfig::default().with_card(Card::Chart {
+            entity_id: EntityId::new(),
+            hours: 0,
+        });
+
+        assert!(matches!(
+            config.validate(),
+            Err(ValidationError::InvalidDashboard(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_layout_when_too_many_cards() {
+        let config = (0..=MAX_CARDS).fold(DashboardConfig::default(), |config, _| {
+            config.with_card(Card::Area {
+                area_id: AreaId::new(),
+            })
+        });
+
+        assert!(config.validate().is_err());
+    }
+}
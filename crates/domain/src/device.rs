@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::entity::Entity;
 use crate::error::{MiniHubError, ValidationError};
 use crate::id::{AreaId, DeviceId};
 
@@ -15,6 +16,15 @@ pub struct Device {
     pub area_id: Option<AreaId>,
     pub integration: String,
     pub unique_id: String,
+    /// Device this one is reached through, e.g. the bridge or coordinator
+    /// exposing it.
+    pub via_device_id: Option<DeviceId>,
+    /// Firmware or software version.
+    pub sw_version: Option<String>,
+    /// Hardware revision.
+    pub hw_version: Option<String>,
+    /// Name of the area the integration suggests placing the device in.
+    pub suggested_area: Option<String>,
 }
 
 impl Device {
@@ -28,7 +38,8 @@ impl Device {
     ///
     /// # Errors
     ///
-    /// Returns [`MiniHubError::Validation`] when `name` is empty.
+    /// Returns [`MiniHubError::Validation`] when `name`, `integration` or
+    /// `unique_id` is empty, or when the device is its own `via_device_id`.
     pub fn validate(&self) -> Result<(), MiniHubError> {
         if self.name.is_empty() {
             return Err(ValidationError::EmptyName.into());
@@ -39,10 +50,60 @@ impl Device {
         if self.unique_id.is_empty() {
             return Err(ValidationError::EmptyUniqueId.into());
         }
+        if self.via_device_id == Some(self.id) {
+            return Err(ValidationError::SelfViaDevice.into());
+        }
         Ok(())
     }
 }
 
+/// Availability of a device, derived from the states of its entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceStatus {
+    /// At least one entity is available.
+    Online,
+    /// Every entity is unavailable.
+    Unavailable,
+    /// The device has no entity to derive a status from.
+    #[default]
+    Unknown,
+}
+
+impl DeviceStatus {
+    /// Derive the status of a device from its entities.
+    #[must_use]
+    pub fn from_entities<'a>(entities: impl IntoIterator<Item = &'a Entity>) -> Self {
+        let mut status = Self::Unknown;
+        for entity in entities {
+            if entity.state.is_available() {
+                return Self::Online;
+            }
+            status = Self::Unavailable;
+        }
+        status
+    }
+}
+
+impl std::fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Online => f.write_str("online"),
+            Self::Unavailable => f.write_str("unavailable"),
+            Self::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// A device along with its derived [`DeviceStatus`], as served by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceWithStatus {
+    #[serde(flatten)]
+    pub device: Device,
+    #[serde(default)]
+    pub status: DeviceStatus,
+}
+
 /// Step-by-step builder for [`Device`].
 #[derive(Debug, Default)]
 pub struct DeviceBuilder {
@@ -53,6 +114,10 @@ pub struct DeviceBuilder {
     area_id: Option<AreaId>,
     integration: Option<String>,
     unique_id: Option<String>,
+    via_device_id: Option<DeviceId>,
+    sw_version: Option<String>,
+    hw_version: Option<String>,
+    suggested_area: Option<String>,
 }
 
 impl DeviceBuilder {
@@ -98,6 +163,30 @@ impl DeviceBuilder {
         self
     }
 
+    #[must_use]
+    pub fn via_device_id(mut self, via_device_id: DeviceId) -> Self {
+        self.via_device_id = Some(via_device_id);
+        self
+    }
+
+    #[must_use]
+    pub fn sw_version(mut self, sw_version: impl Into<String>) -> Self {
+        self.sw_version = Some(sw_version.into());
+        self
+    }
+
+    #[must_use]
+    pub fn hw_version(mut self, hw_version: impl Into<String>) -> Self {
+        self.hw_version = Some(hw_version.into());
+        self
+    }
+
+    #[must_use]
+    pub fn suggested_area(mut self, suggested_area: impl Into<String>) -> Self {
+        self.suggested_area = Some(suggested_area.into());
+        self
+    }
+
     /// Consume the builder, validate, and return a [`Device`].
     ///
     /// # Errors
@@ -112,6 +201,10 @@ impl DeviceBuilder {
             area_id: self.area_id,
             integration: self.integration.unwrap_or_default(),
             unique_id: self.unique_id.unwrap_or_default(),
+            via_device_id: self.via_device_id,
+            sw_version: self.sw_version,
+            hw_version: self.hw_version,
+            suggested_area: self.suggested_area,
         };
         device.validate()?;
         Ok(device)
@@ -121,6 +214,7 @@ impl DeviceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entity::EntityState;
 
     fn valid_device() -> Device {
         Device::builder()
@@ -190,6 +284,60 @@ mod tests {
         assert_eq!(device.area_id, Some(area));
     }
 
+    #[test]
+    fn should_build_device_with_hierarchy_and_versions() {
+        let bridge = valid_device();
+        let device = Device::builder()
+            .name("Motion Sensor")
+            .integration("zigbee")
+            .unique_id("0x00158d0001a2b3c4")
+            .via_device_id(bridge.id)
+            .sw_version("3000-0001")
+            .hw_version("rev2")
+            .suggested_area("Hallway")
+            .build()
+            .unwrap();
+
+        assert_eq!(device.via_device_id, Some(bridge.id));
+        assert_eq!(device.sw_version.as_deref(), Some("3000-0001"));
+        assert_eq!(device.hw_version.as_deref(), Some("rev2"));
+        assert_eq!(device.suggested_area.as_deref(), Some("Hallway"));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_device_is_its_own_via_device() {
+        let id = DeviceId::new();
+        let result = Device::builder()
+            .id(id)
+            .name("Loop")
+            .integration("test")
+            .unique_id("loop")
+            .via_device_id(id)
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::SelfViaDevice))
+        ));
+    }
+
+    #[test]
+    fn should_deserialize_device_without_hierarchy_fields() {
+        let json = serde_json::json!({
+            "id": DeviceId::new(),
+            "name": "Legacy",
+            "manufacturer": null,
+            "model": null,
+            "area_id": null,
+            "integration": "test",
+            "unique_id": "legacy",
+        });
+
+        let device: Device = serde_json::from_value(json).unwrap();
+
+        assert!(device.via_device_id.is_none());
+        assert!(device.sw_version.is_none());
+    }
+
     #[test]
     fn should_roundtrip_through_serde_json() {
         let device = valid_device();
@@ -200,4 +348,48 @@ mod tests {
         assert_eq!(parsed.integration, device.integration);
         assert_eq!(parsed.unique_id, device.unique_id);
     }
+
+    fn entity(state: EntityState) -> Entity {
+        Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id("sensor.probe")
+            .friendly_name("Probe")
+            .state(state)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_derive_online_when_any_entity_is_available() {
+        let entities = [entity(EntityState::Unavailable), entity(EntityState::Off)];
+        assert_eq!(DeviceStatus::from_entities(&entities), DeviceStatus::Online);
+    }
+
+    #[test]
+    fn should_derive_unavailable_when_every_entity_is_unavailable() {
+        let entities = [
+            entity(EntityState::Unavailable),
+            entity(EntityState::Unavailable),
+        ];
+        assert_eq!(
+            DeviceStatus::from_entities(&entities),
+            DeviceStatus::Unavailable
+        );
+    }
+
+    #[test]
+    fn should_derive_unknown_when_device_has_no_entity() {
+        assert_eq!(DeviceStatus::from_entities(&[]), DeviceStatus::Unknown);
+    }
+
+    #[test]
+    fn should_flatten_device_with_status_in_json() {
+        let with_status = DeviceWithStatus {
+            device: valid_device(),
+            status: DeviceStatus::Unavailable,
+        };
+        let json = serde_json::to_value(&with_status).unwrap();
+        assert_eq!(json["name"], "Hue Bridge");
+        assert_eq!(json["status"], "unavailable");
+    }
 }
@@ -0,0 +1,325 @@
+//! Energy usage — electrical consumption accumulated per entity and hour.
+//!
+//! Power sensors report instantaneous watts and energy meters a growing
+//! counter; both are turned into the watt-hours consumed between two
+//! readings, stored hour by hour and summed per day, week or month on
+//! demand.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, DurationRound, Months, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::id::EntityId;
+use crate::time::Timestamp;
+
+/// Watt-hours consumed by an entity during one hour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnergyUsage {
+    pub entity_id: EntityId,
+    /// Start of the hour.
+    pub hour: Timestamp,
+    pub energy_wh: f64,
+}
+
+impl EnergyUsage {
+    /// Split `energy_wh`, consumed between `from` and `to`, over the hours
+    /// the interval spans, in proportion to the time spent in each.
+    #[must_use]
+    pub fn spread(
+        entity_id: EntityId,
+        from: Timestamp,
+        to: Timestamp,
+        energy_wh: f64,
+    ) -> Vec<Self> {
+        if to <= from {
+            return vec![Self {
+                entity_id,
+                hour: start_of_hour(to),
+                energy_wh,
+            }];
+        }
+        let total = (to - from).as_seconds_f64();
+        let mut usage = Vec::new();
+        let mut hour = start_of_hour(from);
+        while hour < to {
+            let next = hour + TimeDelta::hours(1);
+            let overlap = (next.min(to) - hour.max(from)).as_seconds_f64();
+            usage.push(Self {
+                entity_id,
+                hour,
+                energy_wh: energy_wh * overlap / total,
+            });
+            hour = next;
+        }
+        usage
+    }
+}
+
+/// The beginning of the hour `at` falls in.
+#[must_use]
+pub fn start_of_hour(at: Timestamp) -> Timestamp {
+    at.duration_trunc(TimeDelta::hours(1)).unwrap_or(at)
+}
+
+/// Length of the buckets an [`EnergyReport`] sums the consumption over.
+/// Days, weeks (starting on Monday) and months follow the UTC calendar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnergyPeriod {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl EnergyPeriod {
+    /// The `snake_case` name used in JSON and query strings.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// The beginning of the period `at` falls in.
+    #[must_use]
+    pub fn start_of(self, at: Timestamp) -> Timestamp {
+        let midnight = at.with_time(NaiveTime::MIN).single().unwrap_or(at);
+        match self {
+            Self::Hour => start_of_hour(at),
+            Self::Day => midnight,
+            Self::Week => {
+                midnight - TimeDelta::days(i64::from(at.weekday().num_days_from_monday()))
+            }
+            Self::Month => midnight.with_day(1).unwrap_or(midnight),
+        }
+    }
+
+    /// The beginning of the period `count` periods before the one `at`
+    /// falls in.
+    #[must_use]
+    pub fn periods_before(self, at: Timestamp, count: u32) -> Timestamp {
+        let start = self.start_of(at);
+        match self {
+            Self::Hour => start - TimeDelta::hours(i64::from(count)),
+            Self::Day => start - TimeDelta::days(i64::from(count)),
+            Self::Week => start - TimeDelta::weeks(i64::from(count)),
+            Self::Month => start
+                .checked_sub_months(Months::new(count))
+                .unwrap_or(start),
+        }
+    }
+
+    /// How many periods a report covers when no range is given: a day of
+    /// hours, a month of days, a quarter of weeks or a year of months.
+    #[must_use]
+    pub fn default_count(self) -> u32 {
+        match self {
+            Self::Hour => 24,
+            Self::Day => 30,
+            Self::Week => 13,
+            Self::Month => 12,
+        }
+    }
+}
+
+impl std::fmt::Display for EnergyPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Energy consumed during one period of an [`EnergyReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnergyBucket {
+    /// Start of the period.
+    pub start: Timestamp,
+    pub energy_kwh: f64,
+}
+
+/// Consumption of an entity between two instants, summed per period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnergyReport {
+    pub entity_id: EntityId,
+    pub period: EnergyPeriod,
+    pub from: Timestamp,
+    pub to: Timestamp,
+    /// Sum of the buckets.
+    pub total_kwh: f64,
+    /// One bucket per period with some consumption, oldest first. Periods
+    /// without any are omitted.
+    pub buckets: Vec<EnergyBucket>,
+}
+
+impl EnergyReport {
+    /// Sum the hourly `usage` of `entity_id` into buckets of `period`.
+    #[must_use]
+    pub fn aggregate(
+        entity_id: EntityId,
+        period: EnergyPeriod,
+        from: Timestamp,
+        to: Timestamp,
+        usage: &[EnergyUsage],
+    ) -> Self {
+        let mut sums: BTreeMap<Timestamp, f64> = BTreeMap::new();
+        for hourly in usage {
+            *sums.entry(period.start_of(hourly.hour)).or_default() += hourly.energy_wh;
+        }
+        let buckets: Vec<EnergyBucket> = sums
+            .into_iter()
+            .map(|(start, energy_wh)| EnergyBucket {
+                start,
+                energy_kwh: energy_wh / 1000.0,
+            })
+            .collect();
+        Self {
+            entity_id,
+            period,
+            from,
+            to,
+            total_kwh: buckets.iter().map(|bucket| bucket.energy_kwh).sum(),
+            buckets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn should_keep_usage_within_one_hour_in_a_single_bucket() {
+        let id = EntityId::new();
+        let usage = EnergyUsage::spread(
+            id,
+            at("2026-03-02T10:05:00Z"),
+            at("2026-03-02T10:35:00Z"),
+            50.0,
+        );
+        assert_eq!(
+            usage,
+            vec![EnergyUsage {
+                entity_id: id,
+                hour: at("2026-03-02T10:00:00Z"),
+                energy_wh: 50.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_spread_usage_over_hours_in_proportion_to_time() {
+        let usage = EnergyUsage::spread(
+            EntityId::new(),
+            at("2026-03-02T10:30:00Z"),
+            at("2026-03-02T12:00:00Z"),
+            300.0,
+        );
+        let split: Vec<(Timestamp, f64)> = usage
+            .iter()
+            .map(|hourly| (hourly.hour, hourly.energy_wh))
+            .collect();
+        assert_eq!(
+            split,
+            vec![
+                (at("2026-03-02T10:00:00Z"), 100.0),
+                (at("2026-03-02T11:00:00Z"), 200.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_put_instant_usage_in_the_hour_of_the_reading() {
+        let instant = at("2026-03-02T10:59:59Z");
+        let usage = EnergyUsage::spread(EntityId::new(), instant, instant, 12.0);
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].hour, at("2026-03-02T10:00:00Z"));
+    }
+
+    #[test]
+    fn should_start_periods_on_utc_calendar_boundaries() {
+        // 2026-03-04 is a Wednesday.
+        let instant = at("2026-03-04T17:42:10Z");
+        assert_eq!(
+            EnergyPeriod::Hour.start_of(instant),
+            at("2026-03-04T17:00:00Z")
+        );
+        assert_eq!(
+            EnergyPeriod::Day.start_of(instant),
+            at("2026-03-04T00:00:00Z")
+        );
+        assert_eq!(
+            EnergyPeriod::Week.start_of(instant),
+            at("2026-03-02T00:00:00Z")
+        );
+        assert_eq!(
+            EnergyPeriod::Month.start_of(instant),
+            at("2026-03-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn should_step_back_whole_periods() {
+        let instant = at("2026-03-04T17:42:10Z");
+        assert_eq!(
+            EnergyPeriod::Day.periods_before(instant, 2),
+            at("2026-03-02T00:00:00Z")
+        );
+        assert_eq!(
+            EnergyPeriod::Month.periods_before(instant, 3),
+            at("2025-12-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn should_sum_hourly_usage_per_day() {
+        let id = EntityId::new();
+        let hourly = |hour: &str, energy_wh: f64| EnergyUsage {
+            entity_id: id,
+            hour: at(hour),
+            energy_wh,
+        };
+        let usage = [
+            hourly("2026-03-02T08:00:00Z", 250.0),
+            hourly("2026-03-02T21:00:00Z", 750.0),
+            hourly("2026-03-04T06:00:00Z", 500.0),
+        ];
+
+        let report = EnergyReport::aggregate(
+            id,
+            EnergyPeriod::Day,
+            at("2026-03-01T00:00:00Z"),
+            at("2026-03-05T00:00:00Z"),
+            &usage,
+        );
+
+        assert_eq!(
+            report.buckets,
+            vec![
+                EnergyBucket {
+                    start: at("2026-03-02T00:00:00Z"),
+                    energy_kwh: 1.0,
+                },
+                EnergyBucket {
+                    start: at("2026-03-04T00:00:00Z"),
+                    energy_kwh: 0.5,
+                },
+            ]
+        );
+        assert!((report.total_kwh - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn should_serialize_period_as_snake_case() {
+        let json = serde_json::to_string(&EnergyPeriod::Week).unwrap();
+        assert_eq!(json, format!("\"{}\"", EnergyPeriod::Week));
+    }
+}
@@ -3,20 +3,44 @@
 //! An entity represents a single observable/controllable aspect of a device
 //! (e.g., a light's on/off state, a temperature sensor's reading).
 
+mod attribute_meta;
 mod attribute_value;
+mod device_class;
 mod state;
 
+pub use attribute_meta::AttributeMeta;
 pub use attribute_value::AttributeValue;
+pub use device_class::DeviceClass;
 pub use state::EntityState;
 
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::error::{MiniHubError, ValidationError};
+use crate::error::{FieldError, MiniHubError, ValidationError};
 use crate::id::{DeviceId, EntityId};
 use crate::time::Timestamp;
 
+/// Derive an `entity_id` in `domain` from a display `name`, e.g.
+/// `("light", "Living room")` → `"light.living_room"`.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::EmptyName`] when `name` has no letter or
+/// digit to build the id from.
+pub fn entity_id_from_name(domain: &str, name: &str) -> Result<String, ValidationError> {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if slug.is_empty() {
+        return Err(ValidationError::EmptyName);
+    }
+    Ok(format!("{domain}.{slug}"))
+}
+
 /// An observable/controllable data point in the system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
@@ -26,11 +50,29 @@ pub struct Entity {
     pub entity_id: String,
     pub friendly_name: String,
     pub state: EntityState,
+    /// What the entity measures or detects, used to pick icons.
+    #[serde(default)]
+    pub device_class: Option<DeviceClass>,
+    /// Unit of the entity's primary value, e.g. `"°C"`.
+    #[serde(default)]
+    pub unit_of_measurement: Option<String>,
     pub attributes: HashMap<String, AttributeValue>,
+    /// Display and validation hints, keyed by attribute name.
+    #[serde(default)]
+    pub attribute_meta: HashMap<String, AttributeMeta>,
     /// Hardware MAC address, if the entity is backed by a BLE/network device.
     pub mac_address: Option<String>,
     pub last_changed: Timestamp,
     pub last_updated: Timestamp,
+    /// Revision of the entity, bumped by every stored update and used for
+    /// optimistic locking. Starts at 1.
+    #[serde(default = "first_version")]
+    pub version: u32,
+}
+
+/// Version of an entity that was never updated.
+const fn first_version() -> u32 {
+    1
 }
 
 impl Entity {
@@ -40,6 +82,36 @@ impl Entity {
         EntityBuilder::default()
     }
 
+    /// The `entity_id` prefix, e.g. `"light"` for `"light.kitchen"`.
+    #[must_use]
+    pub fn domain(&self) -> &str {
+        self.entity_id
+            .split_once('.')
+            .map_or(self.entity_id.as_str(), |(domain, _)| domain)
+    }
+
+    /// Check that `state` is one the entity's domain and device class allow.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidStateForDomain`] listing the allowed
+    /// states otherwise.
+    pub fn check_state(&self, state: &EntityState) -> Result<(), ValidationError> {
+        let allowed = EntityState::allowed_kinds(self.domain(), self.device_class);
+        if allowed.contains(&state.kind()) {
+            return Ok(());
+        }
+        let domain = match self.device_class {
+            Some(class) => format!("{} {class}", self.domain()),
+            None => self.domain().to_string(),
+        };
+        Err(ValidationError::InvalidStateForDomain {
+            domain,
+            state: state.to_string(),
+            allowed: allowed.join(", "),
+        })
+    }
+
     /// Update the state, bumping `last_changed` only when the value differs.
     pub fn update_state(&mut self, new_state: EntityState, timestamp: Timestamp) {
         if self.state != new_state {
@@ -60,20 +132,101 @@ impl Entity {
         self.attributes.get(key)
     }
 
+    /// Look up the metadata of an attribute by key.
+    #[must_use]
+    pub fn get_attribute_meta(&self, key: &str) -> Option<&AttributeMeta> {
+        self.attribute_meta.get(key)
+    }
+
+    /// Return a copy whose float attributes are rounded to the precision
+    /// declared in their metadata.
+    #[must_use]
+    pub fn rounded(mut self) -> Self {
+        for (key, value) in &mut self.attributes {
+            if let Some(meta) = self.attribute_meta.get(key) {
+                *value = meta.round(value);
+            }
+        }
+        self
+    }
+
+    /// Range-check numeric service-call data against attribute metadata.
+    ///
+    /// Keys without metadata and non-numeric values are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when a value falls outside the
+    /// `min`/`max` bounds of its attribute.
+    pub fn validate_service_data(&self, data: &serde_json::Value) -> Result<(), MiniHubError> {
+        let Some(fields) = data.as_object() else {
+            return Ok(());
+        };
+        for (key, value) in fields {
+            if let (Some(meta), Some(number)) = (self.attribute_meta.get(key), value.as_f64()) {
+                meta.check_range(key, number)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the `entity_id`, returning the previous one.
+    ///
+    /// The new id must keep the `domain.object` shape and the same domain,
+    /// since services and input helpers dispatch on that prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when `new_entity_id` is empty,
+    /// malformed or belongs to another domain.
+    pub fn rename(&mut self, new_entity_id: impl Into<String>) -> Result<String, MiniHubError> {
+        let new_entity_id = new_entity_id.into();
+        if new_entity_id.is_empty() {
+            return Err(ValidationError::EmptyEntityId.into());
+        }
+        let Some((domain, object)) = new_entity_id.split_once('.') else {
+            return Err(ValidationError::InvalidEntityId(new_entity_id).into());
+        };
+        if object.is_empty() || new_entity_id.contains(char::is_whitespace) {
+            return Err(ValidationError::InvalidEntityId(new_entity_id).into());
+        }
+        if self.entity_id.split_once('.').map(|(current, _)| current) != Some(domain) {
+            return Err(ValidationError::InvalidEntityId(new_entity_id).into());
+        }
+        Ok(std::mem::replace(&mut self.entity_id, new_entity_id))
+    }
+
     /// Check domain invariants.
     ///
     /// # Errors
     ///
     /// Returns [`MiniHubError::Validation`] when `entity_id` or
-    /// `friendly_name` is empty.
+    /// `friendly_name` is empty, or the state is not allowed for the domain.
     pub fn validate(&self) -> Result<(), MiniHubError> {
+        match self.field_errors().into_iter().next() {
+            Some(FieldError { error, .. }) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Every invariant [`validate`](Self::validate) checks that does not
+    /// hold, each with the field it concerns.
+    #[must_use]
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
         if self.entity_id.is_empty() {
-            return Err(ValidationError::EmptyEntityId.into());
+            errors.push(FieldError::new("entity_id", ValidationError::EmptyEntityId));
         }
         if self.friendly_name.is_empty() {
-            return Err(ValidationError::EmptyFriendlyName.into());
+            errors.push(FieldError::new(
+                "friendly_name",
+                ValidationError::EmptyFriendlyName,
+            ));
         }
-        Ok(())
+        if let Err(err) = self.check_state(&self.state) {
+            errors.push(FieldError::new("state", err));
+        }
+        errors
     }
 }
 
@@ -85,8 +238,12 @@ pub struct EntityBuilder {
     entity_id: Option<String>,
     friendly_name: Option<String>,
     state: Option<EntityState>,
+    device_class: Option<DeviceClass>,
+    unit_of_measurement: Option<String>,
     attributes: HashMap<String, AttributeValue>,
+    attribute_meta: HashMap<String, AttributeMeta>,
     mac_address: Option<String>,
+    version: Option<u32>,
 }
 
 impl EntityBuilder {
@@ -120,12 +277,31 @@ impl EntityBuilder {
         self
     }
 
+    #[must_use]
+    pub fn device_class(mut self, device_class: DeviceClass) -> Self {
+        self.device_class = Some(device_class);
+        self
+    }
+
+    #[must_use]
+    pub fn unit_of_measurement(mut self, unit: impl Into<String>) -> Self {
+        self.unit_of_measurement = Some(unit.into());
+        self
+    }
+
     #[must_use]
     pub fn attribute(mut self, key: impl Into<String>, value: AttributeValue) -> Self {
         self.attributes.insert(key.into(), value);
         self
     }
 
+    /// Attach display and validation hints to an attribute.
+    #[must_use]
+    pub fn attribute_meta(mut self, key: impl Into<String>, meta: AttributeMeta) -> Self {
+        self.attribute_meta.insert(key.into(), meta);
+        self
+    }
+
     /// Set the hardware MAC address.
     #[must_use]
     pub fn mac_address(mut self, mac: impl Into<String>) -> Self {
@@ -133,26 +309,48 @@ impl EntityBuilder {
         self
     }
 
+    /// Set the revision the entity was read at; defaults to 1.
+    #[must_use]
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
     /// Consume the builder, validate, and return an [`Entity`].
     ///
     /// # Errors
     ///
     /// Returns [`MiniHubError::Validation`] if required fields are missing or empty.
     pub fn build(self) -> Result<Entity, MiniHubError> {
+        let entity = self.assemble();
+        entity.validate()?;
+        Ok(entity)
+    }
+
+    /// Consume the builder and list every field failing validation, without
+    /// building anything.
+    #[must_use]
+    pub fn field_errors(self) -> Vec<FieldError> {
+        self.assemble().field_errors()
+    }
+
+    fn assemble(self) -> Entity {
         let now = crate::time::now();
-        let entity = Entity {
+        Entity {
             id: self.id.unwrap_or_default(),
             device_id: self.device_id.unwrap_or_default(),
             entity_id: self.entity_id.unwrap_or_default(),
             friendly_name: self.friendly_name.unwrap_or_default(),
             state: self.state.unwrap_or_default(),
+            device_class: self.device_class,
+            unit_of_measurement: self.unit_of_measurement,
             attributes: self.attributes,
+            attribute_meta: self.attribute_meta,
             mac_address: self.mac_address,
             last_changed: now,
             last_updated: now,
-        };
-        entity.validate()?;
-        Ok(entity)
+            version: self.version.unwrap_or_else(first_version),
+        }
     }
 }
 
@@ -188,6 +386,20 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn should_list_every_invalid_field_when_checking_builder() {
+        let errors = Entity::builder().state(EntityState::On).field_errors();
+        let fields: Vec<&str> = errors.iter().map(|err| err.field.as_str()).collect();
+        assert_eq!(fields, ["entity_id", "friendly_name"]);
+        assert!(
+            Entity::builder()
+                .entity_id("sensor.temp")
+                .friendly_name("Temp")
+                .field_errors()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn should_return_validation_error_when_friendly_name_is_empty() {
         let result = Entity::builder().entity_id("sensor.temp").build();
@@ -264,4 +476,150 @@ mod tests {
             Some(&AttributeValue::String("°C".to_string()))
         );
     }
+
+    fn thermometer() -> Entity {
+        Entity::builder()
+            .entity_id("sensor.temp")
+            .friendly_name("Temperature")
+            .attribute("temperature", AttributeValue::Float(21.456))
+            .attribute("humidity", AttributeValue::Float(45.25))
+            .attribute_meta(
+                "temperature",
+                AttributeMeta::default()
+                    .with_precision(1)
+                    .with_range(-40.0, 85.0),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_round_attributes_with_precision_metadata() {
+        let entity = thermometer().rounded();
+
+        assert_eq!(
+            entity.get_attribute("temperature"),
+            Some(&AttributeValue::Float(21.5))
+        );
+        assert_eq!(
+            entity.get_attribute("humidity"),
+            Some(&AttributeValue::Float(45.25))
+        );
+    }
+
+    #[test]
+    fn should_accept_service_data_within_range() {
+        let entity = thermometer();
+        let data = serde_json::json!({ "temperature": 20, "mode": "heat" });
+        assert!(entity.validate_service_data(&data).is_ok());
+    }
+
+    #[test]
+    fn should_reject_service_data_out_of_range() {
+        let entity = thermometer();
+        let data = serde_json::json!({ "temperature": 120.0 });
+        assert!(matches!(
+            entity.validate_service_data(&data),
+            Err(MiniHubError::Validation(
+                ValidationError::AttributeOutOfRange { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_reject_off_when_sensor_measures_a_value() {
+        let result = Entity::builder()
+            .entity_id("sensor.temp")
+            .friendly_name("Temperature")
+            .device_class(DeviceClass::Temperature)
+            .state(EntityState::Off)
+            .build();
+
+        let Err(MiniHubError::Validation(ValidationError::InvalidStateForDomain {
+            domain,
+            state,
+            allowed,
+        })) = result
+        else {
+            panic!("expected InvalidStateForDomain, got {result:?}");
+        };
+        assert_eq!(domain, "sensor temperature");
+        assert_eq!(state, "off");
+        assert_eq!(allowed, "on, numeric, unknown, unavailable");
+    }
+
+    #[test]
+    fn should_accept_off_when_domain_is_two_state() {
+        let entity = Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .state(EntityState::Off)
+            .build()
+            .unwrap();
+
+        assert_eq!(entity.domain(), "light");
+        assert!(entity.check_state(&EntityState::On).is_ok());
+    }
+
+    #[test]
+    fn should_default_attribute_meta_when_missing_from_json() {
+        let mut json = serde_json::to_value(valid_entity()).unwrap();
+        json.as_object_mut().unwrap().remove("attribute_meta");
+        let parsed: Entity = serde_json::from_value(json).unwrap();
+        assert!(parsed.attribute_meta.is_empty());
+    }
+
+    #[test]
+    fn should_default_version_when_missing_from_json() {
+        let mut json = serde_json::to_value(valid_entity()).unwrap();
+        json.as_object_mut().unwrap().remove("version");
+        let parsed: Entity = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.version, 1);
+    }
+
+    #[test]
+    fn should_build_entity_with_device_class_and_unit() {
+        let entity = Entity::builder()
+            .entity_id("sensor.temp")
+            .friendly_name("Temperature")
+            .device_class(DeviceClass::Temperature)
+            .unit_of_measurement("°C")
+            .build()
+            .unwrap();
+
+        assert_eq!(entity.device_class, Some(DeviceClass::Temperature));
+        assert_eq!(entity.unit_of_measurement.as_deref(), Some("°C"));
+    }
+
+    #[test]
+    fn should_return_previous_entity_id_when_renamed() {
+        let mut entity = valid_entity();
+
+        let previous = entity.rename("light.lounge").unwrap();
+
+        assert_eq!(previous, "light.living_room");
+        assert_eq!(entity.entity_id, "light.lounge");
+    }
+
+    #[test]
+    fn should_reject_rename_when_entity_id_is_malformed_or_changes_domain() {
+        let mut entity = valid_entity();
+
+        for invalid in ["lounge", "light.", "light.lounge lamp", "switch.lounge"] {
+            assert!(
+                matches!(
+                    entity.rename(invalid),
+                    Err(MiniHubError::Validation(ValidationError::InvalidEntityId(
+                        _
+                    )))
+                ),
+                "{invalid} should be rejected"
+            );
+        }
+        assert!(matches!(
+            entity.rename(""),
+            Err(MiniHubError::Validation(ValidationError::EmptyEntityId))
+        ));
+        assert_eq!(entity.entity_id, "light.living_room");
+    }
 }
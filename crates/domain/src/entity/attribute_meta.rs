@@ -0,0 +1,134 @@
+//! Attribute metadata — rendering and validation hints for attribute values.
+
+use serde::{Deserialize, Serialize};
+
+use super::AttributeValue;
+use crate::error::ValidationError;
+
+/// Hints describing how a single attribute should be displayed and bounded.
+///
+/// Set by integrations at discovery time. The API uses `precision` to round
+/// values, the service-call validator uses `min`/`max` to range-check
+/// inputs, and dashboards use all of it to render sliders and gauges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttributeMeta {
+    /// Number of decimal places to keep when presenting float values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u32>,
+    /// Smallest accepted value (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Largest accepted value (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Unit shown next to the value, e.g. `"°C"` or `"%"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_unit: Option<String>,
+}
+
+impl AttributeMeta {
+    #[must_use]
+    pub fn with_precision(mut self, precision: u32) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    #[must_use]
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn with_display_unit(mut self, unit: impl Into<String>) -> Self {
+        self.display_unit = Some(unit.into());
+        self
+    }
+
+    /// Round a float value to `precision` decimal places.
+    ///
+    /// Non-float values, and all values when no precision is set, are
+    /// returned unchanged.
+    #[must_use]
+    pub fn round(&self, value: &AttributeValue) -> AttributeValue {
+        match (value, self.precision) {
+            (AttributeValue::Float(v), Some(precision)) => {
+                let factor = 10_f64.powi(i32::try_from(precision).unwrap_or(i32::MAX));
+                AttributeValue::Float((v * factor).round() / factor)
+            }
+            _ => value.clone(),
+        }
+    }
+
+    /// Check that `value` lies within `min..=max`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::AttributeOutOfRange`] when `value` is below
+    /// `min` or above `max`.
+    pub fn check_range(&self, key: &str, value: f64) -> Result<(), ValidationError> {
+        let below = self.min.is_some_and(|min| value < min);
+        let above = self.max.is_some_and(|max| value > max);
+        if below || above {
+            return Err(ValidationError::AttributeOutOfRange {
+                key: key.to_string(),
+                value,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_float_to_precision() {
+        let meta = AttributeMeta::default().with_precision(1);
+        assert_eq!(
+            meta.round(&AttributeValue::Float(21.456)),
+            AttributeValue::Float(21.5)
+        );
+    }
+
+    #[test]
+    fn should_leave_value_unchanged_when_no_precision() {
+        let meta = AttributeMeta::default();
+        assert_eq!(
+            meta.round(&AttributeValue::Float(21.456)),
+            AttributeValue::Float(21.456)
+        );
+    }
+
+    #[test]
+    fn should_leave_int_unchanged_when_rounding() {
+        let meta = AttributeMeta::default().with_precision(0);
+        assert_eq!(meta.round(&AttributeValue::Int(7)), AttributeValue::Int(7));
+    }
+
+    #[test]
+    fn should_accept_value_within_range() {
+        let meta = AttributeMeta::default().with_range(0.0, 100.0);
+        assert!(meta.check_range("moisture", 0.0).is_ok());
+        assert!(meta.check_range("moisture", 100.0).is_ok());
+    }
+
+    #[test]
+    fn should_reject_value_outside_range() {
+        let meta = AttributeMeta::default().with_range(0.0, 100.0);
+        assert!(matches!(
+            meta.check_range("moisture", 101.0),
+            Err(ValidationError::AttributeOutOfRange { key, .. }) if key == "moisture"
+        ));
+        assert!(meta.check_range("moisture", -1.0).is_err());
+    }
+
+    #[test]
+    fn should_omit_unset_fields_when_serialized() {
+        let meta = AttributeMeta::default().with_display_unit("%");
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json, serde_json::json!({ "display_unit": "%" }));
+    }
+}
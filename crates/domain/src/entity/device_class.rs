@@ -0,0 +1,86 @@
+//! Device class — what kind of quantity or sensor an entity represents.
+
+use serde::{Deserialize, Serialize};
+
+/// Classification of an entity, used to pick icons and units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceClass {
+    Temperature,
+    Humidity,
+    Illuminance,
+    Moisture,
+    Conductivity,
+    Battery,
+    Voltage,
+    Power,
+    Energy,
+    Pressure,
+    Motion,
+    Occupancy,
+    Door,
+    Window,
+}
+
+impl DeviceClass {
+    /// The `snake_case` name used in JSON and storage.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Humidity => "humidity",
+            Self::Illuminance => "illuminance",
+            Self::Moisture => "moisture",
+            Self::Conductivity => "conductivity",
+            Self::Battery => "battery",
+            Self::Voltage => "voltage",
+            Self::Power => "power",
+            Self::Energy => "energy",
+            Self::Pressure => "pressure",
+            Self::Motion => "motion",
+            Self::Occupancy => "occupancy",
+            Self::Door => "door",
+            Self::Window => "window",
+        }
+    }
+
+    /// Whether the class describes a two-state (on/off) sensor rather than a
+    /// measurement.
+    #[must_use]
+    pub fn is_binary(self) -> bool {
+        matches!(
+            self,
+            Self::Motion | Self::Occupancy | Self::Door | Self::Window
+        )
+    }
+}
+
+impl std::fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_snake_case_name() {
+        assert_eq!(DeviceClass::Temperature.to_string(), "temperature");
+        assert_eq!(DeviceClass::Illuminance.to_string(), "illuminance");
+    }
+
+    #[test]
+    fn should_serialize_as_display_name() {
+        let json = serde_json::to_string(&DeviceClass::Door).unwrap();
+        assert_eq!(json, format!("\"{}\"", DeviceClass::Door));
+    }
+
+    #[test]
+    fn should_report_binary_classes() {
+        assert!(DeviceClass::Motion.is_binary());
+        assert!(DeviceClass::Door.is_binary());
+        assert!(!DeviceClass::Temperature.is_binary());
+    }
+}
@@ -1,37 +1,180 @@
 //! Entity state — the current operational state of an entity.
 
-use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::str::FromStr;
 
-/// Discrete operational state of an entity.
-#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::DeviceClass;
+
+/// Current state of an entity.
+///
+/// Switch-like entities use the discrete variants, while sensors and input
+/// helpers report their reading as [`Numeric`](Self::Numeric) or
+/// [`Text`](Self::Text). On the wire the discrete variants are their
+/// lowercase names, numeric states are JSON numbers and text states are plain
+/// strings, so payloads written before the value variants existed still
+/// parse.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum EntityState {
     On,
     Off,
     #[default]
     Unknown,
     Unavailable,
+    /// A measurement, e.g. a temperature reading.
+    Numeric(f64),
+    /// A free-form value, e.g. the location of a `device_tracker`.
+    Text(String),
 }
 
 impl EntityState {
+    /// Kinds allowed for two-state entities such as lights and switches.
+    const SWITCH: &[&str] = &["on", "off", "unknown", "unavailable"];
+
+    /// Kinds allowed for entities reporting a measurement.
+    const MEASUREMENT: &[&str] = &["on", "numeric", "unknown", "unavailable"];
+
+    /// Kinds allowed for entities reporting a named value, such as a place.
+    const SELECTION: &[&str] = &["on", "text", "unknown", "unavailable"];
+
+    /// Kinds allowed for generic sensors, which may report anything.
+    const ANY: &[&str] = &["on", "off", "unknown", "unavailable", "numeric", "text"];
+
+    /// The kind of state, as listed by [`allowed_kinds`](Self::allowed_kinds):
+    /// the lowercase variant name.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::On => "on",
+            Self::Off => "off",
+            Self::Unknown => "unknown",
+            Self::Unavailable => "unavailable",
+            Self::Numeric(_) => "numeric",
+            Self::Text(_) => "text",
+        }
+    }
+
+    /// The state kinds an entity in `domain` (its `entity_id` prefix) with
+    /// `device_class` may take.
+    ///
+    /// Measurement sensors and `input_number` helpers report a number, or
+    /// `on` when the reading still lives in their attributes, so `off` has no
+    /// meaning for them; `device_tracker` entities report their location
+    /// (`home`, `not_home`) and the `sun` entity its position
+    /// (`above_horizon`, `below_horizon`) as text, and `input_select` helpers,
+    /// which keep their option in an attribute and report `on`, may too.
+    /// Sensors without a class accept anything, and every other domain is
+    /// two-state.
+    #[must_use]
+    pub fn allowed_kinds(
+        domain: &str,
+        device_class: Option<DeviceClass>,
+    ) -> &'static [&'static str] {
+        let measurement = device_class.is_some_and(|class| !class.is_binary());
+        match domain {
+            _ if measurement => Self::MEASUREMENT,
+            "input_number" => Self::MEASUREMENT,
+            "input_select" | "device_tracker" | "sun" => Self::SELECTION,
+            "sensor" => Self::ANY,
+            _ => Self::SWITCH,
+        }
+    }
+
+    /// Rebuild a state from its [`kind`](Self::kind) and its
+    /// [`Display`](std::fmt::Display) form, so text reading like a number
+    /// stays text; `None` for an unknown kind or a malformed number.
+    #[must_use]
+    pub fn from_kind(kind: &str, value: &str) -> Option<Self> {
+        match kind {
+            "on" => Some(Self::On),
+            "off" => Some(Self::Off),
+            "unknown" => Some(Self::Unknown),
+            "unavailable" => Some(Self::Unavailable),
+            "numeric" => value.parse().ok().map(Self::Numeric),
+            "text" => Some(Self::Text(value.to_string())),
+            _ => None,
+        }
+    }
+
     /// Whether the entity is reachable (anything but [`Unavailable`](Self::Unavailable)).
     #[must_use]
     pub fn is_available(&self) -> bool {
         !matches!(self, Self::Unavailable)
     }
+
+    /// The reading of a [`Numeric`](Self::Numeric) state.
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Numeric(value) => Some(*value),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for EntityState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::On => f.write_str("on"),
-            Self::Off => f.write_str("off"),
-            Self::Unknown => f.write_str("unknown"),
-            Self::Unavailable => f.write_str("unavailable"),
+            Self::Numeric(value) => value.fmt(f),
+            Self::Text(text) => f.write_str(text),
+            other => f.write_str(other.kind()),
+        }
+    }
+}
+
+/// Parse the [`Display`](std::fmt::Display) form back: the discrete variant
+/// names first, then numbers, anything else being text.
+impl FromStr for EntityState {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "on" => Self::On,
+            "off" => Self::Off,
+            "unknown" => Self::Unknown,
+            "unavailable" => Self::Unavailable,
+            _ => s
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map_or_else(|| Self::Text(s.to_string()), Self::Numeric),
+        })
+    }
+}
+
+impl Serialize for EntityState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Numeric(value) => serializer.serialize_f64(*value),
+            Self::Text(text) => serializer.serialize_str(text),
+            other => serializer.serialize_str(other.kind()),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for EntityState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            Text(String),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Self::Numeric(value),
+            Repr::Text(text) => match text.as_str() {
+                "on" => Self::On,
+                "off" => Self::Off,
+                "unknown" => Self::Unknown,
+                "unavailable" => Self::Unavailable,
+                _ => Self::Text(text),
+            },
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,12 +204,92 @@ mod tests {
         assert_eq!(EntityState::default(), EntityState::Unknown);
     }
 
+    #[test]
+    fn should_rebuild_state_from_its_kind() {
+        for state in [
+            EntityState::Off,
+            EntityState::Numeric(1.5),
+            EntityState::Text("10".to_string()),
+        ] {
+            assert_eq!(
+                EntityState::from_kind(state.kind(), &state.to_string()),
+                Some(state)
+            );
+        }
+        assert_eq!(EntityState::from_kind("numeric", "ten"), None);
+        assert_eq!(EntityState::from_kind("dimmed", "50"), None);
+    }
+
     #[test]
     fn should_display_lowercase_variant_name() {
         assert_eq!(EntityState::On.to_string(), "on");
         assert_eq!(EntityState::Off.to_string(), "off");
     }
 
+    #[test]
+    fn should_allow_only_switch_states_when_domain_is_two_state() {
+        assert_eq!(
+            EntityState::allowed_kinds("light", None),
+            EntityState::SWITCH
+        );
+        assert_eq!(
+            EntityState::allowed_kinds("binary_sensor", Some(DeviceClass::Door)),
+            EntityState::SWITCH
+        );
+    }
+
+    #[test]
+    fn should_reject_off_when_entity_holds_a_value() {
+        let measurement = EntityState::allowed_kinds("sensor", Some(DeviceClass::Temperature));
+        assert!(!measurement.contains(&EntityState::Off.kind()));
+        assert!(measurement.contains(&EntityState::Numeric(21.5).kind()));
+        let selection = EntityState::allowed_kinds("input_select", None);
+        assert!(selection.contains(&EntityState::Text("away".to_string()).kind()));
+        assert!(!EntityState::allowed_kinds("input_number", None).contains(&"off"));
+    }
+
+    #[test]
+    fn should_allow_text_location_when_domain_is_device_tracker() {
+        let tracker = EntityState::allowed_kinds("device_tracker", None);
+        assert!(tracker.contains(&EntityState::Text("not_home".to_string()).kind()));
+        assert!(!tracker.contains(&EntityState::Off.kind()));
+    }
+
+    #[test]
+    fn should_serialize_value_states_as_plain_json() {
+        assert_eq!(
+            serde_json::to_string(&EntityState::Numeric(21.5)).unwrap(),
+            "21.5"
+        );
+        assert_eq!(
+            serde_json::to_string(&EntityState::Text("away".to_string())).unwrap(),
+            "\"away\""
+        );
+        let parsed: Vec<EntityState> = serde_json::from_str(r#"[21.5, "away", "off"]"#).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                EntityState::Numeric(21.5),
+                EntityState::Text("away".to_string()),
+                EntityState::Off
+            ]
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_through_display_and_from_str() {
+        for state in [
+            EntityState::On,
+            EntityState::Unavailable,
+            EntityState::Numeric(-3.25),
+            EntityState::Numeric(20.0),
+            EntityState::Text("heat".to_string()),
+        ] {
+            let parsed: EntityState = state.to_string().parse().unwrap();
+            assert_eq!(parsed, state);
+        }
+    }
+
     #[test]
     fn should_roundtrip_through_serde_json() {
         let state = EntityState::On;
@@ -1,7 +1,8 @@
 //! Entity history — time-series records of entity state and attribute changes.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use chrono::TimeDelta;
 use serde::{Deserialize, Serialize};
 
 use crate::entity::{AttributeValue, EntityState};
@@ -136,6 +137,113 @@ impl EntityHistoryBuilder {
     }
 }
 
+/// Aggregate of the samples of a [`HistorySeries`] falling in one bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryBucket {
+    /// Start of the bucket.
+    pub start: Timestamp,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Number of samples aggregated, `min` and `max` only differing from
+    /// `mean` when above one.
+    pub count: u32,
+}
+
+/// Numeric readings of the state, or of one attribute, of an entity
+/// downsampled into buckets of equal length.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistorySeries {
+    /// [`HistorySeries::STATE`] for the state, the attribute key otherwise.
+    pub name: String,
+    /// One bucket per period with some samples, oldest first. Periods
+    /// without any are omitted.
+    pub buckets: Vec<HistoryBucket>,
+}
+
+impl HistorySeries {
+    /// Name of the series of numeric states.
+    pub const STATE: &'static str = "state";
+
+    /// Names of the series with a numeric sample in `records`: the state
+    /// first, then the attributes in alphabetical order.
+    #[must_use]
+    pub fn names(records: &[EntityHistory]) -> Vec<String> {
+        let mut attributes: Vec<&String> = records
+            .iter()
+            .flat_map(|record| &record.attributes)
+            .filter(|(_, value)| numeric(value).is_some())
+            .map(|(key, _)| key)
+            .collect();
+        attributes.sort();
+        attributes.dedup();
+        let state = records
+            .iter()
+            .any(|record| record.state.as_f64().is_some())
+            .then(|| Self::STATE.to_string());
+        state
+            .into_iter()
+            .chain(attributes.into_iter().cloned())
+            .collect()
+    }
+
+    /// Aggregate the samples of series `name` in `records` into buckets of
+    /// `bucket` length starting at `from`.
+    #[must_use]
+    pub fn downsample(
+        records: &[EntityHistory],
+        name: &str,
+        from: Timestamp,
+        bucket: TimeDelta,
+    ) -> Self {
+        let bucket_ms = bucket.num_milliseconds().max(1);
+        let mut buckets: BTreeMap<i64, HistoryBucket> = BTreeMap::new();
+        for record in records {
+            let value = if name == Self::STATE {
+                record.state.as_f64()
+            } else {
+                record.get_attribute(name).and_then(numeric)
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let index = (record.recorded_at - from)
+                .num_milliseconds()
+                .div_euclid(bucket_ms);
+            buckets
+                .entry(index)
+                .and_modify(|bucket| {
+                    let count = f64::from(bucket.count);
+                    bucket.mean = (bucket.mean * count + value) / (count + 1.0);
+                    bucket.min = bucket.min.min(value);
+                    bucket.max = bucket.max.max(value);
+                    bucket.count += 1;
+                })
+                .or_insert_with(|| HistoryBucket {
+                    start: from + TimeDelta::milliseconds(index * bucket_ms),
+                    mean: value,
+                    min: value,
+                    max: value,
+                    count: 1,
+                });
+        }
+        Self {
+            name: name.to_string(),
+            buckets: buckets.into_values().collect(),
+        }
+    }
+}
+
+/// The reading of a numeric attribute value.
+#[allow(clippy::cast_precision_loss)]
+fn numeric(value: &AttributeValue) -> Option<f64> {
+    match value {
+        AttributeValue::Float(value) => Some(*value),
+        AttributeValue::Int(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,4 +346,79 @@ mod tests {
         assert_eq!(deserialized.state, history.state);
         assert_eq!(deserialized.attributes, history.attributes);
     }
+
+    fn at(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    fn reading(recorded_at: &str, temperature: f64) -> EntityHistory {
+        EntityHistory::builder()
+            .state(EntityState::Numeric(temperature))
+            .attribute("temperature", AttributeValue::Float(temperature))
+            .attribute("battery", AttributeValue::Int(80))
+            .attribute("model", AttributeValue::String("LYWSD03MMC".to_string()))
+            .recorded_at(at(recorded_at))
+            .build()
+    }
+
+    #[test]
+    fn should_list_state_then_numeric_attributes_when_naming_series() {
+        let records = [reading("2026-01-01T00:00:00Z", 20.0)];
+
+        assert_eq!(
+            HistorySeries::names(&records),
+            ["state", "battery", "temperature"]
+        );
+    }
+
+    #[test]
+    fn should_aggregate_samples_per_bucket_when_downsampling() {
+        let records = [
+            reading("2026-01-01T00:10:00Z", 20.0),
+            reading("2026-01-01T00:50:00Z", 22.0),
+            reading("2026-01-01T03:30:00Z", 19.0),
+        ];
+
+        let series = HistorySeries::downsample(
+            &records,
+            "temperature",
+            at("2026-01-01T00:00:00Z"),
+            TimeDelta::hours(1),
+        );
+
+        assert_eq!(series.name, "temperature");
+        assert_eq!(
+            series.buckets,
+            [
+                HistoryBucket {
+                    start: at("2026-01-01T00:00:00Z"),
+                    mean: 21.0,
+                    min: 20.0,
+                    max: 22.0,
+                    count: 2,
+                },
+                HistoryBucket {
+                    start: at("2026-01-01T03:00:00Z"),
+                    mean: 19.0,
+                    min: 19.0,
+                    max: 19.0,
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_skip_non_numeric_samples_when_downsampling() {
+        let records = [reading("2026-01-01T00:10:00Z", 20.0)];
+
+        let series = HistorySeries::downsample(
+            &records,
+            "model",
+            at("2026-01-01T00:00:00Z"),
+            TimeDelta::hours(1),
+        );
+
+        assert!(series.buckets.is_empty());
+    }
 }
@@ -1,9 +1,11 @@
 //! Common error types used across the workspace.
 //!
 //! Each layer defines its own concrete error types. The domain layer provides
-//! [`ValidationError`] and [`NotFoundError`]. Adapter layers define their own
-//! (e.g., `StorageError` wrapping `sqlx::Error`) and wire them into
-//! [`MiniHubError`] via `#[from]` conversion.
+//! [`ValidationError`], [`NotFoundError`] and [`ConflictError`]. Adapter
+//! layers define their own (e.g., `StorageError` wrapping `sqlx::Error`) and
+//! wire them into [`MiniHubError`] via `#[from]` conversion.
+
+use crate::service::ParameterError;
 
 /// Validation failures raised by domain invariant checks.
 #[derive(Debug, thiserror::Error)]
@@ -18,10 +20,117 @@ pub enum ValidationError {
     EmptyIntegration,
     #[error("unique_id cannot be empty")]
     EmptyUniqueId,
+    #[error("a device cannot be its own via_device")]
+    SelfViaDevice,
     #[error("at least one action is required")]
     NoActions,
+    #[error("message cannot be empty")]
+    EmptyMessage,
+    #[error("invalid {event_type} event payload: {reason}")]
+    InvalidEventPayload { event_type: String, reason: String },
+    #[error("at least one scene member is required")]
+    NoSceneMembers,
+    #[error("scene member target state must be on or off, got {0}")]
+    UnsupportedSceneState(String),
+    #[error("at least one group member is required")]
+    NoGroupMembers,
+    #[error("invalid group member: {0}")]
+    InvalidGroupMember(String),
+    #[error("{key} value {value} is out of range")]
+    AttributeOutOfRange { key: String, value: f64 },
     #[error("invalid RFC 3339 timestamp: {0}")]
     InvalidTimestamp(String),
+    #[error("invalid patch: {0}")]
+    InvalidPatch(String),
+    #[error("unknown home mode: {0}")]
+    UnknownHomeMode(String),
+    #[error("unknown execution mode: {0}")]
+    UnknownExecutionMode(String),
+    #[error("invalid input helper: {0}")]
+    InvalidInputHelper(String),
+    #[error("invalid input value: {0}")]
+    InvalidInputValue(String),
+    #[error("invalid entity_id: {0}")]
+    InvalidEntityId(String),
+    #[error("entity_id {0} is already in use")]
+    DuplicateEntityId(String),
+    #[error("unknown audit actor: {0}")]
+    UnknownActor(String),
+    #[error("unknown role {0}, expected viewer, controller or admin")]
+    UnknownRole(String),
+    #[error(
+        "invalid webhook id {0:?}, expected 16 to 64 letters, digits, dashes or underscores with at least 8 distinct characters"
+    )]
+    InvalidWebhookId(String),
+    #[error("invalid location: {0}")]
+    InvalidLocation(String),
+    #[error("invalid sun offset {0} minutes, expected at most 720 minutes before or after")]
+    InvalidSunOffset(i32),
+    #[error("invalid cron pattern {0}")]
+    InvalidCron(String),
+    #[error("invalid expression: {0}")]
+    InvalidExpression(String),
+    #[error(
+        "invalid repeat count {0}, expected 1 to 100 runs including those of the enclosing repeats"
+    )]
+    InvalidRepeatCount(u32),
+    #[error("actions cannot be nested more than {0} levels deep")]
+    ActionsTooDeep(usize),
+    #[error("invalid dashboard: {0}")]
+    InvalidDashboard(String),
+    #[error(
+        "invalid settings name {0:?}, expected 1 to 64 lowercase letters, digits or underscores"
+    )]
+    InvalidSettingsName(String),
+    #[error("settings namespace {0} is managed by its own endpoint")]
+    ReservedSettingsNamespace(String),
+    #[error(
+        "invalid secret name {0:?}, expected 1 to 64 lowercase letters, digits, underscores, dashes or dots"
+    )]
+    InvalidSecretName(String),
+    #[error("secret value cannot be empty")]
+    EmptySecretValue,
+    #[error("automation {0} is defined in a file and cannot be changed through the API")]
+    ReadOnlyAutomation(String),
+    #[error("state {state} is not allowed for {domain} entities, expected one of {allowed}")]
+    InvalidStateForDomain {
+        domain: String,
+        state: String,
+        allowed: String,
+    },
+    #[error("invalid data for service {service}: {}", join(.errors))]
+    InvalidServiceData {
+        service: String,
+        errors: Vec<ParameterError>,
+    },
+}
+
+/// A validation failure along with the field it concerns, e.g. `name` or
+/// `actions[2]`, so forms can show it next to the offending input.
+#[derive(Debug, thiserror::Error)]
+#[error("{field}: {error}")]
+pub struct FieldError {
+    pub field: String,
+    pub error: ValidationError,
+}
+
+impl FieldError {
+    #[must_use]
+    pub fn new(field: impl Into<String>, error: ValidationError) -> Self {
+        Self {
+            field: field.into(),
+            error,
+        }
+    }
+}
+
+/// Join parameter errors into one sentence.
+fn join(errors: &[ParameterError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// Returned when a lookup by identifier finds nothing.
@@ -32,6 +141,17 @@ pub struct NotFoundError {
     pub id: String,
 }
 
+/// Returned when a compare-and-set finds a different current value than the
+/// caller expected.
+#[derive(Debug, thiserror::Error)]
+#[error("{entity} {id} is {actual}, expected {expected}")]
+pub struct ConflictError {
+    pub entity: &'static str,
+    pub id: String,
+    pub expected: String,
+    pub actual: String,
+}
+
 /// Top-level domain error.
 ///
 /// Adapter crates may introduce additional variants by wrapping their own
@@ -44,6 +164,9 @@ pub enum MiniHubError {
     #[error("{0}")]
     NotFound(#[from] NotFoundError),
 
+    #[error("{0}")]
+    Conflict(#[from] ConflictError),
+
     #[error(transparent)]
     Storage(#[from] anyhow::Error),
 }
@@ -85,4 +208,15 @@ mod tests {
         .into();
         assert!(matches!(err, MiniHubError::NotFound(_)));
     }
+
+    #[test]
+    fn should_display_conflict_error_with_expected_and_actual() {
+        let err = ConflictError {
+            entity: "Entity",
+            id: "abc-123".to_string(),
+            expected: "off".to_string(),
+            actual: "on".to_string(),
+        };
+        assert_eq!(err.to_string(), "Entity abc-123 is on, expected off");
+    }
 }
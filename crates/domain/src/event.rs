@@ -2,12 +2,38 @@
 //!
 //! Events are produced when entity state changes, services are called,
 //! automations fire, etc.
+//!
+//! Events record their causality: an event produced in reaction to another
+//! one points to it through `caused_by` and inherits its `correlation_id`,
+//! so a whole chain — a state change firing an automation, whose service
+//! call changes another state, and so on — can be traced back.
+//!
+//! The `data` of an event is the JSON form of the payload struct of its
+//! type, e.g. [`StateChangedPayload`] for [`EventType::StateChanged`],
+//! read back with [`Event::payload`]. Events carry the
+//! [`schema_version`](Event::schema_version) of their payload, so its
+//! shape can evolve without consumers misreading older or newer events.
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use crate::id::{EntityId, EventId};
+use crate::device::DeviceStatus;
+use crate::entity::EntityState;
+use crate::error::ValidationError;
+use crate::id::{AutomationId, DeviceId, EntityId, EventId};
+use crate::sun::SunEvent;
 use crate::time::Timestamp;
 
+/// Version of the payload shapes produced by this build. Bumped whenever a
+/// payload struct changes in a way older consumers cannot read.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Version of the events recorded before payloads were versioned, whose
+/// shapes are the ones of version 1.
+const fn legacy_schema_version() -> u32 {
+    1
+}
+
 /// An immutable record of something that happened in the system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -16,6 +42,16 @@ pub struct Event {
     pub entity_id: Option<EntityId>,
     pub timestamp: Timestamp,
     pub data: serde_json::Value,
+    /// Id shared by every event of a causal chain: the id of the event
+    /// that started it.
+    pub correlation_id: EventId,
+    /// The event this one was produced in reaction to, if any.
+    #[serde(default)]
+    pub caused_by: Option<EventId>,
+    /// Version of the shape of `data`, [`SCHEMA_VERSION`] for the events
+    /// produced by this build.
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
 }
 
 /// The kind of event that occurred.
@@ -26,11 +62,25 @@ pub enum EventType {
     AttributeChanged,
     EntityCreated,
     EntityRemoved,
+    EntityRenamed,
     AutomationTriggered,
+    /// An automation was not run because it already ran within the causal
+    /// chain of its triggering event.
+    AutomationSuppressed,
     DeviceDetected,
+    DeviceUpdated,
+    DeviceUnavailable,
+    DeviceBackOnline,
     ServiceCallRequested,
     ServiceCallCompleted,
     ServiceCallFailed,
+    NotificationRequested,
+    /// An external system called an inbound webhook.
+    WebhookReceived,
+    /// The sun rose or set, possibly some minutes ago or ahead.
+    SunEvent,
+    /// A `time_pattern` schedule came due.
+    TimePatternFired,
 }
 
 impl Event {
@@ -41,12 +91,79 @@ impl Event {
         entity_id: Option<EntityId>,
         data: serde_json::Value,
     ) -> Self {
+        let id = EventId::new();
         Self {
-            id: EventId::new(),
+            id,
             event_type,
             entity_id,
             timestamp: crate::time::now(),
             data,
+            correlation_id: id,
+            caused_by: None,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+
+    /// Create a new event of `event_type` carrying `payload`, with the
+    /// current timestamp.
+    ///
+    /// `event_type` must be one of the [`EventPayload::EVENT_TYPES`] of the
+    /// payload.
+    #[must_use]
+    pub fn with_payload<P: EventPayload>(
+        event_type: EventType,
+        entity_id: Option<EntityId>,
+        payload: &P,
+    ) -> Self {
+        debug_assert!(
+            P::EVENT_TYPES.contains(&event_type),
+            "{event_type} events do not carry this payload"
+        );
+        let data = serde_json::to_value(payload).unwrap_or_default();
+        Self::new(event_type, entity_id, data)
+    }
+
+    /// Read the payload of the event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidEventPayload`] when the event is
+    /// not one of the [`EventPayload::EVENT_TYPES`] of `P`, was produced
+    /// with a newer [`SCHEMA_VERSION`] than this build knows, or its `data`
+    /// does not have the shape of `P`.
+    pub fn payload<P: EventPayload>(&self) -> Result<P, ValidationError> {
+        let invalid = |reason: String| ValidationError::InvalidEventPayload {
+            event_type: self.event_type.to_string(),
+            reason,
+        };
+        if !P::EVENT_TYPES.contains(&self.event_type) {
+            return Err(invalid("not the payload of this event type".to_string()));
+        }
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(invalid(format!(
+                "schema version {} is newer than the supported {SCHEMA_VERSION}",
+                self.schema_version
+            )));
+        }
+        P::deserialize(&self.data).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Mark this event as produced in reaction to `cause`, joining its
+    /// causal chain.
+    #[must_use]
+    pub fn with_cause(mut self, cause: &Event) -> Self {
+        self.caused_by = Some(cause.id);
+        self.correlation_id = cause.correlation_id;
+        self
+    }
+
+    /// Like [`Self::with_cause`], leaving the event untouched when there is
+    /// no cause.
+    #[must_use]
+    pub fn with_cause_opt(self, cause: Option<&Event>) -> Self {
+        match cause {
+            Some(cause) => self.with_cause(cause),
+            None => self,
         }
     }
 }
@@ -59,11 +176,20 @@ impl EventType {
             Self::AttributeChanged => "attribute_changed",
             Self::EntityCreated => "entity_created",
             Self::EntityRemoved => "entity_removed",
+            Self::EntityRenamed => "entity_renamed",
             Self::AutomationTriggered => "automation_triggered",
+            Self::AutomationSuppressed => "automation_suppressed",
             Self::DeviceDetected => "device_detected",
+            Self::DeviceUpdated => "device_updated",
+            Self::DeviceUnavailable => "device_unavailable",
+            Self::DeviceBackOnline => "device_back_online",
             Self::ServiceCallRequested => "service_call_requested",
             Self::ServiceCallCompleted => "service_call_completed",
             Self::ServiceCallFailed => "service_call_failed",
+            Self::NotificationRequested => "notification_requested",
+            Self::WebhookReceived => "webhook_received",
+            Self::SunEvent => "sun_event",
+            Self::TimePatternFired => "time_pattern_fired",
         }
     }
 }
@@ -74,6 +200,206 @@ impl std::fmt::Display for EventType {
     }
 }
 
+/// The typed `data` of the events of some [`EventType`]s.
+///
+/// [`EventType::EntityRemoved`] events carry no payload, and
+/// [`EventType::DeviceDetected`] ones carry whatever the detecting
+/// integration knows about the device.
+pub trait EventPayload: Serialize + DeserializeOwned {
+    /// Types of the events carrying this payload.
+    const EVENT_TYPES: &'static [EventType];
+}
+
+/// Payload of [`EventType::StateChanged`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChangedPayload {
+    pub old_state: EntityState,
+    pub new_state: EntityState,
+}
+
+impl EventPayload for StateChangedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::StateChanged];
+}
+
+/// Payload of [`EventType::AttributeChanged`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeChangedPayload {
+    /// New value of every attribute that changed, by key.
+    pub changed: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EventPayload for AttributeChangedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::AttributeChanged];
+}
+
+/// Payload of [`EventType::EntityCreated`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityCreatedPayload {
+    /// Human-readable id of the entity, e.g. `light.kitchen`.
+    pub entity_id: String,
+}
+
+impl EventPayload for EntityCreatedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::EntityCreated];
+}
+
+/// Payload of [`EventType::EntityRenamed`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityRenamedPayload {
+    pub old_entity_id: String,
+    pub new_entity_id: String,
+}
+
+impl EventPayload for EntityRenamedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::EntityRenamed];
+}
+
+/// Payload of [`EventType::AutomationTriggered`] and
+/// [`EventType::AutomationSuppressed`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomationPayload {
+    pub automation_id: AutomationId,
+    pub automation_name: String,
+    /// Why a suppressed automation was not run: `"cascade"` or `"single"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl EventPayload for AutomationPayload {
+    const EVENT_TYPES: &'static [EventType] = &[
+        EventType::AutomationTriggered,
+        EventType::AutomationSuppressed,
+    ];
+}
+
+/// Payload of [`EventType::DeviceUpdated`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceUpdatedPayload {
+    pub device_id: DeviceId,
+    pub integration: String,
+    pub unique_id: String,
+    /// Names of the fields that changed, e.g. `["name", "sw_version"]`.
+    pub changed: Vec<String>,
+}
+
+impl EventPayload for DeviceUpdatedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::DeviceUpdated];
+}
+
+/// Payload of [`EventType::DeviceUnavailable`] and
+/// [`EventType::DeviceBackOnline`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceAvailabilityPayload {
+    pub device_id: DeviceId,
+    pub device_name: String,
+    pub status: DeviceStatus,
+}
+
+impl EventPayload for DeviceAvailabilityPayload {
+    const EVENT_TYPES: &'static [EventType] =
+        &[EventType::DeviceUnavailable, EventType::DeviceBackOnline];
+}
+
+/// Payload of [`EventType::ServiceCallRequested`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceCallPayload {
+    /// Service to call, e.g. `turn_on`.
+    pub service: String,
+    /// Arguments of the call, e.g. `{ "brightness": 128 }`.
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+impl EventPayload for ServiceCallPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::ServiceCallRequested];
+}
+
+/// Payload of [`EventType::ServiceCallCompleted`] and
+/// [`EventType::ServiceCallFailed`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceCallResultPayload {
+    pub service: String,
+    /// Why a failed call failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether a failed call is kept to be retried later.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deferred: bool,
+}
+
+impl ServiceCallResultPayload {
+    /// Payload of a completed call to `service`.
+    #[must_use]
+    pub fn completed(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            error: None,
+            deferred: false,
+        }
+    }
+
+    /// Payload of a call to `service` that failed with `error`.
+    #[must_use]
+    pub fn failed(service: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            error: Some(error.into()),
+            deferred: false,
+        }
+    }
+
+    /// Mark a failed call as kept to be retried later.
+    #[must_use]
+    pub fn deferred(mut self) -> Self {
+        self.deferred = true;
+        self
+    }
+}
+
+impl EventPayload for ServiceCallResultPayload {
+    const EVENT_TYPES: &'static [EventType] = &[
+        EventType::ServiceCallCompleted,
+        EventType::ServiceCallFailed,
+    ];
+}
+
+/// Payload of [`EventType::WebhookReceived`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookReceivedPayload {
+    pub webhook_id: String,
+    /// Body of the call, `null` when it had none.
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
+
+impl EventPayload for WebhookReceivedPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::WebhookReceived];
+}
+
+/// Payload of [`EventType::SunEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SunEventPayload {
+    pub event: SunEvent,
+    /// How long after the event it fired, in minutes; negative before.
+    #[serde(default)]
+    pub offset_minutes: i32,
+}
+
+impl EventPayload for SunEventPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::SunEvent];
+}
+
+/// Payload of [`EventType::TimePatternFired`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimePatternFiredPayload {
+    /// The cron pattern that came due, as written in the trigger.
+    pub cron: String,
+}
+
+impl EventPayload for TimePatternFiredPayload {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::TimePatternFired];
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +437,27 @@ mod tests {
         assert_ne!(a.id, b.id);
     }
 
+    #[test]
+    fn should_start_a_new_chain_when_created() {
+        let event = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+
+        assert_eq!(event.correlation_id, event.id);
+        assert!(event.caused_by.is_none());
+    }
+
+    #[test]
+    fn should_join_chain_of_cause() {
+        let root = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+        let call = Event::new(EventType::ServiceCallRequested, None, serde_json::json!({}))
+            .with_cause(&root);
+        let change =
+            Event::new(EventType::StateChanged, None, serde_json::json!({})).with_cause(&call);
+
+        assert_eq!(call.caused_by, Some(root.id));
+        assert_eq!(change.caused_by, Some(call.id));
+        assert_eq!(change.correlation_id, root.id);
+    }
+
     #[test]
     fn should_roundtrip_event_through_serde_json() {
         let event = Event::new(
@@ -126,6 +473,83 @@ mod tests {
         assert_eq!(parsed.event_type, event.event_type);
         assert_eq!(parsed.entity_id, event.entity_id);
         assert_eq!(parsed.data, event.data);
+        assert_eq!(parsed.correlation_id, event.correlation_id);
+        assert_eq!(parsed.caused_by, event.caused_by);
+    }
+
+    #[test]
+    fn should_read_back_typed_payload() {
+        let payload = StateChangedPayload {
+            old_state: EntityState::Off,
+            new_state: EntityState::Numeric(21.5),
+        };
+
+        let event = Event::with_payload(EventType::StateChanged, None, &payload);
+
+        assert_eq!(event.schema_version, SCHEMA_VERSION);
+        assert_eq!(event.data["old_state"], "off");
+        assert_eq!(event.payload::<StateChangedPayload>().unwrap(), payload);
+    }
+
+    #[test]
+    fn should_reject_payload_of_another_event_type_or_shape() {
+        let renamed = Event::new(
+            EventType::EntityRenamed,
+            None,
+            serde_json::json!({"old_state": "off", "new_state": "on"}),
+        );
+        let legacy = Event::new(
+            EventType::StateChanged,
+            None,
+            serde_json::json!({"from": "off", "to": "on"}),
+        );
+
+        assert!(renamed.payload::<StateChangedPayload>().is_err());
+        assert!(legacy.payload::<StateChangedPayload>().is_err());
+    }
+
+    #[test]
+    fn should_reject_payload_of_newer_schema_version() {
+        let mut event = Event::with_payload(
+            EventType::ServiceCallCompleted,
+            None,
+            &ServiceCallResultPayload::completed("turn_on"),
+        );
+        event.schema_version = SCHEMA_VERSION + 1;
+
+        let err = event.payload::<ServiceCallResultPayload>().unwrap_err();
+
+        assert!(err.to_string().contains("newer"));
+    }
+
+    #[test]
+    fn should_default_schema_version_of_events_recorded_before_versioning() {
+        let mut json = serde_json::to_value(Event::new(
+            EventType::EntityRemoved,
+            None,
+            serde_json::json!({}),
+        ))
+        .unwrap();
+        json.as_object_mut().unwrap().remove("schema_version");
+
+        let parsed: Event = serde_json::from_value(json).unwrap();
+
+        assert_eq!(parsed.schema_version, 1);
+    }
+
+    #[test]
+    fn should_omit_error_and_deferred_of_completed_service_calls() {
+        let completed =
+            serde_json::to_value(ServiceCallResultPayload::completed("turn_on")).unwrap();
+        let failed =
+            serde_json::to_value(ServiceCallResultPayload::failed("turn_on", "timeout").deferred())
+                .unwrap();
+
+        assert_eq!(completed, serde_json::json!({"service": "turn_on"}));
+        assert_eq!(
+            failed,
+            serde_json::json!({"service": "turn_on", "error": "timeout", "deferred": true})
+        );
     }
 
     #[test]
@@ -135,11 +559,20 @@ mod tests {
             EventType::AttributeChanged,
             EventType::EntityCreated,
             EventType::EntityRemoved,
+            EventType::EntityRenamed,
             EventType::AutomationTriggered,
+            EventType::AutomationSuppressed,
             EventType::DeviceDetected,
+            EventType::DeviceUpdated,
             EventType::ServiceCallRequested,
             EventType::ServiceCallCompleted,
             EventType::ServiceCallFailed,
+            EventType::NotificationRequested,
+            EventType::DeviceUnavailable,
+            EventType::DeviceBackOnline,
+            EventType::WebhookReceived,
+            EventType::SunEvent,
+            EventType::TimePatternFired,
         ];
 
         for variant in &variants {
@@ -153,11 +586,25 @@ mod tests {
     fn should_display_event_type_as_snake_case() {
         assert_eq!(EventType::StateChanged.to_string(), "state_changed");
         assert_eq!(EventType::EntityCreated.to_string(), "entity_created");
+        assert_eq!(EventType::EntityRenamed.to_string(), "entity_renamed");
         assert_eq!(
             EventType::AutomationTriggered.to_string(),
             "automation_triggered"
         );
+        assert_eq!(
+            EventType::AutomationSuppressed.to_string(),
+            "automation_suppressed"
+        );
         assert_eq!(EventType::DeviceDetected.to_string(), "device_detected");
+        assert_eq!(EventType::DeviceUpdated.to_string(), "device_updated");
+        assert_eq!(
+            EventType::DeviceUnavailable.to_string(),
+            "device_unavailable"
+        );
+        assert_eq!(
+            EventType::DeviceBackOnline.to_string(),
+            "device_back_online"
+        );
         assert_eq!(
             EventType::ServiceCallRequested.to_string(),
             "service_call_requested"
@@ -170,5 +617,15 @@ mod tests {
             EventType::ServiceCallFailed.to_string(),
             "service_call_failed"
         );
+        assert_eq!(
+            EventType::NotificationRequested.to_string(),
+            "notification_requested"
+        );
+        assert_eq!(EventType::WebhookReceived.to_string(), "webhook_received");
+        assert_eq!(EventType::SunEvent.to_string(), "sun_event");
+        assert_eq!(
+            EventType::TimePatternFired.to_string(),
+            "time_pattern_fired"
+        );
     }
 }
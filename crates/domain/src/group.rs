@@ -0,0 +1,333 @@
+//! Group — several lights or switches controlled as one entity.
+//!
+//! A group is exposed as a regular `light.*` or `switch.*` entity on the hub
+//! device. Its state aggregates the states of its members, and service calls
+//! targeting it are fanned out to every member, so "all living room lights"
+//! can be toggled at once.
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{AttributeValue, Entity, EntityState, entity_id_from_name};
+use crate::error::{MiniHubError, ValidationError};
+use crate::id::{DeviceId, EntityId, GroupId};
+
+/// Attribute of the group entity listing the ids of its members.
+pub const MEMBERS_ATTRIBUTE: &str = "members";
+
+/// Kind of entities a [`Group`] gathers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    Light,
+    Switch,
+}
+
+impl GroupKind {
+    /// The `entity_id` domain of the group entity and of its members,
+    /// e.g. `"light"`.
+    #[must_use]
+    pub fn domain(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Switch => "switch",
+        }
+    }
+
+    /// Whether the entity with `entity_id` can be a member of a group of
+    /// this kind.
+    #[must_use]
+    pub fn accepts(self, entity_id: &str) -> bool {
+        entity_id
+            .split_once('.')
+            .is_some_and(|(domain, _)| domain == self.domain())
+    }
+}
+
+/// Several entities of the same kind controlled through one group entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    pub id: GroupId,
+    pub name: String,
+    pub kind: GroupKind,
+    /// The entity exposing the group.
+    pub entity_id: EntityId,
+    pub members: Vec<EntityId>,
+}
+
+impl Group {
+    /// Create a builder for constructing a [`Group`].
+    #[must_use]
+    pub fn builder() -> GroupBuilder {
+        GroupBuilder::default()
+    }
+
+    /// Check domain invariants.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when `name` is empty, when there
+    /// are no members, or when the group lists itself or the same member
+    /// twice.
+    pub fn validate(&self) -> Result<(), MiniHubError> {
+        if self.name.trim().is_empty() {
+            return Err(ValidationError::EmptyName.into());
+        }
+        if self.members.is_empty() {
+            return Err(ValidationError::NoGroupMembers.into());
+        }
+        if self.members.contains(&self.entity_id) {
+            return Err(ValidationError::InvalidGroupMember(
+                "a group cannot contain itself".into(),
+            )
+            .into());
+        }
+        for (index, member) in self.members.iter().enumerate() {
+            if self.members[..index].contains(member) {
+                return Err(ValidationError::InvalidGroupMember(format!(
+                    "{member} is listed twice"
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Derive the `entity_id` of the group entity, e.g. `"Living room"` →
+    /// `"light.living_room"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::EmptyName`] when the name has no letter or
+    /// digit to build the id from.
+    pub fn entity_id_string(&self) -> Result<String, ValidationError> {
+        entity_id_from_name(self.kind.domain(), &self.name)
+    }
+
+    /// Aggregate the states of the members: `on` as soon as one member is
+    /// on, `off` when the others are off, `unavailable` when every member
+    /// is, and `unknown` otherwise. Readings count as neither on nor off.
+    #[must_use]
+    pub fn aggregate_state<'a>(states: impl IntoIterator<Item = &'a EntityState>) -> EntityState {
+        let mut any_off = false;
+        let mut all_unavailable = true;
+        let mut empty = true;
+        for state in states {
+            empty = false;
+            match state {
+                EntityState::On => return EntityState::On,
+                EntityState::Off => any_off = true,
+                EntityState::Unknown | EntityState::Numeric(_) | EntityState::Text(_) => {}
+                EntityState::Unavailable => continue,
+            }
+            all_unavailable = false;
+        }
+        if any_off {
+            EntityState::Off
+        } else if all_unavailable && !empty {
+            EntityState::Unavailable
+        } else {
+            EntityState::Unknown
+        }
+    }
+
+    /// Build the group entity on `device_id`, in `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the group or the entity
+    /// breaks an invariant.
+    pub fn to_entity(
+        &self,
+        device_id: DeviceId,
+        state: EntityState,
+    ) -> Result<Entity, MiniHubError> {
+        self.validate()?;
+        let members = self
+            .members
+            .iter()
+            .map(|member| serde_json::Value::String(member.to_string()))
+            .collect();
+        Entity::builder()
+            .id(self.entity_id)
+            .device_id(device_id)
+            .entity_id(self.entity_id_string()?)
+            .friendly_name(self.name.trim())
+            .state(state)
+            .attribute(
+                MEMBERS_ATTRIBUTE,
+                AttributeValue::Json(serde_json::Value::Array(members)),
+            )
+            .build()
+    }
+}
+
+/// Step-by-step builder for [`Group`].
+#[derive(Debug, Default)]
+pub struct GroupBuilder {
+    id: Option<GroupId>,
+    name: Option<String>,
+    kind: Option<GroupKind>,
+    entity_id: Option<EntityId>,
+    members: Vec<EntityId>,
+}
+
+impl GroupBuilder {
+    #[must_use]
+    pub fn id(mut self, id: GroupId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn kind(mut self, kind: GroupKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Id of the entity exposing the group; a new one by default.
+    #[must_use]
+    pub fn entity_id(mut self, entity_id: EntityId) -> Self {
+        self.entity_id = Some(entity_id);
+        self
+    }
+
+    #[must_use]
+    pub fn member(mut self, member: EntityId) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// Consume the builder, validate, and return a [`Group`]. The kind
+    /// defaults to [`GroupKind::Light`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the group breaks an invariant
+    /// (see [`Group::validate`]).
+    pub fn build(self) -> Result<Group, MiniHubError> {
+        let group = Group {
+            id: self.id.unwrap_or_default(),
+            name: self.name.unwrap_or_default(),
+            kind: self.kind.unwrap_or(GroupKind::Light),
+            entity_id: self.entity_id.unwrap_or_default(),
+            members: self.members,
+        };
+        group.validate()?;
+        Ok(group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(members: &[EntityId]) -> Result<Group, MiniHubError> {
+        members
+            .iter()
+            .fold(Group::builder().name("Living room"), |builder, member| {
+                builder.member(*member)
+            })
+            .build()
+    }
+
+    #[test]
+    fn should_build_light_group_by_default() {
+        let member = EntityId::new();
+
+        let group = group(&[member]).unwrap();
+
+        assert_eq!(group.kind, GroupKind::Light);
+        assert_eq!(group.members, vec![member]);
+        assert_eq!(group.entity_id_string().unwrap(), "light.living_room");
+    }
+
+    #[test]
+    fn should_return_validation_error_when_no_members() {
+        assert!(matches!(
+            group(&[]),
+            Err(MiniHubError::Validation(ValidationError::NoGroupMembers))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_member_is_listed_twice() {
+        let member = EntityId::new();
+        assert!(matches!(
+            group(&[member, member]),
+            Err(MiniHubError::Validation(
+                ValidationError::InvalidGroupMember(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_group_contains_itself() {
+        let entity_id = EntityId::new();
+        let result = Group::builder()
+            .name("Loop")
+            .entity_id(entity_id)
+            .member(entity_id)
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(
+                ValidationError::InvalidGroupMember(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_accept_members_of_the_same_domain() {
+        assert!(GroupKind::Light.accepts("light.ceiling"));
+        assert!(!GroupKind::Light.accepts("switch.plug"));
+        assert!(!GroupKind::Switch.accepts("switch"));
+    }
+
+    #[test]
+    fn should_aggregate_member_states() {
+        use EntityState::{Off, On, Unavailable, Unknown};
+
+        assert_eq!(Group::aggregate_state(&[Off, On, Unavailable]), On);
+        assert_eq!(Group::aggregate_state(&[Off, Unavailable]), Off);
+        assert_eq!(
+            Group::aggregate_state(&[Unavailable, Unavailable]),
+            Unavailable
+        );
+        assert_eq!(Group::aggregate_state(&[Unknown, Unavailable]), Unknown);
+        assert_eq!(Group::aggregate_state(&[]), Unknown);
+    }
+
+    #[test]
+    fn should_expose_group_as_entity_listing_members() {
+        let member = EntityId::new();
+        let group = group(&[member]).unwrap();
+        let device_id = DeviceId::new();
+
+        let entity = group.to_entity(device_id, EntityState::On).unwrap();
+
+        assert_eq!(entity.id, group.entity_id);
+        assert_eq!(entity.device_id, device_id);
+        assert_eq!(entity.entity_id, "light.living_room");
+        assert_eq!(entity.state, EntityState::On);
+        assert_eq!(
+            entity.get_attribute(MEMBERS_ATTRIBUTE),
+            Some(&AttributeValue::Json(serde_json::json!([
+                member.to_string()
+            ])))
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_through_serde_json() {
+        let group = group(&[EntityId::new()]).unwrap();
+        let json = serde_json::to_string(&group).unwrap();
+        let parsed: Group = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, group);
+    }
+}
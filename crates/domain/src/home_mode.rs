@@ -0,0 +1,160 @@
+//! Home mode — the hub-wide mode automations can branch on.
+//!
+//! The mode lives on a regular entity, [`HOME_MODE_ENTITY_ID`], in its
+//! [`MODE_ATTRIBUTE`] attribute. Storing it on an entity means a mode change
+//! is persisted, recorded in history and streamed like any other attribute
+//! change. The entity is a built-in [select input helper](crate::input_helper),
+//! so the mode can also be changed through the entity state API.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{AttributeValue, Entity};
+use crate::error::{MiniHubError, ValidationError};
+use crate::id::DeviceId;
+use crate::input_helper::{self, InputHelper};
+
+/// Entity id of the entity carrying the home mode.
+pub const HOME_MODE_ENTITY_ID: &str = "input_select.home_mode";
+
+/// Attribute holding the current mode, e.g. `"away"`.
+pub const MODE_ATTRIBUTE: &str = input_helper::VALUE_ATTRIBUTE;
+
+/// Attribute listing every selectable mode.
+pub const OPTIONS_ATTRIBUTE: &str = input_helper::OPTIONS_ATTRIBUTE;
+
+/// What the household is currently doing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeMode {
+    /// Someone is home and awake.
+    #[default]
+    Home,
+    /// Everybody is out for the day.
+    Away,
+    /// Everybody is home and asleep.
+    Night,
+    /// Everybody is out for several days.
+    Vacation,
+}
+
+impl HomeMode {
+    /// Every mode, in display order.
+    pub const ALL: [Self; 4] = [Self::Home, Self::Away, Self::Night, Self::Vacation];
+
+    /// The `snake_case` name used in JSON and storage.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Home => "home",
+            Self::Away => "away",
+            Self::Night => "night",
+            Self::Vacation => "vacation",
+        }
+    }
+
+    /// Read the mode stored on the home mode entity.
+    ///
+    /// Returns `None` when the attribute is missing or holds an unknown mode.
+    #[must_use]
+    pub fn of(entity: &Entity) -> Option<Self> {
+        match entity.get_attribute(MODE_ATTRIBUTE) {
+            Some(AttributeValue::String(mode)) => mode.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Build the home mode entity, attached to `device_id`, holding this mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the entity fails its invariants.
+    pub fn to_entity(self, device_id: DeviceId) -> Result<Entity, MiniHubError> {
+        let helper = InputHelper::Select {
+            options: Self::ALL
+                .iter()
+                .map(|mode| mode.as_str().to_string())
+                .collect(),
+        };
+        helper.to_entity(
+            device_id,
+            "Home Mode",
+            Some(&serde_json::Value::from(self.as_str())),
+        )
+    }
+}
+
+impl std::fmt::Display for HomeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HomeMode {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == value)
+            .ok_or_else(|| ValidationError::UnknownHomeMode(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_default_to_home() {
+        assert_eq!(HomeMode::default(), HomeMode::Home);
+    }
+
+    #[test]
+    fn should_roundtrip_every_mode_through_from_str() {
+        for mode in HomeMode::ALL {
+            assert_eq!(mode.to_string().parse::<HomeMode>().unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_mode() {
+        let err = "party".parse::<HomeMode>().unwrap_err();
+        assert!(matches!(err, ValidationError::UnknownHomeMode(ref mode) if mode == "party"));
+    }
+
+    #[test]
+    fn should_serialize_as_snake_case() {
+        assert_eq!(
+            serde_json::to_value(HomeMode::Vacation).unwrap(),
+            serde_json::json!("vacation")
+        );
+    }
+
+    #[test]
+    fn should_read_mode_back_from_entity() {
+        let entity = HomeMode::Night.to_entity(DeviceId::new()).unwrap();
+
+        assert_eq!(entity.entity_id, HOME_MODE_ENTITY_ID);
+        assert_eq!(HomeMode::of(&entity), Some(HomeMode::Night));
+        assert_eq!(
+            entity.get_attribute(OPTIONS_ATTRIBUTE),
+            Some(&AttributeValue::Json(serde_json::json!([
+                "home", "away", "night", "vacation"
+            ])))
+        );
+    }
+
+    #[test]
+    fn should_return_none_when_entity_has_no_mode() {
+        let entity = Entity::builder()
+            .device_id(DeviceId::new())
+            .entity_id(HOME_MODE_ENTITY_ID)
+            .friendly_name("Home Mode")
+            .build()
+            .unwrap();
+
+        assert_eq!(HomeMode::of(&entity), None);
+    }
+}
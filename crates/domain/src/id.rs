@@ -78,6 +78,36 @@ define_id!(
     EventId
 );
 
+define_id!(
+    /// Unique identifier for an [`AutomationRun`](crate::automation_run::AutomationRun).
+    AutomationRunId
+);
+
+define_id!(
+    /// Unique identifier for a [`Scene`](crate::scene::Scene).
+    SceneId
+);
+
+define_id!(
+    /// Unique identifier for a [`Group`](crate::group::Group).
+    GroupId
+);
+
+define_id!(
+    /// Unique identifier for a [`Notification`](crate::notification::Notification).
+    NotificationId
+);
+
+define_id!(
+    /// Unique identifier for a [`PendingDevice`](crate::pending_device::PendingDevice).
+    PendingDeviceId
+);
+
+define_id!(
+    /// Unique identifier for an [`AuditEntry`](crate::audit::AuditEntry).
+    AuditEntryId
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
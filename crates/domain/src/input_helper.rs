@@ -0,0 +1,440 @@
+//! Input helpers — user-defined virtual entities for automations and
+//! dashboards.
+//!
+//! A helper is a regular entity whose `entity_id` domain tells its kind:
+//!
+//! - `input_boolean.*` is a switch, held in the entity state (`on`/`off`);
+//! - `input_number.*` holds a number in [`VALUE_ATTRIBUTE`], bounded by the
+//!   attribute's metadata range and snapped to [`STEP_ATTRIBUTE`];
+//! - `input_select.*` holds one of [`OPTIONS_ATTRIBUTE`] in
+//!   [`VALUE_ATTRIBUTE`].
+//!
+//! The constraints live on the entity itself, so [`InputHelper::of`] can
+//! rebuild them from storage without a dedicated table.
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{AttributeMeta, AttributeValue, Entity, EntityState, entity_id_from_name};
+use crate::error::{MiniHubError, ValidationError};
+use crate::id::DeviceId;
+
+/// Attribute holding the value of number and select helpers.
+pub const VALUE_ATTRIBUTE: &str = "value";
+
+/// Attribute listing the choices of a select helper.
+pub const OPTIONS_ATTRIBUTE: &str = "options";
+
+/// Attribute holding the increment of a number helper.
+pub const STEP_ATTRIBUTE: &str = "step";
+
+/// Tolerance used when checking that a number sits on a step.
+const STEP_EPSILON: f64 = 1e-9;
+
+fn default_step() -> f64 {
+    1.0
+}
+
+/// Kind and constraints of an input helper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputHelper {
+    /// An on/off switch.
+    Boolean,
+    /// A number between `min` and `max` (inclusive), in increments of `step`
+    /// from `min`.
+    Number {
+        min: f64,
+        max: f64,
+        #[serde(default = "default_step")]
+        step: f64,
+    },
+    /// One choice among `options`.
+    Select { options: Vec<String> },
+}
+
+impl InputHelper {
+    /// The `entity_id` domain of helpers of this kind, e.g. `"input_number"`.
+    #[must_use]
+    pub fn domain(&self) -> &'static str {
+        match self {
+            Self::Boolean => "input_boolean",
+            Self::Number { .. } => "input_number",
+            Self::Select { .. } => "input_select",
+        }
+    }
+
+    /// Derive the `entity_id` of a helper called `name`, e.g.
+    /// `"Guest mode"` → `"input_boolean.guest_mode"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::EmptyName`] when `name` has no letter or
+    /// digit to build the id from.
+    pub fn entity_id_for(&self, name: &str) -> Result<String, ValidationError> {
+        entity_id_from_name(self.domain(), name)
+    }
+
+    /// Check the constraints are usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidInputHelper`] when a number range
+    /// is empty or its step is not positive, or when a select has no
+    /// options, an empty option or the same option twice.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self {
+            Self::Boolean => Ok(()),
+            Self::Number { min, max, step } => {
+                if !(min.is_finite() && max.is_finite() && min < max) {
+                    return Err(ValidationError::InvalidInputHelper(format!(
+                        "min {min} must be lower than max {max}"
+                    )));
+                }
+                if !(step.is_finite() && *step > 0.0) {
+                    return Err(ValidationError::InvalidInputHelper(format!(
+                        "step {step} must be positive"
+                    )));
+                }
+                Ok(())
+            }
+            Self::Select { options } => {
+                if options.is_empty() {
+                    return Err(ValidationError::InvalidInputHelper(
+                        "at least one option is required".to_string(),
+                    ));
+                }
+                for (index, option) in options.iter().enumerate() {
+                    if option.trim().is_empty() {
+                        return Err(ValidationError::InvalidInputHelper(
+                            "options cannot be empty".to_string(),
+                        ));
+                    }
+                    if options[..index].contains(option) {
+                        return Err(ValidationError::InvalidInputHelper(format!(
+                            "option {option:?} is listed twice"
+                        )));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check `value` against the constraints and convert it to the stored
+    /// attribute value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidInputValue`] when `value` has the
+    /// wrong type, falls outside the range, is off-step or is not one of
+    /// the options. Boolean helpers hold no value and reject every value:
+    /// they are switched through their state.
+    pub fn parse_value(
+        &self,
+        value: &serde_json::Value,
+    ) -> Result<AttributeValue, ValidationError> {
+        match self {
+            Self::Boolean => Err(ValidationError::InvalidInputValue(
+                "input_boolean helpers are switched through their state".to_string(),
+            )),
+            Self::Number { min, max, step } => {
+                let Some(number) = value.as_f64() else {
+                    return Err(ValidationError::InvalidInputValue(format!(
+                        "expected a number, got {value}"
+                    )));
+                };
+                if number < *min || number > *max {
+                    return Err(ValidationError::InvalidInputValue(format!(
+                        "{number} is outside {min}..={max}"
+                    )));
+                }
+                let steps = (number - min) / step;
+                if (steps - steps.round()).abs() > STEP_EPSILON {
+                    return Err(ValidationError::InvalidInputValue(format!(
+                        "{number} is not a multiple of {step} from {min}"
+                    )));
+                }
+                Ok(AttributeValue::Float(number))
+            }
+            Self::Select { options } => match value.as_str() {
+                Some(option) if options.iter().any(|known| known == option) => {
+                    Ok(AttributeValue::String(option.to_string()))
+                }
+                _ => Err(ValidationError::InvalidInputValue(format!(
+                    "{value} is not one of {options:?}"
+                ))),
+            },
+        }
+    }
+
+    /// Rebuild the constraints of a helper entity.
+    ///
+    /// Returns `None` when the entity is not an input helper, or when its
+    /// constraint attributes are missing.
+    #[must_use]
+    pub fn of(entity: &Entity) -> Option<Self> {
+        let (domain, _) = entity.entity_id.split_once('.')?;
+        match domain {
+            "input_boolean" => Some(Self::Boolean),
+            "input_number" => {
+                let meta = entity.get_attribute_meta(VALUE_ATTRIBUTE)?;
+                let step = match entity.get_attribute(STEP_ATTRIBUTE) {
+                    Some(AttributeValue::Float(step)) => *step,
+                    #[allow(clippy::cast_precision_loss)]
+                    Some(AttributeValue::Int(step)) => *step as f64,
+                    _ => default_step(),
+                };
+                Some(Self::Number {
+                    min: meta.min?,
+                    max: meta.max?,
+                    step,
+                })
+            }
+            "input_select" => match entity.get_attribute(OPTIONS_ATTRIBUTE)? {
+                AttributeValue::Json(serde_json::Value::Array(options)) => Some(Self::Select {
+                    options: options
+                        .iter()
+                        .filter_map(|option| option.as_str().map(str::to_string))
+                        .collect(),
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Build the entity of a helper called `name`, attached to `device_id`.
+    ///
+    /// Starts from `initial` when given, otherwise off, at `min`, or on the
+    /// first option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when the constraints are
+    /// invalid, `initial` does not satisfy them, or the entity fails its
+    /// invariants.
+    pub fn to_entity(
+        &self,
+        device_id: DeviceId,
+        name: &str,
+        initial: Option<&serde_json::Value>,
+    ) -> Result<Entity, MiniHubError> {
+        self.validate()?;
+        let builder = Entity::builder()
+            .device_id(device_id)
+            .entity_id(self.entity_id_for(name)?)
+            .friendly_name(name.trim());
+        let builder = match self {
+            Self::Boolean => {
+                let state = match initial {
+                    None | Some(serde_json::Value::Bool(false)) => EntityState::Off,
+                    Some(serde_json::Value::Bool(true)) => EntityState::On,
+                    Some(value) => {
+                        return Err(ValidationError::InvalidInputValue(format!(
+                            "expected true or false, got {value}"
+                        ))
+                        .into());
+                    }
+                };
+                builder.state(state)
+            }
+            Self::Number { min, max, step } => {
+                let value = match initial {
+                    Some(value) => self.parse_value(value)?,
+                    None => AttributeValue::Float(*min),
+                };
+                builder
+                    .state(EntityState::On)
+                    .attribute(VALUE_ATTRIBUTE, value)
+                    .attribute(STEP_ATTRIBUTE, AttributeValue::Float(*step))
+                    .attribute_meta(
+                        VALUE_ATTRIBUTE,
+                        AttributeMeta::default().with_range(*min, *max),
+                    )
+            }
+            Self::Select { options } => {
+                let value = match initial {
+                    Some(value) => self.parse_value(value)?,
+                    None => AttributeValue::String(options[0].clone()),
+                };
+                let options = options
+                    .iter()
+                    .map(|option| option.as_str().into())
+                    .collect();
+                builder
+                    .state(EntityState::On)
+                    .attribute(VALUE_ATTRIBUTE, value)
+                    .attribute(
+                        OPTIONS_ATTRIBUTE,
+                        AttributeValue::Json(serde_json::Value::Array(options)),
+                    )
+            }
+        };
+        builder.build()
+    }
+}
+
+/// Whether two helper values are equal, comparing numbers by value so that
+/// `21` matches `21.0`.
+#[must_use]
+pub fn value_eq(left: &serde_json::Value, right: &serde_json::Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => (left - right).abs() < STEP_EPSILON,
+        _ => left == right,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn thermostat() -> InputHelper {
+        InputHelper::Number {
+            min: 15.0,
+            max: 25.0,
+            step: 0.5,
+        }
+    }
+
+    fn scene_select() -> InputHelper {
+        InputHelper::Select {
+            options: vec!["relax".to_string(), "movie".to_string()],
+        }
+    }
+
+    #[test]
+    fn should_derive_entity_id_from_name() {
+        assert_eq!(
+            InputHelper::Boolean.entity_id_for("Guest mode!").unwrap(),
+            "input_boolean.guest_mode"
+        );
+        assert!(matches!(
+            InputHelper::Boolean.entity_id_for(" - "),
+            Err(ValidationError::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn should_deserialize_kind_tag_with_default_step() {
+        let helper: InputHelper =
+            serde_json::from_value(json!({ "kind": "number", "min": 0, "max": 10 })).unwrap();
+        assert_eq!(
+            helper,
+            InputHelper::Number {
+                min: 0.0,
+                max: 10.0,
+                step: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_constraints() {
+        let empty_range = InputHelper::Number {
+            min: 5.0,
+            max: 5.0,
+            step: 1.0,
+        };
+        let duplicate = InputHelper::Select {
+            options: vec!["a".to_string(), "a".to_string()],
+        };
+        let no_options = InputHelper::Select { options: vec![] };
+
+        for helper in [empty_range, duplicate, no_options] {
+            assert!(matches!(
+                helper.validate(),
+                Err(ValidationError::InvalidInputHelper(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn should_accept_number_on_step_within_range() {
+        assert_eq!(
+            thermostat().parse_value(&json!(21.5)).unwrap(),
+            AttributeValue::Float(21.5)
+        );
+    }
+
+    #[test]
+    fn should_reject_number_off_step_or_out_of_range() {
+        for value in [json!(21.2), json!(30), json!("21")] {
+            assert!(matches!(
+                thermostat().parse_value(&value),
+                Err(ValidationError::InvalidInputValue(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn should_only_accept_known_options() {
+        assert_eq!(
+            scene_select().parse_value(&json!("movie")).unwrap(),
+            AttributeValue::String("movie".to_string())
+        );
+        assert!(matches!(
+            scene_select().parse_value(&json!("party")),
+            Err(ValidationError::InvalidInputValue(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_values_for_boolean_helpers() {
+        assert!(matches!(
+            InputHelper::Boolean.parse_value(&json!(true)),
+            Err(ValidationError::InvalidInputValue(_))
+        ));
+    }
+
+    #[test]
+    fn should_rebuild_constraints_from_entity() {
+        for helper in [InputHelper::Boolean, thermostat(), scene_select()] {
+            let entity = helper.to_entity(DeviceId::new(), "Helper", None).unwrap();
+            assert_eq!(InputHelper::of(&entity), Some(helper));
+        }
+    }
+
+    #[test]
+    fn should_start_from_initial_value_or_default() {
+        let entity = thermostat()
+            .to_entity(DeviceId::new(), "Target", None)
+            .unwrap();
+        assert_eq!(
+            entity.get_attribute(VALUE_ATTRIBUTE),
+            Some(&AttributeValue::Float(15.0))
+        );
+
+        let entity = scene_select()
+            .to_entity(DeviceId::new(), "Scene", Some(&json!("movie")))
+            .unwrap();
+        assert_eq!(entity.entity_id, "input_select.scene");
+        assert_eq!(
+            entity.get_attribute(VALUE_ATTRIBUTE),
+            Some(&AttributeValue::String("movie".to_string()))
+        );
+
+        let entity = InputHelper::Boolean
+            .to_entity(DeviceId::new(), "Guest mode", Some(&json!(true)))
+            .unwrap();
+        assert_eq!(entity.state, EntityState::On);
+    }
+
+    #[test]
+    fn should_not_treat_regular_entities_as_helpers() {
+        let entity = Entity::builder()
+            .entity_id("light.kitchen")
+            .friendly_name("Kitchen")
+            .build()
+            .unwrap();
+        assert_eq!(InputHelper::of(&entity), None);
+    }
+
+    #[test]
+    fn should_compare_numbers_by_value() {
+        assert!(value_eq(&json!(21), &json!(21.0)));
+        assert!(!value_eq(&json!(21), &json!("21")));
+        assert!(value_eq(&json!("away"), &json!("away")));
+    }
+}
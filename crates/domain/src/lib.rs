@@ -10,6 +10,20 @@
 //! - Define **Services** (commands: `turn_on`, `turn_off`, `toggle`, …)
 //! - Define **Events** (state-change records)
 //! - Define **Automations** (trigger → condition → action rules)
+//! - Define the **Home mode** (hub-wide home/away/night/vacation mode)
+//! - Define **Input helpers** (user-defined boolean, number and select entities)
+//! - Define **Scenes** (named snapshots of target entity states)
+//! - Define **Groups** (lights or switches controlled as one entity)
+//! - Define **Notifications** (user-facing messages delivered by notifiers)
+//! - Define **Pending devices** (devices detected by integrations, waiting to be adopted)
+//! - Define **Audit entries** (who created, updated or deleted what, and when)
+//! - Define **Roles** (what a client of the REST API is allowed to do)
+//! - Define the **Dashboard layout** (cards pinned to the dashboard home page)
+//! - Define **Settings** (hub-wide options stored under namespaced keys)
+//! - Define **Secrets** (integration credentials, never shown back once stored)
+//! - Define **Energy usage** (consumption of power and energy sensors per hour)
+//! - Define **Webhooks** (inbound calls from external systems triggering automations)
+//! - Define the **Sun** (sunrise, sunset and elevation at the home location)
 //! - Contain all invariant enforcement and domain logic
 //!
 //! ## Dependency rule
@@ -22,9 +36,25 @@ pub mod id;
 pub mod time;
 
 pub mod area;
+pub mod audit;
 pub mod automation;
+pub mod automation_run;
+pub mod dashboard;
 pub mod device;
+pub mod energy;
 pub mod entity;
 pub mod entity_history;
 pub mod event;
+pub mod group;
+pub mod home_mode;
+pub mod input_helper;
+pub mod notification;
+pub mod pending_device;
+pub mod report;
+pub mod role;
+pub mod scene;
+pub mod secret;
 pub mod service;
+pub mod settings;
+pub mod sun;
+pub mod webhook;
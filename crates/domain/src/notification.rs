@@ -0,0 +1,226 @@
+//! Notification — a user-facing message delivered by a notifier.
+//!
+//! Automations and integrations request a notification by publishing an
+//! [`EventType::NotificationRequested`] event carrying the serialized
+//! notification; the notification service forwards it to the configured
+//! delivery adapters (webhook, chat bot, …).
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MiniHubError, ValidationError};
+use crate::event::{Event, EventPayload, EventType};
+use crate::id::{EntityId, NotificationId};
+use crate::time::Timestamp;
+
+/// How urgently a notification deserves attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => f.write_str("info"),
+            Self::Warning => f.write_str("warning"),
+            Self::Critical => f.write_str("critical"),
+        }
+    }
+}
+
+/// A message meant for the humans using minihub.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: NotificationId,
+    pub title: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub severity: Severity,
+    /// The entity the notification is about, if any.
+    pub entity_id: Option<EntityId>,
+    pub created_at: Timestamp,
+}
+
+impl Notification {
+    /// Create a builder for constructing a [`Notification`].
+    #[must_use]
+    pub fn builder() -> NotificationBuilder {
+        NotificationBuilder::default()
+    }
+
+    /// Check domain invariants.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when `message` is empty.
+    pub fn validate(&self) -> Result<(), MiniHubError> {
+        if self.message.is_empty() {
+            return Err(ValidationError::EmptyMessage.into());
+        }
+        Ok(())
+    }
+
+    /// Wrap the notification in an [`EventType::NotificationRequested`] event.
+    #[must_use]
+    pub fn to_event(&self) -> Event {
+        Event::with_payload(EventType::NotificationRequested, self.entity_id, self)
+    }
+
+    /// Extract the notification carried by an
+    /// [`EventType::NotificationRequested`] event.
+    ///
+    /// Returns `Ok(None)` for any other event type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when the payload cannot be
+    /// decoded or breaks an invariant.
+    pub fn from_event(event: &Event) -> Result<Option<Self>, MiniHubError> {
+        if event.event_type != EventType::NotificationRequested {
+            return Ok(None);
+        }
+        let notification: Self = event.payload()?;
+        notification.validate()?;
+        Ok(Some(notification))
+    }
+}
+
+impl EventPayload for Notification {
+    const EVENT_TYPES: &'static [EventType] = &[EventType::NotificationRequested];
+}
+
+/// Step-by-step builder for [`Notification`].
+#[derive(Debug, Default)]
+pub struct NotificationBuilder {
+    id: Option<NotificationId>,
+    title: Option<String>,
+    message: Option<String>,
+    severity: Severity,
+    entity_id: Option<EntityId>,
+}
+
+impl NotificationBuilder {
+    #[must_use]
+    pub fn id(mut self, id: NotificationId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    #[must_use]
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    #[must_use]
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    #[must_use]
+    pub fn entity_id(mut self, entity_id: EntityId) -> Self {
+        self.entity_id = Some(entity_id);
+        self
+    }
+
+    /// Consume the builder, validate, and return a [`Notification`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if `message` is missing or empty.
+    pub fn build(self) -> Result<Notification, MiniHubError> {
+        let notification = Notification {
+            id: self.id.unwrap_or_default(),
+            title: self.title,
+            message: self.message.unwrap_or_default(),
+            severity: self.severity,
+            entity_id: self.entity_id,
+            created_at: crate::time::now(),
+        };
+        notification.validate()?;
+        Ok(notification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_valid_notification_when_message_provided() {
+        let notification = Notification::builder()
+            .title("Door")
+            .message("Front door opened")
+            .severity(Severity::Warning)
+            .build()
+            .unwrap();
+
+        assert_eq!(notification.title.as_deref(), Some("Door"));
+        assert_eq!(notification.message, "Front door opened");
+        assert_eq!(notification.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn should_return_validation_error_when_message_is_empty() {
+        let result = Notification::builder().title("Door").build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::EmptyMessage))
+        ));
+    }
+
+    #[test]
+    fn should_roundtrip_through_event() {
+        let entity_id = EntityId::new();
+        let notification = Notification::builder()
+            .message("Battery low")
+            .entity_id(entity_id)
+            .build()
+            .unwrap();
+
+        let event = notification.to_event();
+        assert_eq!(event.event_type, EventType::NotificationRequested);
+        assert_eq!(event.entity_id, Some(entity_id));
+
+        let parsed = Notification::from_event(&event).unwrap().unwrap();
+        assert_eq!(parsed, notification);
+    }
+
+    #[test]
+    fn should_ignore_other_event_types() {
+        let event = Event::new(EventType::StateChanged, None, serde_json::json!({}));
+        assert!(Notification::from_event(&event).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_reject_malformed_notification_event() {
+        let event = Event::new(
+            EventType::NotificationRequested,
+            None,
+            serde_json::json!({ "title": 42 }),
+        );
+        assert!(matches!(
+            Notification::from_event(&event),
+            Err(MiniHubError::Validation(
+                ValidationError::InvalidEventPayload { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_serialize_severity_as_lowercase() {
+        let json = serde_json::to_string(&Severity::Critical).unwrap();
+        assert_eq!(json, "\"critical\"");
+    }
+}
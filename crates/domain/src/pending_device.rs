@@ -0,0 +1,247 @@
+//! Pending device — a device an integration detected but nobody registered.
+//!
+//! Integrations publish a [`EventType::DeviceDetected`] event for every
+//! device they notice, whether or not they can drive it. Those devices wait
+//! in a discovery inbox until a user adopts one, turning it into a regular
+//! [`Device`] assigned to an integration.
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::Device;
+use crate::error::MiniHubError;
+use crate::event::{Event, EventType};
+use crate::id::{AreaId, PendingDeviceId};
+use crate::time::Timestamp;
+
+/// A detected device waiting to be adopted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingDevice {
+    pub id: PendingDeviceId,
+    /// Integration that detected the device, e.g. `"ble"` or `"mdns"`.
+    pub integration: String,
+    /// Address the device was detected at: its MAC address, or `host:port`.
+    pub address: String,
+    pub name: Option<String>,
+    /// Integration able to drive the device, when the detecting one knows.
+    pub suggested_integration: Option<String>,
+    /// Payload of the last detection event.
+    pub details: serde_json::Value,
+    pub first_seen: Timestamp,
+    pub last_seen: Timestamp,
+}
+
+/// Choices made when adopting a [`PendingDevice`]; every field falls back
+/// to what the detection reported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adoption {
+    /// Device name; the detected name, or the address.
+    pub name: Option<String>,
+    /// Integration the device is assigned to; the suggested integration, or
+    /// the one that detected it.
+    pub integration: Option<String>,
+    /// Identifier of the device within its integration; the address.
+    pub unique_id: Option<String>,
+    pub area_id: Option<AreaId>,
+}
+
+impl PendingDevice {
+    /// Read the device reported by a [`EventType::DeviceDetected`] event.
+    ///
+    /// Returns `None` for other events and for detections carrying neither
+    /// a `mac` nor an `address`.
+    #[must_use]
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.event_type != EventType::DeviceDetected {
+            return None;
+        }
+        let field = |key: &str| {
+            event
+                .data
+                .get(key)
+                .and_then(|value| value.as_str())
+                .filter(|value| !value.is_empty())
+        };
+        let address = field("mac").or_else(|| field("address"))?;
+        Some(Self {
+            id: PendingDeviceId::new(),
+            integration: field("integration").unwrap_or("unknown").to_string(),
+            address: address.to_string(),
+            name: field("name").map(str::to_string),
+            suggested_integration: field("suggested_integration").map(str::to_string),
+            details: event.data.clone(),
+            first_seen: event.timestamp,
+            last_seen: event.timestamp,
+        })
+    }
+
+    /// Whether `other` describes the same physical device.
+    #[must_use]
+    pub fn same_device(&self, other: &Self) -> bool {
+        self.integration == other.integration && self.address.eq_ignore_ascii_case(&other.address)
+    }
+
+    /// Update with a later detection of the same device, keeping the id
+    /// and the first detection time. A detection without name keeps the
+    /// known one.
+    pub fn refresh(&mut self, detection: Self) {
+        if detection.name.is_some() {
+            self.name = detection.name;
+        }
+        self.suggested_integration = detection.suggested_integration;
+        self.details = detection.details;
+        self.last_seen = self.last_seen.max(detection.last_seen);
+    }
+
+    /// `(integration, unique_id)` of the device an adoption registers.
+    #[must_use]
+    pub fn registration_key(&self, adoption: &Adoption) -> (String, String) {
+        let integration = adoption
+            .integration
+            .clone()
+            .or_else(|| self.suggested_integration.clone())
+            .unwrap_or_else(|| self.integration.clone());
+        let unique_id = adoption
+            .unique_id
+            .clone()
+            .unwrap_or_else(|| self.address.clone());
+        (integration, unique_id)
+    }
+
+    /// Build the device registered when adopting this one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the adoption leaves the name,
+    /// integration or unique id empty.
+    pub fn adopt(&self, adoption: Adoption) -> Result<Device, MiniHubError> {
+        let (integration, unique_id) = self.registration_key(&adoption);
+        let name = adoption
+            .name
+            .or_else(|| self.name.clone())
+            .unwrap_or_else(|| self.address.clone());
+        let mut builder = Device::builder()
+            .name(name.trim())
+            .integration(integration)
+            .unique_id(unique_id);
+        if let Some(area_id) = adoption.area_id {
+            builder = builder.area_id(area_id);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ValidationError;
+
+    use super::*;
+
+    fn detected(data: serde_json::Value) -> Event {
+        Event::new(EventType::DeviceDetected, None, data)
+    }
+
+    fn ble_device() -> PendingDevice {
+        PendingDevice::from_event(&detected(serde_json::json!({
+            "integration": "ble",
+            "mac": "A4:C1:38:00:00:01",
+            "name": "LYWSD03MMC",
+            "rssi": -60,
+        })))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_read_pending_device_from_detection_event() {
+        let device = ble_device();
+
+        assert_eq!(device.integration, "ble");
+        assert_eq!(device.address, "A4:C1:38:00:00:01");
+        assert_eq!(device.name.as_deref(), Some("LYWSD03MMC"));
+        assert_eq!(device.details["rssi"], -60);
+        assert_eq!(device.first_seen, device.last_seen);
+    }
+
+    #[test]
+    fn should_ignore_detection_without_address() {
+        let event = detected(serde_json::json!({ "integration": "ble" }));
+        assert!(PendingDevice::from_event(&event).is_none());
+    }
+
+    #[test]
+    fn should_ignore_other_events() {
+        let event = Event::new(
+            EventType::StateChanged,
+            None,
+            serde_json::json!({ "mac": "A4:C1:38:00:00:01" }),
+        );
+        assert!(PendingDevice::from_event(&event).is_none());
+    }
+
+    #[test]
+    fn should_keep_id_and_name_when_refreshed_without_name() {
+        let mut device = ble_device();
+        let id = device.id;
+        let later = PendingDevice::from_event(&detected(serde_json::json!({
+            "integration": "ble",
+            "mac": "a4:c1:38:00:00:01",
+            "rssi": -70,
+        })))
+        .unwrap();
+
+        assert!(device.same_device(&later));
+        device.refresh(later);
+
+        assert_eq!(device.id, id);
+        assert_eq!(device.name.as_deref(), Some("LYWSD03MMC"));
+        assert_eq!(device.details["rssi"], -70);
+    }
+
+    #[test]
+    fn should_adopt_with_detected_values_by_default() {
+        let device = ble_device().adopt(Adoption::default()).unwrap();
+
+        assert_eq!(device.name, "LYWSD03MMC");
+        assert_eq!(device.integration, "ble");
+        assert_eq!(device.unique_id, "A4:C1:38:00:00:01");
+        assert!(device.area_id.is_none());
+    }
+
+    #[test]
+    fn should_adopt_with_suggested_integration_and_overrides() {
+        let pending = PendingDevice::from_event(&detected(serde_json::json!({
+            "integration": "mdns",
+            "suggested_integration": "esphome",
+            "address": "192.168.1.20:6053",
+        })))
+        .unwrap();
+        let area_id = AreaId::new();
+
+        let device = pending
+            .adopt(Adoption {
+                name: Some("Living room node".to_string()),
+                unique_id: Some("AC:67:B2:00:00:01".to_string()),
+                area_id: Some(area_id),
+                ..Adoption::default()
+            })
+            .unwrap();
+
+        assert_eq!(device.name, "Living room node");
+        assert_eq!(device.integration, "esphome");
+        assert_eq!(device.unique_id, "AC:67:B2:00:00:01");
+        assert_eq!(device.area_id, Some(area_id));
+    }
+
+    #[test]
+    fn should_reject_adoption_with_empty_integration() {
+        let result = ble_device().adopt(Adoption {
+            integration: Some(String::new()),
+            ..Adoption::default()
+        });
+
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::EmptyIntegration))
+        ));
+    }
+}
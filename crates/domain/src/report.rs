@@ -0,0 +1,70 @@
+//! Reports — read-only summaries aggregated across entities, devices and events.
+//!
+//! Reports are read models: they are computed by the storage layer in a
+//! single pass rather than assembled from individual repository calls.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::time::Timestamp;
+
+/// System-wide overview of the hub's inventory and recent activity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Overview {
+    pub entities: u64,
+    pub devices: u64,
+    pub areas: u64,
+    pub automations: u64,
+    pub enabled_automations: u64,
+    /// Number of entities per current state (`"on"`, `"unavailable"`, …).
+    pub entities_by_state: BTreeMap<String, u64>,
+    /// Start of the activity window.
+    pub since: Timestamp,
+    /// Number of events recorded since [`Overview::since`].
+    pub events_since: u64,
+}
+
+impl Overview {
+    /// Number of entities currently reporting as unavailable.
+    #[must_use]
+    pub fn unavailable_entities(&self) -> u64 {
+        self.entities_by_state
+            .get("unavailable")
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Size of the storage backend, reported by `GET /api/system/info`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageStats {
+    /// Size of the database in bytes, or `0` when the backend cannot tell.
+    pub database_bytes: u64,
+    pub entities: u64,
+    pub events: u64,
+    /// Number of recorded entity history snapshots.
+    pub entity_history: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_unavailable_entities_when_present() {
+        let overview = Overview {
+            entities_by_state: BTreeMap::from([
+                ("on".to_string(), 2),
+                ("unavailable".to_string(), 3),
+            ]),
+            ..Overview::default()
+        };
+        assert_eq!(overview.unavailable_entities(), 3);
+    }
+
+    #[test]
+    fn should_return_zero_unavailable_entities_when_absent() {
+        assert_eq!(Overview::default().unavailable_entities(), 0);
+    }
+}
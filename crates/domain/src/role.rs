@@ -0,0 +1,84 @@
+//! Roles — what a client of the REST API is allowed to do.
+//!
+//! Roles are ordered: each one allows everything the previous one does.
+//! A `viewer` only reads, a `controller` also operates devices, and an
+//! `admin` also changes the configuration of the hub.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ValidationError;
+
+/// Permission level of an API client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Reads states, history, events and configuration.
+    Viewer,
+    /// Also calls services, updates states and activates scenes.
+    Controller,
+    /// Also creates, changes and deletes devices, automations and the
+    /// configuration of the hub.
+    Admin,
+}
+
+impl Role {
+    /// Every role, from the least to the most privileged.
+    pub const ALL: [Self; 3] = [Self::Viewer, Self::Controller, Self::Admin];
+
+    /// The role as configured and serialized, e.g. `"viewer"`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Controller => "controller",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Whether this role may do what `required` may.
+    #[must_use]
+    pub fn allows(self, required: Self) -> bool {
+        self >= required
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.as_str() == value)
+            .ok_or_else(|| ValidationError::UnknownRole(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_allow_lower_roles_only() {
+        assert!(Role::Admin.allows(Role::Controller));
+        assert!(Role::Controller.allows(Role::Controller));
+        assert!(!Role::Viewer.allows(Role::Controller));
+        assert!(!Role::Controller.allows(Role::Admin));
+    }
+
+    #[test]
+    fn should_parse_role_names() {
+        for role in Role::ALL {
+            assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+        }
+        assert!("root".parse::<Role>().is_err());
+    }
+}
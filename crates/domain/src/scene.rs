@@ -0,0 +1,220 @@
+//! Scene — a named set of target states applied to several entities at once.
+//!
+//! Activating a scene requests one service call per member, e.g. a
+//! "Movie night" scene turning the ceiling light off and a lamp on at 30 %.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{AttributeValue, EntityState};
+use crate::error::{MiniHubError, ValidationError};
+use crate::id::{EntityId, SceneId};
+
+/// The target state of a single entity within a [`Scene`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneMember {
+    pub entity_id: EntityId,
+    pub state: EntityState,
+    /// Attributes forwarded as service-call data (brightness, colour, …).
+    #[serde(default)]
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+impl SceneMember {
+    /// Create a member targeting `state` with no attributes.
+    #[must_use]
+    pub fn new(entity_id: EntityId, state: EntityState) -> Self {
+        Self {
+            entity_id,
+            state,
+            attributes: HashMap::new(),
+        }
+    }
+
+    /// Add a target attribute.
+    #[must_use]
+    pub fn with_attribute(mut self, key: impl Into<String>, value: AttributeValue) -> Self {
+        self.attributes.insert(key.into(), value);
+        self
+    }
+
+    /// Name of the service that brings the entity to its target state.
+    ///
+    /// Returns `None` for states that cannot be requested (`unknown`,
+    /// `unavailable` and readings).
+    #[must_use]
+    pub fn service(&self) -> Option<&'static str> {
+        match self.state {
+            EntityState::On => Some("turn_on"),
+            EntityState::Off => Some("turn_off"),
+            EntityState::Unknown
+            | EntityState::Unavailable
+            | EntityState::Numeric(_)
+            | EntityState::Text(_) => None,
+        }
+    }
+}
+
+/// A named set of entity target states.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub id: SceneId,
+    pub name: String,
+    pub members: Vec<SceneMember>,
+}
+
+impl Scene {
+    /// Create a builder for constructing a [`Scene`].
+    #[must_use]
+    pub fn builder() -> SceneBuilder {
+        SceneBuilder::default()
+    }
+
+    /// Check domain invariants.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] when `name` is empty, when there
+    /// are no members, or when a member targets a state other than `on` or
+    /// `off`.
+    pub fn validate(&self) -> Result<(), MiniHubError> {
+        if self.name.is_empty() {
+            return Err(ValidationError::EmptyName.into());
+        }
+        if self.members.is_empty() {
+            return Err(ValidationError::NoSceneMembers.into());
+        }
+        if let Some(member) = self.members.iter().find(|m| m.service().is_none()) {
+            return Err(ValidationError::UnsupportedSceneState(member.state.to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Step-by-step builder for [`Scene`].
+#[derive(Debug, Default)]
+pub struct SceneBuilder {
+    id: Option<SceneId>,
+    name: Option<String>,
+    members: Vec<SceneMember>,
+}
+
+impl SceneBuilder {
+    #[must_use]
+    pub fn id(mut self, id: SceneId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn member(mut self, member: SceneMember) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// Consume the builder, validate, and return a [`Scene`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MiniHubError::Validation`] if the scene breaks an invariant
+    /// (see [`Scene::validate`]).
+    pub fn build(self) -> Result<Scene, MiniHubError> {
+        let scene = Scene {
+            id: self.id.unwrap_or_default(),
+            name: self.name.unwrap_or_default(),
+            members: self.members,
+        };
+        scene.validate()?;
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_valid_scene_when_name_and_member_provided() {
+        let entity_id = EntityId::new();
+        let scene = Scene::builder()
+            .name("Movie night")
+            .member(SceneMember::new(entity_id, EntityState::Off))
+            .build()
+            .unwrap();
+
+        assert_eq!(scene.name, "Movie night");
+        assert_eq!(scene.members.len(), 1);
+        assert_eq!(scene.members[0].entity_id, entity_id);
+    }
+
+    #[test]
+    fn should_return_validation_error_when_name_is_empty() {
+        let result = Scene::builder()
+            .member(SceneMember::new(EntityId::new(), EntityState::On))
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::EmptyName))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_no_members() {
+        let result = Scene::builder().name("Empty").build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::NoSceneMembers))
+        ));
+    }
+
+    #[test]
+    fn should_return_validation_error_when_member_targets_unknown_state() {
+        let result = Scene::builder()
+            .name("Broken")
+            .member(SceneMember::new(EntityId::new(), EntityState::Unknown))
+            .build();
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(ValidationError::UnsupportedSceneState(state))) if state == "unknown"
+        ));
+    }
+
+    #[test]
+    fn should_map_target_state_to_service() {
+        let on = SceneMember::new(EntityId::new(), EntityState::On);
+        let off = SceneMember::new(EntityId::new(), EntityState::Off);
+        assert_eq!(on.service(), Some("turn_on"));
+        assert_eq!(off.service(), Some("turn_off"));
+    }
+
+    #[test]
+    fn should_default_attributes_when_missing_from_json() {
+        let entity_id = EntityId::new();
+        let json = format!(r#"{{"entity_id":"{entity_id}","state":"on"}}"#);
+        let member: SceneMember = serde_json::from_str(&json).unwrap();
+        assert!(member.attributes.is_empty());
+    }
+
+    #[test]
+    fn should_roundtrip_through_serde_json() {
+        let scene = Scene::builder()
+            .name("Evening")
+            .member(
+                SceneMember::new(EntityId::new(), EntityState::On)
+                    .with_attribute("brightness", AttributeValue::Int(30)),
+            )
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&scene).unwrap();
+        let parsed: Scene = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.id, scene.id);
+        assert_eq!(parsed.members, scene.members);
+    }
+}
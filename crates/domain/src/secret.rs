@@ -0,0 +1,108 @@
+//! Secrets — credentials of integrations, such as API tokens or passwords,
+//! stored encrypted and never shown back once written.
+//!
+//! A secret is known by its name, e.g. `telegram.bot_token`; only its name
+//! and when it was last written can be listed.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::error::ValidationError;
+use crate::time::Timestamp;
+
+/// Longest secret name.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Validate a secret name: 1 to [`MAX_NAME_LEN`] lowercase ASCII letters,
+/// digits, underscores, dashes or dots.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidSecretName`] otherwise.
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.bytes().all(|byte| {
+            byte.is_ascii_lowercase() || byte.is_ascii_digit() || matches!(byte, b'_' | b'-' | b'.')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidSecretName(name.to_string()))
+    }
+}
+
+/// The value of a secret. Its `Debug` output is redacted so it cannot leak
+/// into logs by accident.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretValue(String);
+
+impl SecretValue {
+    /// Wrap a plain value.
+    #[must_use]
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The plain value, to hand over to whatever needs the credential.
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretValue(***)")
+    }
+}
+
+/// What can be known about a stored secret without reading its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretInfo {
+    pub name: String,
+    /// When the value was last written.
+    pub updated_at: Timestamp,
+}
+
+/// Compare `a` and `b` in a time depending on their length only, so
+/// checking a presented credential does not tell how close a guess is.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_dotted_names() {
+        assert!(validate_name("telegram.bot_token").is_ok());
+        assert!(validate_name("shelly-kitchen.password").is_ok());
+    }
+
+    #[test]
+    fn should_reject_empty_long_or_unusual_names() {
+        assert!(validate_name("").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+        assert!(validate_name("Token").is_err());
+        assert!(validate_name("a/b").is_err());
+    }
+
+    #[test]
+    fn should_compare_equal_values_only() {
+        assert!(constant_time_eq(b"hunter2", b"hunter2"));
+        assert!(!constant_time_eq(b"hunter2", b"hunter3"));
+        assert!(!constant_time_eq(b"hunter2", b"hunter"));
+    }
+
+    #[test]
+    fn should_redact_value_when_debug_printed() {
+        let value = SecretValue::new("hunter2");
+
+        assert_eq!(format!("{value:?}"), "SecretValue(***)");
+        assert_eq!(value.expose(), "hunter2");
+    }
+}
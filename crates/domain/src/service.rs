@@ -1,6 +1,416 @@
 //! Service — a callable command exposed by an integration or the core.
 //!
-//! Examples: `light.turn_on`, `switch.toggle`, `climate.set_temperature`.
+//! Examples: `light.turn_on`, `switch.toggle`, `cover.set_position`.
 //!
-//! TODO(M2): Define `ServiceDefinition` struct (domain, name, schema of accepted fields).
-//! TODO(M2): Define `ServiceCall` struct (target entities + parameters).
+//! A [`ServiceDefinition`] describes the parameters a service accepts, and a
+//! [`ServiceCall`] is a request checked against the definition matching its
+//! target's domain before it is dispatched. Services without a definition,
+//! such as the integration-specific `blink` or `refresh`, are dispatched
+//! unchecked. Parameters a definition does not declare are passed through
+//! too, since integrations may read extra ones (e.g. `color_temp`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::entity::Entity;
+use crate::error::{MiniHubError, ValidationError};
+use crate::event::{Event, EventType, ServiceCallPayload};
+use crate::id::EntityId;
+
+/// Type of the value a service parameter accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterType {
+    Boolean,
+    /// A whole number.
+    Integer,
+    /// Any number.
+    Number,
+    String,
+}
+
+impl ParameterType {
+    /// Whether `value` is of this type.
+    fn accepts(self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::Boolean => value.is_boolean(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Number => value.is_number(),
+            Self::String => value.is_string(),
+        }
+    }
+
+    /// The `snake_case` name used in JSON.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+        }
+    }
+}
+
+impl std::fmt::Display for ParameterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A parameter a service accepts in its `data`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ParameterType,
+    /// Whether calls must provide the parameter.
+    #[serde(default)]
+    pub required: bool,
+    /// Inclusive lower bound of numeric values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Inclusive upper bound of numeric values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl Parameter {
+    /// An optional, unbounded parameter.
+    #[must_use]
+    pub fn new(name: impl Into<String>, kind: ParameterType) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            required: false,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Require calls to provide the parameter.
+    #[must_use]
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Bound numeric values to `min..=max`.
+    #[must_use]
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    /// Why `value`, the one given in a call or `None` when missing, is not
+    /// acceptable, if it is not.
+    fn check(&self, value: Option<&serde_json::Value>) -> Option<ParameterError> {
+        let reason = match value {
+            None if self.required => "is required".to_string(),
+            None => return None,
+            Some(value) if !self.kind.accepts(value) => {
+                format!("must be a {}, got {value}", self.kind)
+            }
+            Some(value) => {
+                let number = value.as_f64()?;
+                match (self.min, self.max) {
+                    (Some(min), _) if number < min => format!("must be at least {min}"),
+                    (_, Some(max)) if number > max => format!("must be at most {max}"),
+                    _ => return None,
+                }
+            }
+        };
+        Some(ParameterError {
+            parameter: self.name.clone(),
+            reason,
+        })
+    }
+}
+
+/// A parameter of a service call that does not match its definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterError {
+    /// Name of the parameter, `data` when the data itself is malformed.
+    pub parameter: String,
+    /// What is wrong with it, e.g. `"must be at most 255"`.
+    pub reason: String,
+}
+
+impl std::fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.parameter, self.reason)
+    }
+}
+
+/// A service, the entities it applies to and the parameters it accepts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceDefinition {
+    /// Service name, e.g. `turn_on`.
+    pub name: String,
+    /// Entity domain the service targets, e.g. `light`; `None` for every
+    /// domain.
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+}
+
+impl ServiceDefinition {
+    /// A service for every domain, without parameters.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            domain: None,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// Restrict the service to entities of `domain`.
+    #[must_use]
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Declare a parameter.
+    #[must_use]
+    pub fn with_parameter(mut self, parameter: Parameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    /// The services known to the core: switching entities on and off,
+    /// dimming lights and moving covers.
+    #[must_use]
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new("turn_on"),
+            Self::new("turn_on").with_domain("light").with_parameter(
+                Parameter::new("brightness", ParameterType::Number).with_range(0.0, 255.0),
+            ),
+            Self::new("turn_off"),
+            Self::new("toggle"),
+            Self::new("open").with_domain("cover"),
+            Self::new("close").with_domain("cover"),
+            Self::new("stop").with_domain("cover"),
+            Self::new("set_position")
+                .with_domain("cover")
+                .with_parameter(
+                    Parameter::new("position", ParameterType::Number)
+                        .required()
+                        .with_range(0.0, 100.0),
+                ),
+        ]
+    }
+
+    /// The built-in definition of `service` for entities of `domain`,
+    /// preferring one specific to the domain over one for every domain.
+    #[must_use]
+    pub fn find(domain: &str, service: &str) -> Option<Self> {
+        let mut matching: Vec<Self> = Self::builtin()
+            .into_iter()
+            .filter(|definition| {
+                definition.name == service
+                    && definition.domain.as_deref().is_none_or(|d| d == domain)
+            })
+            .collect();
+        matching.sort_by_key(|definition| definition.domain.is_none());
+        matching.into_iter().next()
+    }
+
+    /// Check `data` against the declared parameters, reporting every
+    /// mismatch at once. `null` stands for no parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidServiceData`] when `data` is not an
+    /// object, misses a required parameter, or holds a parameter of the
+    /// wrong type or out of range.
+    pub fn validate(&self, data: &serde_json::Value) -> Result<(), ValidationError> {
+        let errors = match data {
+            serde_json::Value::Null => self
+                .parameters
+                .iter()
+                .filter_map(|parameter| parameter.check(None))
+                .collect(),
+            serde_json::Value::Object(fields) => self
+                .parameters
+                .iter()
+                .filter_map(|parameter| parameter.check(fields.get(&parameter.name)))
+                .collect(),
+            other => vec![ParameterError {
+                parameter: "data".to_string(),
+                reason: format!("must be an object, got {other}"),
+            }],
+        };
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError::InvalidServiceData {
+                service: self.name.clone(),
+                errors,
+            })
+        }
+    }
+}
+
+/// A request to run `service` on an entity, checked against the service
+/// definition and the entity's attribute bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceCall {
+    pub entity_id: EntityId,
+    pub service: String,
+    pub data: serde_json::Value,
+}
+
+impl ServiceCall {
+    /// Build a call of `service` on `entity` with `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidServiceData`] when `data` does not
+    /// match the built-in definition of the service for the entity's domain,
+    /// or [`ValidationError::AttributeOutOfRange`] when a value falls outside
+    /// the bounds of the entity attribute it sets.
+    pub fn new(
+        entity: &Entity,
+        service: impl Into<String>,
+        data: serde_json::Value,
+    ) -> Result<Self, MiniHubError> {
+        let service = service.into();
+        if let Some(definition) = ServiceDefinition::find(entity.domain(), &service) {
+            definition.validate(&data)?;
+        }
+        entity.validate_service_data(&data)?;
+        Ok(Self {
+            entity_id: entity.id,
+            service,
+            data,
+        })
+    }
+
+    /// The [`EventType::ServiceCallRequested`] event asking the owning
+    /// integration to run the call.
+    #[must_use]
+    pub fn requested_event(&self) -> Event {
+        Event::with_payload(
+            EventType::ServiceCallRequested,
+            Some(self.entity_id),
+            &ServiceCallPayload {
+                service: self.service.clone(),
+                data: self.data.clone(),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::entity::AttributeMeta;
+
+    fn entity(entity_id: &str) -> Entity {
+        Entity::builder()
+            .entity_id(entity_id)
+            .friendly_name("Test")
+            .build()
+            .unwrap()
+    }
+
+    fn errors_of(result: Result<ServiceCall, MiniHubError>) -> Vec<ParameterError> {
+        match result {
+            Err(MiniHubError::Validation(ValidationError::InvalidServiceData {
+                errors, ..
+            })) => errors,
+            other => panic!("expected invalid service data, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn should_prefer_domain_specific_definition() {
+        let light = ServiceDefinition::find("light", "turn_on").unwrap();
+        let switch = ServiceDefinition::find("switch", "turn_on").unwrap();
+
+        assert_eq!(light.domain.as_deref(), Some("light"));
+        assert_eq!(switch.domain, None);
+        assert_eq!(ServiceDefinition::find("switch", "set_position"), None);
+        assert_eq!(ServiceDefinition::find("sensor", "blink"), None);
+    }
+
+    #[test]
+    fn should_accept_valid_data_and_pass_undeclared_parameters_through() {
+        let call = ServiceCall::new(
+            &entity("light.kitchen"),
+            "turn_on",
+            json!({ "brightness": 128, "color_temp": 300 }),
+        )
+        .unwrap();
+
+        assert_eq!(call.service, "turn_on");
+        assert_eq!(call.data["color_temp"], 300);
+        assert!(ServiceCall::new(&entity("switch.fan"), "toggle", serde_json::Value::Null).is_ok());
+        assert!(ServiceCall::new(&entity("sensor.tag"), "blink", json!({ "times": 3 })).is_ok());
+    }
+
+    #[test]
+    fn should_report_every_invalid_parameter() {
+        let cover = entity("cover.garage");
+
+        let missing = errors_of(ServiceCall::new(&cover, "set_position", json!({})));
+        let wrong_type = errors_of(ServiceCall::new(
+            &cover,
+            "set_position",
+            json!({ "position": "half" }),
+        ));
+        let out_of_range = errors_of(ServiceCall::new(
+            &entity("light.kitchen"),
+            "turn_on",
+            json!({ "brightness": 300 }),
+        ));
+        let not_object = errors_of(ServiceCall::new(&cover, "set_position", json!([50])));
+
+        assert_eq!(missing[0].to_string(), "position is required");
+        assert_eq!(wrong_type[0].parameter, "position");
+        assert!(wrong_type[0].reason.starts_with("must be a number"));
+        assert_eq!(out_of_range[0].reason, "must be at most 255");
+        assert_eq!(not_object[0].parameter, "data");
+    }
+
+    #[test]
+    fn should_check_entity_attribute_bounds_after_definition() {
+        let entity = Entity::builder()
+            .entity_id("light.dim")
+            .friendly_name("Dim")
+            .attribute_meta(
+                "brightness",
+                AttributeMeta::default().with_range(0.0, 100.0),
+            )
+            .build()
+            .unwrap();
+
+        let result = ServiceCall::new(&entity, "turn_on", json!({ "brightness": 150 }));
+
+        assert!(matches!(
+            result,
+            Err(MiniHubError::Validation(
+                ValidationError::AttributeOutOfRange { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_build_requested_event_from_call() {
+        let light = entity("light.kitchen");
+        let call = ServiceCall::new(&light, "turn_on", json!({ "brightness": 10 })).unwrap();
+
+        let event = call.requested_event();
+
+        assert_eq!(event.event_type, EventType::ServiceCallRequested);
+        assert_eq!(event.entity_id, Some(light.id));
+        assert_eq!(event.data["service"], "turn_on");
+        assert_eq!(event.data["data"]["brightness"], 10);
+    }
+}
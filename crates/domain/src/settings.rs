@@ -0,0 +1,55 @@
+//! Settings — hub-wide options persisted as JSON values under namespaced
+//! keys, e.g. the key `layout` of the `dashboard` namespace.
+//!
+//! Namespaces group the keys of one feature, so new settings do not need a
+//! schema change.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::error::ValidationError;
+
+/// Longest namespace or key name.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// The keys of a namespace and their values.
+pub type Settings = BTreeMap<String, Value>;
+
+/// Validate a namespace or key name: 1 to [`MAX_NAME_LEN`] lowercase ASCII
+/// letters, digits or underscores.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidSettingsName`] otherwise.
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidSettingsName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_snake_case_names() {
+        assert!(validate_name("dashboard").is_ok());
+        assert!(validate_name("mqtt_2").is_ok());
+    }
+
+    #[test]
+    fn should_reject_empty_long_or_unusual_names() {
+        assert!(validate_name("").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+        assert!(validate_name("Dashboard").is_err());
+        assert!(validate_name("a/b").is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! Sun — where the sun is relative to the home's horizon.
+//!
+//! Sunrise, sunset and elevation are computed from the home [`Location`]
+//! with the NOAA solar equations, accurate to about a minute away from the
+//! polar circles. The result is exposed on the synthetic [`SUN_ENTITY_ID`]
+//! entity, and each sunrise or sunset is published as an
+//! [`EventType::SunEvent`] event that automations with a `sun` trigger react
+//! to.
+
+use std::f64::consts::PI;
+
+use chrono::{Datelike, NaiveDate, TimeDelta, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{AttributeValue, Entity, EntityState};
+use crate::error::{MiniHubError, ValidationError};
+use crate::event::{Event, EventType, SunEventPayload};
+use crate::id::DeviceId;
+use crate::time::Timestamp;
+
+/// Entity id of the entity tracking the sun.
+pub const SUN_ENTITY_ID: &str = "sun.sun";
+
+/// State of the sun entity while the sun is up.
+pub const ABOVE_HORIZON: &str = "above_horizon";
+
+/// State of the sun entity while the sun is down.
+pub const BELOW_HORIZON: &str = "below_horizon";
+
+/// Attribute holding the elevation of the sun, in degrees.
+pub const ELEVATION_ATTRIBUTE: &str = "elevation";
+
+/// Attribute holding the next sunrise, RFC 3339.
+pub const NEXT_RISING_ATTRIBUTE: &str = "next_rising";
+
+/// Attribute holding the next sunset, RFC 3339.
+pub const NEXT_SETTING_ATTRIBUTE: &str = "next_setting";
+
+/// Largest offset, in minutes, a sun trigger may have before or after the
+/// event: half a day.
+pub const MAX_OFFSET_MINUTES: i32 = 720;
+
+/// Elevation of the sun's centre at sunrise and sunset, in degrees: the
+/// sun's radius plus atmospheric refraction below the geometric horizon.
+const HORIZON_ELEVATION: f64 = -0.833;
+
+/// Where the home is, in decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// North is positive.
+    pub latitude: f64,
+    /// East is positive.
+    pub longitude: f64,
+}
+
+impl Location {
+    /// Create a location, checking both coordinates are in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::InvalidLocation`] when the latitude is
+    /// outside ±90° or the longitude outside ±180°.
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, ValidationError> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(ValidationError::InvalidLocation(format!(
+                "latitude {latitude} must be between -90 and 90"
+            )));
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(ValidationError::InvalidLocation(format!(
+                "longitude {longitude} must be between -180 and 180"
+            )));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+/// The moment the sun crosses the horizon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SunEvent {
+    Sunrise,
+    Sunset,
+}
+
+impl SunEvent {
+    /// The `snake_case` name used in JSON.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sunrise => "sunrise",
+            Self::Sunset => "sunset",
+        }
+    }
+}
+
+impl std::fmt::Display for SunEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Check that a sun trigger offset is at most half a day.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidSunOffset`] otherwise.
+pub fn validate_offset(offset_minutes: i32) -> Result<(), ValidationError> {
+    if offset_minutes.abs() <= MAX_OFFSET_MINUTES {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidSunOffset(offset_minutes))
+    }
+}
+
+/// Equation of time, in minutes, and declination, in radians, of the sun
+/// `minutes` after midnight UTC on `date`.
+fn solar_terms(date: NaiveDate, minutes: f64) -> (f64, f64) {
+    let gamma = 2.0 * PI / 365.0 * (f64::from(date.ordinal0()) + (minutes / 60.0 - 12.0) / 24.0);
+    let eqtime = 229.18
+        * (0.000_075 + 0.001_868 * gamma.cos()
+            - 0.032_077 * gamma.sin()
+            - 0.014_615 * (2.0 * gamma).cos()
+            - 0.040_849 * (2.0 * gamma).sin());
+    let declination = 0.006_918 - 0.399_912 * gamma.cos() + 0.070_257 * gamma.sin()
+        - 0.006_758 * (2.0 * gamma).cos()
+        + 0.000_907 * (2.0 * gamma).sin()
+        - 0.002_697 * (3.0 * gamma).cos()
+        + 0.001_48 * (3.0 * gamma).sin();
+    (eqtime, declination)
+}
+
+/// Elevation of the sun above the horizon at `location` and `at`, in
+/// degrees; negative at night.
+#[must_use]
+pub fn elevation(location: Location, at: Timestamp) -> f64 {
+    let minutes = f64::from(at.num_seconds_from_midnight()) / 60.0;
+    let (eqtime, declination) = solar_terms(at.date_naive(), minutes);
+    let solar_time = minutes + eqtime + 4.0 * location.longitude;
+    let hour_angle = (solar_time / 4.0 - 180.0).to_radians();
+    let latitude = location.latitude.to_radians();
+    let cos_zenith =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// When `event` happens at `location` on the solar day around `date`.
+///
+/// Far from Greenwich the result may fall on the previous or next UTC day.
+/// Returns `None` during polar day or polar night, when the sun does not
+/// cross the horizon.
+#[must_use]
+pub fn event_time(location: Location, date: NaiveDate, event: SunEvent) -> Option<Timestamp> {
+    let midnight = date.and_hms_opt(0, 0, 0)?.and_utc();
+    let latitude = location.latitude.to_radians();
+    let mut minutes = 720.0;
+    // The first pass uses the sun's position at noon; the second refines it
+    // with the position at the estimated event time.
+    for _ in 0..2 {
+        let (eqtime, declination) = solar_terms(date, minutes);
+        let cos_hour_angle = (90.0 - HORIZON_ELEVATION).to_radians().cos()
+            / (latitude.cos() * declination.cos())
+            - latitude.tan() * declination.tan();
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let hour_angle = cos_hour_angle.acos().to_degrees();
+        let hour_angle = match event {
+            SunEvent::Sunrise => hour_angle,
+            SunEvent::Sunset => -hour_angle,
+        };
+        minutes = 720.0 - 4.0 * (location.longitude + hour_angle) - eqtime;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let seconds = (minutes * 60.0).round() as i64;
+    Some(midnight + TimeDelta::seconds(seconds))
+}
+
+/// Every time `offset_minutes` after `event` at `location` within
+/// `(after, until]`, in order.
+#[must_use]
+pub fn occurrences(
+    location: Location,
+    event: SunEvent,
+    offset_minutes: i32,
+    after: Timestamp,
+    until: Timestamp,
+) -> Vec<Timestamp> {
+    let offset = TimeDelta::minutes(i64::from(offset_minutes));
+    let first = (after - offset - TimeDelta::days(1)).date_naive();
+    let last = (until - offset + TimeDelta::days(1)).date_naive();
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter_map(|date| event_time(location, date, event))
+        .map(|time| time + offset)
+        .filter(|time| *time > after && *time <= until)
+        .collect()
+}
+
+/// The first `event` at `location` after `after`, looking two days ahead;
+/// `None` during polar day or polar night.
+#[must_use]
+pub fn next_event(location: Location, event: SunEvent, after: Timestamp) -> Option<Timestamp> {
+    occurrences(location, event, 0, after, after + TimeDelta::days(2))
+        .into_iter()
+        .next()
+}
+
+/// Build the sun entity, attached to `device_id`, as seen from `location`
+/// at `now`.
+///
+/// # Errors
+///
+/// Returns [`MiniHubError::Validation`] if the entity fails its invariants.
+pub fn to_entity(
+    location: Location,
+    device_id: DeviceId,
+    now: Timestamp,
+) -> Result<Entity, MiniHubError> {
+    let elevation = elevation(location, now);
+    let state = if elevation > HORIZON_ELEVATION {
+        ABOVE_HORIZON
+    } else {
+        BELOW_HORIZON
+    };
+    let mut builder = Entity::builder()
+        .device_id(device_id)
+        .entity_id(SUN_ENTITY_ID)
+        .friendly_name("Sun")
+        .state(EntityState::Text(state.to_string()))
+        .attribute(
+            ELEVATION_ATTRIBUTE,
+            AttributeValue::Float((elevation * 10.0).round() / 10.0),
+        );
+    for (attribute, event) in [
+        (NEXT_RISING_ATTRIBUTE, SunEvent::Sunrise),
+        (NEXT_SETTING_ATTRIBUTE, SunEvent::Sunset),
+    ] {
+        if let Some(time) = next_event(location, event, now) {
+            builder = builder.attribute(attribute, AttributeValue::String(time.to_rfc3339()));
+        }
+    }
+    builder.build()
+}
+
+/// Whether the sun entity reports the sun above the horizon; `None` when
+/// its state is not one of the sun states.
+#[must_use]
+pub fn is_above_horizon(entity: &Entity) -> Option<bool> {
+    match &entity.state {
+        EntityState::Text(state) if state == ABOVE_HORIZON => Some(true),
+        EntityState::Text(state) if state == BELOW_HORIZON => Some(false),
+        _ => None,
+    }
+}
+
+/// The [`EventType::SunEvent`] event published `offset_minutes` after
+/// `event`.
+#[must_use]
+pub fn fired_event(event: SunEvent, offset_minutes: i32) -> Event {
+    Event::with_payload(
+        EventType::SunEvent,
+        None,
+        &SunEventPayload {
+            event,
+            offset_minutes,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const PARIS: Location = Location {
+        latitude: 48.8566,
+        longitude: 2.3522,
+    };
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Timestamp {
+        chrono::Utc
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn assert_close(actual: Timestamp, expected: Timestamp) {
+        let diff = (actual - expected).num_seconds().abs();
+        assert!(diff <= 180, "{actual} is {diff}s away from {expected}");
+    }
+
+    #[test]
+    fn should_compute_sunrise_and_sunset_in_paris_at_midsummer() {
+        let date = NaiveDate::from_ymd_opt(2026, 6, 21).unwrap();
+
+        let sunrise = event_time(PARIS, date, SunEvent::Sunrise).unwrap();
+        let sunset = event_time(PARIS, date, SunEvent::Sunset).unwrap();
+
+        assert_close(sunrise, at(2026, 6, 21, 3, 47));
+        assert_close(sunset, at(2026, 6, 21, 19, 58));
+    }
+
+    #[test]
+    fn should_have_no_sunset_during_polar_day() {
+        let tromso = Location::new(69.65, 18.96).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 6, 21).unwrap();
+
+        assert_eq!(event_time(tromso, date, SunEvent::Sunset), None);
+        assert!(elevation(tromso, at(2026, 6, 21, 23, 0)) > 0.0);
+    }
+
+    #[test]
+    fn should_compute_elevation_high_at_noon_and_negative_at_midnight() {
+        assert!(elevation(PARIS, at(2026, 6, 21, 12, 0)) > 60.0);
+        assert!(elevation(PARIS, at(2026, 6, 21, 0, 0)) < -10.0);
+    }
+
+    #[test]
+    fn should_list_offset_occurrences_within_window() {
+        let after = at(2026, 6, 21, 0, 0);
+        let until = at(2026, 6, 23, 0, 0);
+
+        let times = occurrences(PARIS, SunEvent::Sunset, -30, after, until);
+
+        assert_eq!(times.len(), 2);
+        assert_close(times[0], at(2026, 6, 21, 19, 28));
+        assert!(times[1] > times[0] + TimeDelta::hours(23));
+    }
+
+    #[test]
+    fn should_build_sun_entity_below_horizon_at_night() {
+        let entity = to_entity(PARIS, DeviceId::new(), at(2026, 6, 21, 0, 0)).unwrap();
+
+        assert_eq!(entity.entity_id, SUN_ENTITY_ID);
+        assert_eq!(is_above_horizon(&entity), Some(false));
+        let Some(AttributeValue::String(next_rising)) = entity.get_attribute(NEXT_RISING_ATTRIBUTE)
+        else {
+            panic!("missing next_rising");
+        };
+        assert!(next_rising.starts_with("2026-06-21T03:4"));
+    }
+
+    #[test]
+    fn should_reject_out_of_range_location_and_offset() {
+        assert!(Location::new(91.0, 0.0).is_err());
+        assert!(Location::new(0.0, -181.0).is_err());
+        assert!(validate_offset(-MAX_OFFSET_MINUTES).is_ok());
+        assert!(validate_offset(MAX_OFFSET_MINUTES + 1).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Webhook — an inbound HTTP call from an external system.
+//!
+//! Doorbell firmware, CI jobs or IFTTT applets call
+//! `POST /api/webhook/{webhook_id}`; the call is published as an
+//! [`EventType::WebhookReceived`] event carrying the request body, which
+//! automations with a matching `webhook` trigger react to.
+//!
+//! Webhook calls need no API token, so the id is what keeps strangers from
+//! firing automations: it must be long and varied enough not to be
+//! guessed, such as the ones made by [`generate_id`].
+
+use crate::error::ValidationError;
+use crate::event::{Event, EventType, WebhookReceivedPayload};
+
+/// Shortest webhook id accepted.
+pub const MIN_ID_LEN: usize = 16;
+
+/// Longest webhook id accepted.
+const MAX_ID_LEN: usize = 64;
+
+/// Fewest distinct characters in a webhook id, rejecting repetitive ids
+/// such as `aaaaaaaaaaaaaaaa`.
+const MIN_DISTINCT_CHARS: usize = 8;
+
+/// Check that `webhook_id` is 16 to 64 ASCII letters, digits, dashes or
+/// underscores, so it can be used as is in a URL path, with at least 8
+/// distinct characters.
+///
+/// # Errors
+///
+/// Returns [`ValidationError::InvalidWebhookId`] otherwise.
+pub fn validate_id(webhook_id: &str) -> Result<(), ValidationError> {
+    let distinct: std::collections::HashSet<char> = webhook_id.chars().collect();
+    let valid = (MIN_ID_LEN..=MAX_ID_LEN).contains(&webhook_id.len())
+        && distinct.len() >= MIN_DISTINCT_CHARS
+        && webhook_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidWebhookId(webhook_id.to_string()))
+    }
+}
+
+/// A random webhook id, 32 hexadecimal characters long.
+#[must_use]
+pub fn generate_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// The [`EventType::WebhookReceived`] event of a call to `webhook_id` with
+/// `body`, `null` when the call had none.
+#[must_use]
+pub fn received_event(webhook_id: &str, body: serde_json::Value) -> Event {
+    Event::with_payload(
+        EventType::WebhookReceived,
+        None,
+        &WebhookReceivedPayload {
+            webhook_id: webhook_id.to_string(),
+            body,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accept_url_safe_webhook_ids() {
+        assert!(validate_id("doorbell-7f3c9a1e52").is_ok());
+        assert!(validate_id("ci-build_42-k8XqT2").is_ok());
+        assert!(validate_id(&generate_id()).is_ok());
+        assert!(validate_id(&"0123456789abcdef".repeat(MAX_ID_LEN / 16)).is_ok());
+    }
+
+    #[test]
+    fn should_reject_empty_long_or_unsafe_webhook_ids() {
+        assert!(validate_id("").is_err());
+        assert!(validate_id("doorbell").is_err());
+        assert!(validate_id(&format!("{}x", "0123456789abcdef".repeat(MAX_ID_LEN / 16))).is_err());
+        assert!(validate_id("front door").is_err());
+        assert!(validate_id("../admin").is_err());
+    }
+
+    #[test]
+    fn should_reject_guessable_webhook_ids() {
+        assert!(validate_id("a").is_err());
+        assert!(validate_id(&"a".repeat(MIN_ID_LEN)).is_err());
+        assert!(validate_id("abababababababab").is_err());
+        assert!(validate_id(&"x".repeat(MIN_ID_LEN - 1)).is_err());
+    }
+
+    #[test]
+    fn should_carry_webhook_id_and_body_in_received_event() {
+        let event = received_event("doorbell", serde_json::json!({"button": 1}));
+
+        assert_eq!(event.event_type, EventType::WebhookReceived);
+        assert_eq!(event.entity_id, None);
+        assert_eq!(event.data["webhook_id"], "doorbell");
+        assert_eq!(event.data["body"]["button"], 1);
+    }
+}